@@ -0,0 +1,34 @@
+// Web Push handling, imported into the generated PWA service worker.
+// Payloads carry only a title and a channel id, never message content.
+self.addEventListener('push', function (event) {
+  var payload = {};
+  try {
+    payload = event.data ? event.data.json() : {};
+  } catch (e) {
+    payload = {};
+  }
+  var title = payload.title || 'Paracord';
+  event.waitUntil(
+    self.registration.showNotification(title, {
+      icon: '/pwa-192x192.png',
+      badge: '/pwa-64x64.png',
+      // One notification per channel; a newer one replaces it.
+      tag: payload.channel_id ? 'channel-' + payload.channel_id : undefined,
+      data: { channelId: payload.channel_id || null },
+    }),
+  );
+});
+
+self.addEventListener('notificationclick', function (event) {
+  event.notification.close();
+  event.waitUntil(
+    self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then(function (windows) {
+      for (var i = 0; i < windows.length; i++) {
+        if ('focus' in windows[i]) {
+          return windows[i].focus();
+        }
+      }
+      return self.clients.openWindow('/app');
+    }),
+  );
+});
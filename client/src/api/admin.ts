@@ -21,7 +21,7 @@ export const adminApi = {
     total_channels: number;
   }>('/admin/stats'),
 
-  listSecurityEvents: (params?: { before?: string; limit?: number; action?: string }) =>
+  listSecurityEvents: (params?: { before?: string; limit?: number; action?: string; type?: string; user_id?: string }) =>
     apiClient.get<SecurityEvent[]>('/admin/security-events', { params }),
 
   getSettings: () => apiClient.get<Record<string, string>>('/admin/settings'),
@@ -52,6 +52,14 @@ export const adminApi = {
   deleteUser: (userId: string) =>
     apiClient.delete(`/admin/users/${userId}`),
 
+  revokeUserSessions: (userId: string) =>
+    apiClient.post<{ revoked: number; tokens_revoked: number }>(
+      `/admin/users/${userId}/sessions/revoke-all`
+    ),
+
+  updateLiveKitCredentials: (data: { api_key: string; api_secret: string }) =>
+    apiClient.put<{ api_key: string; livekit_available: boolean }>('/admin/livekit/credentials', data),
+
   getGuilds: () =>
     apiClient.get<{
       guilds: Array<{
@@ -1,5 +1,13 @@
 import { apiClient } from './client';
-import type { LoginRequest, LoginResponse, RegisterRequest, ReadState, User, UserSettings } from '../types';
+import type {
+  EmailVerificationPendingResponse,
+  LoginRequest,
+  LoginResponse,
+  RegisterRequest,
+  ReadState,
+  User,
+  UserSettings,
+} from '../types';
 
 export interface AuthSession {
   id: string;
@@ -15,12 +23,22 @@ export interface AuthSession {
 export interface AuthOptions {
   allow_username_login: boolean;
   require_email: boolean;
+  unique_usernames?: boolean;
+  require_email_verification?: boolean;
+  password_reset_available?: boolean;
 }
 
 export const authApi = {
   options: () => apiClient.get<AuthOptions>('/auth/options'),
   login: (data: LoginRequest) => apiClient.post<LoginResponse>('/auth/login', data),
-  register: (data: RegisterRequest) => apiClient.post<LoginResponse>('/auth/register', data),
+  register: (data: RegisterRequest) =>
+    apiClient.post<LoginResponse | EmailVerificationPendingResponse>('/auth/register', data),
+  verifyEmail: (token: string) => apiClient.post('/auth/verify-email', { token }),
+  resendEmailVerification: (email: string) =>
+    apiClient.post('/auth/verify-email/resend', { email }),
+  forgotPassword: (email: string) => apiClient.post('/auth/forgot-password', { email }),
+  resetPassword: (token: string, newPassword: string) =>
+    apiClient.post('/auth/reset-password', { token, new_password: newPassword }),
   refresh: (refreshToken?: string) =>
     apiClient.post<{ token: string; refresh_token?: string }>(
       '/auth/refresh',
@@ -47,4 +65,11 @@ export const authApi = {
       new_email: newEmail,
     }),
   exportMyData: () => apiClient.get<Record<string, unknown>>('/users/@me/data-export'),
+  getPushVapidKey: () =>
+    apiClient.get<{ public_key: string }>('/users/@me/push-subscriptions/vapid-key'),
+  /** Register the browser's `PushSubscription.toJSON()`. */
+  registerPushSubscription: (subscription: { endpoint: string; keys: { p256dh: string; auth: string } }) =>
+    apiClient.post('/users/@me/push-subscriptions', subscription),
+  deletePushSubscription: (endpoint: string) =>
+    apiClient.delete('/users/@me/push-subscriptions', { data: { endpoint } }),
 };
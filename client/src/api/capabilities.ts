@@ -0,0 +1,9 @@
+import { apiClient } from './client';
+
+export interface ServerCapabilities {
+  locales: string[];
+}
+
+export const capabilitiesApi = {
+  get: () => apiClient.get<ServerCapabilities>('/capabilities'),
+};
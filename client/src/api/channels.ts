@@ -10,6 +10,14 @@ import type {
   SendMessageRequest,
 } from '../types';
 
+interface FollowedChannel {
+  id: string;
+  channel_id: string;
+  webhook_channel_id: string;
+  webhook_id: string;
+  created_at: string;
+}
+
 interface CreateThreadRequest {
   name: string;
   message_id?: string;
@@ -20,6 +28,15 @@ interface UpdateThreadRequest {
   name?: string;
   archived?: boolean;
   locked?: boolean;
+  auto_archive_duration?: number;
+}
+
+interface PermalinkResolution {
+  viewable: boolean;
+  reason: 'not_found' | 'forbidden' | 'message_not_found' | null;
+  guild: { id: string; name: string; icon_hash: string | null } | null;
+  channel: { id: string; name: string | null; type: number; guild_id: string | null } | null;
+  message: { id: string; channel_id: string; author_id: string; created_at: string } | null;
 }
 
 interface CreatePollOptionRequest {
@@ -41,8 +58,8 @@ export const channelApi = {
 
   getMessages: (id: string, params?: PaginationParams) =>
     apiClient.get<Message[]>(`/channels/${id}/messages`, { params }),
-  searchMessages: (id: string, q: string, limit = 20) =>
-    apiClient.get<Message[]>(`/channels/${id}/messages/search`, { params: { q, limit } }),
+  searchMessages: (id: string, q: string, limit = 20, offset = 0) =>
+    apiClient.get<Message[]>(`/channels/${id}/messages/search`, { params: { q, limit, offset } }),
   bulkDeleteMessages: (id: string, messageIds: string[]) =>
     apiClient.post<{ deleted: number }>(`/channels/${id}/messages/bulk-delete`, { message_ids: messageIds }),
   sendMessage: (id: string, data: SendMessageRequest) =>
@@ -51,6 +68,10 @@ export const channelApi = {
     apiClient.patch<Message>(`/channels/${channelId}/messages/${messageId}`, data),
   deleteMessage: (channelId: string, messageId: string) =>
     apiClient.delete(`/channels/${channelId}/messages/${messageId}`),
+  resolvePermalink: (guildId: string | null, channelId: string, messageId: string) =>
+    apiClient.get<PermalinkResolution>('/permalink/resolve', {
+      params: { guild: guildId ?? '@me', channel: channelId, message: messageId },
+    }),
 
   getPins: (id: string) => apiClient.get<Message[]>(`/channels/${id}/pins`),
   pinMessage: (channelId: string, messageId: string) =>
@@ -58,6 +79,15 @@ export const channelApi = {
   unpinMessage: (channelId: string, messageId: string) =>
     apiClient.delete(`/channels/${channelId}/pins/${messageId}`),
 
+  followChannel: (channelId: string, webhookChannelId: string) =>
+    apiClient.post<FollowedChannel>(`/channels/${channelId}/followers`, {
+      webhook_channel_id: webhookChannelId,
+    }),
+  unfollowChannel: (channelId: string, followerId: string) =>
+    apiClient.delete(`/channels/${channelId}/followers/${followerId}`),
+  crosspostMessage: (channelId: string, messageId: string) =>
+    apiClient.post<Message>(`/channels/${channelId}/messages/${messageId}/crosspost`),
+
   addReaction: (channelId: string, messageId: string, emoji: string) =>
     apiClient.put(
       `/channels/${channelId}/messages/${messageId}/reactions/${encodeURIComponent(emoji)}/@me`
@@ -76,6 +106,12 @@ export const channelApi = {
 
   createThread: (channelId: string, data: CreateThreadRequest) =>
     apiClient.post<Channel>(`/channels/${channelId}/threads`, data),
+  startThreadFromMessage: (
+    channelId: string,
+    messageId: string,
+    data: { name: string; auto_archive_duration?: number },
+  ) =>
+    apiClient.post<Channel>(`/channels/${channelId}/messages/${messageId}/threads`, data),
   getThreads: (channelId: string) =>
     apiClient.get<Channel[]>(`/channels/${channelId}/threads`),
   getArchivedThreads: (channelId: string) =>
@@ -62,6 +62,9 @@ export const apiClient = axios.create({
   baseURL: resolveApiBaseUrl(),
   headers: { 'Content-Type': 'application/json' },
   withCredentials: true,
+  withXSRFToken: true,
+  xsrfCookieName: 'paracord_csrf',
+  xsrfHeaderName: 'X-CSRF-Token',
   timeout: 15_000, // 15s default timeout to avoid indefinite hangs.
 });
 
@@ -152,6 +155,9 @@ export function createApiClient(
     baseURL: baseUrl,
     headers: { 'Content-Type': 'application/json' },
     withCredentials: true,
+    withXSRFToken: true,
+    xsrfCookieName: 'paracord_csrf',
+    xsrfHeaderName: 'X-CSRF-Token',
     timeout: 15_000,
   });
 
@@ -5,4 +5,10 @@ export const dmApi = {
   list: () => apiClient.get<Channel[]>('/users/@me/dms'),
   create: (recipientId: string) =>
     apiClient.post<Channel>('/users/@me/dms', { recipient_id: recipientId }),
+  createGroup: (recipientIds: string[], name?: string) =>
+    apiClient.post<Channel>('/users/@me/group-dms', { recipient_ids: recipientIds, name }),
+  addRecipient: (channelId: string, userId: string) =>
+    apiClient.put(`/channels/${channelId}/recipients/${userId}`),
+  removeRecipient: (channelId: string, userId: string) =>
+    apiClient.delete(`/channels/${channelId}/recipients/${userId}`),
 };
@@ -136,6 +136,10 @@ export const fileApi = {
     });
   },
 
+  /** Short-lived URL that displays the attachment inline where the server requires embed tokens. */
+  getEmbedToken: (id: string) =>
+    apiClient.get<{ token: string; url: string; expires_at: string }>(`/attachments/${id}/embed-token`),
+
   /** Delete an attachment. */
   delete: (id: string) => apiClient.delete(`/attachments/${id}`),
 };
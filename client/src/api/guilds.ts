@@ -11,6 +11,7 @@ import type {
   CreateGuildRequest,
   CreateChannelRequest,
   CreateRoleRequest,
+  Message,
   CreateInviteRequest,
   UpdateMemberRequest,
 } from '../types';
@@ -19,7 +20,8 @@ export const guildApi = {
   getAll: () => apiClient.get<Guild[]>('/users/@me/guilds'),
   create: (data: CreateGuildRequest) => apiClient.post<Guild>('/guilds', data),
   get: (id: string) => apiClient.get<Guild>(`/guilds/${id}`),
-  update: (id: string, data: Partial<Guild>) => apiClient.patch<Guild>(`/guilds/${id}`, data),
+  update: (id: string, data: Partial<Guild> & { link_unfurls?: boolean }) =>
+    apiClient.patch<Guild>(`/guilds/${id}`, data),
   delete: (id: string) => apiClient.delete(`/guilds/${id}`),
   transferOwnership: (id: string, newOwnerId: string) =>
     apiClient.post(`/guilds/${id}/owner`, { new_owner_id: newOwnerId }),
@@ -28,6 +30,8 @@ export const guildApi = {
     apiClient.get<Channel[]>(`/guilds/${id}/channels`, config),
   createChannel: (id: string, data: CreateChannelRequest) =>
     apiClient.post<Channel>(`/guilds/${id}/channels`, data),
+  searchMessages: (id: string, q: string, limit = 20, offset = 0) =>
+    apiClient.get<Message[]>(`/guilds/${id}/messages/search`, { params: { q, limit, offset } }),
 
   getMembers: (id: string) => apiClient.get<Member[]>(`/guilds/${id}/members`),
   updateMember: (guildId: string, userId: string, data: UpdateMemberRequest) =>
@@ -45,8 +49,11 @@ export const guildApi = {
     apiClient.delete(`/guilds/${guildId}/roles/${roleId}`),
 
   getBans: (id: string) => apiClient.get<Ban[]>(`/guilds/${id}/bans`),
-  banMember: (guildId: string, userId: string, reason?: string) =>
-    apiClient.put(`/guilds/${guildId}/bans/${userId}`, { reason }),
+  banMember: (guildId: string, userId: string, reason?: string, durationSeconds?: number) =>
+    apiClient.put(`/guilds/${guildId}/bans/${userId}`, {
+      reason,
+      duration_seconds: durationSeconds ?? null,
+    }),
   unbanMember: (guildId: string, userId: string) =>
     apiClient.delete(`/guilds/${guildId}/bans/${userId}`),
 
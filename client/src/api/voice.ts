@@ -20,6 +20,17 @@ export interface VoiceJoinResponse {
   livekit_available?: boolean;
   /** TLS certificate hash for QUIC certificate pinning. */
   cert_hash?: string;
+  /** The channel's active recording, or null when it is not being recorded. */
+  recording?: VoiceRecording | null;
+}
+
+/** `VOICE_RECORDING_UPDATE` payload; `started_*` are null once it stops. */
+export interface VoiceRecording {
+  guild_id: string;
+  channel_id: string;
+  recording: boolean;
+  started_by: string | null;
+  started_at: string | null;
 }
 
 function resolveV2VoiceUrl(path: string): string {
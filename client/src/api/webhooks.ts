@@ -25,6 +25,8 @@ export const webhookApi = {
   update: (webhookId: string, data: UpdateWebhookRequest) =>
     apiClient.patch<Webhook>(`/webhooks/${webhookId}`, data),
   delete: (webhookId: string) => apiClient.delete(`/webhooks/${webhookId}`),
+  regenerateToken: (webhookId: string) =>
+    apiClient.post<Webhook>(`/webhooks/${webhookId}/token/regenerate`),
   execute: (webhookId: string, token: string, data: ExecuteWebhookRequest) =>
     apiClient.post(`/webhooks/${webhookId}/${token}`, data),
 };
@@ -299,6 +299,13 @@ export function GuildSettings({ guildId, guildName, onClose }: GuildSettingsProp
     }, 'Failed to save server overview');
   };
 
+  const setLinkPreviews = async (enabled: boolean) => {
+    await runAction(async () => {
+      await guildApi.update(guildId, { link_unfurls: enabled });
+      await refreshAll();
+    }, 'Failed to update link previews');
+  };
+
   const onGuildIconChange = (e: ChangeEvent<HTMLInputElement>) => {
     const file = e.target.files?.[0];
     if (!file) return;
@@ -776,6 +783,17 @@ export function GuildSettings({ guildId, guildName, onClose }: GuildSettingsProp
                   </label>
                 </div>
               </div>
+              <label className="flex items-center gap-2 cursor-pointer">
+                <input
+                  type="checkbox"
+                  checked={guild?.features?.includes('LINK_UNFURLS') ?? false}
+                  onChange={(e) => void setLinkPreviews(e.target.checked)}
+                  className="h-4 w-4 rounded border-border-subtle accent-accent-primary"
+                />
+                <span className="text-sm" style={{ color: 'var(--text-secondary)' }}>
+                  Show link previews (fetched by the server when it has them enabled)
+                </span>
+              </label>
               <div className="settings-action-row">
                 <button className="btn-primary" onClick={() => void saveOverview()}>Save Changes</button>
                 {guild && authUser && guild.owner_id !== authUser.id && (
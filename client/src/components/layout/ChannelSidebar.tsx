@@ -14,6 +14,9 @@ import { Permissions, hasPermission, isAdmin as isGlobalAdmin, type Channel } fr
 import { buildChannelGroups, isVirtualGroup } from '../../lib/channelGroups';
 import { guildApi } from '../../api/guilds';
 import { dmApi } from '../../api/dms';
+import { extractApiError } from '../../api/client';
+import { toast } from '../../stores/toastStore';
+import { dmDisplayName, MAX_GROUP_DM_RECIPIENTS } from '../../lib/dmChannels';
 import { useVoice } from '../../hooks/useVoice';
 import { usePermissions } from '../../hooks/usePermissions';
 import { useUnreadCounts } from '../../hooks/useUnreadCounts';
@@ -62,6 +65,9 @@ export function ChannelSidebar({ collapsed = false }: ChannelSidebarProps) {
   const [showInviteModal, setShowInviteModal] = useState(false);
   const [dmSearch, setDmSearch] = useState('');
   const [showDmPicker, setShowDmPicker] = useState(false);
+  // Friend ids picked for a new group DM; null while starting a 1:1 DM.
+  const [groupSelection, setGroupSelection] = useState<string[] | null>(null);
+  const [groupName, setGroupName] = useState('');
   const relationships = useRelationshipStore((s) => s.relationships);
   const fetchRelationships = useRelationshipStore((s) => s.fetchRelationships);
   const { connected, channelId: activeVoiceChannelId, joinChannel, selfMute, selfDeaf, toggleMute, toggleDeaf } = useVoice();
@@ -162,6 +168,20 @@ export function ChannelSidebar({ collapsed = false }: ChannelSidebarProps) {
     }
   };
 
+  const closeDmPicker = () => {
+    setShowDmPicker(false);
+    setGroupSelection(null);
+    setGroupName('');
+  };
+
+  const openNewDm = (dm: Channel) => {
+    const current = useChannelStore.getState().channelsByGuild[''] || [];
+    setDmChannels(current.some((c) => c.id === dm.id) ? current : [...current, dm]);
+    selectChannel(dm.id);
+    closeDmPicker();
+    navigate(`/app/dms/${dm.id}`);
+  };
+
   if (!currentGuild && collapsed) {
     const compactDms = dmChannels.slice(0, 32);
     return (
@@ -199,7 +219,7 @@ export function ChannelSidebar({ collapsed = false }: ChannelSidebarProps) {
           {compactDms.map((dm) => {
             const isSelected = selectedChannelId === dm.id;
             return (
-              <Tooltip key={dm.id} content={dm.recipient?.username || 'Direct Message'} side="right">
+              <Tooltip key={dm.id} content={dmDisplayName(dm, user?.id)} side="right">
                 <button
                   onClick={() => {
                     selectChannel(dm.id);
@@ -212,7 +232,7 @@ export function ChannelSidebar({ collapsed = false }: ChannelSidebarProps) {
                       : 'border-transparent bg-bg-mod-subtle text-text-secondary hover:border-border-subtle hover:text-text-primary'
                   )}
                 >
-                  {(dm.recipient?.username || 'D').charAt(0).toUpperCase()}
+                  {dmDisplayName(dm, user?.id).charAt(0).toUpperCase()}
                   <PresenceStatusDot userId={dm.recipient?.id} className="absolute -bottom-0.5 -right-0.5 h-2.5 w-2.5 border border-bg-secondary" />
                 </button>
               </Tooltip>
@@ -225,7 +245,7 @@ export function ChannelSidebar({ collapsed = false }: ChannelSidebarProps) {
 
   if (!currentGuild) {
     const filteredDms = dmChannels.filter((dm) =>
-      (dm.recipient?.username || 'Direct Message').toLowerCase().includes(dmSearch.toLowerCase())
+      dmDisplayName(dm, user?.id).toLowerCase().includes(dmSearch.toLowerCase())
     );
 
     return (
@@ -312,12 +332,12 @@ export function ChannelSidebar({ collapsed = false }: ChannelSidebarProps) {
                 >
                   <div className="relative">
                     <div className="flex h-9 w-9 items-center justify-center rounded-xl bg-bg-mod-strong text-sm font-semibold text-text-primary">
-                      {(dm.recipient?.username || 'D').charAt(0).toUpperCase()}
+                      {dmDisplayName(dm, user?.id).charAt(0).toUpperCase()}
                     </div>
                     <PresenceStatusDot userId={dm.recipient?.id} className="absolute -bottom-0.5 -right-0.5 h-3 w-3 border-[2px] border-bg-secondary" />
                   </div>
                   <div className="flex min-w-0 flex-1 flex-col items-start">
-                    <span className="truncate font-semibold text-[15px]">{dm.recipient?.username || 'Direct Message'}</span>
+                    <span className="truncate font-semibold text-[15px]">{dmDisplayName(dm, user?.id)}</span>
                     <PresenceStatusText userId={dm.recipient?.id} className="truncate text-xs text-text-muted opacity-0 group-hover:opacity-100 transition-opacity" />
                   </div>
                 </button>
@@ -339,31 +359,92 @@ export function ChannelSidebar({ collapsed = false }: ChannelSidebarProps) {
             <div
               className="fixed inset-0 z-50"
               style={{ backgroundColor: 'var(--overlay-backdrop)' }}
-              onClick={() => setShowDmPicker(false)}
+              onClick={closeDmPicker}
             />
             <div className="glass-modal fixed left-1/2 top-1/2 z-50 max-h-[70vh] w-full max-w-[480px] -translate-x-1/2 -translate-y-1/2 overflow-hidden rounded-2xl">
-              <div className="panel-divider border-b px-5 py-4 text-lg font-semibold text-text-primary">Start Direct Message</div>
+              <div className="panel-divider flex items-center justify-between border-b px-5 py-4">
+                <span className="text-lg font-semibold text-text-primary">
+                  {groupSelection ? 'Create Group DM' : 'Start Direct Message'}
+                </span>
+                <button
+                  className="rounded-lg border border-border-subtle px-3 py-1.5 text-xs font-semibold text-text-secondary transition-colors hover:bg-bg-mod-subtle hover:text-text-primary"
+                  onClick={() => setGroupSelection(groupSelection ? null : [])}
+                >
+                  {groupSelection ? 'Single DM' : 'New Group'}
+                </button>
+              </div>
+              {groupSelection && (
+                <div className="px-5 pt-3">
+                  <input
+                    type="text"
+                    placeholder="Group name (optional)"
+                    maxLength={100}
+                    className="h-10 w-full rounded-xl border border-border-subtle bg-bg-mod-subtle px-3 text-sm text-text-primary placeholder:text-text-muted outline-none focus:border-border-strong"
+                    value={groupName}
+                    onChange={(e) => setGroupName(e.target.value)}
+                  />
+                  <div className="mt-2 text-xs text-text-muted">
+                    Pick 2-{MAX_GROUP_DM_RECIPIENTS - 1} friends ({groupSelection.length} selected)
+                  </div>
+                </div>
+              )}
               <div className="max-h-[50vh] overflow-y-auto p-3">
-                {relationships.filter((r) => r.type === 1).map((rel) => (
+                {relationships.filter((r) => r.type === 1).map((rel) => {
+                  const picked = groupSelection?.includes(rel.user.id) ?? false;
+                  return (
+                    <button
+                      key={rel.id}
+                      className={cn(
+                        'flex w-full items-center justify-between rounded-lg px-3.5 py-2.5 text-left text-sm font-medium transition-colors hover:bg-bg-mod-subtle',
+                        picked && 'bg-bg-mod-strong'
+                      )}
+                      onClick={async () => {
+                        if (groupSelection) {
+                          setGroupSelection(
+                            picked
+                              ? groupSelection.filter((id) => id !== rel.user.id)
+                              : groupSelection.length < MAX_GROUP_DM_RECIPIENTS - 1
+                                ? [...groupSelection, rel.user.id]
+                                : groupSelection
+                          );
+                          return;
+                        }
+                        const { data } = await dmApi.create(rel.user.id);
+                        openNewDm(data);
+                      }}
+                    >
+                      <div className="text-sm text-text-primary">{rel.user.username}</div>
+                      {groupSelection && (
+                        <input type="checkbox" readOnly checked={picked} className="pointer-events-none" />
+                      )}
+                    </button>
+                  );
+                })}
+                {relationships.filter((r) => r.type === 1).length === 0 && (
+                  <div className="p-5 text-sm text-text-muted text-center">No friends available for DM.</div>
+                )}
+              </div>
+              {groupSelection && (
+                <div className="panel-divider flex justify-end border-t px-5 py-3">
                   <button
-                    key={rel.id}
-                    className="w-full rounded-lg px-3.5 py-2.5 text-left text-sm font-medium transition-colors hover:bg-bg-mod-subtle"
+                    className="btn-primary"
+                    disabled={groupSelection.length < 2}
                     onClick={async () => {
-                      const { data } = await dmApi.create(rel.user.id);
-                      const current = useChannelStore.getState().channelsByGuild[''] || [];
-                      const next = current.some((c) => c.id === data.id) ? current : [...current, data];
-                      setDmChannels(next);
-                      selectChannel(data.id);
-                      setShowDmPicker(false);
-                      navigate(`/app/dms/${data.id}`);
+                      try {
+                        const { data } = await dmApi.createGroup(groupSelection, groupName.trim() || undefined);
+                        openNewDm(data);
+                      } catch (err) {
+                        toast.error(`Could not create the group: ${extractApiError(err)}`);
+                      }
                     }}
                   >
-                    <div className="text-sm text-text-primary">{rel.user.username}</div>
+                    Create Group
                   </button>
-                ))}
-                {relationships.filter((r) => r.type === 1).length === 0 && (
-                  <div className="p-5 text-sm text-text-muted text-center">No friends available for DM.</div>
-                )}
+                </div>
+              )}
+            </div>
+          </>
+        )}
               </div>
             </div>
           </>
@@ -8,6 +8,7 @@ import { useChannelStore } from '../../stores/channelStore';
 import { useAuthStore } from '../../stores/authStore';
 import { isAdmin } from '../../types';
 import { cn } from '../../lib/utils';
+import { dmDisplayName } from '../../lib/dmChannels';
 import type { Channel, Guild } from '../../types';
 
 interface PaletteItem {
@@ -142,7 +143,7 @@ export function CommandPalette() {
 
     // DM channels
     dmChannels.forEach((dm: Channel) => {
-      const recipientName = dm.recipient?.username || 'Direct Message';
+      const recipientName = dmDisplayName(dm, user?.id);
       items.push({
         id: `dm-${dm.id}`,
         label: recipientName,
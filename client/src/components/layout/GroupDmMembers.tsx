@@ -0,0 +1,132 @@
+import { useEffect, useState } from 'react';
+import { useNavigate } from 'react-router-dom';
+import { Crown, LogOut, UserMinus, UserPlus } from 'lucide-react';
+import { dmApi } from '../../api/dms';
+import { extractApiError } from '../../api/client';
+import { useAuthStore } from '../../stores/authStore';
+import { useRelationshipStore } from '../../stores/relationshipStore';
+import { confirm } from '../../stores/confirmStore';
+import { toast } from '../../stores/toastStore';
+import { MAX_GROUP_DM_RECIPIENTS } from '../../lib/dmChannels';
+import type { Channel } from '../../types';
+
+interface GroupDmMembersProps {
+  channel: Channel;
+}
+
+/**
+ * Participant list for a group DM. Anyone may add friends; only the creator
+ * removes others, and everyone else can leave.
+ */
+export function GroupDmMembers({ channel }: GroupDmMembersProps) {
+  const navigate = useNavigate();
+  const selfId = useAuthStore((s) => s.user?.id);
+  const relationships = useRelationshipStore((s) => s.relationships);
+  const fetchRelationships = useRelationshipStore((s) => s.fetchRelationships);
+  const [adding, setAdding] = useState(false);
+
+  const recipients = channel.recipients ?? [];
+  const isOwner = Boolean(selfId) && channel.owner_id === selfId;
+  const isFull = recipients.length >= MAX_GROUP_DM_RECIPIENTS;
+  const candidates = relationships.filter(
+    (rel) => rel.type === 1 && !recipients.some((r) => r.id === rel.user.id)
+  );
+
+  useEffect(() => {
+    if (adding) void fetchRelationships();
+  }, [adding, fetchRelationships]);
+
+  const addRecipient = async (userId: string) => {
+    try {
+      await dmApi.addRecipient(channel.id, userId);
+      setAdding(false);
+    } catch (err) {
+      toast.error(`Could not add to the group: ${extractApiError(err)}`);
+    }
+  };
+
+  const removeRecipient = async (userId: string, username: string) => {
+    const leaving = userId === selfId;
+    const confirmed = await confirm({
+      title: leaving ? 'Leave group?' : `Remove ${username}?`,
+      description: leaving
+        ? 'You will lose access to this conversation and its history.'
+        : `${username} will lose access to this conversation and its history.`,
+      confirmLabel: leaving ? 'Leave' : 'Remove',
+      variant: 'danger',
+    });
+    if (!confirmed) return;
+    try {
+      await dmApi.removeRecipient(channel.id, userId);
+      if (leaving) navigate('/app');
+    } catch (err) {
+      toast.error(`Could not update the group: ${extractApiError(err)}`);
+    }
+  };
+
+  return (
+    <aside className="hidden w-60 shrink-0 flex-col border-l border-border-subtle lg:flex">
+      <div className="flex items-center justify-between px-4 py-3">
+        <span className="text-xs font-semibold uppercase tracking-wide text-text-muted">
+          Members — {recipients.length}
+        </span>
+        {!isFull && (
+          <button
+            className="rounded-lg p-1.5 text-text-muted transition-colors hover:bg-bg-mod-subtle hover:text-text-primary"
+            title="Add friends"
+            aria-label="Add friends"
+            onClick={() => setAdding((open) => !open)}
+          >
+            <UserPlus size={15} />
+          </button>
+        )}
+      </div>
+      {adding && (
+        <div className="mx-3 mb-3 max-h-56 overflow-y-auto rounded-xl border border-border-subtle bg-bg-mod-subtle p-1.5">
+          {candidates.length === 0 ? (
+            <div className="p-3 text-center text-xs text-text-muted">No friends left to add.</div>
+          ) : (
+            candidates.map((rel) => (
+              <button
+                key={rel.id}
+                className="w-full rounded-lg px-3 py-2 text-left text-sm text-text-primary transition-colors hover:bg-bg-mod-strong"
+                onClick={() => void addRecipient(rel.user.id)}
+              >
+                {rel.user.username}
+              </button>
+            ))
+          )}
+        </div>
+      )}
+      <div className="flex-1 space-y-0.5 overflow-y-auto px-2 pb-3 scrollbar-thin">
+        {recipients.map((recipient) => {
+          const isSelf = recipient.id === selfId;
+          const isCreator = recipient.id === channel.owner_id;
+          const canRemove = isSelf ? !isOwner : isOwner;
+          return (
+            <div
+              key={recipient.id}
+              className="group flex items-center gap-2.5 rounded-lg px-2 py-1.5 hover:bg-bg-mod-subtle"
+            >
+              <div className="flex h-8 w-8 shrink-0 items-center justify-center rounded-lg bg-bg-mod-strong text-xs font-semibold text-text-primary">
+                {recipient.username.charAt(0).toUpperCase()}
+              </div>
+              <span className="min-w-0 flex-1 truncate text-sm text-text-secondary">{recipient.username}</span>
+              {isCreator && <Crown size={13} className="shrink-0 text-accent-warning" aria-label="Group creator" />}
+              {canRemove && (
+                <button
+                  className="rounded p-1 text-text-muted opacity-0 transition-opacity hover:text-accent-danger group-hover:opacity-100"
+                  title={isSelf ? 'Leave group' : `Remove ${recipient.username}`}
+                  aria-label={isSelf ? 'Leave group' : `Remove ${recipient.username}`}
+                  onClick={() => void removeRecipient(recipient.id, recipient.username)}
+                >
+                  {isSelf ? <LogOut size={14} /> : <UserMinus size={14} />}
+                </button>
+              )}
+            </div>
+          );
+        })}
+      </div>
+    </aside>
+  );
+}
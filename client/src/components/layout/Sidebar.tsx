@@ -319,6 +319,15 @@ export function Sidebar() {
                 } catch {
                   /* ignore */
                 }
+                // Mirror to server settings so role mentions skip muted servers.
+                const { settings, updateSettings } = useAuthStore.getState();
+                if (settings) {
+                  void updateSettings({
+                    notifications: { ...settings.notifications, mutedGuildIds: next },
+                  }).catch(() => {
+                    /* local mute still applies */
+                  });
+                }
                 setContextMenu(null);
               }}
             >
@@ -1,5 +1,6 @@
 import { ExternalLink } from 'lucide-react';
 import type { MessageEmbed as EmbedType } from '../../types';
+import { resolveResourceUrl } from '../../lib/apiBaseUrl';
 
 interface MessageEmbedCardProps {
   embed: EmbedType;
@@ -8,7 +9,8 @@ interface MessageEmbedCardProps {
 export function MessageEmbedCard({ embed }: MessageEmbedCardProps) {
   const accentColor = embed.color || 'var(--accent-primary)';
   const hasImage = Boolean(embed.image || embed.thumbnail);
-  const imageUrl = embed.image || embed.thumbnail;
+  // Link preview thumbnails are served by the server's image proxy.
+  const imageUrl = embed.image || (embed.thumbnail ? resolveResourceUrl(embed.thumbnail) : undefined);
 
   return (
     <a
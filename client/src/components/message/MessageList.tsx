@@ -12,7 +12,7 @@ import { useMemberStore } from '../../stores/memberStore';
 import { channelApi } from '../../api/channels';
 import { fileApi } from '../../api/files';
 import { extractApiError } from '../../api/client';
-import { MessageType, Permissions, hasPermission, type Channel, type Message } from '../../types';
+import { MessageFlags, MessageType, Permissions, hasPermission, type Channel, type Message } from '../../types';
 import { UserProfilePopup } from '../user/UserProfile';
 import { EmojiPicker } from '../ui/EmojiPicker';
 import { ContextMenu, useContextMenu, type ContextMenuItem } from '../ui/ContextMenu';
@@ -1077,6 +1077,7 @@ export function MessageList({ channelId, onReply }: MessageListProps) {
 
           {/* URL Embeds — server-provided or client-extracted */}
           {(() => {
+            if (((msg.flags ?? 0) & MessageFlags.SUPPRESS_EMBEDS) !== 0) return null;
             const embeds = msg.embeds || [];
             const contentUrls = embeds.length === 0 ? extractUrls(msg.content) : [];
             const allEmbeds = embeds.length > 0
@@ -0,0 +1,97 @@
+import { useEffect, useRef, useState } from 'react';
+import { createPortal } from 'react-dom';
+import { AnimatePresence, motion } from 'framer-motion';
+import { ShieldCheck } from 'lucide-react';
+import { useMfaPromptStore } from '../../stores/mfaPromptStore';
+import { useFocusTrap } from '../../hooks/useFocusTrap';
+
+export function MfaPromptDialog() {
+  const isOpen = useMfaPromptStore((s) => s.isOpen);
+  const serverName = useMfaPromptStore((s) => s.serverName);
+  const close = useMfaPromptStore((s) => s.close);
+  const dialogRef = useRef<HTMLDivElement>(null);
+  const [code, setCode] = useState('');
+
+  useFocusTrap(dialogRef, isOpen, () => close(null));
+
+  useEffect(() => {
+    if (!isOpen) setCode('');
+  }, [isOpen]);
+
+  const submit = (e: React.FormEvent) => {
+    e.preventDefault();
+    const trimmed = code.trim();
+    if (trimmed) close(trimmed);
+  };
+
+  return createPortal(
+    <AnimatePresence>
+      {isOpen && (
+        <motion.div
+          className="fixed inset-0 z-[60] flex items-center justify-center px-4 backdrop-blur-sm"
+          style={{ backgroundColor: 'var(--overlay-backdrop)' }}
+          initial={{ opacity: 0 }}
+          animate={{ opacity: 1 }}
+          exit={{ opacity: 0 }}
+          onClick={() => close(null)}
+        >
+          <motion.div
+            ref={dialogRef}
+            role="dialog"
+            aria-modal="true"
+            aria-labelledby="mfa-prompt-title"
+            tabIndex={-1}
+            initial={{ opacity: 0, scale: 0.95, y: 10 }}
+            animate={{ opacity: 1, scale: 1, y: 0 }}
+            exit={{ opacity: 0, scale: 0.95, y: 10 }}
+            transition={{ duration: 0.18 }}
+            className="glass-modal w-full max-w-[440px] overflow-hidden rounded-2xl"
+            onClick={(e) => e.stopPropagation()}
+          >
+            <form onSubmit={submit}>
+              <div className="px-6 pb-2 pt-6">
+                <div className="mb-4 flex h-10 w-10 items-center justify-center rounded-xl bg-accent-primary/12 text-accent-primary">
+                  <ShieldCheck size={20} />
+                </div>
+                <h2 id="mfa-prompt-title" className="text-lg font-bold text-text-primary">
+                  Two-factor authentication
+                </h2>
+                <p className="mt-2 text-sm leading-relaxed text-text-secondary">
+                  Enter the code from your authenticator app or a recovery code to sign in to{' '}
+                  <span className="font-medium text-text-primary">{serverName}</span>.
+                </p>
+                <input
+                  type="text"
+                  inputMode="text"
+                  autoComplete="one-time-code"
+                  value={code}
+                  onChange={(e) => setCode(e.target.value)}
+                  className="input-field mt-4"
+                  placeholder="123456"
+                  autoFocus
+                />
+              </div>
+              <div className="flex justify-end gap-3 px-6 pb-5 pt-4">
+                <button
+                  type="button"
+                  className="h-10 rounded-xl border border-border-strong px-5 text-sm font-semibold text-text-secondary transition-colors hover:bg-bg-mod-subtle hover:text-text-primary"
+                  onClick={() => close(null)}
+                >
+                  Cancel
+                </button>
+                <button
+                  type="submit"
+                  disabled={!code.trim()}
+                  className="h-10 rounded-xl bg-accent-primary px-5 text-sm font-semibold text-white transition-colors hover:bg-accent-primary-hover"
+                >
+                  Verify
+                </button>
+              </div>
+            </form>
+          </motion.div>
+        </motion.div>
+      )}
+    </AnimatePresence>,
+    document.body
+  );
+}
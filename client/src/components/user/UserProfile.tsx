@@ -5,6 +5,7 @@ import type { User } from '../../types/index';
 import { apiClient } from '../../api/client';
 import { dmApi } from '../../api/dms';
 import { relationshipApi } from '../../api/relationships';
+import { useAuthStore } from '../../stores/authStore';
 import { useChannelStore } from '../../stores/channelStore';
 import { usePresenceStore } from '../../stores/presenceStore';
 import { useServerListStore } from '../../stores/serverListStore';
@@ -13,6 +14,7 @@ import {
   formatActivityLabel,
   getPrimaryActivity,
 } from '../../lib/activityPresence';
+import { formatUserTag } from '../../lib/formatters';
 
 interface MutualGuild {
   id: string;
@@ -90,6 +92,7 @@ export function UserProfilePopup({ user, position, onClose, roles = [] }: UserPr
   const [now, setNow] = useState(() => Date.now());
   const [profileData, setProfileData] = useState<ProfileData | null>(null);
   const activeServerId = useServerListStore((state) => state.activeServerId);
+  const uniqueUsernames = useAuthStore((state) => state.uniqueUsernames);
   const presence = usePresenceStore((state) =>
     state.getPresence(user.id, activeServerId ?? undefined)
   );
@@ -172,7 +175,7 @@ export function UserProfilePopup({ user, position, onClose, roles = [] }: UserPr
   const handleAddFriend = async () => {
     try {
       setActionError(null);
-      await relationshipApi.addFriend(user.username);
+      await relationshipApi.addFriend(user.id);
       onClose();
     } catch {
       setActionError('Could not send a friend request.');
@@ -253,7 +256,7 @@ export function UserProfilePopup({ user, position, onClose, roles = [] }: UserPr
             )}
           </div>
           <div className="text-sm" style={{ color: 'var(--text-secondary)' }}>
-            {user.username}
+            {formatUserTag(user, uniqueUsernames)}
           </div>
           {activityLabel && (
             <div className="mt-1 text-xs font-medium" style={{ color: 'var(--text-secondary)' }}>
@@ -12,13 +12,17 @@ import { isAdmin } from '../../types';
 import { adminApi } from '../../api/admin';
 import { apiClient, extractApiError } from '../../api/client';
 import { authApi, type AuthSession } from '../../api/auth';
+import { capabilitiesApi } from '../../api/capabilities';
 import { cn } from '../../lib/utils';
+import { formatUserTag } from '../../lib/formatters';
 import { confirm } from '../../stores/confirmStore';
 import {
   isEnabled as isNotificationsEnabled,
   setEnabled as setNotificationsEnabled,
   isPermissionGranted as checkNotificationPermission,
   requestPermission as requestNotificationPermission,
+  syncPushSubscription,
+  clearPushSubscription,
 } from '../../lib/notifications';
 import {
   getKnownActivityAppsFromStorage,
@@ -59,6 +63,7 @@ export function UserSettings({ onClose }: UserSettingsProps) {
   const navigate = useNavigate();
   const [activeSection, setActiveSection] = useState<SettingsSection>('account');
   const user = useAuthStore(s => s.user);
+  const uniqueUsernames = useAuthStore(s => s.uniqueUsernames);
   const settings = useAuthStore(s => s.settings);
   const logout = useAuthStore(s => s.logout);
   const fetchUser = useAuthStore(s => s.fetchUser);
@@ -75,6 +80,7 @@ export function UserSettings({ onClose }: UserSettingsProps) {
   const [displayName, setDisplayName] = useState('');
   const [bio, setBio] = useState('');
   const [locale, setLocale] = useState('en-US');
+  const [availableLocales, setAvailableLocales] = useState<string[]>([]);
   const [messageCompact, setMessageCompact] = useState(false);
   const [notifications, setNotifications] = useState<Record<string, unknown>>({});
   const [knownActivityApps, setKnownActivityApps] = useState<string[]>([]);
@@ -134,6 +140,18 @@ export function UserSettings({ onClose }: UserSettingsProps) {
     void fetchSettings();
   }, []);
 
+  useEffect(() => {
+    capabilitiesApi
+      .get()
+      .then(({ data }) => setAvailableLocales(data.locales))
+      .catch(() => setAvailableLocales([]));
+  }, []);
+
+  const localeOptions = useMemo(
+    () => (availableLocales.includes(locale) ? availableLocales : [locale, ...availableLocales]),
+    [availableLocales, locale]
+  );
+
   useEffect(() => {
     if (user) {
       setDisplayName(user.display_name || '');
@@ -380,7 +398,13 @@ export function UserSettings({ onClose }: UserSettingsProps) {
       a.click();
       document.body.removeChild(a);
       URL.revokeObjectURL(url);
-      setStatusText('Account data export downloaded.');
+      const warnings = Array.isArray(data.warnings) ? (data.warnings as { section?: string }[]) : [];
+      if (warnings.length > 0) {
+        const sections = warnings.map((w) => w.section ?? 'unknown').join(', ');
+        setStatusText(`Account data export downloaded, but some sections could not be exported: ${sections}.`);
+      } else {
+        setStatusText('Account data export downloaded.');
+      }
     } catch (err) {
       setStatusText(`Account export failed: ${extractApiError(err)}`);
     } finally {
@@ -394,7 +418,7 @@ export function UserSettings({ onClose }: UserSettingsProps) {
     try {
       await updateSettings({
         theme,
-        locale,
+        ...(locale !== settings?.locale ? { locale } : {}),
         message_display_compact: messageCompact,
         crypto_auth_enabled: cryptoAuthEnabled,
         notifications: {
@@ -454,7 +478,7 @@ export function UserSettings({ onClose }: UserSettingsProps) {
     try {
       await updateSettings({
         theme,
-        locale,
+        ...(locale !== settings?.locale ? { locale } : {}),
         message_display_compact: messageCompact,
         crypto_auth_enabled: enabled,
         notifications: {
@@ -692,7 +716,7 @@ export function UserSettings({ onClose }: UserSettingsProps) {
                     >
                       <div className="card-surface rounded-xl border border-border-subtle bg-bg-mod-subtle/55 px-6 py-5">
                         <div className="mb-3 text-xs font-semibold uppercase tracking-wide text-text-secondary">Username</div>
-                        <div className="text-sm font-medium text-text-primary">{user?.username || 'Unknown'}</div>
+                        <div className="text-sm font-medium text-text-primary">{user ? formatUserTag(user, uniqueUsernames) : 'Unknown'}</div>
                       </div>
                       <div className="card-surface rounded-xl border border-border-subtle bg-bg-mod-subtle/55 px-6 py-5">
                         <div className="mb-3 text-xs font-semibold uppercase tracking-wide text-text-secondary">Display Name</div>
@@ -1023,7 +1047,13 @@ export function UserSettings({ onClose }: UserSettingsProps) {
               <div className="card-stack-relaxed">
                 <label className="block">
                   <span className="text-xs font-semibold uppercase tracking-wide text-text-secondary">Locale</span>
-                  <input className="input-field mt-3" value={locale} onChange={(e) => setLocale(e.target.value)} />
+                  <select className="select-field mt-3" value={locale} onChange={(e) => setLocale(e.target.value)}>
+                    {localeOptions.map((tag) => (
+                      <option key={tag} value={tag}>
+                        {tag}
+                      </option>
+                    ))}
+                  </select>
                 </label>
                 <div className="card-surface flex flex-wrap items-center justify-between gap-3 rounded-xl border border-border-subtle bg-bg-mod-subtle/70 px-6 py-5">
                   <div>
@@ -1155,10 +1185,12 @@ export function UserSettings({ onClose }: UserSettingsProps) {
                           setNotifEnabled(granted);
                           setNotificationsEnabled(granted);
                           setNotifications((prev) => ({ ...prev, desktop: granted }));
+                          if (granted) void syncPushSubscription();
                         });
                       } else {
                         setNotifEnabled(false);
                         setNotificationsEnabled(false);
+                        void clearPushSubscription();
                         setNotifications((prev) => ({ ...prev, desktop: false }));
                       }
                     }}
@@ -13,6 +13,7 @@ export function MiniVoiceBar() {
   const toggleMute = useVoiceStore((s) => s.toggleMute);
   const toggleDeaf = useVoiceStore((s) => s.toggleDeaf);
   const leaveChannel = useVoiceStore((s) => s.leaveChannel);
+  const recording = useVoiceStore((s) => (channelId ? s.recordings.has(channelId) : false));
   const channels = useChannelStore((s) => s.channels);
   const navigate = useNavigate();
 
@@ -51,8 +52,17 @@ export function MiniVoiceBar() {
       >
         <Signal size={14} className="voice-connected-pulse shrink-0" style={{ color: 'var(--accent-success)' }} />
         <div className="min-w-0 flex-1 text-left">
-          <div className="text-[11px] font-semibold leading-tight" style={{ color: 'var(--accent-success)' }}>
+          <div className="flex items-center gap-1.5 text-[11px] font-semibold leading-tight" style={{ color: 'var(--accent-success)' }}>
             Voice Connected
+            {recording && (
+              <span
+                className="rounded px-1 text-[10px] font-bold uppercase tracking-wide"
+                style={{ backgroundColor: 'var(--accent-danger)', color: '#fff' }}
+                title="This voice channel is being recorded"
+              >
+                Rec
+              </span>
+            )}
           </div>
           <div className="truncate text-[13px] font-medium leading-snug text-text-secondary">
             {channelName}
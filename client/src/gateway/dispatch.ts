@@ -12,6 +12,7 @@ import { useAuthStore } from '../stores/authStore';
 import { hasUnlockedPrivateKey } from '../lib/accountSession';
 import { ensurePrekeysUploaded } from '../lib/signalPrekeys';
 import { GatewayEvents } from './events';
+import { MessageFlags } from '../types';
 import { sendNotification, isEnabled as notificationsEnabled } from '../lib/notifications';
 
 /* eslint-disable @typescript-eslint/no-explicit-any */
@@ -61,6 +62,7 @@ export function dispatchGatewayEvent(serverId: string, event: string, data: any)
           }
         }
         useVoiceStore.getState().loadVoiceStates(g.id, g.voice_states ?? []);
+        useVoiceStore.getState().loadVoiceRecordings(g.id, g.voice_recordings ?? []);
         if (g.presences?.length) {
           for (const p of g.presences) {
             usePresenceStore.getState().updatePresence(p, serverId);
@@ -107,7 +109,7 @@ export function dispatchGatewayEvent(serverId: string, event: string, data: any)
       useMessageStore.getState().addMessage(data.channel_id, data);
       useChannelStore.getState().updateLastMessageId(data.channel_id, data.id);
       // Desktop notification for messages not from self and not in focused channel
-      if (notificationsEnabled()) {
+      if (notificationsEnabled() && ((data.flags ?? 0) & MessageFlags.SILENT) === 0) {
         const currentUserId = useAuthStore.getState().user?.id;
         const authorId = data.author?.id ?? data.user_id;
         const focusedChannelId = useChannelStore.getState().selectedChannelId;
@@ -159,6 +161,19 @@ export function dispatchGatewayEvent(serverId: string, event: string, data: any)
     case GatewayEvents.CHANNEL_DELETE:
       useChannelStore.getState().removeChannel(data.guild_id, data.id);
       break;
+    case GatewayEvents.CHANNEL_RECIPIENT_ADD:
+    case GatewayEvents.CHANNEL_RECIPIENT_REMOVE: {
+      const group = (useChannelStore.getState().channelsByGuild[''] || []).find(
+        (ch) => ch.id === data.channel_id
+      );
+      if (!group || !data.user) break;
+      const others = (group.recipients ?? []).filter((r) => r.id !== data.user.id);
+      useChannelStore.getState().updateChannel({
+        ...group,
+        recipients: event === GatewayEvents.CHANNEL_RECIPIENT_ADD ? [...others, data.user] : others,
+      });
+      break;
+    }
 
     case GatewayEvents.THREAD_CREATE:
       useChannelStore.getState().addChannel({
@@ -225,6 +240,14 @@ export function dispatchGatewayEvent(serverId: string, event: string, data: any)
       useVoiceStore.getState().handleVoiceStateUpdate(data);
       break;
 
+    case GatewayEvents.VOICE_SERVER_UPDATE:
+      useVoiceStore.getState().handleVoiceServerUpdate(data);
+      break;
+
+    case GatewayEvents.VOICE_RECORDING_UPDATE:
+      useVoiceStore.getState().handleVoiceRecordingUpdate(data);
+      break;
+
     case GatewayEvents.MESSAGE_REACTION_ADD: {
       const currentUserId = useAuthStore.getState().user?.id || '';
       useMessageStore.getState().handleReactionAdd(
@@ -239,6 +262,18 @@ export function dispatchGatewayEvent(serverId: string, event: string, data: any)
       );
       break;
     }
+    case GatewayEvents.MESSAGE_REACTION_ADD_BULK:
+    case GatewayEvents.MESSAGE_REACTION_REMOVE_BULK: {
+      const currentUserId3 = useAuthStore.getState().user?.id || '';
+      const messageStore = useMessageStore.getState();
+      const apply = event === GatewayEvents.MESSAGE_REACTION_ADD_BULK
+        ? messageStore.handleReactionAdd
+        : messageStore.handleReactionRemove;
+      for (const reaction of data.reactions ?? []) {
+        apply(data.channel_id, data.message_id, reaction.emoji, data.user_id, currentUserId3);
+      }
+      break;
+    }
     case GatewayEvents.POLL_VOTE_ADD:
     case GatewayEvents.POLL_VOTE_REMOVE:
       if (data.poll) {
@@ -258,10 +293,20 @@ export function dispatchGatewayEvent(serverId: string, event: string, data: any)
       }
       break;
 
+    case GatewayEvents.TYPING_STOP:
+      if (data.channel_id && data.user_id) {
+        useTypingStore.getState().removeTyping(data.channel_id, data.user_id);
+      }
+      break;
+
     case GatewayEvents.USER_UPDATE:
       useAuthStore.getState().fetchUser();
       break;
 
+    case GatewayEvents.SESSIONS_REVOKED:
+      void useAuthStore.getState().logout();
+      break;
+
     case GatewayEvents.RELATIONSHIP_ADD:
     case GatewayEvents.RELATIONSHIP_REMOVE:
       void useRelationshipStore.getState().fetchRelationships();
@@ -13,6 +13,8 @@ export const GatewayEvents = {
   CHANNEL_UPDATE: 'CHANNEL_UPDATE',
   CHANNEL_DELETE: 'CHANNEL_DELETE',
   CHANNEL_PINS_UPDATE: 'CHANNEL_PINS_UPDATE',
+  CHANNEL_RECIPIENT_ADD: 'CHANNEL_RECIPIENT_ADD',
+  CHANNEL_RECIPIENT_REMOVE: 'CHANNEL_RECIPIENT_REMOVE',
 
   // Thread events
   THREAD_CREATE: 'THREAD_CREATE',
@@ -26,6 +28,8 @@ export const GatewayEvents = {
   MESSAGE_DELETE_BULK: 'MESSAGE_DELETE_BULK',
   MESSAGE_REACTION_ADD: 'MESSAGE_REACTION_ADD',
   MESSAGE_REACTION_REMOVE: 'MESSAGE_REACTION_REMOVE',
+  MESSAGE_REACTION_ADD_BULK: 'MESSAGE_REACTION_ADD_BULK',
+  MESSAGE_REACTION_REMOVE_BULK: 'MESSAGE_REACTION_REMOVE_BULK',
   POLL_VOTE_ADD: 'POLL_VOTE_ADD',
   POLL_VOTE_REMOVE: 'POLL_VOTE_REMOVE',
 
@@ -47,10 +51,12 @@ export const GatewayEvents = {
   // Presence & typing
   PRESENCE_UPDATE: 'PRESENCE_UPDATE',
   TYPING_START: 'TYPING_START',
+  TYPING_STOP: 'TYPING_STOP',
 
   // Voice events
   VOICE_STATE_UPDATE: 'VOICE_STATE_UPDATE',
   VOICE_SERVER_UPDATE: 'VOICE_SERVER_UPDATE',
+  VOICE_RECORDING_UPDATE: 'VOICE_RECORDING_UPDATE',
 
   // Invite events
   INVITE_CREATE: 'INVITE_CREATE',
@@ -63,6 +69,9 @@ export const GatewayEvents = {
   RELATIONSHIP_ADD: 'RELATIONSHIP_ADD',
   RELATIONSHIP_REMOVE: 'RELATIONSHIP_REMOVE',
 
+  // Session events
+  SESSIONS_REVOKED: 'SESSIONS_REVOKED',
+
   // Scheduled event events
   GUILD_SCHEDULED_EVENT_CREATE: 'GUILD_SCHEDULED_EVENT_CREATE',
   GUILD_SCHEDULED_EVENT_UPDATE: 'GUILD_SCHEDULED_EVENT_UPDATE',
@@ -13,6 +13,7 @@ import { UpdateNotification } from '../components/UpdateNotification';
 import { ToastContainer } from '../components/ui/Toast';
 import { ImageLightbox } from '../components/ui/ImageLightbox';
 import { logVoiceDiagnostic } from './desktopDiagnostics';
+import { syncPushSubscription } from './notifications';
 
 function AppInitializer({ children }: { children: ReactNode }) {
   // Initialize gateway connection when authenticated
@@ -33,6 +34,7 @@ function AppInitializer({ children }: { children: ReactNode }) {
   const hydrateServerTokens = useServerListStore((s) => s.hydrateTokens);
   const fetchUser = useAuthStore((s) => s.fetchUser);
   const fetchSettings = useAuthStore((s) => s.fetchSettings);
+  const fetchAuthOptions = useAuthStore((s) => s.fetchAuthOptions);
   const settings = useAuthStore((s) => s.settings);
   const fetchGuilds = useGuildStore((s) => s.fetchGuilds);
   const voiceConnected = useVoiceStore((s) => s.connected);
@@ -47,11 +49,16 @@ function AppInitializer({ children }: { children: ReactNode }) {
     void initializeSession();
   }, [initializeSession]);
 
+  useEffect(() => {
+    void fetchAuthOptions();
+  }, [fetchAuthOptions]);
+
   useEffect(() => {
     if (token) {
       void fetchUser();
       void fetchSettings();
       void fetchGuilds();
+      void syncPushSubscription();
     }
   }, [token, fetchUser, fetchSettings, fetchGuilds]);
 
@@ -1,9 +1,10 @@
 ﻿import { type AxiosInstance } from 'axios';
-import { createApiClient } from '../api/client';
+import { createApiClient, extractApiErrorCode } from '../api/client';
 import { useServerListStore, type ServerEntry } from '../stores/serverListStore';
 import { useAccountStore } from '../stores/accountStore';
 import { useUIStore } from '../stores/uiStore';
 import { useAuthStore } from '../stores/authStore';
+import { requestMfaCode } from '../stores/mfaPromptStore';
 import {
   hasUnlockedPrivateKey,
   signServerChallengeWithUnlockedKey,
@@ -330,6 +331,7 @@ class ConnectionManager {
     server: ServerEntry,
     publicKey: string,
     username: string,
+    mfaCode?: string,
   ): Promise<string> {
     // Step 1: Get challenge
     const { data: challenge } = await client.post<{
@@ -361,17 +363,29 @@ class ConnectionManager {
 
     // Step 3: Verify (this also auto-registers if needed)
     const displayName = useAccountStore.getState().displayName;
-    const { data: authResponse } = await client.post<{
+    let authResponse: {
       token: string;
       user: { id: string; username: string; flags: number; public_key: string };
-    }>('/auth/verify', {
-      public_key: publicKey,
-      nonce: challenge.nonce,
-      timestamp: challenge.timestamp,
-      signature,
-      username,
-      display_name: displayName || undefined,
-    });
+    };
+    try {
+      ({ data: authResponse } = await client.post('/auth/verify', {
+        public_key: publicKey,
+        nonce: challenge.nonce,
+        timestamp: challenge.timestamp,
+        signature,
+        username,
+        display_name: displayName || undefined,
+        ...(mfaCode ? { mfa_code: mfaCode } : {}),
+      }));
+    } catch (err) {
+      // The account has TOTP enabled: ask for a code and sign a fresh
+      // challenge, since the first nonce is spent.
+      if (!mfaCode && extractApiErrorCode(err) === 'MFA_REQUIRED') {
+        const code = await requestMfaCode(server.name || server.url);
+        if (code) return this.authenticate(client, server, publicKey, username, code);
+      }
+      throw err;
+    }
 
     // Store the user's server-local ID
     useServerListStore.getState().updateServerInfo(server.id, {
@@ -0,0 +1,42 @@
+import { describe, it, expect } from 'vitest';
+import { ChannelType, type Channel } from '../types';
+import { dmDisplayName, isGroupDm } from './dmChannels';
+
+function channel(overrides: Partial<Channel>): Channel {
+  return {
+    id: '1',
+    type: ChannelType.DM,
+    position: 0,
+    nsfw: false,
+    created_at: '2026-01-01T00:00:00Z',
+    ...overrides,
+  };
+}
+
+const participant = (id: string, username: string) => ({ id, username, discriminator: 0 });
+
+describe('dmDisplayName', () => {
+  it('uses the other user for a 1:1 DM', () => {
+    const dm = channel({ recipient: participant('2', 'bob') });
+    expect(isGroupDm(dm)).toBe(false);
+    expect(dmDisplayName(dm, '1')).toBe('bob');
+  });
+
+  it('prefers the group name', () => {
+    const group = channel({
+      type: ChannelType.GroupDM,
+      name: 'Trip',
+      recipients: [participant('1', 'me'), participant('2', 'bob')],
+    });
+    expect(isGroupDm(group)).toBe(true);
+    expect(dmDisplayName(group, '1')).toBe('Trip');
+  });
+
+  it('lists the other participants of an unnamed group', () => {
+    const group = channel({
+      type: ChannelType.GroupDM,
+      recipients: [participant('1', 'me'), participant('2', 'bob'), participant('3', 'carol')],
+    });
+    expect(dmDisplayName(group, '1')).toBe('bob, carol');
+  });
+});
@@ -0,0 +1,23 @@
+import { ChannelType, type Channel } from '../types';
+
+/** Participants allowed in a group DM, the creator included. */
+export const MAX_GROUP_DM_RECIPIENTS = 10;
+
+export function isGroupDm(channel: Channel): boolean {
+  return (channel.channel_type ?? channel.type) === ChannelType.GroupDM;
+}
+
+/**
+ * Title for a DM conversation: the other user for a 1:1 DM; for a group DM
+ * its name, or the other participants' usernames when it has none.
+ */
+export function dmDisplayName(channel: Channel, selfId?: string | null): string {
+  if (!isGroupDm(channel)) {
+    return channel.recipient?.username || 'Direct Message';
+  }
+  if (channel.name) return channel.name;
+  const others = (channel.recipients ?? [])
+    .filter((recipient) => recipient.id !== selfId)
+    .map((recipient) => recipient.username);
+  return others.length > 0 ? others.join(', ') : 'Group DM';
+}
@@ -152,3 +152,19 @@ export function formatMemberCount(count: number): string {
   if (count < 1_000_000) return (count / 1000).toFixed(1).replace(/\.0$/, '') + 'K';
   return (count / 1_000_000).toFixed(1).replace(/\.0$/, '') + 'M';
 }
+
+// ============ User Formatters ============
+
+/**
+ * Formats a user's tag: "name#0001", or just "name" when the server
+ * enforces unique handles and discriminators carry no meaning.
+ */
+export function formatUserTag(
+  user: { username: string; discriminator?: string | number | null },
+  uniqueUsernames: boolean
+): string {
+  if (uniqueUsernames || user.discriminator == null || user.discriminator === '') {
+    return user.username;
+  }
+  return `${user.username}#${String(user.discriminator).padStart(4, '0')}`;
+}
@@ -1,3 +1,4 @@
+import { authApi } from '../api/auth';
 import { isTauri } from './tauriEnv';
 
 const NOTIFICATIONS_ENABLED_KEY = 'paracord:notifications-enabled';
@@ -103,3 +104,62 @@ export function setEnabled(enabled: boolean): void {
     // localStorage unavailable
   }
 }
+
+function urlBase64ToUint8Array(value: string): Uint8Array {
+  const padded = value + '='.repeat((4 - (value.length % 4)) % 4);
+  const raw = atob(padded.replace(/-/g, '+').replace(/_/g, '/'));
+  return Uint8Array.from(raw, (c) => c.charCodeAt(0));
+}
+
+function webPushSupported(): boolean {
+  return (
+    !isTauri() &&
+    typeof navigator !== 'undefined' &&
+    'serviceWorker' in navigator &&
+    typeof window !== 'undefined' &&
+    'PushManager' in window
+  );
+}
+
+/**
+ * Register this browser for Web Push so mentions and DMs arrive while the
+ * app is closed. Subscriptions belong to the current session, so this runs
+ * after every sign-in. Does nothing when notifications are off, permission
+ * is missing, or the server has push disabled.
+ */
+export async function syncPushSubscription(): Promise<void> {
+  if (!webPushSupported() || !isEnabled()) return;
+  if (Notification.permission !== 'granted') return;
+  try {
+    const { data } = await authApi.getPushVapidKey();
+    const registration = await navigator.serviceWorker.ready;
+    const subscription =
+      (await registration.pushManager.getSubscription()) ??
+      (await registration.pushManager.subscribe({
+        userVisibleOnly: true,
+        applicationServerKey: urlBase64ToUint8Array(data.public_key),
+      }));
+    const json = subscription.toJSON();
+    if (!json.endpoint || !json.keys?.p256dh || !json.keys?.auth) return;
+    await authApi.registerPushSubscription({
+      endpoint: json.endpoint,
+      keys: { p256dh: json.keys.p256dh, auth: json.keys.auth },
+    });
+  } catch {
+    // Push disabled on the server or unsupported by the browser.
+  }
+}
+
+/** Stop Web Push for this browser. */
+export async function clearPushSubscription(): Promise<void> {
+  if (!webPushSupported()) return;
+  try {
+    const registration = await navigator.serviceWorker.getRegistration();
+    const subscription = await registration?.pushManager.getSubscription();
+    if (!subscription) return;
+    await authApi.deletePushSubscription(subscription.endpoint).catch(() => undefined);
+    await subscription.unsubscribe();
+  } catch {
+    // Nothing to clear.
+  }
+}
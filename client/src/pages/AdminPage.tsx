@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
 import { useNavigate } from 'react-router-dom';
-import { ArrowLeft, Users, Server, Settings, BarChart3, Shield, ShieldOff, Trash2, Pencil, HardDrive, Download, Plus, Loader2, RotateCcw } from 'lucide-react';
+import { ArrowLeft, Users, Server, Settings, BarChart3, Shield, ShieldOff, Trash2, Building2, LogOut, Pencil, HardDrive, Download, Plus, Loader2, RotateCcw } from 'lucide-react';
 import { adminApi } from '../api/admin';
 import { extractApiError } from '../api/client';
 import { toast } from '../stores/toastStore';
@@ -137,6 +137,7 @@ function UsersPanel() {
   const [total, setTotal] = useState(0);
   const [offset, setOffset] = useState(0);
   const [search, setSearch] = useState('');
+  const uniqueUsernames = useAuthStore((s) => s.uniqueUsernames);
   const limit = 25;
 
   const fetchUsers = () => {
@@ -167,6 +168,28 @@ function UsersPanel() {
     }
   };
 
+  const toggleGuildCreator = async (userId: string, currentFlags: number) => {
+    try {
+      await adminApi.updateUser(userId, { flags: currentFlags ^ UserFlags.GUILD_CREATOR });
+      fetchUsers();
+    } catch (err) {
+      toast.error(`Failed to update user: ${extractApiError(err)}`);
+    }
+  };
+
+  const revokeSessions = async (userId: string, username: string) => {
+    if (!confirm(`Log "${username}" out of every device and revoke their access tokens?`)) return;
+    try {
+      const { data } = await adminApi.revokeUserSessions(userId);
+      toast.success(
+        `Revoked ${data.revoked} session${data.revoked === 1 ? '' : 's'} and ` +
+          `${data.tokens_revoked} access token${data.tokens_revoked === 1 ? '' : 's'}`
+      );
+    } catch (err) {
+      toast.error(`Failed to revoke sessions: ${extractApiError(err)}`);
+    }
+  };
+
   const deleteUser = async (userId: string, username: string) => {
     if (!confirm(`Delete user "${username}"? This cannot be undone.`)) return;
     try {
@@ -220,7 +243,9 @@ function UsersPanel() {
               <tr key={u.id} className="border-b border-border-subtle/50 last:border-b-0 transition-colors hover:bg-bg-mod-subtle/30">
                 <td className="px-6 py-5 text-text-primary">
                   <span className="font-medium">{u.display_name || u.username}</span>
-                  <span className="ml-1 text-text-muted">#{u.discriminator}</span>
+                  {!uniqueUsernames && (
+                    <span className="ml-1 text-text-muted">#{u.discriminator}</span>
+                  )}
                 </td>
                 <td className="px-6 py-5 text-text-secondary">{u.email}</td>
                 <td className="px-6 py-5">
@@ -246,6 +271,28 @@ function UsersPanel() {
                         >
                           {isAdmin(u.flags) ? <ShieldOff size={16} /> : <Shield size={16} />}
                         </button>
+                        <button
+                          onClick={() => toggleGuildCreator(u.id, u.flags)}
+                          className={`rounded-lg p-1.5 transition-colors hover:bg-bg-mod-subtle ${
+                            u.flags & UserFlags.GUILD_CREATOR
+                              ? 'text-accent-primary'
+                              : 'text-text-secondary hover:text-text-primary'
+                          }`}
+                          title={
+                            u.flags & UserFlags.GUILD_CREATOR
+                              ? 'Revoke guild creation'
+                              : 'Allow guild creation when restricted'
+                          }
+                        >
+                          <Building2 size={16} />
+                        </button>
+                        <button
+                          onClick={() => revokeSessions(u.id, u.username)}
+                          className="rounded-lg p-1.5 text-text-secondary transition-colors hover:bg-bg-mod-subtle hover:text-text-primary"
+                          title="Log out everywhere"
+                        >
+                          <LogOut size={16} />
+                        </button>
                         <button
                           onClick={() => deleteUser(u.id, u.username)}
                           className="rounded-lg p-1.5 text-text-secondary transition-colors hover:bg-accent-danger/10 hover:text-accent-danger"
@@ -681,6 +728,82 @@ function SettingsPanel() {
           </button>
         </div>
 
+        {/* Guild creation restriction */}
+        <div className="card-surface flex items-center justify-between rounded-xl border border-border-subtle bg-bg-mod-subtle/70 px-6 py-6">
+          <div>
+            <p className="font-medium text-text-primary">Restrict Guild Creation</p>
+            <p className="text-sm text-text-muted">Only admins and allowed users can create guilds</p>
+          </div>
+          <button
+            onClick={() =>
+              update(
+                'guild_creation_restricted',
+                settings.guild_creation_restricted === 'true' ? 'false' : 'true'
+              )
+            }
+            className={`relative h-7 w-12 rounded-full transition-colors ${
+              settings.guild_creation_restricted === 'true'
+                ? 'bg-accent-success'
+                : 'bg-bg-mod-strong'
+            }`}
+          >
+            <div
+              className={`absolute top-0.5 h-6 w-6 rounded-full bg-white shadow transition-transform ${
+                settings.guild_creation_restricted === 'true' ? 'translate-x-5' : 'translate-x-0.5'
+              }`}
+            />
+          </button>
+        </div>
+
+        {/* Email verification requirement */}
+        <div className="card-surface flex items-center justify-between rounded-xl border border-border-subtle bg-bg-mod-subtle/70 px-6 py-6">
+          <div>
+            <p className="font-medium text-text-primary">Require Email Verification</p>
+            <p className="text-sm text-text-muted">New accounts must verify their email before logging in</p>
+          </div>
+          <button
+            onClick={() =>
+              update(
+                'require_email_verification',
+                settings.require_email_verification === 'true' ? 'false' : 'true'
+              )
+            }
+            className={`relative h-7 w-12 rounded-full transition-colors ${
+              settings.require_email_verification === 'true'
+                ? 'bg-accent-success'
+                : 'bg-bg-mod-strong'
+            }`}
+          >
+            <div
+              className={`absolute top-0.5 h-6 w-6 rounded-full bg-white shadow transition-transform ${
+                settings.require_email_verification === 'true' ? 'translate-x-5' : 'translate-x-0.5'
+              }`}
+            />
+          </button>
+        </div>
+
+        {/* Blocked name patterns */}
+        <div>
+          <label className="mb-3 block text-sm font-medium text-text-secondary">
+            Blocked Username and Guild Name Patterns
+          </label>
+          <textarea
+            value={settings.blocked_name_patterns || ''}
+            onChange={(e) => update('blocked_name_patterns', e.target.value)}
+            rows={4}
+            placeholder={'One per line; wrap a line in slashes for a regex, e.g. /^staff\\d*$/'}
+            className="input-field resize-none font-mono"
+          />
+          <label className="mt-3 flex items-center gap-2 text-sm text-text-muted">
+            <input
+              type="checkbox"
+              checked={settings.blocked_names_fold !== 'false'}
+              onChange={(e) => update('blocked_names_fold', e.target.checked ? 'true' : 'false')}
+            />
+            Ignore case and lookalike characters
+          </label>
+        </div>
+
         {/* Max guilds per user */}
         <div>
           <label className="mb-3 block text-sm font-medium text-text-secondary">
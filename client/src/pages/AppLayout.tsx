@@ -6,6 +6,7 @@ import { ChannelSidebar } from '../components/layout/ChannelSidebar';
 import { MemberList } from '../components/layout/MemberList';
 import { CommandPalette } from '../components/layout/CommandPalette';
 import { ConfirmDialog } from '../components/ui/ConfirmDialog';
+import { MfaPromptDialog } from '../components/ui/MfaPromptDialog';
 import { MiniVoiceBar } from '../components/voice/MiniVoiceBar';
 import { MobileBottomNav } from '../components/layout/MobileBottomNav';
 import { useUIStore } from '../stores/uiStore';
@@ -165,6 +166,7 @@ export function AppLayout() {
 
       <CommandPalette />
       <ConfirmDialog />
+      <MfaPromptDialog />
 
       {/* Windowed Settings Overlays */}
       <AnimatePresence>
@@ -4,7 +4,10 @@ import { MessageCircleMore } from 'lucide-react';
 import { TopBar } from '../components/layout/TopBar';
 import { MessageList } from '../components/message/MessageList';
 import { MessageInput } from '../components/message/MessageInput';
+import { GroupDmMembers } from '../components/layout/GroupDmMembers';
 import { useChannelStore } from '../stores/channelStore';
+import { useAuthStore } from '../stores/authStore';
+import { dmDisplayName, isGroupDm } from '../lib/dmChannels';
 import type { Channel, Message } from '../types';
 
 const EMPTY_CHANNELS: Channel[] = [];
@@ -14,7 +17,8 @@ export function DMPage() {
   const navigate = useNavigate();
   const dmChannels = useChannelStore((s) => s.channelsByGuild[''] ?? EMPTY_CHANNELS);
   const dmChannel = dmChannels.find((c) => c.id === channelId);
-  const recipientName = dmChannel?.recipient?.username || 'Direct Message';
+  const selfId = useAuthStore((s) => s.user?.id);
+  const recipientName = dmChannel ? dmDisplayName(dmChannel, selfId) : 'Direct Message';
   const [replyingTo, setReplyingTo] = useState<{ id: string; author: string; content: string } | null>(null);
 
   useEffect(() => {
@@ -55,17 +59,22 @@ export function DMPage() {
   return (
     <div className="flex h-full min-h-0 flex-col">
       <TopBar isDM recipientName={recipientName} />
-      <MessageList
-        channelId={channelId}
-        onReply={(msg: Message) =>
-          setReplyingTo({
-            id: msg.id,
-            author: msg.author.username,
-            content: msg.content || '',
-          })
-        }
-      />
-      <MessageInput channelId={channelId} replyingTo={replyingTo} onCancelReply={() => setReplyingTo(null)} />
+      <div className="flex min-h-0 flex-1">
+        <div className="flex min-h-0 min-w-0 flex-1 flex-col">
+          <MessageList
+            channelId={channelId}
+            onReply={(msg: Message) =>
+              setReplyingTo({
+                id: msg.id,
+                author: msg.author.username,
+                content: msg.content || '',
+              })
+            }
+          />
+          <MessageInput channelId={channelId} replyingTo={replyingTo} onCancelReply={() => setReplyingTo(null)} />
+        </div>
+        {dmChannel && isGroupDm(dmChannel) && <GroupDmMembers channel={dmChannel} />}
+      </div>
     </div>
   );
 }
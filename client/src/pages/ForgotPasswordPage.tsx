@@ -0,0 +1,83 @@
+import { useState } from 'react';
+import { Link } from 'react-router-dom';
+import { authApi } from '../api/auth';
+import { extractApiErrorCode } from '../api/client';
+
+export function ForgotPasswordPage() {
+  const [email, setEmail] = useState('');
+  const [sent, setSent] = useState(false);
+  const [error, setError] = useState('');
+  const [loading, setLoading] = useState(false);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setError('');
+    setLoading(true);
+    try {
+      await authApi.forgotPassword(email.trim());
+      setSent(true);
+    } catch (err) {
+      const code = extractApiErrorCode(err);
+      setError(
+        code === 'RATE_LIMITED'
+          ? 'Too many reset emails requested. Try again later.'
+          : code === 'SERVICE_UNAVAILABLE'
+            ? 'This server cannot send reset emails. Contact your server administrator.'
+            : 'Could not send a reset email. Try again.',
+      );
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="auth-shell">
+      <form onSubmit={handleSubmit} className="auth-card mx-auto w-full max-w-md space-y-8 p-10">
+        <div className="text-center">
+          <h1 className="text-3xl font-bold leading-tight text-text-primary">Reset your password</h1>
+          <p className="mt-3 text-sm text-text-muted">
+            Enter your account's email address and we will send you a reset link.
+          </p>
+        </div>
+
+        {error && (
+          <div className="rounded-xl border border-accent-danger/35 bg-accent-danger/10 px-5 py-4 text-sm font-medium text-accent-danger">
+            {error}
+          </div>
+        )}
+
+        {sent ? (
+          <p className="text-sm text-text-secondary">
+            If an account uses that address, a reset link is on its way. It expires in one hour.
+          </p>
+        ) : (
+          <>
+            <label className="block">
+              <span className="block text-xs font-semibold uppercase tracking-wide text-text-secondary">
+                Email <span className="text-accent-danger">*</span>
+              </span>
+              <input
+                type="email"
+                value={email}
+                onChange={(e) => setEmail(e.target.value)}
+                required
+                className="input-field mt-2"
+                placeholder="you@example.com"
+              />
+            </label>
+
+            <button type="submit" disabled={loading} className="btn-primary w-full">
+              {loading ? 'Sending...' : 'Send Reset Link'}
+            </button>
+          </>
+        )}
+
+        <p className="text-sm text-text-muted">
+          <Link to="/login" className="font-semibold text-text-link hover:underline">
+            Back to Login
+          </Link>
+        </p>
+      </form>
+    </div>
+  );
+}
@@ -13,6 +13,7 @@ import { dmApi } from '../api/dms';
 import { CreateGuildModal } from '../components/guild/CreateGuildModal';
 import { isSafeImageDataUrl } from '../lib/security';
 import { getGuildColor } from '../lib/colors';
+import { dmDisplayName } from '../lib/dmChannels';
 import { Tooltip } from '../components/ui/Tooltip';
 
 import type { Channel } from '../types';
@@ -204,7 +205,7 @@ export function HomePage() {
             <div className="rounded-[16px] border border-border-subtle bg-bg-mod-subtle">
               {recentDms.length > 0 ? (
                 recentDms.map((dm, idx) => {
-                  const username = dm.recipient?.username || 'Direct Message';
+                  const username = dmDisplayName(dm, user?.id);
                   const isOnline = (getPresence(dm.recipient?.id || '', activeServerId ?? undefined)?.status || 'offline') !== 'offline';
                   return (
                     <div
@@ -11,6 +11,7 @@ import {
 } from '../lib/apiBaseUrl';
 import { hasAccount } from '../lib/account';
 import { authApi } from '../api/auth';
+import { extractApiErrorCode } from '../api/client';
 
 type LoginIdentifierMode = {
   allowUsernameInput: boolean;
@@ -36,10 +37,17 @@ export function resolveLoginIdentifierMode(
 export function LoginPage() {
   const [identifier, setIdentifier] = useState('');
   const [password, setPassword] = useState('');
+  // Shown once the server answers MFA_REQUIRED for these credentials.
+  const [mfaRequired, setMfaRequired] = useState(false);
+  const [mfaCode, setMfaCode] = useState('');
+  // Set when the server answers EMAIL_UNVERIFIED for this account.
+  const [emailUnverified, setEmailUnverified] = useState(false);
+  const [resendState, setResendState] = useState<'idle' | 'sending' | 'sent'>('idle');
   const [error, setError] = useState('');
   const [loading, setLoading] = useState(false);
   const [allowUsernameLogin, setAllowUsernameLogin] = useState(true);
   const [requireEmail, setRequireEmail] = useState(false);
+  const [passwordResetAvailable, setPasswordResetAvailable] = useState(false);
   const [failedAttempts, setFailedAttempts] = useState(0);
   const [cooldownUntil, setCooldownUntil] = useState(0);
   const navigate = useNavigate();
@@ -54,6 +62,7 @@ export function LoginPage() {
         if (cancelled) return;
         setAllowUsernameLogin(data.allow_username_login);
         setRequireEmail(data.require_email);
+        setPasswordResetAvailable(Boolean(data.password_reset_available));
       })
       .catch(() => {
         // Keep conservative defaults when options are unavailable.
@@ -68,9 +77,26 @@ export function LoginPage() {
     window.location.href = '/connect';
   };
 
+  const handleResendVerification = async () => {
+    setResendState('sending');
+    try {
+      await authApi.resendEmailVerification(identifier.trim());
+      setResendState('sent');
+    } catch (err) {
+      setResendState('idle');
+      setError(
+        extractApiErrorCode(err) === 'RATE_LIMITED'
+          ? 'Too many verification emails requested. Try again later.'
+          : 'Could not send a verification email. Try again.',
+      );
+    }
+  };
+
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     setError('');
+    setEmailUnverified(false);
+    setResendState('idle');
     const now = Date.now();
     if (now < cooldownUntil) {
       const waitSeconds = Math.ceil((cooldownUntil - now) / 1000);
@@ -79,7 +105,7 @@ export function LoginPage() {
     }
     setLoading(true);
     try {
-      await login(identifier, password);
+      await login(identifier, password, mfaRequired ? mfaCode : undefined);
       setFailedAttempts(0);
       setCooldownUntil(0);
 
@@ -117,14 +143,30 @@ export function LoginPage() {
       // Go straight to the app — legacy token auth works without a local
       // keypair. Users can set up a local crypto identity later in Settings.
       navigate('/app');
-    } catch {
+    } catch (err) {
+      if (extractApiErrorCode(err) === 'MFA_REQUIRED') {
+        // The password was right; ask for the second factor instead of
+        // counting a failure.
+        setMfaRequired(true);
+        setMfaCode('');
+        return;
+      }
+      if (extractApiErrorCode(err) === 'EMAIL_UNVERIFIED') {
+        setEmailUnverified(true);
+        setError('Verify your email address before logging in. Check your inbox for the link.');
+        return;
+      }
       const nextFailures = failedAttempts + 1;
       setFailedAttempts(nextFailures);
       if (nextFailures >= 3) {
         const backoffSeconds = Math.min(30, 2 ** Math.min(5, nextFailures - 3));
         setCooldownUntil(Date.now() + backoffSeconds * 1000);
       }
-      setError('Login failed. Check your credentials and try again.');
+      setError(
+        mfaRequired
+          ? 'That code did not work. Check your authenticator app or use a recovery code.'
+          : 'Login failed. Check your credentials and try again.',
+      );
     } finally {
       setLoading(false);
     }
@@ -146,6 +188,25 @@ export function LoginPage() {
           </div>
         )}
 
+        {emailUnverified && (
+          <p className="text-sm text-text-muted">
+            {resendState === 'sent' ? (
+              'A new verification link is on its way.'
+            ) : identifier.includes('@') ? (
+              <button
+                type="button"
+                onClick={handleResendVerification}
+                disabled={resendState === 'sending'}
+                className="font-semibold text-text-link hover:underline"
+              >
+                {resendState === 'sending' ? 'Sending...' : 'Resend verification email'}
+              </button>
+            ) : (
+              'Log in with your email address to get a new verification link.'
+            )}
+          </p>
+        )}
+
         <div className="card-stack-roomy">
           <label className="block">
             <span className="block text-xs font-semibold uppercase tracking-wide text-text-secondary">
@@ -154,7 +215,10 @@ export function LoginPage() {
             <input
               type={identifierMode.inputType}
               value={identifier}
-              onChange={(e) => setIdentifier(e.target.value)}
+              onChange={(e) => {
+                setIdentifier(e.target.value);
+                setMfaRequired(false);
+              }}
               required
               className="input-field mt-2"
               placeholder={identifierMode.placeholder}
@@ -168,16 +232,46 @@ export function LoginPage() {
             <input
               type="password"
               value={password}
-              onChange={(e) => setPassword(e.target.value)}
+              onChange={(e) => {
+                setPassword(e.target.value);
+                setMfaRequired(false);
+              }}
               required
               className="input-field mt-2"
               placeholder="Enter your password"
             />
           </label>
+
+          {mfaRequired && (
+            <label className="block">
+              <span className="block text-xs font-semibold uppercase tracking-wide text-text-secondary">
+                Authentication Code <span className="text-accent-danger">*</span>
+              </span>
+              <input
+                type="text"
+                autoComplete="one-time-code"
+                value={mfaCode}
+                onChange={(e) => setMfaCode(e.target.value)}
+                required
+                autoFocus
+                className="input-field mt-2"
+                placeholder="6-digit code or recovery code"
+              />
+              <span className="mt-2 block text-xs text-text-muted">
+                This account uses two-factor authentication.
+              </span>
+            </label>
+          )}
         </div>
 
         <p className="text-xs leading-5 text-text-muted">
-          Forgot your password? Contact your server administrator to reset your credentials.
+          {passwordResetAvailable ? (
+            <Link to="/forgot-password" className="font-semibold text-text-link hover:underline">
+              Forgot your password?
+            </Link>
+          ) : (
+            'Forgot your password? Contact your server administrator to reset your credentials.'
+          )}
         </p>
 
         <button
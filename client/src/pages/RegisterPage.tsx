@@ -50,6 +50,10 @@ export function RegisterPage() {
     setLoading(true);
     try {
       await register(email, username, password, displayName);
+      if (!useAuthStore.getState().token) {
+        setError('Check your email to verify your account, then log in.');
+        return;
+      }
 
       // If the user already has a local keypair, attach it to this server account.
       if (hasAccount()) {
@@ -0,0 +1,98 @@
+import { useState } from 'react';
+import { Link, useSearchParams } from 'react-router-dom';
+import { authApi } from '../api/auth';
+import { extractApiError } from '../api/client';
+
+export function ResetPasswordPage() {
+  const [searchParams] = useSearchParams();
+  const token = searchParams.get('token') ?? '';
+  const [password, setPassword] = useState('');
+  const [confirm, setConfirm] = useState('');
+  const [done, setDone] = useState(false);
+  const [error, setError] = useState(token ? '' : 'This reset link is missing its token.');
+  const [loading, setLoading] = useState(false);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (password !== confirm) {
+      setError('The passwords do not match.');
+      return;
+    }
+    setError('');
+    setLoading(true);
+    try {
+      await authApi.resetPassword(token, password);
+      setDone(true);
+    } catch (err) {
+      setError(extractApiError(err) || 'This reset link is invalid or has expired.');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="auth-shell">
+      <form onSubmit={handleSubmit} className="auth-card mx-auto w-full max-w-md space-y-8 p-10">
+        <div className="text-center">
+          <h1 className="text-3xl font-bold leading-tight text-text-primary">Choose a new password</h1>
+        </div>
+
+        {error && (
+          <div className="rounded-xl border border-accent-danger/35 bg-accent-danger/10 px-5 py-4 text-sm font-medium text-accent-danger">
+            {error}
+          </div>
+        )}
+
+        {done ? (
+          <p className="text-sm text-text-secondary">
+            Your password has been changed and every other session was signed out. You can log in
+            now.
+          </p>
+        ) : (
+          <>
+            <div className="card-stack-roomy">
+              <label className="block">
+                <span className="block text-xs font-semibold uppercase tracking-wide text-text-secondary">
+                  New Password <span className="text-accent-danger">*</span>
+                </span>
+                <input
+                  type="password"
+                  autoComplete="new-password"
+                  value={password}
+                  onChange={(e) => setPassword(e.target.value)}
+                  required
+                  minLength={10}
+                  maxLength={128}
+                  className="input-field mt-2"
+                />
+              </label>
+              <label className="block">
+                <span className="block text-xs font-semibold uppercase tracking-wide text-text-secondary">
+                  Confirm Password <span className="text-accent-danger">*</span>
+                </span>
+                <input
+                  type="password"
+                  autoComplete="new-password"
+                  value={confirm}
+                  onChange={(e) => setConfirm(e.target.value)}
+                  required
+                  className="input-field mt-2"
+                />
+              </label>
+            </div>
+
+            <button type="submit" disabled={loading || !token} className="btn-primary w-full">
+              {loading ? 'Saving...' : 'Set Password'}
+            </button>
+          </>
+        )}
+
+        <p className="text-sm text-text-muted">
+          <Link to="/login" className="font-semibold text-text-link hover:underline">
+            Go to Login
+          </Link>
+        </p>
+      </form>
+    </div>
+  );
+}
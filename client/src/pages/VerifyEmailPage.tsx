@@ -0,0 +1,61 @@
+import { useEffect, useState } from 'react';
+import { Link, useSearchParams } from 'react-router-dom';
+import { MailCheck } from 'lucide-react';
+import { authApi } from '../api/auth';
+import { extractApiError } from '../api/client';
+
+export function VerifyEmailPage() {
+  const [searchParams] = useSearchParams();
+  const token = searchParams.get('token') ?? '';
+  const [status, setStatus] = useState<'verifying' | 'verified' | 'failed'>('verifying');
+  const [error, setError] = useState('');
+
+  useEffect(() => {
+    if (!token) {
+      setStatus('failed');
+      setError('This verification link is missing its token.');
+      return;
+    }
+    let cancelled = false;
+    authApi
+      .verifyEmail(token)
+      .then(() => {
+        if (!cancelled) setStatus('verified');
+      })
+      .catch((err) => {
+        if (cancelled) return;
+        setStatus('failed');
+        setError(extractApiError(err) || 'This verification link is invalid or has expired.');
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [token]);
+
+  return (
+    <div className="auth-shell">
+      <div className="auth-card text-center">
+        <div className="mx-auto mb-4 flex h-20 w-20 items-center justify-center rounded-3xl border border-border-subtle bg-accent-primary text-3xl font-bold text-white shadow-[0_16px_40px_rgba(78,102,232,0.38)]">
+          <MailCheck size={30} />
+        </div>
+        <h1 className="mb-1 text-2xl font-bold text-text-primary">Email verification</h1>
+        {status === 'verifying' && (
+          <p className="mb-6 text-sm text-text-secondary">Verifying your email address...</p>
+        )}
+        {status === 'verified' && (
+          <p className="mb-6 text-sm text-text-secondary">
+            Your email address is verified. You can log in now.
+          </p>
+        )}
+        {status === 'failed' && (
+          <div className="mb-6 rounded-xl border border-accent-danger/35 bg-accent-danger/10 px-3 py-2.5 text-sm font-medium text-accent-danger">
+            {error} Log in with your email address to request a new link.
+          </div>
+        )}
+        <Link to="/login" className="btn-primary w-full">
+          Go to Login
+        </Link>
+      </div>
+    </div>
+  );
+}
@@ -9,6 +9,7 @@ const mockAuthApi = vi.hoisted(() => ({
   updateMe: vi.fn(),
   getSettings: vi.fn(),
   updateSettings: vi.fn(),
+  options: vi.fn(),
 }));
 
 vi.mock('../lib/authToken', () => ({
@@ -65,6 +66,7 @@ describe('authStore', () => {
       settings: null,
       hasFetchedSettings: false,
       sessionBootstrapComplete: false,
+      uniqueUsernames: false,
       isLoading: false,
       error: null,
     });
@@ -100,6 +102,20 @@ describe('authStore', () => {
       expect(state.error).toBeNull();
     });
 
+    it('sends the second-factor code when one is given', async () => {
+      mockAuthApi.login.mockResolvedValue({
+        data: { token: 'tok123', user: fakeUser },
+      });
+
+      await useAuthStore.getState().login('test@example.com', 'pass123', ' 123456 ');
+      expect(mockAuthApi.login).toHaveBeenCalledWith({
+        identifier: 'test@example.com',
+        email: 'test@example.com',
+        password: 'pass123',
+        mfa_code: '123456',
+      });
+    });
+
     it('sets isLoading during login', async () => {
       let resolveLogin: (v: unknown) => void;
       mockAuthApi.login.mockImplementation(
@@ -276,6 +292,22 @@ describe('authStore', () => {
     });
   });
 
+  describe('fetchAuthOptions', () => {
+    it('records handle mode from the server options', async () => {
+      mockAuthApi.options.mockResolvedValue({ data: { unique_usernames: true } });
+
+      await useAuthStore.getState().fetchAuthOptions();
+      expect(useAuthStore.getState().uniqueUsernames).toBe(true);
+    });
+
+    it('keeps discriminators visible when the options request fails', async () => {
+      mockAuthApi.options.mockRejectedValue(new Error('offline'));
+
+      await useAuthStore.getState().fetchAuthOptions();
+      expect(useAuthStore.getState().uniqueUsernames).toBe(false);
+    });
+  });
+
   describe('setToken', () => {
     it('sets the token', () => {
       useAuthStore.getState().setToken('new-token');
@@ -11,15 +11,19 @@ interface AuthState {
   settings: UserSettings | null;
   hasFetchedSettings: boolean;
   sessionBootstrapComplete: boolean;
+  /** True when the server runs in handle mode (`server.unique_usernames`). */
+  uniqueUsernames: boolean;
   isLoading: boolean;
   error: string | null;
 
-  login: (identifier: string, password: string) => Promise<void>;
+  /** Rejects with an `MFA_REQUIRED` API error when the account needs `mfaCode`. */
+  login: (identifier: string, password: string, mfaCode?: string) => Promise<void>;
   register: (email: string, username: string, password: string, displayName?: string) => Promise<void>;
   initializeSession: () => Promise<void>;
   setToken: (token: string | null) => void;
   logout: () => Promise<void>;
   fetchUser: () => Promise<void>;
+  fetchAuthOptions: () => Promise<void>;
   updateUser: (data: Partial<User>) => Promise<void>;
   fetchSettings: () => Promise<void>;
   updateSettings: (data: Partial<UserSettings>) => Promise<void>;
@@ -44,10 +48,11 @@ export const useAuthStore = create<AuthState>()((set) => ({
   settings: null,
   hasFetchedSettings: false,
   sessionBootstrapComplete: false,
+  uniqueUsernames: false,
   isLoading: false,
   error: null,
 
-  login: async (identifier, password) => {
+  login: async (identifier, password, mfaCode) => {
     set({ isLoading: true, error: null });
     try {
       const trimmedIdentifier = identifier.trim();
@@ -55,6 +60,7 @@ export const useAuthStore = create<AuthState>()((set) => ({
         identifier: trimmedIdentifier,
         email: trimmedIdentifier,
         password,
+        ...(mfaCode ? { mfa_code: mfaCode.trim() } : {}),
       });
       setAccessToken(data.token);
       if (data.refresh_token) setRefreshToken(data.refresh_token);
@@ -77,6 +83,11 @@ export const useAuthStore = create<AuthState>()((set) => ({
         password,
         display_name: displayName || undefined,
       });
+      // No session until the account verifies its email address.
+      if ('email_verification_required' in data) {
+        set({ isLoading: false });
+        return;
+      }
       setAccessToken(data.token);
       if (data.refresh_token) setRefreshToken(data.refresh_token);
       set({ token: data.token, user: data.user, isLoading: false });
@@ -126,6 +137,15 @@ export const useAuthStore = create<AuthState>()((set) => ({
     }
   },
 
+  fetchAuthOptions: async () => {
+    try {
+      const { data } = await authApi.options();
+      set({ uniqueUsernames: Boolean(data.unique_usernames) });
+    } catch {
+      // Fall back to showing discriminators.
+    }
+  },
+
   updateUser: async (userData) => {
     const { data } = await authApi.updateMe(userData);
     set({ user: data });
@@ -0,0 +1,38 @@
+import { create } from 'zustand';
+
+interface MfaPromptState {
+  isOpen: boolean;
+  /** Server the code is for, shown in the dialog. */
+  serverName: string | null;
+  resolve: ((code: string | null) => void) | null;
+
+  request: (serverName: string) => Promise<string | null>;
+  close: (code: string | null) => void;
+}
+
+export const useMfaPromptStore = create<MfaPromptState>()((set, get) => ({
+  isOpen: false,
+  serverName: null,
+  resolve: null,
+
+  request: (serverName) => {
+    // A second prompt supersedes one that is still open.
+    get().resolve?.(null);
+    return new Promise<string | null>((resolve) => {
+      set({ isOpen: true, serverName, resolve });
+    });
+  },
+
+  close: (code) => {
+    const { resolve } = get();
+    resolve?.(code);
+    set({ isOpen: false, serverName: null, resolve: null });
+  },
+}));
+
+/**
+ * Ask the user for a TOTP or recovery code after a login answered
+ * `MFA_REQUIRED`. Resolves to `null` if they cancel.
+ */
+export const requestMfaCode = (serverName: string) =>
+  useMfaPromptStore.getState().request(serverName);
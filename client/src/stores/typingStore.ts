@@ -5,6 +5,7 @@ const typingTimeouts = new Map<string, ReturnType<typeof setTimeout>>();
 interface TypingState {
   typingByChannel: Record<string, string[]>;
   addTyping: (channelId: string, userId: string) => void;
+  removeTyping: (channelId: string, userId: string) => void;
   clearChannel: (channelId: string) => void;
 }
 
@@ -45,6 +46,20 @@ export const useTypingStore = create<TypingState>()((set) => ({
       };
     }),
 
+  removeTyping: (channelId, userId) =>
+    set((state) => {
+      const timeoutKey = `${channelId}:${userId}`;
+      const existing = typingTimeouts.get(timeoutKey);
+      if (existing) clearTimeout(existing);
+      typingTimeouts.delete(timeoutKey);
+      return {
+        typingByChannel: {
+          ...state.typingByChannel,
+          [channelId]: (state.typingByChannel[channelId] || []).filter((u) => u !== userId),
+        },
+      };
+    }),
+
   clearChannel: (channelId) =>
     set((state) => ({
       typingByChannel: {
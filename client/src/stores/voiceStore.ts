@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import type { VoiceState } from '../types';
-import { voiceApi } from '../api/voice';
+import { voiceApi, type VoiceJoinResponse, type VoiceRecording } from '../api/voice';
 import {
   Room,
   RoomEvent,
@@ -24,6 +24,7 @@ import {
 } from 'livekit-client';
 import { useAuthStore } from './authStore';
 import { useServerListStore } from './serverListStore';
+import { toast } from './toastStore';
 import { playVoiceJoinSound, playVoiceLeaveSound } from '../lib/voiceSounds';
 import { startNativeSystemAudio, stopNativeSystemAudio } from '../lib/systemAudioCapture';
 import { isTauri } from '../lib/tauriEnv';
@@ -2274,6 +2275,8 @@ interface VoiceStoreState {
   channelParticipants: Map<string, VoiceState[]>;
   // Set of user IDs currently speaking (from LiveKit)
   speakingUsers: Set<string>;
+  // Active recordings, keyed by channel ID
+  recordings: Map<string, VoiceRecording>;
   // LiveKit connection info
   livekitToken: string | null;
   livekitUrl: string | null;
@@ -2299,7 +2302,14 @@ interface VoiceStoreState {
   /** Active native MediaEngine instance (non-null when connected via native media). */
   mediaEngine: MediaEngine | null;
 
-  joinChannel: (channelId: string, guildId?: string, internalRetryAttempt?: number) => Promise<void>;
+  /** `grant` reuses connection details the server already issued (e.g. on
+   *  a moderator move) instead of requesting a new join. */
+  joinChannel: (
+    channelId: string,
+    guildId?: string,
+    internalRetryAttempt?: number,
+    grant?: VoiceJoinResponse,
+  ) => Promise<void>;
   leaveChannel: () => Promise<void>;
   toggleMute: () => Promise<void>;
   toggleDeaf: () => Promise<void>;
@@ -2319,8 +2329,14 @@ interface VoiceStoreState {
 
   // Gateway event handlers
   handleVoiceStateUpdate: (state: VoiceState) => void;
+  // Fresh room credentials pushed by the server after a moderator move
+  handleVoiceServerUpdate: (update: VoiceJoinResponse & { channel_id: string; guild_id?: string }) => void;
   // Load initial voice states from READY payload
   loadVoiceStates: (guildId: string, states: VoiceState[]) => void;
+  // Recording started or stopped in a channel
+  handleVoiceRecordingUpdate: (update: VoiceRecording) => void;
+  // Load active recordings from READY payload
+  loadVoiceRecordings: (guildId: string, recordings: VoiceRecording[]) => void;
   // Speaking state from LiveKit
   setSpeakingUsers: (userIds: string[]) => void;
 }
@@ -2340,6 +2356,7 @@ export const useVoiceStore = create<VoiceStoreState>()((set, get) => ({
   participants: new Map(),
   channelParticipants: new Map(),
   speakingUsers: new Set(),
+  recordings: new Map(),
   livekitToken: null,
   livekitUrl: null,
   roomName: null,
@@ -2361,7 +2378,7 @@ export const useVoiceStore = create<VoiceStoreState>()((set, get) => ({
   useNativeMedia: true,
   mediaEngine: null,
 
-  joinChannel: async (channelId, guildId, internalRetryAttempt = 0) => {
+  joinChannel: async (channelId, guildId, internalRetryAttempt = 0, grant) => {
     configureLivekitLogging();
     const joinStartMs = Date.now();
     const elapsed = () => `${Date.now() - joinStartMs}ms`;
@@ -2456,7 +2473,7 @@ export const useVoiceStore = create<VoiceStoreState>()((set, get) => ({
     try {
       voiceTimingLog(`[voice] +${elapsed()} API call starting`);
       logVoiceDiagnostic(`[voice] +${elapsed()} API call starting`);
-      const { data } = await voiceApi.joinChannel(channelId);
+      const { data } = grant ? { data: grant } : await voiceApi.joinChannel(channelId);
       voiceTimingLog(`[voice] +${elapsed()} API call done url=${data?.url} candidates=${JSON.stringify(data?.url_candidates)}`);
       logVoiceDiagnostic(`[voice] +${elapsed()} API call done`, {
         channelId,
@@ -2468,6 +2485,10 @@ export const useVoiceStore = create<VoiceStoreState>()((set, get) => ({
         typeof data?.session_id === 'string' && data.session_id.length > 0
           ? data.session_id
           : null;
+      if (data?.recording?.recording) {
+        get().handleVoiceRecordingUpdate(data.recording);
+        toast.info('This voice channel is being recorded.');
+      }
       // Bail if superseded during the API call — avoids creating a Room
       // and starting a LiveKit connect that will just be torn down.
       if (activeJoinAttempt !== joinAttempt) {
@@ -4015,6 +4036,13 @@ export const useVoiceStore = create<VoiceStoreState>()((set, get) => ({
     });
   },
 
+  handleVoiceServerUpdate: (update) => {
+    const state = get();
+    if (!state.connected && !state.joining) return;
+    if (state.channelId === update.channel_id) return;
+    void get().joinChannel(update.channel_id, update.guild_id, 0, update);
+  },
+
   loadVoiceStates: (guildId, states) =>
     set((prev) => {
       const channelParticipants = new Map(prev.channelParticipants);
@@ -4081,6 +4109,38 @@ export const useVoiceStore = create<VoiceStoreState>()((set, get) => ({
       return { channelParticipants, participants };
     }),
 
+  handleVoiceRecordingUpdate: (update) => {
+    const wasRecording = get().recordings.has(update.channel_id);
+    set((prev) => {
+      const recordings = new Map(prev.recordings);
+      if (update.recording) {
+        recordings.set(update.channel_id, update);
+      } else {
+        recordings.delete(update.channel_id);
+      }
+      return { recordings };
+    });
+    // Everyone in the channel is told when recording starts or stops.
+    const { channelId } = get();
+    if (channelId !== update.channel_id || wasRecording === update.recording) return;
+    if (update.recording) {
+      toast.info('This voice channel is now being recorded.');
+    } else {
+      toast.info('Recording stopped.');
+    }
+  },
+
+  loadVoiceRecordings: (guildId, recordings) =>
+    set((prev) => {
+      const next = new Map(
+        [...prev.recordings].filter(([, recording]) => recording.guild_id !== guildId)
+      );
+      for (const recording of recordings) {
+        if (recording.recording) next.set(recording.channel_id, recording);
+      }
+      return { recordings: next };
+    }),
+
   setSpeakingUsers: (userIds) =>
     set(() => ({
       speakingUsers: new Set(userIds),
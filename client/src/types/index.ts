@@ -25,6 +25,7 @@ export interface UserSettings {
   status: 'online' | 'idle' | 'dnd' | 'invisible';
   custom_status?: string;
   crypto_auth_enabled: boolean;
+  hide_mutual_guilds?: boolean;
   notifications?: Record<string, unknown>;
   keybinds?: Record<string, unknown>;
 }
@@ -55,6 +56,10 @@ export interface Guild {
   created_at: string;
   hub_settings?: HubSettings;
   bot_settings?: any;
+  /** Any visible channel has unread messages (guild list, bootstrap and READY). */
+  unread?: boolean;
+  /** Unread mentions summed over visible channels. */
+  mention_count?: number;
   /** Base URL of the server this guild was fetched from (client-side tag). */
   server_url?: string;
 }
@@ -90,6 +95,7 @@ export interface Channel {
   bitrate?: number;
   user_limit?: number;
   rate_limit_per_user?: number;
+  read_only?: boolean;
   parent_id?: string | null;
   last_message_id?: string;
   required_role_ids?: string[];
@@ -99,13 +105,17 @@ export interface Channel {
   applied_tags?: string[] | null;
   default_sort_order?: number | null;
   created_at: string;
-  recipient?: {
-    id: string;
-    username: string;
-    discriminator: string | number;
-    avatar_hash?: string | null;
-    public_key?: string | null;
-  };
+  recipient?: DmRecipient;
+  /** Group DMs only: every participant, the viewer included. */
+  recipients?: DmRecipient[];
+}
+
+export interface DmRecipient {
+  id: string;
+  username: string;
+  discriminator: string | number;
+  avatar_hash?: string | null;
+  public_key?: string | null;
 }
 
 export enum MessageType {
@@ -146,6 +156,9 @@ export interface Message {
   tts: boolean;
   mention_everyone: boolean;
   pinned: boolean;
+  flags?: number;
+  /** Position in the channel; null for ephemeral messages. */
+  seq?: number | null;
   type: MessageType | number;
   message_type?: number;
   attachments: Attachment[];
@@ -205,7 +218,9 @@ export interface Attachment {
 export interface Reaction {
   emoji: string;
   count: number;
+  count_details?: { normal: number; burst: number };
   me: boolean;
+  me_burst?: boolean;
 }
 
 export interface PollOption {
@@ -238,6 +253,15 @@ export interface Member {
   joined_at: string;
   deaf: boolean;
   mute: boolean;
+  /** Joined a screened guild and has not accepted its rules yet. */
+  pending?: boolean;
+}
+
+export interface MemberScreening {
+  guild_id: string;
+  enabled: boolean;
+  rules: string;
+  updated_at: string | null;
 }
 
 export interface Role {
@@ -298,6 +322,7 @@ export interface VoiceState {
   self_stream: boolean;
   self_video: boolean;
   suppress: boolean;
+  priority_speaker?: boolean;
   username?: string;
   avatar_hash?: string | null;
 }
@@ -323,6 +348,7 @@ export interface Ban {
   user: User;
   reason?: string;
   guild_id: string;
+  expires_at?: string | null;
 }
 
 export interface AuditLogEntry {
@@ -339,6 +365,8 @@ export interface AuditLogEntry {
 export interface ReadState {
   channel_id: string;
   last_message_id: string;
+  latest_message_id?: string | null;
+  unread?: boolean;
   mention_count: number;
 }
 
@@ -346,12 +374,18 @@ export interface ReadState {
 
 export const UserFlags = {
   ADMIN: 1 << 0,
+  GUILD_CREATOR: 1 << 2,
 } as const;
 
 export function isAdmin(flags: number): boolean {
   return (flags & UserFlags.ADMIN) !== 0;
 }
 
+export const MessageFlags = {
+  SUPPRESS_EMBEDS: 1 << 2,
+  SILENT: 1 << 12,
+} as const;
+
 // ============ Permission Flags ============
 
 export const Permissions = {
@@ -428,6 +462,7 @@ export interface LoginRequest {
   identifier?: string;
   username?: string;
   password: string;
+  mfa_code?: string;
 }
 
 export interface LoginResponse {
@@ -436,6 +471,12 @@ export interface LoginResponse {
   refresh_token?: string;
 }
 
+/** Registration response while the server requires email verification. */
+export interface EmailVerificationPendingResponse {
+  user: User;
+  email_verification_required: true;
+}
+
 export interface RegisterRequest {
   email: string;
   username: string;
@@ -443,6 +484,33 @@ export interface RegisterRequest {
   display_name?: string;
 }
 
+/** `begin` response; binary fields inside `public_key` are base64url. */
+export interface WebAuthnCeremony {
+  ceremony_id: string;
+  public_key: Record<string, unknown>;
+}
+
+/** `credential` is the `PublicKeyCredential` from the browser, via `toJSON()`. */
+export interface WebAuthnRegisterFinishRequest {
+  ceremony_id: string;
+  credential: Record<string, unknown>;
+  name?: string;
+}
+
+export interface WebAuthnLoginFinishRequest {
+  ceremony_id: string;
+  credential: Record<string, unknown>;
+  mfa_code?: string;
+}
+
+export interface WebAuthnCredential {
+  id: string;
+  name: string | null;
+  transports: string[];
+  created_at: string;
+  last_used_at: string | null;
+}
+
 export interface CreateGuildRequest {
   name: string;
   icon?: string;
@@ -458,6 +526,8 @@ export interface CreateChannelRequest {
   position?: number;
   bitrate?: number;
   user_limit?: number;
+  rate_limit_per_user?: number;
+  read_only?: boolean;
 }
 
 export interface SendMessageRequest {
@@ -466,6 +536,7 @@ export interface SendMessageRequest {
   attachment_ids?: string[];
   e2ee?: MessageE2eePayload;
   nonce?: string;
+  tts?: boolean;
 }
 
 export interface EditMessageRequest {
@@ -501,5 +572,6 @@ export interface InviteAcceptResponse {
 export interface PaginationParams {
   before?: string;
   after?: string;
+  after_seq?: number;
   limit?: number;
 }
@@ -35,6 +35,8 @@ export default defineConfig(({ mode }) => {
         },
         workbox: {
           navigateFallbackDenylist: [/^\/api\//, /^\/gateway/, /^\/livekit/, /^\/health/],
+          // Shows Web Push notifications for mentions and DMs.
+          importScripts: ["push-sw.js"],
           runtimeCaching: [],
           skipWaiting: true,
           clientsClaim: true,
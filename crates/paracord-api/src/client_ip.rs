@@ -0,0 +1,259 @@
+//! Real client IP resolution behind reverse proxies.
+//!
+//! Forwarded headers (`X-Forwarded-For`, `Forwarded`) are only honored when the
+//! directly-connected peer is a configured trusted proxy. The resolved address
+//! is computed once per request by [`client_ip_middleware`] and stamped onto the
+//! request as [`CLIENT_IP_HEADER`] so rate limiting, security logging and
+//! per-route checks all see the same value.
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use paracord_util::net::IpCidr;
+use std::net::{IpAddr, SocketAddr};
+
+/// Internal header carrying the resolved client IP. Any client-supplied value
+/// is stripped before the middleware sets it.
+pub const CLIENT_IP_HEADER: &str = "x-paracord-client-ip";
+
+/// Trusted reverse-proxy configuration, read from `PARACORD_TRUST_PROXY` and
+/// `PARACORD_TRUSTED_PROXY_IPS` (comma-separated IPs or CIDRs).
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpCidr>,
+}
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<IpCidr>) -> Self {
+        Self { networks }
+    }
+
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("PARACORD_TRUST_PROXY")
+            .ok()
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return Self::default();
+        }
+        let networks = std::env::var("PARACORD_TRUSTED_PROXY_IPS")
+            .ok()
+            .map(|raw| {
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .filter_map(|v| v.parse::<IpCidr>().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { networks }
+    }
+
+    pub fn is_trusted(&self, ip: &IpAddr) -> bool {
+        paracord_util::net::ip_in_any(&self.networks, ip)
+    }
+
+    /// Whether forwarded headers from `peer` may be honored.
+    pub fn peer_is_trusted(&self, peer: Option<IpAddr>) -> bool {
+        peer.is_some_and(|ip| self.is_trusted(&ip))
+    }
+
+    /// Resolve the originating client address.
+    ///
+    /// When the peer is a trusted proxy, the forwarded chain is walked from the
+    /// right (closest hop) and the first address that is not itself a trusted
+    /// proxy wins. Entries left of that are client-controlled and ignored.
+    pub fn resolve(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        if !self.peer_is_trusted(peer) {
+            return peer;
+        }
+        let chain = forwarded_chain(headers);
+        chain
+            .iter()
+            .rev()
+            .find(|ip| !self.is_trusted(ip))
+            .or_else(|| chain.first())
+            .copied()
+            .or(peer)
+    }
+}
+
+/// Addresses from `X-Forwarded-For`, or from `Forwarded: for=` when XFF is
+/// absent, in left-to-right order. Unparseable entries are skipped.
+pub fn forwarded_chain(headers: &HeaderMap) -> Vec<IpAddr> {
+    let xff: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|raw| raw.split(','))
+        .filter_map(parse_forwarded_node)
+        .collect();
+    if !xff.is_empty() {
+        return xff;
+    }
+    headers
+        .get_all("forwarded")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|raw| raw.split(','))
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                if key.trim().eq_ignore_ascii_case("for") {
+                    parse_forwarded_node(value)
+                } else {
+                    None
+                }
+            })
+        })
+        .collect()
+}
+
+/// Parse a forwarded node: `1.2.3.4`, `1.2.3.4:5678`, `"[2001:db8::1]:443"`.
+fn parse_forwarded_node(raw: &str) -> Option<IpAddr> {
+    let node = raw.trim().trim_matches('"');
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(addr) = node.parse::<SocketAddr>() {
+        return Some(addr.ip());
+    }
+    node.strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(ip, _)| ip.parse().ok())
+}
+
+/// Resolve the client IP for a request using the current trusted-proxy config.
+pub fn resolve_client_ip(headers: &HeaderMap, peer_ip: Option<&str>) -> Option<String> {
+    if let Some(resolved) = client_ip_from_headers(headers) {
+        return Some(resolved);
+    }
+    let peer = peer_ip.and_then(|raw| raw.parse::<IpAddr>().ok());
+    TrustedProxies::from_env()
+        .resolve(headers, peer)
+        .map(|ip| ip.to_string())
+        .or_else(|| peer_ip.map(str::to_string))
+}
+
+/// Whether forwarded headers from `peer_ip` may be honored.
+pub fn peer_is_trusted_proxy(peer_ip: Option<&str>) -> bool {
+    let peer = peer_ip.and_then(|raw| raw.parse::<IpAddr>().ok());
+    TrustedProxies::from_env().peer_is_trusted(peer)
+}
+
+/// The client IP stamped by [`client_ip_middleware`], if present.
+pub fn client_ip_from_headers(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(CLIENT_IP_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+pub async fn client_ip_middleware(mut req: Request, next: Next) -> Response {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    req.headers_mut().remove(CLIENT_IP_HEADER);
+    let resolved = TrustedProxies::from_env().resolve(req.headers(), peer);
+    if let Some(value) = resolved.and_then(|ip| HeaderValue::from_str(&ip.to_string()).ok()) {
+        req.headers_mut().insert(CLIENT_IP_HEADER, value);
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxies(raw: &[&str]) -> TrustedProxies {
+        TrustedProxies::new(raw.iter().map(|v| v.parse().unwrap()).collect())
+    }
+
+    fn ip(raw: &str) -> IpAddr {
+        raw.parse().unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn uses_forwarded_address_behind_one_trusted_hop() {
+        let trusted = proxies(&["10.0.0.5"]);
+        let headers = headers(&[("x-forwarded-for", "203.0.113.4")]);
+        assert_eq!(
+            trusted.resolve(&headers, Some(ip("10.0.0.5"))),
+            Some(ip("203.0.113.4"))
+        );
+    }
+
+    #[test]
+    fn ignores_forwarded_headers_from_untrusted_peer() {
+        let trusted = proxies(&["10.0.0.5"]);
+        let headers = headers(&[
+            ("x-forwarded-for", "203.0.113.4"),
+            ("forwarded", "for=203.0.113.4"),
+        ]);
+        assert_eq!(
+            trusted.resolve(&headers, Some(ip("198.51.100.9"))),
+            Some(ip("198.51.100.9"))
+        );
+        assert_eq!(
+            TrustedProxies::default().resolve(&headers, Some(ip("10.0.0.5"))),
+            Some(ip("10.0.0.5"))
+        );
+    }
+
+    #[test]
+    fn skips_client_supplied_entries_left_of_the_trusted_hop() {
+        // Client sent a spoofed XFF; the proxy appended the real address.
+        let trusted = proxies(&["10.0.0.0/8"]);
+        let headers = headers(&[("x-forwarded-for", "1.1.1.1, 203.0.113.4, 10.0.0.7")]);
+        assert_eq!(
+            trusted.resolve(&headers, Some(ip("10.0.0.5"))),
+            Some(ip("203.0.113.4"))
+        );
+    }
+
+    #[test]
+    fn parses_rfc7239_forwarded_header() {
+        let trusted = proxies(&["10.0.0.5"]);
+        let headers = headers(&[(
+            "forwarded",
+            "for=\"[2001:db8::17]:4711\";proto=https, for=10.0.0.5",
+        )]);
+        assert_eq!(
+            trusted.resolve(&headers, Some(ip("10.0.0.5"))),
+            Some(ip("2001:db8::17"))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_peer_when_forwarded_chain_is_unusable() {
+        let trusted = proxies(&["10.0.0.5"]);
+        let headers = headers(&[("x-forwarded-for", "not-an-ip")]);
+        assert_eq!(
+            trusted.resolve(&headers, Some(ip("10.0.0.5"))),
+            Some(ip("10.0.0.5"))
+        );
+    }
+
+    #[test]
+    fn stamped_header_wins_over_recomputation() {
+        let headers = headers(&[(CLIENT_IP_HEADER, "203.0.113.8")]);
+        assert_eq!(
+            resolve_client_ip(&headers, Some("10.0.0.5")).as_deref(),
+            Some("203.0.113.8")
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Double-submit CSRF protection for cookie-authenticated requests.
+//!
+//! Whenever a response sets the session access cookie, [`csrf_middleware`]
+//! also hands out a script-readable [`CSRF_COOKIE_NAME`] cookie. Unsafe
+//! requests that authenticate with the session cookies must echo that value
+//! back in [`CSRF_HEADER_NAME`]. Requests carrying an `Authorization` header
+//! (bearer or bot tokens) cannot be forged cross-site and are never checked.
+//!
+//! Enabled by default; set `PARACORD_CSRF_PROTECTION=false` to turn it off.
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use rand::RngCore;
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+
+pub const CSRF_COOKIE_NAME: &str = "paracord_csrf";
+pub const CSRF_HEADER_NAME: &str = "x-csrf-token";
+
+/// Cookie that authenticates a browser session on its own. The refresh cookie
+/// is scoped to the auth endpoints and can only rotate the caller's own
+/// session, so it does not count.
+const ACCESS_COOKIE_NAME: &str = "paracord_access";
+const ACCESS_COOKIE_PREFIX: &str = "paracord_access=";
+
+/// Refresh authenticates with the refresh cookie and only rotates the
+/// caller's own session. It is also how a client whose CSRF cookie has
+/// lapsed gets a new one, so it is never checked.
+const REFRESH_PATH: &str = "/api/v1/auth/refresh";
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+fn csrf_protection_enabled() -> bool {
+    *ENABLED.get_or_init(|| {
+        std::env::var("PARACORD_CSRF_PROTECTION")
+            .ok()
+            .map(|v| {
+                !matches!(
+                    v.trim().to_ascii_lowercase().as_str(),
+                    "0" | "false" | "no" | "off"
+                )
+            })
+            .unwrap_or(true)
+    })
+}
+
+fn cookie_value<'a>(headers: &'a HeaderMap, cookie_name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|raw| raw.split(';'))
+        .filter_map(|part| part.trim().split_once('='))
+        .find(|(name, _)| *name == cookie_name)
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+/// True when the request would be authenticated by session cookies alone.
+fn is_cookie_authenticated(headers: &HeaderMap) -> bool {
+    !headers.contains_key(header::AUTHORIZATION)
+        && cookie_value(headers, ACCESS_COOKIE_NAME).is_some()
+}
+
+fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn has_valid_csrf_token(headers: &HeaderMap) -> bool {
+    let Some(expected) = cookie_value(headers, CSRF_COOKIE_NAME) else {
+        return false;
+    };
+    headers
+        .get(CSRF_HEADER_NAME)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .is_some_and(|presented| constant_time_eq(presented.as_bytes(), expected.as_bytes()))
+}
+
+fn generate_csrf_token() -> String {
+    let mut buf = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut buf);
+    buf.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The access cookie `Set-Cookie` on this response, whether it starts a
+/// session or clears one on logout.
+fn issued_access_cookie(response: &Response) -> Option<&str> {
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|v| v.starts_with(ACCESS_COOKIE_PREFIX))
+}
+
+fn build_csrf_cookie(token: &str, max_age: &str, secure: bool) -> String {
+    let secure_attr = if secure { "; Secure" } else { "" };
+    // Not HttpOnly: the web client has to read it to echo it back.
+    format!("{CSRF_COOKIE_NAME}={token}; Path=/; SameSite=Lax; Max-Age={max_age}{secure_attr}")
+}
+
+pub async fn csrf_middleware(req: Request, next: Next) -> Response {
+    if !csrf_protection_enabled() {
+        return next.run(req).await;
+    }
+
+    if !is_safe_method(req.method())
+        && req.uri().path() != REFRESH_PATH
+        && is_cookie_authenticated(req.headers())
+        && !has_valid_csrf_token(req.headers())
+    {
+        tracing::warn!(
+            method = %req.method(),
+            path = %req.uri().path(),
+            "rejected cookie-authenticated request without a valid CSRF token"
+        );
+        return ApiError::Forbidden.into_response();
+    }
+
+    // Keep an existing token across session refreshes so in-flight requests
+    // from other tabs stay valid.
+    let existing_token = cookie_value(req.headers(), CSRF_COOKIE_NAME).map(str::to_string);
+    let mut response = next.run(req).await;
+
+    let Some(access_cookie) = issued_access_cookie(&response) else {
+        return response;
+    };
+    let secure = access_cookie.contains("; Secure");
+    let csrf_cookie = if access_cookie.starts_with(&format!("{ACCESS_COOKIE_PREFIX};")) {
+        build_csrf_cookie("", "0", secure)
+    } else {
+        let token = existing_token.unwrap_or_else(generate_csrf_token);
+        let max_age = access_cookie
+            .split(';')
+            .filter_map(|attr| attr.trim().strip_prefix("Max-Age="))
+            .next()
+            .unwrap_or("86400")
+            .to_string();
+        build_csrf_cookie(&token, &max_age, secure)
+    };
+    if let Ok(value) = HeaderValue::from_str(&csrf_cookie) {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(
+                header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        map
+    }
+
+    #[test]
+    fn bearer_requests_are_not_cookie_authenticated() {
+        let h = headers(&[
+            ("authorization", "Bearer abc"),
+            ("cookie", "paracord_access=tok"),
+        ]);
+        assert!(!is_cookie_authenticated(&h));
+    }
+
+    #[test]
+    fn session_cookie_without_authorization_is_cookie_authenticated() {
+        assert!(is_cookie_authenticated(&headers(&[(
+            "cookie",
+            "theme=dark; paracord_access=tok"
+        )])));
+        assert!(!is_cookie_authenticated(&headers(&[(
+            "cookie",
+            "theme=dark"
+        )])));
+    }
+
+    #[test]
+    fn csrf_header_must_match_cookie() {
+        let ok = headers(&[
+            ("cookie", "paracord_access=tok; paracord_csrf=abc123"),
+            ("x-csrf-token", "abc123"),
+        ]);
+        assert!(has_valid_csrf_token(&ok));
+
+        let mismatch = headers(&[
+            ("cookie", "paracord_access=tok; paracord_csrf=abc123"),
+            ("x-csrf-token", "abc124"),
+        ]);
+        assert!(!has_valid_csrf_token(&mismatch));
+
+        let no_cookie = headers(&[("x-csrf-token", "abc123")]);
+        assert!(!has_valid_csrf_token(&no_cookie));
+    }
+}
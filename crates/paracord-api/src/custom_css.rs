@@ -0,0 +1,430 @@
+//! Sanitizer for user-supplied custom CSS themes.
+//!
+//! The stylesheet is parsed into rules and declarations and re-serialized
+//! from what survives: comments are dropped, only `@media`, `@supports` and
+//! `@keyframes` at-rules are kept, and declarations must use an allow-listed
+//! property (or a `--custom-property`) with a value that cannot load remote
+//! resources or run script. Anything else is removed rather than failing the
+//! whole theme.
+//!
+//! `url(...)` is only kept for inline raster images (`data:image/png` etc.)
+//! and only when `PARACORD_CUSTOM_CSS_DATA_IMAGES=true` (exported from
+//! `server.custom_css_data_images`).
+
+use std::sync::OnceLock;
+
+/// Exact property names a theme may set.
+const ALLOWED_PROPERTIES: &[&str] = &[
+    "accent-color",
+    "backdrop-filter",
+    "box-shadow",
+    "box-sizing",
+    "caret-color",
+    "color",
+    "cursor",
+    "display",
+    "filter",
+    "gap",
+    "height",
+    "letter-spacing",
+    "line-height",
+    "max-height",
+    "max-width",
+    "min-height",
+    "min-width",
+    "opacity",
+    "overflow",
+    "overflow-x",
+    "overflow-y",
+    "row-gap",
+    "scrollbar-color",
+    "scrollbar-width",
+    "transform",
+    "visibility",
+    "white-space",
+    "width",
+    "word-spacing",
+];
+
+/// Property families allowed by prefix (`border-top-left-radius`, ...).
+const ALLOWED_PROPERTY_PREFIXES: &[&str] = &[
+    "align-",
+    "animation",
+    "background",
+    "border",
+    "column-",
+    "flex",
+    "font",
+    "grid",
+    "justify-",
+    "margin",
+    "outline",
+    "padding",
+    "text-",
+    "transition",
+];
+
+/// At-rules whose blocks hold further rules and are sanitized recursively.
+const NESTING_AT_RULES: &[&str] = &["media", "supports", "keyframes", "-webkit-keyframes"];
+
+/// Value fragments that can run script or pull in remote content.
+const FORBIDDEN_VALUE_FRAGMENTS: &[&str] = &[
+    "javascript:",
+    "vbscript:",
+    "expression(",
+    "behavior",
+    "-moz-binding",
+    "image-set(",
+    "image(",
+    "cross-fade(",
+    "element(",
+    "src(",
+];
+
+const DATA_IMAGE_PREFIXES: &[&str] = &[
+    "data:image/png;base64,",
+    "data:image/gif;base64,",
+    "data:image/jpeg;base64,",
+    "data:image/webp;base64,",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CustomCssPolicy {
+    /// Keep `url(data:image/...;base64,...)` values.
+    pub allow_data_images: bool,
+}
+
+impl CustomCssPolicy {
+    /// Parsed like the `PARACORD_CUSTOM_CSS_DATA_IMAGES` override in the
+    /// server config, so both accept the same values.
+    pub fn from_env() -> Self {
+        let allow_data_images = std::env::var("PARACORD_CUSTOM_CSS_DATA_IMAGES")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+        Self { allow_data_images }
+    }
+}
+
+static POLICY: OnceLock<CustomCssPolicy> = OnceLock::new();
+
+pub fn custom_css_policy() -> CustomCssPolicy {
+    *POLICY.get_or_init(CustomCssPolicy::from_env)
+}
+
+/// Remove `/* ... */` comments outside of strings.
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+                // Comments separate tokens, so keep the boundary.
+                out.push(' ');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Index of the first of `stops` at nesting depth zero, skipping strings and
+/// parenthesized groups.
+fn find_top_level(input: &str, stops: &[char]) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in input.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && stops.contains(&c) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// For a block whose `{` ends right before `input`, the index of its closing
+/// `}` and the index just past it. An unclosed block runs to the end.
+fn find_block_end(input: &str) -> (usize, usize) {
+    let mut depth = 1usize;
+    let mut rest = 0;
+    while let Some(offset) = find_top_level(&input[rest..], &['{', '}']) {
+        let at = rest + offset;
+        if input[at..].starts_with('{') {
+            depth += 1;
+        } else {
+            depth -= 1;
+            if depth == 0 {
+                return (at, at + 1);
+            }
+        }
+        rest = at + 1;
+    }
+    (input.len(), input.len())
+}
+
+fn is_allowed_property(name: &str) -> bool {
+    if let Some(custom) = name.strip_prefix("--") {
+        return !custom.is_empty()
+            && custom
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    }
+    ALLOWED_PROPERTIES.contains(&name)
+        || ALLOWED_PROPERTY_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+fn is_safe_data_image(url: &str) -> bool {
+    let url = url.trim().trim_matches(|c| c == '"' || c == '\'');
+    let lower = url.to_ascii_lowercase();
+    DATA_IMAGE_PREFIXES.iter().any(|prefix| {
+        lower.strip_prefix(prefix).is_some_and(|payload| {
+            !payload.is_empty()
+                && payload
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '='))
+        })
+    })
+}
+
+fn is_safe_value(value: &str, policy: CustomCssPolicy) -> bool {
+    if value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == '\\' || c == '<' || c == '@' || c.is_control())
+    {
+        return false;
+    }
+    let compact: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if FORBIDDEN_VALUE_FRAGMENTS
+        .iter()
+        .any(|fragment| compact.contains(fragment))
+    {
+        return false;
+    }
+    let mut rest = compact.as_str();
+    while let Some(start) = rest.find("url(") {
+        let after = &rest[start + 4..];
+        let Some(end) = after.find(')') else {
+            return false;
+        };
+        if !policy.allow_data_images || !is_safe_data_image(&after[..end]) {
+            return false;
+        }
+        rest = &after[end + 1..];
+    }
+    true
+}
+
+fn sanitize_declarations(block: &str, policy: CustomCssPolicy) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = block;
+    while !rest.trim().is_empty() {
+        let end = find_top_level(rest, &[';']).unwrap_or(rest.len());
+        let declaration = &rest[..end];
+        rest = rest.get(end + 1..).unwrap_or("");
+
+        let Some((name, value)) = declaration.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        let name = if name.starts_with("--") {
+            name.to_string()
+        } else {
+            name.to_ascii_lowercase()
+        };
+        let value = value.trim();
+        if is_allowed_property(&name) && is_safe_value(value, policy) {
+            out.push(format!("{name}: {value};"));
+        }
+    }
+    out
+}
+
+fn is_safe_prelude(prelude: &str) -> bool {
+    !prelude.is_empty()
+        && !prelude
+            .chars()
+            .any(|c| matches!(c, '\\' | '<' | '@' | ';' | '{' | '}') || c.is_control())
+        && !prelude.to_ascii_lowercase().contains("url(")
+}
+
+fn sanitize_rules(css: &str, policy: CustomCssPolicy, indent: usize, out: &mut String) {
+    let pad = "  ".repeat(indent);
+    let mut rest = css;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '}');
+        if rest.is_empty() {
+            break;
+        }
+        let Some(stop) = find_top_level(rest, &['{', ';']) else {
+            break;
+        };
+        let prelude = rest[..stop].trim();
+        if rest[stop..].starts_with(';') {
+            // Statement at-rules (`@import`, `@charset`, ...) and stray
+            // declarations are dropped.
+            rest = &rest[stop + 1..];
+            continue;
+        }
+        let body_start = stop + 1;
+        let (body_end, next) = find_block_end(&rest[body_start..]);
+        let body = &rest[body_start..body_start + body_end];
+        rest = &rest[body_start + next..];
+
+        if let Some(at_rule) = prelude.strip_prefix('@') {
+            let (name, condition) = at_rule
+                .split_once(char::is_whitespace)
+                .unwrap_or((at_rule, ""));
+            let name = name.to_ascii_lowercase();
+            let condition = condition.trim();
+            if !NESTING_AT_RULES.contains(&name.as_str()) || !is_safe_prelude(condition) {
+                continue;
+            }
+            let mut inner = String::new();
+            sanitize_rules(body, policy, indent + 1, &mut inner);
+            if !inner.is_empty() {
+                out.push_str(&format!("{pad}@{name} {condition} {{\n{inner}{pad}}}\n"));
+            }
+        } else if is_safe_prelude(prelude) {
+            let declarations = sanitize_declarations(body, policy);
+            if !declarations.is_empty() {
+                out.push_str(&format!("{pad}{prelude} {{\n"));
+                for declaration in declarations {
+                    out.push_str(&format!("{pad}  {declaration}\n"));
+                }
+                out.push_str(&format!("{pad}}}\n"));
+            }
+        }
+    }
+}
+
+/// Sanitize a custom stylesheet, returning `None` when nothing survives.
+pub fn sanitize_custom_css(css: &str, policy: CustomCssPolicy) -> Option<String> {
+    let mut out = String::new();
+    sanitize_rules(&strip_comments(css), policy, 0, &mut out);
+    let out = out.trim_end();
+    (!out.is_empty()).then(|| out.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: CustomCssPolicy = CustomCssPolicy {
+        allow_data_images: false,
+    };
+    const WITH_DATA_IMAGES: CustomCssPolicy = CustomCssPolicy {
+        allow_data_images: true,
+    };
+
+    #[test]
+    fn safe_theme_passes_through() {
+        let theme = r#"
+            :root { --bg-primary: #1e1f22; --accent: rgb(88, 101, 242); }
+            .message:hover { background-color: var(--bg-primary) !important; border-radius: 4px; }
+            @media (max-width: 600px) { .sidebar { display: none; } }
+        "#;
+        let sanitized = sanitize_custom_css(theme, DEFAULT).unwrap();
+        assert!(sanitized.contains("--bg-primary: #1e1f22;"));
+        assert!(sanitized.contains("--accent: rgb(88, 101, 242);"));
+        assert!(sanitized.contains("background-color: var(--bg-primary) !important;"));
+        assert!(sanitized.contains("border-radius: 4px;"));
+        assert!(sanitized.contains("@media (max-width: 600px) {"));
+        assert!(sanitized.contains("display: none;"));
+    }
+
+    #[test]
+    fn script_bearing_values_are_removed() {
+        let sanitized = sanitize_custom_css(
+            ".a { color: red; background: url(javascript:alert(1)); }\n\
+             .b { width: expression(alert(1)); -moz-binding: url(x.xml); }",
+            DEFAULT,
+        )
+        .unwrap();
+        assert_eq!(sanitized, ".a {\n  color: red;\n}");
+        assert!(!sanitized.contains("javascript"));
+        assert_eq!(
+            sanitize_custom_css(".a { color: j\\61vascript; }", DEFAULT),
+            None
+        );
+    }
+
+    #[test]
+    fn import_inside_comment_is_not_a_rejection() {
+        let sanitized = sanitize_custom_css(
+            "/* @import url(https://evil.example/x.css); */ .a { color: blue; }",
+            DEFAULT,
+        )
+        .unwrap();
+        assert_eq!(sanitized, ".a {\n  color: blue;\n}");
+    }
+
+    #[test]
+    fn disallowed_at_rules_and_properties_are_dropped() {
+        let sanitized = sanitize_custom_css(
+            "@import url(https://evil.example/x.css);\n\
+             @font-face { font-family: x; src: url(https://evil.example/f.woff); }\n\
+             .a { position: fixed; color: green; }",
+            DEFAULT,
+        )
+        .unwrap();
+        assert_eq!(sanitized, ".a {\n  color: green;\n}");
+    }
+
+    #[test]
+    fn data_images_require_policy() {
+        let css = ".a { background-image: url(\"data:image/png;base64,iVBORw0KGgo=\"); }";
+        assert_eq!(sanitize_custom_css(css, DEFAULT), None);
+        let allowed = sanitize_custom_css(css, WITH_DATA_IMAGES).unwrap();
+        assert!(allowed.contains("data:image/png;base64,iVBORw0KGgo="));
+
+        let svg = ".a { background: url(data:image/svg+xml;base64,PHN2Zz4=); }";
+        assert_eq!(sanitize_custom_css(svg, WITH_DATA_IMAGES), None);
+        let remote = ".a { background: url(https://tracker.example/p.png); }";
+        assert_eq!(sanitize_custom_css(remote, WITH_DATA_IMAGES), None);
+    }
+}
@@ -0,0 +1,75 @@
+//! Channels every new guild starts with.
+//!
+//! Operators set the layout with `server.default_guild_channels`, exported
+//! as JSON in `PARACORD_DEFAULT_GUILD_CHANNELS`. Guilds created from a
+//! template take the template's channels instead.
+
+use std::sync::OnceLock;
+
+use paracord_core::guild::{builtin_default_channels, validate_default_channels, DefaultChannel};
+
+pub fn parse(raw: &str) -> Result<Vec<DefaultChannel>, String> {
+    let channels: Vec<DefaultChannel> =
+        serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {e}"))?;
+    validate_default_channels(&channels)?;
+    Ok(channels)
+}
+
+pub fn from_env() -> Vec<DefaultChannel> {
+    let Ok(raw) = std::env::var("PARACORD_DEFAULT_GUILD_CHANNELS") else {
+        return builtin_default_channels();
+    };
+    parse(&raw).unwrap_or_else(|err| {
+        tracing::warn!(
+            "Ignoring PARACORD_DEFAULT_GUILD_CHANNELS ({}); using the built-in channels",
+            err
+        );
+        builtin_default_channels()
+    })
+}
+
+static CHANNELS: OnceLock<Vec<DefaultChannel>> = OnceLock::new();
+
+pub fn default_guild_channels() -> &'static [DefaultChannel] {
+    CHANNELS.get_or_init(from_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paracord_core::guild::DefaultChannelKind;
+
+    #[test]
+    fn parses_categories_and_nested_channels() {
+        let channels = parse(
+            r#"[
+                {"name": "Info", "type": "category"},
+                {"name": "rules", "type": "text", "category": "Info"},
+                {"name": "Lounge", "type": "voice"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(channels.len(), 3);
+        assert_eq!(channels[0].kind, DefaultChannelKind::Category);
+        assert_eq!(channels[1].category.as_deref(), Some("Info"));
+        assert_eq!(channels[2].kind, DefaultChannelKind::Voice);
+    }
+
+    #[test]
+    fn rejects_category_references_out_of_order() {
+        assert!(parse(
+            r#"[
+                {"name": "rules", "type": "text", "category": "Info"},
+                {"name": "Info", "type": "category"}
+            ]"#,
+        )
+        .is_err());
+        assert!(parse(r#"[{"name": "", "type": "text"}]"#).is_err());
+        assert!(parse(r#"[{"name": "x", "type": "stage"}]"#).is_err());
+    }
+
+    #[test]
+    fn empty_set_is_allowed() {
+        assert!(parse("[]").unwrap().is_empty());
+    }
+}
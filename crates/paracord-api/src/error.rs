@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -12,14 +12,29 @@ pub enum ApiError {
     NotFound,
     #[error("unauthorized")]
     Unauthorized,
+    /// Password was accepted but the account needs a second-factor code.
+    #[error("mfa_required")]
+    MfaRequired,
+    /// Password was accepted but the account has not verified its email.
+    #[error("email_unverified")]
+    EmailUnverified,
     #[error("forbidden")]
     Forbidden,
     #[error("bad request: {0}")]
     BadRequest(String),
     #[error("conflict: {0}")]
     Conflict(String),
+    #[error("payload too large: {0}")]
+    PayloadTooLarge(String),
     #[error("rate limited")]
     RateLimited,
+    /// Too many failed logins; the client may retry after `retry_after` seconds.
+    #[error("too many failed login attempts")]
+    LoginLocked { retry_after: i64 },
+    /// The channel's slowmode applies; the member may post again after
+    /// `retry_after` seconds.
+    #[error("slowmode is active in this channel")]
+    Slowmode { retry_after: i64 },
     #[error("service unavailable: {0}")]
     ServiceUnavailable(String),
     #[error("internal server error")]
@@ -32,10 +47,15 @@ impl ApiError {
         match self {
             ApiError::NotFound => "NOT_FOUND",
             ApiError::Unauthorized => "UNAUTHORIZED",
+            ApiError::MfaRequired => "MFA_REQUIRED",
+            ApiError::EmailUnverified => "EMAIL_UNVERIFIED",
             ApiError::Forbidden => "FORBIDDEN",
             ApiError::BadRequest(_) => "BAD_REQUEST",
             ApiError::Conflict(_) => "CONFLICT",
-            ApiError::RateLimited => "RATE_LIMITED",
+            ApiError::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            ApiError::RateLimited | ApiError::LoginLocked { .. } | ApiError::Slowmode { .. } => {
+                "RATE_LIMITED"
+            }
             ApiError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
             ApiError::Internal(_) => "INTERNAL_ERROR",
         }
@@ -44,11 +64,14 @@ impl ApiError {
     fn status_code(&self) -> StatusCode {
         match self {
             ApiError::NotFound => StatusCode::NOT_FOUND,
-            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
-            ApiError::Forbidden => StatusCode::FORBIDDEN,
+            ApiError::Unauthorized | ApiError::MfaRequired => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden | ApiError::EmailUnverified => StatusCode::FORBIDDEN,
             ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
             ApiError::Conflict(_) => StatusCode::CONFLICT,
-            ApiError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::RateLimited | ApiError::LoginLocked { .. } | ApiError::Slowmode { .. } => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
             ApiError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
             ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -68,7 +91,14 @@ impl IntoResponse for ApiError {
             other => other.to_string(),
         };
 
-        let body = json!({
+        let retry_after = match &self {
+            ApiError::LoginLocked { retry_after } | ApiError::Slowmode { retry_after } => {
+                Some(*retry_after)
+            }
+            _ => None,
+        };
+
+        let mut body = json!({
             "code": code,
             "message": message,
             // Keep legacy "error" field for backwards compatibility
@@ -76,6 +106,16 @@ impl IntoResponse for ApiError {
             "details": Value::Null,
         });
 
+        if let Some(retry_after) = retry_after {
+            body["retry_after"] = json!(retry_after);
+            return (
+                status,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(body),
+            )
+                .into_response();
+        }
+
         (status, Json(body)).into_response()
     }
 }
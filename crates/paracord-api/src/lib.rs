@@ -4,30 +4,50 @@ use axum::{
     middleware::{from_fn, Next},
     response::IntoResponse,
     response::Response,
-    routing::{any, delete, get, patch, post, put},
+    routing::{any, delete, get, head, patch, post, put},
     Json, Router,
 };
-use dashmap::DashMap;
-use paracord_core::{observability, AppState};
+use paracord_core::{observability, rate_limit::RateLimitStore, AppState};
 use serde_json::json;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 use tokio::sync::Notify;
 
+pub mod client_ip;
+pub mod csrf;
+pub mod custom_css;
+pub mod default_guild_channels;
 pub mod error;
+pub mod link_unfurl;
+pub mod locales;
+pub mod mail;
+pub mod markup;
 pub mod middleware;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod outbound_url;
+pub mod push;
+pub mod reaction_emoji;
+pub mod request_timeout;
+pub mod response_compression;
 pub mod routes;
+pub mod security_headers;
 
 const DEFAULT_REQUEST_BODY_LIMIT_BYTES: usize = 2 * 1024 * 1024;
 const ATTACHMENT_REQUEST_BODY_LIMIT_BYTES: usize = 64 * 1024 * 1024;
 
 pub fn build_router() -> Router<AppState> {
     let cors = build_cors_layer();
-    Router::new()
+    let router = Router::new();
+    #[cfg(feature = "openapi")]
+    let router = router.route("/api/openapi.json", get(openapi::openapi_json));
+    router
         // Health
         .route("/health", get(health))
         .route("/api/v1/health", get(health))
+        .route("/healthz", get(health))
+        .route("/readyz", get(routes::health::readiness))
         .route("/metrics", get(metrics))
         .route("/api/v1/metrics", get(metrics))
         // Realtime v2 (SSE + HTTP command bus)
@@ -96,10 +116,46 @@ pub fn build_router() -> Router<AppState> {
         .route("/api/v1/auth/register", post(routes::auth::register))
         .route("/api/v1/auth/login", post(routes::auth::login))
         .route("/api/v1/auth/options", get(routes::auth::auth_options))
+        .route(
+            "/api/v1/auth/verify-email",
+            post(routes::auth::verify_email),
+        )
+        .route(
+            "/api/v1/auth/verify-email/resend",
+            post(routes::auth::resend_email_verification),
+        )
+        .route(
+            "/api/v1/auth/forgot-password",
+            post(routes::auth::forgot_password),
+        )
+        .route(
+            "/api/v1/auth/reset-password",
+            post(routes::auth::reset_password),
+        )
+        .route(
+            "/api/v1/capabilities",
+            get(routes::capabilities::get_capabilities),
+        )
         .route("/api/v1/auth/refresh", post(routes::auth::refresh))
         .route("/api/v1/auth/logout", post(routes::auth::logout))
         .route("/api/v1/auth/challenge", post(routes::auth::challenge))
         .route("/api/v1/auth/verify", post(routes::auth::verify))
+        .route(
+            "/api/v1/auth/webauthn/register/begin",
+            post(routes::webauthn::register_begin),
+        )
+        .route(
+            "/api/v1/auth/webauthn/register/finish",
+            post(routes::webauthn::register_finish),
+        )
+        .route(
+            "/api/v1/auth/webauthn/login/begin",
+            post(routes::webauthn::login_begin),
+        )
+        .route(
+            "/api/v1/auth/webauthn/login/finish",
+            post(routes::webauthn::login_finish),
+        )
         .route(
             "/api/v1/auth/attach-public-key",
             post(routes::auth::attach_public_key),
@@ -137,21 +193,79 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/users/@me/import",
             post(routes::users::import_identity),
         )
+        .route(
+            "/api/v1/users/@me/tokens",
+            get(routes::access_tokens::list_tokens).post(routes::access_tokens::create_token),
+        )
+        .route(
+            "/api/v1/users/@me/tokens/{token_id}",
+            delete(routes::access_tokens::revoke_token),
+        )
+        .route(
+            "/api/v1/users/@me/push-subscriptions",
+            post(routes::push_subscriptions::register_subscription)
+                .delete(routes::push_subscriptions::delete_subscription),
+        )
+        .route(
+            "/api/v1/users/@me/push-subscriptions/vapid-key",
+            get(routes::push_subscriptions::get_vapid_key),
+        )
+        .route(
+            "/api/v1/users/@me/mfa/totp",
+            get(routes::mfa::get_totp_status).post(routes::mfa::begin_totp_enrollment),
+        )
+        .route(
+            "/api/v1/users/@me/mfa/totp/confirm",
+            post(routes::mfa::confirm_totp_enrollment),
+        )
+        .route(
+            "/api/v1/users/@me/mfa/totp/disable",
+            post(routes::mfa::disable_totp),
+        )
         .route(
             "/api/v1/users/{user_id}/profile",
             get(routes::users::get_user_profile),
         )
         .route("/api/v1/users/@me/guilds", get(routes::guilds::list_guilds))
+        .route(
+            "/api/v1/users/@me/bootstrap",
+            get(routes::users::get_bootstrap),
+        )
         .route(
             "/api/v1/users/@me/dms",
             get(routes::dms::list_dms).post(routes::dms::create_dm),
         )
+        .route(
+            "/api/v1/users/@me/group-dms",
+            post(routes::dms::create_group_dm),
+        )
+        .route(
+            "/api/v1/channels/{channel_id}/recipients/{user_id}",
+            put(routes::dms::add_group_dm_recipient).delete(routes::dms::remove_group_dm_recipient),
+        )
         .route(
             "/api/v1/users/@me/read-states",
             get(routes::users::get_read_states),
         )
         // Guilds
         .route("/api/v1/guilds", post(routes::guilds::create_guild))
+        .route(
+            "/api/v1/guilds/templates",
+            post(routes::guild_templates::create_template),
+        )
+        .route(
+            "/api/v1/guilds/templates/{code}",
+            get(routes::guild_templates::get_template)
+                .delete(routes::guild_templates::delete_template),
+        )
+        .route(
+            "/api/v1/guilds/from-template/{code}",
+            post(routes::guild_templates::create_guild_from_template),
+        )
+        .route(
+            "/api/v1/guilds/{guild_id}/templates",
+            get(routes::guild_templates::list_guild_templates),
+        )
         .route(
             "/api/v1/guilds/{guild_id}",
             get(routes::guilds::get_guild)
@@ -168,6 +282,10 @@ pub fn build_router() -> Router<AppState> {
                 .post(routes::channels::create_channel)
                 .patch(routes::guilds::update_channel_positions),
         )
+        .route(
+            "/api/v1/guilds/{guild_id}/messages/search",
+            get(routes::channels::search_guild_messages),
+        )
         .route(
             "/api/v1/guilds/{guild_id}/members",
             get(routes::members::list_members),
@@ -180,6 +298,15 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/guilds/{guild_id}/members/@me",
             delete(routes::members::leave_guild),
         )
+        .route(
+            "/api/v1/guilds/{guild_id}/members/@me/screening",
+            post(routes::members::complete_member_screening),
+        )
+        .route(
+            "/api/v1/guilds/{guild_id}/member-screening",
+            get(routes::guilds::get_member_screening)
+                .patch(routes::guilds::update_member_screening),
+        )
         .route(
             "/api/v1/guilds/{guild_id}/bans",
             get(routes::bans::list_bans),
@@ -196,6 +323,10 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/guilds/{guild_id}/roles/{role_id}",
             patch(routes::roles::update_role).delete(routes::roles::delete_role),
         )
+        .route(
+            "/api/v1/guilds/{guild_id}/roles/{role_id}/icon",
+            get(routes::roles::get_role_icon).post(routes::roles::upload_role_icon),
+        )
         .route(
             "/api/v1/guilds/{guild_id}/invites",
             get(routes::invites::list_guild_invites),
@@ -216,6 +347,15 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/guilds/{guild_id}/webhooks",
             get(routes::webhooks::list_guild_webhooks).post(routes::webhooks::create_webhook),
         )
+        .route(
+            "/api/v1/guilds/{guild_id}/integrations/webhooks",
+            get(routes::integrations::list_integration_webhooks)
+                .post(routes::integrations::create_integration_webhook),
+        )
+        .route(
+            "/api/v1/guilds/{guild_id}/integrations/webhooks/{subscription_id}",
+            delete(routes::integrations::delete_integration_webhook),
+        )
         .route(
             "/api/v1/guilds/{guild_id}/events",
             get(routes::events::list_events).post(routes::events::create_event),
@@ -230,6 +370,10 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/guilds/{guild_id}/events/{event_id}/rsvp",
             put(routes::events::add_rsvp).delete(routes::events::remove_rsvp),
         )
+        .route(
+            "/api/v1/guilds/{guild_id}/commands",
+            get(routes::commands::list_guild_available_commands_handler),
+        )
         .route(
             "/api/v1/guilds/{guild_id}/bots",
             get(routes::bots::list_guild_bots),
@@ -271,7 +415,13 @@ pub fn build_router() -> Router<AppState> {
         )
         .route(
             "/api/v1/channels/{channel_id}/messages/{message_id}",
-            patch(routes::channels::edit_message).delete(routes::channels::delete_message),
+            get(routes::channels::get_message)
+                .patch(routes::channels::edit_message)
+                .delete(routes::channels::delete_message),
+        )
+        .route(
+            "/api/v1/permalink/resolve",
+            get(routes::channels::resolve_permalink),
         )
         .route(
             "/api/v1/channels/{channel_id}/polls",
@@ -310,6 +460,14 @@ pub fn build_router() -> Router<AppState> {
             put(routes::channels::upsert_channel_overwrite)
                 .delete(routes::channels::delete_channel_overwrite),
         )
+        .route(
+            "/api/v1/channels/{channel_id}/messages/{message_id}/reactions/bulk",
+            post(routes::channels::bulk_reactions),
+        )
+        .route(
+            "/api/v1/channels/{channel_id}/messages/{message_id}/reactions/{emoji}",
+            get(routes::channels::list_reaction_users),
+        )
         .route(
             "/api/v1/channels/{channel_id}/messages/{message_id}/reactions/{emoji}/@me",
             put(routes::channels::add_reaction).delete(routes::channels::remove_reaction),
@@ -318,11 +476,27 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/channels/{channel_id}/webhooks",
             get(routes::webhooks::list_channel_webhooks),
         )
+        .route(
+            "/api/v1/channels/{channel_id}/followers",
+            post(routes::channels::follow_channel),
+        )
+        .route(
+            "/api/v1/channels/{channel_id}/followers/{follower_id}",
+            delete(routes::channels::unfollow_channel),
+        )
+        .route(
+            "/api/v1/channels/{channel_id}/messages/{message_id}/crosspost",
+            post(routes::channels::crosspost_message),
+        )
         // Threads
         .route(
             "/api/v1/channels/{channel_id}/threads",
             post(routes::channels::create_thread).get(routes::channels::get_threads),
         )
+        .route(
+            "/api/v1/channels/{channel_id}/messages/{message_id}/threads",
+            post(routes::channels::start_thread_from_message),
+        )
         .route(
             "/api/v1/channels/{channel_id}/threads/archived",
             get(routes::channels::get_archived_threads),
@@ -368,6 +542,10 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/webhooks/{webhook_id}/{token}",
             post(routes::webhooks::execute_webhook),
         )
+        .route(
+            "/api/v1/webhooks/{webhook_id}/token/regenerate",
+            post(routes::webhooks::regenerate_webhook_token),
+        )
         .route(
             "/api/v1/discovery/guilds",
             get(routes::discovery::list_discoverable_guilds),
@@ -398,6 +576,50 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/oauth2/authorize",
             post(routes::bots::oauth2_authorize),
         )
+        // Application commands
+        .route(
+            "/api/v1/applications/{app_id}/commands",
+            get(routes::commands::list_global_commands)
+                .post(routes::commands::create_global_command)
+                .put(routes::commands::bulk_overwrite_global_commands),
+        )
+        .route(
+            "/api/v1/applications/{app_id}/commands/{cmd_id}",
+            get(routes::commands::get_global_command)
+                .patch(routes::commands::update_global_command)
+                .delete(routes::commands::delete_global_command),
+        )
+        .route(
+            "/api/v1/applications/{app_id}/guilds/{guild_id}/commands",
+            get(routes::commands::list_guild_commands)
+                .post(routes::commands::create_guild_command)
+                .put(routes::commands::bulk_overwrite_guild_commands),
+        )
+        .route(
+            "/api/v1/applications/{app_id}/guilds/{guild_id}/commands/{cmd_id}",
+            get(routes::commands::get_guild_command)
+                .patch(routes::commands::update_guild_command)
+                .delete(routes::commands::delete_guild_command),
+        )
+        // Interactions. The second segment is the interaction id for the
+        // callback and the application id for response/followup routes.
+        .route(
+            "/api/v1/interactions",
+            post(routes::interactions::invoke_interaction),
+        )
+        .route(
+            "/api/v1/interactions/{id}/{token}/callback",
+            post(routes::interactions::interaction_callback),
+        )
+        .route(
+            "/api/v1/interactions/{id}/{token}/messages/@original",
+            patch(routes::interactions::edit_original_response)
+                .delete(routes::interactions::delete_original_response),
+        )
+        .route(
+            "/api/v1/interactions/{id}/{token}/followup",
+            post(routes::interactions::create_followup_message),
+        )
         // Signal prekey management
         .route("/api/v1/users/@me/keys", put(routes::keys::upload_keys))
         .route(
@@ -418,6 +640,14 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/voice/{channel_id}/stream/stop",
             post(routes::voice::stop_stream),
         )
+        .route(
+            "/api/v1/voice/{channel_id}/recording",
+            post(routes::voice::start_recording),
+        )
+        .route(
+            "/api/v1/voice/{channel_id}/recording/stop",
+            post(routes::voice::stop_recording),
+        )
         .route(
             "/api/v1/voice/{channel_id}/leave",
             post(routes::voice::leave_voice),
@@ -452,6 +682,20 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/attachments/{id}",
             get(routes::files::download_file).delete(routes::files::delete_file),
         )
+        .route(
+            "/api/v1/attachments/{id}/embed-token",
+            get(routes::files::embed_token),
+        )
+        .route(
+            "/api/v1/embeds/thumbnail",
+            get(link_unfurl::proxy_thumbnail),
+        )
+        // Resumable (tus) uploads
+        .route("/api/v1/uploads", post(routes::uploads::create_upload))
+        .route(
+            "/api/v1/uploads/{upload_id}",
+            head(routes::uploads::get_upload_offset).patch(routes::uploads::append_upload),
+        )
         // QUIC file transfer pre-authorization
         .route(
             "/api/v2/channels/{channel_id}/upload-token",
@@ -472,6 +716,11 @@ pub fn build_router() -> Router<AppState> {
             put(routes::relationships::accept_friend)
                 .delete(routes::relationships::remove_relationship),
         )
+        // Presences
+        .route(
+            "/api/v1/presences",
+            post(routes::presences::query_presences),
+        )
         // Admin
         .route("/api/v1/admin/stats", get(routes::admin::get_stats))
         .route(
@@ -482,16 +731,49 @@ pub fn build_router() -> Router<AppState> {
             "/api/v1/admin/settings",
             get(routes::admin::get_settings).patch(routes::admin::update_settings),
         )
+        .route(
+            "/api/v1/admin/livekit/credentials",
+            put(routes::admin::update_livekit_credentials),
+        )
         .route("/api/v1/admin/users", get(routes::admin::list_users))
         .route(
             "/api/v1/admin/users/{user_id}",
             patch(routes::admin::update_user).delete(routes::admin::delete_user),
         )
+        .route(
+            "/api/v1/admin/users/{user_id}/impersonate",
+            post(routes::admin::impersonate_user),
+        )
+        .route(
+            "/api/v1/admin/users/{user_id}/sessions",
+            get(routes::admin::list_user_sessions),
+        )
+        .route(
+            "/api/v1/admin/users/{user_id}/sessions/revoke-all",
+            post(routes::admin::revoke_all_user_sessions),
+        )
+        .route(
+            "/api/v1/admin/impersonation/stop",
+            post(routes::admin::stop_impersonation),
+        )
+        .route("/api/v1/admin/usage", get(routes::admin::list_user_usage))
+        .route(
+            "/api/v1/admin/usage/{user_id}",
+            delete(routes::admin::clear_user_usage),
+        )
         .route("/api/v1/admin/guilds", get(routes::admin::list_guilds))
         .route(
             "/api/v1/admin/guilds/{guild_id}",
             patch(routes::admin::update_guild).delete(routes::admin::delete_guild),
         )
+        .route(
+            "/api/v1/admin/federation/queue",
+            get(routes::admin::list_federation_queue),
+        )
+        .route(
+            "/api/v1/admin/federation/queue/{id}/retry",
+            post(routes::admin::retry_federation_queue_entry),
+        )
         .route(
             "/api/v1/admin/restart-update",
             post(routes::admin::restart_update),
@@ -511,9 +793,13 @@ pub fn build_router() -> Router<AppState> {
         )
         // Middleware layers
         .layer(DefaultBodyLimit::max(DEFAULT_REQUEST_BODY_LIMIT_BYTES))
+        .layer(from_fn(request_timeout::request_timeout_middleware))
+        .layer(from_fn(csrf::csrf_middleware))
         .layer(from_fn(metrics_middleware))
         .layer(from_fn(rate_limit_middleware))
-        .layer(from_fn(security_headers_middleware))
+        .layer(from_fn(client_ip::client_ip_middleware))
+        .layer(from_fn(security_headers::security_headers_middleware))
+        .layer(response_compression::compression_layer())
         .layer(cors)
         .layer(
             tower_http::trace::TraceLayer::new_for_http()
@@ -631,6 +917,24 @@ fn build_cors_layer() -> tower_http::cors::CorsLayer {
             header::CONTENT_TYPE,
             header::ACCEPT,
             header::ORIGIN,
+            HeaderName::from_static(csrf::CSRF_HEADER_NAME),
+            HeaderName::from_static("tus-resumable"),
+            HeaderName::from_static("upload-length"),
+            HeaderName::from_static("upload-offset"),
+            HeaderName::from_static("upload-metadata"),
+        ])
+        .expose_headers([
+            header::LOCATION,
+            HeaderName::from_static("tus-resumable"),
+            HeaderName::from_static("tus-version"),
+            HeaderName::from_static("tus-max-size"),
+            HeaderName::from_static("tus-extension"),
+            HeaderName::from_static("upload-length"),
+            HeaderName::from_static("upload-offset"),
+            HeaderName::from_static("upload-expires"),
+            HeaderName::from_static("x-attachment-id"),
+            HeaderName::from_static("x-webhook-count"),
+            HeaderName::from_static("x-webhook-limit"),
         ])
         .max_age(Duration::from_secs(600));
 
@@ -701,6 +1005,7 @@ async fn metrics(headers: HeaderMap) -> impl IntoResponse {
     let ws_snapshot = paracord_core::observability::ws_metrics_snapshot();
     let ws_active = ws_snapshot.active_connections;
     let ws_events = ws_snapshot.total_events;
+    let plaintext_origins = paracord_core::observability::plaintext_public_origins();
 
     let dur_sum_us = DURATION_SUM_US.load(Ordering::Relaxed);
     let dur_count = DURATION_COUNT.load(Ordering::Relaxed);
@@ -740,6 +1045,9 @@ async fn metrics(headers: HeaderMap) -> impl IntoResponse {
          # HELP paracord_ws_events_total Total WebSocket events dispatched.\n\
          # TYPE paracord_ws_events_total counter\n\
          paracord_ws_events_total {ws_events}\n\
+         # HELP paracord_plaintext_public_origins Public origins configured without TLS.\n\
+         # TYPE paracord_plaintext_public_origins gauge\n\
+         paracord_plaintext_public_origins {plaintext_origins}\n\
          # HELP paracord_ws_events_by_type_total Total WebSocket events dispatched by event type.\n\
          # TYPE paracord_ws_events_by_type_total counter\n",
         DURATION_LE_5.load(Ordering::Relaxed),
@@ -767,55 +1075,7 @@ async fn metrics(headers: HeaderMap) -> impl IntoResponse {
     )
 }
 
-struct RateBucket {
-    count: u32,
-    window_start: i64,
-}
-
-pub struct HttpRateLimiter {
-    buckets: DashMap<String, Mutex<RateBucket>>,
-}
-
-impl HttpRateLimiter {
-    fn new() -> Self {
-        Self {
-            buckets: DashMap::new(),
-        }
-    }
-
-    fn check_rate_limit(&self, key: &str, window_seconds: i64, max_count: u32) -> bool {
-        let now = chrono::Utc::now().timestamp();
-        let bucket = self.buckets.entry(key.to_string()).or_insert_with(|| {
-            Mutex::new(RateBucket {
-                count: 0,
-                window_start: now,
-            })
-        });
-        let mut guard = match bucket.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        if now.saturating_sub(guard.window_start) >= window_seconds {
-            guard.window_start = now;
-            guard.count = 0;
-        }
-        guard.count = guard.count.saturating_add(1);
-        guard.count <= max_count
-    }
-
-    fn cleanup_stale(&self, max_age_seconds: i64) {
-        let now = chrono::Utc::now().timestamp();
-        self.buckets.retain(|_, bucket| {
-            let guard = match bucket.lock() {
-                Ok(guard) => guard,
-                Err(poisoned) => poisoned.into_inner(),
-            };
-            now.saturating_sub(guard.window_start) <= max_age_seconds
-        });
-    }
-}
-
-static HTTP_RATE_LIMITER: OnceLock<HttpRateLimiter> = OnceLock::new();
+static HTTP_RATE_LIMITER: OnceLock<RateLimitStore> = OnceLock::new();
 static HTTP_TRACE_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
 static REQUEST_COUNT: AtomicU64 = AtomicU64::new(0);
 static RATE_LIMITED_COUNT: AtomicU64 = AtomicU64::new(0);
@@ -897,8 +1157,14 @@ fn record_status_code(status: u16) {
     }
 }
 
+/// Install the default in-memory store for the HTTP rate limiter.
 pub fn install_http_rate_limiter() {
-    let _ = HTTP_RATE_LIMITER.set(HttpRateLimiter::new());
+    install_http_rate_limit_store(RateLimitStore::default());
+}
+
+/// Install the store shared with the rest of the server (`AppState::rate_limits`).
+pub fn install_http_rate_limit_store(store: RateLimitStore) {
+    let _ = HTTP_RATE_LIMITER.set(store);
 }
 
 pub fn spawn_http_rate_limiter_cleanup(shutdown: Arc<Notify>) {
@@ -908,8 +1174,11 @@ pub fn spawn_http_rate_limiter_cleanup(shutdown: Arc<Notify>) {
             tokio::select! {
                 _ = shutdown.notified() => break,
                 _ = ticker.tick() => {
-                    if let Some(limiter) = HTTP_RATE_LIMITER.get() {
-                        limiter.cleanup_stale(600);
+                    if let Some(store) = HTTP_RATE_LIMITER.get() {
+                        let now = chrono::Utc::now().timestamp();
+                        if let Err(err) = store.purge_expired(now).await {
+                            tracing::warn!("rate-limit cleanup failed: {}", err);
+                        }
                     }
                 }
             }
@@ -936,50 +1205,22 @@ async fn rate_limit_middleware(req: Request, next: Next) -> Response {
 
     REQUEST_COUNT.fetch_add(1, Ordering::Relaxed);
     let is_auth_path = path.starts_with("/api/v1/auth/");
-    let trust_proxy = std::env::var("PARACORD_TRUST_PROXY")
-        .ok()
-        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
-        .unwrap_or(false);
-    let peer_ip = req
-        .extensions()
-        .get::<ConnectInfo<std::net::SocketAddr>>()
-        .map(|info| info.0.ip().to_string());
-    let trusted_proxy_ips = if trust_proxy {
-        std::env::var("PARACORD_TRUSTED_PROXY_IPS")
-            .ok()
-            .map(|raw| {
-                raw.split(',')
-                    .map(str::trim)
-                    .filter(|v| !v.is_empty())
-                    .map(str::to_string)
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-    let can_trust_forwarded = trust_proxy
-        && peer_ip
-            .as_deref()
-            .is_some_and(|ip| trusted_proxy_ips.iter().any(|trusted| trusted == ip));
-
-    let key = if can_trust_forwarded {
-        req.headers()
-            .get("x-forwarded-for")
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.split(',').next())
-            .map(str::trim)
-            .filter(|s| !s.is_empty())
-            .map(str::to_string)
-            .or_else(|| peer_ip.clone())
+    let key = client_ip::client_ip_from_headers(req.headers()).unwrap_or_else(|| {
+        let peer_ip = req
+            .extensions()
+            .get::<ConnectInfo<std::net::SocketAddr>>()
+            .map(|info| info.0.ip().to_string());
+        client_ip::resolve_client_ip(req.headers(), peer_ip.as_deref())
             .unwrap_or_else(|| "unknown".to_string())
-    } else {
-        peer_ip.unwrap_or_else(|| "unknown".to_string())
-    };
+    });
 
     if let Some(limiter) = HTTP_RATE_LIMITER.get() {
+        let now = chrono::Utc::now().timestamp();
         let global_key = format!("http:global:{key}");
-        if !limiter.check_rate_limit(&global_key, 1, GLOBAL_LIMIT_PER_SECOND) {
+        if !limiter
+            .check(&global_key, 1, GLOBAL_LIMIT_PER_SECOND, now)
+            .await
+        {
             RATE_LIMITED_COUNT.fetch_add(1, Ordering::Relaxed);
             return crate::error::ApiError::RateLimited.into_response();
         }
@@ -994,7 +1235,7 @@ async fn rate_limit_middleware(req: Request, next: Next) -> Response {
         {
             let token_hash = paracord_db::bot_applications::hash_token(bot_token);
             let bot_key = format!("http:bot:{}", &token_hash[..24]);
-            if !limiter.check_rate_limit(&bot_key, 60, BOT_LIMIT_PER_MINUTE) {
+            if !limiter.check(&bot_key, 60, BOT_LIMIT_PER_MINUTE, now).await {
                 RATE_LIMITED_COUNT.fetch_add(1, Ordering::Relaxed);
                 return crate::error::ApiError::RateLimited.into_response();
             }
@@ -1002,7 +1243,10 @@ async fn rate_limit_middleware(req: Request, next: Next) -> Response {
 
         if is_auth_path {
             let auth_key = format!("http:auth:{key}");
-            if !limiter.check_rate_limit(&auth_key, 60, AUTH_LIMIT_PER_MINUTE) {
+            if !limiter
+                .check(&auth_key, 60, AUTH_LIMIT_PER_MINUTE, now)
+                .await
+            {
                 RATE_LIMITED_COUNT.fetch_add(1, Ordering::Relaxed);
                 return crate::error::ApiError::RateLimited.into_response();
             }
@@ -1012,66 +1256,6 @@ async fn rate_limit_middleware(req: Request, next: Next) -> Response {
     next.run(req).await
 }
 
-async fn security_headers_middleware(req: Request, next: Next) -> Response {
-    let path = req.uri().path().to_string();
-    let is_https = req
-        .headers()
-        .get("x-forwarded-proto")
-        .and_then(|v| v.to_str().ok())
-        .map(|v| v.eq_ignore_ascii_case("https"))
-        .unwrap_or(false);
-
-    let mut response = next.run(req).await;
-    let headers = response.headers_mut();
-    headers.insert(
-        header::X_CONTENT_TYPE_OPTIONS,
-        HeaderValue::from_static("nosniff"),
-    );
-    headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
-    headers.insert(
-        header::REFERRER_POLICY,
-        HeaderValue::from_static("no-referrer"),
-    );
-    headers.insert(
-        HeaderName::from_static("permissions-policy"),
-        HeaderValue::from_static("camera=(), microphone=(), geolocation=()"),
-    );
-    headers.insert(
-        HeaderName::from_static("cross-origin-opener-policy"),
-        HeaderValue::from_static("same-origin"),
-    );
-    headers.insert(
-        HeaderName::from_static("cross-origin-resource-policy"),
-        HeaderValue::from_static("same-origin"),
-    );
-    if path == "/health"
-        || path == "/metrics"
-        || path.starts_with("/api/")
-        || path.starts_with("/_paracord/")
-        || path.starts_with("/.well-known/")
-    {
-        headers.insert(
-            header::CONTENT_SECURITY_POLICY,
-            HeaderValue::from_static("default-src 'none'; frame-ancestors 'none'; base-uri 'none'"),
-        );
-    } else {
-        headers.insert(
-            header::CONTENT_SECURITY_POLICY,
-            HeaderValue::from_static(
-                "default-src 'self'; base-uri 'self'; frame-ancestors 'none'; object-src 'none'; script-src 'self'; style-src 'self' 'unsafe-inline' https://fonts.googleapis.com; font-src 'self' data: https://fonts.gstatic.com; img-src 'self' data: blob: https: http:; connect-src 'self' ws: wss: http: https:; media-src 'self' data: blob: https: http:",
-            ),
-        );
-    }
-    if is_https {
-        headers.insert(
-            header::STRICT_TRANSPORT_SECURITY,
-            HeaderValue::from_static("max-age=31536000; includeSubDomains"),
-        );
-    }
-
-    response
-}
-
 /// Middleware that records request duration and response status for the /metrics endpoint.
 async fn metrics_middleware(req: Request, next: Next) -> Response {
     let start = Instant::now();
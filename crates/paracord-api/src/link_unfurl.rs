@@ -0,0 +1,404 @@
+//! Server-side link previews for message content.
+//!
+//! Links are checked with [`outbound_url::parse_target`] before anything is
+//! fetched, and the HTTP fetcher resolves every hop (including redirects)
+//! with [`outbound_url::resolve_public`] and pins the connection to the
+//! checked addresses, so a message can never make the server request a
+//! private address.
+//!
+//! Preview images are never linked directly: embeds point at
+//! [`proxy_thumbnail`] with a signature over the image URL, and the server
+//! fetches the image itself, so the site behind a link never sees readers'
+//! addresses.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use hmac::{Hmac, Mac};
+use paracord_core::link_unfurl::{
+    ImageFetchFn, ImageFetchFuture, LinkFetchFn, LinkFetchFuture, LinkImage,
+};
+use paracord_core::{
+    AppState, MESSAGE_FLAG_DM_E2EE, MESSAGE_FLAG_EPHEMERAL, MESSAGE_FLAG_SUPPRESS_EMBEDS,
+};
+use paracord_db::messages::MessageRow;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::error::ApiError;
+use crate::outbound_url;
+
+/// Links previewed per message; later links are ignored.
+pub const MAX_UNFURLS_PER_MESSAGE: usize = 3;
+const MAX_REDIRECTS: usize = 3;
+const THUMBNAIL_SIGNATURE_PREFIX: &str = "link-thumbnail:";
+/// Image types served by the thumbnail proxy. SVG is left out because it
+/// can carry script.
+const THUMBNAIL_CONTENT_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+];
+const USER_AGENT: &str = concat!(
+    "Mozilla/5.0 (compatible; Paracord/",
+    env!("CARGO_PKG_VERSION"),
+    "; link preview)"
+);
+
+/// `https://` links in `content`, in order and without duplicates. Links
+/// wrapped in `<...>` are skipped, the usual way to post a link without a
+/// preview.
+pub fn extract_links(content: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    for word in content.split_whitespace() {
+        let Some(start) = word.find("https://") else {
+            continue;
+        };
+        if word[..start].ends_with('<') {
+            continue;
+        }
+        let link = word[start..].trim_end_matches(|c: char| {
+            matches!(
+                c,
+                '.' | ',' | ';' | ':' | '!' | '?' | ')' | ']' | '}' | '>' | '"' | '\'' | '*' | '_'
+            )
+        });
+        if link.len() > "https://".len() && !links.iter().any(|l| l == link) {
+            links.push(link.to_string());
+        }
+    }
+    links
+}
+
+/// Fetcher for [`paracord_core::link_unfurl::LinkUnfurler`] that reads at
+/// most `max_bytes` of an HTML page within `timeout`.
+pub fn http_fetcher(timeout: Duration, max_bytes: usize) -> LinkFetchFn {
+    Arc::new(move |url: String| -> LinkFetchFuture {
+        Box::pin(async move {
+            let (_, body) = fetch_public(
+                url,
+                "text/html,application/xhtml+xml",
+                |ct| ct.starts_with("text/html") || ct.starts_with("application/xhtml"),
+                timeout,
+                max_bytes,
+                true,
+            )
+            .await?;
+            Ok(String::from_utf8_lossy(&body).into_owned())
+        })
+    })
+}
+
+/// Image fetcher for the thumbnail proxy. Images larger than `max_bytes`
+/// are refused rather than cut short.
+pub fn http_image_fetcher(timeout: Duration, max_bytes: usize) -> ImageFetchFn {
+    Arc::new(move |url: String| -> ImageFetchFuture {
+        Box::pin(async move {
+            let (content_type, body) = fetch_public(
+                url,
+                "image/avif,image/webp,image/png,image/jpeg,image/gif",
+                |ct| THUMBNAIL_CONTENT_TYPES.iter().any(|t| ct.starts_with(t)),
+                timeout,
+                max_bytes,
+                false,
+            )
+            .await?;
+            Ok(LinkImage { content_type, body })
+        })
+    })
+}
+
+/// GET `raw` from a public address, following up to [`MAX_REDIRECTS`]
+/// checked redirects. Returns the lowercased media type and the body. With
+/// `truncate` the body is cut at `max_bytes`; otherwise a longer body is an
+/// error.
+async fn fetch_public(
+    raw: String,
+    accept: &str,
+    accepts_type: impl Fn(&str) -> bool,
+    timeout: Duration,
+    max_bytes: usize,
+    truncate: bool,
+) -> Result<(String, Vec<u8>), String> {
+    let mut url = outbound_url::parse_target(&raw)?;
+    for _ in 0..=MAX_REDIRECTS {
+        let addrs = outbound_url::resolve_public(&url).await?;
+        let host = url.host_str().unwrap_or_default().to_string();
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve_to_addrs(&host, &addrs)
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut response = client
+            .get(url.clone())
+            .header("accept", accept)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let status = response.status();
+        if status.is_redirection() {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or("redirect without a location")?;
+            let next = url.join(location).map_err(|_| "invalid redirect")?;
+            url = outbound_url::parse_target(next.as_str())?;
+            continue;
+        }
+        if !status.is_success() {
+            return Err(format!("HTTP {}", status.as_u16()));
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| {
+                v.split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .unwrap_or_default();
+        if !accepts_type(&content_type) {
+            return Err(format!("unexpected content type '{content_type}'"));
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            let room = max_bytes - body.len();
+            if chunk.len() > room && !truncate {
+                return Err("response too large".into());
+            }
+            body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            if body.len() >= max_bytes {
+                break;
+            }
+        }
+        return Ok((content_type, body));
+    }
+    Err("too many redirects".into())
+}
+
+fn thumbnail_mac(secret: &str, image_url: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{THUMBNAIL_SIGNATURE_PREFIX}{image_url}").as_bytes());
+    mac
+}
+
+/// Proxy path that serves `image_url` as a preview thumbnail.
+pub fn thumbnail_proxy_path(secret: &str, image_url: &str) -> String {
+    let signature: String = thumbnail_mac(secret, image_url)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("url", image_url)
+        .append_pair("sig", &signature)
+        .finish();
+    format!("/api/v1/embeds/thumbnail?{query}")
+}
+
+fn verify_thumbnail_signature(secret: &str, image_url: &str, signature: &str) -> bool {
+    if !signature.len().is_multiple_of(2) {
+        return false;
+    }
+    let Some(signature) = (0..signature.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(signature.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return false;
+    };
+    thumbnail_mac(secret, image_url)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+#[derive(Deserialize)]
+pub struct ThumbnailQuery {
+    pub url: String,
+    pub sig: String,
+}
+
+/// Serve a link preview image fetched by the server. Only URLs the server
+/// signed into an embed are accepted, so this is not an open proxy.
+pub async fn proxy_thumbnail(
+    State(state): State<AppState>,
+    Query(query): Query<ThumbnailQuery>,
+) -> Result<Response, ApiError> {
+    if !verify_thumbnail_signature(&state.config.jwt_secret, &query.url, &query.sig) {
+        return Err(ApiError::NotFound);
+    }
+    let unfurler = state.link_unfurler.clone().ok_or(ApiError::NotFound)?;
+    let image = unfurler.image(&query.url).await.ok_or(ApiError::NotFound)?;
+    let content_type =
+        HeaderValue::from_str(&image.content_type).map_err(|_| ApiError::NotFound)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=86400"),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_static("inline"),
+            ),
+        ],
+        image.body.clone(),
+    )
+        .into_response())
+}
+
+/// Refresh the link previews of a guild message in the background and send
+/// `MESSAGE_UPDATE` if they changed. Does nothing unless the server has link
+/// unfurling enabled and the guild has the `LINK_UNFURLS` feature. Links that
+/// fail the outbound URL checks are dropped without a request.
+pub async fn spawn_message_unfurl(state: &AppState, message: &MessageRow, guild_id: Option<i64>) {
+    let Some(unfurler) = state.link_unfurler.clone() else {
+        return;
+    };
+    let Some(guild_id) = guild_id else {
+        return;
+    };
+    let skip_flags = MESSAGE_FLAG_DM_E2EE | MESSAGE_FLAG_EPHEMERAL | MESSAGE_FLAG_SUPPRESS_EMBEDS;
+    if message.flags & skip_flags != 0 {
+        return;
+    }
+    let enabled = paracord_db::guilds::get_guild(&state.db, guild_id)
+        .await
+        .ok()
+        .flatten()
+        .is_some_and(|g| g.features & paracord_models::guild::GUILD_FEATURE_LINK_UNFURLS != 0);
+    if !enabled {
+        return;
+    }
+
+    let content = message.content.clone().unwrap_or_default();
+    let links: Vec<String> = extract_links(&content)
+        .into_iter()
+        .filter(|link| outbound_url::parse_target(link).is_ok())
+        .take(MAX_UNFURLS_PER_MESSAGE)
+        .collect();
+    if links.is_empty() && message.edited_at.is_none() {
+        return;
+    }
+    let state = state.clone();
+    let message_id = message.id;
+    tokio::spawn(async move {
+        let mut embeds = Vec::new();
+        for link in &links {
+            if let Some(mut embed) = unfurler.unfurl(link).await {
+                embed.thumbnail = embed
+                    .thumbnail
+                    .filter(|_| unfurler.proxies_images())
+                    .filter(|image| outbound_url::parse_target(image).is_ok())
+                    .map(|image| {
+                        state
+                            .config
+                            .media_url(&thumbnail_proxy_path(&state.config.jwt_secret, &image))
+                    });
+                if let Ok(embed) = serde_json::to_string(&embed) {
+                    embeds.push(embed);
+                }
+            }
+        }
+
+        // The message may have been edited or deleted while we were fetching.
+        let Ok(Some(current)) = paracord_db::messages::get_message(&state.db, message_id).await
+        else {
+            return;
+        };
+        if current.content.as_deref().unwrap_or_default() != content {
+            return;
+        }
+        match paracord_db::messages::set_message_link_embeds(&state.db, message_id, &embeds).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                tracing::warn!(
+                    "message {}: failed to store link previews: {}",
+                    message_id,
+                    e
+                );
+                return;
+            }
+        }
+
+        let msg_json = crate::routes::channels::message_to_json(
+            &state,
+            &current,
+            current.author_id,
+            Some(guild_id),
+        )
+        .await;
+        state
+            .event_bus
+            .dispatch("MESSAGE_UPDATE", msg_json, Some(guild_id));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_https_links_in_order() {
+        let links = extract_links(
+            "see https://example.com/a, (https://example.com/b) and \
+             [docs](https://docs.example.com/x). https://example.com/a again",
+        );
+        assert_eq!(
+            links,
+            vec![
+                "https://example.com/a",
+                "https://example.com/b",
+                "https://docs.example.com/x",
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_bracketed_and_plain_http_links() {
+        assert!(extract_links("<https://example.com/quiet> http://example.com").is_empty());
+    }
+
+    #[test]
+    fn thumbnail_signature_covers_the_image_url() {
+        let path = thumbnail_proxy_path("secret", "https://cdn.example.com/a.png?x=1&y=2");
+        let (query, signature) = path.split_once("&sig=").unwrap();
+        assert_eq!(
+            query,
+            "/api/v1/embeds/thumbnail?url=https%3A%2F%2Fcdn.example.com%2Fa.png%3Fx%3D1%26y%3D2"
+        );
+        assert!(verify_thumbnail_signature(
+            "secret",
+            "https://cdn.example.com/a.png?x=1&y=2",
+            signature
+        ));
+        assert!(!verify_thumbnail_signature(
+            "secret",
+            "https://cdn.example.com/b.png",
+            signature
+        ));
+        assert!(!verify_thumbnail_signature(
+            "other",
+            "https://cdn.example.com/a.png?x=1&y=2",
+            signature
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+//! Locales users may pick in their settings.
+//!
+//! Operators set the list with `server.allowed_locales`, exported
+//! comma-separated in `PARACORD_ALLOWED_LOCALES`. Every entry must be a
+//! BCP-47 tag such as `en-US` or `zh-Hant-TW`.
+
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+
+pub const DEFAULT_LOCALES: &[&str] = &[
+    "en-US", "en-GB", "de-DE", "es-ES", "fr-FR", "it-IT", "ja-JP", "ko-KR", "nl-NL", "pl-PL",
+    "pt-BR", "ru-RU", "sv-SE", "tr-TR", "uk-UA", "zh-CN", "zh-TW",
+];
+
+const MAX_LOCALE_LEN: usize = 35;
+
+/// True for a BCP-47 language tag: a 2-3 letter language, then optional
+/// script, region and variant subtags of 1-8 letters or digits.
+pub fn is_well_formed(tag: &str) -> bool {
+    if tag.is_empty() || tag.len() > MAX_LOCALE_LEN {
+        return false;
+    }
+    let mut subtags = tag.split('-');
+    let language = subtags.next().unwrap_or_default();
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return false;
+    }
+    subtags.all(|subtag| {
+        (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+pub fn parse(raw: &str) -> Result<Vec<String>, String> {
+    let mut locales: Vec<String> = Vec::new();
+    for tag in raw.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !is_well_formed(tag) {
+            return Err(format!("{tag:?} is not a BCP-47 language tag"));
+        }
+        if !locales.iter().any(|l| l.eq_ignore_ascii_case(tag)) {
+            locales.push(tag.to_string());
+        }
+    }
+    if locales.is_empty() {
+        return Err("no locales listed".into());
+    }
+    Ok(locales)
+}
+
+fn builtin_locales() -> Vec<String> {
+    DEFAULT_LOCALES.iter().map(|l| l.to_string()).collect()
+}
+
+pub fn from_env() -> Vec<String> {
+    let Ok(raw) = std::env::var("PARACORD_ALLOWED_LOCALES") else {
+        return builtin_locales();
+    };
+    parse(&raw).unwrap_or_else(|err| {
+        tracing::warn!(
+            "Ignoring PARACORD_ALLOWED_LOCALES ({}); using the built-in locales",
+            err
+        );
+        builtin_locales()
+    })
+}
+
+static LOCALES: OnceLock<Vec<String>> = OnceLock::new();
+
+pub fn allowed_locales() -> &'static [String] {
+    LOCALES.get_or_init(from_env)
+}
+
+/// The entry of `allowed` matching `requested`, ignoring case. A bare
+/// language such as the legacy `en` default resolves to the first allowed
+/// tag for that language.
+pub fn resolve(allowed: &[String], requested: &str) -> Result<String, ApiError> {
+    let requested = requested.trim();
+    if !is_well_formed(requested) {
+        return Err(ApiError::BadRequest("Invalid locale".into()));
+    }
+    if let Some(exact) = allowed.iter().find(|l| l.eq_ignore_ascii_case(requested)) {
+        return Ok(exact.clone());
+    }
+    if !requested.contains('-') {
+        if let Some(regional) = allowed.iter().find(|l| {
+            l.split('-')
+                .next()
+                .is_some_and(|language| language.eq_ignore_ascii_case(requested))
+        }) {
+            return Ok(regional.clone());
+        }
+    }
+    Err(ApiError::BadRequest("Unsupported locale".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_bcp47_tags() {
+        for tag in ["en", "en-US", "zh-Hant-TW", "es-419", "de-CH-1996"] {
+            assert!(is_well_formed(tag), "{tag}");
+        }
+        for tag in [
+            "", "e", "english", "en_US", "en-", "en--US", "en-US!", "ещё",
+        ] {
+            assert!(!is_well_formed(tag), "{tag}");
+        }
+    }
+
+    #[test]
+    fn parses_list_and_rejects_malformed_entries() {
+        assert_eq!(
+            parse(" en-US, fr-FR ,en-us").unwrap(),
+            vec!["en-US".to_string(), "fr-FR".to_string()]
+        );
+        assert!(parse("en-US,not a tag").is_err());
+        assert!(parse(" , ").is_err());
+    }
+
+    #[test]
+    fn resolves_to_the_allowed_spelling() {
+        let allowed = vec!["en-US".to_string(), "pt-BR".to_string()];
+        assert_eq!(resolve(&allowed, "pt-br").unwrap(), "pt-BR");
+        assert!(resolve(&allowed, "de-DE").is_err());
+        assert!(resolve(&allowed, "<script>").is_err());
+    }
+
+    #[test]
+    fn resolves_legacy_bare_languages() {
+        let allowed = vec![
+            "en-US".to_string(),
+            "en-GB".to_string(),
+            "pt-BR".to_string(),
+        ];
+        assert_eq!(resolve(&allowed, "en").unwrap(), "en-US");
+        assert_eq!(resolve(&allowed, "PT").unwrap(), "pt-BR");
+        assert!(resolve(&allowed, "de").is_err());
+        assert!(resolve(&allowed, "en-AU").is_err());
+    }
+}
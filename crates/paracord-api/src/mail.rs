@@ -0,0 +1,71 @@
+//! SMTP delivery for [`paracord_core::mail::Mailer`].
+
+use std::time::Duration;
+
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use paracord_core::mail::{MailSendFn, MailSendFuture, OutgoingMail};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    Starttls,
+    Tls,
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    /// Empty = no authentication.
+    pub username: String,
+    pub password: String,
+    pub from: String,
+    pub timeout: Duration,
+}
+
+/// Sender that relays every message through one SMTP server.
+pub fn smtp_sender(settings: SmtpSettings) -> Result<MailSendFn, String> {
+    let from: Mailbox = settings
+        .from
+        .parse()
+        .map_err(|e| format!("invalid sender '{}': {e}", settings.from))?;
+    let mut builder = match settings.security {
+        SmtpSecurity::Starttls => {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+        }
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host),
+        SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            &settings.host,
+        )),
+    }
+    .map_err(|e| e.to_string())?
+    .port(settings.port)
+    .timeout(Some(settings.timeout));
+    if !settings.username.is_empty() {
+        builder = builder.credentials(Credentials::new(settings.username, settings.password));
+    }
+    let transport = builder.build();
+    Ok(std::sync::Arc::new(
+        move |mail: OutgoingMail| -> MailSendFuture {
+            let transport = transport.clone();
+            let from = from.clone();
+            Box::pin(async move {
+                let to: Mailbox = mail
+                    .to
+                    .parse()
+                    .map_err(|e| format!("invalid recipient: {e}"))?;
+                let message = Message::builder()
+                    .from(from)
+                    .to(to)
+                    .subject(mail.subject)
+                    .body(mail.body)
+                    .map_err(|e| e.to_string())?;
+                transport.send(message).await.map_err(|e| e.to_string())?;
+                Ok(())
+            })
+        },
+    ))
+}
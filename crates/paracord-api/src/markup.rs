@@ -0,0 +1,269 @@
+//! Markup policy for plain-text profile fields (display name, bio, custom
+//! status).
+//!
+//! These fields never render HTML, so nothing tag-shaped is allowed: any
+//! `<tag>`, `</tag>`, `<!...>` or `<?...>` counts as markup, as does a URL
+//! that starts with a script-capable scheme (`javascript:`, `vbscript:`,
+//! `data:`). Words that merely contain those strings ("onload", "I write
+//! javascript") and comparisons such as `a < b` or `<3` are left alone.
+//!
+//! What happens to offending input is configurable with
+//! `PARACORD_PROFILE_MARKUP` (exported from `server.profile_markup`):
+//! `reject` (the default) refuses the update, `strip` removes the markup
+//! and keeps the rest of the text.
+
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+
+/// URL schemes that can execute script when a client turns text into a link.
+const DANGEROUS_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:"];
+
+/// Elements whose contents are dropped along with the tags when stripping.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "iframe", "noscript"];
+
+const MAX_STRIP_PASSES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkupPolicy {
+    Reject,
+    Strip,
+}
+
+impl MarkupPolicy {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "reject" => Some(Self::Reject),
+            "strip" => Some(Self::Strip),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let Ok(raw) = std::env::var("PARACORD_PROFILE_MARKUP") else {
+            return Self::Reject;
+        };
+        Self::parse(&raw).unwrap_or_else(|| {
+            tracing::warn!(
+                "Ignoring unknown PARACORD_PROFILE_MARKUP value {:?}; rejecting markup",
+                raw
+            );
+            Self::Reject
+        })
+    }
+
+    /// Apply the policy to `value`, returning the text to store.
+    pub fn apply(self, field: &str, value: &str) -> Result<String, ApiError> {
+        if !contains_markup(value) {
+            return Ok(value.to_string());
+        }
+        match self {
+            Self::Reject => Err(ApiError::BadRequest(format!(
+                "{field} contains unsafe markup"
+            ))),
+            Self::Strip => {
+                // Stripping can splice fragments back together
+                // (`java<b>script:`), so repeat until nothing is left.
+                let mut stripped = strip_markup(value);
+                for _ in 0..MAX_STRIP_PASSES {
+                    if !contains_markup(&stripped) {
+                        return Ok(stripped);
+                    }
+                    stripped = strip_markup(&stripped);
+                }
+                Err(ApiError::BadRequest(format!(
+                    "{field} contains unsafe markup"
+                )))
+            }
+        }
+    }
+}
+
+static POLICY: OnceLock<MarkupPolicy> = OnceLock::new();
+
+pub fn profile_markup_policy() -> MarkupPolicy {
+    *POLICY.get_or_init(MarkupPolicy::from_env)
+}
+
+/// Length of the tag starting at `bytes[0] == b'<'`, or `None` when the `<`
+/// is ordinary text.
+fn tag_len(bytes: &[u8]) -> Option<usize> {
+    let opens_tag = match bytes.get(1) {
+        Some(b'/') => bytes.get(2).is_some_and(u8::is_ascii_alphabetic),
+        Some(b'!' | b'?') => true,
+        Some(c) => c.is_ascii_alphabetic(),
+        None => false,
+    };
+    if !opens_tag {
+        return None;
+    }
+    // An unterminated tag still counts; browsers recover it up to the end.
+    Some(
+        bytes
+            .iter()
+            .position(|&b| b == b'>')
+            .map_or(bytes.len(), |end| end + 1),
+    )
+}
+
+fn tag_name(tag: &[u8]) -> String {
+    tag.iter()
+        .skip(1)
+        .skip_while(|&&b| b == b'/')
+        .take_while(|b| b.is_ascii_alphanumeric())
+        .map(|b| b.to_ascii_lowercase() as char)
+        .collect()
+}
+
+/// Length of a dangerous scheme prefix at the start of `bytes`, if any.
+/// Browsers drop tabs and newlines inside URLs, so `java\tscript:` matches.
+fn scheme_len(bytes: &[u8]) -> Option<usize> {
+    DANGEROUS_SCHEMES.iter().find_map(|scheme| {
+        let mut consumed = 0;
+        for &expected in scheme.as_bytes() {
+            while matches!(bytes.get(consumed), Some(b'\t' | b'\n' | b'\r')) {
+                consumed += 1;
+            }
+            if bytes
+                .get(consumed)
+                .is_none_or(|b| b.to_ascii_lowercase() != expected)
+            {
+                return None;
+            }
+            consumed += 1;
+        }
+        Some(consumed)
+    })
+}
+
+/// A scheme only counts at the start of a token, not inside a word or a
+/// longer URL path.
+fn starts_token(bytes: &[u8], index: usize) -> bool {
+    index == 0
+        || !bytes[index - 1].is_ascii_alphanumeric()
+            && !matches!(bytes[index - 1], b'/' | b'.' | b'-' | b'_' | b'+')
+}
+
+pub fn contains_markup(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    (0..bytes.len()).any(|i| {
+        (bytes[i] == b'<' && tag_len(&bytes[i..]).is_some())
+            || (starts_token(bytes, i) && scheme_len(&bytes[i..]).is_some())
+    })
+}
+
+/// Remove tags (and the contents of script-like elements) and dangerous URL
+/// schemes, keeping the surrounding text.
+pub fn strip_markup(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'<' {
+            if let Some(len) = tag_len(&bytes[i..]) {
+                let tag = &bytes[i..i + len];
+                i += len;
+                let name = tag_name(tag);
+                if tag.get(1) != Some(&b'/') && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                    let closing = format!("</{name}");
+                    let rest = value[i..].to_ascii_lowercase();
+                    i = match rest.find(&closing) {
+                        Some(offset) => {
+                            let close_start = i + offset;
+                            close_start + tag_len(&bytes[close_start..]).unwrap_or(closing.len())
+                        }
+                        None => bytes.len(),
+                    };
+                }
+                continue;
+            }
+        }
+        if starts_token(bytes, i) {
+            if let Some(len) = scheme_len(&bytes[i..]) {
+                i += len;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    // Only ASCII ranges are removed, so the remainder is still valid UTF-8.
+    String::from_utf8(out).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn benign_prose_is_not_markup() {
+        for text in [
+            "I write javascript for a living",
+            "the onload event fires late",
+            "onerror=retry is my motto",
+            "a < b and b > c",
+            "<3 paracord",
+            "see https://example.com/data:thing",
+            "metadata: none",
+        ] {
+            assert!(!contains_markup(text), "{text:?} should be allowed");
+        }
+    }
+
+    #[test]
+    fn tags_and_script_urls_are_markup() {
+        for text in [
+            "<script>alert(1)</script>",
+            "hi <img src=x onerror=alert(1)>",
+            "</b>",
+            "<!-- hidden -->",
+            "<svg",
+            "javascript:alert(1)",
+            "click (JavaScript:alert(1))",
+            "java\tscript:alert(1)",
+            "data:text/html;base64,PHNjcmlwdD4=",
+        ] {
+            assert!(contains_markup(text), "{text:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn strip_keeps_text_and_drops_markup() {
+        assert_eq!(strip_markup("hello <b>world</b>"), "hello world");
+        assert_eq!(
+            strip_markup("hi<script>alert(1)</script> there"),
+            "hi there"
+        );
+        assert_eq!(strip_markup("go javascript:alert(1)"), "go alert(1)");
+        assert_eq!(strip_markup("a < b"), "a < b");
+        assert_eq!(strip_markup("tail <iframe src=x>never closed"), "tail ");
+    }
+
+    #[test]
+    fn policy_rejects_or_strips() {
+        assert!(matches!(
+            MarkupPolicy::Reject.apply("bio", "<script>x</script>"),
+            Err(ApiError::BadRequest(_))
+        ));
+        assert_eq!(
+            MarkupPolicy::Strip
+                .apply("bio", "<b>bold</b> move")
+                .unwrap(),
+            "bold move"
+        );
+        assert_eq!(
+            MarkupPolicy::Strip
+                .apply("bio", "java<b>script:alert(1)")
+                .unwrap(),
+            "alert(1)"
+        );
+        assert_eq!(
+            MarkupPolicy::Reject
+                .apply("bio", "javascript developer")
+                .unwrap(),
+            "javascript developer"
+        );
+        assert_eq!(MarkupPolicy::parse(" Strip "), Some(MarkupPolicy::Strip));
+        assert_eq!(MarkupPolicy::parse("sanitize"), None);
+    }
+}
@@ -11,6 +11,8 @@ pub struct AuthUser {
     pub user_id: i64,
     pub session_id: Option<String>,
     pub token_jti: Option<String>,
+    /// Admin acting as `user_id` when the request uses an impersonation token.
+    pub impersonator_id: Option<i64>,
 }
 
 const ACCESS_COOKIE_NAME: &str = "paracord_access";
@@ -18,6 +20,7 @@ const ACCESS_COOKIE_NAME: &str = "paracord_access";
 enum AuthScheme<'a> {
     Bearer(&'a str),
     Bot(&'a str),
+    Pat(&'a str),
 }
 
 fn extract_auth_scheme(parts: &Parts) -> Option<AuthScheme<'_>> {
@@ -32,6 +35,9 @@ fn extract_auth_scheme(parts: &Parts) -> Option<AuthScheme<'_>> {
     if let Some(token) = raw.strip_prefix("Bot ") {
         return Some(AuthScheme::Bot(token));
     }
+    if let Some(token) = raw.strip_prefix("Pat ") {
+        return Some(AuthScheme::Pat(token));
+    }
     None
 }
 
@@ -78,18 +84,27 @@ async fn validate_auth(
         _ => return Err(ApiError::Unauthorized),
     };
 
+    let now = Utc::now();
     let active = paracord_db::sessions::is_access_token_active(
         &state.db,
         claims.sub,
         session_id,
         jti,
-        Utc::now(),
+        now,
+        state.config.session_idle_cutoff(now),
     )
     .await
     .map_err(|_| ApiError::Internal(anyhow::anyhow!("database error")))?;
     if !active {
         return Err(ApiError::Unauthorized);
     }
+    let _ = paracord_db::sessions::touch_session(
+        &state.db,
+        session_id,
+        now,
+        chrono::Duration::minutes(1),
+    )
+    .await;
 
     Ok(claims)
 }
@@ -111,6 +126,56 @@ async fn validate_bot_auth(parts: &Parts, state: &AppState) -> Result<i64, ApiEr
     Ok(app.bot_user_id)
 }
 
+/// Validate a "Pat <token>" header and check the token's scopes against the
+/// request. Returns the owning user id.
+async fn validate_pat_auth(parts: &Parts, state: &AppState, token: &str) -> Result<i64, ApiError> {
+    let now = Utc::now();
+    let token_hash = paracord_db::bot_applications::hash_token(token);
+    let pat =
+        paracord_db::personal_access_tokens::get_active_token_by_hash(&state.db, &token_hash, now)
+            .await
+            .map_err(|_| ApiError::Internal(anyhow::anyhow!("database error")))?
+            .ok_or(ApiError::Unauthorized)?;
+
+    if !crate::routes::access_tokens::pat_allows(&pat.scope_list(), &parts.method, parts.uri.path())
+    {
+        return Err(ApiError::Forbidden);
+    }
+
+    let _ = paracord_db::personal_access_tokens::touch_last_used(
+        &state.db,
+        pat.id,
+        now,
+        chrono::Duration::minutes(1),
+    )
+    .await;
+
+    Ok(pat.user_id)
+}
+
+/// Audit every request made under an impersonation token, reads included,
+/// recording both the admin and the impersonated user.
+async fn log_impersonated_action(
+    parts: &Parts,
+    state: &AppState,
+    impersonator_id: i64,
+    claims: &paracord_core::auth::Claims,
+) {
+    crate::routes::security::log_security_event(
+        state,
+        "admin.impersonation.action",
+        Some(impersonator_id),
+        Some(claims.sub),
+        claims.sid.as_deref(),
+        Some(&parts.headers),
+        Some(serde_json::json!({
+            "method": parts.method.as_str(),
+            "path": parts.uri.path(),
+        })),
+    )
+    .await;
+}
+
 impl FromRequestParts<AppState> for AuthUser {
     type Rejection = ApiError;
 
@@ -118,12 +183,28 @@ impl FromRequestParts<AppState> for AuthUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
+        // A PAT is never combined with cookie or query auth, and its scope
+        // rejection must surface as-is rather than falling through.
+        if let Some(AuthScheme::Pat(token)) = extract_auth_scheme(parts) {
+            let user_id = validate_pat_auth(parts, state, token).await?;
+            return Ok(AuthUser {
+                user_id,
+                session_id: None,
+                token_jti: None,
+                impersonator_id: None,
+            });
+        }
+
         // Try Bearer JWT first, then Bot token.
         if let Ok(claims) = validate_auth(parts, state).await {
+            if let Some(impersonator_id) = claims.imp {
+                log_impersonated_action(parts, state, impersonator_id, &claims).await;
+            }
             return Ok(AuthUser {
                 user_id: claims.sub,
                 session_id: claims.sid,
                 token_jti: claims.jti,
+                impersonator_id: claims.imp,
             });
         }
 
@@ -132,6 +213,7 @@ impl FromRequestParts<AppState> for AuthUser {
                 user_id: bot_user_id,
                 session_id: None,
                 token_jti: None,
+                impersonator_id: None,
             });
         }
 
@@ -152,6 +234,10 @@ impl FromRequestParts<AppState> for AdminUser {
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
         let claims = validate_auth(parts, state).await?;
+        // Impersonation never grants admin access, even when acting as an admin.
+        if claims.imp.is_some() {
+            return Err(ApiError::Forbidden);
+        }
 
         let user = paracord_db::users::get_user_by_id(&state.db, claims.sub)
             .await
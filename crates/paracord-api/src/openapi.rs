@@ -0,0 +1,2028 @@
+//! OpenAPI 3 description of the REST API, served at `/api/openapi.json`.
+//!
+//! Request and response schemas are generated with `schemars` from the same
+//! serde types the handlers extract and return, so field names, optionality
+//! and aliases follow the wire format. Handlers that still assemble their
+//! bodies as `serde_json::Value` are described as free-form objects until they
+//! grow a typed response. [`OPERATIONS`] lists every `/api/v*` route; a test
+//! checks it against the router.
+
+use axum::Json;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{Schema, SchemaObject},
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+use std::sync::OnceLock;
+
+use crate::routes::{auth, capabilities, channels, guilds, roles, users};
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    gen.subschema_for::<T>()
+}
+
+struct Operation {
+    method: &'static str,
+    path: &'static str,
+    operation_id: &'static str,
+    tag: &'static str,
+    authenticated: bool,
+    query: Option<SchemaFn>,
+    request: Option<SchemaFn>,
+    status: u16,
+    response: SchemaFn,
+    /// Media type of the success body; `None` when the response has none.
+    content_type: Option<&'static str>,
+}
+
+const JSON: &str = "application/json";
+const BINARY: &str = "application/octet-stream";
+const EVENT_STREAM: &str = "text/event-stream";
+const PLAIN_TEXT: &str = "text/plain";
+
+fn raw_body(_: &mut SchemaGenerator) -> Schema {
+    let schema: Value = json!({ "type": "string", "format": "binary" });
+    serde_json::from_value(schema).expect("static schema")
+}
+
+/// An authenticated route whose handler still builds `serde_json::Value`:
+/// no request body and a free-form JSON response until adjusted below.
+const fn untyped(
+    method: &'static str,
+    path: &'static str,
+    operation_id: &'static str,
+    tag: &'static str,
+) -> Operation {
+    Operation {
+        method,
+        path,
+        operation_id,
+        tag,
+        authenticated: true,
+        query: None,
+        request: None,
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    }
+}
+
+impl Operation {
+    const fn public(mut self) -> Self {
+        self.authenticated = false;
+        self
+    }
+
+    /// Takes a free-form JSON request body.
+    const fn with_body(mut self) -> Self {
+        self.request = Some(schema::<Value>);
+        self
+    }
+
+    /// Succeeds with `status`; `204` implies an empty body.
+    const fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        if status == 204 {
+            self.content_type = None;
+        }
+        self
+    }
+
+    /// Returns a non-JSON body of `content_type`.
+    const fn returns(mut self, content_type: &'static str) -> Self {
+        self.response = raw_body;
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Answers with headers only.
+    const fn without_body(mut self) -> Self {
+        self.content_type = None;
+        self
+    }
+}
+
+const OPERATIONS: &[Operation] = &[
+    Operation {
+        method: "post",
+        path: "/api/v1/auth/register",
+        operation_id: "register",
+        tag: "auth",
+        authenticated: false,
+        query: None,
+        request: Some(schema::<auth::RegisterRequest>),
+        status: 201,
+        response: schema::<auth::AuthResponse>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "post",
+        path: "/api/v1/auth/login",
+        operation_id: "login",
+        tag: "auth",
+        authenticated: false,
+        query: None,
+        request: Some(schema::<auth::LoginRequest>),
+        status: 200,
+        response: schema::<auth::AuthResponse>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "get",
+        path: "/api/v1/auth/options",
+        operation_id: "authOptions",
+        tag: "auth",
+        authenticated: false,
+        query: None,
+        request: None,
+        status: 200,
+        response: schema::<auth::AuthOptionsResponse>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "get",
+        path: "/api/v1/capabilities",
+        operation_id: "getCapabilities",
+        tag: "server",
+        authenticated: false,
+        query: None,
+        request: None,
+        status: 200,
+        response: schema::<capabilities::CapabilitiesResponse>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "get",
+        path: "/api/v1/users/@me",
+        operation_id: "getCurrentUser",
+        tag: "users",
+        authenticated: true,
+        query: None,
+        request: None,
+        status: 200,
+        response: schema::<users::CurrentUserResponse>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "patch",
+        path: "/api/v1/users/@me",
+        operation_id: "updateCurrentUser",
+        tag: "users",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<users::UpdateMeRequest>),
+        status: 200,
+        response: schema::<users::CurrentUserResponse>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "get",
+        path: "/api/v1/users/@me/settings",
+        operation_id: "getSettings",
+        tag: "users",
+        authenticated: true,
+        query: None,
+        request: None,
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "patch",
+        path: "/api/v1/users/@me/settings",
+        operation_id: "updateSettings",
+        tag: "users",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<users::UpdateSettingsRequest>),
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "post",
+        path: "/api/v1/guilds",
+        operation_id: "createGuild",
+        tag: "guilds",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<guilds::CreateGuildRequest>),
+        status: 201,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "patch",
+        path: "/api/v1/guilds/{guild_id}",
+        operation_id: "updateGuild",
+        tag: "guilds",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<guilds::UpdateGuildRequest>),
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "post",
+        path: "/api/v1/guilds/{guild_id}/channels",
+        operation_id: "createChannel",
+        tag: "channels",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<channels::CreateChannelRequest>),
+        status: 201,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "post",
+        path: "/api/v1/guilds/{guild_id}/roles",
+        operation_id: "createRole",
+        tag: "roles",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<roles::CreateRoleRequest>),
+        status: 201,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "patch",
+        path: "/api/v1/guilds/{guild_id}/roles/{role_id}",
+        operation_id: "updateRole",
+        tag: "roles",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<roles::UpdateRoleRequest>),
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "patch",
+        path: "/api/v1/channels/{channel_id}",
+        operation_id: "updateChannel",
+        tag: "channels",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<channels::UpdateChannelRequest>),
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "get",
+        path: "/api/v1/channels/{channel_id}/messages",
+        operation_id: "listMessages",
+        tag: "messages",
+        authenticated: true,
+        query: Some(schema::<channels::MessageQuery>),
+        request: None,
+        status: 200,
+        response: schema::<Vec<Value>>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "post",
+        path: "/api/v1/channels/{channel_id}/messages",
+        operation_id: "createMessage",
+        tag: "messages",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<channels::SendMessageRequest>),
+        status: 201,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "get",
+        path: "/api/v1/channels/{channel_id}/messages/{message_id}",
+        operation_id: "getMessage",
+        tag: "messages",
+        authenticated: true,
+        query: None,
+        request: None,
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    Operation {
+        method: "patch",
+        path: "/api/v1/channels/{channel_id}/messages/{message_id}",
+        operation_id: "editMessage",
+        tag: "messages",
+        authenticated: true,
+        query: None,
+        request: Some(schema::<channels::EditMessageRequest>),
+        status: 200,
+        response: schema::<Value>,
+        content_type: Some(JSON),
+    },
+    untyped("get", "/api/v1/health", "getHealth", "server").public(),
+    untyped("get", "/api/v1/metrics", "getMetrics", "server")
+        .public()
+        .returns(PLAIN_TEXT),
+    untyped("post", "/api/v2/rt/session", "createSession", "realtime"),
+    untyped("get", "/api/v2/rt/events", "streamEvents", "realtime").returns(EVENT_STREAM),
+    untyped("post", "/api/v2/rt/commands", "postCommand", "realtime").with_body(),
+    untyped("post", "/api/v1/auth/verify-email", "verifyEmail", "auth")
+        .public()
+        .with_body()
+        .status(204),
+    untyped(
+        "post",
+        "/api/v1/auth/verify-email/resend",
+        "resendEmailVerification",
+        "auth",
+    )
+    .public()
+    .with_body()
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/auth/forgot-password",
+        "forgotPassword",
+        "auth",
+    )
+    .public()
+    .with_body()
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/auth/reset-password",
+        "resetPassword",
+        "auth",
+    )
+    .public()
+    .with_body()
+    .status(204),
+    untyped("post", "/api/v1/auth/refresh", "refresh", "auth").public(),
+    untyped("post", "/api/v1/auth/logout", "logout", "auth").status(204),
+    untyped("post", "/api/v1/auth/challenge", "challenge", "auth").public(),
+    untyped("post", "/api/v1/auth/verify", "verify", "auth")
+        .public()
+        .with_body(),
+    untyped(
+        "post",
+        "/api/v1/auth/webauthn/register/begin",
+        "registerBegin",
+        "auth",
+    ),
+    untyped(
+        "post",
+        "/api/v1/auth/webauthn/register/finish",
+        "registerFinish",
+        "auth",
+    )
+    .with_body(),
+    untyped(
+        "post",
+        "/api/v1/auth/webauthn/login/begin",
+        "loginBegin",
+        "auth",
+    )
+    .public()
+    .with_body(),
+    untyped(
+        "post",
+        "/api/v1/auth/webauthn/login/finish",
+        "loginFinish",
+        "auth",
+    )
+    .public()
+    .with_body(),
+    untyped(
+        "post",
+        "/api/v1/auth/attach-public-key",
+        "attachPublicKey",
+        "auth",
+    )
+    .with_body(),
+    untyped("get", "/api/v1/auth/sessions", "listSessions", "auth"),
+    untyped(
+        "delete",
+        "/api/v1/auth/sessions/{session_id}",
+        "revokeSession",
+        "auth",
+    )
+    .status(204),
+    untyped("delete", "/api/v1/users/@me", "deleteMe", "users").status(204),
+    untyped(
+        "put",
+        "/api/v1/users/@me/password",
+        "changePassword",
+        "users",
+    )
+    .with_body()
+    .status(204),
+    untyped("put", "/api/v1/users/@me/email", "changeEmail", "users")
+        .with_body()
+        .status(204),
+    untyped(
+        "get",
+        "/api/v1/users/@me/data-export",
+        "exportMyData",
+        "users",
+    ),
+    untyped(
+        "post",
+        "/api/v1/users/@me/export",
+        "exportIdentity",
+        "users",
+    ),
+    untyped(
+        "post",
+        "/api/v1/users/@me/import",
+        "importIdentity",
+        "users",
+    )
+    .with_body(),
+    untyped("get", "/api/v1/users/@me/tokens", "listTokens", "users"),
+    untyped("post", "/api/v1/users/@me/tokens", "createToken", "users")
+        .with_body()
+        .status(201),
+    untyped(
+        "delete",
+        "/api/v1/users/@me/tokens/{token_id}",
+        "revokeToken",
+        "users",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/users/@me/push-subscriptions",
+        "registerSubscription",
+        "users",
+    )
+    .with_body()
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/users/@me/push-subscriptions",
+        "deleteSubscription",
+        "users",
+    )
+    .with_body()
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/users/@me/push-subscriptions/vapid-key",
+        "getVapidKey",
+        "users",
+    ),
+    untyped(
+        "get",
+        "/api/v1/users/@me/mfa/totp",
+        "getTotpStatus",
+        "users",
+    ),
+    untyped(
+        "post",
+        "/api/v1/users/@me/mfa/totp",
+        "beginTotpEnrollment",
+        "users",
+    )
+    .with_body(),
+    untyped(
+        "post",
+        "/api/v1/users/@me/mfa/totp/confirm",
+        "confirmTotpEnrollment",
+        "users",
+    )
+    .with_body(),
+    untyped(
+        "post",
+        "/api/v1/users/@me/mfa/totp/disable",
+        "disableTotp",
+        "users",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/users/{user_id}/profile",
+        "getUserProfile",
+        "users",
+    ),
+    untyped("get", "/api/v1/users/@me/guilds", "listGuilds", "users"),
+    untyped(
+        "get",
+        "/api/v1/users/@me/bootstrap",
+        "getBootstrap",
+        "users",
+    ),
+    untyped("get", "/api/v1/users/@me/dms", "listDms", "users"),
+    untyped("post", "/api/v1/users/@me/dms", "createDm", "users")
+        .with_body()
+        .status(201),
+    untyped(
+        "post",
+        "/api/v1/users/@me/group-dms",
+        "createGroupDm",
+        "users",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "put",
+        "/api/v1/channels/{channel_id}/recipients/{user_id}",
+        "addGroupDmRecipient",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/recipients/{user_id}",
+        "removeGroupDmRecipient",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/users/@me/read-states",
+        "getReadStates",
+        "users",
+    ),
+    untyped(
+        "post",
+        "/api/v1/guilds/templates",
+        "createTemplate",
+        "guilds",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/guilds/templates/{code}",
+        "getTemplate",
+        "guilds",
+    ),
+    untyped(
+        "delete",
+        "/api/v1/guilds/templates/{code}",
+        "deleteTemplate",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/guilds/from-template/{code}",
+        "createGuildFromTemplate",
+        "guilds",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/templates",
+        "listGuildTemplates",
+        "guilds",
+    ),
+    untyped("get", "/api/v1/guilds/{guild_id}", "getGuild", "guilds"),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}",
+        "deleteGuild",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/guilds/{guild_id}/owner",
+        "transferOwnership",
+        "guilds",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/channels",
+        "getChannels",
+        "channels",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/guilds/{guild_id}/channels",
+        "updateChannelPositions",
+        "channels",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/messages/search",
+        "searchGuildMessages",
+        "messages",
+    ),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/members",
+        "listMembers",
+        "guilds",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/guilds/{guild_id}/members/{user_id}",
+        "updateMember",
+        "guilds",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/members/{user_id}",
+        "kickMember",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/members/@me",
+        "leaveGuild",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/guilds/{guild_id}/members/@me/screening",
+        "completeMemberScreening",
+        "guilds",
+    ),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/member-screening",
+        "getMemberScreening",
+        "guilds",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/guilds/{guild_id}/member-screening",
+        "updateMemberScreening",
+        "guilds",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/bans",
+        "listBans",
+        "guilds",
+    ),
+    untyped(
+        "put",
+        "/api/v1/guilds/{guild_id}/bans/{user_id}",
+        "banMember",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/bans/{user_id}",
+        "unbanMember",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/roles",
+        "listRoles",
+        "roles",
+    ),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/roles/{role_id}",
+        "deleteRole",
+        "roles",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/roles/{role_id}/icon",
+        "getRoleIcon",
+        "roles",
+    )
+    .public()
+    .returns(BINARY),
+    untyped(
+        "post",
+        "/api/v1/guilds/{guild_id}/roles/{role_id}/icon",
+        "uploadRoleIcon",
+        "roles",
+    ),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/invites",
+        "listGuildInvites",
+        "guilds",
+    ),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/emojis",
+        "listGuildEmojis",
+        "guilds",
+    ),
+    untyped(
+        "post",
+        "/api/v1/guilds/{guild_id}/emojis",
+        "createEmoji",
+        "guilds",
+    )
+    .status(201),
+    untyped(
+        "patch",
+        "/api/v1/guilds/{guild_id}/emojis/{emoji_id}",
+        "updateEmoji",
+        "guilds",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/emojis/{emoji_id}",
+        "deleteEmoji",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/emojis/{emoji_id}/image",
+        "getEmojiImage",
+        "guilds",
+    )
+    .public()
+    .returns(BINARY),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/webhooks",
+        "listGuildWebhooks",
+        "guilds",
+    ),
+    untyped(
+        "post",
+        "/api/v1/guilds/{guild_id}/webhooks",
+        "createWebhook",
+        "guilds",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/integrations/webhooks",
+        "listIntegrationWebhooks",
+        "guilds",
+    ),
+    untyped(
+        "post",
+        "/api/v1/guilds/{guild_id}/integrations/webhooks",
+        "createIntegrationWebhook",
+        "guilds",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/integrations/webhooks/{subscription_id}",
+        "deleteIntegrationWebhook",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/events",
+        "listEvents",
+        "guilds",
+    ),
+    untyped(
+        "post",
+        "/api/v1/guilds/{guild_id}/events",
+        "createEvent",
+        "guilds",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/events/{event_id}",
+        "getEvent",
+        "guilds",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/guilds/{guild_id}/events/{event_id}",
+        "updateEvent",
+        "guilds",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/events/{event_id}",
+        "deleteEvent",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "put",
+        "/api/v1/guilds/{guild_id}/events/{event_id}/rsvp",
+        "addRsvp",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/events/{event_id}/rsvp",
+        "removeRsvp",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/commands",
+        "listGuildAvailableCommands",
+        "guilds",
+    ),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/bots",
+        "listGuildBots",
+        "guilds",
+    ),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/bots/{bot_app_id}",
+        "removeGuildBot",
+        "guilds",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/storage",
+        "getStorage",
+        "guilds",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/guilds/{guild_id}/storage",
+        "updateStorage",
+        "guilds",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/files",
+        "listFiles",
+        "guilds",
+    ),
+    untyped(
+        "delete",
+        "/api/v1/guilds/{guild_id}/files",
+        "deleteFiles",
+        "guilds",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/guilds/{guild_id}/audit-logs",
+        "getAuditLogs",
+        "guilds",
+    ),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}",
+        "getChannel",
+        "channels",
+    ),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}",
+        "deleteChannel",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/messages/search",
+        "searchMessages",
+        "messages",
+    ),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/messages/bulk-delete",
+        "bulkDeleteMessages",
+        "messages",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/messages/{message_id}",
+        "deleteMessage",
+        "messages",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/permalink/resolve",
+        "resolvePermalink",
+        "messages",
+    ),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/polls",
+        "createPoll",
+        "channels",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/polls/{poll_id}",
+        "getPoll",
+        "channels",
+    ),
+    untyped(
+        "put",
+        "/api/v1/channels/{channel_id}/polls/{poll_id}/votes/{option_id}",
+        "addPollVote",
+        "channels",
+    ),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/polls/{poll_id}/votes/{option_id}",
+        "removePollVote",
+        "channels",
+    ),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/pins",
+        "getPins",
+        "channels",
+    ),
+    untyped(
+        "put",
+        "/api/v1/channels/{channel_id}/pins/{message_id}",
+        "pinMessage",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/pins/{message_id}",
+        "unpinMessage",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/typing",
+        "typing",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "put",
+        "/api/v1/channels/{channel_id}/read",
+        "updateReadState",
+        "channels",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/overwrites",
+        "listChannelOverwrites",
+        "channels",
+    ),
+    untyped(
+        "put",
+        "/api/v1/channels/{channel_id}/overwrites/{target_id}",
+        "upsertChannelOverwrite",
+        "channels",
+    )
+    .with_body()
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/overwrites/{target_id}",
+        "deleteChannelOverwrite",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/messages/{message_id}/reactions/bulk",
+        "bulkReactions",
+        "messages",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/messages/{message_id}/reactions/{emoji}",
+        "listReactionUsers",
+        "messages",
+    ),
+    untyped(
+        "put",
+        "/api/v1/channels/{channel_id}/messages/{message_id}/reactions/{emoji}/@me",
+        "addReaction",
+        "messages",
+    )
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/messages/{message_id}/reactions/{emoji}/@me",
+        "removeReaction",
+        "messages",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/webhooks",
+        "listChannelWebhooks",
+        "channels",
+    ),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/followers",
+        "followChannel",
+        "channels",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/followers/{follower_id}",
+        "unfollowChannel",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/messages/{message_id}/crosspost",
+        "crosspostMessage",
+        "messages",
+    ),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/threads",
+        "createThread",
+        "channels",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/threads",
+        "getThreads",
+        "channels",
+    ),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/messages/{message_id}/threads",
+        "startThreadFromMessage",
+        "messages",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/threads/archived",
+        "getArchivedThreads",
+        "channels",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/channels/{channel_id}/threads/{thread_id}",
+        "updateThread",
+        "channels",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/threads/{thread_id}",
+        "deleteThread",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/forum/posts",
+        "getForumPosts",
+        "channels",
+    ),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/forum/posts",
+        "createForumPost",
+        "channels",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/channels/{channel_id}/forum/tags",
+        "listForumTags",
+        "channels",
+    ),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/forum/tags",
+        "createForumTag",
+        "channels",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "delete",
+        "/api/v1/channels/{channel_id}/forum/tags/{tag_id}",
+        "deleteForumTag",
+        "channels",
+    )
+    .status(204),
+    untyped(
+        "patch",
+        "/api/v1/channels/{channel_id}/forum/sort",
+        "updateForumSortOrder",
+        "channels",
+    )
+    .with_body()
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/invites",
+        "createInvite",
+        "channels",
+    )
+    .with_body()
+    .status(201),
+    untyped("get", "/api/v1/invites/{code}", "getInvite", "invites").public(),
+    untyped("post", "/api/v1/invites/{code}", "acceptInvite", "invites"),
+    untyped(
+        "delete",
+        "/api/v1/invites/{code}",
+        "deleteInvite",
+        "invites",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/webhooks/{webhook_id}",
+        "getWebhook",
+        "webhooks",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/webhooks/{webhook_id}",
+        "updateWebhook",
+        "webhooks",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/webhooks/{webhook_id}",
+        "deleteWebhook",
+        "webhooks",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/webhooks/{webhook_id}/{token}",
+        "executeWebhook",
+        "webhooks",
+    )
+    .public()
+    .status(201),
+    untyped(
+        "post",
+        "/api/v1/webhooks/{webhook_id}/token/regenerate",
+        "regenerateWebhookToken",
+        "webhooks",
+    ),
+    untyped(
+        "get",
+        "/api/v1/discovery/guilds",
+        "listDiscoverableGuilds",
+        "guilds",
+    )
+    .public(),
+    untyped(
+        "get",
+        "/api/v1/bots/applications",
+        "listBotApplications",
+        "bots",
+    ),
+    untyped(
+        "post",
+        "/api/v1/bots/applications",
+        "createBotApplication",
+        "bots",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/bots/applications/{bot_app_id}",
+        "getBotApplication",
+        "bots",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/bots/applications/{bot_app_id}",
+        "updateBotApplication",
+        "bots",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/bots/applications/{bot_app_id}",
+        "deleteBotApplication",
+        "bots",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/bots/applications/{bot_app_id}/public",
+        "getPublicBotApplication",
+        "bots",
+    ),
+    untyped(
+        "post",
+        "/api/v1/bots/applications/{bot_app_id}/token",
+        "regenerateBotToken",
+        "bots",
+    ),
+    untyped(
+        "get",
+        "/api/v1/bots/applications/{bot_app_id}/installs",
+        "listBotApplicationInstalls",
+        "bots",
+    ),
+    untyped(
+        "post",
+        "/api/v1/oauth2/authorize",
+        "oauth2Authorize",
+        "bots",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/applications/{app_id}/commands",
+        "listGlobalCommands",
+        "applications",
+    ),
+    untyped(
+        "post",
+        "/api/v1/applications/{app_id}/commands",
+        "createGlobalCommand",
+        "applications",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "put",
+        "/api/v1/applications/{app_id}/commands",
+        "bulkOverwriteGlobalCommands",
+        "applications",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/applications/{app_id}/commands/{cmd_id}",
+        "getGlobalCommand",
+        "applications",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/applications/{app_id}/commands/{cmd_id}",
+        "updateGlobalCommand",
+        "applications",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/applications/{app_id}/commands/{cmd_id}",
+        "deleteGlobalCommand",
+        "applications",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/applications/{app_id}/guilds/{guild_id}/commands",
+        "listGuildCommands",
+        "applications",
+    ),
+    untyped(
+        "post",
+        "/api/v1/applications/{app_id}/guilds/{guild_id}/commands",
+        "createGuildCommand",
+        "applications",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "put",
+        "/api/v1/applications/{app_id}/guilds/{guild_id}/commands",
+        "bulkOverwriteGuildCommands",
+        "applications",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/applications/{app_id}/guilds/{guild_id}/commands/{cmd_id}",
+        "getGuildCommand",
+        "applications",
+    ),
+    untyped(
+        "patch",
+        "/api/v1/applications/{app_id}/guilds/{guild_id}/commands/{cmd_id}",
+        "updateGuildCommand",
+        "applications",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/applications/{app_id}/guilds/{guild_id}/commands/{cmd_id}",
+        "deleteGuildCommand",
+        "applications",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/interactions",
+        "invokeInteraction",
+        "interactions",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "post",
+        "/api/v1/interactions/{id}/{token}/callback",
+        "interactionCallback",
+        "interactions",
+    )
+    .public()
+    .with_body(),
+    untyped(
+        "patch",
+        "/api/v1/interactions/{id}/{token}/messages/@original",
+        "editOriginalResponse",
+        "messages",
+    )
+    .public()
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/interactions/{id}/{token}/messages/@original",
+        "deleteOriginalResponse",
+        "messages",
+    )
+    .public()
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/interactions/{id}/{token}/followup",
+        "createFollowupMessage",
+        "interactions",
+    )
+    .public()
+    .with_body()
+    .status(201),
+    untyped("put", "/api/v1/users/@me/keys", "uploadKeys", "users").with_body(),
+    untyped(
+        "get",
+        "/api/v1/users/@me/keys/count",
+        "getKeyCount",
+        "users",
+    ),
+    untyped("get", "/api/v1/users/{user_id}/keys", "getKeys", "users"),
+    untyped(
+        "get",
+        "/api/v1/voice/{channel_id}/join",
+        "joinVoice",
+        "voice",
+    ),
+    untyped(
+        "post",
+        "/api/v1/voice/{channel_id}/stream",
+        "startStream",
+        "voice",
+    ),
+    untyped(
+        "post",
+        "/api/v1/voice/{channel_id}/stream/stop",
+        "stopStream",
+        "voice",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/voice/{channel_id}/recording",
+        "startRecording",
+        "voice",
+    ),
+    untyped(
+        "post",
+        "/api/v1/voice/{channel_id}/recording/stop",
+        "stopRecording",
+        "voice",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/voice/{channel_id}/leave",
+        "leaveVoice",
+        "voice",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/voice/livekit/webhook",
+        "livekitWebhook",
+        "voice",
+    )
+    .public()
+    .status(204),
+    untyped(
+        "post",
+        "/api/v2/voice/{channel_id}/join",
+        "joinVoiceV2",
+        "voice",
+    ),
+    untyped(
+        "post",
+        "/api/v2/voice/{channel_id}/leave",
+        "leaveVoiceV2",
+        "voice",
+    )
+    .status(204),
+    untyped("post", "/api/v2/voice/state", "updateVoiceStateV2", "voice").with_body(),
+    untyped("post", "/api/v2/voice/recover", "recoverVoiceV2", "voice").with_body(),
+    untyped(
+        "post",
+        "/api/v1/channels/{channel_id}/attachments",
+        "uploadFile",
+        "channels",
+    )
+    .status(201),
+    untyped("get", "/api/v1/attachments/{id}", "downloadFile", "files").returns(BINARY),
+    untyped("delete", "/api/v1/attachments/{id}", "deleteFile", "files").status(204),
+    untyped(
+        "get",
+        "/api/v1/attachments/{id}/embed-token",
+        "embedToken",
+        "files",
+    ),
+    untyped(
+        "get",
+        "/api/v1/embeds/thumbnail",
+        "getLinkThumbnail",
+        "files",
+    )
+    .public()
+    .returns(BINARY),
+    untyped("post", "/api/v1/uploads", "createUpload", "files")
+        .status(201)
+        .without_body(),
+    untyped(
+        "head",
+        "/api/v1/uploads/{upload_id}",
+        "getUploadOffset",
+        "files",
+    )
+    .without_body(),
+    untyped(
+        "patch",
+        "/api/v1/uploads/{upload_id}",
+        "appendUpload",
+        "files",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v2/channels/{channel_id}/upload-token",
+        "uploadToken",
+        "files",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/federated-files/{origin_server}/{attachment_id}",
+        "downloadFederatedFile",
+        "files",
+    )
+    .returns(BINARY),
+    untyped(
+        "get",
+        "/api/v1/users/@me/relationships",
+        "listRelationships",
+        "users",
+    ),
+    untyped(
+        "post",
+        "/api/v1/users/@me/relationships",
+        "addFriend",
+        "users",
+    )
+    .with_body()
+    .status(204),
+    untyped(
+        "put",
+        "/api/v1/users/@me/relationships/{user_id}",
+        "acceptFriend",
+        "users",
+    )
+    .status(204),
+    untyped(
+        "delete",
+        "/api/v1/users/@me/relationships/{user_id}",
+        "removeRelationship",
+        "users",
+    )
+    .status(204),
+    untyped("post", "/api/v1/presences", "queryPresences", "users").with_body(),
+    untyped("get", "/api/v1/admin/stats", "adminGetStats", "admin"),
+    untyped(
+        "get",
+        "/api/v1/admin/security-events",
+        "adminListSecurityEvents",
+        "admin",
+    ),
+    untyped("get", "/api/v1/admin/settings", "adminGetSettings", "admin"),
+    untyped(
+        "patch",
+        "/api/v1/admin/settings",
+        "adminUpdateSettings",
+        "admin",
+    )
+    .with_body(),
+    untyped(
+        "put",
+        "/api/v1/admin/livekit/credentials",
+        "adminUpdateLivekitCredentials",
+        "admin",
+    )
+    .with_body(),
+    untyped("get", "/api/v1/admin/users", "adminListUsers", "admin"),
+    untyped(
+        "patch",
+        "/api/v1/admin/users/{user_id}",
+        "adminUpdateUser",
+        "admin",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/admin/users/{user_id}",
+        "adminDeleteUser",
+        "admin",
+    )
+    .status(204),
+    untyped(
+        "post",
+        "/api/v1/admin/users/{user_id}/impersonate",
+        "adminImpersonateUser",
+        "admin",
+    )
+    .with_body()
+    .status(201),
+    untyped(
+        "get",
+        "/api/v1/admin/users/{user_id}/sessions",
+        "adminListUserSessions",
+        "admin",
+    ),
+    untyped(
+        "post",
+        "/api/v1/admin/users/{user_id}/sessions/revoke-all",
+        "adminRevokeAllUserSessions",
+        "admin",
+    ),
+    untyped(
+        "post",
+        "/api/v1/admin/impersonation/stop",
+        "adminStopImpersonation",
+        "admin",
+    )
+    .status(204),
+    untyped("get", "/api/v1/admin/usage", "adminListUserUsage", "admin"),
+    untyped(
+        "delete",
+        "/api/v1/admin/usage/{user_id}",
+        "adminClearUserUsage",
+        "admin",
+    )
+    .status(204),
+    untyped("get", "/api/v1/admin/guilds", "adminListGuilds", "admin"),
+    untyped(
+        "patch",
+        "/api/v1/admin/guilds/{guild_id}",
+        "adminUpdateGuild",
+        "admin",
+    )
+    .with_body(),
+    untyped(
+        "delete",
+        "/api/v1/admin/guilds/{guild_id}",
+        "adminDeleteGuild",
+        "admin",
+    )
+    .status(204),
+    untyped(
+        "get",
+        "/api/v1/admin/federation/queue",
+        "adminListFederationQueue",
+        "admin",
+    ),
+    untyped(
+        "post",
+        "/api/v1/admin/federation/queue/{id}/retry",
+        "adminRetryFederationQueueEntry",
+        "admin",
+    ),
+    untyped(
+        "post",
+        "/api/v1/admin/restart-update",
+        "adminRestartUpdate",
+        "admin",
+    ),
+    untyped("post", "/api/v1/admin/backup", "adminCreateBackup", "admin").with_body(),
+    untyped("get", "/api/v1/admin/backups", "adminListBackups", "admin"),
+    untyped(
+        "post",
+        "/api/v1/admin/restore",
+        "adminRestoreBackup",
+        "admin",
+    )
+    .with_body(),
+    untyped(
+        "get",
+        "/api/v1/admin/backups/{name}",
+        "adminDownloadBackup",
+        "admin",
+    )
+    .returns(BINARY),
+    untyped(
+        "delete",
+        "/api/v1/admin/backups/{name}",
+        "adminDeleteBackup",
+        "admin",
+    )
+    .status(204),
+];
+
+/// Apply the generator's OpenAPI visitors (bool-schema replacement, `$ref`
+/// sibling removal) to a schema produced outside `root_schema_for`.
+fn finish_schema(gen: &mut SchemaGenerator, mut schema: Schema) -> Value {
+    for visitor in gen.visitors_mut() {
+        visitor.visit_schema(&mut schema);
+    }
+    serde_json::to_value(schema).unwrap_or_else(|_| json!({}))
+}
+
+/// Path parameters that are not snowflake ids.
+const STRING_PARAMETERS: &[&str] = &[
+    "code",
+    "emoji",
+    "name",
+    "origin_server",
+    "session_id",
+    "token",
+];
+
+fn path_parameters(path: &str) -> Vec<Value> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| {
+            let schema = if STRING_PARAMETERS.contains(&name) {
+                json!({ "type": "string" })
+            } else {
+                json!({ "type": "string", "pattern": "^[0-9]+$" })
+            };
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": schema,
+            })
+        })
+        .collect()
+}
+
+/// Flatten a query-string struct into one `in: query` parameter per field.
+fn query_parameters(gen: &mut SchemaGenerator, query: SchemaFn) -> Vec<Value> {
+    let schema = query(gen);
+    let Some(Schema::Object(SchemaObject {
+        object: Some(object),
+        ..
+    })) = gen.dereference(&schema).cloned()
+    else {
+        return Vec::new();
+    };
+    object
+        .properties
+        .into_iter()
+        .map(|(name, property)| {
+            let mut parameter = json!({
+                "name": name,
+                "in": "query",
+                "required": object.required.contains(&name),
+                "schema": finish_schema(gen, property.clone()),
+            });
+            if let Schema::Object(SchemaObject {
+                metadata: Some(metadata),
+                ..
+            }) = &property
+            {
+                if let Some(description) = &metadata.description {
+                    parameter["description"] = json!(description);
+                }
+            }
+            parameter
+        })
+        .collect()
+}
+
+fn json_content(schema: Value) -> Value {
+    json!({ JSON: { "schema": schema } })
+}
+
+/// Build the OpenAPI document for the routes in [`OPERATIONS`].
+pub fn build_spec() -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let mut paths = Map::new();
+
+    for op in OPERATIONS {
+        let mut parameters = path_parameters(op.path);
+        if let Some(query) = op.query {
+            parameters.extend(query_parameters(&mut gen, query));
+        }
+
+        let mut success = json!({ "description": "Success" });
+        if let Some(content_type) = op.content_type {
+            let response_schema = (op.response)(&mut gen);
+            success["content"] = json!({
+                content_type: { "schema": finish_schema(&mut gen, response_schema) },
+            });
+        }
+        let mut responses = Map::new();
+        responses.insert(op.status.to_string(), success);
+        responses.insert(
+            "default".to_string(),
+            json!({
+                "description": "Error",
+                "content": json_content(json!({ "$ref": "#/components/schemas/ApiError" })),
+            }),
+        );
+        let mut operation = json!({
+            "operationId": op.operation_id,
+            "tags": [op.tag],
+            "responses": responses,
+        });
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+        if let Some(request) = op.request {
+            let request_schema = request(&mut gen);
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": json_content(finish_schema(&mut gen, request_schema)),
+            });
+        }
+        if op.authenticated {
+            operation["security"] = json!([{ "bearerAuth": [] }]);
+        }
+
+        let entry = paths
+            .entry(op.path.to_string())
+            .or_insert_with(|| json!({}));
+        entry[op.method] = operation;
+    }
+
+    let mut schemas = Map::new();
+    for (name, schema) in gen.take_definitions() {
+        schemas.insert(name, finish_schema(&mut gen, schema));
+    }
+    schemas.insert(
+        "ApiError".to_string(),
+        json!({
+            "type": "object",
+            "required": ["code", "message"],
+            "properties": {
+                "code": { "type": "string" },
+                "message": { "type": "string" },
+                "error": { "type": "string" },
+            },
+        }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Paracord API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+            },
+        },
+    })
+}
+
+pub async fn openapi_json() -> Json<Value> {
+    static SPEC: OnceLock<Value> = OnceLock::new();
+    Json(SPEC.get_or_init(build_spec).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn component<'a>(spec: &'a Value, reference: &Value) -> &'a Value {
+        let name = reference["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/components/schemas/"))
+            .expect("schema is a component reference");
+        &spec["components"]["schemas"][name]
+    }
+
+    fn required(schema: &Value) -> Vec<&str> {
+        schema["required"]
+            .as_array()
+            .map(|fields| fields.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn login_describes_credentials_and_token_response() {
+        let spec = build_spec();
+        let login = &spec["paths"]["/api/v1/auth/login"]["post"];
+        assert!(login["security"].is_null());
+
+        let request = component(
+            &spec,
+            &login["requestBody"]["content"]["application/json"]["schema"],
+        );
+        assert!(request["properties"]["email"].is_object());
+        assert!(request["properties"]["password"].is_object());
+
+        let response = component(
+            &spec,
+            &login["responses"]["200"]["content"]["application/json"]["schema"],
+        );
+        assert!(required(response).contains(&"token"));
+        assert!(required(response).contains(&"user"));
+        assert_eq!(response["properties"]["refresh_token"]["nullable"], true);
+    }
+
+    #[test]
+    fn message_create_requires_content_and_path_id() {
+        let spec = build_spec();
+        let create = &spec["paths"]["/api/v1/channels/{channel_id}/messages"]["post"];
+        assert_eq!(create["security"][0]["bearerAuth"], json!([]));
+        assert_eq!(create["parameters"][0]["name"], "channel_id");
+        assert_eq!(create["parameters"][0]["in"], "path");
+        assert!(create["responses"]["201"].is_object());
+
+        let request = component(
+            &spec,
+            &create["requestBody"]["content"]["application/json"]["schema"],
+        );
+        assert_eq!(required(request), vec!["content"]);
+        assert_eq!(request["properties"]["attachment_ids"]["type"], "array");
+        assert!(request["properties"]["e2ee"].is_object());
+    }
+
+    #[test]
+    fn message_list_flattens_query_parameters() {
+        let spec = build_spec();
+        let list = &spec["paths"]["/api/v1/channels/{channel_id}/messages"]["get"];
+        let mut names: Vec<&str> = list["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|p| p["name"].as_str())
+            .collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            vec!["after_seq", "around", "before", "channel_id", "limit"]
+        );
+    }
+
+    #[test]
+    fn current_user_response_lists_account_fields() {
+        let spec = build_spec();
+        let me = &spec["paths"]["/api/v1/users/@me"]["get"];
+        assert!(me["parameters"].is_null());
+
+        let response = component(
+            &spec,
+            &me["responses"]["200"]["content"]["application/json"]["schema"],
+        );
+        for field in ["id", "username", "discriminator", "email", "flags", "bot"] {
+            assert!(required(response).contains(&field), "missing {field}");
+        }
+        assert_eq!(response["properties"]["id"]["type"], "string");
+        assert_eq!(response["properties"]["display_name"]["nullable"], true);
+    }
+
+    #[test]
+    fn every_reference_resolves_to_a_component() {
+        fn walk(spec: &Value, value: &Value) {
+            match value {
+                Value::Object(map) => {
+                    if let Some(reference) = map.get("$ref") {
+                        assert!(component(spec, &json!({ "$ref": reference })).is_object());
+                    }
+                    map.values().for_each(|v| walk(spec, v));
+                }
+                Value::Array(items) => items.iter().for_each(|v| walk(spec, v)),
+                _ => {}
+            }
+        }
+        let spec = build_spec();
+        walk(&spec, &spec);
+    }
+
+    /// Every `(method, path)` that `build_router` registers under `/api/v*`,
+    /// read from the router source so a new route cannot skip the table.
+    fn routed_operations() -> BTreeSet<(String, String)> {
+        const METHODS: &[&str] = &["get", "post", "put", "patch", "delete", "head"];
+        let source = include_str!("lib.rs");
+        let mut routes = BTreeSet::new();
+        for (start, _) in source.match_indices(".route(") {
+            let call = &source[start + ".route(".len()..];
+            let mut depth = 1;
+            let end = call
+                .char_indices()
+                .find(|&(_, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .map(|(i, _)| i)
+                .expect("balanced route call");
+            let call = &call[..end];
+            let path = call.split('"').nth(1).expect("route path literal");
+            if !path.starts_with("/api/v") {
+                continue;
+            }
+            let handlers = &call[call.find(',').expect("route handler")..];
+            for (i, _) in handlers.match_indices('(') {
+                let before = &handlers[..i];
+                let name_start = before
+                    .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+                    .map_or(0, |j| j + 1);
+                let name = &before[name_start..];
+                if METHODS.contains(&name) {
+                    routes.insert((name.to_string(), path.to_string()));
+                }
+            }
+        }
+        routes
+    }
+
+    #[test]
+    fn operations_cover_every_api_route() {
+        let routed = routed_operations();
+        assert!(
+            routed.len() > OPERATIONS.len() / 2,
+            "router parse found {routed:?}"
+        );
+        let documented: BTreeSet<_> = OPERATIONS
+            .iter()
+            .map(|op| (op.method.to_string(), op.path.to_string()))
+            .collect();
+        assert_eq!(documented.len(), OPERATIONS.len(), "duplicate operations");
+
+        let undocumented: Vec<_> = routed.difference(&documented).collect();
+        let stale: Vec<_> = documented.difference(&routed).collect();
+        assert!(
+            undocumented.is_empty() && stale.is_empty(),
+            "routes missing from OPERATIONS: {undocumented:?}; \
+             OPERATIONS entries with no route: {stale:?}"
+        );
+    }
+
+    #[test]
+    fn operation_ids_are_unique() {
+        let mut seen = BTreeSet::new();
+        for op in OPERATIONS {
+            assert!(seen.insert(op.operation_id), "{}", op.operation_id);
+        }
+    }
+
+    #[test]
+    fn empty_responses_have_no_content() {
+        let spec = build_spec();
+        let typing = &spec["paths"]["/api/v1/channels/{channel_id}/typing"]["post"];
+        assert!(typing["responses"]["204"].is_object());
+        assert!(typing["responses"]["204"]["content"].is_null());
+
+        let invite = &spec["paths"]["/api/v1/invites/{code}"]["get"];
+        assert!(invite["security"].is_null());
+        assert!(invite["parameters"][0]["schema"]["pattern"].is_null());
+    }
+}
@@ -0,0 +1,84 @@
+//! Web Push delivery for new messages.
+//!
+//! Push endpoints are supplied by clients, so the HTTP poster checks them
+//! with [`outbound_url::parse_target`] and pins the connection to addresses
+//! [`outbound_url::resolve_public`] accepted, like link previews do.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use paracord_core::push::{PushPayload, PushPostFn, PushPostFuture, PushRequest};
+use paracord_core::{AppState, MESSAGE_FLAG_EPHEMERAL, MESSAGE_FLAG_SILENT};
+use paracord_db::messages::MessageRow;
+
+use crate::outbound_url;
+
+/// Poster for [`paracord_core::push::PushNotifier`] that gives each push
+/// service `timeout` to answer.
+pub fn http_poster(timeout: Duration) -> PushPostFn {
+    Arc::new(move |request: PushRequest| -> PushPostFuture {
+        Box::pin(post_push(request, timeout))
+    })
+}
+
+async fn post_push(request: PushRequest, timeout: Duration) -> Result<u16, String> {
+    let url = outbound_url::parse_target(&request.endpoint)?;
+    let addrs = outbound_url::resolve_public(&url).await?;
+    let host = url.host_str().unwrap_or_default().to_string();
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(&host, &addrs)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut builder = client.post(url);
+    for (name, value) in request.headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(request.body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(response.status().as_u16())
+}
+
+/// Push a notification about a new message to offline recipients: every
+/// other participant of a DM, or the users `mentioned` in a guild channel.
+/// Does nothing when push is disabled or the message is silent.
+pub fn spawn_message_push(
+    state: &AppState,
+    message: &MessageRow,
+    guild_id: Option<i64>,
+    mentioned: &[i64],
+    dm_recipients: &[i64],
+) {
+    let Some(notifier) = state.push_notifier.clone() else {
+        return;
+    };
+    if message.flags & (MESSAGE_FLAG_SILENT | MESSAGE_FLAG_EPHEMERAL) != 0 {
+        return;
+    }
+    let (title, targets) = match guild_id {
+        None => ("New direct message", dm_recipients),
+        Some(_) => ("You were mentioned", mentioned),
+    };
+    let user_ids: Vec<i64> = targets
+        .iter()
+        .copied()
+        .filter(|id| *id != message.author_id)
+        .collect();
+    if user_ids.is_empty() {
+        return;
+    }
+    let payload = PushPayload {
+        title: title.to_string(),
+        channel_id: message.channel_id.to_string(),
+    };
+    let state = state.clone();
+    tokio::spawn(async move {
+        notifier
+            .notify_offline(&state.db, &state.online_users, &user_ids, &payload)
+            .await;
+    });
+}
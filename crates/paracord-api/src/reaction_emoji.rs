@@ -0,0 +1,124 @@
+//! Canonical keys for Unicode reaction emoji.
+//!
+//! Clients send the same emoji with and without variation selectors
+//! (`❤` vs `❤️`), which would otherwise split one reaction into several
+//! buckets. Reactions are stored under a key with U+FE0E/U+FE0F removed;
+//! the emoji exactly as sent is kept separately for display.
+//!
+//! Skin-tone modifiers are configurable with `PARACORD_REACTION_SKIN_TONES`
+//! (exported from `server.reaction_skin_tones`): `separate` (the default)
+//! keeps `👍` and `👍🏽` as different reactions, `merge` collapses every
+//! tone into the base emoji's bucket.
+
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+
+const TEXT_PRESENTATION_SELECTOR: char = '\u{FE0E}';
+const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+fn is_skin_tone_modifier(ch: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&ch)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinTonePolicy {
+    Separate,
+    Merge,
+}
+
+impl SkinTonePolicy {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "separate" => Some(Self::Separate),
+            "merge" => Some(Self::Merge),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let Ok(raw) = std::env::var("PARACORD_REACTION_SKIN_TONES") else {
+            return Self::Separate;
+        };
+        Self::parse(&raw).unwrap_or_else(|| {
+            tracing::warn!(
+                "Ignoring unknown PARACORD_REACTION_SKIN_TONES value {:?}; keeping skin tones separate",
+                raw
+            );
+            Self::Separate
+        })
+    }
+
+    /// The bucket key `emoji` is counted under.
+    pub fn canonicalize(self, emoji: &str) -> Result<String, ApiError> {
+        let key: String = emoji
+            .trim()
+            .chars()
+            .filter(|&ch| {
+                ch != TEXT_PRESENTATION_SELECTOR
+                    && ch != EMOJI_PRESENTATION_SELECTOR
+                    && !(self == Self::Merge && is_skin_tone_modifier(ch))
+            })
+            .collect();
+        if key.is_empty() {
+            return Err(ApiError::BadRequest("Invalid emoji".into()));
+        }
+        Ok(key)
+    }
+}
+
+static POLICY: OnceLock<SkinTonePolicy> = OnceLock::new();
+
+pub fn reaction_skin_tone_policy() -> SkinTonePolicy {
+    *POLICY.get_or_init(SkinTonePolicy::from_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variation_selectors_never_split_buckets() {
+        for policy in [SkinTonePolicy::Separate, SkinTonePolicy::Merge] {
+            assert_eq!(
+                policy.canonicalize("❤\u{FE0F}").unwrap(),
+                policy.canonicalize("❤").unwrap()
+            );
+            assert_eq!(
+                policy.canonicalize("☺\u{FE0E}").unwrap(),
+                policy.canonicalize("☺\u{FE0F}").unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn skin_tones_follow_policy() {
+        let separate = SkinTonePolicy::Separate;
+        assert_ne!(
+            separate.canonicalize("👍🏽").unwrap(),
+            separate.canonicalize("👍").unwrap()
+        );
+
+        let merge = SkinTonePolicy::Merge;
+        assert_eq!(merge.canonicalize("👍🏽").unwrap(), "👍");
+        assert_eq!(merge.canonicalize("👍🏿").unwrap(), "👍");
+        // ZWJ sequences keep their structure.
+        assert_eq!(merge.canonicalize("👩🏽\u{200D}💻").unwrap(), "👩\u{200D}💻");
+    }
+
+    #[test]
+    fn named_emoji_are_untouched() {
+        assert_eq!(SkinTonePolicy::Merge.canonicalize("fire").unwrap(), "fire");
+        assert_eq!(
+            SkinTonePolicy::Separate.canonicalize("party:123").unwrap(),
+            "party:123"
+        );
+    }
+
+    #[test]
+    fn rejects_selector_only_input() {
+        assert!(SkinTonePolicy::Separate.canonicalize("\u{FE0F}").is_err());
+        assert!(SkinTonePolicy::Merge.canonicalize("🏽").is_err());
+        assert!(SkinTonePolicy::parse("bogus").is_none());
+    }
+}
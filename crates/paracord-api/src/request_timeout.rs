@@ -0,0 +1,247 @@
+//! Per-request deadlines so slow handlers cannot pile up and hold database
+//! connections indefinitely.
+//!
+//! The handler future is dropped when the deadline passes, which cancels any
+//! query it is awaiting, and the client gets a 503. Only safe methods (GET,
+//! HEAD, OPTIONS) are bounded: write handlers often run several statements
+//! outside a transaction, and dropping one halfway would leave partial state
+//! behind, so they always run to completion. Exports, backups, downloads and
+//! the LiveKit proxy get the longer limit. Only the time to produce response
+//! headers is bounded: streamed bodies (attachment downloads, SSE) are not cut
+//! off mid-transfer.
+//!
+//! Limits come from `PARACORD_REQUEST_TIMEOUT_SECS` (default 30) and
+//! `PARACORD_LONG_REQUEST_TIMEOUT_SECS` (default 600); `0` disables a limit.
+
+use axum::{
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::error::ApiError;
+
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_LONG_REQUEST_TIMEOUT_SECS: u64 = 600;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequestTimeoutPolicy {
+    pub default: Option<Duration>,
+    pub long_running: Option<Duration>,
+}
+
+impl Default for RequestTimeoutPolicy {
+    fn default() -> Self {
+        Self {
+            default: Some(Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS)),
+            long_running: Some(Duration::from_secs(DEFAULT_LONG_REQUEST_TIMEOUT_SECS)),
+        }
+    }
+}
+
+fn env_timeout(name: &str, default_secs: u64) -> Option<Duration> {
+    let secs = match std::env::var(name) {
+        Ok(raw) => raw.trim().parse::<u64>().unwrap_or_else(|_| {
+            tracing::warn!("Ignoring non-numeric {}={:?}", name, raw);
+            default_secs
+        }),
+        Err(_) => default_secs,
+    };
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+impl RequestTimeoutPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            default: env_timeout("PARACORD_REQUEST_TIMEOUT_SECS", DEFAULT_REQUEST_TIMEOUT_SECS),
+            long_running: env_timeout(
+                "PARACORD_LONG_REQUEST_TIMEOUT_SECS",
+                DEFAULT_LONG_REQUEST_TIMEOUT_SECS,
+            ),
+        }
+    }
+
+    pub fn deadline_for(&self, method: &Method, path: &str) -> Option<Duration> {
+        if !method.is_safe() {
+            None
+        } else if is_long_running_path(path) {
+            self.long_running
+        } else {
+            self.default
+        }
+    }
+}
+
+static POLICY: OnceLock<RequestTimeoutPolicy> = OnceLock::new();
+
+fn policy() -> &'static RequestTimeoutPolicy {
+    POLICY.get_or_init(RequestTimeoutPolicy::from_env)
+}
+
+/// Routes that legitimately move a lot of data before responding.
+fn is_long_running_path(path: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "/api/v1/attachments/",
+        "/api/v1/federated-files/",
+        "/api/v1/uploads/",
+        "/api/v1/admin/backup",
+        "/api/v1/admin/restore",
+        "/api/v1/users/@me/data-export",
+        "/api/v1/users/@me/export",
+        "/livekit/",
+    ];
+    if PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+        return true;
+    }
+    // Uploads: /api/v1/channels/{id}/attachments and role/emoji image uploads.
+    path.starts_with("/api/v1/channels/") && path.ends_with("/attachments")
+        || path.starts_with("/api/v1/guilds/") && path.ends_with("/icon")
+        || path.starts_with("/api/v1/guilds/") && path.ends_with("/emojis")
+}
+
+/// Run the rest of the stack under `policy`'s deadline for this path.
+pub async fn enforce_deadline(policy: &RequestTimeoutPolicy, req: Request, next: Next) -> Response {
+    let Some(deadline) = policy.deadline_for(req.method(), req.uri().path()) else {
+        return next.run(req).await;
+    };
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    match tokio::time::timeout(deadline, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(
+                method = %method,
+                path = %path,
+                deadline_ms = deadline.as_millis() as u64,
+                "request exceeded its deadline and was cancelled"
+            );
+            ApiError::ServiceUnavailable("request timed out".into()).into_response()
+        }
+    }
+}
+
+pub async fn request_timeout_middleware(req: Request, next: Next) -> Response {
+    enforce_deadline(policy(), req, next).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware::from_fn, routing::get, Router};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+    use tower::ServiceExt;
+
+    fn app(policy: RequestTimeoutPolicy, finished: Arc<AtomicBool>) -> Router {
+        let slow = move || {
+            let finished = finished.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                finished.store(true, Ordering::SeqCst);
+                "done"
+            }
+        };
+        Router::new()
+            .route("/api/v1/slow", get(slow.clone()).post(slow.clone()))
+            .route("/api/v1/attachments/1", get(slow))
+            .layer(from_fn(move |req: Request, next: Next| async move {
+                enforce_deadline(&policy, req, next).await
+            }))
+    }
+
+    async fn send(app: Router, method: &str, uri: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn slow_handler_is_cut_off_at_the_deadline() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let policy = RequestTimeoutPolicy {
+            default: Some(Duration::from_millis(50)),
+            long_running: Some(Duration::from_secs(5)),
+        };
+
+        let started = Instant::now();
+        let status = send(app(policy, finished.clone()), "GET", "/api/v1/slow").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(started.elapsed() < Duration::from_millis(250));
+
+        // The handler was dropped, not left running in the background.
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn long_running_routes_use_the_higher_limit() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let policy = RequestTimeoutPolicy {
+            default: Some(Duration::from_millis(50)),
+            long_running: Some(Duration::from_secs(5)),
+        };
+
+        let status = send(
+            app(policy, finished.clone()),
+            "GET",
+            "/api/v1/attachments/1",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn writes_are_never_cancelled() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let policy = RequestTimeoutPolicy {
+            default: Some(Duration::from_millis(50)),
+            long_running: Some(Duration::from_millis(50)),
+        };
+
+        let status = send(app(policy, finished.clone()), "POST", "/api/v1/slow").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn zero_disables_the_deadline() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let policy = RequestTimeoutPolicy {
+            default: None,
+            long_running: None,
+        };
+
+        let status = send(app(policy, finished.clone()), "GET", "/api/v1/slow").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn classifies_long_running_paths() {
+        for path in [
+            "/api/v1/attachments/123",
+            "/api/v1/admin/backup",
+            "/api/v1/admin/backups/paracord.tar.gz",
+            "/api/v1/admin/restore",
+            "/api/v1/users/@me/data-export",
+            "/api/v1/channels/42/attachments",
+            "/api/v1/guilds/1/roles/2/icon",
+        ] {
+            assert!(is_long_running_path(path), "{path}");
+        }
+        for path in [
+            "/api/v1/channels/42/messages",
+            "/api/v1/users/@me",
+            "/api/v1/guilds/1",
+        ] {
+            assert!(!is_long_running_path(path), "{path}");
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! gzip/brotli compression for REST responses.
+//!
+//! Only textual bodies (JSON, text, JavaScript, XML) of at least
+//! `PARACORD_HTTP_COMPRESSION_MIN_BYTES` bytes (default 1024; the server
+//! exports it from `server.http_compression_min_bytes`) are encoded, and only
+//! when the client's `Accept-Encoding` allows it. File downloads (anything
+//! sent with `Content-Disposition`), media, and event streams pass through
+//! untouched so already-compressed payloads are not encoded twice.
+
+use axum::http::{header, Extensions, HeaderMap, StatusCode, Version};
+use std::sync::OnceLock;
+use tower_http::compression::{
+    predicate::{Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+const DEFAULT_MIN_COMPRESSED_BYTES: u16 = 1024;
+
+static MIN_COMPRESSED_BYTES: OnceLock<u16> = OnceLock::new();
+
+fn min_compressed_bytes() -> u16 {
+    *MIN_COMPRESSED_BYTES.get_or_init(|| {
+        match std::env::var("PARACORD_HTTP_COMPRESSION_MIN_BYTES") {
+            Ok(raw) => raw.trim().parse::<u16>().unwrap_or_else(|_| {
+                tracing::warn!(
+                    "Ignoring invalid PARACORD_HTTP_COMPRESSION_MIN_BYTES={:?}",
+                    raw
+                );
+                DEFAULT_MIN_COMPRESSED_BYTES
+            }),
+            Err(_) => DEFAULT_MIN_COMPRESSED_BYTES,
+        }
+    })
+}
+
+/// Whether a response with these headers is worth compressing, ignoring size.
+pub fn is_compressible(headers: &HeaderMap) -> bool {
+    if headers.contains_key(header::CONTENT_DISPOSITION)
+        || headers.contains_key(header::CONTENT_ENCODING)
+    {
+        return false;
+    }
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match essence.split_once('/') {
+        Some(("text", "event-stream")) => false,
+        Some(("text", _)) => true,
+        Some(("application", subtype)) => {
+            matches!(subtype, "json" | "javascript" | "xml") || subtype.ends_with("+json")
+        }
+        _ => false,
+    }
+}
+
+fn textual_response(
+    _status: StatusCode,
+    _version: Version,
+    headers: &HeaderMap,
+    _extensions: &Extensions,
+) -> bool {
+    is_compressible(headers)
+}
+
+pub fn compression_layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new()
+        .gzip(true)
+        .br(true)
+        .compress_when(SizeAbove::new(min_compressed_bytes()).and(textual_response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(header::HeaderName, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(name.clone(), HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn json_and_text_are_compressible() {
+        for content_type in [
+            "application/json",
+            "application/json; charset=utf-8",
+            "application/problem+json",
+            "text/html",
+            "application/javascript",
+        ] {
+            assert!(
+                is_compressible(&headers(&[(header::CONTENT_TYPE, content_type)])),
+                "{content_type}"
+            );
+        }
+    }
+
+    #[test]
+    fn downloads_media_and_streams_are_left_alone() {
+        assert!(!is_compressible(&headers(&[
+            (header::CONTENT_TYPE, "text/plain"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"notes.txt\""
+            ),
+        ])));
+        assert!(!is_compressible(&headers(&[
+            (header::CONTENT_TYPE, "application/json"),
+            (header::CONTENT_ENCODING, "gzip"),
+        ])));
+        for content_type in [
+            "image/png",
+            "video/mp4",
+            "application/zip",
+            "text/event-stream",
+        ] {
+            assert!(
+                !is_compressible(&headers(&[(header::CONTENT_TYPE, content_type)])),
+                "{content_type}"
+            );
+        }
+        assert!(!is_compressible(&HeaderMap::new()));
+    }
+}
@@ -0,0 +1,285 @@
+//! Personal access tokens: long-lived, scoped credentials a user mints for
+//! scripts and integrations acting as themselves, sent as
+//! `Authorization: Pat <token>`.
+//!
+//! Scopes are coarse: `read` allows safe methods (GET/HEAD/OPTIONS), `write`
+//! allows everything else. Account-security and admin endpoints never accept
+//! a PAT regardless of scope, so a leaked token cannot be used to mint more
+//! tokens, change credentials, or export the account.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, Method, StatusCode},
+    Json,
+};
+use chrono::Utc;
+use paracord_core::AppState;
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+use crate::routes::security;
+
+pub const TOKEN_PREFIX: &str = "pat_";
+pub const SCOPE_READ: &str = "read";
+pub const SCOPE_WRITE: &str = "write";
+const KNOWN_SCOPES: [&str; 2] = [SCOPE_READ, SCOPE_WRITE];
+
+const MAX_TOKEN_NAME_LEN: usize = 100;
+const MAX_TOKENS_PER_USER: i64 = 25;
+const MAX_EXPIRY_DAYS: i64 = 365;
+
+/// Paths a PAT may never touch, whatever its scopes.
+const PAT_FORBIDDEN_PREFIXES: [&str; 9] = [
+    "/api/v1/auth/",
+    "/api/v1/admin",
+    "/api/v1/users/@me/tokens",
+    "/api/v1/users/@me/mfa",
+    "/api/v1/users/@me/password",
+    "/api/v1/users/@me/email",
+    "/api/v1/users/@me/data-export",
+    "/api/v1/users/@me/export",
+    "/api/v1/users/@me/import",
+];
+
+fn is_read_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Whether a PAT carrying `scopes` may perform `method` on `path`.
+pub fn pat_allows(scopes: &[&str], method: &Method, path: &str) -> bool {
+    if PAT_FORBIDDEN_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    {
+        return false;
+    }
+    if path == "/api/v1/users/@me" && *method == Method::DELETE {
+        return false;
+    }
+    let required = if is_read_method(method) {
+        SCOPE_READ
+    } else {
+        SCOPE_WRITE
+    };
+    scopes.contains(&required)
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0_u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    let mut out = String::with_capacity(TOKEN_PREFIX.len() + bytes.len() * 2);
+    out.push_str(TOKEN_PREFIX);
+    for b in bytes {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+
+/// Validate requested scopes and return them deduplicated in canonical order.
+fn normalize_scopes(requested: &[String]) -> Result<Vec<&'static str>, ApiError> {
+    if requested.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one scope is required".into(),
+        ));
+    }
+    for scope in requested {
+        if !KNOWN_SCOPES.contains(&scope.as_str()) {
+            return Err(ApiError::BadRequest(format!("Unknown scope '{}'", scope)));
+        }
+    }
+    Ok(KNOWN_SCOPES
+        .into_iter()
+        .filter(|known| requested.iter().any(|s| s == known))
+        .collect())
+}
+
+fn token_to_json(row: &paracord_db::personal_access_tokens::PersonalAccessTokenRow) -> Value {
+    json!({
+        "id": row.id.to_string(),
+        "name": row.name,
+        "scopes": row.scope_list(),
+        "expires_at": row.expires_at.map(|t| t.to_rfc3339()),
+        "last_used_at": row.last_used_at.map(|t| t.to_rfc3339()),
+        "created_at": row.created_at.to_rfc3339(),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub password: String,
+    /// Current TOTP or recovery code; required once TOTP is enabled.
+    #[serde(default)]
+    pub mfa_code: Option<String>,
+    pub expires_in_days: Option<i64>,
+}
+
+pub async fn create_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(body): Json<CreateTokenRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TOKEN_NAME_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "Token name must be between 1 and {} characters",
+            MAX_TOKEN_NAME_LEN
+        )));
+    }
+    let scopes = normalize_scopes(&body.scopes)?;
+    if let Some(days) = body.expires_in_days {
+        if !(1..=MAX_EXPIRY_DAYS).contains(&days) {
+            return Err(ApiError::BadRequest(format!(
+                "expires_in_days must be between 1 and {}",
+                MAX_EXPIRY_DAYS
+            )));
+        }
+    }
+
+    let user = paracord_db::users::get_user_auth_by_id(&state.db, auth.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if user.password_hash.trim().is_empty() {
+        return Err(ApiError::Forbidden);
+    }
+    let valid =
+        paracord_core::auth::verify_password(&body.password, &user.password_hash).unwrap_or(false);
+    if !valid {
+        return Err(ApiError::Unauthorized);
+    }
+    // A token outlives the session that minted it, so it needs the same
+    // second factor as a login.
+    crate::routes::mfa::require_login_mfa(&state, auth.user_id, body.mfa_code.as_deref(), &headers)
+        .await?;
+
+    let existing = paracord_db::personal_access_tokens::count_user_tokens(&state.db, auth.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if existing >= MAX_TOKENS_PER_USER {
+        return Err(ApiError::BadRequest(format!(
+            "Maximum number of access tokens ({}) reached",
+            MAX_TOKENS_PER_USER
+        )));
+    }
+
+    let token = generate_token();
+    let token_hash = paracord_db::bot_applications::hash_token(&token);
+    let expires_at = body
+        .expires_in_days
+        .map(|days| Utc::now() + chrono::Duration::days(days));
+    let row = paracord_db::personal_access_tokens::create_token(
+        &state.db,
+        paracord_util::snowflake::generate(1),
+        auth.user_id,
+        name,
+        &token_hash,
+        &scopes.join(","),
+        expires_at,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    security::log_security_event(
+        &state,
+        "auth.pat.create",
+        Some(auth.user_id),
+        Some(auth.user_id),
+        auth.session_id.as_deref(),
+        Some(&headers),
+        Some(json!({ "token_id": row.id.to_string(), "scopes": scopes })),
+    )
+    .await;
+
+    let mut out = token_to_json(&row);
+    out["token"] = json!(token);
+    Ok((StatusCode::CREATED, Json(out)))
+}
+
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Value>, ApiError> {
+    let rows = paracord_db::personal_access_tokens::get_user_tokens(&state.db, auth.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    Ok(Json(Value::Array(rows.iter().map(token_to_json).collect())))
+}
+
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Path(token_id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let deleted =
+        paracord_db::personal_access_tokens::delete_token(&state.db, token_id, auth.user_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !deleted {
+        return Err(ApiError::NotFound);
+    }
+
+    security::log_security_event(
+        &state,
+        "auth.pat.revoke",
+        Some(auth.user_id),
+        Some(auth.user_id),
+        auth.session_id.as_deref(),
+        Some(&headers),
+        Some(json!({ "token_id": token_id.to_string() })),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_map_to_methods() {
+        let path = "/api/v1/channels/1/messages";
+        assert!(pat_allows(&[SCOPE_READ], &Method::GET, path));
+        assert!(!pat_allows(&[SCOPE_READ], &Method::POST, path));
+        assert!(pat_allows(&[SCOPE_WRITE], &Method::POST, path));
+        assert!(!pat_allows(&[SCOPE_WRITE], &Method::GET, path));
+        assert!(pat_allows(
+            &[SCOPE_READ, SCOPE_WRITE],
+            &Method::DELETE,
+            path
+        ));
+    }
+
+    #[test]
+    fn sensitive_paths_reject_every_scope() {
+        let all = [SCOPE_READ, SCOPE_WRITE];
+        assert!(!pat_allows(&all, &Method::POST, "/api/v1/users/@me/tokens"));
+        assert!(!pat_allows(&all, &Method::GET, "/api/v1/users/@me/tokens"));
+        assert!(!pat_allows(
+            &all,
+            &Method::PUT,
+            "/api/v1/users/@me/password"
+        ));
+        assert!(!pat_allows(&all, &Method::GET, "/api/v1/auth/sessions"));
+        assert!(!pat_allows(&all, &Method::GET, "/api/v1/admin/stats"));
+        assert!(!pat_allows(&all, &Method::DELETE, "/api/v1/users/@me"));
+        assert!(pat_allows(&all, &Method::PATCH, "/api/v1/users/@me"));
+    }
+
+    #[test]
+    fn normalize_scopes_rejects_unknown_and_dedupes() {
+        assert!(normalize_scopes(&[]).is_err());
+        assert!(normalize_scopes(&["admin".to_string()]).is_err());
+        let scopes =
+            normalize_scopes(&["write".to_string(), "read".to_string(), "write".to_string()])
+                .unwrap();
+        assert_eq!(scopes, vec![SCOPE_READ, SCOPE_WRITE]);
+    }
+}
@@ -11,9 +11,14 @@ use std::collections::HashMap;
 use tokio_util::io::ReaderStream;
 
 use crate::error::ApiError;
-use crate::middleware::AdminUser;
+use crate::middleware::{AdminUser, AuthUser};
+use crate::routes::auth::AuthSessionView;
 use crate::routes::security;
 
+/// Lifetime of an impersonation session. There is no refresh token, so the
+/// admin has to start a new session once it lapses.
+const IMPERSONATION_TTL_SECONDS: u64 = 15 * 60;
+
 // ── Restart & Update ─────────────────────────────────────────────────
 
 pub async fn restart_update(
@@ -56,6 +61,57 @@ pub struct SecurityEventsQuery {
     pub before: Option<i64>,
     pub limit: Option<i64>,
     pub action: Option<String>,
+    /// Alias for `action`; takes precedence when both are given.
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    /// Events where this user is either the actor or the target.
+    pub user_id: Option<i64>,
+}
+
+/// Detail keys whose values must never be shown back to admins, even though
+/// current callers avoid logging them.
+const REDACTED_DETAIL_KEY_PARTS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "authorization",
+    "cookie",
+    "otp",
+    "totp",
+    "recovery_code",
+    "private_key",
+    "signature",
+];
+
+fn is_sensitive_detail_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    // Identifiers of sensitive objects (e.g. `token_id`) are safe to show.
+    if key.ends_with("_id") || key.ends_with("_ids") {
+        return false;
+    }
+    REDACTED_DETAIL_KEY_PARTS
+        .iter()
+        .any(|part| key.contains(part))
+}
+
+fn redact_security_details(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    if is_sensitive_detail_key(&key) {
+                        (key, Value::String("[redacted]".to_string()))
+                    } else {
+                        (key, redact_security_details(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(redact_security_details).collect())
+        }
+        other => other,
+    }
 }
 
 pub async fn list_security_events(
@@ -64,9 +120,14 @@ pub async fn list_security_events(
     Query(params): Query<SecurityEventsQuery>,
 ) -> Result<Json<Value>, ApiError> {
     let limit = params.limit.unwrap_or(100).clamp(1, 500);
-    let rows = paracord_db::security_events::list_events(
+    let action = params
+        .event_type
+        .or(params.action)
+        .filter(|value| !value.trim().is_empty());
+    let rows = paracord_db::security_events::list_events_filtered(
         &state.db,
-        params.action.as_deref(),
+        action.as_deref(),
+        params.user_id,
         params.before,
         limit,
     )
@@ -85,7 +146,7 @@ pub async fn list_security_events(
                 "device_id": row.device_id,
                 "user_agent": row.user_agent,
                 "ip_address": row.ip_address,
-                "details": row.details,
+                "details": row.details.map(redact_security_details),
                 "created_at": row.created_at.to_rfc3339(),
             })
         })
@@ -133,7 +194,13 @@ pub async fn get_settings(
         "server_name": settings.server_name,
         "server_description": settings.server_description,
         "max_guilds_per_user": settings.max_guilds_per_user.to_string(),
+        "guild_creation_restricted": settings.guild_creation_restricted.to_string(),
         "max_members_per_guild": settings.max_members_per_guild.to_string(),
+        "max_webhooks_per_channel": settings.max_webhooks_per_channel.to_string(),
+        "max_webhooks_per_guild": settings.max_webhooks_per_guild.to_string(),
+        "require_email_verification": settings.require_email_verification.to_string(),
+        "blocked_name_patterns": settings.name_filter.source(),
+        "blocked_names_fold": settings.name_filter.fold().to_string(),
         "max_guild_storage_quota": max_guild_storage_quota,
         "federation_file_cache_enabled": federation_file_cache_enabled,
         "federation_file_cache_max_size": federation_file_cache_max_size,
@@ -146,7 +213,13 @@ const ALLOWED_SETTINGS: &[&str] = &[
     "server_name",
     "server_description",
     "max_guilds_per_user",
+    "guild_creation_restricted",
     "max_members_per_guild",
+    "max_webhooks_per_channel",
+    "max_webhooks_per_guild",
+    "require_email_verification",
+    "blocked_name_patterns",
+    "blocked_names_fold",
     "max_guild_storage_quota",
     "federation_file_cache_enabled",
     "federation_file_cache_max_size",
@@ -177,7 +250,10 @@ fn validate_setting(key: &str, value: &str) -> Result<(), String> {
                 ));
             }
         }
-        "max_guilds_per_user" | "max_members_per_guild" => {
+        "max_guilds_per_user"
+        | "max_members_per_guild"
+        | "max_webhooks_per_channel"
+        | "max_webhooks_per_guild" => {
             let n: u32 = value
                 .parse()
                 .map_err(|_| format!("{key}: must be a positive integer"))?;
@@ -190,7 +266,14 @@ fn validate_setting(key: &str, value: &str) -> Result<(), String> {
                 .parse()
                 .map_err(|_| format!("{key}: must be a positive integer"))?;
         }
-        "federation_file_cache_enabled" => {
+        "blocked_name_patterns" => {
+            paracord_core::name_filter::NameFilter::new(value, true)
+                .map_err(|e| format!("{key}: {e}"))?;
+        }
+        "guild_creation_restricted"
+        | "require_email_verification"
+        | "blocked_names_fold"
+        | "federation_file_cache_enabled" => {
             if value != "true" && value != "false" {
                 return Err(format!("{key}: must be \"true\" or \"false\""));
             }
@@ -220,6 +303,14 @@ pub async fn update_settings(
     for (key, value) in &body {
         validate_setting(key, value).map_err(ApiError::BadRequest)?;
     }
+    if body.get("require_email_verification").map(String::as_str) == Some("true")
+        && state.mailer.is_none()
+    {
+        return Err(ApiError::BadRequest(
+            "require_email_verification: needs a mail transport ([mail] in the server config)"
+                .into(),
+        ));
+    }
 
     let sanitized: HashMap<String, String> = body
         .into_iter()
@@ -257,14 +348,44 @@ pub async fn update_settings(
                     settings.max_guilds_per_user = v;
                 }
             }
+            "guild_creation_restricted" => {
+                settings.guild_creation_restricted = value == "true";
+            }
             "max_members_per_guild" => {
                 if let Ok(v) = value.parse() {
                     settings.max_members_per_guild = v;
                 }
             }
+            "max_webhooks_per_channel" => {
+                if let Ok(v) = value.parse() {
+                    settings.max_webhooks_per_channel = v;
+                }
+            }
+            "max_webhooks_per_guild" => {
+                if let Ok(v) = value.parse() {
+                    settings.max_webhooks_per_guild = v;
+                }
+            }
+            "require_email_verification" => {
+                settings.require_email_verification = value == "true";
+            }
             _ => {}
         }
     }
+    if sanitized.contains_key("blocked_name_patterns")
+        || sanitized.contains_key("blocked_names_fold")
+    {
+        let patterns = sanitized
+            .get("blocked_name_patterns")
+            .map(String::as_str)
+            .unwrap_or(settings.name_filter.source());
+        let fold = sanitized
+            .get("blocked_names_fold")
+            .map_or(settings.name_filter.fold(), |value| value == "true");
+        let filter = paracord_core::name_filter::NameFilter::new(patterns, fold)
+            .map_err(ApiError::BadRequest)?;
+        settings.name_filter = filter;
+    }
 
     let changed_keys: Vec<&str> = sanitized.keys().map(String::as_str).collect();
     security::log_security_event(
@@ -283,7 +404,87 @@ pub async fn update_settings(
         "server_name": settings.server_name,
         "server_description": settings.server_description,
         "max_guilds_per_user": settings.max_guilds_per_user.to_string(),
+        "guild_creation_restricted": settings.guild_creation_restricted.to_string(),
         "max_members_per_guild": settings.max_members_per_guild.to_string(),
+        "max_webhooks_per_channel": settings.max_webhooks_per_channel.to_string(),
+        "max_webhooks_per_guild": settings.max_webhooks_per_guild.to_string(),
+        "require_email_verification": settings.require_email_verification.to_string(),
+        "blocked_name_patterns": settings.name_filter.source(),
+        "blocked_names_fold": settings.name_filter.fold().to_string(),
+    })))
+}
+
+// ── LiveKit ─────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct UpdateLiveKitCredentialsRequest {
+    pub api_key: String,
+    pub api_secret: String,
+}
+
+/// Rotate the LiveKit API key and secret without a restart. The new pair is
+/// checked against LiveKit's admin API first; if that fails nothing changes
+/// and the current credentials stay in use. Not persisted: update
+/// `[livekit]` in the config file too so a restart keeps the new keys.
+pub async fn update_livekit_credentials(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    headers: HeaderMap,
+    Json(body): Json<UpdateLiveKitCredentialsRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let api_key = body.api_key.trim();
+    let api_secret = body.api_secret.trim();
+    if api_key.is_empty() || api_secret.is_empty() {
+        return Err(ApiError::BadRequest(
+            "api_key and api_secret are required".into(),
+        ));
+    }
+
+    let current = state.voice.livekit();
+    let candidate = std::sync::Arc::new(paracord_media::LiveKitConfig {
+        api_key: api_key.to_string(),
+        api_secret: api_secret.to_string(),
+        url: current.url.clone(),
+        http_url: current.http_url.clone(),
+    });
+    if let Err(err) = candidate.check_health().await {
+        security::log_security_event(
+            &state,
+            "admin.livekit.credentials.rejected",
+            Some(admin.user_id),
+            None,
+            None,
+            Some(&headers),
+            Some(json!({ "api_key": api_key })),
+        )
+        .await;
+        return Err(ApiError::BadRequest(format!(
+            "LiveKit rejected the new credentials: {err}"
+        )));
+    }
+
+    state.voice.set_livekit(candidate);
+    if state.set_livekit_available(true) {
+        state.event_bus.dispatch(
+            paracord_models::gateway::EVENT_VOICE_AVAILABILITY_UPDATE,
+            json!({ "livekit_available": true }),
+            None,
+        );
+    }
+    security::log_security_event(
+        &state,
+        "admin.livekit.credentials.update",
+        Some(admin.user_id),
+        None,
+        None,
+        Some(&headers),
+        Some(json!({ "previous_api_key": current.api_key, "api_key": api_key })),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "api_key": api_key,
+        "livekit_available": true,
     })))
 }
 
@@ -409,6 +610,405 @@ pub async fn delete_user(
     Ok(StatusCode::NO_CONTENT)
 }
 
+// ── Usage ───────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct UsageQuery {
+    pub limit: Option<usize>,
+}
+
+pub async fn list_user_usage(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Query(params): Query<UsageQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let limit = params.limit.unwrap_or(100).clamp(1, 500);
+    let thresholds = state.usage.thresholds();
+    let users: Vec<Value> = state
+        .usage
+        .snapshot(limit)
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "user_id": entry.user_id.to_string(),
+                "messages_per_minute": entry.messages_per_minute,
+                "uploads_per_minute": entry.uploads_per_minute,
+                "upload_bytes_per_hour": entry.upload_bytes_per_hour,
+                "flagged_until": entry.flagged_until.and_then(|ts| {
+                    chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.to_rfc3339())
+                }),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "thresholds": {
+            "messages_per_minute": thresholds.messages_per_minute,
+            "uploads_per_minute": thresholds.uploads_per_minute,
+            "upload_bytes_per_hour": thresholds.upload_bytes_per_hour,
+            "auto_timeout_seconds": thresholds.auto_timeout_seconds,
+        },
+        "users": users,
+    })))
+}
+
+pub async fn clear_user_usage(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    headers: HeaderMap,
+    Path(user_id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    if !state.usage.clear(user_id) {
+        return Err(ApiError::NotFound);
+    }
+    security::log_security_event(
+        &state,
+        "admin.user.usage.clear",
+        Some(admin.user_id),
+        Some(user_id),
+        None,
+        Some(&headers),
+        None,
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Federation queue ────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct FederationQueueQuery {
+    /// `pending` or `dead_lettered`; both when omitted.
+    pub state: Option<String>,
+    pub before: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+fn millis_to_rfc3339(ms: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp_millis(ms).map(|dt| dt.to_rfc3339())
+}
+
+fn federation_queue_entry_json(row: &paracord_db::federation::OutboundQueueEntryRow) -> Value {
+    let dead_lettered = row.dead_lettered_at_ms.is_some();
+    json!({
+        "id": row.id.to_string(),
+        "destination_server": row.destination_server,
+        "event_id": row.event_id,
+        "room_id": row.room_id,
+        "event_type": row.event_type,
+        "state": if dead_lettered { "dead_lettered" } else { "pending" },
+        "attempt_count": row.attempt_count,
+        // Dead letters are not retried until an admin requeues them.
+        "next_attempt_at": if dead_lettered {
+            None
+        } else {
+            millis_to_rfc3339(row.next_attempt_at_ms)
+        },
+        "last_error": row.last_error,
+        "created_at": millis_to_rfc3339(row.created_at_ms),
+        "updated_at": millis_to_rfc3339(row.updated_at_ms),
+        "dead_lettered_at": row.dead_lettered_at_ms.and_then(millis_to_rfc3339),
+    })
+}
+
+pub async fn list_federation_queue(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Query(params): Query<FederationQueueQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let dead_lettered = match params.state.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some("pending") => Some(false),
+        Some("dead_lettered") => Some(true),
+        Some(_) => {
+            return Err(ApiError::BadRequest(
+                "state must be 'pending' or 'dead_lettered'".into(),
+            ))
+        }
+    };
+    let limit = params.limit.unwrap_or(100).clamp(1, 500);
+    let rows = paracord_db::federation::list_outbound_queue_entries(
+        &state.db,
+        dead_lettered,
+        params.before,
+        limit,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    Ok(Json(json!(rows
+        .iter()
+        .map(federation_queue_entry_json)
+        .collect::<Vec<_>>())))
+}
+
+pub async fn retry_federation_queue_entry(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let row = paracord_db::federation::requeue_dead_lettered_outbound_event(&state.db, id, now_ms)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    security::log_security_event(
+        &state,
+        "admin.federation.queue.retry",
+        Some(admin.user_id),
+        None,
+        None,
+        Some(&headers),
+        Some(json!({
+            "queue_id": row.id.to_string(),
+            "destination_server": row.destination_server,
+            "event_id": row.event_id,
+        })),
+    )
+    .await;
+    Ok(Json(federation_queue_entry_json(&row)))
+}
+
+// ── Sessions ────────────────────────────────────────────────────────────
+
+pub async fn list_user_sessions(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(user_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    paracord_db::users::get_user_by_id(&state.db, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let sessions =
+        paracord_db::sessions::list_user_sessions(&state.db, user_id, chrono::Utc::now())
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let mapped: Vec<AuthSessionView> = sessions
+        .iter()
+        .map(|session| AuthSessionView {
+            id: session.id.clone(),
+            current: false,
+            device_id: session.device_id.clone(),
+            user_agent: session.user_agent.clone(),
+            ip_address: session.ip_address.clone(),
+            issued_at: session.issued_at.to_rfc3339(),
+            last_seen_at: session.last_seen_at.to_rfc3339(),
+            expires_at: session.expires_at.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(Json(json!(mapped)))
+}
+
+/// Force-logout: revokes every session of the user and drops their gateway
+/// connections. Refresh tokens stop working, so clients must log in again.
+pub async fn revoke_all_user_sessions(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    headers: HeaderMap,
+    Path(user_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    if user_id == admin.user_id {
+        return Err(ApiError::BadRequest(
+            "Cannot revoke your own sessions here".into(),
+        ));
+    }
+    paracord_db::users::get_user_by_id(&state.db, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+
+    let revoked = paracord_db::sessions::revoke_all_user_sessions_except(
+        &state.db,
+        user_id,
+        None,
+        "admin_revoke_all",
+        chrono::Utc::now(),
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    // Personal access tokens are long-lived credentials of their own, so a
+    // force-logout that left them working would not contain a compromise.
+    let tokens_revoked =
+        paracord_db::personal_access_tokens::delete_user_tokens(&state.db, user_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    state.event_bus.dispatch_to_users(
+        paracord_models::gateway::EVENT_SESSIONS_REVOKED,
+        json!({ "user_id": user_id.to_string() }),
+        vec![user_id],
+    );
+
+    security::log_security_event(
+        &state,
+        "admin.user.sessions.revoke_all",
+        Some(admin.user_id),
+        Some(user_id),
+        None,
+        Some(&headers),
+        Some(json!({ "revoked": revoked, "tokens_revoked": tokens_revoked })),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "revoked": revoked,
+        "tokens_revoked": tokens_revoked,
+    })))
+}
+
+// ── Impersonation ───────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct ImpersonateRequest {
+    pub reason: Option<String>,
+    /// Required to impersonate another server admin.
+    #[serde(default)]
+    pub confirm_admin: bool,
+}
+
+pub async fn impersonate_user(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    headers: HeaderMap,
+    Path(user_id): Path<i64>,
+    Json(body): Json<ImpersonateRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if user_id == admin.user_id {
+        return Err(ApiError::BadRequest("Cannot impersonate yourself".into()));
+    }
+    let reason = body
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.chars().take(512).collect::<String>());
+
+    let target = paracord_db::users::get_user_by_id(&state.db, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let target_is_admin = paracord_core::is_admin(target.flags);
+    if target_is_admin && !body.confirm_admin {
+        security::log_security_event(
+            &state,
+            "admin.impersonation.denied",
+            Some(admin.user_id),
+            Some(user_id),
+            None,
+            Some(&headers),
+            Some(json!({ "reason": "target_is_admin" })),
+        )
+        .await;
+        return Err(ApiError::BadRequest(
+            "Impersonating another admin requires confirm_admin".into(),
+        ));
+    }
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let jti = uuid::Uuid::new_v4().to_string();
+    // Refresh hashes are hex SHA-256 digests, so this value can never match a
+    // presented refresh token; the session cannot be extended.
+    let refresh_token_hash = format!("impersonation:{}", uuid::Uuid::new_v4().simple());
+    let expires_at =
+        chrono::Utc::now() + chrono::Duration::seconds(IMPERSONATION_TTL_SECONDS as i64);
+    let user_agent = headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    let ip_address = crate::client_ip::client_ip_from_headers(&headers);
+
+    paracord_db::sessions::create_session(
+        &state.db,
+        &session_id,
+        user_id,
+        &refresh_token_hash,
+        &jti,
+        None,
+        Some("impersonation"),
+        user_agent,
+        ip_address.as_deref(),
+        expires_at,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let token = paracord_core::auth::create_impersonation_token(
+        user_id,
+        admin.user_id,
+        &state.config.jwt_secret,
+        IMPERSONATION_TTL_SECONDS,
+        &session_id,
+        &jti,
+    )
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    security::log_security_event(
+        &state,
+        "admin.impersonation.start",
+        Some(admin.user_id),
+        Some(user_id),
+        Some(&session_id),
+        Some(&headers),
+        Some(json!({
+            "reason": reason,
+            "target_is_admin": target_is_admin,
+            "expires_at": expires_at.to_rfc3339(),
+        })),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "token": token,
+            "session_id": session_id,
+            "user_id": user_id.to_string(),
+            "impersonator_id": admin.user_id.to_string(),
+            "impersonation": true,
+            "expires_at": expires_at.to_rfc3339(),
+        })),
+    ))
+}
+
+/// End the impersonation session the request is authenticated with.
+pub async fn stop_impersonation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let (Some(impersonator_id), Some(session_id)) =
+        (auth.impersonator_id, auth.session_id.as_deref())
+    else {
+        return Err(ApiError::BadRequest("Not an impersonation session".into()));
+    };
+
+    paracord_db::sessions::revoke_session(
+        &state.db,
+        session_id,
+        auth.user_id,
+        "impersonation_stopped",
+        chrono::Utc::now(),
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    security::log_security_event(
+        &state,
+        "admin.impersonation.stop",
+        Some(impersonator_id),
+        Some(auth.user_id),
+        Some(session_id),
+        Some(&headers),
+        None,
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ── Guilds ──────────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -416,6 +1016,8 @@ pub struct UpdateGuildRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub icon: Option<String>,
+    /// Replaces the guild's feature flags, e.g. `["ROLE_ICONS"]`.
+    pub features: Option<Vec<String>>,
 }
 
 pub async fn list_guilds(
@@ -435,6 +1037,7 @@ pub async fn list_guilds(
                 "description": g.description,
                 "icon_hash": g.icon_hash,
                 "owner_id": g.owner_id.to_string(),
+                "features": paracord_models::guild::guild_feature_names(g.features),
                 "created_at": g.created_at.to_rfc3339(),
             })
         })
@@ -450,7 +1053,14 @@ pub async fn update_guild(
     Path(guild_id): Path<i64>,
     Json(body): Json<UpdateGuildRequest>,
 ) -> Result<Json<Value>, ApiError> {
-    let updated = paracord_core::admin::admin_update_guild(
+    let features = body
+        .features
+        .as_deref()
+        .map(paracord_models::guild::guild_feature_bits)
+        .transpose()
+        .map_err(|name| ApiError::BadRequest(format!("Unknown guild feature: {name}")))?;
+
+    let mut updated = paracord_core::admin::admin_update_guild(
         &state.db,
         guild_id,
         body.name.as_deref(),
@@ -458,6 +1068,11 @@ pub async fn update_guild(
         body.icon.as_deref(),
     )
     .await?;
+    if let Some(features) = features {
+        updated = paracord_db::guilds::set_space_features(&state.db, guild_id, features)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
 
     let guild_json = json!({
         "id": updated.id.to_string(),
@@ -465,6 +1080,7 @@ pub async fn update_guild(
         "description": updated.description,
         "icon_hash": updated.icon_hash,
         "owner_id": updated.owner_id.to_string(),
+        "features": paracord_models::guild::guild_feature_names(updated.features),
         "created_at": updated.created_at.to_rfc3339(),
     });
 
@@ -692,6 +1308,8 @@ mod tests {
     #[test]
     fn validate_setting_rejects_unknown_bool_value() {
         assert!(validate_setting("registration_enabled", "maybe").is_err());
+        assert!(validate_setting("guild_creation_restricted", "yes").is_err());
+        assert!(validate_setting("guild_creation_restricted", "true").is_ok());
     }
 
     #[test]
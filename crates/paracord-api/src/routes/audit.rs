@@ -1,5 +1,5 @@
 use paracord_core::AppState;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 pub const ACTION_GUILD_UPDATE: i16 = 1;
 pub const ACTION_CHANNEL_CREATE: i16 = 10;
@@ -14,6 +14,14 @@ pub const ACTION_ROLE_UPDATE: i16 = 31;
 pub const ACTION_ROLE_DELETE: i16 = 32;
 pub const ACTION_INVITE_CREATE: i16 = 40;
 pub const ACTION_INVITE_DELETE: i16 = 41;
+pub const ACTION_WEBHOOK_CREATE: i16 = 50;
+pub const ACTION_WEBHOOK_UPDATE: i16 = 51;
+pub const ACTION_WEBHOOK_DELETE: i16 = 52;
+pub const ACTION_FEDERATED_MEMBER_JOIN: i16 = 60;
+pub const ACTION_FEDERATED_MEMBER_LEAVE: i16 = 61;
+pub const ACTION_FEDERATED_MEDIA_RELAY: i16 = 62;
+pub const ACTION_INTEGRATION_CREATE: i16 = 80;
+pub const ACTION_INTEGRATION_DELETE: i16 = 82;
 
 pub async fn log_action(
     state: &AppState,
@@ -41,3 +49,62 @@ pub async fn log_action(
         tracing::warn!("failed to write audit entry: {}", err);
     }
 }
+
+/// Record an action driven by a remote server. The actor is the remote
+/// user's local shadow account; `changes.federation` names where it came from.
+/// Actions caused by a federation event pass its `event_id`, so a redelivered
+/// event is recorded only once.
+#[allow(clippy::too_many_arguments)]
+pub async fn log_federated_action(
+    state: &AppState,
+    guild_id: i64,
+    actor_id: i64,
+    action_type: i16,
+    target_id: Option<i64>,
+    origin_server: &str,
+    remote_user: &str,
+    event_id: Option<&str>,
+    details: Option<Value>,
+) {
+    let mut changes = json!({
+        "federation": {
+            "origin_server": origin_server,
+            "remote_user": remote_user,
+        },
+    });
+    if let Some(event_id) = event_id {
+        changes["federation"]["event_id"] = json!(event_id);
+    }
+    if let (Some(Value::Object(details)), Some(target)) = (details, changes.as_object_mut()) {
+        target.extend(details);
+    }
+    let reason = format!("Federated from {origin_server}");
+    let Some(event_id) = event_id else {
+        log_action(
+            state,
+            guild_id,
+            actor_id,
+            action_type,
+            target_id,
+            Some(&reason),
+            Some(changes),
+        )
+        .await;
+        return;
+    };
+    if let Err(err) = paracord_db::audit_log::create_federated_entry(
+        &state.db,
+        paracord_util::snowflake::generate(1),
+        guild_id,
+        actor_id,
+        action_type,
+        target_id,
+        Some(&reason),
+        Some(&changes),
+        event_id,
+    )
+    .await
+    {
+        tracing::warn!("failed to write audit entry: {}", err);
+    }
+}
@@ -19,6 +19,7 @@ use uuid::Uuid;
 
 use crate::error::ApiError;
 use crate::middleware::AuthUser;
+use crate::routes::mfa::LoginMfa;
 use crate::routes::security;
 
 const REFRESH_COOKIE_NAME: &str = "paracord_refresh";
@@ -28,8 +29,8 @@ const ACCESS_COOKIE_PATH: &str = "/api/v1";
 const CHALLENGE_STORE_MAX_ENTRIES: usize = 10_000;
 const MAX_DISPLAY_NAME_LEN: usize = 64;
 const AUTH_GUARD_TTL_SECONDS: i64 = 3600;
-const AUTH_GUARD_CLEANUP_LIMIT: i64 = 512;
 const MAX_LOGIN_BODY_BYTES: usize = 16 * 1024;
+const DISCRIMINATOR_ALLOCATION_ATTEMPTS: usize = 8;
 
 // In-memory challenge nonce store (nonce -> timestamp). Cleaned up on each request.
 static CHALLENGE_STORE: OnceLock<Mutex<HashMap<String, i64>>> = OnceLock::new();
@@ -69,46 +70,12 @@ fn constant_time_equal(a: &str, b: &str) -> bool {
     diff == 0
 }
 
-fn trust_proxy_headers() -> bool {
-    std::env::var("PARACORD_TRUST_PROXY")
-        .ok()
-        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
-        .unwrap_or(false)
-}
-
 fn proxy_peer_is_trusted(peer_ip: Option<&str>) -> bool {
-    if !trust_proxy_headers() {
-        return false;
-    }
-    let Some(peer_ip) = peer_ip else {
-        return false;
-    };
-    let trusted = std::env::var("PARACORD_TRUSTED_PROXY_IPS")
-        .ok()
-        .map(|raw| {
-            raw.split(',')
-                .map(str::trim)
-                .filter(|v| !v.is_empty())
-                .map(str::to_string)
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
-    !trusted.is_empty() && trusted.iter().any(|ip| ip == peer_ip)
+    crate::client_ip::peer_is_trusted_proxy(peer_ip)
 }
 
 fn resolve_client_ip(headers: &HeaderMap, peer_ip: Option<&str>) -> String {
-    if proxy_peer_is_trusted(peer_ip) {
-        if let Some(ip) = headers
-            .get("x-forwarded-for")
-            .and_then(|v| v.to_str().ok())
-            .and_then(|raw| raw.split(',').next())
-            .map(str::trim)
-            .filter(|v| !v.is_empty())
-        {
-            return ip.to_string();
-        }
-    }
-    peer_ip.unwrap_or("unknown").to_string()
+    crate::client_ip::resolve_client_ip(headers, peer_ip).unwrap_or_else(|| "unknown".to_string())
 }
 
 fn auth_guard_keys(
@@ -164,18 +131,12 @@ async fn auth_guard_maybe_cleanup(state: &AppState, now: i64) {
         return;
     }
     let cutoff = now.saturating_sub(AUTH_GUARD_TTL_SECONDS);
-    if let Err(err) = paracord_db::rate_limits::purge_auth_guard_older_than(
-        &state.db,
-        cutoff,
-        AUTH_GUARD_CLEANUP_LIMIT,
-    )
-    .await
-    {
+    if let Err(err) = state.rate_limits.purge_auth_guard_older_than(cutoff).await {
         tracing::warn!("auth-guard cleanup failed: {}", err);
     }
 }
 
-async fn auth_guard_enforce(
+pub(crate) async fn auth_guard_enforce(
     state: &AppState,
     headers: &HeaderMap,
     peer_ip: Option<&str>,
@@ -183,7 +144,9 @@ async fn auth_guard_enforce(
 ) -> Result<(), ApiError> {
     let now = Utc::now().timestamp();
     let keys = auth_guard_keys(headers, peer_ip, account_hint);
-    let rows = paracord_db::rate_limits::get_auth_guard_states(&state.db, &keys)
+    let rows = state
+        .rate_limits
+        .auth_guard_states(&keys)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     let locked = rows.iter().any(|row| row.locked_until > now);
@@ -195,7 +158,7 @@ async fn auth_guard_enforce(
     Ok(())
 }
 
-async fn auth_guard_record_failure(
+pub(crate) async fn auth_guard_record_failure(
     state: &AppState,
     headers: &HeaderMap,
     peer_ip: Option<&str>,
@@ -204,27 +167,165 @@ async fn auth_guard_record_failure(
     let now = Utc::now().timestamp();
     let keys = auth_guard_keys(headers, peer_ip, account_hint);
     for key in keys {
-        if let Err(err) =
-            paracord_db::rate_limits::record_auth_guard_failure(&state.db, &key, now).await
-        {
+        if let Err(err) = state.rate_limits.record_auth_guard_failure(&key, now).await {
             tracing::warn!("auth-guard failure update failed for '{}': {}", key, err);
         }
     }
     auth_guard_maybe_cleanup(state, now).await;
 }
 
-async fn auth_guard_record_success(
+pub(crate) async fn auth_guard_record_success(
     state: &AppState,
     headers: &HeaderMap,
     peer_ip: Option<&str>,
     account_hint: Option<&str>,
 ) {
     let keys = auth_guard_keys(headers, peer_ip, account_hint);
-    if let Err(err) = paracord_db::rate_limits::clear_auth_guard_keys(&state.db, &keys).await {
+    if let Err(err) = state.rate_limits.clear_auth_guard_keys(&keys).await {
         tracing::warn!("auth-guard success clear failed: {}", err);
     }
 }
 
+/// Refuse the login while this source IP and identifier pair is locked out
+/// after too many failed password verifications.
+async fn login_lockout_enforce(state: &AppState, key: &str) -> Result<(), ApiError> {
+    let policy = paracord_core::auth::LoginLockoutPolicy::from_config(&state.config);
+    let retry_after = paracord_core::auth::login_lockout_retry_after(
+        &state.rate_limits,
+        policy,
+        key,
+        Utc::now().timestamp(),
+    )
+    .await?;
+    match retry_after {
+        Some(retry_after) => Err(ApiError::LoginLocked { retry_after }),
+        None => Ok(()),
+    }
+}
+
+async fn login_lockout_record_failure(
+    state: &AppState,
+    headers: &HeaderMap,
+    key: &str,
+    user_id: Option<i64>,
+) {
+    let policy = paracord_core::auth::LoginLockoutPolicy::from_config(&state.config);
+    let locked = match paracord_core::auth::record_login_failure(
+        &state.rate_limits,
+        policy,
+        key,
+        Utc::now().timestamp(),
+    )
+    .await
+    {
+        Ok(locked) => locked,
+        Err(err) => {
+            tracing::warn!("login lockout failure update failed: {}", err);
+            return;
+        }
+    };
+    if locked {
+        security::log_security_event(
+            state,
+            "auth.login.lockout",
+            None,
+            user_id,
+            None,
+            Some(headers),
+            Some(json!({
+                "max_failures": policy.max_failures,
+                "lockout_seconds": policy.lockout_seconds,
+            })),
+        )
+        .await;
+    }
+}
+
+/// Store a verification token for `user_id` and mail it to `email`.
+/// Delivery runs in the background, so response times do not depend on it;
+/// failures are logged and the user can ask for another message.
+async fn issue_email_verification(
+    state: &AppState,
+    user_id: i64,
+    email: &str,
+) -> Result<(), ApiError> {
+    let Some(mailer) = state.mailer.clone() else {
+        tracing::warn!(
+            user_id,
+            "email verification required but no mail transport is configured"
+        );
+        return Ok(());
+    };
+    let token = paracord_core::auth::generate_email_verification_token();
+    let expires_at =
+        Utc::now() + Duration::hours(paracord_core::auth::EMAIL_VERIFICATION_TTL_HOURS);
+    paracord_db::users::create_email_verification(
+        &state.db,
+        user_id,
+        &paracord_core::auth::hash_email_verification_token(&token),
+        expires_at,
+    )
+    .await?;
+    let server_name = state.runtime.read().await.server_name.clone();
+    let delivery = mailer.send(mailer.email_verification(email, &server_name, &token));
+    tokio::spawn(async move {
+        if let Err(err) = delivery.await {
+            tracing::warn!(user_id, "email verification delivery failed: {}", err);
+        }
+    });
+    Ok(())
+}
+
+/// Run `create` with the lowest free discriminator for `username`, picking
+/// the next one when a concurrent signup claims it first.
+///
+/// In handle mode (`unique_usernames`) the name itself must be free and the
+/// discriminator is always 0.
+async fn create_user_with_free_discriminator<F, Fut>(
+    state: &AppState,
+    username: &str,
+    mut create: F,
+) -> Result<paracord_db::users::UserRow, ApiError>
+where
+    F: FnMut(i16) -> Fut,
+    Fut: std::future::Future<Output = Result<paracord_db::users::UserRow, paracord_db::DbError>>,
+{
+    if state.config.unique_usernames {
+        // Handles are kept unique by `idx_users_handle`; this lookup only
+        // catches names held by accounts made before handle mode was enabled.
+        let existing = paracord_db::users::get_user_by_username_only(&state.db, username)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        if existing.is_some() {
+            return Err(ApiError::Conflict("Username is already taken".into()));
+        }
+        return match create(0).await {
+            Ok(user) => Ok(user),
+            Err(err)
+                if paracord_db::users::is_handle_conflict(&err)
+                    || paracord_db::users::is_discriminator_conflict(&err) =>
+            {
+                Err(ApiError::Conflict("Username is already taken".into()))
+            }
+            Err(err) => Err(ApiError::Internal(anyhow::anyhow!(err.to_string()))),
+        };
+    }
+    for _ in 0..DISCRIMINATOR_ALLOCATION_ATTEMPTS {
+        let discriminator = paracord_db::users::allocate_discriminator(&state.db, username)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .ok_or_else(|| ApiError::Conflict("Too many users have this username".into()))?;
+        match create(discriminator).await {
+            Ok(user) => return Ok(user),
+            Err(err) if paracord_db::users::is_discriminator_conflict(&err) => continue,
+            Err(err) => return Err(ApiError::Internal(anyhow::anyhow!(err.to_string()))),
+        }
+    }
+    Err(ApiError::Conflict(
+        "Could not allocate a discriminator; try again".into(),
+    ))
+}
+
 fn refresh_session_ttl_days() -> i64 {
     std::env::var("PARACORD_REFRESH_SESSION_TTL_DAYS")
         .ok()
@@ -241,7 +342,7 @@ fn username_login_effective(allow_username_login: bool, require_email: bool) ->
     allow_username_login || !require_email
 }
 
-fn normalize_login_identifier_for_auth(value: &str) -> String {
+pub(crate) fn normalize_login_identifier_for_auth(value: &str) -> String {
     value.trim().to_ascii_lowercase()
 }
 
@@ -286,15 +387,22 @@ fn parse_login_json_value(value: Value) -> Option<LoginRequest> {
         .unwrap_or_default()
         .to_string();
 
+    let mfa_code = source
+        .get("mfa_code")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
     Some(LoginRequest {
         email: identifier,
         password,
+        mfa_code,
     })
 }
 
 fn parse_login_form_value(body: &[u8]) -> Option<LoginRequest> {
     let mut identifier = String::new();
     let mut password = String::new();
+    let mut mfa_code = None;
 
     for (key, value) in url::form_urlencoded::parse(body) {
         match key.as_ref() {
@@ -304,6 +412,9 @@ fn parse_login_form_value(body: &[u8]) -> Option<LoginRequest> {
             "password" | "passphrase" if password.is_empty() => {
                 password = value.into_owned();
             }
+            "mfa_code" if mfa_code.is_none() => {
+                mfa_code = Some(value.into_owned());
+            }
             _ => {}
         }
     }
@@ -315,6 +426,7 @@ fn parse_login_form_value(body: &[u8]) -> Option<LoginRequest> {
     Some(LoginRequest {
         email: identifier,
         password,
+        mfa_code,
     })
 }
 
@@ -442,7 +554,7 @@ fn default_server_scheme_from_env() -> &'static str {
     "http"
 }
 
-fn resolve_server_origin(
+pub(crate) fn resolve_server_origin(
     configured_public_url: Option<&str>,
     headers: &HeaderMap,
     peer_ip: Option<&str>,
@@ -541,7 +653,7 @@ fn get_cookie_value(headers: &HeaderMap, cookie_name: &str) -> Option<String> {
     None
 }
 
-fn random_token_hex(bytes: usize) -> String {
+pub(crate) fn random_token_hex(bytes: usize) -> String {
     let mut buf = vec![0u8; bytes];
     rand::thread_rng().fill_bytes(&mut buf);
     let mut out = String::with_capacity(bytes * 2);
@@ -562,7 +674,7 @@ fn sha256_hex(value: &str) -> String {
     out
 }
 
-fn header_value(value: &str) -> Result<HeaderValue, ApiError> {
+pub(crate) fn header_value(value: &str) -> Result<HeaderValue, ApiError> {
     HeaderValue::from_str(value)
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("invalid header value: {}", e)))
 }
@@ -589,7 +701,7 @@ fn request_metadata(
 
 /// Result of issuing a new auth session:
 /// (access_token, access_cookie, refresh_cookie, session_id, raw_refresh_token)
-async fn issue_auth_session(
+pub(crate) async fn issue_auth_session(
     state: &AppState,
     user_id: i64,
     public_key: Option<&str>,
@@ -656,6 +768,13 @@ async fn rotate_auth_session(
     if session.revoked_at.is_some() || session.expires_at <= now {
         return Err(ApiError::Unauthorized);
     }
+    if state
+        .config
+        .session_idle_cutoff(now)
+        .is_some_and(|cutoff| session.last_seen_at <= cutoff)
+    {
+        return Err(ApiError::Unauthorized);
+    }
 
     let new_refresh = random_token_hex(48);
     let new_refresh_hash = sha256_hex(&new_refresh);
@@ -713,7 +832,7 @@ fn user_json(user: &paracord_db::users::UserRow) -> Value {
     })
 }
 
-fn user_auth_json(user: &paracord_db::users::UserAuthRow) -> Value {
+pub(crate) fn user_auth_json(user: &paracord_db::users::UserAuthRow) -> Value {
     json!({
         "id": user.id.to_string(),
         "username": user.username,
@@ -729,6 +848,40 @@ fn user_auth_json(user: &paracord_db::users::UserAuthRow) -> Value {
     })
 }
 
+/// Look up the account a normalized login identifier (email, `name#0001`, or
+/// bare username when username login is enabled) refers to.
+pub(crate) async fn resolve_login_user(
+    state: &AppState,
+    normalized_identifier: &str,
+) -> Result<Option<paracord_db::users::UserAuthRow>, ApiError> {
+    let allow_username_login = username_login_effective(
+        state.config.allow_username_login,
+        state.config.require_email,
+    );
+    let user = if allow_username_login && !normalized_identifier.contains('@') {
+        if let Some((username, discriminator)) =
+            parse_username_with_discriminator(normalized_identifier)
+        {
+            paracord_db::users::get_user_auth_by_username(&state.db, username, discriminator)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        } else {
+            paracord_db::users::get_user_auth_by_username_only(&state.db, normalized_identifier)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        }
+    } else {
+        let normalized_email = normalize_email_for_auth(normalized_identifier);
+        if paracord_util::validation::validate_email(&normalized_email).is_err() {
+            return Ok(None);
+        }
+        paracord_db::users::get_user_by_email(&state.db, &normalized_email)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    };
+    Ok(user)
+}
+
 async fn auto_join_public_spaces(state: &AppState, user_id: i64) -> Result<(), ApiError> {
     let spaces = paracord_db::guilds::list_all_spaces(&state.db)
         .await
@@ -737,6 +890,13 @@ async fn auto_join_public_spaces(state: &AppState, user_id: i64) -> Result<(), A
         s.visibility == "public"
             && paracord_db::guilds::parse_allowed_role_ids(&s.allowed_roles).is_empty()
     }) {
+        // Skip the space when the ban lookup fails rather than joining.
+        if paracord_db::bans::is_banned(&state.db, user_id, space.id, Utc::now())
+            .await
+            .unwrap_or(true)
+        {
+            continue;
+        }
         let _ = paracord_db::members::add_member(&state.db, user_id, space.id).await;
         let _ = paracord_db::roles::add_member_role(&state.db, user_id, space.id, space.id).await;
     }
@@ -744,6 +904,7 @@ async fn auto_join_public_spaces(state: &AppState, user_id: i64) -> Result<(), A
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct RegisterRequest {
     #[serde(default)]
     pub email: String,
@@ -753,14 +914,19 @@ pub struct RegisterRequest {
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct LoginRequest {
     #[serde(default, alias = "identifier", alias = "username", alias = "login")]
     pub email: String,
     #[serde(default)]
     pub password: String,
+    /// TOTP code or recovery code; required once TOTP is confirmed.
+    #[serde(default)]
+    pub mfa_code: Option<String>,
 }
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct AuthResponse {
     pub token: String,
     pub user: Value,
@@ -783,9 +949,14 @@ pub struct AuthSessionView {
 }
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct AuthOptionsResponse {
     pub allow_username_login: bool,
     pub require_email: bool,
+    pub unique_usernames: bool,
+    pub require_email_verification: bool,
+    /// Whether `forgot-password` can mail reset links.
+    pub password_reset_available: bool,
 }
 
 pub async fn auth_options(State(state): State<AppState>) -> Json<AuthOptionsResponse> {
@@ -796,9 +967,204 @@ pub async fn auth_options(State(state): State<AppState>) -> Json<AuthOptionsResp
     Json(AuthOptionsResponse {
         allow_username_login,
         require_email: state.config.require_email,
+        unique_usernames: state.config.unique_usernames,
+        require_email_verification: state.runtime.read().await.require_email_verification,
+        password_reset_available: state.mailer.is_some(),
     })
 }
 
+#[derive(Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+/// Consume a one-time email verification token.
+pub async fn verify_email(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<VerifyEmailRequest>,
+) -> Result<StatusCode, ApiError> {
+    let token_hash = paracord_core::auth::hash_email_verification_token(&body.token);
+    let user_id =
+        paracord_db::users::consume_email_verification(&state.db, &token_hash, Utc::now())
+            .await?
+            .ok_or_else(|| ApiError::BadRequest("Invalid or expired verification token".into()))?;
+    security::log_security_event(
+        &state,
+        "auth.email.verified",
+        Some(user_id),
+        Some(user_id),
+        None,
+        Some(&headers),
+        None,
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ResendEmailVerificationRequest {
+    pub email: String,
+}
+
+/// Mail a fresh verification link to an unverified account, replacing any
+/// earlier one. Always 204 so the response does not reveal whether the
+/// address is registered.
+pub async fn resend_email_verification(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<ResendEmailVerificationRequest>,
+) -> Result<StatusCode, ApiError> {
+    let email = body.email.trim().to_ascii_lowercase();
+    let client_ip = resolve_client_ip(&headers, Some(addr.ip().to_string().as_str()));
+    let now = Utc::now().timestamp();
+    if !state
+        .rate_limits
+        .check(&format!("verify-resend:ip:{client_ip}"), 3600, 10, now)
+        .await
+        || !state
+            .rate_limits
+            .check(&format!("verify-resend:email:{email}"), 3600, 3, now)
+            .await
+    {
+        return Err(ApiError::RateLimited);
+    }
+    if email.is_empty() || !state.runtime.read().await.require_email_verification {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    let Some(user) = paracord_db::users::get_user_by_email(&state.db, &email).await? else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
+    if user.email_verified_at.is_some() {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    paracord_db::users::delete_user_email_verifications(&state.db, user.id).await?;
+    issue_email_verification(&state, user.id, &user.email).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+/// Mail a password reset link to the account with `email`. Always 204 so
+/// the response does not reveal whether the address is registered.
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<ForgotPasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    let Some(mailer) = state.mailer.clone() else {
+        return Err(ApiError::ServiceUnavailable(
+            "Password reset by email is not available on this server".into(),
+        ));
+    };
+    let email = body.email.trim().to_ascii_lowercase();
+    let client_ip = resolve_client_ip(&headers, Some(addr.ip().to_string().as_str()));
+    let now = Utc::now().timestamp();
+    if !state
+        .rate_limits
+        .check(&format!("password-reset:ip:{client_ip}"), 3600, 10, now)
+        .await
+        || !state
+            .rate_limits
+            .check(&format!("password-reset:email:{email}"), 3600, 3, now)
+            .await
+    {
+        return Err(ApiError::RateLimited);
+    }
+    if email.is_empty() {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    let Some(user) = paracord_db::users::get_user_by_email(&state.db, &email).await? else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
+    if user.password_hash.trim().is_empty() {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    let token = paracord_core::auth::generate_password_reset_token();
+    let expires_at =
+        Utc::now() + Duration::minutes(paracord_core::auth::PASSWORD_RESET_TTL_MINUTES);
+    paracord_db::users::create_password_reset(
+        &state.db,
+        user.id,
+        &paracord_core::auth::hash_password_reset_token(&token),
+        expires_at,
+    )
+    .await?;
+    let server_name = state.runtime.read().await.server_name.clone();
+    let delivery = mailer.send(mailer.password_reset(&user.email, &server_name, &token));
+    let user_id = user.id;
+    tokio::spawn(async move {
+        if let Err(err) = delivery.await {
+            tracing::warn!(user_id, "password reset delivery failed: {}", err);
+        }
+    });
+    security::log_security_event(
+        &state,
+        "auth.password.reset_requested",
+        Some(user.id),
+        Some(user.id),
+        None,
+        Some(&headers),
+        None,
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Consume a password reset token, set the new password and sign the
+/// account out everywhere.
+pub async fn reset_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    paracord_util::validation::validate_password(&body.new_password).map_err(|_| {
+        ApiError::BadRequest("Password must be between 10 and 128 characters".into())
+    })?;
+    let token_hash = paracord_core::auth::hash_password_reset_token(&body.token);
+    let user_id = paracord_db::users::consume_password_reset(&state.db, &token_hash, Utc::now())
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Invalid or expired reset token".into()))?;
+
+    let new_hash = paracord_core::auth::hash_password(&body.new_password)
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    paracord_db::users::update_user_password_hash(&state.db, user_id, &new_hash).await?;
+
+    let revoked = paracord_db::sessions::revoke_all_user_sessions_except(
+        &state.db,
+        user_id,
+        None,
+        "password_reset",
+        Utc::now(),
+    )
+    .await?;
+    paracord_db::personal_access_tokens::delete_user_tokens(&state.db, user_id).await?;
+
+    security::log_security_event(
+        &state,
+        "auth.password.reset",
+        Some(user_id),
+        Some(user_id),
+        None,
+        Some(&headers),
+        Some(json!({ "revoked_sessions": revoked })),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 pub async fn register(
     State(state): State<AppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
@@ -844,6 +1210,7 @@ pub async fn register(
             "Username must be between 2 and 32 valid characters".into(),
         ));
     }
+    security::ensure_name_allowed(&state, "username", &body.username, None, Some(&headers)).await?;
     if state.config.require_email && normalized_email.is_empty() {
         auth_guard_record_failure(
             &state,
@@ -882,6 +1249,19 @@ pub async fn register(
             "Server requires email login or username login support".into(),
         ));
     }
+    let require_email_verification = state.runtime.read().await.require_email_verification;
+    if require_email_verification && normalized_email.is_empty() {
+        auth_guard_record_failure(
+            &state,
+            &headers,
+            Some(peer_ip.as_str()),
+            Some(&account_hint),
+        )
+        .await;
+        return Err(ApiError::BadRequest(
+            "An email address is required to verify this account".into(),
+        ));
+    }
     paracord_util::validation::validate_password(&body.password).map_err(|_| {
         ApiError::BadRequest("Password must be between 10 and 128 characters".into())
     })?;
@@ -914,17 +1294,20 @@ pub async fn register(
     } else {
         normalized_email.clone()
     };
-    let mut user = paracord_db::users::create_user_as_first_admin(
-        &state.db,
-        id,
-        &body.username,
-        0,
-        &resolved_email,
-        &password_hash,
-        paracord_core::USER_FLAG_ADMIN,
-    )
-    .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let mut user = create_user_with_free_discriminator(&state, &body.username, |discriminator| {
+        paracord_db::users::create_user_as_first_admin(
+            &state.db,
+            id,
+            &body.username,
+            discriminator,
+            &resolved_email,
+            &password_hash,
+            paracord_core::USER_FLAG_ADMIN,
+            require_email_verification,
+            state.config.unique_usernames,
+        )
+    })
+    .await?;
 
     auto_join_public_spaces(&state, user.id).await?;
 
@@ -939,6 +1322,36 @@ pub async fn register(
             .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     }
 
+    // The first user becomes admin and is verified on creation.
+    if require_email_verification && !paracord_core::is_admin(user.flags) {
+        issue_email_verification(&state, user.id, &user.email).await?;
+        security::log_security_event(
+            &state,
+            "auth.register.password",
+            Some(user.id),
+            Some(user.id),
+            None,
+            Some(&headers),
+            Some(json!({ "auth_method": "password", "email_verification": "pending" })),
+        )
+        .await;
+        auth_guard_record_success(
+            &state,
+            &headers,
+            Some(peer_ip.as_str()),
+            Some(&account_hint),
+        )
+        .await;
+        return Ok((
+            StatusCode::CREATED,
+            Json(json!({
+                "user": user_json(&user),
+                "email_verification_required": true,
+            })),
+        )
+            .into_response());
+    }
+
     let (token, access_cookie, refresh_cookie, session_id, raw_refresh) = issue_auth_session(
         &state,
         user.id,
@@ -976,7 +1389,8 @@ pub async fn register(
             user: user_json(&user),
             refresh_token: Some(raw_refresh),
         }),
-    ))
+    )
+        .into_response())
 }
 
 pub async fn login(
@@ -1013,38 +1427,13 @@ pub async fn login(
         return Err(ApiError::Unauthorized);
     }
 
-    let allow_username_login = username_login_effective(
-        state.config.allow_username_login,
-        state.config.require_email,
+    let resolved_user = resolve_login_user(&state, &normalized_identifier).await?;
+
+    let lockout_key = paracord_core::auth::login_lockout_key(
+        &resolve_client_ip(&headers, Some(peer_ip.as_str())),
+        &normalized_identifier,
     );
-    let resolved_user = if allow_username_login && !normalized_identifier.contains('@') {
-        if let Some((username, discriminator)) =
-            parse_username_with_discriminator(&normalized_identifier)
-        {
-            paracord_db::users::get_user_auth_by_username(&state.db, username, discriminator)
-                .await
-                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-        } else {
-            paracord_db::users::get_user_auth_by_username_only(&state.db, &normalized_identifier)
-                .await
-                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-        }
-    } else {
-        let normalized_email = normalize_email_for_auth(&normalized_identifier);
-        if paracord_util::validation::validate_email(&normalized_email).is_err() {
-            auth_guard_record_failure(
-                &state,
-                &headers,
-                Some(peer_ip.as_str()),
-                Some(&normalized_identifier),
-            )
-            .await;
-            return Err(ApiError::Unauthorized);
-        }
-        paracord_db::users::get_user_by_email(&state.db, &normalized_email)
-            .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-    };
+    login_lockout_enforce(&state, &lockout_key).await?;
 
     let Some(user) = resolved_user else {
         auth_guard_record_failure(
@@ -1054,6 +1443,7 @@ pub async fn login(
             Some(&normalized_identifier),
         )
         .await;
+        login_lockout_record_failure(&state, &headers, &lockout_key, None).await;
         return Err(ApiError::Unauthorized);
     };
     if user.password_hash.trim().is_empty() {
@@ -1064,6 +1454,7 @@ pub async fn login(
             Some(&normalized_identifier),
         )
         .await;
+        login_lockout_record_failure(&state, &headers, &lockout_key, Some(user.id)).await;
         return Err(ApiError::Unauthorized);
     }
 
@@ -1078,9 +1469,30 @@ pub async fn login(
             Some(&normalized_identifier),
         )
         .await;
+        login_lockout_record_failure(&state, &headers, &lockout_key, Some(user.id)).await;
         return Err(ApiError::Unauthorized);
     }
 
+    paracord_core::auth::ensure_email_verified(&user, &*state.runtime.read().await)
+        .map_err(|_| ApiError::EmailUnverified)?;
+
+    match crate::routes::mfa::check_login_mfa(&state, user.id, body.mfa_code.as_deref(), &headers)
+        .await?
+    {
+        LoginMfa::Satisfied | LoginMfa::RecoveryCodeUsed { .. } => {}
+        LoginMfa::Required => return Err(ApiError::MfaRequired),
+        LoginMfa::Invalid => {
+            auth_guard_record_failure(
+                &state,
+                &headers,
+                Some(peer_ip.as_str()),
+                Some(&normalized_identifier),
+            )
+            .await;
+            return Err(ApiError::Unauthorized);
+        }
+    }
+
     let (token, access_cookie, refresh_cookie, session_id, raw_refresh) = issue_auth_session(
         &state,
         user.id,
@@ -1106,6 +1518,11 @@ pub async fn login(
         Some(&normalized_identifier),
     )
     .await;
+    if let Err(err) =
+        paracord_core::auth::clear_login_failures(&state.rate_limits, &lockout_key).await
+    {
+        tracing::warn!("login lockout clear failed: {}", err);
+    }
 
     Ok((
         AppendHeaders([
@@ -1424,6 +1841,9 @@ pub struct VerifyRequest {
     pub signature: String,
     pub username: String,
     pub display_name: Option<String>,
+    /// TOTP code or recovery code; required once TOTP is confirmed.
+    #[serde(default)]
+    pub mfa_code: Option<String>,
 }
 
 pub async fn verify(
@@ -1562,19 +1982,32 @@ pub async fn verify(
                 .await;
                 return Err(ApiError::Forbidden);
             }
+            // A key-only account has no address to verify.
+            if state.runtime.read().await.require_email_verification {
+                return Err(ApiError::BadRequest(
+                    "Email verification is required; register with an email address".into(),
+                ));
+            }
+
+            security::ensure_name_allowed(&state, "username", &body.username, None, Some(&headers))
+                .await?;
 
             // Auto-register: create new user from public key.
             let id = paracord_util::snowflake::generate(1);
-            let new_user = paracord_db::users::create_user_from_pubkey_as_first_admin(
-                &state.db,
-                id,
-                &body.public_key,
-                &body.username,
-                normalized_display_name.as_deref(),
-                paracord_core::USER_FLAG_ADMIN,
-            )
-            .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+            let new_user =
+                create_user_with_free_discriminator(&state, &body.username, |discriminator| {
+                    paracord_db::users::create_user_from_pubkey_as_first_admin(
+                        &state.db,
+                        id,
+                        &body.public_key,
+                        &body.username,
+                        discriminator,
+                        normalized_display_name.as_deref(),
+                        paracord_core::USER_FLAG_ADMIN,
+                        state.config.unique_usernames,
+                    )
+                })
+                .await?;
 
             auto_join_public_spaces(&state, new_user.id).await?;
 
@@ -1582,6 +2015,30 @@ pub async fn verify(
         }
     };
 
+    // Password accounts can attach a key; that must not skip verification.
+    let auth_row = paracord_db::users::get_user_auth_by_id(&state.db, user.id)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    paracord_core::auth::ensure_email_verified(&auth_row, &*state.runtime.read().await)
+        .map_err(|_| ApiError::EmailUnverified)?;
+
+    match crate::routes::mfa::check_login_mfa(&state, user.id, body.mfa_code.as_deref(), &headers)
+        .await?
+    {
+        LoginMfa::Satisfied | LoginMfa::RecoveryCodeUsed { .. } => {}
+        LoginMfa::Required => return Err(ApiError::MfaRequired),
+        LoginMfa::Invalid => {
+            auth_guard_record_failure(
+                &state,
+                &headers,
+                Some(peer_ip.as_str()),
+                Some(&body.public_key),
+            )
+            .await;
+            return Err(ApiError::Unauthorized);
+        }
+    }
+
     let (token, access_cookie, refresh_cookie, session_id, raw_refresh) = issue_auth_session(
         &state,
         user.id,
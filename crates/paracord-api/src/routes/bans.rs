@@ -12,6 +12,8 @@ use crate::middleware::AuthUser;
 use crate::routes::audit;
 
 const MAX_BAN_REASON_LEN: usize = 512;
+/// Longest temporary ban; anything longer should be a permanent ban.
+const MAX_BAN_DURATION_SECONDS: u64 = 10 * 365 * 24 * 3600;
 
 fn contains_dangerous_markup(value: &str) -> bool {
     let lower = value.to_ascii_lowercase();
@@ -59,6 +61,7 @@ pub async fn list_bans(
                 "reason": b.reason,
                 "banned_by": b.banned_by.map(|id| id.to_string()),
                 "created_at": b.created_at.to_rfc3339(),
+                "expires_at": b.expires_at.map(|t| t.to_rfc3339()),
             })
         })
         .collect();
@@ -69,6 +72,9 @@ pub async fn list_bans(
 #[derive(Deserialize)]
 pub struct BanRequest {
     pub reason: Option<String>,
+    /// Lift the ban automatically after this many seconds; absent or null
+    /// bans permanently.
+    pub duration_seconds: Option<u64>,
 }
 
 pub async fn ban_member(
@@ -77,7 +83,9 @@ pub async fn ban_member(
     Path((guild_id, user_id)): Path<(i64, i64)>,
     body: Option<Json<BanRequest>>,
 ) -> Result<StatusCode, ApiError> {
-    let reason = body.and_then(|b| b.0.reason);
+    let (reason, duration_seconds) = body
+        .map(|Json(b)| (b.reason, b.duration_seconds))
+        .unwrap_or_default();
     if let Some(reason_text) = reason.as_deref() {
         if reason_text.trim().len() > MAX_BAN_REASON_LEN {
             return Err(ApiError::BadRequest("Ban reason is too long".into()));
@@ -88,12 +96,25 @@ pub async fn ban_member(
             ));
         }
     }
+    let expires_at = match duration_seconds {
+        None => None,
+        Some(0) => {
+            return Err(ApiError::BadRequest(
+                "duration_seconds must be positive".into(),
+            ))
+        }
+        Some(seconds) if seconds > MAX_BAN_DURATION_SECONDS => {
+            return Err(ApiError::BadRequest("Ban duration is too long".into()));
+        }
+        Some(seconds) => Some(chrono::Utc::now() + chrono::Duration::seconds(seconds as i64)),
+    };
     paracord_core::admin::ban_member(
         &state.db,
         guild_id,
         auth.user_id,
         user_id,
         reason.as_deref(),
+        expires_at,
     )
     .await?;
 
@@ -102,6 +123,7 @@ pub async fn ban_member(
         json!({
             "guild_id": guild_id.to_string(),
             "user_id": user_id.to_string(),
+            "expires_at": expires_at.map(|t| t.to_rfc3339()),
         }),
         Some(guild_id),
     );
@@ -136,7 +158,7 @@ pub async fn ban_member(
         audit::ACTION_MEMBER_BAN_ADD,
         Some(user_id),
         reason.as_deref(),
-        None,
+        duration_seconds.map(|seconds| json!({ "duration_seconds": seconds })),
     )
     .await;
 
@@ -171,3 +193,53 @@ pub async fn unban_member(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Lift temporary bans whose expiry has passed, announcing each with
+/// `GUILD_BAN_REMOVE` and an audit entry credited to whoever issued the ban.
+/// Returns how many bans were lifted.
+pub async fn expire_temp_bans(state: &AppState) -> Result<usize, ApiError> {
+    let now = chrono::Utc::now();
+    let expired = paracord_db::bans::get_expired_bans(&state.db, now, 256)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let mut lifted = 0;
+    for ban in expired {
+        let removed =
+            paracord_db::bans::delete_expired_ban(&state.db, ban.user_id, ban.guild_id, now)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        if !removed {
+            continue;
+        }
+        lifted += 1;
+        state.event_bus.dispatch(
+            "GUILD_BAN_REMOVE",
+            json!({
+                "guild_id": ban.guild_id.to_string(),
+                "user_id": ban.user_id.to_string(),
+            }),
+            Some(ban.guild_id),
+        );
+        let actor_id = match ban.banned_by {
+            Some(id) => Some(id),
+            None => paracord_db::guilds::get_guild(&state.db, ban.guild_id)
+                .await
+                .ok()
+                .flatten()
+                .map(|guild| guild.owner_id),
+        };
+        if let Some(actor_id) = actor_id {
+            audit::log_action(
+                state,
+                ban.guild_id,
+                actor_id,
+                audit::ACTION_MEMBER_BAN_REMOVE,
+                Some(ban.user_id),
+                Some("Temporary ban expired"),
+                None,
+            )
+            .await;
+        }
+    }
+    Ok(lifted)
+}
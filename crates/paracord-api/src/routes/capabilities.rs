@@ -0,0 +1,16 @@
+use axum::Json;
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
+pub struct CapabilitiesResponse {
+    /// Locales accepted in user settings.
+    pub locales: Vec<String>,
+}
+
+/// Server options clients need before offering choices to the user.
+pub async fn get_capabilities() -> Json<CapabilitiesResponse> {
+    Json(CapabilitiesResponse {
+        locales: crate::locales::allowed_locales().to_vec(),
+    })
+}
@@ -1,24 +1,37 @@
+use std::collections::{HashMap, HashSet};
+
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
-use paracord_core::{AppState, MESSAGE_FLAG_DM_E2EE};
+use paracord_core::{
+    events::ServerEvent, AppState, MESSAGE_FLAG_CROSSPOSTED, MESSAGE_FLAG_DM_E2EE,
+    MESSAGE_FLAG_SILENT, MESSAGE_FLAG_SUPPRESS_EMBEDS, MESSAGE_FLAG_TTS,
+};
 use paracord_models::permissions::Permissions;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::error::ApiError;
 use crate::middleware::AuthUser;
+use crate::reaction_emoji::reaction_skin_tone_policy;
 use crate::routes::audit;
 
 const MAX_CHANNEL_TOPIC_LEN: usize = 1_024;
+const MAX_VOICE_USER_LIMIT: i32 = 99;
+/// Longest slowmode a channel can have: six hours.
+const MAX_RATE_LIMIT_PER_USER: i32 = 21_600;
 const MAX_BULK_DELETE_REQUEST_IDS: usize = 500;
 const MAX_POLL_QUESTION_LEN: usize = 300;
 const MAX_POLL_OPTION_LEN: usize = 100;
 const MAX_POLL_OPTIONS: usize = 10;
 const MAX_POLL_DURATION_MINUTES: i64 = 60 * 24 * 14; // 14 days
 const MAX_MESSAGE_NONCE_LEN: usize = 64;
+const MAX_ALLOWED_MENTION_IDS: usize = 100;
+const MAX_UNIQUE_REACTIONS_PER_MESSAGE: usize = 20;
+/// Upper bound on attachments loaded per message when serializing a batch.
+const MAX_ATTACHMENTS_PER_FETCHED_MESSAGE: i64 = 100;
 
 fn contains_dangerous_markup(value: &str) -> bool {
     let lower = value.to_ascii_lowercase();
@@ -30,34 +43,66 @@ fn contains_dangerous_markup(value: &str) -> bool {
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct CreateChannelRequest {
     pub name: String,
     #[serde(default)]
     pub channel_type: i16,
     pub parent_id: Option<i64>,
     pub required_role_ids: Option<Vec<String>>,
+    /// Slowmode: seconds a member must wait between messages; `0` is off.
+    pub rate_limit_per_user: Option<i32>,
+    /// Announcement-style: only members with MANAGE_MESSAGES may post.
+    pub read_only: Option<bool>,
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct UpdateChannelRequest {
     pub name: Option<String>,
     pub topic: Option<String>,
     pub required_role_ids: Option<Vec<String>>,
+    /// Voice channels only; `0` removes the limit.
+    pub user_limit: Option<i32>,
+    /// Slowmode: seconds a member must wait between messages; `0` is off.
+    pub rate_limit_per_user: Option<i32>,
+    /// Announcement-style: only members with MANAGE_MESSAGES may post.
+    pub read_only: Option<bool>,
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct MessageQuery {
     pub before: Option<i64>,
+    /// Return a window centred on this message instead of paginating.
+    pub around: Option<i64>,
+    /// Return messages with a channel sequence above this, oldest first.
+    pub after_seq: Option<i64>,
     pub limit: Option<i64>,
 }
 
 #[derive(Deserialize)]
 pub struct MessageSearchQuery {
+    /// Words, `"quoted phrases"`, `from:<user_id>` and `has:attachment`.
     pub q: String,
     pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl MessageSearchQuery {
+    fn parse(&self) -> Result<(paracord_db::messages::MessageSearch, i64, i64), ApiError> {
+        let search = paracord_core::message::parse_search_query(&self.q)?;
+        if search.is_empty() {
+            return Err(ApiError::BadRequest("Query must not be empty".into()));
+        }
+        let limit = self.limit.unwrap_or(20).clamp(1, 100);
+        let offset = self.offset.unwrap_or(0).clamp(0, 5_000);
+        Ok((search, limit, offset))
+    }
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct DmE2eePayloadRequest {
     pub version: u8,
     pub nonce: String,
@@ -66,6 +111,7 @@ pub struct DmE2eePayloadRequest {
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct SendMessageRequest {
     pub content: String,
     pub referenced_message_id: Option<String>,
@@ -73,6 +119,75 @@ pub struct SendMessageRequest {
     pub attachment_ids: Vec<String>,
     pub e2ee: Option<DmE2eePayloadRequest>,
     pub nonce: Option<String>,
+    /// Gateway session sending the message; only that session's echo is
+    /// suppressed when it asked for `suppress_own_message_echo`.
+    pub session_id: Option<String>,
+    /// `SUPPRESS_EMBEDS` and/or `SILENT`.
+    #[serde(default)]
+    pub flags: i32,
+    /// Ask TTS-capable clients to read the message aloud; guild channels
+    /// require `SEND_TTS_MESSAGES`.
+    #[serde(default)]
+    pub tts: bool,
+    /// Which mentions notify anyone; omitted means all of them.
+    pub allowed_mentions: Option<AllowedMentionsRequest>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
+pub struct AllowedMentionsRequest {
+    /// Mention types to notify in full: `users`, `roles` and/or `everyone`.
+    #[serde(default)]
+    pub parse: Vec<String>,
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl AllowedMentionsRequest {
+    fn into_allowed_mentions(self) -> Result<paracord_core::message::AllowedMentions, ApiError> {
+        let mut allowed = paracord_core::message::AllowedMentions {
+            parse_users: false,
+            parse_roles: false,
+            parse_everyone: false,
+            users: Vec::with_capacity(self.users.len()),
+            roles: Vec::with_capacity(self.roles.len()),
+        };
+        for kind in &self.parse {
+            match kind.as_str() {
+                "users" => allowed.parse_users = true,
+                "roles" => allowed.parse_roles = true,
+                "everyone" => allowed.parse_everyone = true,
+                _ => {
+                    return Err(ApiError::BadRequest(format!(
+                        "Unknown allowed_mentions parse type '{kind}'"
+                    )))
+                }
+            }
+        }
+        if self.users.len() > MAX_ALLOWED_MENTION_IDS || self.roles.len() > MAX_ALLOWED_MENTION_IDS
+        {
+            return Err(ApiError::BadRequest(format!(
+                "allowed_mentions lists are limited to {MAX_ALLOWED_MENTION_IDS} ids"
+            )));
+        }
+        for id in &self.users {
+            allowed.users.push(
+                id.parse().map_err(|_| {
+                    ApiError::BadRequest("Invalid user ID in allowed_mentions".into())
+                })?,
+            );
+        }
+        for id in &self.roles {
+            allowed.roles.push(
+                id.parse().map_err(|_| {
+                    ApiError::BadRequest("Invalid role ID in allowed_mentions".into())
+                })?,
+            );
+        }
+        Ok(allowed)
+    }
 }
 
 #[derive(Deserialize)]
@@ -90,9 +205,12 @@ pub struct CreatePollRequest {
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct EditMessageRequest {
-    pub content: String,
+    pub content: Option<String>,
     pub e2ee: Option<DmE2eePayloadRequest>,
+    /// Only the `SUPPRESS_EMBEDS` bit is applied; other bits are ignored.
+    pub flags: Option<i32>,
 }
 
 #[derive(Deserialize)]
@@ -140,6 +258,8 @@ pub fn channel_to_json(c: &paracord_db::channels::ChannelRow) -> Value {
         "parent_id": c.parent_id.map(|id| id.to_string()),
         "nsfw": c.nsfw,
         "rate_limit_per_user": c.rate_limit_per_user,
+        "read_only": c.read_only,
+        "user_limit": c.user_limit.unwrap_or(0),
         "last_message_id": c.last_message_id.map(|id| id.to_string()),
         "required_role_ids": required_role_ids,
         "thread_metadata": thread_metadata,
@@ -251,34 +371,183 @@ async fn ensure_channel_permissions(
     Ok(())
 }
 
-async fn author_to_json(state: &AppState, author_id: i64) -> Value {
-    if let Some(author) = paracord_db::users::get_user_by_id(&state.db, author_id)
+/// Drop cached permissions for a channel and the threads that inherit them.
+/// Users whose DM messages `viewer_id` does not see in `channel`: everyone
+/// on either side of a block with the viewer. Empty for guild channels.
+async fn dm_hidden_authors(
+    state: &AppState,
+    channel: &paracord_db::channels::ChannelRow,
+    viewer_id: i64,
+) -> Result<HashSet<i64>, ApiError> {
+    if channel.guild_id().is_some() {
+        return Ok(HashSet::new());
+    }
+    let blocked = paracord_db::relationships::get_blocked_user_ids(&state.db, viewer_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    Ok(blocked.into_iter().collect())
+}
+
+/// Opening a thread or forum post counts as posting, so in a `read_only`
+/// channel it needs `MANAGE_MESSAGES` too.
+async fn ensure_can_post_read_only(
+    state: &AppState,
+    channel: &paracord_db::channels::ChannelRow,
+    user_id: i64,
+) -> Result<(), ApiError> {
+    if channel.read_only {
+        ensure_channel_permissions(state, channel, user_id, &[Permissions::MANAGE_MESSAGES])
+            .await?;
+    }
+    Ok(())
+}
+
+async fn invalidate_channel_permissions(state: &AppState, channel_id: i64) {
+    paracord_core::permissions::invalidate_channel(&state.permission_cache, channel_id).await;
+    let thread_ids = paracord_db::channels::get_thread_ids(&state.db, channel_id)
         .await
-        .ok()
-        .flatten()
+        .unwrap_or_default();
+    for thread_id in thread_ids {
+        paracord_core::permissions::invalidate_channel(&state.permission_cache, thread_id).await;
+    }
+}
+
+fn validate_rate_limit_per_user(rate_limit_per_user: Option<i32>) -> Result<(), ApiError> {
+    match rate_limit_per_user {
+        Some(seconds) if !(0..=MAX_RATE_LIMIT_PER_USER).contains(&seconds) => {
+            Err(ApiError::BadRequest(format!(
+                "rate_limit_per_user must be between 0 and {MAX_RATE_LIMIT_PER_USER}"
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Hold `user_id` to the channel's slowmode. Members who can manage messages
+/// or the channel are exempt, as is a retry of a message already sent with
+/// the same `nonce`. Returns the time of the claimed slot, which the caller
+/// hands back with [`release_slowmode`] if the message is not created.
+async fn enforce_slowmode(
+    state: &AppState,
+    channel: &paracord_db::channels::ChannelRow,
+    user_id: i64,
+    nonce: Option<&str>,
+) -> Result<Option<i64>, ApiError> {
+    let Some(guild_id) = channel.guild_id() else {
+        return Ok(None);
+    };
+    if channel.rate_limit_per_user <= 0 {
+        return Ok(None);
+    }
+    let guild = paracord_db::guilds::get_guild(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let perms = paracord_core::permissions::compute_channel_permissions(
+        &state.db,
+        guild_id,
+        channel.id,
+        guild.owner_id,
+        user_id,
+    )
+    .await?;
+    if perms.contains(Permissions::MANAGE_MESSAGES) || perms.contains(Permissions::MANAGE_CHANNELS)
     {
-        json!({
-            "id": author.id.to_string(),
-            "username": author.username,
-            "discriminator": author.discriminator,
-            "avatar_hash": author.avatar_hash,
-            "public_key": author.public_key,
-            "flags": author.flags,
-            "bot": paracord_core::is_bot(author.flags),
-        })
-    } else {
-        json!({
-            "id": author_id.to_string(),
-            "username": "Unknown",
-            "discriminator": 0,
-            "avatar_hash": null,
-            "public_key": null,
-            "flags": 0,
-            "bot": false,
-        })
+        return Ok(None);
+    }
+    if let Some(nonce) = nonce {
+        let existing = paracord_db::messages::get_message_by_channel_author_nonce(
+            &state.db, channel.id, user_id, nonce,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        if existing.is_some() {
+            return Ok(None);
+        }
+    }
+    let now = chrono::Utc::now().timestamp();
+    let retry_after = paracord_db::rate_limits::claim_slowmode_slot(
+        &state.db,
+        channel.id,
+        user_id,
+        i64::from(channel.rate_limit_per_user),
+        now,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    match retry_after {
+        Some(retry_after) => Err(ApiError::Slowmode { retry_after }),
+        None => Ok(Some(now)),
+    }
+}
+
+/// Give back a slowmode slot claimed at `claimed_at` for a message that was
+/// never created, so a rejected send does not start the cooldown.
+async fn release_slowmode(state: &AppState, channel_id: i64, user_id: i64, claimed_at: i64) {
+    if let Err(e) =
+        paracord_db::rate_limits::release_slowmode_slot(&state.db, channel_id, user_id, claimed_at)
+            .await
+    {
+        tracing::warn!("channel {channel_id}: failed to release slowmode slot: {e}");
     }
 }
 
+/// Author blocks for the given message authors, keyed by user id. In guild
+/// channels the members' nicknames are resolved so clients don't have to
+/// join against the member list.
+async fn authors_to_json(
+    state: &AppState,
+    author_ids: &[i64],
+    guild_id: Option<i64>,
+) -> HashMap<i64, Value> {
+    let users: HashMap<i64, paracord_db::users::UserRow> =
+        paracord_db::users::get_users_by_ids(&state.db, author_ids)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|user| (user.id, user))
+            .collect();
+    let mut nicks: HashMap<i64, Option<String>> = match guild_id {
+        Some(guild_id) => paracord_db::members::get_member_nicks(&state.db, guild_id, author_ids)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+        None => HashMap::new(),
+    };
+    author_ids
+        .iter()
+        .map(|&author_id| {
+            let nick = nicks.remove(&author_id).flatten();
+            let author = match users.get(&author_id) {
+                Some(author) => json!({
+                    "id": author.id.to_string(),
+                    "username": author.username,
+                    "discriminator": author.discriminator,
+                    "display_name": author.display_name,
+                    "nick": nick,
+                    "avatar_hash": author.avatar_hash,
+                    "public_key": author.public_key,
+                    "flags": author.flags,
+                    "bot": paracord_core::is_bot(author.flags),
+                }),
+                None => json!({
+                    "id": author_id.to_string(),
+                    "username": "Unknown",
+                    "discriminator": 0,
+                    "display_name": null,
+                    "nick": null,
+                    "avatar_hash": null,
+                    "public_key": null,
+                    "flags": 0,
+                    "bot": false,
+                }),
+            };
+            (author_id, author)
+        })
+        .collect()
+}
+
 fn poll_to_json(poll: &paracord_db::polls::PollWithOptions) -> Value {
     let options: Vec<Value> = poll
         .options
@@ -308,101 +577,167 @@ fn poll_to_json(poll: &paracord_db::polls::PollWithOptions) -> Value {
     })
 }
 
-async fn message_to_json(
+pub(crate) async fn message_to_json(
     state: &AppState,
     msg: &paracord_db::messages::MessageRow,
     viewer_id: i64,
+    guild_id: Option<i64>,
 ) -> Value {
-    let is_dm_e2ee = (msg.flags & MESSAGE_FLAG_DM_E2EE) != 0;
-    let e2ee_payload = if is_dm_e2ee {
-        msg.nonce
-            .as_ref()
-            .zip(msg.content.as_ref())
-            .map(|(nonce, ciphertext)| {
-                let version = if msg.e2ee_header.is_some() { 2 } else { 1 };
-                let mut payload = json!({
-                    "version": version,
-                    "nonce": nonce,
-                    "ciphertext": ciphertext,
-                });
-                if let Some(header) = &msg.e2ee_header {
-                    payload["header"] = json!(header);
-                }
-                payload
-            })
-    } else {
-        None
-    };
-    let content = if is_dm_e2ee {
-        Value::Null
-    } else {
-        json!(msg.content)
-    };
-
-    let author = author_to_json(state, msg.author_id).await;
-    let attachments = paracord_db::attachments::get_message_attachments(&state.db, msg.id)
+    messages_to_json(state, std::slice::from_ref(msg), viewer_id, guild_id)
         .await
-        .unwrap_or_default();
-    let attachment_json: Vec<Value> = attachments
-        .iter()
-        .map(|a| {
-            json!({
+        .pop()
+        .unwrap_or(Value::Null)
+}
+
+/// Serialize messages with their authors, attachments, link previews,
+/// reaction summaries and polls. Authors, attachments, previews and
+/// reactions are loaded for the whole batch at once.
+pub(crate) async fn messages_to_json(
+    state: &AppState,
+    messages: &[paracord_db::messages::MessageRow],
+    viewer_id: i64,
+    guild_id: Option<i64>,
+) -> Vec<Value> {
+    let message_ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+
+    let mut attachments_by_message: HashMap<i64, Vec<Value>> = HashMap::new();
+    let attachments = paracord_db::attachments::get_attachments_for_message_ids(
+        &state.db,
+        &message_ids,
+        message_ids.len() as i64 * MAX_ATTACHMENTS_PER_FETCHED_MESSAGE,
+    )
+    .await
+    .unwrap_or_default();
+    for a in attachments {
+        let Some(message_id) = a.message_id else {
+            continue;
+        };
+        attachments_by_message
+            .entry(message_id)
+            .or_default()
+            .push(json!({
                 "id": a.id.to_string(),
                 "filename": a.filename,
                 "size": a.size,
                 "content_type": a.content_type,
-                "url": a.url,
+                "url": state.config.media_url(&a.url),
                 "width": a.width,
                 "height": a.height,
-            })
-        })
-        .collect();
+            }));
+    }
 
-    let reactions = paracord_db::reactions::get_message_reactions(&state.db, msg.id)
+    let reactions = paracord_db::reactions::get_reactions_for_message_ids(&state.db, &message_ids)
         .await
         .unwrap_or_default();
-    let mut reaction_json = Vec::with_capacity(reactions.len());
-    for reaction in reactions {
-        let me = paracord_db::reactions::get_reaction_users(
+    let own_reactions: HashMap<(i64, String), bool> = if reactions.is_empty() {
+        HashMap::new()
+    } else {
+        paracord_db::reactions::get_user_reactions_for_message_ids(
             &state.db,
-            msg.id,
-            &reaction.emoji_name,
-            1000,
+            &message_ids,
+            viewer_id,
         )
         .await
-        .map(|users| users.contains(&viewer_id))
-        .unwrap_or(false);
-        reaction_json.push(json!({
-            "emoji": reaction.emoji_name,
-            "count": reaction.count,
-            "me": me,
-        }));
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(message_id, emoji, burst)| ((message_id, emoji), burst))
+        .collect()
+    };
+    let mut reactions_by_message: HashMap<i64, Vec<Value>> = HashMap::new();
+    for reaction in reactions {
+        let own = own_reactions
+            .get(&(reaction.message_id, reaction.emoji_name.clone()))
+            .copied();
+        reactions_by_message
+            .entry(reaction.message_id)
+            .or_default()
+            .push(json!({
+                "emoji": reaction.emoji_name,
+                "emoji_display": reaction.emoji_display,
+                "count": reaction.count,
+                "count_details": {
+                    "normal": reaction.count - reaction.burst_count,
+                    "burst": reaction.burst_count,
+                },
+                "me": own == Some(false),
+                "me_burst": own == Some(true),
+            }));
     }
 
-    let poll_json = paracord_db::polls::get_message_poll(&state.db, msg.id, viewer_id)
+    let mut embeds_by_message: HashMap<i64, Vec<Value>> = HashMap::new();
+    let embeds = paracord_db::messages::get_embeds_for_message_ids(&state.db, &message_ids)
         .await
-        .ok()
-        .flatten()
-        .map(|poll| poll_to_json(&poll));
+        .unwrap_or_default();
+    for (message_id, embed) in embeds {
+        if let Ok(embed) = serde_json::from_str::<Value>(&embed) {
+            embeds_by_message.entry(message_id).or_default().push(embed);
+        }
+    }
 
-    json!({
-        "id": msg.id.to_string(),
-        "channel_id": msg.channel_id.to_string(),
-        "author": author,
-        "content": content,
-        "e2ee": e2ee_payload,
-        "pinned": msg.pinned,
-        "type": msg.message_type,
-        "message_type": msg.message_type,
-        "timestamp": msg.created_at.to_rfc3339(),
-        "created_at": msg.created_at.to_rfc3339(),
-        "edited_timestamp": msg.edited_at.map(|t| t.to_rfc3339()),
-        "edited_at": msg.edited_at.map(|t| t.to_rfc3339()),
-        "reference_id": msg.reference_id.map(|id| id.to_string()),
-        "attachments": attachment_json,
-        "reactions": reaction_json,
-        "poll": poll_json,
-    })
+    let mut author_ids: Vec<i64> = messages.iter().map(|msg| msg.author_id).collect();
+    author_ids.sort_unstable();
+    author_ids.dedup();
+    let authors = authors_to_json(state, &author_ids, guild_id).await;
+    let mut result = Vec::with_capacity(messages.len());
+    for msg in messages {
+        let is_dm_e2ee = (msg.flags & MESSAGE_FLAG_DM_E2EE) != 0;
+        let e2ee_payload = if is_dm_e2ee {
+            msg.nonce
+                .as_ref()
+                .zip(msg.content.as_ref())
+                .map(|(nonce, ciphertext)| {
+                    let version = if msg.e2ee_header.is_some() { 2 } else { 1 };
+                    let mut payload = json!({
+                        "version": version,
+                        "nonce": nonce,
+                        "ciphertext": ciphertext,
+                    });
+                    if let Some(header) = &msg.e2ee_header {
+                        payload["header"] = json!(header);
+                    }
+                    payload
+                })
+        } else {
+            None
+        };
+        let content = if is_dm_e2ee {
+            Value::Null
+        } else {
+            json!(msg.content)
+        };
+
+        let author = authors.get(&msg.author_id).cloned().unwrap_or(Value::Null);
+
+        let poll_json = paracord_db::polls::get_message_poll(&state.db, msg.id, viewer_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|poll| poll_to_json(&poll));
+
+        result.push(json!({
+            "id": msg.id.to_string(),
+            "channel_id": msg.channel_id.to_string(),
+            "author": author,
+            "content": content,
+            "e2ee": e2ee_payload,
+            "pinned": msg.pinned,
+            "type": msg.message_type,
+            "message_type": msg.message_type,
+            "flags": msg.flags,
+            "tts": msg.flags & MESSAGE_FLAG_TTS != 0,
+            "seq": msg.seq,
+            "timestamp": msg.created_at.to_rfc3339(),
+            "created_at": msg.created_at.to_rfc3339(),
+            "edited_timestamp": msg.edited_at.map(|t| t.to_rfc3339()),
+            "edited_at": msg.edited_at.map(|t| t.to_rfc3339()),
+            "reference_id": msg.reference_id.map(|id| id.to_string()),
+            "attachments": attachments_by_message.remove(&msg.id).unwrap_or_default(),
+            "embeds": embeds_by_message.remove(&msg.id).unwrap_or_default(),
+            "reactions": reactions_by_message.remove(&msg.id).unwrap_or_default(),
+            "poll": poll_json,
+        }));
+    }
+    result
 }
 
 pub async fn create_channel(
@@ -411,6 +746,7 @@ pub async fn create_channel(
     Path(guild_id): Path<i64>,
     Json(body): Json<CreateChannelRequest>,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
+    validate_rate_limit_per_user(body.rate_limit_per_user)?;
     let channel_id = paracord_util::snowflake::generate(1);
     let required_role_ids = match body.required_role_ids.as_deref() {
         Some(raw_role_ids) => {
@@ -419,7 +755,7 @@ pub async fn create_channel(
         None => None,
     };
 
-    let channel = paracord_core::channel::create_channel(
+    let mut channel = paracord_core::channel::create_channel(
         &state.db,
         guild_id,
         auth.user_id,
@@ -430,6 +766,20 @@ pub async fn create_channel(
         required_role_ids.as_deref(),
     )
     .await?;
+    if let Some(rate_limit_per_user) = body.rate_limit_per_user.filter(|seconds| *seconds > 0) {
+        channel = paracord_db::channels::update_channel_rate_limit(
+            &state.db,
+            channel.id,
+            rate_limit_per_user,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
+    if body.read_only == Some(true) {
+        channel = paracord_db::channels::update_channel_read_only(&state.db, channel.id, true)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
 
     let channel_json = channel_to_json(&channel);
 
@@ -481,11 +831,25 @@ pub async fn update_channel(
         }
     }
 
-    let guild_id = paracord_db::channels::get_channel(&state.db, channel_id)
+    if let Some(user_limit) = body.user_limit {
+        if !(0..=MAX_VOICE_USER_LIMIT).contains(&user_limit) {
+            return Err(ApiError::BadRequest(format!(
+                "user_limit must be between 0 and {MAX_VOICE_USER_LIMIT}"
+            )));
+        }
+    }
+    validate_rate_limit_per_user(body.rate_limit_per_user)?;
+
+    let existing = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-        .and_then(|c| c.guild_id())
         .ok_or(ApiError::NotFound)?;
+    if body.user_limit.is_some() && existing.channel_type != 2 {
+        return Err(ApiError::BadRequest(
+            "user_limit is only supported on voice channels".into(),
+        ));
+    }
+    let guild_id = existing.guild_id().ok_or(ApiError::NotFound)?;
     let required_role_ids = match body.required_role_ids.as_deref() {
         Some(raw_role_ids) => {
             Some(normalize_required_role_ids(&state, guild_id, auth.user_id, raw_role_ids).await?)
@@ -493,7 +857,7 @@ pub async fn update_channel(
         None => None,
     };
 
-    let updated = paracord_core::channel::update_channel(
+    let mut updated = paracord_core::channel::update_channel(
         &state.db,
         channel_id,
         auth.user_id,
@@ -502,6 +866,40 @@ pub async fn update_channel(
         required_role_ids.as_deref(),
     )
     .await?;
+    if let Some(user_limit) = body.user_limit {
+        updated =
+            paracord_db::channels::update_channel_user_limit(&state.db, channel_id, user_limit)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        if updated.user_limit != existing.user_limit {
+            let max_participants = paracord_media::voice::room_max_participants(updated.user_limit);
+            if let Err(err) = state
+                .voice
+                .update_room_max_participants(channel_id, max_participants)
+                .await
+            {
+                tracing::warn!(
+                    "failed to apply user_limit to voice room for channel {}: {}",
+                    channel_id,
+                    err
+                );
+            }
+        }
+    }
+    if let Some(rate_limit_per_user) = body.rate_limit_per_user {
+        updated = paracord_db::channels::update_channel_rate_limit(
+            &state.db,
+            channel_id,
+            rate_limit_per_user,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
+    if let Some(read_only) = body.read_only {
+        updated = paracord_db::channels::update_channel_read_only(&state.db, channel_id, read_only)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
 
     let channel_json = channel_to_json(&updated);
 
@@ -516,7 +914,13 @@ pub async fn update_channel(
             audit::ACTION_CHANNEL_UPDATE,
             Some(updated.id),
             None,
-            Some(json!({ "name": updated.name, "topic": updated.topic })),
+            Some(json!({
+                "name": updated.name,
+                "topic": updated.topic,
+                "user_limit": updated.user_limit,
+                "rate_limit_per_user": updated.rate_limit_per_user,
+                "read_only": updated.read_only,
+            })),
         )
         .await;
     }
@@ -571,34 +975,66 @@ pub async fn get_messages(
     )
     .await?;
 
+    let hidden_authors = dm_hidden_authors(&state, &channel, auth.user_id).await?;
     let limit = params.limit.unwrap_or(50).min(100);
-    let messages = paracord_db::messages::get_channel_messages(
-        &state.db,
-        channel_id,
-        params.before,
-        None,
-        limit,
-    )
-    .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-
-    let mut result = Vec::new();
-    for msg in &messages {
-        result.push(message_to_json(&state, msg, auth.user_id).await);
+    if let Some(after_seq) = params.after_seq {
+        if params.before.is_some() || params.around.is_some() {
+            return Err(ApiError::BadRequest(
+                "after_seq cannot be combined with before or around".into(),
+            ));
+        }
+        let mut messages = paracord_db::messages::get_channel_messages_after_seq(
+            &state.db,
+            channel_id,
+            after_seq.max(0),
+            limit,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        messages.retain(|msg| !hidden_authors.contains(&msg.author_id));
+        let result = messages_to_json(&state, &messages, auth.user_id, channel.guild_id()).await;
+        return Ok(Json(json!(result)));
+    }
+    let mut messages = match params.around {
+        Some(around_id) => {
+            if params.before.is_some() {
+                return Err(ApiError::BadRequest(
+                    "around cannot be combined with before".into(),
+                ));
+            }
+            paracord_db::messages::get_channel_messages_around(
+                &state.db,
+                channel_id,
+                Some(auth.user_id),
+                around_id,
+                limit,
+            )
+            .await
+        }
+        None => {
+            paracord_db::messages::get_channel_messages_for_viewer(
+                &state.db,
+                channel_id,
+                Some(auth.user_id),
+                params.before,
+                None,
+                limit,
+            )
+            .await
+        }
     }
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    messages.retain(|msg| !hidden_authors.contains(&msg.author_id));
 
+    let result = messages_to_json(&state, &messages, auth.user_id, channel.guild_id()).await;
     Ok(Json(json!(result)))
 }
 
-pub async fn search_messages(
+pub async fn get_message(
     State(state): State<AppState>,
     auth: AuthUser,
-    Path(channel_id): Path<i64>,
-    Query(params): Query<MessageSearchQuery>,
+    Path((channel_id, message_id)): Path<(i64, i64)>,
 ) -> Result<Json<Value>, ApiError> {
-    if params.q.trim().is_empty() {
-        return Err(ApiError::BadRequest("Query must not be empty".into()));
-    }
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
@@ -611,40 +1047,231 @@ pub async fn search_messages(
     )
     .await?;
 
-    let limit = params.limit.unwrap_or(20).min(100);
-    let messages = paracord_db::messages::search_messages(&state.db, channel_id, &params.q, limit)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let mut result = Vec::with_capacity(messages.len());
-    for msg in &messages {
-        result.push(message_to_json(&state, msg, auth.user_id).await);
+    let msg = paracord_db::messages::get_channel_message_for_viewer(
+        &state.db,
+        channel_id,
+        message_id,
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    .ok_or(ApiError::NotFound)?;
+    if dm_hidden_authors(&state, &channel, auth.user_id)
+        .await?
+        .contains(&msg.author_id)
+    {
+        return Err(ApiError::NotFound);
     }
-    Ok(Json(json!(result)))
+
+    Ok(Json(
+        message_to_json(&state, &msg, auth.user_id, channel.guild_id()).await,
+    ))
 }
 
-pub async fn bulk_delete_messages(
+#[derive(Deserialize)]
+pub struct PermalinkQuery {
+    /// Guild ID, or `@me` for DM channels.
+    pub guild: String,
+    pub channel: i64,
+    pub message: i64,
+}
+
+/// Resolve a `/channels/{guild}/{channel}/{message}` link. Always 200 so
+/// clients can render dead links without special-casing error statuses;
+/// `reason` says why a link is not viewable. Channels the caller cannot see
+/// return no context beyond the reason.
+pub async fn resolve_permalink(
     State(state): State<AppState>,
     auth: AuthUser,
-    Path(channel_id): Path<i64>,
-    Json(body): Json<BulkDeleteMessagesRequest>,
+    Query(params): Query<PermalinkQuery>,
 ) -> Result<Json<Value>, ApiError> {
-    if body.message_ids.is_empty() {
-        return Err(ApiError::BadRequest(
-            "message_ids must contain at least one message".into(),
-        ));
-    }
-    if body.message_ids.len() > MAX_BULK_DELETE_REQUEST_IDS {
-        return Err(ApiError::BadRequest(
-            "Too many message_ids in one request".into(),
-        ));
-    }
-    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+    let unresolved = |reason: &str| {
+        Json(json!({
+            "viewable": false,
+            "reason": reason,
+            "guild": null,
+            "channel": null,
+            "message": null,
+        }))
+    };
+
+    let guild_id = match params.guild.as_str() {
+        "@me" => None,
+        raw => match raw.parse::<i64>() {
+            Ok(id) => Some(id),
+            Err(_) => return Err(ApiError::BadRequest("Invalid guild id".into())),
+        },
+    };
+
+    let Some(channel) = paracord_db::channels::get_channel(&state.db, params.channel)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-        .ok_or(ApiError::NotFound)?;
-    ensure_channel_permissions(
-        &state,
-        &channel,
+    else {
+        return Ok(unresolved("not_found"));
+    };
+    if channel.guild_id() != guild_id {
+        return Ok(unresolved("not_found"));
+    }
+
+    match ensure_channel_permissions(
+        &state,
+        &channel,
+        auth.user_id,
+        &[Permissions::VIEW_CHANNEL, Permissions::READ_MESSAGE_HISTORY],
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(ApiError::Forbidden | ApiError::NotFound) => return Ok(unresolved("forbidden")),
+        Err(e) => return Err(e),
+    }
+
+    let guild = match guild_id {
+        Some(id) => paracord_db::guilds::get_guild(&state.db, id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .map(|g| {
+                json!({
+                    "id": g.id.to_string(),
+                    "name": g.name,
+                    "icon_hash": g.icon_hash,
+                })
+            }),
+        None => None,
+    };
+    let channel_json = json!({
+        "id": channel.id.to_string(),
+        "name": channel.name,
+        "type": channel.channel_type,
+        "guild_id": channel.guild_id().map(|id| id.to_string()),
+    });
+
+    let msg = paracord_db::messages::get_channel_message_for_viewer(
+        &state.db,
+        channel.id,
+        params.message,
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    Ok(Json(match msg {
+        Some(msg) => json!({
+            "viewable": true,
+            "reason": null,
+            "guild": guild,
+            "channel": channel_json,
+            "message": {
+                "id": msg.id.to_string(),
+                "channel_id": msg.channel_id.to_string(),
+                "author_id": msg.author_id.to_string(),
+                "created_at": msg.created_at.to_rfc3339(),
+            },
+        }),
+        None => json!({
+            "viewable": false,
+            "reason": "message_not_found",
+            "guild": guild,
+            "channel": channel_json,
+            "message": null,
+        }),
+    }))
+}
+
+pub async fn search_messages(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<i64>,
+    Query(params): Query<MessageSearchQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let (search, limit, offset) = params.parse()?;
+    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    ensure_channel_permissions(
+        &state,
+        &channel,
+        auth.user_id,
+        &[Permissions::VIEW_CHANNEL, Permissions::READ_MESSAGE_HISTORY],
+    )
+    .await?;
+
+    let messages =
+        paracord_db::messages::search_messages(&state.db, channel_id, &search, limit, offset)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let result = messages_to_json(&state, &messages, auth.user_id, channel.guild_id()).await;
+    Ok(Json(json!(result)))
+}
+
+/// Search every channel in a guild the caller can view and read history in.
+pub async fn search_guild_messages(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(guild_id): Path<i64>,
+    Query(params): Query<MessageSearchQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let (search, limit, offset) = params.parse()?;
+    paracord_core::permissions::ensure_guild_member(&state.db, guild_id, auth.user_id).await?;
+    let guild = paracord_db::guilds::get_guild(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let channels = paracord_db::channels::get_guild_channels(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let perms = paracord_core::permissions::compute_all_channel_permissions(
+        &state.db,
+        guild_id,
+        &channels,
+        guild.owner_id,
+        auth.user_id,
+    )
+    .await?;
+    let readable = Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY;
+    let channel_ids: Vec<i64> = channels
+        .iter()
+        .filter(|c| perms.get(&c.id).is_some_and(|p| p.contains(readable)))
+        .map(|c| c.id)
+        .collect();
+
+    let messages = paracord_db::messages::search_channels_messages(
+        &state.db,
+        &channel_ids,
+        &search,
+        limit,
+        offset,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let result = messages_to_json(&state, &messages, auth.user_id, Some(guild_id)).await;
+    Ok(Json(json!(result)))
+}
+
+pub async fn bulk_delete_messages(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<i64>,
+    Json(body): Json<BulkDeleteMessagesRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if body.message_ids.is_empty() {
+        return Err(ApiError::BadRequest(
+            "message_ids must contain at least one message".into(),
+        ));
+    }
+    if body.message_ids.len() > MAX_BULK_DELETE_REQUEST_IDS {
+        return Err(ApiError::BadRequest(
+            "Too many message_ids in one request".into(),
+        ));
+    }
+    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    ensure_channel_permissions(
+        &state,
+        &channel,
         auth.user_id,
         &[Permissions::VIEW_CHANNEL, Permissions::MANAGE_MESSAGES],
     )
@@ -657,18 +1284,23 @@ pub async fn bulk_delete_messages(
                 .map_err(|_| ApiError::BadRequest("Invalid message ID".into()))?,
         );
     }
+    let mirrors = paracord_db::channel_followers::get_crosspost_mirrors_for_sources(
+        &state.db, channel_id, &ids,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     let deleted = paracord_db::messages::bulk_delete_messages(&state.db, channel_id, &ids)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    propagate_crosspost_delete(&state, mirrors).await;
     let guild_id = channel.guild_id();
     let bulk_payload = json!({
         "channel_id": channel_id.to_string(),
         "ids": body.message_ids,
     });
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("MESSAGE_DELETE_BULK", bulk_payload, recipient_ids);
@@ -719,6 +1351,14 @@ pub async fn send_message(
         })?;
     }
 
+    let allowed_mentions = body
+        .allowed_mentions
+        .map(AllowedMentionsRequest::into_allowed_mentions)
+        .transpose()?
+        .unwrap_or_default();
+
+    crate::routes::security::ensure_not_usage_timed_out(&state, auth.user_id)?;
+
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
@@ -730,6 +1370,16 @@ pub async fn send_message(
         &[Permissions::VIEW_CHANNEL, Permissions::SEND_MESSAGES],
     )
     .await?;
+    // Only moderators may post in a locked thread.
+    if channel.channel_type == 6 && thread_is_locked(&channel) {
+        ensure_channel_permissions(
+            &state,
+            &channel,
+            auth.user_id,
+            &[Permissions::MANAGE_CHANNELS],
+        )
+        .await?;
+    }
 
     let referenced_message_id = match body.referenced_message_id.as_deref() {
         Some(id) => Some(
@@ -739,6 +1389,13 @@ pub async fn send_message(
         None => None,
     };
 
+    let max_attachments = state.config.max_attachments_per_message;
+    if max_attachments > 0 && body.attachment_ids.len() > max_attachments as usize {
+        return Err(ApiError::BadRequest(format!(
+            "A message can have at most {max_attachments} attachments"
+        )));
+    }
+
     let mut attachments = Vec::with_capacity(body.attachment_ids.len());
     let now = chrono::Utc::now();
     for attachment_id in &body.attachment_ids {
@@ -767,6 +1424,18 @@ pub async fn send_message(
         }
         attachments.push(attachment);
     }
+    let max_attachment_bytes = state.config.max_attachment_bytes_per_message;
+    let total_attachment_bytes: u64 = attachments
+        .iter()
+        .map(|attachment| attachment.size.max(0) as u64)
+        .sum();
+    if max_attachment_bytes > 0 && total_attachment_bytes > max_attachment_bytes {
+        return Err(ApiError::BadRequest(format!(
+            "Attachments on a message must total {max_attachment_bytes} bytes or less"
+        )));
+    }
+
+    let slowmode_claim = enforce_slowmode(&state, &channel, auth.user_id, nonce.as_deref()).await?;
 
     let msg_id = paracord_util::snowflake::generate(1);
 
@@ -779,7 +1448,7 @@ pub async fn send_message(
             header: payload.header,
         });
 
-    let msg = paracord_core::message::create_message_with_options(
+    let msg = match paracord_core::message::create_message_with_options(
         &state.db,
         msg_id,
         channel_id,
@@ -790,12 +1459,32 @@ pub async fn send_message(
             reference_id: referenced_message_id,
             allow_empty_content: !body.attachment_ids.is_empty(),
             dm_e2ee,
-            nonce,
+            nonce: nonce.clone(),
+            flags: body.flags,
+            tts: body.tts,
         },
     )
-    .await?;
+    .await
+    {
+        Ok(msg) => msg,
+        Err(e) => {
+            if let Some(claimed_at) = slowmode_claim {
+                release_slowmode(&state, channel_id, auth.user_id, claimed_at).await;
+            }
+            return Err(e.into());
+        }
+    };
     let created_new = msg.id == msg_id;
-    for attachment in &attachments {
+    if created_new && state.usage.record_message(auth.user_id) {
+        crate::routes::security::log_usage_threshold_exceeded(
+            &state,
+            auth.user_id,
+            "message",
+            None,
+        )
+        .await;
+    }
+    for (position, attachment) in attachments.iter().enumerate() {
         if attachment.message_id == Some(msg.id) {
             continue;
         }
@@ -806,6 +1495,7 @@ pub async fn send_message(
             &state.db,
             attachment.id,
             msg.id,
+            position as i32,
             auth.user_id,
             channel_id,
             now.clone(),
@@ -832,25 +1522,109 @@ pub async fn send_message(
     // Increment thread message count if the channel is a thread
     if created_new && channel.channel_type == 6 {
         let _ = paracord_db::channels::increment_thread_message_count(&state.db, channel_id).await;
+        // A new message revives an archived thread unless it was locked.
+        let metadata = channel
+            .thread_metadata
+            .as_deref()
+            .and_then(|raw| serde_json::from_str::<Value>(raw).ok())
+            .unwrap_or(Value::Null);
+        let flag = |key: &str| metadata.get(key).and_then(|v| v.as_bool()) == Some(true);
+        if flag("archived") && !flag("locked") {
+            if let Ok(thread) = paracord_db::channels::update_thread(
+                &state.db,
+                channel_id,
+                None,
+                Some(false),
+                None,
+                None,
+            )
+            .await
+            {
+                state.event_bus.dispatch(
+                    "THREAD_UPDATE",
+                    channel_to_json(&thread),
+                    thread.guild_id(),
+                );
+            }
+        }
     }
 
     let guild_id = channel.guild_id();
-    let msg_json = message_to_json(&state, &msg, auth.user_id).await;
+    let mut msg_json = message_to_json(&state, &msg, auth.user_id, guild_id).await;
+    // Echo the client nonce so the sender can match the dispatch to its
+    // optimistic copy.
+    msg_json["nonce"] = json!(nonce);
 
     if created_new {
+        paracord_core::typing::typing_tracker().stop(auth.user_id, channel_id);
+        let mut dm_recipient_ids = Vec::new();
         if guild_id.is_none() {
             // DM channel: deliver only to participants, not all connected users
-            let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-                .await
-                .unwrap_or_default();
-            state
-                .event_bus
-                .dispatch_to_users("MESSAGE_CREATE", msg_json.clone(), recipient_ids);
+            // The message is kept, but nobody on the other side of a block
+            // receives it.
+            dm_recipient_ids =
+                paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id)
+                    .await;
+            state.event_bus.publish(
+                ServerEvent::new(
+                    "MESSAGE_CREATE",
+                    msg_json.clone(),
+                    None,
+                    Some(dm_recipient_ids.clone()),
+                )
+                .with_origin_session(body.session_id.clone()),
+            );
         } else {
-            state
-                .event_bus
-                .dispatch("MESSAGE_CREATE", msg_json.clone(), guild_id);
+            state.event_bus.publish(
+                ServerEvent::new("MESSAGE_CREATE", msg_json.clone(), guild_id, None)
+                    .with_origin_session(body.session_id.clone()),
+            );
+        }
+        // Resolving mentions can reach every member of a large guild, so it
+        // runs after the message is delivered rather than delaying it.
+        // Silent messages are delivered but notify nobody.
+        if msg.flags & MESSAGE_FLAG_SILENT == 0 {
+            let mention_state = state.clone();
+            let mention_msg = msg.clone();
+            tokio::spawn(async move {
+                let (state, msg) = (mention_state, mention_msg);
+                let mentioned = match paracord_core::message::record_mentions(
+                    &state.db,
+                    &msg,
+                    guild_id,
+                    &allowed_mentions,
+                )
+                .await
+                {
+                    Ok(user_ids) => user_ids,
+                    Err(e) => {
+                        tracing::warn!("message {}: failed to record mentions: {e}", msg.id);
+                        Vec::new()
+                    }
+                };
+                crate::push::spawn_message_push(
+                    &state,
+                    &msg,
+                    guild_id,
+                    &mentioned,
+                    &dm_recipient_ids,
+                );
+                if !mentioned.is_empty() {
+                    // Only users who can see the channel are in `mentioned`.
+                    state.event_bus.dispatch_to_users(
+                        paracord_models::gateway::EVENT_MESSAGE_MENTION,
+                        json!({
+                            "message_id": msg.id.to_string(),
+                            "channel_id": channel_id.to_string(),
+                            "guild_id": guild_id.map(|id| id.to_string()),
+                            "author_id": msg.author_id.to_string(),
+                        }),
+                        mentioned,
+                    );
+                }
+            });
         }
+        crate::link_unfurl::spawn_message_unfurl(&state, &msg, guild_id).await;
 
         // Federation: forward message to peer servers (non-blocking)
         if let Some(gid) = guild_id {
@@ -979,12 +1753,11 @@ pub async fn create_poll(
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
     let guild_id = channel.guild_id();
-    let msg_json = message_to_json(&state, &msg, auth.user_id).await;
+    let msg_json = message_to_json(&state, &msg, auth.user_id, guild_id).await;
 
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("MESSAGE_CREATE", msg_json.clone(), recipient_ids);
@@ -1074,9 +1847,8 @@ pub async fn add_poll_vote(
     });
     let guild_id = channel.guild_id();
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("POLL_VOTE_ADD", event_payload, recipient_ids);
@@ -1136,9 +1908,8 @@ pub async fn remove_poll_vote(
     });
     let guild_id = channel.guild_id();
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("POLL_VOTE_REMOVE", event_payload, recipient_ids);
@@ -1157,33 +1928,59 @@ pub async fn edit_message(
     Path((channel_id, message_id)): Path<(i64, i64)>,
     Json(body): Json<EditMessageRequest>,
 ) -> Result<Json<Value>, ApiError> {
-    if body.e2ee.is_none() {
-        paracord_util::validation::validate_message_content(&body.content).map_err(|_| {
-            ApiError::BadRequest("Message content must be 1-2000 characters".into())
-        })?;
-    }
-    if body.e2ee.is_none() && contains_dangerous_markup(&body.content) {
-        return Err(ApiError::BadRequest(
-            "Message contains unsafe markup".into(),
-        ));
+    let edits_content = body.content.is_some() || body.e2ee.is_some();
+    let content = body.content.unwrap_or_default();
+
+    let mut edited = None;
+    if edits_content {
+        if body.e2ee.is_none() {
+            paracord_util::validation::validate_message_content(&content).map_err(|_| {
+                ApiError::BadRequest("Message content must be 1-2000 characters".into())
+            })?;
+        }
+        if body.e2ee.is_none() && contains_dangerous_markup(&content) {
+            return Err(ApiError::BadRequest(
+                "Message contains unsafe markup".into(),
+            ));
+        }
+        let dm_e2ee = body
+            .e2ee
+            .map(|payload| paracord_core::message::DmE2eePayload {
+                version: payload.version,
+                nonce: payload.nonce,
+                ciphertext: payload.ciphertext,
+                header: payload.header,
+            });
+        edited = Some(
+            paracord_core::message::edit_message_with_options(
+                &state.db,
+                channel_id,
+                message_id,
+                auth.user_id,
+                &content,
+                dm_e2ee,
+            )
+            .await?,
+        );
     }
-    let dm_e2ee = body
-        .e2ee
-        .map(|payload| paracord_core::message::DmE2eePayload {
-            version: payload.version,
-            nonce: payload.nonce,
-            ciphertext: payload.ciphertext,
-            header: payload.header,
-        });
-    let updated = paracord_core::message::edit_message_with_options(
-        &state.db,
-        channel_id,
-        message_id,
-        auth.user_id,
-        &body.content,
-        dm_e2ee,
-    )
-    .await?;
+    let updated = match (edited, body.flags) {
+        (_, Some(flags)) => {
+            paracord_core::message::set_suppress_embeds(
+                &state.db,
+                channel_id,
+                message_id,
+                auth.user_id,
+                flags & MESSAGE_FLAG_SUPPRESS_EMBEDS != 0,
+            )
+            .await?
+        }
+        (Some(edited), None) => edited,
+        (None, None) => {
+            return Err(ApiError::BadRequest(
+                "Nothing to update: provide content or flags".into(),
+            ))
+        }
+    };
 
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
@@ -1191,12 +1988,11 @@ pub async fn edit_message(
         .flatten();
     let guild_id = channel.and_then(|c| c.guild_id());
 
-    let msg_json = message_to_json(&state, &updated, auth.user_id).await;
+    let msg_json = message_to_json(&state, &updated, auth.user_id, guild_id).await;
 
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("MESSAGE_UPDATE", msg_json.clone(), recipient_ids);
@@ -1206,7 +2002,15 @@ pub async fn edit_message(
             .dispatch("MESSAGE_UPDATE", msg_json.clone(), guild_id);
     }
 
-    if let Some(gid) = guild_id {
+    if edits_content {
+        crate::link_unfurl::spawn_message_unfurl(&state, &updated, guild_id).await;
+    }
+
+    if edits_content && updated.flags & MESSAGE_FLAG_CROSSPOSTED != 0 {
+        propagate_crosspost_edit(&state, message_id, &content).await;
+    }
+
+    if let Some(gid) = guild_id.filter(|_| edits_content) {
         if paracord_federation::is_enabled() {
             let fed_state = state.clone();
             let fed_author = auth.user_id;
@@ -1214,7 +2018,7 @@ pub async fn edit_message(
                 "guild_id": gid.to_string(),
                 "channel_id": channel_id.to_string(),
                 "message_id": message_id.to_string(),
-                "body": body.content,
+                "body": content,
             });
             let fed_ts = chrono::Utc::now().timestamp_millis();
             tokio::spawn(async move {
@@ -1241,7 +2045,11 @@ pub async fn delete_message(
     auth: AuthUser,
     Path((channel_id, message_id)): Path<(i64, i64)>,
 ) -> Result<StatusCode, ApiError> {
+    let mirrors = paracord_db::channel_followers::get_crosspost_mirrors(&state.db, message_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     paracord_core::message::delete_message(&state.db, message_id, channel_id, auth.user_id).await?;
+    propagate_crosspost_delete(&state, mirrors).await;
 
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
@@ -1252,9 +2060,8 @@ pub async fn delete_message(
     let delete_payload =
         json!({"id": message_id.to_string(), "channel_id": channel_id.to_string()});
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("MESSAGE_DELETE", delete_payload, recipient_ids);
@@ -1314,11 +2121,7 @@ pub async fn get_pins(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let mut pinned = Vec::with_capacity(messages.len());
-    for msg in &messages {
-        pinned.push(message_to_json(&state, msg, auth.user_id).await);
-    }
-
+    let pinned = messages_to_json(&state, &messages, auth.user_id, channel.guild_id()).await;
     Ok(Json(json!(pinned)))
 }
 
@@ -1350,9 +2153,8 @@ pub async fn pin_message(
     let pins_payload = json!({ "channel_id": channel_id.to_string() });
 
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("CHANNEL_PINS_UPDATE", pins_payload, recipient_ids);
@@ -1393,9 +2195,8 @@ pub async fn unpin_message(
     let pins_payload = json!({ "channel_id": channel_id.to_string() });
 
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("CHANNEL_PINS_UPDATE", pins_payload, recipient_ids);
@@ -1426,6 +2227,12 @@ pub async fn typing(
     )
     .await?;
     let guild_id = channel.guild_id();
+    paracord_core::typing::typing_tracker().start(
+        auth.user_id,
+        channel_id,
+        guild_id,
+        std::time::Instant::now(),
+    );
     let typing_payload = json!({
         "channel_id": channel_id.to_string(),
         "user_id": auth.user_id.to_string(),
@@ -1433,9 +2240,8 @@ pub async fn typing(
     });
 
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("TYPING_START", typing_payload, recipient_ids);
@@ -1553,7 +2359,7 @@ pub async fn upsert_channel_overwrite(
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     // Invalidate permission cache when channel overwrites change
-    paracord_core::permissions::invalidate_channel(&state.permission_cache, channel_id).await;
+    invalidate_channel_permissions(&state, channel_id).await;
     state.event_bus.dispatch(
         "CHANNEL_UPDATE",
         json!({ "id": channel_id.to_string() }),
@@ -1582,7 +2388,7 @@ pub async fn delete_channel_overwrite(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     // Invalidate permission cache when channel overwrites are removed
-    paracord_core::permissions::invalidate_channel(&state.permission_cache, channel_id).await;
+    invalidate_channel_permissions(&state, channel_id).await;
     state.event_bus.dispatch(
         "CHANNEL_UPDATE",
         json!({ "id": channel_id.to_string() }),
@@ -1591,10 +2397,18 @@ pub async fn delete_channel_overwrite(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[derive(Deserialize)]
+pub struct AddReactionQuery {
+    /// Add a super (burst) reaction instead of a normal one.
+    #[serde(default)]
+    pub burst: bool,
+}
+
 pub async fn add_reaction(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((channel_id, message_id, emoji)): Path<(i64, i64, String)>,
+    Query(query): Query<AddReactionQuery>,
 ) -> Result<StatusCode, ApiError> {
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
@@ -1607,24 +2421,49 @@ pub async fn add_reaction(
         &[Permissions::VIEW_CHANNEL, Permissions::READ_MESSAGE_HISTORY],
     )
     .await?;
+    paracord_db::messages::get_channel_message_for_viewer(
+        &state.db,
+        channel_id,
+        message_id,
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    .ok_or(ApiError::NotFound)?;
 
-    paracord_db::reactions::add_reaction(&state.db, message_id, auth.user_id, &emoji, None)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let emoji_key = reaction_skin_tone_policy().canonicalize(&emoji)?;
+    let added = paracord_db::reactions::add_reaction_capped(
+        &state.db,
+        message_id,
+        auth.user_id,
+        &emoji_key,
+        &emoji,
+        None,
+        query.burst,
+        MAX_UNIQUE_REACTIONS_PER_MESSAGE,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !added {
+        return Err(unique_reaction_cap_error());
+    }
 
+    // Federation carries the emoji as sent; each server applies its own
+    // canonicalization policy.
     let emoji_for_federation = emoji.clone();
     let guild_id = channel.guild_id();
     let reaction_payload = json!({
         "user_id": auth.user_id.to_string(),
         "channel_id": channel_id.to_string(),
         "message_id": message_id.to_string(),
-        "emoji": emoji,
+        "emoji": emoji_key,
+        "emoji_display": emoji,
+        "burst": query.burst,
     });
 
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state
             .event_bus
             .dispatch_to_users("MESSAGE_REACTION_ADD", reaction_payload, recipient_ids);
@@ -1664,11 +2503,19 @@ pub async fn add_reaction(
     Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn remove_reaction(
+#[derive(Deserialize)]
+pub struct ReactionUsersQuery {
+    /// Restrict to burst (`true`) or normal (`false`) reactions.
+    pub burst: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+pub async fn list_reaction_users(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((channel_id, message_id, emoji)): Path<(i64, i64, String)>,
-) -> Result<StatusCode, ApiError> {
+    Query(query): Query<ReactionUsersQuery>,
+) -> Result<Json<Value>, ApiError> {
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
@@ -1680,24 +2527,109 @@ pub async fn remove_reaction(
         &[Permissions::VIEW_CHANNEL, Permissions::READ_MESSAGE_HISTORY],
     )
     .await?;
-
-    paracord_db::reactions::remove_reaction(&state.db, message_id, auth.user_id, &emoji)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-
+    paracord_db::messages::get_channel_message_for_viewer(
+        &state.db,
+        channel_id,
+        message_id,
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    .ok_or(ApiError::NotFound)?;
+
+    let emoji = reaction_skin_tone_policy().canonicalize(&emoji)?;
+    let limit = query.limit.unwrap_or(25).clamp(1, 100);
+    let user_ids = paracord_db::reactions::get_reaction_users(
+        &state.db,
+        message_id,
+        &emoji,
+        query.burst,
+        limit,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let counts = paracord_db::reactions::get_message_reactions(&state.db, message_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .into_iter()
+        .find(|reaction| reaction.emoji_name == emoji);
+    let (count, burst_count) = counts
+        .map(|reaction| (reaction.count, reaction.burst_count))
+        .unwrap_or((0, 0));
+
+    let mut by_id: HashMap<i64, paracord_db::users::UserRow> =
+        paracord_db::users::get_users_by_ids(&state.db, &user_ids)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .into_iter()
+            .map(|user| (user.id, user))
+            .collect();
+    // Keep the order reactions were added in.
+    let users: Vec<Value> = user_ids
+        .iter()
+        .filter_map(|user_id| by_id.remove(user_id))
+        .map(|user| {
+            json!({
+                "id": user.id.to_string(),
+                "username": user.username,
+                "discriminator": user.discriminator,
+                "display_name": user.display_name,
+                "avatar_hash": user.avatar_hash,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "users": users,
+        "count": count,
+        "count_details": {
+            "normal": count - burst_count,
+            "burst": burst_count,
+        },
+    })))
+}
+
+pub async fn remove_reaction(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((channel_id, message_id, emoji)): Path<(i64, i64, String)>,
+) -> Result<StatusCode, ApiError> {
+    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    ensure_channel_permissions(
+        &state,
+        &channel,
+        auth.user_id,
+        &[Permissions::VIEW_CHANNEL, Permissions::READ_MESSAGE_HISTORY],
+    )
+    .await?;
+
+    let emoji_key = reaction_skin_tone_policy().canonicalize(&emoji)?;
+    paracord_db::reactions::remove_reaction(&state.db, message_id, auth.user_id, &emoji_key)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    // Reactions stored before `server.reaction_skin_tones` changed may be
+    // keyed by the raw emoji.
+    if emoji_key != emoji {
+        paracord_db::reactions::remove_reaction(&state.db, message_id, auth.user_id, &emoji)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
+
     let emoji_for_federation = emoji.clone();
     let guild_id = channel.guild_id();
     let reaction_payload = json!({
         "user_id": auth.user_id.to_string(),
         "channel_id": channel_id.to_string(),
         "message_id": message_id.to_string(),
-        "emoji": emoji,
+        "emoji": emoji_key,
     });
 
     if guild_id.is_none() {
-        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-            .await
-            .unwrap_or_default();
+        let recipient_ids =
+            paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await;
         state.event_bus.dispatch_to_users(
             "MESSAGE_REACTION_REMOVE",
             reaction_payload,
@@ -1739,6 +2671,587 @@ pub async fn remove_reaction(
     Ok(StatusCode::NO_CONTENT)
 }
 
+fn unique_reaction_cap_error() -> ApiError {
+    ApiError::BadRequest(format!(
+        "Messages can have at most {MAX_UNIQUE_REACTIONS_PER_MESSAGE} different reactions"
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct BulkReactionsRequest {
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// Add and remove several of the caller's reactions on a message at once.
+/// Limited to the message author and members with MANAGE_MESSAGES, so polls
+/// and similar posts can seed their options in one request.
+pub async fn bulk_reactions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((channel_id, message_id)): Path<(i64, i64)>,
+    Json(body): Json<BulkReactionsRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if body.add.is_empty() && body.remove.is_empty() {
+        return Err(ApiError::BadRequest(
+            "add or remove must contain at least one emoji".into(),
+        ));
+    }
+    if body.add.len() > MAX_UNIQUE_REACTIONS_PER_MESSAGE
+        || body.remove.len() > MAX_UNIQUE_REACTIONS_PER_MESSAGE
+    {
+        return Err(unique_reaction_cap_error());
+    }
+    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let message = paracord_db::messages::get_channel_message_for_viewer(
+        &state.db,
+        channel_id,
+        message_id,
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    .ok_or(ApiError::NotFound)?;
+    let required = if message.author_id == auth.user_id {
+        [Permissions::VIEW_CHANNEL, Permissions::READ_MESSAGE_HISTORY]
+    } else {
+        [Permissions::VIEW_CHANNEL, Permissions::MANAGE_MESSAGES]
+    };
+    ensure_channel_permissions(&state, &channel, auth.user_id, &required).await?;
+
+    let policy = reaction_skin_tone_policy();
+    let mut add = Vec::with_capacity(body.add.len());
+    for emoji in &body.add {
+        add.push((policy.canonicalize(emoji)?, emoji.clone()));
+    }
+    // Reactions stored before `server.reaction_skin_tones` changed may be
+    // keyed by the raw emoji, so removals match either form. Maps each stored key to (key, as sent).
+    let mut remove_keys: HashMap<String, (String, String)> = HashMap::new();
+    let mut remove = Vec::with_capacity(body.remove.len());
+    for emoji in &body.remove {
+        let key = policy.canonicalize(emoji)?;
+        for stored in [&key, emoji] {
+            if !remove_keys.contains_key(stored) {
+                remove_keys.insert(stored.clone(), (key.clone(), emoji.clone()));
+                remove.push(stored.clone());
+            }
+        }
+    }
+
+    let result = paracord_db::reactions::apply_reaction_batch(
+        &state.db,
+        message_id,
+        auth.user_id,
+        &add,
+        &remove,
+        MAX_UNIQUE_REACTIONS_PER_MESSAGE,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    .ok_or_else(unique_reaction_cap_error)?;
+
+    let mut removed: Vec<(String, String)> = Vec::new();
+    for stored in &result.removed {
+        if let Some((key, raw)) = remove_keys.get(stored) {
+            if !removed.iter().any(|(k, _)| k == key) {
+                removed.push((key.clone(), raw.clone()));
+            }
+        }
+    }
+
+    let guild_id = channel.guild_id();
+    let recipient_ids = if guild_id.is_none() {
+        paracord_core::channel::dm_event_recipients(&state.db, channel_id, auth.user_id).await
+    } else {
+        Vec::new()
+    };
+    let batches = [
+        (
+            "MESSAGE_REACTION_ADD_BULK",
+            "m.reaction.add",
+            result
+                .added
+                .iter()
+                .map(|(key, display)| json!({ "emoji": key, "emoji_display": display }))
+                .collect::<Vec<_>>(),
+            result
+                .added
+                .iter()
+                .map(|(_, display)| display.clone())
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "MESSAGE_REACTION_REMOVE_BULK",
+            "m.reaction.remove",
+            removed
+                .iter()
+                .map(|(key, _)| json!({ "emoji": key }))
+                .collect::<Vec<_>>(),
+            removed
+                .iter()
+                .map(|(_, raw)| raw.clone())
+                .collect::<Vec<_>>(),
+        ),
+    ];
+    for (event_type, federation_type, reactions, federated_emoji) in batches {
+        if reactions.is_empty() {
+            continue;
+        }
+        let payload = json!({
+            "user_id": auth.user_id.to_string(),
+            "channel_id": channel_id.to_string(),
+            "message_id": message_id.to_string(),
+            "reactions": reactions,
+        });
+        if guild_id.is_none() {
+            state
+                .event_bus
+                .dispatch_to_users(event_type, payload, recipient_ids.clone());
+        } else {
+            state.event_bus.dispatch(event_type, payload, guild_id);
+        }
+
+        // Peers only understand single reactions, so federate one at a time.
+        if let Some(gid) = guild_id {
+            if paracord_federation::is_enabled() {
+                for emoji in federated_emoji {
+                    let fed_state = state.clone();
+                    let fed_author = auth.user_id;
+                    let fed_content = json!({
+                        "guild_id": gid.to_string(),
+                        "channel_id": channel_id.to_string(),
+                        "message_id": message_id.to_string(),
+                        "emoji": emoji,
+                    });
+                    let fed_ts = chrono::Utc::now().timestamp_millis();
+                    tokio::spawn(async move {
+                        federation_forward_generic(
+                            &fed_state,
+                            federation_type,
+                            channel_id,
+                            gid,
+                            fed_author,
+                            &fed_content,
+                            fed_ts,
+                            Some(format!("{}:{}", message_id, emoji)),
+                        )
+                        .await;
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(Json(json!({
+        "added": result.added.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+        "removed": removed.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+    })))
+}
+
+// ============ Announcement follow endpoints ============
+
+const CHANNEL_TYPE_TEXT: i16 = 0;
+const CHANNEL_TYPE_ANNOUNCEMENT: i16 = 5;
+
+#[derive(Deserialize)]
+pub struct FollowChannelRequest {
+    pub webhook_channel_id: String,
+}
+
+fn channel_follower_to_json(
+    follower: &paracord_db::channel_followers::ChannelFollowerRow,
+) -> Value {
+    json!({
+        "id": follower.id.to_string(),
+        "channel_id": follower.source_channel_id.to_string(),
+        "webhook_channel_id": follower.target_channel_id.to_string(),
+        "webhook_id": follower.webhook_id.to_string(),
+        "created_at": follower.created_at.to_rfc3339(),
+    })
+}
+
+/// Subscribe a text channel to an announcement channel. Published messages
+/// are delivered into the follower channel through a dedicated webhook.
+pub async fn follow_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<i64>,
+    Json(body): Json<FollowChannelRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let source = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if source.channel_type != CHANNEL_TYPE_ANNOUNCEMENT {
+        return Err(ApiError::BadRequest(
+            "Only announcement channels can be followed".into(),
+        ));
+    }
+    ensure_channel_permissions(&state, &source, auth.user_id, &[Permissions::VIEW_CHANNEL]).await?;
+
+    let target_id = body
+        .webhook_channel_id
+        .parse::<i64>()
+        .map_err(|_| ApiError::BadRequest("Invalid webhook_channel_id".into()))?;
+    if target_id == channel_id {
+        return Err(ApiError::BadRequest(
+            "A channel cannot follow itself".into(),
+        ));
+    }
+    let target = paracord_db::channels::get_channel(&state.db, target_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let target_guild_id = target.guild_id().ok_or(ApiError::BadRequest(
+        "Follower channel must belong to a guild".into(),
+    ))?;
+    if target.channel_type != CHANNEL_TYPE_TEXT {
+        return Err(ApiError::BadRequest(
+            "Follower channel must be a text channel".into(),
+        ));
+    }
+    crate::routes::webhooks::require_manage_webhooks(&state, target_guild_id, auth.user_id).await?;
+
+    if paracord_db::channel_followers::get_follower_by_channels(&state.db, channel_id, target_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .is_some()
+    {
+        return Err(ApiError::Conflict(
+            "Channel is already following this announcement channel".into(),
+        ));
+    }
+    crate::routes::webhooks::ensure_webhook_capacity(&state, target_guild_id, target_id).await?;
+
+    let source_guild_name = match source.guild_id() {
+        Some(guild_id) => paracord_db::guilds::get_guild(&state.db, guild_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .map(|guild| guild.name)
+            .unwrap_or_default(),
+        None => String::new(),
+    };
+    let webhook_name: String = format!(
+        "{} #{}",
+        source_guild_name,
+        source.name.as_deref().unwrap_or("announcements")
+    )
+    .trim()
+    .chars()
+    .take(80)
+    .collect();
+
+    let webhook_id = paracord_util::snowflake::generate(1);
+    let token = crate::routes::webhooks::generate_webhook_token();
+    paracord_db::webhooks::create_webhook(
+        &state.db,
+        webhook_id,
+        target_guild_id,
+        target_id,
+        &webhook_name,
+        &token,
+        auth.user_id,
+        "[]",
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let follower = match paracord_db::channel_followers::create_follower(
+        &state.db,
+        paracord_util::snowflake::generate(1),
+        channel_id,
+        target_id,
+        webhook_id,
+        auth.user_id,
+    )
+    .await
+    {
+        Ok(follower) => follower,
+        Err(e) => {
+            let _ = paracord_db::webhooks::delete_webhook(&state.db, webhook_id).await;
+            return Err(ApiError::Internal(anyhow::anyhow!(e.to_string())));
+        }
+    };
+
+    audit::log_action(
+        &state,
+        target_guild_id,
+        auth.user_id,
+        audit::ACTION_WEBHOOK_CREATE,
+        Some(webhook_id),
+        None,
+        Some(json!({ "followed_channel_id": channel_id.to_string() })),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(channel_follower_to_json(&follower)),
+    ))
+}
+
+/// Remove a follow. Allowed for webhook managers in the follower's guild and
+/// for channel managers of the announcement channel.
+pub async fn unfollow_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((channel_id, follower_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, ApiError> {
+    let follower = paracord_db::channel_followers::get_follower(&state.db, follower_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if follower.source_channel_id != channel_id {
+        return Err(ApiError::NotFound);
+    }
+
+    let target_guild_id = paracord_db::channels::get_channel(&state.db, follower.target_channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .and_then(|channel| channel.guild_id());
+    let manages_target = match target_guild_id {
+        Some(guild_id) => {
+            crate::routes::webhooks::require_manage_webhooks(&state, guild_id, auth.user_id)
+                .await
+                .is_ok()
+        }
+        None => false,
+    };
+    if !manages_target {
+        let source = paracord_db::channels::get_channel(&state.db, channel_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .ok_or(ApiError::NotFound)?;
+        ensure_channel_permissions(
+            &state,
+            &source,
+            auth.user_id,
+            &[Permissions::VIEW_CHANNEL, Permissions::MANAGE_CHANNELS],
+        )
+        .await?;
+    }
+
+    paracord_db::channel_followers::delete_follower(&state.db, follower_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    paracord_db::webhooks::delete_webhook(&state.db, follower.webhook_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    if let Some(guild_id) = target_guild_id {
+        audit::log_action(
+            &state,
+            guild_id,
+            auth.user_id,
+            audit::ACTION_WEBHOOK_DELETE,
+            Some(follower.webhook_id),
+            None,
+            Some(json!({ "followed_channel_id": channel_id.to_string() })),
+        )
+        .await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Publish an announcement message to every following channel.
+pub async fn crosspost_message(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((channel_id, message_id)): Path<(i64, i64)>,
+) -> Result<Json<Value>, ApiError> {
+    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if channel.channel_type != CHANNEL_TYPE_ANNOUNCEMENT {
+        return Err(ApiError::BadRequest(
+            "Only messages in announcement channels can be published".into(),
+        ));
+    }
+    let message = paracord_db::messages::get_message(&state.db, message_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if message.channel_id != channel_id {
+        return Err(ApiError::NotFound);
+    }
+    let required = if message.author_id == auth.user_id {
+        [Permissions::VIEW_CHANNEL, Permissions::SEND_MESSAGES]
+    } else {
+        [Permissions::VIEW_CHANNEL, Permissions::MANAGE_MESSAGES]
+    };
+    ensure_channel_permissions(&state, &channel, auth.user_id, &required).await?;
+
+    let flagged = paracord_db::messages::add_message_flag(
+        &state.db,
+        message_id,
+        channel_id,
+        MESSAGE_FLAG_CROSSPOSTED,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !flagged {
+        return Err(ApiError::Conflict(
+            "Message has already been published".into(),
+        ));
+    }
+
+    let content = message.content.clone().unwrap_or_default();
+    let followers = paracord_db::channel_followers::get_channel_followers(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    for follower in followers {
+        let webhook = match paracord_db::webhooks::get_webhook(&state.db, follower.webhook_id).await
+        {
+            Ok(Some(webhook)) => webhook,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(
+                    follower_id = follower.id,
+                    "crosspost: webhook lookup failed: {e}"
+                );
+                continue;
+            }
+        };
+        let mirror_id = match crate::routes::webhooks::post_webhook_message(
+            &state,
+            &webhook,
+            &content,
+            &webhook.name,
+        )
+        .await
+        {
+            Ok((mirror_id, _)) => mirror_id,
+            Err(e) => {
+                tracing::warn!(
+                    follower_id = follower.id,
+                    "crosspost: delivery to channel {} failed: {e:?}",
+                    follower.target_channel_id
+                );
+                continue;
+            }
+        };
+        if let Err(e) = paracord_db::channel_followers::record_crosspost_mirror(
+            &state.db,
+            mirror_id,
+            message_id,
+            follower.target_channel_id,
+            webhook.id,
+        )
+        .await
+        {
+            tracing::warn!(
+                follower_id = follower.id,
+                "crosspost: recording mirror {mirror_id} failed: {e}"
+            );
+        }
+    }
+
+    let updated = paracord_db::messages::get_message(&state.db, message_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let guild_id = channel.guild_id();
+    let msg_json = message_to_json(&state, &updated, auth.user_id, guild_id).await;
+    state
+        .event_bus
+        .dispatch("MESSAGE_UPDATE", msg_json.clone(), guild_id);
+
+    Ok(Json(msg_json))
+}
+
+/// Carry an edit of a published announcement over to its follower copies.
+async fn propagate_crosspost_edit(state: &AppState, source_message_id: i64, content: &str) {
+    let mirrors =
+        match paracord_db::channel_followers::get_crosspost_mirrors(&state.db, source_message_id)
+            .await
+        {
+            Ok(mirrors) => mirrors,
+            Err(e) => {
+                tracing::warn!("crosspost: mirror lookup for {source_message_id} failed: {e}");
+                return;
+            }
+        };
+    for mirror in mirrors {
+        let updated = match paracord_db::messages::update_message(
+            &state.db,
+            mirror.mirror_message_id,
+            content,
+        )
+        .await
+        {
+            Ok(updated) => updated,
+            Err(e) => {
+                tracing::warn!(
+                    "crosspost: updating mirror {} failed: {e}",
+                    mirror.mirror_message_id
+                );
+                continue;
+            }
+        };
+        let guild_id = paracord_db::channels::get_channel(&state.db, mirror.channel_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|c| c.guild_id());
+        let display_name = paracord_db::webhooks::get_webhook(&state.db, mirror.webhook_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|webhook| webhook.name)
+            .unwrap_or_default();
+        let msg_json = crate::routes::webhooks::webhook_message_json(
+            state,
+            mirror.webhook_id,
+            &display_name,
+            &updated,
+            guild_id,
+        )
+        .await;
+        state
+            .event_bus
+            .dispatch("MESSAGE_UPDATE", msg_json, guild_id);
+    }
+}
+
+/// Remove the follower copies of a published announcement. The mirrors must
+/// be looked up before the source is deleted, since the mapping cascades.
+async fn propagate_crosspost_delete(
+    state: &AppState,
+    mirrors: Vec<paracord_db::channel_followers::CrosspostMirrorRow>,
+) {
+    for mirror in mirrors {
+        if let Err(e) =
+            paracord_db::messages::delete_message(&state.db, mirror.mirror_message_id).await
+        {
+            tracing::warn!(
+                "crosspost: deleting mirror {} failed: {e}",
+                mirror.mirror_message_id
+            );
+            continue;
+        }
+        let guild_id = paracord_db::channels::get_channel(&state.db, mirror.channel_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|c| c.guild_id());
+        state.event_bus.dispatch(
+            "MESSAGE_DELETE",
+            json!({
+                "id": mirror.mirror_message_id.to_string(),
+                "channel_id": mirror.channel_id.to_string(),
+            }),
+            guild_id,
+        );
+    }
+}
+
 // ============ Thread endpoints ============
 
 #[derive(Deserialize)]
@@ -1753,6 +3266,13 @@ pub struct UpdateThreadRequest {
     pub name: Option<String>,
     pub archived: Option<bool>,
     pub locked: Option<bool>,
+    pub auto_archive_duration: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct StartThreadFromMessageRequest {
+    pub name: String,
+    pub auto_archive_duration: Option<i64>,
 }
 
 #[derive(Deserialize)]
@@ -1780,22 +3300,83 @@ pub struct UpdateForumSortOrderRequest {
     pub sort_order: i32,
 }
 
+/// Inactivity periods, in minutes, after which a thread is archived.
+const THREAD_AUTO_ARCHIVE_DURATIONS: [i64; 4] = [60, 1440, 4320, 10080];
+const DEFAULT_THREAD_AUTO_ARCHIVE_DURATION: i64 = 1440;
+
+fn validate_auto_archive_duration(minutes: i64) -> Result<i64, ApiError> {
+    if THREAD_AUTO_ARCHIVE_DURATIONS.contains(&minutes) {
+        Ok(minutes)
+    } else {
+        Err(ApiError::BadRequest(
+            "auto_archive_duration must be one of 60, 1440, 4320 or 10080".into(),
+        ))
+    }
+}
+
 pub async fn create_thread(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(channel_id): Path<i64>,
     Json(body): Json<CreateThreadRequest>,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
-    if body.name.trim().is_empty() || body.name.len() > 100 {
+    let starter_message_id = body
+        .message_id
+        .as_deref()
+        .map(|raw| {
+            raw.parse::<i64>()
+                .map_err(|_| ApiError::BadRequest("Invalid message_id".into()))
+        })
+        .transpose()?;
+    start_thread(
+        &state,
+        auth.user_id,
+        channel_id,
+        &body.name,
+        starter_message_id,
+        body.auto_archive_duration,
+    )
+    .await
+}
+
+pub async fn start_thread_from_message(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((channel_id, message_id)): Path<(i64, i64)>,
+    Json(body): Json<StartThreadFromMessageRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    start_thread(
+        &state,
+        auth.user_id,
+        channel_id,
+        &body.name,
+        Some(message_id),
+        body.auto_archive_duration,
+    )
+    .await
+}
+
+async fn start_thread(
+    state: &AppState,
+    user_id: i64,
+    channel_id: i64,
+    name: &str,
+    starter_message_id: Option<i64>,
+    auto_archive_duration: Option<i64>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if name.trim().is_empty() || name.len() > 100 {
         return Err(ApiError::BadRequest(
             "Thread name must be 1-100 characters".into(),
         ));
     }
-    if contains_dangerous_markup(&body.name) {
+    if contains_dangerous_markup(name) {
         return Err(ApiError::BadRequest(
             "Thread name contains unsafe markup".into(),
         ));
     }
+    let auto_archive_duration = validate_auto_archive_duration(
+        auto_archive_duration.unwrap_or(DEFAULT_THREAD_AUTO_ARCHIVE_DURATION),
+    )?;
 
     let parent_channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
@@ -1810,36 +3391,28 @@ pub async fn create_thread(
     }
 
     ensure_channel_permissions(
-        &state,
+        state,
         &parent_channel,
-        auth.user_id,
+        user_id,
         &[Permissions::VIEW_CHANNEL, Permissions::SEND_MESSAGES],
     )
     .await?;
+    ensure_can_post_read_only(state, &parent_channel, user_id).await?;
 
     let guild_id = parent_channel
         .guild_id()
         .ok_or(ApiError::BadRequest("Cannot create threads in DMs".into()))?;
 
-    let auto_archive_duration = body.auto_archive_duration.unwrap_or(1440);
-    let starter_message_id = match body.message_id.as_deref() {
-        Some(raw_message_id) => {
-            let parsed_message_id = raw_message_id
-                .parse::<i64>()
-                .map_err(|_| ApiError::BadRequest("Invalid message_id".into()))?;
-            let starter_message = paracord_db::messages::get_message(&state.db, parsed_message_id)
-                .await
-                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-                .ok_or(ApiError::BadRequest("Starter message not found".into()))?;
-            if starter_message.channel_id != channel_id {
-                return Err(ApiError::BadRequest(
-                    "Starter message must belong to the parent channel".into(),
-                ));
-            }
-            Some(parsed_message_id)
-        }
-        None => None,
-    };
+    // The starter must be a message in the parent channel that the caller
+    // can see; ephemeral messages for someone else do not exist to them.
+    if let Some(message_id) = starter_message_id {
+        paracord_db::messages::get_channel_message_for_viewer(
+            &state.db, channel_id, message_id, user_id,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    }
 
     let thread_id = paracord_util::snowflake::generate(1);
     let thread = paracord_db::channels::create_thread(
@@ -1847,13 +3420,16 @@ pub async fn create_thread(
         thread_id,
         guild_id,
         channel_id,
-        body.name.trim(),
-        auth.user_id,
+        name.trim(),
+        user_id,
         auto_archive_duration,
         starter_message_id,
     )
     .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    .ok_or(ApiError::Conflict(
+        "A thread already exists for this message".into(),
+    ))?;
 
     let thread_json = channel_to_json(&thread);
 
@@ -1864,6 +3440,30 @@ pub async fn create_thread(
     Ok((StatusCode::CREATED, Json(thread_json)))
 }
 
+fn thread_is_locked(thread: &paracord_db::channels::ChannelRow) -> bool {
+    thread
+        .thread_metadata
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<Value>(raw).ok())
+        .and_then(|metadata| metadata.get("locked")?.as_bool())
+        .unwrap_or(false)
+}
+
+/// Archive every thread idle for longer than its `auto_archive_duration`
+/// and announce each one with `THREAD_UPDATE`. Returns how many were
+/// archived.
+pub async fn auto_archive_threads(state: &AppState) -> Result<usize, ApiError> {
+    let archived = paracord_db::channels::archive_inactive_threads(&state.db, chrono::Utc::now())
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    for thread in &archived {
+        state
+            .event_bus
+            .dispatch("THREAD_UPDATE", channel_to_json(thread), thread.guild_id());
+    }
+    Ok(archived.len())
+}
+
 pub async fn get_threads(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -1964,7 +3564,12 @@ pub async fn update_thread(
         .await?;
     }
 
-    if body.name.is_some() && !is_thread_owner {
+    let auto_archive_duration = body
+        .auto_archive_duration
+        .map(validate_auto_archive_duration)
+        .transpose()?;
+
+    if (body.name.is_some() || auto_archive_duration.is_some()) && !is_thread_owner {
         ensure_channel_permissions(
             &state,
             &parent_channel,
@@ -1980,6 +3585,7 @@ pub async fn update_thread(
         body.name.as_deref(),
         body.archived,
         body.locked,
+        auto_archive_duration,
     )
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
@@ -2119,6 +3725,7 @@ pub async fn create_forum_post(
         &[Permissions::VIEW_CHANNEL, Permissions::SEND_MESSAGES],
     )
     .await?;
+    ensure_can_post_read_only(&state, &forum_channel, auth.user_id).await?;
 
     let guild_id = forum_channel.guild_id().ok_or(ApiError::BadRequest(
         "Cannot create forum posts in DMs".into(),
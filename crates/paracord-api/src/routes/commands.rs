@@ -43,29 +43,57 @@ fn validate_command_description(desc: &str) -> Result<(), ApiError> {
     Ok(())
 }
 
+/// Option types: 1 = SUB_COMMAND, 2 = SUB_COMMAND_GROUP, 3..=11 = value types
+/// (string, integer, boolean, user, channel, role, mentionable, number, attachment).
+const OPTION_TYPE_SUB_COMMAND: i64 = 1;
+const OPTION_TYPE_SUB_COMMAND_GROUP: i64 = 2;
+const OPTION_TYPE_MAX: i64 = 11;
+
 fn validate_options(options: &[serde_json::Value]) -> Result<(), ApiError> {
     if options.len() > MAX_OPTIONS {
         return Err(ApiError::BadRequest(format!(
             "Maximum {MAX_OPTIONS} options allowed"
         )));
     }
+    let mut seen_names = std::collections::HashSet::new();
     for opt in options {
         // Validate option name
-        if let Some(name) = opt.get("name").and_then(|v| v.as_str()) {
-            if name.is_empty() || name.len() > MAX_COMMAND_NAME_LEN {
-                return Err(ApiError::BadRequest(
-                    "Option name must be between 1 and 32 characters".into(),
-                ));
-            }
-            let valid = name
-                .chars()
-                .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
-            if !valid {
-                return Err(ApiError::BadRequest(
-                    "Option name must match ^[\\w-]{1,32}$".into(),
-                ));
-            }
+        let name = opt
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ApiError::BadRequest("Option name is required".into()))?;
+        if name.is_empty() || name.len() > MAX_COMMAND_NAME_LEN {
+            return Err(ApiError::BadRequest(
+                "Option name must be between 1 and 32 characters".into(),
+            ));
+        }
+        let valid = name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        if !valid {
+            return Err(ApiError::BadRequest(
+                "Option name must match ^[\\w-]{1,32}$".into(),
+            ));
+        }
+        if !seen_names.insert(name) {
+            return Err(ApiError::BadRequest(format!(
+                "Duplicate option name: {name}"
+            )));
         }
+        let description = opt
+            .get("description")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if description.is_empty() || description.len() > MAX_COMMAND_DESCRIPTION_LEN {
+            return Err(ApiError::BadRequest(
+                "Option description must be between 1 and 100 characters".into(),
+            ));
+        }
+        let option_type = opt
+            .get("type")
+            .and_then(|v| v.as_i64())
+            .filter(|t| (OPTION_TYPE_SUB_COMMAND..=OPTION_TYPE_MAX).contains(t))
+            .ok_or_else(|| ApiError::BadRequest("Invalid option type".into()))?;
         if let Some(choices) = opt.get("choices").and_then(|v| v.as_array()) {
             if choices.len() > MAX_CHOICES_PER_OPTION {
                 return Err(ApiError::BadRequest(format!(
@@ -73,8 +101,15 @@ fn validate_options(options: &[serde_json::Value]) -> Result<(), ApiError> {
                 )));
             }
         }
-        // Recursively validate nested options (sub-commands)
+        // Only sub-commands and groups may nest options
         if let Some(nested) = opt.get("options").and_then(|v| v.as_array()) {
+            if option_type != OPTION_TYPE_SUB_COMMAND
+                && option_type != OPTION_TYPE_SUB_COMMAND_GROUP
+            {
+                return Err(ApiError::BadRequest(
+                    "Only sub-commands and groups may have nested options".into(),
+                ));
+            }
             validate_options(nested)?;
         }
     }
@@ -100,9 +135,7 @@ async fn ensure_app_owner(
     Ok(app)
 }
 
-fn command_row_to_json(
-    row: &paracord_db::application_commands::ApplicationCommandRow,
-) -> Value {
+fn command_row_to_json(row: &paracord_db::application_commands::ApplicationCommandRow) -> Value {
     let options: Value = row
         .options
         .as_deref()
@@ -280,11 +313,15 @@ pub async fn update_global_command(
         return Err(ApiError::NotFound);
     }
 
-    let name = body.name.as_deref().map(|n| {
-        let trimmed = n.trim().to_lowercase();
-        validate_command_name(&trimmed)?;
-        Ok::<String, ApiError>(trimmed)
-    }).transpose()?;
+    let name = body
+        .name
+        .as_deref()
+        .map(|n| {
+            let trimmed = n.trim().to_lowercase();
+            validate_command_name(&trimmed)?;
+            Ok::<String, ApiError>(trimmed)
+        })
+        .transpose()?;
 
     if let Some(ref desc) = body.description {
         validate_command_description(desc)?;
@@ -370,9 +407,10 @@ pub async fn bulk_overwrite_global_commands(
         let options_json = if cmd.options.is_empty() {
             None
         } else {
-            Some(serde_json::to_string(&cmd.options).map_err(|e| {
-                ApiError::Internal(anyhow::anyhow!("serialize options: {}", e))
-            })?)
+            Some(
+                serde_json::to_string(&cmd.options)
+                    .map_err(|e| ApiError::Internal(anyhow::anyhow!("serialize options: {}", e)))?,
+            )
         };
         let default_member_permissions = cmd
             .default_member_permissions
@@ -397,7 +435,7 @@ pub async fn bulk_overwrite_global_commands(
     }
 
     // Build tuple refs for the DB call
-    let refs: Vec<(i64, &str, &str, Option<&str>, i16, Option<i64>, bool, bool)> = prepared
+    let refs: Vec<paracord_db::application_commands::CommandSpec<'_>> = prepared
         .iter()
         .map(|(id, name, desc, opts, cmd_type, perms, dm, nsfw)| {
             (
@@ -458,10 +496,9 @@ pub async fn list_guild_commands(
 ) -> Result<Json<Value>, ApiError> {
     ensure_app_owner(&state, app_id, auth.user_id).await?;
 
-    let rows =
-        paracord_db::application_commands::list_guild_commands(&state.db, app_id, guild_id)
-            .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let rows = paracord_db::application_commands::list_guild_commands(&state.db, app_id, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
     Ok(Json(json!(rows
         .iter()
@@ -488,9 +525,10 @@ pub async fn create_guild_command(
     }
 
     // Enforce max commands per scope
-    let existing = paracord_db::application_commands::list_guild_commands(&state.db, app_id, guild_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let existing =
+        paracord_db::application_commands::list_guild_commands(&state.db, app_id, guild_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     if existing.len() >= MAX_COMMANDS_PER_SCOPE {
         return Err(ApiError::BadRequest(format!(
             "Maximum {MAX_COMMANDS_PER_SCOPE} commands per scope"
@@ -506,9 +544,10 @@ pub async fn create_guild_command(
     let options_json = if body.options.is_empty() {
         None
     } else {
-        Some(serde_json::to_string(&body.options).map_err(|e| {
-            ApiError::Internal(anyhow::anyhow!("serialize options: {}", e))
-        })?)
+        Some(
+            serde_json::to_string(&body.options)
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!("serialize options: {}", e)))?,
+        )
     };
     let default_member_permissions = body
         .default_member_permissions
@@ -585,11 +624,15 @@ pub async fn update_guild_command(
         return Err(ApiError::NotFound);
     }
 
-    let name = body.name.as_deref().map(|n| {
-        let trimmed = n.trim().to_lowercase();
-        validate_command_name(&trimmed)?;
-        Ok::<String, ApiError>(trimmed)
-    }).transpose()?;
+    let name = body
+        .name
+        .as_deref()
+        .map(|n| {
+            let trimmed = n.trim().to_lowercase();
+            validate_command_name(&trimmed)?;
+            Ok::<String, ApiError>(trimmed)
+        })
+        .transpose()?;
 
     if let Some(ref desc) = body.description {
         validate_command_description(desc)?;
@@ -694,9 +737,10 @@ pub async fn bulk_overwrite_guild_commands(
         let options_json = if cmd.options.is_empty() {
             None
         } else {
-            Some(serde_json::to_string(&cmd.options).map_err(|e| {
-                ApiError::Internal(anyhow::anyhow!("serialize options: {}", e))
-            })?)
+            Some(
+                serde_json::to_string(&cmd.options)
+                    .map_err(|e| ApiError::Internal(anyhow::anyhow!("serialize options: {}", e)))?,
+            )
         };
         let default_member_permissions = cmd
             .default_member_permissions
@@ -720,7 +764,7 @@ pub async fn bulk_overwrite_guild_commands(
         ));
     }
 
-    let refs: Vec<(i64, &str, &str, Option<&str>, i16, Option<i64>, bool, bool)> = prepared
+    let refs: Vec<paracord_db::application_commands::CommandSpec<'_>> = prepared
         .iter()
         .map(|(id, name, desc, opts, cmd_type, perms, dm, nsfw)| {
             (
@@ -737,10 +781,7 @@ pub async fn bulk_overwrite_guild_commands(
         .collect();
 
     let rows = paracord_db::application_commands::bulk_overwrite_guild_commands(
-        &state.db,
-        app_id,
-        guild_id,
-        &refs,
+        &state.db, app_id, guild_id, &refs,
     )
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
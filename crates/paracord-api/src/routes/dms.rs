@@ -1,16 +1,83 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
 use paracord_core::AppState;
+use paracord_db::dms::{
+    AddRecipientOutcome, DmRecipientRow, GROUP_DM_CHANNEL_TYPE, MAX_GROUP_DM_RECIPIENTS,
+};
 use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::error::ApiError;
 use crate::middleware::AuthUser;
 
+const MAX_GROUP_DM_NAME_LEN: usize = 100;
+
 #[derive(Debug, Deserialize)]
 pub struct CreateDmRequest {
     pub recipient_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CreateGroupDmRequest {
+    pub recipient_ids: Vec<String>,
+    pub name: Option<String>,
+}
+
+fn recipient_to_json(r: &DmRecipientRow) -> Value {
+    json!({
+        "id": r.user_id.to_string(),
+        "username": r.username,
+        "discriminator": r.discriminator,
+        "avatar_hash": r.avatar_hash,
+        "public_key": r.public_key,
+    })
+}
+
+fn group_dm_to_json(
+    channel: &paracord_db::channels::ChannelRow,
+    recipients: &[DmRecipientRow],
+) -> Value {
+    json!({
+        "id": channel.id.to_string(),
+        "type": channel.channel_type,
+        "channel_type": channel.channel_type,
+        "guild_id": null,
+        "name": channel.name,
+        "owner_id": channel.owner_id.map(|id| id.to_string()),
+        "last_message_id": channel.last_message_id.map(|id| id.to_string()),
+        "recipients": recipients
+            .iter()
+            .filter(|r| r.channel_id == channel.id)
+            .map(recipient_to_json)
+            .collect::<Vec<Value>>(),
+    })
+}
+
+/// Whether `user_id` may open a conversation with `other_id`: neither has
+/// blocked the other, and they are friends or share a guild.
+async fn ensure_can_dm(state: &AppState, user_id: i64, other_id: i64) -> Result<(), ApiError> {
+    let blocked = paracord_db::relationships::is_blocked(&state.db, user_id, other_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if blocked {
+        return Err(ApiError::Forbidden);
+    }
+
+    let are_friends = paracord_db::relationships::are_friends(&state.db, user_id, other_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let share_guild = paracord_db::members::share_any_guild(&state.db, user_id, other_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !are_friends && !share_guild {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(())
+}
+
 pub async fn list_dms(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -19,7 +86,7 @@ pub async fn list_dms(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let result: Vec<Value> = channels
+    let mut result: Vec<Value> = channels
         .iter()
         .map(|c| {
             json!({
@@ -40,6 +107,15 @@ pub async fn list_dms(
         })
         .collect();
 
+    let groups = paracord_db::dms::list_user_group_dms(&state.db, auth.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let group_ids: Vec<i64> = groups.iter().map(|g| g.id).collect();
+    let recipients = paracord_db::dms::get_dm_recipients(&state.db, &group_ids)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    result.extend(groups.iter().map(|g| group_dm_to_json(g, &recipients)));
+
     Ok(Json(json!(result)))
 }
 
@@ -59,27 +135,7 @@ pub async fn create_dm(
         ));
     }
 
-    let blocked = paracord_db::relationships::is_blocked_either_direction(
-        &state.db,
-        auth.user_id,
-        recipient_id,
-    )
-    .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    if blocked {
-        return Err(ApiError::Forbidden);
-    }
-
-    let are_friends =
-        paracord_db::relationships::are_friends(&state.db, auth.user_id, recipient_id)
-            .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let share_guild = paracord_db::members::share_any_guild(&state.db, auth.user_id, recipient_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    if !are_friends && !share_guild {
-        return Err(ApiError::Forbidden);
-    }
+    ensure_can_dm(&state, auth.user_id, recipient_id).await?;
 
     let recipient = paracord_db::users::get_user_by_id(&state.db, recipient_id)
         .await
@@ -118,3 +174,186 @@ pub async fn create_dm(
         })),
     ))
 }
+
+pub async fn create_group_dm(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<CreateGroupDmRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let mut recipient_ids: Vec<i64> = Vec::with_capacity(body.recipient_ids.len());
+    for raw in &body.recipient_ids {
+        let id: i64 = raw
+            .parse()
+            .map_err(|_| ApiError::BadRequest("Invalid recipient_ids".into()))?;
+        if id != auth.user_id && !recipient_ids.contains(&id) {
+            recipient_ids.push(id);
+        }
+    }
+    if recipient_ids.len() < 2 || recipient_ids.len() + 1 > MAX_GROUP_DM_RECIPIENTS {
+        return Err(ApiError::BadRequest(format!(
+            "A group DM needs 3-{MAX_GROUP_DM_RECIPIENTS} participants"
+        )));
+    }
+    let name = body
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    if name.is_some_and(|name| name.chars().count() > MAX_GROUP_DM_NAME_LEN) {
+        return Err(ApiError::BadRequest("Group DM name is too long".into()));
+    }
+
+    for &recipient_id in &recipient_ids {
+        paracord_db::users::get_user_by_id(&state.db, recipient_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .ok_or(ApiError::NotFound)?;
+        ensure_can_dm(&state, auth.user_id, recipient_id).await?;
+    }
+
+    let channel_id = paracord_util::snowflake::generate(1);
+    let channel = paracord_db::dms::create_group_dm(
+        &state.db,
+        channel_id,
+        auth.user_id,
+        &recipient_ids,
+        name,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let recipients = paracord_db::dms::get_dm_recipients(&state.db, &[channel.id])
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let channel_json = group_dm_to_json(&channel, &recipients);
+    state.event_bus.dispatch_to_users(
+        "CHANNEL_CREATE",
+        channel_json.clone(),
+        recipients.iter().map(|r| r.user_id).collect(),
+    );
+
+    Ok((StatusCode::CREATED, Json(channel_json)))
+}
+
+/// Load a group DM the caller takes part in.
+async fn load_group_dm(
+    state: &AppState,
+    channel_id: i64,
+    user_id: i64,
+) -> Result<paracord_db::channels::ChannelRow, ApiError> {
+    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let is_recipient = paracord_db::dms::is_dm_recipient(&state.db, channel_id, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !is_recipient {
+        return Err(ApiError::NotFound);
+    }
+    if channel.channel_type != GROUP_DM_CHANNEL_TYPE {
+        return Err(ApiError::BadRequest("Channel is not a group DM".into()));
+    }
+    Ok(channel)
+}
+
+pub async fn add_group_dm_recipient(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((channel_id, user_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, ApiError> {
+    let channel = load_group_dm(&state, channel_id, auth.user_id).await?;
+    let current = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if current.contains(&user_id) {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    paracord_db::users::get_user_by_id(&state.db, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    ensure_can_dm(&state, auth.user_id, user_id).await?;
+    // Nobody is pulled into a conversation with someone they blocked.
+    for &member_id in &current {
+        let blocked = paracord_db::relationships::is_blocked(&state.db, member_id, user_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        if blocked {
+            return Err(ApiError::Forbidden);
+        }
+    }
+
+    match paracord_db::dms::add_recipient(&state.db, channel_id, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    {
+        AddRecipientOutcome::Added => {}
+        AddRecipientOutcome::AlreadyPresent => return Ok(StatusCode::NO_CONTENT),
+        AddRecipientOutcome::Full => {
+            return Err(ApiError::BadRequest(format!(
+                "A group DM can have at most {MAX_GROUP_DM_RECIPIENTS} participants"
+            )))
+        }
+    }
+    let recipients = paracord_db::dms::get_dm_recipients(&state.db, &[channel_id])
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    state.event_bus.dispatch_to_users(
+        "CHANNEL_CREATE",
+        group_dm_to_json(&channel, &recipients),
+        vec![user_id],
+    );
+    if let Some(added) = recipients.iter().find(|r| r.user_id == user_id) {
+        state.event_bus.dispatch_to_users(
+            "CHANNEL_RECIPIENT_ADD",
+            json!({ "channel_id": channel_id.to_string(), "user": recipient_to_json(added) }),
+            current,
+        );
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Only the creator removes others; anyone else may remove themselves to
+/// leave the group.
+pub async fn remove_group_dm_recipient(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((channel_id, user_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, ApiError> {
+    let channel = load_group_dm(&state, channel_id, auth.user_id).await?;
+    let is_owner = channel.owner_id == Some(auth.user_id);
+    if user_id != auth.user_id && !is_owner {
+        return Err(ApiError::Forbidden);
+    }
+    if user_id == auth.user_id && is_owner {
+        return Err(ApiError::BadRequest(
+            "The group DM creator cannot leave it".into(),
+        ));
+    }
+
+    let recipients = paracord_db::dms::get_dm_recipients(&state.db, &[channel_id])
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let Some(removed) = recipients.iter().find(|r| r.user_id == user_id) else {
+        return Err(ApiError::NotFound);
+    };
+    paracord_db::dms::remove_recipient(&state.db, channel_id, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    state.event_bus.dispatch_to_users(
+        "CHANNEL_RECIPIENT_REMOVE",
+        json!({ "channel_id": channel_id.to_string(), "user": recipient_to_json(removed) }),
+        recipients.iter().map(|r| r.user_id).collect(),
+    );
+    state.event_bus.dispatch_to_users(
+        "CHANNEL_DELETE",
+        json!({ "id": channel_id.to_string(), "guild_id": null }),
+        vec![user_id],
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -3,7 +3,7 @@ use axum::{
     http::StatusCode,
     Json,
 };
-use paracord_core::AppState;
+use paracord_core::{AppConfig, AppState};
 use paracord_models::permissions::Permissions;
 use serde::Deserialize;
 use serde_json::{json, Value};
@@ -14,12 +14,16 @@ use crate::middleware::AuthUser;
 const MAX_EMOJI_NAME_LEN: usize = 32;
 const MAX_EMOJI_IMAGE_SIZE: usize = 256 * 1024; // 256 KB
 
-fn emoji_to_json(e: &paracord_db::emojis::EmojiRow) -> Value {
+fn emoji_to_json(config: &AppConfig, e: &paracord_db::emojis::EmojiRow) -> Value {
     json!({
         "id": e.id.to_string(),
         "guild_id": e.guild_id.to_string(),
         "name": e.name,
         "animated": e.animated,
+        "url": config.media_url(&format!(
+            "/api/v1/guilds/{}/emojis/{}/image",
+            e.guild_id, e.id
+        )),
         "creator_id": e.creator_id.map(|id| id.to_string()),
         "created_at": e.created_at.to_rfc3339(),
     })
@@ -70,7 +74,10 @@ pub async fn list_guild_emojis(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let result: Vec<Value> = emojis.iter().map(emoji_to_json).collect();
+    let result: Vec<Value> = emojis
+        .iter()
+        .map(|e| emoji_to_json(&state.config, e))
+        .collect();
     Ok(Json(json!(result)))
 }
 
@@ -179,7 +186,7 @@ pub async fn create_emoji(
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let emoji_json = emoji_to_json(&emoji);
+    let emoji_json = emoji_to_json(&state.config, &emoji);
 
     state.event_bus.dispatch(
         "GUILD_EMOJIS_UPDATE",
@@ -226,7 +233,7 @@ pub async fn update_emoji(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let emoji_json = emoji_to_json(&updated);
+    let emoji_json = emoji_to_json(&state.config, &updated);
 
     state.event_bus.dispatch(
         "GUILD_EMOJIS_UPDATE",
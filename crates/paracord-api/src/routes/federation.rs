@@ -16,6 +16,9 @@ use serde_json::{json, Value};
 
 use crate::error::ApiError;
 use crate::middleware::AdminUser;
+use crate::reaction_emoji::reaction_skin_tone_policy;
+use crate::routes::audit;
+use crate::routes::security;
 
 fn parse_signing_key() -> Option<SigningKey> {
     let raw = std::env::var("PARACORD_FEDERATION_SIGNING_KEY_HEX").ok()?;
@@ -493,6 +496,84 @@ fn sanitize_remote_username(localpart: &str, fallback: &str) -> String {
     out
 }
 
+#[derive(Debug, Clone, Copy)]
+enum PeerBudget {
+    /// Inbound events, invites, joins and leaves per minute.
+    Events,
+    /// Remote users materialized locally per hour.
+    UserCreates,
+}
+
+impl PeerBudget {
+    fn name(self) -> &'static str {
+        match self {
+            PeerBudget::Events => "events",
+            PeerBudget::UserCreates => "user_creates",
+        }
+    }
+
+    fn window_seconds(self) -> i64 {
+        match self {
+            PeerBudget::Events => 60,
+            PeerBudget::UserCreates => 3600,
+        }
+    }
+}
+
+/// Spend one unit of `peer`'s `budget`, or reject with 429 once `limit` is
+/// used up within the current window. `None` or `0` disables the limit.
+/// Counters live in the shared rate-limit store, so every node behind the
+/// same database enforces one budget. A security event is recorded when a
+/// peer first goes over its budget in a window.
+async fn enforce_peer_rate_limit(
+    state: &AppState,
+    peer: &str,
+    budget: PeerBudget,
+    limit: Option<u32>,
+    headers: Option<&HeaderMap>,
+) -> Result<(), ApiError> {
+    let Some(limit) = limit.filter(|limit| *limit > 0) else {
+        return Ok(());
+    };
+    let key = format!("fed:{}:{}", budget.name(), peer.to_ascii_lowercase());
+    let count = state
+        .rate_limits
+        .increment(
+            &key,
+            budget.window_seconds(),
+            chrono::Utc::now().timestamp(),
+        )
+        .await
+        .unwrap_or(0);
+    if count <= i64::from(limit) {
+        return Ok(());
+    }
+    if count == i64::from(limit) + 1 {
+        tracing::warn!(
+            peer,
+            budget = budget.name(),
+            limit,
+            "federation peer exceeded its rate limit"
+        );
+        security::log_security_event(
+            state,
+            "federation.peer.rate_limited",
+            None,
+            None,
+            None,
+            headers,
+            Some(json!({
+                "peer": peer,
+                "budget": budget.name(),
+                "limit": limit,
+                "window_seconds": budget.window_seconds(),
+            })),
+        )
+        .await;
+    }
+    Err(ApiError::RateLimited)
+}
+
 async fn ensure_remote_user_mapping(
     state: &AppState,
     identity: &FederatedIdentity,
@@ -505,25 +586,14 @@ async fn ensure_remote_user_mapping(
         return Ok(existing.local_user_id);
     }
 
-    // Per-peer rate limiting on remote user creation
-    if let Some(limit) = state.config.federation_max_user_creates_per_peer_per_hour {
-        if limit > 0 {
-            let now = chrono::Utc::now().timestamp();
-            let hour = now / 3600;
-            let bucket_key = format!("fed:user_create:{}", identity.server);
-            let count = paracord_db::rate_limits::increment_window_counter(
-                &state.db,
-                &bucket_key,
-                hour,
-                3600,
-            )
-            .await
-            .unwrap_or(0);
-            if count > limit as i64 {
-                return Err(ApiError::RateLimited);
-            }
-        }
-    }
+    enforce_peer_rate_limit(
+        state,
+        &identity.server,
+        PeerBudget::UserCreates,
+        state.config.federation_max_user_creates_per_peer_per_hour,
+        None,
+    )
+    .await?;
 
     let digest = paracord_federation::transport::sha256_hex(remote_id.as_bytes());
     let username = format!(
@@ -785,25 +855,14 @@ pub async fn ingest_event(
     )
     .await?;
 
-    // Per-peer rate limiting on event ingestion
-    if let Some(limit) = state.config.federation_max_events_per_peer_per_minute {
-        if limit > 0 {
-            let now = chrono::Utc::now().timestamp();
-            let minute = now / 60;
-            let bucket_key = format!("fed:ingest:{}", transport.origin);
-            let count = paracord_db::rate_limits::increment_window_counter(
-                &state.db,
-                &bucket_key,
-                minute,
-                60,
-            )
-            .await
-            .unwrap_or(0);
-            if count > limit as i64 {
-                return Err(ApiError::RateLimited);
-            }
-        }
-    }
+    enforce_peer_rate_limit(
+        &state,
+        &transport.origin,
+        PeerBudget::Events,
+        state.config.federation_max_events_per_peer_per_minute,
+        Some(&headers),
+    )
+    .await?;
 
     // Validate content size and depth
     validate_federation_content(&payload.content)?;
@@ -1536,12 +1595,20 @@ async fn dispatch_federated_reaction_add(state: &AppState, payload: &FederationE
         return;
     };
     let emoji = content_str(&payload.content, "emoji").unwrap_or("");
-    if emoji.is_empty() {
+    let Ok(emoji_key) = reaction_skin_tone_policy().canonicalize(emoji) else {
         return;
-    }
-    if paracord_db::reactions::add_reaction(&state.db, local_message_id, local_user_id, emoji, None)
-        .await
-        .is_err()
+    };
+    if paracord_db::reactions::add_reaction(
+        &state.db,
+        local_message_id,
+        local_user_id,
+        &emoji_key,
+        emoji,
+        None,
+        false,
+    )
+    .await
+    .is_err()
     {
         return;
     }
@@ -1560,7 +1627,8 @@ async fn dispatch_federated_reaction_add(state: &AppState, payload: &FederationE
             "user_id": local_user_id.to_string(),
             "channel_id": channel_id.to_string(),
             "message_id": local_message_id.to_string(),
-            "emoji": emoji,
+            "emoji": emoji_key,
+            "emoji_display": emoji,
         }),
         guild_id,
     );
@@ -1577,12 +1645,17 @@ async fn dispatch_federated_reaction_remove(state: &AppState, payload: &Federati
         return;
     };
     let emoji = content_str(&payload.content, "emoji").unwrap_or("");
-    if emoji.is_empty() {
+    let Ok(emoji_key) = reaction_skin_tone_policy().canonicalize(emoji) else {
         return;
-    }
-    if paracord_db::reactions::remove_reaction(&state.db, local_message_id, local_user_id, emoji)
-        .await
-        .is_err()
+    };
+    if paracord_db::reactions::remove_reaction(
+        &state.db,
+        local_message_id,
+        local_user_id,
+        &emoji_key,
+    )
+    .await
+    .is_err()
     {
         return;
     }
@@ -1601,7 +1674,7 @@ async fn dispatch_federated_reaction_remove(state: &AppState, payload: &Federati
             "user_id": local_user_id.to_string(),
             "channel_id": channel_id.to_string(),
             "message_id": local_message_id.to_string(),
-            "emoji": emoji,
+            "emoji": emoji_key,
         }),
         guild_id,
     );
@@ -1636,12 +1709,35 @@ async fn dispatch_federated_member_join(state: &AppState, payload: &FederationEv
     let Ok(local_user_id) = ensure_remote_user_mapping(state, &identity).await else {
         return;
     };
+    // A failed lookup counts as banned rather than letting the join through.
+    if paracord_db::bans::is_banned(&state.db, local_user_id, guild_id, chrono::Utc::now())
+        .await
+        .unwrap_or(true)
+    {
+        return;
+    }
     let room_id = if payload.room_id.trim().is_empty() {
         canonical_local_room_id(&service, guild_id)
     } else {
         payload.room_id.clone()
     };
-    let _ = paracord_db::members::add_member(&state.db, local_user_id, guild_id).await;
+    if paracord_db::members::add_member(&state.db, local_user_id, guild_id)
+        .await
+        .is_ok()
+    {
+        audit::log_federated_action(
+            state,
+            guild_id,
+            local_user_id,
+            audit::ACTION_FEDERATED_MEMBER_JOIN,
+            Some(local_user_id),
+            &payload.origin_server,
+            &identity.to_canonical(),
+            Some(&payload.event_id),
+            None,
+        )
+        .await;
+    }
     let _ = paracord_db::roles::add_member_role(&state.db, local_user_id, guild_id, guild_id).await;
     let _ = paracord_db::federation::upsert_room_membership(
         &state.db,
@@ -1709,7 +1805,23 @@ async fn dispatch_federated_member_leave(state: &AppState, payload: &FederationE
     } else {
         payload.room_id.clone()
     };
-    let _ = paracord_db::members::remove_member(&state.db, mapping.local_user_id, guild_id).await;
+    if paracord_db::members::remove_member(&state.db, mapping.local_user_id, guild_id)
+        .await
+        .is_ok()
+    {
+        audit::log_federated_action(
+            state,
+            guild_id,
+            mapping.local_user_id,
+            audit::ACTION_FEDERATED_MEMBER_LEAVE,
+            Some(mapping.local_user_id),
+            &payload.origin_server,
+            &identity.to_canonical(),
+            Some(&payload.event_id),
+            None,
+        )
+        .await;
+    }
     let _ = paracord_db::federation::delete_room_membership(
         &state.db,
         &room_id,
@@ -1770,15 +1882,14 @@ pub async fn get_event(
     if !service.is_enabled() {
         return Err(ApiError::Forbidden);
     }
-    authorize_federation_read_request(&state, &service, &headers, uri.path()).await?;
-    let event = service
+    let scope = authorize_federation_read_request(&state, &service, &headers, uri.path()).await?;
+    let envelope = service
         .fetch_event(&state.db, &event_id)
         .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    match event {
-        Some(envelope) => Ok(Json(json!(envelope))),
-        None => Err(ApiError::NotFound),
-    }
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    scope.ensure_room(&envelope.room_id)?;
+    Ok(Json(json!(envelope)))
 }
 
 #[derive(Debug, Deserialize)]
@@ -1802,7 +1913,9 @@ pub async fn list_events(
         .path_and_query()
         .map(|pq| pq.as_str().to_string())
         .unwrap_or_else(|| uri.path().to_string());
-    authorize_federation_read_request(&state, &service, &headers, &path_and_query).await?;
+    let scope =
+        authorize_federation_read_request(&state, &service, &headers, &path_and_query).await?;
+    scope.ensure_room(&query.room_id)?;
 
     let since_depth = query.since_depth.unwrap_or(0).max(0);
     let limit = query.limit.unwrap_or(100).clamp(1, 1000);
@@ -1980,25 +2093,85 @@ pub async fn run_federation_catchup_once(
     }
 }
 
+/// How long a read token issued on join stays valid. Invite tokens last as
+/// long as the invite.
+const FEDERATION_READ_TOKEN_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// What a federation read request may see.
+enum FederationReadScope {
+    /// Signed peer request or the server-wide `PARACORD_FEDERATION_READ_TOKEN`.
+    Server,
+    /// Read token issued on invite/join; only events in this room.
+    Room(String),
+}
+
+impl FederationReadScope {
+    fn ensure_room(&self, room_id: &str) -> Result<(), ApiError> {
+        match self {
+            Self::Room(scope) if scope != room_id => Err(ApiError::Forbidden),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn hash_federation_read_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Mint a read token for `server_name` scoped to `room_id`, returning the
+/// token and its expiry (unix milliseconds).
+async fn issue_federation_read_token(
+    state: &AppState,
+    room_id: &str,
+    server_name: &str,
+    ttl_seconds: i64,
+) -> Result<(String, i64), ApiError> {
+    let token = crate::routes::auth::random_token_hex(32);
+    let expires_at_ms = chrono::Utc::now().timestamp_millis() + ttl_seconds * 1000;
+    paracord_db::federation::insert_read_token(
+        &state.db,
+        &hash_federation_read_token(&token),
+        room_id,
+        server_name,
+        expires_at_ms,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    Ok((token, expires_at_ms))
+}
+
 async fn authorize_federation_read_request(
     state: &AppState,
     service: &FederationService,
     headers: &HeaderMap,
     path: &str,
-) -> Result<(), ApiError> {
-    if let Some(expected) = optional_federation_read_token() {
-        let presented = headers
-            .get("x-paracord-federation-token")
-            .and_then(|v| v.to_str().ok())
-            .map(str::trim);
-        if presented == Some(expected.as_str()) {
-            return Ok(());
+) -> Result<FederationReadScope, ApiError> {
+    let presented = headers
+        .get("x-paracord-federation-token")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    if let Some(presented) = presented {
+        if optional_federation_read_token().as_deref() == Some(presented) {
+            return Ok(FederationReadScope::Server);
+        }
+        let room = paracord_db::federation::get_read_token_room(
+            &state.db,
+            &hash_federation_read_token(presented),
+            chrono::Utc::now().timestamp_millis(),
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        if let Some(room) = room {
+            return Ok(FederationReadScope::Room(room));
         }
     }
 
     verify_transport_request(state, service, headers, "GET", path, &[], None, false)
         .await
-        .map(|_| ())
+        .map(|_| FederationReadScope::Server)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2050,7 +2223,7 @@ pub async fn invite(
     }
 
     let body_bytes = serde_json::to_vec(&body).unwrap_or_default();
-    verify_transport_request(
+    let transport = verify_transport_request(
         &state,
         &service,
         &headers,
@@ -2061,6 +2234,14 @@ pub async fn invite(
         true,
     )
     .await?;
+    enforce_peer_rate_limit(
+        &state,
+        &transport.origin,
+        PeerBudget::Events,
+        state.config.federation_max_events_per_peer_per_minute,
+        Some(&headers),
+    )
+    .await?;
 
     let guild_id = parse_local_room_guild_id(&service, &body.room_id)
         .ok_or(ApiError::BadRequest("Invalid room_id format".to_string()))?;
@@ -2078,6 +2259,7 @@ pub async fn invite(
         .or_else(|| channels.first())
         .map(|ch| ch.id);
     let canonical_room_id = canonical_local_room_id(&service, guild_id);
+    let expires_in_seconds = body.max_age_seconds.unwrap_or(3600).clamp(60, 86_400);
 
     Ok(Json(json!({
         "accepted": true,
@@ -2086,7 +2268,7 @@ pub async fn invite(
         "guild_name": guild.name,
         "default_channel_id": default_channel_id.map(|id| id.to_string()),
         "join_endpoint": "/_paracord/federation/v1/join",
-        "expires_in_seconds": body.max_age_seconds.unwrap_or(3600).clamp(60, 86_400),
+        "expires_in_seconds": expires_in_seconds,
     })))
 }
 
@@ -2100,7 +2282,7 @@ pub async fn join(
         return Err(ApiError::Forbidden);
     }
     let body_bytes = serde_json::to_vec(&body).unwrap_or_default();
-    verify_transport_request(
+    let transport = verify_transport_request(
         &state,
         &service,
         &headers,
@@ -2111,6 +2293,14 @@ pub async fn join(
         true,
     )
     .await?;
+    enforce_peer_rate_limit(
+        &state,
+        &transport.origin,
+        PeerBudget::Events,
+        state.config.federation_max_events_per_peer_per_minute,
+        Some(&headers),
+    )
+    .await?;
 
     let identity = FederatedIdentity::parse(&body.user_id)
         .ok_or(ApiError::BadRequest("Invalid user_id".to_string()))?;
@@ -2125,6 +2315,12 @@ pub async fn join(
     let canonical_room_id = canonical_local_room_id(&service, guild_id);
 
     let local_user_id = ensure_remote_user_mapping(&state, &identity).await?;
+    if paracord_db::bans::is_banned(&state.db, local_user_id, guild_id, chrono::Utc::now())
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    {
+        return Err(ApiError::Forbidden);
+    }
     paracord_db::members::add_member(&state.db, local_user_id, guild_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
@@ -2162,11 +2358,21 @@ pub async fn join(
         Some(guild_id),
     );
 
+    let (read_token, read_token_expires_at_ms) = issue_federation_read_token(
+        &state,
+        &canonical_room_id,
+        &transport.origin,
+        FEDERATION_READ_TOKEN_TTL_SECONDS,
+    )
+    .await?;
+
     Ok(Json(json!({
         "joined": true,
         "room_id": canonical_room_id,
         "guild_id": guild_id.to_string(),
         "local_user_id": local_user_id.to_string(),
+        "read_token": read_token,
+        "read_token_expires_at_ms": read_token_expires_at_ms,
     })))
 }
 
@@ -2180,7 +2386,7 @@ pub async fn leave(
         return Err(ApiError::Forbidden);
     }
     let body_bytes = serde_json::to_vec(&body).unwrap_or_default();
-    verify_transport_request(
+    let transport = verify_transport_request(
         &state,
         &service,
         &headers,
@@ -2191,6 +2397,14 @@ pub async fn leave(
         true,
     )
     .await?;
+    enforce_peer_rate_limit(
+        &state,
+        &transport.origin,
+        PeerBudget::Events,
+        state.config.federation_max_events_per_peer_per_minute,
+        Some(&headers),
+    )
+    .await?;
 
     let identity = FederatedIdentity::parse(&body.user_id)
         .ok_or(ApiError::BadRequest("Invalid user_id".to_string()))?;
@@ -2228,6 +2442,20 @@ pub async fn leave(
         &identity.to_canonical(),
     )
     .await;
+    // The peer keeps its read tokens while any of its users are still in the
+    // room.
+    let remaining =
+        paracord_db::federation::list_room_member_servers(&state.db, &canonical_room_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !remaining.contains(&transport.origin) {
+        let _ = paracord_db::federation::delete_read_tokens(
+            &state.db,
+            &canonical_room_id,
+            &transport.origin,
+        )
+        .await;
+    }
 
     Ok(Json(json!({
         "left": removed,
@@ -2307,22 +2535,32 @@ pub async fn media_token(
     .await?;
     paracord_core::permissions::require_permission(perms, Permissions::VIEW_CHANNEL)?;
     paracord_core::permissions::require_permission(perms, Permissions::CONNECT)?;
+    crate::routes::voice::ensure_voice_channel_capacity(&state, &channel, local_user_id, perms)
+        .await?;
     let user = paracord_db::users::get_user_by_id(&state.db, local_user_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
         .ok_or(ApiError::NotFound)?;
+    let member = paracord_db::members::get_member(&state.db, local_user_id, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::Forbidden)?;
 
     let session_id = uuid::Uuid::new_v4().to_string();
+    let ttl_seconds = federation_media_token_ttl(&state.config);
     let join_resp = state
         .voice
-        .join_channel(
+        .join_channel_with_token_ttl(
             channel_id,
             guild_id,
             local_user_id,
             &user.username,
             &session_id,
-            true,
+            !(member.mute || member.deaf),
+            perms.contains(Permissions::PRIORITY_SPEAKER),
             paracord_media::AudioBitrate::default(),
+            paracord_media::voice::room_max_participants(channel.user_limit),
+            ttl_seconds,
         )
         .await
         .map_err(ApiError::Internal)?;
@@ -2333,6 +2571,7 @@ pub async fn media_token(
         "room_name": join_resp.room_name,
         "session_id": session_id,
         "local_user_id": local_user_id.to_string(),
+        "expires_in_seconds": ttl_seconds,
     })))
 }
 
@@ -2408,7 +2647,7 @@ pub async fn media_relay(
     paracord_core::permissions::require_permission(perms, Permissions::CONNECT)?;
     paracord_core::permissions::require_permission(perms, Permissions::STREAM)?;
 
-    match body.action.as_str() {
+    let result = match body.action.as_str() {
         "start_stream" => {
             let user = paracord_db::users::get_user_by_id(&state.db, local_user_id)
                 .await
@@ -2443,7 +2682,22 @@ pub async fn media_relay(
         _ => Err(ApiError::BadRequest(
             "Unsupported media relay action".to_string(),
         )),
+    };
+    if result.is_ok() {
+        audit::log_federated_action(
+            &state,
+            guild_id,
+            local_user_id,
+            audit::ACTION_FEDERATED_MEDIA_RELAY,
+            Some(channel_id),
+            &body.origin_server,
+            &identity.to_canonical(),
+            None,
+            Some(json!({ "action": body.action })),
+        )
+        .await;
     }
+    result
 }
 
 // ── Federated Server Management (admin-only) ────────────────────────────────
@@ -2532,6 +2786,11 @@ pub async fn add_server(
     )
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !body.trusted {
+        paracord_db::federation::delete_server_read_tokens(&state.db, &body.server_name)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
 
     Ok((
         StatusCode::CREATED,
@@ -2576,6 +2835,9 @@ pub async fn delete_server(
     let deleted = paracord_db::federation::delete_federated_server(&state.db, &server_name)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    paracord_db::federation::delete_server_read_tokens(&state.db, &server_name)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     if deleted {
         Ok(StatusCode::NO_CONTENT)
     } else {
@@ -2598,13 +2860,52 @@ fn federation_file_hmac(key: &str, message: &str) -> String {
     paracord_federation::hex_encode(&hasher.finalize())
 }
 
+const FEDERATION_MEDIA_TOKEN_TTL_RANGE: std::ops::RangeInclusive<u64> =
+    60..=paracord_media::livekit::LIVEKIT_TOKEN_TTL_SECONDS;
+/// Upper bound stays well under the 24h transport replay cache window, which
+/// also records redeemed file token nonces.
+const FEDERATION_FILE_TOKEN_TTL_RANGE: std::ops::RangeInclusive<u64> = 30..=3600;
+
+fn federation_media_token_ttl(config: &paracord_core::AppConfig) -> u64 {
+    config.federation_media_token_ttl_seconds.clamp(
+        *FEDERATION_MEDIA_TOKEN_TTL_RANGE.start(),
+        *FEDERATION_MEDIA_TOKEN_TTL_RANGE.end(),
+    )
+}
+
+fn federation_file_token_ttl(config: &paracord_core::AppConfig) -> u64 {
+    config.federation_file_token_ttl_seconds.clamp(
+        *FEDERATION_FILE_TOKEN_TTL_RANGE.start(),
+        *FEDERATION_FILE_TOKEN_TTL_RANGE.end(),
+    )
+}
+
+/// What a federation file token grants: one download of one attachment in
+/// one channel, by the server that requested it, until `exp`.
+#[derive(Debug, PartialEq, Eq)]
+struct FederationFileTokenClaims {
+    attachment_id: i64,
+    channel_id: i64,
+    exp: i64,
+    nonce: String,
+    requester_server: String,
+}
+
 fn mint_federation_file_token(
     jwt_secret: &str,
     attachment_id: i64,
+    channel_id: i64,
     requester_server: &str,
+    ttl_seconds: u64,
+    now: i64,
 ) -> (String, i64) {
-    let exp = chrono::Utc::now().timestamp() + 300;
-    let payload = format!("{}:{}:{}", attachment_id, requester_server, exp);
+    let exp = now + ttl_seconds as i64;
+    let nonce = uuid::Uuid::new_v4().simple().to_string();
+    // The requester goes last: server names may carry a `:port`.
+    let payload = format!(
+        "{}:{}:{}:{}:{}",
+        attachment_id, channel_id, exp, nonce, requester_server
+    );
     let mac = federation_file_hmac(jwt_secret, &payload);
     let token = format!("{}.{}", payload, mac);
     (token, exp)
@@ -2614,7 +2915,8 @@ fn validate_federation_file_token(
     jwt_secret: &str,
     token: &str,
     expected_attachment_id: i64,
-) -> Result<(), ApiError> {
+    now: i64,
+) -> Result<FederationFileTokenClaims, ApiError> {
     let dot_pos = token.rfind('.').ok_or_else(|| ApiError::Unauthorized)?;
     let payload = &token[..dot_pos];
     let mac = &token[dot_pos + 1..];
@@ -2624,21 +2926,44 @@ fn validate_federation_file_token(
         return Err(ApiError::Unauthorized);
     }
 
-    let parts: Vec<&str> = payload.splitn(3, ':').collect();
-    if parts.len() != 3 {
+    let parts: Vec<&str> = payload.splitn(5, ':').collect();
+    if parts.len() != 5 || parts[3].is_empty() || parts[4].is_empty() {
         return Err(ApiError::Unauthorized);
     }
-    let attachment_id: i64 = parts[0].parse().map_err(|_| ApiError::Unauthorized)?;
-    let exp: i64 = parts[2].parse().map_err(|_| ApiError::Unauthorized)?;
+    let claims = FederationFileTokenClaims {
+        attachment_id: parts[0].parse().map_err(|_| ApiError::Unauthorized)?,
+        channel_id: parts[1].parse().map_err(|_| ApiError::Unauthorized)?,
+        exp: parts[2].parse().map_err(|_| ApiError::Unauthorized)?,
+        nonce: parts[3].to_string(),
+        requester_server: parts[4].to_string(),
+    };
 
-    if attachment_id != expected_attachment_id {
+    if claims.attachment_id != expected_attachment_id {
         return Err(ApiError::Unauthorized);
     }
-    if chrono::Utc::now().timestamp() > exp {
+    if now > claims.exp {
         return Err(ApiError::Unauthorized);
     }
 
-    Ok(())
+    Ok(claims)
+}
+
+/// Mark a file token as redeemed. Returns false if it was already used.
+async fn consume_federation_file_token(
+    state: &AppState,
+    claims: &FederationFileTokenClaims,
+) -> Result<bool, ApiError> {
+    let key = paracord_federation::transport::sha256_hex(
+        format!("file-token\n{}", claims.nonce).as_bytes(),
+    );
+    paracord_db::federation::insert_transport_replay_key(
+        &state.db,
+        &claims.requester_server,
+        &key,
+        claims.exp * 1000,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2730,8 +3055,15 @@ pub async fn file_token(
     paracord_core::permissions::require_permission(perms, Permissions::VIEW_CHANNEL)?;
     paracord_core::permissions::require_permission(perms, Permissions::READ_MESSAGE_HISTORY)?;
 
-    let (token, _exp) =
-        mint_federation_file_token(&state.config.jwt_secret, attachment_id, &transport.origin);
+    let ttl_seconds = federation_file_token_ttl(&state.config);
+    let (token, _exp) = mint_federation_file_token(
+        &state.config.jwt_secret,
+        attachment_id,
+        channel_id,
+        &transport.origin,
+        ttl_seconds,
+        chrono::Utc::now().timestamp(),
+    );
     let download_url = format!(
         "/_paracord/federation/v1/file/{}?token={}",
         attachment_id, token
@@ -2740,7 +3072,7 @@ pub async fn file_token(
     Ok(Json(json!({
         "token": token,
         "download_url": download_url,
-        "expires_in_seconds": 300,
+        "expires_in_seconds": ttl_seconds,
     })))
 }
 
@@ -2754,12 +3086,21 @@ pub async fn file_download(
     Path(attachment_id): Path<i64>,
     Query(query): Query<FileDownloadQuery>,
 ) -> Result<impl axum::response::IntoResponse, ApiError> {
-    validate_federation_file_token(&state.config.jwt_secret, &query.token, attachment_id)?;
+    let claims = validate_federation_file_token(
+        &state.config.jwt_secret,
+        &query.token,
+        attachment_id,
+        chrono::Utc::now().timestamp(),
+    )?;
 
     let attachment = paracord_db::attachments::get_attachment(&state.db, attachment_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
         .ok_or(ApiError::NotFound)?;
+    // The attachment must still live in the channel the token was issued for.
+    if attachment.upload_channel_id != Some(claims.channel_id) {
+        return Err(ApiError::Unauthorized);
+    }
 
     let ext = std::path::Path::new(&attachment.filename)
         .extension()
@@ -2779,6 +3120,11 @@ pub async fn file_download(
     } else {
         stored_data
     };
+    // Only spend the token once the file is in hand, so a storage or
+    // decryption failure leaves it usable for a retry.
+    if !consume_federation_file_token(&state, &claims).await? {
+        return Err(ApiError::Unauthorized);
+    }
 
     let content_type = attachment
         .content_type
@@ -2884,6 +3230,40 @@ mod tests {
         assert!(validate_federation_content(&oversized).is_err());
     }
 
+    #[test]
+    fn file_token_is_scoped_to_one_attachment() {
+        let now = 1_700_000_000;
+        let (token, exp) =
+            mint_federation_file_token("secret", 11, 22, "peer.example:8443", 300, now);
+        let claims = validate_federation_file_token("secret", &token, 11, now).expect("valid");
+        assert_eq!(claims.attachment_id, 11);
+        assert_eq!(claims.channel_id, 22);
+        assert_eq!(claims.exp, exp);
+        assert_eq!(claims.requester_server, "peer.example:8443");
+
+        assert!(validate_federation_file_token("secret", &token, 12, now).is_err());
+        assert!(validate_federation_file_token("other-secret", &token, 11, now).is_err());
+        let forged = token.replacen("11:", "12:", 1);
+        assert!(validate_federation_file_token("secret", &forged, 12, now).is_err());
+    }
+
+    #[test]
+    fn file_token_expires_after_its_ttl() {
+        let now = 1_700_000_000;
+        let (token, _) = mint_federation_file_token("secret", 11, 22, "peer.example", 60, now);
+        assert!(validate_federation_file_token("secret", &token, 11, now + 60).is_ok());
+        assert!(validate_federation_file_token("secret", &token, 11, now + 61).is_err());
+    }
+
+    #[test]
+    fn file_tokens_carry_distinct_nonces() {
+        let (a, _) = mint_federation_file_token("secret", 11, 22, "peer.example", 60, 0);
+        let (b, _) = mint_federation_file_token("secret", 11, 22, "peer.example", 60, 0);
+        let a = validate_federation_file_token("secret", &a, 11, 0).unwrap();
+        let b = validate_federation_file_token("secret", &b, 11, 0).unwrap();
+        assert_ne!(a.nonce, b.nonce);
+    }
+
     #[test]
     fn federation_content_accepts_reasonable_collection() {
         let ok = Value::Array((0..5_000).map(|idx| Value::Number(idx.into())).collect());
@@ -1,28 +1,41 @@
 use axum::{
-    extract::{Multipart, Path, State},
+    body::{Body, Bytes},
+    extract::{Multipart, Path, Query, State},
     http::{header, HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     Json,
 };
 use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{Algorithm, EncodingKey, Header as JwtHeader};
 use paracord_core::AppState;
 use paracord_models::permissions::Permissions;
+use paracord_util::at_rest::{ChunkedDecryptor, FileCryptor};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use unicode_normalization::UnicodeNormalization;
 
 use crate::error::ApiError;
 use crate::middleware::AuthUser;
 
 const PENDING_ATTACHMENT_TTL_MINUTES: i64 = 15;
 const PENDING_ATTACHMENT_CLEANUP_BATCH: i64 = 128;
+const MIN_ATTACHMENT_FILENAME_LENGTH: usize = 16;
 const MALWARE_SCAN_BIN_ENV: &str = "PARACORD_MALWARE_SCAN_BIN";
 const MALWARE_SCAN_ARGS_ENV: &str = "PARACORD_MALWARE_SCAN_ARGS";
 const MALWARE_SCAN_FAIL_CLOSED_ENV: &str = "PARACORD_MALWARE_SCAN_FAIL_CLOSED";
 const MALWARE_SCAN_INFECTED_EXIT_CODES_ENV: &str = "PARACORD_MALWARE_SCAN_INFECTED_EXIT_CODES";
 const MALWARE_QUARANTINE_PATH_ENV: &str = "PARACORD_MALWARE_QUARANTINE_PATH";
 const ATTACHMENT_AAD_PREFIX: &str = "attachment:";
+/// Stored bytes needed to recognise an attachment's encryption format.
+const ATTACHMENT_FORMAT_PROBE_LEN: usize = 8;
+const INLINE_TOKEN_PREFIX: &str = "attachment-inline:";
+/// Embed token lifetime when the server does not require tokens.
+const DEFAULT_INLINE_TOKEN_TTL_SECONDS: u64 = 300;
+const MAX_INLINE_TOKEN_TTL_SECONDS: u64 = 7 * 24 * 3600;
 
 fn attachment_aad(attachment_id: i64) -> String {
     format!("{ATTACHMENT_AAD_PREFIX}{attachment_id}")
@@ -35,6 +48,48 @@ fn sanitize_filename_for_disposition(filename: &str) -> String {
         .collect()
 }
 
+/// Bidirectional overrides and isolates, which can make a filename display
+/// with a different extension than it really has.
+fn is_bidi_control(ch: char) -> bool {
+    matches!(ch, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// NFC-normalize an uploaded filename and cap it at `max_chars` characters,
+/// truncating the stem so the extension survives. Names with control
+/// characters are rejected.
+pub(crate) fn normalize_upload_filename(raw: &str, max_chars: usize) -> Result<String, ApiError> {
+    let normalized: String = raw.nfc().collect();
+    if normalized
+        .chars()
+        .any(|ch| ch.is_control() || is_bidi_control(ch))
+    {
+        return Err(ApiError::BadRequest(
+            "Filename contains control characters".into(),
+        ));
+    }
+    let name = normalized.trim();
+    if name.is_empty() {
+        return Ok("upload".to_string());
+    }
+
+    let max_chars = max_chars.max(MIN_ATTACHMENT_FILENAME_LENGTH);
+    if name.chars().count() <= max_chars {
+        return Ok(name.to_string());
+    }
+    let ext = std::path::Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .filter(|ext| ext.chars().count() < max_chars / 2);
+    Ok(match ext {
+        Some(ext) => {
+            let stem = &name[..name.len() - ext.len() - 1];
+            let keep = max_chars - ext.chars().count() - 1;
+            format!("{}.{ext}", stem.chars().take(keep).collect::<String>())
+        }
+        None => name.chars().take(max_chars).collect(),
+    })
+}
+
 fn has_active_extension(filename: &str) -> bool {
     let ext = std::path::Path::new(filename)
         .extension()
@@ -62,7 +117,7 @@ fn body_looks_like_active_content(data: &[u8]) -> bool {
         || sample.contains("<svg")
 }
 
-fn normalized_content_type(filename: &str, claimed: Option<&str>) -> String {
+pub(crate) fn normalized_content_type(filename: &str, claimed: Option<&str>) -> String {
     let guessed = mime_guess::from_path(filename)
         .first_raw()
         .map(str::to_string);
@@ -134,6 +189,90 @@ fn build_content_disposition(filename: &str, allow_inline: bool) -> String {
     }
 }
 
+fn inline_token_mac(secret: &str, attachment_id: i64, expires: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{INLINE_TOKEN_PREFIX}{attachment_id}:{expires}").as_bytes());
+    mac
+}
+
+/// `{expires}.{hex hmac}` token that lets attachment `attachment_id` be
+/// served inline until `expires` (unix seconds).
+fn sign_inline_token(secret: &str, attachment_id: i64, expires: i64) -> String {
+    let digest = inline_token_mac(secret, attachment_id, expires)
+        .finalize()
+        .into_bytes();
+    let mut out = format!("{expires}.");
+    for b in digest {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+
+fn verify_inline_token(secret: &str, attachment_id: i64, token: &str, now: i64) -> bool {
+    let Some((expires, signature)) = token.split_once('.') else {
+        return false;
+    };
+    let Ok(expires) = expires.parse::<i64>() else {
+        return false;
+    };
+    if expires < now || signature.len() % 2 != 0 {
+        return false;
+    }
+    let Some(signature) = (0..signature.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(signature.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return false;
+    };
+    inline_token_mac(secret, attachment_id, expires)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+fn url_origin(raw: &str) -> Option<String> {
+    let url = url::Url::parse(raw).ok()?;
+    let origin = url.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// The site a download was requested from: the `Origin` header, else the
+/// origin of the `Referer`. `None` for direct navigation and native clients.
+fn request_origin(headers: &HeaderMap) -> Option<String> {
+    let header_value = |name| {
+        headers
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+    };
+    if let Some(origin) = header_value(header::ORIGIN) {
+        return Some(url_origin(origin).unwrap_or_else(|| origin.to_string()));
+    }
+    header_value(header::REFERER).map(|referer| url_origin(referer).unwrap_or_default())
+}
+
+/// Whether a download may be shown inline by the page that requested it.
+/// With `allowed` origins configured, requests from other sites only get
+/// `attachment`; requests naming no site are unaffected.
+fn inline_origin_allowed(
+    allowed: &[String],
+    public_url: Option<&str>,
+    headers: &HeaderMap,
+) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    let Some(origin) = request_origin(headers) else {
+        return true;
+    };
+    allowed
+        .iter()
+        .map(String::as_str)
+        .chain(public_url)
+        .filter_map(url_origin)
+        .any(|allowed| allowed == origin)
+}
+
 fn env_bool(name: &str, default: bool) -> bool {
     std::env::var(name)
         .ok()
@@ -259,11 +398,7 @@ async fn scan_upload_with_malware_hook(
     storage_path: &str,
     attachment_id: i64,
 ) -> Result<(), ApiError> {
-    let scan_bin = std::env::var(MALWARE_SCAN_BIN_ENV)
-        .ok()
-        .map(|v| v.trim().to_string())
-        .filter(|v| !v.is_empty());
-    if scan_bin.is_none() {
+    if malware_scan_bin().is_none() {
         return Ok(());
     }
 
@@ -281,12 +416,34 @@ async fn scan_upload_with_malware_hook(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let (scan_bin, scan_args) = build_scanner_command(&temp_file, filename).unwrap_or_else(|| {
-        (
-            scan_bin.unwrap_or_default(),
-            vec![temp_file.to_string_lossy().to_string()],
-        )
-    });
+    let result =
+        scan_file_with_malware_hook(&temp_file, filename, storage_path, attachment_id).await;
+    // Already gone if the scanner flagged it and it was quarantined.
+    let _ = tokio::fs::remove_file(&temp_file).await;
+    result
+}
+
+fn malware_scan_bin() -> Option<String> {
+    std::env::var(MALWARE_SCAN_BIN_ENV)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Run the configured malware scanner over `file`. An infected file is moved
+/// to quarantine; otherwise it is left where it is.
+async fn scan_file_with_malware_hook(
+    file: &std::path::Path,
+    filename: &str,
+    storage_path: &str,
+    attachment_id: i64,
+) -> Result<(), ApiError> {
+    let Some(scan_bin) = malware_scan_bin() else {
+        return Ok(());
+    };
+
+    let (scan_bin, scan_args) = build_scanner_command(file, filename)
+        .unwrap_or_else(|| (scan_bin, vec![file.to_string_lossy().to_string()]));
 
     let fail_closed = env_bool(MALWARE_SCAN_FAIL_CLOSED_ENV, true);
     let infected_codes = parse_infected_exit_codes();
@@ -297,14 +454,11 @@ async fn scan_upload_with_malware_hook(
         .await;
 
     match output {
-        Ok(result) if result.status.success() => {
-            let _ = tokio::fs::remove_file(&temp_file).await;
-            Ok(())
-        }
+        Ok(result) if result.status.success() => Ok(()),
         Ok(result) => {
             let exit_code = result.status.code().unwrap_or(-1);
             if infected_codes.contains(&exit_code) {
-                move_to_quarantine(&temp_file, storage_path, attachment_id, filename).await;
+                move_to_quarantine(file, storage_path, attachment_id, filename).await;
                 tracing::warn!(
                     "Malware scanner blocked upload id={} filename='{}' exit_code={}",
                     attachment_id,
@@ -314,24 +468,20 @@ async fn scan_upload_with_malware_hook(
                 Err(ApiError::BadRequest(
                     "File upload blocked by malware scanning policy".into(),
                 ))
+            } else if fail_closed {
+                Err(ApiError::ServiceUnavailable(
+                    "Malware scanner failed; upload rejected".into(),
+                ))
             } else {
-                let _ = tokio::fs::remove_file(&temp_file).await;
-                if fail_closed {
-                    Err(ApiError::ServiceUnavailable(
-                        "Malware scanner failed; upload rejected".into(),
-                    ))
-                } else {
-                    tracing::warn!(
-                        "Malware scanner returned unexpected exit code {} for upload id={}; allowing due to fail-open configuration",
-                        exit_code,
-                        attachment_id
-                    );
-                    Ok(())
-                }
+                tracing::warn!(
+                    "Malware scanner returned unexpected exit code {} for upload id={}; allowing due to fail-open configuration",
+                    exit_code,
+                    attachment_id
+                );
+                Ok(())
             }
         }
         Err(err) => {
-            let _ = tokio::fs::remove_file(&temp_file).await;
             if fail_closed {
                 Err(ApiError::ServiceUnavailable(
                     "Malware scanner unavailable".into(),
@@ -359,7 +509,7 @@ fn mime_matches_pattern(content_type: &str, pattern: &str) -> bool {
     content_type == pattern
 }
 
-async fn check_guild_upload_policy(
+pub(crate) async fn check_guild_upload_policy(
     state: &AppState,
     channel_id: i64,
     file_size: u64,
@@ -468,6 +618,44 @@ async fn cleanup_expired_pending_attachments(state: &AppState) {
     }
 }
 
+/// Delete one batch of orphaned attachments (see
+/// [`paracord_db::attachments::find_orphaned`]) along with their stored
+/// files. Returns how many were removed.
+pub async fn collect_orphaned_attachments(state: &AppState, batch: i64) -> Result<usize, ApiError> {
+    let orphaned = paracord_db::attachments::find_orphaned(&state.db, Utc::now(), batch)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let mut removed = 0;
+    for attachment in orphaned {
+        let ext = std::path::Path::new(&attachment.filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("bin");
+        let storage_key = format!("attachments/{}.{}", attachment.id, ext);
+        if let Err(err) = state.storage_backend.delete(&storage_key).await {
+            tracing::warn!(
+                "Failed deleting orphaned attachment {} file: {}",
+                attachment.id,
+                err
+            );
+            continue;
+        }
+        if let Err(err) =
+            paracord_db::attachments::delete_attachment(&state.db, attachment.id).await
+        {
+            tracing::warn!(
+                "Failed deleting orphaned attachment {} metadata: {}",
+                attachment.id,
+                err
+            );
+            continue;
+        }
+        removed += 1;
+    }
+    Ok(removed)
+}
+
 pub async fn upload_file(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -475,6 +663,7 @@ pub async fn upload_file(
     mut multipart: Multipart,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
     cleanup_expired_pending_attachments(&state).await;
+    crate::routes::security::ensure_not_usage_timed_out(&state, auth.user_id)?;
 
     // Verify channel exists and caller can send attachments.
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
@@ -510,8 +699,85 @@ pub async fn upload_file(
         .map_err(|e| ApiError::BadRequest(e.to_string()))?
         .ok_or_else(|| ApiError::BadRequest("No file provided".into()))?;
 
-    let filename = field.file_name().unwrap_or("upload").to_string();
+    let filename = normalize_upload_filename(
+        field.file_name().unwrap_or("upload"),
+        state.config.max_attachment_filename_length as usize,
+    )?;
     let claimed_content_type = field.content_type().map(|s| s.to_string());
+
+    // Take a pending slot before reading the body, so uploads racing each
+    // other cannot all get under the per-user cap.
+    let attachment_id = paracord_util::snowflake::generate(1);
+    let reservation = paracord_db::attachments::reserve_pending_attachment(
+        &state.db,
+        attachment_id,
+        &filename,
+        &format!("/api/v1/attachments/{}", attachment_id),
+        auth.user_id,
+        channel_id,
+        Utc::now() + Duration::minutes(PENDING_ATTACHMENT_TTL_MINUTES),
+        i64::from(state.config.max_pending_uploads_per_user),
+        Utc::now(),
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    .ok_or(ApiError::RateLimited)?;
+
+    let ext = std::path::Path::new(&filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("bin");
+    let storage_key = format!("attachments/{}.{}", attachment_id, ext);
+
+    let attachment = match store_reserved_upload(
+        &state,
+        field,
+        &reservation,
+        channel_id,
+        claimed_content_type.as_deref(),
+        &storage_key,
+    )
+    .await
+    {
+        Ok(attachment) => attachment,
+        Err(err) => {
+            let _ = paracord_db::attachments::delete_attachment(&state.db, attachment_id).await;
+            let _ = state.storage_backend.delete(&storage_key).await;
+            return Err(err);
+        }
+    };
+
+    if state
+        .usage
+        .record_upload(auth.user_id, attachment.size as u64)
+    {
+        crate::routes::security::log_usage_threshold_exceeded(&state, auth.user_id, "upload", None)
+            .await;
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "id": attachment.id.to_string(),
+            "filename": attachment.filename,
+            "size": attachment.size,
+            "content_type": attachment.content_type,
+            "url": state.config.media_url(&attachment.url),
+        })),
+    ))
+}
+
+/// Read, check and store the body of a multipart upload into the pending
+/// attachment `reservation`, filling in its size, type and hash.
+async fn store_reserved_upload(
+    state: &AppState,
+    field: axum::extract::multipart::Field<'_>,
+    reservation: &paracord_db::attachments::AttachmentRow,
+    channel_id: i64,
+    claimed_content_type: Option<&str>,
+    storage_key: &str,
+) -> Result<paracord_db::attachments::AttachmentRow, ApiError> {
+    let filename = reservation.filename.as_str();
     let data = field
         .bytes()
         .await
@@ -535,22 +801,15 @@ pub async fn upload_file(
     let content_hash = format!("{:x}", hasher.finalize());
 
     // Check guild-level upload policy (file size, quota, type restrictions)
-    let resolved_ct = normalized_content_type(&filename, claimed_content_type.as_deref());
-    check_guild_upload_policy(&state, channel_id, size, &resolved_ct).await?;
+    let resolved_ct = normalized_content_type(filename, claimed_content_type);
+    check_guild_upload_policy(state, channel_id, size, &resolved_ct).await?;
 
     // Store file via storage backend
-    let attachment_id = paracord_util::snowflake::generate(1);
-    scan_upload_with_malware_hook(&data, &filename, &state.config.storage_path, attachment_id)
+    scan_upload_with_malware_hook(&data, filename, &state.config.storage_path, reservation.id)
         .await?;
 
-    let ext = std::path::Path::new(&filename)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("bin");
-    let storage_key = format!("attachments/{}.{}", attachment_id, ext);
-
     let stored_payload = if let Some(cryptor) = state.config.file_cryptor.as_ref() {
-        let aad = attachment_aad(attachment_id);
+        let aad = attachment_aad(reservation.id);
         cryptor
             .encrypt_with_aad(&data, aad.as_bytes())
             .map_err(|err| ApiError::Internal(anyhow::anyhow!(err.to_string())))?
@@ -560,50 +819,28 @@ pub async fn upload_file(
 
     state
         .storage_backend
-        .store(&storage_key, &stored_payload)
+        .store(storage_key, &stored_payload)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let url = format!("/api/v1/attachments/{}", attachment_id);
-    let content_type =
-        resolve_stored_content_type(&filename, claimed_content_type.as_deref(), &data);
-    let expires_at = Utc::now() + Duration::minutes(PENDING_ATTACHMENT_TTL_MINUTES);
-
-    let attachment = paracord_db::attachments::create_attachment(
+    let content_type = resolve_stored_content_type(filename, claimed_content_type, &data);
+    paracord_db::attachments::complete_pending_attachment(
         &state.db,
-        attachment_id,
-        None, // pending attachment; linked during message creation
-        &filename,
+        reservation.id,
         Some(&content_type),
         db_size,
-        &url,
-        None,
-        None,
-        Some(auth.user_id),
-        Some(channel_id),
-        Some(expires_at),
         Some(&content_hash),
     )
     .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-
-    Ok((
-        StatusCode::CREATED,
-        Json(json!({
-            "id": attachment.id.to_string(),
-            "filename": attachment.filename,
-            "size": attachment.size,
-            "content_type": attachment.content_type,
-            "url": attachment.url,
-        })),
-    ))
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))
 }
 
-pub async fn download_file(
-    State(state): State<AppState>,
-    auth: AuthUser,
-    Path(id): Path<i64>,
-) -> Result<impl IntoResponse, ApiError> {
+/// A message attachment `user_id` may read, or the error to return.
+async fn load_readable_attachment(
+    state: &AppState,
+    user_id: i64,
+    id: i64,
+) -> Result<paracord_db::attachments::AttachmentRow, ApiError> {
     let attachment = paracord_db::attachments::get_attachment(&state.db, id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
@@ -620,7 +857,7 @@ pub async fn download_file(
         .ok_or(ApiError::NotFound)?;
 
     if let Some(guild_id) = channel.guild_id() {
-        paracord_core::permissions::ensure_guild_member(&state.db, guild_id, auth.user_id).await?;
+        paracord_core::permissions::ensure_guild_member(&state.db, guild_id, user_id).await?;
         let guild = paracord_db::guilds::get_guild(&state.db, guild_id)
             .await
             .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
@@ -630,77 +867,119 @@ pub async fn download_file(
             guild_id,
             channel.id,
             guild.owner_id,
-            auth.user_id,
+            user_id,
         )
         .await?;
         paracord_core::permissions::require_permission(perms, Permissions::VIEW_CHANNEL)?;
         paracord_core::permissions::require_permission(perms, Permissions::READ_MESSAGE_HISTORY)?;
-    } else if !paracord_db::dms::is_dm_recipient(&state.db, channel.id, auth.user_id)
+    } else if !paracord_db::dms::is_dm_recipient(&state.db, channel.id, user_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
     {
         return Err(ApiError::Forbidden);
     }
+    Ok(attachment)
+}
+
+#[derive(Deserialize)]
+pub struct DownloadFileQuery {
+    /// Embed token from [`embed_token`]; required for inline display when
+    /// `attachment_inline_token_ttl_seconds` is set.
+    pub token: Option<String>,
+}
+
+pub async fn download_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Query(query): Query<DownloadFileQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let attachment = load_readable_attachment(&state, auth.user_id, id).await?;
 
     let ext = std::path::Path::new(&attachment.filename)
         .extension()
         .and_then(|e| e.to_str())
         .unwrap_or("bin");
     let storage_key = format!("attachments/{}.{}", attachment.id, ext);
-    let stored_data = state
+    let mut stored = state
         .storage_backend
-        .retrieve(&storage_key)
+        .retrieve_stream(&storage_key)
         .await
         .map_err(|_| ApiError::NotFound)?;
-    let data = if let Some(cryptor) = state.config.file_cryptor.as_ref() {
-        let aad = attachment_aad(attachment.id);
-        match cryptor.decrypt_with_aad(&stored_data, aad.as_bytes()) {
-            Ok(decrypted) => decrypted,
-            Err(paracord_util::at_rest::FileCryptoError::PlaintextReadDisabled)
-                if !paracord_util::at_rest::FileCryptor::payload_is_encrypted(&stored_data) =>
-            {
-                tracing::warn!(
-                    "Serving legacy plaintext attachment {} while file encryption is enabled; re-encrypting in place",
-                    attachment.id
-                );
-                match cryptor.encrypt_with_aad(&stored_data, aad.as_bytes()) {
-                    Ok(reencrypted) => {
-                        if let Err(err) = state
-                            .storage_backend
-                            .store(&storage_key, &reencrypted)
-                            .await
-                        {
-                            tracing::warn!(
-                                "Failed to re-encrypt attachment {} in storage: {}",
-                                attachment.id,
-                                err
-                            );
-                        }
-                    }
-                    Err(err) => {
-                        tracing::warn!(
-                            "Failed to encrypt legacy plaintext attachment {}: {}",
-                            attachment.id,
-                            err
-                        );
-                    }
-                }
-                stored_data
+    // Streamed bodies carry no length of their own; the recorded size is the
+    // plaintext length for every stored format.
+    let mut streamed_length = u64::try_from(attachment.size).ok();
+    let body = if let Some(cryptor) = state.config.file_cryptor.as_ref() {
+        // Read just enough to tell the chunked format, which decrypts as it
+        // streams, from legacy payloads that have to be buffered whole.
+        let mut head = Vec::new();
+        while head.len() < ATTACHMENT_FORMAT_PROBE_LEN {
+            match stored.next().await {
+                Some(chunk) => head.extend_from_slice(&chunk.map_err(|_| ApiError::NotFound)?),
+                None => break,
             }
-            Err(err) => return Err(ApiError::Internal(anyhow::anyhow!(err.to_string()))),
+        }
+        if FileCryptor::payload_is_chunked(&head) {
+            let aad = attachment_aad(attachment.id);
+            let decryptor = cryptor
+                .chunked_decryptor(aad.as_bytes())
+                .map_err(|err| ApiError::Internal(anyhow::anyhow!(err.to_string())))?;
+            Body::from_stream(decrypt_attachment_stream(
+                attachment.id,
+                decryptor,
+                head,
+                stored,
+            ))
+        } else {
+            while let Some(chunk) = stored.next().await {
+                head.extend_from_slice(&chunk.map_err(|_| ApiError::NotFound)?);
+            }
+            streamed_length = None;
+            Body::from(
+                decrypt_legacy_attachment(&state, cryptor, attachment.id, &storage_key, head)
+                    .await?,
+            )
         }
     } else {
-        stored_data
+        Body::from_stream(stored)
     };
     let content_type = attachment
         .content_type
         .clone()
         .unwrap_or_else(|| "application/octet-stream".to_string());
-    let allow_inline =
-        is_inline_safe_content_type(&content_type) && !has_active_extension(&attachment.filename);
+    let token_ok = state.config.attachment_inline_token_ttl_seconds == 0
+        || query.token.as_deref().is_some_and(|token| {
+            verify_inline_token(
+                &state.config.jwt_secret,
+                attachment.id,
+                token,
+                Utc::now().timestamp(),
+            )
+        });
+    let allow_inline = is_inline_safe_content_type(&content_type)
+        && !has_active_extension(&attachment.filename)
+        && token_ok
+        && inline_origin_allowed(
+            &state.config.attachment_inline_origins,
+            state.config.public_url.as_deref(),
+            &headers,
+        );
     let disposition = build_content_disposition(&attachment.filename, allow_inline);
+    if state.config.log_attachment_access {
+        tracing::info!(
+            "attachment access: user={} attachment={} inline={} referer={}",
+            auth.user_id,
+            attachment.id,
+            allow_inline,
+            headers
+                .get(header::REFERER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("-")
+        );
+    }
 
-    Ok((
+    let mut response = (
         [
             (
                 header::CONTENT_TYPE,
@@ -717,8 +996,123 @@ pub async fn download_file(
                 HeaderValue::from_static("nosniff"),
             ),
         ],
-        data,
-    ))
+        body,
+    )
+        .into_response();
+    if let Some(length) = streamed_length {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
+    Ok(response)
+}
+
+/// Decrypt a chunked attachment payload as it streams from storage. A
+/// decryption or storage failure ends the body early, so the client sees a
+/// truncated download rather than tampered or partial plaintext passing as
+/// complete.
+fn decrypt_attachment_stream(
+    attachment_id: i64,
+    decryptor: ChunkedDecryptor,
+    head: Vec<u8>,
+    rest: paracord_media::ByteStream,
+) -> impl futures_util::Stream<Item = std::io::Result<Bytes>> + Send + 'static {
+    let input = futures_util::stream::iter([Ok(Bytes::from(head))]).chain(rest);
+    futures_util::stream::unfold(Some((decryptor, input)), move |state| async move {
+        let (mut decryptor, mut input) = state?;
+        loop {
+            let result = match input.next().await {
+                Some(Ok(chunk)) => match decryptor.update(&chunk) {
+                    Ok(plaintext) if plaintext.is_empty() => continue,
+                    Ok(plaintext) => {
+                        return Some((Ok(Bytes::from(plaintext)), Some((decryptor, input))));
+                    }
+                    Err(err) => Err(err.to_string()),
+                },
+                Some(Err(err)) => Err(err.to_string()),
+                None => decryptor.finish().map_err(|err| err.to_string()),
+            };
+            return Some((
+                result.map(Bytes::from).map_err(|err| {
+                    tracing::warn!("Failed to stream attachment {}: {}", attachment_id, err);
+                    std::io::Error::other(err)
+                }),
+                None,
+            ));
+        }
+    })
+}
+
+/// Decrypt a fully buffered v1/v2 or plaintext payload, re-encrypting legacy
+/// plaintext in place when encryption has since been enabled.
+async fn decrypt_legacy_attachment(
+    state: &AppState,
+    cryptor: &FileCryptor,
+    attachment_id: i64,
+    storage_key: &str,
+    stored_data: Vec<u8>,
+) -> Result<Vec<u8>, ApiError> {
+    let aad = attachment_aad(attachment_id);
+    match cryptor.decrypt_with_aad(&stored_data, aad.as_bytes()) {
+        Ok(decrypted) => Ok(decrypted),
+        Err(paracord_util::at_rest::FileCryptoError::PlaintextReadDisabled)
+            if !FileCryptor::payload_is_encrypted(&stored_data) =>
+        {
+            tracing::warn!(
+                "Serving legacy plaintext attachment {} while file encryption is enabled; re-encrypting in place",
+                attachment_id
+            );
+            match cryptor.encrypt_with_aad(&stored_data, aad.as_bytes()) {
+                Ok(reencrypted) => {
+                    if let Err(err) = state.storage_backend.store(storage_key, &reencrypted).await {
+                        tracing::warn!(
+                            "Failed to re-encrypt attachment {} in storage: {}",
+                            attachment_id,
+                            err
+                        );
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to encrypt legacy plaintext attachment {}: {}",
+                        attachment_id,
+                        err
+                    );
+                }
+            }
+            Ok(stored_data)
+        }
+        Err(err) => Err(ApiError::Internal(anyhow::anyhow!(err.to_string()))),
+    }
+}
+
+/// Short-lived token that lets an attachment be embedded inline when the
+/// server requires one. Returns the ready-to-use download `url`.
+pub async fn embed_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let attachment = load_readable_attachment(&state, auth.user_id, id).await?;
+    let ttl = match state.config.attachment_inline_token_ttl_seconds {
+        0 => DEFAULT_INLINE_TOKEN_TTL_SECONDS,
+        ttl => ttl.min(MAX_INLINE_TOKEN_TTL_SECONDS),
+    };
+    let expires_at = Utc::now() + Duration::seconds(ttl as i64);
+    let token = sign_inline_token(
+        &state.config.jwt_secret,
+        attachment.id,
+        expires_at.timestamp(),
+    );
+    let url = state.config.media_url(&format!(
+        "/api/v1/attachments/{}?token={}",
+        attachment.id, token
+    ));
+    Ok(Json(json!({
+        "token": token,
+        "url": url,
+        "expires_at": expires_at.to_rfc3339(),
+    })))
 }
 
 pub async fn delete_file(
@@ -814,6 +1208,11 @@ pub async fn process_uploaded_file(
         return Err(ApiError::BadRequest("File too large".into()));
     }
     let db_size = i32::try_from(size).map_err(|_| ApiError::BadRequest("File too large".into()))?;
+    let filename = normalize_upload_filename(
+        filename,
+        state.config.max_attachment_filename_length as usize,
+    )?;
+    let filename = filename.as_str();
 
     // Compute SHA-256 content hash
     let mut hasher = Sha256::new();
@@ -849,8 +1248,165 @@ pub async fn process_uploaded_file(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let url = format!("/api/v1/attachments/{}", attachment_id);
     let content_type = resolve_stored_content_type(filename, claimed_content_type, data);
+    record_pending_attachment(
+        state,
+        attachment_id,
+        filename,
+        &content_type,
+        db_size,
+        channel_id,
+        user_id,
+        &content_hash,
+    )
+    .await
+}
+
+/// Like [`process_uploaded_file`], for an upload already on local disk. The
+/// file is hashed, scanned, encrypted and stored without being read into
+/// memory. It is left in place (or moved to quarantine if it is flagged).
+pub(crate) async fn process_uploaded_path(
+    state: &AppState,
+    path: &std::path::Path,
+    filename: &str,
+    claimed_content_type: Option<&str>,
+    channel_id: i64,
+    user_id: i64,
+) -> Result<Value, ApiError> {
+    let size = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e)))?
+        .len();
+    if size == 0 {
+        return Err(ApiError::BadRequest("Empty file".into()));
+    }
+    if size > state.config.max_upload_size {
+        return Err(ApiError::BadRequest("File too large".into()));
+    }
+    let db_size = i32::try_from(size).map_err(|_| ApiError::BadRequest("File too large".into()))?;
+    let filename = normalize_upload_filename(
+        filename,
+        state.config.max_attachment_filename_length as usize,
+    )?;
+    let filename = filename.as_str();
+
+    let resolved_ct = normalized_content_type(filename, claimed_content_type);
+    check_guild_upload_policy(state, channel_id, size, &resolved_ct).await?;
+
+    let attachment_id = paracord_util::snowflake::generate(1);
+    scan_file_with_malware_hook(path, filename, &state.config.storage_path, attachment_id).await?;
+
+    let ext = std::path::Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("bin");
+    let storage_key = format!("attachments/{}.{}", attachment_id, ext);
+
+    // One pass over the file: hash it, keep the head for content sniffing
+    // and, when writing the chunked format, write the sealed copy next to it.
+    let encrypted_path = path.with_extension("enc");
+    let aad = attachment_aad(attachment_id);
+    let mut encryptor = state
+        .config
+        .file_cryptor
+        .as_ref()
+        .filter(|cryptor| cryptor.chunked_writes())
+        .map(|cryptor| cryptor.chunked_encryptor(aad.as_bytes()))
+        .transpose()
+        .map_err(|err| ApiError::Internal(anyhow::anyhow!(err.to_string())))?;
+    let io_err = |e: std::io::Error| ApiError::Internal(anyhow::anyhow!(e));
+    let mut reader = tokio::fs::File::open(path).await.map_err(io_err)?;
+    let mut sealed = match encryptor {
+        Some(_) => Some(
+            tokio::fs::File::create(&encrypted_path)
+                .await
+                .map_err(io_err)?,
+        ),
+        None => None,
+    };
+    let mut hasher = Sha256::new();
+    let mut head = Vec::new();
+    let mut buf = vec![0_u8; paracord_util::at_rest::FILE_CHUNK_LEN];
+    let streamed: Result<(), ApiError> = async {
+        loop {
+            let read = reader.read(&mut buf).await.map_err(io_err)?;
+            if read == 0 {
+                break;
+            }
+            let piece = &buf[..read];
+            hasher.update(piece);
+            if head.len() < 512 {
+                head.extend_from_slice(&piece[..piece.len().min(512 - head.len())]);
+            }
+            if let (Some(encryptor), Some(out)) = (encryptor.as_mut(), sealed.as_mut()) {
+                let bytes = encryptor
+                    .update(piece)
+                    .map_err(|err| ApiError::Internal(anyhow::anyhow!(err.to_string())))?;
+                out.write_all(&bytes).await.map_err(io_err)?;
+            }
+        }
+        if let (Some(encryptor), Some(mut out)) = (encryptor.take(), sealed.take()) {
+            let bytes = encryptor
+                .finish()
+                .map_err(|err| ApiError::Internal(anyhow::anyhow!(err.to_string())))?;
+            out.write_all(&bytes).await.map_err(io_err)?;
+            out.flush().await.map_err(io_err)?;
+        } else if let Some(cryptor) = state.config.file_cryptor.as_ref() {
+            // The single-shot format has to be sealed in memory.
+            let data = tokio::fs::read(path).await.map_err(io_err)?;
+            let bytes = cryptor
+                .encrypt_with_aad(&data, aad.as_bytes())
+                .map_err(|err| ApiError::Internal(anyhow::anyhow!(err.to_string())))?;
+            tokio::fs::write(&encrypted_path, bytes)
+                .await
+                .map_err(io_err)?;
+        }
+        Ok(())
+    }
+    .await;
+    let encrypted = state.config.file_cryptor.is_some();
+    let stored = match streamed {
+        Ok(()) => state
+            .storage_backend
+            .store_path(&storage_key, if encrypted { &encrypted_path } else { path })
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string()))),
+        Err(err) => Err(err),
+    };
+    if encrypted {
+        let _ = tokio::fs::remove_file(&encrypted_path).await;
+    }
+    stored?;
+
+    let content_hash = format!("{:x}", hasher.finalize());
+    let content_type = resolve_stored_content_type(filename, claimed_content_type, &head);
+    record_pending_attachment(
+        state,
+        attachment_id,
+        filename,
+        &content_type,
+        db_size,
+        channel_id,
+        user_id,
+        &content_hash,
+    )
+    .await
+}
+
+/// Create the pending attachment row for a stored upload and return the
+/// attachment JSON.
+#[allow(clippy::too_many_arguments)]
+async fn record_pending_attachment(
+    state: &AppState,
+    attachment_id: i64,
+    filename: &str,
+    content_type: &str,
+    db_size: i32,
+    channel_id: i64,
+    user_id: i64,
+    content_hash: &str,
+) -> Result<Value, ApiError> {
+    let url = format!("/api/v1/attachments/{}", attachment_id);
     let expires_at = Utc::now() + Duration::minutes(PENDING_ATTACHMENT_TTL_MINUTES);
 
     let attachment = paracord_db::attachments::create_attachment(
@@ -858,7 +1414,7 @@ pub async fn process_uploaded_file(
         attachment_id,
         None,
         filename,
-        Some(&content_type),
+        Some(content_type),
         db_size,
         &url,
         None,
@@ -866,7 +1422,7 @@ pub async fn process_uploaded_file(
         Some(user_id),
         Some(channel_id),
         Some(expires_at),
-        Some(&content_hash),
+        Some(content_hash),
     )
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
@@ -876,7 +1432,7 @@ pub async fn process_uploaded_file(
         "filename": attachment.filename,
         "size": attachment.size,
         "content_type": attachment.content_type,
-        "url": attachment.url,
+        "url": state.config.media_url(&attachment.url),
     }))
 }
 
@@ -1086,8 +1642,8 @@ pub async fn download_federated_file(
         .ok_or(ApiError::NotFound)?;
     let user_id = format!("@{}:{}", user.username, service.domain());
 
-    let token_resp = client
-        .request_file_token(
+    let (file_data, resp_content_type, resp_filename) = client
+        .fetch_federated_file(
             &server.federation_endpoint,
             &paracord_federation::client::FederationFileTokenRequest {
                 origin_server: service.server_name().to_string(),
@@ -1097,25 +1653,6 @@ pub async fn download_federated_file(
             },
         )
         .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!("failed to get file token: {}", e)))?;
-
-    // Download the file from origin
-    let full_download_url = if token_resp.download_url.starts_with("http") {
-        token_resp.download_url.clone()
-    } else {
-        format!(
-            "{}{}",
-            server
-                .federation_endpoint
-                .trim_end_matches('/')
-                .trim_end_matches("/v1"),
-            token_resp.download_url
-        )
-    };
-
-    let (file_data, resp_content_type, resp_filename) = client
-        .download_federated_file(&full_download_url)
-        .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("failed to download file: {}", e)))?;
 
     let content_type = resp_content_type.unwrap_or_else(|| "application/octet-stream".to_string());
@@ -1189,7 +1726,9 @@ pub async fn download_federated_file(
 #[cfg(test)]
 mod tests {
     use super::{
-        build_content_disposition, is_inline_safe_content_type, resolve_stored_content_type,
+        build_content_disposition, header, inline_origin_allowed, is_inline_safe_content_type,
+        normalize_upload_filename, resolve_stored_content_type, sign_inline_token,
+        verify_inline_token, HeaderMap, HeaderValue,
     };
 
     #[test]
@@ -1212,4 +1751,90 @@ mod tests {
         let disposition = build_content_disposition("bad\"name\r\n.js", false);
         assert_eq!(disposition, "attachment; filename=\"badname.js\"");
     }
+
+    #[test]
+    fn upload_filename_truncation_keeps_extension() {
+        let long = format!("{}.tar.gz", "a".repeat(300));
+        let name = normalize_upload_filename(&long, 40).unwrap();
+        assert_eq!(name.chars().count(), 40);
+        assert!(name.ends_with(".gz"));
+        assert!(name.starts_with("aaaa"));
+
+        let multibyte = format!("{}.png", "é".repeat(100));
+        let name = normalize_upload_filename(&multibyte, 20).unwrap();
+        assert_eq!(name, format!("{}.png", "é".repeat(16)));
+
+        assert_eq!(
+            normalize_upload_filename("notes.txt", 40).unwrap(),
+            "notes.txt"
+        );
+    }
+
+    #[test]
+    fn upload_filename_is_nfc_normalized() {
+        let decomposed = "cafe\u{0301}.txt";
+        let name = normalize_upload_filename(decomposed, 255).unwrap();
+        assert_eq!(name, "caf\u{00e9}.txt");
+    }
+
+    #[test]
+    fn upload_filename_rejects_control_characters() {
+        assert!(normalize_upload_filename("bad\u{0007}name.txt", 255).is_err());
+        assert!(normalize_upload_filename("bad\r\nname.txt", 255).is_err());
+        assert!(normalize_upload_filename("invoice\u{202E}fdp.exe", 255).is_err());
+    }
+
+    #[test]
+    fn inline_tokens_are_bound_to_attachment_and_expiry() {
+        let token = sign_inline_token("secret", 42, 1_000);
+        assert!(verify_inline_token("secret", 42, &token, 999));
+        assert!(!verify_inline_token("secret", 42, &token, 1_001));
+        assert!(!verify_inline_token("secret", 43, &token, 999));
+        assert!(!verify_inline_token("other", 42, &token, 999));
+        let forged = token.replacen("1000.", "2000.", 1);
+        assert!(!verify_inline_token("secret", 42, &forged, 999));
+        assert!(!verify_inline_token("secret", 42, "garbage", 0));
+    }
+
+    #[test]
+    fn inline_origin_check_uses_origin_then_referer() {
+        let allowed = vec!["https://chat.example.com/".to_string()];
+        let headers = |pairs: &[(header::HeaderName, &str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in pairs {
+                map.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+            }
+            map
+        };
+
+        assert!(inline_origin_allowed(&allowed, None, &headers(&[])));
+        assert!(inline_origin_allowed(
+            &allowed,
+            None,
+            &headers(&[(header::REFERER, "https://chat.example.com/channels/1")])
+        ));
+        assert!(inline_origin_allowed(
+            &allowed,
+            Some("https://paracord.example.org"),
+            &headers(&[(header::ORIGIN, "https://paracord.example.org")])
+        ));
+        assert!(!inline_origin_allowed(
+            &allowed,
+            None,
+            &headers(&[(header::REFERER, "https://evil.example.net/page")])
+        ));
+        assert!(!inline_origin_allowed(
+            &allowed,
+            None,
+            &headers(&[
+                (header::ORIGIN, "https://evil.example.net"),
+                (header::REFERER, "https://chat.example.com/"),
+            ])
+        ));
+        assert!(inline_origin_allowed(
+            &[],
+            None,
+            &headers(&[(header::REFERER, "https://evil.example.net/page")])
+        ));
+    }
 }
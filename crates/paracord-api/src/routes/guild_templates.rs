@@ -0,0 +1,226 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use paracord_core::{guild_template::GuildTemplateSnapshot, AppState};
+use paracord_models::permissions::Permissions;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+
+const TEMPLATE_CODE_LEN: usize = 12;
+const MAX_TEMPLATE_NAME_LEN: usize = 100;
+const MAX_TEMPLATE_DESCRIPTION_LEN: usize = 120;
+
+#[derive(Deserialize)]
+pub struct CreateTemplateRequest {
+    pub guild_id: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateGuildFromTemplateRequest {
+    pub name: String,
+    pub icon: Option<String>,
+}
+
+async fn require_manage_guild(
+    state: &AppState,
+    guild_id: i64,
+    user_id: i64,
+) -> Result<(), ApiError> {
+    let guild = paracord_db::guilds::get_guild(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+
+    paracord_core::permissions::ensure_guild_member(&state.db, guild_id, user_id).await?;
+
+    let roles = paracord_db::roles::get_member_roles(&state.db, user_id, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let perms =
+        paracord_core::permissions::compute_permissions_from_roles(&roles, guild.owner_id, user_id);
+    paracord_core::permissions::require_permission(perms, Permissions::MANAGE_GUILD)?;
+    Ok(())
+}
+
+fn template_to_json(t: &paracord_db::guild_templates::GuildTemplateRow) -> Value {
+    json!({
+        "code": t.code,
+        "name": t.name,
+        "description": t.description,
+        "source_guild_id": t.source_guild_id.map(|id| id.to_string()),
+        "creator_id": t.creator_id.map(|id| id.to_string()),
+        "usage_count": t.usage_count,
+        "created_at": t.created_at.to_rfc3339(),
+        "serialized_source_guild": serde_json::from_str::<Value>(&t.serialized_guild)
+            .unwrap_or(Value::Null),
+    })
+}
+
+async fn load_template(
+    state: &AppState,
+    code: &str,
+) -> Result<paracord_db::guild_templates::GuildTemplateRow, ApiError> {
+    paracord_db::guild_templates::get_template(&state.db, code)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)
+}
+
+pub async fn create_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<CreateTemplateRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let guild_id = body
+        .guild_id
+        .parse::<i64>()
+        .map_err(|_| ApiError::BadRequest("Invalid guild_id".into()))?;
+    let name = body.name.trim();
+    if name.is_empty() || name.len() > MAX_TEMPLATE_NAME_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "Template name must be between 1 and {MAX_TEMPLATE_NAME_LEN} characters"
+        )));
+    }
+    let description = body
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty());
+    if description.is_some_and(|d| d.len() > MAX_TEMPLATE_DESCRIPTION_LEN) {
+        return Err(ApiError::BadRequest("description is too long".into()));
+    }
+
+    require_manage_guild(&state, guild_id, auth.user_id).await?;
+
+    let snapshot = paracord_core::guild_template::snapshot_guild(&state.db, guild_id).await?;
+    let serialized = serde_json::to_string(&snapshot)
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let code = paracord_core::guild::generate_invite_code(TEMPLATE_CODE_LEN);
+    let template = paracord_db::guild_templates::create_template(
+        &state.db,
+        &code,
+        name,
+        description,
+        guild_id,
+        auth.user_id,
+        &serialized,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    Ok((StatusCode::CREATED, Json(template_to_json(&template))))
+}
+
+pub async fn get_template(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+    Path(code): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let template = load_template(&state, &code).await?;
+    Ok(Json(template_to_json(&template)))
+}
+
+pub async fn list_guild_templates(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(guild_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    require_manage_guild(&state, guild_id, auth.user_id).await?;
+
+    let templates = paracord_db::guild_templates::get_guild_templates(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let result: Vec<Value> = templates.iter().map(template_to_json).collect();
+    Ok(Json(json!(result)))
+}
+
+pub async fn delete_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(code): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let template = load_template(&state, &code).await?;
+
+    // The creator can always remove their template; otherwise it is managed
+    // alongside the guild it was taken from.
+    if template.creator_id != Some(auth.user_id) {
+        let source_guild_id = template.source_guild_id.ok_or(ApiError::Forbidden)?;
+        require_manage_guild(&state, source_guild_id, auth.user_id).await?;
+    }
+
+    paracord_db::guild_templates::delete_template(&state.db, &code)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn create_guild_from_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(code): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<CreateGuildFromTemplateRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if body.name.len() < 2 || body.name.len() > 100 {
+        return Err(ApiError::BadRequest(
+            "Guild name must be between 2 and 100 characters".into(),
+        ));
+    }
+    crate::routes::security::ensure_name_allowed(
+        &state,
+        "guild_name",
+        &body.name,
+        Some(auth.user_id),
+        Some(&headers),
+    )
+    .await?;
+    crate::routes::guilds::ensure_can_create_guild(&state, auth.user_id).await?;
+
+    let template = load_template(&state, &code).await?;
+    let snapshot: GuildTemplateSnapshot = serde_json::from_str(&template.serialized_guild)
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let guild_id = paracord_util::snowflake::generate(1);
+    let guild = paracord_core::guild_template::create_guild_from_template(
+        &state.db,
+        guild_id,
+        &body.name,
+        auth.user_id,
+        body.icon.as_deref(),
+        &snapshot,
+    )
+    .await?;
+
+    if let Err(e) = paracord_db::guild_templates::increment_usage(&state.db, &code).await {
+        tracing::warn!("guild template {code}: failed to record usage: {e}");
+    }
+
+    let guild_json = json!({
+        "id": guild.id.to_string(),
+        "name": guild.name,
+        "description": guild.description,
+        "icon_hash": guild.icon_hash,
+        "owner_id": guild.owner_id.to_string(),
+        "member_count": 1,
+        "created_at": guild.created_at.to_rfc3339(),
+        "hub_settings": guild.hub_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
+        "bot_settings": guild.bot_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
+        "template_code": template.code,
+    });
+
+    state.member_index.add_member(guild_id, auth.user_id);
+    state
+        .event_bus
+        .dispatch("GUILD_CREATE", guild_json.clone(), Some(guild_id));
+
+    Ok((StatusCode::CREATED, Json(guild_json)))
+}
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     Json,
 };
 use paracord_core::AppState;
@@ -8,9 +8,10 @@ use paracord_models::permissions::Permissions;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
+use crate::default_guild_channels::default_guild_channels;
 use crate::error::ApiError;
 use crate::middleware::AuthUser;
-use crate::routes::audit;
+use crate::routes::{audit, security};
 
 const MAX_GUILD_DESCRIPTION_LEN: usize = 1_024;
 
@@ -24,18 +25,22 @@ fn contains_dangerous_markup(value: &str) -> bool {
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct CreateGuildRequest {
     pub name: String,
     pub icon: Option<String>,
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct UpdateGuildRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub icon: Option<String>,
     pub hub_settings: Option<Value>,
     pub bot_settings: Option<Value>,
+    /// Turn server-side link previews on or off for this guild.
+    pub link_unfurls: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -43,9 +48,29 @@ pub struct TransferOwnershipRequest {
     pub new_owner_id: String,
 }
 
+/// Reject the request when guild creation is restricted and the user holds
+/// neither the admin nor the guild creator flag.
+pub(crate) async fn ensure_can_create_guild(
+    state: &AppState,
+    user_id: i64,
+) -> Result<(), ApiError> {
+    if !state.runtime.read().await.guild_creation_restricted {
+        return Ok(());
+    }
+    let user = paracord_db::users::get_user_by_id(&state.db, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::Unauthorized)?;
+    if !paracord_core::can_create_guilds(user.flags, &*state.runtime.read().await) {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(())
+}
+
 pub async fn create_guild(
     State(state): State<AppState>,
     auth: AuthUser,
+    headers: HeaderMap,
     Json(body): Json<CreateGuildRequest>,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
     if body.name.len() < 2 || body.name.len() > 100 {
@@ -53,6 +78,15 @@ pub async fn create_guild(
             "Guild name must be between 2 and 100 characters".into(),
         ));
     }
+    security::ensure_name_allowed(
+        &state,
+        "guild_name",
+        &body.name,
+        Some(auth.user_id),
+        Some(&headers),
+    )
+    .await?;
+    ensure_can_create_guild(&state, auth.user_id).await?;
 
     let guild_id = paracord_util::snowflake::generate(1);
 
@@ -62,6 +96,7 @@ pub async fn create_guild(
         &body.name,
         auth.user_id,
         body.icon.as_deref(),
+        default_guild_channels(),
     )
     .await?;
 
@@ -89,13 +124,14 @@ pub async fn list_guilds(
     State(state): State<AppState>,
     auth: AuthUser,
 ) -> Result<Json<Value>, ApiError> {
-    let guilds = paracord_db::guilds::get_user_guilds(&state.db, auth.user_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    // The READY builder batches the permission and read-state lookups that
+    // the per-guild unread summary needs.
+    let guilds = paracord_core::guild::build_ready_payload(&state.db, auth.user_id).await?;
 
     let result: Vec<Value> = guilds
         .iter()
-        .map(|g| {
+        .map(|ready| {
+            let g = &ready.guild;
             json!({
                 "id": g.id.to_string(),
                 "name": g.name,
@@ -105,6 +141,8 @@ pub async fn list_guilds(
                 "created_at": g.created_at.to_rfc3339(),
                 "hub_settings": g.hub_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
                 "bot_settings": g.bot_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
+                "unread": ready.read_summary.unread,
+                "mention_count": ready.read_summary.mention_count,
             })
         })
         .collect();
@@ -135,6 +173,7 @@ pub async fn get_guild(
         "icon_hash": guild.icon_hash,
         "owner_id": guild.owner_id.to_string(),
         "member_count": member_count,
+        "features": paracord_models::guild::guild_feature_names(guild.features),
         "created_at": guild.created_at.to_rfc3339(),
         "hub_settings": guild.hub_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
         "bot_settings": guild.bot_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
@@ -145,8 +184,19 @@ pub async fn update_guild(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(guild_id): Path<i64>,
+    headers: HeaderMap,
     Json(body): Json<UpdateGuildRequest>,
 ) -> Result<Json<Value>, ApiError> {
+    if let Some(name) = body.name.as_deref() {
+        security::ensure_name_allowed(
+            &state,
+            "guild_name",
+            name,
+            Some(auth.user_id),
+            Some(&headers),
+        )
+        .await?;
+    }
     if let Some(description) = body.description.as_deref() {
         if description.trim().len() > MAX_GUILD_DESCRIPTION_LEN {
             return Err(ApiError::BadRequest("description is too long".into()));
@@ -168,7 +218,7 @@ pub async fn update_guild(
         .as_ref()
         .map(|v| serde_json::to_string(v).unwrap_or_else(|_| "{}".to_string()));
 
-    let updated = paracord_core::guild::update_guild(
+    let mut updated = paracord_core::guild::update_guild(
         &state.db,
         guild_id,
         auth.user_id,
@@ -179,6 +229,16 @@ pub async fn update_guild(
         bot_settings_str.as_deref(),
     )
     .await?;
+    if let Some(enabled) = body.link_unfurls {
+        updated = paracord_db::guilds::set_space_feature(
+            &state.db,
+            guild_id,
+            paracord_models::guild::GUILD_FEATURE_LINK_UNFURLS,
+            enabled,
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
 
     let guild_json = json!({
         "id": updated.id.to_string(),
@@ -186,6 +246,7 @@ pub async fn update_guild(
         "description": updated.description,
         "icon_hash": updated.icon_hash,
         "owner_id": updated.owner_id.to_string(),
+        "features": paracord_models::guild::guild_feature_names(updated.features),
         "created_at": updated.created_at.to_rfc3339(),
         "hub_settings": updated.hub_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
         "bot_settings": updated.bot_settings.as_deref().and_then(|s| serde_json::from_str::<Value>(s).ok()),
@@ -204,6 +265,7 @@ pub async fn update_guild(
         Some(json!({
             "name": updated.name,
             "description": updated.description,
+            "link_unfurls": body.link_unfurls,
         })),
     )
     .await;
@@ -551,6 +613,93 @@ pub async fn update_storage(
     })))
 }
 
+const MAX_SCREENING_RULES_LENGTH: usize = 4000;
+
+fn member_screening_json(
+    guild_id: i64,
+    screening: Option<&paracord_db::guild_member_screening::GuildMemberScreeningRow>,
+) -> Value {
+    json!({
+        "guild_id": guild_id.to_string(),
+        "enabled": screening.is_some_and(|s| s.enabled),
+        "rules": screening.map(|s| s.rules.as_str()).unwrap_or(""),
+        "updated_at": screening.map(|s| s.updated_at.as_str()),
+    })
+}
+
+/// Any member can read the screening rules, including pending members who
+/// still have to accept them.
+pub async fn get_member_screening(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(guild_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    paracord_core::permissions::ensure_guild_member(&state.db, guild_id, auth.user_id).await?;
+    let screening =
+        paracord_db::guild_member_screening::get_guild_member_screening(&state.db, guild_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    Ok(Json(member_screening_json(guild_id, screening.as_ref())))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMemberScreeningRequest {
+    pub enabled: Option<bool>,
+    pub rules: Option<String>,
+}
+
+pub async fn update_member_screening(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(guild_id): Path<i64>,
+    Json(body): Json<UpdateMemberScreeningRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_manage_guild(&state, guild_id, auth.user_id).await?;
+
+    let existing =
+        paracord_db::guild_member_screening::get_guild_member_screening(&state.db, guild_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let enabled = body
+        .enabled
+        .unwrap_or_else(|| existing.as_ref().is_some_and(|s| s.enabled));
+    let rules = match body.rules {
+        Some(rules) => rules.trim().to_string(),
+        None => existing.map(|s| s.rules).unwrap_or_default(),
+    };
+    if rules.chars().count() > MAX_SCREENING_RULES_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "rules must be at most {MAX_SCREENING_RULES_LENGTH} characters"
+        )));
+    }
+    if enabled && rules.is_empty() {
+        return Err(ApiError::BadRequest(
+            "rules are required to enable membership screening".into(),
+        ));
+    }
+
+    let screening = paracord_db::guild_member_screening::upsert_guild_member_screening(
+        &state.db, guild_id, enabled, &rules,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    audit::log_action(
+        &state,
+        guild_id,
+        auth.user_id,
+        audit::ACTION_GUILD_UPDATE,
+        Some(guild_id),
+        None,
+        Some(json!({
+            "member_screening": { "enabled": screening.enabled, "rules": screening.rules },
+        })),
+    )
+    .await;
+
+    Ok(Json(member_screening_json(guild_id, Some(&screening))))
+}
+
 #[derive(Deserialize)]
 pub struct ListFilesParams {
     pub before: Option<i64>,
@@ -583,7 +732,7 @@ pub async fn list_files(
                 "filename": a.filename,
                 "size": a.size,
                 "content_type": a.content_type,
-                "url": a.url,
+                "url": state.config.media_url(&a.url),
                 "message_id": a.message_id.map(|id| id.to_string()),
                 "uploader_id": a.uploader_id.map(|id| id.to_string()),
                 "upload_channel_id": a.upload_channel_id.map(|id| id.to_string()),
@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use paracord_core::AppState;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Upper bound on each readiness check so a stuck dependency cannot hang the
+/// probe itself.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long a readiness result is served before the checks run again.
+const READINESS_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Only the outcome is public; why a check failed goes to the server log.
+#[derive(Serialize)]
+pub struct SubsystemStatus {
+    pub ok: bool,
+    pub latency_ms: u64,
+}
+
+#[derive(Serialize)]
+pub struct SubsystemChecks {
+    pub database: SubsystemStatus,
+    pub livekit: SubsystemStatus,
+    pub storage: SubsystemStatus,
+}
+
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    /// `ok`, `degraded` (only LiveKit failed) or `unavailable`.
+    pub status: &'static str,
+    pub checks: SubsystemChecks,
+}
+
+async fn run_check<F, E>(name: &str, check: F) -> SubsystemStatus
+where
+    F: Future<Output = Result<(), E>>,
+    E: std::fmt::Display,
+{
+    let started = Instant::now();
+    let error = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(())) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!("timed out after {}ms", CHECK_TIMEOUT.as_millis())),
+    };
+    if let Some(error) = &error {
+        tracing::warn!("Readiness check {} failed: {}", name, error);
+    }
+    SubsystemStatus {
+        ok: error.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Write and remove a throwaway object to prove the storage backend accepts
+/// writes. Every probe from this process reuses one key, so a delete that
+/// fails leaves at most one object behind under `.health/`.
+async fn check_storage_writable(state: &AppState) -> Result<(), paracord_media::StorageError> {
+    static KEY: OnceLock<String> = OnceLock::new();
+    let key = KEY.get_or_init(|| format!(".health/readyz-{}", uuid::Uuid::new_v4().simple()));
+    state.storage_backend.store(key, b"ok").await?;
+    state.storage_backend.delete(key).await
+}
+
+fn status_code_for(body: &Value) -> StatusCode {
+    if body["status"] == "unavailable" {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    }
+}
+
+/// Readiness probe: the database and storage backend must be usable. LiveKit
+/// is reported but not critical, since voice degrades without it. Results are
+/// cached for [`READINESS_CACHE_TTL`], and concurrent probes wait for the one
+/// in flight instead of starting their own.
+pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
+    let mut cached = state.readiness_cache.lock().await;
+    if let Some((taken_at, body)) = cached.as_ref() {
+        if taken_at.elapsed() < READINESS_CACHE_TTL {
+            return (status_code_for(body), Json(body.clone()));
+        }
+    }
+
+    let livekit = state.voice.livekit();
+    let (database, livekit, storage) = tokio::join!(
+        run_check("database", paracord_db::ping(&state.db)),
+        run_check("livekit", livekit.check_health()),
+        run_check("storage", check_storage_writable(&state)),
+    );
+    let status = if !database.ok || !storage.ok {
+        "unavailable"
+    } else if !livekit.ok {
+        "degraded"
+    } else {
+        "ok"
+    };
+    let body = serde_json::to_value(ReadinessResponse {
+        status,
+        checks: SubsystemChecks {
+            database,
+            livekit,
+            storage,
+        },
+    })
+    .unwrap_or_default();
+    *cached = Some((Instant::now(), body.clone()));
+    (status_code_for(&body), Json(body))
+}
@@ -0,0 +1,458 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use paracord_core::{events::ServerEvent, AppState};
+use paracord_models::permissions::Permissions;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::time::Duration;
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+use crate::outbound_url;
+use crate::routes::audit;
+use crate::routes::webhooks::{generate_webhook_token, require_manage_webhooks};
+
+/// Gateway events a guild can forward to an integration webhook.
+pub const INTEGRATION_EVENT_TYPES: &[&str] = &[
+    "CHANNEL_PINS_UPDATE",
+    "GUILD_MEMBER_ADD",
+    "GUILD_MEMBER_REMOVE",
+    "MESSAGE_CREATE",
+    "MESSAGE_REACTION_ADD",
+    "MESSAGE_REACTION_REMOVE",
+];
+
+const MAX_SUBSCRIPTIONS_PER_GUILD: i64 = 10;
+const MAX_DELIVERY_ATTEMPTS: i64 = 12;
+const MAX_DELIVERY_AGE_MS: i64 = 86_400_000;
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Deliveries sent at once by one queue pass, so a slow receiver only
+/// holds up its own slot.
+const DELIVERY_CONCURRENCY: usize = 8;
+
+pub const SIGNATURE_HEADER: &str = "X-Paracord-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Paracord-Timestamp";
+
+fn subscription_to_json(
+    row: &paracord_db::integration_webhooks::IntegrationSubscriptionRow,
+    secret: Option<&str>,
+) -> Value {
+    let mut v = json!({
+        "id": row.id.to_string(),
+        "guild_id": row.guild_id.to_string(),
+        "url": row.url,
+        "event_types": row.event_type_list(),
+        "creator_id": row.creator_id.map(|id| id.to_string()),
+        "created_at": row.created_at.to_rfc3339(),
+    });
+    if let Some(secret) = secret {
+        v["secret"] = json!(secret);
+    }
+    v
+}
+
+fn normalize_event_types(raw: &[String]) -> Result<String, ApiError> {
+    let mut types: Vec<&str> = Vec::new();
+    for entry in raw {
+        let entry = entry.trim();
+        let known = INTEGRATION_EVENT_TYPES
+            .iter()
+            .find(|t| t.eq_ignore_ascii_case(entry))
+            .ok_or_else(|| ApiError::BadRequest(format!("Unsupported event type: {entry}")))?;
+        if !types.contains(known) {
+            types.push(known);
+        }
+    }
+    if types.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one event type is required".into(),
+        ));
+    }
+    Ok(types.join(","))
+}
+
+/// `sha256=<hex>` HMAC of `"{timestamp}.{body}"` keyed with the subscription
+/// secret; receivers recompute it to authenticate a delivery.
+pub fn sign_delivery(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    let mut out = String::with_capacity(7 + digest.len() * 2);
+    out.push_str("sha256=");
+    for b in digest {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+
+#[derive(Deserialize)]
+pub struct CreateIntegrationWebhookRequest {
+    pub url: String,
+    pub event_types: Vec<String>,
+}
+
+pub async fn create_integration_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(guild_id): Path<i64>,
+    Json(body): Json<CreateIntegrationWebhookRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_manage_webhooks(&state, guild_id, auth.user_id).await?;
+
+    let event_types = normalize_event_types(&body.event_types)?;
+    let url = outbound_url::parse_target(&body.url).map_err(ApiError::BadRequest)?;
+    outbound_url::resolve_public(&url)
+        .await
+        .map_err(ApiError::BadRequest)?;
+
+    let existing =
+        paracord_db::integration_webhooks::count_guild_subscriptions(&state.db, guild_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if existing >= MAX_SUBSCRIPTIONS_PER_GUILD {
+        return Err(ApiError::BadRequest(format!(
+            "Guilds can have at most {MAX_SUBSCRIPTIONS_PER_GUILD} integration webhooks"
+        )));
+    }
+
+    let id = paracord_util::snowflake::generate(1);
+    let secret = generate_webhook_token();
+    let row = paracord_db::integration_webhooks::create_subscription(
+        &state.db,
+        id,
+        guild_id,
+        url.as_str(),
+        &secret,
+        &event_types,
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    audit::log_action(
+        &state,
+        guild_id,
+        auth.user_id,
+        audit::ACTION_INTEGRATION_CREATE,
+        Some(id),
+        None,
+        Some(json!({ "url": row.url, "event_types": row.event_type_list() })),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(subscription_to_json(&row, Some(&secret))),
+    ))
+}
+
+pub async fn list_integration_webhooks(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(guild_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    require_manage_webhooks(&state, guild_id, auth.user_id).await?;
+
+    let rows = paracord_db::integration_webhooks::list_guild_subscriptions(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let result: Vec<Value> = rows
+        .iter()
+        .map(|row| subscription_to_json(row, None))
+        .collect();
+    Ok(Json(json!(result)))
+}
+
+pub async fn delete_integration_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((guild_id, subscription_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, ApiError> {
+    require_manage_webhooks(&state, guild_id, auth.user_id).await?;
+
+    let deleted = paracord_db::integration_webhooks::delete_subscription(
+        &state.db,
+        guild_id,
+        subscription_id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !deleted {
+        return Err(ApiError::NotFound);
+    }
+
+    audit::log_action(
+        &state,
+        guild_id,
+        auth.user_id,
+        audit::ACTION_INTEGRATION_DELETE,
+        Some(subscription_id),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The channel a channel-scoped integration event happened in.
+fn event_channel_id(event: &ServerEvent) -> Option<i64> {
+    event
+        .payload
+        .get("channel_id")
+        .and_then(|v| v.as_str())
+        .and_then(|raw| raw.parse::<i64>().ok())
+}
+
+/// Whether the subscription's creator can still see `channel_id`. Channel
+/// events go out with the creator's view of the guild, so a webhook can't
+/// leak a channel its creator was never allowed to read. Subscriptions whose
+/// creator is gone, or whose check errors, get nothing.
+async fn creator_can_view_channel(
+    state: &AppState,
+    creator_id: Option<i64>,
+    guild_id: i64,
+    owner_id: i64,
+    channel_id: i64,
+) -> bool {
+    let Some(creator_id) = creator_id else {
+        return false;
+    };
+    match paracord_core::permissions::compute_channel_permissions_cached(
+        &state.permission_cache,
+        &state.db,
+        guild_id,
+        channel_id,
+        owner_id,
+        creator_id,
+    )
+    .await
+    {
+        Ok(perms) => perms.contains(Permissions::VIEW_CHANNEL),
+        Err(e) => {
+            tracing::debug!("integrations: permission check failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Queue `event` for every integration webhook of its guild subscribed to it.
+/// Events without a guild (DMs, user-targeted dispatches) are never forwarded,
+/// and channel events only reach subscriptions whose creator can view the
+/// channel.
+pub async fn enqueue_integration_event(state: &AppState, event: &ServerEvent) {
+    let Some(guild_id) = event.guild_id else {
+        return;
+    };
+    if event.target_user_ids.is_some()
+        || !INTEGRATION_EVENT_TYPES.contains(&event.event_type.as_str())
+    {
+        return;
+    }
+    let subscriptions = match paracord_db::integration_webhooks::list_guild_subscriptions(
+        &state.db, guild_id,
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!("integrations: failed to load subscriptions: {}", e);
+            return;
+        }
+    };
+
+    let subscriptions: Vec<_> = subscriptions
+        .into_iter()
+        .filter(|s| s.event_type_list().contains(&event.event_type.as_str()))
+        .collect();
+    if subscriptions.is_empty() {
+        return;
+    }
+    let channel_scope = match event_channel_id(event) {
+        Some(channel_id) => match paracord_db::guilds::get_guild(&state.db, guild_id).await {
+            Ok(Some(guild)) => Some((channel_id, guild.owner_id)),
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("integrations: failed to load guild: {}", e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let now = chrono::Utc::now();
+    for subscription in subscriptions {
+        if let Some((channel_id, owner_id)) = channel_scope {
+            if !creator_can_view_channel(
+                state,
+                subscription.creator_id,
+                guild_id,
+                owner_id,
+                channel_id,
+            )
+            .await
+            {
+                continue;
+            }
+        }
+        let delivery_id = paracord_util::snowflake::generate(1);
+        let body = json!({
+            "id": delivery_id.to_string(),
+            "type": event.event_type,
+            "guild_id": guild_id.to_string(),
+            "timestamp": now.to_rfc3339(),
+            "data": *event.payload,
+        });
+        if let Err(e) = paracord_db::integration_webhooks::enqueue_delivery(
+            &state.db,
+            delivery_id,
+            subscription.id,
+            &event.event_type,
+            &body.to_string(),
+            now.timestamp_millis(),
+        )
+        .await
+        {
+            tracing::warn!("integrations: failed to queue delivery: {}", e);
+        }
+    }
+}
+
+async fn send_delivery(
+    row: &paracord_db::integration_webhooks::IntegrationDeliveryRow,
+) -> Result<u16, String> {
+    // Re-check at send time: the host may resolve differently than when the
+    // subscription was created. The client is pinned to the checked addresses.
+    let url = outbound_url::parse_target(&row.url)?;
+    let addrs = outbound_url::resolve_public(&url).await?;
+    let host = url.host_str().unwrap_or_default().to_string();
+    let client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(&host, &addrs)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let timestamp = chrono::Utc::now().timestamp();
+    let response = client
+        .post(url)
+        .header("content-type", "application/json")
+        .header("X-Paracord-Event", &row.event_type)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            sign_delivery(&row.secret, timestamp, &row.payload),
+        )
+        .body(row.payload.clone())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err(format!("HTTP {}", status.as_u16()))
+    }
+}
+
+/// Send due integration deliveries, rescheduling failures with the same
+/// backoff as the federation outbound queue.
+pub async fn process_integration_deliveries_once(state: &AppState, limit: i64) {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    match paracord_db::integration_webhooks::purge_expired_deliveries(
+        &state.db,
+        now_ms,
+        MAX_DELIVERY_ATTEMPTS,
+        MAX_DELIVERY_AGE_MS,
+    )
+    .await
+    {
+        Ok(purged) if purged > 0 => {
+            tracing::info!("integrations: dropped {} expired deliveries", purged);
+        }
+        Err(e) => tracing::warn!("integrations: failed to purge deliveries: {}", e),
+        _ => {}
+    }
+
+    let due =
+        match paracord_db::integration_webhooks::fetch_due_deliveries(&state.db, now_ms, limit)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::warn!("integrations: failed to load delivery queue: {}", e);
+                return;
+            }
+        };
+
+    let mut sends = futures_util::stream::iter(due)
+        .map(|row| async move {
+            let result = send_delivery(&row).await;
+            (row, result)
+        })
+        .buffer_unordered(DELIVERY_CONCURRENCY);
+    while let Some((row, result)) = sends.next().await {
+        match result {
+            Ok(_) => {
+                let _ =
+                    paracord_db::integration_webhooks::mark_delivery_delivered(&state.db, row.id)
+                        .await;
+            }
+            Err(err) => {
+                let attempt_ts = chrono::Utc::now().timestamp_millis();
+                let retry_at = paracord_federation::next_retry_ts(attempt_ts, row.attempt_count);
+                tracing::debug!(
+                    "integrations: delivery {} to subscription {} failed: {}",
+                    row.id,
+                    row.subscription_id,
+                    err
+                );
+                let _ = paracord_db::integration_webhooks::mark_delivery_retry(
+                    &state.db,
+                    row.id,
+                    retry_at,
+                    Some(&err),
+                )
+                .await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_types_are_validated_and_canonicalized() {
+        let raw = vec![
+            "message_reaction_add".to_string(),
+            "CHANNEL_PINS_UPDATE".to_string(),
+            "MESSAGE_REACTION_ADD".to_string(),
+        ];
+        assert_eq!(
+            normalize_event_types(&raw).unwrap(),
+            "MESSAGE_REACTION_ADD,CHANNEL_PINS_UPDATE"
+        );
+        assert!(normalize_event_types(&[]).is_err());
+        assert!(normalize_event_types(&["TYPING_START".to_string()]).is_err());
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let sig = sign_delivery("secret", 1_700_000_000, "{}");
+        assert!(sig.starts_with("sha256="));
+        assert_eq!(sig.len(), 7 + 64);
+        assert_ne!(sig, sign_delivery("secret", 1_700_000_001, "{}"));
+        assert_ne!(sig, sign_delivery("other", 1_700_000_000, "{}"));
+    }
+}
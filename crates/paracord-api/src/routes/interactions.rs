@@ -4,7 +4,6 @@ use axum::{
     Json,
 };
 use paracord_core::AppState;
-use paracord_util::validation::contains_dangerous_markup;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
@@ -59,19 +58,26 @@ pub struct FollowupMessageRequest {
 
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
+fn contains_dangerous_markup(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    lower.contains("<script")
+        || lower.contains("javascript:")
+        || lower.contains("onerror=")
+        || lower.contains("onload=")
+        || lower.contains("<iframe")
+}
+
 /// Validate an interaction token by hashing it and comparing to the stored hash.
 async fn validate_interaction_token(
     state: &AppState,
     interaction_id: i64,
     raw_token: &str,
 ) -> Result<paracord_db::interaction_tokens::InteractionTokenRow, ApiError> {
-    let token_row = paracord_db::interaction_tokens::get_interaction_token(
-        &state.db,
-        interaction_id,
-    )
-    .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-    .ok_or(ApiError::NotFound)?;
+    let token_row =
+        paracord_db::interaction_tokens::get_interaction_token(&state.db, interaction_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .ok_or(ApiError::NotFound)?;
 
     // Check expiry
     if token_row.expires_at < chrono::Utc::now() {
@@ -164,20 +170,16 @@ pub async fn invoke_interaction(
     match body.interaction_type {
         // ApplicationCommand (2)
         2 => {
-            let command_name = body
-                .command_name
-                .as_deref()
-                .ok_or_else(|| ApiError::BadRequest("command_name required for slash commands".into()))?;
+            let command_name = body.command_name.as_deref().ok_or_else(|| {
+                ApiError::BadRequest("command_name required for slash commands".into())
+            })?;
 
             // Resolve the command
-            let cmd = paracord_core::interactions::resolve_slash_command(
-                &state,
-                command_name,
-                guild_id,
-            )
-            .await
-            .map_err(ApiError::from)?
-            .ok_or_else(|| ApiError::NotFound)?;
+            let cmd =
+                paracord_core::interactions::resolve_slash_command(&state, command_name, guild_id)
+                    .await
+                    .map_err(ApiError::from)?
+                    .ok_or_else(|| ApiError::NotFound)?;
 
             // Look up the bot application to get the bot_user_id
             let bot_app =
@@ -211,17 +213,15 @@ pub async fn invoke_interaction(
         }
         // MessageComponent (3)
         3 => {
-            let message_id_str = body
-                .message_id
-                .as_deref()
-                .ok_or_else(|| ApiError::BadRequest("message_id required for component interactions".into()))?;
+            let message_id_str = body.message_id.as_deref().ok_or_else(|| {
+                ApiError::BadRequest("message_id required for component interactions".into())
+            })?;
             let message_id = message_id_str
                 .parse::<i64>()
                 .map_err(|_| ApiError::BadRequest("Invalid message_id".into()))?;
-            let custom_id = body
-                .custom_id
-                .as_deref()
-                .ok_or_else(|| ApiError::BadRequest("custom_id required for component interactions".into()))?;
+            let custom_id = body.custom_id.as_deref().ok_or_else(|| {
+                ApiError::BadRequest("custom_id required for component interactions".into())
+            })?;
 
             // Look up the message to find the bot author
             let msg = paracord_db::messages::get_message(&state.db, message_id)
@@ -230,11 +230,13 @@ pub async fn invoke_interaction(
                 .ok_or(ApiError::NotFound)?;
 
             // Find the bot application by bot_user_id (the message author)
-            let bot_app =
-                paracord_db::bot_applications::get_bot_application_by_user_id(&state.db, msg.author_id)
-                    .await
-                    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-                    .ok_or_else(|| ApiError::BadRequest("message was not sent by a bot".into()))?;
+            let bot_app = paracord_db::bot_applications::get_bot_application_by_user_id(
+                &state.db,
+                msg.author_id,
+            )
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .ok_or_else(|| ApiError::BadRequest("message was not sent by a bot".into()))?;
 
             let interaction_data = json!({
                 "custom_id": custom_id,
@@ -282,7 +284,9 @@ pub async fn interaction_callback(
     if let Some(data) = body.data.as_ref() {
         if let Some(content) = data.get("content").and_then(|v| v.as_str()) {
             if contains_dangerous_markup(content) {
-                return Err(ApiError::BadRequest("Content contains unsafe markup".into()));
+                return Err(ApiError::BadRequest(
+                    "Content contains unsafe markup".into(),
+                ));
             }
         }
     }
@@ -312,13 +316,10 @@ pub async fn edit_original_response(
 
     // M14: Verify bot is still installed in the guild before allowing edit
     if let Some(guild_id) = token_row.guild_id {
-        let is_installed = paracord_db::bot_applications::is_bot_in_guild(
-            &state.db,
-            app_id,
-            guild_id,
-        )
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        let is_installed =
+            paracord_db::bot_applications::is_bot_in_guild(&state.db, app_id, guild_id)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
         if !is_installed {
             return Err(ApiError::Forbidden);
         }
@@ -328,7 +329,9 @@ pub async fn edit_original_response(
 
     // M18: Validate edited content for dangerous markup
     if !content.is_empty() && contains_dangerous_markup(content) {
-        return Err(ApiError::BadRequest("Content contains unsafe markup".into()));
+        return Err(ApiError::BadRequest(
+            "Content contains unsafe markup".into(),
+        ));
     }
 
     // H12: Use the stored response_message_id to find the original message.
@@ -338,13 +341,9 @@ pub async fn edit_original_response(
         .response_message_id
         .ok_or_else(|| ApiError::NotFound)?;
 
-    let updated = paracord_db::messages::update_message_unchecked(
-        &state.db,
-        msg_id,
-        content,
-    )
-    .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let updated = paracord_db::messages::update_message(&state.db, msg_id, content)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
     let msg_json = json!({
         "id": updated.id.to_string(),
@@ -358,10 +357,12 @@ pub async fn edit_original_response(
     });
 
     // Dispatch MESSAGE_UPDATE
-    state.event_bus.dispatch(
+    paracord_core::interactions::dispatch_response_event(
+        &state,
         "MESSAGE_UPDATE",
         msg_json.clone(),
-        token_row.guild_id,
+        &token_row,
+        updated.flags,
     );
 
     Ok(Json(msg_json))
@@ -378,13 +379,10 @@ pub async fn delete_original_response(
 
     // M14: Verify bot is still installed in the guild before allowing delete
     if let Some(guild_id) = token_row.guild_id {
-        let is_installed = paracord_db::bot_applications::is_bot_in_guild(
-            &state.db,
-            app_id,
-            guild_id,
-        )
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+        let is_installed =
+            paracord_db::bot_applications::is_bot_in_guild(&state.db, app_id, guild_id)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
         if !is_installed {
             return Err(ApiError::Forbidden);
         }
@@ -395,19 +393,27 @@ pub async fn delete_original_response(
         .response_message_id
         .ok_or_else(|| ApiError::NotFound)?;
 
-    paracord_db::messages::delete_message_unchecked(&state.db, msg_id)
+    let flags = paracord_db::messages::get_message(&state.db, msg_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .map(|m| m.flags)
+        .unwrap_or(0);
+
+    paracord_db::messages::delete_message(&state.db, msg_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
     // Dispatch MESSAGE_DELETE
-    state.event_bus.dispatch(
+    paracord_core::interactions::dispatch_response_event(
+        &state,
         "MESSAGE_DELETE",
         json!({
             "id": msg_id.to_string(),
             "channel_id": token_row.channel_id.to_string(),
             "guild_id": token_row.guild_id.map(|id| id.to_string()),
         }),
-        token_row.guild_id,
+        &token_row,
+        flags,
     );
 
     Ok(StatusCode::NO_CONTENT)
@@ -424,43 +430,44 @@ pub async fn create_followup_message(
     let token_row = validate_webhook_token(&state, app_id, &token).await?;
 
     // Look up the bot application to get the real bot_user_id for message authorship
-    let bot_app =
-        paracord_db::bot_applications::get_bot_application(&state.db, app_id)
-            .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-            .ok_or(ApiError::NotFound)?;
+    let bot_app = paracord_db::bot_applications::get_bot_application(&state.db, app_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
 
     let content = body.content.as_deref().unwrap_or("");
 
     // M18: Validate followup content for dangerous markup
     if !content.is_empty() && contains_dangerous_markup(content) {
-        return Err(ApiError::BadRequest("Content contains unsafe markup".into()));
+        return Err(ApiError::BadRequest(
+            "Content contains unsafe markup".into(),
+        ));
     }
 
     let components_json = body
         .components
         .as_ref()
-        .map(|v| serde_json::to_string(v))
+        .map(serde_json::to_string)
         .transpose()
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("serialize components: {}", e)))?;
     let flags = body.flags.unwrap_or(0) as i32;
-    let message_id = paracord_util::snowflake::generate(1);
-
-    let msg = paracord_db::messages::create_message_with_meta(
-        &state.db,
-        message_id,
-        token_row.channel_id,
+    let msg = paracord_core::interactions::create_response_message(
+        &state,
+        &token_row,
         bot_app.bot_user_id,
         content,
-        20, // APPLICATION_COMMAND message type
-        None,
         flags,
-        None,
-        None,
-        components_json.as_deref(),
     )
-    .await
-    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    .await?;
+    if components_json.is_some() {
+        paracord_db::messages::set_message_components(
+            &state.db,
+            msg.id,
+            components_json.as_deref(),
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
 
     let msg_json = json!({
         "id": msg.id.to_string(),
@@ -476,10 +483,12 @@ pub async fn create_followup_message(
         "created_at": msg.created_at.to_rfc3339(),
     });
 
-    state.event_bus.dispatch(
+    paracord_core::interactions::dispatch_response_event(
+        &state,
         "MESSAGE_CREATE",
         msg_json.clone(),
-        token_row.guild_id,
+        &token_row,
+        msg.flags,
     );
 
     Ok((StatusCode::CREATED, Json(msg_json)))
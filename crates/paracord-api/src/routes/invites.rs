@@ -266,10 +266,17 @@ pub async fn accept_invite(
         "Invite target must be a guild/space channel".into(),
     ))?;
 
-    let already_member = paracord_db::members::get_member(&state.db, auth.user_id, space_id)
+    let existing_member = paracord_db::members::get_member(&state.db, auth.user_id, space_id)
         .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
-        .is_some();
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let already_member = existing_member.is_some();
+    if !already_member
+        && paracord_db::bans::is_banned(&state.db, auth.user_id, space_id, chrono::Utc::now())
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    {
+        return Err(ApiError::Forbidden);
+    }
 
     let invite_state = if already_member {
         Some(preview.clone())
@@ -292,11 +299,21 @@ pub async fn accept_invite(
         ));
     };
 
+    let mut pending = existing_member.is_some_and(|member| member.pending);
     if !already_member {
-        // Add user membership only for the invited space.
-        paracord_db::members::add_member(&state.db, auth.user_id, space_id)
+        // Add user membership only for the invited space. Screened guilds hold
+        // new members back until they accept the rules.
+        pending = match paracord_db::members::add_invited_member(&state.db, auth.user_id, space_id)
             .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        {
+            Some(joined_pending) => joined_pending,
+            // A concurrent accept inserted the row first; report its state.
+            None => paracord_db::members::get_member(&state.db, auth.user_id, space_id)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+                .is_some_and(|member| member.pending),
+        };
     }
 
     // Ensure default Member role assignment for this space.
@@ -340,7 +357,11 @@ pub async fn accept_invite(
         state.member_index.add_member(guild.id, auth.user_id);
         state.event_bus.dispatch(
             "GUILD_MEMBER_ADD",
-            json!({"guild_id": guild.id.to_string(), "user_id": auth.user_id.to_string()}),
+            json!({
+                "guild_id": guild.id.to_string(),
+                "user_id": auth.user_id.to_string(),
+                "pending": pending,
+            }),
             Some(guild.id),
         );
 
@@ -369,7 +390,7 @@ pub async fn accept_invite(
         }
     }
 
-    Ok(Json(json!({ "guild": guild_json })))
+    Ok(Json(json!({ "guild": guild_json, "pending": pending })))
 }
 
 pub async fn list_guild_invites(
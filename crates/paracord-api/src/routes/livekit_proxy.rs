@@ -48,13 +48,14 @@ fn is_livekit_access_token_valid(state: &AppState, uri: &axum::http::Uri) -> boo
         return false;
     };
 
+    let livekit = state.voice.livekit();
     let mut validation = Validation::new(Algorithm::HS256);
     validation.validate_exp = true;
-    validation.set_issuer(&[state.config.livekit_api_key.as_str()]);
+    validation.set_issuer(&[livekit.api_key.as_str()]);
 
     decode::<LiveKitProxyClaims>(
         &token,
-        &DecodingKey::from_secret(state.config.livekit_api_secret.as_bytes()),
+        &DecodingKey::from_secret(livekit.api_secret.as_bytes()),
         &validation,
     )
     .map(|data| {
@@ -38,7 +38,9 @@ pub async fn list_members(
             "deaf": m.deaf,
             "mute": m.mute,
             "communication_disabled_until": m.communication_disabled_until.map(|v| v.to_rfc3339()),
+            "pending": m.pending,
             "roles": role_ids,
+            "role_icon": crate::routes::roles::member_role_icon_json(&state.config, &roles),
             "user": {
                 "id": m.user_id.to_string(),
                 "username": m.username,
@@ -59,6 +61,12 @@ pub struct UpdateMemberRequest {
     pub nick: Option<String>,
     pub roles: Option<Vec<String>>,
     pub communication_disabled_until: Option<String>,
+    /// Server-side voice mute.
+    pub mute: Option<bool>,
+    /// Server-side voice deafen.
+    pub deaf: Option<bool>,
+    /// Voice channel to move the member into.
+    pub channel_id: Option<String>,
 }
 
 pub async fn update_member(
@@ -86,18 +94,106 @@ pub async fn update_member(
             paracord_models::permissions::Permissions::MANAGE_NICKNAMES,
         )?;
     }
+    if let Some(nick) = body.nick.as_deref() {
+        crate::routes::security::ensure_name_allowed(
+            &state,
+            "nickname",
+            nick,
+            Some(auth.user_id),
+            None,
+        )
+        .await?;
+    }
+
+    let mut voice_move = None;
+    if let Some(raw_channel_id) = body.channel_id.as_deref() {
+        paracord_core::permissions::require_permission(actor_perms, Permissions::MOVE_MEMBERS)?;
+        let target_channel_id = raw_channel_id
+            .parse::<i64>()
+            .map_err(|_| ApiError::BadRequest("Invalid channel id".into()))?;
+        let channel = paracord_db::channels::get_channel(&state.db, target_channel_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .ok_or(ApiError::NotFound)?;
+        if channel.guild_id() != Some(guild_id) || channel.channel_type != 2 {
+            return Err(ApiError::BadRequest(
+                "channel_id must be a voice channel in this guild".into(),
+            ));
+        }
+        let actor_channel_perms = paracord_core::permissions::compute_channel_permissions(
+            &state.db,
+            guild_id,
+            target_channel_id,
+            guild.owner_id,
+            auth.user_id,
+        )
+        .await?;
+        paracord_core::permissions::require_permission(actor_channel_perms, Permissions::CONNECT)?;
+        let voice_state =
+            paracord_db::voice_states::get_user_voice_state(&state.db, user_id, Some(guild_id))
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+                .ok_or_else(|| ApiError::BadRequest("Member is not connected to voice".into()))?;
+        if voice_state.channel_id != target_channel_id {
+            if !state.livekit_available() {
+                return Err(ApiError::ServiceUnavailable(
+                    "Moving members requires the voice server, which is not available".into(),
+                ));
+            }
+            voice_move = Some((voice_state, channel));
+        }
+    }
+
+    if body.mute.is_some() || body.deaf.is_some() || body.channel_id.is_some() {
+        if body.mute.is_some() {
+            paracord_core::permissions::require_permission(actor_perms, Permissions::MUTE_MEMBERS)?;
+        }
+        if body.deaf.is_some() {
+            paracord_core::permissions::require_permission(
+                actor_perms,
+                Permissions::DEAFEN_MEMBERS,
+            )?;
+        }
+        if auth.user_id != guild.owner_id {
+            if user_id == guild.owner_id {
+                return Err(ApiError::Forbidden);
+            }
+            let actor_top_role_pos = actor_roles.iter().map(|r| r.position).max().unwrap_or(0);
+            let target_roles = paracord_db::roles::get_member_roles(&state.db, user_id, guild_id)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+            let target_top_role_pos = target_roles.iter().map(|r| r.position).max().unwrap_or(0);
+            if user_id != auth.user_id && target_top_role_pos >= actor_top_role_pos {
+                return Err(ApiError::Forbidden);
+            }
+        }
+    }
 
     let updated = paracord_db::members::update_member(
         &state.db,
         user_id,
         guild_id,
         body.nick.as_deref(),
-        None,
-        None,
+        body.deaf,
+        body.mute,
     )
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
+    if let Some((voice_state, to_channel)) = voice_move {
+        move_member_voice(
+            &state,
+            guild_id,
+            guild.owner_id,
+            &updated,
+            &voice_state,
+            &to_channel,
+        )
+        .await?;
+    } else if body.mute.is_some() || body.deaf.is_some() {
+        propagate_server_voice_state(&state, guild_id, &updated, body.deaf.is_some()).await;
+    }
+
     let mut role_ids: Vec<String> =
         paracord_db::roles::get_member_roles(&state.db, user_id, guild_id)
             .await
@@ -225,6 +321,7 @@ pub async fn update_member(
         "deaf": updated.deaf,
         "mute": updated.mute,
         "communication_disabled_until": timed_out_until.map(|v| v.to_rfc3339()),
+        "pending": updated.pending,
         "joined_at": updated.joined_at.to_rfc3339(),
         "roles": role_ids.clone(),
     });
@@ -235,7 +332,10 @@ pub async fn update_member(
             "guild_id": guild_id.to_string(),
             "user_id": user_id.to_string(),
             "nick": updated.nick,
+            "deaf": updated.deaf,
+            "mute": updated.mute,
             "communication_disabled_until": timed_out_until.map(|v| v.to_rfc3339()),
+            "pending": updated.pending,
             "roles": role_ids.clone(),
         }),
         Some(guild_id),
@@ -249,6 +349,8 @@ pub async fn update_member(
         None,
         Some(json!({
             "nick": updated.nick,
+            "deaf": updated.deaf,
+            "mute": updated.mute,
             "communication_disabled_until": timed_out_until.map(|v| v.to_rfc3339()),
             "roles": role_ids,
         })),
@@ -258,6 +360,191 @@ pub async fn update_member(
     Ok(Json(member_json))
 }
 
+/// Apply a member's server mute/deafen to their live voice session, if they
+/// are in one, and broadcast the new voice state.
+async fn propagate_server_voice_state(
+    state: &AppState,
+    guild_id: i64,
+    member: &paracord_db::members::MemberRow,
+    deaf_changed: bool,
+) {
+    let user_id = member.user_id;
+    let Ok(Some(voice_state)) =
+        paracord_db::voice_states::get_user_voice_state(&state.db, user_id, Some(guild_id)).await
+    else {
+        return;
+    };
+    let channel_id = voice_state.channel_id;
+
+    if deaf_changed {
+        if let Err(err) = state
+            .voice
+            .server_deafen_user(channel_id, user_id, member.deaf)
+            .await
+        {
+            tracing::warn!(
+                "Failed to apply server deafen for user {} in channel {}: {}",
+                user_id,
+                channel_id,
+                err
+            );
+        }
+    }
+    // Deafen implies mute, so publishing stays revoked until both are lifted.
+    if let Err(err) = state
+        .voice
+        .server_mute_user(channel_id, user_id, member.mute || member.deaf)
+        .await
+    {
+        tracing::warn!(
+            "Failed to apply server mute for user {} in channel {}: {}",
+            user_id,
+            channel_id,
+            err
+        );
+    }
+
+    let user = paracord_db::users::get_user_by_id(&state.db, user_id)
+        .await
+        .ok()
+        .flatten();
+    state.event_bus.dispatch(
+        "VOICE_STATE_UPDATE",
+        json!({
+            "user_id": user_id.to_string(),
+            "channel_id": channel_id.to_string(),
+            "guild_id": guild_id.to_string(),
+            "session_id": &voice_state.session_id,
+            "self_mute": voice_state.self_mute,
+            "self_deaf": voice_state.self_deaf,
+            "self_stream": voice_state.self_stream,
+            "self_video": voice_state.self_video,
+            "suppress": voice_state.suppress,
+            "mute": member.mute,
+            "deaf": member.deaf,
+            "priority_speaker": voice_state.priority_speaker,
+            "username": user.as_ref().map(|u| u.username.as_str()),
+            "avatar_hash": user.as_ref().and_then(|u| u.avatar_hash.as_deref()),
+        }),
+        Some(guild_id),
+    );
+}
+
+/// Move a connected member into another voice channel: swap their LiveKit
+/// room, record the new voice state, and hand the member's clients a fresh
+/// token over `VOICE_SERVER_UPDATE` so they reconnect to the target room.
+async fn move_member_voice(
+    state: &AppState,
+    guild_id: i64,
+    owner_id: i64,
+    member: &paracord_db::members::MemberRow,
+    voice_state: &paracord_db::voice_states::VoiceStateRow,
+    to_channel: &paracord_db::channels::ChannelRow,
+) -> Result<(), ApiError> {
+    let to_channel_id = to_channel.id;
+    let user_id = member.user_id;
+    let from_channel_id = voice_state.channel_id;
+    let user = paracord_db::users::get_user_by_id(&state.db, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let target_perms = paracord_core::permissions::compute_channel_permissions(
+        &state.db,
+        guild_id,
+        to_channel_id,
+        owner_id,
+        user_id,
+    )
+    .await?;
+    let priority_speaker = target_perms.contains(Permissions::PRIORITY_SPEAKER);
+
+    // Drop the member's connection to the old room; the client reconnects
+    // with the token sent in VOICE_SERVER_UPDATE below.
+    if let Err(err) = state
+        .voice
+        .remove_participant(from_channel_id, guild_id, user_id)
+        .await
+    {
+        tracing::warn!(
+            channel_id = from_channel_id,
+            user_id,
+            error = %err,
+            "failed to disconnect moved member from LiveKit room"
+        );
+    }
+    if let Some(remaining) = state.voice.leave_room(from_channel_id, user_id).await {
+        if remaining.is_empty() {
+            let voice = state.voice.clone();
+            tokio::spawn(async move {
+                let _ = voice.cleanup_room(from_channel_id).await;
+            });
+        }
+    }
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let join_resp = state
+        .voice
+        .join_channel(
+            to_channel_id,
+            guild_id,
+            user_id,
+            &user.username,
+            &session_id,
+            !(member.mute || member.deaf),
+            priority_speaker,
+            paracord_media::AudioBitrate::default(),
+            paracord_media::voice::room_max_participants(to_channel.user_limit),
+        )
+        .await
+        .map_err(ApiError::Internal)?;
+
+    paracord_db::voice_states::upsert_voice_state(
+        &state.db,
+        user_id,
+        Some(guild_id),
+        to_channel_id,
+        &session_id,
+        priority_speaker,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    state.event_bus.dispatch_to_users(
+        paracord_models::gateway::EVENT_VOICE_SERVER_UPDATE,
+        json!({
+            "guild_id": guild_id.to_string(),
+            "channel_id": to_channel_id.to_string(),
+            "token": join_resp.token,
+            "url": &state.config.livekit_public_url,
+            "room_name": join_resp.room_name,
+            "session_id": &session_id,
+            "priority_speaker": priority_speaker,
+        }),
+        vec![user_id],
+    );
+    state.event_bus.dispatch(
+        "VOICE_STATE_UPDATE",
+        json!({
+            "user_id": user_id.to_string(),
+            "channel_id": to_channel_id.to_string(),
+            "guild_id": guild_id.to_string(),
+            "session_id": &session_id,
+            "self_mute": voice_state.self_mute,
+            "self_deaf": voice_state.self_deaf,
+            "self_stream": false,
+            "self_video": false,
+            "suppress": voice_state.suppress,
+            "mute": member.mute,
+            "deaf": member.deaf,
+            "priority_speaker": priority_speaker,
+            "username": &user.username,
+            "avatar_hash": user.avatar_hash,
+        }),
+        Some(guild_id),
+    );
+    Ok(())
+}
+
 pub async fn kick_member(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -296,6 +583,54 @@ pub async fn kick_member(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Accept the guild's membership screening rules, lifting the pending state
+/// that keeps a new member from posting.
+pub async fn complete_member_screening(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(guild_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let member = paracord_db::members::get_member(&state.db, auth.user_id, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let was_pending = member.pending;
+    let member = if was_pending {
+        paracord_db::members::set_member_pending(&state.db, auth.user_id, guild_id, false)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .ok_or(ApiError::NotFound)?
+    } else {
+        member
+    };
+    let role_ids: Vec<String> =
+        paracord_db::roles::get_member_roles(&state.db, auth.user_id, guild_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .iter()
+            .map(|r| r.id.to_string())
+            .collect();
+
+    let member_json = json!({
+        "guild_id": guild_id.to_string(),
+        "user_id": member.user_id.to_string(),
+        "nick": member.nick,
+        "deaf": member.deaf,
+        "mute": member.mute,
+        "communication_disabled_until": member.communication_disabled_until.map(|v| v.to_rfc3339()),
+        "pending": member.pending,
+        "joined_at": member.joined_at.to_rfc3339(),
+        "roles": role_ids,
+    });
+    if was_pending {
+        state
+            .event_bus
+            .dispatch("GUILD_MEMBER_UPDATE", member_json.clone(), Some(guild_id));
+    }
+
+    Ok(Json(member_json))
+}
+
 pub async fn leave_guild(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -0,0 +1,309 @@
+//! TOTP two-factor authentication for logins.
+//!
+//! Enrollment is two-step: `POST /users/@me/mfa/totp` creates a pending
+//! secret, and `POST /users/@me/mfa/totp/confirm` activates it once the user
+//! proves their authenticator produces valid codes. Confirmation returns the
+//! single-use recovery codes; only their hashes are stored. The secret is
+//! sealed with the at-rest key when at-rest encryption is enabled.
+
+use axum::{extract::State, http::HeaderMap, Json};
+use chrono::Utc;
+use paracord_core::AppState;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+use crate::routes::security;
+
+pub use paracord_core::auth::LoginMfa;
+
+const TOTP_ISSUER: &str = "Paracord";
+
+fn db_error(e: paracord_db::DbError) -> ApiError {
+    ApiError::Internal(anyhow::anyhow!(e.to_string()))
+}
+
+fn auth_error(e: paracord_core::auth::AuthError) -> ApiError {
+    ApiError::Internal(anyhow::anyhow!(e.to_string()))
+}
+
+async fn log_recovery_code_use(
+    state: &AppState,
+    user_id: i64,
+    remaining: usize,
+    headers: &HeaderMap,
+) {
+    security::log_security_event(
+        state,
+        "auth.mfa.recovery_code.use",
+        Some(user_id),
+        Some(user_id),
+        None,
+        Some(headers),
+        Some(json!({ "recovery_codes_remaining": remaining })),
+    )
+    .await;
+}
+
+/// Run the shared login second-factor gate and record its outcome in the
+/// security log. Every path that issues a session for a user calls this.
+pub async fn check_login_mfa(
+    state: &AppState,
+    user_id: i64,
+    mfa_code: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<LoginMfa, ApiError> {
+    let outcome = paracord_core::auth::check_login_mfa(
+        &state.db,
+        state.config.secret_cryptor.as_ref(),
+        user_id,
+        mfa_code,
+        Utc::now().timestamp(),
+    )
+    .await
+    .map_err(auth_error)?;
+    match outcome {
+        LoginMfa::RecoveryCodeUsed { remaining } => {
+            log_recovery_code_use(state, user_id, remaining, headers).await;
+        }
+        LoginMfa::Invalid => {
+            security::log_security_event(
+                state,
+                "auth.mfa.failed",
+                Some(user_id),
+                Some(user_id),
+                None,
+                Some(headers),
+                None,
+            )
+            .await;
+        }
+        LoginMfa::Satisfied | LoginMfa::Required => {}
+    }
+    Ok(outcome)
+}
+
+/// [`check_login_mfa`] for handlers that only need pass or fail:
+/// `MFA_REQUIRED` when no code was sent, `UNAUTHORIZED` when it was wrong.
+pub async fn require_login_mfa(
+    state: &AppState,
+    user_id: i64,
+    mfa_code: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<(), ApiError> {
+    match check_login_mfa(state, user_id, mfa_code, headers).await? {
+        LoginMfa::Satisfied | LoginMfa::RecoveryCodeUsed { .. } => Ok(()),
+        LoginMfa::Required => Err(ApiError::MfaRequired),
+        LoginMfa::Invalid => Err(ApiError::Unauthorized),
+    }
+}
+
+/// Changing the second factor needs the account password, and is never
+/// allowed from an admin impersonation session.
+async fn require_password(
+    state: &AppState,
+    auth: &AuthUser,
+    password: &str,
+) -> Result<paracord_db::users::UserAuthRow, ApiError> {
+    if auth.impersonator_id.is_some() {
+        return Err(ApiError::Forbidden);
+    }
+    let user = paracord_db::users::get_user_auth_by_id(&state.db, auth.user_id)
+        .await
+        .map_err(db_error)?
+        .ok_or(ApiError::NotFound)?;
+    if user.password_hash.trim().is_empty() {
+        return Err(ApiError::Forbidden);
+    }
+    let valid =
+        paracord_core::auth::verify_password(password, &user.password_hash).unwrap_or(false);
+    if !valid {
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(user)
+}
+
+pub async fn get_totp_status(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Value>, ApiError> {
+    let totp = paracord_db::totp::get_totp(&state.db, auth.user_id)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(json!({
+        "enabled": totp.as_ref().is_some_and(|row| row.is_confirmed()),
+        "pending": totp.as_ref().is_some_and(|row| !row.is_confirmed()),
+        "confirmed_at": totp.as_ref().and_then(|row| row.confirmed_at).map(|t| t.to_rfc3339()),
+        "recovery_codes_remaining": totp
+            .as_ref()
+            .filter(|row| row.is_confirmed())
+            .map(|row| row.recovery_codes_hashed.len()),
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct BeginTotpRequest {
+    pub password: String,
+}
+
+pub async fn begin_totp_enrollment(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(body): Json<BeginTotpRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let user = require_password(&state, &auth, &body.password).await?;
+
+    let secret = paracord_core::auth::generate_totp_secret();
+    let stored = paracord_core::auth::seal_totp_secret(
+        state.config.secret_cryptor.as_ref(),
+        auth.user_id,
+        &secret,
+    )
+    .map_err(auth_error)?;
+    let started = paracord_db::totp::begin_enrollment(&state.db, auth.user_id, &stored)
+        .await
+        .map_err(db_error)?;
+    if !started {
+        return Err(ApiError::Conflict(
+            "Two-factor authentication is already enabled".into(),
+        ));
+    }
+
+    security::log_security_event(
+        &state,
+        "auth.mfa.totp.enroll",
+        Some(auth.user_id),
+        Some(auth.user_id),
+        auth.session_id.as_deref(),
+        Some(&headers),
+        None,
+    )
+    .await;
+
+    let account = if state.config.unique_usernames {
+        user.username.clone()
+    } else {
+        format!("{}#{:04}", user.username, user.discriminator)
+    };
+    Ok(Json(json!({
+        "secret": secret,
+        "otpauth_uri": paracord_core::auth::totp_provisioning_uri(TOTP_ISSUER, &account, &secret),
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmTotpRequest {
+    pub code: String,
+}
+
+pub async fn confirm_totp_enrollment(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(body): Json<ConfirmTotpRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if auth.impersonator_id.is_some() {
+        return Err(ApiError::Forbidden);
+    }
+    let totp = paracord_db::totp::get_totp(&state.db, auth.user_id)
+        .await
+        .map_err(db_error)?
+        .filter(|row| !row.is_confirmed())
+        .ok_or_else(|| ApiError::BadRequest("No pending two-factor enrollment".into()))?;
+
+    let secret = paracord_core::auth::open_totp_secret(
+        state.config.secret_cryptor.as_ref(),
+        auth.user_id,
+        &totp.secret,
+    )
+    .map_err(auth_error)?;
+    let now = Utc::now();
+    let step = paracord_core::auth::verify_totp_code(&secret, &body.code, now.timestamp())
+        .ok_or_else(|| ApiError::BadRequest("Invalid authentication code".into()))?;
+
+    let recovery_codes = paracord_core::auth::generate_recovery_codes();
+    let hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| paracord_core::auth::hash_recovery_code(code))
+        .collect();
+    let confirmed =
+        paracord_db::totp::confirm_enrollment(&state.db, auth.user_id, &hashes, step, now)
+            .await
+            .map_err(db_error)?;
+    if !confirmed {
+        return Err(ApiError::Conflict(
+            "Two-factor authentication is already enabled".into(),
+        ));
+    }
+
+    security::log_security_event(
+        &state,
+        "auth.mfa.totp.enable",
+        Some(auth.user_id),
+        Some(auth.user_id),
+        auth.session_id.as_deref(),
+        Some(&headers),
+        None,
+    )
+    .await;
+
+    Ok(Json(json!({ "recovery_codes": recovery_codes })))
+}
+
+#[derive(Deserialize)]
+pub struct DisableTotpRequest {
+    pub password: String,
+    /// Current TOTP code or an unused recovery code.
+    pub code: String,
+}
+
+pub async fn disable_totp(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(body): Json<DisableTotpRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_password(&state, &auth, &body.password).await?;
+    let totp = paracord_db::totp::get_totp(&state.db, auth.user_id)
+        .await
+        .map_err(db_error)?
+        .filter(|row| row.is_confirmed())
+        .ok_or_else(|| ApiError::BadRequest("Two-factor authentication is not enabled".into()))?;
+    match paracord_core::auth::verify_second_factor(
+        &state.db,
+        state.config.secret_cryptor.as_ref(),
+        &totp,
+        &body.code,
+        Utc::now().timestamp(),
+    )
+    .await
+    .map_err(auth_error)?
+    {
+        LoginMfa::Satisfied => {}
+        LoginMfa::RecoveryCodeUsed { remaining } => {
+            log_recovery_code_use(&state, auth.user_id, remaining, &headers).await;
+        }
+        LoginMfa::Required | LoginMfa::Invalid => {
+            return Err(ApiError::BadRequest("Invalid authentication code".into()));
+        }
+    }
+
+    paracord_db::totp::delete_totp(&state.db, auth.user_id)
+        .await
+        .map_err(db_error)?;
+
+    security::log_security_event(
+        &state,
+        "auth.mfa.totp.disable",
+        Some(auth.user_id),
+        Some(auth.user_id),
+        auth.session_id.as_deref(),
+        Some(&headers),
+        None,
+    )
+    .await;
+
+    Ok(Json(json!({ "enabled": false })))
+}
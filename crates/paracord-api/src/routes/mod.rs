@@ -1,26 +1,38 @@
+pub mod access_tokens;
 pub mod admin;
 pub mod audit;
 pub mod audit_logs;
 pub mod auth;
 pub mod bans;
 pub mod bots;
+pub mod capabilities;
 pub mod channels;
+pub mod commands;
 pub mod discovery;
 pub mod dms;
 pub mod emojis;
 pub mod events;
 pub mod federation;
 pub mod files;
+pub mod guild_templates;
 pub mod guilds;
+pub mod health;
+pub mod integrations;
+pub mod interactions;
 pub mod invites;
 pub mod keys;
 pub mod livekit_proxy;
 pub mod members;
+pub mod mfa;
+pub mod presences;
+pub mod push_subscriptions;
 pub mod realtime;
 pub mod relationships;
 pub mod roles;
 pub mod security;
+pub mod uploads;
 pub mod users;
 pub mod voice;
 pub mod voice_v2;
+pub mod webauthn;
 pub mod webhooks;
@@ -0,0 +1,67 @@
+use axum::{extract::State, Json};
+use paracord_core::AppState;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+
+const MAX_PRESENCE_USER_IDS: usize = 200;
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
+pub struct PresenceQueryRequest {
+    pub user_ids: Vec<String>,
+}
+
+/// Current presences for a batch of users. Users the caller shares no guild
+/// with and is not friends with are left out; visible users without a live
+/// presence are reported offline.
+pub async fn query_presences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<PresenceQueryRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if body.user_ids.len() > MAX_PRESENCE_USER_IDS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_PRESENCE_USER_IDS} user ids per request"
+        )));
+    }
+    let mut user_ids = Vec::with_capacity(body.user_ids.len());
+    for raw in &body.user_ids {
+        let id: i64 = raw
+            .parse()
+            .map_err(|_| ApiError::BadRequest("Invalid user ID".into()))?;
+        if !user_ids.contains(&id) {
+            user_ids.push(id);
+        }
+    }
+
+    let mut visible =
+        paracord_db::members::filter_users_sharing_guild(&state.db, auth.user_id, &user_ids)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let friend_ids = paracord_db::relationships::get_friend_user_ids(&state.db, auth.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    visible.extend(friend_ids);
+    visible.push(auth.user_id);
+
+    let presences = state.user_presences.read().await;
+    let out: Vec<Value> = user_ids
+        .into_iter()
+        .filter(|id| visible.contains(id))
+        .map(|id| {
+            presences.get(&id).cloned().unwrap_or_else(|| {
+                json!({
+                    "user_id": id.to_string(),
+                    "status": "offline",
+                    "custom_status": Value::Null,
+                    "activities": [],
+                })
+            })
+        })
+        .collect();
+
+    Ok(Json(Value::Array(out)))
+}
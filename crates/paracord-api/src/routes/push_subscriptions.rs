@@ -0,0 +1,118 @@
+//! Web Push subscriptions for the signed-in user's browsers and desktop
+//! clients. The body of `POST` is the browser's `PushSubscription.toJSON()`.
+//! A subscription is tied to the session that registered it and is removed
+//! when that session is revoked or signs out.
+
+use axum::{extract::State, http::StatusCode, Json};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use paracord_core::AppState;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+use crate::outbound_url;
+
+/// Devices kept per user; registering another drops the oldest.
+const MAX_SUBSCRIPTIONS_PER_USER: usize = 10;
+/// Uncompressed P-256 public key length.
+const P256DH_LEN: usize = 65;
+const AUTH_SECRET_LEN: usize = 16;
+
+fn push_notifier(
+    state: &AppState,
+) -> Result<&std::sync::Arc<paracord_core::push::PushNotifier>, ApiError> {
+    state.push_notifier.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Push notifications are not enabled on this server".into())
+    })
+}
+
+fn decoded_len(value: &str) -> Option<usize> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .ok()
+        .map(|bytes| bytes.len())
+}
+
+pub async fn get_vapid_key(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+) -> Result<Json<Value>, ApiError> {
+    let notifier = push_notifier(&state)?;
+    Ok(Json(json!({ "public_key": notifier.public_key() })))
+}
+
+#[derive(Deserialize)]
+pub struct PushSubscriptionKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Deserialize)]
+pub struct RegisterPushSubscriptionRequest {
+    pub endpoint: String,
+    pub keys: PushSubscriptionKeys,
+}
+
+pub async fn register_subscription(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<RegisterPushSubscriptionRequest>,
+) -> Result<StatusCode, ApiError> {
+    push_notifier(&state)?;
+    let session_id = auth.session_id.as_deref().ok_or_else(|| {
+        ApiError::BadRequest("Push subscriptions need a signed-in session".into())
+    })?;
+    let endpoint = outbound_url::parse_target(&body.endpoint)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid push endpoint: {e}")))?;
+    if decoded_len(&body.keys.p256dh) != Some(P256DH_LEN)
+        || decoded_len(&body.keys.auth) != Some(AUTH_SECRET_LEN)
+    {
+        return Err(ApiError::BadRequest(
+            "Invalid push subscription keys".into(),
+        ));
+    }
+
+    paracord_db::push::upsert_subscription(
+        &state.db,
+        auth.user_id,
+        session_id,
+        endpoint.as_str(),
+        body.keys.p256dh.trim_end_matches('='),
+        body.keys.auth.trim_end_matches('='),
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    paracord_db::push::prune_user_subscriptions(
+        &state.db,
+        auth.user_id,
+        MAX_SUBSCRIPTIONS_PER_USER,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct DeletePushSubscriptionRequest {
+    pub endpoint: String,
+}
+
+pub async fn delete_subscription(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<DeletePushSubscriptionRequest>,
+) -> Result<StatusCode, ApiError> {
+    let endpoint = outbound_url::parse_target(&body.endpoint)
+        .map(|url| url.to_string())
+        .unwrap_or(body.endpoint);
+    let removed = paracord_db::push::delete_user_subscription(&state.db, auth.user_id, &endpoint)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound)
+    }
+}
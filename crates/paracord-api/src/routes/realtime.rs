@@ -20,6 +20,10 @@ use crate::middleware::AuthUser;
 pub struct RealtimeEventsQuery {
     pub session_id: Option<String>,
     pub cursor: Option<u64>,
+    /// Skip `MESSAGE_CREATE` echoes of nonce-tagged sends made with this
+    /// stream's `session_id`.
+    #[serde(default)]
+    pub suppress_own_message_echo: bool,
 }
 
 #[derive(Deserialize)]
@@ -67,14 +71,12 @@ async fn build_ready_payload(state: &AppState, user_id: i64, session_id: &str) -
         })
     };
 
-    let guild_rows = paracord_db::guilds::get_user_guilds(&state.db, user_id)
+    let ready_guilds = paracord_core::guild::build_ready_payload(&state.db, user_id)
         .await
         .unwrap_or_default();
-    let mut guilds_json = Vec::with_capacity(guild_rows.len());
-    for guild in guild_rows {
-        let member_count = paracord_db::members::get_member_count(&state.db, guild.id)
-            .await
-            .unwrap_or(0);
+    let mut guilds_json = Vec::with_capacity(ready_guilds.len());
+    for ready in ready_guilds {
+        let guild = &ready.guild;
         let voice_states = paracord_db::voice_states::get_guild_voice_states(&state.db, guild.id)
             .await
             .unwrap_or_default();
@@ -93,23 +95,25 @@ async fn build_ready_payload(state: &AppState, user_id: i64, session_id: &str) -
                     "suppress": vs.suppress,
                     "mute": false,
                     "deaf": false,
+                    "priority_speaker": vs.priority_speaker,
                     "username": &vs.username,
                     "avatar_hash": &vs.avatar_hash,
                 })
             })
             .collect();
+        let voice_recordings: Vec<Value> = state
+            .voice
+            .get_guild_recordings(guild.id)
+            .await
+            .iter()
+            .map(|r| r.to_json())
+            .collect();
 
-        guilds_json.push(json!({
-            "id": guild.id.to_string(),
-            "name": guild.name,
-            "owner_id": guild.owner_id.to_string(),
-            "icon_hash": guild.icon_hash,
-            "member_count": member_count,
-            "channels": [],
-            "voice_states": voice_states_json,
-            "presences": [],
-            "lazy": true,
-        }));
+        let mut guild_json = ready.to_json();
+        guild_json["voice_states"] = json!(voice_states_json);
+        guild_json["voice_recordings"] = json!(voice_recordings);
+        guild_json["presences"] = json!([]);
+        guilds_json.push(guild_json);
     }
 
     json!({
@@ -130,6 +134,7 @@ struct RealtimeStreamState {
     session_id: String,
     user_id: i64,
     sequence: u64,
+    suppress_own_message_echo: bool,
     ready_payload: Option<String>,
     receiver: tokio::sync::broadcast::Receiver<paracord_core::events::ServerEvent>,
 }
@@ -192,6 +197,7 @@ pub async fn stream_events(
         session_id,
         user_id: auth.user_id,
         sequence: start_sequence,
+        suppress_own_message_echo: query.suppress_own_message_echo,
         ready_payload: Some(ready_payload),
         receiver,
     };
@@ -205,6 +211,9 @@ pub async fn stream_events(
         loop {
             match st.receiver.recv().await {
                 Ok(event) => {
+                    if st.suppress_own_message_echo && event.is_own_message_echo(&st.session_id) {
+                        continue;
+                    }
                     if event.event_type == "GUILD_MEMBER_ADD" {
                         if let Some(uid) = event.payload.get("user_id").and_then(|v| v.as_str()) {
                             if uid == st.user_id.to_string() {
@@ -418,6 +427,7 @@ pub async fn post_command(
                     Some(guild_id),
                     channel_id,
                     &session_id,
+                    perms.contains(Permissions::PRIORITY_SPEAKER),
                 )
                 .await;
                 state
@@ -450,6 +460,7 @@ pub async fn post_command(
                         "suppress": false,
                         "mute": false,
                         "deaf": false,
+                        "priority_speaker": perms.contains(Permissions::PRIORITY_SPEAKER),
                         "username": user.as_ref().map(|u| u.username.as_str()),
                         "avatar_hash": user.as_ref().and_then(|u| u.avatar_hash.as_deref()),
                     }),
@@ -554,15 +565,24 @@ pub async fn post_command(
                 return Err(ApiError::Forbidden);
             }
 
+            paracord_core::typing::typing_tracker().start(
+                auth.user_id,
+                channel_id,
+                guild_id,
+                std::time::Instant::now(),
+            );
             let typing_payload = json!({
                 "channel_id": channel_id.to_string(),
                 "user_id": auth.user_id.to_string(),
                 "timestamp": Utc::now().timestamp(),
             });
             if guild_id.is_none() {
-                let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, channel_id)
-                    .await
-                    .unwrap_or_default();
+                let recipient_ids = paracord_core::channel::dm_event_recipients(
+                    &state.db,
+                    channel_id,
+                    auth.user_id,
+                )
+                .await;
                 state
                     .event_bus
                     .dispatch_to_users("TYPING_START", typing_payload, recipient_ids);
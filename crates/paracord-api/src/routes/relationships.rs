@@ -89,6 +89,14 @@ pub async fn add_friend(
         return Ok(StatusCode::NO_CONTENT);
     }
 
+    // No friend requests across a block, whichever side set it.
+    let blocked = paracord_db::relationships::is_blocked(&state.db, auth.user_id, target_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if blocked {
+        return Err(ApiError::Forbidden);
+    }
+
     // Check if the target already sent us a pending request
     let incoming = paracord_db::relationships::get_relationship(&state.db, target_id, auth.user_id)
         .await
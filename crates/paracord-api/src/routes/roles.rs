@@ -1,16 +1,23 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Multipart, Path, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use paracord_core::AppState;
+use paracord_core::{AppConfig, AppState};
+use paracord_models::permissions::Permissions;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 
 use crate::error::ApiError;
 use crate::middleware::AuthUser;
 use crate::routes::audit;
 
+const MAX_ROLE_ICON_IMAGE_SIZE: usize = 256 * 1024; // 256 KB
+const MAX_ROLE_ICON_EMOJI_CHARS: usize = 8;
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
 fn validate_role_permission_assignment(
     guild_owner_id: i64,
     actor_user_id: i64,
@@ -32,7 +39,119 @@ fn validate_role_permission_assignment(
     Ok(())
 }
 
-fn role_to_json(r: &paracord_db::roles::RoleRow) -> Value {
+/// Uploaded icons are stored by their SHA-256 hex digest; anything else in
+/// `role_icon` is a unicode emoji.
+fn is_role_icon_image_hash(role_icon: &str) -> bool {
+    role_icon.len() == 64 && role_icon.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Accept a short run of non-ASCII, printable characters: a single emoji,
+/// possibly with skin-tone modifiers or ZWJ joins.
+fn validate_role_icon_emoji(emoji: &str) -> Result<(), ApiError> {
+    let count = emoji.chars().count();
+    if count == 0
+        || count > MAX_ROLE_ICON_EMOJI_CHARS
+        || emoji
+            .chars()
+            .any(|c| c.is_ascii() || c.is_whitespace() || c.is_control())
+    {
+        return Err(ApiError::BadRequest(
+            "role_icon must be a single unicode emoji".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn role_icon_image_path(state: &AppState, hash: &str) -> std::path::PathBuf {
+    std::path::Path::new(&state.config.storage_path)
+        .join("role_icons")
+        .join(hash)
+}
+
+/// The icon shown next to a member: that of their highest role with one.
+pub(crate) fn member_role_icon_json(
+    config: &AppConfig,
+    roles: &[paracord_db::roles::RoleRow],
+) -> Value {
+    roles
+        .iter()
+        .filter(|r| r.role_icon.is_some())
+        .max_by_key(|r| r.position)
+        .map(|r| {
+            json!({
+                "role_id": r.id.to_string(),
+                "role_icon": r.role_icon,
+                "role_icon_url": role_icon_url(config, r),
+            })
+        })
+        .unwrap_or(Value::Null)
+}
+
+fn role_icon_url(config: &AppConfig, r: &paracord_db::roles::RoleRow) -> Option<String> {
+    // The hash in the query string keeps the immutable cache entry honest
+    // when the icon is replaced.
+    r.role_icon
+        .as_deref()
+        .filter(|icon| is_role_icon_image_hash(icon))
+        .map(|hash| {
+            config.media_url(&format!(
+                "/api/v1/guilds/{}/roles/{}/icon?v={}",
+                r.guild_id(),
+                r.id,
+                &hash[..16]
+            ))
+        })
+}
+
+/// Role icons need the guild's ROLE_ICONS feature and MANAGE_ROLES on a role
+/// below the actor's highest role (owners are exempt from the hierarchy).
+async fn ensure_role_icon_access(
+    state: &AppState,
+    guild_id: i64,
+    role_id: i64,
+    user_id: i64,
+) -> Result<(), ApiError> {
+    let guild = paracord_db::guilds::get_guild(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let user_roles = paracord_db::roles::get_member_roles(&state.db, user_id, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let perms = paracord_core::permissions::compute_permissions_from_roles(
+        &user_roles,
+        guild.owner_id,
+        user_id,
+    );
+    paracord_core::permissions::require_permission(perms, Permissions::MANAGE_ROLES)?;
+    ensure_role_icons_enabled(&guild)?;
+
+    let target_role = paracord_db::roles::get_role(&state.db, role_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if target_role.guild_id() != guild_id {
+        return Err(ApiError::NotFound);
+    }
+    if user_id != guild.owner_id {
+        let actor_top_role_pos = user_roles.iter().map(|r| r.position).max().unwrap_or(0);
+        if target_role.position >= actor_top_role_pos {
+            return Err(ApiError::Forbidden);
+        }
+    }
+    Ok(())
+}
+
+fn ensure_role_icons_enabled(guild: &paracord_db::guilds::GuildRow) -> Result<(), ApiError> {
+    if guild.features & paracord_models::guild::GUILD_FEATURE_ROLE_ICONS == 0 {
+        return Err(ApiError::BadRequest(
+            "Role icons are not enabled for this guild".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn role_to_json(config: &AppConfig, r: &paracord_db::roles::RoleRow) -> Value {
     json!({
         "id": r.id.to_string(),
         "guild_id": r.guild_id().to_string(),
@@ -43,6 +162,8 @@ fn role_to_json(r: &paracord_db::roles::RoleRow) -> Value {
         "permissions": r.permissions,
         "managed": r.managed,
         "mentionable": r.mentionable,
+        "role_icon": r.role_icon,
+        "role_icon_url": role_icon_url(config, r),
         "created_at": r.created_at.to_rfc3339(),
     })
 }
@@ -58,11 +179,15 @@ pub async fn list_roles(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let result: Vec<Value> = roles.iter().map(role_to_json).collect();
+    let result: Vec<Value> = roles
+        .iter()
+        .map(|r| role_to_json(&state.config, r))
+        .collect();
     Ok(Json(json!(result)))
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct CreateRoleRequest {
     pub name: String,
     #[serde(default)]
@@ -115,7 +240,7 @@ pub async fn create_role(
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    let role_json = role_to_json(&role);
+    let role_json = role_to_json(&state.config, &role);
 
     state.event_bus.dispatch(
         "GUILD_ROLE_CREATE",
@@ -137,12 +262,15 @@ pub async fn create_role(
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct UpdateRoleRequest {
     pub name: Option<String>,
     pub permissions: Option<i64>,
     pub color: Option<i32>,
     pub hoist: Option<bool>,
     pub mentionable: Option<bool>,
+    /// Unicode emoji icon; an empty string clears the icon.
+    pub role_icon: Option<String>,
 }
 
 pub async fn update_role(
@@ -189,8 +317,13 @@ pub async fn update_role(
             return Err(ApiError::Forbidden);
         }
     }
+    let role_icon = body.role_icon.as_deref().map(str::trim);
+    if let Some(icon) = role_icon.filter(|icon| !icon.is_empty()) {
+        ensure_role_icons_enabled(&guild)?;
+        validate_role_icon_emoji(icon)?;
+    }
 
-    let updated = paracord_db::roles::update_role(
+    let mut updated = paracord_db::roles::update_role(
         &state.db,
         role_id,
         body.name.as_deref(),
@@ -201,11 +334,20 @@ pub async fn update_role(
     )
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if let Some(icon) = role_icon {
+        updated = paracord_db::roles::set_role_icon(
+            &state.db,
+            role_id,
+            Some(icon).filter(|icon| !icon.is_empty()),
+        )
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
 
     // Invalidate permission cache when role permissions change
     paracord_core::permissions::invalidate_all(&state.permission_cache).await;
 
-    let role_json = role_to_json(&updated);
+    let role_json = role_to_json(&state.config, &updated);
 
     state.event_bus.dispatch(
         "GUILD_ROLE_UPDATE",
@@ -299,3 +441,126 @@ pub async fn delete_role(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Upload a PNG or GIF image as the role's icon.
+pub async fn upload_role_icon(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((guild_id, role_id)): Path<(i64, i64)>,
+    mut multipart: Multipart,
+) -> Result<Json<Value>, ApiError> {
+    ensure_role_icon_access(&state, guild_id, role_id, auth.user_id).await?;
+
+    let mut image: Option<(Option<String>, Vec<u8>)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?
+    {
+        if matches!(field.name(), Some("image" | "file")) {
+            let content_type = field.content_type().map(str::to_string);
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            image = Some((content_type, data.to_vec()));
+        }
+    }
+    let (content_type, data) =
+        image.ok_or_else(|| ApiError::BadRequest("Missing role icon image".into()))?;
+    if data.is_empty() {
+        return Err(ApiError::BadRequest("Empty role icon image".into()));
+    }
+    if data.len() > MAX_ROLE_ICON_IMAGE_SIZE {
+        return Err(ApiError::BadRequest(
+            "Role icon image must be under 256 KB".into(),
+        ));
+    }
+    let is_valid_signature = match content_type.as_deref() {
+        Some("image/png") => data.starts_with(PNG_SIGNATURE),
+        Some("image/gif") => data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a"),
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Only PNG and GIF role icons are supported".into(),
+            ))
+        }
+    };
+    if !is_valid_signature {
+        return Err(ApiError::BadRequest(
+            "Role icon contents do not match the declared image type".into(),
+        ));
+    }
+
+    let hash = format!("{:x}", Sha256::digest(&data));
+    let path = role_icon_image_path(&state, &hash);
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
+    tokio::fs::write(&path, &data)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let updated = paracord_db::roles::set_role_icon(&state.db, role_id, Some(&hash))
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let role_json = role_to_json(&state.config, &updated);
+
+    state.event_bus.dispatch(
+        "GUILD_ROLE_UPDATE",
+        json!({"guild_id": guild_id.to_string(), "role": &role_json}),
+        Some(guild_id),
+    );
+    audit::log_action(
+        &state,
+        guild_id,
+        auth.user_id,
+        audit::ACTION_ROLE_UPDATE,
+        Some(role_id),
+        None,
+        Some(json!({ "role_icon": hash })),
+    )
+    .await;
+
+    Ok(Json(role_json))
+}
+
+pub async fn get_role_icon(
+    State(state): State<AppState>,
+    Path((guild_id, role_id)): Path<(i64, i64)>,
+) -> Result<Response, ApiError> {
+    let role = paracord_db::roles::get_role(&state.db, role_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if role.guild_id() != guild_id {
+        return Err(ApiError::NotFound);
+    }
+    let hash = role
+        .role_icon
+        .as_deref()
+        .filter(|icon| is_role_icon_image_hash(icon))
+        .ok_or(ApiError::NotFound)?;
+
+    let data = tokio::fs::read(role_icon_image_path(&state, hash))
+        .await
+        .map_err(|_| ApiError::NotFound)?;
+    let content_type = if data.starts_with(PNG_SIGNATURE) {
+        "image/png"
+    } else {
+        "image/gif"
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=31536000, immutable"),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
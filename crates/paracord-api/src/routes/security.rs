@@ -1,6 +1,7 @@
+use crate::error::ApiError;
 use axum::http::{header, HeaderMap};
 use paracord_core::AppState;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 fn header_opt(headers: &HeaderMap, name: &str) -> Option<String> {
     headers
@@ -25,19 +26,7 @@ fn request_metadata(
         .map(str::trim)
         .filter(|v| !v.is_empty())
         .map(str::to_string);
-    let trust_proxy = std::env::var("PARACORD_TRUST_PROXY")
-        .ok()
-        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
-        .unwrap_or(false);
-    let trusted_proxy_configured = std::env::var("PARACORD_TRUSTED_PROXY_IPS")
-        .ok()
-        .map(|raw| !raw.trim().is_empty())
-        .unwrap_or(false);
-    let ip_address = if trust_proxy && trusted_proxy_configured {
-        header_opt(headers, "x-forwarded-for")
-    } else {
-        None
-    };
+    let ip_address = crate::client_ip::client_ip_from_headers(headers);
     (device_id, user_agent, ip_address)
 }
 
@@ -71,3 +60,76 @@ pub async fn log_security_event(
         tracing::warn!("failed to write security event '{}': {}", action, err);
     }
 }
+
+/// Reject `name` with 400 when it matches the admin's blocked name patterns,
+/// recording the attempt. `kind` is `"username"`, `"display_name"`,
+/// `"nickname"` or `"guild_name"`.
+pub async fn ensure_name_allowed(
+    state: &AppState,
+    kind: &str,
+    name: &str,
+    actor_user_id: Option<i64>,
+    headers: Option<&HeaderMap>,
+) -> Result<(), ApiError> {
+    let pattern = {
+        let settings = state.runtime.read().await;
+        match settings.name_filter.blocked_by(name) {
+            Some(pattern) => pattern.to_string(),
+            None => return Ok(()),
+        }
+    };
+    tracing::warn!(kind, name, pattern = %pattern, "blocked name rejected");
+    log_security_event(
+        state,
+        "name_filter.blocked",
+        actor_user_id,
+        None,
+        None,
+        headers,
+        Some(json!({ "kind": kind, "name": name, "pattern": pattern })),
+    )
+    .await;
+    let label = match kind {
+        "guild_name" => "Guild name",
+        "display_name" => "Display name",
+        "nickname" => "Nickname",
+        _ => "Username",
+    };
+    Err(ApiError::BadRequest(format!("{label} is not allowed")))
+}
+
+/// Reject the request while the user is under an automatic abuse timeout.
+pub fn ensure_not_usage_timed_out(state: &AppState, user_id: i64) -> Result<(), ApiError> {
+    if state.usage.timed_out_until(user_id).is_some() {
+        return Err(ApiError::RateLimited);
+    }
+    Ok(())
+}
+
+/// Record that `user_id` crossed an abuse threshold while performing `activity`.
+pub async fn log_usage_threshold_exceeded(
+    state: &AppState,
+    user_id: i64,
+    activity: &str,
+    headers: Option<&HeaderMap>,
+) {
+    let thresholds = state.usage.thresholds();
+    let timed_out_until = state.usage.timed_out_until(user_id);
+    tracing::warn!(user_id, activity, "user exceeded abuse threshold");
+    log_security_event(
+        state,
+        "abuse.threshold_exceeded",
+        None,
+        Some(user_id),
+        None,
+        headers,
+        Some(json!({
+            "activity": activity,
+            "messages_per_minute": thresholds.messages_per_minute,
+            "uploads_per_minute": thresholds.uploads_per_minute,
+            "upload_bytes_per_hour": thresholds.upload_bytes_per_hour,
+            "timed_out_until": timed_out_until,
+        })),
+    )
+    .await;
+}
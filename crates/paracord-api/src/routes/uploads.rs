@@ -0,0 +1,622 @@
+//! Resumable uploads speaking the tus 1.0.0 protocol (core, creation and
+//! expiration extensions).
+//!
+//! `POST /api/v1/uploads` declares the file size and target channel,
+//! `PATCH /api/v1/uploads/{id}` appends bytes at the current offset and
+//! `HEAD /api/v1/uploads/{id}` reports how far the upload got so a client can
+//! resume after a dropped connection. Bytes are written to a temp file under
+//! `{storage_path}/tus/`; once the declared length has arrived the file goes
+//! through the same scan/encrypt/store path as a multipart upload and becomes
+//! a pending attachment, whose id is returned in `X-Attachment-Id`.
+//!
+//! There is no `OPTIONS` discovery endpoint because the CORS layer answers
+//! every `OPTIONS` request; the `Tus-*` capability headers are sent on `HEAD`
+//! responses instead.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use futures_util::StreamExt;
+use paracord_core::AppState;
+use serde_json::json;
+use std::sync::OnceLock;
+use std::time::Instant;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+use crate::routes::files;
+
+const TUS_VERSION: &str = "1.0.0";
+const TUS_EXTENSIONS: &str = "creation,expiration";
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+/// How long an unfinished upload may sit idle before it is discarded. Every
+/// append starts the clock again.
+const PENDING_UPLOAD_TTL_HOURS: i64 = 24;
+const PENDING_UPLOAD_CLEANUP_BATCH: i64 = 100;
+/// How long one PATCH may hold its upload. A body still streaming when the
+/// claim lapses is cut off there and the client resumes from the offset.
+const APPEND_LEASE_MINUTES: i64 = 15;
+/// Temp files with no upload record are removed once they are this old.
+const ORPHANED_TEMP_FILE_AGE: std::time::Duration = std::time::Duration::from_secs(3600);
+/// Minimum gap between `UPLOAD_PROGRESS` events for one upload. The event
+/// for the last byte is always sent.
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+const TUS_RESUMABLE: HeaderName = HeaderName::from_static("tus-resumable");
+const TUS_VERSION_HEADER: HeaderName = HeaderName::from_static("tus-version");
+const TUS_MAX_SIZE: HeaderName = HeaderName::from_static("tus-max-size");
+const TUS_EXTENSION: HeaderName = HeaderName::from_static("tus-extension");
+const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+const UPLOAD_METADATA: HeaderName = HeaderName::from_static("upload-metadata");
+const UPLOAD_EXPIRES: HeaderName = HeaderName::from_static("upload-expires");
+const ATTACHMENT_ID: HeaderName = HeaderName::from_static("x-attachment-id");
+
+fn db_err(e: paracord_db::DbError) -> ApiError {
+    ApiError::Internal(anyhow::anyhow!(e.to_string()))
+}
+
+fn temp_dir(state: &AppState) -> std::path::PathBuf {
+    std::path::Path::new(&state.config.storage_path).join("tus")
+}
+
+fn temp_path(state: &AppState, upload_id: i64) -> std::path::PathBuf {
+    temp_dir(state).join(format!("{upload_id}.part"))
+}
+
+/// When each upload last reported progress, for throttling.
+static LAST_PROGRESS: OnceLock<DashMap<i64, Instant>> = OnceLock::new();
+
+/// Send `UPLOAD_PROGRESS` to the uploader's own sessions unless one went out
+/// for this upload less than [`PROGRESS_INTERVAL`] ago.
+fn report_progress(state: &AppState, user_id: i64, upload_id: i64, received: u64, total: u64) {
+    let last = LAST_PROGRESS.get_or_init(DashMap::new);
+    let now = Instant::now();
+    if received < total {
+        let mut due = true;
+        last.entry(upload_id)
+            .and_modify(|at| {
+                due = now.duration_since(*at) >= PROGRESS_INTERVAL;
+                if due {
+                    *at = now;
+                }
+            })
+            .or_insert(now);
+        if !due {
+            return;
+        }
+    } else {
+        last.remove(&upload_id);
+    }
+    state.event_bus.dispatch_to_users(
+        paracord_models::gateway::EVENT_UPLOAD_PROGRESS,
+        json!({
+            "upload_id": upload_id.to_string(),
+            "bytes_received": received,
+            "total": total,
+        }),
+        vec![user_id],
+    );
+}
+
+/// `Upload-Expires` uses the RFC 7231 HTTP date format.
+fn http_date(value: DateTime<Utc>) -> String {
+    value.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn header_value(value: impl ToString) -> HeaderValue {
+    HeaderValue::from_str(&value.to_string()).unwrap_or(HeaderValue::from_static(""))
+}
+
+fn tus_response(status: StatusCode) -> Response {
+    let mut response = status.into_response();
+    response
+        .headers_mut()
+        .insert(TUS_RESUMABLE, HeaderValue::from_static(TUS_VERSION));
+    response
+}
+
+/// Requests must speak the one protocol version we implement; anything else
+/// gets a 412 advertising the supported version.
+fn version_mismatch(headers: &HeaderMap) -> Option<Response> {
+    let version = headers.get(TUS_RESUMABLE).and_then(|v| v.to_str().ok());
+    if version == Some(TUS_VERSION) {
+        return None;
+    }
+    let mut response = tus_response(StatusCode::PRECONDITION_FAILED);
+    response
+        .headers_mut()
+        .insert(TUS_VERSION_HEADER, HeaderValue::from_static(TUS_VERSION));
+    Some(response)
+}
+
+fn parse_u64_header(headers: &HeaderMap, name: &HeaderName) -> Result<u64, ApiError> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .ok_or_else(|| ApiError::BadRequest(format!("Missing or invalid {name} header")))
+}
+
+/// Decode `Upload-Metadata`: comma-separated `key base64(value)` pairs.
+fn parse_metadata(raw: &str) -> Result<Vec<(String, String)>, ApiError> {
+    let mut pairs = Vec::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (key, encoded) = entry.split_once(' ').unwrap_or((entry, ""));
+        let decoded = STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!("Invalid Upload-Metadata value for {key}"))
+            })?;
+        pairs.push((key.to_string(), decoded));
+    }
+    Ok(pairs)
+}
+
+fn metadata_value<'a>(metadata: &'a [(String, String)], keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|key| {
+        metadata
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    })
+}
+
+async fn cleanup_expired_pending_uploads(state: &AppState) -> usize {
+    let expired = match paracord_db::pending_uploads::get_expired_pending_uploads(
+        &state.db,
+        Utc::now(),
+        PENDING_UPLOAD_CLEANUP_BATCH,
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(err) => {
+            tracing::warn!("Failed loading expired pending uploads: {}", err);
+            return 0;
+        }
+    };
+
+    let mut removed = 0;
+    for upload in expired {
+        if let Err(err) =
+            paracord_db::pending_uploads::delete_pending_upload(&state.db, upload.id).await
+        {
+            tracing::warn!("Failed deleting expired upload {}: {}", upload.id, err);
+            continue;
+        }
+        let _ = tokio::fs::remove_file(temp_path(state, upload.id)).await;
+        if let Some(last) = LAST_PROGRESS.get() {
+            last.remove(&upload.id);
+        }
+        removed += 1;
+    }
+    removed
+}
+
+/// Discard expired uploads and their temp files, then sweep the temp
+/// directory for files no upload owns any more (left behind by a crash or a
+/// failed finish). Run periodically by the server. Returns how many uploads
+/// and stray files were removed.
+pub async fn collect_expired_uploads(state: &AppState) -> Result<usize, ApiError> {
+    let mut removed = 0;
+    loop {
+        let batch = cleanup_expired_pending_uploads(state).await;
+        removed += batch;
+        if batch < PENDING_UPLOAD_CLEANUP_BATCH as usize {
+            break;
+        }
+    }
+
+    let mut entries = match tokio::fs::read_dir(temp_dir(state)).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(removed),
+        Err(err) => return Err(ApiError::Internal(anyhow::anyhow!(err))),
+    };
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e)))?
+    {
+        let path = entry.path();
+        let old_enough = entry
+            .metadata()
+            .await
+            .ok()
+            .and_then(|meta| meta.modified().ok())
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= ORPHANED_TEMP_FILE_AGE);
+        if !old_enough {
+            continue;
+        }
+        let upload_id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<i64>().ok());
+        let owned = match upload_id {
+            Some(id) => {
+                // Only the `.part` file of an unfinished upload is live.
+                let live = path.extension().is_some_and(|ext| ext == "part");
+                live && paracord_db::pending_uploads::get_pending_upload(&state.db, id)
+                    .await
+                    .map_err(db_err)?
+                    .is_some_and(|upload| upload.attachment_id.is_none())
+            }
+            None => false,
+        };
+        if !owned && tokio::fs::remove_file(&path).await.is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Load an upload owned by `user_id`. Other users' and expired uploads are
+/// reported as missing.
+async fn load_own_upload(
+    state: &AppState,
+    upload_id: i64,
+    user_id: i64,
+) -> Result<paracord_db::pending_uploads::PendingUploadRow, ApiError> {
+    paracord_db::pending_uploads::get_pending_upload(&state.db, upload_id)
+        .await
+        .map_err(db_err)?
+        .filter(|upload| upload.uploader_id == user_id && upload.expires_at > Utc::now())
+        .ok_or(ApiError::NotFound)
+}
+
+/// `POST /api/v1/uploads` — declare a new upload.
+///
+/// `Upload-Metadata` must carry `filename` and `channel_id`; `filetype` (or
+/// `content_type`) is optional.
+pub async fn create_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    if let Some(response) = version_mismatch(&headers) {
+        return Ok(response);
+    }
+    cleanup_expired_pending_uploads(&state).await;
+    crate::routes::security::ensure_not_usage_timed_out(&state, auth.user_id)?;
+
+    let total_size = parse_u64_header(&headers, &UPLOAD_LENGTH)?;
+    if total_size == 0 {
+        return Err(ApiError::BadRequest("Empty file".into()));
+    }
+    if total_size > state.config.max_upload_size {
+        return Err(ApiError::PayloadTooLarge("File too large".into()));
+    }
+    let db_total = i64::try_from(total_size)
+        .map_err(|_| ApiError::PayloadTooLarge("File too large".into()))?;
+
+    let metadata = parse_metadata(
+        headers
+            .get(UPLOAD_METADATA)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or(""),
+    )?;
+    let channel_id = metadata_value(&metadata, &["channel_id"])
+        .and_then(|v| v.parse::<i64>().ok())
+        .ok_or_else(|| ApiError::BadRequest("Upload-Metadata must include channel_id".into()))?;
+    let filename = files::normalize_upload_filename(
+        metadata_value(&metadata, &["filename", "name"]).unwrap_or("upload"),
+        state.config.max_attachment_filename_length as usize,
+    )?;
+    let claimed_content_type = metadata_value(&metadata, &["filetype", "content_type"]);
+
+    files::validate_upload_permissions(&state, channel_id, auth.user_id).await?;
+
+    // Fail fast on guild size/type/quota rules rather than after the upload.
+    let resolved_ct = files::normalized_content_type(&filename, claimed_content_type);
+    files::check_guild_upload_policy(&state, channel_id, total_size, &resolved_ct).await?;
+
+    let upload_id = paracord_util::snowflake::generate(1);
+    let expires_at = Utc::now() + Duration::hours(PENDING_UPLOAD_TTL_HOURS);
+    let upload = paracord_db::pending_uploads::create_pending_upload(
+        &state.db,
+        upload_id,
+        auth.user_id,
+        channel_id,
+        &filename,
+        claimed_content_type,
+        db_total,
+        expires_at,
+        i64::from(state.config.max_pending_uploads_per_user),
+        Utc::now(),
+    )
+    .await
+    .map_err(db_err)?
+    .ok_or(ApiError::RateLimited)?;
+
+    let path = temp_path(&state, upload_id);
+    let created: std::io::Result<()> = async {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::File::create(&path).await?;
+        Ok(())
+    }
+    .await;
+    if let Err(err) = created {
+        let _ = paracord_db::pending_uploads::delete_pending_upload(&state.db, upload_id).await;
+        return Err(ApiError::Internal(anyhow::anyhow!(err)));
+    }
+
+    let mut response = tus_response(StatusCode::CREATED);
+    let headers = response.headers_mut();
+    headers.insert(
+        header::LOCATION,
+        header_value(format!("/api/v1/uploads/{}", upload.id)),
+    );
+    headers.insert(UPLOAD_EXPIRES, header_value(http_date(upload.expires_at)));
+    Ok(response)
+}
+
+/// `HEAD /api/v1/uploads/{id}` — report the current offset.
+pub async fn get_upload_offset(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(upload_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    if let Some(response) = version_mismatch(&headers) {
+        return Ok(response);
+    }
+    let upload = load_own_upload(&state, upload_id, auth.user_id).await?;
+
+    let mut response = tus_response(StatusCode::OK);
+    let headers = response.headers_mut();
+    headers.insert(UPLOAD_OFFSET, header_value(upload.upload_offset));
+    headers.insert(UPLOAD_LENGTH, header_value(upload.total_size));
+    headers.insert(UPLOAD_EXPIRES, header_value(http_date(upload.expires_at)));
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    headers.insert(TUS_VERSION_HEADER, HeaderValue::from_static(TUS_VERSION));
+    headers.insert(TUS_EXTENSION, HeaderValue::from_static(TUS_EXTENSIONS));
+    headers.insert(TUS_MAX_SIZE, header_value(state.config.max_upload_size));
+    if let Some(attachment_id) = upload.attachment_id {
+        headers.insert(ATTACHMENT_ID, header_value(attachment_id));
+    }
+    Ok(response)
+}
+
+/// `PATCH /api/v1/uploads/{id}` — append bytes at `Upload-Offset`.
+///
+/// The body is streamed to disk and rejected with 413 as soon as it would
+/// run past the declared length, so an oversized upload never has to be
+/// received in full.
+pub async fn append_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(upload_id): Path<i64>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ApiError> {
+    if let Some(response) = version_mismatch(&headers) {
+        return Ok(response);
+    }
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    if content_type != Some(OFFSET_OCTET_STREAM) {
+        return Ok(tus_response(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+    }
+    let offset = parse_u64_header(&headers, &UPLOAD_OFFSET)?;
+
+    let upload = load_own_upload(&state, upload_id, auth.user_id).await?;
+    if upload.attachment_id.is_some() || i64::try_from(offset).ok() != Some(upload.upload_offset) {
+        return Err(ApiError::Conflict(
+            "Upload-Offset does not match the current offset".into(),
+        ));
+    }
+    let total_size = upload.total_size as u64;
+    let remaining = total_size - offset;
+    if let Some(declared) = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+    {
+        if declared > remaining {
+            return Err(ApiError::PayloadTooLarge(
+                "Chunk exceeds the declared Upload-Length".into(),
+            ));
+        }
+    }
+
+    // Hold the upload while the body streams in, so a second PATCH at the
+    // same offset cannot write the temp file at the same time.
+    let lock_id = paracord_util::snowflake::generate(1);
+    let lease_until = Utc::now() + Duration::minutes(APPEND_LEASE_MINUTES);
+    if !paracord_db::pending_uploads::claim_pending_upload(
+        &state.db,
+        upload.id,
+        upload.upload_offset,
+        lock_id,
+        Utc::now(),
+        lease_until,
+    )
+    .await
+    .map_err(db_err)?
+    {
+        return Err(ApiError::Conflict(
+            "Upload-Offset does not match the current offset".into(),
+        ));
+    }
+    let received = receive_chunk(&state, &upload, offset, lease_until, body).await;
+    let (written, interrupted) = match received {
+        Ok(received) => received,
+        Err(err) => {
+            let _ =
+                paracord_db::pending_uploads::release_pending_upload(&state.db, upload.id, lock_id)
+                    .await;
+            return Err(err);
+        }
+    };
+
+    let new_offset = offset + written;
+    let expires_at = Utc::now() + Duration::hours(PENDING_UPLOAD_TTL_HOURS);
+    if !paracord_db::pending_uploads::advance_pending_upload_offset(
+        &state.db,
+        upload.id,
+        lock_id,
+        new_offset as i64,
+        expires_at,
+    )
+    .await
+    .map_err(db_err)?
+    {
+        return Err(ApiError::Conflict(
+            "Upload-Offset does not match the current offset".into(),
+        ));
+    }
+    if written > 0 {
+        report_progress(&state, auth.user_id, upload.id, new_offset, total_size);
+    }
+    if let Some(err) = interrupted {
+        return Err(ApiError::BadRequest(err));
+    }
+
+    let mut attachment_id = None;
+    if new_offset == total_size {
+        attachment_id = Some(finish_upload(&state, &upload, auth.user_id).await?);
+    }
+
+    let mut response = tus_response(StatusCode::NO_CONTENT);
+    let headers = response.headers_mut();
+    headers.insert(UPLOAD_OFFSET, header_value(new_offset));
+    headers.insert(UPLOAD_EXPIRES, header_value(http_date(expires_at)));
+    if let Some(attachment_id) = attachment_id {
+        headers.insert(ATTACHMENT_ID, header_value(attachment_id));
+    }
+    Ok(response)
+}
+
+/// Stream a PATCH body into the temp file at `offset`, reporting progress
+/// as it goes. Returns how many bytes were written and, if the body was cut
+/// short or ran into the claim's `lease_until`, why; what arrived is kept
+/// either way so the client can resume.
+async fn receive_chunk(
+    state: &AppState,
+    upload: &paracord_db::pending_uploads::PendingUploadRow,
+    offset: u64,
+    lease_until: DateTime<Utc>,
+    body: Body,
+) -> Result<(u64, Option<String>), ApiError> {
+    let total_size = upload.total_size as u64;
+    let remaining = total_size - offset;
+    let path = temp_path(state, upload.id);
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e)))?;
+    // Drop anything an earlier, interrupted PATCH wrote past the recorded
+    // offset before appending.
+    file.set_len(offset)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e)))?;
+    file.seek(std::io::SeekFrom::Start(offset))
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e)))?;
+
+    let mut written: u64 = 0;
+    let mut interrupted = None;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                // Keep what arrived so the client can resume from here.
+                interrupted = Some(err.to_string());
+                break;
+            }
+        };
+        if Utc::now() >= lease_until {
+            interrupted = Some("Upload claim expired; resume from Upload-Offset".into());
+            break;
+        }
+        written += chunk.len() as u64;
+        if written > remaining {
+            return Err(ApiError::PayloadTooLarge(
+                "Chunk exceeds the declared Upload-Length".into(),
+            ));
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e)))?;
+        if offset + written < total_size {
+            report_progress(
+                state,
+                upload.uploader_id,
+                upload.id,
+                offset + written,
+                total_size,
+            );
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e)))?;
+    Ok((written, interrupted))
+}
+
+/// Turn a fully received upload into a pending attachment. A file that fails
+/// the scan or the guild policy is discarded along with its upload record.
+async fn finish_upload(
+    state: &AppState,
+    upload: &paracord_db::pending_uploads::PendingUploadRow,
+    user_id: i64,
+) -> Result<i64, ApiError> {
+    let path = temp_path(state, upload.id);
+
+    // Membership or permissions may have changed while the upload ran.
+    let result = match files::validate_upload_permissions(state, upload.channel_id, user_id).await {
+        Ok(()) => {
+            files::process_uploaded_path(
+                state,
+                &path,
+                &upload.filename,
+                upload.content_type.as_deref(),
+                upload.channel_id,
+                user_id,
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    };
+    let _ = tokio::fs::remove_file(&path).await;
+
+    let attachment = match result {
+        Ok(attachment) => attachment,
+        Err(err) => {
+            let _ = paracord_db::pending_uploads::delete_pending_upload(&state.db, upload.id).await;
+            return Err(err);
+        }
+    };
+    let attachment_id = attachment["id"]
+        .as_str()
+        .and_then(|id| id.parse::<i64>().ok())
+        .ok_or_else(|| ApiError::Internal(anyhow::anyhow!("attachment id missing")))?;
+    paracord_db::pending_uploads::set_pending_upload_attachment(
+        &state.db,
+        upload.id,
+        attachment_id,
+    )
+    .await
+    .map_err(db_err)?;
+
+    if state.usage.record_upload(user_id, upload.total_size as u64) {
+        crate::routes::security::log_usage_threshold_exceeded(state, user_id, "upload", None).await;
+    }
+    Ok(attachment_id)
+}
@@ -4,10 +4,12 @@ use axum::{
     Json,
 };
 use paracord_core::AppState;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
+use crate::custom_css;
 use crate::error::ApiError;
+use crate::markup::profile_markup_policy;
 use crate::middleware::AuthUser;
 use crate::routes::security;
 
@@ -16,15 +18,6 @@ const MAX_BIO_LEN: usize = 512;
 const MAX_CUSTOM_STATUS_LEN: usize = 128;
 const MAX_CUSTOM_CSS_LEN: usize = 10 * 1024;
 
-fn contains_dangerous_markup(value: &str) -> bool {
-    let lower = value.to_ascii_lowercase();
-    lower.contains("<script")
-        || lower.contains("javascript:")
-        || lower.contains("onerror=")
-        || lower.contains("onload=")
-        || lower.contains("<iframe")
-}
-
 fn sanitize_custom_css(value: &str) -> Result<Option<String>, ApiError> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -33,46 +26,81 @@ fn sanitize_custom_css(value: &str) -> Result<Option<String>, ApiError> {
     if trimmed.len() > MAX_CUSTOM_CSS_LEN {
         return Err(ApiError::BadRequest("custom_css exceeds 10KB".into()));
     }
-    let lower = trimmed.to_ascii_lowercase();
-    if lower.contains("@import")
-        || lower.contains("@font-face")
-        || lower.contains("url(")
-        || lower.contains("expression(")
-        || lower.contains("javascript:")
-    {
-        return Err(ApiError::BadRequest(
-            "custom_css contains disallowed directives".into(),
-        ));
+    Ok(custom_css::sanitize_custom_css(
+        trimmed,
+        custom_css::custom_css_policy(),
+    ))
+}
+
+/// The authenticated user's own account, as returned by `/users/@me`.
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
+pub struct CurrentUserResponse {
+    pub id: String,
+    pub username: String,
+    pub discriminator: i16,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub avatar_hash: Option<String>,
+    pub banner_hash: Option<String>,
+    pub bio: Option<String>,
+    pub flags: i32,
+    pub bot: bool,
+    pub system: bool,
+    pub created_at: String,
+}
+
+impl CurrentUserResponse {
+    fn from_row(user: paracord_db::users::UserRow) -> Self {
+        Self {
+            id: user.id.to_string(),
+            bot: paracord_core::is_bot(user.flags),
+            system: false,
+            created_at: user.created_at.to_rfc3339(),
+            username: user.username,
+            discriminator: user.discriminator,
+            email: user.email,
+            display_name: user.display_name,
+            avatar_hash: user.avatar_hash,
+            banner_hash: user.banner_hash,
+            bio: user.bio,
+            flags: user.flags,
+        }
     }
-    Ok(Some(trimmed.to_string()))
 }
 
 pub async fn get_me(
     State(state): State<AppState>,
     auth: AuthUser,
+) -> Result<Json<CurrentUserResponse>, ApiError> {
+    let user = paracord_db::users::get_user_by_id(&state.db, auth.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(CurrentUserResponse::from_row(user)))
+}
+
+/// The current user plus every guild with its visible channels, roles, and
+/// the user's membership: the same data as the gateway READY, over REST.
+pub async fn get_bootstrap(
+    State(state): State<AppState>,
+    auth: AuthUser,
 ) -> Result<Json<Value>, ApiError> {
     let user = paracord_db::users::get_user_by_id(&state.db, auth.user_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
         .ok_or(ApiError::NotFound)?;
+    let guilds = paracord_core::guild::build_ready_payload(&state.db, auth.user_id).await?;
 
     Ok(Json(json!({
-        "id": user.id.to_string(),
-        "username": user.username,
-        "discriminator": user.discriminator,
-        "email": user.email,
-        "display_name": user.display_name,
-        "avatar_hash": user.avatar_hash,
-        "banner_hash": user.banner_hash,
-        "bio": user.bio,
-        "flags": user.flags,
-        "bot": paracord_core::is_bot(user.flags),
-        "system": false,
-        "created_at": user.created_at.to_rfc3339(),
+        "user": CurrentUserResponse::from_row(user),
+        "guilds": guilds.iter().map(|g| g.to_json()).collect::<Vec<_>>(),
     })))
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct UpdateMeRequest {
     pub display_name: Option<String>,
     pub bio: Option<String>,
@@ -83,51 +111,44 @@ pub async fn update_me(
     State(state): State<AppState>,
     auth: AuthUser,
     Json(body): Json<UpdateMeRequest>,
-) -> Result<Json<Value>, ApiError> {
-    if let Some(display_name) = body.display_name.as_deref() {
-        let trimmed = display_name.trim();
-        if trimmed.len() > MAX_DISPLAY_NAME_LEN {
-            return Err(ApiError::BadRequest("display_name is too long".into()));
+) -> Result<Json<CurrentUserResponse>, ApiError> {
+    let display_name = match body.display_name.as_deref() {
+        Some(display_name) => {
+            if display_name.trim().len() > MAX_DISPLAY_NAME_LEN {
+                return Err(ApiError::BadRequest("display_name is too long".into()));
+            }
+            security::ensure_name_allowed(
+                &state,
+                "display_name",
+                display_name,
+                Some(auth.user_id),
+                None,
+            )
+            .await?;
+            Some(profile_markup_policy().apply("display_name", display_name)?)
         }
-        if contains_dangerous_markup(trimmed) {
-            return Err(ApiError::BadRequest(
-                "display_name contains unsafe markup".into(),
-            ));
-        }
-    }
-    if let Some(bio) = body.bio.as_deref() {
-        let trimmed = bio.trim();
-        if trimmed.len() > MAX_BIO_LEN {
-            return Err(ApiError::BadRequest("bio is too long".into()));
-        }
-        if contains_dangerous_markup(trimmed) {
-            return Err(ApiError::BadRequest("bio contains unsafe markup".into()));
+        None => None,
+    };
+    let bio = match body.bio.as_deref() {
+        Some(bio) => {
+            if bio.trim().len() > MAX_BIO_LEN {
+                return Err(ApiError::BadRequest("bio is too long".into()));
+            }
+            Some(profile_markup_policy().apply("bio", bio)?)
         }
-    }
+        None => None,
+    };
 
     let updated = paracord_core::user::update_profile(
         &state.db,
         auth.user_id,
-        body.display_name.as_deref(),
-        body.bio.as_deref(),
+        display_name.as_deref(),
+        bio.as_deref(),
         body.avatar_hash.as_deref(),
     )
     .await?;
 
-    Ok(Json(json!({
-        "id": updated.id.to_string(),
-        "username": updated.username,
-        "discriminator": updated.discriminator,
-        "email": updated.email,
-        "display_name": updated.display_name,
-        "avatar_hash": updated.avatar_hash,
-        "banner_hash": updated.banner_hash,
-        "bio": updated.bio,
-        "flags": updated.flags,
-        "bot": paracord_core::is_bot(updated.flags),
-        "system": false,
-        "created_at": updated.created_at.to_rfc3339(),
-    })))
+    Ok(Json(CurrentUserResponse::from_row(updated)))
 }
 
 pub async fn get_settings(
@@ -148,6 +169,7 @@ pub async fn get_settings(
             "status": "online",
             "custom_status": null,
             "crypto_auth_enabled": s.crypto_auth_enabled,
+            "hide_mutual_guilds": s.hide_mutual_guilds,
             "notifications": s.notifications,
             "keybinds": s.keybinds,
         })))
@@ -161,6 +183,7 @@ pub async fn get_settings(
             "status": "online",
             "custom_status": null,
             "crypto_auth_enabled": false,
+            "hide_mutual_guilds": false,
             "notifications": {},
             "keybinds": {},
         })))
@@ -168,6 +191,7 @@ pub async fn get_settings(
 }
 
 #[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct UpdateSettingsRequest {
     pub theme: Option<String>,
     pub locale: Option<String>,
@@ -176,6 +200,7 @@ pub struct UpdateSettingsRequest {
     pub status: Option<String>,
     pub custom_status: Option<String>,
     pub crypto_auth_enabled: Option<bool>,
+    pub hide_mutual_guilds: Option<bool>,
     pub notifications: Option<serde_json::Value>,
     pub keybinds: Option<serde_json::Value>,
 }
@@ -195,8 +220,19 @@ pub async fn update_settings(
         .as_deref()
         .or_else(|| existing.as_ref().map(|s| s.theme.as_str()))
         .unwrap_or("dark");
-    let locale = body
+    // Re-saving the stored locale is a no-op even if the operator has
+    // since dropped it from `server.allowed_locales`.
+    let requested_locale = body
         .locale
+        .as_deref()
+        .filter(|locale| {
+            existing
+                .as_ref()
+                .is_none_or(|s| !s.locale.eq_ignore_ascii_case(locale.trim()))
+        })
+        .map(|locale| crate::locales::resolve(crate::locales::allowed_locales(), locale))
+        .transpose()?;
+    let locale = requested_locale
         .as_deref()
         .or_else(|| existing.as_ref().map(|s| s.locale.as_str()))
         .unwrap_or("en-US");
@@ -216,16 +252,15 @@ pub async fn update_settings(
         "cozy"
     };
 
-    if let Some(status) = body.custom_status.as_deref() {
-        if status.trim().len() > MAX_CUSTOM_STATUS_LEN {
-            return Err(ApiError::BadRequest("custom_status is too long".into()));
-        }
-        if contains_dangerous_markup(status) {
-            return Err(ApiError::BadRequest(
-                "custom_status contains unsafe markup".into(),
-            ));
+    let custom_status = match body.custom_status.as_deref() {
+        Some(status) => {
+            if status.trim().len() > MAX_CUSTOM_STATUS_LEN {
+                return Err(ApiError::BadRequest("custom_status is too long".into()));
+            }
+            Some(profile_markup_policy().apply("custom_status", status)?)
         }
-    }
+        None => None,
+    };
 
     let custom_css = if let Some(css) = body.custom_css.as_deref() {
         sanitize_custom_css(css)?
@@ -241,6 +276,7 @@ pub async fn update_settings(
         message_display,
         custom_css.as_deref(),
         body.crypto_auth_enabled,
+        body.hide_mutual_guilds,
         body.notifications.as_ref(),
         body.keybinds.as_ref(),
     )
@@ -267,8 +303,9 @@ pub async fn update_settings(
         "message_display_compact": settings.message_display == "compact",
         "custom_css": settings.custom_css,
         "status": body.status.unwrap_or_else(|| "online".to_string()),
-        "custom_status": body.custom_status,
+        "custom_status": custom_status,
         "crypto_auth_enabled": settings.crypto_auth_enabled,
+        "hide_mutual_guilds": settings.hide_mutual_guilds,
         "notifications": settings.notifications,
         "keybinds": settings.keybinds,
     })))
@@ -281,12 +318,19 @@ pub async fn get_read_states(
     let rows = paracord_db::read_states::get_user_read_states(&state.db, auth.user_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let channel_ids: Vec<i64> = rows.iter().map(|row| row.channel_id).collect();
+    let latest = paracord_db::messages::latest_message_ids_for_channels(&state.db, &channel_ids)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
     let result: Vec<Value> = rows
         .iter()
         .map(|row| {
+            let latest_message_id = latest.get(&row.channel_id).copied();
             json!({
                 "channel_id": row.channel_id.to_string(),
                 "last_message_id": row.last_message_id.to_string(),
+                "latest_message_id": latest_message_id.map(|id| id.to_string()),
+                "unread": latest_message_id.is_some_and(|id| id > row.last_message_id),
                 "mention_count": row.mention_count,
             })
         })
@@ -294,6 +338,27 @@ pub async fn get_read_states(
     Ok(Json(json!(result)))
 }
 
+/// Render one data export section, or record a warning and leave the section
+/// `null` if it could not be read, so one bad row never blocks the rest.
+fn export_section<T, E: std::fmt::Display>(
+    section: &str,
+    result: Result<T, E>,
+    warnings: &mut Vec<Value>,
+    render: impl FnOnce(T) -> Value,
+) -> Value {
+    match result {
+        Ok(rows) => render(rows),
+        Err(err) => {
+            tracing::warn!(section, "data export section failed: {}", err);
+            warnings.push(json!({
+                "section": section,
+                "message": "This section could not be exported",
+            }));
+            Value::Null
+        }
+    }
+}
+
 pub async fn export_my_data(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -302,28 +367,181 @@ pub async fn export_my_data(
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
         .ok_or(ApiError::NotFound)?;
-    let settings = paracord_db::users::get_user_settings(&state.db, auth.user_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let guilds = paracord_db::guilds::get_user_guilds(&state.db, auth.user_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let dms = paracord_db::dms::list_user_dm_channels(&state.db, auth.user_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let relationships = paracord_db::relationships::get_relationships(&state.db, auth.user_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let read_states = paracord_db::read_states::get_user_read_states(&state.db, auth.user_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let sessions =
+    let mut warnings = Vec::new();
+
+    let settings = export_section(
+        "settings",
+        paracord_db::users::get_user_settings(&state.db, auth.user_id).await,
+        &mut warnings,
+        |settings| {
+            json!(settings.map(|s| json!({
+                "theme": s.theme,
+                "locale": s.locale,
+                "message_display": s.message_display,
+                "custom_css": s.custom_css,
+                "crypto_auth_enabled": s.crypto_auth_enabled,
+                "hide_mutual_guilds": s.hide_mutual_guilds,
+                "notifications": s.notifications,
+                "keybinds": s.keybinds,
+                "updated_at": s.updated_at.to_rfc3339(),
+            })))
+        },
+    );
+    let guilds = export_section(
+        "guilds",
+        paracord_db::guilds::get_user_guilds(&state.db, auth.user_id).await,
+        &mut warnings,
+        |guilds| {
+            guilds
+                .into_iter()
+                .map(|g| {
+                    json!({
+                        "id": g.id.to_string(),
+                        "name": g.name,
+                        "description": g.description,
+                        "icon_hash": g.icon_hash,
+                        "owner_id": g.owner_id.to_string(),
+                        "created_at": g.created_at.to_rfc3339(),
+                    })
+                })
+                .collect()
+        },
+    );
+    let dms = export_section(
+        "dms",
+        paracord_db::dms::list_user_dm_channels(&state.db, auth.user_id).await,
+        &mut warnings,
+        |dms| {
+            dms.into_iter()
+                .map(|dm| {
+                    json!({
+                        "channel_id": dm.id.to_string(),
+                        "recipient_id": dm.recipient_id.to_string(),
+                        "recipient_username": dm.recipient_username,
+                        "recipient_discriminator": dm.recipient_discriminator,
+                        "last_message_id": dm.last_message_id.map(|id| id.to_string()),
+                    })
+                })
+                .collect()
+        },
+    );
+    let group_dms_result = async {
+        let groups = paracord_db::dms::list_user_group_dms(&state.db, auth.user_id).await?;
+        let group_ids: Vec<i64> = groups.iter().map(|g| g.id).collect();
+        let recipients = paracord_db::dms::get_dm_recipients(&state.db, &group_ids).await?;
+        Ok::<_, paracord_db::DbError>((groups, recipients))
+    }
+    .await;
+    let group_dms = export_section(
+        "group_dms",
+        group_dms_result,
+        &mut warnings,
+        |(groups, recipients)| {
+            groups
+                .into_iter()
+                .map(|group| {
+                    json!({
+                        "channel_id": group.id.to_string(),
+                        "name": group.name,
+                        "owner_id": group.owner_id.map(|id| id.to_string()),
+                        "last_message_id": group.last_message_id.map(|id| id.to_string()),
+                        "recipients": recipients
+                            .iter()
+                            .filter(|r| r.channel_id == group.id)
+                            .map(|r| {
+                                json!({
+                                    "id": r.user_id.to_string(),
+                                    "username": r.username,
+                                    "discriminator": r.discriminator,
+                                })
+                            })
+                            .collect::<Vec<Value>>(),
+                    })
+                })
+                .collect()
+        },
+    );
+    let relationships = export_section(
+        "relationships",
+        paracord_db::relationships::get_relationships(&state.db, auth.user_id).await,
+        &mut warnings,
+        |relationships| {
+            relationships
+                .into_iter()
+                .map(|rel| {
+                    json!({
+                        "target_id": rel.target_id.to_string(),
+                        "type": rel.rel_type,
+                        "created_at": rel.created_at.to_rfc3339(),
+                        "target_username": rel.target_username,
+                        "target_discriminator": rel.target_discriminator,
+                    })
+                })
+                .collect()
+        },
+    );
+    let read_states = export_section(
+        "read_states",
+        paracord_db::read_states::get_user_read_states(&state.db, auth.user_id).await,
+        &mut warnings,
+        |read_states| {
+            read_states
+                .into_iter()
+                .map(|row| {
+                    json!({
+                        "channel_id": row.channel_id.to_string(),
+                        "last_message_id": row.last_message_id.to_string(),
+                        "mention_count": row.mention_count,
+                    })
+                })
+                .collect()
+        },
+    );
+    let sessions = export_section(
+        "sessions",
         paracord_db::sessions::list_user_sessions(&state.db, auth.user_id, chrono::Utc::now())
-            .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
-    let messages = paracord_db::messages::list_messages_by_author(&state.db, auth.user_id, 50_000)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+            .await,
+        &mut warnings,
+        |sessions| {
+            sessions
+                .into_iter()
+                .map(|session| {
+                    json!({
+                        "id": session.id,
+                        "device_id": session.device_id,
+                        "user_agent": session.user_agent,
+                        "ip_address": session.ip_address,
+                        "issued_at": session.issued_at.to_rfc3339(),
+                        "last_seen_at": session.last_seen_at.to_rfc3339(),
+                        "expires_at": session.expires_at.to_rfc3339(),
+                    })
+                })
+                .collect()
+        },
+    );
+    let messages = export_section(
+        "messages",
+        paracord_db::messages::list_messages_by_author(&state.db, auth.user_id, 50_000).await,
+        &mut warnings,
+        |messages| {
+            messages
+                .into_iter()
+                .map(|msg| {
+                    json!({
+                        "id": msg.id.to_string(),
+                        "channel_id": msg.channel_id.to_string(),
+                        "content": msg.content,
+                        "type": msg.message_type,
+                        "flags": msg.flags,
+                        "reference_id": msg.reference_id.map(|id| id.to_string()),
+                        "pinned": msg.pinned,
+                        "created_at": msg.created_at.to_rfc3339(),
+                        "edited_at": msg.edited_at.map(|dt| dt.to_rfc3339()),
+                    })
+                })
+                .collect()
+        },
+    );
 
     Ok(Json(json!({
         "exported_at": chrono::Utc::now().to_rfc3339(),
@@ -340,63 +558,15 @@ pub async fn export_my_data(
             "created_at": user.created_at.to_rfc3339(),
             "public_key": user.public_key,
         },
-        "settings": settings.map(|s| json!({
-            "theme": s.theme,
-            "locale": s.locale,
-            "message_display": s.message_display,
-            "custom_css": s.custom_css,
-            "crypto_auth_enabled": s.crypto_auth_enabled,
-            "notifications": s.notifications,
-            "keybinds": s.keybinds,
-            "updated_at": s.updated_at.to_rfc3339(),
-        })),
-        "guilds": guilds.into_iter().map(|g| json!({
-            "id": g.id.to_string(),
-            "name": g.name,
-            "description": g.description,
-            "icon_hash": g.icon_hash,
-            "owner_id": g.owner_id.to_string(),
-            "created_at": g.created_at.to_rfc3339(),
-        })).collect::<Vec<Value>>(),
-        "dms": dms.into_iter().map(|dm| json!({
-            "channel_id": dm.id.to_string(),
-            "recipient_id": dm.recipient_id.to_string(),
-            "recipient_username": dm.recipient_username,
-            "recipient_discriminator": dm.recipient_discriminator,
-            "last_message_id": dm.last_message_id.map(|id| id.to_string()),
-        })).collect::<Vec<Value>>(),
-        "relationships": relationships.into_iter().map(|rel| json!({
-            "target_id": rel.target_id.to_string(),
-            "type": rel.rel_type,
-            "created_at": rel.created_at.to_rfc3339(),
-            "target_username": rel.target_username,
-            "target_discriminator": rel.target_discriminator,
-        })).collect::<Vec<Value>>(),
-        "read_states": read_states.into_iter().map(|row| json!({
-            "channel_id": row.channel_id.to_string(),
-            "last_message_id": row.last_message_id.to_string(),
-            "mention_count": row.mention_count,
-        })).collect::<Vec<Value>>(),
-        "sessions": sessions.into_iter().map(|session| json!({
-            "id": session.id,
-            "device_id": session.device_id,
-            "user_agent": session.user_agent,
-            "ip_address": session.ip_address,
-            "issued_at": session.issued_at.to_rfc3339(),
-            "last_seen_at": session.last_seen_at.to_rfc3339(),
-            "expires_at": session.expires_at.to_rfc3339(),
-        })).collect::<Vec<Value>>(),
-        "messages": messages.into_iter().map(|msg| json!({
-            "id": msg.id.to_string(),
-            "channel_id": msg.channel_id.to_string(),
-            "content": msg.content,
-            "type": msg.message_type,
-            "flags": msg.flags,
-            "reference_id": msg.reference_id.map(|id| id.to_string()),
-            "pinned": msg.pinned,
-            "created_at": msg.created_at.to_rfc3339(),
-            "edited_at": msg.edited_at.map(|dt| dt.to_rfc3339()),
-        })).collect::<Vec<Value>>(),
+        "settings": settings,
+        "guilds": guilds,
+        "dms": dms,
+        "group_dms": group_dms,
+        "relationships": relationships,
+        "read_states": read_states,
+        "sessions": sessions,
+        "messages": messages,
+        "warnings": warnings,
     })))
 }
 
@@ -410,9 +580,27 @@ pub async fn get_user_profile(
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
         .ok_or(ApiError::NotFound)?;
 
-    let mutual_guilds = paracord_db::users::get_mutual_guilds(&state.db, auth.user_id, user_id)
-        .await
-        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    // Mutual guilds (and the guild roles derived from them) are hidden from
+    // non-friends when the profile owner opted out of showing them.
+    let hide_mutual_guilds = if user_id == auth.user_id {
+        false
+    } else {
+        let opted_out = paracord_db::users::get_user_settings(&state.db, user_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+            .is_some_and(|settings| settings.hide_mutual_guilds);
+        opted_out
+            && !paracord_db::relationships::are_friends(&state.db, auth.user_id, user_id)
+                .await
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    };
+    let mutual_guilds = if hide_mutual_guilds {
+        Vec::new()
+    } else {
+        paracord_db::users::get_mutual_guilds(&state.db, auth.user_id, user_id)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+    };
 
     let mutual_friends = paracord_db::users::get_mutual_friends(&state.db, auth.user_id, user_id)
         .await
@@ -553,6 +741,7 @@ pub async fn change_password(
         now,
     )
     .await;
+    let _ = paracord_db::personal_access_tokens::delete_user_tokens(&state.db, auth.user_id).await;
 
     security::log_security_event(
         &state,
@@ -137,6 +137,20 @@ struct LiveKitWebhookAuthClaims {
     sha256: Option<String>,
 }
 
+/// 503 for voice features that need LiveKit. Distinguishes a LiveKit that was
+/// never available from one that went down after startup.
+fn livekit_unavailable(state: &AppState, feature: &str) -> ApiError {
+    if state.config.livekit_available {
+        ApiError::ServiceUnavailable(format!(
+            "{feature} is temporarily unavailable — the voice server is not responding. Please try again shortly."
+        ))
+    } else {
+        ApiError::ServiceUnavailable(format!(
+            "{feature} is not available — LiveKit server binary not found. Place livekit-server next to the Paracord server executable."
+        ))
+    }
+}
+
 fn verify_livekit_webhook_auth(
     headers: &HeaderMap,
     body: &[u8],
@@ -204,6 +218,8 @@ pub struct LiveKitWebhookPayload {
     pub event: String,
     pub room: Option<LiveKitRoom>,
     pub participant: Option<LiveKitParticipant>,
+    #[serde(rename = "egressInfo")]
+    pub egress_info: Option<LiveKitEgressInfo>,
 }
 
 #[derive(Deserialize)]
@@ -216,6 +232,50 @@ pub struct LiveKitParticipant {
     pub identity: String,
 }
 
+#[derive(Deserialize)]
+pub struct LiveKitEgressInfo {
+    #[serde(rename = "egressId")]
+    pub egress_id: String,
+    #[serde(rename = "roomName")]
+    pub room_name: String,
+}
+
+/// Split a `guild_{guild_id}_channel_{channel_id}` LiveKit room name.
+fn parse_room_name(room_name: &str) -> Option<(Option<i64>, i64)> {
+    let parts: Vec<&str> = room_name.split('_').collect();
+    if parts.len() < 4 {
+        return None;
+    }
+    let guild_id = parts[1].parse::<i64>().ok();
+    let channel_id = parts[3].parse::<i64>().ok()?;
+    Some((guild_id, channel_id))
+}
+
+/// Reject a join into a voice channel that already has `user_limit`
+/// participants. Members with MOVE_MEMBERS (including administrators) may
+/// join a full channel, and rejoining the same channel never counts twice.
+pub(crate) async fn ensure_voice_channel_capacity(
+    state: &AppState,
+    channel: &paracord_db::channels::ChannelRow,
+    user_id: i64,
+    perms: Permissions,
+) -> Result<(), ApiError> {
+    let Some(user_limit) = channel.user_limit.filter(|limit| *limit > 0) else {
+        return Ok(());
+    };
+    if perms.contains(Permissions::MOVE_MEMBERS) {
+        return Ok(());
+    }
+    let connected = paracord_db::voice_states::get_channel_voice_states(&state.db, channel.id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let others = connected.iter().filter(|vs| vs.user_id != user_id).count();
+    if others >= user_limit as usize {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(())
+}
+
 pub async fn join_voice(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -223,13 +283,11 @@ pub async fn join_voice(
     Path(channel_id): Path<i64>,
     Query(query): Query<VoiceJoinQuery>,
 ) -> Result<Json<Value>, ApiError> {
-    if !state.config.livekit_available
+    if !state.livekit_available()
         && !state.config.native_media_enabled
         && !paracord_federation::is_enabled()
     {
-        return Err(ApiError::ServiceUnavailable(
-            "Voice chat is not available — LiveKit server binary not found. Place livekit-server next to the Paracord server executable.".into(),
-        ));
+        return Err(livekit_unavailable(&state, "Voice chat"));
     }
 
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
@@ -259,11 +317,19 @@ pub async fn join_voice(
     .await?;
     paracord_core::permissions::require_permission(perms, Permissions::VIEW_CHANNEL)?;
     paracord_core::permissions::require_permission(perms, Permissions::CONNECT)?;
+    let priority_speaker = perms.contains(Permissions::PRIORITY_SPEAKER);
+    ensure_voice_channel_capacity(&state, &channel, auth.user_id, perms).await?;
 
     let user = paracord_db::users::get_user_by_id(&state.db, auth.user_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
         .ok_or(ApiError::NotFound)?;
+    // Server mute/deafen live on the member, so they carry over to every
+    // new voice session rather than resetting on rejoin.
+    let member = paracord_db::members::get_member(&state.db, auth.user_id, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::Forbidden)?;
 
     // If the user was tracked in any other voice room, remove that stale
     // in-memory membership before joining the new channel.
@@ -338,6 +404,7 @@ pub async fn join_voice(
                             channel.guild_id(),
                             channel_id,
                             &remote.session_id,
+                            priority_speaker,
                         )
                         .await;
                         state.event_bus.dispatch(
@@ -352,8 +419,9 @@ pub async fn join_voice(
                                 "self_stream": false,
                                 "self_video": false,
                                 "suppress": false,
-                                "mute": false,
-                                "deaf": false,
+                                "mute": member.mute,
+                                "deaf": member.deaf,
+                                "priority_speaker": priority_speaker,
                                 "username": &user.username,
                                 "avatar_hash": user.avatar_hash,
                             }),
@@ -415,6 +483,7 @@ pub async fn join_voice(
             channel.guild_id(),
             channel_id,
             &session_id,
+            priority_speaker,
         )
         .await;
 
@@ -430,8 +499,9 @@ pub async fn join_voice(
                 "self_stream": false,
                 "self_video": false,
                 "suppress": false,
-                "mute": false,
-                "deaf": false,
+                "mute": member.mute,
+                "deaf": member.deaf,
+                "priority_speaker": priority_speaker,
                 "username": &user.username,
                 "avatar_hash": user.avatar_hash,
             }),
@@ -468,6 +538,7 @@ pub async fn join_voice(
             // Extra claims are tolerated by serde and help with diagnostics.
             "session_id": &session_id,
             "room": &room_name,
+            "priority_speaker": priority_speaker,
         });
         let media_token = jsonwebtoken::encode(
             &jsonwebtoken::Header::new(Algorithm::HS256),
@@ -493,14 +564,14 @@ pub async fn join_voice(
             "cert_hash": cert_hash,
             "room_name": room_name,
             "session_id": session_id,
-            "livekit_available": state.config.livekit_available,
+            "priority_speaker": priority_speaker,
+            "livekit_available": state.livekit_available(),
+            "recording": state.voice.get_recording(channel_id).await.map(|r| r.to_json()),
         })));
     }
 
-    if !state.config.livekit_available {
-        return Err(ApiError::ServiceUnavailable(
-            "Voice chat is not available - LiveKit server binary not found. Place livekit-server next to the Paracord server executable.".into(),
-        ));
+    if !state.livekit_available() {
+        return Err(livekit_unavailable(&state, "Voice chat"));
     }
 
     let session_id = uuid::Uuid::new_v4().to_string();
@@ -513,8 +584,10 @@ pub async fn join_voice(
             auth.user_id,
             &user.username,
             &session_id,
-            true, // can_speak
+            !(member.mute || member.deaf), // can_speak
+            priority_speaker,
             paracord_media::AudioBitrate::default(),
+            paracord_media::voice::room_max_participants(channel.user_limit),
         )
         .await
         .map_err(ApiError::Internal)?;
@@ -525,6 +598,7 @@ pub async fn join_voice(
         channel.guild_id(),
         channel_id,
         &session_id,
+        priority_speaker,
     )
     .await;
 
@@ -540,8 +614,9 @@ pub async fn join_voice(
             "self_stream": false,
             "self_video": false,
             "suppress": false,
-            "mute": false,
-            "deaf": false,
+            "mute": member.mute,
+            "deaf": member.deaf,
+            "priority_speaker": priority_speaker,
             "username": &user.username,
             "avatar_hash": user.avatar_hash,
         }),
@@ -565,6 +640,8 @@ pub async fn join_voice(
         "url_candidates": url_candidates,
         "room_name": join_resp.room_name,
         "session_id": session_id,
+        "priority_speaker": priority_speaker,
+        "recording": state.voice.get_recording(channel_id).await.map(|r| r.to_json()),
     })))
 }
 
@@ -576,13 +653,11 @@ pub async fn start_stream(
     Query(query): Query<VoiceJoinQuery>,
     body: Option<Json<StartStreamRequest>>,
 ) -> Result<Json<Value>, ApiError> {
-    if !state.config.livekit_available
+    if !state.livekit_available()
         && !state.config.native_media_enabled
         && !paracord_federation::is_enabled()
     {
-        return Err(ApiError::ServiceUnavailable(
-            "Streaming is not available — LiveKit server binary not found.".into(),
-        ));
+        return Err(livekit_unavailable(&state, "Streaming"));
     }
 
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
@@ -796,10 +871,8 @@ pub async fn start_stream(
         })));
     }
 
-    if !state.config.livekit_available {
-        return Err(ApiError::ServiceUnavailable(
-            "Streaming is not available - LiveKit server binary not found.".into(),
-        ));
+    if !state.livekit_available() {
+        return Err(livekit_unavailable(&state, "Streaming"));
     }
 
     let stream_resp = state
@@ -969,6 +1042,122 @@ pub async fn stop_stream(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// `VOICE_RECORDING_UPDATE` payload for a channel that is no longer recorded.
+fn recording_stopped_json(guild_id: i64, channel_id: i64) -> Value {
+    json!({
+        "guild_id": guild_id.to_string(),
+        "channel_id": channel_id.to_string(),
+        "recording": false,
+        "started_by": null,
+        "started_at": null,
+    })
+}
+
+/// Resolve a guild voice channel and require MANAGE_CHANNELS on it.
+async fn require_recording_permission(
+    state: &AppState,
+    channel_id: i64,
+    user_id: i64,
+) -> Result<i64, ApiError> {
+    let channel = paracord_db::channels::get_channel(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    if channel.channel_type != 2 {
+        return Err(ApiError::BadRequest("Not a voice channel".into()));
+    }
+    let guild_id = channel.guild_id().ok_or(ApiError::BadRequest(
+        "Voice is only supported in guild channels".into(),
+    ))?;
+    paracord_core::permissions::ensure_guild_member(&state.db, guild_id, user_id).await?;
+    let guild = paracord_db::guilds::get_guild(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+    let perms = paracord_core::permissions::compute_channel_permissions(
+        &state.db,
+        guild_id,
+        channel_id,
+        guild.owner_id,
+        user_id,
+    )
+    .await?;
+    paracord_core::permissions::require_permission(perms, Permissions::VIEW_CHANNEL)?;
+    paracord_core::permissions::require_permission(perms, Permissions::MANAGE_CHANNELS)?;
+    Ok(guild_id)
+}
+
+pub async fn start_recording(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.livekit_available() {
+        return Err(livekit_unavailable(&state, "Voice recording"));
+    }
+    if !state.config.livekit_recording_enabled {
+        return Err(ApiError::BadRequest(
+            "Voice recording is disabled on this server".into(),
+        ));
+    }
+    let guild_id = require_recording_permission(&state, channel_id, auth.user_id).await?;
+
+    let recording = state
+        .voice
+        .start_recording(
+            channel_id,
+            guild_id,
+            auth.user_id,
+            &state.config.livekit_recording_filepath,
+        )
+        .await
+        .map_err(ApiError::Internal)?
+        .ok_or_else(|| ApiError::Conflict("This channel is already being recorded".into()))?;
+    tracing::info!(
+        "Voice recording {} started by user={} channel={}",
+        recording.egress_id,
+        auth.user_id,
+        channel_id
+    );
+
+    let payload = recording.to_json();
+    state
+        .event_bus
+        .dispatch("VOICE_RECORDING_UPDATE", payload.clone(), Some(guild_id));
+    Ok(Json(payload))
+}
+
+pub async fn stop_recording(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let guild_id = require_recording_permission(&state, channel_id, auth.user_id).await?;
+    let Some(recording) = state
+        .voice
+        .stop_recording(channel_id)
+        .await
+        .map_err(ApiError::Internal)?
+    else {
+        return Err(ApiError::BadRequest(
+            "This channel is not being recorded".into(),
+        ));
+    };
+    tracing::info!(
+        "Voice recording {} stopped by user={} channel={}",
+        recording.egress_id,
+        auth.user_id,
+        channel_id
+    );
+
+    state.event_bus.dispatch(
+        "VOICE_RECORDING_UPDATE",
+        recording_stopped_json(guild_id, channel_id),
+        Some(guild_id),
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
 pub async fn leave_voice(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -1047,17 +1236,20 @@ pub async fn livekit_webhook(
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<StatusCode, ApiError> {
-    verify_livekit_webhook_auth(
-        &headers,
-        &body,
-        &state.config.livekit_api_key,
-        &state.config.livekit_api_secret,
-    )?;
+    let livekit = state.voice.livekit();
+    verify_livekit_webhook_auth(&headers, &body, &livekit.api_key, &livekit.api_secret)?;
     let payload: LiveKitWebhookPayload =
         serde_json::from_slice(&body).map_err(|e| ApiError::BadRequest(e.to_string()))?;
 
-    if payload.event != "participant_left" {
-        return Ok(StatusCode::NO_CONTENT);
+    match payload.event.as_str() {
+        "participant_left" => {}
+        "egress_started" | "egress_ended" => {
+            if let Some(egress) = payload.egress_info {
+                handle_egress_webhook(&state, payload.event == "egress_started", &egress).await;
+            }
+            return Ok(StatusCode::NO_CONTENT);
+        }
+        _ => return Ok(StatusCode::NO_CONTENT),
     }
     let room_name = if let Some(room) = payload.room {
         room.name
@@ -1073,13 +1265,7 @@ pub async fn livekit_webhook(
         return Ok(StatusCode::NO_CONTENT);
     };
 
-    let parts: Vec<&str> = room_name.split('_').collect();
-    if parts.len() < 4 {
-        return Ok(StatusCode::NO_CONTENT);
-    }
-    let guild_id = parts[1].parse::<i64>().ok();
-    let channel_id = parts[3].parse::<i64>().ok();
-    let Some(channel_id) = channel_id else {
+    let Some((guild_id, channel_id)) = parse_room_name(&room_name) else {
         return Ok(StatusCode::NO_CONTENT);
     };
 
@@ -1151,6 +1337,32 @@ pub async fn livekit_webhook(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Keep recording state in sync with egresses LiveKit reports, including
+/// ones started or stopped outside Paracord.
+async fn handle_egress_webhook(state: &AppState, started: bool, egress: &LiveKitEgressInfo) {
+    let Some((Some(guild_id), channel_id)) = parse_room_name(&egress.room_name) else {
+        return;
+    };
+    let payload = if started {
+        state
+            .voice
+            .recording_started(channel_id, guild_id, &egress.egress_id)
+            .await
+            .map(|recording| recording.to_json())
+    } else {
+        state
+            .voice
+            .recording_ended(channel_id, &egress.egress_id)
+            .await
+            .map(|_| recording_stopped_json(guild_id, channel_id))
+    };
+    if let Some(payload) = payload {
+        state
+            .event_bus
+            .dispatch("VOICE_RECORDING_UPDATE", payload, Some(guild_id));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::verify_livekit_webhook_auth;
@@ -0,0 +1,565 @@
+//! WebAuthn passkey registration and login.
+//!
+//! Each ceremony is two requests. `begin` stores the challenge server-side
+//! under a short-lived ceremony ID and returns the options for
+//! `navigator.credentials.create()` / `.get()`; `finish` takes the ceremony
+//! (so it can only be completed once) and verifies the authenticator's
+//! response against it. A successful login passes the same email
+//! verification and two-factor gates as a password login and issues the
+//! same session.
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap},
+    response::{AppendHeaders, IntoResponse},
+    Json,
+};
+use chrono::Utc;
+use paracord_core::auth::webauthn::{
+    self, DiscoverableAuthentication, DiscoverableKey, Passkey, PasskeyAuthentication,
+    PasskeyRegistration, PublicKeyCredential, RegisterPublicKeyCredential, RelyingParty,
+    WebauthnError,
+};
+use paracord_core::AppState;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Mutex, OnceLock};
+
+use crate::error::ApiError;
+use crate::middleware::AuthUser;
+use crate::routes::auth::{self as auth_routes, AuthResponse};
+use crate::routes::mfa::LoginMfa;
+use crate::routes::security;
+
+const CEREMONY_STORE_MAX_ENTRIES: usize = 10_000;
+const MAX_CREDENTIALS_PER_USER: usize = 20;
+const MAX_CREDENTIAL_NAME_LEN: usize = 64;
+const MAX_TRANSPORTS: usize = 8;
+
+enum CeremonyState {
+    Register {
+        user_id: i64,
+        state: PasskeyRegistration,
+    },
+    /// Login for a named account, limited to its passkeys.
+    Login {
+        user_id: i64,
+        state: PasskeyAuthentication,
+    },
+    /// Login with any discoverable passkey; the account comes from its
+    /// user handle.
+    Discoverable(DiscoverableAuthentication),
+}
+
+struct Ceremony {
+    state: CeremonyState,
+    rp: RelyingParty,
+    created_at_ms: i64,
+}
+
+// In-memory ceremony store (ceremony id -> pending ceremony). Cleaned up on each begin.
+static CEREMONY_STORE: OnceLock<Mutex<HashMap<String, Ceremony>>> = OnceLock::new();
+
+fn ceremony_store() -> &'static Mutex<HashMap<String, Ceremony>> {
+    CEREMONY_STORE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn ceremony_expired(ceremony: &Ceremony, now_ms: i64) -> bool {
+    now_ms - ceremony.created_at_ms > webauthn::CEREMONY_TIMEOUT_MS as i64
+}
+
+fn start_ceremony(rp: RelyingParty, state: CeremonyState) -> Result<String, ApiError> {
+    let ceremony_id = auth_routes::random_token_hex(24);
+    let mut store = ceremony_store()
+        .lock()
+        .map_err(|_| ApiError::Internal(anyhow::anyhow!("lock error")))?;
+    let now_ms = Utc::now().timestamp_millis();
+    store.retain(|_, pending| !ceremony_expired(pending, now_ms));
+    if store.len() >= CEREMONY_STORE_MAX_ENTRIES {
+        let oldest = store
+            .iter()
+            .min_by_key(|(_, pending)| pending.created_at_ms)
+            .map(|(id, _)| id.clone());
+        if let Some(oldest) = oldest {
+            store.remove(&oldest);
+        }
+    }
+    store.insert(
+        ceremony_id.clone(),
+        Ceremony {
+            state,
+            rp,
+            created_at_ms: now_ms,
+        },
+    );
+    Ok(ceremony_id)
+}
+
+fn unknown_ceremony() -> ApiError {
+    ApiError::BadRequest("Unknown or expired WebAuthn ceremony".into())
+}
+
+/// Remove and return a live ceremony. Unknown and expired IDs fail the same
+/// way; callers reject a ceremony of the wrong kind with [`unknown_ceremony`].
+fn take_ceremony(ceremony_id: &str) -> Result<Ceremony, ApiError> {
+    let ceremony = ceremony_store()
+        .lock()
+        .map_err(|_| ApiError::Internal(anyhow::anyhow!("lock error")))?
+        .remove(ceremony_id);
+    ceremony
+        .filter(|c| !ceremony_expired(c, Utc::now().timestamp_millis()))
+        .ok_or_else(unknown_ceremony)
+}
+
+fn relying_party(
+    state: &AppState,
+    headers: &HeaderMap,
+    peer_ip: &str,
+) -> Result<RelyingParty, ApiError> {
+    let origin = auth_routes::resolve_server_origin(
+        state.config.public_url.as_deref(),
+        headers,
+        Some(peer_ip),
+    );
+    RelyingParty::from_origin(&origin)
+        .ok_or_else(|| ApiError::Internal(anyhow::anyhow!("invalid server origin: {origin}")))
+}
+
+fn verifier(rp: &RelyingParty) -> Result<webauthn::Webauthn, ApiError> {
+    rp.verifier()
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("WebAuthn configuration: {e}")))
+}
+
+fn db_error(e: paracord_db::DbError) -> ApiError {
+    ApiError::Internal(anyhow::anyhow!(e.to_string()))
+}
+
+fn stored_passkey(
+    credential: &paracord_db::webauthn_credentials::WebAuthnCredentialRow,
+) -> Result<Passkey, ApiError> {
+    serde_json::from_str(&credential.passkey).map_err(|e| {
+        ApiError::Internal(anyhow::anyhow!(
+            "invalid stored passkey {}: {e}",
+            credential.credential_id
+        ))
+    })
+}
+
+fn stored_passkeys(
+    credentials: &[paracord_db::webauthn_credentials::WebAuthnCredentialRow],
+) -> Result<Vec<Passkey>, ApiError> {
+    credentials.iter().map(stored_passkey).collect()
+}
+
+fn credential_json(credential: &paracord_db::webauthn_credentials::WebAuthnCredentialRow) -> Value {
+    json!({
+        "id": credential.credential_id,
+        "name": credential.name,
+        "transports": credential.transports,
+        "created_at": credential.created_at.to_rfc3339(),
+        "last_used_at": credential.last_used_at.map(|t| t.to_rfc3339()),
+    })
+}
+
+pub async fn register_begin(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth: AuthUser,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    if auth.impersonator_id.is_some() {
+        return Err(ApiError::Forbidden);
+    }
+    let user = paracord_db::users::get_user_auth_by_id(&state.db, auth.user_id)
+        .await
+        .map_err(db_error)?
+        .ok_or(ApiError::NotFound)?;
+    let existing = paracord_db::webauthn_credentials::get_user_credentials(&state.db, user.id)
+        .await
+        .map_err(db_error)?;
+    if existing.len() >= MAX_CREDENTIALS_PER_USER {
+        return Err(ApiError::BadRequest("Too many passkeys registered".into()));
+    }
+
+    let exclude = stored_passkeys(&existing)?
+        .iter()
+        .map(|passkey| passkey.cred_id().clone())
+        .collect();
+
+    let rp = relying_party(&state, &headers, &addr.ip().to_string())?;
+    let display_name = user
+        .display_name
+        .clone()
+        .unwrap_or_else(|| user.username.clone());
+    let (options, registration) = verifier(&rp)?
+        .start_passkey_registration(
+            webauthn::user_handle(user.id),
+            &user.username,
+            &display_name,
+            Some(exclude),
+        )
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("WebAuthn registration: {e}")))?;
+    let ceremony_id = start_ceremony(
+        rp,
+        CeremonyState::Register {
+            user_id: user.id,
+            state: registration,
+        },
+    )?;
+
+    Ok(Json(json!({
+        "ceremony_id": ceremony_id,
+        "public_key": options.public_key,
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct RegisterFinishRequest {
+    pub ceremony_id: String,
+    /// The `navigator.credentials.create()` result as JSON
+    /// (`PublicKeyCredential.toJSON()`).
+    pub credential: RegisterPublicKeyCredential,
+    pub name: Option<String>,
+}
+
+pub async fn register_finish(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(body): Json<RegisterFinishRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if auth.impersonator_id.is_some() {
+        return Err(ApiError::Forbidden);
+    }
+    let ceremony = take_ceremony(&body.ceremony_id)?;
+    let CeremonyState::Register {
+        user_id,
+        state: registration,
+    } = &ceremony.state
+    else {
+        return Err(unknown_ceremony());
+    };
+    if *user_id != auth.user_id {
+        return Err(unknown_ceremony());
+    }
+
+    let name = body
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    if name.is_some_and(|name| name.chars().count() > MAX_CREDENTIAL_NAME_LEN) {
+        return Err(ApiError::BadRequest("Passkey name is too long".into()));
+    }
+    let transports: Vec<String> = body
+        .credential
+        .response
+        .transports
+        .iter()
+        .flatten()
+        .filter_map(|t| serde_json::to_value(t).ok())
+        .filter_map(|t| t.as_str().map(str::to_string))
+        .filter(|t| t != "unknown")
+        .take(MAX_TRANSPORTS)
+        .collect();
+
+    let passkey = verifier(&ceremony.rp)?
+        .finish_passkey_registration(&body.credential, registration)
+        .map_err(|e| ApiError::BadRequest(format!("Passkey registration failed: {e}")))?;
+    let credential_id = webauthn::encode_credential_id(passkey.cred_id());
+    let serialized = serde_json::to_string(&passkey)
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    if paracord_db::webauthn_credentials::get_credential(&state.db, &credential_id)
+        .await
+        .map_err(db_error)?
+        .is_some()
+    {
+        return Err(ApiError::Conflict("Passkey is already registered".into()));
+    }
+    let credential = paracord_db::webauthn_credentials::create_credential(
+        &state.db,
+        &credential_id,
+        auth.user_id,
+        &serialized,
+        0,
+        &transports,
+        name,
+    )
+    .await
+    .map_err(db_error)?;
+
+    security::log_security_event(
+        &state,
+        "auth.webauthn.register",
+        Some(auth.user_id),
+        Some(auth.user_id),
+        auth.session_id.as_deref(),
+        Some(&headers),
+        Some(json!({ "credential_id": credential.credential_id })),
+    )
+    .await;
+
+    Ok(Json(credential_json(&credential)))
+}
+
+#[derive(Deserialize)]
+pub struct LoginBeginRequest {
+    /// Optional email or username; without it the browser offers any
+    /// discoverable passkey for this site.
+    #[serde(default, alias = "email", alias = "username")]
+    pub identifier: Option<String>,
+}
+
+pub async fn login_begin(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<LoginBeginRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let peer_ip = addr.ip().to_string();
+    let identifier = body
+        .identifier
+        .as_deref()
+        .map(auth_routes::normalize_login_identifier_for_auth)
+        .filter(|identifier| !identifier.is_empty());
+    auth_routes::auth_guard_enforce(&state, &headers, Some(&peer_ip), identifier.as_deref())
+        .await?;
+
+    let mut account = None;
+    if let Some(identifier) = identifier.as_deref() {
+        if let Some(user) = auth_routes::resolve_login_user(&state, identifier).await? {
+            let credentials =
+                paracord_db::webauthn_credentials::get_user_credentials(&state.db, user.id)
+                    .await
+                    .map_err(db_error)?;
+            let passkeys = stored_passkeys(&credentials)?;
+            if !passkeys.is_empty() {
+                account = Some((user.id, passkeys));
+            }
+        }
+    }
+
+    // An unknown account, or one without passkeys, gets a discoverable
+    // ceremony (empty allow-list) rather than an error, so this endpoint
+    // does not reveal which identifiers exist.
+    let rp = relying_party(&state, &headers, &peer_ip)?;
+    let verifier = verifier(&rp)?;
+    let (options, ceremony) = match account {
+        Some((user_id, passkeys)) => verifier
+            .start_passkey_authentication(&passkeys)
+            .map(|(options, state)| (options, CeremonyState::Login { user_id, state })),
+        None => verifier
+            .start_discoverable_authentication()
+            .map(|(options, state)| (options, CeremonyState::Discoverable(state))),
+    }
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!("WebAuthn login: {e}")))?;
+    let ceremony_id = start_ceremony(rp, ceremony)?;
+
+    Ok(Json(json!({
+        "ceremony_id": ceremony_id,
+        "public_key": options.public_key,
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct LoginFinishRequest {
+    pub ceremony_id: String,
+    /// The `navigator.credentials.get()` result as JSON
+    /// (`PublicKeyCredential.toJSON()`).
+    pub credential: PublicKeyCredential,
+    /// TOTP or recovery code, needed only when the authenticator did not
+    /// verify the user and the account has two-factor enabled.
+    pub mfa_code: Option<String>,
+}
+
+pub async fn login_finish(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<LoginFinishRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let peer_ip = addr.ip().to_string();
+    let credential_id = webauthn::encode_credential_id(body.credential.raw_id.as_ref());
+    let credential_id = credential_id.as_str();
+    auth_routes::auth_guard_enforce(&state, &headers, Some(&peer_ip), Some(credential_id)).await?;
+
+    let ceremony = take_ceremony(&body.ceremony_id)?;
+    let verifier = verifier(&ceremony.rp)?;
+    let credential = paracord_db::webauthn_credentials::get_credential(&state.db, credential_id)
+        .await
+        .map_err(db_error)?;
+    let credential = match (&ceremony.state, credential) {
+        (CeremonyState::Login { user_id, .. }, Some(credential))
+            if credential.user_id == *user_id =>
+        {
+            Some(credential)
+        }
+        // The user handle the authenticator returns must name the account
+        // that owns the credential.
+        (CeremonyState::Discoverable(_), Some(credential)) => verifier
+            .identify_discoverable_authentication(&body.credential)
+            .ok()
+            .and_then(|(handle, _)| webauthn::user_id_from_handle(handle))
+            .filter(|user_id| *user_id == credential.user_id)
+            .map(|_| credential),
+        (CeremonyState::Register { .. }, _) => return Err(unknown_ceremony()),
+        _ => None,
+    };
+    let Some(credential) = credential else {
+        auth_routes::auth_guard_record_failure(
+            &state,
+            &headers,
+            Some(&peer_ip),
+            Some(credential_id),
+        )
+        .await;
+        return Err(ApiError::Unauthorized);
+    };
+    let mut passkey = stored_passkey(&credential)?;
+
+    let verified = match ceremony.state {
+        CeremonyState::Login {
+            state: authentication,
+            ..
+        } => verifier.finish_passkey_authentication(&body.credential, &authentication),
+        CeremonyState::Discoverable(authentication) => verifier.finish_discoverable_authentication(
+            &body.credential,
+            authentication,
+            &[DiscoverableKey::from(&passkey)],
+        ),
+        CeremonyState::Register { .. } => return Err(unknown_ceremony()),
+    };
+    let verified = match verified {
+        // Compare-and-swap so two logins presenting the same counter value
+        // cannot both succeed.
+        Ok(result) => {
+            passkey.update_credential(&result);
+            let serialized = serde_json::to_string(&passkey)
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+            let advanced = paracord_db::webauthn_credentials::update_passkey(
+                &state.db,
+                &credential.credential_id,
+                credential.sign_count,
+                i64::from(result.counter()),
+                &serialized,
+                Utc::now(),
+            )
+            .await
+            .map_err(db_error)?;
+            if advanced {
+                Ok(result)
+            } else {
+                Err(WebauthnError::CredentialPossibleCompromise)
+            }
+        }
+        Err(e) => Err(e),
+    };
+    let verified = match verified {
+        Ok(result) => result,
+        Err(e) => {
+            if matches!(e, WebauthnError::CredentialPossibleCompromise) {
+                security::log_security_event(
+                    &state,
+                    "auth.webauthn.clone_detected",
+                    None,
+                    Some(credential.user_id),
+                    None,
+                    Some(&headers),
+                    Some(json!({
+                        "credential_id": credential.credential_id,
+                        "stored_sign_count": credential.sign_count,
+                    })),
+                )
+                .await;
+            }
+            auth_routes::auth_guard_record_failure(
+                &state,
+                &headers,
+                Some(&peer_ip),
+                Some(credential_id),
+            )
+            .await;
+            return Err(ApiError::Unauthorized);
+        }
+    };
+
+    let user = paracord_db::users::get_user_auth_by_id(&state.db, credential.user_id)
+        .await
+        .map_err(db_error)?
+        .ok_or(ApiError::Unauthorized)?;
+    paracord_core::auth::ensure_email_verified(&user, &*state.runtime.read().await)
+        .map_err(|_| ApiError::EmailUnverified)?;
+
+    // A user-verified passkey (PIN or biometric) is already two factors;
+    // otherwise the account's TOTP applies as it does to a password login.
+    if !verified.user_verified() {
+        match crate::routes::mfa::check_login_mfa(
+            &state,
+            user.id,
+            body.mfa_code.as_deref(),
+            &headers,
+        )
+        .await?
+        {
+            LoginMfa::Satisfied | LoginMfa::RecoveryCodeUsed { .. } => {}
+            LoginMfa::Required => return Err(ApiError::MfaRequired),
+            LoginMfa::Invalid => {
+                auth_routes::auth_guard_record_failure(
+                    &state,
+                    &headers,
+                    Some(&peer_ip),
+                    Some(credential_id),
+                )
+                .await;
+                return Err(ApiError::Unauthorized);
+            }
+        }
+    }
+
+    let (token, access_cookie, refresh_cookie, session_id, raw_refresh) =
+        auth_routes::issue_auth_session(
+            &state,
+            user.id,
+            user.public_key.as_deref(),
+            &headers,
+            Some(&peer_ip),
+        )
+        .await?;
+    security::log_security_event(
+        &state,
+        "auth.login.webauthn",
+        Some(user.id),
+        Some(user.id),
+        Some(&session_id),
+        Some(&headers),
+        Some(json!({
+            "auth_method": "webauthn",
+            "credential_id": credential.credential_id,
+            "user_verified": verified.user_verified(),
+        })),
+    )
+    .await;
+    auth_routes::auth_guard_record_success(&state, &headers, Some(&peer_ip), Some(credential_id))
+        .await;
+
+    Ok((
+        AppendHeaders([
+            (
+                header::SET_COOKIE,
+                auth_routes::header_value(&access_cookie)?,
+            ),
+            (
+                header::SET_COOKIE,
+                auth_routes::header_value(&refresh_cookie)?,
+            ),
+        ]),
+        Json(AuthResponse {
+            token,
+            user: auth_routes::user_auth_json(&user),
+            refresh_token: Some(raw_refresh),
+        }),
+    ))
+}
@@ -1,16 +1,22 @@
 use axum::{
     body::Bytes,
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    Json,
+    extract::{ConnectInfo, Path, Query, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use paracord_core::AppState;
 use paracord_models::permissions::Permissions;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::net::{IpAddr, SocketAddr};
 
 use crate::error::ApiError;
 use crate::middleware::AuthUser;
+use crate::routes::audit;
+
+const WEBHOOK_COUNT: HeaderName = HeaderName::from_static("x-webhook-count");
+const WEBHOOK_LIMIT: HeaderName = HeaderName::from_static("x-webhook-limit");
 
 fn webhook_to_json(w: &paracord_db::webhooks::WebhookRow, token: Option<&str>) -> Value {
     let mut v = json!({
@@ -19,6 +25,7 @@ fn webhook_to_json(w: &paracord_db::webhooks::WebhookRow, token: Option<&str>) -
         "channel_id": w.channel_id.to_string(),
         "name": w.name,
         "creator_id": w.creator_id.map(|id| id.to_string()),
+        "allowed_cidrs": paracord_db::webhooks::parse_allowed_cidrs(&w.allowed_cidrs),
         "created_at": w.created_at.to_rfc3339(),
     });
     if let Some(token) = token {
@@ -27,7 +34,7 @@ fn webhook_to_json(w: &paracord_db::webhooks::WebhookRow, token: Option<&str>) -
     v
 }
 
-async fn require_manage_webhooks(
+pub(crate) async fn require_manage_webhooks(
     state: &AppState,
     guild_id: i64,
     user_id: i64,
@@ -48,10 +55,88 @@ async fn require_manage_webhooks(
     Ok(())
 }
 
+const MAX_WEBHOOK_ALLOWED_CIDRS: usize = 100;
+
+/// Validate and normalize a source-IP allow-list into its stored JSON form.
+fn normalize_allowed_cidrs(raw: &[String]) -> Result<String, ApiError> {
+    if raw.len() > MAX_WEBHOOK_ALLOWED_CIDRS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_WEBHOOK_ALLOWED_CIDRS} allowed CIDRs per webhook"
+        )));
+    }
+    let mut normalized = Vec::with_capacity(raw.len());
+    for entry in raw {
+        let cidr = entry
+            .parse::<paracord_util::net::IpCidr>()
+            .map_err(|_| ApiError::BadRequest(format!("Invalid CIDR: {}", entry.trim())))?
+            .to_string();
+        if !normalized.contains(&cidr) {
+            normalized.push(cidr);
+        }
+    }
+    Ok(json!(normalized).to_string())
+}
+
+/// Enforce the webhook's source-IP allow-list, if it has one.
+fn ensure_source_ip_allowed(
+    webhook: &paracord_db::webhooks::WebhookRow,
+    headers: &HeaderMap,
+    peer_ip: Option<&str>,
+) -> Result<(), ApiError> {
+    let networks: Vec<paracord_util::net::IpCidr> =
+        paracord_db::webhooks::parse_allowed_cidrs(&webhook.allowed_cidrs)
+            .iter()
+            .filter_map(|raw| raw.parse().ok())
+            .collect();
+    if networks.is_empty() {
+        return Ok(());
+    }
+    let client_ip = crate::client_ip::resolve_client_ip(headers, peer_ip).unwrap_or_default();
+    let allowed = client_ip
+        .parse::<IpAddr>()
+        .is_ok_and(|ip| paracord_util::net::ip_in_any(&networks, &ip));
+    if !allowed {
+        tracing::warn!(
+            webhook_id = webhook.id,
+            client_ip = %client_ip,
+            "rejected webhook execution from disallowed source IP"
+        );
+        return Err(ApiError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Reject webhook creation once the channel or guild cap has been reached.
+pub(crate) async fn ensure_webhook_capacity(
+    state: &AppState,
+    guild_id: i64,
+    channel_id: i64,
+) -> Result<(), ApiError> {
+    let (max_per_channel, max_per_guild) = {
+        let settings = state.runtime.read().await;
+        (
+            settings.max_webhooks_per_channel,
+            settings.max_webhooks_per_guild,
+        )
+    };
+    let channel_count = paracord_db::webhooks::count_channel_webhooks(&state.db, channel_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let guild_count = paracord_db::webhooks::count_guild_webhooks(&state.db, guild_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if channel_count >= i64::from(max_per_channel) || guild_count >= i64::from(max_per_guild) {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(())
+}
+
 #[derive(Deserialize)]
 pub struct CreateWebhookRequest {
     pub name: String,
     pub channel_id: Option<String>,
+    /// Optional source-IP allow-list (CIDR notation) for executing the webhook.
+    pub allowed_cidrs: Option<Vec<String>>,
 }
 
 pub async fn create_webhook(
@@ -98,6 +183,10 @@ pub async fn create_webhook(
         ));
     }
 
+    let allowed_cidrs = normalize_allowed_cidrs(body.allowed_cidrs.as_deref().unwrap_or(&[]))?;
+
+    ensure_webhook_capacity(&state, guild_id, channel_id).await?;
+
     let id = paracord_util::snowflake::generate(1);
     let token = generate_webhook_token();
 
@@ -109,6 +198,7 @@ pub async fn create_webhook(
         name,
         &token,
         auth.user_id,
+        &allowed_cidrs,
     )
     .await
     .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
@@ -119,26 +209,36 @@ pub async fn create_webhook(
     ))
 }
 
+/// Listings stay a plain array; the count and the cap that applies to
+/// the listed scope travel in headers.
+fn webhook_listing(webhooks: Vec<Value>, limit: u32) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert(WEBHOOK_COUNT, HeaderValue::from(webhooks.len()));
+    headers.insert(WEBHOOK_LIMIT, HeaderValue::from(limit));
+    (headers, Json(webhooks)).into_response()
+}
+
 pub async fn list_guild_webhooks(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(guild_id): Path<i64>,
-) -> Result<Json<Value>, ApiError> {
+) -> Result<Response, ApiError> {
     require_manage_webhooks(&state, guild_id, auth.user_id).await?;
 
     let webhooks = paracord_db::webhooks::get_guild_webhooks(&state.db, guild_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let limit = state.runtime.read().await.max_webhooks_per_guild;
 
     let result: Vec<Value> = webhooks.iter().map(|w| webhook_to_json(w, None)).collect();
-    Ok(Json(json!(result)))
+    Ok(webhook_listing(result, limit))
 }
 
 pub async fn list_channel_webhooks(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(channel_id): Path<i64>,
-) -> Result<Json<Value>, ApiError> {
+) -> Result<Response, ApiError> {
     let channel = paracord_db::channels::get_channel(&state.db, channel_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
@@ -151,9 +251,10 @@ pub async fn list_channel_webhooks(
     let webhooks = paracord_db::webhooks::get_channel_webhooks(&state.db, channel_id)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let limit = state.runtime.read().await.max_webhooks_per_channel;
 
     let result: Vec<Value> = webhooks.iter().map(|w| webhook_to_json(w, None)).collect();
-    Ok(Json(json!(result)))
+    Ok(webhook_listing(result, limit))
 }
 
 pub async fn get_webhook(
@@ -174,6 +275,8 @@ pub async fn get_webhook(
 #[derive(Deserialize)]
 pub struct UpdateWebhookRequest {
     pub name: Option<String>,
+    /// Replaces the source-IP allow-list; an empty list removes the restriction.
+    pub allowed_cidrs: Option<Vec<String>>,
 }
 
 pub async fn update_webhook(
@@ -198,14 +301,56 @@ pub async fn update_webhook(
         }
     }
 
-    let updated =
-        paracord_db::webhooks::update_webhook(&state.db, webhook_id, body.name.as_deref())
-            .await
-            .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+    let allowed_cidrs = body
+        .allowed_cidrs
+        .as_deref()
+        .map(normalize_allowed_cidrs)
+        .transpose()?;
+
+    let updated = paracord_db::webhooks::update_webhook(
+        &state.db,
+        webhook_id,
+        body.name.as_deref(),
+        allowed_cidrs.as_deref(),
+    )
+    .await
+    .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
 
     Ok(Json(webhook_to_json(&updated, None)))
 }
 
+/// Issue a new token for the webhook. The new token is only returned here.
+pub async fn regenerate_webhook_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(webhook_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let webhook = paracord_db::webhooks::get_webhook(&state.db, webhook_id)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
+        .ok_or(ApiError::NotFound)?;
+
+    require_manage_webhooks(&state, webhook.space_id, auth.user_id).await?;
+
+    let token = generate_webhook_token();
+    let updated = paracord_db::webhooks::update_webhook_token(&state.db, webhook_id, &token)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    audit::log_action(
+        &state,
+        webhook.space_id,
+        auth.user_id,
+        audit::ACTION_WEBHOOK_UPDATE,
+        Some(webhook_id),
+        None,
+        Some(json!({ "token": "regenerated" })),
+    )
+    .await;
+
+    Ok(Json(webhook_to_json(&updated, Some(&token))))
+}
+
 pub async fn delete_webhook(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -232,6 +377,13 @@ pub struct ExecuteWebhookRequest {
     pub avatar_url: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct ExecuteWebhookQuery {
+    /// When false the message is created in the background and the request
+    /// returns 204 without a body. Defaults to true.
+    pub wait: Option<bool>,
+}
+
 fn format_github_event(event_type: &str, payload: &Value) -> String {
     match event_type {
         "push" => {
@@ -377,14 +529,33 @@ fn format_github_event(event_type: &str, payload: &Value) -> String {
 pub async fn execute_webhook(
     State(state): State<AppState>,
     Path((webhook_id, token)): Path<(i64, String)>,
+    Query(query): Query<ExecuteWebhookQuery>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
     headers: HeaderMap,
     body: Bytes,
-) -> Result<(StatusCode, Json<Value>), ApiError> {
+) -> Result<Response, ApiError> {
     let webhook = paracord_db::webhooks::get_webhook_by_id_and_token(&state.db, webhook_id, &token)
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!(e.to_string())))?
         .ok_or(ApiError::NotFound)?;
 
+    let peer_ip = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip().to_string());
+    ensure_source_ip_allowed(&webhook, &headers, peer_ip.as_deref())?;
+
+    let now = chrono::Utc::now().timestamp();
+    if !state
+        .rate_limits
+        .check(
+            &format!("webhook:execute:{}", webhook.id),
+            60,
+            state.config.webhook_executions_per_minute,
+            now,
+        )
+        .await
+    {
+        return Err(ApiError::RateLimited);
+    }
+
     // Check for GitHub webhook
     let (content, display_name) = if let Some(github_event) = headers.get("X-GitHub-Event") {
         let event_type = github_event.to_str().unwrap_or("unknown");
@@ -409,6 +580,31 @@ pub async fn execute_webhook(
         (content, name)
     };
 
+    if !query.wait.unwrap_or(true) {
+        tokio::spawn(async move {
+            if let Err(e) = post_webhook_message(&state, &webhook, &content, &display_name).await {
+                tracing::warn!(
+                    webhook_id = webhook.id,
+                    "webhook: queued execution failed: {e:?}"
+                );
+            }
+        });
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+
+    let (_, msg_json) = post_webhook_message(&state, &webhook, &content, &display_name).await?;
+
+    Ok((StatusCode::CREATED, Json(msg_json)).into_response())
+}
+
+/// Post `content` into the webhook's channel and broadcast it. Returns the new
+/// message id with its JSON payload.
+pub(crate) async fn post_webhook_message(
+    state: &AppState,
+    webhook: &paracord_db::webhooks::WebhookRow,
+    content: &str,
+    display_name: &str,
+) -> Result<(i64, Value), ApiError> {
     // Create the message using the webhook creator as the author
     let msg_id = paracord_util::snowflake::generate(1);
     let author_id = webhook.creator_id.unwrap_or(0);
@@ -418,7 +614,7 @@ pub async fn execute_webhook(
         msg_id,
         webhook.channel_id,
         author_id,
-        &content,
+        content,
         0, // message_type: 0 = default
         None,
     )
@@ -431,38 +627,37 @@ pub async fn execute_webhook(
         .flatten();
     let guild_id = channel.and_then(|c| c.guild_id());
 
-    let msg_json = json!({
-        "id": msg.id.to_string(),
-        "channel_id": msg.channel_id.to_string(),
-        "author": {
-            "id": webhook.id.to_string(),
-            "username": display_name,
-            "discriminator": 0,
-            "avatar_hash": null,
-            "bot": true,
-        },
-        "content": msg.content,
-        "pinned": msg.pinned,
-        "type": msg.message_type,
-        "message_type": msg.message_type,
-        "timestamp": msg.created_at.to_rfc3339(),
-        "created_at": msg.created_at.to_rfc3339(),
-        "edited_timestamp": null,
-        "edited_at": null,
-        "reference_id": null,
-        "attachments": [],
-        "reactions": [],
-        "webhook_id": webhook.id.to_string(),
-    });
+    let msg_json = webhook_message_json(state, webhook.id, display_name, &msg, guild_id).await;
 
     state
         .event_bus
         .dispatch("MESSAGE_CREATE", msg_json.clone(), guild_id);
 
-    Ok((StatusCode::CREATED, Json(msg_json)))
+    Ok((msg.id, msg_json))
+}
+
+/// Message payload with the webhook shown as the author.
+pub(crate) async fn webhook_message_json(
+    state: &AppState,
+    webhook_id: i64,
+    display_name: &str,
+    msg: &paracord_db::messages::MessageRow,
+    guild_id: Option<i64>,
+) -> Value {
+    let mut msg_json =
+        crate::routes::channels::message_to_json(state, msg, msg.author_id, guild_id).await;
+    msg_json["author"] = json!({
+        "id": webhook_id.to_string(),
+        "username": display_name,
+        "discriminator": 0,
+        "avatar_hash": null,
+        "bot": true,
+    });
+    msg_json["webhook_id"] = json!(webhook_id.to_string());
+    msg_json
 }
 
-fn generate_webhook_token() -> String {
+pub(crate) fn generate_webhook_token() -> String {
     use rand::RngCore;
     let mut bytes = [0_u8; 32];
     rand::rngs::OsRng.fill_bytes(&mut bytes);
@@ -0,0 +1,224 @@
+//! Security response headers for the API and the served web UI.
+//!
+//! API and metadata paths get a locked-down `default-src 'none'` CSP; every
+//! other path is assumed to be the web UI and gets the UI policy. The UI CSP,
+//! `Referrer-Policy` and `X-Frame-Options` can be overridden with
+//! `PARACORD_UI_CSP`, `PARACORD_REFERRER_POLICY` and `PARACORD_FRAME_OPTIONS`
+//! (the server exports these from the `[server]` config section).
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::OnceLock;
+
+const API_CONTENT_SECURITY_POLICY: &str =
+    "default-src 'none'; frame-ancestors 'none'; base-uri 'none'";
+const DEFAULT_REFERRER_POLICY: &str = "no-referrer";
+const DEFAULT_FRAME_OPTIONS: &str = "DENY";
+
+fn default_ui_content_security_policy(frame_ancestors: &str) -> String {
+    format!(
+        "default-src 'self'; base-uri 'self'; frame-ancestors {frame_ancestors}; object-src 'none'; script-src 'self'; style-src 'self' 'unsafe-inline' https://fonts.googleapis.com; font-src 'self' data: https://fonts.gstatic.com; img-src 'self' data: blob: https: http:; connect-src 'self' ws: wss: http: https:; media-src 'self' data: blob: https: http:"
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeaderPolicy {
+    pub ui_content_security_policy: HeaderValue,
+    pub referrer_policy: HeaderValue,
+    pub frame_options: HeaderValue,
+}
+
+impl Default for SecurityHeaderPolicy {
+    fn default() -> Self {
+        Self {
+            ui_content_security_policy: HeaderValue::from_str(&default_ui_content_security_policy(
+                "'none'",
+            ))
+            .expect("default CSP is a valid header value"),
+            referrer_policy: HeaderValue::from_static(DEFAULT_REFERRER_POLICY),
+            frame_options: HeaderValue::from_static(DEFAULT_FRAME_OPTIONS),
+        }
+    }
+}
+
+fn env_header_value(name: &str) -> Option<HeaderValue> {
+    let raw = std::env::var(name).ok()?;
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    match HeaderValue::from_str(trimmed) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            tracing::warn!("Ignoring {} with characters not allowed in a header", name);
+            None
+        }
+    }
+}
+
+impl SecurityHeaderPolicy {
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Some(frame_options) = env_header_value("PARACORD_FRAME_OPTIONS") {
+            // Keep the default UI CSP's frame-ancestors in step with the
+            // legacy header so the two never disagree.
+            if frame_options
+                .to_str()
+                .is_ok_and(|v| v.eq_ignore_ascii_case("SAMEORIGIN"))
+            {
+                policy.ui_content_security_policy =
+                    HeaderValue::from_str(&default_ui_content_security_policy("'self'"))
+                        .expect("default CSP is a valid header value");
+            }
+            policy.frame_options = frame_options;
+        }
+        if let Some(csp) = env_header_value("PARACORD_UI_CSP") {
+            policy.ui_content_security_policy = csp;
+        }
+        if let Some(referrer_policy) = env_header_value("PARACORD_REFERRER_POLICY") {
+            policy.referrer_policy = referrer_policy;
+        }
+        policy
+    }
+}
+
+static POLICY: OnceLock<SecurityHeaderPolicy> = OnceLock::new();
+
+fn policy() -> &'static SecurityHeaderPolicy {
+    POLICY.get_or_init(SecurityHeaderPolicy::from_env)
+}
+
+fn is_api_path(path: &str) -> bool {
+    path == "/health"
+        || path == "/healthz"
+        || path == "/readyz"
+        || path == "/metrics"
+        || path.starts_with("/api/")
+        || path.starts_with("/_paracord/")
+        || path.starts_with("/.well-known/")
+}
+
+pub fn apply_security_headers(
+    headers: &mut HeaderMap,
+    path: &str,
+    is_https: bool,
+    policy: &SecurityHeaderPolicy,
+) {
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(header::X_FRAME_OPTIONS, policy.frame_options.clone());
+    headers.insert(header::REFERRER_POLICY, policy.referrer_policy.clone());
+    headers.insert(
+        HeaderName::from_static("permissions-policy"),
+        HeaderValue::from_static("camera=(), microphone=(), geolocation=()"),
+    );
+    headers.insert(
+        HeaderName::from_static("cross-origin-opener-policy"),
+        HeaderValue::from_static("same-origin"),
+    );
+    headers.insert(
+        HeaderName::from_static("cross-origin-resource-policy"),
+        HeaderValue::from_static("same-origin"),
+    );
+    if is_api_path(path) {
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static(API_CONTENT_SECURITY_POLICY),
+        );
+    } else {
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            policy.ui_content_security_policy.clone(),
+        );
+    }
+    if is_https {
+        headers.insert(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_static("max-age=31536000; includeSubDomains"),
+        );
+    }
+}
+
+pub async fn security_headers_middleware(req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let is_https = req
+        .headers()
+        .get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("https"))
+        .unwrap_or(false);
+
+    let mut response = next.run(req).await;
+    apply_security_headers(response.headers_mut(), &path, is_https, policy());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn headers_for(app: Router, path: &str) -> HeaderMap {
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap().headers().clone()
+    }
+
+    #[tokio::test]
+    async fn api_and_static_responses_carry_security_headers() {
+        let static_dir = tempfile::tempdir().unwrap();
+        std::fs::write(static_dir.path().join("index.html"), "<html></html>").unwrap();
+        let app = Router::new()
+            .route("/api/v1/ping", get(|| async { "pong" }))
+            .fallback_service(tower_http::services::ServeDir::new(static_dir.path()))
+            .layer(from_fn(security_headers_middleware));
+
+        let api = headers_for(app.clone(), "/api/v1/ping").await;
+        let ui = headers_for(app, "/index.html").await;
+        for headers in [&api, &ui] {
+            assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+            assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+            assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+        }
+        assert_eq!(
+            api[header::CONTENT_SECURITY_POLICY],
+            API_CONTENT_SECURITY_POLICY
+        );
+        let ui_csp = ui[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+        assert!(ui_csp.contains("script-src 'self'"));
+        assert!(ui_csp.contains("frame-ancestors 'none'"));
+    }
+
+    #[test]
+    fn configured_policy_applies_while_api_keeps_strict_csp() {
+        let policy = SecurityHeaderPolicy {
+            ui_content_security_policy: HeaderValue::from_static("default-src 'self'"),
+            referrer_policy: HeaderValue::from_static("strict-origin-when-cross-origin"),
+            frame_options: HeaderValue::from_static("SAMEORIGIN"),
+        };
+
+        let mut ui = HeaderMap::new();
+        apply_security_headers(&mut ui, "/channels/1", true, &policy);
+        assert_eq!(ui[header::CONTENT_SECURITY_POLICY], "default-src 'self'");
+        assert_eq!(
+            ui[header::REFERRER_POLICY],
+            "strict-origin-when-cross-origin"
+        );
+        assert_eq!(ui[header::X_FRAME_OPTIONS], "SAMEORIGIN");
+        assert!(ui.contains_key(header::STRICT_TRANSPORT_SECURITY));
+
+        let mut api = HeaderMap::new();
+        apply_security_headers(&mut api, "/api/v1/users/@me", false, &policy);
+        assert_eq!(
+            api[header::CONTENT_SECURITY_POLICY],
+            API_CONTENT_SECURITY_POLICY
+        );
+        assert!(!api.contains_key(header::STRICT_TRANSPORT_SECURITY));
+    }
+}
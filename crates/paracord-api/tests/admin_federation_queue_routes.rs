@@ -0,0 +1,231 @@
+use axum::http::{Method, StatusCode};
+use chrono::Utc;
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_user_with_flags, TestContext};
+
+async fn enqueue(db: &paracord_db::DbPool, event_id: &str, now_ms: i64) -> anyhow::Result<()> {
+    paracord_db::federation::enqueue_outbound_event(
+        db,
+        "peer.example",
+        event_id,
+        "!room:local.example",
+        "m.message",
+        "@alice:local.example",
+        "local.example",
+        now_ms,
+        &json!({ "body": "hello" }),
+        1,
+        None,
+        &json!({}),
+        now_ms,
+    )
+    .await?;
+    Ok(())
+}
+
+fn entry_for<'a>(payload: &'a Value, event_id: &str) -> &'a Value {
+    payload
+        .as_array()
+        .expect("queue list")
+        .iter()
+        .find(|entry| entry["event_id"] == event_id)
+        .expect("queue entry")
+}
+
+#[tokio::test]
+async fn federation_queue_lists_pending_and_dead_lettered_entries() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let now_ms = Utc::now().timestamp_millis();
+    enqueue(&ctx.db, "$pending", now_ms).await?;
+    enqueue(&ctx.db, "$stuck", now_ms).await?;
+    for _ in 0..3 {
+        paracord_db::federation::mark_outbound_event_retry(
+            &ctx.db,
+            "peer.example",
+            "$stuck",
+            now_ms + 60_000,
+            Some("connection refused"),
+            now_ms,
+        )
+        .await?;
+    }
+    let dead = paracord_db::federation::dead_letter_expired_outbound_events(
+        &ctx.db, now_ms, 3, 86_400_000,
+    )
+    .await?;
+    assert_eq!(dead, 1);
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            "/api/v1/admin/federation/queue",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload.as_array().map(Vec::len), Some(2));
+
+    let pending = entry_for(&payload, "$pending");
+    assert_eq!(pending["state"], "pending");
+    assert_eq!(pending["destination_server"], "peer.example");
+    assert_eq!(pending["attempt_count"], 0);
+    assert!(pending["next_attempt_at"].is_string(), "{pending}");
+    assert!(pending["dead_lettered_at"].is_null(), "{pending}");
+
+    let stuck = entry_for(&payload, "$stuck");
+    assert_eq!(stuck["state"], "dead_lettered");
+    assert_eq!(stuck["attempt_count"], 3);
+    assert_eq!(stuck["last_error"], "connection refused");
+    assert!(stuck["next_attempt_at"].is_null(), "{stuck}");
+    assert!(stuck["dead_lettered_at"].is_string(), "{stuck}");
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            "/api/v1/admin/federation/queue?state=dead_lettered",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload.as_array().map(Vec::len), Some(1));
+    assert_eq!(payload[0]["event_id"], "$stuck");
+
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            "/api/v1/admin/federation/queue?state=stuck",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, user_token) = create_user_with_flags(&ctx.db, 0).await?;
+    let (status, _) = ctx
+        .request_json_as(
+            &user_token,
+            Method::GET,
+            "/api/v1/admin/federation/queue",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    Ok(())
+}
+
+#[tokio::test]
+async fn retrying_a_dead_letter_resets_its_schedule() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let long_ago_ms = Utc::now().timestamp_millis() - 2 * 86_400_000;
+    enqueue(&ctx.db, "$old", long_ago_ms).await?;
+    paracord_db::federation::mark_outbound_event_retry(
+        &ctx.db,
+        "peer.example",
+        "$old",
+        long_ago_ms + 60_000,
+        Some("timed out"),
+        long_ago_ms,
+    )
+    .await?;
+    let now_ms = Utc::now().timestamp_millis();
+    paracord_db::federation::dead_letter_expired_outbound_events(&ctx.db, now_ms, 12, 86_400_000)
+        .await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            "/api/v1/admin/federation/queue?state=dead_lettered",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    let id = payload[0]["id"].as_str().expect("queue id").to_string();
+
+    let (status, retried) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::POST,
+            &format!("/api/v1/admin/federation/queue/{id}/retry"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{retried}");
+    assert_eq!(retried["state"], "pending");
+    assert_eq!(retried["attempt_count"], 0);
+    assert!(retried["dead_lettered_at"].is_null(), "{retried}");
+    let next_attempt_at =
+        chrono::DateTime::parse_from_rfc3339(retried["next_attempt_at"].as_str().unwrap())?;
+    assert!(next_attempt_at.timestamp_millis() >= now_ms, "{retried}");
+
+    // The retry window restarts, so the entry is not dead-lettered again for
+    // being older than the maximum age.
+    let dead = paracord_db::federation::dead_letter_expired_outbound_events(
+        &ctx.db,
+        Utc::now().timestamp_millis(),
+        12,
+        86_400_000,
+    )
+    .await?;
+    assert_eq!(dead, 0);
+
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::POST,
+            &format!("/api/v1/admin/federation/queue/{id}/retry"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn enqueueing_a_dead_lettered_event_requeues_it() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let now_ms = Utc::now().timestamp_millis();
+    enqueue(&ctx.db, "$stuck", now_ms).await?;
+    for _ in 0..3 {
+        paracord_db::federation::mark_outbound_event_retry(
+            &ctx.db,
+            "peer.example",
+            "$stuck",
+            now_ms + 60_000,
+            Some("connection refused"),
+            now_ms,
+        )
+        .await?;
+    }
+    let dead = paracord_db::federation::dead_letter_expired_outbound_events(
+        &ctx.db, now_ms, 3, 86_400_000,
+    )
+    .await?;
+    assert_eq!(dead, 1);
+
+    enqueue(&ctx.db, "$stuck", now_ms + 1).await?;
+    let entries =
+        paracord_db::federation::list_outbound_queue_entries(&ctx.db, Some(false), None, 10)
+            .await?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].event_id, "$stuck");
+    assert_eq!(entries[0].attempt_count, 0);
+    assert_eq!(entries[0].next_attempt_at_ms, now_ms + 1);
+    assert!(entries[0].dead_lettered_at_ms.is_none());
+
+    // The next sweep leaves it alone since its attempts were reset.
+    let dead = paracord_db::federation::dead_letter_expired_outbound_events(
+        &ctx.db,
+        now_ms + 1,
+        3,
+        86_400_000,
+    )
+    .await?;
+    assert_eq!(dead, 0);
+    Ok(())
+}
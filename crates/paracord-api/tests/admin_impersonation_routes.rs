@@ -0,0 +1,197 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_user_with_flags, TestContext, JWT_SECRET};
+
+impl TestContext {
+    async fn impersonate(&self, user_id: i64, body: Value) -> anyhow::Result<(StatusCode, Value)> {
+        self.request_json_as(
+            &self.token,
+            Method::POST,
+            &format!("/api/v1/admin/users/{user_id}/impersonate"),
+            Some(body),
+        )
+        .await
+    }
+
+    async fn security_events(
+        &self,
+        action: &str,
+    ) -> anyhow::Result<Vec<paracord_db::security_events::SecurityEventRow>> {
+        Ok(paracord_db::security_events::list_events(&self.db, Some(action), None, 50).await?)
+    }
+}
+
+#[tokio::test]
+async fn impersonation_token_carries_flag_and_acts_as_target() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (user_id, _) = create_user_with_flags(&ctx.db, 0).await?;
+
+    let (status, payload) = ctx
+        .impersonate(user_id, json!({ "reason": "ticket 4821" }))
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    assert_eq!(payload["impersonation"], true);
+    let token = payload["token"].as_str().unwrap().to_string();
+
+    let claims = paracord_core::auth::validate_token(&token, JWT_SECRET)?;
+    assert_eq!(claims.sub, user_id);
+    assert_eq!(claims.imp, Some(ctx.user_id));
+    assert!(claims.exp - claims.iat <= 15 * 60);
+
+    let (status, me) = ctx
+        .request_json_as(&token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me["id"], user_id.to_string());
+
+    let started = ctx.security_events("admin.impersonation.start").await?;
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].actor_user_id, Some(ctx.user_id));
+    assert_eq!(started[0].target_user_id, Some(user_id));
+    assert_eq!(
+        started[0].details.as_ref().unwrap()["reason"],
+        "ticket 4821"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn impersonated_actions_are_audited_against_both_identities() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (user_id, _) = create_user_with_flags(&ctx.db, 0).await?;
+
+    let (status, payload) = ctx.impersonate(user_id, json!({})).await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    let token = payload["token"].as_str().unwrap().to_string();
+
+    let (status, _) = ctx
+        .request_json_as(&token, Method::GET, "/api/v1/users/@me/dms", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = ctx
+        .request_json_as(
+            &token,
+            Method::PATCH,
+            "/api/v1/users/@me",
+            Some(json!({ "display_name": "Changed By Support" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    // Reads are audited too: viewing a user's DMs is as sensitive as writing.
+    let actions = ctx.security_events("admin.impersonation.action").await?;
+    assert_eq!(actions.len(), 2);
+    for action in &actions {
+        assert_eq!(action.actor_user_id, Some(ctx.user_id));
+        assert_eq!(action.target_user_id, Some(user_id));
+    }
+    let mut requests: Vec<(String, String)> = actions
+        .iter()
+        .map(|action| {
+            let details = action.details.as_ref().unwrap();
+            (
+                details["method"].as_str().unwrap().to_string(),
+                details["path"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    requests.sort();
+    assert_eq!(
+        requests,
+        [
+            ("GET".to_string(), "/api/v1/users/@me/dms".to_string()),
+            ("PATCH".to_string(), "/api/v1/users/@me".to_string()),
+        ]
+    );
+
+    let (status, _) = ctx
+        .request_json_as(
+            &token,
+            Method::POST,
+            "/api/v1/admin/impersonation/stop",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let stopped = ctx.security_events("admin.impersonation.stop").await?;
+    assert_eq!(stopped.len(), 1);
+    assert_eq!(stopped[0].actor_user_id, Some(ctx.user_id));
+    assert_eq!(stopped[0].target_user_id, Some(user_id));
+
+    let (status, _) = ctx
+        .request_json_as(&token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn impersonating_an_admin_requires_confirmation() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (other_admin_id, _) =
+        create_user_with_flags(&ctx.db, paracord_core::USER_FLAG_ADMIN).await?;
+
+    let (status, _) = ctx.impersonate(other_admin_id, json!({})).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        ctx.security_events("admin.impersonation.denied")
+            .await?
+            .len(),
+        1
+    );
+    assert!(ctx
+        .security_events("admin.impersonation.start")
+        .await?
+        .is_empty());
+
+    let (status, payload) = ctx
+        .impersonate(other_admin_id, json!({ "confirm_admin": true }))
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    let token = payload["token"].as_str().unwrap().to_string();
+
+    // The impersonated admin's privileges are not usable through the token.
+    let (status, _) = ctx
+        .request_json_as(&token, Method::GET, "/api/v1/admin/stats", None)
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn only_admins_can_impersonate() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (_, user_token) = create_user_with_flags(&ctx.db, 0).await?;
+
+    let (status, _) = ctx
+        .request_json_as(
+            &user_token,
+            Method::POST,
+            &format!("/api/v1/admin/users/{}/impersonate", ctx.user_id),
+            Some(json!({})),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = ctx.impersonate(ctx.user_id, json!({})).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = ctx
+        .request_json_as(
+            &user_token,
+            Method::POST,
+            "/api/v1/admin/impersonation/stop",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
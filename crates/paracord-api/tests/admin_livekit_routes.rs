@@ -0,0 +1,136 @@
+use axum::{
+    http::{header, HeaderMap, Method, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_user_with_flags, use_livekit_http_url, TestContext};
+
+/// Admin context whose voice manager talks to LiveKit at `livekit_http_url`.
+async fn livekit_context(livekit_http_url: &str) -> anyhow::Result<TestContext> {
+    let ctx = TestContext::with_state(|state| {
+        use_livekit_http_url(state, livekit_http_url);
+    })
+    .await?;
+    ctx.promote_to_admin().await?;
+    Ok(ctx)
+}
+
+impl TestContext {
+    async fn security_events(
+        &self,
+        action: &str,
+    ) -> anyhow::Result<Vec<paracord_db::security_events::SecurityEventRow>> {
+        Ok(paracord_db::security_events::list_events(&self.db, Some(action), None, 50).await?)
+    }
+}
+
+/// Stand-in for LiveKit's RoomService that only accepts admin tokens signed
+/// with `api_key`/`api_secret`. Returns its base URL.
+async fn spawn_mock_livekit(
+    api_key: &'static str,
+    api_secret: &'static str,
+) -> anyhow::Result<String> {
+    let app = Router::new().route(
+        "/twirp/livekit.RoomService/ListRooms",
+        post(move |headers: HeaderMap| async move {
+            let token = headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .unwrap_or_default();
+            let mut validation = Validation::new(Algorithm::HS256);
+            validation.set_issuer(&[api_key]);
+            let key = DecodingKey::from_secret(api_secret.as_bytes());
+            match decode::<Value>(token, &key, &validation) {
+                Ok(_) => (StatusCode::OK, Json(json!({ "rooms": [] }))),
+                Err(_) => (
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({ "code": "unauthenticated" })),
+                ),
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    Ok(format!("http://{addr}"))
+}
+
+#[tokio::test]
+async fn livekit_credentials_are_swapped_after_a_passing_health_check() -> anyhow::Result<()> {
+    let livekit_url = spawn_mock_livekit("rotated-key", "rotated-secret").await?;
+    let ctx = livekit_context(&livekit_url).await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PUT,
+            "/api/v1/admin/livekit/credentials",
+            Some(json!({ "api_key": "rotated-key", "api_secret": "rotated-secret" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["api_key"], "rotated-key");
+    assert_eq!(payload["livekit_available"], true);
+    assert!(payload.get("api_secret").is_none());
+
+    let livekit = ctx.state.voice.livekit();
+    assert_eq!(livekit.api_key, "rotated-key");
+    assert_eq!(livekit.api_secret, "rotated-secret");
+    assert_eq!(livekit.http_url, livekit_url);
+    livekit.check_health().await?;
+
+    let events = ctx
+        .security_events("admin.livekit.credentials.update")
+        .await?;
+    assert_eq!(events.len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn livekit_credentials_are_kept_when_the_health_check_fails() -> anyhow::Result<()> {
+    let livekit_url = spawn_mock_livekit("rotated-key", "rotated-secret").await?;
+    let ctx = livekit_context(&livekit_url).await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PUT,
+            "/api/v1/admin/livekit/credentials",
+            Some(json!({ "api_key": "rotated-key", "api_secret": "wrong-secret" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+
+    let livekit = ctx.state.voice.livekit();
+    assert_eq!(livekit.api_key, "lk-test-key");
+    assert_eq!(livekit.api_secret, "lk-test-secret");
+    let events = ctx
+        .security_events("admin.livekit.credentials.rejected")
+        .await?;
+    assert_eq!(events.len(), 1);
+    assert!(ctx
+        .security_events("admin.livekit.credentials.update")
+        .await?
+        .is_empty());
+
+    let (_, user_token) = create_user_with_flags(&ctx.db, 0).await?;
+    let (status, _) = ctx
+        .request_json_as(
+            &user_token,
+            Method::PUT,
+            "/api/v1/admin/livekit/credentials",
+            Some(json!({ "api_key": "rotated-key", "api_secret": "rotated-secret" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(ctx.state.voice.livekit().api_key, "lk-test-key");
+    Ok(())
+}
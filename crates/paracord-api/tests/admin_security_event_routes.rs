@@ -0,0 +1,202 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_user_with_flags, TestContext};
+
+async fn insert_event(
+    db: &paracord_db::DbPool,
+    action: &str,
+    actor_user_id: Option<i64>,
+    target_user_id: Option<i64>,
+    details: Option<Value>,
+) -> anyhow::Result<i64> {
+    let id = paracord_util::snowflake::generate(1);
+    paracord_db::security_events::create_event(
+        db,
+        id,
+        actor_user_id,
+        action,
+        target_user_id,
+        None,
+        None,
+        None,
+        Some("127.0.0.1"),
+        details.as_ref(),
+    )
+    .await?;
+    Ok(id)
+}
+
+fn event_ids(payload: &Value) -> Vec<String> {
+    payload
+        .as_array()
+        .expect("event list")
+        .iter()
+        .map(|event| event["id"].as_str().expect("event id").to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn security_events_filter_by_type_and_user() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (alice_id, _) = create_user_with_flags(&ctx.db, 0).await?;
+    let (bob_id, _) = create_user_with_flags(&ctx.db, 0).await?;
+
+    let alice_login = insert_event(&ctx.db, "auth.login", Some(alice_id), None, None).await?;
+    let bob_login = insert_event(&ctx.db, "auth.login", Some(bob_id), None, None).await?;
+    let alice_targeted = insert_event(
+        &ctx.db,
+        "admin.user.flags.update",
+        Some(bob_id),
+        Some(alice_id),
+        None,
+    )
+    .await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            "/api/v1/admin/security-events?type=auth.login",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(
+        event_ids(&payload),
+        vec![bob_login.to_string(), alice_login.to_string()]
+    );
+
+    // Matches events where the user is the actor or the target.
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/security-events?user_id={alice_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(
+        event_ids(&payload),
+        vec![alice_targeted.to_string(), alice_login.to_string()]
+    );
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/security-events?user_id={alice_id}&type=auth.login"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(event_ids(&payload), vec![alice_login.to_string()]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn security_events_paginate_with_before_cursor() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (user_id, _) = create_user_with_flags(&ctx.db, 0).await?;
+
+    let mut ids = Vec::new();
+    for _ in 0..5 {
+        ids.push(insert_event(&ctx.db, "auth.login", Some(user_id), None, None).await?);
+    }
+    ids.reverse();
+    let expected: Vec<String> = ids.iter().map(i64::to_string).collect();
+
+    let (status, first_page) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/security-events?user_id={user_id}&limit=2"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{first_page}");
+    assert_eq!(event_ids(&first_page), expected[..2].to_vec());
+
+    let cursor = &expected[1];
+    let (status, second_page) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/security-events?user_id={user_id}&limit=2&before={cursor}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{second_page}");
+    assert_eq!(event_ids(&second_page), expected[2..4].to_vec());
+
+    let cursor = &expected[3];
+    let (status, last_page) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/security-events?user_id={user_id}&limit=2&before={cursor}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{last_page}");
+    assert_eq!(event_ids(&last_page), expected[4..].to_vec());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn security_events_redact_sensitive_details() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (user_id, _) = create_user_with_flags(&ctx.db, 0).await?;
+    insert_event(
+        &ctx.db,
+        "auth.token.create",
+        Some(user_id),
+        None,
+        Some(json!({
+            "token_id": "42",
+            "token": "pat_secret_value",
+            "nested": { "refresh_token": "r-secret", "reason": "rotation" },
+        })),
+    )
+    .await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/security-events?user_id={user_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    let details = &payload[0]["details"];
+    assert_eq!(details["token_id"], "42");
+    assert_eq!(details["token"], "[redacted]");
+    assert_eq!(details["nested"]["refresh_token"], "[redacted]");
+    assert_eq!(details["nested"]["reason"], "rotation");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn security_events_require_server_admin() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (_, user_token) = create_user_with_flags(&ctx.db, 0).await?;
+
+    let (status, _) = ctx
+        .request_json_as(
+            &user_token,
+            Method::GET,
+            "/api/v1/admin/security-events",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    Ok(())
+}
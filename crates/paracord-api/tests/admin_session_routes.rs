@@ -0,0 +1,136 @@
+use axum::http::{Method, StatusCode};
+
+mod common;
+
+use common::{create_user_with_flags, TestContext};
+
+impl TestContext {
+    async fn security_events(
+        &self,
+        action: &str,
+    ) -> anyhow::Result<Vec<paracord_db::security_events::SecurityEventRow>> {
+        Ok(paracord_db::security_events::list_events(&self.db, Some(action), None, 50).await?)
+    }
+}
+
+#[tokio::test]
+async fn admin_revoke_all_logs_the_user_out_everywhere() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (user_id, user_token) = create_user_with_flags(&ctx.db, 0).await?;
+    paracord_db::personal_access_tokens::create_token(
+        &ctx.db,
+        paracord_util::snowflake::generate(1),
+        user_id,
+        "ci",
+        &paracord_db::bot_applications::hash_token("pat-secret"),
+        "read",
+        None,
+    )
+    .await?;
+
+    let (status, sessions) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/users/{user_id}/sessions"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{sessions}");
+    assert_eq!(sessions.as_array().unwrap().len(), 1);
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::POST,
+            &format!("/api/v1/admin/users/{user_id}/sessions/revoke-all"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["revoked"], 1);
+    assert_eq!(payload["tokens_revoked"], 1);
+    assert!(
+        paracord_db::personal_access_tokens::get_user_tokens(&ctx.db, user_id)
+            .await?
+            .is_empty()
+    );
+
+    let (status, _) = ctx
+        .request_json_as(&user_token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, sessions) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::GET,
+            &format!("/api/v1/admin/users/{user_id}/sessions"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(sessions.as_array().unwrap().is_empty());
+
+    let events = ctx
+        .security_events("admin.user.sessions.revoke_all")
+        .await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].actor_user_id, Some(ctx.user_id));
+    assert_eq!(events[0].target_user_id, Some(user_id));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn only_admins_can_manage_other_users_sessions() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (user_id, user_token) = create_user_with_flags(&ctx.db, 0).await?;
+    let (other_id, other_token) = create_user_with_flags(&ctx.db, 0).await?;
+
+    let (status, _) = ctx
+        .request_json_as(
+            &user_token,
+            Method::GET,
+            &format!("/api/v1/admin/users/{other_id}/sessions"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = ctx
+        .request_json_as(
+            &user_token,
+            Method::POST,
+            &format!("/api/v1/admin/users/{other_id}/sessions/revoke-all"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = ctx
+        .request_json_as(&other_token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::POST,
+            &format!("/api/v1/admin/users/{}/sessions/revoke-all", ctx.user_id),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::POST,
+            &format!("/api/v1/admin/users/{}/sessions/revoke-all", user_id + 1),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
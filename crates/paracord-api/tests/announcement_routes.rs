@@ -0,0 +1,270 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_guild, create_text_channel, TestContext};
+
+#[tokio::test]
+async fn followed_announcement_channel_delivers_until_unfollowed() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let source_guild_id = create_guild(&ctx, "News Guild").await?;
+    let (status, announcements) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{source_guild_id}/channels"),
+            Some(json!({
+                "name": "announcements",
+                "channel_type": 5,
+                "parent_id": Value::Null,
+                "required_role_ids": Value::Null,
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{announcements}");
+    let source_id = announcements["id"]
+        .as_str()
+        .context("channel id")?
+        .to_string();
+    let follower_guild_id = create_guild(&ctx, "Reader Guild").await?;
+    let target_id = create_text_channel(&ctx, &follower_guild_id, "news-feed").await?;
+
+    // Only announcement channels can be followed.
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{target_id}/followers"),
+            Some(json!({ "webhook_channel_id": source_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let followers_path = format!("/api/v1/channels/{source_id}/followers");
+    let (status, follower) = ctx
+        .request_json(
+            Method::POST,
+            &followers_path,
+            Some(json!({ "webhook_channel_id": target_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{follower}");
+    assert_eq!(follower["webhook_channel_id"], target_id.as_str());
+    let follower_id = follower["id"].as_str().context("follower id")?.to_string();
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &followers_path,
+            Some(json!({ "webhook_channel_id": target_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let publish = |content: &'static str| {
+        let ctx = &ctx;
+        let source_id = source_id.clone();
+        async move {
+            let (status, message) = ctx
+                .request_json(
+                    Method::POST,
+                    &format!("/api/v1/channels/{source_id}/messages"),
+                    Some(json!({ "content": content })),
+                )
+                .await?;
+            assert_eq!(status, StatusCode::CREATED);
+            let message_id = message["id"].as_str().context("message id")?.to_string();
+            ctx.request_json(
+                Method::POST,
+                &format!("/api/v1/channels/{source_id}/messages/{message_id}/crosspost"),
+                None,
+            )
+            .await
+        }
+    };
+    let target_messages = || async {
+        let (status, messages) = ctx
+            .request_json(
+                Method::GET,
+                &format!("/api/v1/channels/{target_id}/messages"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK);
+        anyhow::Ok(
+            messages
+                .as_array()
+                .context("messages array")?
+                .iter()
+                .filter_map(|m| m["content"].as_str().map(str::to_string))
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let (status, published) = publish("launch day").await?;
+    assert_eq!(status, StatusCode::OK, "{published}");
+    assert_eq!(
+        published["flags"].as_i64().unwrap_or_default()
+            & i64::from(paracord_core::MESSAGE_FLAG_CROSSPOSTED),
+        i64::from(paracord_core::MESSAGE_FLAG_CROSSPOSTED)
+    );
+    assert_eq!(target_messages().await?, vec!["launch day".to_string()]);
+
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            &format!("{followers_path}/{follower_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = publish("after unfollow").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(target_messages().await?, vec!["launch day".to_string()]);
+
+    Ok(())
+}
+
+/// Creates an announcement channel followed by a text channel in another
+/// guild. Returns `(source_id, target_id)`.
+async fn followed_announcement_channel(ctx: &TestContext) -> anyhow::Result<(String, String)> {
+    let source_guild_id = create_guild(ctx, "News Guild").await?;
+    let (status, announcements) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{source_guild_id}/channels"),
+            Some(json!({
+                "name": "announcements",
+                "channel_type": 5,
+                "parent_id": Value::Null,
+                "required_role_ids": Value::Null,
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{announcements}");
+    let source_id = announcements["id"]
+        .as_str()
+        .context("channel id")?
+        .to_string();
+    let follower_guild_id = create_guild(ctx, "Reader Guild").await?;
+    let target_id = create_text_channel(ctx, &follower_guild_id, "news-feed").await?;
+    let (status, follower) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{source_id}/followers"),
+            Some(json!({ "webhook_channel_id": target_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{follower}");
+
+    Ok((source_id, target_id))
+}
+
+#[tokio::test]
+async fn crosspost_edits_and_deletes_reach_follower_copies() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (source_id, target_id) = followed_announcement_channel(&ctx).await?;
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{source_id}/messages"),
+            Some(json!({ "content": "launch day" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let message_path = format!(
+        "/api/v1/channels/{source_id}/messages/{}",
+        message["id"].as_str().context("message id")?
+    );
+    let (status, published) = ctx
+        .request_json(Method::POST, &format!("{message_path}/crosspost"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{published}");
+
+    let target_messages = || async {
+        let (status, messages) = ctx
+            .request_json(
+                Method::GET,
+                &format!("/api/v1/channels/{target_id}/messages"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK);
+        anyhow::Ok(messages.as_array().context("messages array")?.clone())
+    };
+    let mirrors = target_messages().await?;
+    assert_eq!(mirrors.len(), 1);
+    let mirror_id = mirrors[0]["id"].clone();
+
+    let (status, edited) = ctx
+        .request_json(
+            Method::PATCH,
+            &message_path,
+            Some(json!({ "content": "launch moved to friday" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{edited}");
+    let mirrors = target_messages().await?;
+    assert_eq!(mirrors.len(), 1);
+    assert_eq!(mirrors[0]["id"], mirror_id);
+    assert_eq!(mirrors[0]["content"], "launch moved to friday");
+    assert!(!mirrors[0]["edited_timestamp"].is_null());
+
+    let (status, _) = ctx
+        .request_json(Method::DELETE, &message_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(target_messages().await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn bulk_delete_removes_follower_copies() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (source_id, target_id) = followed_announcement_channel(&ctx).await?;
+
+    let mut message_ids = Vec::new();
+    for content in ["first post", "second post"] {
+        let (status, message) = ctx
+            .request_json(
+                Method::POST,
+                &format!("/api/v1/channels/{source_id}/messages"),
+                Some(json!({ "content": content })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED);
+        let message_id = message["id"].as_str().context("message id")?.to_string();
+        let (status, published) = ctx
+            .request_json(
+                Method::POST,
+                &format!("/api/v1/channels/{source_id}/messages/{message_id}/crosspost"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK, "{published}");
+        message_ids.push(message_id);
+    }
+
+    let target_path = format!("/api/v1/channels/{target_id}/messages");
+    let (status, mirrors) = ctx.request_json(Method::GET, &target_path, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(mirrors.as_array().context("messages array")?.len(), 2);
+
+    let (status, body) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{source_id}/messages/bulk-delete"),
+            Some(json!({ "message_ids": message_ids })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["deleted"], 2);
+
+    let (status, mirrors) = ctx.request_json(Method::GET, &target_path, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(mirrors.as_array().context("messages array")?.is_empty());
+
+    Ok(())
+}
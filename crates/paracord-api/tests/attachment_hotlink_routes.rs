@@ -0,0 +1,266 @@
+use anyhow::Context;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_guild, create_text_channel, TestContext};
+
+async fn hotlink_context(
+    inline_origins: &[&str],
+    inline_token_ttl_seconds: u64,
+) -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        state.config.attachment_inline_origins = inline_origins
+            .iter()
+            .map(|origin| origin.to_string())
+            .collect();
+        state.config.attachment_inline_token_ttl_seconds = inline_token_ttl_seconds;
+    })
+    .await
+}
+
+/// Post a message carrying a stored PNG and return the attachment id.
+async fn post_image(ctx: &TestContext) -> anyhow::Result<i64> {
+    let guild_id = create_guild(ctx, "Hotlink Guild").await?;
+    let channel_id = create_text_channel(ctx, &guild_id, "images").await?;
+
+    let attachment_id = paracord_util::snowflake::generate(1);
+    ctx.state
+        .storage_backend
+        .store(
+            &format!("attachments/{attachment_id}.png"),
+            b"\x89PNG\r\n\x1a\n",
+        )
+        .await?;
+    paracord_db::attachments::create_attachment(
+        &ctx.state.db,
+        attachment_id,
+        None,
+        "photo.png",
+        Some("image/png"),
+        8,
+        &format!("/api/v1/attachments/{attachment_id}"),
+        None,
+        None,
+        Some(ctx.user_id),
+        Some(channel_id.parse()?),
+        Some(Utc::now() + Duration::minutes(10)),
+        None,
+    )
+    .await?;
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "look", "attachment_ids": [attachment_id.to_string()] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    Ok(attachment_id)
+}
+
+/// Download `path` with the given extra headers and return its
+/// `Content-Disposition`.
+async fn download_disposition(
+    ctx: &TestContext,
+    path: &str,
+    headers: &[(header::HeaderName, &str)],
+) -> anyhow::Result<String> {
+    let mut builder = Request::builder()
+        .method(Method::GET)
+        .uri(path)
+        .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token));
+    for (name, value) in headers {
+        builder = builder.header(name, *value);
+    }
+    let response = ctx
+        .app
+        .clone()
+        .oneshot(builder.body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(response
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .context("download should set Content-Disposition")?
+        .to_str()?
+        .to_string())
+}
+
+#[tokio::test]
+async fn inline_images_are_only_served_inline_to_allowed_origins() -> anyhow::Result<()> {
+    let ctx = hotlink_context(&["https://chat.example.com"], 0).await?;
+    let attachment_id = post_image(&ctx).await?;
+    let path = format!("/api/v1/attachments/{attachment_id}");
+
+    let allowed = download_disposition(
+        &ctx,
+        &path,
+        &[(header::REFERER, "https://chat.example.com/channels/1")],
+    )
+    .await?;
+    assert!(allowed.starts_with("inline;"), "{allowed}");
+
+    let foreign = download_disposition(
+        &ctx,
+        &path,
+        &[(header::REFERER, "https://hotlinker.example.net/gallery")],
+    )
+    .await?;
+    assert!(foreign.starts_with("attachment;"), "{foreign}");
+
+    let direct = download_disposition(&ctx, &path, &[]).await?;
+    assert!(direct.starts_with("inline;"), "{direct}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn required_embed_tokens_gate_inline_display() -> anyhow::Result<()> {
+    let ctx = hotlink_context(&[], 120).await?;
+    let attachment_id = post_image(&ctx).await?;
+    let path = format!("/api/v1/attachments/{attachment_id}");
+
+    let without = download_disposition(&ctx, &path, &[]).await?;
+    assert!(without.starts_with("attachment;"), "{without}");
+
+    let (status, payload) = ctx
+        .request_json(Method::GET, &format!("{path}/embed-token"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    let url = payload["url"].as_str().context("embed url")?;
+    assert!(url.starts_with(&format!("{path}?token=")), "{url}");
+    let with = download_disposition(&ctx, url, &[]).await?;
+    assert!(with.starts_with("inline;"), "{with}");
+
+    let tampered = format!("{url}0");
+    let tampered = download_disposition(&ctx, &tampered, &[]).await?;
+    assert!(tampered.starts_with("attachment;"), "{tampered}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn encrypted_attachments_stream_back_decrypted() -> anyhow::Result<()> {
+    // The single-shot format stays the default so older releases can still
+    // read new files; both are served with their plaintext length.
+    download_encrypted_attachment(false).await?;
+    download_encrypted_attachment(true).await
+}
+
+async fn download_encrypted_attachment(chunked: bool) -> anyhow::Result<()> {
+    let master =
+        paracord_util::at_rest::parse_master_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=")?;
+    let cryptor = paracord_util::at_rest::FileCryptor::from_master_key(&master, false)
+        .with_chunked_writes(chunked);
+    let ctx = TestContext::with_state(|state| state.config.file_cryptor = Some(cryptor)).await?;
+    let guild_id = create_guild(&ctx, "Encrypted Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    // Spans several encryption chunks so the download decrypts incrementally.
+    let contents: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+    let boundary = "paracord-test-boundary";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"data.bin\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(&contents);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(format!("/api/v1/channels/{channel_id}/attachments"))
+        .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(Body::from(body))?;
+    let response = ctx.app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let uploaded: Value =
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    let attachment_id = uploaded["id"]
+        .as_str()
+        .context("attachment id")?
+        .to_string();
+
+    let stored = ctx
+        .state
+        .storage_backend
+        .retrieve(&format!("attachments/{attachment_id}.bin"))
+        .await?;
+    assert!(paracord_util::at_rest::FileCryptor::payload_is_encrypted(
+        &stored
+    ));
+    assert_eq!(
+        paracord_util::at_rest::FileCryptor::payload_is_chunked(&stored),
+        chunked
+    );
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": [&attachment_id] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/api/v1/attachments/{attachment_id}"))
+        .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+        .body(Body::empty())?;
+    let response = ctx.app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let disposition = response
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .context("download should set Content-Disposition")?
+        .to_str()?;
+    assert!(disposition.starts_with("attachment;"), "{disposition}");
+    assert_eq!(
+        response.headers().get(header::X_CONTENT_TYPE_OPTIONS),
+        Some(&header::HeaderValue::from_static("nosniff"))
+    );
+    assert_eq!(
+        response.headers().get(header::CONTENT_LENGTH),
+        Some(&header::HeaderValue::from(contents.len()))
+    );
+    let downloaded = to_bytes(response.into_body(), usize::MAX).await?;
+    assert_eq!(downloaded.as_ref(), contents.as_slice());
+    Ok(())
+}
+
+#[tokio::test]
+async fn deleted_message_attachments_are_collected_with_their_files() -> anyhow::Result<()> {
+    let ctx = hotlink_context(&[], 0).await?;
+    let attachment_id = post_image(&ctx).await?;
+    let storage_key = format!("attachments/{attachment_id}.png");
+    let message_id = paracord_db::attachments::get_attachment(&ctx.state.db, attachment_id)
+        .await?
+        .and_then(|attachment| attachment.message_id)
+        .context("attachment should be linked")?;
+
+    assert_eq!(
+        paracord_api::routes::files::collect_orphaned_attachments(&ctx.state, 16).await?,
+        0
+    );
+    paracord_db::messages::delete_message(&ctx.state.db, message_id).await?;
+    assert_eq!(
+        paracord_api::routes::files::collect_orphaned_attachments(&ctx.state, 16).await?,
+        1
+    );
+
+    assert!(
+        paracord_db::attachments::get_attachment(&ctx.state.db, attachment_id)
+            .await?
+            .is_none()
+    );
+    assert!(!ctx.state.storage_backend.exists(&storage_key).await?);
+    Ok(())
+}
@@ -0,0 +1,289 @@
+use anyhow::Context;
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_guild, create_pending_attachment, create_text_channel, TestContext};
+
+/// Context with tight attachment limits so the caps are easy to hit.
+async fn attachment_context(media_cdn_base_url: Option<&str>) -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        state.config.max_attachments_per_message = 3;
+        state.config.max_attachment_bytes_per_message = 1024;
+        state.config.max_pending_uploads_per_user = 3;
+        state.config.media_cdn_base_url = media_cdn_base_url.map(str::to_string);
+    })
+    .await
+}
+
+/// Upload a small text file through the multipart upload route.
+async fn upload_text_file(ctx: &TestContext, channel_id: &str) -> anyhow::Result<StatusCode> {
+    let boundary = "paracord-test-boundary";
+    let body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"note.txt\"\r\nContent-Type: text/plain\r\n\r\nhello\r\n--{boundary}--\r\n"
+    );
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(format!("/api/v1/channels/{channel_id}/attachments"))
+        .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(Body::from(body))?;
+    let response = ctx.app.clone().oneshot(request).await?;
+    Ok(response.status())
+}
+
+#[tokio::test]
+async fn pending_upload_cap_is_released_by_linking() -> anyhow::Result<()> {
+    let ctx = attachment_context(None).await?;
+    let guild_id = create_guild(&ctx, "Upload Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    for _ in 0..2 {
+        assert_eq!(
+            upload_text_file(&ctx, &channel_id).await?,
+            StatusCode::CREATED
+        );
+    }
+    let pending = create_pending_attachment(&ctx, &channel_id, 10).await?;
+    assert_eq!(
+        upload_text_file(&ctx, &channel_id).await?,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": [&pending] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    assert_eq!(
+        upload_text_file(&ctx, &channel_id).await?,
+        StatusCode::CREATED
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn pending_upload_cap_is_released_by_expiry() -> anyhow::Result<()> {
+    let ctx = attachment_context(None).await?;
+    let guild_id = create_guild(&ctx, "Upload Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    let mut pending = Vec::new();
+    for _ in 0..3 {
+        pending.push(create_pending_attachment(&ctx, &channel_id, 10).await?);
+    }
+    assert_eq!(
+        upload_text_file(&ctx, &channel_id).await?,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let expired_at = (Utc::now() - Duration::minutes(1))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    sqlx::query("UPDATE attachments SET upload_expires_at = $1 WHERE id = $2")
+        .bind(expired_at)
+        .bind(pending[0].parse::<i64>()?)
+        .execute(&ctx.db)
+        .await?;
+    assert_eq!(
+        upload_text_file(&ctx, &channel_id).await?,
+        StatusCode::CREATED
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn concurrent_uploads_cannot_overshoot_the_pending_cap() -> anyhow::Result<()> {
+    let ctx = attachment_context(None).await?;
+    let guild_id = create_guild(&ctx, "Upload Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    let statuses =
+        futures_util::future::join_all((0..8).map(|_| upload_text_file(&ctx, &channel_id)))
+            .await
+            .into_iter()
+            .collect::<anyhow::Result<Vec<_>>>()?;
+    let created = statuses
+        .iter()
+        .filter(|status| **status == StatusCode::CREATED)
+        .count();
+    let limited = statuses
+        .iter()
+        .filter(|status| **status == StatusCode::TOO_MANY_REQUESTS)
+        .count();
+    assert_eq!((created, limited), (3, 5), "{statuses:?}");
+
+    let (pending,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM attachments WHERE message_id IS NULL")
+            .fetch_one(&ctx.db)
+            .await?;
+    assert_eq!(pending, 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn message_attachment_count_is_capped() -> anyhow::Result<()> {
+    let ctx = attachment_context(None).await?;
+    let guild_id = create_guild(&ctx, "Attachment Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    let mut attachment_ids = Vec::new();
+    for _ in 0..4 {
+        attachment_ids.push(create_pending_attachment(&ctx, &channel_id, 10).await?);
+    }
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": attachment_ids })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": &attachment_ids[..3] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    assert_eq!(payload["attachments"].as_array().map(Vec::len), Some(3));
+    Ok(())
+}
+
+#[tokio::test]
+async fn attachments_keep_the_order_the_client_sent() -> anyhow::Result<()> {
+    let ctx = attachment_context(None).await?;
+    let guild_id = create_guild(&ctx, "Attachment Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    let mut attachment_ids = Vec::new();
+    for _ in 0..3 {
+        attachment_ids.push(create_pending_attachment(&ctx, &channel_id, 10).await?);
+    }
+    // Send them newest-first so id order and client order disagree.
+    attachment_ids.reverse();
+
+    let returned_ids = |attachments: &Value| -> Vec<String> {
+        attachments
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|a| a["id"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": &attachment_ids })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+    assert_eq!(returned_ids(&message["attachments"]), attachment_ids);
+
+    let (status, messages) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{messages}");
+    assert_eq!(returned_ids(&messages[0]["attachments"]), attachment_ids);
+    Ok(())
+}
+
+#[tokio::test]
+async fn attachment_urls_use_the_media_cdn_when_configured() -> anyhow::Result<()> {
+    for (cdn, expected_prefix) in [
+        (None, "/api/v1/attachments/"),
+        (
+            Some("https://cdn.example.com/"),
+            "https://cdn.example.com/api/v1/attachments/",
+        ),
+    ] {
+        let ctx = attachment_context(cdn).await?;
+        let guild_id = create_guild(&ctx, "CDN Guild").await?;
+        let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+        let attachment_id = create_pending_attachment(&ctx, &channel_id, 10).await?;
+
+        let (status, message) = ctx
+            .request_json(
+                Method::POST,
+                &format!("/api/v1/channels/{channel_id}/messages"),
+                Some(json!({ "content": "", "attachment_ids": [&attachment_id] })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED, "{message}");
+        assert_eq!(
+            message["attachments"][0]["url"],
+            format!("{expected_prefix}{attachment_id}")
+        );
+
+        let (status, messages) = ctx
+            .request_json(
+                Method::GET,
+                &format!("/api/v1/channels/{channel_id}/messages"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK, "{messages}");
+        assert_eq!(
+            messages[0]["attachments"][0]["url"],
+            format!("{expected_prefix}{attachment_id}")
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn message_attachment_total_size_is_capped() -> anyhow::Result<()> {
+    let ctx = attachment_context(None).await?;
+    let guild_id = create_guild(&ctx, "Attachment Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    let first = create_pending_attachment(&ctx, &channel_id, 600).await?;
+    let second = create_pending_attachment(&ctx, &channel_id, 600).await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": [&first, &second] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    let pending = paracord_db::attachments::get_attachment(&ctx.db, first.parse()?)
+        .await?
+        .context("attachment should still exist")?;
+    assert_eq!(pending.message_id, None);
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": [&first] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+mod common;
+
+use common::{create_authenticated_user_token, create_guild, create_text_channel, TestContext};
+
+#[tokio::test]
+async fn temporary_bans_block_rejoining_until_they_expire() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Temp Ban Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let (status, invite) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/invites"),
+            Some(json!({})),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{invite}");
+    let invite_path = format!(
+        "/api/v1/invites/{}",
+        invite["code"].as_str().context("code")?
+    );
+
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (status, _) = ctx
+        .request_json_as(&member_token, Method::POST, &invite_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let ban_path = format!("/api/v1/guilds/{guild_id}/bans/{member_id}");
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &ban_path,
+            Some(json!({ "duration_seconds": 0 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &ban_path,
+            Some(json!({ "reason": "cool off", "duration_seconds": 3600 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, bans) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/bans"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(bans[0]["expires_at"].is_string(), "{bans}");
+
+    let (status, _) = ctx
+        .request_json_as(&member_token, Method::POST, &invite_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Nothing has expired yet, so the sweeper leaves the ban alone.
+    assert_eq!(
+        paracord_api::routes::bans::expire_temp_bans(&ctx.state).await?,
+        0
+    );
+
+    sqlx::query("UPDATE bans SET expires_at = $1 WHERE user_id = $2")
+        .bind(
+            (Utc::now() - Duration::minutes(1))
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+        )
+        .bind(member_id)
+        .execute(&ctx.db)
+        .await?;
+
+    // An expired ban stops blocking joins even before the sweeper runs.
+    let (status, joined) = ctx
+        .request_json_as(&member_token, Method::POST, &invite_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{joined}");
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    assert_eq!(
+        paracord_api::routes::bans::expire_temp_bans(&ctx.state).await?,
+        1
+    );
+    let event = events.try_recv()?;
+    assert_eq!(event.event_type, "GUILD_BAN_REMOVE");
+    assert_eq!(event.payload["user_id"], member_id.to_string());
+
+    let (status, bans) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/bans"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bans.as_array().map(Vec::len), Some(0));
+    let (status, audit) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/audit-logs?action_type=23"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let entries = audit["audit_log_entries"].as_array().context("entries")?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["target_id"], member_id.to_string());
+    assert_eq!(entries[0]["reason"], "Temporary ban expired");
+    Ok(())
+}
@@ -1,226 +1,15 @@
-use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
-
 use anyhow::Context;
-use axum::{
-    body::{to_bytes, Body},
-    http::{header, Method, Request, StatusCode},
-    Router,
-};
+use axum::http::{Method, StatusCode};
 use chrono::{Duration, Utc};
-use paracord_core::{build_permission_cache, AppConfig, AppState, RuntimeSettings};
-use paracord_media::{
-    LiveKitConfig, LocalStorage, Storage, StorageConfig, StorageManager, VoiceManager,
-};
 use serde_json::{json, Value};
-use tempfile::TempDir;
-use tokio::sync::{Notify, RwLock};
-use tower::ServiceExt;
 use uuid::Uuid;
 
-struct TestContext {
-    app: Router,
-    token: String,
-    _storage_dir: TempDir,
-    _media_dir: TempDir,
-    _backup_dir: TempDir,
-}
-
-impl TestContext {
-    async fn new() -> anyhow::Result<Self> {
-        let db = paracord_db::create_pool("sqlite::memory:", 1).await?;
-        paracord_db::run_migrations(&db).await?;
-
-        let storage_dir = tempfile::tempdir()?;
-        let media_dir = tempfile::tempdir()?;
-        let backup_dir = tempfile::tempdir()?;
-        let jwt_secret = "integration-test-secret".to_string();
-
-        let livekit = Arc::new(LiveKitConfig {
-            api_key: "lk-test-key".to_string(),
-            api_secret: "lk-test-secret".to_string(),
-            url: "ws://localhost:7880".to_string(),
-            http_url: "http://localhost:7880".to_string(),
-        });
-
-        let state = AppState {
-            db: db.clone(),
-            event_bus: paracord_core::events::EventBus::default(),
-            config: AppConfig {
-                jwt_secret: jwt_secret.clone(),
-                jwt_expiry_seconds: 3600,
-                registration_enabled: true,
-                allow_username_login: false,
-                require_email: true,
-                storage_path: storage_dir.path().to_string_lossy().into_owned(),
-                max_upload_size: 10 * 1024 * 1024,
-                livekit_api_key: livekit.api_key.clone(),
-                livekit_api_secret: livekit.api_secret.clone(),
-                livekit_url: livekit.url.clone(),
-                livekit_http_url: livekit.http_url.clone(),
-                livekit_public_url: livekit.url.clone(),
-                livekit_available: false,
-                public_url: None,
-                media_storage_path: media_dir.path().to_string_lossy().into_owned(),
-                media_max_file_size: 10 * 1024 * 1024,
-                media_p2p_threshold: 1024 * 1024,
-                file_cryptor: None,
-                backup_dir: backup_dir.path().to_string_lossy().into_owned(),
-                database_url: "sqlite::memory:".to_string(),
-                federation_max_events_per_peer_per_minute: None,
-                federation_max_user_creates_per_peer_per_hour: None,
-                native_media_enabled: false,
-                native_media_port: 8443,
-                native_media_max_participants: 50,
-                native_media_e2ee_required: false,
-                max_guild_storage_quota: 0,
-                federation_file_cache_enabled: false,
-                federation_file_cache_max_size: 0,
-                federation_file_cache_ttl_hours: 0,
-            },
-            runtime: Arc::new(RwLock::new(RuntimeSettings::default())),
-            voice: Arc::new(VoiceManager::new(livekit)),
-            storage: Arc::new(StorageManager::new(StorageConfig {
-                base_path: media_dir.path().to_path_buf(),
-                max_file_size: 10 * 1024 * 1024,
-                p2p_threshold: 1024 * 1024,
-                allowed_extensions: None,
-            })),
-            storage_backend: Arc::new(Storage::Local(LocalStorage::new(storage_dir.path()))),
-            shutdown: Arc::new(Notify::new()),
-            online_users: Arc::new(RwLock::new(HashSet::new())),
-            user_presences: Arc::new(RwLock::new(HashMap::new())),
-            permission_cache: build_permission_cache(),
-            federation_service: None,
-            member_index: Arc::new(paracord_core::member_index::MemberIndex::empty()),
-            native_media: None,
-        };
-
-        paracord_api::install_http_rate_limiter();
-        let app = paracord_api::build_router().with_state(state);
-        let token = create_authenticated_user_token(&db, &jwt_secret).await?;
-
-        Ok(Self {
-            app,
-            token,
-            _storage_dir: storage_dir,
-            _media_dir: media_dir,
-            _backup_dir: backup_dir,
-        })
-    }
-
-    async fn request_json(
-        &self,
-        method: Method,
-        path: &str,
-        body: Option<Value>,
-    ) -> anyhow::Result<(StatusCode, Value)> {
-        let mut builder = Request::builder()
-            .method(method)
-            .uri(path)
-            .header(header::AUTHORIZATION, format!("Bearer {}", self.token));
-
-        let request = if let Some(payload) = body {
-            builder = builder.header(header::CONTENT_TYPE, "application/json");
-            builder.body(Body::from(payload.to_string()))?
-        } else {
-            builder.body(Body::empty())?
-        };
-
-        let response = self.app.clone().oneshot(request).await?;
-        let status = response.status();
-        let body_bytes = to_bytes(response.into_body(), usize::MAX).await?;
-        let payload = if body_bytes.is_empty() {
-            Value::Null
-        } else {
-            serde_json::from_slice(&body_bytes)
-                .unwrap_or_else(|_| json!({ "raw": String::from_utf8_lossy(&body_bytes) }))
-        };
-
-        Ok((status, payload))
-    }
-}
-
-async fn create_authenticated_user_token(
-    db: &paracord_db::DbPool,
-    jwt_secret: &str,
-) -> anyhow::Result<String> {
-    let user_id = paracord_util::snowflake::generate(1);
-    let nonce = Uuid::new_v4().simple().to_string();
-    let username = format!("integration_{nonce}");
-    let email = format!("{nonce}@example.com");
-    let password_hash = paracord_core::auth::hash_password("IntegrationPass123!")?;
-
-    let user =
-        paracord_db::users::create_user(db, user_id, &username, 1, &email, &password_hash).await?;
-
-    let session_id = format!("sess-{}", Uuid::new_v4().simple());
-    let jti = format!("jti-{}", Uuid::new_v4().simple());
-    let refresh_hash = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
-    paracord_db::sessions::create_session(
-        db,
-        &session_id,
-        user.id,
-        &refresh_hash,
-        &jti,
-        user.public_key.as_deref(),
-        None,
-        None,
-        None,
-        Utc::now() + Duration::days(1),
-    )
-    .await?;
-
-    let token = paracord_core::auth::create_session_token(
-        user.id,
-        user.public_key.as_deref(),
-        jwt_secret,
-        3600,
-        &session_id,
-        &jti,
-    )?;
-
-    Ok(token)
-}
+mod common;
 
-async fn create_guild(ctx: &TestContext, name: &str) -> anyhow::Result<String> {
-    let (status, payload) = ctx
-        .request_json(
-            Method::POST,
-            "/api/v1/guilds",
-            Some(json!({ "name": name, "icon": Value::Null })),
-        )
-        .await?;
-    assert_eq!(status, StatusCode::CREATED);
-    Ok(payload["id"]
-        .as_str()
-        .context("guild id should be a string")?
-        .to_string())
-}
-
-async fn create_text_channel(
-    ctx: &TestContext,
-    guild_id: &str,
-    name: &str,
-) -> anyhow::Result<String> {
-    let (status, payload) = ctx
-        .request_json(
-            Method::POST,
-            &format!("/api/v1/guilds/{guild_id}/channels"),
-            Some(json!({
-                "name": name,
-                "channel_type": 0,
-                "parent_id": Value::Null,
-                "required_role_ids": Value::Null,
-            })),
-        )
-        .await?;
-    assert_eq!(status, StatusCode::CREATED);
-    Ok(payload["id"]
-        .as_str()
-        .context("channel id should be a string")?
-        .to_string())
-}
+use common::{
+    add_guild_member, create_authenticated_user_token, create_guild, create_text_channel,
+    send_guild_message, TestContext,
+};
 
 #[tokio::test]
 async fn create_guild_channel_send_message_flow_works_end_to_end() -> anyhow::Result<()> {
@@ -378,6 +167,77 @@ async fn message_crud_routes_work() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn messages_around_returns_a_centred_window() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Around Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "history").await?;
+
+    let mut ids = Vec::new();
+    for i in 0..9 {
+        let (status, created) = ctx
+            .request_json(
+                Method::POST,
+                &format!("/api/v1/channels/{channel_id}/messages"),
+                Some(json!({ "content": format!("message {i}") })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED, "{created}");
+        ids.push(created["id"].as_str().context("message id")?.to_string());
+    }
+    let window_ids = |payload: &Value| -> Vec<String> {
+        payload
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["id"].as_str().map(str::to_string))
+            .collect()
+    };
+
+    let (status, middle) = ctx
+        .request_json(
+            Method::GET,
+            &format!(
+                "/api/v1/channels/{channel_id}/messages?around={}&limit=5",
+                ids[4]
+            ),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{middle}");
+    let expected: Vec<String> = ids[2..=6].iter().rev().cloned().collect();
+    assert_eq!(window_ids(&middle), expected);
+
+    let (status, first) = ctx
+        .request_json(
+            Method::GET,
+            &format!(
+                "/api/v1/channels/{channel_id}/messages?around={}&limit=5",
+                ids[0]
+            ),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{first}");
+    let expected: Vec<String> = ids[0..5].iter().rev().cloned().collect();
+    assert_eq!(window_ids(&first), expected);
+
+    let (status, last) = ctx
+        .request_json(
+            Method::GET,
+            &format!(
+                "/api/v1/channels/{channel_id}/messages?around={}&limit=5",
+                ids[8]
+            ),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{last}");
+    let expected: Vec<String> = ids[4..9].iter().rev().cloned().collect();
+    assert_eq!(window_ids(&last), expected);
+    Ok(())
+}
+
 #[tokio::test]
 async fn thread_routes_work() -> anyhow::Result<()> {
     let ctx = TestContext::new().await?;
@@ -461,3 +321,731 @@ async fn thread_routes_work() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn guild_message_author_includes_member_nick() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Nick Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "chat").await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/members/{}", ctx.user_id),
+            Some(json!({ "nick": "Captain" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "hello crew" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(message["author"]["nick"], "Captain");
+
+    let (status, messages) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(messages[0]["author"]["nick"], "Captain");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn dm_message_author_uses_global_display_name() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            "/api/v1/users/@me",
+            Some(json!({ "display_name": "Global Name" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+
+    let other_id = paracord_util::snowflake::generate(1);
+    let nonce = Uuid::new_v4().simple().to_string();
+    paracord_db::users::create_user(
+        &ctx.db,
+        other_id,
+        &format!("dmpeer_{nonce}"),
+        1,
+        &format!("{nonce}@example.com"),
+        "unused-hash",
+    )
+    .await?;
+    let dm_id = paracord_util::snowflake::generate(1);
+    paracord_db::dms::create_dm_channel(&ctx.db, dm_id, ctx.user_id, other_id).await?;
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{dm_id}/messages"),
+            Some(json!({
+                "content": "",
+                "e2ee": { "version": 1, "nonce": "bm9uY2U=", "ciphertext": "Y2lwaGVy" }
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+    assert_eq!(message["author"]["display_name"], "Global Name");
+    assert!(message["author"]["nick"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn message_create_dispatch_carries_the_client_nonce() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Nonce Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "nonce").await?;
+    let mut events =
+        ctx.state
+            .event_bus
+            .register_session("nonce-echo", ctx.user_id, &[guild_id.parse()?]);
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+
+    let (status, sent) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({
+                "content": "optimistic",
+                "nonce": "client-123",
+                "session_id": "nonce-echo",
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{sent}");
+    assert_eq!(sent["nonce"], "client-123");
+
+    // A retry with the same nonce returns the original without a second dispatch.
+    let (status, retried) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "optimistic", "nonce": "client-123" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{retried}");
+    assert_eq!(retried["id"], sent["id"]);
+    assert_eq!(retried["nonce"], "client-123");
+
+    let (status, plain) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "no nonce" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{plain}");
+
+    let mut creates = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "MESSAGE_CREATE" {
+            creates.push(event);
+        }
+    }
+    assert_eq!(creates.len(), 2);
+    assert_eq!(creates[0].payload["id"], sent["id"]);
+    assert_eq!(creates[0].payload["nonce"], "client-123");
+    assert!(creates[0].is_own_message_echo("nonce-echo"));
+    // The sender's other sessions still receive the message.
+    assert!(!creates[0].is_own_message_echo("other-device"));
+    assert_eq!(creates[1].payload["nonce"], Value::Null);
+    assert!(!creates[1].is_own_message_echo("nonce-echo"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn messages_after_seq_returns_the_rest_of_the_channel_in_order() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Sync Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "sync").await?;
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+
+    for content in ["one", "two", "three"] {
+        let (status, message) = ctx
+            .request_json(
+                Method::POST,
+                &messages_path,
+                Some(json!({ "content": content })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED, "{message}");
+    }
+
+    let (status, synced) = ctx
+        .request_json(Method::GET, &format!("{messages_path}?after_seq=1"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{synced}");
+    let synced = synced.as_array().context("messages array")?;
+    let pairs: Vec<(i64, &str)> = synced
+        .iter()
+        .map(|m| {
+            (
+                m["seq"].as_i64().unwrap_or_default(),
+                m["content"].as_str().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(pairs, vec![(2, "two"), (3, "three")]);
+
+    let (status, _) = ctx
+        .request_json(
+            Method::GET,
+            &format!("{messages_path}?after_seq=1&before=1"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn slowmode_limits_members_but_not_moderators() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Slowmode Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "busy").await?;
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id.parse()?).await?;
+
+    let (status, _) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}"),
+            Some(json!({ "rate_limit_per_user": 100_000 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, channel) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}"),
+            Some(json!({ "rate_limit_per_user": 60 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(channel["rate_limit_per_user"], 60);
+
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+    let send_as_member = |content: &'static str, nonce: &'static str| {
+        ctx.request_json_as(
+            &member_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": content, "nonce": nonce })),
+        )
+    };
+    // A send that fails validation does not start the cooldown.
+    let (status, _) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "rejected", "flags": 1 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, first) = send_as_member("first", "slow-1").await?;
+    assert_eq!(status, StatusCode::CREATED);
+    // Retrying the same message is not a new send.
+    let (status, retried) = send_as_member("first", "slow-1").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(retried["id"], first["id"]);
+    let (status, payload) = send_as_member("second", "slow-2").await?;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let retry_after = payload["retry_after"].as_i64().context("retry_after")?;
+    assert!((1..=60).contains(&retry_after));
+
+    // The guild owner can manage messages, so slowmode does not apply.
+    for content in ["owner one", "owner two"] {
+        let (status, _) = ctx
+            .request_json(
+                Method::POST,
+                &messages_path,
+                Some(json!({ "content": content })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn read_only_channels_only_accept_posts_from_managers() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Announcements Guild").await?;
+    let (status, channel) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/channels"),
+            Some(json!({ "name": "announcements", "channel_type": 0, "read_only": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{channel}");
+    assert_eq!(channel["read_only"], true);
+    let channel_id = channel["id"].as_str().context("channel id")?;
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (manager_id, manager_token) = create_authenticated_user_token(&ctx.db).await?;
+    for user_id in [member_id, manager_id] {
+        paracord_db::members::add_member(&ctx.db, user_id, guild_id.parse()?).await?;
+    }
+    let role_id = paracord_util::snowflake::generate(1);
+    paracord_db::roles::create_role(
+        &ctx.db,
+        role_id,
+        guild_id.parse()?,
+        "Moderators",
+        paracord_models::permissions::Permissions::MANAGE_MESSAGES.bits(),
+    )
+    .await?;
+    paracord_db::roles::add_member_role(&ctx.db, manager_id, guild_id.parse()?, role_id).await?;
+
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+    let (status, _) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "can I post?" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = ctx
+        .request_json_as(
+            &manager_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "release notes" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, messages) = ctx
+        .request_json_as(&member_token, Method::GET, &messages_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(messages.as_array().map(Vec::len), Some(1));
+    assert_eq!(messages[0]["content"], "release notes");
+
+    // Threads under the channel are held to the same rule.
+    let threads_path = format!("/api/v1/channels/{channel_id}/threads");
+    let (status, _) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &threads_path,
+            Some(json!({ "name": "questions" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, thread) = ctx
+        .request_json_as(
+            &manager_token,
+            Method::POST,
+            &threads_path,
+            Some(json!({ "name": "discussion" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{thread}");
+    let thread_messages_path = format!(
+        "/api/v1/channels/{}/messages",
+        thread["id"].as_str().context("thread id")?
+    );
+    for (token, expected) in [
+        (&member_token, StatusCode::FORBIDDEN),
+        (&manager_token, StatusCode::CREATED),
+    ] {
+        let (status, _) = ctx
+            .request_json_as(
+                token,
+                Method::POST,
+                &thread_messages_path,
+                Some(json!({ "content": "in the thread" })),
+            )
+            .await?;
+        assert_eq!(status, expected);
+    }
+
+    // Clearing the flag opens the channel back up.
+    let (status, channel) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}"),
+            Some(json!({ "read_only": false })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(channel["read_only"], false);
+    let (status, _) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "now I can" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetched_message_includes_attachments_and_reaction_counts() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Fetch Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+
+    let attachment_id = paracord_util::snowflake::generate(1);
+    paracord_db::attachments::create_attachment(
+        &ctx.db,
+        attachment_id,
+        None,
+        "photo.png",
+        Some("image/png"),
+        10,
+        &format!("/api/v1/attachments/{attachment_id}"),
+        Some(640),
+        Some(480),
+        Some(ctx.user_id),
+        Some(channel_id.parse()?),
+        Some(Utc::now() + Duration::minutes(10)),
+        None,
+    )
+    .await?;
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "look", "attachment_ids": [attachment_id.to_string()] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    let message_id = payload["id"]
+        .as_str()
+        .context("message id should be a string")?
+        .to_string();
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "other" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+
+    let message_path = format!("{messages_path}/{message_id}");
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &format!("{message_path}/reactions/fire/@me"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let member_id = add_guild_member(&ctx, &guild_id).await?;
+    for emoji in ["fire", "tada"] {
+        paracord_db::reactions::add_reaction(
+            &ctx.db,
+            message_id.parse()?,
+            member_id,
+            emoji,
+            emoji,
+            None,
+            false,
+        )
+        .await?;
+    }
+
+    let (status, message) = ctx.request_json(Method::GET, &message_path, None).await?;
+    assert_eq!(status, StatusCode::OK, "{message}");
+    assert_eq!(message["id"], message_id);
+    assert_eq!(message["content"], "look");
+    let attachment = &message["attachments"][0];
+    assert_eq!(attachment["id"], attachment_id.to_string());
+    assert_eq!(attachment["content_type"], "image/png");
+    assert_eq!(attachment["width"], 640);
+    assert_eq!(attachment["height"], 480);
+    let reactions = message["reactions"].as_array().context("reactions")?;
+    assert_eq!(reactions.len(), 2);
+    assert_eq!(reactions[0]["emoji"], "fire");
+    assert_eq!(reactions[0]["count"], 2);
+    assert_eq!(reactions[0]["me"], true);
+    assert_eq!(reactions[1]["emoji"], "tada");
+    assert_eq!(reactions[1]["count"], 1);
+    assert_eq!(reactions[1]["me"], false);
+
+    // The batched list groups attachments and reactions per message.
+    let (status, messages) = ctx.request_json(Method::GET, &messages_path, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(messages[0]["content"], "other");
+    assert_eq!(messages[0]["attachments"], json!([]));
+    assert_eq!(messages[0]["reactions"], json!([]));
+    assert_eq!(messages[1]["reactions"], message["reactions"]);
+    assert_eq!(messages[1]["attachments"], message["attachments"]);
+
+    let (status, _) = ctx
+        .request_json(Method::GET, &format!("{messages_path}/1"), None)
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn permalink_resolves_viewable_message_with_context() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (guild_id, channel_id, message_id) = send_guild_message(&ctx).await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::GET,
+            &format!(
+                "/api/v1/permalink/resolve?guild={guild_id}&channel={channel_id}&message={message_id}"
+            ),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["viewable"], true);
+    assert_eq!(payload["reason"], Value::Null);
+    assert_eq!(payload["guild"]["id"], guild_id.as_str());
+    assert_eq!(payload["guild"]["name"], "Message Guild");
+    assert_eq!(payload["channel"]["id"], channel_id.as_str());
+    assert_eq!(payload["channel"]["name"], "general");
+    assert_eq!(payload["message"]["id"], message_id.as_str());
+    assert_eq!(payload["message"]["author_id"], ctx.user_id.to_string());
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/permalink/resolve?guild={guild_id}&channel={channel_id}&message=1"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["viewable"], false);
+    assert_eq!(payload["reason"], "message_not_found");
+    assert_eq!(payload["channel"]["id"], channel_id.as_str());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn permalink_hides_context_from_non_members() -> anyhow::Result<()> {
+    let mut ctx = TestContext::new().await?;
+    let (guild_id, channel_id, message_id) = send_guild_message(&ctx).await?;
+    let path = format!(
+        "/api/v1/permalink/resolve?guild={guild_id}&channel={channel_id}&message={message_id}"
+    );
+
+    let (_, outsider_token) = create_authenticated_user_token(&ctx.db).await?;
+    ctx.token = outsider_token;
+
+    let (status, payload) = ctx.request_json(Method::GET, &path, None).await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["viewable"], false);
+    assert_eq!(payload["reason"], "forbidden");
+    assert_eq!(payload["guild"], Value::Null);
+    assert_eq!(payload["channel"], Value::Null);
+    assert_eq!(payload["message"], Value::Null);
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::GET,
+            &format!(
+                "/api/v1/permalink/resolve?guild=@me&channel={channel_id}&message={message_id}"
+            ),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["reason"], "not_found");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn threads_cannot_start_from_messages_the_caller_cannot_see() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Thread Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let other_channel_id = create_text_channel(&ctx, &guild_id, "other").await?;
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id.parse()?).await?;
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "only for me" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let message_id = message["id"].as_str().context("message id")?;
+    sqlx::query("UPDATE messages SET visible_to = author_id WHERE id = $1")
+        .bind(message_id.parse::<i64>()?)
+        .execute(&ctx.db)
+        .await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages/{message_id}/threads"),
+            Some(json!({ "name": "Peek" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{payload}");
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{other_channel_id}/messages/{message_id}/threads"),
+            Some(json!({ "name": "Elsewhere" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{payload}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn threads_start_from_messages_and_inherit_parent_permissions() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Thread Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id.parse()?).await?;
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "let's discuss" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let message_id = message["id"].as_str().context("message id")?;
+    let start_path = format!("/api/v1/channels/{channel_id}/messages/{message_id}/threads");
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &start_path,
+            Some(json!({ "name": "Discussion", "auto_archive_duration": 42 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, thread) = ctx
+        .request_json(
+            Method::POST,
+            &start_path,
+            Some(json!({ "name": "Discussion", "auto_archive_duration": 60 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(thread["parent_id"], channel_id);
+    assert_eq!(thread["thread_metadata"]["starter_message_id"], message_id);
+    assert_eq!(thread["thread_metadata"]["auto_archive_duration"], 60);
+    let thread_id = thread["id"].as_str().context("thread id")?;
+    let (status, _) = ctx
+        .request_json(Method::POST, &start_path, Some(json!({ "name": "Again" })))
+        .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Posting in an archived thread brings it back.
+    let (status, _) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}/threads/{thread_id}"),
+            Some(json!({ "archived": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{thread_id}/messages"),
+            Some(json!({ "content": "still going" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, threads) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/channels/{channel_id}/threads"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(threads[0]["id"], thread_id);
+    assert_eq!(threads[0]["thread_metadata"]["archived"], false);
+
+    // Locking a thread closes it to everyone but moderators.
+    let thread_messages_path = format!("/api/v1/channels/{thread_id}/messages");
+    let (status, _) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}/threads/{thread_id}"),
+            Some(json!({ "locked": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &thread_messages_path,
+            Some(json!({ "content": "can I still post?" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &thread_messages_path,
+            Some(json!({ "content": "closing this out" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let thread_path = format!("/api/v1/channels/{thread_id}");
+    let (status, _) = ctx
+        .request_json_as(&member_token, Method::GET, &thread_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    // Hiding the parent channel hides its threads too.
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &format!("/api/v1/channels/{channel_id}/overwrites/{member_id}"),
+            Some(json!({
+                "target_type": paracord_core::permissions::OVERWRITE_TARGET_MEMBER,
+                "allow_perms": 0,
+                "deny_perms": paracord_models::permissions::Permissions::VIEW_CHANNEL.bits(),
+            })),
+        )
+        .await?;
+    assert!(status.is_success());
+    let (status, _) = ctx
+        .request_json_as(&member_token, Method::GET, &thread_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    Ok(())
+}
@@ -0,0 +1,556 @@
+//! Shared fixture for the API integration tests.
+//!
+//! Each test binary compiles its own copy of this module and uses a
+//! different subset of it.
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use axum::{
+    body::{to_bytes, Body},
+    extract::ConnectInfo,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use chrono::{Duration, Utc};
+use paracord_core::mail::{MailSendFuture, Mailer, OutgoingMail};
+use paracord_core::{build_permission_cache, AppConfig, AppState, RuntimeSettings};
+use paracord_media::{
+    LiveKitConfig, LocalStorage, Storage, StorageConfig, StorageManager, VoiceManager,
+};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::sync::{Notify, RwLock};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+pub const JWT_SECRET: &str = "integration-test-secret";
+pub const PASSWORD: &str = "IntegrationPass123!";
+/// Public URL the recording mailer puts in its links.
+pub const MAIL_BASE_URL: &str = "https://chat.example.com";
+
+/// Every message sent by a [`TestContext::with_outbox`] mailer.
+pub type Outbox = Arc<Mutex<Vec<OutgoingMail>>>;
+
+/// Scratch directories backing the storage, media and backup paths.
+pub struct TestDirs {
+    pub storage: TempDir,
+    pub media: TempDir,
+    pub backup: TempDir,
+}
+
+impl TestDirs {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            storage: tempfile::tempdir()?,
+            media: tempfile::tempdir()?,
+            backup: tempfile::tempdir()?,
+        })
+    }
+}
+
+/// Config matching a default single-node install, with every optional
+/// limit and feature turned off.
+pub fn test_config(dirs: &TestDirs) -> AppConfig {
+    AppConfig {
+        jwt_secret: JWT_SECRET.to_string(),
+        jwt_expiry_seconds: 3600,
+        registration_enabled: true,
+        allow_username_login: false,
+        require_email: true,
+        unique_usernames: false,
+        login_max_failures: 0,
+        login_lockout_seconds: 900,
+        session_idle_timeout_seconds: 0,
+        storage_path: dirs.storage.path().to_string_lossy().into_owned(),
+        max_upload_size: 10 * 1024 * 1024,
+        livekit_api_key: "lk-test-key".to_string(),
+        livekit_api_secret: "lk-test-secret".to_string(),
+        livekit_url: "ws://localhost:7880".to_string(),
+        livekit_http_url: "http://localhost:7880".to_string(),
+        livekit_public_url: "ws://localhost:7880".to_string(),
+        livekit_available: false,
+        livekit_recording_enabled: false,
+        livekit_recording_filepath: "recordings/{room_name}-{time}".to_string(),
+        public_url: None,
+        media_storage_path: dirs.media.path().to_string_lossy().into_owned(),
+        media_max_file_size: 10 * 1024 * 1024,
+        media_p2p_threshold: 1024 * 1024,
+        file_cryptor: None,
+        secret_cryptor: None,
+        backup_dir: dirs.backup.path().to_string_lossy().into_owned(),
+        database_url: "sqlite::memory:".to_string(),
+        federation_max_events_per_peer_per_minute: None,
+        federation_max_user_creates_per_peer_per_hour: None,
+        webhook_executions_per_minute: 30,
+        federation_media_token_ttl_seconds: 600,
+        federation_file_token_ttl_seconds: 300,
+        native_media_enabled: false,
+        native_media_port: 8443,
+        native_media_max_participants: 50,
+        native_media_e2ee_required: false,
+        max_guild_storage_quota: 0,
+        max_attachments_per_message: 10,
+        max_attachment_bytes_per_message: 100 * 1024 * 1024,
+        max_attachment_filename_length: 255,
+        max_pending_uploads_per_user: 50,
+        attachment_inline_origins: Vec::new(),
+        attachment_inline_token_ttl_seconds: 0,
+        log_attachment_access: false,
+        federation_file_cache_enabled: false,
+        federation_file_cache_max_size: 0,
+        federation_file_cache_ttl_hours: 0,
+        media_cdn_base_url: None,
+        gateway_compression_threshold_bytes: 0,
+    }
+}
+
+fn voice_manager(config: &AppConfig) -> Arc<VoiceManager> {
+    Arc::new(VoiceManager::new(Arc::new(LiveKitConfig {
+        api_key: config.livekit_api_key.clone(),
+        api_secret: config.livekit_api_secret.clone(),
+        url: config.livekit_url.clone(),
+        http_url: config.livekit_http_url.clone(),
+    })))
+}
+
+/// Point the voice manager at a LiveKit server API on `http_url`.
+pub fn use_livekit_http_url(state: &mut AppState, http_url: &str) {
+    state.config.livekit_http_url = http_url.to_string();
+    state.voice = voice_manager(&state.config);
+}
+
+/// App state over `db` using [`test_config`] and local storage in `dirs`.
+pub fn test_state(db: paracord_db::DbPool, dirs: &TestDirs) -> AppState {
+    let config = test_config(dirs);
+    AppState {
+        db,
+        event_bus: paracord_core::events::EventBus::default(),
+        voice: voice_manager(&config),
+        config,
+        runtime: Arc::new(RwLock::new(RuntimeSettings::default())),
+        storage: Arc::new(StorageManager::new(StorageConfig {
+            base_path: dirs.media.path().to_path_buf(),
+            max_file_size: 10 * 1024 * 1024,
+            p2p_threshold: 1024 * 1024,
+            allowed_extensions: None,
+        })),
+        storage_backend: Arc::new(Storage::Local(LocalStorage::new(dirs.storage.path()))),
+        shutdown: Arc::new(Notify::new()),
+        online_users: Arc::new(RwLock::new(HashSet::new())),
+        user_presences: Arc::new(RwLock::new(HashMap::new())),
+        permission_cache: build_permission_cache(),
+        federation_service: None,
+        member_index: Arc::new(paracord_core::member_index::MemberIndex::empty()),
+        presence_manager: Arc::new(paracord_core::presence_manager::PresenceManager::new()),
+        usage: Arc::new(paracord_core::usage::UserUsageTracker::default()),
+        rate_limits: Arc::new(paracord_core::rate_limit::RateLimitStore::default()),
+        livekit_online: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        native_media: None,
+        link_unfurler: None,
+        push_notifier: None,
+        mailer: None,
+        readiness_cache: Arc::default(),
+    }
+}
+
+/// A migrated in-memory database, the router over it and one signed-in user.
+pub struct TestContext {
+    pub app: Router,
+    pub state: AppState,
+    pub db: paracord_db::DbPool,
+    pub user_id: i64,
+    pub token: String,
+    pub dirs: TestDirs,
+}
+
+impl TestContext {
+    pub async fn new() -> anyhow::Result<Self> {
+        Self::with_state(|_| {}).await
+    }
+
+    /// Like [`Self::new`] with the signed-in user flagged as a server admin.
+    pub async fn new_admin() -> anyhow::Result<Self> {
+        let ctx = Self::new().await?;
+        ctx.promote_to_admin().await?;
+        Ok(ctx)
+    }
+
+    /// Like [`Self::new`] with username login on and email optional, as the
+    /// account flow tests expect.
+    pub async fn with_username_login() -> anyhow::Result<Self> {
+        Self::with_state(|state| {
+            state.config.allow_username_login = true;
+            state.config.require_email = false;
+        })
+        .await
+    }
+
+    /// Like [`Self::with_username_login`] with a mailer that records every
+    /// message instead of sending it.
+    pub async fn with_outbox() -> anyhow::Result<(Self, Outbox)> {
+        let outbox = Outbox::default();
+        let sink = outbox.clone();
+        let ctx = Self::with_state(|state| {
+            state.config.allow_username_login = true;
+            state.config.require_email = false;
+            state.mailer = Some(Arc::new(Mailer::new(
+                MAIL_BASE_URL,
+                Arc::new(move |mail| -> MailSendFuture {
+                    sink.lock().unwrap().push(mail);
+                    Box::pin(async { Ok(()) })
+                }),
+            )));
+        })
+        .await?;
+        Ok((ctx, outbox))
+    }
+
+    pub async fn promote_to_admin(&self) -> anyhow::Result<()> {
+        paracord_db::users::update_user_flags(
+            &self.db,
+            self.user_id,
+            paracord_core::USER_FLAG_ADMIN,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Build the context after letting `configure` adjust the state, e.g.
+    /// to change config values or plug in a notifier.
+    pub async fn with_state(configure: impl FnOnce(&mut AppState)) -> anyhow::Result<Self> {
+        paracord_api::install_http_rate_limiter();
+        Self::build(configure).await
+    }
+
+    /// Like [`Self::with_state`] but leaves the process-wide HTTP rate
+    /// limiter uninstalled, for suites that would otherwise trip its shared
+    /// buckets.
+    pub async fn without_http_rate_limiter(
+        configure: impl FnOnce(&mut AppState),
+    ) -> anyhow::Result<Self> {
+        Self::build(configure).await
+    }
+
+    async fn build(configure: impl FnOnce(&mut AppState)) -> anyhow::Result<Self> {
+        let db = paracord_db::create_pool("sqlite::memory:", 1).await?;
+        paracord_db::run_migrations(&db).await?;
+        let dirs = TestDirs::new()?;
+        let mut state = test_state(db.clone(), &dirs);
+        configure(&mut state);
+
+        let app = paracord_api::build_router().with_state(state.clone());
+        let (user_id, token) = create_authenticated_user_token(&db).await?;
+
+        Ok(Self {
+            app,
+            state,
+            db,
+            user_id,
+            token,
+            dirs,
+        })
+    }
+
+    pub async fn request_json(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        self.request_json_as(&self.token, method, path, body).await
+    }
+
+    pub async fn request_json_as(
+        &self,
+        token: &str,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        self.request_with(Some(token), method, path, body).await
+    }
+
+    pub async fn request_json_unauthenticated(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        self.request_with(None, method, path, body).await
+    }
+
+    pub async fn request_with(
+        &self,
+        token: Option<&str>,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let authorization = token.map(|token| format!("Bearer {token}"));
+        self.send_json(authorization.as_deref(), method, path, body)
+            .await
+    }
+
+    /// Like [`Self::request_json_as`] but with a complete `Authorization`
+    /// header value, e.g. `Pat <token>`.
+    pub async fn request_json_authorized(
+        &self,
+        authorization: &str,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        self.send_json(Some(authorization), method, path, body)
+            .await
+    }
+
+    async fn send_json(
+        &self,
+        authorization: Option<&str>,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let mut builder = Request::builder().method(method).uri(path);
+        if let Some(authorization) = authorization {
+            builder = builder.header(header::AUTHORIZATION, authorization);
+        }
+        let request = if let Some(payload) = body {
+            builder = builder.header(header::CONTENT_TYPE, "application/json");
+            builder.body(Body::from(payload.to_string()))?
+        } else {
+            builder.body(Body::empty())?
+        };
+        self.send(request).await
+    }
+
+    /// Send a prepared request and decode its JSON body; empty bodies come
+    /// back as `null` and non-JSON bodies as `{ "raw": ... }`. Requests
+    /// without a peer address are sent from `127.0.0.1:40000`.
+    pub async fn send(&self, mut request: Request<Body>) -> anyhow::Result<(StatusCode, Value)> {
+        if request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .is_none()
+        {
+            let peer: SocketAddr = "127.0.0.1:40000".parse()?;
+            request.extensions_mut().insert(ConnectInfo(peer));
+        }
+        let response = self.app.clone().oneshot(request).await?;
+        let status = response.status();
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await?;
+        let payload = if body_bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body_bytes)
+                .unwrap_or_else(|_| json!({ "raw": String::from_utf8_lossy(&body_bytes) }))
+        };
+        Ok((status, payload))
+    }
+
+    /// Register through the public endpoint with [`PASSWORD`].
+    pub async fn register(
+        &self,
+        username: &str,
+        email: &str,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        self.request_json_unauthenticated(
+            Method::POST,
+            "/api/v1/auth/register",
+            Some(json!({
+                "username": username,
+                "email": email,
+                "password": PASSWORD,
+            })),
+        )
+        .await
+    }
+}
+
+/// Create a user with a live session and return its id and bearer token.
+/// The token in the `link_path` link of the latest message mailed to `to`.
+pub fn mailed_token(outbox: &Outbox, to: &str, link_path: &str) -> String {
+    let outbox = outbox.lock().unwrap();
+    let mail = outbox
+        .iter()
+        .rev()
+        .find(|mail| mail.to == to)
+        .unwrap_or_else(|| panic!("no mail to {to}"));
+    let (_, rest) = mail
+        .body
+        .split_once(&format!("{MAIL_BASE_URL}{link_path}?token="))
+        .unwrap_or_else(|| panic!("no {link_path} link in mail to {to}"));
+    rest.split_whitespace().next().unwrap().to_string()
+}
+
+pub async fn create_authenticated_user_token(
+    db: &paracord_db::DbPool,
+) -> anyhow::Result<(i64, String)> {
+    create_user_with_flags(db, 0).await
+}
+
+/// Like [`create_authenticated_user_token`] with user `flags` set, e.g.
+/// `paracord_core::USER_FLAG_ADMIN`.
+pub async fn create_user_with_flags(
+    db: &paracord_db::DbPool,
+    flags: i32,
+) -> anyhow::Result<(i64, String)> {
+    let user_id = paracord_util::snowflake::generate(1);
+    let nonce = Uuid::new_v4().simple().to_string();
+    let username = format!("integration_{nonce}");
+    let email = format!("{nonce}@example.com");
+    let password_hash = paracord_core::auth::hash_password(PASSWORD)?;
+
+    let user =
+        paracord_db::users::create_user(db, user_id, &username, 1, &email, &password_hash).await?;
+    if flags != 0 {
+        paracord_db::users::update_user_flags(db, user.id, flags).await?;
+    }
+    let token = create_session_token(db, &user).await?;
+    Ok((user.id, token))
+}
+
+/// Open a session for an existing user and return its bearer token.
+pub async fn create_session_token(
+    db: &paracord_db::DbPool,
+    user: &paracord_db::users::UserRow,
+) -> anyhow::Result<String> {
+    let session_id = format!("sess-{}", Uuid::new_v4().simple());
+    let jti = format!("jti-{}", Uuid::new_v4().simple());
+    let refresh_hash = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    paracord_db::sessions::create_session(
+        db,
+        &session_id,
+        user.id,
+        &refresh_hash,
+        &jti,
+        user.public_key.as_deref(),
+        None,
+        None,
+        None,
+        Utc::now() + Duration::days(1),
+    )
+    .await?;
+
+    let token = paracord_core::auth::create_session_token(
+        user.id,
+        user.public_key.as_deref(),
+        JWT_SECRET,
+        3600,
+        &session_id,
+        &jti,
+    )?;
+    Ok(token)
+}
+
+pub async fn create_guild(ctx: &TestContext, name: &str) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            "/api/v1/guilds",
+            Some(json!({ "name": name, "icon": Value::Null })),
+        )
+        .await?;
+    assert_eq!(
+        status,
+        StatusCode::CREATED,
+        "create guild failed: {payload}"
+    );
+    Ok(payload["id"]
+        .as_str()
+        .context("guild id should be a string")?
+        .to_string())
+}
+
+pub async fn create_text_channel(
+    ctx: &TestContext,
+    guild_id: &str,
+    name: &str,
+) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/channels"),
+            Some(json!({
+                "name": name,
+                "channel_type": 0,
+                "parent_id": Value::Null,
+                "required_role_ids": Value::Null,
+            })),
+        )
+        .await?;
+    assert_eq!(
+        status,
+        StatusCode::CREATED,
+        "create channel failed: {payload}"
+    );
+    Ok(payload["id"]
+        .as_str()
+        .context("channel id should be a string")?
+        .to_string())
+}
+
+/// Add a fresh user (without a session) to a guild and return its id.
+pub async fn add_guild_member(ctx: &TestContext, guild_id: &str) -> anyhow::Result<i64> {
+    let user_id = paracord_util::snowflake::generate(1);
+    let nonce = Uuid::new_v4().simple().to_string();
+    paracord_db::users::create_user(
+        &ctx.db,
+        user_id,
+        &format!("member_{nonce}"),
+        1,
+        &format!("{nonce}@example.com"),
+        "unused-hash",
+    )
+    .await?;
+    paracord_db::members::add_member(&ctx.db, user_id, guild_id.parse()?).await?;
+    Ok(user_id)
+}
+
+/// Create a guild with a text channel and post one message in it. Returns
+/// `(guild_id, channel_id, message_id)`.
+pub async fn send_guild_message(ctx: &TestContext) -> anyhow::Result<(String, String, String)> {
+    let guild_id = create_guild(ctx, "Message Guild").await?;
+    let channel_id = create_text_channel(ctx, &guild_id, "general").await?;
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "pick one" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+    let message_id = message["id"].as_str().context("message id")?.to_string();
+    Ok((guild_id, channel_id, message_id))
+}
+
+/// Record an upload of `size` bytes that the signed-in user has not yet
+/// linked to a message.
+pub async fn create_pending_attachment(
+    ctx: &TestContext,
+    channel_id: &str,
+    size: i32,
+) -> anyhow::Result<String> {
+    let id = paracord_util::snowflake::generate(1);
+    paracord_db::attachments::create_attachment(
+        &ctx.db,
+        id,
+        None,
+        "file.txt",
+        Some("text/plain"),
+        size,
+        &format!("/api/v1/attachments/{id}"),
+        None,
+        None,
+        Some(ctx.user_id),
+        Some(channel_id.parse()?),
+        Some(Utc::now() + Duration::minutes(10)),
+        None,
+    )
+    .await?;
+    Ok(id.to_string())
+}
@@ -0,0 +1,117 @@
+use std::net::SocketAddr;
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header, Method, Request, StatusCode},
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+mod common;
+
+use common::TestContext;
+
+#[tokio::test]
+async fn cookie_auth_writes_require_csrf_token_but_bearer_writes_do_not() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let create_guild_with = |headers: Vec<(header::HeaderName, String)>| {
+        let app = ctx.app.clone();
+        async move {
+            let mut builder = Request::builder()
+                .method(Method::POST)
+                .uri("/api/v1/guilds")
+                .header(header::CONTENT_TYPE, "application/json");
+            for (name, value) in headers {
+                builder = builder.header(name, value);
+            }
+            let request = builder.body(Body::from(json!({ "name": "csrf" }).to_string()))?;
+            anyhow::Ok(app.oneshot(request).await?.status())
+        }
+    };
+    let session_cookie = format!(
+        "paracord_access={}; paracord_csrf=csrf-test-token",
+        ctx.token
+    );
+
+    let status = create_guild_with(vec![(header::COOKIE, session_cookie.clone())]).await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let status = create_guild_with(vec![
+        (header::COOKIE, session_cookie.clone()),
+        (
+            header::HeaderName::from_static("x-csrf-token"),
+            "wrong-token".to_string(),
+        ),
+    ])
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let status = create_guild_with(vec![
+        (header::COOKIE, session_cookie.clone()),
+        (
+            header::HeaderName::from_static("x-csrf-token"),
+            "csrf-test-token".to_string(),
+        ),
+    ])
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let status = create_guild_with(vec![
+        (header::COOKIE, session_cookie),
+        (header::AUTHORIZATION, format!("Bearer {}", ctx.token)),
+    ])
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn refresh_skips_the_csrf_check_and_issues_a_token() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let peer: SocketAddr = "127.0.0.1:40000".parse()?;
+    let login = Request::builder()
+        .method(Method::POST)
+        .uri("/api/v1/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .extension(ConnectInfo(peer))
+        .body(Body::from(
+            json!({
+                "username": "csrf_refresh",
+                "email": "csrf_refresh@example.com",
+                "password": common::PASSWORD,
+            })
+            .to_string(),
+        ))?;
+    let response = ctx.app.clone().oneshot(login).await?;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let session_cookies: Vec<String> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .filter_map(|v| v.split(';').next())
+        .filter(|v| v.starts_with("paracord_access=") || v.starts_with("paracord_refresh="))
+        .map(str::to_string)
+        .collect();
+    assert_eq!(session_cookies.len(), 2, "{session_cookies:?}");
+
+    // A session cookie but no CSRF cookie, as left behind by older releases.
+    let refresh = Request::builder()
+        .method(Method::POST)
+        .uri("/api/v1/auth/refresh")
+        .extension(ConnectInfo(peer))
+        .header(header::COOKIE, session_cookies.join("; "))
+        .body(Body::empty())?;
+    let response = ctx.app.clone().oneshot(refresh).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.starts_with("paracord_csrf=") && !v.starts_with("paracord_csrf=;")));
+
+    Ok(())
+}
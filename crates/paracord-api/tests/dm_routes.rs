@@ -0,0 +1,284 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{
+    add_guild_member, create_authenticated_user_token, create_guild, create_text_channel,
+    TestContext,
+};
+
+#[tokio::test]
+async fn group_dms_fan_out_to_current_recipients() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Group DM Guild").await?;
+    let (bob_id, bob_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, bob_id, guild_id.parse()?).await?;
+    let carol_id = add_guild_member(&ctx, &guild_id).await?;
+    let dave_id = add_guild_member(&ctx, &guild_id).await?;
+    let eve_id = add_guild_member(&ctx, &guild_id).await?;
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            "/api/v1/users/@me/group-dms",
+            Some(json!({ "recipient_ids": [bob_id.to_string()] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, group) = ctx
+        .request_json(
+            Method::POST,
+            "/api/v1/users/@me/group-dms",
+            Some(json!({
+                "recipient_ids": [bob_id.to_string(), carol_id.to_string()],
+                "name": "Weekend plans",
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{group}");
+    assert_eq!(group["type"], 3);
+    assert_eq!(group["owner_id"], ctx.user_id.to_string());
+    assert_eq!(group["recipients"].as_array().map(Vec::len), Some(3));
+    let group_id = group["id"].as_str().context("group id")?;
+
+    let (status, dms) = ctx
+        .request_json(Method::GET, "/api/v1/users/@me/dms", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(dms
+        .as_array()
+        .context("dms")?
+        .iter()
+        .any(|dm| dm["id"] == group_id && dm["name"] == "Weekend plans"));
+
+    // Members cannot add someone who blocked one of the participants.
+    paracord_db::relationships::create_relationship(&ctx.db, eve_id, carol_id, 2).await?;
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &format!("/api/v1/channels/{group_id}/recipients/{eve_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = ctx
+        .request_json_as(
+            &bob_token,
+            Method::PUT,
+            &format!("/api/v1/channels/{group_id}/recipients/{dave_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // Only the creator removes other people.
+    let (status, _) = ctx
+        .request_json_as(
+            &bob_token,
+            Method::DELETE,
+            &format!("/api/v1/channels/{group_id}/recipients/{carol_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            &format!("/api/v1/channels/{group_id}/recipients/{carol_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    let (status, sent) = ctx
+        .request_json_as(
+            &bob_token,
+            Method::POST,
+            &format!("/api/v1/channels/{group_id}/messages"),
+            Some(json!({
+                "content": "",
+                "e2ee": { "version": 1, "nonce": "bm9uY2U=", "ciphertext": "Y2lwaGVy" }
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{sent}");
+    let mut targets = None;
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "MESSAGE_CREATE" {
+            targets = event.target_user_ids.clone();
+        }
+    }
+    let mut targets = targets.context("MESSAGE_CREATE targets")?;
+    targets.sort();
+    let mut expected = vec![ctx.user_id, bob_id, dave_id];
+    expected.sort();
+    assert_eq!(targets, expected);
+
+    let (status, _) = ctx
+        .request_json_as(
+            &bob_token,
+            Method::DELETE,
+            &format!("/api/v1/channels/{group_id}/recipients/{bob_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = ctx
+        .request_json_as(
+            &bob_token,
+            Method::GET,
+            &format!("/api/v1/channels/{group_id}/messages"),
+            None,
+        )
+        .await?;
+    assert!(status == StatusCode::FORBIDDEN || status == StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn blocks_stop_dms_friend_requests_and_mentions_both_ways() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Block Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let (bob_id, bob_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, bob_id, guild_id.parse()?).await?;
+    let dm_id = paracord_util::snowflake::generate(1);
+    paracord_db::dms::create_dm_channel(&ctx.db, dm_id, ctx.user_id, bob_id).await?;
+
+    // Bob blocks us; the checks below run from both sides of the block.
+    let (status, _) = ctx
+        .request_json_as(
+            &bob_token,
+            Method::POST,
+            "/api/v1/users/@me/relationships",
+            Some(json!({ "user_id": ctx.user_id.to_string(), "type": 2 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    for (token, author_id, other_id) in [
+        (ctx.token.as_str(), ctx.user_id, bob_id),
+        (bob_token.as_str(), bob_id, ctx.user_id),
+    ] {
+        let (status, _) = ctx
+            .request_json_as(
+                token,
+                Method::POST,
+                "/api/v1/users/@me/dms",
+                Some(json!({ "recipient_id": other_id.to_string() })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = ctx
+            .request_json_as(
+                token,
+                Method::POST,
+                "/api/v1/users/@me/relationships",
+                Some(json!({ "user_id": other_id.to_string() })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // The existing DM still accepts the message but only the author gets it.
+        let mut events = ctx.state.event_bus.subscribe_system();
+        let (status, sent) = ctx
+            .request_json_as(
+                token,
+                Method::POST,
+                &format!("/api/v1/channels/{dm_id}/messages"),
+                Some(json!({
+                    "content": "",
+                    "e2ee": { "version": 1, "nonce": "bm9uY2U=", "ciphertext": "Y2lwaGVy" }
+                })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED, "{sent}");
+        let mut targets = None;
+        while let Ok(event) = events.try_recv() {
+            if event.event_type == "MESSAGE_CREATE" {
+                targets = event.target_user_ids.clone();
+            }
+        }
+        assert_eq!(targets, Some(vec![author_id]));
+
+        // Follow-up events on the message stay on the author's side too.
+        let message_path = format!(
+            "/api/v1/channels/{dm_id}/messages/{}",
+            sent["id"].as_str().context("message id")?
+        );
+        let (status, _) = ctx
+            .request_json_as(
+                token,
+                Method::PUT,
+                &format!("{message_path}/reactions/fire/@me"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let mut targets = None;
+        while let Ok(event) = events.try_recv() {
+            if event.event_type == "MESSAGE_REACTION_ADD" {
+                targets = event.target_user_ids.clone();
+            }
+        }
+        assert_eq!(targets, Some(vec![author_id]));
+
+        // The other side does not see it in the DM history either.
+        let other_token = if author_id == ctx.user_id {
+            bob_token.as_str()
+        } else {
+            ctx.token.as_str()
+        };
+        let (status, _) = ctx
+            .request_json_as(other_token, Method::GET, &message_path, None)
+            .await?;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, history) = ctx
+            .request_json_as(
+                other_token,
+                Method::GET,
+                &format!("/api/v1/channels/{dm_id}/messages"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK);
+        let author = author_id.to_string();
+        assert!(history
+            .as_array()
+            .context("history")?
+            .iter()
+            .all(|msg| msg["author"]["id"] != author.as_str()));
+        let (status, history) = ctx
+            .request_json_as(
+                token,
+                Method::GET,
+                &format!("/api/v1/channels/{dm_id}/messages"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!history.as_array().context("history")?.is_empty());
+
+        let (status, _) = ctx
+            .request_json_as(
+                token,
+                Method::POST,
+                &format!("/api/v1/channels/{channel_id}/messages"),
+                Some(json!({ "content": format!("hey <@{other_id}>") })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED);
+        // Mentions are counted in the background after the message is sent.
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let read_state =
+            paracord_db::read_states::get_read_state(&ctx.db, other_id, channel_id.parse()?)
+                .await?;
+        assert_eq!(read_state.map_or(0, |row| row.mention_count), 0);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{mailed_token, Outbox, TestContext};
+
+impl TestContext {
+    async fn login(
+        &self,
+        email: &str,
+        mfa_code: Option<&str>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let mut body = json!({ "email": email, "password": "IntegrationPass123!" });
+        if let Some(code) = mfa_code {
+            body["mfa_code"] = json!(code);
+        }
+        self.request_with(None, Method::POST, "/api/v1/auth/login", Some(body))
+            .await
+    }
+
+    /// An admin context with a recording mailer and email verification
+    /// turned on.
+    async fn with_verification_required() -> anyhow::Result<(Self, Outbox)> {
+        let (ctx, outbox) = Self::with_outbox().await?;
+        ctx.promote_to_admin().await?;
+        let (status, settings) = ctx
+            .request_with(
+                Some(&ctx.token),
+                Method::PATCH,
+                "/api/v1/admin/settings",
+                Some(json!({ "require_email_verification": "true" })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK, "{settings}");
+        assert_eq!(settings["require_email_verification"], "true");
+        Ok((ctx, outbox))
+    }
+
+    async fn verify_email(&self, token: &str) -> anyhow::Result<StatusCode> {
+        let (status, _) = self
+            .request_with(
+                None,
+                Method::POST,
+                "/api/v1/auth/verify-email",
+                Some(json!({ "token": token })),
+            )
+            .await?;
+        Ok(status)
+    }
+}
+
+#[tokio::test]
+async fn unverified_accounts_need_the_mailed_token_to_log_in() -> anyhow::Result<()> {
+    let (ctx, outbox) = TestContext::with_verification_required().await?;
+
+    let (status, options) = ctx
+        .request_with(None, Method::GET, "/api/v1/auth/options", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(options["require_email_verification"], true);
+
+    let (status, registered) = ctx.register("pending_user", "pending@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    assert_eq!(registered["email_verification_required"], true);
+    assert!(registered.get("token").is_none(), "{registered}");
+    let first_token = mailed_token(&outbox, "pending@example.com", "/verify-email");
+
+    let (status, body) = ctx.login("pending@example.com", None).await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    assert_eq!(body["code"], "EMAIL_UNVERIFIED");
+
+    // Accounts from before verification was required can still sign in.
+    let existing = paracord_db::users::get_user_by_id(&ctx.db, ctx.user_id)
+        .await?
+        .expect("fixture user");
+    let (status, body) = ctx.login(&existing.email, None).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    assert_eq!(
+        ctx.verify_email("not-a-real-token").await?,
+        StatusCode::BAD_REQUEST
+    );
+
+    // A resent link replaces the first one.
+    let (status, body) = ctx
+        .request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/verify-email/resend",
+            Some(json!({ "email": "Pending@Example.com" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    assert_eq!(outbox.lock().unwrap().len(), 2);
+    let token = mailed_token(&outbox, "pending@example.com", "/verify-email");
+    assert_ne!(token, first_token);
+    assert_eq!(
+        ctx.verify_email(&first_token).await?,
+        StatusCode::BAD_REQUEST
+    );
+
+    assert_eq!(ctx.verify_email(&token).await?, StatusCode::NO_CONTENT);
+    assert_eq!(ctx.verify_email(&token).await?, StatusCode::BAD_REQUEST);
+
+    let (status, body) = ctx.login("pending@example.com", None).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    // Verified and unknown addresses get the same answer and no mail.
+    for email in ["pending@example.com", "nobody@example.com"] {
+        let (status, _) = ctx
+            .request_with(
+                None,
+                Method::POST,
+                "/api/v1/auth/verify-email/resend",
+                Some(json!({ "email": email })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    assert_eq!(outbox.lock().unwrap().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn verification_cannot_be_required_without_a_mail_transport() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    ctx.promote_to_admin().await?;
+    let (status, body) = ctx
+        .request_with(
+            Some(&ctx.token),
+            Method::PATCH,
+            "/api/v1/admin/settings",
+            Some(json!({ "require_email_verification": "true" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn registration_without_email_is_rejected_while_verification_is_required(
+) -> anyhow::Result<()> {
+    let (ctx, _outbox) = TestContext::with_verification_required().await?;
+
+    let (status, body) = ctx.register("no_email_user", "").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    Ok(())
+}
+
+async fn key_login(
+    ctx: &TestContext,
+    key: &ed25519_dalek::SigningKey,
+    username: &str,
+) -> anyhow::Result<(StatusCode, Value)> {
+    use ed25519_dalek::Signer;
+
+    let hex = |bytes: &[u8]| -> String { bytes.iter().map(|b| format!("{b:02x}")).collect() };
+    let (status, challenge) = ctx
+        .request_with(None, Method::POST, "/api/v1/auth/challenge", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{challenge}");
+    let nonce = challenge["nonce"].as_str().unwrap();
+    let timestamp = challenge["timestamp"].as_i64().unwrap();
+    let origin = challenge["server_origin"].as_str().unwrap();
+    let signature = key.sign(format!("{nonce}:{timestamp}:{origin}").as_bytes());
+    let body = json!({
+        "public_key": hex(key.verifying_key().as_bytes()),
+        "nonce": nonce,
+        "timestamp": timestamp,
+        "signature": hex(&signature.to_bytes()),
+        "username": username,
+    });
+    ctx.request_with(None, Method::POST, "/api/v1/auth/verify", Some(body))
+        .await
+}
+
+#[tokio::test]
+async fn public_key_login_follows_the_verification_requirement() -> anyhow::Result<()> {
+    let (ctx, _outbox) = TestContext::with_verification_required().await?;
+    let (status, registered) = ctx.register("keyed_user", "keyed@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let user_id: i64 = registered["user"]["id"].as_str().unwrap().parse()?;
+
+    let key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+    let public_key: String = key
+        .verifying_key()
+        .as_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    sqlx::query("UPDATE users SET public_key = $1 WHERE id = $2")
+        .bind(&public_key)
+        .bind(user_id)
+        .execute(&ctx.db)
+        .await?;
+    let (status, body) = key_login(&ctx, &key, "keyed_user").await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    assert_eq!(body["code"], "EMAIL_UNVERIFIED");
+
+    // Key-only accounts have no address to verify, so none are created.
+    let stranger = ed25519_dalek::SigningKey::from_bytes(&[8u8; 32]);
+    let (status, body) = key_login(&ctx, &stranger, "key_only").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{create_user_with_flags, TestContext};
+
+async fn create_guild(ctx: &TestContext, token: &str, path: &str) -> anyhow::Result<StatusCode> {
+    let (status, _) = ctx
+        .request_json_as(
+            token,
+            Method::POST,
+            path,
+            Some(json!({ "name": "Restricted Guild" })),
+        )
+        .await?;
+    Ok(status)
+}
+
+#[tokio::test]
+async fn restricted_guild_creation_blocks_normal_users_only() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (_, member_token) = create_user_with_flags(&ctx.db, 0).await?;
+    let (_, creator_token) =
+        create_user_with_flags(&ctx.db, paracord_core::USER_FLAG_GUILD_CREATOR).await?;
+
+    assert_eq!(
+        create_guild(&ctx, &member_token, "/api/v1/guilds").await?,
+        StatusCode::CREATED
+    );
+
+    let (status, settings) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            "/api/v1/admin/settings",
+            Some(json!({ "guild_creation_restricted": "true" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{settings}");
+    assert_eq!(settings["guild_creation_restricted"], "true");
+
+    assert_eq!(
+        create_guild(&ctx, &member_token, "/api/v1/guilds").await?,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        create_guild(&ctx, &member_token, "/api/v1/guilds/from-template/unknown").await?,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        create_guild(&ctx, &creator_token, "/api/v1/guilds").await?,
+        StatusCode::CREATED
+    );
+    assert_eq!(
+        create_guild(&ctx, &ctx.token, "/api/v1/guilds").await?,
+        StatusCode::CREATED
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn bootstrap_returns_guilds_with_channels_roles_and_member() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (user_id, token) = create_user_with_flags(&ctx.db, 0).await?;
+
+    assert_eq!(
+        create_guild(&ctx, &token, "/api/v1/guilds").await?,
+        StatusCode::CREATED
+    );
+
+    let (status, bootstrap) = ctx
+        .request_json_as(&token, Method::GET, "/api/v1/users/@me/bootstrap", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{bootstrap}");
+    assert_eq!(bootstrap["user"]["id"], user_id.to_string());
+
+    let guilds = bootstrap["guilds"].as_array().expect("guilds array");
+    assert_eq!(guilds.len(), 1);
+    let guild = &guilds[0];
+    assert_eq!(guild["member_count"], 1);
+    assert!(!guild["channels"].as_array().expect("channels").is_empty());
+    assert!(guild["roles"]
+        .as_array()
+        .expect("roles")
+        .iter()
+        .any(|role| role["id"] == guild["id"]));
+    assert_eq!(guild["member"]["user_id"], user_id.to_string());
+    assert!(guild["member"]["roles"].is_array());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn blocked_name_patterns_reject_usernames_and_guild_names() -> anyhow::Result<()> {
+    let ctx = TestContext::new_admin().await?;
+    let (status, settings) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            "/api/v1/admin/settings",
+            Some(json!({ "blocked_name_patterns": "/(unclosed/" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{settings}");
+    let (status, settings) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            "/api/v1/admin/settings",
+            Some(json!({ "blocked_name_patterns": "official\n/^staff\\d*$/" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{settings}");
+    assert_eq!(settings["blocked_names_fold"], "true");
+
+    let register = |username: &'static str, email: &'static str| {
+        ctx.request_json_as(
+            "",
+            Method::POST,
+            "/api/v1/auth/register",
+            Some(json!({
+                "email": email,
+                "username": username,
+                "password": "IntegrationPass123!",
+            })),
+        )
+    };
+    // A Cyrillic "О" and a "4" stand in for "o" and "a".
+    let (status, body) = register("\u{041e}ffici4l_news", "blocked@example.com").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    let (status, body) = register("Staff42", "staff@example.com").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    let (status, body) = register("friendly_user", "clean@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+
+    let (status, body) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::POST,
+            "/api/v1/guilds",
+            Some(json!({ "name": "The OFFICIAL Guild" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    let (status, guild) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::POST,
+            "/api/v1/guilds",
+            Some(json!({ "name": "Book Club" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{guild}");
+    let guild_id = guild["id"].as_str().expect("guild id");
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}"),
+            Some(json!({ "name": "Official Book Club" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Display names and nicknames go through the same filter.
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            "/api/v1/users/@me",
+            Some(json!({ "display_name": "0fficial Support" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            "/api/v1/users/@me",
+            Some(json!({ "display_name": "Book Worm" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let member_path = format!("/api/v1/guilds/{guild_id}/members/{}", ctx.user_id);
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            &member_path,
+            Some(json!({ "nick": "staff7" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = ctx
+        .request_json_as(
+            &ctx.token,
+            Method::PATCH,
+            &member_path,
+            Some(json!({ "nick": "Librarian" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let events =
+        paracord_db::security_events::list_events(&ctx.db, Some("name_filter.blocked"), None, 10)
+            .await?;
+    assert_eq!(events.len(), 6);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::TestContext;
+
+// The configured layout is read once per process, so this binary holds a
+// single test that sets it before the first guild is created.
+#[tokio::test]
+async fn new_guilds_get_the_configured_default_channels_in_order() -> anyhow::Result<()> {
+    std::env::set_var(
+        "PARACORD_DEFAULT_GUILD_CHANNELS",
+        json!([
+            { "name": "Information", "type": "category" },
+            { "name": "rules", "type": "text", "category": "Information" },
+            { "name": "general", "type": "text" },
+            { "name": "Lounge", "type": "voice" },
+        ])
+        .to_string(),
+    );
+    let ctx = TestContext::new().await?;
+
+    let (status, guild) = ctx
+        .request_json(
+            Method::POST,
+            "/api/v1/guilds",
+            Some(json!({ "name": "Configured Guild", "icon": Value::Null })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{guild}");
+    let guild_id = guild["id"]
+        .as_str()
+        .context("guild id should be a string")?;
+
+    let (status, channels) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/channels"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{channels}");
+    let channels = channels.as_array().context("channel list")?;
+    let layout: Vec<(&str, i64)> = channels
+        .iter()
+        .map(|c| {
+            (
+                c["name"].as_str().unwrap_or_default(),
+                c["type"].as_i64().unwrap_or(-1),
+            )
+        })
+        .collect();
+    assert_eq!(
+        layout,
+        vec![
+            ("Information", 4),
+            ("rules", 0),
+            ("general", 0),
+            ("Lounge", 2)
+        ]
+    );
+    assert_eq!(channels[1]["parent_id"], channels[0]["id"]);
+    assert!(channels[2]["parent_id"].is_null());
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_authenticated_user_token, create_guild, TestContext};
+
+async fn create_role(ctx: &TestContext, guild_id: &str, body: Value) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/roles"),
+            Some(body),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    Ok(payload["id"]
+        .as_str()
+        .context("role id should be a string")?
+        .to_string())
+}
+
+async fn create_channel(ctx: &TestContext, guild_id: &str, body: Value) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/channels"),
+            Some(body),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    Ok(payload["id"]
+        .as_str()
+        .context("channel id should be a string")?
+        .to_string())
+}
+
+async fn create_template(ctx: &TestContext, guild_id: &str) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            "/api/v1/guilds/templates",
+            Some(json!({ "guild_id": guild_id, "name": "Starter", "description": "Basic layout" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    assert_eq!(payload["source_guild_id"], guild_id);
+    Ok(payload["code"]
+        .as_str()
+        .context("template code should be a string")?
+        .to_string())
+}
+
+/// Channels as `(name, type, parent name, topic, required role names)`,
+/// ordered by name, plus role overwrites as `(channel, role, allow, deny)`.
+type ChannelLayout = Vec<(String, i16, Option<String>, Option<String>, Vec<String>)>;
+type OverwriteLayout = Vec<(String, String, i64, i64)>;
+
+async fn guild_layout(
+    ctx: &TestContext,
+    guild_id: i64,
+) -> anyhow::Result<(
+    Vec<(String, i64, i32, bool)>,
+    ChannelLayout,
+    OverwriteLayout,
+)> {
+    let roles = paracord_db::roles::get_guild_roles(&ctx.db, guild_id).await?;
+    let role_name = |id: i64| {
+        roles
+            .iter()
+            .find(|r| r.id == id)
+            .map(|r| r.name.clone())
+            .unwrap_or_default()
+    };
+    let mut role_layout: Vec<_> = roles
+        .iter()
+        .map(|r| (r.name.clone(), r.permissions, r.color, r.hoist))
+        .collect();
+    role_layout.sort();
+
+    let channels = paracord_db::channels::get_guild_channels(&ctx.db, guild_id).await?;
+    let channel_name = |id: i64| {
+        channels
+            .iter()
+            .find(|c| c.id == id)
+            .and_then(|c| c.name.clone())
+            .unwrap_or_default()
+    };
+    let mut channel_layout: ChannelLayout = channels
+        .iter()
+        .map(|c| {
+            (
+                c.name.clone().unwrap_or_default(),
+                c.channel_type,
+                c.parent_id.map(channel_name),
+                c.topic.clone(),
+                paracord_db::channels::parse_required_role_ids(&c.required_role_ids)
+                    .into_iter()
+                    .map(role_name)
+                    .collect(),
+            )
+        })
+        .collect();
+    channel_layout.sort();
+
+    let overwrites = paracord_db::channel_overwrites::get_overwrites_for_channels(
+        &ctx.db,
+        &channels.iter().map(|c| c.id).collect::<Vec<_>>(),
+    )
+    .await?;
+    let mut overwrite_layout: OverwriteLayout = overwrites
+        .iter()
+        .map(|o| {
+            (
+                channel_name(o.channel_id),
+                role_name(o.target_id),
+                o.allow_perms,
+                o.deny_perms,
+            )
+        })
+        .collect();
+    overwrite_layout.sort();
+
+    Ok((role_layout, channel_layout, overwrite_layout))
+}
+
+#[tokio::test]
+async fn guild_from_template_reproduces_roles_and_channels() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Source Guild").await?;
+    let moderators = create_role(
+        &ctx,
+        &guild_id,
+        json!({ "name": "Moderators", "permissions": 8192, "color": 0x3498db, "hoist": true }),
+    )
+    .await?;
+    let category = create_channel(
+        &ctx,
+        &guild_id,
+        json!({ "name": "Staff", "channel_type": 4 }),
+    )
+    .await?;
+    let mod_chat = create_channel(
+        &ctx,
+        &guild_id,
+        json!({
+            "name": "mod-chat",
+            "channel_type": 0,
+            "parent_id": category.parse::<i64>()?,
+            "required_role_ids": [moderators],
+        }),
+    )
+    .await?;
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{mod_chat}"),
+            Some(json!({ "topic": "Moderation only" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    for (target, allow, deny) in [(&guild_id, 0, 2048), (&moderators, 2048, 0)] {
+        let (status, payload) = ctx
+            .request_json(
+                Method::PUT,
+                &format!("/api/v1/channels/{mod_chat}/overwrites/{target}"),
+                Some(json!({ "target_type": 0, "allow_perms": allow, "deny_perms": deny })),
+            )
+            .await?;
+        assert!(status.is_success(), "{status}: {payload}");
+    }
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{mod_chat}/messages"),
+            Some(json!({ "content": "not part of the template" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+
+    let code = create_template(&ctx, &guild_id).await?;
+    let (status, created) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/from-template/{code}"),
+            Some(json!({ "name": "Copied Guild" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    let new_guild_id: i64 = created["id"].as_str().context("guild id")?.parse()?;
+    assert_ne!(new_guild_id.to_string(), guild_id);
+
+    let source = guild_layout(&ctx, guild_id.parse()?).await?;
+    let copy = guild_layout(&ctx, new_guild_id).await?;
+    assert_eq!(copy, source);
+    assert_eq!(copy.1.len(), 4, "{:?}", copy.1);
+
+    let copied_channels = paracord_db::channels::get_guild_channels(&ctx.db, new_guild_id).await?;
+    assert!(copied_channels.iter().all(|c| c.last_message_id.is_none()));
+
+    let (_, template) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/templates/{code}"),
+            None,
+        )
+        .await?;
+    assert_eq!(template["usage_count"], 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn guild_templates_enforce_permissions() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Source Guild").await?;
+    let code = create_template(&ctx, &guild_id).await?;
+    let (outsider_id, outsider) = create_authenticated_user_token(&ctx.db).await?;
+
+    // Only members with MANAGE_GUILD can snapshot or list a guild's templates.
+    let (status, _) = ctx
+        .request_json_as(
+            &outsider,
+            Method::POST,
+            "/api/v1/guilds/templates",
+            Some(json!({ "guild_id": guild_id, "name": "Stolen" })),
+        )
+        .await?;
+    assert!(status.is_client_error(), "{status}");
+    let (status, _) = ctx
+        .request_json_as(
+            &outsider,
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/templates"),
+            None,
+        )
+        .await?;
+    assert!(status.is_client_error(), "{status}");
+
+    // Anyone holding the code can start a guild from it and owns the result.
+    let (status, created) = ctx
+        .request_json_as(
+            &outsider,
+            Method::POST,
+            &format!("/api/v1/guilds/from-template/{code}"),
+            Some(json!({ "name": "Outsider Guild" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    let (status, mine) = ctx
+        .request_json_as(
+            &outsider,
+            Method::GET,
+            &format!("/api/v1/guilds/{}", created["id"].as_str().context("id")?),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{mine}");
+    assert_eq!(mine["owner_id"], outsider_id.to_string());
+
+    let (status, _) = ctx
+        .request_json_as(
+            &outsider,
+            Method::DELETE,
+            &format!("/api/v1/guilds/templates/{code}"),
+            None,
+        )
+        .await?;
+    assert!(status.is_client_error(), "{status}");
+
+    let (status, listed) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/templates"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().map(Vec::len), Some(1));
+
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            &format!("/api/v1/guilds/templates/{code}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/from-template/{code}"),
+            Some(json!({ "name": "Too Late" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::http::{Method, StatusCode};
+use paracord_media::{LocalStorage, Storage};
+
+mod common;
+
+use common::TestContext;
+
+/// `storage_root` overrides where the storage backend writes; by default
+/// it is a fresh temp directory.
+async fn health_context(storage_root: Option<PathBuf>) -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        if let Some(root) = storage_root {
+            state.storage_backend = Arc::new(Storage::Local(LocalStorage::new(root)));
+        }
+    })
+    .await
+}
+
+#[tokio::test]
+async fn liveness_is_always_ok() -> anyhow::Result<()> {
+    let ctx = health_context(None).await?;
+    let (status, payload) = ctx
+        .request_json_unauthenticated(Method::GET, "/healthz", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["status"], "ok");
+    Ok(())
+}
+
+#[tokio::test]
+async fn readiness_reports_each_subsystem() -> anyhow::Result<()> {
+    let ctx = health_context(None).await?;
+    let (status, payload) = ctx
+        .request_json_unauthenticated(Method::GET, "/readyz", None)
+        .await?;
+
+    // No LiveKit is running in tests; it is reported but not critical.
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["status"], "degraded");
+    assert_eq!(payload["checks"]["database"]["ok"], true);
+    assert_eq!(payload["checks"]["storage"]["ok"], true);
+    assert_eq!(payload["checks"]["livekit"]["ok"], false);
+    assert!(payload["checks"]["database"]["latency_ms"].is_u64());
+    // Failure details stay in the server log.
+    assert!(payload["checks"]["livekit"].get("error").is_none());
+    assert!(payload["checks"]["livekit"].get("critical").is_none());
+
+    // The storage probe cleans up after itself.
+    let health_dir = std::path::Path::new(&ctx.state.config.storage_path).join(".health");
+    assert!(!health_dir.exists() || std::fs::read_dir(&health_dir)?.next().is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn readiness_results_are_cached_briefly() -> anyhow::Result<()> {
+    let ctx = health_context(None).await?;
+    *ctx.state.readiness_cache.lock().await = Some((
+        std::time::Instant::now(),
+        serde_json::json!({ "status": "unavailable" }),
+    ));
+    let (status, payload) = ctx
+        .request_json_unauthenticated(Method::GET, "/readyz", None)
+        .await?;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{payload}");
+    assert_eq!(payload["status"], "unavailable");
+
+    *ctx.state.readiness_cache.lock().await = Some((
+        std::time::Instant::now() - std::time::Duration::from_secs(60),
+        serde_json::json!({ "status": "unavailable" }),
+    ));
+    let (status, payload) = ctx
+        .request_json_unauthenticated(Method::GET, "/readyz", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["checks"]["database"]["ok"], true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn readiness_fails_when_storage_is_not_writable() -> anyhow::Result<()> {
+    let blocker = tempfile::NamedTempFile::new()?;
+    let ctx = health_context(Some(blocker.path().to_path_buf())).await?;
+    let (status, payload) = ctx
+        .request_json_unauthenticated(Method::GET, "/readyz", None)
+        .await?;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{payload}");
+    assert_eq!(payload["status"], "unavailable");
+    assert_eq!(payload["checks"]["storage"]["ok"], false);
+    assert_eq!(payload["checks"]["database"]["ok"], true);
+    Ok(())
+}
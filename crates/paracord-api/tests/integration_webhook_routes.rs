@@ -0,0 +1,205 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+mod common;
+
+use common::{create_guild, create_text_channel, create_user_with_flags, TestContext};
+
+async fn subscribe(
+    ctx: &TestContext,
+    guild_id: &str,
+    url: &str,
+    event_types: Value,
+) -> anyhow::Result<(StatusCode, Value)> {
+    ctx.request_json(
+        Method::POST,
+        &format!("/api/v1/guilds/{guild_id}/integrations/webhooks"),
+        Some(json!({ "url": url, "event_types": event_types })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn subscribed_event_is_queued_as_a_signed_delivery() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Integrations").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "releases").await?;
+
+    let (status, subscription) = subscribe(
+        &ctx,
+        &guild_id,
+        "https://93.184.215.14/hooks/paracord",
+        json!(["channel_pins_update"]),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED, "{subscription}");
+    assert_eq!(subscription["event_types"], json!(["CHANNEL_PINS_UPDATE"]));
+    let secret = subscription["secret"]
+        .as_str()
+        .context("secret is returned at creation")?
+        .to_string();
+
+    let (status, listed) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/integrations/webhooks"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert!(listed[0].get("secret").is_none());
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "v1.2 is out" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+    let message_id = message["id"].as_str().context("message id")?;
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &format!("/api/v1/channels/{channel_id}/pins/{message_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    while let Ok(event) = events.try_recv() {
+        paracord_api::routes::integrations::enqueue_integration_event(&ctx.state, &event).await;
+    }
+
+    let due = paracord_db::integration_webhooks::fetch_due_deliveries(
+        &ctx.state.db,
+        Utc::now().timestamp_millis(),
+        10,
+    )
+    .await?;
+    assert_eq!(due.len(), 1, "only the pin event is subscribed");
+    let delivery = &due[0];
+    assert_eq!(delivery.event_type, "CHANNEL_PINS_UPDATE");
+    assert_eq!(delivery.url, "https://93.184.215.14/hooks/paracord");
+    let body: Value = serde_json::from_str(&delivery.payload)?;
+    assert_eq!(body["type"], "CHANNEL_PINS_UPDATE");
+    assert_eq!(body["guild_id"], guild_id.as_str());
+    assert_eq!(body["data"]["channel_id"], channel_id.as_str());
+
+    let timestamp = 1_700_000_000;
+    let signature = paracord_api::routes::integrations::sign_delivery(
+        &delivery.secret,
+        timestamp,
+        &delivery.payload,
+    );
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(format!("{timestamp}.{}", delivery.payload).as_bytes());
+    let expected: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    assert_eq!(signature, format!("sha256={expected}"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn channel_events_skip_subscriptions_whose_creator_cannot_view_the_channel(
+) -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Integrations").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "staff").await?;
+
+    let (status, visible) = subscribe(
+        &ctx,
+        &guild_id,
+        "https://93.184.215.14/hooks/owner",
+        json!(["MESSAGE_CREATE"]),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED, "{visible}");
+    // A subscription left behind by someone who is no longer a member.
+    let (outsider_id, _) = create_user_with_flags(&ctx.db, 0).await?;
+    paracord_db::integration_webhooks::create_subscription(
+        &ctx.db,
+        paracord_util::snowflake::generate(1),
+        guild_id.parse()?,
+        "https://93.184.215.14/hooks/outsider",
+        "secret",
+        "MESSAGE_CREATE",
+        outsider_id,
+    )
+    .await?;
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "quarterly numbers" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+    while let Ok(event) = events.try_recv() {
+        paracord_api::routes::integrations::enqueue_integration_event(&ctx.state, &event).await;
+    }
+
+    let due = paracord_db::integration_webhooks::fetch_due_deliveries(
+        &ctx.state.db,
+        Utc::now().timestamp_millis(),
+        10,
+    )
+    .await?;
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].url, "https://93.184.215.14/hooks/owner");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn subscriptions_to_private_or_insecure_urls_are_rejected() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Integrations").await?;
+
+    for url in [
+        "https://127.0.0.1/hook",
+        "https://10.0.0.5/hook",
+        "https://169.254.169.254/latest/meta-data",
+        "https://[::1]/hook",
+        "https://localhost/hook",
+        "http://93.184.215.14/hook",
+        "ftp://93.184.215.14/hook",
+    ] {
+        let (status, body) = subscribe(&ctx, &guild_id, url, json!(["GUILD_MEMBER_ADD"])).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}: {body}");
+    }
+
+    let (status, _) = subscribe(
+        &ctx,
+        &guild_id,
+        "https://93.184.215.14/hook",
+        json!(["TYPING_START"]),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, listed) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/integrations/webhooks"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(listed.as_array().unwrap().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Method, Request, StatusCode};
+use paracord_core::link_unfurl::{ImageFetchFuture, LinkFetchFuture, LinkImage, LinkUnfurler};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{
+    add_guild_member, create_guild, create_session_token, create_text_channel, TestContext,
+};
+
+/// URLs the stub fetcher was asked for.
+type FetchLog = Arc<Mutex<Vec<String>>>;
+
+const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\nstub";
+
+/// A context whose link unfurler serves canned Open Graph pages (each with
+/// an `og:image`) and a stub image, and logs every page URL it is asked to
+/// fetch.
+async fn unfurl_context() -> anyhow::Result<(TestContext, FetchLog)> {
+    let fetched = Arc::new(Mutex::new(Vec::new()));
+    let log = fetched.clone();
+    let unfurler = LinkUnfurler::new(
+        Arc::new(move |url: String| -> LinkFetchFuture {
+            log.lock().unwrap().push(url.clone());
+            Box::pin(async move {
+                Ok(format!(
+                    r#"<html><head>
+                        <meta property="og:title" content="Preview of {url}">
+                        <meta property="og:description" content="Fetched by the stub">
+                        <meta property="og:site_name" content="Example">
+                        <meta property="og:image" content="https://cdn.example.com/card.png">
+                        </head></html>"#
+                ))
+            })
+        }),
+        std::time::Duration::from_secs(60),
+    )
+    .with_image_fetcher(Arc::new(|_url: String| -> ImageFetchFuture {
+        Box::pin(async {
+            Ok(LinkImage {
+                content_type: "image/png".into(),
+                body: PNG_BYTES.to_vec(),
+            })
+        })
+    }));
+    let ctx = TestContext::with_state(|state| {
+        state.link_unfurler = Some(Arc::new(unfurler));
+    })
+    .await?;
+    Ok((ctx, fetched))
+}
+
+async fn enable_link_unfurls(ctx: &TestContext, guild_id: &str) -> anyhow::Result<()> {
+    let (status, guild) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}"),
+            Some(json!({ "link_unfurls": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{guild}");
+    assert_eq!(guild["features"], json!(["LINK_UNFURLS"]));
+    Ok(())
+}
+
+/// GET `uri` without credentials, returning the status, content type and
+/// raw body.
+async fn get_raw(ctx: &TestContext, uri: &str) -> anyhow::Result<(StatusCode, String, Vec<u8>)> {
+    let mut request = Request::builder().uri(uri).body(Body::empty())?;
+    request
+        .extensions_mut()
+        .insert(axum::extract::ConnectInfo(std::net::SocketAddr::from((
+            [127, 0, 0, 1],
+            40000,
+        ))));
+    let response = ctx.app.clone().oneshot(request).await?;
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, content_type, body.to_vec()))
+}
+
+async fn send_message(ctx: &TestContext, channel_id: &str, content: &str) -> anyhow::Result<Value> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": content })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    Ok(payload)
+}
+
+/// Poll the message until the background unfurl has stored its embeds.
+async fn wait_for_embeds(ctx: &TestContext, message: &Value) -> anyhow::Result<Vec<Value>> {
+    let path = format!(
+        "/api/v1/channels/{}/messages/{}",
+        message["channel_id"].as_str().context("channel id")?,
+        message["id"].as_str().context("message id")?
+    );
+    for _ in 0..100 {
+        let (status, payload) = ctx.request_json(Method::GET, &path, None).await?;
+        assert_eq!(status, StatusCode::OK);
+        let embeds = payload["embeds"].as_array().cloned().unwrap_or_default();
+        if !embeds.is_empty() {
+            return Ok(embeds);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    anyhow::bail!("message never got embeds")
+}
+
+#[tokio::test]
+async fn public_link_gets_a_cached_preview_embed() -> anyhow::Result<()> {
+    let (ctx, fetched) = unfurl_context().await?;
+    let guild_id = create_guild(&ctx, "Unfurl Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "links").await?;
+    enable_link_unfurls(&ctx, &guild_id).await?;
+
+    let message = send_message(&ctx, &channel_id, "read https://news.example.com/story.").await?;
+    assert_eq!(message["embeds"], json!([]));
+    let embeds = wait_for_embeds(&ctx, &message).await?;
+    assert_eq!(embeds.len(), 1);
+    assert_eq!(embeds[0]["url"], "https://news.example.com/story");
+    assert_eq!(
+        embeds[0]["title"],
+        "Preview of https://news.example.com/story"
+    );
+    assert_eq!(embeds[0]["description"], "Fetched by the stub");
+    assert_eq!(embeds[0]["type"], "link");
+    assert_eq!(embeds[0]["site_name"], "Example");
+
+    let repeat = send_message(&ctx, &channel_id, "again: https://news.example.com/story").await?;
+    let embeds = wait_for_embeds(&ctx, &repeat).await?;
+    assert_eq!(embeds[0]["url"], "https://news.example.com/story");
+    assert_eq!(
+        *fetched.lock().unwrap(),
+        vec!["https://news.example.com/story".to_string()],
+        "the second message should be served from the cache"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn private_and_loopback_links_are_never_fetched() -> anyhow::Result<()> {
+    let (ctx, fetched) = unfurl_context().await?;
+    let guild_id = create_guild(&ctx, "Unfurl Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "links").await?;
+    enable_link_unfurls(&ctx, &guild_id).await?;
+
+    let private_only = send_message(
+        &ctx,
+        &channel_id,
+        "https://127.0.0.1/admin https://localhost:8080/ https://[::1]/ \
+         https://169.254.169.254/latest/meta-data https://10.0.0.5/ https://intranet/wiki",
+    )
+    .await?;
+    assert_eq!(private_only["embeds"], json!([]));
+    assert!(fetched.lock().unwrap().is_empty());
+
+    let mixed = send_message(
+        &ctx,
+        &channel_id,
+        "https://192.168.1.1/router http://example.com/plain https://public.example.org/page",
+    )
+    .await?;
+    let embeds = wait_for_embeds(&ctx, &mixed).await?;
+    assert_eq!(embeds.len(), 1);
+    assert_eq!(embeds[0]["url"], "https://public.example.org/page");
+    assert_eq!(
+        *fetched.lock().unwrap(),
+        vec!["https://public.example.org/page".to_string()]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn links_are_not_unfurled_without_the_guild_feature() -> anyhow::Result<()> {
+    let (ctx, fetched) = unfurl_context().await?;
+    let guild_id = create_guild(&ctx, "Plain Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "links").await?;
+
+    send_message(&ctx, &channel_id, "https://news.example.com/story").await?;
+    assert!(fetched.lock().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn preview_images_are_served_through_the_signed_proxy() -> anyhow::Result<()> {
+    let (ctx, _) = unfurl_context().await?;
+    let guild_id = create_guild(&ctx, "Image Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "links").await?;
+    enable_link_unfurls(&ctx, &guild_id).await?;
+
+    let message = send_message(&ctx, &channel_id, "https://news.example.com/story").await?;
+    let embeds = wait_for_embeds(&ctx, &message).await?;
+    let thumbnail = embeds[0]["thumbnail"].as_str().context("thumbnail")?;
+    assert!(
+        thumbnail.starts_with("/api/v1/embeds/thumbnail?url=https%3A%2F%2Fcdn.example.com"),
+        "{thumbnail}"
+    );
+
+    let (status, content_type, body) = get_raw(&ctx, thumbnail).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "image/png");
+    assert_eq!(body, PNG_BYTES);
+
+    let (status, _, _) = get_raw(&ctx, &thumbnail.replace("card.png", "other.png")).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn only_guild_managers_toggle_link_previews() -> anyhow::Result<()> {
+    let (ctx, fetched) = unfurl_context().await?;
+    let guild_id = create_guild(&ctx, "Managed Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "links").await?;
+    let member_id = add_guild_member(&ctx, &guild_id).await?;
+    let member = paracord_db::users::get_user_by_id(&ctx.db, member_id)
+        .await?
+        .context("member")?;
+    let member_token = create_session_token(&ctx.db, &member).await?;
+
+    let (status, _) = ctx
+        .request_with(
+            Some(&member_token),
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}"),
+            Some(json!({ "link_unfurls": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    enable_link_unfurls(&ctx, &guild_id).await?;
+    let (status, guild) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}"),
+            Some(json!({ "link_unfurls": false })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{guild}");
+    assert_eq!(guild["features"], json!([]));
+
+    send_message(&ctx, &channel_id, "https://news.example.com/story").await?;
+    assert!(fetched.lock().unwrap().is_empty());
+
+    Ok(())
+}
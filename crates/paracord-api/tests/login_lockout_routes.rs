@@ -0,0 +1,132 @@
+use std::net::SocketAddr;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::ConnectInfo,
+    http::{header, HeaderMap, Method, Request, StatusCode},
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{TestContext, PASSWORD};
+
+async fn lockout_context() -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        state.config.allow_username_login = true;
+        state.config.require_email = false;
+        state.config.login_max_failures = 3;
+    })
+    .await
+}
+
+impl TestContext {
+    async fn request_from(
+        &self,
+        peer: &str,
+        path: &str,
+        body: Value,
+    ) -> anyhow::Result<(StatusCode, HeaderMap, Value)> {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?;
+        let peer: SocketAddr = format!("{peer}:40000").parse()?;
+        request.extensions_mut().insert(ConnectInfo(peer));
+
+        let response = self.app.clone().oneshot(request).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await?;
+        let payload = if body_bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body_bytes)
+                .unwrap_or_else(|_| json!({ "raw": String::from_utf8_lossy(&body_bytes) }))
+        };
+
+        Ok((status, headers, payload))
+    }
+
+    async fn login(
+        &self,
+        peer: &str,
+        email: &str,
+        password: &str,
+    ) -> anyhow::Result<(StatusCode, HeaderMap, Value)> {
+        self.request_from(
+            peer,
+            "/api/v1/auth/login",
+            json!({ "email": email, "password": password }),
+        )
+        .await
+    }
+}
+
+#[tokio::test]
+async fn repeated_failures_lock_only_that_ip_for_the_account() -> anyhow::Result<()> {
+    let ctx = lockout_context().await?;
+    let (status, body) = ctx.register("lockout_target", "target@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+
+    for _ in 0..3 {
+        let (status, _, body) = ctx
+            .login("203.0.113.1", "target@example.com", "wrong-password")
+            .await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+    }
+
+    // Even the right password from that IP is refused until the lock ends.
+    let (status, headers, body) = ctx
+        .login("203.0.113.1", "target@example.com", PASSWORD)
+        .await?;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{body}");
+    assert_eq!(body["code"], "RATE_LIMITED");
+    let retry_after = body["retry_after"].as_i64().expect("retry_after");
+    assert!(retry_after > 0 && retry_after <= 900, "{body}");
+    assert_eq!(
+        headers
+            .get(header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok()),
+        Some(retry_after.to_string().as_str())
+    );
+
+    // The owner, signing in from elsewhere, is not locked out.
+    let (status, _, body) = ctx
+        .login("203.0.113.2", "target@example.com", PASSWORD)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    let events =
+        paracord_db::security_events::list_events(&ctx.db, Some("auth.login.lockout"), None, 10)
+            .await?;
+    assert_eq!(events.len(), 1);
+    assert!(events[0].target_user_id.is_some());
+    assert_eq!(events[0].details.as_ref().unwrap()["max_failures"], 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn success_resets_the_failure_count() -> anyhow::Result<()> {
+    let ctx = lockout_context().await?;
+    let (status, body) = ctx.register("lockout_user", "user@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+
+    for _ in 0..2 {
+        for _ in 0..2 {
+            let (status, _, _) = ctx
+                .login("198.51.100.1", "user@example.com", "nope")
+                .await?;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        let (status, _, body) = ctx
+            .login("198.51.100.1", "user@example.com", PASSWORD)
+            .await?;
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{create_authenticated_user_token, create_guild, create_text_channel, TestContext};
+
+#[tokio::test]
+async fn screened_members_cannot_post_until_they_accept_the_rules() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Screened Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+
+    let (status, _) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/member-screening"),
+            Some(json!({ "enabled": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, screening) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/member-screening"),
+            Some(json!({ "enabled": true, "rules": "Be kind. No spam." })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{screening}");
+    assert_eq!(screening["enabled"], true);
+
+    let (status, invite) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/invites"),
+            Some(json!({})),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{invite}");
+    let code = invite["code"].as_str().context("invite code")?;
+
+    let (joiner_id, joiner_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (status, joined) = ctx
+        .request_json_as(
+            &joiner_token,
+            Method::POST,
+            &format!("/api/v1/invites/{code}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{joined}");
+    assert_eq!(joined["pending"], true);
+
+    let (status, rules) = ctx
+        .request_json_as(
+            &joiner_token,
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/member-screening"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rules["rules"], "Be kind. No spam.");
+
+    let post = json!({ "content": "hello" });
+    let (status, _) = ctx
+        .request_json_as(
+            &joiner_token,
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(post.clone()),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    let (status, member) = ctx
+        .request_json_as(
+            &joiner_token,
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/members/@me/screening"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{member}");
+    assert_eq!(member["pending"], false);
+    let mut update = None;
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "GUILD_MEMBER_UPDATE" {
+            update = Some(event.payload.clone());
+        }
+    }
+    let update = update.context("GUILD_MEMBER_UPDATE should be dispatched")?;
+    assert_eq!(update["user_id"], joiner_id.to_string());
+    assert_eq!(update["pending"], false);
+
+    let (status, _) = ctx
+        .request_json_as(
+            &joiner_token,
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(post),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    Ok(())
+}
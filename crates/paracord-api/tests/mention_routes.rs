@@ -0,0 +1,185 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{add_guild_member, create_guild, create_text_channel, TestContext};
+
+/// Poll until the background mention work has counted `expected` mentions
+/// of `user_id` in `channel_id`.
+async fn wait_for_mention_count(
+    db: &paracord_db::DbPool,
+    user_id: i64,
+    channel_id: i64,
+    expected: i32,
+) -> anyhow::Result<()> {
+    let mut count = 0;
+    for _ in 0..100 {
+        count = paracord_db::read_states::get_read_state(db, user_id, channel_id)
+            .await?
+            .map_or(0, |row| row.mention_count);
+        if count == expected {
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    anyhow::bail!("mention count stayed at {count}, expected {expected}")
+}
+
+#[tokio::test]
+async fn silent_messages_skip_mention_notifications() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Silent Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let member_id = add_guild_member(&ctx, &guild_id).await?;
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+    let mention_count = || async {
+        let state =
+            paracord_db::read_states::get_read_state(&ctx.db, member_id, channel_id.parse()?)
+                .await?;
+        anyhow::Ok(state.map_or(0, |row| row.mention_count))
+    };
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": format!("quiet ping <@{member_id}>"), "flags": 1 << 12 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(payload["flags"], 1 << 12);
+    assert_eq!(mention_count().await?, 0);
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": format!("loud ping <@{member_id}>") })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    wait_for_mention_count(&ctx.db, member_id, channel_id.parse()?, 1).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn role_mentions_notify_only_role_holders() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Role Ping Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let holder_id = add_guild_member(&ctx, &guild_id).await?;
+    let muted_holder_id = add_guild_member(&ctx, &guild_id).await?;
+    let bystander_id = add_guild_member(&ctx, &guild_id).await?;
+
+    let role_id = paracord_util::snowflake::generate(1);
+    paracord_db::roles::create_role(&ctx.db, role_id, guild_id.parse()?, "On Call", 0).await?;
+    for user_id in [holder_id, muted_holder_id] {
+        paracord_db::roles::add_member_role(&ctx.db, user_id, guild_id.parse()?, role_id).await?;
+    }
+    paracord_db::users::upsert_user_settings(
+        &ctx.db,
+        muted_holder_id,
+        "dark",
+        "en-US",
+        "cozy",
+        None,
+        None,
+        None,
+        Some(&json!({ "mutedGuildIds": [guild_id.clone()] })),
+        None,
+    )
+    .await?;
+
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+    let mention_count = |user_id: i64| {
+        let channel_id = channel_id.clone();
+        let db = ctx.db.clone();
+        async move {
+            let state =
+                paracord_db::read_states::get_read_state(&db, user_id, channel_id.parse()?).await?;
+            anyhow::Ok(state.map_or(0, |row| row.mention_count))
+        }
+    };
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({
+                "content": format!("<@&{role_id}> deploy is out"),
+                "allowed_mentions": { "parse": ["users"] },
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(mention_count(holder_id).await?, 0);
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": format!("<@&{role_id}> deploy is out") })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    wait_for_mention_count(&ctx.db, holder_id, channel_id.parse()?, 1).await?;
+    assert_eq!(mention_count(muted_holder_id).await?, 0);
+    assert_eq!(mention_count(bystander_id).await?, 0);
+    assert_eq!(mention_count(ctx.user_id).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn mention_event_reaches_only_mentioned_users_who_can_see_the_channel() -> anyhow::Result<()>
+{
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Mention Event Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let viewer_id = add_guild_member(&ctx, &guild_id).await?;
+    let hidden_id = add_guild_member(&ctx, &guild_id).await?;
+    paracord_db::channel_overwrites::upsert_channel_overwrite(
+        &ctx.db,
+        channel_id.parse()?,
+        hidden_id,
+        paracord_core::permissions::OVERWRITE_TARGET_MEMBER,
+        0,
+        paracord_models::permissions::Permissions::VIEW_CHANNEL.bits(),
+    )
+    .await?;
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": format!("<@{viewer_id}> <@{hidden_id}> look") })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Mentions are resolved after MESSAGE_CREATE goes out.
+    let mut saw_create = false;
+    let event = loop {
+        let event =
+            tokio::time::timeout(std::time::Duration::from_secs(2), events.recv()).await??;
+        match event.event_type.as_str() {
+            "MESSAGE_CREATE" => saw_create = true,
+            "MESSAGE_MENTION" => break event,
+            _ => {}
+        }
+    };
+    assert!(saw_create);
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(std::iter::from_fn(|| events.try_recv().ok())
+        .all(|event| event.event_type != "MESSAGE_MENTION"));
+    assert_eq!(event.target_user_ids, Some(vec![viewer_id]));
+    assert_eq!(event.payload["message_id"], message["id"]);
+    assert_eq!(event.payload["channel_id"], channel_id.as_str());
+    assert_eq!(event.payload["guild_id"], guild_id.as_str());
+    assert!(event.payload.get("content").is_none());
+
+    Ok(())
+}
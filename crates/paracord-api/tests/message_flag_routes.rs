@@ -0,0 +1,132 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{create_authenticated_user_token, create_guild, create_text_channel, TestContext};
+
+#[tokio::test]
+async fn message_flags_round_trip_and_suppress_embeds_toggles() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Flags Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "see https://example.com", "flags": 4 | 4096 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(payload["flags"], 4 | 4096);
+    let message_id = payload["id"]
+        .as_str()
+        .context("message id should be a string")?
+        .to_string();
+
+    let (status, payload) = ctx.request_json(Method::GET, &messages_path, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload[0]["flags"], 4 | 4096);
+
+    let message_path = format!("{messages_path}/{message_id}");
+    let (status, payload) = ctx
+        .request_json(Method::PATCH, &message_path, Some(json!({ "flags": 0 })))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["flags"], 4096);
+
+    let (status, payload) = ctx
+        .request_json(Method::PATCH, &message_path, Some(json!({ "flags": 4 })))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payload["flags"], 4 | 4096);
+    assert_eq!(payload["content"], "see https://example.com");
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "nope", "flags": 1 << 1 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn tts_messages_need_send_tts_permission_and_round_trip() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "TTS Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "speech").await?;
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id.parse()?).await?;
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+
+    // @everyone does not get SEND_TTS_MESSAGES by default.
+    let (status, _) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "read me", "tts": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, plain) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "quietly" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(plain["tts"], false);
+
+    // The TTS bit can only be set through the permission-checked field.
+    let (status, _) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "sneaky", "flags": 1 << 3 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    let (status, sent) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "read me", "tts": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{sent}");
+    assert_eq!(sent["tts"], true);
+    let mut creates = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "MESSAGE_CREATE" {
+            creates.push(event);
+        }
+    }
+    assert_eq!(creates.len(), 1);
+    assert_eq!(creates[0].payload["id"], sent["id"]);
+    assert_eq!(creates[0].payload["tts"], true);
+
+    let (status, fetched) = ctx.request_json(Method::GET, &messages_path, None).await?;
+    assert_eq!(status, StatusCode::OK);
+    let fetched = fetched
+        .as_array()
+        .context("messages")?
+        .iter()
+        .find(|message| message["id"] == sent["id"])
+        .context("tts message")?;
+    assert_eq!(fetched["tts"], true);
+
+    Ok(())
+}
@@ -0,0 +1,309 @@
+use axum::http::{Method, StatusCode};
+use paracord_util::at_rest::SecretCryptor;
+use serde_json::{json, Value};
+
+mod common;
+
+use common::TestContext;
+
+impl TestContext {
+    async fn login(
+        &self,
+        email: &str,
+        mfa_code: Option<&str>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let mut body = json!({ "email": email, "password": "IntegrationPass123!" });
+        if let Some(code) = mfa_code {
+            body["mfa_code"] = json!(code);
+        }
+        self.request_with(None, Method::POST, "/api/v1/auth/login", Some(body))
+            .await
+    }
+}
+
+fn code_at_step(secret: &str, step: i64) -> String {
+    paracord_core::auth::totp_code(secret, step).expect("valid secret")
+}
+
+/// Enroll and confirm TOTP, returning the secret, the step used to confirm
+/// and the recovery codes.
+async fn enable_totp(ctx: &TestContext, token: &str) -> anyhow::Result<(String, i64, Vec<String>)> {
+    let (status, begin) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/users/@me/mfa/totp",
+            Some(json!({ "password": "IntegrationPass123!" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{begin}");
+    let secret = begin["secret"].as_str().unwrap().to_string();
+    assert!(begin["otpauth_uri"]
+        .as_str()
+        .unwrap()
+        .starts_with("otpauth://totp/"));
+
+    let step = paracord_core::auth::totp_step(chrono::Utc::now().timestamp());
+    let (status, confirm) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/users/@me/mfa/totp/confirm",
+            Some(json!({ "code": code_at_step(&secret, step) })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{confirm}");
+    let recovery_codes: Vec<String> = serde_json::from_value(confirm["recovery_codes"].clone())?;
+    assert_eq!(
+        recovery_codes.len(),
+        paracord_core::auth::TOTP_RECOVERY_CODE_COUNT
+    );
+    Ok((secret, step, recovery_codes))
+}
+
+#[tokio::test]
+async fn login_requires_a_fresh_totp_code_once_enabled() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, registered) = ctx.register("totp_user", "totp@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap();
+
+    let (secret, step, _) = enable_totp(&ctx, token).await?;
+
+    let (status, missing) = ctx.login("totp@example.com", None).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(missing["code"], "MFA_REQUIRED");
+
+    // The code that confirmed enrollment cannot be replayed.
+    let (status, _) = ctx
+        .login("totp@example.com", Some(&code_at_step(&secret, step)))
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let next_code = code_at_step(&secret, step + 1);
+    let (status, login) = ctx.login("totp@example.com", Some(&next_code)).await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+    assert!(login["token"].as_str().is_some());
+
+    let (status, _) = ctx.login("totp@example.com", Some(&next_code)).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, status_body) = ctx
+        .request_with(Some(token), Method::GET, "/api/v1/users/@me/mfa/totp", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(status_body["enabled"], json!(true));
+    assert_eq!(status_body["recovery_codes_remaining"], json!(10));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn recovery_codes_are_single_use_and_disable_turns_totp_off() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, registered) = ctx
+        .register("recovery_user", "recovery@example.com")
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap();
+
+    let (_, _, recovery_codes) = enable_totp(&ctx, token).await?;
+
+    // Already enabled: a second enrollment is refused.
+    let (status, _) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/users/@me/mfa/totp",
+            Some(json!({ "password": "IntegrationPass123!" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, login) = ctx
+        .login(
+            "recovery@example.com",
+            Some(&recovery_codes[0].to_uppercase()),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+    let (status, _) = ctx
+        .login("recovery@example.com", Some(&recovery_codes[0]))
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/users/@me/mfa/totp/disable",
+            Some(json!({ "password": "wrong-password", "code": recovery_codes[1] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, disabled) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/users/@me/mfa/totp/disable",
+            Some(json!({ "password": "IntegrationPass123!", "code": recovery_codes[1] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{disabled}");
+    assert_eq!(disabled["enabled"], json!(false));
+
+    let (status, login) = ctx.login("recovery@example.com", None).await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn access_token_creation_requires_the_second_factor() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, registered) = ctx
+        .register("pat_totp_user", "pat-totp@example.com")
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap();
+
+    let (secret, step, _) = enable_totp(&ctx, token).await?;
+    let mut body = json!({
+        "name": "ci script",
+        "scopes": ["read"],
+        "password": "IntegrationPass123!",
+    });
+
+    let (status, missing) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/users/@me/tokens",
+            Some(body.clone()),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(missing["code"], "MFA_REQUIRED");
+
+    body["mfa_code"] = json!(code_at_step(&secret, step + 1));
+    let (status, created) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/users/@me/tokens",
+            Some(body),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    assert!(created["token"].as_str().is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn totp_secrets_are_sealed_with_the_at_rest_key() -> anyhow::Result<()> {
+    let ctx = TestContext::with_state(|state| {
+        state.config.secret_cryptor = Some(SecretCryptor::from_master_key(&[9u8; 32]));
+    })
+    .await?;
+    let (status, registered) = ctx
+        .register("sealed_totp", "sealed-totp@example.com")
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap();
+    let user_id: i64 = registered["user"]["id"].as_str().unwrap().parse()?;
+
+    let (secret, step, _) = enable_totp(&ctx, token).await?;
+    let row = paracord_db::totp::get_totp(&ctx.db, user_id)
+        .await?
+        .expect("totp row");
+    assert!(SecretCryptor::is_sealed(&row.secret));
+    assert!(!row.secret.contains(&secret));
+
+    let (status, login) = ctx
+        .login(
+            "sealed-totp@example.com",
+            Some(&code_at_step(&secret, step + 1)),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn public_key_login_requires_the_second_factor() -> anyhow::Result<()> {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    let ctx = TestContext::with_username_login().await?;
+    let (status, registered) = ctx.register("key_totp", "key-totp@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap().to_string();
+
+    let key = SigningKey::from_bytes(&[5u8; 32]);
+    let public_key: String = key
+        .verifying_key()
+        .as_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    let (status, attached) = ctx
+        .request_with(
+            Some(&token),
+            Method::POST,
+            "/api/v1/auth/attach-public-key",
+            Some(json!({ "public_key": public_key })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{attached}");
+    let token = attached["token"].as_str().unwrap().to_string();
+    let (secret, step, _) = enable_totp(&ctx, &token).await?;
+
+    let key_login = |mfa_code: Option<String>| {
+        let ctx = &ctx;
+        let key = &key;
+        let public_key = public_key.clone();
+        async move {
+            let (status, challenge) = ctx
+                .request_with(None, Method::POST, "/api/v1/auth/challenge", None)
+                .await?;
+            assert_eq!(status, StatusCode::OK, "{challenge}");
+            let nonce = challenge["nonce"].as_str().unwrap();
+            let timestamp = challenge["timestamp"].as_i64().unwrap();
+            let origin = challenge["server_origin"].as_str().unwrap();
+            let signature: String = key
+                .sign(format!("{nonce}:{timestamp}:{origin}").as_bytes())
+                .to_bytes()
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect();
+            let mut body = json!({
+                "public_key": public_key,
+                "nonce": nonce,
+                "timestamp": timestamp,
+                "signature": signature,
+                "username": "key_totp",
+            });
+            if let Some(code) = mfa_code {
+                body["mfa_code"] = json!(code);
+            }
+            ctx.request_with(None, Method::POST, "/api/v1/auth/verify", Some(body))
+                .await
+        }
+    };
+
+    let (status, missing) = key_login(None).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(missing["code"], "MFA_REQUIRED");
+
+    let (status, wrong) = key_login(Some("000000".to_string())).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(wrong["code"], "UNAUTHORIZED");
+
+    let (status, login) = key_login(Some(code_at_step(&secret, step + 1))).await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+    assert!(login["token"].as_str().is_some());
+
+    Ok(())
+}
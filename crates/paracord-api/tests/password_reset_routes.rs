@@ -0,0 +1,181 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{mailed_token, TestContext};
+
+impl TestContext {
+    async fn login(&self, email: &str, password: &str) -> anyhow::Result<(StatusCode, Value)> {
+        self.request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/login",
+            Some(json!({ "email": email, "password": password })),
+        )
+        .await
+    }
+
+    async fn forgot_password(&self, email: &str) -> anyhow::Result<StatusCode> {
+        let (status, _) = self
+            .request_with(
+                None,
+                Method::POST,
+                "/api/v1/auth/forgot-password",
+                Some(json!({ "email": email })),
+            )
+            .await?;
+        Ok(status)
+    }
+
+    async fn reset_password(
+        &self,
+        token: &str,
+        new_password: &str,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        self.request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/reset-password",
+            Some(json!({ "token": token, "new_password": new_password })),
+        )
+        .await
+    }
+
+    async fn pending_resets(&self, user_id: i64) -> anyhow::Result<i64> {
+        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM password_resets WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(row.0)
+    }
+}
+
+#[tokio::test]
+async fn forgot_password_does_not_reveal_whether_the_email_exists() -> anyhow::Result<()> {
+    let (ctx, outbox) = TestContext::with_outbox().await?;
+    let (status, user) = ctx.register("forgetful", "forgetful@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{user}");
+    let user_id: i64 = user["user"]["id"].as_str().unwrap().parse()?;
+
+    assert_eq!(
+        ctx.forgot_password("nobody@example.com").await?,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(ctx.pending_resets(user_id).await?, 0);
+    assert!(outbox.lock().unwrap().is_empty());
+
+    assert_eq!(
+        ctx.forgot_password("Forgetful@Example.com").await?,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(ctx.pending_resets(user_id).await?, 1);
+
+    let token = mailed_token(&outbox, "forgetful@example.com", "/reset-password");
+    let (status, body) = ctx.reset_password(&token, "BrandNewPass456!").await?;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    let (status, body) = ctx
+        .login("forgetful@example.com", "BrandNewPass456!")
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn forgot_password_is_throttled_per_address() -> anyhow::Result<()> {
+    let (ctx, outbox) = TestContext::with_outbox().await?;
+    let (status, user) = ctx.register("throttled", "throttled@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{user}");
+
+    for _ in 0..3 {
+        assert_eq!(
+            ctx.forgot_password("throttled@example.com").await?,
+            StatusCode::NO_CONTENT
+        );
+    }
+    assert_eq!(
+        ctx.forgot_password("Throttled@Example.com").await?,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(outbox.lock().unwrap().len(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn forgot_password_needs_a_mail_transport() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, options) = ctx
+        .request_with(None, Method::GET, "/api/v1/auth/options", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(options["password_reset_available"], false);
+    assert_eq!(
+        ctx.forgot_password("nobody@example.com").await?,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reset_token_sets_the_password_once_and_revokes_sessions() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, user) = ctx.register("resetter", "resetter@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{user}");
+    let user_id: i64 = user["user"]["id"].as_str().unwrap().parse()?;
+    let old_token = user["token"].as_str().unwrap().to_string();
+    let session = paracord_db::sessions::list_user_sessions(&ctx.db, user_id, chrono::Utc::now())
+        .await?
+        .remove(0);
+    paracord_db::push::upsert_subscription(
+        &ctx.db,
+        user_id,
+        &session.id,
+        "https://push.example.com/send/resetter",
+        "key",
+        "auth",
+    )
+    .await?;
+
+    let token = paracord_core::auth::generate_password_reset_token();
+    paracord_db::users::create_password_reset(
+        &ctx.db,
+        user_id,
+        &paracord_core::auth::hash_password_reset_token(&token),
+        chrono::Utc::now() + chrono::Duration::hours(1),
+    )
+    .await?;
+
+    let (status, _) = ctx.reset_password(&token, "short").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = ctx
+        .reset_password("not-a-real-token", "BrandNewPass456!")
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = ctx.reset_password(&token, "BrandNewPass456!").await?;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    let (status, _) = ctx.reset_password(&token, "AnotherPass789!").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = ctx
+        .request_with(Some(&old_token), Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(paracord_db::push::get_user_subscriptions(&ctx.db, user_id)
+        .await?
+        .is_empty());
+
+    let (status, _) = ctx
+        .login("resetter@example.com", "IntegrationPass123!")
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = ctx
+        .login("resetter@example.com", "BrandNewPass456!")
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::TestContext;
+
+impl TestContext {
+    async fn mint_token(&self, scopes: Value) -> anyhow::Result<(String, String)> {
+        let (status, body) = self
+            .request_json(
+                Method::POST,
+                "/api/v1/users/@me/tokens",
+                Some(json!({
+                    "name": "ci script",
+                    "scopes": scopes,
+                    "password": "IntegrationPass123!",
+                })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED, "{body}");
+        let id = body["id"].as_str().context("token id")?.to_string();
+        let token = body["token"].as_str().context("token secret")?.to_string();
+        Ok((id, format!("Pat {token}")))
+    }
+}
+
+#[tokio::test]
+async fn personal_access_token_authenticates_as_its_owner() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+
+    let (status, body) = ctx
+        .request_json(
+            Method::POST,
+            "/api/v1/users/@me/tokens",
+            Some(json!({
+                "name": "ci script",
+                "scopes": ["read"],
+                "password": "wrong-password",
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+
+    let (token_id, pat) = ctx.mint_token(json!(["read", "write"])).await?;
+
+    let (status, me) = ctx
+        .request_json_authorized(&pat, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{me}");
+    assert_eq!(me["id"], json!(ctx.user_id.to_string()));
+
+    let (status, listed) = ctx
+        .request_json(Method::GET, "/api/v1/users/@me/tokens", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{listed}");
+    let listed = listed.as_array().context("token list")?;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], json!(token_id));
+    assert_eq!(listed[0]["scopes"], json!(["read", "write"]));
+    assert!(listed[0].get("token").is_none());
+    assert!(!listed[0]["last_used_at"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn personal_access_token_scopes_are_enforced() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (_, read_only) = ctx.mint_token(json!(["read"])).await?;
+
+    let (status, _) = ctx
+        .request_json_authorized(&read_only, Method::GET, "/api/v1/users/@me/guilds", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = ctx
+        .request_json_authorized(
+            &read_only,
+            Method::POST,
+            "/api/v1/guilds",
+            Some(json!({ "name": "Scripted Guild" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+    // Even a fully scoped token cannot manage tokens or credentials.
+    let (_, full) = ctx.mint_token(json!(["read", "write"])).await?;
+    let (status, body) = ctx
+        .request_json_authorized(
+            &full,
+            Method::POST,
+            "/api/v1/users/@me/tokens",
+            Some(json!({
+                "name": "escalated",
+                "scopes": ["read", "write"],
+                "password": "IntegrationPass123!",
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    let (status, _) = ctx
+        .request_json_authorized(&full, Method::GET, "/api/v1/auth/sessions", None)
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = ctx
+        .request_json_authorized(
+            &full,
+            Method::POST,
+            "/api/v1/guilds",
+            Some(json!({ "name": "Scripted Guild" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn revoked_personal_access_token_is_rejected() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (token_id, pat) = ctx.mint_token(json!(["read"])).await?;
+
+    let (status, _) = ctx
+        .request_json_authorized(&pat, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            &format!("/api/v1/users/@me/tokens/{token_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = ctx
+        .request_json_authorized(&pat, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            &format!("/api/v1/users/@me/tokens/{token_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{create_authenticated_user_token, TestContext};
+
+async fn set_presence(ctx: &TestContext, user_id: i64, status: &str) {
+    ctx.state.user_presences.write().await.insert(
+        user_id,
+        json!({
+            "user_id": user_id.to_string(),
+            "status": status,
+            "custom_status": null,
+            "activities": [],
+        }),
+    );
+}
+
+#[tokio::test]
+async fn presences_are_returned_in_one_batch() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (caller_id, caller_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (guildmate_id, _) = create_authenticated_user_token(&ctx.db).await?;
+    let (friend_id, _) = create_authenticated_user_token(&ctx.db).await?;
+    let (idle_id, _) = create_authenticated_user_token(&ctx.db).await?;
+
+    let guild_id = paracord_util::snowflake::generate(1);
+    paracord_db::guilds::create_guild(&ctx.db, guild_id, "Presence Guild", caller_id, None).await?;
+    for user_id in [caller_id, guildmate_id, idle_id] {
+        paracord_db::members::add_member(&ctx.db, user_id, guild_id).await?;
+    }
+    paracord_db::relationships::create_relationship(&ctx.db, caller_id, friend_id, 1).await?;
+    paracord_db::relationships::create_relationship(&ctx.db, friend_id, caller_id, 1).await?;
+
+    set_presence(&ctx, guildmate_id, "online").await;
+    set_presence(&ctx, friend_id, "dnd").await;
+
+    let (status, body) = ctx
+        .request_json_as(
+            &caller_token,
+            Method::POST,
+            "/api/v1/presences",
+            Some(json!({
+                "user_ids": [
+                    guildmate_id.to_string(),
+                    friend_id.to_string(),
+                    idle_id.to_string(),
+                ],
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let presences = body.as_array().expect("presence array");
+    assert_eq!(presences.len(), 3);
+    assert_eq!(presences[0]["user_id"], guildmate_id.to_string());
+    assert_eq!(presences[0]["status"], "online");
+    assert_eq!(presences[1]["user_id"], friend_id.to_string());
+    assert_eq!(presences[1]["status"], "dnd");
+    assert_eq!(presences[2]["user_id"], idle_id.to_string());
+    assert_eq!(presences[2]["status"], "offline");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn presences_omit_users_without_shared_guild_or_friendship() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (caller_id, caller_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (guildmate_id, _) = create_authenticated_user_token(&ctx.db).await?;
+    let (stranger_id, _) = create_authenticated_user_token(&ctx.db).await?;
+    let (pending_id, _) = create_authenticated_user_token(&ctx.db).await?;
+
+    let guild_id = paracord_util::snowflake::generate(1);
+    paracord_db::guilds::create_guild(&ctx.db, guild_id, "Presence Guild", caller_id, None).await?;
+    paracord_db::members::add_member(&ctx.db, caller_id, guild_id).await?;
+    paracord_db::members::add_member(&ctx.db, guildmate_id, guild_id).await?;
+
+    let other_guild_id = paracord_util::snowflake::generate(1);
+    paracord_db::guilds::create_guild(&ctx.db, other_guild_id, "Elsewhere", stranger_id, None)
+        .await?;
+    paracord_db::members::add_member(&ctx.db, stranger_id, other_guild_id).await?;
+    // An outgoing friend request is not a friendship.
+    paracord_db::relationships::create_relationship(&ctx.db, caller_id, pending_id, 4).await?;
+
+    set_presence(&ctx, guildmate_id, "online").await;
+    set_presence(&ctx, stranger_id, "online").await;
+    set_presence(&ctx, pending_id, "online").await;
+
+    let (status, body) = ctx
+        .request_json_as(
+            &caller_token,
+            Method::POST,
+            "/api/v1/presences",
+            Some(json!({
+                "user_ids": [
+                    stranger_id.to_string(),
+                    guildmate_id.to_string(),
+                    pending_id.to_string(),
+                    caller_id.to_string(),
+                ],
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let ids: Vec<&str> = body
+        .as_array()
+        .expect("presence array")
+        .iter()
+        .filter_map(|p| p["user_id"].as_str())
+        .collect();
+    assert_eq!(ids, vec![guildmate_id.to_string(), caller_id.to_string()]);
+
+    let (status, _) = ctx
+        .request_json_as(
+            &caller_token,
+            Method::POST,
+            "/api/v1/presences",
+            Some(json!({ "user_ids": ["not-a-snowflake"] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::sync::{Arc, Mutex};
+
+use axum::http::{Method, StatusCode};
+use paracord_core::push::{PushNotifier, PushPostFuture, PushRequest};
+use serde_json::json;
+use uuid::Uuid;
+
+mod common;
+
+use common::{create_authenticated_user_token, TestContext};
+
+// Test vectors from the web-push crate.
+const VAPID_PRIVATE_KEY: &str = "IQ9Ur0ykXoHS9gzfYX0aBjy9lvdrjx_PFUXmie9YRcY";
+const P256DH: &str =
+    "BLMbF9ffKBiWQLCKvTHb6LO8Nb6dcUh6TItC455vu2kElga6PQvUmaFyCdykxY2nOSSL3yKgfbmFLRTUaGv4yV8";
+const AUTH: &str = "xS03Fi5ErfTNH_l9WHE9Ig";
+
+/// The stub push service behind the notifier.
+struct PushService {
+    /// Requests the stub push service received.
+    requests: Arc<Mutex<Vec<PushRequest>>>,
+    /// Status the stub push service answers with.
+    status: Arc<Mutex<u16>>,
+}
+
+impl PushService {
+    /// Wait for the background push task to post `count` requests in total.
+    async fn wait_for(&self, count: usize) -> anyhow::Result<Vec<PushRequest>> {
+        for _ in 0..100 {
+            let pushed = self.requests.lock().unwrap().clone();
+            if pushed.len() >= count {
+                return Ok(pushed);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        anyhow::bail!("expected {count} push requests")
+    }
+}
+
+async fn push_context() -> anyhow::Result<(TestContext, PushService)> {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let status = Arc::new(Mutex::new(201));
+    let (log, answer) = (requests.clone(), status.clone());
+    let notifier = PushNotifier::new(
+        VAPID_PRIVATE_KEY,
+        "mailto:admin@example.com",
+        Arc::new(move |request: PushRequest| -> PushPostFuture {
+            log.lock().unwrap().push(request);
+            let status = *answer.lock().unwrap();
+            Box::pin(async move { Ok(status) })
+        }),
+    )
+    .map_err(anyhow::Error::msg)?;
+    let ctx = TestContext::with_state(|state| {
+        state.push_notifier = Some(Arc::new(notifier));
+    })
+    .await?;
+    Ok((ctx, PushService { requests, status }))
+}
+
+/// A second user with a DM channel to the test user and one push
+/// subscription registered from their session. Returns
+/// `(user_id, token, dm_channel_id)`.
+async fn dm_peer_with_subscription(
+    ctx: &TestContext,
+    endpoint: &str,
+) -> anyhow::Result<(i64, String, i64)> {
+    let (peer_id, token) = create_authenticated_user_token(&ctx.state.db).await?;
+    let (status, payload) = ctx
+        .request_json_as(
+            &token,
+            Method::POST,
+            "/api/v1/users/@me/push-subscriptions",
+            Some(json!({ "endpoint": endpoint, "keys": { "p256dh": P256DH, "auth": AUTH } })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{payload}");
+    let dm_id = paracord_util::snowflake::generate(1);
+    paracord_db::dms::create_dm_channel(&ctx.state.db, dm_id, ctx.user_id, peer_id).await?;
+    Ok((peer_id, token, dm_id))
+}
+
+async fn send_dm(ctx: &TestContext, dm_id: i64) -> anyhow::Result<()> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{dm_id}/messages"),
+            Some(json!({
+                "content": "",
+                "e2ee": {
+                    "version": 1,
+                    // The E2EE nonce doubles as the idempotency key.
+                    "nonce": Uuid::new_v4().simple().to_string(),
+                    "ciphertext": "Y2lwaGVy",
+                }
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn subscriptions_are_registered_with_validated_endpoints_and_keys() -> anyhow::Result<()> {
+    let (ctx, _push) = push_context().await?;
+    let path = "/api/v1/users/@me/push-subscriptions";
+
+    let (status, payload) = ctx
+        .request_json(Method::GET, &format!("{path}/vapid-key"), None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert!(payload["public_key"]
+        .as_str()
+        .is_some_and(|k| !k.is_empty()));
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            path,
+            Some(json!({
+                "endpoint": "https://push.example.com/send/me",
+                "keys": { "p256dh": P256DH, "auth": AUTH },
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{payload}");
+    let subs = paracord_db::push::get_user_subscriptions(&ctx.state.db, ctx.user_id).await?;
+    assert_eq!(subs.len(), 1);
+    assert_eq!(subs[0].endpoint, "https://push.example.com/send/me");
+
+    for body in [
+        json!({ "endpoint": "https://127.0.0.1/send", "keys": { "p256dh": P256DH, "auth": AUTH } }),
+        json!({ "endpoint": "http://push.example.com/send", "keys": { "p256dh": P256DH, "auth": AUTH } }),
+        json!({ "endpoint": "https://push.example.com/send", "keys": { "p256dh": "short", "auth": AUTH } }),
+    ] {
+        let (status, payload) = ctx.request_json(Method::POST, path, Some(body)).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    }
+
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            path,
+            Some(json!({ "endpoint": "https://push.example.com/send/me" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(
+        paracord_db::push::get_user_subscriptions(&ctx.state.db, ctx.user_id)
+            .await?
+            .is_empty()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn direct_messages_push_only_to_offline_recipients() -> anyhow::Result<()> {
+    let (ctx, push) = push_context().await?;
+    let endpoint = "https://push.example.com/send/peer";
+    let (peer_id, _, dm_id) = dm_peer_with_subscription(&ctx, endpoint).await?;
+
+    // Connected to the gateway: the live event is enough.
+    ctx.state.online_users.write().await.insert(peer_id);
+    send_dm(&ctx, dm_id).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(push.requests.lock().unwrap().is_empty());
+
+    ctx.state.online_users.write().await.remove(&peer_id);
+    send_dm(&ctx, dm_id).await?;
+    let pushed = push.wait_for(1).await?;
+    assert_eq!(pushed.len(), 1);
+    assert_eq!(pushed[0].endpoint, endpoint);
+    assert!(pushed[0]
+        .headers
+        .iter()
+        .any(|(name, value)| name == "authorization" && value.starts_with("vapid ")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn gone_endpoints_are_pruned() -> anyhow::Result<()> {
+    let (ctx, push) = push_context().await?;
+    let (peer_id, _, dm_id) =
+        dm_peer_with_subscription(&ctx, "https://push.example.com/send/expired").await?;
+    *push.status.lock().unwrap() = 410;
+
+    send_dm(&ctx, dm_id).await?;
+    push.wait_for(1).await?;
+    for _ in 0..100 {
+        if paracord_db::push::get_user_subscriptions(&ctx.state.db, peer_id)
+            .await?
+            .is_empty()
+        {
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    anyhow::bail!("expired subscription was not pruned")
+}
+
+#[tokio::test]
+async fn signing_out_removes_the_sessions_subscription() -> anyhow::Result<()> {
+    let (ctx, push) = push_context().await?;
+    let (peer_id, token, dm_id) =
+        dm_peer_with_subscription(&ctx, "https://push.example.com/send/signed-out").await?;
+
+    let (status, _) = ctx
+        .request_json_as(&token, Method::POST, "/api/v1/auth/logout", None)
+        .await?;
+    assert!(status.is_success(), "{status}");
+    assert!(
+        paracord_db::push::get_user_subscriptions(&ctx.state.db, peer_id)
+            .await?
+            .is_empty()
+    );
+
+    send_dm(&ctx, dm_id).await?;
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(push.requests.lock().unwrap().is_empty());
+    Ok(())
+}
@@ -0,0 +1,369 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+mod common;
+
+use common::{
+    add_guild_member, create_guild, create_text_channel, send_guild_message, TestContext,
+};
+
+#[tokio::test]
+async fn super_and_normal_reactions_are_tallied_independently() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Reaction Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "chat").await?;
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "react to me" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let message_id = message["id"].as_str().context("message id")?.to_string();
+    let reactions_path = format!("/api/v1/channels/{channel_id}/messages/{message_id}/reactions");
+
+    // Another user leaves a normal reaction with the same emoji.
+    let other_id = paracord_util::snowflake::generate(1);
+    let nonce = Uuid::new_v4().simple().to_string();
+    paracord_db::users::create_user(
+        &ctx.db,
+        other_id,
+        &format!("reactor_{nonce}"),
+        1,
+        &format!("{nonce}@example.com"),
+        "unused-hash",
+    )
+    .await?;
+    paracord_db::reactions::add_reaction(
+        &ctx.db,
+        message_id.parse()?,
+        other_id,
+        "fire",
+        "fire",
+        None,
+        false,
+    )
+    .await?;
+
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &format!("{reactions_path}/fire/@me?burst=true"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    // A later normal add does not downgrade the super reaction.
+    let (status, _) = ctx
+        .request_json(Method::PUT, &format!("{reactions_path}/fire/@me"), None)
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = ctx
+        .request_json(Method::PUT, &format!("{reactions_path}/wave/@me"), None)
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, messages) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let reactions = messages[0]["reactions"]
+        .as_array()
+        .context("reactions array")?;
+    let fire = reactions
+        .iter()
+        .find(|r| r["emoji"] == "fire")
+        .context("fire reaction")?;
+    assert_eq!(fire["count"], 2);
+    assert_eq!(fire["count_details"]["normal"], 1);
+    assert_eq!(fire["count_details"]["burst"], 1);
+    assert_eq!(fire["me"], false);
+    assert_eq!(fire["me_burst"], true);
+    let wave = reactions
+        .iter()
+        .find(|r| r["emoji"] == "wave")
+        .context("wave reaction")?;
+    assert_eq!(wave["count"], 1);
+    assert_eq!(wave["count_details"]["burst"], 0);
+    assert_eq!(wave["me"], true);
+
+    let (status, listing) = ctx
+        .request_json(
+            Method::GET,
+            &format!("{reactions_path}/fire?burst=true"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listing["count"], 2);
+    assert_eq!(listing["count_details"]["burst"], 1);
+    let burst_users = listing["users"].as_array().context("users")?;
+    assert_eq!(burst_users.len(), 1);
+    assert_eq!(burst_users[0]["id"], ctx.user_id.to_string());
+
+    let (_, listing) = ctx
+        .request_json(
+            Method::GET,
+            &format!("{reactions_path}/fire?burst=false"),
+            None,
+        )
+        .await?;
+    let normal_users = listing["users"].as_array().context("users")?;
+    assert_eq!(normal_users.len(), 1);
+    assert_eq!(normal_users[0]["id"], other_id.to_string());
+
+    Ok(())
+}
+
+fn encode_path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn bulk_reactions_respect_the_unique_reaction_cap() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (guild_id, channel_id, message_id) = send_guild_message(&ctx).await?;
+    let reactions_path = format!("/api/v1/channels/{channel_id}/messages/{message_id}/reactions");
+    let bulk_path = format!("{reactions_path}/bulk");
+
+    // Another member fills the message up to two short of the cap.
+    let other_id = add_guild_member(&ctx, &guild_id).await?;
+    for i in 0..18 {
+        let emoji = format!("e{i}");
+        paracord_db::reactions::add_reaction(
+            &ctx.db,
+            message_id.parse()?,
+            other_id,
+            &emoji,
+            &emoji,
+            None,
+            false,
+        )
+        .await?;
+    }
+    let unique_count = || async {
+        let keys =
+            paracord_db::reactions::get_message_reaction_keys(&ctx.db, message_id.parse()?).await?;
+        anyhow::Ok(keys.len())
+    };
+
+    // Three new emoji would make 21: rejected without writing any of them.
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &bulk_path,
+            Some(json!({ "add": ["a", "b", "c"] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    assert_eq!(unique_count().await?, 18);
+
+    // Reacting with an emoji already on the message does not use up the cap.
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &bulk_path,
+            Some(json!({ "add": ["e0", "a", "b"] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["added"], json!(["e0", "a", "b"]));
+    assert_eq!(unique_count().await?, 20);
+
+    let (status, _) = ctx
+        .request_json(Method::PUT, &format!("{reactions_path}/c/@me"), None)
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Removals apply before the cap is checked.
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &bulk_path,
+            Some(json!({ "add": ["c"], "remove": ["a"] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["added"], json!(["c"]));
+    assert_eq!(payload["removed"], json!(["a"]));
+    assert_eq!(unique_count().await?, 20);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn bulk_reactions_dispatch_one_event_per_batch() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (guild_id, channel_id, message_id) = send_guild_message(&ctx).await?;
+    let bulk_path = format!("/api/v1/channels/{channel_id}/messages/{message_id}/reactions/bulk");
+    let mut events =
+        ctx.state
+            .event_bus
+            .register_session("bulk-reactions", ctx.user_id, &[guild_id.parse()?]);
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &bulk_path,
+            Some(json!({ "add": ["fire", "tada", "fire"] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &bulk_path,
+            Some(json!({ "remove": ["fire", "tada", "wave"] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["removed"], json!(["fire", "tada"]));
+
+    let mut reaction_events = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if event.event_type.starts_with("MESSAGE_REACTION") {
+            reaction_events.push((event.event_type.clone(), (*event.payload).clone()));
+        }
+    }
+    assert_eq!(reaction_events.len(), 2, "{reaction_events:?}");
+    let (event_type, added) = &reaction_events[0];
+    assert_eq!(event_type, "MESSAGE_REACTION_ADD_BULK");
+    assert_eq!(added["message_id"], message_id);
+    assert_eq!(added["user_id"], ctx.user_id.to_string());
+    assert_eq!(
+        added["reactions"],
+        json!([
+            { "emoji": "fire", "emoji_display": "fire" },
+            { "emoji": "tada", "emoji_display": "tada" },
+        ])
+    );
+    let (event_type, removed) = &reaction_events[1];
+    assert_eq!(event_type, "MESSAGE_REACTION_REMOVE_BULK");
+    assert_eq!(
+        removed["reactions"],
+        json!([{ "emoji": "fire" }, { "emoji": "tada" }])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unicode_reactions_aggregate_across_variation_selectors() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Emoji Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "chat").await?;
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "react to me" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let message_id = message["id"].as_str().context("message id")?.to_string();
+    let reactions_path = format!("/api/v1/channels/{channel_id}/messages/{message_id}/reactions");
+
+    let other_id = paracord_util::snowflake::generate(1);
+    let nonce = Uuid::new_v4().simple().to_string();
+    paracord_db::users::create_user(
+        &ctx.db,
+        other_id,
+        &format!("reactor_{nonce}"),
+        1,
+        &format!("{nonce}@example.com"),
+        "unused-hash",
+    )
+    .await?;
+    paracord_db::reactions::add_reaction(
+        &ctx.db,
+        message_id.parse()?,
+        other_id,
+        "❤",
+        "❤",
+        None,
+        false,
+    )
+    .await?;
+
+    // The same heart with an emoji presentation selector joins that bucket.
+    for emoji in ["❤\u{FE0F}", "👍", "👍🏽"] {
+        let (status, _) = ctx
+            .request_json(
+                Method::PUT,
+                &format!("{reactions_path}/{}/@me", encode_path_segment(emoji)),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+
+    let (status, messages) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let reactions = messages[0]["reactions"]
+        .as_array()
+        .context("reactions array")?;
+    assert_eq!(reactions.len(), 3);
+    let heart = reactions
+        .iter()
+        .find(|r| r["emoji"] == "❤")
+        .context("heart reaction")?;
+    assert_eq!(heart["count"], 2);
+    assert!(
+        heart["emoji_display"] == "❤" || heart["emoji_display"] == "❤\u{FE0F}",
+        "{heart}"
+    );
+    assert_eq!(heart["me"], true);
+    // Skin tones stay separate under the default policy, display intact.
+    let toned = reactions
+        .iter()
+        .find(|r| r["emoji"] == "👍🏽")
+        .context("toned thumbs up")?;
+    assert_eq!(toned["count"], 1);
+    assert_eq!(toned["emoji_display"], "👍🏽");
+
+    // Removing with either spelling removes the caller's reaction.
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            &format!("{reactions_path}/{}/@me", encode_path_segment("❤\u{FE0F}")),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, listing) = ctx
+        .request_json(
+            Method::GET,
+            &format!("{reactions_path}/{}", encode_path_segment("❤")),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listing["count"], 1);
+    assert_eq!(listing["users"][0]["id"], other_id.to_string());
+
+    Ok(())
+}
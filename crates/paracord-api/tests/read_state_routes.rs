@@ -0,0 +1,114 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{create_guild, create_text_channel, TestContext};
+
+#[tokio::test]
+async fn read_states_report_latest_message_and_unread() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Unread Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+
+    let (_, first) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "one" })),
+        )
+        .await?;
+    let (status, _) = ctx
+        .request_json(
+            Method::PUT,
+            &format!("/api/v1/channels/{channel_id}/read"),
+            Some(json!({ "last_message_id": first["id"] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let (_, second) = ctx
+        .request_json(
+            Method::POST,
+            &messages_path,
+            Some(json!({ "content": "two" })),
+        )
+        .await?;
+
+    let (status, states) = ctx
+        .request_json(Method::GET, "/api/v1/users/@me/read-states", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let state = states
+        .as_array()
+        .and_then(|rows| rows.iter().find(|row| row["channel_id"] == channel_id))
+        .expect("read state for channel");
+    assert_eq!(state["last_message_id"], first["id"]);
+    assert_eq!(state["latest_message_id"], second["id"]);
+    assert_eq!(state["unread"], true);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn guild_list_sums_channel_mentions_into_an_unread_summary() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Summary Guild").await?;
+    let quiet_guild_id = create_guild(&ctx, "Quiet Guild").await?;
+    let first = create_text_channel(&ctx, &guild_id, "first").await?;
+    let second = create_text_channel(&ctx, &guild_id, "second").await?;
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{second}/messages"),
+            Some(json!({ "content": "unread" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let me = ctx.user_id;
+    for (channel_id, mentions) in [(&first, 2), (&second, 3)] {
+        for _ in 0..mentions {
+            paracord_db::read_states::increment_mention_count(&ctx.db, me, channel_id.parse()?)
+                .await?;
+        }
+    }
+    let mut channel_mentions = 0;
+    for channel_id in [&first, &second] {
+        channel_mentions +=
+            paracord_db::read_states::get_read_state(&ctx.db, me, channel_id.parse()?)
+                .await?
+                .map_or(0, |row| row.mention_count);
+    }
+
+    let (status, guilds) = ctx
+        .request_json(Method::GET, "/api/v1/users/@me/guilds", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let find = |id: &str| {
+        guilds
+            .as_array()
+            .and_then(|rows| rows.iter().find(|g| g["id"] == id))
+            .cloned()
+            .expect("guild in list")
+    };
+    let summary = find(&guild_id);
+    assert_eq!(summary["mention_count"], channel_mentions);
+    assert_eq!(summary["mention_count"], 5);
+    assert_eq!(summary["unread"], true);
+    let quiet = find(&quiet_guild_id);
+    assert_eq!(quiet["mention_count"], 0);
+    assert_eq!(quiet["unread"], false);
+
+    let (status, bootstrap) = ctx
+        .request_json(Method::GET, "/api/v1/users/@me/bootstrap", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let ready = bootstrap["guilds"]
+        .as_array()
+        .and_then(|rows| rows.iter().find(|g| g["id"] == guild_id))
+        .expect("guild in bootstrap");
+    assert_eq!(ready["mention_count"], channel_mentions);
+
+    Ok(())
+}
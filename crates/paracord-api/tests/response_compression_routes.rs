@@ -0,0 +1,124 @@
+use std::io::Read;
+
+use anyhow::Context;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_guild, create_text_channel, TestContext};
+
+fn get_with_encoding(ctx: &TestContext, path: &str, accept_encoding: &str) -> Request<Body> {
+    Request::builder()
+        .method(Method::GET)
+        .uri(path)
+        .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+        .header(header::ACCEPT_ENCODING, accept_encoding)
+        .body(Body::empty())
+        .expect("request")
+}
+
+#[tokio::test]
+async fn large_json_responses_are_gzipped_when_accepted() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Compression Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "chatter").await?;
+    for i in 0..20 {
+        let (status, payload) = ctx
+            .request_json(
+                Method::POST,
+                &format!("/api/v1/channels/{channel_id}/messages"),
+                Some(json!({ "content": format!("message {i} {}", "lorem ipsum ".repeat(20)) })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::CREATED, "{payload}");
+    }
+    let path = format!("/api/v1/channels/{channel_id}/messages?limit=50");
+
+    let response = ctx
+        .app
+        .clone()
+        .oneshot(get_with_encoding(&ctx, &path, "gzip"))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_ENCODING),
+        Some(&header::HeaderValue::from_static("gzip"))
+    );
+    let compressed = to_bytes(response.into_body(), usize::MAX).await?;
+    let mut json_bytes = Vec::new();
+    flate2::read::GzDecoder::new(&compressed[..]).read_to_end(&mut json_bytes)?;
+    assert!(compressed.len() < json_bytes.len());
+    let messages: Value = serde_json::from_slice(&json_bytes)?;
+    assert_eq!(messages.as_array().map(Vec::len), Some(20));
+
+    let response = ctx
+        .app
+        .clone()
+        .oneshot(get_with_encoding(&ctx, &path, "identity"))
+        .await?;
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    let plain = to_bytes(response.into_body(), usize::MAX).await?;
+    assert_eq!(serde_json::from_slice::<Value>(&plain)?, messages);
+    Ok(())
+}
+
+#[tokio::test]
+async fn attachment_downloads_are_not_compressed() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Download Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    let contents = "compressible text ".repeat(4096).into_bytes();
+    let boundary = "paracord-test-boundary";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"notes.txt\"\r\nContent-Type: text/plain\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(&contents);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(format!("/api/v1/channels/{channel_id}/attachments"))
+        .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(Body::from(body))?;
+    let response = ctx.app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let uploaded: Value =
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    let attachment_id = uploaded["id"]
+        .as_str()
+        .context("attachment id")?
+        .to_string();
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "", "attachment_ids": [&attachment_id] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+
+    let response = ctx
+        .app
+        .clone()
+        .oneshot(get_with_encoding(
+            &ctx,
+            &format!("/api/v1/attachments/{attachment_id}"),
+            "gzip, br",
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    let downloaded = to_bytes(response.into_body(), usize::MAX).await?;
+    assert_eq!(&downloaded[..], &contents[..]);
+    Ok(())
+}
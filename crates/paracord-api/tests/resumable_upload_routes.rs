@@ -0,0 +1,337 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_guild, create_text_channel, TestContext};
+
+async fn upload_context(max_upload_size: u64) -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| state.config.max_upload_size = max_upload_size).await
+}
+
+impl TestContext {
+    /// Send a tus request and return the status and response headers.
+    async fn tus(
+        &self,
+        method: Method,
+        path: &str,
+        headers: &[(&str, String)],
+        body: Vec<u8>,
+    ) -> anyhow::Result<(StatusCode, HeaderMap)> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", self.token))
+            .header("Tus-Resumable", "1.0.0");
+        for (name, value) in headers {
+            builder = builder.header(*name, value);
+        }
+        let response = self
+            .app
+            .clone()
+            .oneshot(builder.body(Body::from(body))?)
+            .await?;
+        Ok((response.status(), response.headers().clone()))
+    }
+}
+
+fn upload_metadata(channel_id: &str, filename: &str) -> String {
+    use base64::Engine;
+    let b64 = |v: &str| base64::engine::general_purpose::STANDARD.encode(v);
+    format!(
+        "filename {},channel_id {},filetype {}",
+        b64(filename),
+        b64(channel_id),
+        b64("text/plain")
+    )
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn tus_uploads_resume_and_become_attachments() -> anyhow::Result<()> {
+    let ctx = upload_context(1024).await?;
+    let guild_id = create_guild(&ctx, "Uploads Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    let content = b"hello resumable world".to_vec();
+    let (status, headers) = ctx
+        .tus(
+            Method::POST,
+            "/api/v1/uploads",
+            &[
+                ("Upload-Length", content.len().to_string()),
+                ("Upload-Metadata", upload_metadata(&channel_id, "notes.txt")),
+            ],
+            Vec::new(),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let location = header_str(&headers, "location").to_string();
+    assert!(location.starts_with("/api/v1/uploads/"), "{location}");
+
+    let mut events = ctx.state.event_bus.subscribe_system();
+    let (status, headers) = ctx
+        .tus(
+            Method::PATCH,
+            &location,
+            &[
+                ("Upload-Offset", "0".to_string()),
+                (
+                    "Content-Type",
+                    "application/offset+octet-stream".to_string(),
+                ),
+            ],
+            content[..10].to_vec(),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(header_str(&headers, "upload-offset"), "10");
+    let event = events.try_recv()?;
+    assert_eq!(event.event_type, "UPLOAD_PROGRESS");
+    assert_eq!(event.target_user_ids, Some(vec![ctx.user_id]));
+    assert_eq!(event.payload["bytes_received"], 10);
+    assert!(!header_str(&headers, "upload-expires").is_empty());
+
+    // A client that lost track of the offset asks for it before resuming.
+    let (status, _) = ctx
+        .tus(
+            Method::PATCH,
+            &location,
+            &[
+                ("Upload-Offset", "4".to_string()),
+                (
+                    "Content-Type",
+                    "application/offset+octet-stream".to_string(),
+                ),
+            ],
+            content[4..].to_vec(),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, headers) = ctx.tus(Method::HEAD, &location, &[], Vec::new()).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header_str(&headers, "upload-offset"), "10");
+    assert_eq!(
+        header_str(&headers, "upload-length"),
+        content.len().to_string()
+    );
+
+    // Progress right after the last report is held back; the last byte is
+    // always reported.
+    for (start, end) in [(10, 15), (15, content.len())] {
+        let (status, _) = ctx
+            .tus(
+                Method::PATCH,
+                &location,
+                &[
+                    ("Upload-Offset", start.to_string()),
+                    (
+                        "Content-Type",
+                        "application/offset+octet-stream".to_string(),
+                    ),
+                ],
+                content[start..end].to_vec(),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    let mut reported = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "UPLOAD_PROGRESS" {
+            reported.push(event.payload["bytes_received"].as_u64());
+        }
+    }
+    assert_eq!(reported, vec![Some(content.len() as u64)]);
+    let (status, headers) = ctx.tus(Method::HEAD, &location, &[], Vec::new()).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        header_str(&headers, "upload-offset"),
+        content.len().to_string()
+    );
+    let attachment_id = header_str(&headers, "x-attachment-id").to_string();
+    assert!(!attachment_id.is_empty());
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "resumed", "attachment_ids": [attachment_id] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+
+    let response = ctx
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/api/v1/attachments/{attachment_id}"))
+                .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let downloaded = to_bytes(response.into_body(), usize::MAX).await?;
+    assert_eq!(downloaded.as_ref(), content.as_slice());
+    Ok(())
+}
+
+#[tokio::test]
+async fn tus_uploads_enforce_the_size_limit_while_streaming() -> anyhow::Result<()> {
+    let ctx = upload_context(16).await?;
+    let guild_id = create_guild(&ctx, "Limits Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+    let metadata = upload_metadata(&channel_id, "big.txt");
+
+    let (status, _) = ctx
+        .tus(
+            Method::POST,
+            "/api/v1/uploads",
+            &[
+                ("Upload-Length", "17".to_string()),
+                ("Upload-Metadata", metadata.clone()),
+            ],
+            Vec::new(),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let (status, headers) = ctx
+        .tus(
+            Method::POST,
+            "/api/v1/uploads",
+            &[
+                ("Upload-Length", "8".to_string()),
+                ("Upload-Metadata", metadata),
+            ],
+            Vec::new(),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let location = header_str(&headers, "location").to_string();
+
+    let (status, _) = ctx
+        .tus(
+            Method::PATCH,
+            &location,
+            &[
+                ("Upload-Offset", "0".to_string()),
+                (
+                    "Content-Type",
+                    "application/offset+octet-stream".to_string(),
+                ),
+            ],
+            vec![b'x'; 12],
+        )
+        .await?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    let (_, headers) = ctx.tus(Method::HEAD, &location, &[], Vec::new()).await?;
+    assert_eq!(header_str(&headers, "upload-offset"), "0");
+
+    // Uploads without the protocol header are refused outright.
+    let response = ctx
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::HEAD)
+                .uri(&location)
+                .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn encrypted_tus_uploads_stream_to_storage_and_back() -> anyhow::Result<()> {
+    encrypted_tus_round_trip(false).await?;
+    encrypted_tus_round_trip(true).await
+}
+
+async fn encrypted_tus_round_trip(chunked: bool) -> anyhow::Result<()> {
+    let master =
+        paracord_util::at_rest::parse_master_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=")?;
+    let cryptor = paracord_util::at_rest::FileCryptor::from_master_key(&master, false)
+        .with_chunked_writes(chunked);
+    let ctx = TestContext::with_state(|state| {
+        state.config.max_upload_size = 1024 * 1024;
+        state.config.file_cryptor = Some(cryptor);
+    })
+    .await?;
+    let guild_id = create_guild(&ctx, "Encrypted Uploads Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "files").await?;
+
+    // Spans several encryption chunks.
+    let content: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+    let (status, headers) = ctx
+        .tus(
+            Method::POST,
+            "/api/v1/uploads",
+            &[
+                ("Upload-Length", content.len().to_string()),
+                ("Upload-Metadata", upload_metadata(&channel_id, "data.bin")),
+            ],
+            Vec::new(),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let location = header_str(&headers, "location").to_string();
+    let (status, headers) = ctx
+        .tus(
+            Method::PATCH,
+            &location,
+            &[
+                ("Upload-Offset", "0".to_string()),
+                (
+                    "Content-Type",
+                    "application/offset+octet-stream".to_string(),
+                ),
+            ],
+            content.clone(),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let attachment_id = header_str(&headers, "x-attachment-id").to_string();
+    assert!(!attachment_id.is_empty());
+
+    // Only the stored object is left; the temp files are gone.
+    let tus_dir = std::path::Path::new(&ctx.state.config.storage_path).join("tus");
+    assert_eq!(std::fs::read_dir(&tus_dir)?.count(), 0);
+
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": "encrypted", "attachment_ids": [attachment_id] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+    let response = ctx
+        .app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/api/v1/attachments/{attachment_id}"))
+                .header(header::AUTHORIZATION, format!("Bearer {}", ctx.token))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let downloaded = to_bytes(response.into_body(), usize::MAX).await?;
+    assert_eq!(downloaded.as_ref(), content.as_slice());
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use anyhow::Context;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_guild, TestContext};
+
+impl TestContext {
+    /// POST a single-file multipart form under the field name `image`.
+    async fn upload_image(
+        &self,
+        path: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let boundary = "paracord-test-boundary";
+        let mut body = Vec::new();
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"image\"; filename=\"icon\"\r\nContent-Type: {content_type}\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", self.token))
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(Body::from(body))?;
+        let response = self.app.clone().oneshot(request).await?;
+        let status = response.status();
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await?;
+        let payload = serde_json::from_slice(&body_bytes).unwrap_or(Value::Null);
+        Ok((status, payload))
+    }
+
+    async fn get_raw(&self, path: &str) -> anyhow::Result<(StatusCode, Vec<u8>)> {
+        let request = Request::builder().uri(path).body(Body::empty())?;
+        let response = self.app.clone().oneshot(request).await?;
+        let status = response.status();
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, body_bytes.to_vec()))
+    }
+}
+
+async fn create_role(ctx: &TestContext, guild_id: &str, name: &str) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/roles"),
+            Some(json!({ "name": name })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    Ok(payload["id"]
+        .as_str()
+        .context("role id should be a string")?
+        .to_string())
+}
+
+async fn enable_role_icons(ctx: &TestContext, guild_id: &str) -> anyhow::Result<()> {
+    paracord_db::guilds::set_space_features(
+        &ctx.db,
+        guild_id.parse()?,
+        paracord_models::guild::GUILD_FEATURE_ROLE_ICONS,
+    )
+    .await?;
+    Ok(())
+}
+
+const TINY_PNG: &[u8] = &[
+    0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13, b'I', b'H', b'D', b'R',
+];
+
+#[tokio::test]
+async fn role_icons_require_the_guild_feature() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Icon Guild").await?;
+    let role_id = create_role(&ctx, &guild_id, "Stars").await?;
+    let role_path = format!("/api/v1/guilds/{guild_id}/roles/{role_id}");
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &role_path,
+            Some(json!({ "role_icon": "\u{2b50}" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    let (status, payload) = ctx
+        .upload_image(&format!("{role_path}/icon"), "image/png", TINY_PNG)
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn emoji_role_icon_is_validated_and_serialized() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Icon Guild").await?;
+    enable_role_icons(&ctx, &guild_id).await?;
+    let role_id = create_role(&ctx, &guild_id, "Stars").await?;
+    let role_path = format!("/api/v1/guilds/{guild_id}/roles/{role_id}");
+
+    for invalid in ["star", ":star:", "\u{2b50} \u{2b50}"] {
+        let (status, payload) = ctx
+            .request_json(
+                Method::PATCH,
+                &role_path,
+                Some(json!({ "role_icon": invalid })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{invalid}: {payload}");
+    }
+
+    let (status, role) = ctx
+        .request_json(
+            Method::PATCH,
+            &role_path,
+            Some(json!({ "role_icon": "\u{1f468}\u{200d}\u{1f680}" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{role}");
+    assert_eq!(role["role_icon"], "\u{1f468}\u{200d}\u{1f680}");
+    assert_eq!(role["role_icon_url"], Value::Null);
+
+    let (status, roles) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/roles"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let listed = roles
+        .as_array()
+        .context("roles list")?
+        .iter()
+        .find(|r| r["id"] == role_id.as_str())
+        .context("role in list")?;
+    assert_eq!(listed["role_icon"], "\u{1f468}\u{200d}\u{1f680}");
+
+    let (status, role) = ctx
+        .request_json(Method::PATCH, &role_path, Some(json!({ "role_icon": "" })))
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{role}");
+    assert_eq!(role["role_icon"], Value::Null);
+    Ok(())
+}
+
+#[tokio::test]
+async fn uploaded_role_icon_is_validated_stored_and_shown_on_members() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Icon Guild").await?;
+    enable_role_icons(&ctx, &guild_id).await?;
+    let role_id = create_role(&ctx, &guild_id, "Badge").await?;
+    let icon_path = format!("/api/v1/guilds/{guild_id}/roles/{role_id}/icon");
+
+    let (status, payload) = ctx
+        .upload_image(&icon_path, "image/jpeg", b"\xff\xd8\xff")
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    let (status, payload) = ctx
+        .upload_image(&icon_path, "image/png", b"GIF89a not a png")
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    let oversized = [TINY_PNG, &vec![0_u8; 256 * 1024]].concat();
+    let (status, payload) = ctx
+        .upload_image(&icon_path, "image/png", &oversized)
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+
+    let (status, role) = ctx.upload_image(&icon_path, "image/png", TINY_PNG).await?;
+    assert_eq!(status, StatusCode::OK, "{role}");
+    let hash = role["role_icon"].as_str().context("icon hash")?;
+    assert_eq!(hash.len(), 64);
+    let icon_url = role["role_icon_url"].as_str().context("icon url")?;
+    assert!(icon_url.starts_with(&icon_path));
+
+    let (status, bytes) = ctx.get_raw(icon_url).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bytes, TINY_PNG);
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/members/{}", ctx.user_id),
+            Some(json!({ "roles": [&role_id] })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    let (status, members) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/members"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let me = members
+        .as_array()
+        .context("members list")?
+        .iter()
+        .find(|m| m["user_id"] == ctx.user_id.to_string().as_str())
+        .context("own member entry")?;
+    assert_eq!(me["role_icon"]["role_id"], role_id.as_str());
+    assert_eq!(me["role_icon"]["role_icon"], hash);
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use anyhow::Context;
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::{
+    create_authenticated_user_token, create_guild, create_pending_attachment, create_text_channel,
+    TestContext,
+};
+
+async fn send_text_message(
+    ctx: &TestContext,
+    channel_id: &str,
+    content: &str,
+    attachment_ids: &[String],
+) -> anyhow::Result<String> {
+    let (status, message) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/channels/{channel_id}/messages"),
+            Some(json!({ "content": content, "attachment_ids": attachment_ids })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+    Ok(message["id"].as_str().context("message id")?.to_string())
+}
+
+async fn search_ids(ctx: &TestContext, path: &str) -> anyhow::Result<Vec<String>> {
+    let (status, payload) = ctx.request_json(Method::GET, path, None).await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    Ok(payload
+        .as_array()
+        .context("search results should be an array")?
+        .iter()
+        .filter_map(|m| m["id"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[tokio::test]
+async fn message_search_supports_phrases_and_filters() -> anyhow::Result<()> {
+    let mut ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Search Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "general").await?;
+    let messages_path = format!("/api/v1/channels/{channel_id}/messages");
+    let owner_id = ctx.user_id;
+
+    let quarterly = send_text_message(&ctx, &channel_id, "quarterly release notes", &[]).await?;
+    send_text_message(&ctx, &channel_id, "release the notes", &[]).await?;
+    let attachment_id = create_pending_attachment(&ctx, &channel_id, 10).await?;
+    let attached = send_text_message(
+        &ctx,
+        &channel_id,
+        "release notes attached",
+        &[attachment_id],
+    )
+    .await?;
+
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id.parse()?).await?;
+    let owner_token = std::mem::replace(&mut ctx.token, member_token);
+    let from_member = send_text_message(&ctx, &channel_id, "my release notes", &[]).await?;
+    ctx.token = owner_token;
+
+    let search = |q: &str| {
+        format!(
+            "/api/v1/channels/{channel_id}/messages/search?q={}",
+            q.replace(' ', "%20")
+                .replace('"', "%22")
+                .replace(':', "%3A")
+        )
+    };
+    let mut ids = search_ids(&ctx, &search("\"release notes\"")).await?;
+    ids.sort();
+    let mut expected = vec![quarterly.clone(), attached.clone(), from_member.clone()];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    assert_eq!(
+        search_ids(
+            &ctx,
+            &search(&format!("\"release notes\" from:{member_id}"))
+        )
+        .await?,
+        vec![from_member]
+    );
+    assert_eq!(
+        search_ids(&ctx, &search("notes has:attachment")).await?,
+        vec![attached]
+    );
+    assert_eq!(
+        search_ids(&ctx, &search(&format!("quarterly from:{owner_id}"))).await?,
+        vec![quarterly.clone()]
+    );
+
+    let (status, _) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("{messages_path}/{quarterly}"),
+            Some(json!({ "content": "annual summary" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(search_ids(&ctx, &search("quarterly")).await?.is_empty());
+
+    let (status, _) = ctx
+        .request_json(Method::GET, &search("has:nothing"), None)
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[tokio::test]
+async fn guild_message_search_skips_channels_the_caller_cannot_read() -> anyhow::Result<()> {
+    let mut ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Search Guild").await?;
+    let general = create_text_channel(&ctx, &guild_id, "general").await?;
+    let secret = create_text_channel(&ctx, &guild_id, "secret").await?;
+    let visible = send_text_message(&ctx, &general, "launch plan", &[]).await?;
+    send_text_message(&ctx, &secret, "launch plan", &[]).await?;
+    let guild_search = format!("/api/v1/guilds/{guild_id}/messages/search?q=launch");
+    assert_eq!(search_ids(&ctx, &guild_search).await?.len(), 2);
+
+    let (member_id, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id.parse()?).await?;
+    paracord_db::channel_overwrites::upsert_channel_overwrite(
+        &ctx.db,
+        secret.parse()?,
+        member_id,
+        paracord_core::permissions::OVERWRITE_TARGET_MEMBER,
+        0,
+        paracord_models::permissions::Permissions::VIEW_CHANNEL.bits(),
+    )
+    .await?;
+    ctx.token = member_token;
+
+    assert_eq!(search_ids(&ctx, &guild_search).await?, vec![visible]);
+    let (status, _) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/channels/{secret}/messages/search?q=launch"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (_, outsider_token) = create_authenticated_user_token(&ctx.db).await?;
+    ctx.token = outsider_token;
+    let (status, _) = ctx.request_json(Method::GET, &guild_search, None).await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    Ok(())
+}
@@ -1,139 +1,45 @@
-use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::OnceLock;
 
 use axum::{
-    body::{to_bytes, Body},
+    body::Body,
     http::{header, Request, StatusCode},
-    Router,
 };
-use paracord_core::{build_permission_cache, AppConfig, AppState, RuntimeSettings};
-use paracord_media::{
-    LiveKitConfig, LocalStorage, Storage, StorageConfig, StorageManager, VoiceManager,
-};
-use serde_json::{json, Value};
-use tempfile::TempDir;
-use tokio::sync::{Notify, RwLock};
-use tower::ServiceExt;
-
-fn env_lock() -> &'static Mutex<()> {
-    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
-    LOCK.get_or_init(|| Mutex::new(()))
-}
-
-struct TestHarness {
-    app: Router,
-    db: paracord_db::DbPool,
-    _storage_dir: TempDir,
-    _media_dir: TempDir,
-    _backup_dir: TempDir,
-}
+use serde_json::json;
 
-impl TestHarness {
-    async fn new(run_migrations: bool) -> anyhow::Result<Self> {
-        let db = paracord_db::create_pool("sqlite::memory:", 1).await?;
-        if run_migrations {
-            paracord_db::run_migrations(&db).await?;
-        }
+mod common;
 
-        let storage_dir = tempfile::tempdir()?;
-        let media_dir = tempfile::tempdir()?;
-        let backup_dir = tempfile::tempdir()?;
-        let livekit = Arc::new(LiveKitConfig {
-            api_key: "lk-test-key".to_string(),
-            api_secret: "lk-test-secret".to_string(),
-            url: "ws://localhost:7880".to_string(),
-            http_url: "http://localhost:7880".to_string(),
-        });
+use common::TestContext;
 
-        let state = AppState {
-            db: db.clone(),
-            event_bus: paracord_core::events::EventBus::default(),
-            config: AppConfig {
-                jwt_secret: "integration-test-secret".to_string(),
-                jwt_expiry_seconds: 3600,
-                registration_enabled: true,
-                allow_username_login: false,
-                require_email: true,
-                storage_path: storage_dir.path().to_string_lossy().into_owned(),
-                max_upload_size: 10 * 1024 * 1024,
-                livekit_api_key: livekit.api_key.clone(),
-                livekit_api_secret: livekit.api_secret.clone(),
-                livekit_url: livekit.url.clone(),
-                livekit_http_url: livekit.http_url.clone(),
-                livekit_public_url: livekit.url.clone(),
-                livekit_available: false,
-                public_url: None,
-                media_storage_path: media_dir.path().to_string_lossy().into_owned(),
-                media_max_file_size: 10 * 1024 * 1024,
-                media_p2p_threshold: 1024 * 1024,
-                file_cryptor: None,
-                backup_dir: backup_dir.path().to_string_lossy().into_owned(),
-                database_url: "sqlite::memory:".to_string(),
-                federation_max_events_per_peer_per_minute: None,
-                federation_max_user_creates_per_peer_per_hour: None,
-                native_media_enabled: false,
-                native_media_port: 8443,
-                native_media_max_participants: 50,
-                native_media_e2ee_required: false,
-                max_guild_storage_quota: 0,
-                federation_file_cache_enabled: false,
-                federation_file_cache_max_size: 0,
-                federation_file_cache_ttl_hours: 0,
-            },
-            runtime: Arc::new(RwLock::new(RuntimeSettings::default())),
-            voice: Arc::new(VoiceManager::new(livekit)),
-            storage: Arc::new(StorageManager::new(StorageConfig {
-                base_path: media_dir.path().to_path_buf(),
-                max_file_size: 10 * 1024 * 1024,
-                p2p_threshold: 1024 * 1024,
-                allowed_extensions: None,
-            })),
-            storage_backend: Arc::new(Storage::Local(LocalStorage::new(storage_dir.path()))),
-            shutdown: Arc::new(Notify::new()),
-            online_users: Arc::new(RwLock::new(HashSet::new())),
-            user_presences: Arc::new(RwLock::new(HashMap::new())),
-            permission_cache: build_permission_cache(),
-            federation_service: None,
-            member_index: Arc::new(paracord_core::member_index::MemberIndex::empty()),
-            native_media: None,
-        };
-
-        let app = paracord_api::build_router().with_state(state);
-        Ok(Self {
-            app,
-            db,
-            _storage_dir: storage_dir,
-            _media_dir: media_dir,
-            _backup_dir: backup_dir,
-        })
-    }
+/// Serializes tests that set federation env vars. An async lock, because
+/// the guard is held across the requests each test makes.
+fn env_lock() -> &'static tokio::sync::Mutex<()> {
+    static LOCK: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| tokio::sync::Mutex::new(()))
+}
 
-    async fn request(&self, request: Request<Body>) -> anyhow::Result<(StatusCode, Value)> {
-        let response = self.app.clone().oneshot(request).await?;
-        let status = response.status();
-        let body = to_bytes(response.into_body(), usize::MAX).await?;
-        let payload = if body.is_empty() {
-            Value::Null
-        } else {
-            serde_json::from_slice(&body)
-                .unwrap_or_else(|_| json!({ "raw": String::from_utf8_lossy(&body) }))
-        };
-        Ok((status, payload))
-    }
+async fn federation_context(
+    max_events_per_minute: Option<u32>,
+    max_user_creates_per_hour: Option<u32>,
+) -> anyhow::Result<TestContext> {
+    TestContext::without_http_rate_limiter(|state| {
+        state.config.federation_max_events_per_peer_per_minute = max_events_per_minute;
+        state.config.federation_max_user_creates_per_peer_per_hour = max_user_creates_per_hour;
+    })
+    .await
 }
 
 #[tokio::test]
 async fn federation_read_rejects_unsigned_requests_without_token() -> anyhow::Result<()> {
-    let _guard = env_lock().lock().unwrap_or_else(|e| e.into_inner());
+    let _guard = env_lock().lock().await;
     std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
     std::env::remove_var("PARACORD_FEDERATION_READ_TOKEN");
 
-    let harness = TestHarness::new(true).await?;
+    let harness = federation_context(None, None).await?;
     let request = Request::builder()
         .uri("/_paracord/federation/v1/event/nonexistent")
         .body(Body::empty())?;
 
-    let (status, _) = harness.request(request).await?;
+    let (status, _) = harness.send(request).await?;
     assert_eq!(status, StatusCode::UNAUTHORIZED);
 
     std::env::remove_var("PARACORD_FEDERATION_ENABLED");
@@ -142,18 +48,18 @@ async fn federation_read_rejects_unsigned_requests_without_token() -> anyhow::Re
 
 #[tokio::test]
 async fn federation_read_accepts_configured_token() -> anyhow::Result<()> {
-    let _guard = env_lock().lock().unwrap_or_else(|e| e.into_inner());
+    let _guard = env_lock().lock().await;
     std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
     std::env::set_var("PARACORD_FEDERATION_READ_TOKEN", "token-123");
 
-    let harness = TestHarness::new(true).await?;
+    let harness = federation_context(None, None).await?;
     let request = Request::builder()
         .uri("/_paracord/federation/v1/event/nonexistent")
         .header("x-paracord-federation-token", "token-123")
         .header(header::ACCEPT, "application/json")
         .body(Body::empty())?;
 
-    let (status, _) = harness.request(request).await?;
+    let (status, _) = harness.send(request).await?;
     assert_eq!(status, StatusCode::NOT_FOUND);
 
     std::env::remove_var("PARACORD_FEDERATION_ENABLED");
@@ -163,10 +69,10 @@ async fn federation_read_accepts_configured_token() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn federation_media_token_requires_existing_room_membership() -> anyhow::Result<()> {
-    let _guard = env_lock().lock().unwrap_or_else(|e| e.into_inner());
+    let _guard = env_lock().lock().await;
     std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
 
-    let harness = TestHarness::new(true).await?;
+    let harness = federation_context(None, None).await?;
 
     let owner_id = 5001;
     paracord_db::users::create_user(
@@ -247,8 +153,184 @@ async fn federation_media_token_requires_existing_room_membership() -> anyhow::R
         .header("x-paracord-signature", signature)
         .body(Body::from(body_bytes))?;
 
-    let (status, _) = harness.request(request).await?;
+    let (status, _) = harness.send(request).await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    std::env::remove_var("PARACORD_FEDERATION_ENABLED");
+    std::env::remove_var("PARACORD_FEDERATION_ALLOWED_GUILD_IDS");
+    Ok(())
+}
+
+#[tokio::test]
+async fn federation_read_tokens_are_scoped_to_the_joined_room() -> anyhow::Result<()> {
+    let _guard = env_lock().lock().await;
+    std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
+    std::env::remove_var("PARACORD_FEDERATION_READ_TOKEN");
+
+    let harness = federation_context(None, None).await?;
+    let owner_id = 5101;
+    paracord_db::users::create_user(
+        &harness.db,
+        owner_id,
+        "owner",
+        1,
+        "owner@example.com",
+        "hash",
+    )
+    .await?;
+    let (joined_guild_id, other_guild_id) = (7101, 7201);
+    for guild_id in [joined_guild_id, other_guild_id] {
+        paracord_db::guilds::create_guild(&harness.db, guild_id, "Guild", owner_id, None).await?;
+    }
+    std::env::set_var(
+        "PARACORD_FEDERATION_ALLOWED_GUILD_IDS",
+        format!("{joined_guild_id},{other_guild_id}"),
+    );
+
+    let origin_server = "remote.example";
+    let key_id = "ed25519:test";
+    let (signing_key, public_key_hex) = paracord_federation::signing::generate_keypair();
+    paracord_db::federation::upsert_federated_server(
+        &harness.db,
+        9101,
+        origin_server,
+        origin_server,
+        "https://remote.example/_paracord/federation/v1",
+        Some(&public_key_hex),
+        Some(key_id),
+        true,
+    )
+    .await?;
+    let service =
+        paracord_federation::FederationService::new(paracord_federation::FederationConfig {
+            enabled: true,
+            server_name: "localhost".to_string(),
+            domain: "localhost".to_string(),
+            key_id: "ed25519:local".to_string(),
+            signing_key: None,
+            allow_discovery: false,
+        });
+    service
+        .upsert_server_key(
+            &harness.db,
+            &paracord_federation::FederationServerKey {
+                server_name: origin_server.to_string(),
+                key_id: key_id.to_string(),
+                public_key: public_key_hex.to_string(),
+                valid_until: chrono::Utc::now().timestamp_millis() + 600_000,
+            },
+        )
+        .await?;
+
+    let timestamp_ms = chrono::Utc::now().timestamp_millis();
+    let signed_join = |guild_id: i64| -> anyhow::Result<Request<Body>> {
+        let body = json!({
+            "origin_server": origin_server,
+            "room_id": format!("!{guild_id}:localhost"),
+            "user_id": "@alice:remote.example"
+        });
+        let body_bytes = serde_json::to_vec(&body)?;
+        let canonical = paracord_federation::transport::canonical_transport_bytes_with_body(
+            "POST",
+            "/_paracord/federation/v1/join",
+            timestamp_ms,
+            &body_bytes,
+        );
+        let signature = paracord_federation::signing::sign(&signing_key, &canonical);
+        Ok(Request::builder()
+            .method("POST")
+            .uri("/_paracord/federation/v1/join")
+            .header("content-type", "application/json")
+            .header("x-paracord-origin", origin_server)
+            .header("x-paracord-key-id", key_id)
+            .header("x-paracord-timestamp", timestamp_ms.to_string())
+            .header("x-paracord-signature", signature)
+            .body(Body::from(body_bytes))?)
+    };
+    let (status, joined) = harness.send(signed_join(joined_guild_id)?).await?;
+    assert_eq!(status, StatusCode::OK, "{joined}");
+    let read_token = joined["read_token"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    assert!(!read_token.is_empty());
+
+    for (event_id, guild_id) in [
+        ("evt-joined", joined_guild_id),
+        ("evt-other", other_guild_id),
+    ] {
+        service
+            .persist_event(
+                &harness.db,
+                &paracord_federation::FederationEventEnvelope {
+                    event_id: event_id.to_string(),
+                    room_id: format!("!{guild_id}:localhost"),
+                    event_type: "m.message".to_string(),
+                    sender: "@owner:localhost".to_string(),
+                    origin_server: "localhost".to_string(),
+                    origin_ts: timestamp_ms,
+                    content: json!({ "body": "hello" }),
+                    depth: 1,
+                    state_key: None,
+                    signatures: json!({}),
+                },
+            )
+            .await?;
+    }
+
+    let read = |uri: String| {
+        let request = Request::builder()
+            .uri(uri)
+            .header("x-paracord-federation-token", read_token.as_str())
+            .body(Body::empty());
+        async { harness.send(request?).await }
+    };
+    let (status, event) = read("/_paracord/federation/v1/event/evt-joined".into()).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(event["room_id"], format!("!{joined_guild_id}:localhost"));
+    let (status, _) = read("/_paracord/federation/v1/event/evt-other".into()).await?;
     assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = read(format!(
+        "/_paracord/federation/v1/events?room_id=%21{other_guild_id}%3Alocalhost"
+    ))
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, listed) = read(format!(
+        "/_paracord/federation/v1/events?room_id=%21{joined_guild_id}%3Alocalhost"
+    ))
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed["events"].as_array().map(Vec::len), Some(1));
+
+    // A remote user banned from a guild cannot join it over federation.
+    let alice_id =
+        paracord_db::federation::get_remote_user_mapping(&harness.db, "@alice:remote.example")
+            .await?
+            .map(|mapping| mapping.local_user_id)
+            .unwrap_or_default();
+    paracord_db::bans::create_ban(&harness.db, alice_id, other_guild_id, None, owner_id).await?;
+    let (status, refused) = harness.send(signed_join(other_guild_id)?).await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{refused}");
+    assert!(
+        paracord_db::members::get_member(&harness.db, alice_id, other_guild_id)
+            .await?
+            .is_none()
+    );
+
+    // Dropping trust in the peer revokes its tokens.
+    paracord_db::federation::upsert_federated_server(
+        &harness.db,
+        9101,
+        origin_server,
+        origin_server,
+        "https://remote.example/_paracord/federation/v1",
+        Some(&public_key_hex),
+        Some(key_id),
+        false,
+    )
+    .await?;
+    let (status, _) = read("/_paracord/federation/v1/event/evt-joined".into()).await?;
+    assert_ne!(status, StatusCode::OK);
 
     std::env::remove_var("PARACORD_FEDERATION_ENABLED");
     std::env::remove_var("PARACORD_FEDERATION_ALLOWED_GUILD_IDS");
@@ -257,10 +339,10 @@ async fn federation_media_token_requires_existing_room_membership() -> anyhow::R
 
 #[tokio::test]
 async fn federation_message_ingest_materializes_missing_space_and_channel() -> anyhow::Result<()> {
-    let _guard = env_lock().lock().unwrap_or_else(|e| e.into_inner());
+    let _guard = env_lock().lock().await;
     std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
 
-    let harness = TestHarness::new(true).await?;
+    let harness = federation_context(None, None).await?;
     let origin_server = "remote.example";
     let key_id = "ed25519:test";
     let (signing_key, public_key_hex) = paracord_federation::signing::generate_keypair();
@@ -349,7 +431,7 @@ async fn federation_message_ingest_materializes_missing_space_and_channel() -> a
         .header("x-paracord-signature", transport_sig)
         .body(Body::from(body_bytes))?;
 
-    let (status, body) = harness.request(request).await?;
+    let (status, body) = harness.send(request).await?;
     assert_eq!(status, StatusCode::ACCEPTED);
     assert_eq!(body.get("inserted").and_then(|v| v.as_bool()), Some(true));
 
@@ -388,10 +470,10 @@ async fn federation_message_ingest_materializes_missing_space_and_channel() -> a
 
 #[tokio::test]
 async fn federation_ingest_does_not_collide_with_existing_local_ids() -> anyhow::Result<()> {
-    let _guard = env_lock().lock().unwrap_or_else(|e| e.into_inner());
+    let _guard = env_lock().lock().await;
     std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
 
-    let harness = TestHarness::new(true).await?;
+    let harness = federation_context(None, None).await?;
     let owner_id = 42_001;
     paracord_db::users::create_user(
         &harness.db,
@@ -500,7 +582,7 @@ async fn federation_ingest_does_not_collide_with_existing_local_ids() -> anyhow:
         .header("x-paracord-timestamp", timestamp_ms.to_string())
         .header("x-paracord-signature", transport_sig)
         .body(Body::from(body_bytes))?;
-    let (status, _) = harness.request(request).await?;
+    let (status, _) = harness.send(request).await?;
     assert_eq!(status, StatusCode::ACCEPTED);
 
     let space_mapping =
@@ -545,10 +627,10 @@ async fn federation_ingest_does_not_collide_with_existing_local_ids() -> anyhow:
 #[tokio::test]
 async fn federation_room_namespace_mapping_is_used_even_when_sender_differs() -> anyhow::Result<()>
 {
-    let _guard = env_lock().lock().unwrap_or_else(|e| e.into_inner());
+    let _guard = env_lock().lock().await;
     std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
 
-    let harness = TestHarness::new(true).await?;
+    let harness = federation_context(None, None).await?;
 
     let owner_id = 58_001;
     paracord_db::users::create_user(
@@ -682,7 +764,7 @@ async fn federation_room_namespace_mapping_is_used_even_when_sender_differs() ->
         .header("x-paracord-timestamp", timestamp_ms.to_string())
         .header("x-paracord-signature", transport_sig)
         .body(Body::from(body_bytes))?;
-    let (status, _) = harness.request(request).await?;
+    let (status, _) = harness.send(request).await?;
     assert_eq!(status, StatusCode::ACCEPTED);
 
     let msgs =
@@ -705,3 +787,364 @@ async fn federation_room_namespace_mapping_is_used_even_when_sender_differs() ->
     std::env::remove_var("PARACORD_FEDERATION_ENABLED");
     Ok(())
 }
+
+#[tokio::test]
+async fn federated_member_join_writes_federation_tagged_audit_entry() -> anyhow::Result<()> {
+    let _guard = env_lock().lock().await;
+    std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
+
+    let harness = federation_context(None, None).await?;
+
+    let owner_id = 59_001;
+    paracord_db::users::create_user(
+        &harness.db,
+        owner_id,
+        "owner",
+        1,
+        "owner@example.com",
+        "hash",
+    )
+    .await?;
+    let local_guild_id = 89_001;
+    paracord_db::guilds::create_guild(
+        &harness.db,
+        local_guild_id,
+        "Mirrored Remote",
+        owner_id,
+        None,
+    )
+    .await?;
+    paracord_db::federation::upsert_space_mapping(
+        &harness.db,
+        "remote.example",
+        "7010",
+        local_guild_id,
+    )
+    .await?;
+    std::env::set_var(
+        "PARACORD_FEDERATION_ALLOWED_GUILD_IDS",
+        local_guild_id.to_string(),
+    );
+
+    let origin_server = "remote.example";
+    let key_id = "ed25519:test";
+    let (signing_key, public_key_hex) = paracord_federation::signing::generate_keypair();
+    paracord_db::federation::upsert_federated_server(
+        &harness.db,
+        9401,
+        origin_server,
+        origin_server,
+        "https://remote.example/_paracord/federation/v1",
+        Some(&public_key_hex),
+        Some(key_id),
+        true,
+    )
+    .await?;
+
+    let service =
+        paracord_federation::FederationService::new(paracord_federation::FederationConfig {
+            enabled: true,
+            server_name: "local.example".to_string(),
+            domain: "local.example".to_string(),
+            key_id: "ed25519:local".to_string(),
+            signing_key: None,
+            allow_discovery: false,
+        });
+    service
+        .upsert_server_key(
+            &harness.db,
+            &paracord_federation::FederationServerKey {
+                server_name: origin_server.to_string(),
+                key_id: key_id.to_string(),
+                public_key: public_key_hex.to_string(),
+                valid_until: chrono::Utc::now().timestamp_millis() + 600_000,
+            },
+        )
+        .await?;
+
+    let mut envelope = paracord_federation::FederationEventEnvelope {
+        event_id: "$evt-join:remote.example".to_string(),
+        room_id: "!7010:remote.example".to_string(),
+        event_type: "m.member.join".to_string(),
+        sender: "@carol:remote.example".to_string(),
+        origin_server: origin_server.to_string(),
+        origin_ts: chrono::Utc::now().timestamp_millis(),
+        content: json!({ "guild_id": "7010" }),
+        depth: chrono::Utc::now().timestamp_millis(),
+        state_key: None,
+        signatures: json!({}),
+    };
+    let payload_sig = paracord_federation::signing::sign(
+        &signing_key,
+        &paracord_federation::canonical_envelope_bytes(&envelope),
+    );
+    envelope.signatures = json!({
+        origin_server: {
+            key_id: payload_sig,
+        }
+    });
+
+    let body_bytes = serde_json::to_vec(&envelope)?;
+    let timestamp_ms = chrono::Utc::now().timestamp_millis();
+    let canonical = paracord_federation::transport::canonical_transport_bytes_with_body(
+        "POST",
+        "/_paracord/federation/v1/event",
+        timestamp_ms,
+        &body_bytes,
+    );
+    let transport_sig = paracord_federation::signing::sign(&signing_key, &canonical);
+    let request = Request::builder()
+        .method("POST")
+        .uri("/_paracord/federation/v1/event")
+        .header("content-type", "application/json")
+        .header("x-paracord-origin", origin_server)
+        .header("x-paracord-key-id", key_id)
+        .header("x-paracord-timestamp", timestamp_ms.to_string())
+        .header("x-paracord-signature", transport_sig)
+        .body(Body::from(body_bytes))?;
+    let (status, _) = harness.send(request).await?;
+    assert_eq!(status, StatusCode::ACCEPTED);
+
+    let mapping =
+        paracord_db::federation::get_remote_user_mapping(&harness.db, "@carol:remote.example")
+            .await?
+            .expect("remote user should be mapped");
+    let entries = paracord_db::audit_log::get_guild_entries(
+        &harness.db,
+        local_guild_id,
+        Some(paracord_api::routes::audit::ACTION_FEDERATED_MEMBER_JOIN),
+        None,
+        None,
+        10,
+    )
+    .await?;
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.user_id, mapping.local_user_id);
+    assert_eq!(entry.target_id, Some(mapping.local_user_id));
+    let federation = &entry.changes.as_ref().expect("changes should be set")["federation"];
+    assert_eq!(federation["origin_server"], origin_server);
+    assert_eq!(federation["remote_user"], "@carol:remote.example");
+    assert_eq!(federation["event_id"], "$evt-join:remote.example");
+
+    // The same event handled again (redelivery, backfill) adds no entry.
+    paracord_api::routes::audit::log_federated_action(
+        &harness.state,
+        local_guild_id,
+        mapping.local_user_id,
+        paracord_api::routes::audit::ACTION_FEDERATED_MEMBER_JOIN,
+        Some(mapping.local_user_id),
+        origin_server,
+        "@carol:remote.example",
+        Some("$evt-join:remote.example"),
+        None,
+    )
+    .await;
+    let entries = paracord_db::audit_log::get_guild_entries(
+        &harness.db,
+        local_guild_id,
+        Some(paracord_api::routes::audit::ACTION_FEDERATED_MEMBER_JOIN),
+        None,
+        None,
+        10,
+    )
+    .await?;
+    assert_eq!(entries.len(), 1);
+
+    std::env::remove_var("PARACORD_FEDERATION_ALLOWED_GUILD_IDS");
+    std::env::remove_var("PARACORD_FEDERATION_ENABLED");
+    Ok(())
+}
+
+/// Register `origin_server` as a trusted peer and return its signing key.
+async fn trust_remote_peer(
+    harness: &TestContext,
+    peer_id: i64,
+    origin_server: &str,
+    key_id: &str,
+) -> anyhow::Result<ed25519_dalek::SigningKey> {
+    let (signing_key, public_key_hex) = paracord_federation::signing::generate_keypair();
+    paracord_db::federation::upsert_federated_server(
+        &harness.db,
+        peer_id,
+        origin_server,
+        origin_server,
+        &format!("https://{origin_server}/_paracord/federation/v1"),
+        Some(&public_key_hex),
+        Some(key_id),
+        true,
+    )
+    .await?;
+    let service =
+        paracord_federation::FederationService::new(paracord_federation::FederationConfig {
+            enabled: true,
+            server_name: "local.example".to_string(),
+            domain: "local.example".to_string(),
+            key_id: "ed25519:local".to_string(),
+            signing_key: None,
+            allow_discovery: false,
+        });
+    service
+        .upsert_server_key(
+            &harness.db,
+            &paracord_federation::FederationServerKey {
+                server_name: origin_server.to_string(),
+                key_id: key_id.to_string(),
+                public_key: public_key_hex.to_string(),
+                valid_until: chrono::Utc::now().timestamp_millis() + 600_000,
+            },
+        )
+        .await?;
+    Ok(signing_key)
+}
+
+/// A signed `/event` request carrying one `m.message` from `sender`.
+fn signed_message_event(
+    signing_key: &ed25519_dalek::SigningKey,
+    origin_server: &str,
+    key_id: &str,
+    sender: &str,
+    seq: i64,
+) -> anyhow::Result<Request<Body>> {
+    let mut envelope = paracord_federation::FederationEventEnvelope {
+        event_id: format!("$evt{seq}:{origin_server}"),
+        room_id: format!("!7310:{origin_server}"),
+        event_type: "m.message".to_string(),
+        sender: sender.to_string(),
+        origin_server: origin_server.to_string(),
+        origin_ts: chrono::Utc::now().timestamp_millis(),
+        content: json!({
+            "body": format!("message {seq}"),
+            "msgtype": "m.text",
+            "guild_id": "7310",
+            "guild_name": "Remote Guild",
+            "channel_id": "7320",
+            "channel_name": "general",
+            "channel_type": 0,
+            "message_id": (95_000 + seq).to_string(),
+        }),
+        depth: chrono::Utc::now().timestamp_millis() + seq,
+        state_key: None,
+        signatures: json!({}),
+    };
+    let payload_sig = paracord_federation::signing::sign(
+        signing_key,
+        &paracord_federation::canonical_envelope_bytes(&envelope),
+    );
+    envelope.signatures = json!({ origin_server: { key_id: payload_sig } });
+
+    let body_bytes = serde_json::to_vec(&envelope)?;
+    let timestamp_ms = chrono::Utc::now().timestamp_millis();
+    let canonical = paracord_federation::transport::canonical_transport_bytes_with_body(
+        "POST",
+        "/_paracord/federation/v1/event",
+        timestamp_ms,
+        &body_bytes,
+    );
+    let transport_sig = paracord_federation::signing::sign(signing_key, &canonical);
+    Ok(Request::builder()
+        .method("POST")
+        .uri("/_paracord/federation/v1/event")
+        .header("content-type", "application/json")
+        .header("x-paracord-origin", origin_server)
+        .header("x-paracord-key-id", key_id)
+        .header("x-paracord-timestamp", timestamp_ms.to_string())
+        .header("x-paracord-signature", transport_sig)
+        .body(Body::from(body_bytes))?)
+}
+
+#[tokio::test]
+async fn federation_peer_over_event_budget_is_throttled() -> anyhow::Result<()> {
+    let _guard = env_lock().lock().await;
+    std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
+
+    let harness = federation_context(Some(2), None).await?;
+    let key_id = "ed25519:test";
+    let noisy = trust_remote_peer(&harness, 9301, "noisy.example", key_id).await?;
+    let quiet = trust_remote_peer(&harness, 9302, "quiet.example", key_id).await?;
+
+    for seq in 1..=2 {
+        let request =
+            signed_message_event(&noisy, "noisy.example", key_id, "@alice:noisy.example", seq)?;
+        let (status, body) = harness.send(request).await?;
+        assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+    }
+    for seq in 3..=4 {
+        let request =
+            signed_message_event(&noisy, "noisy.example", key_id, "@alice:noisy.example", seq)?;
+        let (status, _) = harness.send(request).await?;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    // Another peer keeps its own budget.
+    let request = signed_message_event(&quiet, "quiet.example", key_id, "@bob:quiet.example", 1)?;
+    let (status, body) = harness.send(request).await?;
+    assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+
+    // Only the transition into the throttled state is logged.
+    let events = paracord_db::security_events::list_events(
+        &harness.db,
+        Some("federation.peer.rate_limited"),
+        None,
+        10,
+    )
+    .await?;
+    assert_eq!(events.len(), 1);
+    let details = events[0].details.clone().expect("details");
+    assert_eq!(details["peer"], "noisy.example");
+    assert_eq!(details["budget"], "events");
+
+    std::env::remove_var("PARACORD_FEDERATION_ENABLED");
+    Ok(())
+}
+
+#[tokio::test]
+async fn federation_user_create_budget_is_independent_of_event_budget() -> anyhow::Result<()> {
+    let _guard = env_lock().lock().await;
+    std::env::set_var("PARACORD_FEDERATION_ENABLED", "true");
+
+    let harness = federation_context(Some(10), Some(1)).await?;
+    let origin_server = "remote.example";
+    let key_id = "ed25519:test";
+    let signing_key = trust_remote_peer(&harness, 9401, origin_server, key_id).await?;
+
+    let senders = [
+        "@alice:remote.example",
+        "@bob:remote.example",
+        "@alice:remote.example",
+    ];
+    for (seq, sender) in senders.into_iter().enumerate() {
+        let request =
+            signed_message_event(&signing_key, origin_server, key_id, sender, seq as i64 + 1)?;
+        let (status, body) = harness.send(request).await?;
+        // Events stay within their own budget even once user creation is
+        // exhausted; the message falls back to the federated system user.
+        assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+    }
+
+    assert!(
+        paracord_db::federation::get_remote_user_mapping(&harness.db, "@alice:remote.example")
+            .await?
+            .is_some()
+    );
+    assert!(
+        paracord_db::federation::get_remote_user_mapping(&harness.db, "@bob:remote.example")
+            .await?
+            .is_none()
+    );
+    let events = paracord_db::security_events::list_events(
+        &harness.db,
+        Some("federation.peer.rate_limited"),
+        None,
+        10,
+    )
+    .await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].details.as_ref().expect("details")["budget"],
+        "user_creates"
+    );
+
+    std::env::remove_var("PARACORD_FEDERATION_ENABLED");
+    Ok(())
+}
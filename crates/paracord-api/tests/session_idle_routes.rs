@@ -0,0 +1,163 @@
+use axum::http::{Method, StatusCode};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+mod common;
+
+use common::{TestContext, JWT_SECRET};
+
+const IDLE_TIMEOUT_SECONDS: u64 = 3600;
+
+async fn idle_context() -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        state.config.session_idle_timeout_seconds = IDLE_TIMEOUT_SECONDS;
+    })
+    .await
+}
+
+impl TestContext {
+    async fn set_last_seen(
+        &self,
+        session_id: &str,
+        at: chrono::DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query("UPDATE auth_sessions SET last_seen_at = $1 WHERE id = $2")
+            .bind(at.format("%Y-%m-%d %H:%M:%S").to_string())
+            .bind(session_id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+struct TestSession {
+    id: String,
+    access_token: String,
+    refresh_token: String,
+}
+
+fn sha256_hex(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+async fn create_user_session(db: &paracord_db::DbPool) -> anyhow::Result<TestSession> {
+    let user_id = paracord_util::snowflake::generate(1);
+    let nonce = Uuid::new_v4().simple().to_string();
+    let username = format!("integration_{nonce}");
+    let email = format!("{nonce}@example.com");
+    let password_hash = paracord_core::auth::hash_password("IntegrationPass123!")?;
+
+    let user =
+        paracord_db::users::create_user(db, user_id, &username, 1, &email, &password_hash).await?;
+
+    let session_id = format!("sess-{}", Uuid::new_v4().simple());
+    let jti = format!("jti-{}", Uuid::new_v4().simple());
+    let refresh_token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    paracord_db::sessions::create_session(
+        db,
+        &session_id,
+        user.id,
+        &sha256_hex(&refresh_token),
+        &jti,
+        None,
+        None,
+        None,
+        None,
+        Utc::now() + Duration::days(30),
+    )
+    .await?;
+
+    let access_token = paracord_core::auth::create_session_token(
+        user.id,
+        None,
+        JWT_SECRET,
+        3600,
+        &session_id,
+        &jti,
+    )?;
+
+    Ok(TestSession {
+        id: session_id,
+        access_token,
+        refresh_token,
+    })
+}
+
+#[tokio::test]
+async fn idle_session_is_rejected_while_active_session_persists() -> anyhow::Result<()> {
+    let ctx = idle_context().await?;
+    let idle = create_user_session(&ctx.db).await?;
+    let active = create_user_session(&ctx.db).await?;
+
+    let stale = Utc::now() - Duration::seconds(IDLE_TIMEOUT_SECONDS as i64 + 60);
+    ctx.set_last_seen(&idle.id, stale).await?;
+    ctx.set_last_seen(&active.id, Utc::now() - Duration::minutes(30))
+        .await?;
+
+    let (status, _) = ctx
+        .request_with(
+            Some(&idle.access_token),
+            Method::GET,
+            "/api/v1/users/@me",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, payload) = ctx
+        .request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/refresh",
+            Some(json!({ "refresh_token": idle.refresh_token })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{payload}");
+
+    let (status, payload) = ctx
+        .request_with(
+            Some(&active.access_token),
+            Method::GET,
+            "/api/v1/users/@me",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+
+    // Using the session moves its idle deadline forward.
+    let session = paracord_db::sessions::get_session_by_id(&ctx.db, &active.id)
+        .await?
+        .expect("active session");
+    assert!(session.last_seen_at > Utc::now() - Duration::minutes(5));
+    Ok(())
+}
+
+#[tokio::test]
+async fn refresh_keeps_an_active_session_alive() -> anyhow::Result<()> {
+    let ctx = idle_context().await?;
+    let session = create_user_session(&ctx.db).await?;
+    ctx.set_last_seen(&session.id, Utc::now() - Duration::minutes(50))
+        .await?;
+
+    let (status, payload) = ctx
+        .request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/refresh",
+            Some(json!({ "refresh_token": session.refresh_token })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    let access_token = payload["token"].as_str().expect("access token").to_string();
+
+    let (status, payload) = ctx
+        .request_with(Some(&access_token), Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_authenticated_user_token, TestContext};
+
+#[tokio::test]
+async fn data_export_includes_every_section() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (user_id, token) = create_authenticated_user_token(&ctx.db).await?;
+    let (friend_id, _) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::relationships::create_relationship(&ctx.db, user_id, friend_id, 1).await?;
+    paracord_db::relationships::create_relationship(&ctx.db, friend_id, user_id, 1).await?;
+    let (other_id, _) = create_authenticated_user_token(&ctx.db).await?;
+    let group_id = paracord_util::snowflake::generate(1);
+    paracord_db::dms::create_group_dm(
+        &ctx.db,
+        group_id,
+        friend_id,
+        &[user_id, other_id],
+        Some("Trip"),
+    )
+    .await?;
+
+    let (status, body) = ctx
+        .request_json_as(&token, Method::GET, "/api/v1/users/@me/data-export", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["user"]["id"], user_id.to_string());
+    assert_eq!(body["relationships"][0]["target_id"], friend_id.to_string());
+    assert!(body["sessions"].as_array().is_some_and(|s| !s.is_empty()));
+    assert_eq!(body["group_dms"][0]["channel_id"], group_id.to_string());
+    assert_eq!(body["group_dms"][0]["name"], "Trip");
+    assert_eq!(body["group_dms"][0]["owner_id"], friend_id.to_string());
+    assert_eq!(
+        body["group_dms"][0]["recipients"].as_array().map(Vec::len),
+        Some(3)
+    );
+    assert_eq!(body["warnings"], json!([]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn data_export_reports_a_failed_section_and_keeps_the_rest() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let (user_id, token) = create_authenticated_user_token(&ctx.db).await?;
+    let (friend_id, _) = create_authenticated_user_token(&ctx.db).await?;
+    paracord_db::relationships::create_relationship(&ctx.db, user_id, friend_id, 1).await?;
+
+    let guild_id = paracord_util::snowflake::generate(1);
+    paracord_db::guilds::create_guild(&ctx.db, guild_id, "Export Guild", user_id, None).await?;
+    paracord_db::members::add_member(&ctx.db, user_id, guild_id).await?;
+
+    // A corrupt relationship row makes that section unreadable.
+    sqlx::query("UPDATE relationships SET created_at = 'not a timestamp' WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&ctx.db)
+        .await?;
+
+    let (status, body) = ctx
+        .request_json_as(&token, Method::GET, "/api/v1/users/@me/data-export", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["relationships"], Value::Null);
+    assert_eq!(
+        body["warnings"],
+        json!([{
+            "section": "relationships",
+            "message": "This section could not be exported",
+        }])
+    );
+
+    assert_eq!(body["user"]["id"], user_id.to_string());
+    assert_eq!(body["guilds"][0]["id"], guild_id.to_string());
+    assert!(body["sessions"].as_array().is_some_and(|s| !s.is_empty()));
+    assert!(body["messages"].is_array());
+    assert!(body["read_states"].is_array());
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::TestContext;
+
+#[tokio::test]
+async fn profile_fields_allow_prose_but_reject_markup() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            "/api/v1/users/@me",
+            Some(json!({
+                "display_name": "onload fan",
+                "bio": "I write javascript and care about <3 and a < b",
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["display_name"], "onload fan");
+    assert_eq!(
+        payload["bio"],
+        "I write javascript and care about <3 and a < b"
+    );
+
+    for body in [
+        json!({ "display_name": "<script>alert(1)</script>" }),
+        json!({ "bio": "see javascript:alert(1)" }),
+        json!({ "bio": "<img src=x onerror=alert(1)>" }),
+    ] {
+        let (status, payload) = ctx
+            .request_json(Method::PATCH, "/api/v1/users/@me", Some(body))
+            .await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    }
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({ "custom_status": "<iframe src=javascript:x>" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({ "custom_status": "debugging javascript" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["custom_status"], "debugging javascript");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn custom_css_is_sanitized_instead_of_rejected() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({
+                "custom_css": "/* @import url(x.css) */ .a { color: red; background: url(javascript:alert(1)); }"
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["custom_css"], ".a {\n  color: red;\n}");
+
+    Ok(())
+}
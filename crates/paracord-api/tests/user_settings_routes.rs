@@ -0,0 +1,203 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::TestContext;
+
+#[tokio::test]
+async fn settings_locale_must_be_an_allowed_bcp47_tag() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, user) = ctx.register("alice", "alice@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{user}");
+    let token = user["token"].as_str().unwrap();
+
+    for bad in ["english", "en_US", "<script>", "xx-XX"] {
+        let (status, body) = ctx
+            .request_with(
+                Some(token),
+                Method::PATCH,
+                "/api/v1/users/@me/settings",
+                Some(json!({ "locale": bad })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{bad}: {body}");
+    }
+
+    let (status, settings) = ctx
+        .request_with(
+            Some(token),
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({ "locale": "pt-br" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{settings}");
+    assert_eq!(settings["locale"], json!("pt-BR"));
+
+    let (status, settings) = ctx
+        .request_with(Some(token), Method::GET, "/api/v1/users/@me/settings", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(settings["locale"], json!("pt-BR"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn legacy_locales_survive_unrelated_settings_saves() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, user) = ctx.register("alice", "alice@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{user}");
+    let token = user["token"].as_str().unwrap();
+    let user_id: i64 = user["user"]["id"].as_str().unwrap().parse()?;
+
+    let (status, _) = ctx
+        .request_with(
+            Some(token),
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({ "theme": "dark" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    sqlx::query("UPDATE user_settings SET locale = 'en' WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&ctx.db)
+        .await?;
+
+    let (status, settings) = ctx
+        .request_with(
+            Some(token),
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({ "theme": "light", "locale": "en" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{settings}");
+    assert_eq!(settings["theme"], json!("light"));
+    assert_eq!(settings["locale"], json!("en"));
+
+    let (status, settings) = ctx
+        .request_with(
+            Some(token),
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({ "locale": "pt" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{settings}");
+    assert_eq!(settings["locale"], json!("pt-BR"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn capabilities_list_allowed_locales() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+
+    let (status, body) = ctx
+        .request_with(None, Method::GET, "/api/v1/capabilities", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let locales: Vec<&str> = body["locales"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert!(locales.contains(&"en-US"));
+    assert!(locales.contains(&"pt-BR"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn hidden_mutual_guilds_are_only_shown_to_friends() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, alice) = ctx.register("alice", "alice@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{alice}");
+    let (status, bob) = ctx.register("bob", "bob@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{bob}");
+    let (status, carol) = ctx.register("carol", "carol@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{carol}");
+    let alice_token = alice["token"].as_str().unwrap();
+    let alice_id: i64 = alice["user"]["id"].as_str().unwrap().parse()?;
+    let bob_id: i64 = bob["user"]["id"].as_str().unwrap().parse()?;
+    let carol_id: i64 = carol["user"]["id"].as_str().unwrap().parse()?;
+
+    let (status, guild) = ctx
+        .request_with(
+            Some(alice_token),
+            Method::POST,
+            "/api/v1/guilds",
+            Some(json!({ "name": "Shared Guild" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{guild}");
+    let guild_id: i64 = guild["id"].as_str().unwrap().parse()?;
+    paracord_db::members::add_member(&ctx.db, bob_id, guild_id).await?;
+    paracord_db::members::add_member(&ctx.db, carol_id, guild_id).await?;
+    paracord_db::relationships::create_relationship(&ctx.db, alice_id, carol_id, 1).await?;
+    paracord_db::relationships::create_relationship(&ctx.db, carol_id, alice_id, 1).await?;
+
+    let profile_path = format!("/api/v1/users/{alice_id}/profile");
+    let (status, profile) = ctx
+        .request_with(bob["token"].as_str(), Method::GET, &profile_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{profile}");
+    assert_eq!(profile["mutual_guilds"].as_array().unwrap().len(), 1);
+
+    let (status, settings) = ctx
+        .request_with(
+            Some(alice_token),
+            Method::PATCH,
+            "/api/v1/users/@me/settings",
+            Some(json!({ "hide_mutual_guilds": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{settings}");
+    assert_eq!(settings["hide_mutual_guilds"], json!(true));
+
+    let (status, profile) = ctx
+        .request_with(bob["token"].as_str(), Method::GET, &profile_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{profile}");
+    assert_eq!(profile["mutual_guilds"], json!([]));
+    assert_eq!(profile["roles"], json!([]));
+
+    let (status, profile) = ctx
+        .request_with(carol["token"].as_str(), Method::GET, &profile_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{profile}");
+    assert_eq!(profile["mutual_guilds"].as_array().unwrap().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn email_is_never_part_of_another_users_profile() -> anyhow::Result<()> {
+    let ctx = TestContext::with_username_login().await?;
+    let (status, alice) = ctx.register("alice", "alice@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{alice}");
+    let (status, bob) = ctx.register("bob", "bob@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{bob}");
+    let alice_id = alice["user"]["id"].as_str().unwrap();
+
+    let (status, profile) = ctx
+        .request_with(
+            bob["token"].as_str(),
+            Method::GET,
+            &format!("/api/v1/users/{alice_id}/profile"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{profile}");
+    assert!(
+        !profile.to_string().contains("alice@example.com"),
+        "profile leaked email: {profile}"
+    );
+    assert!(profile["user"].get("email").is_none());
+
+    Ok(())
+}
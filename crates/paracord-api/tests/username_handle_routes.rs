@@ -0,0 +1,92 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+mod common;
+
+use common::TestContext;
+
+async fn handle_context(unique_usernames: bool) -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        state.config.allow_username_login = true;
+        state.config.require_email = false;
+        state.config.unique_usernames = unique_usernames;
+    })
+    .await
+}
+
+#[tokio::test]
+async fn handle_mode_rejects_duplicate_username_at_registration() -> anyhow::Result<()> {
+    let ctx = handle_context(true).await?;
+
+    let (status, body) = ctx.register("Alice", "alice@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    assert_eq!(body["user"]["discriminator"], json!(0));
+
+    let (status, _) = ctx.register("alice", "other@example.com").await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = ctx.register("Alice", "third@example.com").await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn handle_mode_lookups_resolve_by_handle_alone() -> anyhow::Result<()> {
+    let ctx = handle_context(true).await?;
+
+    let (status, alice) = ctx.register("alice", "alice@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{alice}");
+    let (status, bob) = ctx.register("bob", "bob@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{bob}");
+
+    let (status, login) = ctx
+        .request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/login",
+            Some(json!({ "identifier": "Alice", "password": "IntegrationPass123!" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+    assert_eq!(login["user"]["id"], alice["user"]["id"]);
+
+    let bob_token = bob["token"].as_str().unwrap();
+    let (status, _) = ctx
+        .request_with(
+            Some(bob_token),
+            Method::POST,
+            "/api/v1/users/@me/relationships",
+            Some(json!({ "username": "ALICE" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, relationships) = ctx
+        .request_with(
+            Some(bob_token),
+            Method::GET,
+            "/api/v1/users/@me/relationships",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let relationships = relationships.as_array().unwrap();
+    assert_eq!(relationships.len(), 1);
+    assert_eq!(relationships[0]["user"]["id"], alice["user"]["id"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn discriminator_mode_allows_duplicate_usernames() -> anyhow::Result<()> {
+    let ctx = handle_context(false).await?;
+
+    let (status, first) = ctx.register("sam", "sam1@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{first}");
+    let (status, second) = ctx.register("sam", "sam2@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{second}");
+    assert_eq!(first["user"]["discriminator"], json!(0));
+    assert_eq!(second["user"]["discriminator"], json!(1));
+
+    Ok(())
+}
@@ -1,195 +1,84 @@
-use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::Context;
 use axum::{
-    body::{to_bytes, Body},
-    http::{header, Method, Request, StatusCode},
+    http::{Method, StatusCode},
     Router,
 };
-use chrono::{Duration, Utc};
-use paracord_core::{build_permission_cache, AppConfig, AppState, RuntimeSettings};
-use paracord_media::{
-    LiveKitConfig, LocalStorage, Storage, StorageConfig, StorageManager, VoiceManager,
-};
+use chrono::Utc;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use paracord_media::voice::USER_LIMIT_EXEMPT_HEADROOM;
 use serde_json::{json, Value};
-use tempfile::TempDir;
-use tokio::sync::{Notify, RwLock};
-use tower::ServiceExt;
-use uuid::Uuid;
-
-struct VoiceTestContext {
-    app: Router,
-    #[allow(dead_code)]
-    db: paracord_db::DbPool,
-    token: String,
-    _storage_dir: TempDir,
-    _media_dir: TempDir,
-    _backup_dir: TempDir,
-}
 
-impl VoiceTestContext {
-    async fn new(native_media_enabled: bool, livekit_available: bool) -> anyhow::Result<Self> {
-        let db = paracord_db::create_pool("sqlite::memory:", 1).await?;
-        paracord_db::run_migrations(&db).await?;
-
-        let storage_dir = tempfile::tempdir()?;
-        let media_dir = tempfile::tempdir()?;
-        let backup_dir = tempfile::tempdir()?;
-        let jwt_secret = "voice-test-secret".to_string();
-
-        let livekit = Arc::new(LiveKitConfig {
-            api_key: "lk-test-key".to_string(),
-            api_secret: "lk-test-secret".to_string(),
-            url: "ws://localhost:7880".to_string(),
-            http_url: "http://localhost:7880".to_string(),
-        });
-
-        let state = AppState {
-            db: db.clone(),
-            event_bus: paracord_core::events::EventBus::default(),
-            config: AppConfig {
-                jwt_secret: jwt_secret.clone(),
-                jwt_expiry_seconds: 3600,
-                registration_enabled: true,
-                allow_username_login: false,
-                require_email: true,
-                storage_path: storage_dir.path().to_string_lossy().into_owned(),
-                max_upload_size: 10 * 1024 * 1024,
-                livekit_api_key: livekit.api_key.clone(),
-                livekit_api_secret: livekit.api_secret.clone(),
-                livekit_url: livekit.url.clone(),
-                livekit_http_url: livekit.http_url.clone(),
-                livekit_public_url: livekit.url.clone(),
-                livekit_available,
-                public_url: None,
-                media_storage_path: media_dir.path().to_string_lossy().into_owned(),
-                media_max_file_size: 10 * 1024 * 1024,
-                media_p2p_threshold: 1024 * 1024,
-                file_cryptor: None,
-                backup_dir: backup_dir.path().to_string_lossy().into_owned(),
-                database_url: "sqlite::memory:".to_string(),
-                federation_max_events_per_peer_per_minute: None,
-                federation_max_user_creates_per_peer_per_hour: None,
-                native_media_enabled,
-                native_media_port: 8443,
-                native_media_max_participants: 50,
-                native_media_e2ee_required: false,
-                max_guild_storage_quota: 0,
-                federation_file_cache_enabled: false,
-                federation_file_cache_max_size: 0,
-                federation_file_cache_ttl_hours: 0,
-            },
-            runtime: Arc::new(RwLock::new(RuntimeSettings::default())),
-            voice: Arc::new(VoiceManager::new(livekit)),
-            storage: Arc::new(StorageManager::new(StorageConfig {
-                base_path: media_dir.path().to_path_buf(),
-                max_file_size: 10 * 1024 * 1024,
-                p2p_threshold: 1024 * 1024,
-                allowed_extensions: None,
-            })),
-            storage_backend: Arc::new(Storage::Local(LocalStorage::new(storage_dir.path()))),
-            shutdown: Arc::new(Notify::new()),
-            online_users: Arc::new(RwLock::new(HashSet::new())),
-            user_presences: Arc::new(RwLock::new(HashMap::new())),
-            permission_cache: build_permission_cache(),
-            federation_service: None,
-            member_index: Arc::new(paracord_core::member_index::MemberIndex::empty()),
-            presence_manager: Arc::new(paracord_core::presence_manager::PresenceManager::new()),
-            native_media: None,
-        };
-
-        paracord_api::install_http_rate_limiter();
-        let app = paracord_api::build_router().with_state(state);
-        let token = create_voice_test_user_token(&db, &jwt_secret).await?;
-
-        Ok(Self {
-            app,
-            db,
-            token,
-            _storage_dir: storage_dir,
-            _media_dir: media_dir,
-            _backup_dir: backup_dir,
-        })
-    }
+mod common;
 
-    async fn request_json(
-        &self,
-        method: Method,
-        path: &str,
-        body: Option<Value>,
-    ) -> anyhow::Result<(StatusCode, Value)> {
-        let mut builder = Request::builder()
-            .method(method)
-            .uri(path)
-            .header(header::AUTHORIZATION, format!("Bearer {}", self.token));
-
-        let request = if let Some(payload) = body {
-            builder = builder.header(header::CONTENT_TYPE, "application/json");
-            builder.body(Body::from(payload.to_string()))?
-        } else {
-            builder.body(Body::empty())?
-        };
-
-        let response = self.app.clone().oneshot(request).await?;
-        let status = response.status();
-        let body_bytes = to_bytes(response.into_body(), usize::MAX).await?;
-        let payload = if body_bytes.is_empty() {
-            Value::Null
-        } else {
-            serde_json::from_slice(&body_bytes)
-                .unwrap_or_else(|_| json!({ "raw": String::from_utf8_lossy(&body_bytes) }))
-        };
-
-        Ok((status, payload))
-    }
-}
+use common::{create_authenticated_user_token, use_livekit_http_url, TestContext};
 
-async fn create_voice_test_user_token(
-    db: &paracord_db::DbPool,
-    jwt_secret: &str,
-) -> anyhow::Result<String> {
-    let user_id = paracord_util::snowflake::generate(1);
-    let nonce = Uuid::new_v4().simple().to_string();
-    let username = format!("voicetest_{nonce}");
-    let email = format!("{nonce}@example.com");
-    let password_hash = paracord_core::auth::hash_password("VoiceTestPass123!")?;
-
-    let user =
-        paracord_db::users::create_user(db, user_id, &username, 1, &email, &password_hash).await?;
-
-    let session_id = format!("sess-{}", Uuid::new_v4().simple());
-    let jti = format!("jti-{}", Uuid::new_v4().simple());
-    let refresh_hash = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
-    paracord_db::sessions::create_session(
-        db,
-        &session_id,
-        user.id,
-        &refresh_hash,
-        &jti,
-        user.public_key.as_deref(),
-        None,
-        None,
-        None,
-        Utc::now() + Duration::days(1),
+async fn voice_context(
+    native_media_enabled: bool,
+    livekit_available: bool,
+) -> anyhow::Result<TestContext> {
+    voice_context_with_livekit(
+        native_media_enabled,
+        livekit_available,
+        "http://localhost:7880",
     )
-    .await?;
+    .await
+}
 
-    let token = paracord_core::auth::create_session_token(
-        user.id,
-        user.public_key.as_deref(),
-        jwt_secret,
-        3600,
-        &session_id,
-        &jti,
-    )?;
+async fn voice_context_with_livekit(
+    native_media_enabled: bool,
+    livekit_available: bool,
+    livekit_http_url: &str,
+) -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        state.config.native_media_enabled = native_media_enabled;
+        state.config.livekit_available = livekit_available;
+        use_livekit_http_url(state, livekit_http_url);
+        state.livekit_online = Arc::new(AtomicBool::new(livekit_available));
+    })
+    .await
+}
 
-    Ok(token)
+type RecordedLiveKitCalls = Arc<std::sync::Mutex<Vec<(String, Value)>>>;
+
+/// Start a stand-in for the LiveKit RoomService and Egress APIs that accepts
+/// every call and records `(method, body)` pairs.
+async fn spawn_mock_livekit() -> anyhow::Result<(String, RecordedLiveKitCalls)> {
+    let calls: RecordedLiveKitCalls = Arc::default();
+    let recorder = calls.clone();
+    let handler = move |axum::extract::Path(method): axum::extract::Path<String>,
+                        axum::Json(body): axum::Json<Value>| {
+        let recorder = recorder.clone();
+        async move {
+            let response = if method == "StartRoomCompositeEgress" {
+                json!({ "egress_id": "EG_mock", "room_name": body["room_name"] })
+            } else {
+                json!({})
+            };
+            recorder.lock().unwrap().push((method, body));
+            axum::Json(response)
+        }
+    };
+    let app = Router::new()
+        .route(
+            "/twirp/livekit.RoomService/{method}",
+            axum::routing::post(handler.clone()),
+        )
+        .route(
+            "/twirp/livekit.Egress/{method}",
+            axum::routing::post(handler),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    Ok((format!("http://{addr}"), calls))
 }
 
-async fn create_guild_and_voice_channel(
-    ctx: &VoiceTestContext,
-) -> anyhow::Result<(String, String)> {
+async fn create_guild_and_voice_channel(ctx: &TestContext) -> anyhow::Result<(String, String)> {
     // Create a guild
     let (status, payload) = ctx
         .request_json(
@@ -230,7 +119,7 @@ async fn create_guild_and_voice_channel(
 
 #[tokio::test]
 async fn native_plus_livekit_returns_native_only_with_livekit_available() -> anyhow::Result<()> {
-    let ctx = VoiceTestContext::new(true, true).await?;
+    let ctx = voice_context(true, true).await?;
     let (_guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
 
     // Join via v1 GET (no ?fallback)
@@ -261,11 +150,626 @@ async fn native_plus_livekit_returns_native_only_with_livekit_available() -> any
     Ok(())
 }
 
+// ── Priority speaker is granted from the PRIORITY_SPEAKER permission ──
+
+#[tokio::test]
+async fn priority_speaker_flag_follows_channel_permission() -> anyhow::Result<()> {
+    let ctx = voice_context(true, false).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let guild_id: i64 = guild_id.parse()?;
+    let join_path = format!("/api/v1/voice/{channel_id}/join");
+
+    // The guild owner holds every permission, including PRIORITY_SPEAKER.
+    let (status, payload) = ctx.request_json(Method::GET, &join_path, None).await?;
+    assert_eq!(status, StatusCode::OK, "owner join: {payload}");
+    assert_eq!(payload["priority_speaker"], json!(true));
+
+    // A regular member only has the @everyone defaults.
+    let (_, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (_, member) = ctx
+        .request_json_as(&member_token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    let member_id: i64 = member["id"].as_str().context("member id")?.parse()?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id).await?;
+    let (status, payload) = ctx
+        .request_json_as(&member_token, Method::GET, &join_path, None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "member join: {payload}");
+    assert_eq!(payload["priority_speaker"], json!(false));
+
+    let states =
+        paracord_db::voice_states::get_channel_voice_states(&ctx.db, channel_id.parse()?).await?;
+    assert_eq!(states.len(), 2);
+    for voice_state in states {
+        assert_eq!(
+            voice_state.priority_speaker,
+            voice_state.user_id != member_id,
+            "only the owner should be stored as priority speaker"
+        );
+    }
+
+    Ok(())
+}
+
+/// Add a fresh user to the guild and join them to `channel_id`, returning
+/// their id and token.
+async fn join_new_member_to_voice(
+    ctx: &TestContext,
+    guild_id: &str,
+    channel_id: &str,
+) -> anyhow::Result<(String, String)> {
+    let (_, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (_, member) = ctx
+        .request_json_as(&member_token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    let member_id = member["id"].as_str().context("member id")?.to_string();
+    paracord_db::members::add_member(&ctx.db, member_id.parse()?, guild_id.parse()?).await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &member_token,
+            Method::GET,
+            &format!("/api/v1/voice/{channel_id}/join"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "member join: {payload}");
+    Ok((member_id, member_token))
+}
+
+async fn create_second_voice_channel(ctx: &TestContext, guild_id: &str) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/channels"),
+            Some(json!({ "name": "voice-move-target", "channel_type": 2 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "channel creation: {payload}");
+    Ok(payload["id"].as_str().context("channel id")?.to_string())
+}
+
+// ── Server mute on a member reaches their live LiveKit room ──
+
+#[tokio::test]
+async fn server_mute_updates_livekit_participant_in_active_room() -> anyhow::Result<()> {
+    let (livekit_url, calls) = spawn_mock_livekit().await?;
+    let ctx = voice_context_with_livekit(false, true, &livekit_url).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+
+    let (member_id, _) = join_new_member_to_voice(&ctx, &guild_id, &channel_id).await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/members/{member_id}"),
+            Some(json!({ "mute": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "server mute: {payload}");
+    assert_eq!(payload["mute"], json!(true));
+
+    let calls = calls.lock().unwrap().clone();
+    let (_, update) = calls
+        .iter()
+        .find(|(method, _)| method == "UpdateParticipant")
+        .context("server mute should call UpdateParticipant")?;
+    assert_eq!(
+        update["room"],
+        json!(format!("guild_{guild_id}_channel_{channel_id}"))
+    );
+    assert_eq!(update["identity"], json!(member_id));
+    assert_eq!(update["permission"]["canPublish"], json!(false));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_mute_and_deafen_survive_leave_and_rejoin() -> anyhow::Result<()> {
+    let (livekit_url, _calls) = spawn_mock_livekit().await?;
+    let ctx = voice_context_with_livekit(false, true, &livekit_url).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let (member_id, member_token) = join_new_member_to_voice(&ctx, &guild_id, &channel_id).await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/members/{member_id}"),
+            Some(json!({ "mute": true, "deaf": true })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "server mute: {payload}");
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &format!("/api/v1/voice/{channel_id}/leave"),
+            None,
+        )
+        .await?;
+    assert!(status.is_success(), "leave: {payload}");
+
+    let mut events = ctx.state.event_bus.register_session(
+        "rejoin-observer".to_string(),
+        member_id.parse()?,
+        &[guild_id.parse()?],
+    );
+    let (status, payload) = ctx
+        .request_json_as(
+            &member_token,
+            Method::GET,
+            &format!("/api/v1/voice/{channel_id}/join"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "rejoin: {payload}");
+
+    let token = payload["token"].as_str().context("livekit token")?;
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&["lk-test-key"]);
+    let claims = decode::<Value>(
+        token,
+        &DecodingKey::from_secret(b"lk-test-secret"),
+        &validation,
+    )?
+    .claims;
+    assert_eq!(claims["video"]["canPublish"], json!(false));
+
+    let mut voice_state = None;
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "VOICE_STATE_UPDATE" && event.payload["user_id"] == json!(member_id)
+        {
+            voice_state = Some(event.payload.clone());
+        }
+    }
+    let voice_state = voice_state.context("rejoin should dispatch VOICE_STATE_UPDATE")?;
+    assert_eq!(voice_state["mute"], json!(true));
+    assert_eq!(voice_state["deaf"], json!(true));
+
+    Ok(())
+}
+
+// ── Moving a member between voice channels ──
+
+#[tokio::test]
+async fn move_member_requires_move_members_permission() -> anyhow::Result<()> {
+    let (livekit_url, _calls) = spawn_mock_livekit().await?;
+    let ctx = voice_context_with_livekit(false, true, &livekit_url).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let target_channel_id = create_second_voice_channel(&ctx, &guild_id).await?;
+    let (member_id, _) = join_new_member_to_voice(&ctx, &guild_id, &channel_id).await?;
+    let (_, bystander_token) = join_new_member_to_voice(&ctx, &guild_id, &channel_id).await?;
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &bystander_token,
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/members/{member_id}"),
+            Some(json!({ "channel_id": target_channel_id })),
+        )
+        .await?;
+    assert_eq!(
+        status,
+        StatusCode::FORBIDDEN,
+        "move without perm: {payload}"
+    );
+
+    let voice_state = paracord_db::voice_states::get_user_voice_state(
+        &ctx.db,
+        member_id.parse()?,
+        Some(guild_id.parse()?),
+    )
+    .await?
+    .context("member should still be in voice")?;
+    assert_eq!(voice_state.channel_id.to_string(), channel_id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn move_member_moves_voice_state_and_issues_new_token() -> anyhow::Result<()> {
+    let (livekit_url, calls) = spawn_mock_livekit().await?;
+    let ctx = voice_context_with_livekit(false, true, &livekit_url).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let target_channel_id = create_second_voice_channel(&ctx, &guild_id).await?;
+    let (member_id, _) = join_new_member_to_voice(&ctx, &guild_id, &channel_id).await?;
+    let before = paracord_db::voice_states::get_user_voice_state(
+        &ctx.db,
+        member_id.parse()?,
+        Some(guild_id.parse()?),
+    )
+    .await?
+    .context("member should be in voice")?;
+
+    let mut events = ctx.state.event_bus.register_session(
+        "move-observer".to_string(),
+        member_id.parse()?,
+        &[guild_id.parse()?],
+    );
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/guilds/{guild_id}/members/{member_id}"),
+            Some(json!({ "channel_id": target_channel_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "move member: {payload}");
+
+    let after = paracord_db::voice_states::get_user_voice_state(
+        &ctx.db,
+        member_id.parse()?,
+        Some(guild_id.parse()?),
+    )
+    .await?
+    .context("member should still be in voice")?;
+    assert_eq!(after.channel_id.to_string(), target_channel_id);
+    assert_ne!(after.session_id, before.session_id);
+
+    let target_room = format!("guild_{guild_id}_channel_{target_channel_id}");
+    assert!(
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(method, body)| method == "CreateRoom" && body["name"] == json!(target_room)),
+        "target LiveKit room should be created"
+    );
+    let source_room = format!("guild_{guild_id}_channel_{channel_id}");
+    assert!(
+        calls.lock().unwrap().iter().any(|(method, body)| {
+            method == "RemoveParticipant"
+                && body["room"] == json!(source_room)
+                && body["identity"] == json!(member_id)
+        }),
+        "member should be disconnected from the source LiveKit room"
+    );
+
+    let mut server_update = None;
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "VOICE_SERVER_UPDATE" {
+            server_update = Some(event.payload.clone());
+        }
+    }
+    let server_update = server_update.context("member should receive VOICE_SERVER_UPDATE")?;
+    assert_eq!(server_update["channel_id"], json!(target_channel_id));
+    assert_eq!(server_update["room_name"], json!(target_room));
+    assert_eq!(server_update["session_id"], json!(after.session_id));
+    assert!(server_update["token"]
+        .as_str()
+        .is_some_and(|t| !t.is_empty()));
+
+    Ok(())
+}
+
+// ── Voice channel user limit ──
+
+#[tokio::test]
+async fn full_voice_channel_rejects_members_but_not_moderators() -> anyhow::Result<()> {
+    let (livekit_url, calls) = spawn_mock_livekit().await?;
+    let ctx = voice_context_with_livekit(false, true, &livekit_url).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}"),
+            Some(json!({ "user_limit": 1 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "set user limit: {payload}");
+    assert_eq!(payload["user_limit"], json!(1));
+
+    join_new_member_to_voice(&ctx, &guild_id, &channel_id).await?;
+    let room = format!("guild_{guild_id}_channel_{channel_id}");
+    assert!(
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(method, body)| method == "CreateRoom"
+                && body["name"] == json!(room)
+                && body["max_participants"] == json!(1 + USER_LIMIT_EXEMPT_HEADROOM)),
+        "LiveKit room should be capped at the user limit plus exempt headroom"
+    );
+
+    let (_, late_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (_, late) = ctx
+        .request_json_as(&late_token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    let late_id: i64 = late["id"].as_str().context("member id")?.parse()?;
+    paracord_db::members::add_member(&ctx.db, late_id, guild_id.parse()?).await?;
+    let (status, payload) = ctx
+        .request_json_as(
+            &late_token,
+            Method::GET,
+            &format!("/api/v1/voice/{channel_id}/join"),
+            None,
+        )
+        .await?;
+    assert_eq!(
+        status,
+        StatusCode::FORBIDDEN,
+        "join full channel: {payload}"
+    );
+
+    // The guild owner holds MOVE_MEMBERS and may exceed the limit.
+    let (status, payload) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/voice/{channel_id}/join"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "owner join full channel: {payload}");
+
+    let states =
+        paracord_db::voice_states::get_channel_voice_states(&ctx.db, channel_id.parse()?).await?;
+    assert_eq!(states.len(), 2);
+
+    // Raising the limit while the room is open updates the live LiveKit cap.
+    let (status, payload) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}"),
+            Some(json!({ "user_limit": 5 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "raise user limit: {payload}");
+    assert!(
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(method, body)| method == "CreateRoom"
+                && body["name"] == json!(room)
+                && body["max_participants"] == json!(5 + USER_LIMIT_EXEMPT_HEADROOM)),
+        "open LiveKit room should pick up the edited user limit"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn user_limit_is_validated_and_voice_only() -> anyhow::Result<()> {
+    let ctx = voice_context(true, false).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+
+    let (status, _) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!("/api/v1/channels/{channel_id}"),
+            Some(json!({ "user_limit": 100 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, text_channel) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/channels"),
+            Some(json!({ "name": "text", "channel_type": 0 })),
+        )
+        .await?;
+    assert_eq!(
+        status,
+        StatusCode::CREATED,
+        "channel creation: {text_channel}"
+    );
+    let (status, _) = ctx
+        .request_json(
+            Method::PATCH,
+            &format!(
+                "/api/v1/channels/{}",
+                text_channel["id"].as_str().context("channel id")?
+            ),
+            Some(json!({ "user_limit": 5 })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+// ── Recording state is broadcast to guild members ──
+
+fn recording_updates(
+    events: &mut tokio::sync::broadcast::Receiver<paracord_core::events::ServerEvent>,
+) -> Vec<Value> {
+    let mut updates = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if event.event_type == "VOICE_RECORDING_UPDATE" {
+            updates.push((*event.payload).clone());
+        }
+    }
+    updates
+}
+
+#[tokio::test]
+async fn start_and_stop_recording_dispatch_to_guild_members() -> anyhow::Result<()> {
+    let (livekit_url, calls) = spawn_mock_livekit().await?;
+    let ctx = TestContext::with_state(|state| {
+        state.config.livekit_available = true;
+        state.config.livekit_recording_enabled = true;
+        state.config.livekit_recording_filepath = "calls/{room_name}".to_string();
+        use_livekit_http_url(state, &livekit_url);
+        state.livekit_online = Arc::new(AtomicBool::new(true));
+    })
+    .await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let (_, owner) = ctx
+        .request_json(Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    let owner_id = owner["id"].as_str().context("owner id")?.to_string();
+
+    let (_, member_token) = create_authenticated_user_token(&ctx.db).await?;
+    let (_, member) = ctx
+        .request_json_as(&member_token, Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    let member_id: i64 = member["id"].as_str().context("member id")?.parse()?;
+    paracord_db::members::add_member(&ctx.db, member_id, guild_id.parse()?).await?;
+    let mut events = ctx.state.event_bus.register_session(
+        "recording-observer".to_string(),
+        member_id,
+        &[guild_id.parse()?],
+    );
+
+    let (status, payload) = ctx
+        .request_json_as(
+            &member_token,
+            Method::POST,
+            &format!("/api/v1/voice/{channel_id}/recording"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "member start: {payload}");
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/voice/{channel_id}/recording"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "start recording: {payload}");
+    assert_eq!(payload["recording"], json!(true));
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/voice/{channel_id}/recording"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CONFLICT, "second start: {payload}");
+
+    let updates = recording_updates(&mut events);
+    assert_eq!(updates.len(), 1, "{updates:?}");
+    assert_eq!(updates[0]["channel_id"], json!(channel_id));
+    assert_eq!(updates[0]["recording"], json!(true));
+    assert_eq!(updates[0]["started_by"], json!(owner_id));
+    assert!(updates[0]["started_at"].is_string());
+
+    let (status, joined) = ctx
+        .request_json_as(
+            &member_token,
+            Method::GET,
+            &format!("/api/v1/voice/{channel_id}/join"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "member join: {joined}");
+    assert_eq!(joined["recording"]["started_by"], json!(owner_id));
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/voice/{channel_id}/recording/stop"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT, "stop recording: {payload}");
+
+    let updates = recording_updates(&mut events);
+    assert_eq!(updates.len(), 1, "{updates:?}");
+    assert_eq!(updates[0]["recording"], json!(false));
+    assert_eq!(updates[0]["started_by"], Value::Null);
+
+    let calls = calls.lock().unwrap().clone();
+    let (_, start) = calls
+        .iter()
+        .find(|(method, _)| method == "StartRoomCompositeEgress")
+        .context("start should call StartRoomCompositeEgress")?;
+    assert_eq!(
+        start["room_name"],
+        json!(format!("guild_{guild_id}_channel_{channel_id}"))
+    );
+    assert_eq!(
+        start["file_outputs"][0]["filepath"],
+        json!("calls/{room_name}")
+    );
+    let (_, stop) = calls
+        .iter()
+        .find(|(method, _)| method == "StopEgress")
+        .context("stop should call StopEgress")?;
+    assert_eq!(stop["egress_id"], json!("EG_mock"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn recording_is_off_unless_the_operator_enables_it() -> anyhow::Result<()> {
+    let (livekit_url, calls) = spawn_mock_livekit().await?;
+    let ctx = voice_context_with_livekit(false, true, &livekit_url).await?;
+    let (_, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/voice/{channel_id}/recording"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{payload}");
+    assert!(calls.lock().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn egress_webhooks_dispatch_recording_updates() -> anyhow::Result<()> {
+    let ctx = voice_context(false, true).await?;
+    let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let (_, owner) = ctx
+        .request_json(Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    let owner_id: i64 = owner["id"].as_str().context("owner id")?.parse()?;
+    let mut events = ctx.state.event_bus.register_session(
+        "egress-observer".to_string(),
+        owner_id,
+        &[guild_id.parse()?],
+    );
+
+    let webhook_token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+        &json!({
+            "iss": "lk-test-key",
+            "exp": Utc::now().timestamp() + 300,
+        }),
+        &jsonwebtoken::EncodingKey::from_secret(b"lk-test-secret"),
+    )?;
+    let room_name = format!("guild_{guild_id}_channel_{channel_id}");
+    for event in ["egress_started", "egress_started", "egress_ended"] {
+        let (status, payload) = ctx
+            .request_json_as(
+                &webhook_token,
+                Method::POST,
+                "/api/v1/voice/livekit/webhook",
+                Some(json!({
+                    "event": event,
+                    "egressInfo": { "egressId": "EG_external", "roomName": room_name },
+                })),
+            )
+            .await?;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{event}: {payload}");
+    }
+
+    let updates = recording_updates(&mut events);
+    assert_eq!(updates.len(), 2, "duplicate start is ignored: {updates:?}");
+    assert_eq!(updates[0]["recording"], json!(true));
+    assert_eq!(updates[0]["channel_id"], json!(channel_id));
+    assert_eq!(updates[0]["started_by"], Value::Null);
+    assert_eq!(updates[1]["recording"], json!(false));
+
+    Ok(())
+}
+
 // ── Test D2: ?fallback=livekit routes to LiveKit path ──
 
 #[tokio::test]
 async fn fallback_livekit_query_routes_to_livekit_path() -> anyhow::Result<()> {
-    let ctx = VoiceTestContext::new(true, true).await?;
+    let ctx = voice_context(true, true).await?;
     let (_guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
 
     // Join with ?fallback=livekit — this will attempt the LiveKit path.
@@ -305,7 +809,7 @@ async fn fallback_livekit_query_routes_to_livekit_path() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn native_disabled_livekit_available_returns_livekit_response() -> anyhow::Result<()> {
-    let ctx = VoiceTestContext::new(false, true).await?;
+    let ctx = voice_context(false, true).await?;
     let (_guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
 
     // Without native media, the default join should go to LiveKit path.
@@ -343,7 +847,7 @@ async fn native_disabled_livekit_available_returns_livekit_response() -> anyhow:
 
 #[tokio::test]
 async fn native_only_no_livekit_returns_native_without_fallback() -> anyhow::Result<()> {
-    let ctx = VoiceTestContext::new(true, false).await?;
+    let ctx = voice_context(true, false).await?;
     let (_guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
 
     let (status, payload) = ctx
@@ -369,7 +873,7 @@ async fn native_only_no_livekit_returns_native_without_fallback() -> anyhow::Res
 
 #[tokio::test]
 async fn v2_join_accepts_fallback_query_param() -> anyhow::Result<()> {
-    let ctx = VoiceTestContext::new(true, true).await?;
+    let ctx = voice_context(true, true).await?;
     let (_guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
 
     // v2 POST without fallback → native media
@@ -413,7 +917,7 @@ async fn v2_join_accepts_fallback_query_param() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn v2_leave_ignores_stale_session_id() -> anyhow::Result<()> {
-    let ctx = VoiceTestContext::new(true, true).await?;
+    let ctx = voice_context(true, true).await?;
     let (guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
 
     let (status, join_one) = ctx
@@ -498,3 +1002,53 @@ async fn v2_leave_ignores_stale_session_id() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+// ── LiveKit availability flag flips at runtime ──
+
+#[tokio::test]
+async fn livekit_going_down_returns_503_on_join() -> anyhow::Result<()> {
+    let ctx = voice_context(false, true).await?;
+    let (_guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let join_path = format!("/api/v1/voice/{channel_id}/join");
+
+    let (status, _payload) = ctx.request_json(Method::GET, &join_path, None).await?;
+    assert_ne!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    ctx.state.livekit_online.store(false, Ordering::Relaxed);
+    let (status, payload) = ctx.request_json(Method::GET, &join_path, None).await?;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{payload}");
+    let message = payload["message"].as_str().unwrap_or_default();
+    assert!(
+        message.contains("temporarily unavailable"),
+        "expected user-facing outage message: {payload}"
+    );
+
+    ctx.state.livekit_online.store(true, Ordering::Relaxed);
+    let (status, _payload) = ctx.request_json(Method::GET, &join_path, None).await?;
+    assert_ne!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn livekit_going_down_reports_unavailable_in_native_join() -> anyhow::Result<()> {
+    let ctx = voice_context(true, true).await?;
+    let (_guild_id, channel_id) = create_guild_and_voice_channel(&ctx).await?;
+    let join_path = format!("/api/v1/voice/{channel_id}/join");
+
+    ctx.state.livekit_online.store(false, Ordering::Relaxed);
+    let (status, payload) = ctx.request_json(Method::GET, &join_path, None).await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    assert_eq!(payload["livekit_available"], json!(false));
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v2/voice/{channel_id}/join?fallback=livekit"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{payload}");
+
+    Ok(())
+}
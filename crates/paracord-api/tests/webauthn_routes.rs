@@ -0,0 +1,375 @@
+use axum::http::{Method, StatusCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ciborium::Value as CborValue;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+mod common;
+
+use common::TestContext;
+
+const ORIGIN: &str = "https://chat.example.com";
+const RP_ID: &str = "chat.example.com";
+
+async fn passkey_context() -> anyhow::Result<TestContext> {
+    TestContext::with_state(|state| {
+        state.config.allow_username_login = true;
+        state.config.require_email = false;
+        state.config.public_url = Some(ORIGIN.to_string());
+    })
+    .await
+}
+
+fn client_data(ceremony: &str, challenge: &str) -> String {
+    URL_SAFE_NO_PAD.encode(
+        json!({
+            "type": ceremony,
+            "challenge": challenge,
+            "origin": ORIGIN,
+            "crossOrigin": false,
+        })
+        .to_string(),
+    )
+}
+
+/// User present and user verified.
+const FLAGS_UP_UV: u8 = 0x05;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+fn authenticator_data(flags: u8, sign_count: u32) -> Vec<u8> {
+    let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+    data.push(flags);
+    data.extend_from_slice(&sign_count.to_be_bytes());
+    data
+}
+
+/// The WebAuthn user handle the server assigns an account.
+fn user_handle(user_id: &str) -> String {
+    let mut handle = [0u8; 16];
+    handle[8..].copy_from_slice(&user_id.parse::<u64>().unwrap().to_be_bytes());
+    URL_SAFE_NO_PAD.encode(handle)
+}
+
+/// `navigator.credentials.create()` result (as `toJSON()`) for a "none"
+/// attestation.
+fn attestation_response(key: &SigningKey, credential_id: &[u8], challenge: &str) -> Value {
+    let point = key.verifying_key().to_encoded_point(false);
+    let cose_key = CborValue::Map(vec![
+        (CborValue::from(1), CborValue::from(2)),
+        (CborValue::from(3), CborValue::from(-7)),
+        (CborValue::from(-1), CborValue::from(1)),
+        (
+            CborValue::from(-2),
+            CborValue::Bytes(point.x().unwrap().to_vec()),
+        ),
+        (
+            CborValue::from(-3),
+            CborValue::Bytes(point.y().unwrap().to_vec()),
+        ),
+    ]);
+    let mut auth_data = authenticator_data(FLAGS_UP_UV | FLAG_ATTESTED_CREDENTIAL_DATA, 0);
+    auth_data.extend_from_slice(&[0u8; 16]);
+    auth_data.extend_from_slice(&(credential_id.len() as u16).to_be_bytes());
+    auth_data.extend_from_slice(credential_id);
+    ciborium::into_writer(&cose_key, &mut auth_data).unwrap();
+    let attestation = CborValue::Map(vec![
+        (CborValue::from("fmt"), CborValue::from("none")),
+        (CborValue::from("attStmt"), CborValue::Map(Vec::new())),
+        (CborValue::from("authData"), CborValue::Bytes(auth_data)),
+    ]);
+    let mut attestation_object = Vec::new();
+    ciborium::into_writer(&attestation, &mut attestation_object).unwrap();
+    let id = URL_SAFE_NO_PAD.encode(credential_id);
+    json!({
+        "id": id,
+        "rawId": id,
+        "type": "public-key",
+        "response": {
+            "clientDataJSON": client_data("webauthn.create", challenge),
+            "attestationObject": URL_SAFE_NO_PAD.encode(attestation_object),
+            "transports": ["internal"],
+        },
+        "clientExtensionResults": {},
+    })
+}
+
+/// `navigator.credentials.get()` result (as `toJSON()`) signed by `key`.
+fn assertion_response(
+    key: &SigningKey,
+    credential_id: &[u8],
+    challenge: &str,
+    flags: u8,
+    sign_count: u32,
+    user_handle: Option<&str>,
+) -> Value {
+    let client_data_json = client_data("webauthn.get", challenge);
+    let auth_data = authenticator_data(flags, sign_count);
+    let mut signed = auth_data.clone();
+    signed.extend_from_slice(&Sha256::digest(
+        URL_SAFE_NO_PAD.decode(&client_data_json).unwrap(),
+    ));
+    let signature: Signature = key.sign(&signed);
+    let id = URL_SAFE_NO_PAD.encode(credential_id);
+    json!({
+        "id": id,
+        "rawId": id,
+        "type": "public-key",
+        "response": {
+            "clientDataJSON": client_data_json,
+            "authenticatorData": URL_SAFE_NO_PAD.encode(auth_data),
+            "signature": URL_SAFE_NO_PAD.encode(signature.to_der().as_bytes()),
+            "userHandle": user_handle,
+        },
+        "clientExtensionResults": {},
+    })
+}
+
+async fn register_passkey(
+    ctx: &TestContext,
+    token: &str,
+    key: &SigningKey,
+    credential_id: &[u8],
+) -> anyhow::Result<()> {
+    let (status, begin) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/auth/webauthn/register/begin",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{begin}");
+    assert_eq!(begin["public_key"]["rp"]["id"], RP_ID);
+    assert_eq!(
+        begin["public_key"]["authenticatorSelection"]["userVerification"],
+        "required"
+    );
+    let challenge = begin["public_key"]["challenge"].as_str().unwrap();
+
+    let finish = json!({
+        "ceremony_id": begin["ceremony_id"],
+        "credential": attestation_response(key, credential_id, challenge),
+        "name": "Laptop",
+    });
+    let (status, credential) = ctx
+        .request_with(
+            Some(token),
+            Method::POST,
+            "/api/v1/auth/webauthn/register/finish",
+            Some(finish),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{credential}");
+    assert_eq!(credential["id"], URL_SAFE_NO_PAD.encode(credential_id));
+    assert_eq!(credential["name"], "Laptop");
+    assert_eq!(credential["transports"], json!(["internal"]));
+    Ok(())
+}
+
+struct Assertion<'a> {
+    identifier: Option<&'a str>,
+    flags: u8,
+    sign_count: u32,
+    user_handle: Option<&'a str>,
+}
+
+impl Assertion<'_> {
+    fn verified(sign_count: u32) -> Self {
+        Self {
+            identifier: None,
+            flags: FLAGS_UP_UV,
+            sign_count,
+            user_handle: None,
+        }
+    }
+}
+
+async fn passkey_login(
+    ctx: &TestContext,
+    key: &SigningKey,
+    credential_id: &[u8],
+    assertion: Assertion<'_>,
+) -> anyhow::Result<(StatusCode, Value)> {
+    let (status, begin) = ctx
+        .request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/webauthn/login/begin",
+            Some(json!({ "identifier": assertion.identifier })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{begin}");
+    let challenge = begin["public_key"]["challenge"].as_str().unwrap();
+
+    let finish = json!({
+        "ceremony_id": begin["ceremony_id"],
+        "credential": assertion_response(
+            key,
+            credential_id,
+            challenge,
+            assertion.flags,
+            assertion.sign_count,
+            assertion.user_handle,
+        ),
+    });
+    ctx.request_with(
+        None,
+        Method::POST,
+        "/api/v1/auth/webauthn/login/finish",
+        Some(finish),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn passkey_registration_and_login_issue_a_session() -> anyhow::Result<()> {
+    let ctx = passkey_context().await?;
+    let (status, registered) = ctx.register("passkeyuser", "passkey@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap().to_string();
+    let user_id = registered["user"]["id"].as_str().unwrap().to_string();
+    let handle = user_handle(&user_id);
+
+    let (status, _) = ctx
+        .request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/webauthn/register/begin",
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let key = SigningKey::from_slice(&[11u8; 32])?;
+    register_passkey(&ctx, &token, &key, b"laptop-passkey").await?;
+
+    let (status, begin) = ctx
+        .request_with(
+            None,
+            Method::POST,
+            "/api/v1/auth/webauthn/login/begin",
+            Some(json!({ "identifier": "passkey@example.com" })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{begin}");
+    assert_eq!(
+        begin["public_key"]["allowCredentials"][0]["id"],
+        URL_SAFE_NO_PAD.encode(b"laptop-passkey")
+    );
+
+    // A discoverable-credential login names no account up front; the
+    // authenticator's user handle identifies it.
+    let (status, login) = passkey_login(
+        &ctx,
+        &key,
+        b"laptop-passkey",
+        Assertion {
+            user_handle: Some(&handle),
+            ..Assertion::verified(1)
+        },
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+    assert_eq!(login["user"]["id"], user_id.as_str());
+    assert!(login["refresh_token"].is_string());
+    let session_token = login["token"].as_str().unwrap();
+    let (status, me) = ctx
+        .request_with(Some(session_token), Method::GET, "/api/v1/users/@me", None)
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{me}");
+
+    // A discoverable login without a user handle cannot name the account.
+    let (status, _) = passkey_login(&ctx, &key, b"laptop-passkey", Assertion::verified(2)).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A signature from a different key is rejected.
+    let other_key = SigningKey::from_slice(&[12u8; 32])?;
+    let (status, _) = passkey_login(
+        &ctx,
+        &other_key,
+        b"laptop-passkey",
+        Assertion {
+            identifier: Some("passkey@example.com"),
+            ..Assertion::verified(3)
+        },
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn passkey_login_rejects_a_replayed_sign_count() -> anyhow::Result<()> {
+    let ctx = passkey_context().await?;
+    let (status, registered) = ctx.register("cloneduser", "cloned@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap().to_string();
+
+    let key = SigningKey::from_slice(&[21u8; 32])?;
+    register_passkey(&ctx, &token, &key, b"security-key").await?;
+    let login_at = |sign_count| Assertion {
+        identifier: Some("cloned@example.com"),
+        ..Assertion::verified(sign_count)
+    };
+
+    let (status, login) = passkey_login(&ctx, &key, b"security-key", login_at(5)).await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+
+    for stale in [5, 3] {
+        let (status, body) = passkey_login(&ctx, &key, b"security-key", login_at(stale)).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+    }
+
+    let (status, login) = passkey_login(&ctx, &key, b"security-key", login_at(6)).await?;
+    assert_eq!(status, StatusCode::OK, "{login}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn passkey_login_requires_user_verification_and_a_verified_email() -> anyhow::Result<()> {
+    let ctx = passkey_context().await?;
+    let (status, registered) = ctx.register("uvuser", "uv@example.com").await?;
+    assert_eq!(status, StatusCode::CREATED, "{registered}");
+    let token = registered["token"].as_str().unwrap().to_string();
+
+    let key = SigningKey::from_slice(&[31u8; 32])?;
+    register_passkey(&ctx, &token, &key, b"roaming-key").await?;
+
+    // User presence alone (no PIN or biometric) is not accepted.
+    let (status, body) = passkey_login(
+        &ctx,
+        &key,
+        b"roaming-key",
+        Assertion {
+            identifier: Some("uv@example.com"),
+            flags: 0x01,
+            ..Assertion::verified(1)
+        },
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+
+    // Passkeys do not bypass the email verification requirement.
+    let user_id: i64 = registered["user"]["id"].as_str().unwrap().parse()?;
+    sqlx::query("UPDATE users SET email_verified_at = NULL WHERE id = $1")
+        .bind(user_id)
+        .execute(&ctx.db)
+        .await?;
+    ctx.state.runtime.write().await.require_email_verification = true;
+    let (status, body) = passkey_login(
+        &ctx,
+        &key,
+        b"roaming-key",
+        Assertion {
+            identifier: Some("uv@example.com"),
+            ..Assertion::verified(2)
+        },
+    )
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    assert_eq!(body["code"], "EMAIL_UNVERIFIED");
+    Ok(())
+}
@@ -0,0 +1,452 @@
+use std::net::SocketAddr;
+
+use anyhow::Context;
+use axum::{
+    body::{to_bytes, Body},
+    extract::ConnectInfo,
+    http::{header, HeaderMap, Method, Request, StatusCode},
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_guild, create_text_channel, TestContext};
+
+impl TestContext {
+    /// List webhooks at `path`, returning the count and limit headers.
+    async fn list_webhooks(&self, path: &str) -> anyhow::Result<(StatusCode, HeaderMap, Value)> {
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", self.token))
+            .extension(ConnectInfo("127.0.0.1:40000".parse::<SocketAddr>()?))
+            .body(Body::empty())?;
+        let response = self.app.clone().oneshot(request).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, headers, serde_json::from_slice(&body)?))
+    }
+
+    /// Execute a webhook as an unauthenticated caller connecting from `peer`.
+    async fn execute_webhook(
+        &self,
+        path: &str,
+        peer: &str,
+        extra_headers: &[(&str, &str)],
+    ) -> anyhow::Result<StatusCode> {
+        let (status, _) = self.execute_webhook_json(path, peer, extra_headers).await?;
+        Ok(status)
+    }
+
+    async fn execute_webhook_json(
+        &self,
+        path: &str,
+        peer: &str,
+        extra_headers: &[(&str, &str)],
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let peer: SocketAddr = format!("{peer}:40000").parse()?;
+        let mut builder = Request::builder()
+            .method(Method::POST)
+            .uri(path)
+            .header(header::CONTENT_TYPE, "application/json");
+        for (name, value) in extra_headers {
+            builder = builder.header(*name, *value);
+        }
+        let mut request =
+            builder.body(Body::from(json!({ "content": "build passed" }).to_string()))?;
+        request.extensions_mut().insert(ConnectInfo(peer));
+
+        let response = self.app.clone().oneshot(request).await?;
+        let status = response.status();
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await?;
+        let payload = if body_bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body_bytes)
+                .unwrap_or_else(|_| json!({ "raw": String::from_utf8_lossy(&body_bytes) }))
+        };
+        Ok((status, payload))
+    }
+}
+
+async fn create_webhook(
+    ctx: &TestContext,
+    guild_id: &str,
+    channel_id: &str,
+    allowed_cidrs: Value,
+) -> anyhow::Result<String> {
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/webhooks"),
+            Some(json!({
+                "name": "CI",
+                "channel_id": channel_id,
+                "allowed_cidrs": allowed_cidrs,
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::CREATED, "{payload}");
+    let id = payload["id"].as_str().context("webhook id")?;
+    let token = payload["token"].as_str().context("webhook token")?;
+    Ok(format!("/api/v1/webhooks/{id}/{token}"))
+}
+
+#[tokio::test]
+async fn webhook_allowlist_accepts_matching_source_ip() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let execute_path =
+        create_webhook(&ctx, &guild_id, &channel_id, json!(["192.30.252.0/22"])).await?;
+
+    let status = ctx
+        .execute_webhook(&execute_path, "192.30.253.17", &[])
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_allowlist_rejects_other_source_ips() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let execute_path =
+        create_webhook(&ctx, &guild_id, &channel_id, json!(["192.30.252.0/22"])).await?;
+
+    let status = ctx
+        .execute_webhook(&execute_path, "198.51.100.9", &[])
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Forwarded headers from an untrusted peer must not bypass the allow-list.
+    let status = ctx
+        .execute_webhook(
+            &execute_path,
+            "198.51.100.9",
+            &[("x-forwarded-for", "192.30.253.17")],
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_without_allowlist_accepts_any_source() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let execute_path = create_webhook(&ctx, &guild_id, &channel_id, Value::Null).await?;
+
+    let status = ctx
+        .execute_webhook(&execute_path, "198.51.100.9", &[])
+        .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_executions_are_rate_limited_per_webhook() -> anyhow::Result<()> {
+    let ctx = TestContext::with_state(|state| {
+        state.config.webhook_executions_per_minute = 5;
+    })
+    .await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let execute_path = create_webhook(&ctx, &guild_id, &channel_id, Value::Null).await?;
+    let other_path = create_webhook(&ctx, &guild_id, &channel_id, Value::Null).await?;
+
+    let window = chrono::Utc::now().timestamp() / 60;
+    let mut statuses = Vec::new();
+    for i in 0..6 {
+        // Spread the calls over source addresses: the limit is per webhook.
+        let peer = format!("198.51.100.{}", 100 + i);
+        statuses.push(ctx.execute_webhook(&execute_path, &peer, &[]).await?);
+    }
+    let other = ctx
+        .execute_webhook(&other_path, "198.51.100.200", &[])
+        .await?;
+    if chrono::Utc::now().timestamp() / 60 != window {
+        // Crossed a window boundary mid-test; the counts are not meaningful.
+        return Ok(());
+    }
+
+    assert!(statuses[..5].iter().all(|s| *s == StatusCode::CREATED));
+    assert_eq!(statuses[5], StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(other, StatusCode::CREATED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_allowlist_rejects_invalid_cidr() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/webhooks"),
+            Some(json!({
+                "name": "CI",
+                "channel_id": channel_id,
+                "allowed_cidrs": ["10.0.0.0/40"],
+            })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_allowlist_uses_forwarded_ip_from_trusted_proxy() -> anyhow::Result<()> {
+    std::env::set_var("PARACORD_TRUST_PROXY", "true");
+    std::env::set_var("PARACORD_TRUSTED_PROXY_IPS", "10.0.0.5");
+
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let execute_path =
+        create_webhook(&ctx, &guild_id, &channel_id, json!(["140.82.112.0/20"])).await?;
+
+    let via_proxy = ctx
+        .execute_webhook(
+            &execute_path,
+            "10.0.0.5",
+            &[("x-forwarded-for", "140.82.115.3, 10.0.0.5")],
+        )
+        .await?;
+    let disallowed_via_proxy = ctx
+        .execute_webhook(
+            &execute_path,
+            "10.0.0.5",
+            &[("x-forwarded-for", "203.0.113.50")],
+        )
+        .await?;
+
+    std::env::remove_var("PARACORD_TRUST_PROXY");
+    std::env::remove_var("PARACORD_TRUSTED_PROXY_IPS");
+
+    assert_eq!(via_proxy, StatusCode::CREATED);
+    assert_eq!(disallowed_via_proxy, StatusCode::FORBIDDEN);
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_creation_is_capped_per_channel() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    ctx.state.runtime.write().await.max_webhooks_per_channel = 2;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let other_channel_id = create_text_channel(&ctx, &guild_id, "alerts").await?;
+
+    create_webhook(&ctx, &guild_id, &channel_id, json!([])).await?;
+    create_webhook(&ctx, &guild_id, &channel_id, json!([])).await?;
+
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/webhooks"),
+            Some(json!({ "name": "CI", "channel_id": channel_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The cap is per channel; other channels in the guild are unaffected.
+    create_webhook(&ctx, &guild_id, &other_channel_id, json!([])).await?;
+
+    let (status, headers, listing) = ctx
+        .list_webhooks(&format!("/api/v1/channels/{channel_id}/webhooks"))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listing.as_array().map(Vec::len), Some(2));
+    assert_eq!(headers["x-webhook-count"], "2");
+    assert_eq!(headers["x-webhook-limit"], "2");
+
+    let (status, headers, listing) = ctx
+        .list_webhooks(&format!("/api/v1/guilds/{guild_id}/webhooks"))
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listing.as_array().map(Vec::len), Some(3));
+    assert_eq!(headers["x-webhook-count"], "3");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_creation_is_capped_per_guild() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    ctx.state.runtime.write().await.max_webhooks_per_guild = 1;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let other_channel_id = create_text_channel(&ctx, &guild_id, "alerts").await?;
+
+    create_webhook(&ctx, &guild_id, &channel_id, json!([])).await?;
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/webhooks"),
+            Some(json!({ "name": "CI", "channel_id": other_channel_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn deleting_a_webhook_frees_a_slot() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    ctx.state.runtime.write().await.max_webhooks_per_channel = 1;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+
+    let execute_path = create_webhook(&ctx, &guild_id, &channel_id, json!([])).await?;
+    let (status, _) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/guilds/{guild_id}/webhooks"),
+            Some(json!({ "name": "CI", "channel_id": channel_id })),
+        )
+        .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let webhook_id = execute_path
+        .trim_start_matches("/api/v1/webhooks/")
+        .split('/')
+        .next()
+        .context("webhook id in path")?;
+    let (status, _) = ctx
+        .request_json(
+            Method::DELETE,
+            &format!("/api/v1/webhooks/{webhook_id}"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    create_webhook(&ctx, &guild_id, &channel_id, json!([])).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn regenerating_webhook_token_invalidates_the_old_one() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let old_path = create_webhook(&ctx, &guild_id, &channel_id, json!([])).await?;
+    let webhook_id = old_path
+        .trim_start_matches("/api/v1/webhooks/")
+        .split('/')
+        .next()
+        .context("webhook id in path")?
+        .to_string();
+
+    let (status, payload) = ctx
+        .request_json(
+            Method::POST,
+            &format!("/api/v1/webhooks/{webhook_id}/token/regenerate"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK, "{payload}");
+    let new_token = payload["token"].as_str().context("new token")?;
+    let new_path = format!("/api/v1/webhooks/{webhook_id}/{new_token}");
+    assert_ne!(new_path, old_path);
+
+    let status = ctx.execute_webhook(&old_path, "203.0.113.9", &[]).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let status = ctx.execute_webhook(&new_path, "203.0.113.9", &[]).await?;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // The token is only ever returned by create and regenerate.
+    let (_, fetched) = ctx
+        .request_json(Method::GET, &format!("/api/v1/webhooks/{webhook_id}"), None)
+        .await?;
+    assert!(fetched.get("token").is_none());
+
+    let (status, audit) = ctx
+        .request_json(
+            Method::GET,
+            &format!("/api/v1/guilds/{guild_id}/audit-logs?action_type=51"),
+            None,
+        )
+        .await?;
+    assert_eq!(status, StatusCode::OK);
+    let entries = audit["audit_log_entries"].as_array().context("entries")?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["target_id"], webhook_id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_execution_waits_and_returns_the_hydrated_message() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let execute_path = create_webhook(&ctx, &guild_id, &channel_id, Value::Null).await?;
+
+    for path in [execute_path.clone(), format!("{execute_path}?wait=true")] {
+        let (status, message) = ctx.execute_webhook_json(&path, "203.0.113.9", &[]).await?;
+        assert_eq!(status, StatusCode::CREATED, "{message}");
+        assert_eq!(message["content"], "build passed");
+        assert_eq!(message["channel_id"], channel_id);
+        assert_eq!(message["author"]["bot"], true);
+        assert!(message["webhook_id"].is_string());
+        assert!(message["attachments"].is_array());
+        assert!(message["reactions"].is_array());
+        assert!(message.get("flags").is_some());
+        assert!(message.get("poll").is_some());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_execution_without_wait_returns_no_content() -> anyhow::Result<()> {
+    let ctx = TestContext::new().await?;
+    let guild_id = create_guild(&ctx, "Webhook Guild").await?;
+    let channel_id = create_text_channel(&ctx, &guild_id, "ci").await?;
+    let execute_path = create_webhook(&ctx, &guild_id, &channel_id, Value::Null).await?;
+
+    let (status, body) = ctx
+        .execute_webhook_json(&format!("{execute_path}?wait=false"), "203.0.113.9", &[])
+        .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(body, Value::Null);
+
+    // The create is queued rather than dropped.
+    let mut delivered = false;
+    for _ in 0..50 {
+        let (status, messages) = ctx
+            .request_json(
+                Method::GET,
+                &format!("/api/v1/channels/{channel_id}/messages"),
+                None,
+            )
+            .await?;
+        assert_eq!(status, StatusCode::OK, "{messages}");
+        if messages
+            .as_array()
+            .is_some_and(|list| list.iter().any(|m| m["content"] == "build passed"))
+        {
+            delivered = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(delivered, "queued webhook message was never created");
+
+    // A malformed flag is rejected rather than treated as either mode.
+    let (status, _) = ctx
+        .execute_webhook_json(&format!("{execute_path}?wait=maybe"), "203.0.113.9", &[])
+        .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
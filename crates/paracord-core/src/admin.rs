@@ -33,13 +33,15 @@ pub async fn kick_member(
     Ok(())
 }
 
-/// Ban a member from a guild. Requires BAN_MEMBERS permission.
+/// Ban a member from a guild, until `expires_at` if given. Requires
+/// BAN_MEMBERS permission.
 pub async fn ban_member(
     pool: &DbPool,
     guild_id: i64,
     actor_id: i64,
     target_id: i64,
     reason: Option<&str>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
 ) -> Result<(), CoreError> {
     let guild = paracord_db::guilds::get_guild(pool, guild_id)
         .await?
@@ -57,7 +59,17 @@ pub async fn ban_member(
     let _ = paracord_db::members::remove_member(pool, target_id, guild_id).await;
 
     // Create ban entry
-    paracord_db::bans::create_ban(pool, target_id, guild_id, reason, actor_id).await?;
+    match expires_at {
+        Some(expires_at) => {
+            paracord_db::bans::create_temp_ban(
+                pool, target_id, guild_id, reason, actor_id, expires_at,
+            )
+            .await?;
+        }
+        None => {
+            paracord_db::bans::create_ban(pool, target_id, guild_id, reason, actor_id).await?;
+        }
+    }
 
     Ok(())
 }
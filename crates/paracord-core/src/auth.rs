@@ -4,10 +4,13 @@ use argon2::{
 };
 use ed25519_dalek::{Signature, VerifyingKey};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use paracord_util::at_rest::SecretCryptor;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod webauthn;
+
 #[derive(Debug, Error)]
 pub enum AuthError {
     #[error("invalid credentials")]
@@ -18,6 +21,8 @@ pub enum AuthError {
     InvalidToken,
     #[error("registration disabled")]
     RegistrationDisabled,
+    #[error("email_unverified")]
+    EmailUnverified,
     #[error("internal error: {0}")]
     Internal(String),
 }
@@ -33,6 +38,9 @@ pub struct Claims {
     pub jti: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pub_key: Option<String>,
+    /// Server admin acting as `sub` during a support impersonation session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imp: Option<i64>,
 }
 
 fn create_token_internal(
@@ -42,6 +50,7 @@ fn create_token_internal(
     expiry_secs: u64,
     session_id: Option<&str>,
     jti: Option<&str>,
+    impersonator_id: Option<i64>,
 ) -> Result<String, AuthError> {
     let now = chrono::Utc::now().timestamp() as usize;
     let claims = Claims {
@@ -51,6 +60,7 @@ fn create_token_internal(
         sid: session_id.map(str::to_string),
         jti: jti.map(str::to_string),
         pub_key: public_key.map(str::to_string),
+        imp: impersonator_id,
     };
     encode(
         &Header::default(),
@@ -77,7 +87,7 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, AuthError> {
 }
 
 pub fn create_token(user_id: i64, secret: &str, expiry_secs: u64) -> Result<String, AuthError> {
-    create_token_internal(user_id, None, secret, expiry_secs, None, None)
+    create_token_internal(user_id, None, secret, expiry_secs, None, None, None)
 }
 
 pub fn create_token_with_pubkey(
@@ -86,7 +96,15 @@ pub fn create_token_with_pubkey(
     secret: &str,
     expiry_secs: u64,
 ) -> Result<String, AuthError> {
-    create_token_internal(user_id, Some(public_key), secret, expiry_secs, None, None)
+    create_token_internal(
+        user_id,
+        Some(public_key),
+        secret,
+        expiry_secs,
+        None,
+        None,
+        None,
+    )
 }
 
 pub fn create_session_token(
@@ -104,6 +122,28 @@ pub fn create_session_token(
         expiry_secs,
         Some(session_id),
         Some(jti),
+        None,
+    )
+}
+
+/// Session token that lets `impersonator_id` act as `user_id`. The `imp`
+/// claim marks every request made with it.
+pub fn create_impersonation_token(
+    user_id: i64,
+    impersonator_id: i64,
+    secret: &str,
+    expiry_secs: u64,
+    session_id: &str,
+    jti: &str,
+) -> Result<String, AuthError> {
+    create_token_internal(
+        user_id,
+        None,
+        secret,
+        expiry_secs,
+        Some(session_id),
+        Some(jti),
+        Some(impersonator_id),
     )
 }
 
@@ -166,6 +206,383 @@ pub fn verify_challenge(
         .is_ok())
 }
 
+// ── TOTP (RFC 6238) ─────────────────────────────────────────────────────
+
+pub const TOTP_STEP_SECONDS: i64 = 30;
+pub const TOTP_DIGITS: u32 = 6;
+pub const TOTP_RECOVERY_CODE_COUNT: usize = 10;
+const TOTP_SECRET_BYTES: usize = 20;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Fresh random TOTP secret, base32-encoded without padding.
+pub fn generate_totp_secret() -> String {
+    let mut bytes = [0u8; TOTP_SECRET_BYTES];
+    rand::thread_rng().fill(&mut bytes);
+    base32_encode(&bytes)
+}
+
+/// `otpauth://` provisioning URI for authenticator apps.
+pub fn totp_provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let issuer = urlencoding::encode(issuer);
+    let account = urlencoding::encode(account);
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={TOTP_DIGITS}&period={TOTP_STEP_SECONDS}"
+    )
+}
+
+pub fn totp_step(unix_time: i64) -> i64 {
+    unix_time.div_euclid(TOTP_STEP_SECONDS)
+}
+
+/// The code for `step`, or `None` if `secret` is not valid base32.
+pub fn totp_code(secret: &str, step: i64) -> Option<String> {
+    use hmac::{Hmac, Mac};
+
+    let key = base32_decode(secret)?;
+    let mut mac = Hmac::<sha1::Sha1>::new_from_slice(&key).ok()?;
+    mac.update(&(step as u64).to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    let code = binary % 10u32.pow(TOTP_DIGITS);
+    Some(format!("{code:0width$}", width = TOTP_DIGITS as usize))
+}
+
+/// Check `code` against the steps around `unix_time` (±1 step for clock
+/// drift) and return the matching step. Callers must reject steps that were
+/// already used to stop replays.
+pub fn verify_totp_code(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let current = totp_step(unix_time);
+    (current - 1..=current + 1).find(|step| {
+        totp_code(secret, *step)
+            .is_some_and(|expected| constant_time_eq(expected.as_bytes(), code.as_bytes()))
+    })
+}
+
+/// Single-use recovery codes in `xxxxx-xxxxx` form.
+pub fn generate_recovery_codes() -> Vec<String> {
+    const CHARSET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..TOTP_RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw: String = (0..10)
+                .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+                .collect();
+            format!("{}-{}", &raw[..5], &raw[5..])
+        })
+        .collect()
+}
+
+/// Hash stored for a recovery code; input is normalised so case and the
+/// separator do not matter.
+pub fn hash_recovery_code(code: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hex_encode(&Sha256::digest(normalized.as_bytes()))
+}
+
+fn totp_secret_aad(user_id: i64) -> String {
+    format!("totp:{user_id}")
+}
+
+/// Value stored for a TOTP secret: sealed when at-rest encryption is on,
+/// otherwise the base32 secret itself.
+pub fn seal_totp_secret(
+    cryptor: Option<&SecretCryptor>,
+    user_id: i64,
+    secret: &str,
+) -> Result<String, AuthError> {
+    match cryptor {
+        Some(cryptor) => cryptor
+            .seal(secret, totp_secret_aad(user_id).as_bytes())
+            .map_err(|e| AuthError::Internal(e.to_string())),
+        None => Ok(secret.to_string()),
+    }
+}
+
+/// The base32 secret behind a stored value. Secrets stored before at-rest
+/// encryption was enabled are read as-is.
+pub fn open_totp_secret(
+    cryptor: Option<&SecretCryptor>,
+    user_id: i64,
+    stored: &str,
+) -> Result<String, AuthError> {
+    if !SecretCryptor::is_sealed(stored) {
+        return Ok(stored.to_string());
+    }
+    let cryptor = cryptor.ok_or_else(|| {
+        AuthError::Internal("TOTP secret is sealed but at-rest encryption is off".into())
+    })?;
+    cryptor
+        .open(stored, totp_secret_aad(user_id).as_bytes())
+        .map_err(|e| AuthError::Internal(e.to_string()))
+}
+
+/// Outcome of the second-factor check during a login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginMfa {
+    /// TOTP is not enabled, or a valid TOTP code was supplied.
+    Satisfied,
+    /// An unused recovery code was supplied and is now spent.
+    RecoveryCodeUsed { remaining: usize },
+    /// TOTP is enabled and no code was supplied.
+    Required,
+    /// A code was supplied but it is wrong, expired, or already used.
+    Invalid,
+}
+
+/// Accept either a current TOTP code (each time step at most once) or an
+/// unused recovery code, which is spent.
+pub async fn verify_second_factor(
+    pool: &paracord_db::DbPool,
+    cryptor: Option<&SecretCryptor>,
+    totp: &paracord_db::totp::UserTotpRow,
+    code: &str,
+    unix_time: i64,
+) -> Result<LoginMfa, AuthError> {
+    let db_error = |e: paracord_db::DbError| AuthError::Internal(e.to_string());
+    let code = code.trim();
+    if code.len() == TOTP_DIGITS as usize && code.bytes().all(|b| b.is_ascii_digit()) {
+        let secret = open_totp_secret(cryptor, totp.user_id, &totp.secret)?;
+        let Some(step) = verify_totp_code(&secret, code, unix_time) else {
+            return Ok(LoginMfa::Invalid);
+        };
+        let fresh = paracord_db::totp::record_used_step(pool, totp.user_id, step)
+            .await
+            .map_err(db_error)?;
+        return Ok(if fresh {
+            LoginMfa::Satisfied
+        } else {
+            LoginMfa::Invalid
+        });
+    }
+
+    let consumed =
+        paracord_db::totp::consume_recovery_code(pool, totp.user_id, &hash_recovery_code(code))
+            .await
+            .map_err(db_error)?;
+    Ok(if consumed {
+        LoginMfa::RecoveryCodeUsed {
+            remaining: totp.recovery_codes_hashed.len().saturating_sub(1),
+        }
+    } else {
+        LoginMfa::Invalid
+    })
+}
+
+/// The second-factor gate every login path runs once the first factor
+/// (password, key signature or passkey) has been accepted.
+pub async fn check_login_mfa(
+    pool: &paracord_db::DbPool,
+    cryptor: Option<&SecretCryptor>,
+    user_id: i64,
+    mfa_code: Option<&str>,
+    unix_time: i64,
+) -> Result<LoginMfa, AuthError> {
+    let totp = paracord_db::totp::get_totp(pool, user_id)
+        .await
+        .map_err(|e| AuthError::Internal(e.to_string()))?;
+    let Some(totp) = totp.filter(|row| row.is_confirmed()) else {
+        return Ok(LoginMfa::Satisfied);
+    };
+    let Some(code) = mfa_code.map(str::trim).filter(|code| !code.is_empty()) else {
+        return Ok(LoginMfa::Required);
+    };
+    verify_second_factor(pool, cryptor, &totp, code, unix_time).await
+}
+
+/// How long an email verification link stays valid.
+pub const EMAIL_VERIFICATION_TTL_HOURS: i64 = 24;
+
+/// Random one-time email verification token (32 bytes, hex). Only its
+/// [`hash_email_verification_token`] is stored.
+pub fn generate_email_verification_token() -> String {
+    let mut bytes = [0_u8; 32];
+    rand::thread_rng().fill(&mut bytes);
+    hex_encode(&bytes)
+}
+
+pub fn hash_email_verification_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    hex_encode(&Sha256::digest(token.trim().as_bytes()))
+}
+
+/// How long a password reset link stays valid.
+pub const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+
+/// Random single-use password reset token (32 bytes, hex). Only its
+/// [`hash_password_reset_token`] is stored.
+pub fn generate_password_reset_token() -> String {
+    let mut bytes = [0_u8; 32];
+    rand::thread_rng().fill(&mut bytes);
+    hex_encode(&bytes)
+}
+
+pub fn hash_password_reset_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    hex_encode(&Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Reject a password login for an account that still has to verify its
+/// email while the server requires verification.
+pub fn ensure_email_verified(
+    user: &paracord_db::users::UserAuthRow,
+    settings: &crate::RuntimeSettings,
+) -> Result<(), AuthError> {
+    if settings.require_email_verification && user.email_verified_at.is_none() {
+        return Err(AuthError::EmailUnverified);
+    }
+    Ok(())
+}
+
+/// Lock out a source IP and login identifier pair for `lockout_seconds`
+/// after `max_failures` consecutive failed password verifications.
+/// `max_failures == 0` disables it.
+///
+/// The pair is the subject so that failures from other addresses cannot
+/// lock the owner out of their account; throttling one address across many
+/// accounts is left to the auth guard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoginLockoutPolicy {
+    pub max_failures: u32,
+    pub lockout_seconds: u64,
+}
+
+impl LoginLockoutPolicy {
+    pub fn from_config(config: &crate::AppConfig) -> Self {
+        Self {
+            max_failures: config.login_max_failures,
+            lockout_seconds: config.login_lockout_seconds,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_failures > 0
+    }
+
+    fn locked_until(&self, failures: i64, now: i64) -> i64 {
+        if failures >= i64::from(self.max_failures) {
+            let lockout = i64::try_from(self.lockout_seconds)
+                .unwrap_or(i64::MAX)
+                .max(1);
+            now.saturating_add(lockout)
+        } else {
+            0
+        }
+    }
+}
+
+/// Auth-guard key for lockout failures from `client_ip` against the
+/// normalized login `identifier`.
+pub fn login_lockout_key(client_ip: &str, identifier: &str) -> String {
+    format!("lockout:{client_ip}|{}", identifier.to_ascii_lowercase())
+}
+
+/// Seconds until `key` may attempt a login again, or `None` when it is not
+/// locked out.
+pub async fn login_lockout_retry_after(
+    store: &crate::rate_limit::RateLimitStore,
+    policy: LoginLockoutPolicy,
+    key: &str,
+    now: i64,
+) -> Result<Option<i64>, paracord_db::DbError> {
+    if !policy.enabled() {
+        return Ok(None);
+    }
+    let rows = store.auth_guard_states(&[key.to_string()]).await?;
+    Ok(rows
+        .iter()
+        .map(|row| row.locked_until)
+        .filter(|locked_until| *locked_until > now)
+        .max()
+        .map(|locked_until| locked_until - now))
+}
+
+/// Count a failed password verification against `key`. Returns `true` when
+/// this failure locked it out.
+pub async fn record_login_failure(
+    store: &crate::rate_limit::RateLimitStore,
+    policy: LoginLockoutPolicy,
+    key: &str,
+    now: i64,
+) -> Result<bool, paracord_db::DbError> {
+    if !policy.enabled() {
+        return Ok(false);
+    }
+    let row = store
+        .record_auth_guard_failure_with(key, now, |failures| policy.locked_until(failures, now))
+        .await?;
+    Ok(row.failures == i64::from(policy.max_failures))
+}
+
+/// Forget the failures counted against `key` after a successful login.
+pub async fn clear_login_failures(
+    store: &crate::rate_limit::RateLimitStore,
+    key: &str,
+) -> Result<(), paracord_db::DbError> {
+    store.clear_auth_guard_keys(&[key.to_string()]).await?;
+    Ok(())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(value: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(value.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in value.chars().filter(|c| *c != '=' && !c.is_whitespace()) {
+        let index = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | index;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    if out.is_empty() {
+        return None;
+    }
+    Some(out)
+}
+
 fn hex_encode(bytes: &[u8]) -> String {
     let mut out = String::with_capacity(bytes.len() * 2);
     for b in bytes {
@@ -213,6 +630,21 @@ mod tests {
         assert!(claims.jti.is_none());
     }
 
+    #[test]
+    fn impersonation_tokens_carry_impersonator_claim() {
+        let secret = "test-secret";
+        let token =
+            create_impersonation_token(42, 1, secret, 60, "sid-1", "jti-1").expect("create token");
+        let claims = validate_token(&token, secret).expect("validate token");
+        assert_eq!(claims.sub, 42);
+        assert_eq!(claims.imp, Some(1));
+        assert_eq!(claims.sid.as_deref(), Some("sid-1"));
+
+        let regular =
+            create_session_token(42, None, secret, 60, "sid-2", "jti-2").expect("create token");
+        assert!(validate_token(&regular, secret).unwrap().imp.is_none());
+    }
+
     #[test]
     fn create_token_produces_valid_jwt() {
         let secret = "my-secret-key";
@@ -309,4 +741,131 @@ mod tests {
         let (nonce2, _) = generate_challenge();
         assert_ne!(nonce1, nonce2);
     }
+
+    // RFC 6238 appendix B vectors, truncated to six digits.
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn totp_secrets_are_sealed_per_user_when_a_cryptor_is_set() {
+        let cryptor = SecretCryptor::from_master_key(&[7_u8; 32]);
+        let sealed = seal_totp_secret(Some(&cryptor), 1, RFC_SECRET).unwrap();
+        assert_ne!(sealed, RFC_SECRET);
+        assert_eq!(
+            open_totp_secret(Some(&cryptor), 1, &sealed).unwrap(),
+            RFC_SECRET
+        );
+        // A sealed secret copied onto another account does not open.
+        assert!(open_totp_secret(Some(&cryptor), 2, &sealed).is_err());
+        assert!(open_totp_secret(None, 1, &sealed).is_err());
+
+        // Without a cryptor, and for rows written before one was set, the
+        // secret is stored as-is.
+        assert_eq!(seal_totp_secret(None, 1, RFC_SECRET).unwrap(), RFC_SECRET);
+        assert_eq!(
+            open_totp_secret(Some(&cryptor), 1, RFC_SECRET).unwrap(),
+            RFC_SECRET
+        );
+    }
+
+    #[test]
+    fn totp_codes_match_rfc_6238_vectors() {
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+        assert_eq!(
+            totp_code(RFC_SECRET, totp_step(59)).as_deref(),
+            Some("287082")
+        );
+        assert_eq!(
+            totp_code(RFC_SECRET, totp_step(1_111_111_109)).as_deref(),
+            Some("081804")
+        );
+    }
+
+    #[test]
+    fn totp_verification_allows_one_step_of_drift() {
+        let now = 1_700_000_000;
+        let step = totp_step(now);
+        let previous = totp_code(RFC_SECRET, step - 1).unwrap();
+        let next = totp_code(RFC_SECRET, step + 1).unwrap();
+        let stale = totp_code(RFC_SECRET, step - 2).unwrap();
+
+        assert_eq!(verify_totp_code(RFC_SECRET, &previous, now), Some(step - 1));
+        assert_eq!(verify_totp_code(RFC_SECRET, &next, now), Some(step + 1));
+        assert_eq!(verify_totp_code(RFC_SECRET, &stale, now), None);
+        assert_eq!(verify_totp_code(RFC_SECRET, "12345", now), None);
+        assert_eq!(verify_totp_code(RFC_SECRET, "abcdef", now), None);
+    }
+
+    #[test]
+    fn generated_secrets_round_trip_through_base32() {
+        let secret = generate_totp_secret();
+        assert_eq!(base32_decode(&secret).map(|b| b.len()), Some(20));
+    }
+
+    #[test]
+    fn recovery_code_hash_ignores_case_and_separator() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), TOTP_RECOVERY_CODE_COUNT);
+        let code = &codes[0];
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.replace('-', "").to_uppercase())
+        );
+    }
+
+    #[tokio::test]
+    async fn login_lockout_triggers_at_threshold_and_clears_on_success() {
+        let store = crate::rate_limit::RateLimitStore::default();
+        let policy = LoginLockoutPolicy {
+            max_failures: 2,
+            lockout_seconds: 300,
+        };
+        let key = login_lockout_key("198.51.100.7", "Victim@Example.com");
+        let elsewhere = login_lockout_key("203.0.113.9", "victim@example.com");
+        let now = 1_700_000_000;
+
+        assert!(!record_login_failure(&store, policy, &key, now)
+            .await
+            .expect("first failure"));
+        assert_eq!(
+            login_lockout_retry_after(&store, policy, &key, now + 1)
+                .await
+                .expect("check"),
+            None
+        );
+        assert!(record_login_failure(&store, policy, &key, now + 10)
+            .await
+            .expect("second failure"));
+        assert_eq!(
+            login_lockout_retry_after(&store, policy, &key, now + 20)
+                .await
+                .expect("check"),
+            Some(290)
+        );
+        // The same account from another address is unaffected.
+        assert_eq!(
+            login_lockout_retry_after(&store, policy, &elsewhere, now + 20)
+                .await
+                .expect("check"),
+            None
+        );
+
+        let disabled = LoginLockoutPolicy {
+            max_failures: 0,
+            ..policy
+        };
+        assert_eq!(
+            login_lockout_retry_after(&store, disabled, &key, now + 20)
+                .await
+                .expect("check"),
+            None
+        );
+
+        clear_login_failures(&store, &key).await.expect("clear");
+        assert_eq!(
+            login_lockout_retry_after(&store, policy, &key, now + 20)
+                .await
+                .expect("check"),
+            None
+        );
+    }
 }
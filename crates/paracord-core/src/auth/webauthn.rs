@@ -0,0 +1,99 @@
+//! WebAuthn (passkey) ceremonies, verified by `webauthn-rs`.
+//!
+//! This module only binds the library to Paracord: which relying party a
+//! request belongs to and how account IDs map to WebAuthn user handles.
+//! Registrations use the library's passkey policy: `"none"` attestation and
+//! user verification (PIN or biometric) required on every ceremony, so a
+//! passkey login is itself a second factor.
+
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+pub use webauthn_rs::prelude::{
+    AuthenticationResult, CreationChallengeResponse, DiscoverableAuthentication, DiscoverableKey,
+    Passkey, PasskeyAuthentication, PasskeyRegistration, PublicKeyCredential,
+    RegisterPublicKeyCredential, RequestChallengeResponse, WebauthnError,
+};
+use webauthn_rs::prelude::{Url, Uuid};
+pub use webauthn_rs::Webauthn;
+use webauthn_rs::WebauthnBuilder;
+
+/// How long a registration or login ceremony stays valid.
+pub const CEREMONY_TIMEOUT_MS: u64 = 120_000;
+
+const RP_NAME: &str = "Paracord";
+
+/// The relying party a ceremony is bound to: the browser origin and the
+/// RP ID (its host) that credentials are scoped to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelyingParty {
+    pub id: String,
+    pub origin: String,
+}
+
+impl RelyingParty {
+    /// Build from a `scheme://host[:port]` origin; the RP ID is the host.
+    pub fn from_origin(origin: &str) -> Option<Self> {
+        let origin = origin.trim().trim_end_matches('/');
+        let (_, authority) = origin.split_once("://")?;
+        let host = match authority.rsplit_once(':') {
+            Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+            _ => authority,
+        };
+        if host.is_empty() || authority.contains('/') {
+            return None;
+        }
+        Some(Self {
+            id: host.to_ascii_lowercase(),
+            origin: origin.to_string(),
+        })
+    }
+
+    /// The verifier for ceremonies against this relying party.
+    pub fn verifier(&self) -> Result<Webauthn, WebauthnError> {
+        let origin = Url::parse(&self.origin).map_err(|_| WebauthnError::Configuration)?;
+        WebauthnBuilder::new(&self.id, &origin)?
+            .rp_name(RP_NAME)
+            .timeout(Duration::from_millis(CEREMONY_TIMEOUT_MS))
+            .build()
+    }
+}
+
+/// Opaque WebAuthn user handle for an account.
+pub fn user_handle(user_id: i64) -> Uuid {
+    Uuid::from_u64_pair(0, user_id as u64)
+}
+
+/// The account a user handle from [`user_handle`] belongs to.
+pub fn user_id_from_handle(handle: Uuid) -> Option<i64> {
+    match handle.as_u64_pair() {
+        (0, id) => i64::try_from(id).ok(),
+        _ => None,
+    }
+}
+
+/// Base64url (unpadded) form of a credential ID, as stored and listed.
+pub fn encode_credential_id(credential_id: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(credential_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relying_party_id_is_the_origin_host() {
+        let rp = RelyingParty::from_origin("https://Chat.Example.com:8443/").unwrap();
+        assert_eq!(rp.id, "chat.example.com");
+        assert_eq!(rp.origin, "https://Chat.Example.com:8443");
+        assert!(RelyingParty::from_origin("chat.example.com").is_none());
+        assert!(rp.verifier().is_ok());
+    }
+
+    #[test]
+    fn user_handles_round_trip_to_account_ids() {
+        assert_eq!(user_id_from_handle(user_handle(42)), Some(42));
+        assert_eq!(user_id_from_handle(Uuid::new_v4()), None);
+    }
+}
@@ -97,3 +97,22 @@ pub async fn update_channel(
             .await?;
     Ok(updated)
 }
+
+/// Participants of DM `channel_id` who may receive an event `actor_id`
+/// caused: everyone but users on either side of a block with the actor.
+/// Fails closed: if either lookup fails, only the actor is returned.
+pub async fn dm_event_recipients(pool: &DbPool, channel_id: i64, actor_id: i64) -> Vec<i64> {
+    let recipients = paracord_db::dms::get_dm_recipient_ids(pool, channel_id).await;
+    let blocked = paracord_db::relationships::get_blocked_user_ids(pool, actor_id).await;
+    match (recipients, blocked) {
+        (Ok(mut recipients), Ok(blocked)) => {
+            let blocked: std::collections::HashSet<i64> = blocked.into_iter().collect();
+            recipients.retain(|id| !blocked.contains(id));
+            recipients
+        }
+        (Err(err), _) | (_, Err(err)) => {
+            tracing::warn!(channel_id, "failed to resolve DM event recipients: {err}");
+            vec![actor_id]
+        }
+    }
+}
@@ -14,6 +14,50 @@ pub struct ServerEvent {
     pub target_user_ids: Option<Vec<i64>>,
     /// Pre-serialized JSON payload for efficient WebSocket dispatch.
     pub serialized_payload: Option<Arc<String>>,
+    /// Gateway session whose request produced this event, if the client
+    /// named one.
+    pub origin_session_id: Option<String>,
+}
+
+impl ServerEvent {
+    pub fn new(
+        event_type: &str,
+        payload: serde_json::Value,
+        guild_id: Option<i64>,
+        target_user_ids: Option<Vec<i64>>,
+    ) -> Self {
+        let payload = Arc::new(payload);
+        let serialized = Arc::new(serde_json::to_string(&*payload).unwrap_or_default());
+        Self {
+            event_type: event_type.to_string(),
+            payload,
+            guild_id,
+            target_user_ids,
+            serialized_payload: Some(serialized),
+            origin_session_id: None,
+        }
+    }
+
+    pub fn with_origin_session(mut self, session_id: Option<String>) -> Self {
+        self.origin_session_id = session_id;
+        self
+    }
+
+    /// Whether this is the `MESSAGE_CREATE` echo of a nonce-tagged message
+    /// sent from `session_id`. That session can opt out of the echo because
+    /// it already rendered the message optimistically; the sender's other
+    /// sessions still receive it.
+    pub fn is_own_message_echo(&self, session_id: &str) -> bool {
+        if self.event_type != "MESSAGE_CREATE" {
+            return false;
+        }
+        let has_nonce = self
+            .payload
+            .get("nonce")
+            .and_then(|v| v.as_str())
+            .is_some_and(|nonce| !nonce.is_empty());
+        has_nonce && self.origin_session_id.as_deref() == Some(session_id)
+    }
 }
 
 /// Broadcast-based event bus for real-time dispatch.
@@ -201,15 +245,7 @@ impl EventBus {
 
     /// Helper: publish a typed event with guild_id
     pub fn dispatch(&self, event_type: &str, payload: serde_json::Value, guild_id: Option<i64>) {
-        let payload_arc = Arc::new(payload);
-        let serialized = Arc::new(serde_json::to_string(&*payload_arc).unwrap_or_default());
-        self.publish(ServerEvent {
-            event_type: event_type.to_string(),
-            payload: payload_arc,
-            guild_id,
-            target_user_ids: None,
-            serialized_payload: Some(serialized),
-        });
+        self.publish(ServerEvent::new(event_type, payload, guild_id, None));
     }
 
     /// Helper: publish a targeted event delivered only to the specified users.
@@ -219,15 +255,12 @@ impl EventBus {
         payload: serde_json::Value,
         target_user_ids: Vec<i64>,
     ) {
-        let payload_arc = Arc::new(payload);
-        let serialized = Arc::new(serde_json::to_string(&*payload_arc).unwrap_or_default());
-        self.publish(ServerEvent {
-            event_type: event_type.to_string(),
-            payload: payload_arc,
-            guild_id: None,
-            target_user_ids: Some(target_user_ids),
-            serialized_payload: Some(serialized),
-        });
+        self.publish(ServerEvent::new(
+            event_type,
+            payload,
+            None,
+            Some(target_user_ids),
+        ));
     }
 }
 
@@ -236,3 +269,32 @@ impl Default for EventBus {
         Self::new(4096)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message_create(payload: serde_json::Value) -> ServerEvent {
+        ServerEvent::new("MESSAGE_CREATE", payload, Some(1), None)
+            .with_origin_session(Some("session-a".to_string()))
+    }
+
+    #[test]
+    fn own_message_echo_requires_origin_session_and_nonce() {
+        let event = message_create(json!({ "author": { "id": "42" }, "nonce": "n-1" }));
+        assert!(event.is_own_message_echo("session-a"));
+        // The sender's other devices still get the message.
+        assert!(!event.is_own_message_echo("session-b"));
+
+        let untagged = event.clone().with_origin_session(None);
+        assert!(!untagged.is_own_message_echo("session-a"));
+
+        let without_nonce = message_create(json!({ "author": { "id": "42" }, "nonce": null }));
+        assert!(!without_nonce.is_own_message_echo("session-a"));
+
+        let mut update = message_create(json!({ "author": { "id": "42" }, "nonce": "n-1" }));
+        update.event_type = "MESSAGE_UPDATE".to_string();
+        assert!(!update.is_own_message_echo("session-a"));
+    }
+}
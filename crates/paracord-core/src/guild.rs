@@ -1,4 +1,8 @@
+use std::collections::{HashMap, HashSet};
+
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 use crate::error::CoreError;
 use crate::permissions;
@@ -17,15 +21,96 @@ pub fn generate_invite_code(length: usize) -> String {
         .collect()
 }
 
-/// Create a full guild with owner membership, @everyone role, and default channels.
-/// Returns (guild_row, general_channel_id).
+/// Kind of channel in a new guild's default layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultChannelKind {
+    Text,
+    Voice,
+    Category,
+}
+
+impl DefaultChannelKind {
+    fn channel_type(self) -> i16 {
+        match self {
+            Self::Text => 0,
+            Self::Voice => 2,
+            Self::Category => 4,
+        }
+    }
+}
+
+/// One channel every new guild starts with. `category` names a category
+/// listed earlier in the same set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DefaultChannel {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: DefaultChannelKind,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// The layout used when the operator has not configured one: a `general`
+/// text channel and a `General` voice channel.
+pub fn builtin_default_channels() -> Vec<DefaultChannel> {
+    vec![
+        DefaultChannel {
+            name: "general".to_string(),
+            kind: DefaultChannelKind::Text,
+            category: None,
+        },
+        DefaultChannel {
+            name: "General".to_string(),
+            kind: DefaultChannelKind::Voice,
+            category: None,
+        },
+    ]
+}
+
+/// Check that names are usable and every `category` refers to a category
+/// defined before it.
+pub fn validate_default_channels(channels: &[DefaultChannel]) -> Result<(), String> {
+    let mut categories: Vec<&str> = Vec::new();
+    for channel in channels {
+        let name = channel.name.trim();
+        if name.is_empty() || name.len() > 100 {
+            return Err(format!(
+                "default channel name {:?} must be 1-100 characters",
+                channel.name
+            ));
+        }
+        if let Some(category) = channel.category.as_deref() {
+            if channel.kind == DefaultChannelKind::Category {
+                return Err(format!(
+                    "category {name:?} cannot be nested in another category"
+                ));
+            }
+            if !categories.contains(&category) {
+                return Err(format!(
+                    "default channel {name:?} references unknown category {category:?}; list categories before their channels"
+                ));
+            }
+        }
+        if channel.kind == DefaultChannelKind::Category {
+            categories.push(name);
+        }
+    }
+    Ok(())
+}
+
+/// Create a full guild with owner membership, the default Member role, and
+/// `channels` in the given order.
 pub async fn create_guild_full(
     pool: &DbPool,
     guild_id: i64,
     name: &str,
     owner_id: i64,
     icon_hash: Option<&str>,
+    channels: &[DefaultChannel],
 ) -> Result<paracord_db::guilds::GuildRow, CoreError> {
+    validate_default_channels(channels).map_err(CoreError::BadRequest)?;
+
     let guild =
         paracord_db::guilds::create_guild(pool, guild_id, name, owner_id, icon_hash).await?;
 
@@ -39,15 +124,29 @@ pub async fn create_guild_full(
     // Assign Member role to owner
     paracord_db::roles::add_member_role(pool, owner_id, guild_id, guild_id).await?;
 
-    // Create #general text channel
-    let general_id = paracord_util::snowflake::generate(1);
-    paracord_db::channels::create_channel(pool, general_id, guild_id, "general", 0, 0, None, None)
-        .await?;
-
-    // Create General voice channel
-    let voice_id = paracord_util::snowflake::generate(1);
-    paracord_db::channels::create_channel(pool, voice_id, guild_id, "General", 2, 1, None, None)
+    let mut category_ids: HashMap<&str, i64> = HashMap::new();
+    for (position, channel) in channels.iter().enumerate() {
+        let channel_id = paracord_util::snowflake::generate(1);
+        let name = channel.name.trim();
+        let parent_id = channel
+            .category
+            .as_deref()
+            .and_then(|category| category_ids.get(category).copied());
+        paracord_db::channels::create_channel(
+            pool,
+            channel_id,
+            guild_id,
+            name,
+            channel.kind.channel_type(),
+            position as i32,
+            parent_id,
+            None,
+        )
         .await?;
+        if channel.kind == DefaultChannelKind::Category {
+            category_ids.insert(name, channel_id);
+        }
+    }
 
     Ok(guild)
 }
@@ -97,3 +196,556 @@ pub async fn update_guild(
     .await?;
     Ok(updated)
 }
+
+/// One guild as a client needs it at connect time.
+#[derive(Debug, Clone)]
+pub struct ReadyGuild {
+    pub guild: paracord_db::guilds::GuildRow,
+    pub member_count: i64,
+    /// The connecting user's own membership.
+    pub member: paracord_db::members::MemberRow,
+    /// The user's roles in this guild, including @everyone, by position.
+    pub member_role_ids: Vec<i64>,
+    pub roles: Vec<paracord_db::roles::RoleRow>,
+    /// Channels the user can view, by position.
+    pub channels: Vec<paracord_db::channels::ChannelRow>,
+    pub read_summary: GuildReadSummary,
+}
+
+/// Unread state of a guild, aggregated over the channels the user can view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuildReadSummary {
+    /// Some channel has a message newer than the user's read marker, or has
+    /// messages and was never read.
+    pub unread: bool,
+    /// Sum of the unread mention counts of those channels.
+    pub mention_count: i64,
+}
+
+impl GuildReadSummary {
+    /// Aggregate `channels` given the user's read states and each channel's
+    /// newest message id. Categories carry no messages and are skipped.
+    pub fn from_channels(
+        channels: &[paracord_db::channels::ChannelRow],
+        read_states: &HashMap<i64, paracord_db::read_states::ReadStateRow>,
+        latest_message_ids: &HashMap<i64, i64>,
+    ) -> Self {
+        let mut summary = Self::default();
+        for channel in channels {
+            if channel.channel_type == paracord_models::channel::ChannelType::Category as i16 {
+                continue;
+            }
+            let latest = latest_message_ids.get(&channel.id).copied();
+            match read_states.get(&channel.id) {
+                Some(state) => {
+                    summary.unread |= latest.is_some_and(|id| id > state.last_message_id);
+                    summary.mention_count += i64::from(state.mention_count);
+                }
+                None => summary.unread |= latest.is_some(),
+            }
+        }
+        summary
+    }
+}
+
+impl ReadyGuild {
+    pub fn to_json(&self) -> Value {
+        let g = &self.guild;
+        let channels: Vec<Value> = self
+            .channels
+            .iter()
+            .map(|c| {
+                let required_role_ids: Vec<String> =
+                    paracord_db::channels::parse_required_role_ids(&c.required_role_ids)
+                        .into_iter()
+                        .map(|id| id.to_string())
+                        .collect();
+                json!({
+                    "id": c.id.to_string(),
+                    "guild_id": c.guild_id().map(|id| id.to_string()),
+                    "name": c.name,
+                    "topic": c.topic,
+                    "type": c.channel_type,
+                    "channel_type": c.channel_type,
+                    "position": c.position,
+                    "parent_id": c.parent_id.map(|id| id.to_string()),
+                    "nsfw": c.nsfw,
+                    "rate_limit_per_user": c.rate_limit_per_user,
+                    "last_message_id": c.last_message_id.map(|id| id.to_string()),
+                    "required_role_ids": required_role_ids,
+                })
+            })
+            .collect();
+        let roles: Vec<Value> = self
+            .roles
+            .iter()
+            .map(|r| {
+                json!({
+                    "id": r.id.to_string(),
+                    "guild_id": r.guild_id().to_string(),
+                    "name": r.name,
+                    "color": r.color,
+                    "hoist": r.hoist,
+                    "position": r.position,
+                    "permissions": r.permissions,
+                    "managed": r.managed,
+                    "mentionable": r.mentionable,
+                    "role_icon": r.role_icon,
+                    "created_at": r.created_at.to_rfc3339(),
+                })
+            })
+            .collect();
+        let m = &self.member;
+        json!({
+            "id": g.id.to_string(),
+            "name": g.name,
+            "description": g.description,
+            "icon_hash": g.icon_hash,
+            "owner_id": g.owner_id.to_string(),
+            "member_count": self.member_count,
+            "features": paracord_models::guild::guild_feature_names(g.features),
+            "created_at": g.created_at.to_rfc3339(),
+            "unread": self.read_summary.unread,
+            "mention_count": self.read_summary.mention_count,
+            "channels": channels,
+            "roles": roles,
+            "member": {
+                "user_id": m.user_id.to_string(),
+                "nick": m.nick,
+                "avatar_hash": m.avatar_hash,
+                "joined_at": m.joined_at.to_rfc3339(),
+                "deaf": m.deaf,
+                "mute": m.mute,
+                "communication_disabled_until": m.communication_disabled_until.map(|t| t.to_rfc3339()),
+                "roles": self.member_role_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>(),
+            },
+        })
+    }
+}
+
+/// Fetch every guild `user_id` can see, with its channels, roles, the
+/// user's membership and its unread summary, for READY and the REST bootstrap.
+///
+/// Runs a fixed number of queries regardless of how many guilds the user is
+/// in; permissions are then computed in memory the same way as
+/// [`permissions::compute_all_channel_permissions`].
+pub async fn build_ready_payload(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<ReadyGuild>, CoreError> {
+    let (guilds, memberships, member_counts, roles, user_roles, channels, overwrites, read_states) =
+        tokio::try_join!(
+            paracord_db::guilds::get_user_guilds(pool, user_id),
+            paracord_db::members::get_user_memberships(pool, user_id),
+            paracord_db::members::get_user_guild_member_counts(pool, user_id),
+            paracord_db::roles::get_user_guild_roles(pool, user_id),
+            paracord_db::roles::get_user_all_roles(pool, user_id),
+            paracord_db::channels::get_user_guild_channels(pool, user_id),
+            paracord_db::channel_overwrites::get_user_guild_overwrites(pool, user_id),
+            paracord_db::read_states::get_user_read_states(pool, user_id),
+        )?;
+
+    let mut members: HashMap<i64, paracord_db::members::MemberRow> = memberships
+        .into_iter()
+        .map(|row| (row.guild_id, row.member))
+        .collect();
+    let member_counts: HashMap<i64, i64> = member_counts.into_iter().collect();
+    let assigned_role_ids: HashSet<i64> = user_roles.iter().map(|r| r.id).collect();
+
+    let mut roles_by_guild: HashMap<i64, Vec<paracord_db::roles::RoleRow>> = HashMap::new();
+    for role in roles {
+        roles_by_guild.entry(role.space_id).or_default().push(role);
+    }
+    let mut channels_by_guild: HashMap<i64, Vec<paracord_db::channels::ChannelRow>> =
+        HashMap::new();
+    for channel in channels {
+        if let Some(space_id) = channel.space_id {
+            channels_by_guild.entry(space_id).or_default().push(channel);
+        }
+    }
+    let mut overwrites_by_channel: HashMap<
+        i64,
+        Vec<paracord_db::channel_overwrites::ChannelOverwriteRow>,
+    > = HashMap::new();
+    for overwrite in overwrites {
+        overwrites_by_channel
+            .entry(overwrite.channel_id)
+            .or_default()
+            .push(overwrite);
+    }
+
+    let mut ready = Vec::with_capacity(guilds.len());
+    for guild in guilds {
+        let Some(member) = members.remove(&guild.id) else {
+            continue;
+        };
+        let roles = roles_by_guild.remove(&guild.id).unwrap_or_default();
+        // Same membership rule as `roles::get_member_roles`: assigned roles
+        // plus the implicit @everyone role, whose id is the guild id.
+        let member_roles: Vec<paracord_db::roles::RoleRow> = roles
+            .iter()
+            .filter(|r| r.id == guild.id || assigned_role_ids.contains(&r.id))
+            .cloned()
+            .collect();
+        let role_ids: HashSet<i64> = member_roles.iter().map(|r| r.id).collect();
+        let base_perms =
+            permissions::compute_permissions_from_roles(&member_roles, guild.owner_id, user_id);
+        let bypass = user_id == guild.owner_id || base_perms.contains(Permissions::ADMINISTRATOR);
+
+        let channels = channels_by_guild
+            .remove(&guild.id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|channel| {
+                bypass
+                    || permissions::apply_channel_overwrites(
+                        base_perms,
+                        channel,
+                        &role_ids,
+                        overwrites_by_channel
+                            .get(&channel.id)
+                            .map(Vec::as_slice)
+                            .unwrap_or_default(),
+                        guild.id,
+                        user_id,
+                    )
+                    .contains(Permissions::VIEW_CHANNEL)
+            })
+            .collect();
+
+        ready.push(ReadyGuild {
+            member_count: member_counts.get(&guild.id).copied().unwrap_or(0),
+            member,
+            member_role_ids: member_roles.iter().map(|r| r.id).collect(),
+            roles,
+            channels,
+            guild,
+            read_summary: GuildReadSummary::default(),
+        });
+    }
+
+    let read_states: HashMap<i64, paracord_db::read_states::ReadStateRow> = read_states
+        .into_iter()
+        .map(|row| (row.channel_id, row))
+        .collect();
+    let channel_ids: Vec<i64> = ready
+        .iter()
+        .flat_map(|guild| guild.channels.iter().map(|c| c.id))
+        .collect();
+    let latest = paracord_db::messages::latest_message_ids_for_channels(pool, &channel_ids).await?;
+    for guild in &mut ready {
+        guild.read_summary =
+            GuildReadSummary::from_channels(&guild.channels, &read_states, &latest);
+    }
+    Ok(ready)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::{OVERWRITE_TARGET_MEMBER, OVERWRITE_TARGET_ROLE};
+    use paracord_models::channel::ChannelType;
+
+    const OWNER: i64 = 1;
+    const ALICE: i64 = 2;
+    const BOB: i64 = 3;
+    const CAROL: i64 = 4;
+
+    /// Reference assembly: the per-guild queries the REST routes use.
+    async fn assemble_per_guild(pool: &DbPool, user_id: i64) -> Vec<Value> {
+        let mut out = Vec::new();
+        for guild in paracord_db::guilds::get_user_guilds(pool, user_id)
+            .await
+            .unwrap()
+        {
+            let member = paracord_db::members::get_member(pool, user_id, guild.id)
+                .await
+                .unwrap()
+                .unwrap();
+            let member_count = paracord_db::members::get_member_count(pool, guild.id)
+                .await
+                .unwrap();
+            let roles = paracord_db::roles::get_guild_roles(pool, guild.id)
+                .await
+                .unwrap();
+            let member_roles = paracord_db::roles::get_member_roles(pool, user_id, guild.id)
+                .await
+                .unwrap();
+            let mut channels = Vec::new();
+            for channel in paracord_db::channels::get_guild_channels(pool, guild.id)
+                .await
+                .unwrap()
+            {
+                let perms = permissions::compute_channel_permissions(
+                    pool,
+                    guild.id,
+                    channel.id,
+                    guild.owner_id,
+                    user_id,
+                )
+                .await
+                .unwrap();
+                if perms.contains(Permissions::VIEW_CHANNEL) {
+                    channels.push(channel);
+                }
+            }
+            let mut read_summary = GuildReadSummary::default();
+            for channel in &channels {
+                if channel.channel_type == ChannelType::Category as i16 {
+                    continue;
+                }
+                let state = paracord_db::read_states::get_read_state(pool, user_id, channel.id)
+                    .await
+                    .unwrap();
+                let read_up_to = state.as_ref().map_or(0, |row| row.last_message_id);
+                read_summary.unread |= channel.last_message_id.is_some_and(|id| id > read_up_to);
+                read_summary.mention_count += state.map_or(0, |row| i64::from(row.mention_count));
+            }
+            let ready = ReadyGuild {
+                guild,
+                member_count,
+                member,
+                member_role_ids: member_roles.iter().map(|r| r.id).collect(),
+                roles,
+                channels,
+                read_summary,
+            };
+            out.push(ready.to_json());
+        }
+        out
+    }
+
+    /// Roles created in one test share a position, so their relative order
+    /// is unspecified; compare them by id.
+    fn normalize(mut guilds: Vec<Value>) -> Vec<Value> {
+        for guild in &mut guilds {
+            if let Some(roles) = guild["roles"].as_array_mut() {
+                roles.sort_by_key(|role| role["id"].as_str().unwrap_or_default().to_string());
+            }
+            if let Some(role_ids) = guild["member"]["roles"].as_array_mut() {
+                role_ids.sort_by_key(|id| id.as_str().unwrap_or_default().to_string());
+            }
+        }
+        guilds
+    }
+
+    async fn setup() -> DbPool {
+        let pool = paracord_db::create_pool("sqlite::memory:", 1)
+            .await
+            .unwrap();
+        paracord_db::run_migrations(&pool).await.unwrap();
+        for (id, name) in [
+            (OWNER, "owner"),
+            (ALICE, "alice"),
+            (BOB, "bob"),
+            (CAROL, "carol"),
+        ] {
+            paracord_db::users::create_user(
+                &pool,
+                id,
+                name,
+                1,
+                &format!("{name}@example.com"),
+                "x",
+            )
+            .await
+            .unwrap();
+        }
+
+        let view = Permissions::VIEW_CHANNEL.bits();
+        // Guild 100: a role-gated channel, a channel hidden from @everyone
+        // but opened to the mods role and to alice directly, and an admin.
+        paracord_db::guilds::create_guild(&pool, 100, "Alpha", OWNER, None)
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(&pool, 100, 100, "@everyone", view)
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(&pool, 101, 100, "mods", 0)
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(
+            &pool,
+            102,
+            100,
+            "admins",
+            Permissions::ADMINISTRATOR.bits(),
+        )
+        .await
+        .unwrap();
+        paracord_db::channels::create_channel(&pool, 110, 100, "general", 0, 0, None, None)
+            .await
+            .unwrap();
+        paracord_db::channels::create_channel(
+            &pool,
+            111,
+            100,
+            "mods-only",
+            0,
+            1,
+            None,
+            Some("[101]"),
+        )
+        .await
+        .unwrap();
+        paracord_db::channels::create_channel(&pool, 112, 100, "hidden", 0, 2, None, None)
+            .await
+            .unwrap();
+        paracord_db::channel_overwrites::upsert_channel_overwrite(
+            &pool,
+            112,
+            100,
+            OVERWRITE_TARGET_ROLE,
+            0,
+            view,
+        )
+        .await
+        .unwrap();
+        paracord_db::channel_overwrites::upsert_channel_overwrite(
+            &pool,
+            112,
+            101,
+            OVERWRITE_TARGET_ROLE,
+            view,
+            0,
+        )
+        .await
+        .unwrap();
+        paracord_db::channel_overwrites::upsert_channel_overwrite(
+            &pool,
+            112,
+            ALICE,
+            OVERWRITE_TARGET_MEMBER,
+            view,
+            0,
+        )
+        .await
+        .unwrap();
+        for user in [OWNER, ALICE, BOB, CAROL] {
+            paracord_db::members::add_member(&pool, user, 100)
+                .await
+                .unwrap();
+        }
+        paracord_db::roles::add_member_role(&pool, BOB, 100, 101)
+            .await
+            .unwrap();
+        paracord_db::roles::add_member_role(&pool, CAROL, 100, 102)
+            .await
+            .unwrap();
+
+        // Guild 200 is only visible to holders of role 201.
+        paracord_db::guilds::create_guild(&pool, 200, "Beta", OWNER, None)
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(&pool, 200, 200, "@everyone", view)
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(&pool, 201, 200, "insiders", 0)
+            .await
+            .unwrap();
+        paracord_db::guilds::update_space_visibility(&pool, 200, "roles", "[201]")
+            .await
+            .unwrap();
+        paracord_db::channels::create_channel(&pool, 210, 200, "lobby", 0, 0, None, None)
+            .await
+            .unwrap();
+        for user in [OWNER, ALICE, BOB] {
+            paracord_db::members::add_member(&pool, user, 200)
+                .await
+                .unwrap();
+        }
+        paracord_db::roles::add_member_role(&pool, BOB, 200, 201)
+            .await
+            .unwrap();
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn batched_ready_matches_per_guild_assembly() {
+        let pool = setup().await;
+        for user in [OWNER, ALICE, BOB, CAROL] {
+            let batched: Vec<Value> = build_ready_payload(&pool, user)
+                .await
+                .unwrap()
+                .iter()
+                .map(ReadyGuild::to_json)
+                .collect();
+            assert_eq!(
+                normalize(batched),
+                normalize(assemble_per_guild(&pool, user).await),
+                "user {user}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn ready_payload_filters_guilds_and_channels() {
+        let pool = setup().await;
+        let channel_ids =
+            |guild: &ReadyGuild| -> Vec<i64> { guild.channels.iter().map(|c| c.id).collect() };
+
+        let alice = build_ready_payload(&pool, ALICE).await.unwrap();
+        assert_eq!(alice.len(), 1, "alice lacks the role guild 200 requires");
+        assert_eq!(channel_ids(&alice[0]), vec![110, 112]);
+        assert_eq!(alice[0].member_role_ids, vec![100]);
+        assert_eq!(alice[0].member_count, 4);
+
+        let bob = build_ready_payload(&pool, BOB).await.unwrap();
+        assert_eq!(bob.len(), 2);
+        assert_eq!(channel_ids(&bob[0]), vec![110, 111, 112]);
+        assert_eq!(bob[1].member_count, 3);
+
+        let carol = build_ready_payload(&pool, CAROL).await.unwrap();
+        assert_eq!(channel_ids(&carol[0]), vec![110, 111, 112]);
+    }
+
+    #[tokio::test]
+    async fn guild_mention_count_sums_visible_channels() {
+        let pool = setup().await;
+        for (id, channel) in [(1000, 110), (1001, 111), (1002, 112)] {
+            paracord_db::messages::create_message(&pool, id, channel, OWNER, "hi", 0, None)
+                .await
+                .unwrap();
+        }
+        for (user, channel, mentions) in [
+            (BOB, 110, 2),
+            (BOB, 111, 1),
+            (BOB, 112, 4),
+            (ALICE, 110, 3),
+            // Alice cannot view 111, so its mentions stay out of her total.
+            (ALICE, 111, 5),
+        ] {
+            for _ in 0..mentions {
+                paracord_db::read_states::increment_mention_count(&pool, user, channel)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let bob = build_ready_payload(&pool, BOB).await.unwrap();
+        let mut channel_mentions = 0;
+        for channel in &bob[0].channels {
+            channel_mentions += paracord_db::read_states::get_read_state(&pool, BOB, channel.id)
+                .await
+                .unwrap()
+                .map_or(0, |row| i64::from(row.mention_count));
+        }
+        assert_eq!(bob[0].read_summary.mention_count, channel_mentions);
+        assert_eq!(bob[0].read_summary.mention_count, 7);
+        assert!(bob[0].read_summary.unread);
+        assert_eq!(bob[1].read_summary, GuildReadSummary::default());
+
+        let alice = build_ready_payload(&pool, ALICE).await.unwrap();
+        assert_eq!(alice[0].read_summary.mention_count, 3);
+
+        // Reading every channel clears the unread flag; mentions reset with it.
+        for channel in [110, 111, 112] {
+            paracord_db::read_states::update_read_state(&pool, BOB, channel, 1002)
+                .await
+                .unwrap();
+        }
+        let bob = build_ready_payload(&pool, BOB).await.unwrap();
+        assert_eq!(bob[0].read_summary, GuildReadSummary::default());
+    }
+}
@@ -0,0 +1,241 @@
+//! Guild templates: a snapshot of a guild's roles and channel layout that new
+//! guilds can be created from. Messages, members, emojis and role icons are
+//! never copied.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+use crate::permissions::OVERWRITE_TARGET_ROLE;
+use paracord_db::DbPool;
+use paracord_models::permissions::Permissions;
+
+/// Roles and channels reference each other by template-local ids so a
+/// snapshot never leaks the source guild's snowflakes into the new guild.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuildTemplateSnapshot {
+    /// Permissions of the default Member role.
+    pub default_permissions: i64,
+    pub roles: Vec<TemplateRole>,
+    /// Categories come before the channels nested under them.
+    pub channels: Vec<TemplateChannel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateRole {
+    pub id: u32,
+    pub name: String,
+    pub permissions: i64,
+    pub color: i32,
+    pub hoist: bool,
+    pub mentionable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateChannel {
+    pub id: u32,
+    pub name: String,
+    pub topic: Option<String>,
+    pub channel_type: i16,
+    pub position: i32,
+    pub parent_id: Option<u32>,
+    #[serde(default)]
+    pub required_role_ids: Vec<u32>,
+    #[serde(default)]
+    pub permission_overwrites: Vec<TemplateOverwrite>,
+}
+
+/// A role overwrite; `role_id: None` targets the default Member role.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateOverwrite {
+    pub role_id: Option<u32>,
+    pub allow: i64,
+    pub deny: i64,
+}
+
+/// Snapshot `guild_id`'s structure. Callers check MANAGE_GUILD first.
+pub async fn snapshot_guild(
+    pool: &DbPool,
+    guild_id: i64,
+) -> Result<GuildTemplateSnapshot, CoreError> {
+    let mut default_permissions = Permissions::default().bits();
+    let mut roles = Vec::new();
+    let mut role_ids: HashMap<i64, u32> = HashMap::new();
+    for role in paracord_db::roles::get_guild_roles(pool, guild_id).await? {
+        if role.id == guild_id {
+            default_permissions = role.permissions;
+            continue;
+        }
+        // Managed roles belong to installed bots and are recreated on install.
+        if role.managed {
+            continue;
+        }
+        let id = role_ids.len() as u32 + 1;
+        role_ids.insert(role.id, id);
+        roles.push(TemplateRole {
+            id,
+            name: role.name,
+            permissions: role.permissions,
+            color: role.color,
+            hoist: role.hoist,
+            mentionable: role.mentionable,
+        });
+    }
+
+    // Threads and forum posts carry thread metadata and hold conversation,
+    // not structure.
+    let mut source_channels: Vec<_> = paracord_db::channels::get_guild_channels(pool, guild_id)
+        .await?
+        .into_iter()
+        .filter(|c| c.thread_metadata.is_none())
+        .collect();
+    source_channels.sort_by_key(|c| (c.parent_id.is_some(), c.position, c.id));
+
+    let channel_ids: HashMap<i64, u32> = source_channels
+        .iter()
+        .enumerate()
+        .map(|(index, c)| (c.id, index as u32 + 1))
+        .collect();
+    let overwrites = paracord_db::channel_overwrites::get_overwrites_for_channels(
+        pool,
+        &source_channels.iter().map(|c| c.id).collect::<Vec<_>>(),
+    )
+    .await?;
+
+    let channels = source_channels
+        .iter()
+        .map(|c| TemplateChannel {
+            id: channel_ids[&c.id],
+            name: c.name.clone().unwrap_or_default(),
+            topic: c.topic.clone(),
+            channel_type: c.channel_type,
+            position: c.position,
+            parent_id: c.parent_id.and_then(|id| channel_ids.get(&id).copied()),
+            required_role_ids: paracord_db::channels::parse_required_role_ids(&c.required_role_ids)
+                .iter()
+                .filter_map(|id| role_ids.get(id).copied())
+                .collect(),
+            // Member overwrites name specific users and do not carry over.
+            permission_overwrites: overwrites
+                .iter()
+                .filter(|o| o.channel_id == c.id && o.target_type == OVERWRITE_TARGET_ROLE)
+                .filter_map(|o| {
+                    let role_id = if o.target_id == guild_id {
+                        None
+                    } else {
+                        Some(*role_ids.get(&o.target_id)?)
+                    };
+                    Some(TemplateOverwrite {
+                        role_id,
+                        allow: o.allow_perms,
+                        deny: o.deny_perms,
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(GuildTemplateSnapshot {
+        default_permissions,
+        roles,
+        channels,
+    })
+}
+
+/// Create a guild owned by `owner_id` with the snapshot's roles and channels.
+pub async fn create_guild_from_template(
+    pool: &DbPool,
+    guild_id: i64,
+    name: &str,
+    owner_id: i64,
+    icon_hash: Option<&str>,
+    snapshot: &GuildTemplateSnapshot,
+) -> Result<paracord_db::guilds::GuildRow, CoreError> {
+    let guild =
+        paracord_db::guilds::create_guild(pool, guild_id, name, owner_id, icon_hash).await?;
+    paracord_db::members::add_member(pool, owner_id, guild_id).await?;
+
+    // The default Member role keeps its id = guild id convention.
+    paracord_db::roles::create_role(
+        pool,
+        guild_id,
+        guild_id,
+        "Member",
+        snapshot.default_permissions,
+    )
+    .await?;
+    paracord_db::roles::add_member_role(pool, owner_id, guild_id, guild_id).await?;
+
+    let mut role_ids: HashMap<u32, i64> = HashMap::new();
+    for role in &snapshot.roles {
+        let role_id = paracord_util::snowflake::generate(1);
+        paracord_db::roles::create_role(pool, role_id, guild_id, &role.name, role.permissions)
+            .await?;
+        paracord_db::roles::update_role(
+            pool,
+            role_id,
+            None,
+            Some(role.color),
+            Some(role.hoist),
+            None,
+            Some(role.mentionable),
+        )
+        .await?;
+        role_ids.insert(role.id, role_id);
+    }
+
+    let mut channel_ids: HashMap<u32, i64> = HashMap::new();
+    for channel in &snapshot.channels {
+        let channel_id = paracord_util::snowflake::generate(1);
+        let parent_id = match channel.parent_id {
+            Some(parent) => Some(*channel_ids.get(&parent).ok_or_else(|| {
+                CoreError::BadRequest("template channel parent is out of order".into())
+            })?),
+            None => None,
+        };
+        let required_role_ids: Vec<i64> = channel
+            .required_role_ids
+            .iter()
+            .filter_map(|id| role_ids.get(id).copied())
+            .collect();
+        let required_role_ids = (!required_role_ids.is_empty())
+            .then(|| paracord_db::channels::serialize_required_role_ids(&required_role_ids));
+        paracord_db::channels::create_channel(
+            pool,
+            channel_id,
+            guild_id,
+            &channel.name,
+            channel.channel_type,
+            channel.position,
+            parent_id,
+            required_role_ids.as_deref(),
+        )
+        .await?;
+        if let Some(topic) = channel.topic.as_deref() {
+            paracord_db::channels::update_channel(pool, channel_id, None, Some(topic), None)
+                .await?;
+        }
+        for overwrite in &channel.permission_overwrites {
+            let target_id = match overwrite.role_id {
+                None => guild_id,
+                Some(id) => match role_ids.get(&id) {
+                    Some(role_id) => *role_id,
+                    None => continue,
+                },
+            };
+            paracord_db::channel_overwrites::upsert_channel_overwrite(
+                pool,
+                channel_id,
+                target_id,
+                OVERWRITE_TARGET_ROLE,
+                overwrite.allow,
+                overwrite.deny,
+            )
+            .await?;
+        }
+        channel_ids.insert(channel.id, channel_id);
+    }
+
+    Ok(guild)
+}
@@ -17,9 +17,71 @@ fn generate_interaction_token() -> String {
     out
 }
 
+/// Store an interaction response message. Ephemeral responses are scoped to
+/// the invoking user so they never show up in anyone else's channel history.
+pub async fn create_response_message(
+    state: &AppState,
+    token_row: &paracord_db::interaction_tokens::InteractionTokenRow,
+    author_id: i64,
+    content: &str,
+    flags: i32,
+) -> Result<paracord_db::messages::MessageRow, CoreError> {
+    let message_id = paracord_util::snowflake::generate(1);
+    // Message type 20 = ChatInputCommand (interaction response)
+    let msg = if flags & crate::MESSAGE_FLAG_EPHEMERAL != 0 {
+        paracord_db::messages::create_ephemeral_message(
+            &state.db,
+            message_id,
+            token_row.channel_id,
+            author_id,
+            content,
+            20,
+            flags,
+            token_row.user_id,
+        )
+        .await
+    } else {
+        paracord_db::messages::create_message_with_meta(
+            &state.db,
+            message_id,
+            token_row.channel_id,
+            author_id,
+            content,
+            20,
+            None,
+            flags,
+            None,
+            None,
+        )
+        .await
+    };
+    msg.map_err(|e| CoreError::Internal(e.to_string()))
+}
+
+/// Dispatch a gateway event about an interaction response message. Ephemeral
+/// messages only go to the invoking user's sessions.
+pub fn dispatch_response_event(
+    state: &AppState,
+    event_type: &str,
+    payload: Value,
+    token_row: &paracord_db::interaction_tokens::InteractionTokenRow,
+    flags: i32,
+) {
+    if flags & crate::MESSAGE_FLAG_EPHEMERAL != 0 {
+        state
+            .event_bus
+            .dispatch_to_users(event_type, payload, vec![token_row.user_id]);
+    } else {
+        state
+            .event_bus
+            .dispatch(event_type, payload, token_row.guild_id);
+    }
+}
+
 /// Create an interaction, store its token, and dispatch INTERACTION_CREATE to the bot.
 ///
 /// Returns `(interaction_json, raw_token)` so the caller can return the token to the invoking user.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_interaction(
     state: &AppState,
     application_id: i64,
@@ -96,12 +158,10 @@ pub async fn resolve_slash_command(
     command_name: &str,
     guild_id: i64,
 ) -> Result<Option<paracord_db::application_commands::ApplicationCommandRow>, CoreError> {
-    let available = paracord_db::application_commands::list_guild_available_commands(
-        &state.db,
-        guild_id,
-    )
-    .await
-    .map_err(|e| CoreError::Internal(e.to_string()))?;
+    let available =
+        paracord_db::application_commands::list_guild_available_commands(&state.db, guild_id)
+            .await
+            .map_err(|e| CoreError::Internal(e.to_string()))?;
 
     Ok(available.into_iter().find(|cmd| cmd.name == command_name))
 }
@@ -171,10 +231,7 @@ pub async fn process_interaction_response(
             let data = callback_data.ok_or_else(|| {
                 CoreError::BadRequest("callback data required for message response".into())
             })?;
-            let content = data
-                .get("content")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
+            let content = data.get("content").and_then(|v| v.as_str()).unwrap_or("");
 
             // 3. Validate content length (same limits as regular messages)
             const MAX_MESSAGE_CONTENT_LEN: usize = 4_000;
@@ -187,36 +244,26 @@ pub async fn process_interaction_response(
 
             let components_json = data
                 .get("components")
-                .map(|v| serde_json::to_string(v))
+                .map(serde_json::to_string)
                 .transpose()
                 .map_err(|e| CoreError::Internal(format!("serialize components: {e}")))?;
             let embeds_json = data
                 .get("embeds")
-                .map(|v| serde_json::to_string(v))
+                .map(serde_json::to_string)
                 .transpose()
                 .map_err(|e| CoreError::Internal(format!("serialize embeds: {e}")))?;
-            let flags = data
-                .get("flags")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32;
+            let flags = data.get("flags").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
 
-            let message_id = paracord_util::snowflake::generate(1);
-            // Message type 20 = ChatInputCommand (interaction response)
-            let msg = paracord_db::messages::create_message_with_meta(
-                &state.db,
-                message_id,
-                token_row.channel_id,
-                author_id,
-                content,
-                20, // APPLICATION_COMMAND message type
-                None,
-                flags,
-                None,
-                None,
-                components_json.as_deref(),
-            )
-            .await
-            .map_err(|e| CoreError::Internal(e.to_string()))?;
+            let msg = create_response_message(state, token_row, author_id, content, flags).await?;
+            if components_json.is_some() {
+                paracord_db::messages::set_message_components(
+                    &state.db,
+                    msg.id,
+                    components_json.as_deref(),
+                )
+                .await
+                .map_err(|e| CoreError::Internal(e.to_string()))?;
+            }
 
             // Store the response message ID on the token for edit/delete later
             let _ = paracord_db::interaction_tokens::update_response_message_id(
@@ -243,12 +290,12 @@ pub async fn process_interaction_response(
                 "created_at": msg.created_at.to_rfc3339(),
             });
 
-            // Dispatch MESSAGE_CREATE
-            let guild_id = token_row.guild_id;
-            state.event_bus.dispatch(
+            dispatch_response_event(
+                state,
                 "MESSAGE_CREATE",
                 msg_json.clone(),
-                guild_id,
+                token_row,
+                msg.flags,
             );
 
             Ok(Some(msg_json))
@@ -256,18 +303,12 @@ pub async fn process_interaction_response(
         // DEFERRED_CHANNEL_MESSAGE_WITH_SOURCE (5) - acknowledge, bot will edit later
         5 => {
             // Create a placeholder message (type 20) so there's something to edit later
-            let message_id = paracord_util::snowflake::generate(1);
-            let msg = paracord_db::messages::create_message(
-                &state.db,
-                message_id,
-                token_row.channel_id,
-                author_id,
-                "",
-                20, // APPLICATION_COMMAND message type
-                None,
-            )
-            .await
-            .map_err(|e| CoreError::Internal(e.to_string()))?;
+            // Deferred responses may ask to be ephemeral up front.
+            let flags = callback_data
+                .and_then(|d| d.get("flags"))
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0) as i32;
+            let msg = create_response_message(state, token_row, author_id, "", flags).await?;
 
             // Store the response message ID on the token for edit/delete later
             let _ = paracord_db::interaction_tokens::update_response_message_id(
@@ -283,7 +324,7 @@ pub async fn process_interaction_response(
                 "author_id": msg.author_id.to_string(),
                 "content": "",
                 "message_type": 20,
-                "flags": 0,
+                "flags": msg.flags,
                 "interaction": {
                     "id": interaction_id.to_string(),
                     "type": token_row.interaction_type,
@@ -292,10 +333,12 @@ pub async fn process_interaction_response(
                 "created_at": msg.created_at.to_rfc3339(),
             });
 
-            state.event_bus.dispatch(
+            dispatch_response_event(
+                state,
                 "MESSAGE_CREATE",
                 msg_json.clone(),
-                token_row.guild_id,
+                token_row,
+                msg.flags,
             );
 
             Ok(Some(msg_json))
@@ -349,10 +392,7 @@ pub async fn process_interaction_response(
             let data = callback_data.ok_or_else(|| {
                 CoreError::BadRequest("callback data required for update message response".into())
             })?;
-            let content = data
-                .get("content")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
+            let content = data.get("content").and_then(|v| v.as_str()).unwrap_or("");
 
             // 3. Validate content length (same limits as regular messages)
             const MAX_MESSAGE_CONTENT_LEN: usize = 4_000;
@@ -368,7 +408,7 @@ pub async fn process_interaction_response(
                 CoreError::BadRequest("no original response message to update".into())
             })?;
 
-            let updated = paracord_db::messages::update_message_unchecked(&state.db, msg_id, content)
+            let updated = paracord_db::messages::update_message(&state.db, msg_id, content)
                 .await
                 .map_err(|e| CoreError::Internal(e.to_string()))?;
 
@@ -382,10 +422,12 @@ pub async fn process_interaction_response(
                 "created_at": updated.created_at.to_rfc3339(),
             });
 
-            state.event_bus.dispatch(
+            dispatch_response_event(
+                state,
                 "MESSAGE_UPDATE",
                 msg_json.clone(),
-                token_row.guild_id,
+                token_row,
+                updated.flags,
             );
 
             Ok(Some(msg_json))
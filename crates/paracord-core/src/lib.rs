@@ -5,12 +5,21 @@ pub mod channel;
 pub mod error;
 pub mod events;
 pub mod guild;
+pub mod guild_template;
 pub mod identity;
+pub mod interactions;
+pub mod link_unfurl;
+pub mod mail;
 pub mod member_index;
 pub mod message;
+pub mod name_filter;
 pub mod observability;
 pub mod permissions;
 pub mod presence_manager;
+pub mod push;
+pub mod rate_limit;
+pub mod typing;
+pub mod usage;
 pub mod user;
 
 use paracord_db::DbPool;
@@ -22,6 +31,7 @@ use paracord_relay::room::MediaRoomManager;
 use paracord_relay::speaker::SpeakerDetector;
 use paracord_transport::endpoint::MediaEndpoint;
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::{Notify, RwLock};
 
@@ -29,8 +39,25 @@ use tokio::sync::{Notify, RwLock};
 pub const USER_FLAG_ADMIN: i32 = 1 << 0;
 /// Bit flag: user is a bot account.
 pub const USER_FLAG_BOT: i32 = 1 << 1;
+/// Bit flag: user may create guilds while guild creation is restricted.
+pub const USER_FLAG_GUILD_CREATOR: i32 = 1 << 2;
 /// Bit flag: message content is DM end-to-end encrypted ciphertext.
 pub const MESSAGE_FLAG_DM_E2EE: i32 = 1 << 0;
+/// Bit flag: announcement message has been published to following channels.
+pub const MESSAGE_FLAG_CROSSPOSTED: i32 = 1 << 1;
+/// Bit flag: clients should not render link embeds for this message.
+pub const MESSAGE_FLAG_SUPPRESS_EMBEDS: i32 = 1 << 2;
+/// Bit flag: TTS-capable clients should read the message aloud. Set through
+/// the `tts` field on send, which needs `SEND_TTS_MESSAGES`.
+pub const MESSAGE_FLAG_TTS: i32 = 1 << 3;
+/// Bit flag: message is only visible to the user who invoked the interaction.
+/// Same value bots already send for ephemeral interaction responses.
+pub const MESSAGE_FLAG_EPHEMERAL: i32 = 1 << 6;
+/// Bit flag: message was sent silently and notifies nobody.
+/// Same value as Discord's `SUPPRESS_NOTIFICATIONS`.
+pub const MESSAGE_FLAG_SILENT: i32 = 1 << 12;
+/// Flags a client may set when sending a message.
+pub const MESSAGE_FLAGS_USER_SETTABLE: i32 = MESSAGE_FLAG_SUPPRESS_EMBEDS | MESSAGE_FLAG_SILENT;
 
 pub fn is_admin(flags: i32) -> bool {
     flags & USER_FLAG_ADMIN != 0
@@ -40,6 +67,11 @@ pub fn is_bot(flags: i32) -> bool {
     flags & USER_FLAG_BOT != 0
 }
 
+/// Whether a user with `flags` may create guilds. Admins always can.
+pub fn can_create_guilds(flags: i32, settings: &RuntimeSettings) -> bool {
+    !settings.guild_creation_restricted || is_admin(flags) || flags & USER_FLAG_GUILD_CREATOR != 0
+}
+
 /// Settings that can be changed at runtime via the admin dashboard.
 #[derive(Clone, Debug)]
 pub struct RuntimeSettings {
@@ -47,7 +79,15 @@ pub struct RuntimeSettings {
     pub server_name: String,
     pub server_description: String,
     pub max_guilds_per_user: u32,
+    /// Only admins and `USER_FLAG_GUILD_CREATOR` users may create guilds.
+    pub guild_creation_restricted: bool,
     pub max_members_per_guild: u32,
+    pub max_webhooks_per_channel: u32,
+    pub max_webhooks_per_guild: u32,
+    /// New password accounts must verify their email before they can log in.
+    pub require_email_verification: bool,
+    /// Blocked substrings/regexes for usernames and guild names.
+    pub name_filter: name_filter::NameFilter,
 }
 
 impl Default for RuntimeSettings {
@@ -57,7 +97,12 @@ impl Default for RuntimeSettings {
             server_name: "Paracord Server".to_string(),
             server_description: String::new(),
             max_guilds_per_user: 100,
+            guild_creation_restricted: false,
             max_members_per_guild: 1000,
+            max_webhooks_per_channel: 15,
+            max_webhooks_per_guild: 100,
+            require_email_verification: false,
+            name_filter: name_filter::NameFilter::default(),
         }
     }
 }
@@ -96,8 +141,36 @@ pub struct AppState {
     pub member_index: Arc<member_index::MemberIndex>,
     /// Deferred offline presence manager to avoid disconnect/reconnect races.
     pub presence_manager: Arc<presence_manager::PresenceManager>,
+    /// Per-user message/upload counters used for abuse detection.
+    pub usage: Arc<usage::UserUsageTracker>,
+    /// Counters for login throttling, webhook limits and REST buckets.
+    pub rate_limits: Arc<rate_limit::RateLimitStore>,
+    /// Current LiveKit reachability, refreshed by the periodic health probe.
+    /// Seeded from `AppConfig::livekit_available` at startup.
+    pub livekit_online: Arc<AtomicBool>,
     /// Native QUIC media relay state (None when using LiveKit).
     pub native_media: Option<NativeMediaState>,
+    /// Server-side link previews (None when link unfurling is disabled).
+    pub link_unfurler: Option<Arc<link_unfurl::LinkUnfurler>>,
+    /// Web Push delivery for offline users (None when push is disabled).
+    pub push_notifier: Option<Arc<push::PushNotifier>>,
+    /// Outbound email for account flows (None when no mail transport is set).
+    pub mailer: Option<Arc<mail::Mailer>>,
+    /// Last `/readyz` body and when it was taken, reused briefly so frequent
+    /// probes don't each hit the database and storage.
+    pub readiness_cache: Arc<tokio::sync::Mutex<Option<(std::time::Instant, serde_json::Value)>>>,
+}
+
+impl AppState {
+    /// Whether LiveKit is currently reachable.
+    pub fn livekit_available(&self) -> bool {
+        self.livekit_online.load(Ordering::Relaxed)
+    }
+
+    /// Update LiveKit reachability. Returns `true` if the value changed.
+    pub fn set_livekit_available(&self, available: bool) -> bool {
+        self.livekit_online.swap(available, Ordering::Relaxed) != available
+    }
 }
 
 /// State for the native QUIC-based media server.
@@ -120,16 +193,33 @@ pub struct AppConfig {
     pub registration_enabled: bool,
     pub allow_username_login: bool,
     pub require_email: bool,
+    /// Usernames are unique handles and every account has discriminator 0.
+    pub unique_usernames: bool,
+    /// Consecutive failed password verifications allowed per source IP and
+    /// login identifier before further logins get 429. 0 = no lockout.
+    pub login_max_failures: u32,
+    /// How long, in seconds, a locked-out IP and identifier pair waits.
+    pub login_lockout_seconds: u64,
+    /// Sessions with no activity for this many seconds stop authenticating,
+    /// independent of their absolute expiry. 0 = no idle expiry.
+    pub session_idle_timeout_seconds: u64,
     pub storage_path: String,
     pub max_upload_size: u64,
+    /// LiveKit credentials at startup. Admins can rotate them at runtime;
+    /// the live values are on `VoiceManager::livekit()`.
     pub livekit_api_key: String,
     pub livekit_api_secret: String,
     pub livekit_url: String,
     pub livekit_http_url: String,
     /// The LiveKit URL sent to clients. Falls back to `livekit_url` if not set.
     pub livekit_public_url: String,
-    /// Whether a LiveKit server is available for voice/video.
+    /// Whether a LiveKit server was available for voice/video at startup.
+    /// Runtime reachability lives in `AppState::livekit_available()`.
     pub livekit_available: bool,
+    /// Whether voice channels may be recorded through LiveKit Egress.
+    pub livekit_recording_enabled: bool,
+    /// Egress output path template for recordings.
+    pub livekit_recording_filepath: String,
     /// The public URL of this server (e.g., https://chat.example.com).
     /// Used for CORS auto-configuration and invite links.
     pub public_url: Option<String>,
@@ -137,12 +227,21 @@ pub struct AppConfig {
     pub media_max_file_size: u64,
     pub media_p2p_threshold: u64,
     pub file_cryptor: Option<paracord_util::at_rest::FileCryptor>,
+    /// Seals secrets kept in the database (TOTP seeds) when at-rest
+    /// encryption is enabled.
+    pub secret_cryptor: Option<paracord_util::at_rest::SecretCryptor>,
     pub backup_dir: String,
     pub database_url: String,
     /// Per-peer rate limit for inbound federation events (per minute). None = no limit.
     pub federation_max_events_per_peer_per_minute: Option<u32>,
     /// Per-peer rate limit for remote user creation (per hour). None = no limit.
     pub federation_max_user_creates_per_peer_per_hour: Option<u32>,
+    /// Executions allowed per webhook per minute, across every source address.
+    pub webhook_executions_per_minute: u32,
+    /// Lifetime of voice tokens issued to federated peers, in seconds.
+    pub federation_media_token_ttl_seconds: u64,
+    /// Lifetime of single-use file download tokens issued to federated peers, in seconds.
+    pub federation_file_token_ttl_seconds: u64,
     /// Whether the native QUIC media server is enabled.
     pub native_media_enabled: bool,
     /// UDP port for the unified QUIC media endpoint (raw QUIC + WebTransport).
@@ -153,10 +252,57 @@ pub struct AppConfig {
     pub native_media_e2ee_required: bool,
     /// Maximum storage quota per guild in bytes.
     pub max_guild_storage_quota: u64,
+    /// Maximum attachments linked to one message. 0 = unlimited.
+    pub max_attachments_per_message: u32,
+    /// Maximum combined attachment size in bytes for one message. 0 = unlimited.
+    pub max_attachment_bytes_per_message: u64,
+    /// Longest attachment filename kept, in characters.
+    pub max_attachment_filename_length: u32,
+    /// Unlinked, unexpired uploads one user may hold at once. 0 = unlimited.
+    pub max_pending_uploads_per_user: u32,
+    /// Origins besides `public_url` whose pages may show attachments inline.
+    /// When non-empty, downloads whose `Origin`/`Referer` names another site
+    /// are served as `attachment`. Empty = no origin check.
+    pub attachment_inline_origins: Vec<String>,
+    /// When non-zero, attachments are only served inline with a `token`
+    /// from the embed-token route, valid for this many seconds.
+    pub attachment_inline_token_ttl_seconds: u64,
+    /// Log every attachment download (user, attachment, referrer).
+    pub log_attachment_access: bool,
     /// Whether federation file caching is enabled.
     pub federation_file_cache_enabled: bool,
     /// Maximum size of the federation file cache in bytes.
     pub federation_file_cache_max_size: u64,
     /// TTL for cached federation files in hours.
     pub federation_file_cache_ttl_hours: u64,
+    /// Origin that serves media (e.g. `https://cdn.example.com`). When set,
+    /// returned attachment, emoji and role icon URLs point at it instead of
+    /// being server-relative.
+    pub media_cdn_base_url: Option<String>,
+    /// Gateway payloads up to this many bytes go out as uncompressed text
+    /// frames even on `zlib-stream` connections.
+    pub gateway_compression_threshold_bytes: usize,
+}
+
+impl AppConfig {
+    /// Resolve a server-relative media path against `media_cdn_base_url`.
+    /// Query strings (cache-busting versions, signatures) are kept as-is;
+    /// absolute URLs are returned unchanged.
+    pub fn media_url(&self, path: &str) -> String {
+        match self.media_cdn_base_url.as_deref() {
+            Some(base) if path.starts_with('/') && !path.starts_with("//") => {
+                format!("{}{}", base.trim_end_matches('/'), path)
+            }
+            _ => path.to_string(),
+        }
+    }
+
+    /// Sessions last seen at or before the returned instant are idle-expired.
+    pub fn session_idle_cutoff(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        (self.session_idle_timeout_seconds > 0)
+            .then(|| now - chrono::Duration::seconds(self.session_idle_timeout_seconds as i64))
+    }
 }
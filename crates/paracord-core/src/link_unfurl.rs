@@ -0,0 +1,352 @@
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+const MAX_TITLE_CHARS: usize = 256;
+const MAX_DESCRIPTION_CHARS: usize = 350;
+const MAX_SITE_NAME_CHARS: usize = 256;
+const MAX_CACHED_LINKS: u64 = 10_000;
+/// Bytes of preview images kept in memory.
+const MAX_CACHED_IMAGE_BYTES: u64 = 64 * 1024 * 1024;
+
+pub type LinkFetchFuture = Pin<Box<dyn Future<Output = Result<String, String>> + Send>>;
+/// Fetches the HTML behind a link. Called only with URLs that already passed
+/// the caller's outbound URL checks; implementations must re-check the
+/// resolved addresses and enforce their own size and time limits.
+pub type LinkFetchFn = Arc<dyn Fn(String) -> LinkFetchFuture + Send + Sync>;
+
+/// A preview image as served to clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkImage {
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+pub type ImageFetchFuture = Pin<Box<dyn Future<Output = Result<LinkImage, String>> + Send>>;
+/// Fetches a preview image, under the same rules as [`LinkFetchFn`].
+pub type ImageFetchFn = Arc<dyn Fn(String) -> ImageFetchFuture + Send + Sync>;
+
+/// A link preview embed, in the shape clients already render for `embeds`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkPreview {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    /// Absolute `http(s)` image URL from `og:image`. Callers swap it for a
+    /// link to their image proxy before handing the embed to clients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+}
+
+/// Builds link preview embeds from OpenGraph metadata. Results, including
+/// links that produced no preview, are cached per URL so a popular link is
+/// fetched once per TTL no matter how many messages contain it. Preview
+/// images are fetched and cached the same way, so clients load them from the
+/// server instead of the linked site.
+pub struct LinkUnfurler {
+    fetch: LinkFetchFn,
+    cache: moka::future::Cache<String, Option<LinkPreview>>,
+    fetch_image: Option<ImageFetchFn>,
+    images: moka::future::Cache<String, Option<Arc<LinkImage>>>,
+}
+
+impl LinkUnfurler {
+    pub fn new(fetch: LinkFetchFn, cache_ttl: Duration) -> Self {
+        Self {
+            fetch,
+            cache: moka::future::Cache::builder()
+                .max_capacity(MAX_CACHED_LINKS)
+                .time_to_live(cache_ttl)
+                .build(),
+            fetch_image: None,
+            images: moka::future::Cache::builder()
+                .max_capacity(MAX_CACHED_IMAGE_BYTES)
+                .weigher(|_, image: &Option<Arc<LinkImage>>| {
+                    image
+                        .as_ref()
+                        .map_or(1, |i| u32::try_from(i.body.len()).unwrap_or(u32::MAX))
+                })
+                .time_to_live(cache_ttl)
+                .build(),
+        }
+    }
+
+    /// Serve preview images through [`Self::image`]. Without an image
+    /// fetcher previews carry no thumbnail.
+    pub fn with_image_fetcher(mut self, fetch_image: ImageFetchFn) -> Self {
+        self.fetch_image = Some(fetch_image);
+        self
+    }
+
+    pub fn proxies_images(&self) -> bool {
+        self.fetch_image.is_some()
+    }
+
+    /// Preview embed for `url`, or `None` if the page could not be fetched
+    /// or has no usable metadata. Concurrent calls for the same URL share
+    /// one fetch.
+    pub async fn unfurl(&self, url: &str) -> Option<LinkPreview> {
+        let fetch = self.fetch.clone();
+        let owned = url.to_string();
+        self.cache
+            .get_with(url.to_string(), async move {
+                match fetch(owned.clone()).await {
+                    Ok(html) => parse_link_preview(&owned, &html),
+                    Err(e) => {
+                        tracing::debug!("link unfurl: {} failed: {}", owned, e);
+                        None
+                    }
+                }
+            })
+            .await
+    }
+
+    /// The preview image at `url`, or `None` if images are not proxied or
+    /// the fetch failed. Cached like [`Self::unfurl`].
+    pub async fn image(&self, url: &str) -> Option<Arc<LinkImage>> {
+        let fetch = self.fetch_image.clone()?;
+        let owned = url.to_string();
+        self.images
+            .get_with(url.to_string(), async move {
+                match fetch(owned.clone()).await {
+                    Ok(image) => Some(Arc::new(image)),
+                    Err(e) => {
+                        tracing::debug!("link unfurl: image {} failed: {}", owned, e);
+                        None
+                    }
+                }
+            })
+            .await
+    }
+}
+
+/// Build a preview from the OpenGraph (or Twitter card) tags in `html`,
+/// falling back to `<title>` and `<meta name="description">`. Returns `None`
+/// when the page has neither a title nor a description.
+pub fn parse_link_preview(url: &str, html: &str) -> Option<LinkPreview> {
+    let lower = html.to_ascii_lowercase();
+    let head_end = lower.find("</head").unwrap_or(lower.len());
+    let lower = &lower[..head_end];
+
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut cursor = 0;
+    while let Some(found) = lower[cursor..].find("<meta") {
+        let start = cursor + found + "<meta".len();
+        let Some(len) = lower[start..].find('>') else {
+            break;
+        };
+        let attrs = parse_attributes(&html[start..start + len]);
+        let key = attrs
+            .iter()
+            .find(|(name, _)| name == "property" || name == "name")
+            .map(|(_, value)| value.to_ascii_lowercase());
+        let content = attrs
+            .iter()
+            .find(|(name, _)| name == "content")
+            .map(|(_, value)| value.clone());
+        if let (Some(key), Some(content)) = (key, content) {
+            tags.push((key, content));
+        }
+        cursor = start + len;
+    }
+    let meta = |keys: &[&str]| {
+        keys.iter().find_map(|key| {
+            tags.iter()
+                .find(|(name, value)| name == key && !value.trim().is_empty())
+                .map(|(_, value)| decode_entities(value.trim()))
+        })
+    };
+
+    let page_title = lower.find("<title").and_then(|open| {
+        let start = open + lower[open..].find('>')? + 1;
+        let end = start + lower[start..].find("</title")?;
+        Some(decode_entities(html[start..end].trim())).filter(|t| !t.is_empty())
+    });
+    let title = meta(&["og:title", "twitter:title"]).or(page_title);
+    let description = meta(&["og:description", "twitter:description", "description"]);
+    if title.is_none() && description.is_none() {
+        return None;
+    }
+
+    let thumbnail = meta(&["og:image:secure_url", "og:image", "twitter:image"])
+        .filter(|image| image.starts_with("https://") || image.starts_with("http://"));
+
+    Some(LinkPreview {
+        kind: "link",
+        url: url.to_string(),
+        title: title.map(|t| truncate_chars(&t, MAX_TITLE_CHARS)),
+        description: description.map(|d| truncate_chars(&d, MAX_DESCRIPTION_CHARS)),
+        site_name: meta(&["og:site_name"]).map(|n| truncate_chars(&n, MAX_SITE_NAME_CHARS)),
+        thumbnail,
+    })
+}
+
+/// Attributes of a tag body such as ` property="og:title" content='x' /`.
+/// Names are lowercased; values are returned raw.
+fn parse_attributes(raw: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = raw.trim_start();
+    while !rest.is_empty() {
+        let name_len = rest
+            .find(|c: char| c == '=' || c == '/' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        if name_len == 0 {
+            rest = rest[1..].trim_start();
+            continue;
+        }
+        let name = rest[..name_len].to_ascii_lowercase();
+        rest = rest[name_len..].trim_start();
+        let Some(after_eq) = rest.strip_prefix('=') else {
+            continue;
+        };
+        rest = after_eq.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let body = &rest[1..];
+                let end = body.find(quote).unwrap_or(body.len());
+                rest = body.get(end + 1..).unwrap_or("");
+                &body[..end]
+            }
+            _ => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let value = &rest[..end];
+                rest = &rest[end..];
+                value
+            }
+        };
+        attrs.push((name, value.to_string()));
+        rest = rest.trim_start();
+    }
+    attrs
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn truncate_chars(value: &str, max: usize) -> String {
+    match value.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", value[..end].trim_end()),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn parses_open_graph_tags_with_fallbacks() {
+        let html = r#"<!doctype html><html><head>
+            <title>Fallback title</title>
+            <meta property="og:title" content="Paracord &amp; friends">
+            <meta name='description' content='A self-hosted chat server'/>
+            <meta property="og:site_name" content="Example">
+            <meta property="og:image" content="https://cdn.example.com/card.png">
+            </head><body><meta property="og:title" content="ignored"></body></html>"#;
+        let embed = parse_link_preview("https://example.com/post", html).unwrap();
+        assert_eq!(embed.title.as_deref(), Some("Paracord & friends"));
+        assert_eq!(
+            embed.description.as_deref(),
+            Some("A self-hosted chat server")
+        );
+        assert_eq!(embed.url, "https://example.com/post");
+        assert_eq!(embed.site_name.as_deref(), Some("Example"));
+        assert_eq!(
+            embed.thumbnail.as_deref(),
+            Some("https://cdn.example.com/card.png")
+        );
+
+        let embed =
+            parse_link_preview("https://example.com/", "<title> Plain page </title>").unwrap();
+        assert_eq!(embed.title.as_deref(), Some("Plain page"));
+        assert!(parse_link_preview("https://example.com/", "<p>no metadata</p>").is_none());
+    }
+
+    #[test]
+    fn ignores_relative_and_non_http_images() {
+        let html = r#"<meta property="og:title" content="T">
+            <meta property="og:image" content="javascript:alert(1)">"#;
+        let embed = parse_link_preview("https://example.com/", html).unwrap();
+        assert!(embed.thumbnail.is_none());
+    }
+
+    #[tokio::test]
+    async fn caches_results_including_misses() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let unfurler = LinkUnfurler::new(
+            Arc::new(move |url: String| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    if url.ends_with("/missing") {
+                        Err("HTTP 404".to_string())
+                    } else {
+                        Ok(r#"<meta property="og:title" content="Cached">"#.to_string())
+                    }
+                }) as LinkFetchFuture
+            }),
+            Duration::from_secs(60),
+        );
+
+        for _ in 0..2 {
+            let embed = unfurler.unfurl("https://example.com/a").await.unwrap();
+            assert_eq!(embed.title.as_deref(), Some("Cached"));
+            assert!(unfurler
+                .unfurl("https://example.com/missing")
+                .await
+                .is_none());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn images_need_a_fetcher_and_are_cached() {
+        let html: LinkFetchFn = Arc::new(|_| Box::pin(async { Err("unused".to_string()) }));
+        let unfurler = LinkUnfurler::new(html.clone(), Duration::from_secs(60));
+        assert!(!unfurler.proxies_images());
+        assert!(unfurler
+            .image("https://cdn.example.com/a.png")
+            .await
+            .is_none());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let unfurler = LinkUnfurler::new(html, Duration::from_secs(60)).with_image_fetcher(
+            Arc::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async {
+                    Ok(LinkImage {
+                        content_type: "image/png".into(),
+                        body: vec![1, 2, 3],
+                    })
+                }) as ImageFetchFuture
+            }),
+        );
+        for _ in 0..2 {
+            let image = unfurler
+                .image("https://cdn.example.com/a.png")
+                .await
+                .unwrap();
+            assert_eq!(image.body, vec![1, 2, 3]);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
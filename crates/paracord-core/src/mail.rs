@@ -0,0 +1,103 @@
+//! Outbound email for account flows such as address verification and
+//! password resets.
+//!
+//! The transport is injected so the server can send over SMTP while tests
+//! capture messages. Links in messages point at the server's public URL.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// A plain-text message to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingMail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub type MailSendFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+/// Delivers one message. The sender address is the transport's concern.
+pub type MailSendFn = Arc<dyn Fn(OutgoingMail) -> MailSendFuture + Send + Sync>;
+
+pub struct Mailer {
+    public_url: String,
+    send: MailSendFn,
+}
+
+impl Mailer {
+    /// `public_url` is the origin the web client is served from.
+    pub fn new(public_url: &str, send: MailSendFn) -> Self {
+        Self {
+            public_url: public_url.trim().trim_end_matches('/').to_string(),
+            send,
+        }
+    }
+
+    /// Hand `mail` to the transport; the returned future finishes delivery.
+    pub fn send(&self, mail: OutgoingMail) -> MailSendFuture {
+        (self.send)(mail)
+    }
+
+    /// The message carrying an email verification token.
+    pub fn email_verification(&self, to: &str, server_name: &str, token: &str) -> OutgoingMail {
+        let link = format!(
+            "{}/verify-email?token={}",
+            self.public_url,
+            urlencoding::encode(token)
+        );
+        OutgoingMail {
+            to: to.to_string(),
+            subject: format!("Verify your email for {server_name}"),
+            body: format!(
+                "Confirm this address to finish creating your {server_name} account:\n\n\
+                 {link}\n\n\
+                 The link expires in {} hours. If you did not sign up, ignore this email.\n",
+                crate::auth::EMAIL_VERIFICATION_TTL_HOURS
+            ),
+        }
+    }
+
+    /// The message carrying a password reset token.
+    pub fn password_reset(&self, to: &str, server_name: &str, token: &str) -> OutgoingMail {
+        let link = format!(
+            "{}/reset-password?token={}",
+            self.public_url,
+            urlencoding::encode(token)
+        );
+        OutgoingMail {
+            to: to.to_string(),
+            subject: format!("Reset your {server_name} password"),
+            body: format!(
+                "Someone asked to reset the password for your {server_name} account. \
+                 Choose a new one here:\n\n\
+                 {link}\n\n\
+                 The link expires in {} minutes. If you did not ask for this, ignore this email; \
+                 your password has not changed.\n",
+                crate::auth::PASSWORD_RESET_TTL_MINUTES
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_mail_links_to_the_public_url() {
+        let mailer = Mailer::new(
+            "https://chat.example.com/",
+            Arc::new(|_| -> MailSendFuture { Box::pin(async { Ok(()) }) }),
+        );
+        let mail = mailer.email_verification("a@example.com", "Paracord", "a+b/c");
+        assert_eq!(mail.to, "a@example.com");
+        assert!(mail
+            .body
+            .contains("https://chat.example.com/verify-email?token=a%2Bb%2Fc"));
+        let mail = mailer.password_reset("a@example.com", "Paracord", "abc");
+        assert!(mail
+            .body
+            .contains("https://chat.example.com/reset-password?token=abc"));
+    }
+}
@@ -1,8 +1,12 @@
 use crate::error::CoreError;
 use crate::permissions;
-use crate::MESSAGE_FLAG_DM_E2EE;
+use crate::{
+    MESSAGE_FLAGS_USER_SETTABLE, MESSAGE_FLAG_DM_E2EE, MESSAGE_FLAG_SUPPRESS_EMBEDS,
+    MESSAGE_FLAG_TTS,
+};
 use paracord_db::DbPool;
 use paracord_models::permissions::Permissions;
+use std::collections::HashSet;
 
 const MAX_DM_E2EE_NONCE_LEN: usize = 128;
 const MAX_DM_E2EE_CIPHERTEXT_LEN: usize = 16_384;
@@ -76,6 +80,10 @@ pub struct CreateMessageOptions {
     pub allow_empty_content: bool,
     pub dm_e2ee: Option<DmE2eePayload>,
     pub nonce: Option<String>,
+    /// Client-requested flags; only `MESSAGE_FLAGS_USER_SETTABLE` bits.
+    pub flags: i32,
+    /// Text-to-speech message; guild channels require SEND_TTS_MESSAGES.
+    pub tts: bool,
 }
 
 impl Default for CreateMessageOptions {
@@ -86,6 +94,8 @@ impl Default for CreateMessageOptions {
             allow_empty_content: false,
             dm_e2ee: None,
             nonce: None,
+            flags: 0,
+            tts: false,
         }
     }
 }
@@ -111,6 +121,8 @@ pub async fn create_message(
             allow_empty_content: false,
             dm_e2ee: None,
             nonce: None,
+            flags: 0,
+            tts: false,
         },
     )
     .await
@@ -138,6 +150,8 @@ pub async fn create_message_with_type(
             allow_empty_content: false,
             dm_e2ee: None,
             nonce: None,
+            flags: 0,
+            tts: false,
         },
     )
     .await
@@ -152,8 +166,16 @@ pub async fn create_message_with_options(
     content: &str,
     options: CreateMessageOptions,
 ) -> Result<paracord_db::messages::MessageRow, CoreError> {
+    if options.flags & !MESSAGE_FLAGS_USER_SETTABLE != 0 {
+        return Err(CoreError::BadRequest(
+            "Only SUPPRESS_EMBEDS and SILENT flags can be set on new messages".into(),
+        ));
+    }
     let mut stored_content = content.to_string();
-    let mut flags = 0_i32;
+    let mut flags = options.flags;
+    if options.tts {
+        flags |= MESSAGE_FLAG_TTS;
+    }
     let mut nonce = options
         .nonce
         .as_deref()
@@ -189,6 +211,10 @@ pub async fn create_message_with_options(
 
         permissions::ensure_guild_member(pool, guild_id, author_id).await?;
         if let Some(member) = paracord_db::members::get_member(pool, author_id, guild_id).await? {
+            // Members still in membership screening can read but not post.
+            if member.pending {
+                return Err(CoreError::Forbidden);
+            }
             if let Some(until) = member.communication_disabled_until {
                 if until > chrono::Utc::now() {
                     return Err(CoreError::BadRequest(
@@ -211,25 +237,26 @@ pub async fn create_message_with_options(
         .await?;
         permissions::require_permission(perms, Permissions::VIEW_CHANNEL)?;
         permissions::require_permission(perms, Permissions::SEND_MESSAGES)?;
+        if options.tts {
+            permissions::require_permission(perms, Permissions::SEND_TTS_MESSAGES)?;
+        }
+        // Threads and forum posts follow their parent's announcement mode.
+        let read_only = channel.read_only
+            || match (channel.channel_type, channel.parent_id) {
+                (6, Some(parent_id)) => paracord_db::channels::get_channel(pool, parent_id)
+                    .await?
+                    .is_some_and(|parent| parent.read_only),
+                _ => false,
+            };
+        if read_only {
+            permissions::require_permission(perms, Permissions::MANAGE_MESSAGES)?;
+        }
     } else {
         if !paracord_db::dms::is_dm_recipient(pool, channel_id, author_id).await? {
             return Err(CoreError::Forbidden);
         }
-        let recipients = paracord_db::dms::get_dm_recipient_ids(pool, channel_id).await?;
-        for recipient_id in recipients {
-            if recipient_id == author_id {
-                continue;
-            }
-            if paracord_db::relationships::is_blocked_either_direction(
-                pool,
-                author_id,
-                recipient_id,
-            )
-            .await?
-            {
-                return Err(CoreError::Forbidden);
-            }
-        }
+        // Blocks don't fail the send; delivery to the blocked side is
+        // dropped when the message is dispatched.
 
         if let Some(dm_e2ee) = options.dm_e2ee.as_ref() {
             dm_e2ee.validate()?;
@@ -327,7 +354,7 @@ pub async fn edit_message_with_options(
             }
             stored_content = payload.ciphertext.clone();
             nonce = Some(payload.nonce.clone());
-            flags = Some(MESSAGE_FLAG_DM_E2EE);
+            flags = Some(msg.flags | MESSAGE_FLAG_DM_E2EE);
         } else if !content.trim().is_empty() {
             return Err(CoreError::BadRequest(
                 "Plaintext DM messages are disabled; update your client for encrypted DMs".into(),
@@ -401,3 +428,586 @@ pub async fn delete_message(
     }
     Err(CoreError::MissingPermission)
 }
+
+/// User ids mentioned as `<@id>` or `<@!id>`, in order of first appearance.
+pub fn mentioned_user_ids(content: &str) -> Vec<i64> {
+    let mut ids = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<@") {
+        rest = &rest[start + 2..];
+        let digits = rest.strip_prefix('!').unwrap_or(rest);
+        let Some(end) = digits.find('>') else {
+            break;
+        };
+        if let Ok(id) = digits[..end].parse::<i64>() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+/// Role ids mentioned as `<@&id>`, in order of first appearance.
+pub fn mentioned_role_ids(content: &str) -> Vec<i64> {
+    let mut ids = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<@&") {
+        rest = &rest[start + 3..];
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        if let Ok(id) = rest[..end].parse::<i64>() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+/// Whether `content` mentions `@everyone` as a word of its own.
+pub fn mentions_everyone(content: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    content.match_indices("@everyone").any(|(start, m)| {
+        !content[..start].chars().next_back().is_some_and(is_word)
+            && !content[start + m.len()..]
+                .chars()
+                .next()
+                .is_some_and(is_word)
+    })
+}
+
+/// Longest message search query accepted, in characters.
+pub const MAX_SEARCH_QUERY_LEN: usize = 512;
+const MAX_SEARCH_PHRASES: usize = 16;
+
+/// Parse a search box query. Bare words and `"quoted phrases"` must all
+/// match; `from:<user_id>` (or `from:<@user_id>`) and `has:attachment`
+/// filter the results.
+pub fn parse_search_query(query: &str) -> Result<paracord_db::messages::MessageSearch, CoreError> {
+    if query.chars().count() > MAX_SEARCH_QUERY_LEN {
+        return Err(CoreError::BadRequest("Search query is too long".into()));
+    }
+    let mut search = paracord_db::messages::MessageSearch::default();
+    let mut rest = query.trim_start();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let phrase = quoted[..end]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !phrase.is_empty() {
+                search.phrases.push(phrase);
+            }
+            rest = quoted.get(end + 1..).unwrap_or("").trim_start();
+            continue;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = &rest[..end];
+        rest = rest[end..].trim_start();
+
+        if let Some(value) = token.strip_prefix("from:") {
+            let id = value
+                .strip_prefix("<@")
+                .and_then(|v| v.strip_suffix('>'))
+                .map(|v| v.strip_prefix('!').unwrap_or(v))
+                .unwrap_or(value);
+            let id = id
+                .parse::<i64>()
+                .map_err(|_| CoreError::BadRequest("from: expects a user id".into()))?;
+            search.author_id = Some(id);
+        } else if let Some(value) = token.strip_prefix("has:") {
+            match value {
+                "attachment" | "file" => search.has_attachment = true,
+                _ => {
+                    return Err(CoreError::BadRequest(format!(
+                        "Unsupported search filter has:{value}"
+                    )))
+                }
+            }
+        } else {
+            let word = token.trim_matches('"');
+            if !word.is_empty() {
+                search.phrases.push(word.to_string());
+            }
+        }
+    }
+    if search.phrases.len() > MAX_SEARCH_PHRASES {
+        return Err(CoreError::BadRequest("Too many search terms".into()));
+    }
+    Ok(search)
+}
+
+/// Which mentions in a message may notify anyone. The default allows all.
+#[derive(Debug, Clone)]
+pub struct AllowedMentions {
+    /// Notify every mentioned user.
+    pub parse_users: bool,
+    /// Notify every mentioned role.
+    pub parse_roles: bool,
+    /// Let `@everyone` notify the whole guild.
+    pub parse_everyone: bool,
+    /// Users notified even when `parse_users` is off.
+    pub users: Vec<i64>,
+    /// Roles notified even when `parse_roles` is off.
+    pub roles: Vec<i64>,
+}
+
+impl Default for AllowedMentions {
+    fn default() -> Self {
+        Self {
+            parse_users: true,
+            parse_roles: true,
+            parse_everyone: true,
+            users: Vec::new(),
+            roles: Vec::new(),
+        }
+    }
+}
+
+impl AllowedMentions {
+    fn allows_user(&self, user_id: i64) -> bool {
+        self.parse_users || self.users.contains(&user_id)
+    }
+
+    fn allows_role(&self, role_id: i64) -> bool {
+        self.parse_roles || self.roles.contains(&role_id)
+    }
+}
+
+/// Whether `notifications` settings list `guild_id` in `mutedGuildIds`.
+fn guild_muted(notifications: &serde_json::Value, guild_id: i64) -> bool {
+    let guild_id = guild_id.to_string();
+    notifications
+        .get("mutedGuildIds")
+        .and_then(|v| v.as_array())
+        .is_some_and(|ids| ids.iter().any(|id| id.as_str() == Some(guild_id.as_str())))
+}
+
+/// Members reached by the roles `msg` mentions, or by `@everyone` when
+/// `everyone` is set, excluding the author and anyone who muted the guild.
+/// `@everyone` and roles that are not mentionable only count when the author
+/// has MENTION_EVERYONE in the channel.
+async fn group_mention_candidates(
+    pool: &DbPool,
+    msg: &paracord_db::messages::MessageRow,
+    guild_id: i64,
+    guild_owner_id: i64,
+    role_ids: &[i64],
+    everyone: bool,
+) -> Result<Vec<i64>, CoreError> {
+    // `@everyone` shares the guild id and is not a role mention.
+    let roles: Vec<_> = paracord_db::roles::get_guild_roles(pool, guild_id)
+        .await?
+        .into_iter()
+        .filter(|role| role.id != guild_id && role_ids.contains(&role.id))
+        .collect();
+    if roles.is_empty() && !everyone {
+        return Ok(Vec::new());
+    }
+
+    let can_mention_everyone = if !everyone && roles.iter().all(|role| role.mentionable) {
+        false
+    } else {
+        permissions::compute_channel_permissions(
+            pool,
+            guild_id,
+            msg.channel_id,
+            guild_owner_id,
+            msg.author_id,
+        )
+        .await?
+        .contains(Permissions::MENTION_EVERYONE)
+    };
+    let holders = if everyone && can_mention_everyone {
+        paracord_db::members::get_guild_member_user_ids(pool, guild_id).await?
+    } else {
+        let pingable: Vec<i64> = roles
+            .iter()
+            .filter(|role| role.mentionable || can_mention_everyone)
+            .map(|role| role.id)
+            .collect();
+        paracord_db::roles::get_role_member_ids(pool, guild_id, &pingable).await?
+    };
+    let holders: Vec<i64> = holders
+        .into_iter()
+        .filter(|id| *id != msg.author_id)
+        .collect();
+    if holders.is_empty() {
+        return Ok(holders);
+    }
+    let muted: HashSet<i64> =
+        paracord_db::users::get_notification_settings_for_users(pool, &holders)
+            .await?
+            .into_iter()
+            .filter(|(_, notifications)| guild_muted(notifications, guild_id))
+            .map(|(user_id, _)| user_id)
+            .collect();
+    Ok(holders
+        .into_iter()
+        .filter(|id| !muted.contains(id))
+        .collect())
+}
+
+/// Bump the unread mention count of everyone `msg` mentions who can see the
+/// channel, returning the users notified. Role mentions reach the role's
+/// holders and `@everyone` reaches every member. Encrypted messages notify
+/// nobody, and nobody is notified across a block.
+pub async fn record_mentions(
+    pool: &DbPool,
+    msg: &paracord_db::messages::MessageRow,
+    guild_id: Option<i64>,
+    allowed: &AllowedMentions,
+) -> Result<Vec<i64>, CoreError> {
+    if msg.flags & MESSAGE_FLAG_DM_E2EE != 0 {
+        return Ok(Vec::new());
+    }
+    let Some(content) = msg.content.as_deref() else {
+        return Ok(Vec::new());
+    };
+    let candidates: Vec<i64> = mentioned_user_ids(content)
+        .into_iter()
+        .filter(|id| *id != msg.author_id && allowed.allows_user(*id))
+        .collect();
+    let role_ids: Vec<i64> = match guild_id {
+        Some(_) => mentioned_role_ids(content)
+            .into_iter()
+            .filter(|id| allowed.allows_role(*id))
+            .collect(),
+        None => Vec::new(),
+    };
+    let everyone = guild_id.is_some() && allowed.parse_everyone && mentions_everyone(content);
+    if candidates.is_empty() && role_ids.is_empty() && !everyone {
+        return Ok(Vec::new());
+    }
+
+    let mut targets: Vec<i64> = match guild_id {
+        Some(guild_id) => {
+            let guild = paracord_db::guilds::get_guild(pool, guild_id)
+                .await?
+                .ok_or(CoreError::NotFound)?;
+            let mut members: HashSet<i64> = candidates.into_iter().collect();
+            if !role_ids.is_empty() || everyone {
+                members.extend(
+                    group_mention_candidates(
+                        pool,
+                        msg,
+                        guild_id,
+                        guild.owner_id,
+                        &role_ids,
+                        everyone,
+                    )
+                    .await?,
+                );
+            }
+            let members: Vec<i64> = members.into_iter().collect();
+            // Non-members are absent from the map and so never notified.
+            permissions::compute_members_channel_permissions(
+                pool,
+                guild_id,
+                msg.channel_id,
+                guild.owner_id,
+                &members,
+            )
+            .await?
+            .into_iter()
+            .filter(|(_, perms)| perms.contains(Permissions::VIEW_CHANNEL))
+            .map(|(user_id, _)| user_id)
+            .collect()
+        }
+        None => {
+            let recipients: HashSet<i64> =
+                paracord_db::dms::get_dm_recipient_ids(pool, msg.channel_id)
+                    .await?
+                    .into_iter()
+                    .collect();
+            candidates
+                .into_iter()
+                .filter(|id| recipients.contains(id))
+                .collect()
+        }
+    };
+
+    if !targets.is_empty() {
+        let blocked: HashSet<i64> =
+            paracord_db::relationships::get_blocked_user_ids(pool, msg.author_id)
+                .await?
+                .into_iter()
+                .collect();
+        targets.retain(|id| !blocked.contains(id));
+    }
+    paracord_db::read_states::increment_mention_counts(pool, &targets, msg.channel_id).await?;
+    Ok(targets)
+}
+
+/// Set or clear SUPPRESS_EMBEDS. The author may always toggle it; anyone
+/// else needs MANAGE_MESSAGES in the channel.
+pub async fn set_suppress_embeds(
+    pool: &DbPool,
+    channel_id: i64,
+    message_id: i64,
+    user_id: i64,
+    suppress: bool,
+) -> Result<paracord_db::messages::MessageRow, CoreError> {
+    let msg = paracord_db::messages::get_message(pool, message_id)
+        .await?
+        .ok_or(CoreError::NotFound)?;
+    if msg.channel_id != channel_id {
+        return Err(CoreError::NotFound);
+    }
+
+    if msg.author_id != user_id {
+        let channel = paracord_db::channels::get_channel(pool, channel_id)
+            .await?
+            .ok_or(CoreError::NotFound)?;
+        let Some(guild_id) = channel.guild_id() else {
+            return Err(CoreError::Forbidden);
+        };
+        permissions::ensure_guild_member(pool, guild_id, user_id).await?;
+        let guild = paracord_db::guilds::get_guild(pool, guild_id)
+            .await?
+            .ok_or(CoreError::NotFound)?;
+        let perms = permissions::compute_channel_permissions(
+            pool,
+            guild_id,
+            channel_id,
+            guild.owner_id,
+            user_id,
+        )
+        .await?;
+        permissions::require_permission(perms, Permissions::VIEW_CHANNEL)?;
+        permissions::require_permission(perms, Permissions::MANAGE_MESSAGES)?;
+    }
+
+    if suppress {
+        paracord_db::messages::add_message_flag(
+            pool,
+            message_id,
+            channel_id,
+            MESSAGE_FLAG_SUPPRESS_EMBEDS,
+        )
+        .await?;
+    } else {
+        paracord_db::messages::remove_message_flag(
+            pool,
+            message_id,
+            channel_id,
+            MESSAGE_FLAG_SUPPRESS_EMBEDS,
+        )
+        .await?;
+    }
+    paracord_db::messages::get_message(pool, message_id)
+        .await?
+        .ok_or(CoreError::NotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        guild_muted, mentioned_role_ids, mentioned_user_ids, mentions_everyone, parse_search_query,
+        record_mentions, AllowedMentions,
+    };
+    use crate::permissions::OVERWRITE_TARGET_MEMBER;
+    use paracord_models::permissions::Permissions;
+
+    #[test]
+    fn mentioned_user_ids_parses_both_forms_once() {
+        assert_eq!(
+            mentioned_user_ids("hey <@12> and <@!34>, also <@12> again"),
+            vec![12, 34]
+        );
+    }
+
+    #[test]
+    fn mentioned_user_ids_ignores_malformed_mentions() {
+        assert!(mentioned_user_ids("<@abc> <@> <@&56> <@78").is_empty());
+        assert_eq!(mentioned_user_ids("<@<@90>"), vec![90]);
+    }
+
+    #[test]
+    fn mentioned_role_ids_only_parse_role_form() {
+        assert_eq!(
+            mentioned_role_ids("<@&5> <@6> <@!7> <@&8> <@&5> <@&x>"),
+            vec![5, 8]
+        );
+    }
+
+    #[test]
+    fn allowed_mentions_whitelist_specific_ids() {
+        let allowed = AllowedMentions {
+            parse_users: false,
+            parse_roles: false,
+            parse_everyone: false,
+            users: vec![1],
+            roles: vec![2],
+        };
+        assert!(allowed.allows_user(1) && !allowed.allows_user(3));
+        assert!(allowed.allows_role(2) && !allowed.allows_role(4));
+        assert!(AllowedMentions::default().allows_role(4));
+    }
+
+    #[test]
+    fn mentions_everyone_needs_a_standalone_word() {
+        assert!(mentions_everyone("@everyone"));
+        assert!(mentions_everyone("heads up, @everyone!"));
+        assert!(!mentions_everyone("mail@everyone.example"));
+        assert!(!mentions_everyone("@everyones"));
+        assert!(!mentions_everyone("@here"));
+    }
+
+    #[tokio::test]
+    async fn record_mentions_expands_roles_and_gates_everyone() {
+        const OWNER: i64 = 1;
+        const AUTHOR: i64 = 2;
+        const ALICE: i64 = 3;
+        const BOB: i64 = 4;
+        const CAROL: i64 = 5;
+        const GUILD: i64 = 100;
+        const MODS: i64 = 101;
+        const PINGERS: i64 = 102;
+        const CHANNEL: i64 = 110;
+
+        let pool = paracord_db::create_pool("sqlite::memory:", 1)
+            .await
+            .unwrap();
+        paracord_db::run_migrations(&pool).await.unwrap();
+        for (id, name) in [
+            (OWNER, "owner"),
+            (AUTHOR, "author"),
+            (ALICE, "alice"),
+            (BOB, "bob"),
+            (CAROL, "carol"),
+        ] {
+            paracord_db::users::create_user(
+                &pool,
+                id,
+                name,
+                1,
+                &format!("{name}@example.com"),
+                "x",
+            )
+            .await
+            .unwrap();
+        }
+        let view = Permissions::VIEW_CHANNEL.bits();
+        paracord_db::guilds::create_guild(&pool, GUILD, "Guild", OWNER, None)
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(&pool, GUILD, GUILD, "@everyone", view)
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(&pool, MODS, GUILD, "mods", 0)
+            .await
+            .unwrap();
+        paracord_db::roles::update_role(&pool, MODS, None, None, None, None, Some(true))
+            .await
+            .unwrap();
+        paracord_db::roles::create_role(
+            &pool,
+            PINGERS,
+            GUILD,
+            "pingers",
+            Permissions::MENTION_EVERYONE.bits(),
+        )
+        .await
+        .unwrap();
+        paracord_db::channels::create_channel(&pool, CHANNEL, GUILD, "general", 0, 0, None, None)
+            .await
+            .unwrap();
+        for user in [OWNER, AUTHOR, ALICE, BOB, CAROL] {
+            paracord_db::members::add_member(&pool, user, GUILD)
+                .await
+                .unwrap();
+        }
+        paracord_db::roles::add_member_role(&pool, ALICE, GUILD, MODS)
+            .await
+            .unwrap();
+        // Carol cannot see the channel, so no mention may reach her.
+        paracord_db::channel_overwrites::upsert_channel_overwrite(
+            &pool,
+            CHANNEL,
+            CAROL,
+            OVERWRITE_TARGET_MEMBER,
+            0,
+            view,
+        )
+        .await
+        .unwrap();
+
+        let send = |id: i64, content: &'static str| {
+            let pool = pool.clone();
+            async move {
+                paracord_db::messages::create_message(&pool, id, CHANNEL, AUTHOR, content, 0, None)
+                    .await
+                    .unwrap()
+            }
+        };
+        let allowed = AllowedMentions::default();
+
+        // Without MENTION_EVERYONE only the mentionable role expands.
+        let msg = send(1000, "<@&101> @everyone").await;
+        let notified = record_mentions(&pool, &msg, Some(GUILD), &allowed)
+            .await
+            .unwrap();
+        assert_eq!(notified, vec![ALICE]);
+
+        paracord_db::roles::add_member_role(&pool, AUTHOR, GUILD, PINGERS)
+            .await
+            .unwrap();
+        let msg = send(1001, "@everyone standup").await;
+        let mut notified = record_mentions(&pool, &msg, Some(GUILD), &allowed)
+            .await
+            .unwrap();
+        notified.sort_unstable();
+        assert_eq!(notified, vec![OWNER, ALICE, BOB]);
+
+        let no_everyone = AllowedMentions {
+            parse_everyone: false,
+            ..AllowedMentions::default()
+        };
+        let msg = send(1002, "@everyone again").await;
+        let notified = record_mentions(&pool, &msg, Some(GUILD), &no_everyone)
+            .await
+            .unwrap();
+        assert!(notified.is_empty());
+
+        let alice = paracord_db::read_states::get_read_state(&pool, ALICE, CHANNEL)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(alice.mention_count, 2);
+        assert!(
+            paracord_db::read_states::get_read_state(&pool, CAROL, CHANNEL)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn guild_muted_reads_muted_guild_ids() {
+        let settings = serde_json::json!({ "mutedGuildIds": ["10", "11"] });
+        assert!(guild_muted(&settings, 11));
+        assert!(!guild_muted(&settings, 12));
+        assert!(!guild_muted(&serde_json::json!({}), 11));
+    }
+
+    #[test]
+    fn search_query_parses_phrases_and_filters() {
+        let search =
+            parse_search_query(r#"  deploy "release notes"  from:<@42> has:attachment  "#).unwrap();
+        assert_eq!(search.phrases, vec!["deploy", "release notes"]);
+        assert_eq!(search.author_id, Some(42));
+        assert!(search.has_attachment);
+
+        let search = parse_search_query(r#"from:7 "unterminated   phrase"#).unwrap();
+        assert_eq!(search.phrases, vec!["unterminated phrase"]);
+        assert_eq!(search.author_id, Some(7));
+
+        assert!(parse_search_query(r#""" "#).unwrap().is_empty());
+        assert!(parse_search_query("from:someone").is_err());
+        assert!(parse_search_query("has:link").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Server-wide blocklist for usernames and guild names.
+//!
+//! Patterns come from the `blocked_name_patterns` admin setting, one per
+//! line. A line wrapped in slashes (`/^admin\d*$/`) is a regular expression;
+//! anything else is a plain substring. With folding on, names and substring
+//! patterns are compared case-insensitively after mapping common lookalike
+//! characters (Cyrillic and Greek letters, accented letters, leetspeak
+//! digits) to ASCII, so `Аdmіn` and `4dm1n` both match `admin`.
+
+use regex::{Regex, RegexBuilder};
+use unicode_normalization::UnicodeNormalization;
+
+pub const MAX_PATTERNS: usize = 200;
+pub const MAX_PATTERN_LEN: usize = 256;
+/// Compiled size cap per regex so an admin typo cannot blow up memory.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    Substring(String),
+    Regex(Regex),
+}
+
+#[derive(Clone, Debug)]
+pub struct NameFilter {
+    source: String,
+    fold: bool,
+    patterns: Vec<(String, Pattern)>,
+}
+
+/// No patterns, folding on.
+impl Default for NameFilter {
+    fn default() -> Self {
+        Self {
+            source: String::new(),
+            fold: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl NameFilter {
+    /// Compile `source` (newline-separated patterns). Blank lines are
+    /// skipped.
+    pub fn new(source: &str, fold: bool) -> Result<Self, String> {
+        let lines: Vec<&str> = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.len() > MAX_PATTERNS {
+            return Err(format!("at most {MAX_PATTERNS} patterns are allowed"));
+        }
+        let mut patterns = Vec::with_capacity(lines.len());
+        for line in lines {
+            if line.chars().count() > MAX_PATTERN_LEN {
+                return Err(format!(
+                    "patterns must be at most {MAX_PATTERN_LEN} characters"
+                ));
+            }
+            let pattern = match line
+                .strip_prefix('/')
+                .and_then(|rest| rest.strip_suffix('/'))
+                .filter(|expr| !expr.is_empty())
+            {
+                Some(expr) => Pattern::Regex(
+                    RegexBuilder::new(expr)
+                        .case_insensitive(fold)
+                        .size_limit(REGEX_SIZE_LIMIT)
+                        .build()
+                        .map_err(|e| format!("invalid pattern {line}: {e}"))?,
+                ),
+                None if fold => Pattern::Substring(fold_name(line)),
+                None => Pattern::Substring(line.to_string()),
+            };
+            patterns.push((line.to_string(), pattern));
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            fold,
+            patterns,
+        })
+    }
+
+    /// The patterns as the admin entered them.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn fold(&self) -> bool {
+        self.fold
+    }
+
+    /// The first pattern `name` matches, if any.
+    pub fn blocked_by(&self, name: &str) -> Option<&str> {
+        if self.patterns.is_empty() {
+            return None;
+        }
+        let folded = if self.fold {
+            fold_name(name)
+        } else {
+            name.to_string()
+        };
+        self.patterns
+            .iter()
+            .find(|(_, pattern)| match pattern {
+                Pattern::Substring(needle) => folded.contains(needle.as_str()),
+                // Regexes see the name as typed too, so `\d` still matches
+                // digits that folding turns into letters.
+                Pattern::Regex(regex) => regex.is_match(name) || regex.is_match(&folded),
+            })
+            .map(|(line, _)| line.as_str())
+    }
+}
+
+/// Lowercase `name`, strip accents and map lookalike characters to ASCII.
+pub fn fold_name(name: &str) -> String {
+    name.nfkd()
+        .filter(|ch| !is_combining_mark(*ch))
+        .map(fold_capital_confusable)
+        .flat_map(char::to_lowercase)
+        .map(fold_confusable)
+        .collect()
+}
+
+fn is_combining_mark(ch: char) -> bool {
+    matches!(ch, '\u{0300}'..='\u{036f}' | '\u{1ab0}'..='\u{1aff}' | '\u{20d0}'..='\u{20ff}')
+}
+
+/// Capitals that look like a Latin letter only before lowercasing:
+/// "Н" passes for "H", but "н" is read as "n" and looks nothing like "h".
+fn fold_capital_confusable(ch: char) -> char {
+    match ch {
+        'В' => 'b',
+        'Н' => 'h',
+        other => other,
+    }
+}
+
+fn fold_confusable(ch: char) -> char {
+    match ch {
+        // Cyrillic
+        'а' => 'a',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' | 'ё' => 'e',
+        'һ' => 'h',
+        'і' | 'ї' => 'i',
+        'ј' => 'j',
+        'к' => 'k',
+        'м' => 'm',
+        'о' => 'o',
+        'р' => 'p',
+        'ѕ' => 's',
+        'т' => 't',
+        'у' => 'y',
+        'х' => 'x',
+        // Greek
+        'α' => 'a',
+        'β' => 'b',
+        'ε' => 'e',
+        'ι' => 'i',
+        'κ' => 'k',
+        'ν' => 'v',
+        'ο' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' => 'u',
+        'χ' => 'x',
+        // Digits and symbols
+        '0' => 'o',
+        '1' | '!' | '|' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        'ı' | 'ł' => 'i',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substrings_and_regexes_block_names() {
+        let filter = NameFilter::new("admin\n\n/^mod(erator)?\\d*$/\n", true).unwrap();
+        assert_eq!(filter.blocked_by("TheAdminTeam"), Some("admin"));
+        assert_eq!(
+            filter.blocked_by("Moderator42"),
+            Some("/^mod(erator)?\\d*$/")
+        );
+        assert_eq!(filter.blocked_by("modest"), None);
+        assert_eq!(filter.blocked_by("alice"), None);
+    }
+
+    #[test]
+    fn folding_catches_case_and_lookalikes() {
+        let folded = NameFilter::new("admin", true).unwrap();
+        // Cyrillic "А" and "і", then leetspeak.
+        assert!(folded.blocked_by("\u{0410}dm\u{0456}n").is_some());
+        assert!(folded.blocked_by("4DM1N").is_some());
+        assert!(folded.blocked_by("\u{00e1}dmin").is_some());
+
+        // Cyrillic capital "Н" passes for "H"; lowercase "н" does not.
+        let host = NameFilter::new("host", true).unwrap();
+        assert!(host.blocked_by("\u{041d}ost").is_some());
+        assert!(host.blocked_by("\u{043d}ost").is_none());
+
+        let exact = NameFilter::new("admin", false).unwrap();
+        assert!(exact.blocked_by("ADMIN").is_none());
+        assert!(exact.blocked_by("\u{0410}dmin").is_none());
+        assert!(exact.blocked_by("admin").is_some());
+    }
+
+    #[test]
+    fn rejects_invalid_pattern_lists() {
+        assert!(NameFilter::new("/(unclosed/", true).is_err());
+        assert!(NameFilter::new(&"x\n".repeat(MAX_PATTERNS + 1), true).is_err());
+        assert!(NameFilter::new("", true)
+            .unwrap()
+            .blocked_by("anything")
+            .is_none());
+    }
+}
@@ -8,6 +8,7 @@ const MAX_EVENT_TYPE_KEYS: usize = 128;
 
 static WS_CONNECTIONS_ACTIVE: AtomicU64 = AtomicU64::new(0);
 static WS_EVENTS_TOTAL: AtomicU64 = AtomicU64::new(0);
+static PLAINTEXT_PUBLIC_ORIGINS: AtomicU64 = AtomicU64::new(0);
 static WS_EVENTS_BY_TYPE: OnceLock<Mutex<HashMap<String, u64>>> = OnceLock::new();
 static WIRE_TRACE_ENABLED: OnceLock<bool> = OnceLock::new();
 static WIRE_TRACE_PAYLOADS_ENABLED: OnceLock<bool> = OnceLock::new();
@@ -111,6 +112,16 @@ pub fn ws_event_dispatched(event_type: &str) {
     *entry = entry.saturating_add(1);
 }
 
+/// Record how many public origins (public URL, CORS/WS allow-lists) the
+/// server was started with that are served over plaintext HTTP.
+pub fn set_plaintext_public_origins(count: u64) {
+    PLAINTEXT_PUBLIC_ORIGINS.store(count, Ordering::Relaxed);
+}
+
+pub fn plaintext_public_origins() -> u64 {
+    PLAINTEXT_PUBLIC_ORIGINS.load(Ordering::Relaxed)
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct WsMetricsSnapshot {
     pub active_connections: u64,
@@ -161,9 +161,14 @@ pub async fn compute_channel_permissions(
         return Ok(Permissions::all());
     }
 
-    let channel = paracord_db::channels::get_channel(pool, channel_id)
+    let mut channel = paracord_db::channels::get_channel(pool, channel_id)
         .await?
         .ok_or(CoreError::NotFound)?;
+    if let Some(parent_id) = thread_parent_id(&channel) {
+        if let Some(parent) = paracord_db::channels::get_channel(pool, parent_id).await? {
+            channel = parent;
+        }
+    }
 
     let role_ids: std::collections::HashSet<i64> = roles.iter().map(|r| r.id).collect();
     let required_role_ids =
@@ -174,7 +179,7 @@ pub async fn compute_channel_permissions(
     }
 
     let overwrites =
-        paracord_db::channel_overwrites::get_channel_overwrites(pool, channel_id).await?;
+        paracord_db::channel_overwrites::get_channel_overwrites(pool, channel.id).await?;
     if overwrites.is_empty() {
         return Ok(perms);
     }
@@ -214,6 +219,30 @@ pub async fn compute_channel_permissions(
     Ok(perms)
 }
 
+/// Compute a bot's permissions in a channel: what it holds as a guild member,
+/// capped by the permissions granted when the bot was installed.
+pub async fn compute_bot_permissions(
+    pool: &DbPool,
+    bot_user_id: i64,
+    application_id: i64,
+    guild_id: i64,
+    channel_id: i64,
+    guild_owner_id: i64,
+) -> Result<Permissions, CoreError> {
+    let install =
+        paracord_db::bot_applications::get_bot_guild_install(pool, application_id, guild_id)
+            .await?
+            .ok_or(CoreError::Forbidden)?;
+    let member_perms =
+        compute_channel_permissions(pool, guild_id, channel_id, guild_owner_id, bot_user_id)
+            .await?;
+    let granted = Permissions::from_bits_truncate(install.permissions);
+    if granted.contains(Permissions::ADMINISTRATOR) {
+        return Ok(member_perms);
+    }
+    Ok(member_perms & granted)
+}
+
 /// Compute channel permissions for multiple channels in a single batch.
 /// Loads roles once and all overwrites once, then computes in-memory.
 pub async fn compute_all_channel_permissions(
@@ -245,8 +274,25 @@ pub async fn compute_all_channel_permissions(
 
     let role_ids: std::collections::HashSet<i64> = roles.iter().map(|r| r.id).collect();
 
+    // Threads are computed as their parent channel.
+    let mut by_id: HashMap<i64, &paracord_db::channels::ChannelRow> =
+        channels.iter().map(|c| (c.id, c)).collect();
+    let mut missing_parents = Vec::new();
+    for parent_id in channels.iter().filter_map(thread_parent_id) {
+        if !by_id.contains_key(&parent_id) && !missing_parents.contains(&parent_id) {
+            missing_parents.push(parent_id);
+        }
+    }
+    let mut extra_parents = Vec::with_capacity(missing_parents.len());
+    for parent_id in missing_parents {
+        if let Some(parent) = paracord_db::channels::get_channel(pool, parent_id).await? {
+            extra_parents.push(parent);
+        }
+    }
+    by_id.extend(extra_parents.iter().map(|c| (c.id, c)));
+
     // Load all overwrites for all channels in one query
-    let channel_ids: Vec<i64> = channels.iter().map(|c| c.id).collect();
+    let channel_ids: Vec<i64> = by_id.keys().copied().collect();
     let all_overwrites =
         paracord_db::channel_overwrites::get_overwrites_for_channels(pool, &channel_ids).await?;
 
@@ -265,63 +311,146 @@ pub async fn compute_all_channel_permissions(
     // Compute permissions per channel
     let mut result = HashMap::with_capacity(channels.len());
     for channel in channels {
-        let mut perms = base_perms;
-
-        // Check required_role_ids
-        let required_role_ids =
-            paracord_db::channels::parse_required_role_ids(&channel.required_role_ids);
-        if !required_role_ids.is_empty()
-            && !required_role_ids.iter().any(|id| role_ids.contains(id))
-        {
-            perms.remove(Permissions::VIEW_CHANNEL);
-            result.insert(channel.id, perms);
-            continue;
-        }
+        let effective = thread_parent_id(channel)
+            .and_then(|parent_id| by_id.get(&parent_id).copied())
+            .unwrap_or(channel);
+        let overwrites = overwrites_by_channel
+            .get(&effective.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        result.insert(
+            channel.id,
+            apply_channel_overwrites(
+                base_perms, effective, &role_ids, overwrites, guild_id, user_id,
+            ),
+        );
+    }
 
-        // Apply overwrites
-        let overwrites = overwrites_by_channel.get(&channel.id);
-        if let Some(overwrites) = overwrites {
-            // @everyone role overwrite
-            if let Some(everyone) = overwrites
-                .iter()
-                .find(|o| o.target_type == OVERWRITE_TARGET_ROLE && o.target_id == guild_id)
-            {
-                let deny = Permissions::from_bits_truncate(everyone.deny_perms);
-                let allow = Permissions::from_bits_truncate(everyone.allow_perms);
-                perms &= !deny;
-                perms |= allow;
-            }
-
-            // Role overwrites
-            let mut role_deny = Permissions::empty();
-            let mut role_allow = Permissions::empty();
-            for overwrite in overwrites.iter().filter(|o| {
-                o.target_type == OVERWRITE_TARGET_ROLE && role_ids.contains(&o.target_id)
-            }) {
-                role_deny |= Permissions::from_bits_truncate(overwrite.deny_perms);
-                role_allow |= Permissions::from_bits_truncate(overwrite.allow_perms);
-            }
-            perms &= !role_deny;
-            perms |= role_allow;
-
-            // Member overwrite
-            if let Some(member_ow) = overwrites
-                .iter()
-                .find(|o| o.target_type == OVERWRITE_TARGET_MEMBER && o.target_id == user_id)
-            {
-                let deny = Permissions::from_bits_truncate(member_ow.deny_perms);
-                let allow = Permissions::from_bits_truncate(member_ow.allow_perms);
-                perms &= !deny;
-                perms |= allow;
-            }
-        }
+    Ok(result)
+}
 
-        result.insert(channel.id, perms);
+/// Compute one channel's permissions for many users in a single batch.
+/// Loads the channel, its overwrites and the guild roles once, and every
+/// member's role ids in chunks. Users who are not guild members are left out.
+pub async fn compute_members_channel_permissions(
+    pool: &DbPool,
+    guild_id: i64,
+    channel_id: i64,
+    guild_owner_id: i64,
+    user_ids: &[i64],
+) -> Result<std::collections::HashMap<i64, Permissions>, CoreError> {
+    use std::collections::{HashMap, HashSet};
+
+    let member_roles = paracord_db::roles::get_members_role_ids(pool, guild_id, user_ids).await?;
+    if member_roles.is_empty() {
+        return Ok(HashMap::new());
     }
 
+    let mut channel = paracord_db::channels::get_channel(pool, channel_id)
+        .await?
+        .ok_or(CoreError::NotFound)?;
+    if let Some(parent_id) = thread_parent_id(&channel) {
+        if let Some(parent) = paracord_db::channels::get_channel(pool, parent_id).await? {
+            channel = parent;
+        }
+    }
+    let overwrites =
+        paracord_db::channel_overwrites::get_channel_overwrites(pool, channel.id).await?;
+    let guild_roles: HashMap<i64, paracord_db::roles::RoleRow> =
+        paracord_db::roles::get_guild_roles(pool, guild_id)
+            .await?
+            .into_iter()
+            .map(|role| (role.id, role))
+            .collect();
+
+    let mut result = HashMap::with_capacity(member_roles.len());
+    for (user_id, held) in member_roles {
+        // Every member implicitly holds @everyone, which shares the guild id.
+        let mut role_ids: HashSet<i64> = held.into_iter().collect();
+        role_ids.insert(guild_id);
+        let roles: Vec<paracord_db::roles::RoleRow> = role_ids
+            .iter()
+            .filter_map(|id| guild_roles.get(id).cloned())
+            .collect();
+        let base_perms = compute_permissions_from_roles(&roles, guild_owner_id, user_id);
+        let perms = if base_perms.contains(Permissions::ADMINISTRATOR) {
+            Permissions::all()
+        } else {
+            apply_channel_overwrites(
+                base_perms,
+                &channel,
+                &role_ids,
+                &overwrites,
+                guild_id,
+                user_id,
+            )
+        };
+        result.insert(user_id, perms);
+    }
     Ok(result)
 }
 
+/// Threads have no role gate or overwrites of their own; permissions come
+/// from the channel they were started in.
+fn thread_parent_id(channel: &paracord_db::channels::ChannelRow) -> Option<i64> {
+    if channel.channel_type == 6 {
+        channel.parent_id
+    } else {
+        None
+    }
+}
+
+/// Narrow a member's guild-level permissions to one channel: its role gate,
+/// then the @everyone, role, and member overwrites, in that order. Callers
+/// handle the owner and ADMINISTRATOR cases, which bypass all of this.
+pub fn apply_channel_overwrites(
+    base_perms: Permissions,
+    channel: &paracord_db::channels::ChannelRow,
+    role_ids: &std::collections::HashSet<i64>,
+    overwrites: &[paracord_db::channel_overwrites::ChannelOverwriteRow],
+    guild_id: i64,
+    user_id: i64,
+) -> Permissions {
+    let mut perms = base_perms;
+
+    let required_role_ids =
+        paracord_db::channels::parse_required_role_ids(&channel.required_role_ids);
+    if !required_role_ids.is_empty() && !required_role_ids.iter().any(|id| role_ids.contains(id)) {
+        perms.remove(Permissions::VIEW_CHANNEL);
+        return perms;
+    }
+
+    if let Some(everyone) = overwrites
+        .iter()
+        .find(|o| o.target_type == OVERWRITE_TARGET_ROLE && o.target_id == guild_id)
+    {
+        perms &= !Permissions::from_bits_truncate(everyone.deny_perms);
+        perms |= Permissions::from_bits_truncate(everyone.allow_perms);
+    }
+
+    let mut role_deny = Permissions::empty();
+    let mut role_allow = Permissions::empty();
+    for overwrite in overwrites
+        .iter()
+        .filter(|o| o.target_type == OVERWRITE_TARGET_ROLE && role_ids.contains(&o.target_id))
+    {
+        role_deny |= Permissions::from_bits_truncate(overwrite.deny_perms);
+        role_allow |= Permissions::from_bits_truncate(overwrite.allow_perms);
+    }
+    perms &= !role_deny;
+    perms |= role_allow;
+
+    if let Some(member_ow) = overwrites
+        .iter()
+        .find(|o| o.target_type == OVERWRITE_TARGET_MEMBER && o.target_id == user_id)
+    {
+        perms &= !Permissions::from_bits_truncate(member_ow.deny_perms);
+        perms |= Permissions::from_bits_truncate(member_ow.allow_perms);
+    }
+
+    perms
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -341,6 +470,7 @@ mod tests {
             mentionable: false,
             server_wide: false,
             created_at: Utc::now(),
+            role_icon: None,
         }
     }
 
@@ -0,0 +1,237 @@
+//! Web Push notifications for users without a gateway connection.
+//!
+//! Payloads carry only a title and the channel id; message content never
+//! leaves the server through a push service.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use futures_util::StreamExt;
+use paracord_db::push::PushSubscriptionRow;
+use paracord_db::DbPool;
+use serde::Serialize;
+use tokio::sync::RwLock;
+use web_push::{
+    ContentEncoding, PartialVapidSignatureBuilder, SubscriptionInfo, VapidSignatureBuilder,
+    WebPushMessageBuilder,
+};
+
+/// How long a push service holds a notification for an offline device.
+const PUSH_TTL_SECONDS: u32 = 24 * 3600;
+/// Push requests in flight at once while fanning out one notification.
+const PUSH_CONCURRENCY: usize = 16;
+
+/// An encrypted, VAPID-signed Web Push request, ready to POST.
+#[derive(Debug, Clone)]
+pub struct PushRequest {
+    pub endpoint: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+pub type PushPostFuture = Pin<Box<dyn Future<Output = Result<u16, String>> + Send>>;
+/// POSTs a push request and returns the HTTP status. Implementations must
+/// refuse endpoints that resolve to private addresses.
+pub type PushPostFn = Arc<dyn Fn(PushRequest) -> PushPostFuture + Send + Sync>;
+
+/// Everything a notification reveals.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PushPayload {
+    pub title: String,
+    pub channel_id: String,
+}
+
+pub struct PushNotifier {
+    vapid: PartialVapidSignatureBuilder,
+    public_key: String,
+    subject: String,
+    post: PushPostFn,
+}
+
+impl PushNotifier {
+    /// `private_key` is a raw P-256 key in unpadded base64url or a PEM
+    /// document; `subject` is the `mailto:` or `https:` contact push
+    /// services see in the VAPID claims.
+    pub fn new(private_key: &str, subject: &str, post: PushPostFn) -> Result<Self, String> {
+        let private_key = private_key.trim();
+        let vapid = if private_key.starts_with("-----BEGIN") {
+            VapidSignatureBuilder::from_pem_no_sub(private_key.as_bytes())
+        } else {
+            VapidSignatureBuilder::from_base64_no_sub(private_key)
+        }
+        .map_err(|e| format!("invalid VAPID private key: {e}"))?;
+        let public_key = URL_SAFE_NO_PAD.encode(vapid.get_public_key());
+        Ok(Self {
+            vapid,
+            public_key,
+            subject: subject.to_string(),
+            post,
+        })
+    }
+
+    /// The VAPID public key browsers pass as `applicationServerKey`.
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    pub fn build_request(
+        &self,
+        subscription: &PushSubscriptionRow,
+        payload: &PushPayload,
+    ) -> Result<PushRequest, String> {
+        let info = SubscriptionInfo::new(
+            subscription.endpoint.as_str(),
+            subscription.p256dh.as_str(),
+            subscription.auth.as_str(),
+        );
+        let mut signature = self.vapid.clone().add_sub_info(&info);
+        signature.add_claim("sub", self.subject.as_str());
+        let signature = signature.build().map_err(|e| e.to_string())?;
+
+        let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        let mut message = WebPushMessageBuilder::new(&info);
+        message.set_ttl(PUSH_TTL_SECONDS);
+        message.set_payload(ContentEncoding::Aes128Gcm, &body);
+        message.set_vapid_signature(signature);
+        let message = message.build().map_err(|e| e.to_string())?;
+
+        let request = web_push::request_builder::build_request::<Vec<u8>>(message);
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+        Ok(PushRequest {
+            endpoint: subscription.endpoint.clone(),
+            headers,
+            body: request.into_body(),
+        })
+    }
+
+    /// Send `payload` to every signed-in device of each user in `user_ids`
+    /// who has no gateway connection, a few requests at a time. Endpoints the
+    /// push service reports as gone (404/410) are deleted.
+    pub async fn notify_offline(
+        &self,
+        db: &DbPool,
+        online_users: &RwLock<HashSet<i64>>,
+        user_ids: &[i64],
+        payload: &PushPayload,
+    ) {
+        let offline: Vec<i64> = {
+            let online = online_users.read().await;
+            user_ids
+                .iter()
+                .copied()
+                .filter(|id| !online.contains(id))
+                .collect()
+        };
+        if offline.is_empty() {
+            return;
+        }
+        let subscriptions = match paracord_db::push::get_deliverable_subscriptions(
+            db,
+            &offline,
+            chrono::Utc::now(),
+        )
+        .await
+        {
+            Ok(subscriptions) => subscriptions,
+            Err(e) => {
+                tracing::warn!("push: failed to load subscriptions: {}", e);
+                return;
+            }
+        };
+
+        let mut sends = futures_util::stream::iter(subscriptions)
+            .map(|subscription| async move {
+                let result = match self.build_request(&subscription, payload) {
+                    Ok(request) => (self.post)(request).await,
+                    Err(e) => Err(format!("cannot encrypt: {e}")),
+                };
+                (subscription.endpoint, result)
+            })
+            .buffer_unordered(PUSH_CONCURRENCY);
+        while let Some((endpoint, result)) = sends.next().await {
+            match result {
+                Ok(404 | 410) => {
+                    if let Err(e) = paracord_db::push::delete_subscription(db, &endpoint).await {
+                        tracing::warn!("push: failed to prune expired endpoint: {}", e);
+                    }
+                }
+                Ok(status) if !(200..300).contains(&status) => {
+                    tracing::debug!("push: {} answered {}", endpoint, status);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::debug!("push: delivery to {} failed: {}", endpoint, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    // Test vectors from the web-push crate.
+    const PRIVATE_KEY: &str = "IQ9Ur0ykXoHS9gzfYX0aBjy9lvdrjx_PFUXmie9YRcY";
+    const P256DH: &str =
+        "BLMbF9ffKBiWQLCKvTHb6LO8Nb6dcUh6TItC455vu2kElga6PQvUmaFyCdykxY2nOSSL3yKgfbmFLRTUaGv4yV8";
+    const AUTH: &str = "xS03Fi5ErfTNH_l9WHE9Ig";
+
+    #[test]
+    fn builds_encrypted_vapid_signed_requests() {
+        let notifier = PushNotifier::new(
+            PRIVATE_KEY,
+            "mailto:admin@example.com",
+            Arc::new(|_| Box::pin(async { Ok(201u16) }) as PushPostFuture),
+        )
+        .unwrap();
+        assert!(!notifier.public_key().is_empty());
+
+        let payload = PushPayload {
+            title: "New direct message".into(),
+            channel_id: "42".into(),
+        };
+        let request = notifier
+            .build_request(
+                &PushSubscriptionRow {
+                    endpoint: "https://push.example.com/send/abc".into(),
+                    user_id: 1,
+                    p256dh: P256DH.into(),
+                    auth: AUTH.into(),
+                    created_at: Utc::now(),
+                },
+                &payload,
+            )
+            .unwrap();
+        let header = |name: &str| {
+            request
+                .headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(header("content-encoding"), Some("aes128gcm"));
+        assert!(header("authorization").is_some_and(|v| v.starts_with("vapid t=")));
+        // The payload is encrypted for the browser, never sent in the clear.
+        let body = String::from_utf8_lossy(&request.body);
+        assert!(!body.contains("New direct message"));
+
+        assert!(PushNotifier::new(
+            "not-a-key",
+            "mailto:admin@example.com",
+            Arc::new(|_| Box::pin(async { Ok(201u16) }) as PushPostFuture),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,386 @@
+use dashmap::DashMap;
+use paracord_db::rate_limits::AuthGuardStateRow;
+use paracord_db::{DbError, DbPool};
+use std::sync::Arc;
+
+/// Maximum rows removed per purge call against the database store.
+const DB_PURGE_BATCH: i64 = 1000;
+
+fn window_start(window_seconds: i64, now: i64) -> i64 {
+    now.div_euclid(window_seconds.max(1))
+}
+
+/// Process-local store. Cheap, but each server process counts on its own.
+#[derive(Clone, Default)]
+pub struct MemoryRateLimitStore {
+    /// key -> (window index, window length, count)
+    windows: Arc<DashMap<String, (i64, i64, i64)>>,
+    auth_guard: Arc<DashMap<String, AuthGuardStateRow>>,
+}
+
+impl MemoryRateLimitStore {
+    pub async fn increment(
+        &self,
+        key: &str,
+        window_seconds: i64,
+        now: i64,
+    ) -> Result<i64, DbError> {
+        let window_seconds = window_seconds.max(1);
+        let current = window_start(window_seconds, now);
+        let mut entry = self
+            .windows
+            .entry(key.to_string())
+            .or_insert((current, window_seconds, 0));
+        if entry.0 != current || entry.1 != window_seconds {
+            *entry = (current, window_seconds, 0);
+        }
+        entry.2 = entry.2.saturating_add(1);
+        Ok(entry.2)
+    }
+
+    pub async fn purge_expired(&self, now: i64) -> Result<u64, DbError> {
+        let before = self.windows.len();
+        self.windows.retain(|_, (start, seconds, _)| {
+            start.saturating_add(1).saturating_mul(*seconds) > now
+        });
+        Ok(before.saturating_sub(self.windows.len()) as u64)
+    }
+
+    pub async fn auth_guard_states(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<AuthGuardStateRow>, DbError> {
+        Ok(keys
+            .iter()
+            .filter_map(|key| self.auth_guard.get(key).map(|row| row.clone()))
+            .collect())
+    }
+
+    pub async fn record_auth_guard_failure_with(
+        &self,
+        key: &str,
+        now: i64,
+        locked_until: impl FnOnce(i64) -> i64,
+    ) -> Result<AuthGuardStateRow, DbError> {
+        let mut entry =
+            self.auth_guard
+                .entry(key.to_string())
+                .or_insert_with(|| AuthGuardStateRow {
+                    guard_key: key.to_string(),
+                    failures: 0,
+                    locked_until: 0,
+                    last_seen: now,
+                });
+        entry.failures = entry.failures.saturating_add(1);
+        entry.locked_until = locked_until(entry.failures);
+        entry.last_seen = now;
+        Ok(entry.clone())
+    }
+
+    pub async fn clear_auth_guard_keys(&self, keys: &[String]) -> Result<u64, DbError> {
+        Ok(keys
+            .iter()
+            .filter(|key| self.auth_guard.remove(*key).is_some())
+            .count() as u64)
+    }
+
+    pub async fn purge_auth_guard_older_than(&self, min_last_seen: i64) -> Result<u64, DbError> {
+        let before = self.auth_guard.len();
+        self.auth_guard
+            .retain(|_, row| row.last_seen >= min_last_seen || row.locked_until >= min_last_seen);
+        Ok(before.saturating_sub(self.auth_guard.len()) as u64)
+    }
+}
+
+/// Database-backed store. Every hit is a write, but limits are shared by
+/// all server processes using the same database.
+#[derive(Clone)]
+pub struct DbRateLimitStore {
+    pool: DbPool,
+}
+
+impl DbRateLimitStore {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn increment(
+        &self,
+        key: &str,
+        window_seconds: i64,
+        now: i64,
+    ) -> Result<i64, DbError> {
+        let window_seconds = window_seconds.max(1);
+        paracord_db::rate_limits::increment_window_counter(
+            &self.pool,
+            key,
+            window_start(window_seconds, now),
+            window_seconds,
+        )
+        .await
+    }
+
+    pub async fn purge_expired(&self, now: i64) -> Result<u64, DbError> {
+        paracord_db::rate_limits::purge_expired_window_counters(&self.pool, now, DB_PURGE_BATCH)
+            .await
+    }
+
+    pub async fn auth_guard_states(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<AuthGuardStateRow>, DbError> {
+        paracord_db::rate_limits::get_auth_guard_states(&self.pool, keys).await
+    }
+
+    pub async fn record_auth_guard_failure_with(
+        &self,
+        key: &str,
+        now: i64,
+        locked_until: impl FnOnce(i64) -> i64,
+    ) -> Result<AuthGuardStateRow, DbError> {
+        paracord_db::rate_limits::record_auth_guard_failure_with(&self.pool, key, now, locked_until)
+            .await
+    }
+
+    pub async fn clear_auth_guard_keys(&self, keys: &[String]) -> Result<u64, DbError> {
+        paracord_db::rate_limits::clear_auth_guard_keys(&self.pool, keys).await
+    }
+
+    pub async fn purge_auth_guard_older_than(&self, min_last_seen: i64) -> Result<u64, DbError> {
+        paracord_db::rate_limits::purge_auth_guard_older_than(
+            &self.pool,
+            min_last_seen,
+            DB_PURGE_BATCH,
+        )
+        .await
+    }
+}
+
+/// Storage for fixed-window request counters and the login failure guard,
+/// selected by the `[abuse] rate_limit_store` config option.
+///
+/// Windows are aligned to multiples of `window_seconds` since the epoch, so
+/// every process sharing a store agrees on where a window starts.
+#[derive(Clone)]
+pub enum RateLimitStore {
+    Memory(MemoryRateLimitStore),
+    Database(DbRateLimitStore),
+}
+
+impl Default for RateLimitStore {
+    fn default() -> Self {
+        RateLimitStore::Memory(MemoryRateLimitStore::default())
+    }
+}
+
+impl RateLimitStore {
+    /// Count a hit against `key` and return the total for the current window.
+    pub async fn increment(
+        &self,
+        key: &str,
+        window_seconds: i64,
+        now: i64,
+    ) -> Result<i64, DbError> {
+        match self {
+            RateLimitStore::Memory(s) => s.increment(key, window_seconds, now).await,
+            RateLimitStore::Database(s) => s.increment(key, window_seconds, now).await,
+        }
+    }
+
+    /// Count a hit and report whether `key` is still within `max_count` for
+    /// the current window. Store errors fail open.
+    pub async fn check(&self, key: &str, window_seconds: i64, max_count: u32, now: i64) -> bool {
+        match self.increment(key, window_seconds, now).await {
+            Ok(count) => count <= i64::from(max_count),
+            Err(err) => {
+                tracing::warn!("rate-limit store error for '{}': {}", key, err);
+                true
+            }
+        }
+    }
+
+    /// Drop counters whose window ended at or before `now`.
+    pub async fn purge_expired(&self, now: i64) -> Result<u64, DbError> {
+        match self {
+            RateLimitStore::Memory(s) => s.purge_expired(now).await,
+            RateLimitStore::Database(s) => s.purge_expired(now).await,
+        }
+    }
+
+    /// Current guard state for any of `keys` that has recorded failures.
+    pub async fn auth_guard_states(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<AuthGuardStateRow>, DbError> {
+        match self {
+            RateLimitStore::Memory(s) => s.auth_guard_states(keys).await,
+            RateLimitStore::Database(s) => s.auth_guard_states(keys).await,
+        }
+    }
+
+    /// Record a failed login against `key`, locking it out with exponential
+    /// backoff once the failure threshold is reached.
+    pub async fn record_auth_guard_failure(
+        &self,
+        key: &str,
+        now: i64,
+    ) -> Result<AuthGuardStateRow, DbError> {
+        self.record_auth_guard_failure_with(key, now, |failures| {
+            paracord_db::rate_limits::auth_guard_locked_until(failures, now)
+        })
+        .await
+    }
+
+    /// Record a failed login against `key` with a caller-chosen lock
+    /// deadline for the new failure count.
+    pub async fn record_auth_guard_failure_with(
+        &self,
+        key: &str,
+        now: i64,
+        locked_until: impl FnOnce(i64) -> i64,
+    ) -> Result<AuthGuardStateRow, DbError> {
+        match self {
+            RateLimitStore::Memory(s) => {
+                s.record_auth_guard_failure_with(key, now, locked_until)
+                    .await
+            }
+            RateLimitStore::Database(s) => {
+                s.record_auth_guard_failure_with(key, now, locked_until)
+                    .await
+            }
+        }
+    }
+
+    /// Forget the guard state for `keys` (after a successful login).
+    pub async fn clear_auth_guard_keys(&self, keys: &[String]) -> Result<u64, DbError> {
+        match self {
+            RateLimitStore::Memory(s) => s.clear_auth_guard_keys(keys).await,
+            RateLimitStore::Database(s) => s.clear_auth_guard_keys(keys).await,
+        }
+    }
+
+    /// Drop guard state not touched since `min_last_seen`, unless it is
+    /// still locked at that time.
+    pub async fn purge_auth_guard_older_than(&self, min_last_seen: i64) -> Result<u64, DbError> {
+        match self {
+            RateLimitStore::Memory(s) => s.purge_auth_guard_older_than(min_last_seen).await,
+            RateLimitStore::Database(s) => s.purge_auth_guard_older_than(min_last_seen).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn database_store() -> RateLimitStore {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-core-rate-limit-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = paracord_db::create_pool(&db_url, 1).await.expect("pool");
+        paracord_db::run_migrations(&pool)
+            .await
+            .expect("migrations");
+        RateLimitStore::Database(DbRateLimitStore::new(pool))
+    }
+
+    async fn stores() -> Vec<(&'static str, RateLimitStore)> {
+        vec![
+            ("memory", RateLimitStore::default()),
+            ("database", database_store().await),
+        ]
+    }
+
+    #[tokio::test]
+    async fn increments_within_a_window_and_resets_on_the_next() {
+        for (name, store) in stores().await {
+            let now = 1_700_000_080;
+            assert_eq!(store.increment("k", 60, now).await.unwrap(), 1, "{name}");
+            assert_eq!(
+                store.increment("k", 60, now + 5).await.unwrap(),
+                2,
+                "{name}"
+            );
+            assert_eq!(
+                store.increment("other", 60, now).await.unwrap(),
+                1,
+                "{name}"
+            );
+            // 1_700_000_080 is 20s before a minute boundary.
+            assert_eq!(
+                store.increment("k", 60, now + 20).await.unwrap(),
+                1,
+                "{name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn check_detects_over_limit() {
+        for (name, store) in stores().await {
+            let now = 1_700_000_000;
+            for _ in 0..3 {
+                assert!(store.check("burst", 10, 3, now).await, "{name}");
+            }
+            assert!(!store.check("burst", 10, 3, now).await, "{name}");
+            assert!(store.check("burst", 10, 3, now + 10).await, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn purge_expired_drops_only_finished_windows() {
+        for (name, store) in stores().await {
+            let now = 1_700_000_000;
+            store.increment("short", 1, now).await.unwrap();
+            store.increment("long", 3600, now).await.unwrap();
+            assert_eq!(store.purge_expired(now + 2).await.unwrap(), 1, "{name}");
+            assert_eq!(
+                store.increment("long", 3600, now + 2).await.unwrap(),
+                2,
+                "{name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn auth_guard_locks_out_and_clears() {
+        for (name, store) in stores().await {
+            let key = "acct:user@example.com".to_string();
+            let now = 1_700_000_000_i64;
+            for i in 1..=5_i64 {
+                let row = store
+                    .record_auth_guard_failure(&key, now + i)
+                    .await
+                    .unwrap();
+                assert_eq!(row.failures, i, "{name}");
+            }
+            let rows = store
+                .auth_guard_states(std::slice::from_ref(&key))
+                .await
+                .unwrap();
+            assert_eq!(rows.len(), 1, "{name}");
+            assert!(rows[0].locked_until > now + 5, "{name}");
+
+            assert_eq!(
+                store.purge_auth_guard_older_than(now).await.unwrap(),
+                0,
+                "{name}"
+            );
+            assert_eq!(
+                store
+                    .clear_auth_guard_keys(std::slice::from_ref(&key))
+                    .await
+                    .unwrap(),
+                1,
+                "{name}"
+            );
+            assert!(store.auth_guard_states(&[key]).await.unwrap().is_empty());
+        }
+    }
+}
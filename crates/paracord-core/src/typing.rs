@@ -0,0 +1,194 @@
+use dashmap::DashMap;
+use paracord_db::DbPool;
+use paracord_models::gateway::EVENT_TYPING_STOP;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::events::EventBus;
+
+/// How long a typing start stays active without being refreshed. Slightly
+/// longer than the client's own indicator timeout.
+const TYPING_TTL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypingChannel {
+    pub channel_id: i64,
+    pub guild_id: Option<i64>,
+}
+
+struct TypingEntry {
+    guild_id: Option<i64>,
+    expires_at: Instant,
+}
+
+/// Channels each user is currently typing in, so typing can be stopped for
+/// peers when the user disconnects instead of lingering until it times out.
+pub struct TypingTracker {
+    users: DashMap<i64, HashMap<i64, TypingEntry>>,
+    ttl: Duration,
+}
+
+impl TypingTracker {
+    pub fn new() -> Self {
+        Self {
+            users: DashMap::new(),
+            ttl: TYPING_TTL,
+        }
+    }
+
+    pub fn start(&self, user_id: i64, channel_id: i64, guild_id: Option<i64>, now: Instant) {
+        let mut channels = self.users.entry(user_id).or_default();
+        channels.retain(|_, entry| entry.expires_at > now);
+        channels.insert(
+            channel_id,
+            TypingEntry {
+                guild_id,
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    /// Forget typing in one channel, e.g. once the user's message is sent.
+    pub fn stop(&self, user_id: i64, channel_id: i64) {
+        let emptied = match self.users.get_mut(&user_id) {
+            Some(mut channels) => {
+                channels.remove(&channel_id);
+                channels.is_empty()
+            }
+            None => false,
+        };
+        if emptied {
+            self.users
+                .remove_if(&user_id, |_, channels| channels.is_empty());
+        }
+    }
+
+    pub fn is_typing(&self, user_id: i64, channel_id: i64, now: Instant) -> bool {
+        self.users
+            .get(&user_id)
+            .and_then(|channels| channels.get(&channel_id).map(|e| e.expires_at > now))
+            .unwrap_or(false)
+    }
+
+    /// Remove every entry for `user_id`, returning the channels where typing
+    /// had not yet expired.
+    pub fn clear_user(&self, user_id: i64, now: Instant) -> Vec<TypingChannel> {
+        let Some((_, channels)) = self.users.remove(&user_id) else {
+            return Vec::new();
+        };
+        let mut active: Vec<TypingChannel> = channels
+            .into_iter()
+            .filter(|(_, entry)| entry.expires_at > now)
+            .map(|(channel_id, entry)| TypingChannel {
+                channel_id,
+                guild_id: entry.guild_id,
+            })
+            .collect();
+        active.sort_by_key(|c| c.channel_id);
+        active
+    }
+}
+
+impl Default for TypingTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static TYPING: OnceLock<TypingTracker> = OnceLock::new();
+
+/// The process-wide tracker shared by the gateway and REST typing routes.
+pub fn typing_tracker() -> &'static TypingTracker {
+    TYPING.get_or_init(TypingTracker::new)
+}
+
+/// Clear `user_id`'s typing state and dispatch `TYPING_STOP` for every
+/// channel they were still typing in. Returns those channels.
+pub async fn clear_user_typing(
+    pool: &DbPool,
+    event_bus: &EventBus,
+    tracker: &TypingTracker,
+    user_id: i64,
+) -> Vec<TypingChannel> {
+    let channels = tracker.clear_user(user_id, Instant::now());
+    for channel in &channels {
+        let payload = json!({
+            "channel_id": channel.channel_id.to_string(),
+            "user_id": user_id.to_string(),
+        });
+        match channel.guild_id {
+            Some(guild_id) => event_bus.dispatch(EVENT_TYPING_STOP, payload, Some(guild_id)),
+            None => {
+                let recipient_ids =
+                    crate::channel::dm_event_recipients(pool, channel.channel_id, user_id).await;
+                event_bus.dispatch_to_users(EVENT_TYPING_STOP, payload, recipient_ids);
+            }
+        }
+    }
+    channels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_user_returns_only_unexpired_channels() {
+        let tracker = TypingTracker::new();
+        let now = Instant::now();
+        tracker.start(1, 10, Some(100), now);
+        tracker.start(1, 11, None, now + Duration::from_secs(5));
+        tracker.start(2, 10, Some(100), now);
+
+        let later = now + Duration::from_secs(12);
+        assert_eq!(
+            tracker.clear_user(1, later),
+            vec![TypingChannel {
+                channel_id: 11,
+                guild_id: None
+            }]
+        );
+        assert!(!tracker.is_typing(1, 11, later));
+        assert!(tracker.clear_user(1, later).is_empty());
+        assert!(tracker.is_typing(2, 10, now));
+    }
+
+    #[test]
+    fn stop_forgets_a_single_channel() {
+        let tracker = TypingTracker::new();
+        let now = Instant::now();
+        tracker.start(1, 10, Some(100), now);
+        tracker.start(1, 11, Some(100), now);
+        tracker.stop(1, 10);
+        assert!(!tracker.is_typing(1, 10, now));
+        assert!(tracker.is_typing(1, 11, now));
+        tracker.stop(1, 11);
+        assert!(tracker.clear_user(1, now).is_empty());
+    }
+
+    #[tokio::test]
+    async fn disconnect_dispatches_one_typing_stop_per_channel() {
+        let pool = paracord_db::create_pool("sqlite::memory:", 1)
+            .await
+            .unwrap();
+        let bus = EventBus::default();
+        let mut events = bus.subscribe_system();
+        let tracker = TypingTracker::new();
+        tracker.start(7, 10, Some(100), Instant::now());
+
+        let cleared = clear_user_typing(&pool, &bus, &tracker, 7).await;
+        assert_eq!(cleared.len(), 1);
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.event_type, EVENT_TYPING_STOP);
+        assert_eq!(event.guild_id, Some(100));
+        assert_eq!(event.payload["user_id"], "7");
+        assert_eq!(event.payload["channel_id"], "10");
+        assert!(!tracker.is_typing(7, 10, Instant::now()));
+
+        // A second disconnect has nothing left to stop.
+        assert!(clear_user_typing(&pool, &bus, &tracker, 7).await.is_empty());
+        assert!(events.try_recv().is_err());
+    }
+}
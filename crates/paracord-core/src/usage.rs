@@ -0,0 +1,343 @@
+use dashmap::DashMap;
+
+const MINUTE_WINDOW_SECONDS: i64 = 60;
+const HOUR_WINDOW_SECONDS: i64 = 3600;
+/// How long an over-threshold user stays flagged when auto-timeout is disabled.
+const FLAG_ONLY_SECONDS: i64 = 3600;
+
+/// Abuse thresholds for per-user activity. A value of `0` disables that check.
+#[derive(Clone, Debug)]
+pub struct UsageThresholds {
+    pub messages_per_minute: u32,
+    pub uploads_per_minute: u32,
+    pub upload_bytes_per_hour: u64,
+    /// How long an over-threshold user is blocked from sending messages and
+    /// uploading files. `0` only flags the user for admin review.
+    pub auto_timeout_seconds: u64,
+}
+
+impl Default for UsageThresholds {
+    fn default() -> Self {
+        Self {
+            messages_per_minute: 60,
+            uploads_per_minute: 20,
+            upload_bytes_per_hour: 2_147_483_648, // 2GB
+            auto_timeout_seconds: 600,
+        }
+    }
+}
+
+#[derive(Default)]
+struct UserCounters {
+    minute_window_start: i64,
+    messages: u32,
+    uploads: u32,
+    hour_window_start: i64,
+    upload_bytes: u64,
+    flagged_until: Option<i64>,
+    last_activity: i64,
+}
+
+impl UserCounters {
+    fn roll_windows(&mut self, now: i64) {
+        if now.saturating_sub(self.minute_window_start) >= MINUTE_WINDOW_SECONDS {
+            self.minute_window_start = now;
+            self.messages = 0;
+            self.uploads = 0;
+        }
+        if now.saturating_sub(self.hour_window_start) >= HOUR_WINDOW_SECONDS {
+            self.hour_window_start = now;
+            self.upload_bytes = 0;
+        }
+    }
+
+    fn over_threshold(&self, thresholds: &UsageThresholds) -> bool {
+        (thresholds.messages_per_minute > 0 && self.messages > thresholds.messages_per_minute)
+            || (thresholds.uploads_per_minute > 0 && self.uploads > thresholds.uploads_per_minute)
+            || (thresholds.upload_bytes_per_hour > 0
+                && self.upload_bytes > thresholds.upload_bytes_per_hour)
+    }
+
+    /// Flag the user if any counter crossed its threshold. Returns `true` only
+    /// on the transition from unflagged to flagged.
+    fn evaluate(&mut self, now: i64, thresholds: &UsageThresholds) -> bool {
+        if !self.over_threshold(thresholds) {
+            return false;
+        }
+        let already_flagged = self.flagged_until.is_some_and(|until| until > now);
+        let flag_seconds = if thresholds.auto_timeout_seconds > 0 {
+            i64::try_from(thresholds.auto_timeout_seconds).unwrap_or(i64::MAX)
+        } else {
+            FLAG_ONLY_SECONDS
+        };
+        self.flagged_until = Some(now.saturating_add(flag_seconds));
+        !already_flagged
+    }
+}
+
+/// Point-in-time view of a user's counters, exposed to admins.
+#[derive(Clone, Debug)]
+pub struct UserUsageSnapshot {
+    pub user_id: i64,
+    pub messages_per_minute: u32,
+    pub uploads_per_minute: u32,
+    pub upload_bytes_per_hour: u64,
+    /// Unix timestamp (seconds) until which the user is flagged.
+    pub flagged_until: Option<i64>,
+}
+
+/// Lightweight in-memory per-user activity counters used for abuse detection.
+///
+/// Counters use fixed windows (one minute for messages/uploads, one hour for
+/// upload bytes) that reset lazily on the next access. Idle entries are
+/// dropped by [`UserUsageTracker::prune_idle`], which the server calls on a
+/// timer so memory stays proportional to recently-active users.
+pub struct UserUsageTracker {
+    users: DashMap<i64, UserCounters>,
+    thresholds: UsageThresholds,
+    enabled: bool,
+}
+
+impl UserUsageTracker {
+    pub fn new(thresholds: UsageThresholds) -> Self {
+        Self {
+            users: DashMap::new(),
+            thresholds,
+            enabled: true,
+        }
+    }
+
+    /// A tracker that records nothing and never flags anyone.
+    pub fn disabled() -> Self {
+        Self {
+            users: DashMap::new(),
+            thresholds: UsageThresholds::default(),
+            enabled: false,
+        }
+    }
+
+    pub fn thresholds(&self) -> &UsageThresholds {
+        &self.thresholds
+    }
+
+    /// Count a sent message. Returns `true` if this pushed the user over a threshold.
+    pub fn record_message(&self, user_id: i64) -> bool {
+        self.record_message_at(user_id, chrono::Utc::now().timestamp())
+    }
+
+    /// Count an upload of `bytes`. Returns `true` if this pushed the user over a threshold.
+    pub fn record_upload(&self, user_id: i64, bytes: u64) -> bool {
+        self.record_upload_at(user_id, bytes, chrono::Utc::now().timestamp())
+    }
+
+    /// When auto-timeout is enabled, returns the unix timestamp until which the
+    /// user is blocked from sending messages and uploading files.
+    pub fn timed_out_until(&self, user_id: i64) -> Option<i64> {
+        self.timed_out_until_at(user_id, chrono::Utc::now().timestamp())
+    }
+
+    /// Lift any flag or auto-timeout on the user and reset their counters.
+    pub fn clear(&self, user_id: i64) -> bool {
+        self.users.remove(&user_id).is_some()
+    }
+
+    /// Snapshot counters for all tracked users, busiest first.
+    pub fn snapshot(&self, limit: usize) -> Vec<UserUsageSnapshot> {
+        self.snapshot_at(chrono::Utc::now().timestamp(), limit)
+    }
+
+    /// Drop users with no activity in the last hour and no active flag.
+    pub fn prune_idle(&self) -> usize {
+        self.prune_idle_at(chrono::Utc::now().timestamp())
+    }
+
+    fn record_message_at(&self, user_id: i64, now: i64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let mut counters = self.users.entry(user_id).or_default();
+        counters.roll_windows(now);
+        counters.messages = counters.messages.saturating_add(1);
+        counters.last_activity = now;
+        counters.evaluate(now, &self.thresholds)
+    }
+
+    fn record_upload_at(&self, user_id: i64, bytes: u64, now: i64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let mut counters = self.users.entry(user_id).or_default();
+        counters.roll_windows(now);
+        counters.uploads = counters.uploads.saturating_add(1);
+        counters.upload_bytes = counters.upload_bytes.saturating_add(bytes);
+        counters.last_activity = now;
+        counters.evaluate(now, &self.thresholds)
+    }
+
+    fn timed_out_until_at(&self, user_id: i64, now: i64) -> Option<i64> {
+        if !self.enabled || self.thresholds.auto_timeout_seconds == 0 {
+            return None;
+        }
+        self.users
+            .get(&user_id)
+            .and_then(|counters| counters.flagged_until)
+            .filter(|until| *until > now)
+    }
+
+    fn snapshot_at(&self, now: i64, limit: usize) -> Vec<UserUsageSnapshot> {
+        let mut snapshots: Vec<UserUsageSnapshot> = self
+            .users
+            .iter()
+            .map(|entry| {
+                let counters = entry.value();
+                let minute_live =
+                    now.saturating_sub(counters.minute_window_start) < MINUTE_WINDOW_SECONDS;
+                let hour_live =
+                    now.saturating_sub(counters.hour_window_start) < HOUR_WINDOW_SECONDS;
+                UserUsageSnapshot {
+                    user_id: *entry.key(),
+                    messages_per_minute: if minute_live { counters.messages } else { 0 },
+                    uploads_per_minute: if minute_live { counters.uploads } else { 0 },
+                    upload_bytes_per_hour: if hour_live { counters.upload_bytes } else { 0 },
+                    flagged_until: counters.flagged_until.filter(|until| *until > now),
+                }
+            })
+            .collect();
+        snapshots.sort_by(|a, b| {
+            b.flagged_until
+                .is_some()
+                .cmp(&a.flagged_until.is_some())
+                .then(b.messages_per_minute.cmp(&a.messages_per_minute))
+                .then(b.upload_bytes_per_hour.cmp(&a.upload_bytes_per_hour))
+        });
+        snapshots.truncate(limit);
+        snapshots
+    }
+
+    fn prune_idle_at(&self, now: i64) -> usize {
+        let before = self.users.len();
+        self.users.retain(|_, counters| {
+            let flagged = counters.flagged_until.is_some_and(|until| until > now);
+            flagged || now.saturating_sub(counters.last_activity) < HOUR_WINDOW_SECONDS
+        });
+        before.saturating_sub(self.users.len())
+    }
+}
+
+impl Default for UserUsageTracker {
+    fn default() -> Self {
+        Self::new(UsageThresholds::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UsageThresholds, UserUsageTracker};
+
+    fn tracker(messages_per_minute: u32, auto_timeout_seconds: u64) -> UserUsageTracker {
+        UserUsageTracker::new(UsageThresholds {
+            messages_per_minute,
+            uploads_per_minute: 2,
+            upload_bytes_per_hour: 1_000,
+            auto_timeout_seconds,
+        })
+    }
+
+    #[test]
+    fn counters_increment_within_window() {
+        let tracker = tracker(10, 60);
+        for _ in 0..3 {
+            tracker.record_message_at(7, 1_000);
+        }
+        tracker.record_upload_at(7, 250, 1_010);
+
+        let snapshot = tracker.snapshot_at(1_020, 10);
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].messages_per_minute, 3);
+        assert_eq!(snapshot[0].uploads_per_minute, 1);
+        assert_eq!(snapshot[0].upload_bytes_per_hour, 250);
+        assert!(snapshot[0].flagged_until.is_none());
+    }
+
+    #[test]
+    fn counters_decay_after_window_elapses() {
+        let tracker = tracker(10, 60);
+        tracker.record_message_at(7, 1_000);
+        tracker.record_message_at(7, 1_001);
+        tracker.record_upload_at(7, 400, 1_002);
+
+        // Minute counters reset, hourly byte counter survives.
+        let snapshot = tracker.snapshot_at(1_070, 10);
+        assert_eq!(snapshot[0].messages_per_minute, 0);
+        assert_eq!(snapshot[0].upload_bytes_per_hour, 400);
+
+        tracker.record_message_at(7, 1_070);
+        let snapshot = tracker.snapshot_at(1_071, 10);
+        assert_eq!(snapshot[0].messages_per_minute, 1);
+
+        let snapshot = tracker.snapshot_at(4_700, 10);
+        assert_eq!(snapshot[0].upload_bytes_per_hour, 0);
+    }
+
+    #[test]
+    fn over_threshold_user_is_flagged_and_timed_out() {
+        let tracker = tracker(3, 120);
+        for second in 0..3 {
+            assert!(!tracker.record_message_at(9, 1_000 + second));
+        }
+        assert!(tracker.timed_out_until_at(9, 1_003).is_none());
+
+        assert!(tracker.record_message_at(9, 1_003));
+        assert_eq!(tracker.timed_out_until_at(9, 1_004), Some(1_123));
+        // Further activity while flagged does not re-report the transition.
+        assert!(!tracker.record_message_at(9, 1_005));
+        assert!(tracker.timed_out_until_at(9, 1_200).is_none());
+
+        let snapshot = tracker.snapshot_at(1_006, 10);
+        assert!(snapshot[0].flagged_until.is_some());
+    }
+
+    #[test]
+    fn upload_byte_threshold_flags_user() {
+        let tracker = tracker(100, 60);
+        assert!(!tracker.record_upload_at(3, 900, 1_000));
+        assert!(tracker.record_upload_at(3, 200, 1_100));
+        assert!(tracker.timed_out_until_at(3, 1_101).is_some());
+    }
+
+    #[test]
+    fn flag_only_mode_never_times_out() {
+        let tracker = tracker(1, 0);
+        tracker.record_message_at(5, 1_000);
+        assert!(tracker.record_message_at(5, 1_001));
+        assert!(tracker.timed_out_until_at(5, 1_002).is_none());
+        assert!(tracker.snapshot_at(1_002, 10)[0].flagged_until.is_some());
+    }
+
+    #[test]
+    fn disabled_tracker_records_nothing() {
+        let tracker = UserUsageTracker::disabled();
+        for _ in 0..1_000 {
+            assert!(!tracker.record_message(1));
+        }
+        assert!(tracker.snapshot(10).is_empty());
+    }
+
+    #[test]
+    fn prune_drops_idle_unflagged_users() {
+        let tracker = tracker(1, 60);
+        tracker.record_message_at(1, 1_000);
+        tracker.record_message_at(2, 1_000);
+        tracker.record_message_at(2, 1_001);
+        tracker.record_message_at(3, 4_000);
+
+        // User 1 is idle; user 2's flag has expired; user 3 is recent.
+        assert_eq!(tracker.prune_idle_at(4_700), 2);
+        let remaining: Vec<i64> = tracker
+            .snapshot_at(4_700, 10)
+            .iter()
+            .map(|s| s.user_id)
+            .collect();
+        assert_eq!(remaining, vec![3]);
+    }
+}
@@ -0,0 +1,13 @@
+-- Announcement channel follows: published messages are delivered to the
+-- target channel through a dedicated webhook.
+CREATE TABLE IF NOT EXISTS channel_followers (
+    id BIGINT PRIMARY KEY,
+    source_channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    target_channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    webhook_id BIGINT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (source_channel_id, target_channel_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_channel_followers_source ON channel_followers(source_channel_id);
@@ -0,0 +1,15 @@
+-- Long-lived tokens users mint for scripting against their own account.
+-- Only a SHA-256 hash of the token is stored; scopes are a comma-separated
+-- list.
+CREATE TABLE IF NOT EXISTS personal_access_tokens (
+    id BIGINT PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT NOT NULL,
+    expires_at TEXT,
+    last_used_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_personal_access_tokens_user ON personal_access_tokens(user_id);
@@ -0,0 +1,10 @@
+-- Copies of a published announcement delivered to follower channels, so
+-- edits and deletes of the original can be carried over to them.
+CREATE TABLE IF NOT EXISTS crosspost_mirrors (
+    mirror_message_id BIGINT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    source_message_id BIGINT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    webhook_id BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_crosspost_mirrors_source ON crosspost_mirrors(source_message_id);
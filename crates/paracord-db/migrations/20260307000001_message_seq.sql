@@ -0,0 +1,14 @@
+-- Per-channel sequence number assigned at insert, so clients can sync a
+-- channel in commit order without relying on snowflake ordering. Ephemeral
+-- messages have no sequence.
+ALTER TABLE messages ADD COLUMN seq BIGINT;
+
+WITH numbered AS MATERIALIZED (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY channel_id ORDER BY id) AS seq
+    FROM messages
+    WHERE visible_to IS NULL
+)
+UPDATE messages SET seq = (SELECT numbered.seq FROM numbered WHERE numbered.id = messages.id)
+WHERE visible_to IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_messages_channel_seq ON messages(channel_id, seq);
@@ -0,0 +1,2 @@
+ALTER TABLE user_settings
+ADD COLUMN hide_mutual_guilds BOOLEAN NOT NULL DEFAULT FALSE;
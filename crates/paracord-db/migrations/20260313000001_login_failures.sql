@@ -0,0 +1,13 @@
+-- Failed password verifications, one row per failure, keyed by subject
+-- (`user:<id>` or `ip:<addr>`). Counted over a sliding window to lock out
+-- credential stuffing; rows outside the window are purged.
+CREATE TABLE IF NOT EXISTS login_failures (
+    subject_key TEXT NOT NULL,
+    failed_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_failures_subject
+    ON login_failures (subject_key, failed_at);
+
+CREATE INDEX IF NOT EXISTS idx_login_failures_failed_at
+    ON login_failures (failed_at);
@@ -0,0 +1,31 @@
+-- Full-text index over message content, kept in sync by triggers. The FTS
+-- rowid is the message id. E2EE DM ciphertext is never indexed.
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+    content,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO messages_fts (rowid, content)
+SELECT id, content
+FROM messages
+WHERE content IS NOT NULL
+  AND (flags & 1) = 0;
+
+CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages
+WHEN new.content IS NOT NULL AND (new.flags & 1) = 0
+BEGIN
+    INSERT INTO messages_fts (rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content, flags ON messages
+BEGIN
+    DELETE FROM messages_fts WHERE rowid = old.id;
+    INSERT INTO messages_fts (rowid, content)
+    SELECT new.id, new.content
+    WHERE new.content IS NOT NULL AND (new.flags & 1) = 0;
+END;
+
+CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages
+BEGIN
+    DELETE FROM messages_fts WHERE rowid = old.id;
+END;
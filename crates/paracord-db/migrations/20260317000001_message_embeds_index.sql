@@ -0,0 +1,3 @@
+-- Link preview embeds are loaded for whole pages of messages at once.
+CREATE INDEX IF NOT EXISTS idx_message_embeds_message
+    ON message_embeds (message_id);
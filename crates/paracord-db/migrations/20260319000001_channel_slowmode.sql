@@ -0,0 +1,8 @@
+-- When each member last posted in a slowmode channel (unix seconds), so
+-- `channels.rate_limit_per_user` keeps applying across restarts.
+CREATE TABLE IF NOT EXISTS channel_slowmode (
+    channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    last_sent_at BIGINT NOT NULL,
+    PRIMARY KEY (channel_id, user_id)
+);
@@ -0,0 +1,3 @@
+-- Announcement-style channels: everyone can read, only members with
+-- MANAGE_MESSAGES can post.
+ALTER TABLE channels ADD COLUMN read_only BOOLEAN NOT NULL DEFAULT FALSE;
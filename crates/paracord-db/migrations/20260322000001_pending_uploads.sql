@@ -0,0 +1,17 @@
+-- Resumable (tus) uploads in progress. The bytes received so far live in a
+-- temp file under the storage path; once `upload_offset` reaches
+-- `total_size` the file becomes a pending attachment.
+CREATE TABLE IF NOT EXISTS pending_uploads (
+    id BIGINT PRIMARY KEY,
+    uploader_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    filename TEXT NOT NULL,
+    content_type TEXT,
+    total_size BIGINT NOT NULL,
+    upload_offset BIGINT NOT NULL DEFAULT 0,
+    attachment_id BIGINT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_uploads_expires ON pending_uploads(expires_at);
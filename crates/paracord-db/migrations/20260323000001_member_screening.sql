@@ -0,0 +1,10 @@
+-- Membership screening: when enabled for a guild, members who join through
+-- an invite stay `pending` (unable to post) until they accept its rules.
+CREATE TABLE IF NOT EXISTS guild_member_screening (
+    guild_id BIGINT PRIMARY KEY REFERENCES spaces(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    rules TEXT NOT NULL DEFAULT '',
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+ALTER TABLE members ADD COLUMN pending BOOLEAN NOT NULL DEFAULT FALSE;
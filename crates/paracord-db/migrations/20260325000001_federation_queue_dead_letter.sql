@@ -0,0 +1,6 @@
+-- Outbound federation deliveries that exhaust their retries are kept as dead
+-- letters instead of being deleted, so operators can inspect and requeue them.
+ALTER TABLE federation_outbound_queue ADD COLUMN dead_lettered_at_ms BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_fed_outbound_dead_lettered
+    ON federation_outbound_queue(dead_lettered_at_ms);
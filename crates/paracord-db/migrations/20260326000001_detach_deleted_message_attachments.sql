@@ -0,0 +1,11 @@
+-- Deleting a message used to cascade its attachment rows away and leave the
+-- stored files behind. Detach them instead and mark them expired so the
+-- orphaned-attachment collector removes both row and file.
+CREATE TRIGGER IF NOT EXISTS messages_detach_attachments BEFORE DELETE ON messages
+BEGIN
+    UPDATE attachments
+    SET message_id = NULL,
+        upload_channel_id = NULL,
+        upload_expires_at = datetime('now')
+    WHERE message_id = old.id;
+END;
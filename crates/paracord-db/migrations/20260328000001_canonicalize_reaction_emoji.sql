@@ -0,0 +1,30 @@
+-- Reactions stored before emoji canonicalization are keyed by the emoji as
+-- sent, so `❤` and `❤️` were counted apart. Keep the original for display
+-- and fold the key into its canonical form (variation selectors removed).
+UPDATE reactions SET emoji_display = emoji_name WHERE emoji_display IS NULL;
+
+-- A user who reacted with several spellings keeps a single reaction, which
+-- is a burst reaction if any of them was.
+UPDATE reactions SET burst = TRUE
+WHERE NOT burst AND EXISTS (
+    SELECT 1 FROM reactions other
+    WHERE other.message_id = reactions.message_id
+      AND other.user_id = reactions.user_id
+      AND other.burst
+      AND REPLACE(REPLACE(other.emoji_name, char(65038), ''), char(65039), '')
+        = REPLACE(REPLACE(reactions.emoji_name, char(65038), ''), char(65039), '')
+);
+
+DELETE FROM reactions
+WHERE EXISTS (
+    SELECT 1 FROM reactions other
+    WHERE other.message_id = reactions.message_id
+      AND other.user_id = reactions.user_id
+      AND other.emoji_name < reactions.emoji_name
+      AND REPLACE(REPLACE(other.emoji_name, char(65038), ''), char(65039), '')
+        = REPLACE(REPLACE(reactions.emoji_name, char(65038), ''), char(65039), '')
+);
+
+UPDATE reactions
+SET emoji_name = REPLACE(REPLACE(emoji_name, char(65038), ''), char(65039), '')
+WHERE emoji_name <> REPLACE(REPLACE(emoji_name, char(65038), ''), char(65039), '');
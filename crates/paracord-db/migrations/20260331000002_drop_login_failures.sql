@@ -0,0 +1,3 @@
+-- Login lockouts are tracked in auth_guard_state alongside the auth guard,
+-- keyed by source IP and account, so the separate failure log is unused.
+DROP TABLE IF EXISTS login_failures;
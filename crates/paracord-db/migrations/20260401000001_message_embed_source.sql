@@ -0,0 +1,4 @@
+-- Link previews built by the server are stored next to other embeds. The
+-- source keeps them apart so refreshing previews leaves the rest alone.
+ALTER TABLE message_embeds ADD COLUMN source TEXT NOT NULL DEFAULT 'rich';
+UPDATE message_embeds SET source = 'link' WHERE embed_data LIKE '{"type":"link"%';
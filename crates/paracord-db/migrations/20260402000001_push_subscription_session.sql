@@ -0,0 +1,8 @@
+-- A push subscription belongs to the session that registered it and goes
+-- away with it. Earlier rows have no session, so they are dropped and the
+-- client registers again on its next sign-in.
+DELETE FROM push_subscriptions;
+ALTER TABLE push_subscriptions ADD COLUMN session_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_push_subscriptions_session
+    ON push_subscriptions (session_id);
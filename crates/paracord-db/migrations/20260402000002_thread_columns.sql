@@ -0,0 +1,43 @@
+-- Thread state the archiver and the starter-message lookup filter on. The
+-- JSON thread_metadata stays the copy served to clients.
+--   thread_archive_at: when an open thread auto-archives; NULL once archived
+--                      or when it never does.
+--   starter_message_id: the message the thread was started from, unique
+--                       per parent channel.
+ALTER TABLE channels ADD COLUMN thread_archive_at TEXT;
+ALTER TABLE channels ADD COLUMN starter_message_id BIGINT;
+
+UPDATE channels
+SET starter_message_id = CAST(json_extract(thread_metadata, '$.starter_message_id') AS INTEGER)
+WHERE channel_type = 6
+  AND json_extract(thread_metadata, '$.starter_message_id') IS NOT NULL;
+
+-- Earlier check-then-insert races could start two threads from one message;
+-- the oldest keeps the link.
+UPDATE channels
+SET starter_message_id = NULL
+WHERE starter_message_id IS NOT NULL
+  AND id NOT IN (
+      SELECT MIN(id) FROM channels
+      WHERE starter_message_id IS NOT NULL
+      GROUP BY parent_id, starter_message_id
+  );
+
+UPDATE channels
+SET thread_archive_at = datetime(
+    COALESCE(
+        (SELECT MAX(m.created_at) FROM messages m WHERE m.channel_id = channels.id),
+        channels.created_at
+    ),
+    '+' || CAST(json_extract(thread_metadata, '$.auto_archive_duration') AS INTEGER) || ' minutes'
+)
+WHERE channel_type = 6
+  AND COALESCE(json_extract(thread_metadata, '$.archived'), 0) = 0
+  AND CAST(json_extract(thread_metadata, '$.auto_archive_duration') AS INTEGER) > 0;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_channels_thread_starter
+    ON channels (parent_id, starter_message_id)
+    WHERE starter_message_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_channels_thread_archive_at
+    ON channels (thread_archive_at)
+    WHERE thread_archive_at IS NOT NULL;
@@ -0,0 +1,2 @@
+-- Optional per-webhook source-IP allow-list (JSON array of CIDR strings).
+ALTER TABLE webhooks ADD COLUMN allowed_cidrs TEXT NOT NULL DEFAULT '[]';
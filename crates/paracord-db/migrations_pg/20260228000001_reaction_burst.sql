@@ -0,0 +1,2 @@
+-- Super (burst) reactions are tallied separately from normal ones.
+ALTER TABLE reactions ADD COLUMN burst BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Ephemeral messages are stored with the only user allowed to see them.
+-- NULL means the message is visible to everyone who can read the channel.
+ALTER TABLE messages ADD COLUMN visible_to BIGINT;
@@ -0,0 +1,2 @@
+-- Whether the member joined with the PRIORITY_SPEAKER permission in the channel.
+ALTER TABLE voice_states ADD COLUMN priority_speaker BOOLEAN NOT NULL DEFAULT FALSE;
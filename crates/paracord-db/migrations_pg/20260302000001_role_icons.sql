@@ -0,0 +1,2 @@
+-- Role icon: a unicode emoji or the content hash of an uploaded image.
+ALTER TABLE roles ADD COLUMN role_icon TEXT;
@@ -0,0 +1,15 @@
+-- Reusable snapshots of a guild's roles and channel layout. The structure is
+-- stored as JSON so later guilds are created from the snapshot even if the
+-- source guild changes or is deleted.
+CREATE TABLE IF NOT EXISTS guild_templates (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    source_guild_id BIGINT REFERENCES spaces(id) ON DELETE SET NULL,
+    creator_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    serialized_guild TEXT NOT NULL,
+    usage_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_guild_templates_source ON guild_templates(source_guild_id);
@@ -0,0 +1,3 @@
+-- emoji_name is the canonical bucket key reactions are counted under; the
+-- emoji exactly as the user sent it is kept for display.
+ALTER TABLE reactions ADD COLUMN emoji_display VARCHAR(64);
@@ -0,0 +1,30 @@
+-- Outbound integration webhooks. A guild subscribes a URL to a
+-- comma-separated list of gateway event types; matching events are queued in
+-- integration_deliveries and POSTed with an HMAC-SHA256 signature made with
+-- the subscription secret.
+CREATE TABLE IF NOT EXISTS integration_subscriptions (
+    id BIGINT PRIMARY KEY,
+    guild_id BIGINT NOT NULL REFERENCES spaces(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    event_types TEXT NOT NULL,
+    creator_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_integration_subscriptions_guild
+    ON integration_subscriptions(guild_id);
+
+CREATE TABLE IF NOT EXISTS integration_deliveries (
+    id BIGINT PRIMARY KEY,
+    subscription_id BIGINT NOT NULL REFERENCES integration_subscriptions(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempt_count INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at_ms BIGINT NOT NULL,
+    last_error TEXT,
+    created_at_ms BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_integration_deliveries_due
+    ON integration_deliveries(next_attempt_at_ms);
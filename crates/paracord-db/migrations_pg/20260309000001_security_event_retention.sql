@@ -0,0 +1,7 @@
+-- Retention pruning walks security_events oldest-first in (created_at, id)
+-- order, and per-user lookups filter on the target of an event.
+CREATE INDEX IF NOT EXISTS idx_security_events_created_id
+    ON security_events (created_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_security_events_target_created
+    ON security_events (target_user_id, created_at DESC);
@@ -0,0 +1,12 @@
+-- TOTP second factor for password logins. A row without `confirmed_at` is a
+-- pending enrollment. Recovery codes are stored as a JSON array of SHA-256
+-- hashes and removed once used; `last_used_step` rejects replays of a code
+-- within its time step.
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    confirmed_at TEXT,
+    recovery_codes_hashed TEXT NOT NULL DEFAULT '[]',
+    last_used_step BIGINT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,16 @@
+-- WebAuthn passkeys. `credential_id` and `public_key` (an uncompressed SEC1
+-- P-256 point) are base64url without padding. `sign_count` only moves
+-- forward; a regression means the authenticator may have been cloned.
+CREATE TABLE IF NOT EXISTS webauthn_credentials (
+    credential_id TEXT PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    public_key TEXT NOT NULL,
+    sign_count BIGINT NOT NULL DEFAULT 0,
+    transports TEXT NOT NULL DEFAULT '[]',
+    name TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    last_used_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user
+    ON webauthn_credentials (user_id);
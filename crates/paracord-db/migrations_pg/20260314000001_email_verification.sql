@@ -0,0 +1,17 @@
+-- Opt-in email verification. Accounts that existed before this migration
+-- count as verified; new ones are left unverified only while the
+-- `require_email_verification` server setting is on.
+ALTER TABLE users ADD COLUMN email_verified_at TEXT;
+
+UPDATE users SET email_verified_at = created_at WHERE email_verified_at IS NULL;
+
+-- One-time verification tokens. Only the SHA-256 of the token is stored.
+CREATE TABLE IF NOT EXISTS email_verifications (
+    token_hash TEXT PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_verifications_user
+    ON email_verifications (user_id);
@@ -0,0 +1,12 @@
+-- Full-text index over message content. PostgreSQL counterpart of the SQLite
+-- FTS5 table: a generated tsvector column with a GIN index. E2EE DM
+-- ciphertext is never indexed.
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS search_tsv tsvector
+    GENERATED ALWAYS AS (
+        CASE
+            WHEN content IS NOT NULL AND (flags & 1) = 0
+                THEN to_tsvector('simple', content)
+        END
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_messages_search_tsv ON messages USING GIN (search_tsv);
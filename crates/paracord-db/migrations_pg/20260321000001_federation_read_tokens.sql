@@ -0,0 +1,11 @@
+-- Room-scoped read tokens handed to peers when they are invited to or join a
+-- room. Only the SHA-256 of each token is stored.
+CREATE TABLE IF NOT EXISTS federation_read_tokens (
+    token_hash               VARCHAR(64) PRIMARY KEY,
+    room_id                  VARCHAR(255) NOT NULL,
+    server_name              VARCHAR(255) NOT NULL,
+    expires_at_ms            BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_fed_read_tokens_room_server
+    ON federation_read_tokens(room_id, server_name);
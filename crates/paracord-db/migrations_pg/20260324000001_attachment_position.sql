@@ -0,0 +1,3 @@
+-- Order of an attachment within its message, as given in `attachment_ids`
+-- when the message was created.
+ALTER TABLE attachments ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
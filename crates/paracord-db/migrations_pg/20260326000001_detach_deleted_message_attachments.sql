@@ -0,0 +1,21 @@
+-- Deleting a message used to cascade its attachment rows away and leave the
+-- stored files behind. Detach them instead and mark them expired so the
+-- orphaned-attachment collector removes both row and file.
+CREATE OR REPLACE FUNCTION messages_detach_attachments()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+BEGIN
+    UPDATE attachments
+    SET message_id = NULL,
+        upload_channel_id = NULL,
+        upload_expires_at = datetime('now')
+    WHERE message_id = OLD.id;
+    RETURN OLD;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS messages_detach_attachments ON messages;
+CREATE TRIGGER messages_detach_attachments
+BEFORE DELETE ON messages
+FOR EACH ROW EXECUTE FUNCTION messages_detach_attachments();
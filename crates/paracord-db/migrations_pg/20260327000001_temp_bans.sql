@@ -0,0 +1,5 @@
+-- Temporary bans: a ban with an expiry stops applying at expires_at and is
+-- removed by the background sweeper. NULL keeps the ban permanent.
+ALTER TABLE bans ADD COLUMN expires_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_bans_expires_at ON bans(expires_at);
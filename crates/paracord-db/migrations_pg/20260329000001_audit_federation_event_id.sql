@@ -0,0 +1,6 @@
+-- Federated actions record the event that caused them so a redelivered or
+-- replayed event cannot write a second audit entry.
+ALTER TABLE audit_log_entries ADD COLUMN federation_event_id TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_log_federation_event
+    ON audit_log_entries(space_id, action_type, federation_event_id)
+    WHERE federation_event_id IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Accounts registered while `auth.unique_usernames` is on hold their name as
+-- a handle. The index keeps handles unique regardless of case, so two
+-- concurrent signups for the same name cannot both succeed.
+ALTER TABLE users ADD COLUMN username_is_handle BOOLEAN NOT NULL DEFAULT FALSE;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_handle
+    ON users(lower(username))
+    WHERE username_is_handle;
@@ -0,0 +1,7 @@
+-- Passkeys are verified by webauthn-rs, which keeps its own credential
+-- record (algorithm, user-verification and backup flags, counter) as JSON.
+-- Keys saved by the previous verifier held only the raw P-256 point and
+-- cannot be upgraded, so those passkeys have to be registered again.
+DELETE FROM webauthn_credentials;
+ALTER TABLE webauthn_credentials DROP COLUMN public_key;
+ALTER TABLE webauthn_credentials ADD COLUMN passkey TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,4 @@
+-- Accounts created from a public key have only a placeholder address, so
+-- there is nothing to verify. Treat them as verified.
+UPDATE users SET email_verified_at = created_at
+WHERE email_verified_at IS NULL AND password_hash = '' AND email LIKE '%@pubkey';
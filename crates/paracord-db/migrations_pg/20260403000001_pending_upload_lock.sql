@@ -0,0 +1,5 @@
+-- A PATCH claims its upload for the time it streams the body so two
+-- concurrent appends cannot write the temp file at once. `lock_id` names
+-- the request holding the claim; it lapses at `locked_until`.
+ALTER TABLE pending_uploads ADD COLUMN lock_id BIGINT;
+ALTER TABLE pending_uploads ADD COLUMN locked_until TEXT;
@@ -19,6 +19,19 @@ pub struct ApplicationCommandRow {
     pub updated_at: DateTime<Utc>,
 }
 
+/// A command for bulk overwrite: `(id, name, description, options_json, type,
+/// default_member_permissions, dm_permission, nsfw)`.
+pub type CommandSpec<'a> = (
+    i64,
+    &'a str,
+    &'a str,
+    Option<&'a str>,
+    i16,
+    Option<i64>,
+    bool,
+    bool,
+);
+
 const SELECT_COLS: &str = "id, application_id, guild_id, name, description, options, type, default_member_permissions, CASE WHEN dm_permission THEN 1 ELSE 0 END AS dm_permission, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, version, created_at, updated_at";
 
 impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ApplicationCommandRow {
@@ -43,6 +56,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ApplicationCommandRow {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_command(
     pool: &DbPool,
     id: i64,
@@ -77,10 +91,7 @@ pub async fn create_command(
     Ok(row)
 }
 
-pub async fn get_command(
-    pool: &DbPool,
-    id: i64,
-) -> Result<Option<ApplicationCommandRow>, DbError> {
+pub async fn get_command(pool: &DbPool, id: i64) -> Result<Option<ApplicationCommandRow>, DbError> {
     let sql = format!("SELECT {SELECT_COLS} FROM application_commands WHERE id = $1");
     let row = sqlx::query_as::<_, ApplicationCommandRow>(&sql)
         .bind(id)
@@ -119,6 +130,7 @@ pub async fn list_guild_commands(
     Ok(rows)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn update_command(
     pool: &DbPool,
     id: i64,
@@ -166,17 +178,15 @@ pub async fn delete_command(pool: &DbPool, id: i64) -> Result<(), DbError> {
 pub async fn bulk_overwrite_global_commands(
     pool: &DbPool,
     application_id: i64,
-    commands: &[(i64, &str, &str, Option<&str>, i16, Option<i64>, bool, bool)],
+    commands: &[CommandSpec<'_>],
 ) -> Result<Vec<ApplicationCommandRow>, DbError> {
     // Wrap in a transaction for atomicity
     let mut tx = pool.begin().await?;
 
-    sqlx::query(
-        "DELETE FROM application_commands WHERE application_id = $1 AND guild_id IS NULL",
-    )
-    .bind(application_id)
-    .execute(&mut *tx)
-    .await?;
+    sqlx::query("DELETE FROM application_commands WHERE application_id = $1 AND guild_id IS NULL")
+        .bind(application_id)
+        .execute(&mut *tx)
+        .await?;
 
     let insert_sql = format!(
         "INSERT INTO application_commands (id, application_id, guild_id, name, description, options, type, default_member_permissions, dm_permission, nsfw)
@@ -185,7 +195,17 @@ pub async fn bulk_overwrite_global_commands(
     );
 
     let mut results = Vec::with_capacity(commands.len());
-    for &(id, name, description, options, cmd_type, default_member_permissions, dm_permission, nsfw) in commands {
+    for &(
+        id,
+        name,
+        description,
+        options,
+        cmd_type,
+        default_member_permissions,
+        dm_permission,
+        nsfw,
+    ) in commands
+    {
         let row = sqlx::query_as::<_, ApplicationCommandRow>(&insert_sql)
             .bind(id)
             .bind(application_id)
@@ -210,18 +230,16 @@ pub async fn bulk_overwrite_guild_commands(
     pool: &DbPool,
     application_id: i64,
     guild_id: i64,
-    commands: &[(i64, &str, &str, Option<&str>, i16, Option<i64>, bool, bool)],
+    commands: &[CommandSpec<'_>],
 ) -> Result<Vec<ApplicationCommandRow>, DbError> {
     // Wrap in a transaction for atomicity
     let mut tx = pool.begin().await?;
 
-    sqlx::query(
-        "DELETE FROM application_commands WHERE application_id = $1 AND guild_id = $2",
-    )
-    .bind(application_id)
-    .bind(guild_id)
-    .execute(&mut *tx)
-    .await?;
+    sqlx::query("DELETE FROM application_commands WHERE application_id = $1 AND guild_id = $2")
+        .bind(application_id)
+        .bind(guild_id)
+        .execute(&mut *tx)
+        .await?;
 
     let insert_sql = format!(
         "INSERT INTO application_commands (id, application_id, guild_id, name, description, options, type, default_member_permissions, dm_permission, nsfw)
@@ -230,7 +248,17 @@ pub async fn bulk_overwrite_guild_commands(
     );
 
     let mut results = Vec::with_capacity(commands.len());
-    for &(id, name, description, options, cmd_type, default_member_permissions, dm_permission, nsfw) in commands {
+    for &(
+        id,
+        name,
+        description,
+        options,
+        cmd_type,
+        default_member_permissions,
+        dm_permission,
+        nsfw,
+    ) in commands
+    {
         let row = sqlx::query_as::<_, ApplicationCommandRow>(&insert_sql)
             .bind(id)
             .bind(application_id)
@@ -280,26 +308,12 @@ mod tests {
     use crate::{create_pool, run_migrations};
 
     async fn setup_app(pool: &DbPool, owner_id: i64, app_id: i64, bot_user_id: i64) {
-        crate::users::create_user(
-            pool,
-            owner_id,
-            "owner",
-            1,
-            "owner@example.com",
-            "hash",
-        )
-        .await
-        .unwrap();
-        crate::users::create_user(
-            pool,
-            bot_user_id,
-            "botuser",
-            2,
-            "bot@example.com",
-            "hash",
-        )
-        .await
-        .unwrap();
+        crate::users::create_user(pool, owner_id, "owner", 1, "owner@example.com", "hash")
+            .await
+            .unwrap();
+        crate::users::create_user(pool, bot_user_id, "botuser", 2, "bot@example.com", "hash")
+            .await
+            .unwrap();
         crate::bot_applications::create_bot_application(
             pool,
             app_id,
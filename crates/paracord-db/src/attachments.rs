@@ -60,30 +60,110 @@ pub async fn create_attachment(
     upload_expires_at: Option<DateTime<Utc>>,
     content_hash: Option<&str>,
 ) -> Result<AttachmentRow, DbError> {
-    let row = sqlx::query_as::<_, AttachmentRow>(
-        "INSERT INTO attachments (
-            id, message_id, filename, content_type, size, url, width, height,
-            uploader_id, upload_channel_id, upload_expires_at, content_hash
-         )
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-         RETURNING
-            id, message_id, filename, content_type, size, url, width, height,
-            uploader_id, upload_channel_id, upload_created_at, upload_expires_at,
-            content_hash",
-    )
-    .bind(id)
-    .bind(message_id)
-    .bind(filename)
-    .bind(content_type)
-    .bind(size)
-    .bind(url)
-    .bind(width)
-    .bind(height)
-    .bind(uploader_id)
-    .bind(upload_channel_id)
-    .bind(upload_expires_at.map(datetime_to_db_text))
-    .bind(content_hash)
-    .fetch_one(pool)
+    let upload_expires_at = upload_expires_at.map(datetime_to_db_text);
+    let row = crate::retry_on_busy(|| {
+        sqlx::query_as::<_, AttachmentRow>(
+            "INSERT INTO attachments (
+                id, message_id, filename, content_type, size, url, width, height,
+                uploader_id, upload_channel_id, upload_expires_at, content_hash
+             )
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+             RETURNING
+                id, message_id, filename, content_type, size, url, width, height,
+                uploader_id, upload_channel_id, upload_created_at, upload_expires_at,
+                content_hash",
+        )
+        .bind(id)
+        .bind(message_id)
+        .bind(filename)
+        .bind(content_type)
+        .bind(size)
+        .bind(url)
+        .bind(width)
+        .bind(height)
+        .bind(uploader_id)
+        .bind(upload_channel_id)
+        .bind(upload_expires_at.as_deref())
+        .bind(content_hash)
+        .fetch_one(pool)
+    })
+    .await?;
+    Ok(row)
+}
+
+/// Insert an empty pending attachment for `uploader_id` to upload into,
+/// unless they already hold `max_pending` unlinked, unexpired uploads (0
+/// disables the cap). Counting and inserting in one statement keeps
+/// concurrent uploads from all passing the check. Returns `None` at the cap.
+#[allow(clippy::too_many_arguments)]
+pub async fn reserve_pending_attachment(
+    pool: &DbPool,
+    id: i64,
+    filename: &str,
+    url: &str,
+    uploader_id: i64,
+    upload_channel_id: i64,
+    upload_expires_at: DateTime<Utc>,
+    max_pending: i64,
+    now: DateTime<Utc>,
+) -> Result<Option<AttachmentRow>, DbError> {
+    let upload_expires_at = datetime_to_db_text(upload_expires_at);
+    let now = datetime_to_db_text(now);
+    let row = crate::retry_on_busy(|| {
+        sqlx::query_as::<_, AttachmentRow>(
+            "INSERT INTO attachments (
+                id, filename, size, url, uploader_id, upload_channel_id, upload_expires_at
+             )
+             SELECT $1, $2, 0, $3, $4, $5, $6
+             WHERE $7 <= 0
+                OR (SELECT COUNT(*)
+                    FROM attachments
+                    WHERE uploader_id = $4
+                      AND message_id IS NULL
+                      AND (upload_expires_at IS NULL OR upload_expires_at > $8)) < $7
+             RETURNING
+                id, message_id, filename, content_type, size, url, width, height,
+                uploader_id, upload_channel_id, upload_created_at, upload_expires_at,
+                content_hash",
+        )
+        .bind(id)
+        .bind(filename)
+        .bind(url)
+        .bind(uploader_id)
+        .bind(upload_channel_id)
+        .bind(upload_expires_at.as_str())
+        .bind(max_pending)
+        .bind(now.as_str())
+        .fetch_optional(pool)
+    })
+    .await?;
+    Ok(row)
+}
+
+/// Fill in a reservation from [`reserve_pending_attachment`] once its file
+/// is stored.
+pub async fn complete_pending_attachment(
+    pool: &DbPool,
+    id: i64,
+    content_type: Option<&str>,
+    size: i32,
+    content_hash: Option<&str>,
+) -> Result<AttachmentRow, DbError> {
+    let row = crate::retry_on_busy(|| {
+        sqlx::query_as::<_, AttachmentRow>(
+            "UPDATE attachments SET content_type = $2, size = $3, content_hash = $4
+             WHERE id = $1
+             RETURNING
+                id, message_id, filename, content_type, size, url, width, height,
+                uploader_id, upload_channel_id, upload_created_at, upload_expires_at,
+                content_hash",
+        )
+        .bind(id)
+        .bind(content_type)
+        .bind(size)
+        .bind(content_hash)
+        .fetch_one(pool)
+    })
     .await?;
     Ok(row)
 }
@@ -119,7 +199,8 @@ pub async fn get_message_attachments(
             id, message_id, filename, content_type, size, url, width, height,
             uploader_id, upload_channel_id, upload_created_at, upload_expires_at,
             content_hash
-         FROM attachments WHERE message_id = $1",
+         FROM attachments WHERE message_id = $1
+         ORDER BY position ASC, id ASC",
     )
     .bind(message_id)
     .fetch_all(pool)
@@ -127,17 +208,19 @@ pub async fn get_message_attachments(
     Ok(rows)
 }
 
+/// Link a pending upload to `message_id` at `position` within the message.
 pub async fn attach_to_message(
     pool: &DbPool,
     id: i64,
     message_id: i64,
+    position: i32,
     uploader_id: i64,
     channel_id: i64,
     now: DateTime<Utc>,
 ) -> Result<bool, DbError> {
     let result = sqlx::query(
         "UPDATE attachments
-         SET message_id = $2, upload_expires_at = NULL
+         SET message_id = $2, position = $6, upload_expires_at = NULL
          WHERE id = $1
            AND message_id IS NULL
            AND uploader_id = $3
@@ -149,6 +232,7 @@ pub async fn attach_to_message(
     .bind(uploader_id)
     .bind(channel_id)
     .bind(datetime_to_db_text(now))
+    .bind(position)
     .execute(pool)
     .await?;
     Ok(result.rows_affected() > 0)
@@ -201,7 +285,7 @@ pub async fn get_attachments_for_message_ids(
             content_hash
          FROM attachments
          WHERE message_id IN ({})
-         ORDER BY upload_created_at ASC
+         ORDER BY message_id ASC, position ASC, id ASC
          LIMIT ${}",
         placeholders.join(", "),
         message_ids.len() + 1
@@ -239,6 +323,35 @@ pub async fn get_unlinked_attachments_older_than(
     Ok(rows)
 }
 
+/// Attachments whose message no longer exists, plus unlinked ones whose
+/// upload window closed before `now`. Deleted messages leave their files
+/// detached with an expiry, so both kinds still have a stored file to remove.
+pub async fn find_orphaned(
+    pool: &DbPool,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<AttachmentRow>, DbError> {
+    let rows = sqlx::query_as::<_, AttachmentRow>(
+        "SELECT
+            id, message_id, filename, content_type, size, url, width, height,
+            uploader_id, upload_channel_id, upload_created_at, upload_expires_at,
+            content_hash
+         FROM attachments
+         WHERE (message_id IS NOT NULL
+                AND NOT EXISTS (SELECT 1 FROM messages m WHERE m.id = attachments.message_id))
+            OR (message_id IS NULL
+                AND upload_expires_at IS NOT NULL
+                AND upload_expires_at <= $1)
+         ORDER BY id ASC
+         LIMIT $2",
+    )
+    .bind(datetime_to_db_text(now))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,21 +418,164 @@ mod tests {
         .await
         .expect("create attachment");
 
-        let wrong_user =
-            attach_to_message(&db, 5001, message.id, user_b.id, channel_a.id, Utc::now())
-                .await
-                .expect("attach wrong user");
+        let wrong_user = attach_to_message(
+            &db,
+            5001,
+            message.id,
+            0,
+            user_b.id,
+            channel_a.id,
+            Utc::now(),
+        )
+        .await
+        .expect("attach wrong user");
         assert!(!wrong_user);
 
-        let wrong_channel =
-            attach_to_message(&db, 5001, message.id, user_a.id, channel_b.id, Utc::now())
-                .await
-                .expect("attach wrong channel");
+        let wrong_channel = attach_to_message(
+            &db,
+            5001,
+            message.id,
+            0,
+            user_a.id,
+            channel_b.id,
+            Utc::now(),
+        )
+        .await
+        .expect("attach wrong channel");
         assert!(!wrong_channel);
 
-        let ok = attach_to_message(&db, 5001, message.id, user_a.id, channel_a.id, Utc::now())
-            .await
-            .expect("attach correct");
+        let ok = attach_to_message(
+            &db,
+            5001,
+            message.id,
+            0,
+            user_a.id,
+            channel_a.id,
+            Utc::now(),
+        )
+        .await
+        .expect("attach correct");
         assert!(ok);
     }
+
+    #[tokio::test]
+    async fn message_attachments_come_back_in_link_order() {
+        let db = setup_db().await;
+        let user = crate::users::create_user(&db, 1101, "carol", 1, "carol@example.com", "hash")
+            .await
+            .expect("create user");
+        let guild = crate::guilds::create_space(&db, 2101, "space", user.id, None)
+            .await
+            .expect("create space");
+        let channel =
+            crate::channels::create_channel(&db, 3101, guild.id, "general", 0, 0, None, None)
+                .await
+                .expect("create channel");
+        let message =
+            crate::messages::create_message(&db, 4101, channel.id, user.id, "files", 0, None)
+                .await
+                .expect("create message");
+
+        for id in [5101, 5102, 5103] {
+            create_attachment(
+                &db,
+                id,
+                None,
+                "file.txt",
+                Some("text/plain"),
+                1,
+                &format!("/api/v1/attachments/{id}"),
+                None,
+                None,
+                Some(user.id),
+                Some(channel.id),
+                Some(Utc::now() + chrono::Duration::minutes(10)),
+                None,
+            )
+            .await
+            .expect("create attachment");
+        }
+        for (position, id) in [5103, 5101, 5102].into_iter().enumerate() {
+            assert!(attach_to_message(
+                &db,
+                id,
+                message.id,
+                position as i32,
+                user.id,
+                channel.id,
+                Utc::now()
+            )
+            .await
+            .expect("attach"));
+        }
+
+        let ids: Vec<i64> = get_message_attachments(&db, message.id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(ids, vec![5103, 5101, 5102]);
+        let ids: Vec<i64> = get_attachments_for_message_ids(&db, &[message.id], 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(ids, vec![5103, 5101, 5102]);
+    }
+
+    #[tokio::test]
+    async fn deleting_a_message_leaves_its_attachments_orphaned() {
+        let db = setup_db().await;
+        let user = crate::users::create_user(&db, 1201, "dave", 1, "dave@example.com", "hash")
+            .await
+            .expect("create user");
+        let guild = crate::guilds::create_space(&db, 2201, "space", user.id, None)
+            .await
+            .expect("create space");
+        let channel =
+            crate::channels::create_channel(&db, 3201, guild.id, "general", 0, 0, None, None)
+                .await
+                .expect("create channel");
+        let message =
+            crate::messages::create_message(&db, 4201, channel.id, user.id, "file", 0, None)
+                .await
+                .expect("create message");
+
+        for id in [5201, 5202] {
+            create_attachment(
+                &db,
+                id,
+                None,
+                "file.txt",
+                Some("text/plain"),
+                1,
+                &format!("/api/v1/attachments/{id}"),
+                None,
+                None,
+                Some(user.id),
+                Some(channel.id),
+                Some(Utc::now() + chrono::Duration::minutes(10)),
+                None,
+            )
+            .await
+            .expect("create attachment");
+        }
+        assert!(
+            attach_to_message(&db, 5201, message.id, 0, user.id, channel.id, Utc::now())
+                .await
+                .expect("attach")
+        );
+        assert!(find_orphaned(&db, Utc::now(), 10).await.unwrap().is_empty());
+
+        crate::messages::delete_message(&db, message.id)
+            .await
+            .expect("delete message");
+
+        let orphaned = find_orphaned(&db, Utc::now(), 10).await.unwrap();
+        let ids: Vec<i64> = orphaned.iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![5201]);
+        assert!(orphaned[0].upload_channel_id.is_none());
+    }
 }
@@ -70,6 +70,42 @@ pub async fn create_entry(
     Ok(row)
 }
 
+/// Record an action caused by federation event `event_id`. Returns false,
+/// writing nothing, when that event already has an entry of this type.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_federated_entry(
+    pool: &DbPool,
+    id: i64,
+    space_id: i64,
+    user_id: i64,
+    action_type: i16,
+    target_id: Option<i64>,
+    reason: Option<&str>,
+    changes: Option<&serde_json::Value>,
+    event_id: &str,
+) -> Result<bool, DbError> {
+    let changes = changes
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| DbError::Sqlx(sqlx::Error::Protocol(format!("invalid audit json: {e}"))))?;
+    let result = sqlx::query(
+        "INSERT INTO audit_log_entries (id, space_id, user_id, action_type, target_id, reason, changes, federation_event_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(id)
+    .bind(space_id)
+    .bind(user_id)
+    .bind(action_type)
+    .bind(target_id)
+    .bind(reason)
+    .bind(changes)
+    .bind(event_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 /// Get entries for a space. Kept as get_guild_entries for API compat.
 pub async fn get_guild_entries(
     pool: &DbPool,
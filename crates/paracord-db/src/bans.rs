@@ -1,4 +1,4 @@
-use crate::{datetime_from_db_text, DbError, DbPool};
+use crate::{datetime_from_db_text, datetime_to_db_text, DbError, DbPool};
 use chrono::{DateTime, Utc};
 use sqlx::Row;
 
@@ -9,17 +9,24 @@ pub struct BanRow {
     pub reason: Option<String>,
     pub banned_by: Option<i64>,
     pub created_at: DateTime<Utc>,
+    /// When a temporary ban lifts; `None` for permanent bans.
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for BanRow {
     fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
         let created_at_raw: String = row.try_get("created_at")?;
+        let expires_at_raw: Option<String> = row.try_get("expires_at")?;
         Ok(Self {
             user_id: row.try_get("user_id")?,
             guild_id: row.try_get("guild_id")?,
             reason: row.try_get("reason")?,
             banned_by: row.try_get("banned_by")?,
             created_at: datetime_from_db_text(&created_at_raw)?,
+            expires_at: expires_at_raw
+                .as_deref()
+                .map(datetime_from_db_text)
+                .transpose()?,
         })
     }
 }
@@ -30,18 +37,43 @@ pub async fn create_ban(
     guild_id: i64,
     reason: Option<&str>,
     banned_by: i64,
+) -> Result<BanRow, DbError> {
+    upsert_ban(pool, user_id, guild_id, reason, banned_by, None).await
+}
+
+/// Ban `user_id` from `guild_id` until `expires_at`. Re-banning replaces any
+/// existing ban, so a permanent ban can be shortened and vice versa.
+pub async fn create_temp_ban(
+    pool: &DbPool,
+    user_id: i64,
+    guild_id: i64,
+    reason: Option<&str>,
+    banned_by: i64,
+    expires_at: DateTime<Utc>,
+) -> Result<BanRow, DbError> {
+    upsert_ban(pool, user_id, guild_id, reason, banned_by, Some(expires_at)).await
+}
+
+async fn upsert_ban(
+    pool: &DbPool,
+    user_id: i64,
+    guild_id: i64,
+    reason: Option<&str>,
+    banned_by: i64,
+    expires_at: Option<DateTime<Utc>>,
 ) -> Result<BanRow, DbError> {
     let row = sqlx::query_as::<_, BanRow>(
-        "INSERT INTO bans (user_id, guild_id, reason, banned_by)
-         VALUES ($1, $2, $3, $4)
+        "INSERT INTO bans (user_id, guild_id, reason, banned_by, expires_at)
+         VALUES ($1, $2, $3, $4, $5)
          ON CONFLICT (user_id, guild_id)
-         DO UPDATE SET reason = $3, banned_by = $4, created_at = datetime('now')
-         RETURNING user_id, guild_id, reason, banned_by, created_at",
+         DO UPDATE SET reason = $3, banned_by = $4, expires_at = $5, created_at = datetime('now')
+         RETURNING user_id, guild_id, reason, banned_by, created_at, expires_at",
     )
     .bind(user_id)
     .bind(guild_id)
     .bind(reason)
     .bind(banned_by)
+    .bind(expires_at.map(datetime_to_db_text))
     .fetch_one(pool)
     .await?;
     Ok(row)
@@ -53,7 +85,7 @@ pub async fn get_ban(
     guild_id: i64,
 ) -> Result<Option<BanRow>, DbError> {
     let row = sqlx::query_as::<_, BanRow>(
-        "SELECT user_id, guild_id, reason, banned_by, created_at
+        "SELECT user_id, guild_id, reason, banned_by, created_at, expires_at
          FROM bans WHERE user_id = $1 AND guild_id = $2",
     )
     .bind(user_id)
@@ -63,6 +95,67 @@ pub async fn get_ban(
     Ok(row)
 }
 
+/// Whether a ban that has not yet expired keeps `user_id` out of `guild_id`.
+pub async fn is_banned(
+    pool: &DbPool,
+    user_id: i64,
+    guild_id: i64,
+    now: DateTime<Utc>,
+) -> Result<bool, DbError> {
+    let row: Option<(i64,)> = sqlx::query_as(
+        "SELECT 1 FROM bans
+         WHERE user_id = $1 AND guild_id = $2
+           AND (expires_at IS NULL OR expires_at > $3)",
+    )
+    .bind(user_id)
+    .bind(guild_id)
+    .bind(datetime_to_db_text(now))
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some())
+}
+
+/// Temporary bans whose expiry has passed, oldest first.
+pub async fn get_expired_bans(
+    pool: &DbPool,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<BanRow>, DbError> {
+    let rows = sqlx::query_as::<_, BanRow>(
+        "SELECT user_id, guild_id, reason, banned_by, created_at, expires_at
+         FROM bans
+         WHERE expires_at IS NOT NULL AND expires_at <= $1
+         ORDER BY expires_at ASC
+         LIMIT $2",
+    )
+    .bind(datetime_to_db_text(now))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Remove a ban only if it is still expired at `now`, so a ban re-issued
+/// since it was listed survives. Returns whether a row was removed.
+pub async fn delete_expired_ban(
+    pool: &DbPool,
+    user_id: i64,
+    guild_id: i64,
+    now: DateTime<Utc>,
+) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "DELETE FROM bans
+         WHERE user_id = $1 AND guild_id = $2
+           AND expires_at IS NOT NULL AND expires_at <= $3",
+    )
+    .bind(user_id)
+    .bind(guild_id)
+    .bind(datetime_to_db_text(now))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn delete_ban(pool: &DbPool, user_id: i64, guild_id: i64) -> Result<(), DbError> {
     sqlx::query("DELETE FROM bans WHERE user_id = $1 AND guild_id = $2")
         .bind(user_id)
@@ -74,7 +167,7 @@ pub async fn delete_ban(pool: &DbPool, user_id: i64, guild_id: i64) -> Result<()
 
 pub async fn get_guild_bans(pool: &DbPool, guild_id: i64) -> Result<Vec<BanRow>, DbError> {
     let rows = sqlx::query_as::<_, BanRow>(
-        "SELECT user_id, guild_id, reason, banned_by, created_at
+        "SELECT user_id, guild_id, reason, banned_by, created_at, expires_at
          FROM bans
          WHERE guild_id = $1
          ORDER BY created_at DESC",
@@ -87,7 +180,7 @@ pub async fn get_guild_bans(pool: &DbPool, guild_id: i64) -> Result<Vec<BanRow>,
 
 pub async fn get_all_bans(pool: &DbPool) -> Result<Vec<BanRow>, DbError> {
     let rows = sqlx::query_as::<_, BanRow>(
-        "SELECT user_id, guild_id, reason, banned_by, created_at
+        "SELECT user_id, guild_id, reason, banned_by, created_at, expires_at
          FROM bans ORDER BY created_at DESC",
     )
     .fetch_all(pool)
@@ -214,4 +307,63 @@ mod tests {
         let bans = get_guild_bans(&pool, 999).await.unwrap();
         assert!(bans.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_temp_ban_stops_applying_after_expiry() {
+        let pool = test_pool().await;
+        let (owner_id, target_id, guild_id) = setup_guild(&pool).await;
+        let now = Utc::now();
+        let expires_at = now + chrono::Duration::hours(1);
+        let ban = create_temp_ban(&pool, target_id, guild_id, None, owner_id, expires_at)
+            .await
+            .unwrap();
+        assert_eq!(
+            ban.expires_at.map(|t| t.timestamp()),
+            Some(expires_at.timestamp())
+        );
+
+        assert!(is_banned(&pool, target_id, guild_id, now).await.unwrap());
+        let later = expires_at + chrono::Duration::seconds(1);
+        assert!(!is_banned(&pool, target_id, guild_id, later).await.unwrap());
+
+        assert!(get_expired_bans(&pool, now, 10).await.unwrap().is_empty());
+        let expired = get_expired_bans(&pool, later, 10).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert!(!delete_expired_ban(&pool, target_id, guild_id, now)
+            .await
+            .unwrap());
+        assert!(delete_expired_ban(&pool, target_id, guild_id, later)
+            .await
+            .unwrap());
+        assert!(get_ban(&pool, target_id, guild_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_permanent_reban_clears_expiry() {
+        let pool = test_pool().await;
+        let (owner_id, target_id, guild_id) = setup_guild(&pool).await;
+        let now = Utc::now();
+        create_temp_ban(
+            &pool,
+            target_id,
+            guild_id,
+            None,
+            owner_id,
+            now + chrono::Duration::minutes(5),
+        )
+        .await
+        .unwrap();
+        let ban = create_ban(&pool, target_id, guild_id, None, owner_id)
+            .await
+            .unwrap();
+        assert!(ban.expires_at.is_none());
+        let far_future = now + chrono::Duration::days(365);
+        assert!(is_banned(&pool, target_id, guild_id, far_future)
+            .await
+            .unwrap());
+        assert!(get_expired_bans(&pool, far_future, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -69,6 +69,18 @@ pub fn hash_token(token: &str) -> String {
     out
 }
 
+/// Check `token` against a stored hash without short-circuiting on the first
+/// differing byte.
+pub fn verify_token_hash(token: &str, stored_hash: &str) -> bool {
+    let computed = hash_token(token);
+    computed.len() == stored_hash.len()
+        && computed
+            .bytes()
+            .zip(stored_hash.bytes())
+            .fold(0_u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
 pub async fn create_bot_application(
     pool: &DbPool,
     id: i64,
@@ -112,6 +124,20 @@ pub async fn get_bot_application(
     Ok(row)
 }
 
+pub async fn get_bot_application_by_user_id(
+    pool: &DbPool,
+    bot_user_id: i64,
+) -> Result<Option<BotApplicationRow>, DbError> {
+    let row = sqlx::query_as::<_, BotApplicationRow>(
+        "SELECT id, name, description, owner_id, bot_user_id, token_hash, redirect_uri, permissions, created_at, updated_at
+         FROM bot_applications WHERE bot_user_id = $1",
+    )
+    .bind(bot_user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
 pub async fn get_bot_application_by_token_hash(
     pool: &DbPool,
     token_hash: &str,
@@ -241,6 +267,22 @@ pub async fn list_bot_guild_installs(
     Ok(rows)
 }
 
+pub async fn get_bot_guild_install(
+    pool: &DbPool,
+    bot_app_id: i64,
+    guild_id: i64,
+) -> Result<Option<BotGuildInstallRow>, DbError> {
+    let row = sqlx::query_as::<_, BotGuildInstallRow>(
+        "SELECT bot_app_id, guild_id, added_by, permissions, created_at
+         FROM bot_guild_installs WHERE bot_app_id = $1 AND guild_id = $2",
+    )
+    .bind(bot_app_id)
+    .bind(guild_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
 pub async fn list_guild_bots(
     pool: &DbPool,
     guild_id: i64,
@@ -0,0 +1,173 @@
+use crate::{datetime_from_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct ChannelFollowerRow {
+    pub id: i64,
+    pub source_channel_id: i64,
+    pub target_channel_id: i64,
+    pub webhook_id: i64,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ChannelFollowerRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let created_at_raw: String = row.try_get("created_at")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            source_channel_id: row.try_get("source_channel_id")?,
+            target_channel_id: row.try_get("target_channel_id")?,
+            webhook_id: row.try_get("webhook_id")?,
+            created_by: row.try_get("created_by")?,
+            created_at: datetime_from_db_text(&created_at_raw)?,
+        })
+    }
+}
+
+pub async fn create_follower(
+    pool: &DbPool,
+    id: i64,
+    source_channel_id: i64,
+    target_channel_id: i64,
+    webhook_id: i64,
+    created_by: i64,
+) -> Result<ChannelFollowerRow, DbError> {
+    let row = sqlx::query_as::<_, ChannelFollowerRow>(
+        "INSERT INTO channel_followers (id, source_channel_id, target_channel_id, webhook_id, created_by)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, source_channel_id, target_channel_id, webhook_id, created_by, created_at",
+    )
+    .bind(id)
+    .bind(source_channel_id)
+    .bind(target_channel_id)
+    .bind(webhook_id)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_follower(pool: &DbPool, id: i64) -> Result<Option<ChannelFollowerRow>, DbError> {
+    let row = sqlx::query_as::<_, ChannelFollowerRow>(
+        "SELECT id, source_channel_id, target_channel_id, webhook_id, created_by, created_at
+         FROM channel_followers WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_follower_by_channels(
+    pool: &DbPool,
+    source_channel_id: i64,
+    target_channel_id: i64,
+) -> Result<Option<ChannelFollowerRow>, DbError> {
+    let row = sqlx::query_as::<_, ChannelFollowerRow>(
+        "SELECT id, source_channel_id, target_channel_id, webhook_id, created_by, created_at
+         FROM channel_followers WHERE source_channel_id = $1 AND target_channel_id = $2",
+    )
+    .bind(source_channel_id)
+    .bind(target_channel_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_channel_followers(
+    pool: &DbPool,
+    source_channel_id: i64,
+) -> Result<Vec<ChannelFollowerRow>, DbError> {
+    let rows = sqlx::query_as::<_, ChannelFollowerRow>(
+        "SELECT id, source_channel_id, target_channel_id, webhook_id, created_by, created_at
+         FROM channel_followers WHERE source_channel_id = $1 ORDER BY id",
+    )
+    .bind(source_channel_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn delete_follower(pool: &DbPool, id: i64) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM channel_followers WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A follower channel's copy of a published announcement.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CrosspostMirrorRow {
+    pub mirror_message_id: i64,
+    pub source_message_id: i64,
+    pub channel_id: i64,
+    pub webhook_id: i64,
+}
+
+pub async fn record_crosspost_mirror(
+    pool: &DbPool,
+    mirror_message_id: i64,
+    source_message_id: i64,
+    channel_id: i64,
+    webhook_id: i64,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "INSERT INTO crosspost_mirrors (mirror_message_id, source_message_id, channel_id, webhook_id)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(mirror_message_id)
+    .bind(source_message_id)
+    .bind(channel_id)
+    .bind(webhook_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_crosspost_mirrors(
+    pool: &DbPool,
+    source_message_id: i64,
+) -> Result<Vec<CrosspostMirrorRow>, DbError> {
+    let rows = sqlx::query_as::<_, CrosspostMirrorRow>(
+        "SELECT mirror_message_id, source_message_id, channel_id, webhook_id
+         FROM crosspost_mirrors WHERE source_message_id = $1 ORDER BY mirror_message_id",
+    )
+    .bind(source_message_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Mirrors of any of `source_message_ids` that were published from
+/// `source_channel_id`. Used by bulk delete, so ids from other channels
+/// never reach their followers.
+pub async fn get_crosspost_mirrors_for_sources(
+    pool: &DbPool,
+    source_channel_id: i64,
+    source_message_ids: &[i64],
+) -> Result<Vec<CrosspostMirrorRow>, DbError> {
+    if source_message_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (1..=source_message_ids.len())
+        .map(|i| format!("${}", i))
+        .collect();
+    let sql = format!(
+        "SELECT cm.mirror_message_id, cm.source_message_id, cm.channel_id, cm.webhook_id
+         FROM crosspost_mirrors cm
+         INNER JOIN messages m ON m.id = cm.source_message_id
+         WHERE cm.source_message_id IN ({}) AND m.channel_id = ${}
+         ORDER BY cm.mirror_message_id",
+        placeholders.join(", "),
+        source_message_ids.len() + 1
+    );
+    let mut query = sqlx::query_as::<_, CrosspostMirrorRow>(&sql);
+    for id in source_message_ids {
+        query = query.bind(id);
+    }
+    let rows = query.bind(source_channel_id).fetch_all(pool).await?;
+    Ok(rows)
+}
@@ -76,6 +76,24 @@ pub async fn get_overwrites_for_channels(
     Ok(rows)
 }
 
+/// Overwrites on every channel in the spaces `user_id` is a member of.
+pub async fn get_user_guild_overwrites(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<ChannelOverwriteRow>, DbError> {
+    let rows = sqlx::query_as::<_, ChannelOverwriteRow>(
+        "SELECT o.channel_id, o.target_id, o.target_type, o.allow_perms, o.deny_perms
+         FROM channel_overwrites o
+         INNER JOIN channels c ON c.id = o.channel_id
+         INNER JOIN members m ON m.guild_id = c.space_id
+         WHERE m.user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 pub async fn delete_channel_overwrite(
     pool: &DbPool,
     channel_id: i64,
@@ -1,4 +1,4 @@
-use crate::{bool_from_any_row, datetime_from_db_text, DbError, DbPool};
+use crate::{bool_from_any_row, datetime_from_db_text, datetime_to_db_text, DbError, DbPool};
 use chrono::{DateTime, Utc};
 use sqlx::Row;
 use std::collections::BTreeSet;
@@ -36,6 +36,8 @@ pub struct ChannelRow {
     pub message_count: Option<i32>,
     pub applied_tags: Option<String>,
     pub default_sort_order: Option<i32>,
+    /// Only members with MANAGE_MESSAGES may post.
+    pub read_only: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -72,6 +74,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ChannelRow {
             message_count: row.try_get("message_count")?,
             applied_tags: row.try_get("applied_tags")?,
             default_sort_order: row.try_get("default_sort_order")?,
+            read_only: bool_from_any_row(row, "read_only")?,
             created_at: datetime_from_db_text(&created_at_raw)?,
         })
     }
@@ -112,7 +115,7 @@ pub async fn create_channel(
     let row = sqlx::query_as::<_, ChannelRow>(
         "INSERT INTO channels (id, space_id, name, channel_type, position, parent_id, required_role_ids)
          VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, '[]'))
-         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at"
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
     )
     .bind(id)
     .bind(space_id)
@@ -128,7 +131,7 @@ pub async fn create_channel(
 
 pub async fn get_channel(pool: &DbPool, id: i64) -> Result<Option<ChannelRow>, DbError> {
     let row = sqlx::query_as::<_, ChannelRow>(
-        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
          FROM channels WHERE id = $1"
     )
     .bind(id)
@@ -144,7 +147,7 @@ pub async fn get_guild_channels(pool: &DbPool, space_id: i64) -> Result<Vec<Chan
 
 pub async fn get_space_channels(pool: &DbPool, space_id: i64) -> Result<Vec<ChannelRow>, DbError> {
     let rows = sqlx::query_as::<_, ChannelRow>(
-        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
          FROM channels WHERE space_id = $1 ORDER BY position"
     )
     .bind(space_id)
@@ -153,6 +156,25 @@ pub async fn get_space_channels(pool: &DbPool, space_id: i64) -> Result<Vec<Chan
     Ok(rows)
 }
 
+/// Channels of every space `user_id` is a member of, ordered by space then
+/// position. Used to build READY without a query per guild.
+pub async fn get_user_guild_channels(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<ChannelRow>, DbError> {
+    let rows = sqlx::query_as::<_, ChannelRow>(
+        "SELECT c.id, c.space_id, c.name, c.topic, c.channel_type, c.position, c.parent_id, CASE WHEN c.nsfw THEN 1 ELSE 0 END AS nsfw, c.rate_limit_per_user, c.bitrate, c.user_limit, c.last_message_id, c.required_role_ids, c.thread_metadata, c.owner_id, c.message_count, c.applied_tags, c.default_sort_order, CASE WHEN c.read_only THEN 1 ELSE 0 END AS read_only, c.created_at
+         FROM channels c
+         INNER JOIN members m ON m.guild_id = c.space_id
+         WHERE m.user_id = $1
+         ORDER BY c.space_id, c.position"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 pub async fn update_channel(
     pool: &DbPool,
     id: i64,
@@ -167,7 +189,7 @@ pub async fn update_channel(
              required_role_ids = COALESCE($4, required_role_ids),
              updated_at = datetime('now')
          WHERE id = $1
-         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at"
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
     )
     .bind(id)
     .bind(name)
@@ -178,6 +200,66 @@ pub async fn update_channel(
     Ok(row)
 }
 
+/// Set a voice channel's participant cap; `0` removes the limit.
+pub async fn update_channel_user_limit(
+    pool: &DbPool,
+    id: i64,
+    user_limit: i32,
+) -> Result<ChannelRow, DbError> {
+    let row = sqlx::query_as::<_, ChannelRow>(
+        "UPDATE channels
+         SET user_limit = $2,
+             updated_at = datetime('now')
+         WHERE id = $1
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
+    )
+    .bind(id)
+    .bind(user_limit)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Set a channel's slowmode in seconds; `0` turns it off.
+pub async fn update_channel_rate_limit(
+    pool: &DbPool,
+    id: i64,
+    rate_limit_per_user: i32,
+) -> Result<ChannelRow, DbError> {
+    let row = sqlx::query_as::<_, ChannelRow>(
+        "UPDATE channels
+         SET rate_limit_per_user = $2,
+             updated_at = datetime('now')
+         WHERE id = $1
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
+    )
+    .bind(id)
+    .bind(rate_limit_per_user)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Mark a channel read-only (announcement-style) or clear the flag.
+pub async fn update_channel_read_only(
+    pool: &DbPool,
+    id: i64,
+    read_only: bool,
+) -> Result<ChannelRow, DbError> {
+    let row = sqlx::query_as::<_, ChannelRow>(
+        "UPDATE channels
+         SET read_only = $2,
+             updated_at = datetime('now')
+         WHERE id = $1
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
+    )
+    .bind(id)
+    .bind(read_only)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
 pub async fn delete_channel(pool: &DbPool, id: i64) -> Result<(), DbError> {
     sqlx::query("DELETE FROM channels WHERE id = $1")
         .bind(id)
@@ -217,7 +299,7 @@ pub async fn update_channel_positions(
     let mut changed = Vec::new();
     for &(channel_id, position, ref parent_id) in positions {
         let existing = sqlx::query_as::<_, ChannelRow>(
-            "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at
+            "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
              FROM channels WHERE id = $1 AND space_id = $2"
         )
         .bind(channel_id)
@@ -239,7 +321,7 @@ pub async fn update_channel_positions(
         let row = sqlx::query_as::<_, ChannelRow>(
             "UPDATE channels SET position = $2, parent_id = $3, updated_at = datetime('now')
              WHERE id = $1
-             RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at"
+             RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
         )
         .bind(channel_id)
         .bind(position)
@@ -261,7 +343,15 @@ pub fn serialize_required_role_ids(role_ids: &[i64]) -> String {
     serde_json::to_string(&values).unwrap_or_else(|_| "[]".to_string())
 }
 
-/// Create a thread channel under a parent text channel.
+/// When a thread last active at `from` auto-archives, or `None` if its
+/// `auto_archive_duration` never does.
+fn thread_archive_at(from: DateTime<Utc>, auto_archive_duration: i64) -> Option<String> {
+    (auto_archive_duration > 0)
+        .then(|| datetime_to_db_text(from + chrono::Duration::minutes(auto_archive_duration)))
+}
+
+/// Create a thread channel under a parent text channel. Returns `None` if
+/// `starter_message_id` already started a thread under that parent.
 pub async fn create_thread(
     pool: &DbPool,
     id: i64,
@@ -271,7 +361,7 @@ pub async fn create_thread(
     owner_id: i64,
     auto_archive_duration: i64,
     starter_message_id: Option<i64>,
-) -> Result<ChannelRow, DbError> {
+) -> Result<Option<ChannelRow>, DbError> {
     let thread_metadata = serde_json::json!({
         "archived": false,
         "auto_archive_duration": auto_archive_duration,
@@ -282,9 +372,10 @@ pub async fn create_thread(
     .to_string();
 
     let row = sqlx::query_as::<_, ChannelRow>(
-        "INSERT INTO channels (id, space_id, name, channel_type, position, parent_id, required_role_ids, thread_metadata, owner_id, message_count)
-         VALUES ($1, $2, $3, 6, 0, $4, '[]', $5, $6, 0)
-         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at"
+        "INSERT INTO channels (id, space_id, name, channel_type, position, parent_id, required_role_ids, thread_metadata, owner_id, message_count, thread_archive_at, starter_message_id)
+         VALUES ($1, $2, $3, 6, 0, $4, '[]', $5, $6, 0, $7, $8)
+         ON CONFLICT DO NOTHING
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
     )
     .bind(id)
     .bind(space_id)
@@ -292,7 +383,9 @@ pub async fn create_thread(
     .bind(parent_channel_id)
     .bind(&thread_metadata)
     .bind(owner_id)
-    .fetch_one(pool)
+    .bind(thread_archive_at(Utc::now(), auto_archive_duration))
+    .bind(starter_message_id)
+    .fetch_optional(pool)
     .await?;
     Ok(row)
 }
@@ -303,7 +396,7 @@ pub async fn get_channel_threads(
     parent_channel_id: i64,
 ) -> Result<Vec<ChannelRow>, DbError> {
     let rows = sqlx::query_as::<_, ChannelRow>(
-        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
          FROM channels
          WHERE parent_id = $1 AND channel_type = 6
          ORDER BY created_at DESC"
@@ -323,7 +416,7 @@ pub async fn get_archived_threads(
     parent_channel_id: i64,
 ) -> Result<Vec<ChannelRow>, DbError> {
     let rows = sqlx::query_as::<_, ChannelRow>(
-        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
          FROM channels
          WHERE parent_id = $1 AND channel_type = 6
          ORDER BY created_at DESC"
@@ -337,16 +430,80 @@ pub async fn get_archived_threads(
         .collect())
 }
 
-/// Update thread archived/locked state and optionally rename.
+/// Ids of every thread under `parent_channel_id`, archived or not.
+pub async fn get_thread_ids(pool: &DbPool, parent_channel_id: i64) -> Result<Vec<i64>, DbError> {
+    let rows: Vec<(i64,)> =
+        sqlx::query_as("SELECT id FROM channels WHERE parent_id = $1 AND channel_type = 6")
+            .bind(parent_channel_id)
+            .fetch_all(pool)
+            .await?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
+/// The thread under `parent_channel_id` started from `message_id`, archived
+/// or not.
+pub async fn get_thread_for_starter_message(
+    pool: &DbPool,
+    parent_channel_id: i64,
+    message_id: i64,
+) -> Result<Option<ChannelRow>, DbError> {
+    let row = sqlx::query_as::<_, ChannelRow>(
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
+         FROM channels
+         WHERE parent_id = $1 AND starter_message_id = $2 AND channel_type = 6",
+    )
+    .bind(parent_channel_id)
+    .bind(message_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Most threads [`archive_inactive_threads`] archives per call.
+const ARCHIVE_BATCH: i64 = 500;
+
+/// Archive open threads whose last message (or, if they have none, their
+/// creation) is `auto_archive_duration` minutes or more before `now`, up to
+/// [`ARCHIVE_BATCH`] per call. Returns the threads that were archived.
+pub async fn archive_inactive_threads(
+    pool: &DbPool,
+    now: DateTime<Utc>,
+) -> Result<Vec<ChannelRow>, DbError> {
+    let due: Vec<(i64,)> = sqlx::query_as(
+        "SELECT id FROM channels
+         WHERE channel_type = 6 AND thread_archive_at IS NOT NULL AND thread_archive_at <= $1
+         ORDER BY thread_archive_at
+         LIMIT $2",
+    )
+    .bind(datetime_to_db_text(now))
+    .bind(ARCHIVE_BATCH)
+    .fetch_all(pool)
+    .await?;
+
+    let mut archived = Vec::with_capacity(due.len());
+    for (thread_id,) in due {
+        match update_thread(pool, thread_id, None, Some(true), None, None).await {
+            Ok(row) => archived.push(row),
+            // Deleted since it was selected.
+            Err(DbError::NotFound) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(archived)
+}
+
+/// Update thread archived/locked state, auto-archive period (minutes), and
+/// optionally rename.
 pub async fn update_thread(
     pool: &DbPool,
     thread_id: i64,
     name: Option<&str>,
     archived: Option<bool>,
     locked: Option<bool>,
+    auto_archive_duration: Option<i64>,
 ) -> Result<ChannelRow, DbError> {
     let existing = sqlx::query_as::<_, ChannelRow>(
-        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
          FROM channels
          WHERE id = $1 AND channel_type = 6",
     )
@@ -374,6 +531,23 @@ pub async fn update_thread(
     if let Some(locked_val) = locked {
         metadata["locked"] = serde_json::Value::Bool(locked_val);
     }
+    if let Some(minutes) = auto_archive_duration {
+        metadata["auto_archive_duration"] = serde_json::json!(minutes);
+    }
+
+    // Archiving stops the auto-archive clock; reopening the thread or
+    // changing the duration of an open one restarts it from now.
+    let now_archived = metadata.get("archived").and_then(|v| v.as_bool()) == Some(true);
+    let reset_archive_at = archived.is_some() || (auto_archive_duration.is_some() && !now_archived);
+    let archive_at = if now_archived {
+        None
+    } else {
+        let minutes = metadata
+            .get("auto_archive_duration")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0);
+        thread_archive_at(Utc::now(), minutes)
+    };
 
     let metadata_raw = serde_json::to_string(&metadata).unwrap_or_else(|_| "{}".to_string());
 
@@ -381,26 +555,48 @@ pub async fn update_thread(
         "UPDATE channels
          SET name = COALESCE($2, name),
              thread_metadata = $3,
+             thread_archive_at = CASE WHEN $4 = 1 THEN $5 ELSE thread_archive_at END,
              updated_at = datetime('now')
          WHERE id = $1 AND channel_type = 6
-         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at",
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at",
     )
     .bind(thread_id)
     .bind(name)
     .bind(metadata_raw)
+    .bind(i32::from(reset_archive_at))
+    .bind(archive_at)
     .fetch_one(pool)
     .await?;
     Ok(row)
 }
 
-/// Increment the message count for a thread channel.
+/// Increment the message count for a thread channel and, if it is open,
+/// push its auto-archive time back to a full duration from now.
 pub async fn increment_thread_message_count(pool: &DbPool, thread_id: i64) -> Result<(), DbError> {
-    sqlx::query(
-        "UPDATE channels SET message_count = COALESCE(message_count, 0) + 1 WHERE id = $1 AND channel_type = 6"
+    let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+        "UPDATE channels SET message_count = COALESCE(message_count, 0) + 1 WHERE id = $1 AND channel_type = 6
+         RETURNING thread_metadata, thread_archive_at"
     )
     .bind(thread_id)
-    .execute(pool)
+    .fetch_optional(pool)
     .await?;
+    let Some((Some(metadata), Some(_))) = row else {
+        return Ok(());
+    };
+    let minutes = serde_json::from_str::<serde_json::Value>(&metadata)
+        .ok()
+        .and_then(|metadata| metadata.get("auto_archive_duration")?.as_i64())
+        .unwrap_or(0);
+    if let Some(archive_at) = thread_archive_at(Utc::now(), minutes) {
+        sqlx::query(
+            "UPDATE channels SET thread_archive_at = $2
+             WHERE id = $1 AND thread_archive_at IS NOT NULL",
+        )
+        .bind(thread_id)
+        .bind(archive_at)
+        .execute(pool)
+        .await?;
+    }
     Ok(())
 }
 
@@ -428,9 +624,9 @@ pub async fn create_forum_post(
     let tags = applied_tags.unwrap_or("[]");
 
     let row = sqlx::query_as::<_, ChannelRow>(
-        "INSERT INTO channels (id, space_id, name, channel_type, position, parent_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags)
-         VALUES ($1, $2, $3, 6, 0, $4, '[]', $5, $6, 0, $7)
-         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at"
+        "INSERT INTO channels (id, space_id, name, channel_type, position, parent_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, thread_archive_at)
+         VALUES ($1, $2, $3, 6, 0, $4, '[]', $5, $6, 0, $7, $8)
+         RETURNING id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at"
     )
     .bind(id)
     .bind(space_id)
@@ -439,6 +635,7 @@ pub async fn create_forum_post(
     .bind(&thread_metadata)
     .bind(owner_id)
     .bind(tags)
+    .bind(thread_archive_at(Utc::now(), 10080))
     .fetch_one(pool)
     .await?;
     Ok(row)
@@ -459,7 +656,7 @@ pub async fn get_forum_posts(
     };
 
     let sql = format!(
-        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, created_at
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id, CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw, rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids, thread_metadata, owner_id, message_count, applied_tags, default_sort_order, CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
          FROM channels
          WHERE parent_id = $1 AND channel_type = 6
          ORDER BY {}",
@@ -661,6 +858,19 @@ mod tests {
         assert_eq!(updated.topic.as_deref(), Some("topic only"));
     }
 
+    #[tokio::test]
+    async fn test_update_channel_user_limit() {
+        let pool = test_pool().await;
+        let guild_id = setup_guild(&pool).await;
+        create_channel(&pool, 42, guild_id, "voice", 2, 0, None, None)
+            .await
+            .unwrap();
+        let updated = update_channel_user_limit(&pool, 42, 5).await.unwrap();
+        assert_eq!(updated.user_limit, Some(5));
+        let cleared = update_channel_user_limit(&pool, 42, 0).await.unwrap();
+        assert_eq!(cleared.user_limit, Some(0));
+    }
+
     #[tokio::test]
     async fn test_delete_channel() {
         let pool = test_pool().await;
@@ -740,6 +950,7 @@ mod tests {
             .unwrap();
         let thread = create_thread(&pool, 91, guild_id, 90, "my-thread", 1, 1440, None)
             .await
+            .unwrap()
             .unwrap();
         assert_eq!(thread.channel_type, 6);
         assert_eq!(thread.parent_id, Some(90));
@@ -756,14 +967,125 @@ mod tests {
             .unwrap();
         create_thread(&pool, 93, guild_id, 92, "thread-a", 1, 1440, None)
             .await
+            .unwrap()
             .unwrap();
         create_thread(&pool, 94, guild_id, 92, "thread-b", 1, 1440, None)
             .await
+            .unwrap()
             .unwrap();
         let threads = get_channel_threads(&pool, 92).await.unwrap();
         assert_eq!(threads.len(), 2);
     }
 
+    #[tokio::test]
+    async fn inactive_threads_are_archived_after_their_duration() {
+        let pool = test_pool().await;
+        let guild_id = setup_guild(&pool).await;
+        create_channel(&pool, 96, guild_id, "parent", 0, 0, None, None)
+            .await
+            .unwrap();
+        create_thread(&pool, 97, guild_id, 96, "quiet", 1, 60, Some(500))
+            .await
+            .unwrap()
+            .unwrap();
+        create_thread(&pool, 98, guild_id, 96, "busy", 1, 60, None)
+            .await
+            .unwrap()
+            .unwrap();
+        create_thread(&pool, 99, guild_id, 96, "forever", 1, 0, None)
+            .await
+            .unwrap()
+            .unwrap();
+        // "quiet" was created at 10:00; "busy" last saw a message at 11:30.
+        sqlx::query(
+            "UPDATE channels SET thread_archive_at = CASE id WHEN 97 THEN '2026-01-01 11:00:00' ELSE '2026-01-01 12:30:00' END
+             WHERE id IN (97, 98)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let now = datetime_from_db_text("2026-01-01 12:00:00").unwrap();
+        let archived = archive_inactive_threads(&pool, now).await.unwrap();
+        assert_eq!(archived.iter().map(|t| t.id).collect::<Vec<_>>(), vec![97]);
+        assert_eq!(get_archived_threads(&pool, 96).await.unwrap().len(), 1);
+        // Already archived threads are left alone.
+        assert!(archive_inactive_threads(&pool, now)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let later = datetime_from_db_text("2026-01-01 12:31:00").unwrap();
+        let archived = archive_inactive_threads(&pool, later).await.unwrap();
+        assert_eq!(archived.iter().map(|t| t.id).collect::<Vec<_>>(), vec![98]);
+
+        // Reopening restarts the clock from now, so it is not due again at once.
+        update_thread(&pool, 97, None, Some(false), None, None)
+            .await
+            .unwrap();
+        assert!(archive_inactive_threads(&pool, Utc::now())
+            .await
+            .unwrap()
+            .is_empty());
+        let far_future = Utc::now() + chrono::Duration::days(365);
+        let archived = archive_inactive_threads(&pool, far_future).await.unwrap();
+        assert_eq!(archived.iter().map(|t| t.id).collect::<Vec<_>>(), vec![97]);
+    }
+
+    #[tokio::test]
+    async fn messages_push_back_thread_archival() {
+        let pool = test_pool().await;
+        let guild_id = setup_guild(&pool).await;
+        create_channel(&pool, 86, guild_id, "parent", 0, 0, None, None)
+            .await
+            .unwrap();
+        create_thread(&pool, 87, guild_id, 86, "chatty", 1, 60, None)
+            .await
+            .unwrap()
+            .unwrap();
+        sqlx::query("UPDATE channels SET thread_archive_at = '2026-01-01 11:00:00' WHERE id = 87")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        increment_thread_message_count(&pool, 87).await.unwrap();
+        let (archive_at,): (String,) =
+            sqlx::query_as("SELECT thread_archive_at FROM channels WHERE id = 87")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(datetime_from_db_text(&archive_at).unwrap() > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn one_thread_per_starter_message() {
+        let pool = test_pool().await;
+        let guild_id = setup_guild(&pool).await;
+        create_channel(&pool, 88, guild_id, "parent", 0, 0, None, None)
+            .await
+            .unwrap();
+        create_thread(&pool, 89, guild_id, 88, "first", 1, 60, Some(500))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            create_thread(&pool, 85, guild_id, 88, "second", 1, 60, Some(500))
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let started = get_thread_for_starter_message(&pool, 88, 500)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(started.id, 89);
+        assert!(get_thread_for_starter_message(&pool, 88, 501)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_guild_id_backward_compat() {
         let pool = test_pool().await;
@@ -19,9 +19,9 @@ pub async fn find_dm_channel_between(
 ) -> Result<Option<ChannelRow>, DbError> {
     let row = sqlx::query_as::<_, ChannelRow>(
         "SELECT c.id, c.space_id, c.name, c.topic, c.channel_type, c.position, c.parent_id,
-                c.nsfw, c.rate_limit_per_user, c.bitrate, c.user_limit, c.last_message_id,
+                CASE WHEN c.nsfw THEN 1 ELSE 0 END AS nsfw, c.rate_limit_per_user, c.bitrate, c.user_limit, c.last_message_id,
                 c.required_role_ids, c.thread_metadata, c.owner_id, c.message_count,
-                c.applied_tags, c.default_sort_order, c.created_at
+                c.applied_tags, c.default_sort_order, CASE WHEN c.read_only THEN 1 ELSE 0 END AS read_only, c.created_at
          FROM channels c
          INNER JOIN dm_recipients a ON a.channel_id = c.id AND a.user_id = $1
          INNER JOIN dm_recipients b ON b.channel_id = c.id AND b.user_id = $2
@@ -35,39 +35,102 @@ pub async fn find_dm_channel_between(
     Ok(row)
 }
 
+pub const DM_CHANNEL_TYPE: i16 = 1;
+pub const GROUP_DM_CHANNEL_TYPE: i16 = 3;
+/// Participants allowed in a group DM, creator included.
+pub const MAX_GROUP_DM_RECIPIENTS: usize = 10;
+
+/// A user taking part in a group DM.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DmRecipientRow {
+    pub channel_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub discriminator: i16,
+    pub avatar_hash: Option<String>,
+    pub public_key: Option<String>,
+}
+
 pub async fn create_dm_channel(
     pool: &DbPool,
     channel_id: i64,
     user_a: i64,
     user_b: i64,
 ) -> Result<ChannelRow, DbError> {
-    let mut tx = pool.begin().await?;
+    insert_dm_channel(
+        pool,
+        channel_id,
+        DM_CHANNEL_TYPE,
+        None,
+        None,
+        &[user_a, user_b],
+    )
+    .await
+}
 
-    sqlx::query(
-        "INSERT INTO channels (id, space_id, name, channel_type, position)
-         VALUES ($1, NULL, NULL, 1, 0)",
+/// Create a group DM owned by `creator_id` with `recipient_ids` (duplicates
+/// and the creator are ignored).
+pub async fn create_group_dm(
+    pool: &DbPool,
+    channel_id: i64,
+    creator_id: i64,
+    recipient_ids: &[i64],
+    name: Option<&str>,
+) -> Result<ChannelRow, DbError> {
+    let mut members = vec![creator_id];
+    for &id in recipient_ids {
+        if !members.contains(&id) {
+            members.push(id);
+        }
+    }
+    insert_dm_channel(
+        pool,
+        channel_id,
+        GROUP_DM_CHANNEL_TYPE,
+        Some(creator_id),
+        name,
+        &members,
     )
-    .bind(channel_id)
-    .execute(&mut *tx)
-    .await?;
+    .await
+}
+
+async fn insert_dm_channel(
+    pool: &DbPool,
+    channel_id: i64,
+    channel_type: i16,
+    owner_id: Option<i64>,
+    name: Option<&str>,
+    members: &[i64],
+) -> Result<ChannelRow, DbError> {
+    let mut tx = pool.begin().await?;
 
     sqlx::query(
-        "INSERT INTO dm_recipients (channel_id, user_id)
-         VALUES ($1, $2), ($1, $3)",
+        "INSERT INTO channels (id, space_id, name, channel_type, position, owner_id)
+         VALUES ($1, NULL, $2, $3, 0, $4)",
     )
     .bind(channel_id)
-    .bind(user_a)
-    .bind(user_b)
+    .bind(name)
+    .bind(channel_type)
+    .bind(owner_id)
     .execute(&mut *tx)
     .await?;
 
+    for &user_id in members {
+        sqlx::query("INSERT INTO dm_recipients (channel_id, user_id) VALUES ($1, $2)")
+            .bind(channel_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
     tx.commit().await?;
 
     let row = sqlx::query_as::<_, ChannelRow>(
-        "SELECT id, space_id, name, topic, channel_type, position, parent_id, nsfw,
+        "SELECT id, space_id, name, topic, channel_type, position, parent_id,
+                CASE WHEN nsfw THEN 1 ELSE 0 END AS nsfw,
                 rate_limit_per_user, bitrate, user_limit, last_message_id, required_role_ids,
                 thread_metadata, owner_id, message_count, applied_tags, default_sort_order,
-                created_at
+                CASE WHEN read_only THEN 1 ELSE 0 END AS read_only, created_at
          FROM channels
          WHERE id = $1",
     )
@@ -78,6 +141,113 @@ pub async fn create_dm_channel(
     Ok(row)
 }
 
+/// Outcome of [`add_recipient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddRecipientOutcome {
+    Added,
+    AlreadyPresent,
+    /// The group already has [`MAX_GROUP_DM_RECIPIENTS`] participants.
+    Full,
+}
+
+/// Add `user_id` to a group DM unless it is full.
+pub async fn add_recipient(
+    pool: &DbPool,
+    channel_id: i64,
+    user_id: i64,
+) -> Result<AddRecipientOutcome, DbError> {
+    let mut tx = pool.begin().await?;
+
+    // Writing the channel row first serialises concurrent adds to the same
+    // group, so the count below cannot go stale before the insert.
+    sqlx::query("UPDATE channels SET updated_at = datetime('now') WHERE id = $1")
+        .bind(channel_id)
+        .execute(&mut *tx)
+        .await?;
+    let present: Option<(i64,)> =
+        sqlx::query_as("SELECT 1 FROM dm_recipients WHERE channel_id = $1 AND user_id = $2")
+            .bind(channel_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if present.is_some() {
+        return Ok(AddRecipientOutcome::AlreadyPresent);
+    }
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM dm_recipients WHERE channel_id = $1")
+            .bind(channel_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    if count >= MAX_GROUP_DM_RECIPIENTS as i64 {
+        return Ok(AddRecipientOutcome::Full);
+    }
+    sqlx::query("INSERT INTO dm_recipients (channel_id, user_id) VALUES ($1, $2)")
+        .bind(channel_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(AddRecipientOutcome::Added)
+}
+
+/// Remove `user_id` from a DM channel. Returns false if they were not in it.
+pub async fn remove_recipient(
+    pool: &DbPool,
+    channel_id: i64,
+    user_id: i64,
+) -> Result<bool, DbError> {
+    let result = sqlx::query("DELETE FROM dm_recipients WHERE channel_id = $1 AND user_id = $2")
+        .bind(channel_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Group DMs `user_id` takes part in, most recently active first.
+pub async fn list_user_group_dms(pool: &DbPool, user_id: i64) -> Result<Vec<ChannelRow>, DbError> {
+    let rows = sqlx::query_as::<_, ChannelRow>(
+        "SELECT c.id, c.space_id, c.name, c.topic, c.channel_type, c.position, c.parent_id,
+                CASE WHEN c.nsfw THEN 1 ELSE 0 END AS nsfw, c.rate_limit_per_user, c.bitrate, c.user_limit, c.last_message_id,
+                c.required_role_ids, c.thread_metadata, c.owner_id, c.message_count,
+                c.applied_tags, c.default_sort_order, CASE WHEN c.read_only THEN 1 ELSE 0 END AS read_only, c.created_at
+         FROM channels c
+         INNER JOIN dm_recipients me ON me.channel_id = c.id AND me.user_id = $1
+         WHERE c.channel_type = 3
+         ORDER BY CASE WHEN c.last_message_id IS NULL THEN 1 ELSE 0 END, c.last_message_id DESC, c.id DESC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Every participant of the given DM channels, ordered by channel then user.
+pub async fn get_dm_recipients(
+    pool: &DbPool,
+    channel_ids: &[i64],
+) -> Result<Vec<DmRecipientRow>, DbError> {
+    let mut rows = Vec::new();
+    for chunk in channel_ids.chunks(500) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("${i}")).collect();
+        let sql = format!(
+            "SELECT r.channel_id, u.id AS user_id, u.username, u.discriminator, u.avatar_hash, u.public_key
+             FROM dm_recipients r
+             INNER JOIN users u ON u.id = r.user_id
+             WHERE r.channel_id IN ({})
+             ORDER BY r.channel_id, u.id",
+            placeholders.join(", ")
+        );
+        let mut query = sqlx::query_as::<_, DmRecipientRow>(&sql);
+        for id in chunk {
+            query = query.bind(id);
+        }
+        rows.extend(query.fetch_all(pool).await?);
+    }
+    Ok(rows)
+}
+
 pub async fn list_user_dm_channels(
     pool: &DbPool,
     user_id: i64,
@@ -126,3 +296,85 @@ pub async fn is_dm_recipient(
     .await?;
     Ok(exists.is_some())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> DbPool {
+        let pool = crate::create_pool("sqlite::memory:", 1).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn group_dm_recipients_can_be_added_and_removed() {
+        let pool = test_pool().await;
+        for (id, name) in [(1, "creator"), (2, "bob"), (3, "carol"), (4, "dave")] {
+            crate::users::create_user(&pool, id, name, 1, &format!("{name}@example.com"), "hash")
+                .await
+                .unwrap();
+        }
+
+        let group = create_group_dm(&pool, 100, 1, &[2, 3, 2, 1], Some("Trip"))
+            .await
+            .unwrap();
+        assert_eq!(group.channel_type, GROUP_DM_CHANNEL_TYPE);
+        assert_eq!(group.owner_id, Some(1));
+        assert_eq!(group.name.as_deref(), Some("Trip"));
+        let mut ids = get_dm_recipient_ids(&pool, 100).await.unwrap();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        assert_eq!(
+            add_recipient(&pool, 100, 4).await.unwrap(),
+            AddRecipientOutcome::Added
+        );
+        assert_eq!(
+            add_recipient(&pool, 100, 4).await.unwrap(),
+            AddRecipientOutcome::AlreadyPresent
+        );
+        assert!(is_dm_recipient(&pool, 100, 4).await.unwrap());
+        assert!(remove_recipient(&pool, 100, 2).await.unwrap());
+        assert!(!remove_recipient(&pool, 100, 2).await.unwrap());
+        assert!(!is_dm_recipient(&pool, 100, 2).await.unwrap());
+
+        let recipients = get_dm_recipients(&pool, &[100]).await.unwrap();
+        let names: Vec<&str> = recipients.iter().map(|r| r.username.as_str()).collect();
+        assert_eq!(names, vec!["creator", "carol", "dave"]);
+        assert_eq!(list_user_group_dms(&pool, 4).await.unwrap().len(), 1);
+        assert!(list_user_group_dms(&pool, 2).await.unwrap().is_empty());
+        // Group DMs never stand in for a 1:1 conversation.
+        assert!(find_dm_channel_between(&pool, 1, 3)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(list_user_dm_channels(&pool, 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn group_dm_adds_stop_at_the_cap() {
+        let pool = test_pool().await;
+        for id in 1..=11 {
+            let name = format!("user{id}");
+            crate::users::create_user(&pool, id, &name, 1, &format!("{name}@example.com"), "hash")
+                .await
+                .unwrap();
+        }
+        create_group_dm(&pool, 200, 1, &[2, 3], None).await.unwrap();
+        for id in 4..=MAX_GROUP_DM_RECIPIENTS as i64 {
+            assert_eq!(
+                add_recipient(&pool, 200, id).await.unwrap(),
+                AddRecipientOutcome::Added
+            );
+        }
+        assert_eq!(
+            add_recipient(&pool, 200, 11).await.unwrap(),
+            AddRecipientOutcome::Full
+        );
+        assert_eq!(
+            get_dm_recipient_ids(&pool, 200).await.unwrap().len(),
+            MAX_GROUP_DM_RECIPIENTS
+        );
+    }
+}
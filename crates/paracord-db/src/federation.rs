@@ -275,6 +275,8 @@ pub async fn prune_transport_replay_cache(
     Ok(rows)
 }
 
+/// Queue `event_id` for delivery to `destination_server`. Enqueueing an event
+/// that was dead-lettered puts it back in the queue with a fresh retry window.
 pub async fn enqueue_outbound_event(
     pool: &DbPool,
     destination_server: &str,
@@ -301,7 +303,10 @@ pub async fn enqueue_outbound_event(
              $12, $12
          )
          ON CONFLICT (destination_server, event_id) DO UPDATE SET
-             next_attempt_at_ms = CASE WHEN federation_outbound_queue.next_attempt_at_ms < EXCLUDED.next_attempt_at_ms THEN federation_outbound_queue.next_attempt_at_ms ELSE EXCLUDED.next_attempt_at_ms END,
+             attempt_count = CASE WHEN federation_outbound_queue.dead_lettered_at_ms IS NULL THEN federation_outbound_queue.attempt_count ELSE 0 END,
+             next_attempt_at_ms = CASE WHEN federation_outbound_queue.dead_lettered_at_ms IS NULL AND federation_outbound_queue.next_attempt_at_ms < EXCLUDED.next_attempt_at_ms THEN federation_outbound_queue.next_attempt_at_ms ELSE EXCLUDED.next_attempt_at_ms END,
+             created_at_ms = CASE WHEN federation_outbound_queue.dead_lettered_at_ms IS NULL THEN federation_outbound_queue.created_at_ms ELSE EXCLUDED.created_at_ms END,
+             dead_lettered_at_ms = NULL,
              updated_at_ms = EXCLUDED.updated_at_ms",
     )
     .bind(destination_server)
@@ -352,6 +357,7 @@ pub async fn fetch_due_outbound_events(
          LEFT JOIN federation_peer_trust_state pts
            ON pts.server_name = q.destination_server
          WHERE q.next_attempt_at_ms <= $1
+           AND q.dead_lettered_at_ms IS NULL
            AND fs.trusted = TRUE
            AND COALESCE(pts.mode, 'allow') != 'block'
            AND NOT (
@@ -807,8 +813,100 @@ pub async fn upsert_room_sync_cursor(
     Ok(())
 }
 
-/// Purge expired outbound events that have exceeded max retry attempts or age.
-pub async fn purge_expired_outbound_events(
+pub async fn insert_read_token(
+    pool: &DbPool,
+    token_hash: &str,
+    room_id: &str,
+    server_name: &str,
+    expires_at_ms: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO federation_read_tokens (token_hash, room_id, server_name, expires_at_ms)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(token_hash)
+    .bind(room_id)
+    .bind(server_name)
+    .bind(expires_at_ms)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The room an unexpired read token is scoped to. Tokens held by a server
+/// that is no longer trusted, or is blocked or quarantined, resolve to
+/// nothing.
+pub async fn get_read_token_room(
+    pool: &DbPool,
+    token_hash: &str,
+    now_ms: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT rt.room_id
+         FROM federation_read_tokens rt
+         INNER JOIN federated_servers fs
+           ON fs.server_name = rt.server_name
+         LEFT JOIN federation_peer_trust_state pts
+           ON pts.server_name = rt.server_name
+         WHERE rt.token_hash = $1
+           AND rt.expires_at_ms > $2
+           AND fs.trusted = TRUE
+           AND COALESCE(pts.mode, 'allow') != 'block'
+           AND NOT (
+               COALESCE(pts.mode, 'allow') = 'quarantine'
+               AND COALESCE(pts.quarantined_until_ms, 0) > $2
+           )
+         LIMIT 1",
+    )
+    .bind(token_hash)
+    .bind(now_ms)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(room_id,)| room_id))
+}
+
+/// Revoke every read token `server_name` holds for `room_id`.
+pub async fn delete_read_tokens(
+    pool: &DbPool,
+    room_id: &str,
+    server_name: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM federation_read_tokens
+         WHERE room_id = $1
+           AND server_name = $2",
+    )
+    .bind(room_id)
+    .bind(server_name)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Revoke every read token held by `server_name`, in any room.
+pub async fn delete_server_read_tokens(
+    pool: &DbPool,
+    server_name: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM federation_read_tokens WHERE server_name = $1")
+        .bind(server_name)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete read tokens that expired at or before `now_ms`.
+pub async fn purge_expired_read_tokens(pool: &DbPool, now_ms: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM federation_read_tokens WHERE expires_at_ms <= $1")
+        .bind(now_ms)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Move outbound events that have exceeded max retry attempts or age into the
+/// dead-letter state, where they stay visible but are no longer retried.
+pub async fn dead_letter_expired_outbound_events(
     pool: &DbPool,
     now_ms: i64,
     max_attempts: i64,
@@ -816,17 +914,109 @@ pub async fn purge_expired_outbound_events(
 ) -> Result<u64, sqlx::Error> {
     let cutoff_ms = now_ms.saturating_sub(max_age_ms);
     let rows = sqlx::query(
-        "DELETE FROM federation_outbound_queue
-         WHERE attempt_count >= $1 OR created_at_ms < $2",
+        "UPDATE federation_outbound_queue
+         SET dead_lettered_at_ms = $3, updated_at_ms = $3
+         WHERE dead_lettered_at_ms IS NULL
+           AND (attempt_count >= $1 OR created_at_ms < $2)",
     )
     .bind(max_attempts)
     .bind(cutoff_ms)
+    .bind(now_ms)
     .execute(pool)
     .await?
     .rows_affected();
     Ok(rows)
 }
 
+/// Delete dead-lettered outbound events older than `retention_ms`.
+pub async fn purge_dead_lettered_outbound_events(
+    pool: &DbPool,
+    now_ms: i64,
+    retention_ms: i64,
+) -> Result<u64, sqlx::Error> {
+    let cutoff_ms = now_ms.saturating_sub(retention_ms);
+    let rows = sqlx::query(
+        "DELETE FROM federation_outbound_queue
+         WHERE dead_lettered_at_ms IS NOT NULL AND dead_lettered_at_ms < $1",
+    )
+    .bind(cutoff_ms)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(rows)
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboundQueueEntryRow {
+    pub id: i64,
+    pub destination_server: String,
+    pub event_id: String,
+    pub room_id: String,
+    pub event_type: String,
+    pub attempt_count: i64,
+    pub next_attempt_at_ms: i64,
+    pub last_error: Option<String>,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+    pub dead_lettered_at_ms: Option<i64>,
+}
+
+const OUTBOUND_QUEUE_ENTRY_COLUMNS: &str =
+    "id, destination_server, event_id, room_id, event_type, \
+     attempt_count, next_attempt_at_ms, last_error, created_at_ms, updated_at_ms, \
+     dead_lettered_at_ms";
+
+/// List outbound queue entries for operators, newest first. `dead_lettered`
+/// selects only pending (`false`) or only dead-lettered (`true`) entries.
+pub async fn list_outbound_queue_entries(
+    pool: &DbPool,
+    dead_lettered: Option<bool>,
+    before_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<OutboundQueueEntryRow>, sqlx::Error> {
+    let state_filter = match dead_lettered {
+        Some(true) => "AND dead_lettered_at_ms IS NOT NULL",
+        Some(false) => "AND dead_lettered_at_ms IS NULL",
+        None => "",
+    };
+    let sql = format!(
+        "SELECT {OUTBOUND_QUEUE_ENTRY_COLUMNS}
+         FROM federation_outbound_queue
+         WHERE id < $1 {state_filter}
+         ORDER BY id DESC
+         LIMIT $2"
+    );
+    sqlx::query_as::<_, OutboundQueueEntryRow>(&sql)
+        .bind(before_id.unwrap_or(i64::MAX))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+/// Return a dead-lettered entry to the queue with a fresh retry schedule.
+/// Returns the updated entry, or `None` when `id` is not dead-lettered.
+pub async fn requeue_dead_lettered_outbound_event(
+    pool: &DbPool,
+    id: i64,
+    now_ms: i64,
+) -> Result<Option<OutboundQueueEntryRow>, sqlx::Error> {
+    let sql = format!(
+        "UPDATE federation_outbound_queue
+         SET attempt_count = 0,
+             next_attempt_at_ms = $2,
+             dead_lettered_at_ms = NULL,
+             created_at_ms = $2,
+             updated_at_ms = $2
+         WHERE id = $1 AND dead_lettered_at_ms IS NOT NULL
+         RETURNING {OUTBOUND_QUEUE_ENTRY_COLUMNS}"
+    );
+    sqlx::query_as::<_, OutboundQueueEntryRow>(&sql)
+        .bind(id)
+        .bind(now_ms)
+        .fetch_optional(pool)
+        .await
+}
+
 /// Store or replace the local server's ed25519 keypair (singleton row, id=1).
 pub async fn upsert_server_keypair(
     pool: &DbPool,
@@ -0,0 +1,58 @@
+use crate::{bool_from_any_row, DbError, DbPool};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct GuildMemberScreeningRow {
+    pub guild_id: i64,
+    pub enabled: bool,
+    pub rules: String,
+    pub updated_at: String,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for GuildMemberScreeningRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            guild_id: row.try_get("guild_id")?,
+            enabled: bool_from_any_row(row, "enabled")?,
+            rules: row.try_get("rules")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+pub async fn get_guild_member_screening(
+    pool: &DbPool,
+    guild_id: i64,
+) -> Result<Option<GuildMemberScreeningRow>, DbError> {
+    let row = sqlx::query_as::<_, GuildMemberScreeningRow>(
+        "SELECT guild_id, CASE WHEN enabled THEN 1 ELSE 0 END AS enabled, rules, updated_at
+         FROM guild_member_screening WHERE guild_id = $1",
+    )
+    .bind(guild_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn upsert_guild_member_screening(
+    pool: &DbPool,
+    guild_id: i64,
+    enabled: bool,
+    rules: &str,
+) -> Result<GuildMemberScreeningRow, DbError> {
+    let row = sqlx::query_as::<_, GuildMemberScreeningRow>(
+        "INSERT INTO guild_member_screening (guild_id, enabled, rules, updated_at)
+         VALUES ($1, $2, $3, datetime('now'))
+         ON CONFLICT(guild_id) DO UPDATE SET
+            enabled = excluded.enabled,
+            rules = excluded.rules,
+            updated_at = datetime('now')
+         RETURNING guild_id, CASE WHEN enabled THEN 1 ELSE 0 END AS enabled, rules, updated_at",
+    )
+    .bind(guild_id)
+    .bind(enabled)
+    .bind(rules)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
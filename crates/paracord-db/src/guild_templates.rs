@@ -0,0 +1,98 @@
+use crate::{datetime_from_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct GuildTemplateRow {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub source_guild_id: Option<i64>,
+    pub creator_id: Option<i64>,
+    /// JSON snapshot of the source guild's roles and channels.
+    pub serialized_guild: String,
+    pub usage_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for GuildTemplateRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let created_at_raw: String = row.try_get("created_at")?;
+        Ok(Self {
+            code: row.try_get("code")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            source_guild_id: row.try_get("source_guild_id")?,
+            creator_id: row.try_get("creator_id")?,
+            serialized_guild: row.try_get("serialized_guild")?,
+            usage_count: row.try_get("usage_count")?,
+            created_at: datetime_from_db_text(&created_at_raw)?,
+        })
+    }
+}
+
+pub async fn create_template(
+    pool: &DbPool,
+    code: &str,
+    name: &str,
+    description: Option<&str>,
+    source_guild_id: i64,
+    creator_id: i64,
+    serialized_guild: &str,
+) -> Result<GuildTemplateRow, DbError> {
+    let row = sqlx::query_as::<_, GuildTemplateRow>(
+        "INSERT INTO guild_templates (code, name, description, source_guild_id, creator_id, serialized_guild)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING code, name, description, source_guild_id, creator_id, serialized_guild, usage_count, created_at",
+    )
+    .bind(code)
+    .bind(name)
+    .bind(description)
+    .bind(source_guild_id)
+    .bind(creator_id)
+    .bind(serialized_guild)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_template(pool: &DbPool, code: &str) -> Result<Option<GuildTemplateRow>, DbError> {
+    let row = sqlx::query_as::<_, GuildTemplateRow>(
+        "SELECT code, name, description, source_guild_id, creator_id, serialized_guild, usage_count, created_at
+         FROM guild_templates WHERE code = $1",
+    )
+    .bind(code)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_guild_templates(
+    pool: &DbPool,
+    source_guild_id: i64,
+) -> Result<Vec<GuildTemplateRow>, DbError> {
+    let rows = sqlx::query_as::<_, GuildTemplateRow>(
+        "SELECT code, name, description, source_guild_id, creator_id, serialized_guild, usage_count, created_at
+         FROM guild_templates WHERE source_guild_id = $1 ORDER BY created_at",
+    )
+    .bind(source_guild_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn increment_usage(pool: &DbPool, code: &str) -> Result<(), DbError> {
+    sqlx::query("UPDATE guild_templates SET usage_count = usage_count + 1 WHERE code = $1")
+        .bind(code)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_template(pool: &DbPool, code: &str) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM guild_templates WHERE code = $1")
+        .bind(code)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
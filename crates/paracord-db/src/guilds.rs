@@ -144,6 +144,52 @@ pub async fn update_guild(
     .await
 }
 
+/// Replace the guild's feature bits (see `paracord_models::guild`).
+pub async fn set_space_features(
+    pool: &DbPool,
+    id: i64,
+    features: i32,
+) -> Result<SpaceRow, DbError> {
+    let row = sqlx::query_as::<_, SpaceRow>(
+        "UPDATE spaces
+         SET features = $2,
+             updated_at = datetime('now')
+         WHERE id = $1
+         RETURNING id, name, description, icon_hash, banner_hash, owner_id, features, system_channel_id, vanity_url_code, visibility, allowed_roles, created_at, hub_settings, bot_settings"
+    )
+    .bind(id)
+    .bind(features)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Set or clear one feature bit, leaving the others as they are.
+pub async fn set_space_feature(
+    pool: &DbPool,
+    id: i64,
+    feature: i32,
+    enabled: bool,
+) -> Result<SpaceRow, DbError> {
+    let features = if enabled {
+        "features | $2"
+    } else {
+        "features & ~$2"
+    };
+    let row = sqlx::query_as::<_, SpaceRow>(&format!(
+        "UPDATE spaces
+         SET features = {features},
+             updated_at = datetime('now')
+         WHERE id = $1
+         RETURNING id, name, description, icon_hash, banner_hash, owner_id, features, system_channel_id, vanity_url_code, visibility, allowed_roles, created_at, hub_settings, bot_settings"
+    ))
+    .bind(id)
+    .bind(feature)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
 pub async fn update_space_visibility(
     pool: &DbPool,
     id: i64,
@@ -202,41 +248,39 @@ pub async fn get_user_guilds(pool: &DbPool, user_id: i64) -> Result<Vec<SpaceRow
     .fetch_all(pool)
     .await?;
 
-    let mut visible = Vec::with_capacity(rows.len());
-    for row in rows {
-        if row.visibility != "roles" {
-            visible.push(row);
-            continue;
-        }
-
-        let allowed_roles = parse_allowed_role_ids(&row.allowed_roles);
-        if allowed_roles.is_empty() {
-            visible.push(row);
-            continue;
-        }
-
-        let member_role_rows: Vec<(i64,)> = sqlx::query_as(
-            "SELECT mr.role_id
-             FROM member_roles mr
-             INNER JOIN roles r ON r.id = mr.role_id
-             WHERE mr.user_id = $1
-               AND r.space_id = $2",
-        )
-        .bind(user_id)
-        .bind(row.id)
-        .fetch_all(pool)
-        .await?;
-
-        let user_roles: HashSet<i64> = member_role_rows.into_iter().map(|(id,)| id).collect();
-        if allowed_roles
-            .into_iter()
-            .any(|role_id| user_roles.contains(&role_id))
-        {
-            visible.push(row);
-        }
+    if !rows.iter().any(|row| {
+        row.visibility == "roles" && !parse_allowed_role_ids(&row.allowed_roles).is_empty()
+    }) {
+        return Ok(rows);
     }
 
-    Ok(visible)
+    // One query for the user's roles across all spaces, not one per
+    // role-restricted space.
+    let user_roles: HashSet<(i64, i64)> = sqlx::query_as(
+        "SELECT r.space_id, mr.role_id
+         FROM member_roles mr
+         INNER JOIN roles r ON r.id = mr.role_id
+         WHERE mr.user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    Ok(rows
+        .into_iter()
+        .filter(|row| {
+            if row.visibility != "roles" {
+                return true;
+            }
+            let allowed_roles = parse_allowed_role_ids(&row.allowed_roles);
+            allowed_roles.is_empty()
+                || allowed_roles
+                    .into_iter()
+                    .any(|role_id| user_roles.contains(&(row.id, role_id)))
+        })
+        .collect())
 }
 
 pub fn parse_allowed_role_ids(raw: &str) -> Vec<i64> {
@@ -370,6 +414,18 @@ mod tests {
         assert_eq!(updated.description.as_deref(), Some("desc only"));
     }
 
+    #[tokio::test]
+    async fn test_set_space_feature_keeps_other_bits() {
+        let pool = test_pool().await;
+        create_test_user(&pool, 1).await;
+        create_guild(&pool, 302, "Features", 1, None).await.unwrap();
+        set_space_features(&pool, 302, 0b01).await.unwrap();
+        let updated = set_space_feature(&pool, 302, 0b10, true).await.unwrap();
+        assert_eq!(updated.features, 0b11);
+        let updated = set_space_feature(&pool, 302, 0b01, false).await.unwrap();
+        assert_eq!(updated.features, 0b10);
+    }
+
     #[tokio::test]
     async fn test_delete_guild() {
         let pool = test_pool().await;
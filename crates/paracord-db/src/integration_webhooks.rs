@@ -0,0 +1,214 @@
+use crate::{datetime_from_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct IntegrationSubscriptionRow {
+    pub id: i64,
+    pub guild_id: i64,
+    pub url: String,
+    pub secret: String,
+    /// Comma-separated gateway event types.
+    pub event_types: String,
+    pub creator_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IntegrationSubscriptionRow {
+    pub fn event_type_list(&self) -> Vec<&str> {
+        self.event_types
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for IntegrationSubscriptionRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let created_at_raw: String = row.try_get("created_at")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            guild_id: row.try_get("guild_id")?,
+            url: row.try_get("url")?,
+            secret: row.try_get("secret")?,
+            event_types: row.try_get("event_types")?,
+            creator_id: row.try_get("creator_id")?,
+            created_at: datetime_from_db_text(&created_at_raw)?,
+        })
+    }
+}
+
+/// A queued delivery joined with the subscription it goes to.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct IntegrationDeliveryRow {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub url: String,
+    pub secret: String,
+    pub event_type: String,
+    pub payload: String,
+    pub attempt_count: i64,
+}
+
+pub async fn create_subscription(
+    pool: &DbPool,
+    id: i64,
+    guild_id: i64,
+    url: &str,
+    secret: &str,
+    event_types: &str,
+    creator_id: i64,
+) -> Result<IntegrationSubscriptionRow, DbError> {
+    let row = sqlx::query_as::<_, IntegrationSubscriptionRow>(
+        "INSERT INTO integration_subscriptions (id, guild_id, url, secret, event_types, creator_id)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, guild_id, url, secret, event_types, creator_id, created_at",
+    )
+    .bind(id)
+    .bind(guild_id)
+    .bind(url)
+    .bind(secret)
+    .bind(event_types)
+    .bind(creator_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn list_guild_subscriptions(
+    pool: &DbPool,
+    guild_id: i64,
+) -> Result<Vec<IntegrationSubscriptionRow>, DbError> {
+    let rows = sqlx::query_as::<_, IntegrationSubscriptionRow>(
+        "SELECT id, guild_id, url, secret, event_types, creator_id, created_at
+         FROM integration_subscriptions
+         WHERE guild_id = $1
+         ORDER BY id",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn count_guild_subscriptions(pool: &DbPool, guild_id: i64) -> Result<i64, DbError> {
+    let row: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM integration_subscriptions WHERE guild_id = $1")
+            .bind(guild_id)
+            .fetch_one(pool)
+            .await?;
+    Ok(row.0)
+}
+
+/// Deletes the subscription and anything still queued for it.
+pub async fn delete_subscription(
+    pool: &DbPool,
+    guild_id: i64,
+    subscription_id: i64,
+) -> Result<bool, DbError> {
+    let result =
+        sqlx::query("DELETE FROM integration_subscriptions WHERE id = $1 AND guild_id = $2")
+            .bind(subscription_id)
+            .bind(guild_id)
+            .execute(pool)
+            .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+    sqlx::query("DELETE FROM integration_deliveries WHERE subscription_id = $1")
+        .bind(subscription_id)
+        .execute(pool)
+        .await?;
+    Ok(true)
+}
+
+pub async fn enqueue_delivery(
+    pool: &DbPool,
+    id: i64,
+    subscription_id: i64,
+    event_type: &str,
+    payload: &str,
+    now_ms: i64,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "INSERT INTO integration_deliveries
+             (id, subscription_id, event_type, payload, next_attempt_at_ms, created_at_ms)
+         VALUES ($1, $2, $3, $4, $5, $5)",
+    )
+    .bind(id)
+    .bind(subscription_id)
+    .bind(event_type)
+    .bind(payload)
+    .bind(now_ms)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn fetch_due_deliveries(
+    pool: &DbPool,
+    now_ms: i64,
+    limit: i64,
+) -> Result<Vec<IntegrationDeliveryRow>, DbError> {
+    let rows = sqlx::query_as::<_, IntegrationDeliveryRow>(
+        "SELECT d.id, d.subscription_id, s.url, s.secret, d.event_type, d.payload, d.attempt_count
+         FROM integration_deliveries d
+         INNER JOIN integration_subscriptions s ON s.id = d.subscription_id
+         WHERE d.next_attempt_at_ms <= $1
+         ORDER BY d.next_attempt_at_ms ASC
+         LIMIT $2",
+    )
+    .bind(now_ms)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn mark_delivery_delivered(pool: &DbPool, delivery_id: i64) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM integration_deliveries WHERE id = $1")
+        .bind(delivery_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn mark_delivery_retry(
+    pool: &DbPool,
+    delivery_id: i64,
+    next_attempt_at_ms: i64,
+    error: Option<&str>,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "UPDATE integration_deliveries
+         SET attempt_count = attempt_count + 1,
+             next_attempt_at_ms = $2,
+             last_error = $3
+         WHERE id = $1",
+    )
+    .bind(delivery_id)
+    .bind(next_attempt_at_ms)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Drop deliveries that ran out of attempts or are older than `max_age_ms`.
+pub async fn purge_expired_deliveries(
+    pool: &DbPool,
+    now_ms: i64,
+    max_attempts: i64,
+    max_age_ms: i64,
+) -> Result<u64, DbError> {
+    let result = sqlx::query(
+        "DELETE FROM integration_deliveries
+         WHERE attempt_count >= $1 OR created_at_ms < $2",
+    )
+    .bind(max_attempts)
+    .bind(now_ms.saturating_sub(max_age_ms))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -1,20 +1,29 @@
+pub mod application_commands;
 pub mod attachments;
 pub mod audit_log;
 pub mod bans;
 pub mod bot_applications;
+pub mod channel_followers;
 pub mod channel_overwrites;
 pub mod channels;
 pub mod dms;
 pub mod emojis;
 pub mod federation;
 pub mod federation_file_cache;
+pub mod guild_member_screening;
 pub mod guild_storage_policies;
+pub mod guild_templates;
 pub mod guilds;
+pub mod integration_webhooks;
+pub mod interaction_tokens;
 pub mod invites;
 pub mod members;
 pub mod messages;
+pub mod pending_uploads;
+pub mod personal_access_tokens;
 pub mod polls;
 pub mod prekeys;
+pub mod push;
 pub mod rate_limits;
 pub mod reactions;
 pub mod read_states;
@@ -24,13 +33,17 @@ pub mod scheduled_events;
 pub mod security_events;
 pub mod server_settings;
 pub mod sessions;
+pub mod totp;
 pub mod users;
 pub mod voice_states;
+pub mod webauthn_credentials;
 pub mod webhooks;
 
 use sha2::{Digest, Sha256};
 use sqlx::any::AnyPoolOptions;
+use std::future::Future;
 use std::sync::OnceLock;
+use std::time::Duration;
 use thiserror::Error;
 
 pub type DbPool = sqlx::AnyPool;
@@ -69,8 +82,53 @@ pub struct PgConnectOptions {
     pub idle_in_transaction_timeout_secs: u64,
 }
 
+/// `PRAGMA synchronous` level for SQLite connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SqliteSynchronous {
+    Off,
+    /// Safe with WAL: a power loss can roll back the last commits but never
+    /// corrupts the database.
+    #[default]
+    Normal,
+    Full,
+    Extra,
+}
+
+impl SqliteSynchronous {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Normal => "NORMAL",
+            Self::Full => "FULL",
+            Self::Extra => "EXTRA",
+        }
+    }
+}
+
+/// Optional tuning knobs applied after each SQLite connection is established.
+///
+/// SQLite allows a single writer at a time, so extra pool connections only
+/// add read concurrency; a handful (5-10) is usually enough. Writers that
+/// find the database locked wait up to `busy_timeout_ms` before failing with
+/// `SQLITE_BUSY`.
+#[derive(Debug, Clone)]
+pub struct SqliteConnectOptions {
+    /// `busy_timeout` in milliseconds (0 = fail immediately when locked).
+    pub busy_timeout_ms: u64,
+    pub synchronous: SqliteSynchronous,
+}
+
+impl Default for SqliteConnectOptions {
+    fn default() -> Self {
+        Self {
+            busy_timeout_ms: 5000,
+            synchronous: SqliteSynchronous::Normal,
+        }
+    }
+}
+
 pub async fn create_pool(database_url: &str, max_connections: u32) -> Result<DbPool, sqlx::Error> {
-    create_pool_full(database_url, max_connections, None, None, None).await
+    create_pool_full(database_url, max_connections, None, None, None, None).await
 }
 
 pub async fn create_pool_with_sqlite_key(
@@ -78,7 +136,7 @@ pub async fn create_pool_with_sqlite_key(
     max_connections: u32,
     sqlite_key_hex: Option<String>,
 ) -> Result<DbPool, sqlx::Error> {
-    create_pool_full(database_url, max_connections, None, sqlite_key_hex, None).await
+    create_pool_full(database_url, max_connections, None, sqlite_key_hex, None, None).await
 }
 
 pub async fn create_pool_with_engine_and_sqlite_key(
@@ -87,7 +145,7 @@ pub async fn create_pool_with_engine_and_sqlite_key(
     engine: Option<DatabaseEngine>,
     sqlite_key_hex: Option<String>,
 ) -> Result<DbPool, sqlx::Error> {
-    create_pool_full(database_url, max_connections, engine, sqlite_key_hex, None).await
+    create_pool_full(database_url, max_connections, engine, sqlite_key_hex, None, None).await
 }
 
 pub async fn create_pool_full(
@@ -96,6 +154,7 @@ pub async fn create_pool_full(
     engine: Option<DatabaseEngine>,
     sqlite_key_hex: Option<String>,
     pg_options: Option<PgConnectOptions>,
+    sqlite_options: Option<SqliteConnectOptions>,
 ) -> Result<DbPool, sqlx::Error> {
     let detected_engine = detect_database_engine(database_url)?;
     let engine = engine.unwrap_or(detected_engine);
@@ -136,12 +195,14 @@ pub async fn create_pool_full(
 
     let after_connect_key = sqlite_key_hex.clone();
     let pg_opts = pg_options.unwrap_or_default();
+    let sqlite_opts = sqlite_options.unwrap_or_default();
     AnyPoolOptions::new()
         .max_connections(max_connections)
         .after_connect(move |conn, _meta| {
             let sqlite_key_hex = after_connect_key.clone();
             let sqlite_db = matches!(engine, DatabaseEngine::Sqlite);
             let pg_opts = pg_opts.clone();
+            let sqlite_opts = sqlite_opts.clone();
             Box::pin(async move {
                 if sqlite_db {
                     if let Some(key_hex) = sqlite_key_hex {
@@ -172,12 +233,13 @@ pub async fn create_pool_full(
                     sqlx::query("PRAGMA foreign_keys = ON;")
                         .execute(&mut *conn)
                         .await?;
-                    sqlx::query("PRAGMA busy_timeout = 5000;")
-                        .execute(&mut *conn)
-                        .await?;
-                    sqlx::query("PRAGMA synchronous = NORMAL;")
-                        .execute(&mut *conn)
-                        .await?;
+                    let sql = format!("PRAGMA busy_timeout = {};", sqlite_opts.busy_timeout_ms);
+                    sqlx::query(&sql).execute(&mut *conn).await?;
+                    let sql = format!(
+                        "PRAGMA synchronous = {};",
+                        sqlite_opts.synchronous.as_str()
+                    );
+                    sqlx::query(&sql).execute(&mut *conn).await?;
                     sqlx::query("PRAGMA cache_size = -8000;")
                         .execute(&mut *conn)
                         .await?;
@@ -248,6 +310,12 @@ pub fn active_database_engine() -> DatabaseEngine {
     *ACTIVE_DB_ENGINE.get().unwrap_or(&DatabaseEngine::Sqlite)
 }
 
+/// Round-trip a trivial query to confirm the pool can reach the database.
+pub async fn ping(pool: &DbPool) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT 1").execute(pool).await?;
+    Ok(())
+}
+
 fn normalize_sqlite_url_for_any(url: &str) -> String {
     // sqlx::Any uses URL parsing that expects absolute Windows paths in the
     // sqlite:///C:/... form (three slashes), while existing config/tests often
@@ -291,6 +359,57 @@ pub(crate) fn datetime_from_db_text(
     ))
 }
 
+/// Attempts made by [`retry_on_busy`], including the first.
+const BUSY_RETRY_ATTEMPTS: u32 = 4;
+const BUSY_RETRY_BASE_DELAY: Duration = Duration::from_millis(25);
+
+/// Whether `err` is SQLite lock contention (`SQLITE_BUSY`/`SQLITE_LOCKED` or
+/// one of their extended codes) that may succeed if simply run again.
+pub fn is_transient_lock_error(err: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(db_err) = err else {
+        return false;
+    };
+    // Postgres SQLSTATEs can parse as numbers too; only SQLite codes apply.
+    let Some(db_err) = db_err.try_downcast_ref::<sqlx::sqlite::SqliteError>() else {
+        return false;
+    };
+    let db_err: &dyn sqlx::error::DatabaseError = db_err;
+    if let Some(code) = db_err.code() {
+        if let Ok(code) = code.parse::<i32>() {
+            // Primary result code lives in the low byte of extended codes.
+            if matches!(code & 0xff, 5 | 6) {
+                return true;
+            }
+        }
+    }
+    let message = db_err.message().to_ascii_lowercase();
+    message.contains("database is locked") || message.contains("database table is locked")
+}
+
+/// Run a single-statement write, retrying with exponential backoff while it
+/// fails with transient lock contention. Only use this for statements run
+/// directly on the pool: retrying inside a transaction does not release the
+/// lock the transaction is waiting on.
+pub(crate) async fn retry_on_busy<T, F, Fut>(mut op: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut delay = BUSY_RETRY_BASE_DELAY;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(err) if attempt < BUSY_RETRY_ATTEMPTS && is_transient_lock_error(&err) => {
+                tracing::debug!(attempt, "database busy, retrying write: {err}");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
 pub(crate) fn json_from_db_text(value: &str) -> Result<serde_json::Value, sqlx::Error> {
     serde_json::from_str(value)
         .map_err(|e| sqlx::Error::Protocol(format!("invalid json text: {e}").into()))
@@ -370,9 +489,68 @@ async fn backfill_webhook_token_hashes(pool: &DbPool) -> Result<(), sqlx::Error>
 #[cfg(test)]
 mod tests {
     use super::{
-        backfill_webhook_token_hashes, create_pool, create_pool_with_engine_and_sqlite_key,
-        create_pool_with_sqlite_key, run_migrations, run_migrations_for_engine, DatabaseEngine,
+        backfill_webhook_token_hashes, create_pool, create_pool_full,
+        create_pool_with_engine_and_sqlite_key, create_pool_with_sqlite_key, run_migrations,
+        run_migrations_for_engine, DatabaseEngine, DbPool, SqliteConnectOptions,
+        SqliteSynchronous, BUSY_RETRY_ATTEMPTS,
     };
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    async fn file_pool(name: &str, options: SqliteConnectOptions) -> DbPool {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-db-{name}-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = create_pool_full(&db_url, 2, None, None, None, Some(options))
+            .await
+            .expect("pool");
+        sqlx::query("CREATE TABLE counters (id INTEGER PRIMARY KEY, value INTEGER NOT NULL)")
+            .execute(&pool)
+            .await
+            .expect("create table");
+        pool
+    }
+
+    /// Take the database write lock on a dedicated pool connection.
+    async fn lock_writes(pool: &DbPool) -> sqlx::pool::PoolConnection<sqlx::Any> {
+        let mut locker = pool.acquire().await.expect("acquire");
+        sqlx::query("BEGIN IMMEDIATE")
+            .execute(&mut *locker)
+            .await
+            .expect("begin");
+        sqlx::query("INSERT INTO counters (id, value) VALUES (1, 1)")
+            .execute(&mut *locker)
+            .await
+            .expect("insert under lock");
+        locker
+    }
+
+    /// Hold the write lock on one connection for `hold`, while a second
+    /// connection from the same pool tries to insert.
+    async fn insert_while_locked(pool: &DbPool, hold: Duration) -> Result<(), sqlx::Error> {
+        let mut locker = lock_writes(pool).await;
+
+        let writer_pool = pool.clone();
+        let writer = tokio::spawn(async move {
+            sqlx::query("INSERT INTO counters (id, value) VALUES (2, 2)")
+                .execute(&writer_pool)
+                .await
+                .map(|_| ())
+        });
+
+        tokio::time::sleep(hold).await;
+        sqlx::query("COMMIT")
+            .execute(&mut *locker)
+            .await
+            .expect("commit");
+        writer.await.expect("writer task")
+    }
 
     #[tokio::test]
     async fn create_pool_supports_default_sqlite_mode() {
@@ -384,6 +562,157 @@ mod tests {
         assert_eq!(value, 1);
     }
 
+    #[tokio::test]
+    async fn create_pool_applies_sqlite_tuning() {
+        let options = SqliteConnectOptions {
+            busy_timeout_ms: 1234,
+            synchronous: SqliteSynchronous::Full,
+        };
+        let pool = create_pool_full("sqlite::memory:", 1, None, None, None, Some(options))
+            .await
+            .expect("pool");
+        let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+            .fetch_one(&pool)
+            .await
+            .expect("busy_timeout");
+        let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+            .fetch_one(&pool)
+            .await
+            .expect("synchronous");
+        assert_eq!(busy_timeout, 1234);
+        assert_eq!(synchronous, 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_writer_waits_out_lock_with_busy_timeout() {
+        let pool = file_pool("busy-wait", SqliteConnectOptions::default()).await;
+        insert_while_locked(&pool, Duration::from_millis(250))
+            .await
+            .expect("second writer should wait for the lock instead of failing");
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM counters")
+            .fetch_one(&pool)
+            .await
+            .expect("count");
+        assert_eq!(rows, 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_writer_fails_fast_without_busy_timeout() {
+        let pool = file_pool("busy-none", no_busy_timeout()).await;
+        let err = insert_while_locked(&pool, Duration::from_millis(250))
+            .await
+            .expect_err("writer should hit SQLITE_BUSY with no busy timeout");
+        assert!(err.to_string().contains("locked"), "{err}");
+    }
+
+    fn no_busy_timeout() -> SqliteConnectOptions {
+        SqliteConnectOptions {
+            busy_timeout_ms: 0,
+            ..SqliteConnectOptions::default()
+        }
+    }
+
+    async fn insert_with_retry(pool: &DbPool, attempts: &AtomicU32) -> Result<(), sqlx::Error> {
+        super::retry_on_busy(|| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            sqlx::query("INSERT INTO counters (id, value) VALUES (2, 2)").execute(pool)
+        })
+        .await
+        .map(|_| ())
+    }
+
+    #[tokio::test]
+    async fn retry_on_busy_succeeds_once_lock_is_released() {
+        let pool = file_pool("retry-ok", no_busy_timeout()).await;
+        let mut locker = lock_writes(&pool).await;
+        let attempts = AtomicU32::new(0);
+
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            sqlx::query("COMMIT")
+                .execute(&mut *locker)
+                .await
+                .expect("commit");
+        };
+        let (result, ()) = tokio::join!(insert_with_retry(&pool, &attempts), release);
+
+        result.expect("write should succeed after the lock is released");
+        assert!(attempts.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn retry_on_busy_gives_up_after_bounded_attempts() {
+        let pool = file_pool("retry-exhausted", no_busy_timeout()).await;
+        let _locker = lock_writes(&pool).await;
+        let attempts = AtomicU32::new(0);
+
+        let err = insert_with_retry(&pool, &attempts)
+            .await
+            .expect_err("lock is never released");
+        assert!(super::is_transient_lock_error(&err), "{err}");
+        assert_eq!(attempts.load(Ordering::SeqCst), BUSY_RETRY_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn retry_on_busy_does_not_retry_other_errors() {
+        let pool = file_pool("retry-other", SqliteConnectOptions::default()).await;
+        let attempts = AtomicU32::new(0);
+        let err = super::retry_on_busy(|| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            sqlx::query("INSERT INTO missing_table (id) VALUES (1)").execute(&pool)
+        })
+        .await
+        .expect_err("table does not exist");
+        assert!(!super::is_transient_lock_error(&err));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    /// Stand-in for a Postgres error whose SQLSTATE parses as a number.
+    #[derive(Debug)]
+    struct OtherDriverError;
+
+    impl std::fmt::Display for OtherDriverError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("database is locked")
+        }
+    }
+
+    impl std::error::Error for OtherDriverError {}
+
+    impl sqlx::error::DatabaseError for OtherDriverError {
+        fn message(&self) -> &str {
+            "database is locked"
+        }
+
+        fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+            // 0x...05 in the low byte, like SQLITE_BUSY.
+            Some("40965".into())
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    #[test]
+    fn only_sqlite_errors_count_as_lock_contention() {
+        let err = sqlx::Error::Database(Box::new(OtherDriverError));
+        assert!(!super::is_transient_lock_error(&err));
+    }
+
     #[tokio::test]
     async fn rejects_invalid_sqlite_key_format() {
         let err = create_pool_with_sqlite_key("sqlite::memory:", 1, Some("abc".to_string()))
@@ -438,6 +767,105 @@ mod tests {
         assert_ne!(stored, "plaintext-token");
     }
 
+    #[tokio::test]
+    async fn reaction_emoji_migration_merges_legacy_spellings() {
+        let pool = create_pool("sqlite::memory:", 1).await.expect("pool");
+        run_migrations(&pool).await.expect("migrations");
+
+        for sql in [
+            "INSERT INTO users (id, username, discriminator, email, password_hash)
+             VALUES (1, 'u', 1, 'u@example.com', 'hash'), (2, 'v', 2, 'v@example.com', 'hash')",
+            "INSERT INTO spaces (id, name, owner_id) VALUES (3, 'space', 1)",
+            "INSERT INTO channels (id, space_id, name, channel_type, position)
+             VALUES (4, 3, 'general', 0, 0)",
+            "INSERT INTO messages (id, channel_id, author_id, content) VALUES (5, 4, 1, 'hi')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.expect("seed");
+        }
+        // Rows as written before canonicalization: raw key, no display column.
+        for (user_id, emoji, burst) in [
+            (1, "\u{2764}\u{FE0F}", false),
+            (1, "\u{2764}", true),
+            (2, "\u{2764}\u{FE0F}", false),
+            (2, "fire", false),
+        ] {
+            sqlx::query(
+                "INSERT INTO reactions (message_id, user_id, emoji_name, burst) VALUES (5, $1, $2, $3)",
+            )
+            .bind(user_id)
+            .bind(emoji)
+            .bind(burst)
+            .execute(&pool)
+            .await
+            .expect("insert reaction");
+        }
+
+        sqlx::raw_sql(include_str!(
+            "../migrations/20260328000001_canonicalize_reaction_emoji.sql"
+        ))
+        .execute(&pool)
+        .await
+        .expect("canonicalize");
+
+        let rows: Vec<(i64, String, String, i64)> = sqlx::query_as(
+            "SELECT user_id, emoji_name, emoji_display, CASE WHEN burst THEN 1 ELSE 0 END
+             FROM reactions
+             ORDER BY user_id, emoji_name",
+        )
+        .fetch_all(&pool)
+        .await
+        .expect("load reactions");
+        assert_eq!(
+            rows,
+            vec![
+                (1, "\u{2764}".to_string(), "\u{2764}".to_string(), 1),
+                (2, "fire".to_string(), "fire".to_string(), 0),
+                (2, "\u{2764}".to_string(), "\u{2764}\u{FE0F}".to_string(), 0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn capped_reaction_adds_refuse_new_keys_at_the_limit() {
+        let pool = create_pool("sqlite::memory:", 1).await.expect("pool");
+        run_migrations(&pool).await.expect("migrations");
+
+        for sql in [
+            "INSERT INTO users (id, username, discriminator, email, password_hash)
+             VALUES (1, 'u', 1, 'u@example.com', 'hash'), (2, 'v', 2, 'v@example.com', 'hash')",
+            "INSERT INTO spaces (id, name, owner_id) VALUES (3, 'space', 1)",
+            "INSERT INTO channels (id, space_id, name, channel_type, position)
+             VALUES (4, 3, 'general', 0, 0)",
+            "INSERT INTO messages (id, channel_id, author_id, content) VALUES (5, 4, 1, 'hi')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.expect("seed");
+        }
+        let add = |user_id: i64, emoji: &'static str| {
+            let pool = pool.clone();
+            async move {
+                crate::reactions::add_reaction_capped(
+                    &pool, 5, user_id, emoji, emoji, None, false, 2,
+                )
+                .await
+                .expect("add reaction")
+            }
+        };
+
+        assert!(add(1, "a").await);
+        assert!(add(1, "b").await);
+        assert!(!add(1, "c").await, "a third key is over the cap");
+        assert!(
+            add(2, "a").await,
+            "joining an existing key is always allowed"
+        );
+        assert!(add(1, "a").await, "re-adding is an upsert");
+
+        let keys = crate::reactions::get_message_reaction_keys(&pool, 5)
+            .await
+            .expect("keys");
+        assert_eq!(keys.len(), 2);
+    }
+
     #[tokio::test]
     async fn postgres_pool_and_migrations_smoke_when_configured() {
         let Some(url) = std::env::var("PARACORD_TEST_POSTGRES_URL")
@@ -500,7 +928,8 @@ mod tests {
             None,
         )
         .await
-        .expect("create thread");
+        .expect("create thread")
+        .expect("thread inserted");
         assert_eq!(thread.id, thread_id);
         assert_eq!(thread.owner_id, Some(user_id));
 
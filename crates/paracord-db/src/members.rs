@@ -11,6 +11,15 @@ pub struct MemberRow {
     pub deaf: bool,
     pub mute: bool,
     pub communication_disabled_until: Option<DateTime<Utc>>,
+    /// Joined a screened guild and has not accepted its rules yet.
+    pub pending: bool,
+}
+
+/// A user's membership in one guild.
+#[derive(Debug, Clone)]
+pub struct MembershipRow {
+    pub guild_id: i64,
+    pub member: MemberRow,
 }
 
 #[derive(Debug, Clone)]
@@ -22,6 +31,7 @@ pub struct MemberWithUserRow {
     pub deaf: bool,
     pub mute: bool,
     pub communication_disabled_until: Option<DateTime<Utc>>,
+    pub pending: bool,
     pub username: String,
     pub discriminator: i16,
     pub user_avatar_hash: Option<String>,
@@ -43,6 +53,16 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MemberRow {
                 .as_deref()
                 .map(datetime_from_db_text)
                 .transpose()?,
+            pending: bool_from_any_row(row, "pending")?,
+        })
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MembershipRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            guild_id: row.try_get("guild_id")?,
+            member: <MemberRow as sqlx::FromRow<'r, sqlx::any::AnyRow>>::from_row(row)?,
         })
     }
 }
@@ -62,6 +82,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MemberWithUserRow {
                 .as_deref()
                 .map(datetime_from_db_text)
                 .transpose()?,
+            pending: bool_from_any_row(row, "pending")?,
             username: row.try_get("username")?,
             discriminator: row.try_get("discriminator")?,
             user_avatar_hash: row.try_get("user_avatar_hash")?,
@@ -72,14 +93,46 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MemberWithUserRow {
 
 /// Add a user as a server-wide member. guild_id kept for API compat but ignored.
 pub async fn add_member(pool: &DbPool, user_id: i64, guild_id: i64) -> Result<(), DbError> {
-    sqlx::query("INSERT INTO members (user_id, guild_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+    crate::retry_on_busy(|| {
+        sqlx::query(
+            "INSERT INTO members (user_id, guild_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
         .bind(user_id)
         .bind(guild_id)
         .execute(pool)
-        .await?;
+    })
+    .await?;
     Ok(())
 }
 
+/// Add a user who joined through an invite. The member starts out `pending` in
+/// the same statement when the guild has screening enabled, so there is no
+/// window in which they are an unscreened member. Returns `None` if they were
+/// already a member, otherwise whether they are pending.
+pub async fn add_invited_member(
+    pool: &DbPool,
+    user_id: i64,
+    guild_id: i64,
+) -> Result<Option<bool>, DbError> {
+    let row = crate::retry_on_busy(|| {
+        sqlx::query_as::<_, MemberRow>(
+            "INSERT INTO members (user_id, guild_id, pending)
+             SELECT $1, $2, COALESCE(
+                 (SELECT enabled FROM guild_member_screening WHERE guild_id = $2),
+                 FALSE
+             )
+             WHERE TRUE
+             ON CONFLICT DO NOTHING
+             RETURNING user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until, CASE WHEN pending THEN 1 ELSE 0 END AS pending",
+        )
+        .bind(user_id)
+        .bind(guild_id)
+        .fetch_optional(pool)
+    })
+    .await?;
+    Ok(row.map(|member| member.pending))
+}
+
 pub async fn add_server_member(pool: &DbPool, user_id: i64) -> Result<(), DbError> {
     sqlx::query(
         "INSERT INTO members (user_id, guild_id)
@@ -99,7 +152,7 @@ pub async fn get_member(
     guild_id: i64,
 ) -> Result<Option<MemberRow>, DbError> {
     let row = sqlx::query_as::<_, MemberRow>(
-        "SELECT user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until
+        "SELECT user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until, CASE WHEN pending THEN 1 ELSE 0 END AS pending
          FROM members WHERE user_id = $1 AND guild_id = $2",
     )
     .bind(user_id)
@@ -109,9 +162,30 @@ pub async fn get_member(
     Ok(row)
 }
 
+/// Nicknames of the given users in one guild, for those who are members.
+pub async fn get_member_nicks(
+    pool: &DbPool,
+    guild_id: i64,
+    user_ids: &[i64],
+) -> Result<Vec<(i64, Option<String>)>, DbError> {
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (2..=user_ids.len() + 1).map(|i| format!("${i}")).collect();
+    let sql = format!(
+        "SELECT user_id, nick FROM members WHERE guild_id = $1 AND user_id IN ({})",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, (i64, Option<String>)>(&sql).bind(guild_id);
+    for user_id in user_ids {
+        query = query.bind(*user_id);
+    }
+    Ok(query.fetch_all(pool).await?)
+}
+
 pub async fn get_server_member(pool: &DbPool, user_id: i64) -> Result<Option<MemberRow>, DbError> {
     let row = sqlx::query_as::<_, MemberRow>(
-        "SELECT user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until
+        "SELECT user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until, CASE WHEN pending THEN 1 ELSE 0 END AS pending
          FROM members WHERE user_id = $1 ORDER BY joined_at ASC LIMIT 1",
     )
     .bind(user_id)
@@ -120,6 +194,21 @@ pub async fn get_server_member(pool: &DbPool, user_id: i64) -> Result<Option<Mem
     Ok(row)
 }
 
+/// Every guild membership of `user_id`.
+pub async fn get_user_memberships(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<MembershipRow>, DbError> {
+    let rows = sqlx::query_as::<_, MembershipRow>(
+        "SELECT guild_id, user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until, CASE WHEN pending THEN 1 ELSE 0 END AS pending
+         FROM members WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 pub async fn get_guild_members(
     pool: &DbPool,
     guild_id: i64,
@@ -128,7 +217,7 @@ pub async fn get_guild_members(
 ) -> Result<Vec<MemberWithUserRow>, DbError> {
     let rows = if let Some(after_id) = after {
         sqlx::query_as::<_, MemberWithUserRow>(
-            "SELECT m.user_id, m.nick, m.avatar_hash, m.joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until,
+            "SELECT m.user_id, m.nick, m.avatar_hash, m.joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until, CASE WHEN m.pending THEN 1 ELSE 0 END AS pending,
                     u.username, u.discriminator, u.avatar_hash AS user_avatar_hash, u.flags AS user_flags
              FROM members m
              INNER JOIN users u ON u.id = m.user_id
@@ -144,7 +233,7 @@ pub async fn get_guild_members(
         .await?
     } else {
         sqlx::query_as::<_, MemberWithUserRow>(
-            "SELECT m.user_id, m.nick, m.avatar_hash, m.joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until,
+            "SELECT m.user_id, m.nick, m.avatar_hash, m.joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until, CASE WHEN m.pending THEN 1 ELSE 0 END AS pending,
                     u.username, u.discriminator, u.avatar_hash AS user_avatar_hash, u.flags AS user_flags
              FROM members m
              INNER JOIN users u ON u.id = m.user_id
@@ -167,12 +256,12 @@ pub async fn get_server_members(
 ) -> Result<Vec<MemberWithUserRow>, DbError> {
     let rows = if let Some(after_id) = after {
         sqlx::query_as::<_, MemberWithUserRow>(
-            "SELECT m.user_id, m.nick, m.avatar_hash, MIN(m.joined_at) AS joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until,
+            "SELECT m.user_id, m.nick, m.avatar_hash, MIN(m.joined_at) AS joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until, CASE WHEN m.pending THEN 1 ELSE 0 END AS pending,
                     u.username, u.discriminator, u.avatar_hash AS user_avatar_hash, u.flags AS user_flags
              FROM members m
              INNER JOIN users u ON u.id = m.user_id
              WHERE m.user_id > $2
-             GROUP BY m.user_id, m.nick, m.avatar_hash, m.deaf, m.mute, m.communication_disabled_until, u.username, u.discriminator, u.avatar_hash, u.flags
+             GROUP BY m.user_id, m.nick, m.avatar_hash, m.deaf, m.mute, m.communication_disabled_until, m.pending, u.username, u.discriminator, u.avatar_hash, u.flags
              ORDER BY m.user_id
              LIMIT $1"
         )
@@ -182,11 +271,11 @@ pub async fn get_server_members(
         .await?
     } else {
         sqlx::query_as::<_, MemberWithUserRow>(
-            "SELECT m.user_id, m.nick, m.avatar_hash, MIN(m.joined_at) AS joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until,
+            "SELECT m.user_id, m.nick, m.avatar_hash, MIN(m.joined_at) AS joined_at, CASE WHEN m.deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN m.mute THEN 1 ELSE 0 END AS mute, m.communication_disabled_until, CASE WHEN m.pending THEN 1 ELSE 0 END AS pending,
                     u.username, u.discriminator, u.avatar_hash AS user_avatar_hash, u.flags AS user_flags
              FROM members m
              INNER JOIN users u ON u.id = m.user_id
-             GROUP BY m.user_id, m.nick, m.avatar_hash, m.deaf, m.mute, m.communication_disabled_until, u.username, u.discriminator, u.avatar_hash, u.flags
+             GROUP BY m.user_id, m.nick, m.avatar_hash, m.deaf, m.mute, m.communication_disabled_until, m.pending, u.username, u.discriminator, u.avatar_hash, u.flags
              ORDER BY m.joined_at
              LIMIT $1"
         )
@@ -208,7 +297,7 @@ pub async fn update_member(
     let row = sqlx::query_as::<_, MemberRow>(
         "UPDATE members SET nick = COALESCE($2, nick), deaf = COALESCE($3, deaf), mute = COALESCE($4, mute)
          WHERE user_id = $1 AND guild_id = $5
-         RETURNING user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until"
+         RETURNING user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until, CASE WHEN pending THEN 1 ELSE 0 END AS pending"
     )
     .bind(user_id)
     .bind(nick)
@@ -239,7 +328,7 @@ pub async fn set_member_timeout(
         "UPDATE members
          SET communication_disabled_until = $2
          WHERE user_id = $1 AND guild_id = $3
-         RETURNING user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until",
+         RETURNING user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until, CASE WHEN pending THEN 1 ELSE 0 END AS pending",
     )
     .bind(user_id)
     .bind(communication_disabled_until.map(datetime_to_db_text))
@@ -249,6 +338,26 @@ pub async fn set_member_timeout(
     Ok(row)
 }
 
+pub async fn set_member_pending(
+    pool: &DbPool,
+    user_id: i64,
+    guild_id: i64,
+    pending: bool,
+) -> Result<Option<MemberRow>, DbError> {
+    let row = sqlx::query_as::<_, MemberRow>(
+        "UPDATE members
+         SET pending = $2
+         WHERE user_id = $1 AND guild_id = $3
+         RETURNING user_id, nick, avatar_hash, joined_at, CASE WHEN deaf THEN 1 ELSE 0 END AS deaf, CASE WHEN mute THEN 1 ELSE 0 END AS mute, communication_disabled_until, CASE WHEN pending THEN 1 ELSE 0 END AS pending",
+    )
+    .bind(user_id)
+    .bind(pending)
+    .bind(guild_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
 pub async fn get_member_count(pool: &DbPool, guild_id: i64) -> Result<i64, DbError> {
     let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM members WHERE guild_id = $1")
         .bind(guild_id)
@@ -257,6 +366,24 @@ pub async fn get_member_count(pool: &DbPool, guild_id: i64) -> Result<i64, DbErr
     Ok(row.0)
 }
 
+/// `(guild_id, member_count)` for every guild `user_id` is a member of.
+pub async fn get_user_guild_member_counts(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<(i64, i64)>, DbError> {
+    let rows: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT m.guild_id, COUNT(*)
+         FROM members m
+         INNER JOIN members me ON me.guild_id = m.guild_id
+         WHERE me.user_id = $1
+         GROUP BY m.guild_id",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 pub async fn get_all_memberships(pool: &DbPool) -> Result<Vec<(i64, i64)>, DbError> {
     let rows: Vec<(i64, i64)> = sqlx::query_as("SELECT guild_id, user_id FROM members")
         .fetch_all(pool)
@@ -292,6 +419,41 @@ pub async fn share_any_guild(pool: &DbPool, user_a: i64, user_b: i64) -> Result<
     Ok(row.is_some())
 }
 
+/// The subset of `user_ids` sharing at least one guild with `user_id`.
+pub async fn filter_users_sharing_guild(
+    pool: &DbPool,
+    user_id: i64,
+    user_ids: &[i64],
+) -> Result<Vec<i64>, DbError> {
+    const MAX_USER_IDS: usize = 500;
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    if user_ids.len() > MAX_USER_IDS {
+        return Err(DbError::Sqlx(sqlx::Error::Protocol(
+            "too many user ids in shared guild lookup".to_string(),
+        )));
+    }
+
+    let placeholders: Vec<String> = (2..=user_ids.len() + 1)
+        .map(|i| format!("${}", i))
+        .collect();
+    let sql = format!(
+        "SELECT DISTINCT b.user_id
+         FROM members a
+         INNER JOIN members b ON a.guild_id = b.guild_id
+         WHERE a.user_id = $1
+           AND b.user_id IN ({})",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, (i64,)>(&sql).bind(user_id);
+    for id in user_ids {
+        query = query.bind(id);
+    }
+    let rows = query.fetch_all(pool).await?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
 pub async fn get_server_member_count(pool: &DbPool) -> Result<i64, DbError> {
     let row: (i64,) = sqlx::query_as("SELECT COUNT(DISTINCT user_id) FROM members")
         .fetch_one(pool)
@@ -346,6 +508,74 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[tokio::test]
+    async fn test_set_member_pending() {
+        let pool = test_pool().await;
+        let (user_id, guild_id) = setup_guild(&pool).await;
+        add_member(&pool, user_id, guild_id).await.unwrap();
+        assert!(
+            !get_member(&pool, user_id, guild_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .pending
+        );
+
+        let updated = set_member_pending(&pool, user_id, guild_id, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(updated.pending);
+        let members = get_guild_members(&pool, guild_id, 10, None).await.unwrap();
+        assert!(members[0].pending);
+        assert!(set_member_pending(&pool, 999, guild_id, false)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_add_invited_member_starts_pending_when_screened() {
+        let pool = test_pool().await;
+        let (user_id, guild_id) = setup_guild(&pool).await;
+        crate::users::create_user(&pool, 2, "user2", 1, "u2@example.com", "hash")
+            .await
+            .unwrap();
+        assert_eq!(
+            add_invited_member(&pool, user_id, guild_id).await.unwrap(),
+            Some(false)
+        );
+
+        crate::guild_member_screening::upsert_guild_member_screening(
+            &pool, guild_id, true, "Be nice",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            add_invited_member(&pool, 2, guild_id).await.unwrap(),
+            Some(true)
+        );
+        assert!(
+            get_member(&pool, 2, guild_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .pending
+        );
+        // Joining again leaves the existing row alone.
+        assert_eq!(
+            add_invited_member(&pool, user_id, guild_id).await.unwrap(),
+            None
+        );
+        assert!(
+            !get_member(&pool, user_id, guild_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .pending
+        );
+    }
+
     #[tokio::test]
     async fn test_get_member_not_found() {
         let pool = test_pool().await;
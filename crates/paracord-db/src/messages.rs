@@ -2,6 +2,9 @@ use crate::{bool_from_any_row, datetime_from_db_text, datetime_to_db_text, DbErr
 use chrono::{DateTime, Utc};
 use paracord_models::permissions::Permissions;
 use sqlx::Row;
+use std::collections::HashMap;
+
+const SEQ_ALLOCATION_ATTEMPTS: u32 = 8;
 
 #[derive(Debug, Clone)]
 pub struct MessageRow {
@@ -16,6 +19,9 @@ pub struct MessageRow {
     pub pinned: bool,
     pub reference_id: Option<i64>,
     pub e2ee_header: Option<String>,
+    /// Position in the channel, assigned at insert. `None` for ephemeral
+    /// messages.
+    pub seq: Option<i64>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -38,6 +44,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MessageRow {
             pinned: bool_from_any_row(row, "pinned")?,
             reference_id: row.try_get("reference_id")?,
             e2ee_header: row.try_get("e2ee_header")?,
+            seq: row.try_get("seq")?,
             created_at: datetime_from_db_text(&created_at_raw)?,
         })
     }
@@ -80,28 +87,44 @@ pub async fn create_message_with_meta(
     e2ee_header: Option<&str>,
 ) -> Result<MessageRow, DbError> {
     let normalized_nonce = nonce.map(str::trim).filter(|value| !value.is_empty());
-    let row = match sqlx::query_as::<_, MessageRow>(
-        "INSERT INTO messages (id, channel_id, author_id, content, nonce, message_type, flags, reference_id, e2ee_header)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-         RETURNING id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at",
-    )
-    .bind(id)
-    .bind(channel_id)
-    .bind(author_id)
-    .bind(content)
-    .bind(normalized_nonce)
-    .bind(message_type)
-    .bind(flags)
-    .bind(reference_id)
-    .bind(e2ee_header)
-    .fetch_one(pool)
-    .await
-    {
+    let mut attempts = 0;
+    let inserted = loop {
+        let result = crate::retry_on_busy(|| {
+            sqlx::query_as::<_, MessageRow>(
+                "INSERT INTO messages (id, channel_id, author_id, content, nonce, message_type, flags, reference_id, e2ee_header, seq)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9,
+                         (SELECT COALESCE(MAX(seq), 0) + 1 FROM messages WHERE channel_id = $2))
+                 RETURNING id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at",
+            )
+            .bind(id)
+            .bind(channel_id)
+            .bind(author_id)
+            .bind(content)
+            .bind(normalized_nonce)
+            .bind(message_type)
+            .bind(flags)
+            .bind(reference_id)
+            .bind(e2ee_header)
+            .fetch_one(pool)
+        })
+        .await;
+        attempts += 1;
+        match result {
+            // A concurrent insert took the same sequence number; take the next.
+            Err(err) if is_seq_conflict(&err) && attempts < SEQ_ALLOCATION_ATTEMPTS => continue,
+            other => break other,
+        }
+    };
+    let row = match inserted {
         Ok(row) => row,
         Err(err) if normalized_nonce.is_some() && is_nonce_dedup_unique_violation(&err) => {
-            let existing =
-                get_message_by_channel_author_nonce(pool, channel_id, author_id, normalized_nonce.unwrap())
-                    .await?;
+            let existing = get_message_by_channel_author_nonce(
+                pool,
+                channel_id,
+                author_id,
+                normalized_nonce.unwrap(),
+            )
+            .await?;
             if let Some(existing) = existing {
                 return Ok(existing);
             }
@@ -120,6 +143,123 @@ pub async fn create_message_with_meta(
     Ok(row)
 }
 
+/// Insert a message only `visible_to` may see. Ephemeral messages do not
+/// advance the channel's `last_message_id`, so they never mark it unread for
+/// anyone else.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_ephemeral_message(
+    pool: &DbPool,
+    id: i64,
+    channel_id: i64,
+    author_id: i64,
+    content: &str,
+    message_type: i16,
+    flags: i32,
+    visible_to: i64,
+) -> Result<MessageRow, DbError> {
+    let row = sqlx::query_as::<_, MessageRow>(
+        "INSERT INTO messages (id, channel_id, author_id, content, message_type, flags, visible_to)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at",
+    )
+    .bind(id)
+    .bind(channel_id)
+    .bind(author_id)
+    .bind(content)
+    .bind(message_type)
+    .bind(flags)
+    .bind(visible_to)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Store interactive components (buttons, selects) as JSON on a message.
+pub async fn set_message_components(
+    pool: &DbPool,
+    id: i64,
+    components: Option<&str>,
+) -> Result<(), DbError> {
+    sqlx::query("UPDATE messages SET components = $2 WHERE id = $1")
+        .bind(id)
+        .bind(components)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Replace a message's link previews with `embeds` (JSON strings), in order.
+/// Embeds from other sources are kept. Returns `false` without writing when
+/// the stored previews already match.
+pub async fn set_message_link_embeds(
+    pool: &DbPool,
+    message_id: i64,
+    embeds: &[String],
+) -> Result<bool, DbError> {
+    let mut tx = pool.begin().await?;
+    let existing: Vec<(String,)> = sqlx::query_as(
+        "SELECT embed_data FROM message_embeds
+         WHERE message_id = $1 AND source = 'link'
+         ORDER BY id",
+    )
+    .bind(message_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    if existing.iter().map(|(e,)| e).eq(embeds.iter()) {
+        return Ok(false);
+    }
+    sqlx::query("DELETE FROM message_embeds WHERE message_id = $1 AND source = 'link'")
+        .bind(message_id)
+        .execute(&mut *tx)
+        .await?;
+    for embed in embeds {
+        sqlx::query(
+            "INSERT INTO message_embeds (message_id, embed_data, source) VALUES ($1, $2, 'link')",
+        )
+        .bind(message_id)
+        .bind(embed)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Embeds for several messages as `(message_id, embed_data)` pairs, in
+/// insertion order.
+pub async fn get_embeds_for_message_ids(
+    pool: &DbPool,
+    message_ids: &[i64],
+) -> Result<Vec<(i64, String)>, DbError> {
+    if message_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (1..=message_ids.len()).map(|i| format!("${}", i)).collect();
+    let sql = format!(
+        "SELECT message_id, embed_data FROM message_embeds
+         WHERE message_id IN ({})
+         ORDER BY message_id, id",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, (i64, String)>(&sql);
+    for message_id in message_ids {
+        query = query.bind(message_id);
+    }
+    Ok(query.fetch_all(pool).await?)
+}
+
+/// True when an insert lost the race for a channel sequence number.
+fn is_seq_conflict(err: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(db_err) = err else {
+        return false;
+    };
+    let code_binding = db_err.code();
+    let code = code_binding.as_deref().unwrap_or_default();
+    let is_unique = code == "23505" || code == "2067" || code == "1555";
+    let message = db_err.message().to_ascii_lowercase();
+    is_unique && (message.contains("messages.seq") || message.contains("idx_messages_channel_seq"))
+}
+
 fn is_nonce_dedup_unique_violation(err: &sqlx::Error) -> bool {
     let sqlx::Error::Database(db_err) = err else {
         return false;
@@ -135,14 +275,15 @@ fn is_nonce_dedup_unique_violation(err: &sqlx::Error) -> bool {
     message.contains("idx_messages_nonce_dedup_unique")
 }
 
-async fn get_message_by_channel_author_nonce(
+/// The author's earlier message in the channel sent with `nonce`, if any.
+pub async fn get_message_by_channel_author_nonce(
     pool: &DbPool,
     channel_id: i64,
     author_id: i64,
     nonce: &str,
 ) -> Result<Option<MessageRow>, DbError> {
     let row = sqlx::query_as::<_, MessageRow>(
-        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
+        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
          FROM messages
          WHERE channel_id = $1
            AND author_id = $2
@@ -160,7 +301,7 @@ async fn get_message_by_channel_author_nonce(
 
 pub async fn get_message(pool: &DbPool, id: i64) -> Result<Option<MessageRow>, DbError> {
     let row = sqlx::query_as::<_, MessageRow>(
-        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
+        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
          FROM messages WHERE id = $1",
     )
     .bind(id)
@@ -169,43 +310,82 @@ pub async fn get_message(pool: &DbPool, id: i64) -> Result<Option<MessageRow>, D
     Ok(row)
 }
 
+/// A message in `channel_id`, unless it is ephemeral and meant for someone
+/// other than `viewer_id`.
+pub async fn get_channel_message_for_viewer(
+    pool: &DbPool,
+    channel_id: i64,
+    id: i64,
+    viewer_id: i64,
+) -> Result<Option<MessageRow>, DbError> {
+    let row = sqlx::query_as::<_, MessageRow>(
+        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+         FROM messages WHERE id = $1 AND channel_id = $2 AND (visible_to IS NULL OR visible_to = $3)",
+    )
+    .bind(id)
+    .bind(channel_id)
+    .bind(viewer_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
 pub async fn get_channel_messages(
     pool: &DbPool,
     channel_id: i64,
     before: Option<i64>,
     after: Option<i64>,
     limit: i64,
+) -> Result<Vec<MessageRow>, DbError> {
+    get_channel_messages_for_viewer(pool, channel_id, None, before, after, limit).await
+}
+
+/// Channel history as seen by `viewer_id`: shared messages plus the viewer's
+/// own ephemeral messages. With no viewer, ephemeral messages are omitted.
+pub async fn get_channel_messages_for_viewer(
+    pool: &DbPool,
+    channel_id: i64,
+    viewer_id: Option<i64>,
+    before: Option<i64>,
+    after: Option<i64>,
+    limit: i64,
 ) -> Result<Vec<MessageRow>, DbError> {
     let rows = match (before, after) {
         (Some(before_id), _) => {
             sqlx::query_as::<_, MessageRow>(
-                "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
-                 FROM messages WHERE channel_id = $1 AND id < $2 ORDER BY id DESC LIMIT $3",
+                "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+                 FROM messages WHERE channel_id = $1 AND id < $2 AND (visible_to IS NULL OR visible_to = $4)
+                 ORDER BY id DESC LIMIT $3",
             )
             .bind(channel_id)
             .bind(before_id)
             .bind(limit)
+            .bind(viewer_id)
             .fetch_all(pool)
             .await?
         }
         (None, Some(after_id)) => {
             sqlx::query_as::<_, MessageRow>(
-                "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
-                 FROM messages WHERE channel_id = $1 AND id > $2 ORDER BY id ASC LIMIT $3",
+                "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+                 FROM messages WHERE channel_id = $1 AND id > $2 AND (visible_to IS NULL OR visible_to = $4)
+                 ORDER BY id ASC LIMIT $3",
             )
             .bind(channel_id)
             .bind(after_id)
             .bind(limit)
+            .bind(viewer_id)
             .fetch_all(pool)
             .await?
         }
         (None, None) => {
             sqlx::query_as::<_, MessageRow>(
-                "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
-                 FROM messages WHERE channel_id = $1 ORDER BY id DESC LIMIT $2",
+                "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+                 FROM messages WHERE channel_id = $1 AND (visible_to IS NULL OR visible_to = $3)
+                 ORDER BY id DESC LIMIT $2",
             )
             .bind(channel_id)
             .bind(limit)
+            .bind(viewer_id)
             .fetch_all(pool)
             .await?
         }
@@ -213,15 +393,80 @@ pub async fn get_channel_messages(
     Ok(rows)
 }
 
-pub async fn update_message(pool: &DbPool, id: i64, content: &str) -> Result<MessageRow, DbError> {
-    let row = sqlx::query_as::<_, MessageRow>(
-        "UPDATE messages SET content = $2, edited_at = datetime('now')
-         WHERE id = $1
-         RETURNING id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at",
+/// Shared messages with a sequence number above `after_seq`, oldest first.
+pub async fn get_channel_messages_after_seq(
+    pool: &DbPool,
+    channel_id: i64,
+    after_seq: i64,
+    limit: i64,
+) -> Result<Vec<MessageRow>, DbError> {
+    let rows = sqlx::query_as::<_, MessageRow>(
+        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+         FROM messages WHERE channel_id = $1 AND seq > $2
+         ORDER BY seq ASC LIMIT $3",
     )
-    .bind(id)
-    .bind(content)
-    .fetch_one(pool)
+    .bind(channel_id)
+    .bind(after_seq)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Up to `limit` messages centred on `around_id` (which is included if it
+/// exists), newest first. Near either end of the channel the unused half of
+/// the window is filled from the other side.
+pub async fn get_channel_messages_around(
+    pool: &DbPool,
+    channel_id: i64,
+    viewer_id: Option<i64>,
+    around_id: i64,
+    limit: i64,
+) -> Result<Vec<MessageRow>, DbError> {
+    let limit = limit.max(1);
+    let rows = sqlx::query_as::<_, MessageRow>(
+        "SELECT * FROM (
+             SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+             FROM messages WHERE channel_id = $1 AND id <= $2 AND (visible_to IS NULL OR visible_to = $4)
+             ORDER BY id DESC LIMIT $3
+         ) AS older
+         UNION ALL
+         SELECT * FROM (
+             SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+             FROM messages WHERE channel_id = $1 AND id > $2 AND (visible_to IS NULL OR visible_to = $4)
+             ORDER BY id ASC LIMIT $3
+         ) AS newer
+         ORDER BY id DESC",
+    )
+    .bind(channel_id)
+    .bind(around_id)
+    .bind(limit)
+    .bind(viewer_id)
+    .fetch_all(pool)
+    .await?;
+
+    let limit = limit as usize;
+    let newer_available = rows.iter().filter(|row| row.id > around_id).count();
+    let older_available = rows.len() - newer_available;
+    let mut newer = newer_available.min(limit / 2);
+    let older = older_available.min(limit - newer);
+    newer = newer_available.min(limit - older);
+
+    let skip = newer_available - newer;
+    Ok(rows.into_iter().skip(skip).take(newer + older).collect())
+}
+
+pub async fn update_message(pool: &DbPool, id: i64, content: &str) -> Result<MessageRow, DbError> {
+    let row = crate::retry_on_busy(|| {
+        sqlx::query_as::<_, MessageRow>(
+            "UPDATE messages SET content = $2, edited_at = datetime('now')
+             WHERE id = $1
+             RETURNING id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at",
+        )
+        .bind(id)
+        .bind(content)
+        .fetch_one(pool)
+    })
     .await?;
     Ok(row)
 }
@@ -283,7 +528,7 @@ pub async fn update_message_authorized_with_meta(
          WHERE id = $1
            AND channel_id = $2
            AND (author_id = $3 OR EXISTS (SELECT 1 FROM actor_can_manage))
-         RETURNING id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at",
+         RETURNING id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at",
     )
     .bind(id)
     .bind(channel_id)
@@ -362,8 +607,8 @@ pub async fn get_pinned_messages(
     channel_id: i64,
 ) -> Result<Vec<MessageRow>, DbError> {
     let rows = sqlx::query_as::<_, MessageRow>(
-        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
-         FROM messages WHERE channel_id = $1 AND pinned = TRUE ORDER BY id ASC",
+        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
+         FROM messages WHERE channel_id = $1 AND pinned = TRUE AND visible_to IS NULL ORDER BY id ASC",
     )
     .bind(channel_id)
     .fetch_all(pool)
@@ -372,7 +617,7 @@ pub async fn get_pinned_messages(
 }
 
 pub async fn pin_message(pool: &DbPool, id: i64, channel_id: i64) -> Result<bool, DbError> {
-    let result = sqlx::query("UPDATE messages SET pinned = TRUE WHERE id = $1 AND channel_id = $2")
+    let result = sqlx::query("UPDATE messages SET pinned = TRUE WHERE id = $1 AND channel_id = $2 AND visible_to IS NULL")
         .bind(id)
         .bind(channel_id)
         .execute(pool)
@@ -380,6 +625,42 @@ pub async fn pin_message(pool: &DbPool, id: i64, channel_id: i64) -> Result<bool
     Ok(result.rows_affected() > 0)
 }
 
+/// Set `flag` on a message. Returns false if the message is missing or
+/// already carried the flag.
+pub async fn add_message_flag(
+    pool: &DbPool,
+    id: i64,
+    channel_id: i64,
+    flag: i32,
+) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "UPDATE messages SET flags = flags | $3 WHERE id = $1 AND channel_id = $2 AND (flags & $3) = 0",
+    )
+    .bind(id)
+    .bind(channel_id)
+    .bind(flag)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn remove_message_flag(
+    pool: &DbPool,
+    id: i64,
+    channel_id: i64,
+    flag: i32,
+) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "UPDATE messages SET flags = flags & ~$3 WHERE id = $1 AND channel_id = $2 AND (flags & $3) != 0",
+    )
+    .bind(id)
+    .bind(channel_id)
+    .bind(flag)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn unpin_message(pool: &DbPool, id: i64, channel_id: i64) -> Result<bool, DbError> {
     let result =
         sqlx::query("UPDATE messages SET pinned = FALSE WHERE id = $1 AND channel_id = $2")
@@ -427,34 +708,166 @@ pub async fn count_messages(pool: &DbPool) -> Result<i64, DbError> {
     Ok(row.0)
 }
 
+/// Parsed message search. Built from the user's query string by
+/// `paracord_core::message::parse_search_query`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageSearch {
+    /// Words and quoted phrases that must all appear in the content.
+    pub phrases: Vec<String>,
+    /// `from:<user_id>`.
+    pub author_id: Option<i64>,
+    /// `has:attachment`.
+    pub has_attachment: bool,
+}
+
+impl MessageSearch {
+    pub fn is_empty(&self) -> bool {
+        self.phrases.is_empty() && self.author_id.is_none() && !self.has_attachment
+    }
+}
+
+/// Search one channel. See [`search_channels_messages`].
 pub async fn search_messages(
     pool: &DbPool,
     channel_id: i64,
-    query: &str,
+    search: &MessageSearch,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<MessageRow>, DbError> {
+    search_channels_messages(pool, &[channel_id], search, limit, offset).await
+}
+
+/// Full-text search across `channel_ids`, best match first (newest first
+/// when there are no phrases). Uses the `messages_fts` FTS5 table on SQLite
+/// and the `search_tsv` column on PostgreSQL. E2EE DM messages are not
+/// indexed and ephemeral messages are skipped. More than 500 channels are
+/// searched 500 at a time and the results merged by rank.
+pub async fn search_channels_messages(
+    pool: &DbPool,
+    channel_ids: &[i64],
+    search: &MessageSearch,
     limit: i64,
+    offset: i64,
 ) -> Result<Vec<MessageRow>, DbError> {
+    const CHUNK: usize = 500;
+    if channel_ids.is_empty() || search.is_empty() {
+        return Ok(Vec::new());
+    }
+    if channel_ids.len() <= CHUNK {
+        let ranked = search_ranked_messages(pool, channel_ids, search, limit, offset).await?;
+        return Ok(ranked.into_iter().map(|(row, _)| row).collect());
+    }
+
+    // The requested page lies within the top `offset + limit` of each chunk.
+    let window = offset.max(0).saturating_add(limit.max(0));
+    let mut ranked = Vec::new();
+    for chunk in channel_ids.chunks(CHUNK) {
+        ranked.extend(search_ranked_messages(pool, chunk, search, window, 0).await?);
+    }
+    ranked.sort_by(|(a, a_rank), (b, b_rank)| b_rank.total_cmp(a_rank).then(b.id.cmp(&a.id)));
+    Ok(ranked
+        .into_iter()
+        .skip(usize::try_from(offset).unwrap_or(0))
+        .take(usize::try_from(limit).unwrap_or(0))
+        .map(|(row, _)| row)
+        .collect())
+}
+
+/// One query of [`search_channels_messages`], returning each row with a rank
+/// that is comparable across queries (higher is better).
+async fn search_ranked_messages(
+    pool: &DbPool,
+    channel_ids: &[i64],
+    search: &MessageSearch,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<(MessageRow, f64)>, DbError> {
     const MESSAGE_FLAG_DM_E2EE: i32 = 1 << 0;
-    let escaped = query
-        .replace('\\', "\\\\")
-        .replace('%', "\\%")
-        .replace('_', "\\_");
-    let pattern = format!("%{}%", escaped);
-    let rows = sqlx::query_as::<_, MessageRow>(
-        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
-         FROM messages
-         WHERE channel_id = $1
-           AND content LIKE $2 ESCAPE '\\'
-           AND (flags & $4) = 0
-         ORDER BY id DESC
-         LIMIT $3",
-    )
-    .bind(channel_id)
-    .bind(pattern)
-    .bind(limit)
-    .bind(MESSAGE_FLAG_DM_E2EE)
-    .fetch_all(pool)
-    .await?;
-    Ok(rows)
+    let postgres = crate::active_database_engine() == crate::DatabaseEngine::Postgres;
+
+    let mut next = 1;
+    let mut placeholder = || {
+        let p = format!("${next}");
+        next += 1;
+        p
+    };
+    let channel_placeholders: Vec<String> = channel_ids.iter().map(|_| placeholder()).collect();
+    let flag_placeholder = placeholder();
+
+    let mut from = "messages m".to_string();
+    let mut conditions = vec![
+        format!("m.channel_id IN ({})", channel_placeholders.join(", ")),
+        format!("(m.flags & {flag_placeholder}) = 0"),
+        "m.visible_to IS NULL".to_string(),
+    ];
+    let mut rank = "0".to_string();
+    let mut text_binds = Vec::new();
+
+    if !search.phrases.is_empty() {
+        if postgres {
+            let terms: Vec<String> = search
+                .phrases
+                .iter()
+                .map(|phrase| {
+                    text_binds.push(phrase.clone());
+                    format!("phraseto_tsquery('simple', {})", placeholder())
+                })
+                .collect();
+            let tsquery = terms.join(" && ");
+            conditions.push(format!("m.search_tsv @@ ({tsquery})"));
+            rank = format!("ts_rank(m.search_tsv, {tsquery})");
+        } else {
+            let expr: Vec<String> = search
+                .phrases
+                .iter()
+                .map(|phrase| format!("\"{}\"", phrase.replace('"', "\"\"")))
+                .collect();
+            text_binds.push(expr.join(" "));
+            from.push_str(" JOIN messages_fts ON messages_fts.rowid = m.id");
+            conditions.push(format!("messages_fts MATCH {}", placeholder()));
+            // bm25 is lower for better matches.
+            rank = "-bm25(messages_fts)".to_string();
+        }
+    }
+    let author_placeholder = search.author_id.map(|_| placeholder());
+    if let Some(p) = &author_placeholder {
+        conditions.push(format!("m.author_id = {p}"));
+    }
+    if search.has_attachment {
+        conditions
+            .push("EXISTS (SELECT 1 FROM attachments a WHERE a.message_id = m.id)".to_string());
+    }
+    let limit_placeholder = placeholder();
+    let offset_placeholder = placeholder();
+
+    let sql = format!(
+        "SELECT m.id, m.channel_id, m.author_id, m.content, m.nonce, m.message_type, m.flags, m.edited_at, CASE WHEN m.pinned THEN 1 ELSE 0 END AS pinned, m.reference_id, m.e2ee_header, m.seq, m.created_at,
+                CAST({rank} AS DOUBLE PRECISION) AS search_rank
+         FROM {from}
+         WHERE {}
+         ORDER BY search_rank DESC, m.id DESC
+         LIMIT {limit_placeholder} OFFSET {offset_placeholder}",
+        conditions.join("\n           AND "),
+    );
+
+    let mut query = sqlx::query(&sql);
+    for channel_id in channel_ids {
+        query = query.bind(channel_id);
+    }
+    query = query.bind(MESSAGE_FLAG_DM_E2EE);
+    for text in text_binds {
+        query = query.bind(text);
+    }
+    if let Some(author_id) = search.author_id {
+        query = query.bind(author_id);
+    }
+    let rows = query.bind(limit).bind(offset).fetch_all(pool).await?;
+    rows.iter()
+        .map(|row| {
+            let message = <MessageRow as sqlx::FromRow<_>>::from_row(row)?;
+            Ok((message, row.try_get("search_rank")?))
+        })
+        .collect()
 }
 
 pub async fn get_message_ids_older_than(
@@ -482,7 +895,7 @@ pub async fn list_messages_by_author(
     limit: i64,
 ) -> Result<Vec<MessageRow>, DbError> {
     let rows = sqlx::query_as::<_, MessageRow>(
-        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, created_at
+        "SELECT id, channel_id, author_id, content, nonce, message_type, flags, edited_at, CASE WHEN pinned THEN 1 ELSE 0 END AS pinned, reference_id, e2ee_header, seq, created_at
          FROM messages
          WHERE author_id = $1
          ORDER BY id DESC
@@ -495,6 +908,32 @@ pub async fn list_messages_by_author(
     Ok(rows)
 }
 
+/// The newest shared message id of each channel in `channel_ids`, in one
+/// query per 500 channels. Channels without messages are absent. Ephemeral
+/// messages are ignored, as they are for `channels.last_message_id`.
+pub async fn latest_message_ids_for_channels(
+    pool: &DbPool,
+    channel_ids: &[i64],
+) -> Result<HashMap<i64, i64>, DbError> {
+    const CHUNK: usize = 500;
+    let mut latest = HashMap::with_capacity(channel_ids.len());
+    for chunk in channel_ids.chunks(CHUNK) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("${}", i)).collect();
+        let sql = format!(
+            "SELECT channel_id, MAX(id) FROM messages
+             WHERE channel_id IN ({}) AND visible_to IS NULL
+             GROUP BY channel_id",
+            placeholders.join(", ")
+        );
+        let mut query = sqlx::query_as::<_, (i64, i64)>(&sql);
+        for channel_id in chunk {
+            query = query.bind(channel_id);
+        }
+        latest.extend(query.fetch_all(pool).await?);
+    }
+    Ok(latest)
+}
+
 pub async fn delete_messages_by_ids(pool: &DbPool, ids: &[i64]) -> Result<u64, DbError> {
     if ids.is_empty() {
         return Ok(0);
@@ -574,6 +1013,51 @@ mod tests {
         assert_eq!(reply.reference_id, Some(1000));
     }
 
+    #[tokio::test]
+    async fn link_embeds_are_replaced_in_order() {
+        let pool = test_pool().await;
+        let (user_id, _, channel_id) = setup_channel(&pool).await;
+        create_message(&pool, 1100, channel_id, user_id, "links", 0, None)
+            .await
+            .unwrap();
+        create_message(&pool, 1101, channel_id, user_id, "none", 0, None)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO message_embeds (message_id, embed_data) VALUES ($1, $2)")
+            .bind(1100_i64)
+            .bind("{\"rich\":1}")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let links = ["{\"a\":1}".to_string(), "{\"b\":2}".to_string()];
+        assert!(set_message_link_embeds(&pool, 1100, &links).await.unwrap());
+        assert!(!set_message_link_embeds(&pool, 1100, &links).await.unwrap());
+        let embeds = get_embeds_for_message_ids(&pool, &[1100, 1101])
+            .await
+            .unwrap();
+        assert_eq!(
+            embeds,
+            vec![
+                (1100, "{\"rich\":1}".to_string()),
+                (1100, "{\"a\":1}".to_string()),
+                (1100, "{\"b\":2}".to_string())
+            ]
+        );
+
+        assert!(set_message_link_embeds(&pool, 1100, &["{\"c\":3}".into()])
+            .await
+            .unwrap());
+        let embeds = get_embeds_for_message_ids(&pool, &[1100]).await.unwrap();
+        assert_eq!(
+            embeds,
+            vec![
+                (1100, "{\"rich\":1}".to_string()),
+                (1100, "{\"c\":3}".to_string())
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_get_message() {
         let pool = test_pool().await;
@@ -665,6 +1149,40 @@ mod tests {
         assert!(messages.iter().all(|m| m.id > 5002));
     }
 
+    #[tokio::test]
+    async fn get_channel_messages_around_centres_on_target() {
+        let pool = test_pool().await;
+        let (user_id, _, channel_id) = setup_channel(&pool).await;
+        for i in 0..20 {
+            create_message(&pool, 7000 + i, channel_id, user_id, "msg", 0, None)
+                .await
+                .unwrap();
+        }
+        let ids = |rows: Vec<MessageRow>| rows.into_iter().map(|m| m.id).collect::<Vec<_>>();
+
+        let middle = get_channel_messages_around(&pool, channel_id, None, 7010, 7)
+            .await
+            .unwrap();
+        assert_eq!(ids(middle), (7007..=7013).rev().collect::<Vec<_>>());
+
+        // Near the start, the window extends further into newer messages.
+        let start = get_channel_messages_around(&pool, channel_id, None, 7001, 7)
+            .await
+            .unwrap();
+        assert_eq!(ids(start), (7000..=7006).rev().collect::<Vec<_>>());
+
+        // Near the end, it extends further back instead.
+        let end = get_channel_messages_around(&pool, channel_id, None, 7019, 7)
+            .await
+            .unwrap();
+        assert_eq!(ids(end), (7013..=7019).rev().collect::<Vec<_>>());
+
+        let tiny = get_channel_messages_around(&pool, channel_id, None, 7010, 1)
+            .await
+            .unwrap();
+        assert_eq!(ids(tiny), vec![7010]);
+    }
+
     #[tokio::test]
     async fn test_get_channel_messages_with_limit() {
         let pool = test_pool().await;
@@ -712,6 +1230,13 @@ mod tests {
         assert!(msg.is_none());
     }
 
+    fn text_search(phrases: &[&str]) -> MessageSearch {
+        MessageSearch {
+            phrases: phrases.iter().map(|p| p.to_string()).collect(),
+            ..MessageSearch::default()
+        }
+    }
+
     #[tokio::test]
     async fn test_search_messages() {
         let pool = test_pool().await;
@@ -725,10 +1250,65 @@ mod tests {
         create_message(&pool, 9002, channel_id, user_id, "hello again", 0, None)
             .await
             .unwrap();
-        let results = search_messages(&pool, channel_id, "hello", 50)
+        let results = search_messages(&pool, channel_id, &text_search(&["hello"]), 50, 0)
             .await
             .unwrap();
         assert_eq!(results.len(), 2);
+
+        let results = search_messages(&pool, channel_id, &text_search(&["hello world"]), 50, 0)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, 9000);
+
+        let results = search_messages(&pool, channel_id, &text_search(&["world"]), 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_merges_more_than_500_channels() {
+        let pool = test_pool().await;
+        let (user_id, guild_id, first_channel) = setup_channel(&pool).await;
+        let mut channel_ids = vec![first_channel];
+        for i in 0..600 {
+            let id = 10_000 + i;
+            crate::channels::create_channel(&pool, id, guild_id, "bulk", 0, 0, None, None)
+                .await
+                .unwrap();
+            channel_ids.push(id);
+        }
+        let last_channel = *channel_ids.last().unwrap();
+        create_message(&pool, 9300, first_channel, user_id, "needle one", 0, None)
+            .await
+            .unwrap();
+        create_message(&pool, 9301, last_channel, user_id, "needle two", 0, None)
+            .await
+            .unwrap();
+        create_message(&pool, 9302, last_channel, user_id, "haystack", 0, None)
+            .await
+            .unwrap();
+
+        let search = text_search(&["needle"]);
+        let results = search_channels_messages(&pool, &channel_ids, &search, 50, 0)
+            .await
+            .unwrap();
+        let mut ids: Vec<i64> = results.iter().map(|m| m.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![9300, 9301]);
+
+        let newest_first = MessageSearch {
+            author_id: Some(user_id),
+            ..MessageSearch::default()
+        };
+        let page = search_channels_messages(&pool, &channel_ids, &newest_first, 2, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            page.iter().map(|m| m.id).collect::<Vec<_>>(),
+            vec![9301, 9300]
+        );
     }
 
     #[tokio::test]
@@ -738,10 +1318,71 @@ mod tests {
         create_message(&pool, 9100, channel_id, user_id, "nothing here", 0, None)
             .await
             .unwrap();
-        let results = search_messages(&pool, channel_id, "xyz", 50).await.unwrap();
+        let results = search_messages(&pool, channel_id, &text_search(&["xyz"]), 50, 0)
+            .await
+            .unwrap();
         assert!(results.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_search_index_follows_edits_deletes_and_skips_e2ee() {
+        let pool = test_pool().await;
+        let (user_id, _, channel_id) = setup_channel(&pool).await;
+        create_message(
+            &pool,
+            9200,
+            channel_id,
+            user_id,
+            "original wording",
+            0,
+            None,
+        )
+        .await
+        .unwrap();
+        create_message_with_meta(
+            &pool,
+            9201,
+            channel_id,
+            user_id,
+            "ciphertext wording",
+            0,
+            None,
+            1,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let results = search_messages(&pool, channel_id, &text_search(&["wording"]), 50, 0)
+            .await
+            .unwrap();
+        assert_eq!(results.iter().map(|m| m.id).collect::<Vec<_>>(), vec![9200]);
+
+        update_message(&pool, 9200, "revised text").await.unwrap();
+        assert!(
+            search_messages(&pool, channel_id, &text_search(&["original"]), 50, 0)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            search_messages(&pool, channel_id, &text_search(&["revised"]), 50, 0)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        delete_message(&pool, 9200).await.unwrap();
+        assert!(
+            search_messages(&pool, channel_id, &text_search(&["revised"]), 50, 0)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn test_pin_and_unpin_message() {
         let pool = test_pool().await;
@@ -886,6 +1527,54 @@ mod tests {
         assert_eq!(msgs.len(), 2);
     }
 
+    #[tokio::test]
+    async fn latest_message_ids_match_per_channel_max() {
+        let pool = test_pool().await;
+        let (user_id, guild_id, channel_id) = setup_channel(&pool).await;
+        let other_channel = 201;
+        let empty_channel = 202;
+        for id in [other_channel, empty_channel] {
+            crate::channels::create_channel(&pool, id, guild_id, "other", 0, 1, None, None)
+                .await
+                .unwrap();
+        }
+        for (id, channel) in [
+            (16000, channel_id),
+            (16002, other_channel),
+            (16001, channel_id),
+            (16003, other_channel),
+        ] {
+            create_message(&pool, id, channel, user_id, "msg", 0, None)
+                .await
+                .unwrap();
+        }
+        create_ephemeral_message(
+            &pool, 16004, channel_id, user_id, "only you", 0, 64, user_id,
+        )
+        .await
+        .unwrap();
+
+        let channels = [channel_id, other_channel, empty_channel];
+        let latest = latest_message_ids_for_channels(&pool, &channels)
+            .await
+            .unwrap();
+        for channel in channels {
+            let max: Option<i64> = sqlx::query_scalar(
+                "SELECT MAX(id) FROM messages WHERE channel_id = $1 AND visible_to IS NULL",
+            )
+            .bind(channel)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            assert_eq!(latest.get(&channel).copied(), max);
+        }
+        assert_eq!(latest.get(&channel_id), Some(&16001));
+        assert!(latest_message_ids_for_channels(&pool, &[])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_updates_last_message_id_on_channel() {
         let pool = test_pool().await;
@@ -899,4 +1588,74 @@ mod tests {
             .unwrap();
         assert_eq!(ch.last_message_id, Some(15000));
     }
+
+    #[tokio::test]
+    async fn sequence_is_per_channel_and_skips_ephemeral_messages() {
+        let pool = test_pool().await;
+        let (user_id, guild_id, channel_id) = setup_channel(&pool).await;
+        let other_channel = 201;
+        crate::channels::create_channel(&pool, other_channel, guild_id, "other", 0, 1, None, None)
+            .await
+            .unwrap();
+
+        // Ids out of order: the sequence follows insert order, not the id.
+        let first = create_message(&pool, 16005, channel_id, user_id, "a", 0, None)
+            .await
+            .unwrap();
+        let ephemeral =
+            create_ephemeral_message(&pool, 16006, channel_id, user_id, "only you", 0, 0, user_id)
+                .await
+                .unwrap();
+        let second = create_message(&pool, 16001, channel_id, user_id, "b", 0, None)
+            .await
+            .unwrap();
+        let elsewhere = create_message(&pool, 16002, other_channel, user_id, "c", 0, None)
+            .await
+            .unwrap();
+        assert_eq!(first.seq, Some(1));
+        assert_eq!(ephemeral.seq, None);
+        assert_eq!(second.seq, Some(2));
+        assert_eq!(elsewhere.seq, Some(1));
+
+        let after = get_channel_messages_after_seq(&pool, channel_id, 1, 50)
+            .await
+            .unwrap();
+        assert_eq!(after.iter().map(|m| m.id).collect::<Vec<_>>(), vec![16001]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_inserts_get_strictly_increasing_sequence() {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-db-message-seq-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = crate::create_pool(&db_url, 4).await.expect("pool");
+        crate::run_migrations(&pool).await.expect("migrations");
+        let (user_id, _, channel_id) = setup_channel(&pool).await;
+
+        let mut handles = Vec::new();
+        for i in 0..32_i64 {
+            let pool = pool.clone();
+            handles.push(tokio::spawn(async move {
+                create_message(&pool, 17000 + i, channel_id, user_id, "burst", 0, None)
+                    .await
+                    .unwrap()
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let rows = get_channel_messages_after_seq(&pool, channel_id, 0, 100)
+            .await
+            .unwrap();
+        let seqs: Vec<i64> = rows.iter().map(|m| m.seq.unwrap()).collect();
+        assert_eq!(seqs, (1..=32).collect::<Vec<_>>());
+        let _ = std::fs::remove_file(&db_path);
+    }
 }
@@ -0,0 +1,348 @@
+use crate::{datetime_from_db_text, datetime_to_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct PendingUploadRow {
+    pub id: i64,
+    pub uploader_id: i64,
+    pub channel_id: i64,
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub total_size: i64,
+    pub upload_offset: i64,
+    pub attachment_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for PendingUploadRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let created_raw: String = row.try_get("created_at")?;
+        let expires_raw: String = row.try_get("expires_at")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            uploader_id: row.try_get("uploader_id")?,
+            channel_id: row.try_get("channel_id")?,
+            filename: row.try_get("filename")?,
+            content_type: row.try_get("content_type")?,
+            total_size: row.try_get("total_size")?,
+            upload_offset: row.try_get("upload_offset")?,
+            attachment_id: row.try_get("attachment_id")?,
+            created_at: datetime_from_db_text(&created_raw)?,
+            expires_at: datetime_from_db_text(&expires_raw)?,
+        })
+    }
+}
+
+const PENDING_UPLOAD_COLUMNS: &str = "id, uploader_id, channel_id, filename, content_type, \
+     total_size, upload_offset, attachment_id, created_at, expires_at";
+
+/// Start a resumable upload unless `uploader_id` already holds `max_pending`
+/// pending uploads (0 disables the cap): unlinked, unexpired attachments plus
+/// unfinished resumable uploads. Counting and inserting in one statement keeps
+/// concurrent requests from all passing the check. Returns `None` at the cap.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_pending_upload(
+    pool: &DbPool,
+    id: i64,
+    uploader_id: i64,
+    channel_id: i64,
+    filename: &str,
+    content_type: Option<&str>,
+    total_size: i64,
+    expires_at: DateTime<Utc>,
+    max_pending: i64,
+    now: DateTime<Utc>,
+) -> Result<Option<PendingUploadRow>, DbError> {
+    let sql = format!(
+        "INSERT INTO pending_uploads
+            (id, uploader_id, channel_id, filename, content_type, total_size, expires_at)
+         SELECT $1, $2, $3, $4, $5, $6, $7
+         WHERE $8 <= 0
+            OR (SELECT COUNT(*)
+                FROM attachments
+                WHERE uploader_id = $2
+                  AND message_id IS NULL
+                  AND (upload_expires_at IS NULL OR upload_expires_at > $9))
+             + (SELECT COUNT(*)
+                FROM pending_uploads
+                WHERE uploader_id = $2 AND attachment_id IS NULL) < $8
+         RETURNING {PENDING_UPLOAD_COLUMNS}"
+    );
+    let row = sqlx::query_as::<_, PendingUploadRow>(&sql)
+        .bind(id)
+        .bind(uploader_id)
+        .bind(channel_id)
+        .bind(filename)
+        .bind(content_type)
+        .bind(total_size)
+        .bind(datetime_to_db_text(expires_at))
+        .bind(max_pending)
+        .bind(datetime_to_db_text(now))
+        .fetch_optional(pool)
+        .await?;
+    Ok(row)
+}
+
+pub async fn get_pending_upload(
+    pool: &DbPool,
+    id: i64,
+) -> Result<Option<PendingUploadRow>, DbError> {
+    let sql = format!("SELECT {PENDING_UPLOAD_COLUMNS} FROM pending_uploads WHERE id = $1");
+    let row = sqlx::query_as::<_, PendingUploadRow>(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row)
+}
+
+/// Claim upload `id` for one append until `locked_until`, as long as it is
+/// still at `expected_offset`, unfinished and not claimed by someone else.
+/// Returns `false` when another append holds it or the offset has moved.
+pub async fn claim_pending_upload(
+    pool: &DbPool,
+    id: i64,
+    expected_offset: i64,
+    lock_id: i64,
+    now: DateTime<Utc>,
+    locked_until: DateTime<Utc>,
+) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "UPDATE pending_uploads SET lock_id = $3, locked_until = $5
+         WHERE id = $1
+           AND upload_offset = $2
+           AND attachment_id IS NULL
+           AND (locked_until IS NULL OR locked_until <= $4)",
+    )
+    .bind(id)
+    .bind(expected_offset)
+    .bind(lock_id)
+    .bind(datetime_to_db_text(now))
+    .bind(datetime_to_db_text(locked_until))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Drop the claim `lock_id` holds on upload `id` without moving the offset.
+pub async fn release_pending_upload(pool: &DbPool, id: i64, lock_id: i64) -> Result<(), DbError> {
+    sqlx::query(
+        "UPDATE pending_uploads SET lock_id = NULL, locked_until = NULL
+         WHERE id = $1 AND lock_id = $2",
+    )
+    .bind(id)
+    .bind(lock_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record the new offset reached by the append holding `lock_id`, release
+/// the claim and push the idle expiry out to `expires_at`. Returns `false`
+/// when the claim was lost to another append.
+pub async fn advance_pending_upload_offset(
+    pool: &DbPool,
+    id: i64,
+    lock_id: i64,
+    new_offset: i64,
+    expires_at: DateTime<Utc>,
+) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "UPDATE pending_uploads
+         SET upload_offset = $3, expires_at = $4, lock_id = NULL, locked_until = NULL
+         WHERE id = $1 AND lock_id = $2",
+    )
+    .bind(id)
+    .bind(lock_id)
+    .bind(new_offset)
+    .bind(datetime_to_db_text(expires_at))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn set_pending_upload_attachment(
+    pool: &DbPool,
+    id: i64,
+    attachment_id: i64,
+) -> Result<(), DbError> {
+    sqlx::query("UPDATE pending_uploads SET attachment_id = $2 WHERE id = $1")
+        .bind(id)
+        .bind(attachment_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn count_incomplete_uploads_for_uploader(
+    pool: &DbPool,
+    uploader_id: i64,
+) -> Result<i64, DbError> {
+    let row: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM pending_uploads
+         WHERE uploader_id = $1 AND attachment_id IS NULL",
+    )
+    .bind(uploader_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.0)
+}
+
+pub async fn get_expired_pending_uploads(
+    pool: &DbPool,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<PendingUploadRow>, DbError> {
+    let sql = format!(
+        "SELECT {PENDING_UPLOAD_COLUMNS} FROM pending_uploads
+         WHERE expires_at <= $1
+         ORDER BY expires_at ASC
+         LIMIT $2"
+    );
+    let rows = sqlx::query_as::<_, PendingUploadRow>(&sql)
+        .bind(datetime_to_db_text(now))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows)
+}
+
+pub async fn delete_pending_upload(pool: &DbPool, id: i64) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM pending_uploads WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_db() -> DbPool {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-db-uploads-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = crate::create_pool(&db_url, 1).await.expect("pool");
+        crate::run_migrations(&pool).await.expect("migrations");
+        pool
+    }
+
+    #[tokio::test]
+    async fn appends_claim_the_upload_before_advancing_the_offset() {
+        let db = setup_db().await;
+        let user = crate::users::create_user(&db, 9301, "uploader", 1, "u@example.com", "hash")
+            .await
+            .expect("create user");
+        crate::guilds::create_guild(&db, 9305, "Uploads", user.id, None)
+            .await
+            .expect("create guild");
+        let channel = crate::channels::create_channel(&db, 9302, 9305, "general", 0, 0, None, None)
+            .await
+            .expect("create channel");
+
+        let expires = Utc::now() + chrono::Duration::hours(1);
+        let upload = create_pending_upload(
+            &db,
+            9303,
+            user.id,
+            channel.id,
+            "big.bin",
+            Some("application/octet-stream"),
+            100,
+            expires,
+            1,
+            Utc::now(),
+        )
+        .await
+        .unwrap()
+        .expect("under the cap");
+        assert_eq!(upload.upload_offset, 0);
+        assert!(create_pending_upload(
+            &db,
+            9306,
+            user.id,
+            channel.id,
+            "second.bin",
+            None,
+            100,
+            expires,
+            1,
+            Utc::now(),
+        )
+        .await
+        .unwrap()
+        .is_none());
+        assert_eq!(
+            count_incomplete_uploads_for_uploader(&db, user.id)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let now = Utc::now();
+        let lease = now + chrono::Duration::minutes(5);
+        assert!(claim_pending_upload(&db, upload.id, 0, 1, now, lease)
+            .await
+            .unwrap());
+        // A second append waits for the claim to lapse or be released.
+        assert!(!claim_pending_upload(&db, upload.id, 0, 2, now, lease)
+            .await
+            .unwrap());
+        assert!(claim_pending_upload(
+            &db,
+            upload.id,
+            0,
+            2,
+            lease,
+            lease + chrono::Duration::minutes(5)
+        )
+        .await
+        .unwrap());
+        // The lapsed holder can no longer record its offset.
+        let later = expires + chrono::Duration::hours(1);
+        assert!(!advance_pending_upload_offset(&db, upload.id, 1, 40, later)
+            .await
+            .unwrap());
+        assert!(advance_pending_upload_offset(&db, upload.id, 2, 40, later)
+            .await
+            .unwrap());
+        let fetched = get_pending_upload(&db, upload.id).await.unwrap().unwrap();
+        assert_eq!(fetched.upload_offset, 40);
+        assert_eq!(fetched.expires_at.timestamp(), later.timestamp());
+        assert!(!claim_pending_upload(&db, upload.id, 0, 3, now, lease)
+            .await
+            .unwrap());
+        assert!(claim_pending_upload(&db, upload.id, 40, 3, now, lease)
+            .await
+            .unwrap());
+        release_pending_upload(&db, upload.id, 3).await.unwrap();
+        assert!(claim_pending_upload(&db, upload.id, 40, 4, now, lease)
+            .await
+            .unwrap());
+
+        set_pending_upload_attachment(&db, upload.id, 9304)
+            .await
+            .unwrap();
+        assert_eq!(
+            count_incomplete_uploads_for_uploader(&db, user.id)
+                .await
+                .unwrap(),
+            0
+        );
+
+        let expired = get_expired_pending_uploads(&db, later + chrono::Duration::seconds(1), 10)
+            .await
+            .unwrap();
+        assert_eq!(expired.len(), 1);
+        delete_pending_upload(&db, upload.id).await.unwrap();
+        assert!(get_pending_upload(&db, upload.id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{datetime_from_db_text, datetime_to_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct PersonalAccessTokenRow {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    /// Comma-separated scope names.
+    pub scopes: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PersonalAccessTokenRow {
+    pub fn scope_list(&self) -> Vec<&str> {
+        self.scopes
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for PersonalAccessTokenRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let expires_at_raw: Option<String> = row.try_get("expires_at")?;
+        let last_used_at_raw: Option<String> = row.try_get("last_used_at")?;
+        let created_at_raw: String = row.try_get("created_at")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            name: row.try_get("name")?,
+            scopes: row.try_get("scopes")?,
+            expires_at: expires_at_raw
+                .as_deref()
+                .map(datetime_from_db_text)
+                .transpose()?,
+            last_used_at: last_used_at_raw
+                .as_deref()
+                .map(datetime_from_db_text)
+                .transpose()?,
+            created_at: datetime_from_db_text(&created_at_raw)?,
+        })
+    }
+}
+
+pub async fn create_token(
+    pool: &DbPool,
+    id: i64,
+    user_id: i64,
+    name: &str,
+    token_hash: &str,
+    scopes: &str,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<PersonalAccessTokenRow, DbError> {
+    let row = sqlx::query_as::<_, PersonalAccessTokenRow>(
+        "INSERT INTO personal_access_tokens (id, user_id, name, token_hash, scopes, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, user_id, name, scopes, expires_at, last_used_at, created_at",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(name)
+    .bind(token_hash)
+    .bind(scopes)
+    .bind(expires_at.map(datetime_to_db_text))
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// The token with `token_hash`, if it exists and has not expired.
+pub async fn get_active_token_by_hash(
+    pool: &DbPool,
+    token_hash: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<PersonalAccessTokenRow>, DbError> {
+    let row = sqlx::query_as::<_, PersonalAccessTokenRow>(
+        "SELECT id, user_id, name, scopes, expires_at, last_used_at, created_at
+         FROM personal_access_tokens
+         WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > $2)",
+    )
+    .bind(token_hash)
+    .bind(datetime_to_db_text(now))
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_user_tokens(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<PersonalAccessTokenRow>, DbError> {
+    let rows = sqlx::query_as::<_, PersonalAccessTokenRow>(
+        "SELECT id, user_id, name, scopes, expires_at, last_used_at, created_at
+         FROM personal_access_tokens WHERE user_id = $1 ORDER BY id",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn count_user_tokens(pool: &DbPool, user_id: i64) -> Result<i64, DbError> {
+    let row: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM personal_access_tokens WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+    Ok(row.0)
+}
+
+/// Record use, at most once per `min_interval` so busy scripts do not turn
+/// every request into a write.
+pub async fn touch_last_used(
+    pool: &DbPool,
+    id: i64,
+    now: DateTime<Utc>,
+    min_interval: chrono::Duration,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "UPDATE personal_access_tokens SET last_used_at = $2
+         WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < $3)",
+    )
+    .bind(id)
+    .bind(datetime_to_db_text(now))
+    .bind(datetime_to_db_text(now - min_interval))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Delete one of `user_id`'s tokens. Returns false if there was none.
+pub async fn delete_token(pool: &DbPool, id: i64, user_id: i64) -> Result<bool, DbError> {
+    let result = sqlx::query("DELETE FROM personal_access_tokens WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_user_tokens(pool: &DbPool, user_id: i64) -> Result<u64, DbError> {
+    let result = sqlx::query("DELETE FROM personal_access_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,328 @@
+use crate::{datetime_from_db_text, datetime_to_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct PushSubscriptionRow {
+    pub endpoint: String,
+    pub user_id: i64,
+    /// Browser's P-256 public key, base64url.
+    pub p256dh: String,
+    /// Browser's auth secret, base64url.
+    pub auth: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for PushSubscriptionRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let created_at_raw: String = row.try_get("created_at")?;
+        Ok(Self {
+            endpoint: row.try_get("endpoint")?,
+            user_id: row.try_get("user_id")?,
+            p256dh: row.try_get("p256dh")?,
+            auth: row.try_get("auth")?,
+            created_at: datetime_from_db_text(&created_at_raw)?,
+        })
+    }
+}
+
+/// Register `endpoint` for `user_id` under the session `session_id`,
+/// replacing its keys (and owner) if it was already registered.
+pub async fn upsert_subscription(
+    pool: &DbPool,
+    user_id: i64,
+    session_id: &str,
+    endpoint: &str,
+    p256dh: &str,
+    auth: &str,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "INSERT INTO push_subscriptions (endpoint, user_id, p256dh, auth, session_id)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (endpoint) DO UPDATE
+         SET user_id = excluded.user_id, p256dh = excluded.p256dh, auth = excluded.auth,
+             session_id = excluded.session_id",
+    )
+    .bind(endpoint)
+    .bind(user_id)
+    .bind(p256dh)
+    .bind(auth)
+    .bind(session_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_user_subscriptions(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<PushSubscriptionRow>, DbError> {
+    let rows = sqlx::query_as::<_, PushSubscriptionRow>(
+        "SELECT endpoint, user_id, p256dh, auth, created_at
+         FROM push_subscriptions WHERE user_id = $1 ORDER BY created_at, endpoint",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// The subscriptions of `user_ids` whose session is still live at `now`,
+/// ordered by user.
+pub async fn get_deliverable_subscriptions(
+    pool: &DbPool,
+    user_ids: &[i64],
+    now: DateTime<Utc>,
+) -> Result<Vec<PushSubscriptionRow>, DbError> {
+    const CHUNK: usize = 500;
+    let now = datetime_to_db_text(now);
+    let mut out = Vec::new();
+    for chunk in user_ids.chunks(CHUNK) {
+        let placeholders: Vec<String> = (2..=chunk.len() + 1).map(|i| format!("${}", i)).collect();
+        let sql = format!(
+            "SELECT p.endpoint, p.user_id, p.p256dh, p.auth, p.created_at
+             FROM push_subscriptions p
+             INNER JOIN auth_sessions s ON s.id = p.session_id AND s.user_id = p.user_id
+             WHERE s.revoked_at IS NULL
+               AND s.expires_at > $1
+               AND p.user_id IN ({})
+             ORDER BY p.user_id, p.created_at, p.endpoint",
+            placeholders.join(", ")
+        );
+        let mut query = sqlx::query_as::<_, PushSubscriptionRow>(&sql).bind(&now);
+        for user_id in chunk {
+            query = query.bind(user_id);
+        }
+        out.extend(query.fetch_all(pool).await?);
+    }
+    Ok(out)
+}
+
+/// Remove one of the user's subscriptions. Returns whether it existed.
+pub async fn delete_user_subscription(
+    pool: &DbPool,
+    user_id: i64,
+    endpoint: &str,
+) -> Result<bool, DbError> {
+    let result = sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = $1 AND user_id = $2")
+        .bind(endpoint)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Drop an endpoint the push service reported as gone.
+pub async fn delete_subscription(pool: &DbPool, endpoint: &str) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = $1")
+        .bind(endpoint)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Drop subscriptions whose session was revoked, has expired or is gone.
+pub async fn purge_orphaned_subscriptions(
+    pool: &DbPool,
+    now: DateTime<Utc>,
+) -> Result<u64, DbError> {
+    let result = sqlx::query(
+        "DELETE FROM push_subscriptions
+         WHERE session_id IS NULL
+            OR session_id NOT IN (
+                SELECT id FROM auth_sessions WHERE revoked_at IS NULL AND expires_at > $1
+            )",
+    )
+    .bind(datetime_to_db_text(now))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Keep only the user's `keep` newest subscriptions.
+pub async fn prune_user_subscriptions(
+    pool: &DbPool,
+    user_id: i64,
+    keep: usize,
+) -> Result<u64, DbError> {
+    let subscriptions = get_user_subscriptions(pool, user_id).await?;
+    let excess = subscriptions.len().saturating_sub(keep);
+    let mut removed = 0;
+    for subscription in subscriptions.iter().take(excess) {
+        removed += sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = $1")
+            .bind(&subscription.endpoint)
+            .execute(pool)
+            .await?
+            .rows_affected();
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_db() -> DbPool {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-db-push-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = crate::create_pool(&db_url, 1).await.expect("pool");
+        crate::run_migrations(&pool).await.expect("migrations");
+        pool
+    }
+
+    #[tokio::test]
+    async fn subscriptions_upsert_move_between_users_and_prune() {
+        let db = setup_db().await;
+        let alice = crate::users::create_user(&db, 9201, "alice", 1, "a@example.com", "hash")
+            .await
+            .expect("create user");
+        let bob = crate::users::create_user(&db, 9202, "bob", 1, "b@example.com", "hash")
+            .await
+            .expect("create user");
+
+        for endpoint in ["https://push.example.com/1", "https://push.example.com/2"] {
+            upsert_subscription(&db, alice.id, "sess-a", endpoint, "key", "auth")
+                .await
+                .unwrap();
+        }
+        upsert_subscription(
+            &db,
+            alice.id,
+            "sess-a",
+            "https://push.example.com/1",
+            "key2",
+            "auth2",
+        )
+        .await
+        .unwrap();
+        let subs = get_user_subscriptions(&db, alice.id).await.unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[0].p256dh, "key2");
+
+        // The same browser profile signed in as someone else.
+        upsert_subscription(
+            &db,
+            bob.id,
+            "sess-b",
+            "https://push.example.com/2",
+            "key",
+            "auth",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            get_user_subscriptions(&db, alice.id).await.unwrap().len(),
+            1
+        );
+        assert!(
+            !delete_user_subscription(&db, alice.id, "https://push.example.com/2")
+                .await
+                .unwrap()
+        );
+        assert!(
+            delete_user_subscription(&db, bob.id, "https://push.example.com/2")
+                .await
+                .unwrap()
+        );
+
+        for i in 3..6 {
+            upsert_subscription(
+                &db,
+                alice.id,
+                "sess-a",
+                &format!("https://push.example.com/{i}"),
+                "k",
+                "a",
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(prune_user_subscriptions(&db, alice.id, 2).await.unwrap(), 2);
+        assert_eq!(
+            get_user_subscriptions(&db, alice.id).await.unwrap().len(),
+            2
+        );
+
+        delete_subscription(&db, "https://push.example.com/5")
+            .await
+            .unwrap();
+        assert_eq!(
+            get_user_subscriptions(&db, alice.id).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn only_live_sessions_receive_pushes() {
+        let db = setup_db().await;
+        let alice = crate::users::create_user(&db, 9211, "alice", 1, "a@example.com", "hash")
+            .await
+            .expect("create user");
+        let now = Utc::now();
+        for session_id in ["sess-live", "sess-revoked"] {
+            crate::sessions::create_session(
+                &db,
+                session_id,
+                alice.id,
+                session_id,
+                session_id,
+                None,
+                None,
+                None,
+                None,
+                now + chrono::Duration::days(1),
+            )
+            .await
+            .unwrap();
+            upsert_subscription(
+                &db,
+                alice.id,
+                session_id,
+                &format!("https://push.example.com/{session_id}"),
+                "k",
+                "a",
+            )
+            .await
+            .unwrap();
+        }
+        upsert_subscription(
+            &db,
+            alice.id,
+            "sess-gone",
+            "https://push.example.com/gone",
+            "k",
+            "a",
+        )
+        .await
+        .unwrap();
+        crate::sessions::revoke_session(&db, "sess-revoked", alice.id, "logout", now)
+            .await
+            .unwrap();
+
+        let deliverable = get_deliverable_subscriptions(&db, &[alice.id], now)
+            .await
+            .unwrap();
+        assert_eq!(deliverable.len(), 1);
+        assert_eq!(
+            deliverable[0].endpoint,
+            "https://push.example.com/sess-live"
+        );
+        // Revoking the session already removed its subscription.
+        assert_eq!(
+            get_user_subscriptions(&db, alice.id).await.unwrap().len(),
+            2
+        );
+        assert_eq!(purge_orphaned_subscriptions(&db, now).await.unwrap(), 1);
+        assert_eq!(
+            get_user_subscriptions(&db, alice.id).await.unwrap().len(),
+            1
+        );
+    }
+}
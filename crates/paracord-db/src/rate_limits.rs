@@ -59,6 +59,29 @@ pub async fn purge_window_counters_older_than(
     Ok(result.rows_affected())
 }
 
+/// Delete counters whose window has fully elapsed by `now_epoch`.
+/// `window_start` is the window index (`epoch / window_seconds`).
+pub async fn purge_expired_window_counters(
+    pool: &DbPool,
+    now_epoch: i64,
+    limit: i64,
+) -> Result<u64, DbError> {
+    let result = sqlx::query(
+        "DELETE FROM rate_limit_counters
+         WHERE (bucket_key, window_start) IN (
+             SELECT bucket_key, window_start
+             FROM rate_limit_counters
+             WHERE (window_start + 1) * window_seconds <= $1
+             LIMIT $2
+         )",
+    )
+    .bind(now_epoch)
+    .bind(limit.max(1))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
 pub async fn get_auth_guard_states(
     pool: &DbPool,
     keys: &[String],
@@ -121,7 +144,7 @@ pub async fn purge_auth_guard_older_than(
          WHERE guard_key IN (
              SELECT guard_key
              FROM auth_guard_state
-             WHERE last_seen < $1
+             WHERE last_seen < $1 AND locked_until < $1
              ORDER BY last_seen ASC
              LIMIT $2
          )",
@@ -137,6 +160,20 @@ pub async fn record_auth_guard_failure(
     pool: &DbPool,
     guard_key: &str,
     now_epoch: i64,
+) -> Result<AuthGuardStateRow, DbError> {
+    record_auth_guard_failure_with(pool, guard_key, now_epoch, |failures| {
+        auth_guard_locked_until(failures, now_epoch)
+    })
+    .await
+}
+
+/// Record a failure against `guard_key`, setting its lock deadline to
+/// `locked_until(failures)` for the new failure count.
+pub async fn record_auth_guard_failure_with(
+    pool: &DbPool,
+    guard_key: &str,
+    now_epoch: i64,
+    locked_until: impl FnOnce(i64) -> i64,
 ) -> Result<AuthGuardStateRow, DbError> {
     let mut tx = pool.begin().await?;
     let existing = sqlx::query_as::<_, AuthGuardStateRow>(
@@ -152,11 +189,7 @@ pub async fn record_auth_guard_failure(
         .as_ref()
         .map(|row| row.failures.saturating_add(1))
         .unwrap_or(1);
-    let next_locked_until = if next_failures >= AUTH_GUARD_LOCK_THRESHOLD {
-        now_epoch.saturating_add(auth_guard_backoff_seconds(next_failures))
-    } else {
-        0
-    };
+    let next_locked_until = locked_until(next_failures);
 
     if existing.is_some() {
         sqlx::query(
@@ -194,6 +227,16 @@ pub async fn record_auth_guard_failure(
     })
 }
 
+/// Lockout deadline after `failures` consecutive failures, or `0` while the
+/// key is still under the lock threshold.
+pub fn auth_guard_locked_until(failures: i64, now_epoch: i64) -> i64 {
+    if failures >= AUTH_GUARD_LOCK_THRESHOLD {
+        now_epoch.saturating_add(auth_guard_backoff_seconds(failures))
+    } else {
+        0
+    }
+}
+
 fn auth_guard_backoff_seconds(failures: i64) -> i64 {
     if failures < AUTH_GUARD_LOCK_THRESHOLD {
         return 0;
@@ -203,11 +246,75 @@ fn auth_guard_backoff_seconds(failures: i64) -> i64 {
     backoff.min(AUTH_GUARD_MAX_BACKOFF_SECONDS)
 }
 
+/// Record a message from `user_id` in a channel with a `cooldown_seconds`
+/// slowmode, unless they already posted there within the cooldown. Returns
+/// `None` when the message may be sent, or the seconds left to wait.
+pub async fn claim_slowmode_slot(
+    pool: &DbPool,
+    channel_id: i64,
+    user_id: i64,
+    cooldown_seconds: i64,
+    now_epoch: i64,
+) -> Result<Option<i64>, DbError> {
+    let claimed = sqlx::query(
+        "INSERT INTO channel_slowmode (channel_id, user_id, last_sent_at)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (channel_id, user_id) DO UPDATE SET last_sent_at = excluded.last_sent_at
+         WHERE channel_slowmode.last_sent_at <= $4",
+    )
+    .bind(channel_id)
+    .bind(user_id)
+    .bind(now_epoch)
+    .bind(now_epoch.saturating_sub(cooldown_seconds))
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if claimed > 0 {
+        return Ok(None);
+    }
+    let last_sent_at: Option<(i64,)> = sqlx::query_as(
+        "SELECT last_sent_at FROM channel_slowmode WHERE channel_id = $1 AND user_id = $2",
+    )
+    .bind(channel_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(last_sent_at.map(|(last_sent_at,)| {
+        last_sent_at
+            .saturating_add(cooldown_seconds)
+            .saturating_sub(now_epoch)
+            .max(1)
+    }))
+}
+
+/// Give back a slot taken by [`claim_slowmode_slot`] at `claimed_at` when the
+/// message was not sent after all. Any earlier post was already outside the
+/// cooldown when the slot was claimed, so dropping the row restores the
+/// user's state. A later claim is left alone.
+pub async fn release_slowmode_slot(
+    pool: &DbPool,
+    channel_id: i64,
+    user_id: i64,
+    claimed_at: i64,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "DELETE FROM channel_slowmode
+         WHERE channel_id = $1 AND user_id = $2 AND last_sent_at = $3",
+    )
+    .bind(channel_id)
+    .bind(user_id)
+    .bind(claimed_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        clear_auth_guard_keys, get_auth_guard_states, increment_window_counter,
-        purge_window_counters_older_than, record_auth_guard_failure,
+        claim_slowmode_slot, clear_auth_guard_keys, get_auth_guard_states,
+        increment_window_counter, purge_auth_guard_older_than, purge_window_counters_older_than,
+        record_auth_guard_failure, record_auth_guard_failure_with, release_slowmode_slot,
     };
     use crate::DbPool;
 
@@ -289,4 +396,66 @@ mod tests {
             .expect("purge");
         assert_eq!(removed, 2);
     }
+
+    #[tokio::test]
+    async fn custom_auth_guard_locks_survive_purge_until_they_expire() {
+        let db = setup_db().await;
+        let now = 1_700_000_000_i64;
+        let lock = |failures: i64| if failures >= 2 { now + 7200 } else { 0 };
+
+        let first = record_auth_guard_failure_with(&db, "lockout:a", now, lock)
+            .await
+            .expect("first failure");
+        assert_eq!(first.locked_until, 0);
+        let second = record_auth_guard_failure_with(&db, "lockout:a", now, lock)
+            .await
+            .expect("second failure");
+        assert_eq!(second.failures, 2);
+        assert_eq!(second.locked_until, now + 7200);
+        record_auth_guard_failure(&db, "acct:idle", now)
+            .await
+            .expect("idle key");
+
+        // An hour later the idle key is purged but the live lock is kept.
+        let removed = purge_auth_guard_older_than(&db, now + 3600, 10)
+            .await
+            .expect("purge");
+        assert_eq!(removed, 1);
+        let rows = get_auth_guard_states(&db, &["lockout:a".to_string()])
+            .await
+            .expect("load rows");
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn slowmode_slot_is_claimed_once_per_cooldown() {
+        let db = setup_db().await;
+        crate::users::create_user(&db, 7001, "slow", 1, "slow@example.com", "hash")
+            .await
+            .expect("user");
+        crate::guilds::create_guild(&db, 7100, "Slow", 7001, None)
+            .await
+            .expect("guild");
+        crate::channels::create_channel(&db, 7200, 7100, "general", 0, 0, None, None)
+            .await
+            .expect("channel");
+        let now = 1_700_000_000_i64;
+
+        let claim = |at: i64| claim_slowmode_slot(&db, 7200, 7001, 30, at);
+        assert_eq!(claim(now).await.expect("first"), None);
+        assert_eq!(claim(now + 10).await.expect("too soon"), Some(20));
+        // A rejected attempt does not restart the cooldown.
+        assert_eq!(claim(now + 30).await.expect("after cooldown"), None);
+        assert_eq!(claim(now + 31).await.expect("again"), Some(29));
+
+        // A message that failed to send does not use up the slot.
+        release_slowmode_slot(&db, 7200, 7001, now + 30)
+            .await
+            .expect("release");
+        assert_eq!(claim(now + 31).await.expect("released"), None);
+        release_slowmode_slot(&db, 7200, 7001, now + 30)
+            .await
+            .expect("stale release");
+        assert_eq!(claim(now + 32).await.expect("kept"), Some(29));
+    }
 }
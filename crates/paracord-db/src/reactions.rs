@@ -1,4 +1,4 @@
-use crate::{datetime_from_db_text, DbError, DbPool};
+use crate::{bool_from_any_row, datetime_from_db_text, DbError, DbPool};
 use chrono::{DateTime, Utc};
 use sqlx::Row;
 
@@ -8,6 +8,7 @@ pub struct ReactionRow {
     pub user_id: i64,
     pub emoji_id: Option<i64>,
     pub emoji_name: String,
+    pub burst: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -19,6 +20,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ReactionRow {
             user_id: row.try_get("user_id")?,
             emoji_id: row.try_get("emoji_id")?,
             emoji_name: row.try_get("emoji_name")?,
+            burst: bool_from_any_row(row, "burst")?,
             created_at: datetime_from_db_text(&created_at_raw)?,
         })
     }
@@ -26,56 +28,207 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ReactionRow {
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct ReactionCountRow {
+    pub message_id: i64,
     pub emoji_name: String,
+    /// The emoji as first sent, before canonicalization.
+    pub emoji_display: String,
     pub emoji_id: Option<i64>,
+    /// All reactions with this emoji, normal and burst.
     pub count: i64,
+    pub burst_count: i64,
 }
 
+/// Add a reaction. `emoji_name` is the canonical key reactions are counted
+/// under and `emoji_display` the emoji as sent. A user holds at most one
+/// reaction per key; re-adding it as a burst reaction upgrades it, but a
+/// normal re-add never downgrades.
 pub async fn add_reaction(
     pool: &DbPool,
     message_id: i64,
     user_id: i64,
     emoji_name: &str,
+    emoji_display: &str,
     emoji_id: Option<i64>,
+    burst: bool,
 ) -> Result<(), DbError> {
-    sqlx::query(
-        "INSERT INTO reactions (message_id, user_id, emoji_name, emoji_id)
-         VALUES ($1, $2, $3, $4)
-         ON CONFLICT (message_id, user_id, emoji_name) DO NOTHING",
-    )
-    .bind(message_id)
-    .bind(user_id)
-    .bind(emoji_name)
-    .bind(emoji_id)
-    .execute(pool)
+    crate::retry_on_busy(|| {
+        sqlx::query(
+            "INSERT INTO reactions (message_id, user_id, emoji_name, emoji_display, emoji_id, burst)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (message_id, user_id, emoji_name)
+             DO UPDATE SET burst = (reactions.burst OR excluded.burst)",
+        )
+        .bind(message_id)
+        .bind(user_id)
+        .bind(emoji_name)
+        .bind(emoji_display)
+        .bind(emoji_id)
+        .bind(burst)
+        .execute(pool)
+    })
     .await?;
     Ok(())
 }
 
+/// Insert a reaction unless it would give the message more than
+/// `$7` distinct keys. One statement, so concurrent adds can't both slip
+/// under the cap. Binds as in [`add_reaction`].
+const ADD_REACTION_CAPPED_SQL: &str =
+    "INSERT INTO reactions (message_id, user_id, emoji_name, emoji_display, emoji_id, burst)
+     SELECT $1, $2, $3, $4, $5, $6
+     WHERE EXISTS (SELECT 1 FROM reactions WHERE message_id = $1 AND emoji_name = $3)
+        OR (SELECT COUNT(DISTINCT emoji_name) FROM reactions WHERE message_id = $1) < $7
+     ON CONFLICT (message_id, user_id, emoji_name)
+     DO UPDATE SET burst = (reactions.burst OR excluded.burst)";
+
+/// [`add_reaction`], refusing a new key once the message has `max_unique`
+/// distinct ones. Returns `false`, with nothing written, when refused.
+#[allow(clippy::too_many_arguments)]
+pub async fn add_reaction_capped(
+    pool: &DbPool,
+    message_id: i64,
+    user_id: i64,
+    emoji_name: &str,
+    emoji_display: &str,
+    emoji_id: Option<i64>,
+    burst: bool,
+    max_unique: usize,
+) -> Result<bool, DbError> {
+    let result = crate::retry_on_busy(|| {
+        sqlx::query(ADD_REACTION_CAPPED_SQL)
+            .bind(message_id)
+            .bind(user_id)
+            .bind(emoji_name)
+            .bind(emoji_display)
+            .bind(emoji_id)
+            .bind(burst)
+            .bind(max_unique as i64)
+            .execute(pool)
+    })
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn remove_reaction(
     pool: &DbPool,
     message_id: i64,
     user_id: i64,
     emoji_name: &str,
 ) -> Result<(), DbError> {
-    sqlx::query("DELETE FROM reactions WHERE message_id = $1 AND user_id = $2 AND emoji_name = $3")
+    crate::retry_on_busy(|| {
+        sqlx::query(
+            "DELETE FROM reactions WHERE message_id = $1 AND user_id = $2 AND emoji_name = $3",
+        )
         .bind(message_id)
         .bind(user_id)
         .bind(emoji_name)
         .execute(pool)
-        .await?;
+    })
+    .await?;
     Ok(())
 }
 
+/// Distinct reaction keys on a message.
+pub async fn get_message_reaction_keys(
+    pool: &DbPool,
+    message_id: i64,
+) -> Result<Vec<String>, DbError> {
+    let rows: Vec<(String,)> =
+        sqlx::query_as("SELECT DISTINCT emoji_name FROM reactions WHERE message_id = $1")
+            .bind(message_id)
+            .fetch_all(pool)
+            .await?;
+    Ok(rows.into_iter().map(|r| r.0).collect())
+}
+
+/// What [`apply_reaction_batch`] changed.
+#[derive(Debug, Clone, Default)]
+pub struct ReactionBatchResult {
+    /// `(emoji_name, emoji_display)` for reactions the user did not hold yet.
+    pub added: Vec<(String, String)>,
+    /// Keys of reactions the user held and no longer does.
+    pub removed: Vec<String>,
+}
+
+/// Add and remove several of one user's reactions in a single transaction.
+/// Removals apply first. Returns `None`, with nothing written, when the adds
+/// would leave the message with more than `max_unique` distinct emoji.
+pub async fn apply_reaction_batch(
+    pool: &DbPool,
+    message_id: i64,
+    user_id: i64,
+    add: &[(String, String)],
+    remove: &[String],
+    max_unique: usize,
+) -> Result<Option<ReactionBatchResult>, DbError> {
+    let mut tx = pool.begin().await?;
+
+    let held: Vec<(String,)> =
+        sqlx::query_as("SELECT emoji_name FROM reactions WHERE message_id = $1 AND user_id = $2")
+            .bind(message_id)
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+    let mut held: Vec<String> = held.into_iter().map(|r| r.0).collect();
+
+    let mut result = ReactionBatchResult::default();
+    for key in remove {
+        if !held.contains(key) {
+            continue;
+        }
+        sqlx::query(
+            "DELETE FROM reactions WHERE message_id = $1 AND user_id = $2 AND emoji_name = $3",
+        )
+        .bind(message_id)
+        .bind(user_id)
+        .bind(key)
+        .execute(&mut *tx)
+        .await?;
+        held.retain(|k| k != key);
+        result.removed.push(key.clone());
+    }
+
+    for (key, display) in add {
+        if held.contains(key) {
+            continue;
+        }
+        let inserted = sqlx::query(ADD_REACTION_CAPPED_SQL)
+            .bind(message_id)
+            .bind(user_id)
+            .bind(key)
+            .bind(display)
+            .bind(None::<i64>)
+            .bind(false)
+            .bind(max_unique as i64)
+            .execute(&mut *tx)
+            .await?;
+        if inserted.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+        held.push(key.clone());
+        result.added.push((key.clone(), display.clone()));
+    }
+
+    tx.commit().await?;
+    Ok(Some(result))
+}
+
 pub async fn get_message_reactions(
     pool: &DbPool,
     message_id: i64,
 ) -> Result<Vec<ReactionCountRow>, DbError> {
     let rows = sqlx::query_as::<_, ReactionCountRow>(
-        "SELECT emoji_name, emoji_id, COUNT(*) as count
-         FROM reactions WHERE message_id = $1
-         GROUP BY emoji_name, emoji_id
-         ORDER BY MIN(created_at)",
+        "SELECT r.message_id, r.emoji_name, r.emoji_id, COUNT(*) as count,
+                SUM(CASE WHEN r.burst THEN 1 ELSE 0 END) as burst_count,
+                (SELECT COALESCE(earliest.emoji_display, earliest.emoji_name)
+                 FROM reactions earliest
+                 WHERE earliest.message_id = r.message_id AND earliest.emoji_name = r.emoji_name
+                 ORDER BY earliest.created_at, earliest.user_id
+                 LIMIT 1) as emoji_display
+         FROM reactions r WHERE r.message_id = $1
+         GROUP BY r.message_id, r.emoji_name, r.emoji_id
+         ORDER BY MIN(r.created_at)",
     )
     .bind(message_id)
     .fetch_all(pool)
@@ -83,22 +236,124 @@ pub async fn get_message_reactions(
     Ok(rows)
 }
 
+/// Reaction summaries for several messages in one query, ordered by
+/// message and then by when each emoji was first used.
+pub async fn get_reactions_for_message_ids(
+    pool: &DbPool,
+    message_ids: &[i64],
+) -> Result<Vec<ReactionCountRow>, DbError> {
+    if message_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (1..=message_ids.len()).map(|i| format!("${}", i)).collect();
+    let sql = format!(
+        "SELECT r.message_id, r.emoji_name, r.emoji_id, COUNT(*) as count,
+                SUM(CASE WHEN r.burst THEN 1 ELSE 0 END) as burst_count,
+                (SELECT COALESCE(earliest.emoji_display, earliest.emoji_name)
+                 FROM reactions earliest
+                 WHERE earliest.message_id = r.message_id AND earliest.emoji_name = r.emoji_name
+                 ORDER BY earliest.created_at, earliest.user_id
+                 LIMIT 1) as emoji_display
+         FROM reactions r WHERE r.message_id IN ({})
+         GROUP BY r.message_id, r.emoji_name, r.emoji_id
+         ORDER BY r.message_id, MIN(r.created_at)",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, ReactionCountRow>(&sql);
+    for message_id in message_ids {
+        query = query.bind(message_id);
+    }
+    Ok(query.fetch_all(pool).await?)
+}
+
+/// Users who reacted with `emoji_name`, optionally restricted to burst
+/// (`Some(true)`) or normal (`Some(false)`) reactions.
 pub async fn get_reaction_users(
     pool: &DbPool,
     message_id: i64,
     emoji_name: &str,
+    burst: Option<bool>,
     limit: i64,
 ) -> Result<Vec<i64>, DbError> {
-    let rows: Vec<(i64,)> = sqlx::query_as(
-        "SELECT user_id FROM reactions
-         WHERE message_id = $1 AND emoji_name = $2
-         ORDER BY created_at
-         LIMIT $3",
+    let rows: Vec<(i64,)> = match burst {
+        None => {
+            sqlx::query_as(
+                "SELECT user_id FROM reactions
+                 WHERE message_id = $1 AND emoji_name = $2
+                 ORDER BY created_at
+                 LIMIT $3",
+            )
+            .bind(message_id)
+            .bind(emoji_name)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+        }
+        Some(burst) => {
+            sqlx::query_as(
+                "SELECT user_id FROM reactions
+                 WHERE message_id = $1 AND emoji_name = $2 AND burst = $3
+                 ORDER BY created_at
+                 LIMIT $4",
+            )
+            .bind(message_id)
+            .bind(emoji_name)
+            .bind(burst)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+    Ok(rows.into_iter().map(|r| r.0).collect())
+}
+
+/// The viewer's own reactions on a message as `(emoji_name, burst)` pairs.
+pub async fn get_user_message_reactions(
+    pool: &DbPool,
+    message_id: i64,
+    user_id: i64,
+) -> Result<Vec<(String, bool)>, DbError> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT emoji_name, CASE WHEN burst THEN 1 ELSE 0 END
+         FROM reactions
+         WHERE message_id = $1 AND user_id = $2",
     )
     .bind(message_id)
-    .bind(emoji_name)
-    .bind(limit)
+    .bind(user_id)
     .fetch_all(pool)
     .await?;
-    Ok(rows.into_iter().map(|r| r.0).collect())
+    Ok(rows
+        .into_iter()
+        .map(|(emoji, burst)| (emoji, burst != 0))
+        .collect())
+}
+
+/// `(message_id, emoji_name, burst)` for each of `user_id`'s reactions on
+/// `message_ids`.
+pub async fn get_user_reactions_for_message_ids(
+    pool: &DbPool,
+    message_ids: &[i64],
+    user_id: i64,
+) -> Result<Vec<(i64, String, bool)>, DbError> {
+    if message_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (2..=message_ids.len() + 1)
+        .map(|i| format!("${}", i))
+        .collect();
+    let sql = format!(
+        "SELECT message_id, emoji_name, CASE WHEN burst THEN 1 ELSE 0 END
+         FROM reactions
+         WHERE user_id = $1 AND message_id IN ({})",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, (i64, String, i64)>(&sql).bind(user_id);
+    for message_id in message_ids {
+        query = query.bind(message_id);
+    }
+    let rows = query.fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .map(|(message_id, emoji, burst)| (message_id, emoji, burst != 0))
+        .collect())
 }
@@ -45,16 +45,68 @@ pub async fn update_read_state(
     channel_id: i64,
     last_message_id: i64,
 ) -> Result<ReadStateRow, DbError> {
-    let row = sqlx::query_as::<_, ReadStateRow>(
-        "INSERT INTO read_states (user_id, channel_id, last_message_id, mention_count)
-         VALUES ($1, $2, $3, 0)
-         ON CONFLICT (user_id, channel_id) DO UPDATE SET last_message_id = $3, mention_count = 0
-         RETURNING user_id, channel_id, last_message_id, mention_count",
-    )
-    .bind(user_id)
-    .bind(channel_id)
-    .bind(last_message_id)
-    .fetch_one(pool)
+    let row = crate::retry_on_busy(|| {
+        sqlx::query_as::<_, ReadStateRow>(
+            "INSERT INTO read_states (user_id, channel_id, last_message_id, mention_count)
+             VALUES ($1, $2, $3, 0)
+             ON CONFLICT (user_id, channel_id) DO UPDATE SET last_message_id = $3, mention_count = 0
+             RETURNING user_id, channel_id, last_message_id, mention_count",
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .bind(last_message_id)
+        .fetch_one(pool)
+    })
     .await?;
     Ok(row)
 }
+
+/// Count one more unread mention of `user_id` in `channel_id`.
+pub async fn increment_mention_count(
+    pool: &DbPool,
+    user_id: i64,
+    channel_id: i64,
+) -> Result<(), DbError> {
+    crate::retry_on_busy(|| {
+        sqlx::query(
+            "INSERT INTO read_states (user_id, channel_id, last_message_id, mention_count)
+             VALUES ($1, $2, 0, 1)
+             ON CONFLICT (user_id, channel_id) DO UPDATE SET mention_count = read_states.mention_count + 1",
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .execute(pool)
+    })
+    .await?;
+    Ok(())
+}
+
+/// Count one more unread mention in `channel_id` for each of `user_ids`,
+/// a few hundred rows per statement. `user_ids` must not repeat.
+pub async fn increment_mention_counts(
+    pool: &DbPool,
+    user_ids: &[i64],
+    channel_id: i64,
+) -> Result<(), DbError> {
+    const CHUNK: usize = 500;
+    for chunk in user_ids.chunks(CHUNK) {
+        let rows: Vec<String> = (2..=chunk.len() + 1)
+            .map(|i| format!("(${}, $1, 0, 1)", i))
+            .collect();
+        let sql = format!(
+            "INSERT INTO read_states (user_id, channel_id, last_message_id, mention_count)
+             VALUES {}
+             ON CONFLICT (user_id, channel_id) DO UPDATE SET mention_count = read_states.mention_count + 1",
+            rows.join(", ")
+        );
+        crate::retry_on_busy(|| {
+            let mut query = sqlx::query(&sql).bind(channel_id);
+            for user_id in chunk {
+                query = query.bind(user_id);
+            }
+            query.execute(pool)
+        })
+        .await?;
+    }
+    Ok(())
+}
@@ -170,11 +170,8 @@ pub async fn are_friends(pool: &DbPool, user_a: i64, user_b: i64) -> Result<bool
     Ok(row.is_some())
 }
 
-pub async fn is_blocked_either_direction(
-    pool: &DbPool,
-    user_a: i64,
-    user_b: i64,
-) -> Result<bool, DbError> {
+/// Whether either user has blocked the other.
+pub async fn is_blocked(pool: &DbPool, user_a: i64, user_b: i64) -> Result<bool, DbError> {
     let row: Option<(i64,)> = sqlx::query_as(
         "SELECT 1
          FROM relationships
@@ -188,3 +185,16 @@ pub async fn is_blocked_either_direction(
     .await?;
     Ok(row.is_some())
 }
+
+/// Everyone `user_id` has blocked or been blocked by.
+pub async fn get_blocked_user_ids(pool: &DbPool, user_id: i64) -> Result<Vec<i64>, DbError> {
+    let rows: Vec<(i64,)> = sqlx::query_as(
+        "SELECT target_id FROM relationships WHERE rel_type = 2 AND user_id = $1
+         UNION
+         SELECT user_id FROM relationships WHERE rel_type = 2 AND target_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
@@ -15,6 +15,8 @@ pub struct RoleRow {
     pub mentionable: bool,
     pub server_wide: bool,
     pub created_at: DateTime<Utc>,
+    /// Unicode emoji, or the content hash of an uploaded icon image.
+    pub role_icon: Option<String>,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for RoleRow {
@@ -32,6 +34,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for RoleRow {
             mentionable: bool_from_any_row(row, "mentionable")?,
             server_wide: bool_from_any_row(row, "server_wide")?,
             created_at: datetime_from_db_text(&created_at_raw)?,
+            role_icon: row.try_get("role_icon")?,
         })
     }
 }
@@ -53,7 +56,7 @@ pub async fn create_role(
     let row = sqlx::query_as::<_, RoleRow>(
         "INSERT INTO roles (id, space_id, name, permissions)
          VALUES ($1, $2, $3, $4)
-         RETURNING id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at"
+         RETURNING id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at, role_icon"
     )
     .bind(id)
     .bind(space_id)
@@ -66,7 +69,7 @@ pub async fn create_role(
 
 pub async fn get_role(pool: &DbPool, id: i64) -> Result<Option<RoleRow>, DbError> {
     let row = sqlx::query_as::<_, RoleRow>(
-        "SELECT id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at
+        "SELECT id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at, role_icon
          FROM roles WHERE id = $1"
     )
     .bind(id)
@@ -92,7 +95,7 @@ pub async fn update_role(
             permissions = COALESCE($5, permissions),
             mentionable = COALESCE($6, mentionable)
          WHERE id = $1
-         RETURNING id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at"
+         RETURNING id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at, role_icon"
     )
     .bind(id)
     .bind(name)
@@ -105,6 +108,24 @@ pub async fn update_role(
     Ok(row)
 }
 
+/// Set or clear (`None`) a role's icon.
+pub async fn set_role_icon(
+    pool: &DbPool,
+    id: i64,
+    role_icon: Option<&str>,
+) -> Result<RoleRow, DbError> {
+    let row = sqlx::query_as::<_, RoleRow>(
+        "UPDATE roles SET role_icon = $2
+         WHERE id = $1
+         RETURNING id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at, role_icon"
+    )
+    .bind(id)
+    .bind(role_icon)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
 pub async fn delete_role(pool: &DbPool, id: i64) -> Result<(), DbError> {
     sqlx::query("DELETE FROM roles WHERE id = $1")
         .bind(id)
@@ -119,7 +140,7 @@ pub async fn get_guild_roles(pool: &DbPool, space_id: i64) -> Result<Vec<RoleRow
 
 pub async fn get_space_roles(pool: &DbPool, space_id: i64) -> Result<Vec<RoleRow>, DbError> {
     let rows = sqlx::query_as::<_, RoleRow>(
-        "SELECT id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at
+        "SELECT id, space_id, name, color, CASE WHEN hoist THEN 1 ELSE 0 END AS hoist, position, permissions, CASE WHEN managed THEN 1 ELSE 0 END AS managed, CASE WHEN mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN server_wide THEN 1 ELSE 0 END AS server_wide, created_at, role_icon
          FROM roles WHERE space_id = $1 ORDER BY position"
     )
     .bind(space_id)
@@ -128,6 +149,22 @@ pub async fn get_space_roles(pool: &DbPool, space_id: i64) -> Result<Vec<RoleRow
     Ok(rows)
 }
 
+/// Roles of every space `user_id` is a member of, ordered by space then
+/// position.
+pub async fn get_user_guild_roles(pool: &DbPool, user_id: i64) -> Result<Vec<RoleRow>, DbError> {
+    let rows = sqlx::query_as::<_, RoleRow>(
+        "SELECT r.id, r.space_id, r.name, r.color, CASE WHEN r.hoist THEN 1 ELSE 0 END AS hoist, r.position, r.permissions, CASE WHEN r.managed THEN 1 ELSE 0 END AS managed, CASE WHEN r.mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN r.server_wide THEN 1 ELSE 0 END AS server_wide, r.created_at, r.role_icon
+         FROM roles r
+         INNER JOIN members m ON m.guild_id = r.space_id
+         WHERE m.user_id = $1
+         ORDER BY r.space_id, r.position"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 /// member_roles no longer has guild_id - just user_id + role_id
 pub async fn add_member_role(
     pool: &DbPool,
@@ -189,7 +226,7 @@ pub async fn get_member_roles(
 ) -> Result<Vec<RoleRow>, DbError> {
     let rows = sqlx::query_as::<_, RoleRow>(
         "SELECT DISTINCT
-            r.id, r.space_id, r.name, r.color, CASE WHEN r.hoist THEN 1 ELSE 0 END AS hoist, r.position, r.permissions, CASE WHEN r.managed THEN 1 ELSE 0 END AS managed, CASE WHEN r.mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN r.server_wide THEN 1 ELSE 0 END AS server_wide, r.created_at
+            r.id, r.space_id, r.name, r.color, CASE WHEN r.hoist THEN 1 ELSE 0 END AS hoist, r.position, r.permissions, CASE WHEN r.managed THEN 1 ELSE 0 END AS managed, CASE WHEN r.mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN r.server_wide THEN 1 ELSE 0 END AS server_wide, r.created_at, r.role_icon
          FROM roles r
          LEFT JOIN member_roles mr
             ON mr.role_id = r.id
@@ -217,7 +254,7 @@ pub async fn get_member_roles(
 
 pub async fn get_user_all_roles(pool: &DbPool, user_id: i64) -> Result<Vec<RoleRow>, DbError> {
     let rows = sqlx::query_as::<_, RoleRow>(
-        "SELECT r.id, r.space_id, r.name, r.color, CASE WHEN r.hoist THEN 1 ELSE 0 END AS hoist, r.position, r.permissions, CASE WHEN r.managed THEN 1 ELSE 0 END AS managed, CASE WHEN r.mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN r.server_wide THEN 1 ELSE 0 END AS server_wide, r.created_at
+        "SELECT r.id, r.space_id, r.name, r.color, CASE WHEN r.hoist THEN 1 ELSE 0 END AS hoist, r.position, r.permissions, CASE WHEN r.managed THEN 1 ELSE 0 END AS managed, CASE WHEN r.mentionable THEN 1 ELSE 0 END AS mentionable, CASE WHEN r.server_wide THEN 1 ELSE 0 END AS server_wide, r.created_at, r.role_icon
          FROM roles r
          INNER JOIN member_roles mr ON mr.role_id = r.id
          WHERE mr.user_id = $1
@@ -229,6 +266,80 @@ pub async fn get_user_all_roles(pool: &DbPool, user_id: i64) -> Result<Vec<RoleR
     Ok(rows)
 }
 
+/// Ids of guild members holding any of `role_ids`, deduplicated. Roles from
+/// other guilds are ignored.
+pub async fn get_role_member_ids(
+    pool: &DbPool,
+    space_id: i64,
+    role_ids: &[i64],
+) -> Result<Vec<i64>, DbError> {
+    const MAX_ROLE_IDS: usize = 500;
+    if role_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    if role_ids.len() > MAX_ROLE_IDS {
+        return Err(DbError::Sqlx(sqlx::Error::Protocol(
+            "too many role ids in member lookup".to_string(),
+        )));
+    }
+
+    let placeholders: Vec<String> = (2..=role_ids.len() + 1)
+        .map(|i| format!("${}", i))
+        .collect();
+    let sql = format!(
+        "SELECT DISTINCT mr.user_id
+         FROM member_roles mr
+         INNER JOIN roles r ON r.id = mr.role_id
+         INNER JOIN members m ON m.user_id = mr.user_id AND m.guild_id = r.space_id
+         WHERE r.space_id = $1
+           AND mr.role_id IN ({})
+         ORDER BY mr.user_id",
+        placeholders.join(", ")
+    );
+
+    let mut query = sqlx::query_as::<_, (i64,)>(&sql).bind(space_id);
+    for role_id in role_ids {
+        query = query.bind(role_id);
+    }
+    let rows = query.fetch_all(pool).await?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
+/// The space role ids held by each of `user_ids` that is a member of
+/// `space_id`. Members without roles map to an empty list; non-members are
+/// left out. The implicit @everyone role is not included.
+pub async fn get_members_role_ids(
+    pool: &DbPool,
+    space_id: i64,
+    user_ids: &[i64],
+) -> Result<std::collections::HashMap<i64, Vec<i64>>, DbError> {
+    const CHUNK: usize = 500;
+    let mut out: std::collections::HashMap<i64, Vec<i64>> = std::collections::HashMap::new();
+    for chunk in user_ids.chunks(CHUNK) {
+        let placeholders: Vec<String> = (2..=chunk.len() + 1).map(|i| format!("${}", i)).collect();
+        let sql = format!(
+            "SELECT m.user_id, r.id
+             FROM members m
+             LEFT JOIN member_roles mr ON mr.user_id = m.user_id
+             LEFT JOIN roles r ON r.id = mr.role_id AND r.space_id = m.guild_id
+             WHERE m.guild_id = $1
+               AND m.user_id IN ({})",
+            placeholders.join(", ")
+        );
+        let mut query = sqlx::query_as::<_, (i64, Option<i64>)>(&sql).bind(space_id);
+        for user_id in chunk {
+            query = query.bind(user_id);
+        }
+        for (user_id, role_id) in query.fetch_all(pool).await? {
+            let roles = out.entry(user_id).or_default();
+            if let Some(role_id) = role_id {
+                roles.push(role_id);
+            }
+        }
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -381,6 +492,74 @@ mod tests {
         assert!(!role_ids.contains(&520));
     }
 
+    #[tokio::test]
+    async fn test_get_role_member_ids() {
+        let pool = test_pool().await;
+        let (owner_id, guild_id) = setup_guild(&pool).await;
+        crate::members::add_member(&pool, owner_id, guild_id)
+            .await
+            .unwrap();
+        crate::users::create_user(&pool, 2, "holder", 2, "h@example.com", "hash")
+            .await
+            .unwrap();
+        crate::members::add_member(&pool, 2, guild_id)
+            .await
+            .unwrap();
+        create_role(&pool, 540, guild_id, "Ops", 0).await.unwrap();
+        create_role(&pool, 541, guild_id, "Dev", 0).await.unwrap();
+        add_member_role(&pool, 2, guild_id, 540).await.unwrap();
+        add_member_role(&pool, 2, guild_id, 541).await.unwrap();
+        add_member_role(&pool, owner_id, guild_id, 541)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_role_member_ids(&pool, guild_id, &[540]).await.unwrap(),
+            vec![2]
+        );
+        assert_eq!(
+            get_role_member_ids(&pool, guild_id, &[540, 541])
+                .await
+                .unwrap(),
+            vec![owner_id, 2]
+        );
+        assert!(get_role_member_ids(&pool, guild_id + 1, &[540])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_members_role_ids() {
+        let pool = test_pool().await;
+        let (owner_id, guild_id) = setup_guild(&pool).await;
+        crate::members::add_member(&pool, owner_id, guild_id)
+            .await
+            .unwrap();
+        crate::users::create_user(&pool, 2, "holder", 2, "h@example.com", "hash")
+            .await
+            .unwrap();
+        crate::members::add_member(&pool, 2, guild_id)
+            .await
+            .unwrap();
+        crate::users::create_user(&pool, 3, "outsider", 3, "x@example.com", "hash")
+            .await
+            .unwrap();
+        create_role(&pool, 550, guild_id, "Ops", 0).await.unwrap();
+        create_role(&pool, 551, guild_id, "Dev", 0).await.unwrap();
+        add_member_role(&pool, 2, guild_id, 550).await.unwrap();
+        add_member_role(&pool, 2, guild_id, 551).await.unwrap();
+
+        let roles = get_members_role_ids(&pool, guild_id, &[owner_id, 2, 3])
+            .await
+            .unwrap();
+        assert_eq!(roles.len(), 2);
+        assert!(roles[&owner_id].is_empty());
+        let mut holder = roles[&2].clone();
+        holder.sort_unstable();
+        assert_eq!(holder, vec![550, 551]);
+    }
+
     #[tokio::test]
     async fn test_guild_id_backward_compat() {
         let pool = test_pool().await;
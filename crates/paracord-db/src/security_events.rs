@@ -99,65 +99,187 @@ pub async fn purge_entries_older_than(
     Ok(result.rows_affected())
 }
 
+/// Oldest-first batch of events at or before `older_than`, used to archive
+/// rows before retention deletes them.
+pub async fn list_events_older_than(
+    pool: &DbPool,
+    older_than: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<SecurityEventRow>, DbError> {
+    let rows = sqlx::query_as::<_, SecurityEventRow>(
+        "SELECT id, actor_user_id, action, target_user_id, session_id, device_id, user_agent, ip_address, details, created_at
+         FROM security_events
+         WHERE created_at <= $1
+         ORDER BY created_at ASC, id ASC
+         LIMIT $2",
+    )
+    .bind(datetime_to_db_text(older_than))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn delete_events(pool: &DbPool, ids: &[i64]) -> Result<u64, DbError> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let placeholders = (1..=ids.len())
+        .map(|i| format!("${i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!("DELETE FROM security_events WHERE id IN ({placeholders})");
+    let mut query = sqlx::query(&sql);
+    for id in ids {
+        query = query.bind(*id);
+    }
+    let result = query.execute(pool).await?;
+    Ok(result.rows_affected())
+}
+
 pub async fn list_events(
     pool: &DbPool,
     action: Option<&str>,
     before: Option<i64>,
     limit: i64,
 ) -> Result<Vec<SecurityEventRow>, DbError> {
-    let rows = match (action, before) {
-        (None, None) => {
-            sqlx::query_as::<_, SecurityEventRow>(
-                "SELECT id, actor_user_id, action, target_user_id, session_id, device_id, user_agent, ip_address, details, created_at
-                 FROM security_events
-                 ORDER BY id DESC
-                 LIMIT $1",
-            )
-            .bind(limit)
-            .fetch_all(pool)
-            .await?
-        }
-        (Some(action), None) => {
-            sqlx::query_as::<_, SecurityEventRow>(
-                "SELECT id, actor_user_id, action, target_user_id, session_id, device_id, user_agent, ip_address, details, created_at
-                 FROM security_events
-                 WHERE action = $1
-                 ORDER BY id DESC
-                 LIMIT $2",
-            )
-            .bind(action)
-            .bind(limit)
-            .fetch_all(pool)
-            .await?
-        }
-        (None, Some(before)) => {
-            sqlx::query_as::<_, SecurityEventRow>(
-                "SELECT id, actor_user_id, action, target_user_id, session_id, device_id, user_agent, ip_address, details, created_at
-                 FROM security_events
-                 WHERE id < $1
-                 ORDER BY id DESC
-                 LIMIT $2",
-            )
-            .bind(before)
-            .bind(limit)
-            .fetch_all(pool)
-            .await?
-        }
-        (Some(action), Some(before)) => {
-            sqlx::query_as::<_, SecurityEventRow>(
-                "SELECT id, actor_user_id, action, target_user_id, session_id, device_id, user_agent, ip_address, details, created_at
-                 FROM security_events
-                 WHERE action = $1
-                   AND id < $2
-                 ORDER BY id DESC
-                 LIMIT $3",
-            )
-            .bind(action)
-            .bind(before)
-            .bind(limit)
-            .fetch_all(pool)
-            .await?
-        }
+    list_events_filtered(pool, action, None, before, limit).await
+}
+
+/// Newest-first page of events. `user_id` matches events where the user is
+/// either the actor or the target.
+pub async fn list_events_filtered(
+    pool: &DbPool,
+    action: Option<&str>,
+    user_id: Option<i64>,
+    before: Option<i64>,
+    limit: i64,
+) -> Result<Vec<SecurityEventRow>, DbError> {
+    let mut clauses: Vec<String> = Vec::new();
+    let mut param = 0;
+    if action.is_some() {
+        param += 1;
+        clauses.push(format!("action = ${param}"));
+    }
+    if user_id.is_some() {
+        param += 1;
+        clauses.push(format!(
+            "(actor_user_id = ${param} OR target_user_id = ${param})"
+        ));
+    }
+    if before.is_some() {
+        param += 1;
+        clauses.push(format!("id < ${param}"));
+    }
+    let where_sql = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
     };
+    let sql = format!(
+        "SELECT id, actor_user_id, action, target_user_id, session_id, device_id, user_agent, ip_address, details, created_at
+         FROM security_events
+         {where_sql}
+         ORDER BY id DESC
+         LIMIT ${}",
+        param + 1
+    );
+
+    let mut query = sqlx::query_as::<_, SecurityEventRow>(&sql);
+    if let Some(action) = action {
+        query = query.bind(action);
+    }
+    if let Some(user_id) = user_id {
+        query = query.bind(user_id);
+    }
+    if let Some(before) = before {
+        query = query.bind(before);
+    }
+    let rows = query.bind(limit).fetch_all(pool).await?;
     Ok(rows)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_db() -> DbPool {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-db-security-events-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = crate::create_pool(&db_url, 1).await.expect("pool");
+        crate::run_migrations(&pool).await.expect("migrations");
+        pool
+    }
+
+    async fn insert_event_at(pool: &DbPool, id: i64, created_at: DateTime<Utc>) {
+        create_event(
+            pool,
+            id,
+            None,
+            "auth.login",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("create event");
+        sqlx::query("UPDATE security_events SET created_at = $1 WHERE id = $2")
+            .bind(datetime_to_db_text(created_at))
+            .bind(id)
+            .execute(pool)
+            .await
+            .expect("backdate event");
+    }
+
+    #[tokio::test]
+    async fn purge_removes_only_events_outside_retention_window() {
+        let db = setup_db().await;
+        let now = Utc::now();
+        insert_event_at(&db, 1, now - chrono::Duration::days(90)).await;
+        insert_event_at(&db, 2, now - chrono::Duration::days(31)).await;
+        insert_event_at(&db, 3, now - chrono::Duration::days(2)).await;
+        insert_event_at(&db, 4, now).await;
+
+        let cutoff = now - chrono::Duration::days(30);
+        let stale = list_events_older_than(&db, cutoff, 10)
+            .await
+            .expect("list stale");
+        assert_eq!(stale.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let deleted = purge_entries_older_than(&db, cutoff, 10)
+            .await
+            .expect("purge");
+        assert_eq!(deleted, 2);
+
+        let remaining = list_events(&db, None, None, 10).await.expect("list");
+        assert_eq!(
+            remaining.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![4, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_events_removes_listed_ids() {
+        let db = setup_db().await;
+        let now = Utc::now();
+        insert_event_at(&db, 10, now).await;
+        insert_event_at(&db, 11, now).await;
+        insert_event_at(&db, 12, now).await;
+
+        assert_eq!(delete_events(&db, &[]).await.expect("empty delete"), 0);
+        assert_eq!(delete_events(&db, &[10, 12]).await.expect("delete"), 2);
+
+        let remaining = list_events(&db, None, None, 10).await.expect("list");
+        assert_eq!(remaining.iter().map(|e| e.id).collect::<Vec<_>>(), vec![11]);
+    }
+}
@@ -231,6 +231,7 @@ pub async fn update_session_jti(
     Ok(result.rows_affected() > 0)
 }
 
+/// Revoke one session. Its push subscriptions go with it.
 pub async fn revoke_session(
     pool: &DbPool,
     session_id: &str,
@@ -238,6 +239,7 @@ pub async fn revoke_session(
     reason: &str,
     now: DateTime<Utc>,
 ) -> Result<bool, DbError> {
+    let mut tx = pool.begin().await?;
     let result = sqlx::query(
         "UPDATE auth_sessions
          SET revoked_at = $3, revoked_reason = $4
@@ -249,11 +251,19 @@ pub async fn revoke_session(
     .bind(user_id)
     .bind(datetime_to_db_text(now))
     .bind(reason)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
+    sqlx::query("DELETE FROM push_subscriptions WHERE session_id = $1 AND user_id = $2")
+        .bind(session_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
     Ok(result.rows_affected() > 0)
 }
 
+/// Revoke every session of `user_id` but `keep_session_id`, along with
+/// their push subscriptions.
 pub async fn revoke_all_user_sessions_except(
     pool: &DbPool,
     user_id: i64,
@@ -261,6 +271,7 @@ pub async fn revoke_all_user_sessions_except(
     reason: &str,
     now: DateTime<Utc>,
 ) -> Result<u64, DbError> {
+    let mut tx = pool.begin().await?;
     let result = if let Some(keep_id) = keep_session_id {
         sqlx::query(
             "UPDATE auth_sessions
@@ -273,7 +284,7 @@ pub async fn revoke_all_user_sessions_except(
         .bind(keep_id)
         .bind(datetime_to_db_text(now))
         .bind(reason)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?
     } else {
         sqlx::query(
@@ -285,21 +296,39 @@ pub async fn revoke_all_user_sessions_except(
         .bind(user_id)
         .bind(datetime_to_db_text(now))
         .bind(reason)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?
     };
+    sqlx::query(
+        "DELETE FROM push_subscriptions
+         WHERE user_id = $1
+           AND (session_id IS NULL OR session_id != $2)",
+    )
+    .bind(user_id)
+    .bind(keep_session_id.unwrap_or(""))
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
 
     Ok(result.rows_affected())
 }
 
+/// Whether `jti` is the current access token of a live session. When
+/// `idle_cutoff` is set, sessions not seen since that instant count as expired.
 pub async fn is_access_token_active(
     pool: &DbPool,
     user_id: i64,
     session_id: &str,
     jti: &str,
     now: DateTime<Utc>,
+    idle_cutoff: Option<DateTime<Utc>>,
 ) -> Result<bool, DbError> {
-    let row: Option<(i64,)> = sqlx::query_as(
+    let idle_clause = if idle_cutoff.is_some() {
+        "AND last_seen_at > $5"
+    } else {
+        ""
+    };
+    let sql = format!(
         "SELECT 1
          FROM auth_sessions
          WHERE id = $1
@@ -307,16 +336,39 @@ pub async fn is_access_token_active(
            AND current_jti = $3
            AND revoked_at IS NULL
            AND expires_at > $4
-         LIMIT 1",
+           {idle_clause}
+         LIMIT 1"
+    );
+    let mut query = sqlx::query_as::<_, (i64,)>(&sql)
+        .bind(session_id)
+        .bind(user_id)
+        .bind(jti)
+        .bind(datetime_to_db_text(now));
+    if let Some(cutoff) = idle_cutoff {
+        query = query.bind(datetime_to_db_text(cutoff));
+    }
+    let row = query.fetch_optional(pool).await?;
+
+    Ok(row.is_some())
+}
+
+/// Record activity on a session, writing at most once per `min_interval`.
+pub async fn touch_session(
+    pool: &DbPool,
+    session_id: &str,
+    now: DateTime<Utc>,
+    min_interval: chrono::Duration,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "UPDATE auth_sessions SET last_seen_at = $2
+         WHERE id = $1 AND revoked_at IS NULL AND last_seen_at < $3",
     )
     .bind(session_id)
-    .bind(user_id)
-    .bind(jti)
     .bind(datetime_to_db_text(now))
-    .fetch_optional(pool)
+    .bind(datetime_to_db_text(now - min_interval))
+    .execute(pool)
     .await?;
-
-    Ok(row.is_some())
+    Ok(())
 }
 
 pub async fn purge_expired_sessions(
@@ -383,14 +435,15 @@ mod tests {
         .await
         .expect("create session");
 
-        let active = is_access_token_active(&db, user.id, "sess-1", "jti-1", now)
+        let active = is_access_token_active(&db, user.id, "sess-1", "jti-1", now, None)
             .await
             .expect("active check");
         assert!(active);
 
-        let inactive_wrong_jti = is_access_token_active(&db, user.id, "sess-1", "wrong-jti", now)
-            .await
-            .expect("wrong jti check");
+        let inactive_wrong_jti =
+            is_access_token_active(&db, user.id, "sess-1", "wrong-jti", now, None)
+                .await
+                .expect("wrong jti check");
         assert!(!inactive_wrong_jti);
 
         let revoked = revoke_session(&db, "sess-1", user.id, "test", now)
@@ -398,9 +451,47 @@ mod tests {
             .expect("revoke session");
         assert!(revoked);
 
-        let inactive_revoked = is_access_token_active(&db, user.id, "sess-1", "jti-1", now)
+        let inactive_revoked = is_access_token_active(&db, user.id, "sess-1", "jti-1", now, None)
             .await
             .expect("revoked active check");
         assert!(!inactive_revoked);
     }
+
+    #[tokio::test]
+    async fn idle_cutoff_rejects_sessions_not_seen_recently() {
+        let db = setup_db().await;
+        let user = crate::users::create_user(&db, 7002, "idler", 1, "idler@example.com", "hash")
+            .await
+            .expect("create user");
+        let now = Utc::now();
+        create_session(
+            &db,
+            "sess-idle",
+            user.id,
+            "refresh-hash-idle",
+            "jti-idle",
+            None,
+            None,
+            None,
+            None,
+            now + chrono::Duration::days(30),
+        )
+        .await
+        .expect("create session");
+
+        let later = now + chrono::Duration::hours(2);
+        let cutoff = Some(later - chrono::Duration::hours(1));
+        let idle = is_access_token_active(&db, user.id, "sess-idle", "jti-idle", later, cutoff)
+            .await
+            .expect("idle check");
+        assert!(!idle);
+
+        touch_session(&db, "sess-idle", later, chrono::Duration::minutes(1))
+            .await
+            .expect("touch session");
+        let active = is_access_token_active(&db, user.id, "sess-idle", "jti-idle", later, cutoff)
+            .await
+            .expect("active check");
+        assert!(active);
+    }
 }
@@ -0,0 +1,220 @@
+use crate::{datetime_from_db_text, datetime_to_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct UserTotpRow {
+    pub user_id: i64,
+    /// Base32-encoded shared secret.
+    pub secret: String,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// SHA-256 hashes of the unused recovery codes.
+    pub recovery_codes_hashed: Vec<String>,
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserTotpRow {
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+fn recovery_codes_from_db_text(value: &str) -> Result<Vec<String>, sqlx::Error> {
+    serde_json::from_str(value)
+        .map_err(|e| sqlx::Error::Protocol(format!("invalid recovery code list: {e}")))
+}
+
+fn recovery_codes_to_db_text(codes: &[String]) -> String {
+    serde_json::to_string(codes).unwrap_or_else(|_| "[]".to_string())
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UserTotpRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let confirmed_at_raw: Option<String> = row.try_get("confirmed_at")?;
+        let recovery_raw: String = row.try_get("recovery_codes_hashed")?;
+        let created_at_raw: String = row.try_get("created_at")?;
+        Ok(Self {
+            user_id: row.try_get("user_id")?,
+            secret: row.try_get("secret")?,
+            confirmed_at: confirmed_at_raw
+                .as_deref()
+                .map(datetime_from_db_text)
+                .transpose()?,
+            recovery_codes_hashed: recovery_codes_from_db_text(&recovery_raw)?,
+            last_used_step: row.try_get("last_used_step")?,
+            created_at: datetime_from_db_text(&created_at_raw)?,
+        })
+    }
+}
+
+pub async fn get_totp(pool: &DbPool, user_id: i64) -> Result<Option<UserTotpRow>, DbError> {
+    let row = sqlx::query_as::<_, UserTotpRow>(
+        "SELECT user_id, secret, confirmed_at, recovery_codes_hashed, last_used_step, created_at
+         FROM user_totp
+         WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Start (or restart) enrollment with a fresh secret. Returns `false` when the
+/// user already has confirmed TOTP, which must be disabled first.
+pub async fn begin_enrollment(pool: &DbPool, user_id: i64, secret: &str) -> Result<bool, DbError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM user_totp WHERE user_id = $1 AND confirmed_at IS NULL")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query(
+        "INSERT INTO user_totp (user_id, secret)
+         VALUES ($1, $2)
+         ON CONFLICT (user_id) DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(secret)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Confirm a pending enrollment, storing the recovery code hashes and the
+/// time step of the code that confirmed it.
+pub async fn confirm_enrollment(
+    pool: &DbPool,
+    user_id: i64,
+    recovery_codes_hashed: &[String],
+    step: i64,
+    now: DateTime<Utc>,
+) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "UPDATE user_totp
+         SET confirmed_at = $2, recovery_codes_hashed = $3, last_used_step = $4
+         WHERE user_id = $1 AND confirmed_at IS NULL",
+    )
+    .bind(user_id)
+    .bind(datetime_to_db_text(now))
+    .bind(recovery_codes_to_db_text(recovery_codes_hashed))
+    .bind(step)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record that the code for `step` was used. Returns `false` if a code from
+/// this step or a later one was already accepted, i.e. the code is a replay.
+pub async fn record_used_step(pool: &DbPool, user_id: i64, step: i64) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "UPDATE user_totp
+         SET last_used_step = $2
+         WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove `code_hash` from the user's unused recovery codes. Returns `false`
+/// if it is not one of them (including when it was already used).
+pub async fn consume_recovery_code(
+    pool: &DbPool,
+    user_id: i64,
+    code_hash: &str,
+) -> Result<bool, DbError> {
+    let mut tx = pool.begin().await?;
+    let current: Option<String> = sqlx::query(
+        "SELECT recovery_codes_hashed FROM user_totp
+         WHERE user_id = $1 AND confirmed_at IS NOT NULL",
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .map(|row| row.try_get("recovery_codes_hashed"))
+    .transpose()?;
+    let Some(current) = current else {
+        return Ok(false);
+    };
+    let mut codes = recovery_codes_from_db_text(&current)?;
+    let Some(index) = codes.iter().position(|hash| hash == code_hash) else {
+        return Ok(false);
+    };
+    codes.remove(index);
+    // Compare-and-swap so two concurrent logins cannot both spend the code.
+    let result = sqlx::query(
+        "UPDATE user_totp SET recovery_codes_hashed = $2
+         WHERE user_id = $1 AND recovery_codes_hashed = $3",
+    )
+    .bind(user_id)
+    .bind(recovery_codes_to_db_text(&codes))
+    .bind(&current)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_totp(pool: &DbPool, user_id: i64) -> Result<bool, DbError> {
+    let result = sqlx::query("DELETE FROM user_totp WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_db() -> DbPool {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-db-totp-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = crate::create_pool(&db_url, 1).await.expect("pool");
+        crate::run_migrations(&pool).await.expect("migrations");
+        pool
+    }
+
+    #[tokio::test]
+    async fn enrollment_steps_and_recovery_codes_are_single_use() {
+        let db = setup_db().await;
+        let user = crate::users::create_user(&db, 9101, "totp", 1, "totp@example.com", "hash")
+            .await
+            .expect("create user");
+
+        assert!(begin_enrollment(&db, user.id, "SECRETA").await.unwrap());
+        // A pending enrollment can be restarted with a new secret.
+        assert!(begin_enrollment(&db, user.id, "SECRETB").await.unwrap());
+        let pending = get_totp(&db, user.id).await.unwrap().expect("pending");
+        assert_eq!(pending.secret, "SECRETB");
+        assert!(!pending.is_confirmed());
+
+        let hashes = vec!["h1".to_string(), "h2".to_string()];
+        assert!(confirm_enrollment(&db, user.id, &hashes, 100, Utc::now())
+            .await
+            .unwrap());
+        assert!(!begin_enrollment(&db, user.id, "SECRETC").await.unwrap());
+
+        assert!(!record_used_step(&db, user.id, 100).await.unwrap());
+        assert!(record_used_step(&db, user.id, 101).await.unwrap());
+        assert!(!record_used_step(&db, user.id, 100).await.unwrap());
+
+        assert!(consume_recovery_code(&db, user.id, "h1").await.unwrap());
+        assert!(!consume_recovery_code(&db, user.id, "h1").await.unwrap());
+        let row = get_totp(&db, user.id).await.unwrap().expect("row");
+        assert_eq!(row.recovery_codes_hashed, vec!["h2".to_string()]);
+
+        assert!(delete_totp(&db, user.id).await.unwrap());
+        assert!(get_totp(&db, user.id).await.unwrap().is_none());
+    }
+}
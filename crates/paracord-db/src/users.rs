@@ -1,4 +1,7 @@
-use crate::{bool_from_any_row, datetime_from_db_text, json_from_db_text, DbError, DbPool};
+use crate::{
+    bool_from_any_row, datetime_from_db_text, datetime_to_db_text, json_from_db_text, DbError,
+    DbPool,
+};
 use chrono::{DateTime, Utc};
 use sqlx::Row;
 
@@ -37,6 +40,8 @@ pub struct UserAuthRow {
     pub flags: i32,
     pub created_at: DateTime<Utc>,
     pub public_key: Option<String>,
+    /// `None` while the account still has to verify its email address.
+    pub email_verified_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +52,9 @@ pub struct UserSettingsRow {
     pub locale: String,
     pub message_display: String,
     pub crypto_auth_enabled: bool,
+    /// Hide mutual guilds on this user's profile from viewers who are not
+    /// their friends.
+    pub hide_mutual_guilds: bool,
     pub notifications: serde_json::Value,
     pub keybinds: serde_json::Value,
     pub updated_at: DateTime<Utc>,
@@ -75,6 +83,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UserRow {
 impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UserAuthRow {
     fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
         let created_at_raw: String = row.try_get("created_at")?;
+        let email_verified_at_raw: Option<String> = row.try_get("email_verified_at")?;
         Ok(Self {
             id: row.try_get("id")?,
             username: row.try_get("username")?,
@@ -89,6 +98,10 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UserAuthRow {
             flags: row.try_get("flags")?,
             created_at: datetime_from_db_text(&created_at_raw)?,
             public_key: row.try_get("public_key")?,
+            email_verified_at: email_verified_at_raw
+                .as_deref()
+                .map(datetime_from_db_text)
+                .transpose()?,
         })
     }
 }
@@ -105,6 +118,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UserSettingsRow {
             locale: row.try_get("locale")?,
             message_display: row.try_get("message_display")?,
             crypto_auth_enabled: bool_from_any_row(row, "crypto_auth_enabled")?,
+            hide_mutual_guilds: bool_from_any_row(row, "hide_mutual_guilds")?,
             notifications: json_from_db_text(&notifications_raw)?,
             keybinds: json_from_db_text(&keybinds_raw)?,
             updated_at: datetime_from_db_text(&updated_at_raw)?,
@@ -112,6 +126,61 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UserSettingsRow {
     }
 }
 
+/// Highest discriminator handed out to users.
+pub const MAX_DISCRIMINATOR: i16 = 9999;
+
+/// Lowest discriminator not yet used by `username`, or `None` when every
+/// value up to [`MAX_DISCRIMINATOR`] is taken.
+///
+/// A concurrent signup can claim the same value before the caller inserts,
+/// so callers retry when the insert fails with [`is_discriminator_conflict`].
+pub async fn allocate_discriminator(pool: &DbPool, username: &str) -> Result<Option<i16>, DbError> {
+    let taken: Vec<(i16,)> = sqlx::query_as(
+        "SELECT discriminator FROM users WHERE username = $1 ORDER BY discriminator ASC",
+    )
+    .bind(username)
+    .fetch_all(pool)
+    .await?;
+
+    let mut candidate: i16 = 0;
+    for (discriminator,) in taken {
+        if discriminator > candidate {
+            break;
+        }
+        if discriminator == candidate {
+            if candidate == MAX_DISCRIMINATOR {
+                return Ok(None);
+            }
+            candidate += 1;
+        }
+    }
+    Ok(Some(candidate))
+}
+
+/// True when an insert failed because the `(username, discriminator)` pair
+/// was claimed first by someone else.
+pub fn is_discriminator_conflict(err: &DbError) -> bool {
+    let DbError::Sqlx(sqlx::Error::Database(db_err)) = err else {
+        return false;
+    };
+    let code_binding = db_err.code();
+    let code = code_binding.as_deref().unwrap_or_default();
+    let is_unique = code == "23505" || code == "2067" || code == "1555";
+    is_unique && db_err.message().contains("discriminator")
+}
+
+/// True when an insert failed because another account already holds the
+/// handle (see `idx_users_handle`).
+pub fn is_handle_conflict(err: &DbError) -> bool {
+    let DbError::Sqlx(sqlx::Error::Database(db_err)) = err else {
+        return false;
+    };
+    let code_binding = db_err.code();
+    let code = code_binding.as_deref().unwrap_or_default();
+    let is_unique = code == "23505" || code == "2067" || code == "1555";
+    is_unique && db_err.message().contains("idx_users_handle")
+}
+
 pub async fn create_user(
     pool: &DbPool,
     id: i64,
@@ -122,8 +191,8 @@ pub async fn create_user(
 ) -> Result<UserRow, DbError> {
     let normalized_email = normalize_email(email);
     let row = sqlx::query_as::<_, UserRow>(
-        "INSERT INTO users (id, username, discriminator, email, password_hash)
-         VALUES ($1, $2, $3, $4, $5)
+        "INSERT INTO users (id, username, discriminator, email, password_hash, email_verified_at)
+         VALUES ($1, $2, $3, $4, $5, datetime('now'))
          RETURNING id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key",
     )
     .bind(id)
@@ -138,6 +207,11 @@ pub async fn create_user(
 
 /// Create a user and atomically promote to admin if this is the first user.
 /// Uses a transaction to prevent registration races.
+///
+/// With `require_email_verification` the account is left unverified, except
+/// for the first user, who has nobody to ask for a verification link.
+/// `handle` registers `username` as a case-insensitively unique handle.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_user_as_first_admin(
     pool: &DbPool,
     id: i64,
@@ -146,6 +220,8 @@ pub async fn create_user_as_first_admin(
     email: &str,
     password_hash: &str,
     admin_flag: i32,
+    require_email_verification: bool,
+    handle: bool,
 ) -> Result<UserRow, DbError> {
     let normalized_email = normalize_email(email);
     let mut tx = pool.begin().await?;
@@ -153,10 +229,15 @@ pub async fn create_user_as_first_admin(
         .fetch_one(&mut *tx)
         .await?;
     let flags = if count == 0 { admin_flag } else { 0 };
+    let email_verified_at = if require_email_verification && count > 0 {
+        None
+    } else {
+        Some(datetime_to_db_text(Utc::now()))
+    };
 
     let row = sqlx::query_as::<_, UserRow>(
-        "INSERT INTO users (id, username, discriminator, email, password_hash, flags)
-         VALUES ($1, $2, $3, $4, $5, $6)
+        "INSERT INTO users (id, username, discriminator, email, password_hash, flags, email_verified_at, username_is_handle)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
          RETURNING id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key",
     )
     .bind(id)
@@ -165,6 +246,8 @@ pub async fn create_user_as_first_admin(
     .bind(normalized_email)
     .bind(password_hash)
     .bind(flags)
+    .bind(email_verified_at)
+    .bind(handle)
     .fetch_one(&mut *tx)
     .await?;
 
@@ -172,6 +255,152 @@ pub async fn create_user_as_first_admin(
     Ok(row)
 }
 
+/// Store a one-time email verification token for `user_id`. Only the
+/// SHA-256 of the token is kept.
+pub async fn create_email_verification(
+    pool: &DbPool,
+    user_id: i64,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "INSERT INTO email_verifications (token_hash, user_id, expires_at)
+         VALUES ($1, $2, $3)",
+    )
+    .bind(token_hash)
+    .bind(user_id)
+    .bind(datetime_to_db_text(expires_at))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Consume a verification token and mark its user's email as verified.
+/// Returns the user id, or `None` if the token is unknown, already used or
+/// expired. Every outstanding token for that user is dropped.
+pub async fn consume_email_verification(
+    pool: &DbPool,
+    token_hash: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<i64>, DbError> {
+    let mut tx = pool.begin().await?;
+    let row: Option<(i64, String)> = sqlx::query_as(
+        "DELETE FROM email_verifications WHERE token_hash = $1
+         RETURNING user_id, expires_at",
+    )
+    .bind(token_hash)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((user_id, expires_at_raw)) = row else {
+        return Ok(None);
+    };
+    if datetime_from_db_text(&expires_at_raw)? <= now {
+        tx.commit().await?;
+        return Ok(None);
+    }
+
+    sqlx::query(
+        "UPDATE users SET email_verified_at = COALESCE(email_verified_at, $2)
+         WHERE id = $1",
+    )
+    .bind(user_id)
+    .bind(datetime_to_db_text(now))
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM email_verifications WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(Some(user_id))
+}
+
+/// Drop every outstanding verification token for `user_id`.
+pub async fn delete_user_email_verifications(pool: &DbPool, user_id: i64) -> Result<u64, DbError> {
+    let result = sqlx::query("DELETE FROM email_verifications WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete verification tokens that expired at or before `now`.
+pub async fn purge_expired_email_verifications(
+    pool: &DbPool,
+    now: DateTime<Utc>,
+) -> Result<u64, DbError> {
+    let result = sqlx::query("DELETE FROM email_verifications WHERE expires_at <= $1")
+        .bind(datetime_to_db_text(now))
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Store a single-use password reset token for `user_id`. Only the SHA-256
+/// of the token is kept.
+pub async fn create_password_reset(
+    pool: &DbPool,
+    user_id: i64,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), DbError> {
+    sqlx::query(
+        "INSERT INTO password_resets (token_hash, user_id, expires_at)
+         VALUES ($1, $2, $3)",
+    )
+    .bind(token_hash)
+    .bind(user_id)
+    .bind(datetime_to_db_text(expires_at))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Consume a password reset token. Returns the user id, or `None` if the
+/// token is unknown, already used or expired. Every outstanding reset token
+/// for that user is dropped.
+pub async fn consume_password_reset(
+    pool: &DbPool,
+    token_hash: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<i64>, DbError> {
+    let mut tx = pool.begin().await?;
+    let row: Option<(i64, String)> = sqlx::query_as(
+        "DELETE FROM password_resets WHERE token_hash = $1
+         RETURNING user_id, expires_at",
+    )
+    .bind(token_hash)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((user_id, expires_at_raw)) = row else {
+        return Ok(None);
+    };
+    if datetime_from_db_text(&expires_at_raw)? <= now {
+        tx.commit().await?;
+        return Ok(None);
+    }
+
+    sqlx::query("DELETE FROM password_resets WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(Some(user_id))
+}
+
+/// Delete password reset tokens that expired at or before `now`. Returns the
+/// number of rows removed.
+pub async fn purge_expired_password_resets(
+    pool: &DbPool,
+    now: DateTime<Utc>,
+) -> Result<u64, DbError> {
+    let result = sqlx::query("DELETE FROM password_resets WHERE expires_at <= $1")
+        .bind(datetime_to_db_text(now))
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
 pub async fn get_user_by_id(pool: &DbPool, id: i64) -> Result<Option<UserRow>, DbError> {
     let row = sqlx::query_as::<_, UserRow>(
         "SELECT id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key
@@ -183,10 +412,28 @@ pub async fn get_user_by_id(pool: &DbPool, id: i64) -> Result<Option<UserRow>, D
     Ok(row)
 }
 
+/// Users with any of the given ids; unknown ids are skipped.
+pub async fn get_users_by_ids(pool: &DbPool, ids: &[i64]) -> Result<Vec<UserRow>, DbError> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (1..=ids.len()).map(|i| format!("${i}")).collect();
+    let sql = format!(
+        "SELECT id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key
+         FROM users WHERE id IN ({})",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, UserRow>(&sql);
+    for id in ids {
+        query = query.bind(*id);
+    }
+    Ok(query.fetch_all(pool).await?)
+}
+
 pub async fn get_user_by_email(pool: &DbPool, email: &str) -> Result<Option<UserAuthRow>, DbError> {
     let normalized_email = normalize_email(email);
     let row = sqlx::query_as::<_, UserAuthRow>(
-        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key
+        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key, email_verified_at
          FROM users WHERE lower(email) = $1",
     )
     .bind(normalized_email)
@@ -197,7 +444,7 @@ pub async fn get_user_by_email(pool: &DbPool, email: &str) -> Result<Option<User
 
 pub async fn get_user_auth_by_id(pool: &DbPool, id: i64) -> Result<Option<UserAuthRow>, DbError> {
     let row = sqlx::query_as::<_, UserAuthRow>(
-        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key
+        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key, email_verified_at
          FROM users WHERE id = $1",
     )
     .bind(id)
@@ -229,7 +476,7 @@ pub async fn get_user_auth_by_username(
 ) -> Result<Option<UserAuthRow>, DbError> {
     let normalized_username = username.trim().to_ascii_lowercase();
     let row = sqlx::query_as::<_, UserAuthRow>(
-        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key
+        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key, email_verified_at
          FROM users WHERE lower(username) = $1 AND discriminator = $2",
     )
     .bind(normalized_username)
@@ -239,18 +486,21 @@ pub async fn get_user_auth_by_username(
     Ok(row)
 }
 
+/// Oldest user whose username matches `username` case-insensitively. In
+/// handle mode this is the only user with that name.
 pub async fn get_user_by_username_only(
     pool: &DbPool,
     username: &str,
 ) -> Result<Option<UserRow>, DbError> {
+    let normalized_username = username.trim().to_ascii_lowercase();
     let row = sqlx::query_as::<_, UserRow>(
         "SELECT id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key
          FROM users
-         WHERE username = $1
+         WHERE lower(username) = $1
          ORDER BY created_at ASC
          LIMIT 1",
     )
-    .bind(username)
+    .bind(normalized_username)
     .fetch_optional(pool)
     .await?;
     Ok(row)
@@ -262,7 +512,7 @@ pub async fn get_user_auth_by_username_only(
 ) -> Result<Option<UserAuthRow>, DbError> {
     let normalized_username = username.trim().to_ascii_lowercase();
     let row = sqlx::query_as::<_, UserAuthRow>(
-        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key
+        "SELECT id, username, discriminator, email, password_hash, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key, email_verified_at
          FROM users
          WHERE lower(username) = $1
          ORDER BY created_at ASC
@@ -281,16 +531,18 @@ pub async fn update_user(
     bio: Option<&str>,
     avatar_hash: Option<&str>,
 ) -> Result<UserRow, DbError> {
-    let row = sqlx::query_as::<_, UserRow>(
-        "UPDATE users SET display_name = COALESCE($2, display_name), bio = COALESCE($3, bio), avatar_hash = COALESCE($4, avatar_hash), updated_at = datetime('now')
-         WHERE id = $1
-         RETURNING id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key",
-    )
-    .bind(id)
-    .bind(display_name)
-    .bind(bio)
-    .bind(avatar_hash)
-    .fetch_one(pool)
+    let row = crate::retry_on_busy(|| {
+        sqlx::query_as::<_, UserRow>(
+            "UPDATE users SET display_name = COALESCE($2, display_name), bio = COALESCE($3, bio), avatar_hash = COALESCE($4, avatar_hash), updated_at = datetime('now')
+             WHERE id = $1
+             RETURNING id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key",
+        )
+        .bind(id)
+        .bind(display_name)
+        .bind(bio)
+        .bind(avatar_hash)
+        .fetch_one(pool)
+    })
     .await?;
     Ok(row)
 }
@@ -300,7 +552,7 @@ pub async fn get_user_settings(
     user_id: i64,
 ) -> Result<Option<UserSettingsRow>, DbError> {
     let row = sqlx::query_as::<_, UserSettingsRow>(
-        "SELECT user_id, theme, custom_css, locale, message_display, CASE WHEN crypto_auth_enabled THEN 1 ELSE 0 END AS crypto_auth_enabled, notifications, keybinds, updated_at
+        "SELECT user_id, theme, custom_css, locale, message_display, CASE WHEN crypto_auth_enabled THEN 1 ELSE 0 END AS crypto_auth_enabled, CASE WHEN hide_mutual_guilds THEN 1 ELSE 0 END AS hide_mutual_guilds, notifications, keybinds, updated_at
          FROM user_settings WHERE user_id = $1",
     )
     .bind(user_id)
@@ -309,6 +561,31 @@ pub async fn get_user_settings(
     Ok(row)
 }
 
+/// The `notifications` settings blob of each of `user_ids` that has saved
+/// settings.
+pub async fn get_notification_settings_for_users(
+    pool: &DbPool,
+    user_ids: &[i64],
+) -> Result<Vec<(i64, serde_json::Value)>, DbError> {
+    const CHUNK: usize = 500;
+    let mut out = Vec::new();
+    for chunk in user_ids.chunks(CHUNK) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("${}", i)).collect();
+        let sql = format!(
+            "SELECT user_id, notifications FROM user_settings WHERE user_id IN ({})",
+            placeholders.join(", ")
+        );
+        let mut query = sqlx::query_as::<_, (i64, String)>(&sql);
+        for user_id in chunk {
+            query = query.bind(user_id);
+        }
+        for (user_id, raw) in query.fetch_all(pool).await? {
+            out.push((user_id, json_from_db_text(&raw)?));
+        }
+    }
+    Ok(out)
+}
+
 pub async fn count_users(pool: &DbPool) -> Result<i64, DbError> {
     let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
         .fetch_one(pool)
@@ -364,6 +641,7 @@ pub async fn upsert_user_settings(
     message_display: &str,
     custom_css: Option<&str>,
     crypto_auth_enabled: Option<bool>,
+    hide_mutual_guilds: Option<bool>,
     notifications: Option<&serde_json::Value>,
     keybinds: Option<&serde_json::Value>,
 ) -> Result<UserSettingsRow, DbError> {
@@ -380,18 +658,19 @@ pub async fn upsert_user_settings(
         .transpose()
         .map_err(|e| DbError::Sqlx(sqlx::Error::Protocol(format!("invalid keybinds json: {e}"))))?;
     let row = sqlx::query_as::<_, UserSettingsRow>(
-        "INSERT INTO user_settings (user_id, theme, locale, message_display, custom_css, crypto_auth_enabled, notifications, keybinds)
-         VALUES ($1, $2, $3, $4, $5, COALESCE($6, FALSE), COALESCE($7, '{}'), COALESCE($8, '{}'))
+        "INSERT INTO user_settings (user_id, theme, locale, message_display, custom_css, crypto_auth_enabled, hide_mutual_guilds, notifications, keybinds)
+         VALUES ($1, $2, $3, $4, $5, COALESCE($6, FALSE), COALESCE($7, FALSE), COALESCE($8, '{}'), COALESCE($9, '{}'))
          ON CONFLICT (user_id) DO UPDATE SET
             theme = $2,
             locale = $3,
             message_display = $4,
             custom_css = $5,
             crypto_auth_enabled = COALESCE($6, user_settings.crypto_auth_enabled),
-            notifications = COALESCE($7, user_settings.notifications),
-            keybinds = COALESCE($8, user_settings.keybinds),
+            hide_mutual_guilds = COALESCE($7, user_settings.hide_mutual_guilds),
+            notifications = COALESCE($8, user_settings.notifications),
+            keybinds = COALESCE($9, user_settings.keybinds),
             updated_at = datetime('now')
-         RETURNING user_id, theme, custom_css, locale, message_display, CASE WHEN crypto_auth_enabled THEN 1 ELSE 0 END AS crypto_auth_enabled, notifications, keybinds, updated_at",
+         RETURNING user_id, theme, custom_css, locale, message_display, CASE WHEN crypto_auth_enabled THEN 1 ELSE 0 END AS crypto_auth_enabled, CASE WHEN hide_mutual_guilds THEN 1 ELSE 0 END AS hide_mutual_guilds, notifications, keybinds, updated_at",
     )
     .bind(user_id)
     .bind(theme)
@@ -399,6 +678,7 @@ pub async fn upsert_user_settings(
     .bind(message_display)
     .bind(custom_css)
     .bind(crypto_auth_enabled)
+    .bind(hide_mutual_guilds)
     .bind(notifications)
     .bind(keybinds)
     .fetch_one(pool)
@@ -548,13 +828,16 @@ pub async fn create_user_from_pubkey(
 }
 
 /// Create a pubkey-auth user and atomically promote to admin if first user.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_user_from_pubkey_as_first_admin(
     pool: &DbPool,
     id: i64,
     public_key: &str,
     username: &str,
+    discriminator: i16,
     display_name: Option<&str>,
     admin_flag: i32,
+    handle: bool,
 ) -> Result<UserRow, DbError> {
     let mut tx = pool.begin().await?;
     let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
@@ -564,16 +847,18 @@ pub async fn create_user_from_pubkey_as_first_admin(
     let placeholder_email = format!("{}@pubkey", public_key);
 
     let row = sqlx::query_as::<_, UserRow>(
-        "INSERT INTO users (id, username, discriminator, email, password_hash, display_name, public_key, flags)
-         VALUES ($1, $2, 0, $3, '', $4, $5, $6)
+        "INSERT INTO users (id, username, discriminator, email, password_hash, display_name, public_key, flags, username_is_handle, email_verified_at)
+         VALUES ($1, $2, $3, $4, '', $5, $6, $7, $8, datetime('now'))
          RETURNING id, username, discriminator, email, display_name, avatar_hash, banner_hash, bio, accent_color, flags, created_at, public_key",
     )
     .bind(id)
     .bind(username)
+    .bind(discriminator)
     .bind(&placeholder_email)
     .bind(display_name)
     .bind(public_key)
     .bind(flags)
+    .bind(handle)
     .fetch_one(&mut *tx)
     .await?;
 
@@ -609,19 +894,192 @@ mod tests {
     #[tokio::test]
     async fn test_create_user_as_first_admin_sets_only_first_user_admin() {
         let pool = test_pool().await;
-        let first =
-            create_user_as_first_admin(&pool, 2, "first", 1, "first@example.com", "hash", 1)
-                .await
-                .unwrap();
-        let second =
-            create_user_as_first_admin(&pool, 3, "second", 1, "second@example.com", "hash", 1)
-                .await
-                .unwrap();
+        let first = create_user_as_first_admin(
+            &pool,
+            2,
+            "first",
+            1,
+            "first@example.com",
+            "hash",
+            1,
+            false,
+            false,
+        )
+        .await
+        .unwrap();
+        let second = create_user_as_first_admin(
+            &pool,
+            3,
+            "second",
+            1,
+            "second@example.com",
+            "hash",
+            1,
+            false,
+            false,
+        )
+        .await
+        .unwrap();
 
         assert_eq!(first.flags & 1, 1);
         assert_eq!(second.flags & 1, 0);
     }
 
+    #[tokio::test]
+    async fn handles_are_unique_regardless_of_case() {
+        let pool = test_pool().await;
+        create_user_as_first_admin(
+            &pool,
+            2,
+            "Alice",
+            0,
+            "a@example.com",
+            "hash",
+            1,
+            false,
+            true,
+        )
+        .await
+        .unwrap();
+        let err = create_user_as_first_admin(
+            &pool,
+            3,
+            "alice",
+            0,
+            "b@example.com",
+            "hash",
+            1,
+            false,
+            true,
+        )
+        .await
+        .expect_err("handle is taken");
+        assert!(is_handle_conflict(&err), "{err}");
+        assert!(!is_discriminator_conflict(&err));
+
+        // Accounts from discriminator mode are not handles and may share a name.
+        create_user_as_first_admin(
+            &pool,
+            4,
+            "alice",
+            0,
+            "c@example.com",
+            "hash",
+            1,
+            false,
+            false,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_email_verification_token_is_single_use() {
+        let pool = test_pool().await;
+        create_user_as_first_admin(
+            &pool,
+            2,
+            "first",
+            1,
+            "first@example.com",
+            "hash",
+            1,
+            true,
+            false,
+        )
+        .await
+        .unwrap();
+        create_user_as_first_admin(
+            &pool,
+            3,
+            "second",
+            1,
+            "second@example.com",
+            "hash",
+            1,
+            true,
+            false,
+        )
+        .await
+        .unwrap();
+        let first = get_user_auth_by_id(&pool, 2).await.unwrap().unwrap();
+        let second = get_user_auth_by_id(&pool, 3).await.unwrap().unwrap();
+        assert!(first.email_verified_at.is_some());
+        assert!(second.email_verified_at.is_none());
+
+        let now = Utc::now();
+        create_email_verification(&pool, 3, "expired", now - chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        create_email_verification(&pool, 3, "valid", now + chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        create_email_verification(&pool, 3, "stale", now - chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            purge_expired_email_verifications(&pool, now).await.unwrap(),
+            2
+        );
+        assert_eq!(
+            consume_email_verification(&pool, "expired", now)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            consume_email_verification(&pool, "valid", now)
+                .await
+                .unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            consume_email_verification(&pool, "valid", now)
+                .await
+                .unwrap(),
+            None
+        );
+        let second = get_user_auth_by_id(&pool, 3).await.unwrap().unwrap();
+        assert!(second.email_verified_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_token_is_single_use() {
+        let pool = test_pool().await;
+        create_user(&pool, 4, "resetter", 1, "resetter@example.com", "hash")
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        create_password_reset(&pool, 4, "expired", now - chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        create_password_reset(&pool, 4, "first", now + chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        create_password_reset(&pool, 4, "second", now + chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(purge_expired_password_resets(&pool, now).await.unwrap(), 1);
+        assert_eq!(
+            consume_password_reset(&pool, "expired", now).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            consume_password_reset(&pool, "first", now).await.unwrap(),
+            Some(4)
+        );
+        assert_eq!(
+            consume_password_reset(&pool, "first", now).await.unwrap(),
+            None
+        );
+        // Using one token invalidates the user's other outstanding tokens.
+        assert_eq!(
+            consume_password_reset(&pool, "second", now).await.unwrap(),
+            None
+        );
+    }
+
     #[tokio::test]
     async fn test_create_user_duplicate_email_fails() {
         let pool = test_pool().await;
@@ -642,6 +1100,70 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn allocate_discriminator_picks_lowest_free_value() {
+        let pool = test_pool().await;
+        assert_eq!(allocate_discriminator(&pool, "sam").await.unwrap(), Some(0));
+        create_user(&pool, 1, "sam", 0, "a@example.com", "hash")
+            .await
+            .unwrap();
+        create_user(&pool, 2, "sam", 2, "b@example.com", "hash")
+            .await
+            .unwrap();
+        assert_eq!(allocate_discriminator(&pool, "sam").await.unwrap(), Some(1));
+        assert_eq!(
+            allocate_discriminator(&pool, "other").await.unwrap(),
+            Some(0)
+        );
+
+        let err = create_user(&pool, 3, "sam", 2, "c@example.com", "hash")
+            .await
+            .unwrap_err();
+        assert!(is_discriminator_conflict(&err));
+        let err = create_user(&pool, 4, "kim", 0, "a@example.com", "hash")
+            .await
+            .unwrap_err();
+        assert!(!is_discriminator_conflict(&err));
+    }
+
+    #[tokio::test]
+    async fn concurrent_allocation_yields_distinct_discriminators() {
+        let pool = test_pool().await;
+        let mut handles = Vec::new();
+        for i in 0..8_i64 {
+            let pool = pool.clone();
+            handles.push(tokio::spawn(async move {
+                loop {
+                    let discriminator = allocate_discriminator(&pool, "crowd")
+                        .await
+                        .unwrap()
+                        .expect("free discriminator");
+                    tokio::task::yield_now().await;
+                    match create_user(
+                        &pool,
+                        100 + i,
+                        "crowd",
+                        discriminator,
+                        &format!("crowd{i}@example.com"),
+                        "hash",
+                    )
+                    .await
+                    {
+                        Ok(user) => break user.discriminator,
+                        Err(err) if is_discriminator_conflict(&err) => continue,
+                        Err(err) => panic!("unexpected error: {err}"),
+                    }
+                }
+            }));
+        }
+        let mut discriminators = Vec::new();
+        for handle in handles {
+            discriminators.push(handle.await.unwrap());
+        }
+        discriminators.sort_unstable();
+        assert_eq!(discriminators, (0..8).collect::<Vec<i16>>());
+    }
+
     #[tokio::test]
     async fn test_get_user_by_id() {
         let pool = test_pool().await;
@@ -850,17 +1372,27 @@ mod tests {
     #[tokio::test]
     async fn test_create_user_from_pubkey_as_first_admin_sets_only_first_user_admin() {
         let pool = test_pool().await;
-        let first =
-            create_user_from_pubkey_as_first_admin(&pool, 92, "aabbccddeeff", "pub-first", None, 1)
-                .await
-                .unwrap();
+        let first = create_user_from_pubkey_as_first_admin(
+            &pool,
+            92,
+            "aabbccddeeff",
+            "pub-first",
+            0,
+            None,
+            1,
+            false,
+        )
+        .await
+        .unwrap();
         let second = create_user_from_pubkey_as_first_admin(
             &pool,
             93,
             "001122334455",
             "pub-second",
+            0,
             None,
             1,
+            false,
         )
         .await
         .unwrap();
@@ -875,20 +1407,32 @@ mod tests {
         create_user(&pool, 95, "settings_u", 1, "s@example.com", "h")
             .await
             .unwrap();
-        let settings =
-            upsert_user_settings(&pool, 95, "dark", "en-US", "cozy", None, None, None, None)
-                .await
-                .unwrap();
+        let settings = upsert_user_settings(
+            &pool, 95, "dark", "en-US", "cozy", None, None, None, None, None,
+        )
+        .await
+        .unwrap();
         assert_eq!(settings.theme, "dark");
         assert_eq!(settings.locale, "en-US");
+        assert!(!settings.hide_mutual_guilds);
 
         // Upsert again to update
         let updated = upsert_user_settings(
-            &pool, 95, "light", "en-GB", "compact", None, None, None, None,
+            &pool,
+            95,
+            "light",
+            "en-GB",
+            "compact",
+            None,
+            None,
+            Some(true),
+            None,
+            None,
         )
         .await
         .unwrap();
         assert_eq!(updated.theme, "light");
+        assert!(updated.hide_mutual_guilds);
     }
 
     #[tokio::test]
@@ -1,6 +1,16 @@
 use crate::{bool_from_any_row, DbError, DbPool};
 use sqlx::Row;
 
+/// Columns read into [`VoiceStateRow`] from `voice_states vs`. The Any driver
+/// cannot decode SQLite `BOOLEAN` columns, so the flags come back as integers.
+const VOICE_STATE_COLUMNS: &str = "vs.user_id, vs.space_id, vs.channel_id, vs.session_id, \
+     CASE WHEN vs.self_mute THEN 1 ELSE 0 END AS self_mute, \
+     CASE WHEN vs.self_deaf THEN 1 ELSE 0 END AS self_deaf, \
+     CASE WHEN vs.self_stream THEN 1 ELSE 0 END AS self_stream, \
+     CASE WHEN vs.self_video THEN 1 ELSE 0 END AS self_video, \
+     CASE WHEN vs.suppress THEN 1 ELSE 0 END AS suppress, \
+     CASE WHEN vs.priority_speaker THEN 1 ELSE 0 END AS priority_speaker";
+
 #[derive(Debug, Clone)]
 pub struct VoiceStateRow {
     pub user_id: i64,
@@ -12,6 +22,7 @@ pub struct VoiceStateRow {
     pub self_stream: bool,
     pub self_video: bool,
     pub suppress: bool,
+    pub priority_speaker: bool,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for VoiceStateRow {
@@ -26,6 +37,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for VoiceStateRow {
             self_stream: bool_from_any_row(row, "self_stream")?,
             self_video: bool_from_any_row(row, "self_video")?,
             suppress: bool_from_any_row(row, "suppress")?,
+            priority_speaker: bool_from_any_row(row, "priority_speaker")?,
         })
     }
 }
@@ -52,22 +64,26 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for VoiceSessionRow {
     }
 }
 
+/// Record that a user joined a voice channel. `priority_speaker` reflects
+/// whether they hold PRIORITY_SPEAKER in that channel at join time.
 pub async fn upsert_voice_state(
     pool: &DbPool,
     user_id: i64,
     space_id: Option<i64>,
     channel_id: i64,
     session_id: &str,
+    priority_speaker: bool,
 ) -> Result<(), DbError> {
     sqlx::query(
-        "INSERT INTO voice_states (user_id, space_id, channel_id, session_id)
-         VALUES ($1, $2, $3, $4)
-         ON CONFLICT (user_id) DO UPDATE SET space_id = $2, channel_id = $3, session_id = $4",
+        "INSERT INTO voice_states (user_id, space_id, channel_id, session_id, priority_speaker)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (user_id) DO UPDATE SET space_id = $2, channel_id = $3, session_id = $4, priority_speaker = $5",
     )
     .bind(user_id)
     .bind(space_id)
     .bind(channel_id)
     .bind(session_id)
+    .bind(priority_speaker)
     .execute(pool)
     .await?;
     Ok(())
@@ -77,13 +93,11 @@ pub async fn get_channel_voice_states(
     pool: &DbPool,
     channel_id: i64,
 ) -> Result<Vec<VoiceStateRow>, DbError> {
-    let rows = sqlx::query_as::<_, VoiceStateRow>(
-        "SELECT user_id, space_id, channel_id, session_id, self_mute, self_deaf, self_stream, self_video, suppress
-         FROM voice_states WHERE channel_id = $1"
-    )
-    .bind(channel_id)
-    .fetch_all(pool)
-    .await?;
+    let sql = format!("SELECT {VOICE_STATE_COLUMNS} FROM voice_states vs WHERE vs.channel_id = $1");
+    let rows = sqlx::query_as::<_, VoiceStateRow>(&sql)
+        .bind(channel_id)
+        .fetch_all(pool)
+        .await?;
     Ok(rows)
 }
 
@@ -92,14 +106,15 @@ pub async fn get_user_voice_state(
     user_id: i64,
     space_id: Option<i64>,
 ) -> Result<Option<VoiceStateRow>, DbError> {
-    let row = sqlx::query_as::<_, VoiceStateRow>(
-        "SELECT user_id, space_id, channel_id, session_id, self_mute, self_deaf, self_stream, self_video, suppress
-         FROM voice_states WHERE user_id = $1 AND COALESCE(space_id, 0) = COALESCE($2, 0)"
-    )
-    .bind(user_id)
-    .bind(space_id)
-    .fetch_optional(pool)
-    .await?;
+    let sql = format!(
+        "SELECT {VOICE_STATE_COLUMNS} FROM voice_states vs
+         WHERE vs.user_id = $1 AND COALESCE(vs.space_id, 0) = COALESCE($2, 0)"
+    );
+    let row = sqlx::query_as::<_, VoiceStateRow>(&sql)
+        .bind(user_id)
+        .bind(space_id)
+        .fetch_optional(pool)
+        .await?;
     Ok(row)
 }
 
@@ -123,13 +138,11 @@ pub async fn get_all_user_voice_states(
     pool: &DbPool,
     user_id: i64,
 ) -> Result<Vec<VoiceStateRow>, DbError> {
-    let rows = sqlx::query_as::<_, VoiceStateRow>(
-        "SELECT user_id, space_id, channel_id, session_id, self_mute, self_deaf, self_stream, self_video, suppress
-         FROM voice_states WHERE user_id = $1",
-    )
-    .bind(user_id)
-    .fetch_all(pool)
-    .await?;
+    let sql = format!("SELECT {VOICE_STATE_COLUMNS} FROM voice_states vs WHERE vs.user_id = $1");
+    let rows = sqlx::query_as::<_, VoiceStateRow>(&sql)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
     Ok(rows)
 }
 
@@ -191,6 +204,7 @@ pub struct VoiceStateWithUser {
     pub self_stream: bool,
     pub self_video: bool,
     pub suppress: bool,
+    pub priority_speaker: bool,
     pub username: String,
     pub avatar_hash: Option<String>,
 }
@@ -207,6 +221,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for VoiceStateWithUser {
             self_stream: bool_from_any_row(row, "self_stream")?,
             self_video: bool_from_any_row(row, "self_video")?,
             suppress: bool_from_any_row(row, "suppress")?,
+            priority_speaker: bool_from_any_row(row, "priority_speaker")?,
             username: row.try_get("username")?,
             avatar_hash: row.try_get("avatar_hash")?,
         })
@@ -232,15 +247,16 @@ pub async fn get_space_voice_states(
     pool: &DbPool,
     space_id: i64,
 ) -> Result<Vec<VoiceStateWithUser>, DbError> {
-    let rows = sqlx::query_as::<_, VoiceStateWithUser>(
-        "SELECT vs.user_id, vs.space_id, vs.channel_id, vs.session_id, vs.self_mute, vs.self_deaf, vs.self_stream, vs.self_video, vs.suppress, u.username, u.avatar_hash
+    let sql = format!(
+        "SELECT {VOICE_STATE_COLUMNS}, u.username, u.avatar_hash
          FROM voice_states vs
          JOIN users u ON u.id = vs.user_id
          WHERE vs.space_id = $1"
-    )
-    .bind(space_id)
-    .fetch_all(pool)
-    .await?;
+    );
+    let rows = sqlx::query_as::<_, VoiceStateWithUser>(&sql)
+        .bind(space_id)
+        .fetch_all(pool)
+        .await?;
     Ok(rows)
 }
 
@@ -267,3 +283,52 @@ pub async fn update_voice_state(
     .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> DbPool {
+        let pool = crate::create_pool("sqlite::memory:", 1).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_voice_state_flags_read_back() {
+        let pool = test_pool().await;
+        crate::users::create_user(&pool, 1, "speaker", 1, "speaker@example.com", "hash")
+            .await
+            .unwrap();
+        crate::guilds::create_guild(&pool, 100, "Test Guild", 1, None)
+            .await
+            .unwrap();
+        crate::channels::create_channel(&pool, 200, 100, "voice", 2, 0, None, None)
+            .await
+            .unwrap();
+
+        upsert_voice_state(&pool, 1, Some(100), 200, "session", true)
+            .await
+            .unwrap();
+        update_voice_state(&pool, 1, Some(100), true, false, true, false)
+            .await
+            .unwrap();
+
+        let state = get_user_voice_state(&pool, 1, Some(100))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(state.self_mute && !state.self_deaf && state.self_stream && !state.self_video);
+        assert!(state.priority_speaker);
+
+        let in_channel = get_channel_voice_states(&pool, 200).await.unwrap();
+        assert_eq!(in_channel.len(), 1);
+        assert!(in_channel[0].priority_speaker);
+        assert_eq!(get_all_user_voice_states(&pool, 1).await.unwrap().len(), 1);
+
+        let in_space = get_space_voice_states(&pool, 100).await.unwrap();
+        assert_eq!(in_space.len(), 1);
+        assert_eq!(in_space[0].username, "speaker");
+        assert!(in_space[0].self_mute && in_space[0].priority_speaker);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{datetime_from_db_text, datetime_to_db_text, DbError, DbPool};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+#[derive(Debug, Clone)]
+pub struct WebAuthnCredentialRow {
+    /// Base64url credential ID chosen by the authenticator.
+    pub credential_id: String,
+    pub user_id: i64,
+    /// Serialized webauthn-rs `Passkey` (JSON).
+    pub passkey: String,
+    pub sign_count: i64,
+    pub transports: Vec<String>,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for WebAuthnCredentialRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        let transports_raw: String = row.try_get("transports")?;
+        let created_at_raw: String = row.try_get("created_at")?;
+        let last_used_at_raw: Option<String> = row.try_get("last_used_at")?;
+        Ok(Self {
+            credential_id: row.try_get("credential_id")?,
+            user_id: row.try_get("user_id")?,
+            passkey: row.try_get("passkey")?,
+            sign_count: row.try_get("sign_count")?,
+            transports: serde_json::from_str(&transports_raw)
+                .map_err(|e| sqlx::Error::Protocol(format!("invalid transports list: {e}")))?,
+            name: row.try_get("name")?,
+            created_at: datetime_from_db_text(&created_at_raw)?,
+            last_used_at: last_used_at_raw
+                .as_deref()
+                .map(datetime_from_db_text)
+                .transpose()?,
+        })
+    }
+}
+
+pub async fn create_credential(
+    pool: &DbPool,
+    credential_id: &str,
+    user_id: i64,
+    passkey: &str,
+    sign_count: i64,
+    transports: &[String],
+    name: Option<&str>,
+) -> Result<WebAuthnCredentialRow, DbError> {
+    let transports = serde_json::to_string(transports).unwrap_or_else(|_| "[]".to_string());
+    let row = sqlx::query_as::<_, WebAuthnCredentialRow>(
+        "INSERT INTO webauthn_credentials (credential_id, user_id, passkey, sign_count, transports, name)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING credential_id, user_id, passkey, sign_count, transports, name, created_at, last_used_at",
+    )
+    .bind(credential_id)
+    .bind(user_id)
+    .bind(passkey)
+    .bind(sign_count)
+    .bind(transports)
+    .bind(name)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_credential(
+    pool: &DbPool,
+    credential_id: &str,
+) -> Result<Option<WebAuthnCredentialRow>, DbError> {
+    let row = sqlx::query_as::<_, WebAuthnCredentialRow>(
+        "SELECT credential_id, user_id, passkey, sign_count, transports, name, created_at, last_used_at
+         FROM webauthn_credentials
+         WHERE credential_id = $1",
+    )
+    .bind(credential_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn get_user_credentials(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Vec<WebAuthnCredentialRow>, DbError> {
+    let rows = sqlx::query_as::<_, WebAuthnCredentialRow>(
+        "SELECT credential_id, user_id, passkey, sign_count, transports, name, created_at, last_used_at
+         FROM webauthn_credentials
+         WHERE user_id = $1
+         ORDER BY created_at ASC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Save the passkey after a login, advancing the stored signature counter
+/// from `expected` to `sign_count`. Returns `false` if another login moved
+/// the counter first.
+pub async fn update_passkey(
+    pool: &DbPool,
+    credential_id: &str,
+    expected: i64,
+    sign_count: i64,
+    passkey: &str,
+    used_at: DateTime<Utc>,
+) -> Result<bool, DbError> {
+    let result = sqlx::query(
+        "UPDATE webauthn_credentials
+         SET sign_count = $3, passkey = $4, last_used_at = $5
+         WHERE credential_id = $1 AND sign_count = $2",
+    )
+    .bind(credential_id)
+    .bind(expected)
+    .bind(sign_count)
+    .bind(passkey)
+    .bind(datetime_to_db_text(used_at))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_db() -> DbPool {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let db_path = std::env::temp_dir().join(format!("paracord-db-webauthn-{unique}.db"));
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            db_path.to_string_lossy().replace('\\', "/")
+        );
+        let pool = crate::create_pool(&db_url, 1).await.expect("pool");
+        crate::run_migrations(&pool).await.expect("migrations");
+        pool
+    }
+
+    #[tokio::test]
+    async fn sign_count_only_advances_from_the_expected_value() {
+        let db = setup_db().await;
+        let user = crate::users::create_user(&db, 9201, "passkey", 1, "pk@example.com", "hash")
+            .await
+            .expect("create user");
+
+        let transports = vec!["internal".to_string()];
+        let created = create_credential(&db, "cred-1", user.id, "{}", 3, &transports, None)
+            .await
+            .expect("create credential");
+        assert_eq!(created.transports, transports);
+        assert!(created.last_used_at.is_none());
+
+        assert!(update_passkey(&db, "cred-1", 3, 4, "{\"v\":4}", Utc::now())
+            .await
+            .unwrap());
+        assert!(
+            !update_passkey(&db, "cred-1", 3, 5, "{\"v\":5}", Utc::now())
+                .await
+                .unwrap()
+        );
+
+        let stored = get_credential(&db, "cred-1").await.unwrap().expect("row");
+        assert_eq!(stored.sign_count, 4);
+        assert_eq!(stored.passkey, "{\"v\":4}");
+        assert!(stored.last_used_at.is_some());
+        assert_eq!(get_user_credentials(&db, user.id).await.unwrap().len(), 1);
+    }
+}
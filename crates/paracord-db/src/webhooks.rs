@@ -11,6 +11,8 @@ pub struct WebhookRow {
     pub creator_id: Option<i64>,
     pub name: String,
     pub token: String,
+    /// JSON array of CIDR strings; empty means any source IP is accepted.
+    pub allowed_cidrs: String,
     pub created_at: DateTime<Utc>,
 }
 
@@ -24,6 +26,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for WebhookRow {
             creator_id: row.try_get("creator_id")?,
             name: row.try_get("name")?,
             token: row.try_get("token")?,
+            allowed_cidrs: row.try_get("allowed_cidrs")?,
             created_at: datetime_from_db_text(&created_at_raw)?,
         })
     }
@@ -53,6 +56,11 @@ fn normalize_token_hash(token: &str) -> String {
     }
 }
 
+pub fn parse_allowed_cidrs(raw: &str) -> Vec<String> {
+    serde_json::from_str::<Vec<String>>(raw).unwrap_or_default()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn create_webhook(
     pool: &DbPool,
     id: i64,
@@ -61,12 +69,13 @@ pub async fn create_webhook(
     name: &str,
     token: &str,
     creator_id: i64,
+    allowed_cidrs: &str,
 ) -> Result<WebhookRow, DbError> {
     let token_hash = normalize_token_hash(token);
     let row = sqlx::query_as::<_, WebhookRow>(
-        "INSERT INTO webhooks (id, space_id, channel_id, name, token, creator_id)
-         VALUES ($1, $2, $3, $4, $5, $6)
-         RETURNING id, space_id, channel_id, creator_id, name, token, created_at",
+        "INSERT INTO webhooks (id, space_id, channel_id, name, token, creator_id, allowed_cidrs)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING id, space_id, channel_id, creator_id, name, token, allowed_cidrs, created_at",
     )
     .bind(id)
     .bind(space_id)
@@ -74,6 +83,7 @@ pub async fn create_webhook(
     .bind(name)
     .bind(token_hash)
     .bind(creator_id)
+    .bind(allowed_cidrs)
     .fetch_one(pool)
     .await?;
     Ok(row)
@@ -81,7 +91,7 @@ pub async fn create_webhook(
 
 pub async fn get_webhook(pool: &DbPool, id: i64) -> Result<Option<WebhookRow>, DbError> {
     let row = sqlx::query_as::<_, WebhookRow>(
-        "SELECT id, space_id, channel_id, creator_id, name, token, created_at
+        "SELECT id, space_id, channel_id, creator_id, name, token, allowed_cidrs, created_at
          FROM webhooks WHERE id = $1",
     )
     .bind(id)
@@ -97,7 +107,7 @@ pub async fn get_webhook_by_id_and_token(
 ) -> Result<Option<WebhookRow>, DbError> {
     let token_hash = normalize_token_hash(token);
     let row = sqlx::query_as::<_, WebhookRow>(
-        "SELECT id, space_id, channel_id, creator_id, name, token, created_at
+        "SELECT id, space_id, channel_id, creator_id, name, token, allowed_cidrs, created_at
          FROM webhooks WHERE id = $1 AND (token = $2 OR token = $3)",
     )
     .bind(id)
@@ -113,7 +123,7 @@ pub async fn get_channel_webhooks(
     channel_id: i64,
 ) -> Result<Vec<WebhookRow>, DbError> {
     let rows = sqlx::query_as::<_, WebhookRow>(
-        "SELECT id, space_id, channel_id, creator_id, name, token, created_at
+        "SELECT id, space_id, channel_id, creator_id, name, token, allowed_cidrs, created_at
          FROM webhooks WHERE channel_id = $1 ORDER BY created_at",
     )
     .bind(channel_id)
@@ -124,7 +134,7 @@ pub async fn get_channel_webhooks(
 
 pub async fn get_guild_webhooks(pool: &DbPool, space_id: i64) -> Result<Vec<WebhookRow>, DbError> {
     let rows = sqlx::query_as::<_, WebhookRow>(
-        "SELECT id, space_id, channel_id, creator_id, name, token, created_at
+        "SELECT id, space_id, channel_id, creator_id, name, token, allowed_cidrs, created_at
          FROM webhooks WHERE space_id = $1 ORDER BY created_at",
     )
     .bind(space_id)
@@ -133,18 +143,55 @@ pub async fn get_guild_webhooks(pool: &DbPool, space_id: i64) -> Result<Vec<Webh
     Ok(rows)
 }
 
+pub async fn count_channel_webhooks(pool: &DbPool, channel_id: i64) -> Result<i64, DbError> {
+    let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM webhooks WHERE channel_id = $1")
+        .bind(channel_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(row.0)
+}
+
+pub async fn count_guild_webhooks(pool: &DbPool, space_id: i64) -> Result<i64, DbError> {
+    let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM webhooks WHERE space_id = $1")
+        .bind(space_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(row.0)
+}
+
 pub async fn update_webhook(
     pool: &DbPool,
     id: i64,
     name: Option<&str>,
+    allowed_cidrs: Option<&str>,
 ) -> Result<WebhookRow, DbError> {
     let row = sqlx::query_as::<_, WebhookRow>(
-        "UPDATE webhooks SET name = COALESCE($2, name)
+        "UPDATE webhooks SET name = COALESCE($2, name), allowed_cidrs = COALESCE($3, allowed_cidrs)
          WHERE id = $1
-         RETURNING id, space_id, channel_id, creator_id, name, token, created_at",
+         RETURNING id, space_id, channel_id, creator_id, name, token, allowed_cidrs, created_at",
     )
     .bind(id)
     .bind(name)
+    .bind(allowed_cidrs)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Replace the webhook's token. The previous token stops matching immediately.
+pub async fn update_webhook_token(
+    pool: &DbPool,
+    id: i64,
+    token: &str,
+) -> Result<WebhookRow, DbError> {
+    let token_hash = normalize_token_hash(token);
+    let row = sqlx::query_as::<_, WebhookRow>(
+        "UPDATE webhooks SET token = $2
+         WHERE id = $1
+         RETURNING id, space_id, channel_id, creator_id, name, token, allowed_cidrs, created_at",
+    )
+    .bind(id)
+    .bind(token_hash)
     .fetch_one(pool)
     .await?;
     Ok(row)
@@ -4,11 +4,15 @@ use crate::transport;
 use crate::{FederationError, FederationEventEnvelope, FederationServerKey};
 use ed25519_dalek::SigningKey;
 use reqwest::Client;
-use std::time::Duration;
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
 const MAX_RETRIES: u32 = 3;
 const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// File tokens are treated as expired this long before the origin's stated
+/// lifetime runs out, to absorb request latency and clock drift.
+const FILE_TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone)]
 struct TransportSigner {
@@ -282,6 +286,25 @@ impl FederationClient {
         Ok((bytes.to_vec(), content_type, filename))
     }
 
+    /// Request a file token from the origin and download the attachment.
+    ///
+    /// The token is checked for expiry before fetching, and a 401/403 from the
+    /// download endpoint triggers exactly one fresh token request and retry.
+    /// Every download URL, including refreshed ones, must resolve to the
+    /// origin's federation endpoint.
+    pub async fn fetch_federated_file(
+        &self,
+        federation_endpoint: &str,
+        payload: &FederationFileTokenRequest,
+    ) -> Result<(Vec<u8>, Option<String>, Option<String>), FederationError> {
+        download_with_file_token(
+            federation_endpoint,
+            || self.request_file_token(federation_endpoint, payload),
+            |url| async move { self.download_federated_file(&url).await },
+        )
+        .await
+    }
+
     /// GET request with exponential backoff retry.
     async fn get_with_retry(&self, url: &str) -> Result<reqwest::Response, FederationError> {
         self.get_with_retry_with_headers(url, &[]).await
@@ -311,11 +334,10 @@ impl FederationClient {
                     ));
                 }
                 Ok(resp) => {
-                    return Err(FederationError::RemoteError(format!(
-                        "request to {} returned {}",
-                        url,
-                        resp.status()
-                    )));
+                    return Err(FederationError::RemoteStatus {
+                        url: url.to_string(),
+                        status: resp.status().as_u16(),
+                    });
                 }
                 Err(e) => {
                     last_err = FederationError::Http(e.to_string());
@@ -491,6 +513,9 @@ pub struct FederationMediaTokenResponse {
     pub room_name: String,
     pub session_id: String,
     pub local_user_id: String,
+    /// Token lifetime chosen by the issuing server. Absent from older peers.
+    #[serde(default)]
+    pub expires_in_seconds: Option<i64>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -516,3 +541,258 @@ pub struct FederationFileTokenResponse {
     pub download_url: String,
     pub expires_in_seconds: i64,
 }
+
+impl FederationFileTokenResponse {
+    /// Whether a token received at `issued_at` should no longer be used at `now`.
+    pub fn is_expired_at(&self, issued_at: Instant, now: Instant) -> bool {
+        let lifetime = Duration::from_secs(self.expires_in_seconds.max(0) as u64)
+            .saturating_sub(FILE_TOKEN_EXPIRY_MARGIN);
+        now.saturating_duration_since(issued_at) >= lifetime
+    }
+}
+
+/// Resolve a file token's `download_url` against the origin's federation
+/// endpoint. The result must share the endpoint's scheme, host and port so a
+/// remote server cannot point us at arbitrary hosts.
+pub fn resolve_file_download_url(
+    federation_endpoint: &str,
+    download_url: &str,
+) -> Result<String, FederationError> {
+    let endpoint = reqwest::Url::parse(federation_endpoint)
+        .map_err(|e| FederationError::RemoteError(format!("invalid federation endpoint: {e}")))?;
+    let resolved = endpoint
+        .join(download_url)
+        .map_err(|e| FederationError::RemoteError(format!("invalid file download url: {e}")))?;
+    let same_origin = matches!(resolved.scheme(), "http" | "https")
+        && resolved.scheme() == endpoint.scheme()
+        && resolved.host_str() == endpoint.host_str()
+        && resolved.port_or_known_default() == endpoint.port_or_known_default()
+        && resolved.username().is_empty()
+        && resolved.password().is_none();
+    if !same_origin {
+        return Err(FederationError::RemoteError(
+            "file download url does not match the origin federation endpoint".to_string(),
+        ));
+    }
+    Ok(resolved.into())
+}
+
+fn is_token_rejection(err: &FederationError) -> bool {
+    matches!(
+        err,
+        FederationError::RemoteStatus {
+            status: 401 | 403,
+            ..
+        }
+    )
+}
+
+async fn download_with_file_token<T, R, RFut, D, DFut>(
+    federation_endpoint: &str,
+    mut request_token: R,
+    mut download: D,
+) -> Result<T, FederationError>
+where
+    R: FnMut() -> RFut,
+    RFut: Future<Output = Result<FederationFileTokenResponse, FederationError>>,
+    D: FnMut(String) -> DFut,
+    DFut: Future<Output = Result<T, FederationError>>,
+{
+    let mut token = request_token().await?;
+    let mut issued_at = Instant::now();
+    let mut refreshed = false;
+    loop {
+        if token.is_expired_at(issued_at, Instant::now()) {
+            if refreshed {
+                return Err(FederationError::RemoteError(
+                    "file token expired before download".to_string(),
+                ));
+            }
+            refreshed = true;
+            token = request_token().await?;
+            issued_at = Instant::now();
+            continue;
+        }
+        let url = resolve_file_download_url(federation_endpoint, &token.download_url)?;
+        match download(url).await {
+            Err(err) if !refreshed && is_token_rejection(&err) => {
+                tracing::debug!("federated file token rejected, requesting a fresh one: {err}");
+                refreshed = true;
+                token = request_token().await?;
+                issued_at = Instant::now();
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    const ENDPOINT: &str = "https://origin.example/_paracord/federation/v1";
+
+    fn token(download_url: &str, expires_in_seconds: i64) -> FederationFileTokenResponse {
+        FederationFileTokenResponse {
+            token: "t".to_string(),
+            download_url: download_url.to_string(),
+            expires_in_seconds,
+        }
+    }
+
+    fn rejected(status: u16) -> FederationError {
+        FederationError::RemoteStatus {
+            url: ENDPOINT.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn resolves_relative_download_url_against_endpoint_origin() {
+        assert_eq!(
+            resolve_file_download_url(ENDPOINT, "/_paracord/federation/v1/file/7?token=abc")
+                .unwrap(),
+            "https://origin.example/_paracord/federation/v1/file/7?token=abc"
+        );
+    }
+
+    #[test]
+    fn rejects_download_url_on_another_origin() {
+        for url in [
+            "https://evil.example/file/7",
+            "http://origin.example/file/7",
+            "https://origin.example:8443/file/7",
+            "https://user@origin.example/file/7",
+            "//169.254.169.254/latest/meta-data",
+            "file:///etc/passwd",
+        ] {
+            assert!(
+                resolve_file_download_url(ENDPOINT, url).is_err(),
+                "{url} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn token_expiry_accounts_for_margin() {
+        let issued = Instant::now();
+        let fresh = token("/file/1", 300);
+        assert!(!fresh.is_expired_at(issued, issued));
+        assert!(fresh.is_expired_at(issued, issued + Duration::from_secs(296)));
+        assert!(token("/file/1", 0).is_expired_at(issued, issued));
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_re_requested_once_before_download() {
+        let requests = Cell::new(0);
+        let downloads = Cell::new(0);
+        let result = download_with_file_token(
+            ENDPOINT,
+            || {
+                requests.set(requests.get() + 1);
+                let ttl = if requests.get() == 1 { 0 } else { 300 };
+                async move { Ok(token("/file/1?token=fresh", ttl)) }
+            },
+            |url| {
+                downloads.set(downloads.get() + 1);
+                async move { Ok(url) }
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(requests.get(), 2);
+        assert_eq!(downloads.get(), 1);
+        assert_eq!(result, "https://origin.example/file/1?token=fresh");
+    }
+
+    #[tokio::test]
+    async fn rejected_download_refreshes_token_and_retries_once() {
+        let requests = Cell::new(0);
+        let downloads = Cell::new(0);
+        let result: Result<String, _> = download_with_file_token(
+            ENDPOINT,
+            || {
+                requests.set(requests.get() + 1);
+                async move { Ok(token("/file/1", 300)) }
+            },
+            |_url| {
+                downloads.set(downloads.get() + 1);
+                async move { Err(rejected(401)) }
+            },
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(FederationError::RemoteStatus { status: 401, .. })
+        ));
+        assert_eq!(requests.get(), 2);
+        assert_eq!(downloads.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn non_auth_failures_are_not_retried() {
+        let requests = Cell::new(0);
+        let result: Result<String, _> = download_with_file_token(
+            ENDPOINT,
+            || {
+                requests.set(requests.get() + 1);
+                async move { Ok(token("/file/1", 300)) }
+            },
+            |_url| async move { Err(rejected(404)) },
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(requests.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn refreshed_download_url_is_still_origin_checked() {
+        // Expired first token: the refreshed URL points elsewhere.
+        let downloads = Cell::new(0);
+        let requests = Cell::new(0);
+        let result: Result<String, _> = download_with_file_token(
+            ENDPOINT,
+            || {
+                requests.set(requests.get() + 1);
+                let next = if requests.get() == 1 {
+                    token("/file/1", 0)
+                } else {
+                    token("http://127.0.0.1:6379/file/1", 300)
+                };
+                async move { Ok(next) }
+            },
+            |url| {
+                downloads.set(downloads.get() + 1);
+                async move { Ok(url) }
+            },
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(downloads.get(), 0);
+
+        // Rejected first download: the refreshed URL points elsewhere.
+        let downloads = Cell::new(0);
+        let requests = Cell::new(0);
+        let result: Result<String, _> = download_with_file_token(
+            ENDPOINT,
+            || {
+                requests.set(requests.get() + 1);
+                let next = if requests.get() == 1 {
+                    token("/file/1", 300)
+                } else {
+                    token("https://evil.example/file/1", 300)
+                };
+                async move { Ok(next) }
+            },
+            |_url| {
+                downloads.set(downloads.get() + 1);
+                async move { Err(rejected(403)) }
+            },
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(requests.get(), 2);
+        assert_eq!(downloads.get(), 1);
+    }
+}
@@ -24,6 +24,8 @@ pub enum FederationError {
     Http(String),
     #[error("remote server error: {0}")]
     RemoteError(String),
+    #[error("remote server error: request to {url} returned {status}")]
+    RemoteStatus { url: String, status: u16 },
     #[error("unknown server: {0}")]
     UnknownServer(String),
 }
@@ -52,9 +54,51 @@ impl FederationConfig {
     }
 }
 
+/// Backoff and give-up limits for the persistent outbound delivery queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutboundRetryPolicy {
+    /// Delay before the first retry; doubles with each further attempt.
+    pub base_delay_ms: i64,
+    /// Upper bound on the delay between attempts.
+    pub max_delay_ms: i64,
+    /// Failed attempts after which an entry is dead-lettered.
+    pub max_attempts: i64,
+    /// Age after which an undelivered entry is dead-lettered.
+    pub max_age_ms: i64,
+    /// How long dead-lettered entries are kept for inspection.
+    pub dead_letter_retention_ms: i64,
+}
+
+impl Default for OutboundRetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: 5_000,
+            max_delay_ms: 3_600_000,
+            max_attempts: 12,
+            max_age_ms: 86_400_000,
+            dead_letter_retention_ms: 7 * 86_400_000,
+        }
+    }
+}
+
+impl OutboundRetryPolicy {
+    /// Exponential backoff: `base_delay_ms` doubling per attempt, capped at
+    /// `max_delay_ms`.
+    pub fn next_retry_ts(&self, now_ms: i64, attempt_count: i64) -> i64 {
+        let exp = attempt_count.clamp(0, 30) as u32;
+        let delay_ms = self
+            .base_delay_ms
+            .max(0)
+            .saturating_mul(1_i64 << exp)
+            .min(self.max_delay_ms.max(0));
+        now_ms.saturating_add(delay_ms)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FederationService {
     config: FederationConfig,
+    retry_policy: OutboundRetryPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,7 +125,19 @@ pub struct FederationServerKey {
 
 impl FederationService {
     pub fn new(config: FederationConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            retry_policy: OutboundRetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: OutboundRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &OutboundRetryPolicy {
+        &self.retry_policy
     }
 
     pub fn is_enabled(&self) -> bool {
@@ -539,7 +595,7 @@ impl FederationService {
                 Err(e) => {
                     let latency_ms = attempt_started.elapsed().as_millis() as i64;
                     let attempt_ts = chrono::Utc::now().timestamp_millis();
-                    let retry_at = next_retry_ts(attempt_ts, 0);
+                    let retry_at = self.retry_policy.next_retry_ts(attempt_ts, 0);
                     let err_msg = e.to_string();
                     let _ = paracord_db::federation::record_delivery_attempt(
                         pool,
@@ -576,29 +632,35 @@ impl FederationService {
             return;
         }
 
-        // Purge events that have exceeded max retries or max age before processing.
-        const MAX_RETRY_ATTEMPTS: i64 = 12;
-        const MAX_EVENT_AGE_MS: i64 = 86_400_000; // 24 hours
+        // Dead-letter events that have exceeded max retries or max age before
+        // processing, and drop dead letters past their retention.
+        let policy = self.retry_policy;
         let now_ms = chrono::Utc::now().timestamp_millis();
-        match paracord_db::federation::purge_expired_outbound_events(
+        match paracord_db::federation::dead_letter_expired_outbound_events(
             pool,
             now_ms,
-            MAX_RETRY_ATTEMPTS,
-            MAX_EVENT_AGE_MS,
+            policy.max_attempts,
+            policy.max_age_ms,
         )
         .await
         {
-            Ok(purged) if purged > 0 => {
-                tracing::info!(
-                    "federation: purged {} expired outbound queue entries",
-                    purged
-                );
+            Ok(dead) if dead > 0 => {
+                tracing::warn!("federation: dead-lettered {} outbound queue entries", dead);
             }
             Err(e) => {
-                tracing::warn!("federation: failed to purge expired outbound events: {}", e);
+                tracing::warn!("federation: failed to dead-letter outbound events: {}", e);
             }
             _ => {}
         }
+        if let Err(e) = paracord_db::federation::purge_dead_lettered_outbound_events(
+            pool,
+            now_ms,
+            policy.dead_letter_retention_ms,
+        )
+        .await
+        {
+            tracing::warn!("federation: failed to purge dead-lettered events: {}", e);
+        }
         let due =
             match paracord_db::federation::fetch_due_outbound_events(pool, now_ms, limit).await {
                 Ok(rows) => rows,
@@ -660,7 +722,9 @@ impl FederationService {
                 }
                 Err(e) => {
                     let err_msg = e.to_string();
-                    let retry_at = next_retry_ts(attempt_ts, row.attempt_count);
+                    let retry_at = self
+                        .retry_policy
+                        .next_retry_ts(attempt_ts, row.attempt_count);
                     let _ = paracord_db::federation::record_delivery_attempt(
                         pool,
                         &row.destination_server,
@@ -726,10 +790,10 @@ impl FederationService {
     }
 }
 
-fn next_retry_ts(now_ms: i64, attempt_count: i64) -> i64 {
-    let exp = (attempt_count.clamp(0, 8)) as u32;
-    let delay_ms = 5_000_i64.saturating_mul(1_i64 << exp);
-    now_ms.saturating_add(delay_ms.min(3_600_000))
+/// Exponential backoff for queued outbound deliveries under the default
+/// [`OutboundRetryPolicy`]: 5s doubling per attempt, capped at an hour.
+pub fn next_retry_ts(now_ms: i64, attempt_count: i64) -> i64 {
+    OutboundRetryPolicy::default().next_retry_ts(now_ms, attempt_count)
 }
 
 /// Build the canonical bytes used for signing an envelope (excludes signatures).
@@ -847,6 +911,19 @@ mod tests {
         })
     }
 
+    #[test]
+    fn retry_policy_backoff_doubles_up_to_the_cap() {
+        let policy = OutboundRetryPolicy {
+            base_delay_ms: 1_000,
+            max_delay_ms: 10_000,
+            ..OutboundRetryPolicy::default()
+        };
+        assert_eq!(policy.next_retry_ts(0, 0), 1_000);
+        assert_eq!(policy.next_retry_ts(0, 3), 8_000);
+        assert_eq!(policy.next_retry_ts(0, 4), 10_000);
+        assert_eq!(policy.next_retry_ts(0, 1_000), 10_000);
+    }
+
     #[test]
     fn message_envelope_uses_guild_room_and_timestamp_depth() {
         let service = test_service();
@@ -7,14 +7,14 @@ pub mod voice;
 pub use livekit::{AudioBitrate, LiveKitConfig, WebhookEvent};
 pub use s3::S3Config;
 pub use storage::{
-    LocalStorage, P2PTransferRequest, Storage, StorageBackend, StorageConfig, StorageError,
-    StorageManager, StoredFile,
+    ByteStream, LocalStorage, P2PTransferRequest, Storage, StorageBackend, StorageConfig,
+    StorageError, StorageManager, StoredFile,
 };
 pub use streaming::{
     ScreenCaptureConfig, SimulcastLayer, StreamConfig, StreamMetadata, StreamQualityPreset,
     ViewerQuality,
 };
-pub use voice::{StreamStartResponse, VoiceJoinResponse, VoiceManager};
+pub use voice::{StreamStartResponse, VoiceJoinResponse, VoiceManager, VoiceRecording};
 
 /// Create a `Storage` enum from the server configuration.
 ///
@@ -2,7 +2,8 @@ use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-const LIVEKIT_TOKEN_TTL_SECONDS: u64 = 7_200;
+/// Default lifetime of participant tokens.
+pub const LIVEKIT_TOKEN_TTL_SECONDS: u64 = 7_200;
 
 #[derive(Debug, Clone)]
 pub struct LiveKitConfig {
@@ -34,6 +35,8 @@ pub struct VideoGrant {
     pub can_publish_sources: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hidden: Option<bool>,
+    #[serde(rename = "roomRecord", skip_serializing_if = "Option::is_none")]
+    pub room_record: Option<bool>,
 }
 
 impl VideoGrant {
@@ -49,6 +52,7 @@ impl VideoGrant {
             can_publish_data: None,
             can_publish_sources: None,
             hidden: None,
+            room_record: None,
         }
     }
 }
@@ -151,6 +155,7 @@ impl LiveKitConfig {
             can_publish_data: None,
             can_publish_sources: None,
             hidden: None,
+            room_record: None,
         })
     }
 
@@ -158,6 +163,7 @@ impl LiveKitConfig {
     ///
     /// `can_publish` controls whether the user can speak (false = listen-only / push-to-talk off).
     /// `can_subscribe` controls whether the user receives audio from others (false = server-deafened).
+    /// The token expires `ttl_seconds` after issue.
     pub fn generate_voice_token(
         &self,
         room_name: &str,
@@ -165,6 +171,7 @@ impl LiveKitConfig {
         user_name: &str,
         can_publish: bool,
         can_subscribe: bool,
+        ttl_seconds: u64,
     ) -> Result<String, anyhow::Error> {
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
 
@@ -189,7 +196,7 @@ impl LiveKitConfig {
         };
 
         let claims = LiveKitClaims {
-            exp: now + LIVEKIT_TOKEN_TTL_SECONDS,
+            exp: now + ttl_seconds,
             iss: self.api_key.clone(),
             sub: user_id.to_string(),
             name: Some(user_name.to_string()),
@@ -204,6 +211,7 @@ impl LiveKitConfig {
                 room_list: None,
                 room_admin: None,
                 hidden: None,
+                room_record: None,
             },
             metadata: Some(metadata.to_string()),
         };
@@ -222,6 +230,7 @@ impl LiveKitConfig {
         room_name: &str,
         user_id: i64,
         user_name: &str,
+        ttl_seconds: u64,
     ) -> Result<String, anyhow::Error> {
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
 
@@ -231,7 +240,7 @@ impl LiveKitConfig {
         });
 
         let claims = LiveKitClaims {
-            exp: now + LIVEKIT_TOKEN_TTL_SECONDS,
+            exp: now + ttl_seconds,
             iss: self.api_key.clone(),
             sub: user_id.to_string(),
             name: Some(user_name.to_string()),
@@ -251,6 +260,7 @@ impl LiveKitConfig {
                 room_list: None,
                 room_admin: None,
                 hidden: None,
+                room_record: None,
             },
             metadata: Some(metadata.to_string()),
         };
@@ -300,6 +310,7 @@ impl LiveKitConfig {
                 room_list: None,
                 room_admin: None,
                 hidden: None,
+                room_record: None,
             },
             metadata: Some(metadata.to_string()),
         };
@@ -336,6 +347,7 @@ impl LiveKitConfig {
                 room_list: None,
                 room_admin: None,
                 hidden: None,
+                room_record: None,
             },
             metadata: None,
         };
@@ -597,6 +609,78 @@ impl LiveKitConfig {
         Ok(())
     }
 
+    /// Token for the Egress service, which requires the `roomRecord` grant.
+    fn generate_record_token(&self) -> Result<String, anyhow::Error> {
+        self.generate_admin_token(VideoGrant {
+            room_record: Some(true),
+            ..VideoGrant::admin()
+        })
+    }
+
+    /// Start an audio-only room composite recording written to `filepath`,
+    /// an egress path template. Returns the egress ID.
+    pub async fn start_room_recording(
+        &self,
+        room_name: &str,
+        filepath: &str,
+    ) -> Result<String, anyhow::Error> {
+        let admin_token = self.generate_record_token()?;
+
+        let client = Self::api_client();
+        let resp = client
+            .post(format!(
+                "{}/twirp/livekit.Egress/StartRoomCompositeEgress",
+                self.http_url
+            ))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "room_name": room_name,
+                "audio_only": true,
+                "file_outputs": [{
+                    "file_type": "OGG",
+                    "filepath": filepath,
+                }],
+            }))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            anyhow::bail!("Failed to start LiveKit recording: {}", err);
+        }
+
+        let body: serde_json::Value = resp.json().await?;
+        body.get("egress_id")
+            .or_else(|| body.get("egressId"))
+            .and_then(|id| id.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("LiveKit recording response has no egress ID"))
+    }
+
+    /// Stop a running egress.
+    pub async fn stop_egress(&self, egress_id: &str) -> Result<(), anyhow::Error> {
+        let admin_token = self.generate_record_token()?;
+
+        let client = Self::api_client();
+        let resp = client
+            .post(format!("{}/twirp/livekit.Egress/StopEgress", self.http_url))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "egress_id": egress_id,
+            }))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            anyhow::bail!("Failed to stop LiveKit recording: {}", err);
+        }
+
+        Ok(())
+    }
+
     /// Parse and validate a LiveKit webhook request body.
     /// Returns the parsed event. The caller should verify the webhook
     /// token/signature at the HTTP layer before calling this.
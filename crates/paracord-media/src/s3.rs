@@ -54,11 +54,13 @@ impl Default for S3Config {
 #[cfg(feature = "s3")]
 mod inner {
     use super::*;
-    use crate::storage::{StorageBackend, StorageError};
+    use crate::storage::{ByteStream, StorageBackend, StorageError};
     use aws_config::BehaviorVersion;
     use aws_sdk_s3::config::{Credentials, Region};
+    use aws_sdk_s3::operation::get_object::GetObjectOutput;
     use aws_sdk_s3::presigning::PresigningConfig;
     use aws_sdk_s3::Client;
+    use std::path::Path;
     use std::time::Duration;
 
     /// S3-compatible storage backend.
@@ -126,6 +128,23 @@ mod inner {
                 format!("{}{}", self.prefix, key)
             }
         }
+
+        async fn get_object(&self, key: &str) -> Result<GetObjectOutput, StorageError> {
+            self.client
+                .get_object()
+                .bucket(&self.bucket)
+                .key(self.full_key(key))
+                .send()
+                .await
+                .map_err(|e| {
+                    let msg = format!("{}", e);
+                    if msg.contains("NoSuchKey") || msg.contains("404") {
+                        StorageError::NotFound(key.to_string())
+                    } else {
+                        StorageError::Backend(format!("S3 GetObject failed: {}", e))
+                    }
+                })
+        }
     }
 
     impl StorageBackend for S3Storage {
@@ -149,24 +168,31 @@ mod inner {
             Ok(key.to_string())
         }
 
-        async fn retrieve(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        async fn store_path(&self, key: &str, path: &Path) -> Result<String, StorageError> {
             let full_key = self.full_key(key);
+            let content_type = mime_guess::from_path(key)
+                .first_raw()
+                .unwrap_or("application/octet-stream");
+            let body = aws_sdk_s3::primitives::ByteStream::from_path(path)
+                .await
+                .map_err(|e| StorageError::Backend(format!("S3 read file failed: {}", e)))?;
 
-            let resp = self
-                .client
-                .get_object()
+            self.client
+                .put_object()
                 .bucket(&self.bucket)
                 .key(&full_key)
+                .body(body)
+                .content_type(content_type)
                 .send()
                 .await
-                .map_err(|e| {
-                    let msg = format!("{}", e);
-                    if msg.contains("NoSuchKey") || msg.contains("404") {
-                        StorageError::NotFound(key.to_string())
-                    } else {
-                        StorageError::Backend(format!("S3 GetObject failed: {}", e))
-                    }
-                })?;
+                .map_err(|e| StorageError::Backend(format!("S3 PutObject failed: {}", e)))?;
+
+            tracing::debug!("S3: stored object {}", full_key);
+            Ok(key.to_string())
+        }
+
+        async fn retrieve(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+            let resp = self.get_object(key).await?;
 
             let bytes = resp
                 .body
@@ -178,6 +204,18 @@ mod inner {
             Ok(bytes.to_vec())
         }
 
+        async fn retrieve_stream(&self, key: &str) -> Result<ByteStream, StorageError> {
+            let body = self.get_object(key).await?.body;
+            let stream = futures_util::stream::unfold(body, |mut body| async move {
+                let chunk = body
+                    .next()
+                    .await?
+                    .map_err(|e| StorageError::Backend(format!("S3 read body failed: {}", e)));
+                Some((chunk, body))
+            });
+            Ok(Box::pin(stream))
+        }
+
         async fn delete(&self, key: &str) -> Result<(), StorageError> {
             let full_key = self.full_key(key);
 
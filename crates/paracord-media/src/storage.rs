@@ -1,7 +1,11 @@
+use bytes::Bytes;
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
 #[derive(Debug, Error)]
@@ -16,6 +20,10 @@ pub enum StorageError {
     Backend(String),
 }
 
+/// Object contents yielded piece by piece, as returned by
+/// [`StorageBackend::retrieve_stream`].
+pub type ByteStream = BoxStream<'static, Result<Bytes, StorageError>>;
+
 /// Unified storage backend trait for file persistence.
 ///
 /// Implementations exist for local filesystem and S3-compatible object stores.
@@ -25,9 +33,16 @@ pub trait StorageBackend: Send + Sync {
     /// Store `data` under `key`, returning the canonical key.
     async fn store(&self, key: &str, data: &[u8]) -> Result<String, StorageError>;
 
+    /// Store the contents of the local file at `path` under `key` without
+    /// loading it into memory. The file is left in place.
+    async fn store_path(&self, key: &str, path: &Path) -> Result<String, StorageError>;
+
     /// Retrieve the raw bytes for `key`.
     async fn retrieve(&self, key: &str) -> Result<Vec<u8>, StorageError>;
 
+    /// Stream the raw bytes for `key` without loading the whole object.
+    async fn retrieve_stream(&self, key: &str) -> Result<ByteStream, StorageError>;
+
     /// Delete the object at `key`. No-op if it does not exist.
     async fn delete(&self, key: &str) -> Result<(), StorageError>;
 
@@ -60,6 +75,14 @@ impl Storage {
         }
     }
 
+    pub async fn store_path(&self, key: &str, path: &Path) -> Result<String, StorageError> {
+        match self {
+            Storage::Local(s) => s.store_path(key, path).await,
+            #[cfg(feature = "s3")]
+            Storage::S3(s) => s.store_path(key, path).await,
+        }
+    }
+
     pub async fn retrieve(&self, key: &str) -> Result<Vec<u8>, StorageError> {
         match self {
             Storage::Local(s) => s.retrieve(key).await,
@@ -68,6 +91,14 @@ impl Storage {
         }
     }
 
+    pub async fn retrieve_stream(&self, key: &str) -> Result<ByteStream, StorageError> {
+        match self {
+            Storage::Local(s) => s.retrieve_stream(key).await,
+            #[cfg(feature = "s3")]
+            Storage::S3(s) => s.retrieve_stream(key).await,
+        }
+    }
+
     pub async fn delete(&self, key: &str) -> Result<(), StorageError> {
         match self {
             Storage::Local(s) => s.delete(key).await,
@@ -118,6 +149,15 @@ impl StorageBackend for LocalStorage {
         Ok(key.to_string())
     }
 
+    async fn store_path(&self, key: &str, source: &Path) -> Result<String, StorageError> {
+        let path = self.base_path.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::copy(source, &path).await?;
+        Ok(key.to_string())
+    }
+
     async fn retrieve(&self, key: &str) -> Result<Vec<u8>, StorageError> {
         let path = self.base_path.join(key);
         if !Path::new(&path).exists() {
@@ -126,6 +166,20 @@ impl StorageBackend for LocalStorage {
         Ok(fs::read(&path).await?)
     }
 
+    async fn retrieve_stream(&self, key: &str) -> Result<ByteStream, StorageError> {
+        let path = self.base_path.join(key);
+        let file = match fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(StorageError::NotFound(key.to_string()));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(ReaderStream::new(file)
+            .map(|chunk| chunk.map_err(StorageError::from))
+            .boxed())
+    }
+
     async fn delete(&self, key: &str) -> Result<(), StorageError> {
         let path = self.base_path.join(key);
         if path.exists() {
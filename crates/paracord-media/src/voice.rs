@@ -1,8 +1,26 @@
+use chrono::{DateTime, Utc};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use super::livekit::AudioBitrate;
+use super::livekit::{AudioBitrate, LIVEKIT_TOKEN_TTL_SECONDS};
+
+/// LiveKit participant cap for voice channels without a user limit.
+pub const DEFAULT_ROOM_MAX_PARTICIPANTS: u32 = 99;
+
+/// Seats LiveKit keeps above a channel's `user_limit`. Members with
+/// MOVE_MEMBERS may join (or be moved into) a full channel; the API enforces
+/// the limit for everyone else, so the room cap is only a backstop.
+pub const USER_LIMIT_EXEMPT_HEADROOM: u32 = 10;
+
+/// LiveKit `max_participants` for a channel's `user_limit` (0 or unset means
+/// no limit).
+pub fn room_max_participants(user_limit: Option<i32>) -> u32 {
+    match user_limit {
+        Some(limit) if limit > 0 => limit as u32 + USER_LIMIT_EXEMPT_HEADROOM,
+        _ => DEFAULT_ROOM_MAX_PARTICIPANTS,
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct VoiceParticipant {
@@ -30,11 +48,59 @@ pub struct VoiceRoom {
     pub active_streamers: HashSet<i64>,
 }
 
+/// A LiveKit egress recording a voice channel.
+#[derive(Debug, Clone)]
+pub struct VoiceRecording {
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub egress_id: String,
+    /// `None` when the egress was started outside Paracord and only reported
+    /// through a LiveKit webhook.
+    pub started_by: Option<i64>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl VoiceRecording {
+    /// Gateway shape of an active recording, as sent in
+    /// `VOICE_RECORDING_UPDATE` and READY.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "guild_id": self.guild_id.to_string(),
+            "channel_id": self.channel_id.to_string(),
+            "recording": true,
+            "started_by": self.started_by.map(|id| id.to_string()),
+            "started_at": self.started_at.to_rfc3339(),
+        })
+    }
+}
+
 pub struct VoiceManager {
-    livekit: Arc<super::livekit::LiveKitConfig>,
+    /// Swapped whole when an admin rotates credentials; see [`Self::set_livekit`].
+    livekit: std::sync::RwLock<Arc<super::livekit::LiveKitConfig>>,
     rooms: RwLock<HashMap<i64, VoiceRoom>>,
     /// Maps channel_id -> LiveKit room name
     active_livekit_rooms: Arc<RwLock<HashMap<i64, String>>>,
+    /// Maps channel_id -> active recording
+    recordings: RwLock<HashMap<i64, VoiceRecording>>,
+    /// Channels with a recording start in flight, so two concurrent starts
+    /// can't both reach LiveKit.
+    recording_starts: Arc<std::sync::Mutex<HashSet<i64>>>,
+}
+
+/// Releases a channel's `recording_starts` claim when the start finishes,
+/// fails or is cancelled.
+struct RecordingStartClaim {
+    starts: Arc<std::sync::Mutex<HashSet<i64>>>,
+    channel_id: i64,
+}
+
+impl Drop for RecordingStartClaim {
+    fn drop(&mut self) {
+        self.starts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&self.channel_id);
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -54,13 +120,37 @@ pub struct StreamStartResponse {
 impl VoiceManager {
     pub fn new(livekit: Arc<super::livekit::LiveKitConfig>) -> Self {
         Self {
-            livekit,
+            livekit: std::sync::RwLock::new(livekit),
             rooms: RwLock::new(HashMap::new()),
             active_livekit_rooms: Arc::new(RwLock::new(HashMap::new())),
+            recordings: RwLock::new(HashMap::new()),
+            recording_starts: Arc::new(std::sync::Mutex::new(HashSet::new())),
         }
     }
 
+    /// LiveKit credentials and URLs currently used to mint tokens and call
+    /// the admin API.
+    pub fn livekit(&self) -> Arc<super::livekit::LiveKitConfig> {
+        self.livekit
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replace the LiveKit config. Tokens issued afterwards are signed with
+    /// the new secret; rooms and recordings already tracked are kept.
+    pub fn set_livekit(&self, livekit: Arc<super::livekit::LiveKitConfig>) {
+        *self
+            .livekit
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = livekit;
+    }
+
     /// Join a voice channel - creates LiveKit room if needed, returns token.
+    /// `max_participants` only applies when this join creates the room.
+    ///
+    /// `priority_speaker` should only be set for members holding the
+    /// PRIORITY_SPEAKER permission in the channel.
     #[allow(clippy::too_many_arguments)]
     pub async fn join_channel(
         &self,
@@ -70,7 +160,40 @@ impl VoiceManager {
         username: &str,
         session_id: &str,
         can_speak: bool,
+        priority_speaker: bool,
         bitrate: AudioBitrate,
+        max_participants: u32,
+    ) -> Result<VoiceJoinResponse, anyhow::Error> {
+        self.join_channel_with_token_ttl(
+            channel_id,
+            guild_id,
+            user_id,
+            username,
+            session_id,
+            can_speak,
+            priority_speaker,
+            bitrate,
+            max_participants,
+            LIVEKIT_TOKEN_TTL_SECONDS,
+        )
+        .await
+    }
+
+    /// Like [`join_channel`](Self::join_channel), but the returned token
+    /// expires `token_ttl_seconds` after issue.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn join_channel_with_token_ttl(
+        &self,
+        channel_id: i64,
+        guild_id: i64,
+        user_id: i64,
+        username: &str,
+        session_id: &str,
+        can_speak: bool,
+        priority_speaker: bool,
+        bitrate: AudioBitrate,
+        max_participants: u32,
+        token_ttl_seconds: u64,
     ) -> Result<VoiceJoinResponse, anyhow::Error> {
         let room_name = format!("guild_{}_channel_{}", guild_id, channel_id);
 
@@ -78,7 +201,9 @@ impl VoiceManager {
         {
             let mut lk_rooms = self.active_livekit_rooms.write().await;
             if let std::collections::hash_map::Entry::Vacant(e) = lk_rooms.entry(channel_id) {
-                self.livekit.create_room(&room_name, 99, bitrate).await?;
+                self.livekit()
+                    .create_room(&room_name, max_participants, bitrate)
+                    .await?;
                 e.insert(room_name.clone());
             }
         }
@@ -104,19 +229,34 @@ impl VoiceManager {
                     self_video: false,
                     server_mute: false,
                     server_deaf: false,
-                    priority_speaker: false,
+                    priority_speaker,
                 },
             );
         }
 
-        // Generate participant token
-        let token = self
-            .livekit
-            .generate_voice_token(&room_name, user_id, username, can_speak, true)?;
+        // Generate participant token; priority speakers get the priority
+        // metadata so clients can duck other speakers.
+        let token = if priority_speaker && can_speak {
+            self.livekit().generate_priority_speaker_token(
+                &room_name,
+                user_id,
+                username,
+                token_ttl_seconds,
+            )?
+        } else {
+            self.livekit().generate_voice_token(
+                &room_name,
+                user_id,
+                username,
+                can_speak,
+                true,
+                token_ttl_seconds,
+            )?
+        };
 
         Ok(VoiceJoinResponse {
             token,
-            url: self.livekit.url.clone(),
+            url: self.livekit().url.clone(),
             room_name,
         })
     }
@@ -145,12 +285,12 @@ impl VoiceManager {
         }
 
         let token =
-            self.livekit
+            self.livekit()
                 .generate_stream_token(&room_name, user_id, username, stream_title)?;
 
         Ok(StreamStartResponse {
             token,
-            url: self.livekit.url.clone(),
+            url: self.livekit().url.clone(),
             room_name,
         })
     }
@@ -230,11 +370,28 @@ impl VoiceManager {
     pub async fn cleanup_room(&self, channel_id: i64) -> Result<(), anyhow::Error> {
         let mut lk_rooms = self.active_livekit_rooms.write().await;
         if let Some(room_name) = lk_rooms.remove(&channel_id) {
-            self.livekit.delete_room(&room_name).await?;
+            self.livekit().delete_room(&room_name).await?;
         }
         Ok(())
     }
 
+    /// Disconnect a user from the LiveKit room behind `channel_id`.
+    pub async fn remove_participant(
+        &self,
+        channel_id: i64,
+        guild_id: i64,
+        user_id: i64,
+    ) -> Result<(), anyhow::Error> {
+        let room_name = {
+            let lk_rooms = self.active_livekit_rooms.read().await;
+            lk_rooms.get(&channel_id).cloned()
+        }
+        .unwrap_or_else(|| format!("guild_{}_channel_{}", guild_id, channel_id));
+        self.livekit()
+            .remove_participant(&room_name, &user_id.to_string())
+            .await
+    }
+
     /// Check whether a specific participant is currently tracked in a room (local state).
     pub async fn is_participant_in_room(&self, channel_id: i64, user_id: i64) -> bool {
         let rooms = self.rooms.read().await;
@@ -270,7 +427,7 @@ impl VoiceManager {
                 None => return false,
             }
         };
-        match self.livekit.list_participants(&room_name).await {
+        match self.livekit().list_participants(&room_name).await {
             Ok(participants) => {
                 let user_id_str = user_id.to_string();
                 participants.iter().any(|p| {
@@ -335,7 +492,7 @@ impl VoiceManager {
 
         // Update LiveKit permissions
         let identity = user_id.to_string();
-        self.livekit
+        self.livekit()
             .update_participant(
                 &room_name,
                 &identity,
@@ -379,7 +536,7 @@ impl VoiceManager {
 
         // Update LiveKit permissions
         let identity = user_id.to_string();
-        self.livekit
+        self.livekit()
             .update_participant(
                 &room_name,
                 &identity,
@@ -411,9 +568,12 @@ impl VoiceManager {
 
         if priority {
             let room_name = format!("guild_{}_channel_{}", guild_id, channel_id);
-            let token = self
-                .livekit
-                .generate_priority_speaker_token(&room_name, user_id, username)?;
+            let token = self.livekit().generate_priority_speaker_token(
+                &room_name,
+                user_id,
+                username,
+                LIVEKIT_TOKEN_TTL_SECONDS,
+            )?;
             Ok(Some(token))
         } else {
             Ok(None)
@@ -454,9 +614,147 @@ impl VoiceManager {
             .unwrap_or(false)
     }
 
+    /// Apply a changed participant cap to the channel's LiveKit room, if one
+    /// is open. LiveKit's `CreateRoom` updates the settings of an existing
+    /// room, so later `user_limit` edits take effect without a restart.
+    pub async fn update_room_max_participants(
+        &self,
+        channel_id: i64,
+        max_participants: u32,
+    ) -> Result<(), anyhow::Error> {
+        let Some(room_name) = self.get_room_name(channel_id).await else {
+            return Ok(());
+        };
+        let bitrate = self
+            .rooms
+            .read()
+            .await
+            .get(&channel_id)
+            .map(|room| room.audio_bitrate)
+            .unwrap_or_default();
+        self.livekit()
+            .create_room(&room_name, max_participants, bitrate)
+            .await
+    }
+
     /// Get the LiveKit room name for a channel, if active.
     pub async fn get_room_name(&self, channel_id: i64) -> Option<String> {
         let lk_rooms = self.active_livekit_rooms.read().await;
         lk_rooms.get(&channel_id).cloned()
     }
+
+    /// Start recording a voice channel through LiveKit egress, writing to
+    /// the egress `filepath` template. Returns `None` if the channel is
+    /// already being recorded or another start is in flight.
+    pub async fn start_recording(
+        &self,
+        channel_id: i64,
+        guild_id: i64,
+        user_id: i64,
+        filepath: &str,
+    ) -> Result<Option<VoiceRecording>, anyhow::Error> {
+        let _claim = {
+            let recordings = self.recordings.read().await;
+            let mut starts = self
+                .recording_starts
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if recordings.contains_key(&channel_id) || !starts.insert(channel_id) {
+                return Ok(None);
+            }
+            RecordingStartClaim {
+                starts: self.recording_starts.clone(),
+                channel_id,
+            }
+        };
+
+        let room_name = format!("guild_{}_channel_{}", guild_id, channel_id);
+        let egress_id = self
+            .livekit()
+            .start_room_recording(&room_name, filepath)
+            .await?;
+        let recording = VoiceRecording {
+            guild_id,
+            channel_id,
+            egress_id,
+            started_by: Some(user_id),
+            started_at: Utc::now(),
+        };
+        self.recordings
+            .write()
+            .await
+            .insert(channel_id, recording.clone());
+        Ok(Some(recording))
+    }
+
+    /// Stop the channel's recording, if any. Returns the recording that ended.
+    pub async fn stop_recording(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<VoiceRecording>, anyhow::Error> {
+        let Some(recording) = self.get_recording(channel_id).await else {
+            return Ok(None);
+        };
+        self.livekit().stop_egress(&recording.egress_id).await?;
+        Ok(self.recording_ended(channel_id, &recording.egress_id).await)
+    }
+
+    /// Record an egress reported as started by LiveKit. Returns the new
+    /// recording, or `None` if it was already known.
+    pub async fn recording_started(
+        &self,
+        channel_id: i64,
+        guild_id: i64,
+        egress_id: &str,
+    ) -> Option<VoiceRecording> {
+        let mut recordings = self.recordings.write().await;
+        if recordings
+            .get(&channel_id)
+            .is_some_and(|r| r.egress_id == egress_id)
+        {
+            return None;
+        }
+        let recording = VoiceRecording {
+            guild_id,
+            channel_id,
+            egress_id: egress_id.to_string(),
+            started_by: None,
+            started_at: Utc::now(),
+        };
+        recordings.insert(channel_id, recording.clone());
+        Some(recording)
+    }
+
+    /// Forget the channel's recording if it is `egress_id`. Returns the
+    /// recording that ended.
+    pub async fn recording_ended(
+        &self,
+        channel_id: i64,
+        egress_id: &str,
+    ) -> Option<VoiceRecording> {
+        let mut recordings = self.recordings.write().await;
+        if recordings
+            .get(&channel_id)
+            .is_some_and(|r| r.egress_id == egress_id)
+        {
+            return recordings.remove(&channel_id);
+        }
+        None
+    }
+
+    /// Get the active recording for a channel.
+    pub async fn get_recording(&self, channel_id: i64) -> Option<VoiceRecording> {
+        self.recordings.read().await.get(&channel_id).cloned()
+    }
+
+    /// Get all active recordings in a guild.
+    pub async fn get_guild_recordings(&self, guild_id: i64) -> Vec<VoiceRecording> {
+        self.recordings
+            .read()
+            .await
+            .values()
+            .filter(|r| r.guild_id == guild_id)
+            .cloned()
+            .collect()
+    }
 }
@@ -68,14 +68,18 @@ pub const EVENT_MESSAGE_DELETE_BULK: &str = "MESSAGE_DELETE_BULK";
 pub const EVENT_MESSAGE_REACTION_ADD: &str = "MESSAGE_REACTION_ADD";
 pub const EVENT_MESSAGE_REACTION_REMOVE: &str = "MESSAGE_REACTION_REMOVE";
 pub const EVENT_MESSAGE_REACTION_REMOVE_ALL: &str = "MESSAGE_REACTION_REMOVE_ALL";
+/// Sent only to the users a new message mentions and who can see its channel.
+pub const EVENT_MESSAGE_MENTION: &str = "MESSAGE_MENTION";
 
 // Presence and typing
 pub const EVENT_PRESENCE_UPDATE: &str = "PRESENCE_UPDATE";
 pub const EVENT_TYPING_START: &str = "TYPING_START";
+pub const EVENT_TYPING_STOP: &str = "TYPING_STOP";
 
 // Voice events
 pub const EVENT_VOICE_STATE_UPDATE: &str = "VOICE_STATE_UPDATE";
 pub const EVENT_VOICE_SERVER_UPDATE: &str = "VOICE_SERVER_UPDATE";
+pub const EVENT_VOICE_AVAILABILITY_UPDATE: &str = "VOICE_AVAILABILITY_UPDATE";
 
 // Invite events
 pub const EVENT_INVITE_CREATE: &str = "INVITE_CREATE";
@@ -85,6 +89,12 @@ pub const EVENT_INVITE_DELETE: &str = "INVITE_DELETE";
 pub const EVENT_RELATIONSHIP_ADD: &str = "RELATIONSHIP_ADD";
 pub const EVENT_RELATIONSHIP_REMOVE: &str = "RELATIONSHIP_REMOVE";
 
+// Session events
+pub const EVENT_SESSIONS_REVOKED: &str = "SESSIONS_REVOKED";
+
+// Upload events
+pub const EVENT_UPLOAD_PROGRESS: &str = "UPLOAD_PROGRESS";
+
 // Media events
 pub const EVENT_MEDIA_SESSION_DESC: &str = "MEDIA_SESSION_DESC";
 pub const EVENT_MEDIA_KEY_DELIVER: &str = "MEDIA_KEY_DELIVER";
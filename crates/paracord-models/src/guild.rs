@@ -18,3 +18,34 @@ pub struct Guild {
     pub hub_settings: Option<serde_json::Value>,
     pub bot_settings: Option<serde_json::Value>,
 }
+
+/// Guild may give roles an emoji or uploaded image icon.
+pub const GUILD_FEATURE_ROLE_ICONS: i32 = 1 << 0;
+/// Links in the guild's messages get server-side previews, when the server
+/// has link unfurling enabled.
+pub const GUILD_FEATURE_LINK_UNFURLS: i32 = 1 << 1;
+
+const GUILD_FEATURE_NAMES: &[(i32, &str)] = &[
+    (GUILD_FEATURE_ROLE_ICONS, "ROLE_ICONS"),
+    (GUILD_FEATURE_LINK_UNFURLS, "LINK_UNFURLS"),
+];
+
+/// Names of the feature bits set in `features`.
+pub fn guild_feature_names(features: i32) -> Vec<String> {
+    GUILD_FEATURE_NAMES
+        .iter()
+        .filter(|(bit, _)| features & bit != 0)
+        .map(|(_, name)| (*name).to_string())
+        .collect()
+}
+
+/// Feature bits for `names`, or the first unknown name.
+pub fn guild_feature_bits(names: &[String]) -> Result<i32, String> {
+    names.iter().try_fold(0, |bits, name| {
+        GUILD_FEATURE_NAMES
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name.trim()))
+            .map(|(bit, _)| bits | bit)
+            .ok_or_else(|| name.clone())
+    })
+}
@@ -7,6 +7,15 @@ pub struct Args {
     #[arg(short, long, default_value = "config/paracord.toml")]
     pub config: String,
 
+    /// Root directory for server data; paths not set explicitly default under it
+    #[arg(long)]
+    pub data_dir: Option<String>,
+
+    /// Development mode: start with an ephemeral JWT secret instead of
+    /// refusing when the configured one is empty or weak
+    #[arg(long)]
+    pub dev: bool,
+
     /// Path to directory containing built web UI files (overrides config)
     #[arg(long)]
     pub web_dir: Option<String>,
@@ -1,8 +1,14 @@
 use anyhow::Result;
+use paracord_core::guild::DefaultChannel;
 use paracord_media::S3Config;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Root directory that every on-disk path defaults under when no
+/// `server.data_dir` / `--data-dir` is configured.
+pub const DEFAULT_DATA_DIR: &str = "./data";
 
 fn harden_secret_file_permissions(path: &str) -> Result<()> {
     #[cfg(unix)]
@@ -60,6 +66,14 @@ pub struct Config {
     pub at_rest: AtRestConfig,
     #[serde(default)]
     pub backup: BackupConfig,
+    #[serde(default)]
+    pub abuse: AbuseConfig,
+    #[serde(default)]
+    pub link_unfurl: LinkUnfurlConfig,
+    #[serde(default)]
+    pub push: PushConfig,
+    #[serde(default)]
+    pub mail: MailConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -72,6 +86,56 @@ pub struct ServerConfig {
     /// Public URL of this server (e.g., https://chat.example.com).
     /// Used for CORS auto-configuration and invite links.
     pub public_url: Option<String>,
+    /// Root directory for all server data. Storage, media, backups, the
+    /// SQLite database, ACME/TLS files and the federation key default under
+    /// it unless configured explicitly.
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    /// Content-Security-Policy for the served web UI. API responses always
+    /// use a locked-down policy.
+    #[serde(default)]
+    pub ui_content_security_policy: Option<String>,
+    /// `Referrer-Policy` header value. Defaults to `no-referrer`.
+    #[serde(default)]
+    pub referrer_policy: Option<String>,
+    /// `X-Frame-Options` header value (`DENY` or `SAMEORIGIN`). Defaults to `DENY`.
+    #[serde(default)]
+    pub frame_options: Option<String>,
+    /// What to do with markup in display names, bios and custom statuses:
+    /// `reject` (default) or `strip`.
+    #[serde(default)]
+    pub profile_markup: Option<String>,
+    /// Allow inline `data:image/...` URLs in users' custom CSS themes.
+    #[serde(default)]
+    pub custom_css_data_images: bool,
+    /// Unicode reactions that differ only by skin tone: `separate` (default)
+    /// counts them apart, `merge` counts them under the base emoji.
+    #[serde(default)]
+    pub reaction_skin_tones: Option<String>,
+    /// BCP-47 locales users may choose in their settings. Unset keeps the
+    /// built-in list.
+    #[serde(default)]
+    pub allowed_locales: Option<Vec<String>>,
+    /// Seconds a read (GET/HEAD/OPTIONS) may take before it is cancelled with a
+    /// 503 (0 = no limit). Writes always run to completion.
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Deadline for downloads, exports, backups and the LiveKit proxy (0 = no limit).
+    #[serde(default = "default_long_request_timeout_secs")]
+    pub long_request_timeout_secs: u64,
+    /// Channels a new guild starts with, in order. Unset keeps the built-in
+    /// `general` text and `General` voice channels.
+    #[serde(default)]
+    pub default_guild_channels: Option<Vec<DefaultChannel>>,
+    /// On `zlib-stream` gateway connections, payloads up to this many bytes
+    /// are sent as plain text frames; compressing them costs more CPU than
+    /// it saves. 0 compresses everything.
+    #[serde(default = "default_gateway_compression_threshold_bytes")]
+    pub gateway_compression_threshold_bytes: usize,
+    /// Smallest JSON/text REST response, in bytes, that is gzip or brotli
+    /// encoded for clients that accept it.
+    #[serde(default = "default_http_compression_min_bytes")]
+    pub http_compression_min_bytes: u16,
 }
 
 impl Default for ServerConfig {
@@ -81,6 +145,19 @@ impl Default for ServerConfig {
             server_name: default_server_name(),
             web_dir: None,
             public_url: None,
+            data_dir: None,
+            ui_content_security_policy: None,
+            referrer_policy: None,
+            frame_options: None,
+            profile_markup: None,
+            custom_css_data_images: false,
+            reaction_skin_tones: None,
+            allowed_locales: None,
+            request_timeout_secs: default_request_timeout_secs(),
+            long_request_timeout_secs: default_long_request_timeout_secs(),
+            default_guild_channels: None,
+            gateway_compression_threshold_bytes: default_gateway_compression_threshold_bytes(),
+            http_compression_min_bytes: default_http_compression_min_bytes(),
         }
     }
 }
@@ -98,6 +175,23 @@ pub struct DatabaseConfig {
     /// Idle-in-transaction timeout in seconds for PostgreSQL (0 = disabled).
     #[serde(default)]
     pub idle_in_transaction_timeout_secs: u64,
+    /// How long a SQLite writer waits for a lock before failing with
+    /// `SQLITE_BUSY`, in milliseconds (0 = fail immediately).
+    #[serde(default = "default_sqlite_busy_timeout_ms")]
+    pub sqlite_busy_timeout_ms: u64,
+    /// SQLite `PRAGMA synchronous` level.
+    #[serde(default)]
+    pub sqlite_synchronous: SqliteSynchronous,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SqliteSynchronous {
+    Off,
+    #[default]
+    Normal,
+    Full,
+    Extra,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
@@ -121,6 +215,8 @@ impl Default for DatabaseConfig {
             max_connections: default_max_connections(),
             statement_timeout_secs: 0,
             idle_in_transaction_timeout_secs: 0,
+            sqlite_busy_timeout_ms: default_sqlite_busy_timeout_ms(),
+            sqlite_synchronous: SqliteSynchronous::default(),
         }
     }
 }
@@ -136,6 +232,26 @@ pub struct AuthConfig {
     pub allow_username_login: bool,
     #[serde(default = "default_false")]
     pub require_email: bool,
+    /// Handle mode: usernames are globally unique (case-insensitive) and
+    /// every new account gets discriminator 0.
+    #[serde(default = "default_false")]
+    pub unique_usernames: bool,
+    /// Require a double-submit CSRF token on state-changing requests that
+    /// authenticate with the session cookie. Bearer-token clients are exempt.
+    #[serde(default = "default_true")]
+    pub csrf_protection: bool,
+    /// Consecutive failed password logins allowed per source IP and login
+    /// identifier before further attempts are refused for
+    /// `login_lockout_seconds`. 0 (the default) disables the lockout.
+    #[serde(default = "default_login_max_failures")]
+    pub login_max_failures: u32,
+    /// How long, in seconds, a locked-out IP and identifier pair waits.
+    #[serde(default = "default_login_lockout_seconds")]
+    pub login_lockout_seconds: u64,
+    /// Sessions unused for this many seconds stop authenticating even before
+    /// their absolute expiry. 0 disables idle expiry.
+    #[serde(default = "default_session_idle_timeout_seconds")]
+    pub session_idle_timeout_seconds: u64,
 }
 
 impl Default for AuthConfig {
@@ -146,6 +262,11 @@ impl Default for AuthConfig {
             registration_enabled: true,
             allow_username_login: true,
             require_email: false,
+            unique_usernames: false,
+            csrf_protection: true,
+            login_max_failures: default_login_max_failures(),
+            login_lockout_seconds: default_login_lockout_seconds(),
+            session_idle_timeout_seconds: default_session_idle_timeout_seconds(),
         }
     }
 }
@@ -160,6 +281,45 @@ pub struct StorageConfig {
     pub max_upload_size: u64,
     #[serde(default = "default_max_guild_storage_quota")]
     pub max_guild_storage_quota: u64,
+    /// Maximum number of files linked to a single message (0 = unlimited).
+    #[serde(default = "default_max_attachments_per_message")]
+    pub max_attachments_per_message: u32,
+    /// Maximum combined size in bytes of the files on a single message
+    /// (0 = unlimited).
+    #[serde(default = "default_max_attachment_bytes_per_message")]
+    pub max_attachment_bytes_per_message: u64,
+    /// Longest attachment filename kept, in characters. Longer names are
+    /// truncated at upload with the extension preserved.
+    #[serde(default = "default_max_attachment_filename_length")]
+    pub max_attachment_filename_length: u32,
+    /// Uploads a user may have waiting to be linked to a message before
+    /// further uploads get 429 (0 = unlimited).
+    #[serde(default = "default_max_pending_uploads_per_user")]
+    pub max_pending_uploads_per_user: u32,
+    /// Where flagged uploads are moved. Defaults to `<path>/quarantine`.
+    #[serde(default)]
+    pub quarantine_path: Option<String>,
+    /// CDN origin prefixed onto returned media URLs (attachments, emojis,
+    /// role icons). Unset keeps them relative to this server.
+    #[serde(default)]
+    pub media_cdn_base_url: Option<String>,
+    /// Origins besides `server.public_url` whose pages may show attachments
+    /// inline. When set, downloads referred from any other site are served
+    /// as `attachment` instead (empty = no check).
+    #[serde(default)]
+    pub inline_allowed_origins: Vec<String>,
+    /// Require a short-lived token from
+    /// `GET /api/v1/attachments/{id}/embed-token` to serve an attachment
+    /// inline; the value is the token lifetime in seconds (0 = off).
+    #[serde(default)]
+    pub inline_token_ttl_seconds: u64,
+    /// Log each attachment download with the user, attachment and referrer.
+    #[serde(default)]
+    pub log_attachment_access: bool,
+    /// How often, in seconds, attachments left behind by deleted messages
+    /// or expired uploads are removed with their files (0 = never).
+    #[serde(default = "default_orphaned_attachment_gc_interval_seconds")]
+    pub orphaned_attachment_gc_interval_seconds: u64,
 }
 
 impl Default for StorageConfig {
@@ -169,6 +329,17 @@ impl Default for StorageConfig {
             path: default_storage_path(),
             max_upload_size: default_max_upload_size(),
             max_guild_storage_quota: default_max_guild_storage_quota(),
+            max_attachments_per_message: default_max_attachments_per_message(),
+            max_attachment_bytes_per_message: default_max_attachment_bytes_per_message(),
+            max_attachment_filename_length: default_max_attachment_filename_length(),
+            max_pending_uploads_per_user: default_max_pending_uploads_per_user(),
+            quarantine_path: None,
+            media_cdn_base_url: None,
+            inline_allowed_origins: Vec::new(),
+            inline_token_ttl_seconds: 0,
+            log_attachment_access: false,
+            orphaned_attachment_gc_interval_seconds:
+                default_orphaned_attachment_gc_interval_seconds(),
         }
     }
 }
@@ -241,6 +412,13 @@ pub struct LiveKitConfig {
     /// Public LiveKit URL sent to clients (e.g., wss://chat.example.com/livekit).
     /// Falls back to `url` if not set.
     pub public_url: Option<String>,
+    /// Let members with MANAGE_CHANNELS record voice channels through
+    /// LiveKit Egress.
+    #[serde(default = "default_false")]
+    pub recording_enabled: bool,
+    /// Egress output path template; LiveKit fills in `{room_name}` and `{time}`.
+    #[serde(default = "default_livekit_recording_filepath")]
+    pub recording_filepath: String,
 }
 
 impl Default for LiveKitConfig {
@@ -251,6 +429,8 @@ impl Default for LiveKitConfig {
             url: default_livekit_url(),
             http_url: default_livekit_http_url(),
             public_url: None,
+            recording_enabled: false,
+            recording_filepath: default_livekit_recording_filepath(),
         }
     }
 }
@@ -260,12 +440,21 @@ pub struct NetworkConfig {
     /// On Windows, automatically add local firewall allow rules on startup.
     #[serde(default = "default_false")]
     pub windows_firewall_auto_allow: bool,
+    /// Honor `X-Forwarded-For` / `Forwarded` from the proxies listed in
+    /// `trusted_proxies` when deriving the client IP.
+    #[serde(default = "default_false")]
+    pub trust_proxy: bool,
+    /// Reverse-proxy addresses (IPs or CIDRs) whose forwarded headers are trusted.
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
 }
 
 impl Default for NetworkConfig {
     fn default() -> Self {
         Self {
             windows_firewall_auto_allow: false,
+            trust_proxy: false,
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -358,6 +547,10 @@ pub struct RetentionConfig {
     pub attachment_days: Option<i64>,
     pub audit_log_days: Option<i64>,
     pub security_event_days: Option<i64>,
+    /// When set, security events are appended to this JSON-lines file before
+    /// retention deletes them.
+    #[serde(default)]
+    pub security_event_archive_path: Option<String>,
     pub session_days: Option<i64>,
 }
 
@@ -371,6 +564,7 @@ impl Default for RetentionConfig {
             attachment_days: None,
             audit_log_days: None,
             security_event_days: Some(30),
+            security_event_archive_path: None,
             session_days: Some(30),
         }
     }
@@ -388,6 +582,8 @@ pub struct AtRestConfig {
     pub encrypt_files: bool,
     #[serde(default = "default_false")]
     pub allow_plaintext_file_reads: bool,
+    #[serde(default = "default_false")]
+    pub chunked_files: bool,
 }
 
 impl Default for AtRestConfig {
@@ -398,6 +594,7 @@ impl Default for AtRestConfig {
             encrypt_sqlite: false,
             encrypt_files: false,
             allow_plaintext_file_reads: false,
+            chunked_files: false,
         }
     }
 }
@@ -415,12 +612,26 @@ pub struct FederationConfig {
     pub max_events_per_peer_per_minute: Option<u32>,
     #[serde(default = "default_max_user_creates_per_peer_per_hour")]
     pub max_user_creates_per_peer_per_hour: Option<u32>,
+    #[serde(default = "default_federation_media_token_ttl_seconds")]
+    pub media_token_ttl_seconds: u64,
+    #[serde(default = "default_federation_file_token_ttl_seconds")]
+    pub file_token_ttl_seconds: u64,
     #[serde(default = "default_false")]
     pub file_cache_enabled: bool,
     #[serde(default = "default_federation_file_cache_max_size")]
     pub file_cache_max_size: u64,
     #[serde(default = "default_federation_file_cache_ttl_hours")]
     pub file_cache_ttl_hours: u64,
+    #[serde(default = "default_federation_queue_retry_base_seconds")]
+    pub queue_retry_base_seconds: u64,
+    #[serde(default = "default_federation_queue_retry_max_seconds")]
+    pub queue_retry_max_seconds: u64,
+    #[serde(default = "default_federation_queue_max_attempts")]
+    pub queue_max_attempts: u32,
+    #[serde(default = "default_federation_queue_max_age_hours")]
+    pub queue_max_age_hours: u64,
+    #[serde(default = "default_federation_dead_letter_retention_hours")]
+    pub dead_letter_retention_hours: u64,
 }
 
 impl Default for FederationConfig {
@@ -432,9 +643,16 @@ impl Default for FederationConfig {
             allow_discovery: false,
             max_events_per_peer_per_minute: default_max_events_per_peer_per_minute(),
             max_user_creates_per_peer_per_hour: default_max_user_creates_per_peer_per_hour(),
+            media_token_ttl_seconds: default_federation_media_token_ttl_seconds(),
+            file_token_ttl_seconds: default_federation_file_token_ttl_seconds(),
             file_cache_enabled: false,
             file_cache_max_size: default_federation_file_cache_max_size(),
             file_cache_ttl_hours: default_federation_file_cache_ttl_hours(),
+            queue_retry_base_seconds: default_federation_queue_retry_base_seconds(),
+            queue_retry_max_seconds: default_federation_queue_retry_max_seconds(),
+            queue_max_attempts: default_federation_queue_max_attempts(),
+            queue_max_age_hours: default_federation_queue_max_age_hours(),
+            dead_letter_retention_hours: default_federation_dead_letter_retention_hours(),
         }
     }
 }
@@ -465,6 +683,172 @@ impl Default for BackupConfig {
     }
 }
 
+/// Per-user activity thresholds for abuse detection. A threshold of `0`
+/// disables that check.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AbuseConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_abuse_messages_per_minute")]
+    pub messages_per_minute: u32,
+    #[serde(default = "default_abuse_uploads_per_minute")]
+    pub uploads_per_minute: u32,
+    #[serde(default = "default_abuse_upload_bytes_per_hour")]
+    pub upload_bytes_per_hour: u64,
+    /// How long an over-threshold account is blocked from posting. `0` only
+    /// flags the account for admin review.
+    #[serde(default = "default_abuse_auto_timeout_seconds")]
+    pub auto_timeout_seconds: u64,
+    /// Executions allowed per webhook per minute, across every source address.
+    #[serde(default = "default_abuse_webhook_executions_per_minute")]
+    pub webhook_executions_per_minute: u32,
+    /// Where rate-limit counters live: `memory` (per process, default) or
+    /// `database` (shared by every process on the same database).
+    #[serde(default)]
+    pub rate_limit_store: RateLimitStoreKind,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitStoreKind {
+    #[default]
+    Memory,
+    Database,
+}
+
+impl Default for AbuseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            messages_per_minute: default_abuse_messages_per_minute(),
+            uploads_per_minute: default_abuse_uploads_per_minute(),
+            upload_bytes_per_hour: default_abuse_upload_bytes_per_hour(),
+            auto_timeout_seconds: default_abuse_auto_timeout_seconds(),
+            webhook_executions_per_minute: default_abuse_webhook_executions_per_minute(),
+            rate_limit_store: RateLimitStoreKind::default(),
+        }
+    }
+}
+
+/// Server-side link previews. Guilds also need the `LINK_UNFURLS` feature,
+/// which guild managers turn on for their guild.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LinkUnfurlConfig {
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+    #[serde(default = "default_link_unfurl_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Bytes of a page read when looking for metadata.
+    #[serde(default = "default_link_unfurl_max_bytes")]
+    pub max_bytes: usize,
+    /// Largest preview image the server fetches and serves; 0 leaves
+    /// previews without images.
+    #[serde(default = "default_link_unfurl_max_image_bytes")]
+    pub max_image_bytes: usize,
+    #[serde(default = "default_link_unfurl_cache_ttl_seconds")]
+    pub cache_ttl_seconds: u64,
+}
+
+impl Default for LinkUnfurlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_seconds: default_link_unfurl_timeout_seconds(),
+            max_bytes: default_link_unfurl_max_bytes(),
+            max_image_bytes: default_link_unfurl_max_image_bytes(),
+            cache_ttl_seconds: default_link_unfurl_cache_ttl_seconds(),
+        }
+    }
+}
+
+/// Web Push notifications for mentions and DMs while a user is offline.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PushConfig {
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+    /// VAPID private key: a raw P-256 key in unpadded base64url, or PEM.
+    #[serde(default)]
+    pub vapid_private_key: String,
+    /// Contact (`mailto:` or `https:` URL) sent to push services. Defaults
+    /// to `server.public_url`.
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default = "default_push_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            vapid_private_key: String::new(),
+            subject: None,
+            timeout_seconds: default_push_timeout_seconds(),
+        }
+    }
+}
+
+impl PushConfig {
+    /// The VAPID contact: `subject`, else the server's public URL.
+    pub fn effective_subject(&self, public_url: Option<&str>) -> Option<String> {
+        self.subject
+            .as_deref()
+            .or(public_url)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    }
+}
+
+/// Outbound email over SMTP, used for account verification messages.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MailConfig {
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub smtp_host: String,
+    #[serde(default = "default_mail_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// Sender, e.g. `Paracord <noreply@example.com>`.
+    #[serde(default)]
+    pub from: String,
+    #[serde(default = "default_mail_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (port 587).
+    #[default]
+    Starttls,
+    /// TLS from the first byte (port 465).
+    Tls,
+    /// No encryption; only for a relay on the same host.
+    None,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: String::new(),
+            smtp_port: default_mail_smtp_port(),
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+            timeout_seconds: default_mail_timeout_seconds(),
+        }
+    }
+}
+
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
 /// Generate a cryptographically random hex string of the given length.
@@ -485,15 +869,39 @@ fn generate_random_hex(len: usize) -> String {
 fn default_server_name() -> String {
     "localhost".into()
 }
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+fn default_long_request_timeout_secs() -> u64 {
+    600
+}
+fn default_gateway_compression_threshold_bytes() -> usize {
+    256
+}
+fn default_http_compression_min_bytes() -> u16 {
+    1024
+}
 fn default_database_engine() -> DatabaseEngine {
     DatabaseEngine::Sqlite
 }
 fn default_max_connections() -> u32 {
     20
 }
+fn default_sqlite_busy_timeout_ms() -> u64 {
+    5000
+}
 fn default_jwt_expiry() -> u64 {
     900
 }
+fn default_login_max_failures() -> u32 {
+    0
+}
+fn default_login_lockout_seconds() -> u64 {
+    900
+}
+fn default_session_idle_timeout_seconds() -> u64 {
+    14 * 24 * 3600
+}
 fn default_true() -> bool {
     true
 }
@@ -530,6 +938,9 @@ fn default_livekit_url() -> String {
 fn default_livekit_http_url() -> String {
     "http://127.0.0.1:7880".into()
 }
+fn default_livekit_recording_filepath() -> String {
+    "recordings/{room_name}-{time}".into()
+}
 fn default_voice_port() -> u16 {
     8443
 }
@@ -581,15 +992,51 @@ fn default_max_events_per_peer_per_minute() -> Option<u32> {
 fn default_max_user_creates_per_peer_per_hour() -> Option<u32> {
     Some(100)
 }
+fn default_federation_media_token_ttl_seconds() -> u64 {
+    600
+}
+fn default_federation_file_token_ttl_seconds() -> u64 {
+    300
+}
 fn default_max_guild_storage_quota() -> u64 {
     5_368_709_120 // 5GB
 }
+fn default_max_attachments_per_message() -> u32 {
+    10
+}
+fn default_max_attachment_bytes_per_message() -> u64 {
+    104_857_600 // 100MB
+}
+fn default_max_attachment_filename_length() -> u32 {
+    255
+}
+fn default_max_pending_uploads_per_user() -> u32 {
+    50
+}
+fn default_orphaned_attachment_gc_interval_seconds() -> u64 {
+    3600
+}
 fn default_federation_file_cache_max_size() -> u64 {
     1_073_741_824 // 1GB
 }
 fn default_federation_file_cache_ttl_hours() -> u64 {
     168 // 7 days
 }
+fn default_federation_queue_retry_base_seconds() -> u64 {
+    5
+}
+fn default_federation_queue_retry_max_seconds() -> u64 {
+    3600
+}
+fn default_federation_queue_max_attempts() -> u32 {
+    12
+}
+fn default_federation_queue_max_age_hours() -> u64 {
+    24
+}
+fn default_federation_dead_letter_retention_hours() -> u64 {
+    168 // 7 days
+}
 fn default_backup_dir() -> String {
     "./data/backups".into()
 }
@@ -599,6 +1046,55 @@ fn default_auto_backup_interval() -> u64 {
 fn default_max_backups() -> u32 {
     10
 }
+fn default_abuse_messages_per_minute() -> u32 {
+    60
+}
+fn default_abuse_uploads_per_minute() -> u32 {
+    20
+}
+fn default_abuse_upload_bytes_per_hour() -> u64 {
+    2_147_483_648 // 2GB
+}
+fn default_abuse_auto_timeout_seconds() -> u64 {
+    600
+}
+fn default_abuse_webhook_executions_per_minute() -> u32 {
+    30
+}
+fn default_link_unfurl_timeout_seconds() -> u64 {
+    5
+}
+fn default_link_unfurl_max_bytes() -> usize {
+    1_048_576 // 1MB
+}
+fn default_link_unfurl_max_image_bytes() -> usize {
+    2_097_152 // 2MB
+}
+fn default_link_unfurl_cache_ttl_seconds() -> u64 {
+    3600
+}
+fn default_push_timeout_seconds() -> u64 {
+    10
+}
+fn default_mail_smtp_port() -> u16 {
+    587
+}
+fn default_mail_timeout_seconds() -> u64 {
+    10
+}
+
+/// Past this many pooled SQLite connections, extra writers just queue on the
+/// database lock.
+const SQLITE_MAX_USEFUL_CONNECTIONS: u32 = 20;
+
+/// Shortest `auth.jwt_secret` accepted outside `--dev` mode.
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+/// Example secrets shipped in our docs and compose files.
+const KNOWN_DEFAULT_JWT_SECRETS: &[&str] = &[
+    "your-64-char-random-secret-here",
+    "0123456789abcdef0123456789abcdef",
+];
 
 fn looks_like_placeholder_secret(raw: &str) -> bool {
     let normalized = raw.trim().to_ascii_lowercase();
@@ -614,23 +1110,28 @@ fn looks_like_placeholder_secret(raw: &str) -> bool {
         || normalized == "secret"
 }
 
-fn validate_secret_configuration(config: &Config) -> Result<()> {
-    let jwt_secret = config.auth.jwt_secret.trim();
-    if jwt_secret.len() < 32 || looks_like_placeholder_secret(jwt_secret) {
-        anyhow::bail!(
-            "Invalid auth.jwt_secret: use a strong random secret (at least 32 characters) and never leave placeholder values"
-        );
-    }
-
-    let lk_key = config.livekit.api_key.trim();
-    let lk_secret = config.livekit.api_secret.trim();
-    if looks_like_placeholder_secret(lk_key) || looks_like_placeholder_secret(lk_secret) {
-        anyhow::bail!(
-            "Invalid livekit credentials: replace placeholder api_key/api_secret values before startup"
-        );
+/// Describe why `secret` is unsafe to sign sessions with, if it is.
+fn jwt_secret_problem(secret: &str) -> Option<String> {
+    let secret = secret.trim();
+    if secret.is_empty() {
+        Some("auth.jwt_secret is empty".into())
+    } else if KNOWN_DEFAULT_JWT_SECRETS
+        .iter()
+        .any(|known| secret.eq_ignore_ascii_case(known))
+        || looks_like_placeholder_secret(secret)
+    {
+        Some("auth.jwt_secret is still set to an example/default value".into())
+    } else if secret.len() < MIN_JWT_SECRET_LEN {
+        Some(format!(
+            "auth.jwt_secret is shorter than {MIN_JWT_SECRET_LEN} characters"
+        ))
+    } else {
+        None
     }
+}
 
-    Ok(())
+fn cors_allows_any_origin(raw: &str) -> bool {
+    raw.split(',').any(|origin| origin.trim() == "*")
 }
 
 /// Generate a commented config file template with the given values filled in.
@@ -644,6 +1145,8 @@ bind_address = "{bind_address}"
 server_name = "{server_name}"
 # Set explicitly for internet-facing deployments:
 # public_url = "https://your-domain-or-ip:8443"
+# Root for all server data; paths below left at their ./data defaults move under it:
+# data_dir = "/var/lib/paracord"
 
 [database]
 engine = "{db_engine}"
@@ -658,6 +1161,16 @@ registration_enabled = {registration_enabled}
 allow_username_login = {allow_username_login}
 # Require email during password registration.
 require_email = {require_email}
+# Make usernames globally unique handles instead of name#discriminator pairs.
+unique_usernames = {unique_usernames}
+# Require a CSRF token on cookie-authenticated writes (bearer clients are exempt).
+csrf_protection = {csrf_protection}
+# Refuse logins from an IP for an account after this many failed passwords
+# in a row, for login_lockout_seconds (0 disables the lockout).
+login_max_failures = {login_max_failures}
+login_lockout_seconds = {login_lockout_seconds}
+# Sessions unused for this many seconds must log in again (0 disables).
+session_idle_timeout_seconds = {session_idle_timeout_seconds}
 
 [storage]
 # Storage backend: "local" (default) or "s3".
@@ -709,6 +1222,11 @@ allow_discovery = {federation_allow_discovery}
 [network]
 # On Windows, optionally auto-create local firewall allow rules.
 windows_firewall_auto_allow = {windows_firewall_auto_allow}
+# Behind a reverse proxy, derive the client IP from X-Forwarded-For/Forwarded.
+# Forwarded headers are only honored when the connecting peer is listed in
+# trusted_proxies (IPs or CIDRs).
+trust_proxy = {trust_proxy}
+# trusted_proxies = ["127.0.0.1", "10.0.0.0/8"]
 
 [tls]
 # HTTPS support — required for getUserMedia() on non-localhost origins.
@@ -748,6 +1266,8 @@ batch_size = {retention_batch}
 # attachment_days = 30
 # audit_log_days = 365
 # security_event_days = 180
+# Append pruned security events to a JSON-lines file before deleting them.
+# security_event_archive_path = "./data/security_events_archive.jsonl"
 # session_days = 90
 
 [at_rest]
@@ -762,6 +1282,9 @@ encrypt_sqlite = {at_rest_encrypt_sqlite}
 encrypt_files = {at_rest_encrypt_files}
 # During migration, allow reading older plaintext attachment files.
 allow_plaintext_file_reads = {at_rest_allow_plaintext}
+# Write attachments in the chunked format that streams on download. Releases
+# before this one cannot read it, so enable it once a rollback is ruled out.
+chunked_files = {at_rest_chunked_files}
 
 [backup]
 # Backup configuration.
@@ -774,6 +1297,59 @@ auto_backup_interval_seconds = {backup_interval}
 include_media = {backup_include_media}
 # Maximum number of backups to keep (oldest are pruned).
 max_backups = {backup_max_backups}
+
+[abuse]
+# Per-user activity accounting. Accounts exceeding a threshold are flagged
+# for admins and, when auto_timeout_seconds > 0, temporarily blocked from
+# sending messages and uploading files. Set a threshold to 0 to disable it.
+enabled = {abuse_enabled}
+messages_per_minute = {abuse_messages_per_minute}
+uploads_per_minute = {abuse_uploads_per_minute}
+upload_bytes_per_hour = {abuse_upload_bytes_per_hour}
+auto_timeout_seconds = {abuse_auto_timeout_seconds}
+# Executions allowed per webhook per minute, across every source address.
+webhook_executions_per_minute = {abuse_webhook_executions_per_minute}
+# Rate-limit counter storage: "memory" (per process) or "database" (shared
+# across processes that use the same database, at the cost of a write per hit).
+rate_limit_store = "{abuse_rate_limit_store}"
+
+[link_unfurl]
+# Fetch OpenGraph metadata for https links in messages and attach preview
+# embeds. Guilds also need link previews turned on in their settings.
+# Private and loopback addresses are never fetched.
+enabled = {link_unfurl_enabled}
+timeout_seconds = {link_unfurl_timeout_seconds}
+# Bytes of each page read when looking for metadata.
+max_bytes = {link_unfurl_max_bytes}
+# Preview images are fetched by the server and served from it, so readers
+# never contact the linked site. Larger images are dropped; 0 disables them.
+max_image_bytes = {link_unfurl_max_image_bytes}
+cache_ttl_seconds = {link_unfurl_cache_ttl_seconds}
+
+[push]
+# Web Push notifications for mentions and DMs while a user's client is
+# offline. Needs a VAPID key, e.g. from
+#   openssl ecparam -name prime256v1 -genkey -noout -out vapid.pem
+# pasted as PEM (or as its raw base64url private key). Keep the same key:
+# changing it invalidates every registered subscription.
+enabled = {push_enabled}
+# vapid_private_key = ""
+# Contact for push services; defaults to server.public_url.
+# subject = "mailto:admin@example.com"
+timeout_seconds = {push_timeout_seconds}
+
+[mail]
+# SMTP transport for email verification messages. Links in messages point
+# at server.public_url, which must be set.
+enabled = {mail_enabled}
+# smtp_host = "smtp.example.com"
+smtp_port = {mail_smtp_port}
+# "starttls", "tls" (implicit TLS, usually port 465) or "none".
+security = "starttls"
+# username = ""
+# password = ""
+# from = "Paracord <noreply@example.com>"
+timeout_seconds = {mail_timeout_seconds}
 "#,
         bind_address = config.server.bind_address,
         server_name = config.server.server_name,
@@ -788,6 +1364,11 @@ max_backups = {backup_max_backups}
         registration_enabled = config.auth.registration_enabled,
         allow_username_login = config.auth.allow_username_login,
         require_email = config.auth.require_email,
+        unique_usernames = config.auth.unique_usernames,
+        csrf_protection = config.auth.csrf_protection,
+        login_max_failures = config.auth.login_max_failures,
+        login_lockout_seconds = config.auth.login_lockout_seconds,
+        session_idle_timeout_seconds = config.auth.session_idle_timeout_seconds,
         storage_type = config.storage.storage_type,
         storage_path = config.storage.path,
         media_path = config.media.storage_path,
@@ -805,6 +1386,7 @@ max_backups = {backup_max_backups}
             .unwrap_or("./data/federation_signing_key.hex"),
         federation_allow_discovery = config.federation.allow_discovery,
         windows_firewall_auto_allow = config.network.windows_firewall_auto_allow,
+        trust_proxy = config.network.trust_proxy,
         tls_enabled = config.tls.enabled,
         tls_port = config.tls.port,
         tls_cert = config.tls.cert_path,
@@ -826,18 +1408,41 @@ max_backups = {backup_max_backups}
         at_rest_encrypt_sqlite = config.at_rest.encrypt_sqlite,
         at_rest_encrypt_files = config.at_rest.encrypt_files,
         at_rest_allow_plaintext = config.at_rest.allow_plaintext_file_reads,
+        at_rest_chunked_files = config.at_rest.chunked_files,
         backup_dir = config.backup.backup_dir,
         backup_auto_enabled = config.backup.auto_backup_enabled,
         backup_interval = config.backup.auto_backup_interval_seconds,
         backup_include_media = config.backup.include_media,
         backup_max_backups = config.backup.max_backups,
+        abuse_enabled = config.abuse.enabled,
+        abuse_messages_per_minute = config.abuse.messages_per_minute,
+        abuse_uploads_per_minute = config.abuse.uploads_per_minute,
+        abuse_upload_bytes_per_hour = config.abuse.upload_bytes_per_hour,
+        abuse_auto_timeout_seconds = config.abuse.auto_timeout_seconds,
+        abuse_webhook_executions_per_minute = config.abuse.webhook_executions_per_minute,
+        abuse_rate_limit_store = match config.abuse.rate_limit_store {
+            RateLimitStoreKind::Memory => "memory",
+            RateLimitStoreKind::Database => "database",
+        },
+        link_unfurl_enabled = config.link_unfurl.enabled,
+        link_unfurl_timeout_seconds = config.link_unfurl.timeout_seconds,
+        link_unfurl_max_bytes = config.link_unfurl.max_bytes,
+        link_unfurl_max_image_bytes = config.link_unfurl.max_image_bytes,
+        link_unfurl_cache_ttl_seconds = config.link_unfurl.cache_ttl_seconds,
+        push_enabled = config.push.enabled,
+        push_timeout_seconds = config.push.timeout_seconds,
+        mail_enabled = config.mail.enabled,
+        mail_smtp_port = config.mail.smtp_port,
+        mail_timeout_seconds = config.mail.timeout_seconds,
     )
 }
 
 // ── Config Loading ───────────────────────────────────────────────────────────
 
 impl Config {
-    pub fn load(path: &str) -> Result<Self> {
+    /// Load the config file, letting `data_dir` (from `--data-dir`) take
+    /// precedence over `PARACORD_DATA_DIR` and `server.data_dir`.
+    pub fn load(path: &str, data_dir: Option<&str>) -> Result<Self> {
         let mut config = if std::path::Path::new(path).exists() {
             let content = fs::read_to_string(path)?;
             toml::from_str(&content)?
@@ -905,6 +1510,25 @@ impl Config {
                 config.database.idle_in_transaction_timeout_secs = parsed;
             }
         }
+        if let Ok(value) = std::env::var("PARACORD_DATABASE_SQLITE_BUSY_TIMEOUT_MS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.database.sqlite_busy_timeout_ms = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_DATABASE_SQLITE_SYNCHRONOUS") {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "off" => config.database.sqlite_synchronous = SqliteSynchronous::Off,
+                "normal" => config.database.sqlite_synchronous = SqliteSynchronous::Normal,
+                "full" => config.database.sqlite_synchronous = SqliteSynchronous::Full,
+                "extra" => config.database.sqlite_synchronous = SqliteSynchronous::Extra,
+                _ => {
+                    tracing::warn!(
+                        "Ignoring invalid PARACORD_DATABASE_SQLITE_SYNCHRONOUS value '{}'; expected off, normal, full or extra",
+                        value
+                    );
+                }
+            }
+        }
         if let Ok(value) = std::env::var("PARACORD_JWT_SECRET") {
             config.auth.jwt_secret = value;
         }
@@ -928,12 +1552,95 @@ impl Config {
                 config.auth.require_email = parsed;
             }
         }
+        if let Ok(value) = std::env::var("PARACORD_AUTH_UNIQUE_USERNAMES") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.auth.unique_usernames = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_CSRF_PROTECTION") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.auth.csrf_protection = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_AUTH_LOGIN_MAX_FAILURES") {
+            if let Ok(parsed) = value.parse::<u32>() {
+                config.auth.login_max_failures = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_AUTH_LOGIN_LOCKOUT_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.auth.login_lockout_seconds = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_AUTH_SESSION_IDLE_TIMEOUT_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.auth.session_idle_timeout_seconds = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_CUSTOM_CSS_DATA_IMAGES") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.server.custom_css_data_images = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_REQUEST_TIMEOUT_SECS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.server.request_timeout_secs = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LONG_REQUEST_TIMEOUT_SECS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.server.long_request_timeout_secs = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_GATEWAY_COMPRESSION_THRESHOLD_BYTES") {
+            if let Ok(parsed) = value.parse::<usize>() {
+                config.server.gateway_compression_threshold_bytes = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_HTTP_COMPRESSION_MIN_BYTES") {
+            if let Ok(parsed) = value.parse::<u16>() {
+                config.server.http_compression_min_bytes = parsed;
+            }
+        }
         if let Ok(value) = std::env::var("PARACORD_STORAGE_TYPE") {
             config.storage.storage_type = value;
         }
         if let Ok(value) = std::env::var("PARACORD_STORAGE_PATH") {
             config.storage.path = value;
         }
+        if let Ok(value) = std::env::var("PARACORD_MALWARE_QUARANTINE_PATH") {
+            if !value.trim().is_empty() {
+                config.storage.quarantine_path = Some(value);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MEDIA_CDN_BASE_URL") {
+            if !value.trim().is_empty() {
+                config.storage.media_cdn_base_url = Some(value.trim().to_string());
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_INLINE_ALLOWED_ORIGINS") {
+            config.storage.inline_allowed_origins = value
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Ok(value) = std::env::var("PARACORD_INLINE_TOKEN_TTL_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.storage.inline_token_ttl_seconds = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LOG_ATTACHMENT_ACCESS") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.storage.log_attachment_access = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_ORPHANED_ATTACHMENT_GC_INTERVAL_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.storage.orphaned_attachment_gc_interval_seconds = parsed;
+            }
+        }
         // S3 environment overrides
         if let Ok(value) = std::env::var("PARACORD_S3_BUCKET") {
             config.s3.bucket = value;
@@ -979,11 +1686,30 @@ impl Config {
         if let Ok(value) = std::env::var("PARACORD_LIVEKIT_PUBLIC_URL") {
             config.livekit.public_url = Some(value);
         }
+        if let Ok(value) = std::env::var("PARACORD_LIVEKIT_RECORDING_ENABLED") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.livekit.recording_enabled = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LIVEKIT_RECORDING_FILEPATH") {
+            config.livekit.recording_filepath = value;
+        }
         if let Ok(value) = std::env::var("PARACORD_WINDOWS_FIREWALL_AUTO_ALLOW") {
             if let Ok(parsed) = value.parse::<bool>() {
                 config.network.windows_firewall_auto_allow = parsed;
             }
         }
+        if let Ok(value) = std::env::var("PARACORD_TRUST_PROXY") {
+            config.network.trust_proxy = value.eq_ignore_ascii_case("true") || value == "1";
+        }
+        if let Ok(value) = std::env::var("PARACORD_TRUSTED_PROXY_IPS") {
+            config.network.trusted_proxies = value
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
         if let Ok(value) = std::env::var("PARACORD_TLS_ENABLED") {
             if let Ok(parsed) = value.parse::<bool>() {
                 config.tls.enabled = parsed;
@@ -1099,11 +1825,41 @@ impl Config {
                 config.federation.max_user_creates_per_peer_per_hour = Some(parsed);
             }
         }
+        if let Ok(value) = std::env::var("PARACORD_FEDERATION_MEDIA_TOKEN_TTL_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.federation.media_token_ttl_seconds = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_FEDERATION_FILE_TOKEN_TTL_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.federation.file_token_ttl_seconds = parsed;
+            }
+        }
         if let Ok(value) = std::env::var("PARACORD_MAX_GUILD_STORAGE_QUOTA") {
             if let Ok(parsed) = value.parse::<u64>() {
                 config.storage.max_guild_storage_quota = parsed;
             }
         }
+        if let Ok(value) = std::env::var("PARACORD_MAX_ATTACHMENTS_PER_MESSAGE") {
+            if let Ok(parsed) = value.parse::<u32>() {
+                config.storage.max_attachments_per_message = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAX_ATTACHMENT_BYTES_PER_MESSAGE") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.storage.max_attachment_bytes_per_message = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAX_ATTACHMENT_FILENAME_LENGTH") {
+            if let Ok(parsed) = value.parse::<u32>() {
+                config.storage.max_attachment_filename_length = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAX_PENDING_UPLOADS_PER_USER") {
+            if let Ok(parsed) = value.parse::<u32>() {
+                config.storage.max_pending_uploads_per_user = parsed;
+            }
+        }
         if let Ok(value) = std::env::var("PARACORD_FEDERATION_FILE_CACHE_ENABLED") {
             if let Ok(parsed) = value.parse::<bool>() {
                 config.federation.file_cache_enabled = parsed;
@@ -1146,6 +1902,10 @@ impl Config {
         if let Ok(value) = std::env::var("PARACORD_RETENTION_SECURITY_EVENT_DAYS") {
             config.retention.security_event_days = parse_optional_days(&value);
         }
+        if let Ok(value) = std::env::var("PARACORD_RETENTION_SECURITY_EVENT_ARCHIVE_PATH") {
+            config.retention.security_event_archive_path =
+                Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        }
         if let Ok(value) = std::env::var("PARACORD_RETENTION_SESSION_DAYS") {
             config.retention.session_days = parse_optional_days(&value);
         }
@@ -1174,6 +1934,11 @@ impl Config {
                 config.at_rest.allow_plaintext_file_reads = parsed;
             }
         }
+        if let Ok(value) = std::env::var("PARACORD_AT_REST_CHUNKED_FILES") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.at_rest.chunked_files = parsed;
+            }
+        }
         if let Ok(value) = std::env::var("PARACORD_BACKUP_DIR") {
             config.backup.backup_dir = value;
         }
@@ -1197,10 +1962,454 @@ impl Config {
                 config.backup.max_backups = parsed.clamp(1, 100);
             }
         }
+        if let Ok(value) = std::env::var("PARACORD_ABUSE_ENABLED") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.abuse.enabled = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_ABUSE_MESSAGES_PER_MINUTE") {
+            if let Ok(parsed) = value.parse::<u32>() {
+                config.abuse.messages_per_minute = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_ABUSE_UPLOADS_PER_MINUTE") {
+            if let Ok(parsed) = value.parse::<u32>() {
+                config.abuse.uploads_per_minute = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_ABUSE_UPLOAD_BYTES_PER_HOUR") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.abuse.upload_bytes_per_hour = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_ABUSE_AUTO_TIMEOUT_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.abuse.auto_timeout_seconds = parsed.min(604_800);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_ABUSE_WEBHOOK_EXECUTIONS_PER_MINUTE") {
+            if let Ok(parsed) = value.parse::<u32>() {
+                config.abuse.webhook_executions_per_minute = parsed.max(1);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LINK_UNFURL_ENABLED") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.link_unfurl.enabled = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LINK_UNFURL_TIMEOUT_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.link_unfurl.timeout_seconds = parsed.clamp(1, 30);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LINK_UNFURL_MAX_BYTES") {
+            if let Ok(parsed) = value.parse::<usize>() {
+                config.link_unfurl.max_bytes = parsed.clamp(16_384, 10_485_760);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LINK_UNFURL_MAX_IMAGE_BYTES") {
+            if let Ok(parsed) = value.parse::<usize>() {
+                config.link_unfurl.max_image_bytes = parsed.min(10_485_760);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_LINK_UNFURL_CACHE_TTL_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.link_unfurl.cache_ttl_seconds = parsed.max(60);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_PUSH_ENABLED") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.push.enabled = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_PUSH_VAPID_PRIVATE_KEY") {
+            config.push.vapid_private_key = value;
+        }
+        if let Ok(value) = std::env::var("PARACORD_PUSH_SUBJECT") {
+            if !value.trim().is_empty() {
+                config.push.subject = Some(value.trim().to_string());
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_PUSH_TIMEOUT_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.push.timeout_seconds = parsed.clamp(1, 60);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_ENABLED") {
+            if let Ok(parsed) = value.parse::<bool>() {
+                config.mail.enabled = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_SMTP_HOST") {
+            config.mail.smtp_host = value.trim().to_string();
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_SMTP_PORT") {
+            if let Ok(parsed) = value.parse::<u16>() {
+                config.mail.smtp_port = parsed;
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_SECURITY") {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "starttls" => config.mail.security = SmtpSecurity::Starttls,
+                "tls" => config.mail.security = SmtpSecurity::Tls,
+                "none" => config.mail.security = SmtpSecurity::None,
+                _ => {
+                    tracing::warn!(
+                        "Ignoring invalid PARACORD_MAIL_SECURITY value '{}'; expected starttls, tls or none",
+                        value
+                    );
+                }
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_USERNAME") {
+            config.mail.username = value;
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_PASSWORD") {
+            config.mail.password = value;
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_FROM") {
+            config.mail.from = value.trim().to_string();
+        }
+        if let Ok(value) = std::env::var("PARACORD_MAIL_TIMEOUT_SECONDS") {
+            if let Ok(parsed) = value.parse::<u64>() {
+                config.mail.timeout_seconds = parsed.clamp(1, 60);
+            }
+        }
+        if let Ok(value) = std::env::var("PARACORD_RATE_LIMIT_STORE") {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "memory" => config.abuse.rate_limit_store = RateLimitStoreKind::Memory,
+                "database" => config.abuse.rate_limit_store = RateLimitStoreKind::Database,
+                _ => {
+                    tracing::warn!(
+                        "Ignoring invalid PARACORD_RATE_LIMIT_STORE value '{}'; expected memory or database",
+                        value
+                    );
+                }
+            }
+        }
+
+        if let Ok(value) = std::env::var("PARACORD_DATA_DIR") {
+            if !value.trim().is_empty() {
+                config.server.data_dir = Some(value);
+            }
+        }
+        if let Some(value) = data_dir.filter(|value| !value.trim().is_empty()) {
+            config.server.data_dir = Some(value.to_string());
+        }
+        if let Some(root) = config.server.data_dir.clone() {
+            config.apply_data_dir(&root);
+        }
 
-        validate_secret_configuration(&config)?;
         Ok(config)
     }
+
+    /// Move every data path still at its built-in `./data/...` default under
+    /// `root`. Paths that were configured explicitly are left untouched.
+    pub fn apply_data_dir(&mut self, root: &str) {
+        fn rebase(path: &mut String, default: String, root: &str) {
+            if *path == default {
+                *path = data_path(root, &default);
+            }
+        }
+
+        rebase(&mut self.storage.path, default_storage_path(), root);
+        rebase(
+            &mut self.media.storage_path,
+            default_media_storage_path(),
+            root,
+        );
+        rebase(&mut self.backup.backup_dir, default_backup_dir(), root);
+        rebase(&mut self.tls.cert_path, default_cert_path(), root);
+        rebase(&mut self.tls.key_path, default_key_path(), root);
+        rebase(
+            &mut self.tls.acme.webroot_path,
+            default_acme_webroot_path(),
+            root,
+        );
+        if let (Some(path), Some(default)) = (
+            self.federation.signing_key_path.as_mut(),
+            default_federation_signing_key_path(),
+        ) {
+            rebase(path, default, root);
+        }
+        if self.database.url == DatabaseConfig::default().url {
+            self.database.url = format!(
+                "sqlite://{}?mode=rwc",
+                data_path(root, "./data/paracord.db")
+            );
+        }
+    }
+
+    /// Directories the server writes into, created on startup.
+    pub fn data_directories(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = [
+            &self.storage.path,
+            &self.media.storage_path,
+            &self.tls.acme.webroot_path,
+            &self.backup.backup_dir,
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+        if let Some(quarantine) = &self.storage.quarantine_path {
+            dirs.push(PathBuf::from(quarantine));
+        }
+
+        if matches!(self.database.engine, DatabaseEngine::Sqlite) {
+            if let Some(parent) = self
+                .database
+                .url
+                .strip_prefix("sqlite://")
+                .and_then(|s| s.split('?').next())
+                .and_then(|db_path| Path::new(db_path).parent())
+                .filter(|parent| !parent.as_os_str().is_empty())
+            {
+                dirs.push(parent.to_path_buf());
+            }
+        }
+        dirs
+    }
+
+    /// Final startup checks. In `dev` mode a weak `auth.jwt_secret` is
+    /// replaced with an ephemeral random one (with a warning) instead of
+    /// refusing to start.
+    pub fn prepare_for_startup(&mut self, dev: bool) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+        if dev {
+            if let Some(problem) = jwt_secret_problem(&self.auth.jwt_secret) {
+                self.auth.jwt_secret = generate_random_hex(64);
+                warnings.push(format!(
+                    "{problem}; --dev is set, so an ephemeral secret is used and sessions will not survive a restart"
+                ));
+            }
+        }
+        warnings.extend(self.validate()?);
+        Ok(warnings)
+    }
+
+    /// Check cross-field invariants before the server binds. Every fatal
+    /// problem is collected into one error so operators can fix them in a
+    /// single pass; risky-but-workable settings are returned as warnings.
+    pub fn validate(&self) -> Result<Vec<String>> {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        let bind_port = self
+            .server
+            .bind_address
+            .rsplit_once(':')
+            .filter(|(host, _)| !host.is_empty())
+            .and_then(|(_, port)| port.parse::<u16>().ok());
+        if bind_port.is_none() {
+            errors.push(format!(
+                "server.bind_address '{}' must be in host:port form",
+                self.server.bind_address
+            ));
+        }
+        if let Some(public_url) = &self.server.public_url {
+            if !public_url.starts_with("http://") && !public_url.starts_with("https://") {
+                errors.push(format!(
+                    "server.public_url '{public_url}' must start with http:// or https://"
+                ));
+            }
+        }
+        if let Some(cdn) = &self.storage.media_cdn_base_url {
+            if !cdn.starts_with("http://") && !cdn.starts_with("https://") {
+                errors.push(format!(
+                    "storage.media_cdn_base_url '{cdn}' must start with http:// or https://"
+                ));
+            }
+        }
+        if self.push.enabled {
+            if self.push.vapid_private_key.trim().is_empty() {
+                errors.push(
+                    "push.vapid_private_key is required when push.enabled is true".to_string(),
+                );
+            }
+            match self
+                .push
+                .effective_subject(self.server.public_url.as_deref())
+            {
+                None => errors.push(
+                    "push.subject (or server.public_url) is required when push.enabled is true"
+                        .to_string(),
+                ),
+                Some(subject)
+                    if !subject.starts_with("mailto:") && !subject.starts_with("https://") =>
+                {
+                    errors.push(format!(
+                        "push.subject '{subject}' must be a mailto: or https:// URL"
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        if self.mail.enabled {
+            if self.mail.smtp_host.trim().is_empty() {
+                errors.push("mail.smtp_host is required when mail.enabled is true".to_string());
+            }
+            if self.mail.from.trim().is_empty() {
+                errors.push("mail.from is required when mail.enabled is true".to_string());
+            }
+            if self
+                .server
+                .public_url
+                .as_deref()
+                .is_none_or(|url| url.trim().is_empty())
+            {
+                errors.push("server.public_url is required when mail.enabled is true".to_string());
+            }
+        }
+        for origin in &self.storage.inline_allowed_origins {
+            if !origin.starts_with("http://") && !origin.starts_with("https://") {
+                errors.push(format!(
+                    "storage.inline_allowed_origins entry '{origin}' must start with http:// or https://"
+                ));
+            }
+        }
+
+        let request_timeout = self.server.request_timeout_secs;
+        let long_request_timeout = self.server.long_request_timeout_secs;
+        if request_timeout > 0 && long_request_timeout > 0 && long_request_timeout < request_timeout
+        {
+            warnings.push(format!(
+                "server.long_request_timeout_secs ({long_request_timeout}) is shorter than server.request_timeout_secs ({request_timeout}); downloads and exports will be cut off first"
+            ));
+        }
+
+        if let Some(channels) = &self.server.default_guild_channels {
+            if let Err(err) = paracord_core::guild::validate_default_channels(channels) {
+                errors.push(format!("server.default_guild_channels: {err}"));
+            }
+        }
+
+        let db_url = self.database.url.trim();
+        match self.database.engine {
+            DatabaseEngine::Sqlite if !db_url.starts_with("sqlite:") => errors
+                .push("database.engine is \"sqlite\" but database.url is not a sqlite: URL".into()),
+            DatabaseEngine::Postgres
+                if !db_url.starts_with("postgres://") && !db_url.starts_with("postgresql://") =>
+            {
+                errors.push(
+                    "database.engine is \"postgres\" but database.url is not a postgres:// URL"
+                        .into(),
+                )
+            }
+            _ => {}
+        }
+        if self.database.max_connections == 0 {
+            errors.push("database.max_connections must be at least 1".into());
+        }
+        if self.database.engine == DatabaseEngine::Sqlite {
+            if self.database.max_connections > SQLITE_MAX_USEFUL_CONNECTIONS {
+                warnings.push(format!(
+                    "database.max_connections is {} but SQLite allows one writer at a time; more than {} connections only adds lock contention",
+                    self.database.max_connections, SQLITE_MAX_USEFUL_CONNECTIONS
+                ));
+            }
+            if self.database.sqlite_busy_timeout_ms == 0 {
+                warnings.push(
+                    "database.sqlite_busy_timeout_ms is 0; concurrent writes will fail with \"database is locked\" instead of waiting"
+                        .into(),
+                );
+            }
+        }
+
+        if let Some(problem) = jwt_secret_problem(&self.auth.jwt_secret) {
+            errors.push(format!(
+                "{problem}. Set a strong random secret (e.g. `openssl rand -hex 32`) in auth.jwt_secret or PARACORD_JWT_SECRET, or pass --dev for local development"
+            ));
+        } else if self.auth.jwt_secret.trim().len() < 64 {
+            warnings.push(
+                "auth.jwt_secret is shorter than 64 characters; consider a longer random secret"
+                    .into(),
+            );
+        }
+
+        if looks_like_placeholder_secret(self.livekit.api_key.trim())
+            || looks_like_placeholder_secret(self.livekit.api_secret.trim())
+        {
+            errors.push(
+                "livekit.api_key/api_secret still contain placeholder values; replace them before startup"
+                    .into(),
+            );
+        }
+
+        match self.storage.storage_type.as_str() {
+            "local" => {}
+            "s3" => {
+                if self.s3.bucket.trim().is_empty() {
+                    errors.push("storage.storage_type is \"s3\" but s3.bucket is empty".into());
+                }
+            }
+            other => errors.push(format!(
+                "storage.storage_type '{other}' is not supported; expected \"local\" or \"s3\""
+            )),
+        }
+
+        if self.tls.enabled {
+            if self.tls.cert_path.trim().is_empty() || self.tls.key_path.trim().is_empty() {
+                errors
+                    .push("tls.enabled is true but tls.cert_path or tls.key_path is empty".into());
+            } else if !self.tls.auto_generate && !self.tls.acme.enabled {
+                for (field, path) in [
+                    ("tls.cert_path", &self.tls.cert_path),
+                    ("tls.key_path", &self.tls.key_path),
+                ] {
+                    if !Path::new(path).exists() {
+                        errors.push(format!(
+                            "{field} '{path}' does not exist and tls.auto_generate is false"
+                        ));
+                    }
+                }
+            }
+        }
+        if self.tls.acme.enabled {
+            if self.tls.acme.domains.is_empty() {
+                errors.push("tls.acme.enabled is true but tls.acme.domains is empty".into());
+            }
+            if !self.tls.enabled {
+                warnings.push(
+                    "tls.acme.enabled is true while tls.enabled is false; ACME automation will be inactive"
+                        .into(),
+                );
+            }
+        }
+
+        if std::env::var("PARACORD_CORS_ALLOWED_ORIGINS")
+            .map(|raw| cors_allows_any_origin(&raw))
+            .unwrap_or(false)
+        {
+            warnings.push(
+                "PARACORD_CORS_ALLOWED_ORIGINS contains '*'; any website can call this server's API"
+                    .into(),
+            );
+        }
+
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "Invalid configuration:\n{}",
+                errors
+                    .iter()
+                    .map(|error| format!("  - {error}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+        Ok(warnings)
+    }
+}
+
+/// Re-root a `./data/...` default path under `root`.
+fn data_path(root: &str, default: &str) -> String {
+    let relative = default
+        .strip_prefix(DEFAULT_DATA_DIR)
+        .unwrap_or(default)
+        .trim_start_matches('/');
+    Path::new(root)
+        .join(relative)
+        .to_string_lossy()
+        .into_owned()
 }
 
 fn parse_optional_days(raw: &str) -> Option<i64> {
@@ -1211,7 +2420,8 @@ fn parse_optional_days(raw: &str) -> Option<i64> {
 
 #[cfg(test)]
 mod tests {
-    use super::{Config, DatabaseConfig, DatabaseEngine, TlsConfig};
+    use super::{cors_allows_any_origin, Config, DatabaseConfig, DatabaseEngine, TlsConfig};
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn tls_defaults_enable_self_signed_bootstrap() {
@@ -1232,10 +2442,268 @@ mod tests {
         let config_path = temp.path().join("paracord-test.toml");
         std::env::set_var("PARACORD_JWT_SECRET", "0123456789abcdef0123456789abcdef");
         std::env::set_var("PARACORD_DATABASE_ENGINE", "postgres");
-        let config =
-            Config::load(config_path.to_str().expect("config path utf8")).expect("load config");
+        let config = Config::load(config_path.to_str().expect("config path utf8"), None)
+            .expect("load config");
         std::env::remove_var("PARACORD_DATABASE_ENGINE");
         std::env::remove_var("PARACORD_JWT_SECRET");
         assert_eq!(config.database.engine, DatabaseEngine::Postgres);
     }
+
+    #[test]
+    fn data_dir_moves_default_paths_under_root() {
+        let mut config = Config::default();
+        config.apply_data_dir("/srv/paracord");
+
+        let root = Path::new("/srv/paracord");
+        assert_eq!(Path::new(&config.storage.path), root.join("uploads"));
+        assert_eq!(Path::new(&config.media.storage_path), root.join("files"));
+        assert_eq!(Path::new(&config.backup.backup_dir), root.join("backups"));
+        assert_eq!(
+            Path::new(&config.tls.acme.webroot_path),
+            root.join("acme-webroot")
+        );
+        assert_eq!(
+            Path::new(&config.tls.cert_path),
+            root.join("certs/cert.pem")
+        );
+        assert_eq!(
+            Path::new(config.federation.signing_key_path.as_deref().unwrap()),
+            root.join("federation_signing_key.hex")
+        );
+        assert_eq!(
+            config.database.url,
+            format!(
+                "sqlite://{}?mode=rwc",
+                root.join("paracord.db").to_string_lossy()
+            )
+        );
+        assert!(config
+            .data_directories()
+            .iter()
+            .all(|dir| dir.starts_with(root)));
+    }
+
+    #[test]
+    fn explicit_paths_win_over_data_dir() {
+        let mut config = Config::default();
+        config.storage.path = "/mnt/uploads".into();
+        config.backup.backup_dir = "/mnt/backups".into();
+        config.database.url = "sqlite:///var/db/paracord.db?mode=rwc".into();
+        config.storage.quarantine_path = Some("/mnt/quarantine".into());
+        config.apply_data_dir("/srv/paracord");
+
+        assert_eq!(config.storage.path, "/mnt/uploads");
+        assert_eq!(config.backup.backup_dir, "/mnt/backups");
+        assert_eq!(config.database.url, "sqlite:///var/db/paracord.db?mode=rwc");
+        assert_eq!(
+            Path::new(&config.media.storage_path),
+            Path::new("/srv/paracord/files")
+        );
+        let dirs = config.data_directories();
+        assert!(dirs.contains(&PathBuf::from("/mnt/quarantine")));
+        assert!(dirs.contains(&PathBuf::from("/var/db")));
+    }
+
+    fn validation_error(mutate: impl FnOnce(&mut Config)) -> String {
+        let mut config = Config::default();
+        mutate(&mut config);
+        config
+            .validate()
+            .expect_err("config should be rejected")
+            .to_string()
+    }
+
+    #[test]
+    fn default_config_validates_cleanly() {
+        let warnings = Config::default().validate().expect("valid config");
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+    }
+
+    #[test]
+    fn login_lockout_is_off_by_default() {
+        assert_eq!(Config::default().auth.login_max_failures, 0);
+    }
+
+    #[test]
+    fn validate_rejects_bad_bind_address_and_public_url() {
+        let err = validation_error(|c| c.server.bind_address = "8080".into());
+        assert!(err.contains("server.bind_address"), "{err}");
+        let err = validation_error(|c| c.server.public_url = Some("chat.example.com".into()));
+        assert!(err.contains("server.public_url"), "{err}");
+        let err =
+            validation_error(|c| c.storage.media_cdn_base_url = Some("cdn.example.com".into()));
+        assert!(err.contains("storage.media_cdn_base_url"), "{err}");
+        let err = validation_error(|c| {
+            c.storage.inline_allowed_origins = vec!["chat.example.com".into()]
+        });
+        assert!(err.contains("storage.inline_allowed_origins"), "{err}");
+        let err = validation_error(|c| c.push.enabled = true);
+        assert!(err.contains("push.vapid_private_key"), "{err}");
+        assert!(err.contains("push.subject"), "{err}");
+        let err = validation_error(|c| {
+            c.push.enabled = true;
+            c.push.vapid_private_key = "key".into();
+            c.push.subject = Some("admin@example.com".into());
+        });
+        assert!(err.contains("push.subject 'admin@example.com'"), "{err}");
+        let err = validation_error(|c| c.mail.enabled = true);
+        assert!(err.contains("mail.smtp_host"), "{err}");
+        assert!(err.contains("mail.from"), "{err}");
+        assert!(err.contains("server.public_url is required"), "{err}");
+    }
+
+    #[test]
+    fn validate_rejects_database_engine_url_mismatch() {
+        let err = validation_error(|c| c.database.engine = DatabaseEngine::Postgres);
+        assert!(err.contains("not a postgres:// URL"), "{err}");
+        let err = validation_error(|c| c.database.url = "postgres://db/paracord".into());
+        assert!(err.contains("not a sqlite: URL"), "{err}");
+        let err = validation_error(|c| c.database.max_connections = 0);
+        assert!(err.contains("database.max_connections"), "{err}");
+    }
+
+    #[test]
+    fn validate_rejects_weak_jwt_secret_and_warns_on_short_one() {
+        let err = validation_error(|c| c.auth.jwt_secret = String::new());
+        assert!(err.contains("auth.jwt_secret"), "{err}");
+        let err = validation_error(|c| {
+            c.auth.jwt_secret = "change_me_change_me_change_me_change_me".into()
+        });
+        assert!(err.contains("auth.jwt_secret"), "{err}");
+
+        let mut config = Config::default();
+        config.auth.jwt_secret = "8f2c1e9a7b3d4f60a1c5e8d2b7f9034e".into();
+        let warnings = config.validate().expect("32-char secret is allowed");
+        assert!(warnings.iter().any(|w| w.contains("auth.jwt_secret")));
+    }
+
+    #[test]
+    fn validate_warns_on_sqlite_contention_settings() {
+        let mut config = Config::default();
+        config.database.max_connections = 64;
+        config.database.sqlite_busy_timeout_ms = 0;
+        let warnings = config.validate().expect("sqlite tuning only warns");
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("database.max_connections")));
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("database.sqlite_busy_timeout_ms")));
+    }
+
+    #[test]
+    fn validate_rejects_default_channels_with_unknown_category() {
+        let err = validation_error(|c| {
+            c.server.default_guild_channels = Some(vec![paracord_core::guild::DefaultChannel {
+                name: "rules".into(),
+                kind: paracord_core::guild::DefaultChannelKind::Text,
+                category: Some("Info".into()),
+            }])
+        });
+        assert!(err.contains("server.default_guild_channels"), "{err}");
+    }
+
+    #[test]
+    fn validate_rejects_placeholder_livekit_credentials() {
+        let err = validation_error(|c| c.livekit.api_secret = "devsecret".into());
+        assert!(err.contains("livekit.api_key/api_secret"), "{err}");
+    }
+
+    #[test]
+    fn validate_rejects_s3_without_bucket_and_unknown_storage() {
+        let err = validation_error(|c| c.storage.storage_type = "s3".into());
+        assert!(err.contains("s3.bucket is empty"), "{err}");
+        let err = validation_error(|c| c.storage.storage_type = "ftp".into());
+        assert!(err.contains("storage.storage_type 'ftp'"), "{err}");
+    }
+
+    #[test]
+    fn validate_rejects_tls_without_usable_key_material() {
+        let err = validation_error(|c| c.tls.key_path = String::new());
+        assert!(
+            err.contains("tls.cert_path or tls.key_path is empty"),
+            "{err}"
+        );
+
+        let err = validation_error(|c| {
+            c.tls.auto_generate = false;
+            c.tls.cert_path = "/nonexistent/paracord/cert.pem".into();
+            c.tls.key_path = "/nonexistent/paracord/key.pem".into();
+        });
+        assert!(
+            err.contains("tls.cert_path '/nonexistent/paracord/cert.pem'"),
+            "{err}"
+        );
+        assert!(
+            err.contains("tls.key_path '/nonexistent/paracord/key.pem'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn validate_checks_acme_settings() {
+        let err = validation_error(|c| c.tls.acme.enabled = true);
+        assert!(err.contains("tls.acme.domains is empty"), "{err}");
+
+        let mut config = Config::default();
+        config.tls.enabled = false;
+        config.tls.acme.enabled = true;
+        config.tls.acme.domains = vec!["chat.example.com".into()];
+        let warnings = config.validate().expect("acme without tls only warns");
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("ACME automation will be inactive")));
+    }
+
+    #[test]
+    fn validate_reports_every_problem_at_once() {
+        let err = validation_error(|c| {
+            c.auth.jwt_secret = "short".into();
+            c.storage.storage_type = "s3".into();
+            c.database.max_connections = 0;
+        });
+        assert!(err.starts_with("Invalid configuration:"), "{err}");
+        assert_eq!(
+            err.lines().filter(|line| line.starts_with("  - ")).count(),
+            3
+        );
+    }
+
+    #[test]
+    fn wildcard_cors_origin_is_detected() {
+        assert!(cors_allows_any_origin("https://a.example, *"));
+        assert!(!cors_allows_any_origin(
+            "https://a.example,https://b.example"
+        ));
+    }
+
+    #[test]
+    fn weak_jwt_secret_aborts_startup_outside_dev_mode() {
+        for weak in ["", "short", "your-64-char-random-secret-here"] {
+            let mut config = Config::default();
+            config.auth.jwt_secret = weak.into();
+            let err = config
+                .prepare_for_startup(false)
+                .expect_err("weak secret must abort")
+                .to_string();
+            assert!(err.contains("auth.jwt_secret"), "{err}");
+            assert!(err.contains("--dev"), "{err}");
+        }
+    }
+
+    #[test]
+    fn weak_jwt_secret_is_replaced_in_dev_mode() {
+        let mut config = Config::default();
+        config.auth.jwt_secret = String::new();
+        let warnings = config
+            .prepare_for_startup(true)
+            .expect("dev mode tolerates weak secret");
+        assert_eq!(config.auth.jwt_secret.len(), 64);
+        assert!(warnings.iter().any(|w| w.contains("ephemeral secret")));
+
+        let mut config = Config::default();
+        let strong = config.auth.jwt_secret.clone();
+        config.prepare_for_startup(true).expect("strong secret");
+        assert_eq!(config.auth.jwt_secret, strong, "strong secrets are kept");
+    }
 }
@@ -1,8 +1,15 @@
+use std::future::Future;
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::process::{Child, Command};
 
+/// How long to wait for a freshly spawned LiveKit to answer health checks.
+pub const READINESS_TIMEOUT: Duration = Duration::from_secs(20);
+/// Delay between readiness health checks.
+pub const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 /// Handle to a managed LiveKit server process.
 pub struct LiveKitProcess {
     child: Child,
@@ -320,11 +327,9 @@ pub async fn start_livekit(
         }
     };
 
-    // Give LiveKit a moment to start — needs time to bind ports and init
-    tokio::time::sleep(std::time::Duration::from_secs(4)).await;
-
+    // Readiness is established by the caller via `wait_until_ready`.
     tracing::info!(
-        "Managed LiveKit server started (PID: {})",
+        "Managed LiveKit server spawned (PID: {})",
         child
             .id()
             .map(|id| id.to_string())
@@ -333,3 +338,104 @@ pub async fn start_livekit(
 
     Some(LiveKitProcess { child, config_path })
 }
+
+/// Result of waiting for a managed LiveKit to become healthy.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Readiness {
+    Ready { attempts: u32 },
+    TimedOut { attempts: u32, last_error: String },
+}
+
+/// Poll `check` until it succeeds or `timeout` elapses. A single check that
+/// hangs past the deadline counts as a failure.
+pub async fn wait_until_ready<F, Fut>(
+    mut check: F,
+    timeout: Duration,
+    interval: Duration,
+) -> Readiness
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), anyhow::Error>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        let last_error = match tokio::time::timeout(remaining, check()).await {
+            Ok(Ok(())) => return Readiness::Ready { attempts },
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "health check timed out".to_string(),
+        };
+        if tokio::time::Instant::now() + interval >= deadline {
+            return Readiness::TimedOut {
+                attempts,
+                last_error,
+            };
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn ready_once_health_check_passes() {
+        let calls = AtomicU32::new(0);
+        let readiness = wait_until_ready(
+            || {
+                let n = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if n < 3 {
+                        anyhow::bail!("connection refused")
+                    }
+                    Ok(())
+                }
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(5),
+        )
+        .await;
+        assert_eq!(readiness, Readiness::Ready { attempts: 4 });
+    }
+
+    #[tokio::test]
+    async fn times_out_when_health_check_never_passes() {
+        let readiness = wait_until_ready(
+            || async { anyhow::bail!("connection refused") },
+            Duration::from_millis(100),
+            Duration::from_millis(10),
+        )
+        .await;
+        match readiness {
+            Readiness::TimedOut {
+                attempts,
+                last_error,
+            } => {
+                assert!(attempts > 1, "should retry before giving up");
+                assert_eq!(last_error, "connection refused");
+            }
+            other => panic!("expected timeout, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn hanging_health_check_counts_as_timeout() {
+        let readiness = wait_until_ready(
+            std::future::pending::<Result<(), anyhow::Error>>,
+            Duration::from_millis(50),
+            Duration::from_millis(10),
+        )
+        .await;
+        assert_eq!(
+            readiness,
+            Readiness::TimedOut {
+                attempts: 1,
+                last_error: "health check timed out".to_string(),
+            }
+        );
+    }
+}
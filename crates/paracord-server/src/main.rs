@@ -19,6 +19,7 @@ mod tls;
 struct AtRestRuntimeProfile {
     sqlite_key_hex: Option<String>,
     file_cryptor: Option<paracord_util::at_rest::FileCryptor>,
+    secret_cryptor: Option<paracord_util::at_rest::SecretCryptor>,
 }
 
 fn map_db_engine(engine: config::DatabaseEngine) -> paracord_db::DatabaseEngine {
@@ -28,6 +29,15 @@ fn map_db_engine(engine: config::DatabaseEngine) -> paracord_db::DatabaseEngine
     }
 }
 
+fn map_sqlite_synchronous(mode: config::SqliteSynchronous) -> paracord_db::SqliteSynchronous {
+    match mode {
+        config::SqliteSynchronous::Off => paracord_db::SqliteSynchronous::Off,
+        config::SqliteSynchronous::Normal => paracord_db::SqliteSynchronous::Normal,
+        config::SqliteSynchronous::Full => paracord_db::SqliteSynchronous::Full,
+        config::SqliteSynchronous::Extra => paracord_db::SqliteSynchronous::Extra,
+    }
+}
+
 fn parse_env_bool(name: &str, default: bool) -> bool {
     std::env::var(name)
         .ok()
@@ -66,11 +76,29 @@ async fn main() -> Result<()> {
         .init();
 
     let args = cli::Args::parse();
-    let config = config::Config::load(&args.config)?;
-    if config.tls.acme.enabled && !config.tls.enabled {
-        tracing::warn!(
-            "tls.acme.enabled is true while tls.enabled is false; ACME automation will be inactive"
-        );
+    let mut config = config::Config::load(&args.config, args.data_dir.as_deref())?;
+    for warning in config.prepare_for_startup(args.dev)? {
+        tracing::warn!("{}", warning);
+    }
+    let allowed_origins: Vec<String> = [
+        "PARACORD_CORS_ALLOWED_ORIGINS",
+        "PARACORD_WS_ALLOWED_ORIGINS",
+    ]
+    .into_iter()
+    .filter_map(|name| std::env::var(name).ok())
+    .flat_map(|raw| {
+        raw.split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    })
+    .collect();
+    let plaintext_warnings =
+        plaintext_exposure_warnings(config.server.public_url.as_deref(), &allowed_origins);
+    paracord_core::observability::set_plaintext_public_origins(plaintext_warnings.len() as u64);
+    for warning in &plaintext_warnings {
+        tracing::warn!("{}", warning);
     }
     let at_rest_profile = build_at_rest_profile(&config)?;
     if livekit_credentials_look_insecure(&config.livekit.api_key, &config.livekit.api_secret) {
@@ -114,6 +142,54 @@ async fn main() -> Result<()> {
             }
         });
     std::env::set_var("PARACORD_SERVER_NAME", config.server.server_name.clone());
+    if let Some(quarantine_path) = &config.storage.quarantine_path {
+        std::env::set_var("PARACORD_MALWARE_QUARANTINE_PATH", quarantine_path);
+    }
+    configure_trusted_proxies(&config.network);
+    configure_security_headers(&config.server);
+    std::env::set_var(
+        "PARACORD_REQUEST_TIMEOUT_SECS",
+        config.server.request_timeout_secs.to_string(),
+    );
+    std::env::set_var(
+        "PARACORD_LONG_REQUEST_TIMEOUT_SECS",
+        config.server.long_request_timeout_secs.to_string(),
+    );
+    std::env::set_var(
+        "PARACORD_HTTP_COMPRESSION_MIN_BYTES",
+        config.server.http_compression_min_bytes.to_string(),
+    );
+    if let Some(profile_markup) = &config.server.profile_markup {
+        std::env::set_var("PARACORD_PROFILE_MARKUP", profile_markup);
+    }
+    if let Some(skin_tones) = &config.server.reaction_skin_tones {
+        std::env::set_var("PARACORD_REACTION_SKIN_TONES", skin_tones);
+    }
+    if let Some(locales) = &config.server.allowed_locales {
+        std::env::set_var("PARACORD_ALLOWED_LOCALES", locales.join(","));
+    }
+    if let Some(channels) = &config.server.default_guild_channels {
+        std::env::set_var(
+            "PARACORD_DEFAULT_GUILD_CHANNELS",
+            serde_json::to_string(channels)?,
+        );
+    }
+    std::env::set_var(
+        "PARACORD_CUSTOM_CSS_DATA_IMAGES",
+        if config.server.custom_css_data_images {
+            "true"
+        } else {
+            "false"
+        },
+    );
+    std::env::set_var(
+        "PARACORD_CSRF_PROTECTION",
+        if config.auth.csrf_protection {
+            "true"
+        } else {
+            "false"
+        },
+    );
     if let Some(public_url) = &config.server.public_url {
         std::env::set_var("PARACORD_PUBLIC_URL", public_url);
     }
@@ -259,12 +335,17 @@ async fn main() -> Result<()> {
         statement_timeout_secs: config.database.statement_timeout_secs,
         idle_in_transaction_timeout_secs: config.database.idle_in_transaction_timeout_secs,
     };
+    let sqlite_options = paracord_db::SqliteConnectOptions {
+        busy_timeout_ms: config.database.sqlite_busy_timeout_ms,
+        synchronous: map_sqlite_synchronous(config.database.sqlite_synchronous),
+    };
     let db = paracord_db::create_pool_full(
         &config.database.url,
         config.database.max_connections,
         Some(db_engine),
         at_rest_profile.sqlite_key_hex.clone(),
         Some(pg_options),
+        Some(sqlite_options),
     )
     .await
     .map_err(|e| {
@@ -312,8 +393,42 @@ async fn main() -> Result<()> {
             .replace("://localhost:", "://127.0.0.1:"),
     });
 
-    // Verify LiveKit admin API credentials match the running instance.
-    if livekit_reachable {
+    // A managed LiveKit is only available once it answers health checks;
+    // spawning the process says nothing about whether it bound its ports.
+    if managed_livekit.is_some() {
+        match livekit_proc::wait_until_ready(
+            || livekit_config.check_health(),
+            livekit_proc::READINESS_TIMEOUT,
+            livekit_proc::READINESS_POLL_INTERVAL,
+        )
+        .await
+        {
+            livekit_proc::Readiness::Ready { attempts } => {
+                tracing::info!(
+                    "Managed LiveKit is ready (health check passed after {} attempt(s))",
+                    attempts
+                );
+            }
+            livekit_proc::Readiness::TimedOut {
+                attempts,
+                last_error,
+            } => {
+                tracing::error!("==========================================================");
+                tracing::error!(
+                    "  Managed LiveKit did not become healthy within {:?}",
+                    livekit_proc::READINESS_TIMEOUT
+                );
+                tracing::error!("  ({} attempt(s); last error: {})", attempts, last_error);
+                tracing::error!("");
+                tracing::error!("  Voice is disabled until LiveKit responds. See the LiveKit");
+                tracing::error!("  log file for details.");
+                tracing::error!("==========================================================");
+                livekit_reachable = false;
+                livekit_status = format!("Managed (port {}, not ready)", livekit_port);
+            }
+        }
+    } else if livekit_reachable {
+        // Verify LiveKit admin API credentials match the running instance.
         match livekit_config.check_health().await {
             Ok(()) => tracing::info!("LiveKit admin API health check passed"),
             Err(e) => {
@@ -329,7 +444,7 @@ async fn main() -> Result<()> {
         }
     }
 
-    let voice = Arc::new(paracord_media::VoiceManager::new(livekit_config));
+    let voice = Arc::new(paracord_media::VoiceManager::new(livekit_config.clone()));
     let storage = Arc::new(paracord_media::StorageManager::new(
         paracord_media::StorageConfig {
             base_path: config.media.storage_path.clone().into(),
@@ -409,16 +524,17 @@ async fn main() -> Result<()> {
             .domain
             .clone()
             .unwrap_or_else(|| config.server.server_name.clone());
-        Some(paracord_federation::FederationService::new(
-            paracord_federation::FederationConfig {
+        Some(
+            paracord_federation::FederationService::new(paracord_federation::FederationConfig {
                 enabled: true,
                 server_name: config.server.server_name.clone(),
                 domain: fed_domain,
                 key_id: "ed25519:auto".to_string(),
                 signing_key,
                 allow_discovery: config.federation.allow_discovery,
-            },
-        ))
+            })
+            .with_retry_policy(federation_retry_policy(&config.federation)),
+        )
     } else {
         None
     };
@@ -428,6 +544,67 @@ async fn main() -> Result<()> {
         .context("failed to load memberships for member index")?;
     let member_index = paracord_core::member_index::MemberIndex::from_memberships(memberships);
 
+    let usage_tracker = if config.abuse.enabled {
+        paracord_core::usage::UserUsageTracker::new(paracord_core::usage::UsageThresholds {
+            messages_per_minute: config.abuse.messages_per_minute,
+            uploads_per_minute: config.abuse.uploads_per_minute,
+            upload_bytes_per_hour: config.abuse.upload_bytes_per_hour,
+            auto_timeout_seconds: config.abuse.auto_timeout_seconds,
+        })
+    } else {
+        paracord_core::usage::UserUsageTracker::disabled()
+    };
+
+    let rate_limits = match config.abuse.rate_limit_store {
+        config::RateLimitStoreKind::Memory => paracord_core::rate_limit::RateLimitStore::default(),
+        config::RateLimitStoreKind::Database => {
+            paracord_core::rate_limit::RateLimitStore::Database(
+                paracord_core::rate_limit::DbRateLimitStore::new(db.clone()),
+            )
+        }
+    };
+
+    let push_notifier = if config.push.enabled {
+        let subject = config
+            .push
+            .effective_subject(config.server.public_url.as_deref())
+            .unwrap_or_default();
+        let notifier = paracord_core::push::PushNotifier::new(
+            &config.push.vapid_private_key,
+            &subject,
+            paracord_api::push::http_poster(std::time::Duration::from_secs(
+                config.push.timeout_seconds,
+            )),
+        )
+        .map_err(|e| anyhow::anyhow!("push.vapid_private_key: {e}"))?;
+        Some(Arc::new(notifier))
+    } else {
+        None
+    };
+
+    let mailer = if config.mail.enabled {
+        let send = paracord_api::mail::smtp_sender(paracord_api::mail::SmtpSettings {
+            host: config.mail.smtp_host.clone(),
+            port: config.mail.smtp_port,
+            security: match config.mail.security {
+                config::SmtpSecurity::Starttls => paracord_api::mail::SmtpSecurity::Starttls,
+                config::SmtpSecurity::Tls => paracord_api::mail::SmtpSecurity::Tls,
+                config::SmtpSecurity::None => paracord_api::mail::SmtpSecurity::None,
+            },
+            username: config.mail.username.clone(),
+            password: config.mail.password.clone(),
+            from: config.mail.from.clone(),
+            timeout: std::time::Duration::from_secs(config.mail.timeout_seconds),
+        })
+        .map_err(|e| anyhow::anyhow!("mail: {e}"))?;
+        Some(Arc::new(paracord_core::mail::Mailer::new(
+            config.server.public_url.as_deref().unwrap_or_default(),
+            send,
+        )))
+    } else {
+        None
+    };
+
     let mut state = paracord_core::AppState {
         db,
         event_bus: paracord_core::events::EventBus::default(),
@@ -439,6 +616,10 @@ async fn main() -> Result<()> {
             registration_enabled: config.auth.registration_enabled,
             allow_username_login: config.auth.allow_username_login,
             require_email: config.auth.require_email,
+            unique_usernames: config.auth.unique_usernames,
+            login_max_failures: config.auth.login_max_failures,
+            login_lockout_seconds: config.auth.login_lockout_seconds,
+            session_idle_timeout_seconds: config.auth.session_idle_timeout_seconds,
             storage_path: config.storage.path.clone(),
             max_upload_size: config.storage.max_upload_size,
             livekit_api_key: config.livekit.api_key.clone(),
@@ -450,11 +631,14 @@ async fn main() -> Result<()> {
                 .replace("://localhost:", "://127.0.0.1:"),
             livekit_public_url,
             livekit_available: livekit_reachable,
+            livekit_recording_enabled: config.livekit.recording_enabled,
+            livekit_recording_filepath: config.livekit.recording_filepath.clone(),
             public_url: config.server.public_url.clone(),
             media_storage_path: config.media.storage_path.clone(),
             media_max_file_size: config.media.max_file_size,
             media_p2p_threshold: config.media.p2p_threshold,
             file_cryptor: at_rest_profile.file_cryptor.clone(),
+            secret_cryptor: at_rest_profile.secret_cryptor.clone(),
             backup_dir: config.backup.backup_dir.clone(),
             database_url: config.database.url.clone(),
             federation_max_events_per_peer_per_minute: config
@@ -463,14 +647,26 @@ async fn main() -> Result<()> {
             federation_max_user_creates_per_peer_per_hour: config
                 .federation
                 .max_user_creates_per_peer_per_hour,
+            webhook_executions_per_minute: config.abuse.webhook_executions_per_minute,
+            federation_media_token_ttl_seconds: config.federation.media_token_ttl_seconds,
+            federation_file_token_ttl_seconds: config.federation.file_token_ttl_seconds,
             native_media_enabled: config.voice.native_media,
             native_media_port: config.voice.port,
             native_media_max_participants: config.voice.max_participants_per_room,
             native_media_e2ee_required: config.voice.e2ee_required,
             max_guild_storage_quota: config.storage.max_guild_storage_quota,
+            max_attachments_per_message: config.storage.max_attachments_per_message,
+            max_attachment_bytes_per_message: config.storage.max_attachment_bytes_per_message,
+            max_attachment_filename_length: config.storage.max_attachment_filename_length,
+            max_pending_uploads_per_user: config.storage.max_pending_uploads_per_user,
+            attachment_inline_origins: config.storage.inline_allowed_origins.clone(),
+            attachment_inline_token_ttl_seconds: config.storage.inline_token_ttl_seconds,
+            log_attachment_access: config.storage.log_attachment_access,
             federation_file_cache_enabled: config.federation.file_cache_enabled,
             federation_file_cache_max_size: config.federation.file_cache_max_size,
             federation_file_cache_ttl_hours: config.federation.file_cache_ttl_hours,
+            media_cdn_base_url: config.storage.media_cdn_base_url.clone(),
+            gateway_compression_threshold_bytes: config.server.gateway_compression_threshold_bytes,
         },
         voice,
         storage,
@@ -481,7 +677,28 @@ async fn main() -> Result<()> {
         federation_service,
         member_index: Arc::new(member_index),
         presence_manager: Arc::new(paracord_core::presence_manager::PresenceManager::new()),
+        usage: Arc::new(usage_tracker),
+        rate_limits: Arc::new(rate_limits.clone()),
+        livekit_online: Arc::new(std::sync::atomic::AtomicBool::new(livekit_reachable)),
         native_media: None,
+        link_unfurler: config.link_unfurl.enabled.then(|| {
+            let timeout = std::time::Duration::from_secs(config.link_unfurl.timeout_seconds);
+            let mut unfurler = paracord_core::link_unfurl::LinkUnfurler::new(
+                paracord_api::link_unfurl::http_fetcher(timeout, config.link_unfurl.max_bytes),
+                std::time::Duration::from_secs(config.link_unfurl.cache_ttl_seconds),
+            );
+            if config.link_unfurl.max_image_bytes > 0 {
+                unfurler =
+                    unfurler.with_image_fetcher(paracord_api::link_unfurl::http_image_fetcher(
+                        timeout,
+                        config.link_unfurl.max_image_bytes,
+                    ));
+            }
+            Arc::new(unfurler)
+        }),
+        push_notifier,
+        mailer,
+        readiness_cache: Arc::default(),
     };
 
     // ── Native QUIC media server ─────────────────────────────────────────────
@@ -568,7 +785,7 @@ async fn main() -> Result<()> {
         tracing::info!("QUIC file transfer enabled (sharing native media QUIC endpoint)");
     }
 
-    paracord_api::install_http_rate_limiter();
+    paracord_api::install_http_rate_limit_store(rate_limits);
     paracord_api::spawn_http_rate_limiter_cleanup(shutdown_notify.clone());
 
     spawn_pending_attachment_cleanup(
@@ -590,25 +807,54 @@ async fn main() -> Result<()> {
         shutdown_notify.clone(),
     );
     spawn_federation_delivery_worker(state.clone(), shutdown_notify.clone());
+    spawn_integration_webhook_worker(state.clone(), shutdown_notify.clone());
+    spawn_usage_prune(state.usage.clone(), shutdown_notify.clone());
+    spawn_expired_token_cleanup(state.db.clone(), shutdown_notify.clone());
+    spawn_thread_auto_archive(state.clone(), shutdown_notify.clone());
+    spawn_expired_upload_cleanup(state.clone(), shutdown_notify.clone());
+    spawn_temp_ban_expiry(state.clone(), shutdown_notify.clone());
+    spawn_orphaned_attachment_gc(
+        state.clone(),
+        config.storage.orphaned_attachment_gc_interval_seconds,
+        shutdown_notify.clone(),
+    );
+    // A managed LiveKit that missed its readiness window is still probed so
+    // voice comes back on its own if it finishes starting later.
+    if livekit_reachable || managed_livekit.is_some() {
+        spawn_livekit_health_probe(state.clone(), shutdown_notify.clone());
+    }
     bots::spawn_bot_manager(state.clone(), shutdown_notify.clone());
 
+    // The gateway resolves the client IP the same way as the API so a spoofed
+    // internal client-IP header never reaches it.
+    let gateway = paracord_ws::gateway_router().layer(axum::middleware::from_fn(
+        paracord_api::client_ip::client_ip_middleware,
+    ));
     let router = paracord_api::build_router()
-        .merge(paracord_ws::gateway_router())
+        .merge(gateway)
         .with_state(state);
 
     // ── Web UI serving ───────────────────────────────────────────────────────
     let web_ui_status;
+    // The API router carries its own security headers; the UI is merged in
+    // afterwards, so it needs the same middleware applied explicitly.
+    let security_headers =
+        axum::middleware::from_fn(paracord_api::security_headers::security_headers_middleware);
     let app = if let Some(ref dir) = web_dir {
         let index_path = dir.join("index.html");
         let spa_fallback = tower_http::services::ServeFile::new(&index_path);
         let serve_dir = tower_http::services::ServeDir::new(dir).not_found_service(spa_fallback);
         web_ui_status = format!("Serving from {:?}", dir);
-        router.fallback_service(serve_dir)
+        router.merge(
+            axum::Router::new()
+                .fallback_service(serve_dir)
+                .layer(security_headers),
+        )
     } else {
         #[cfg(feature = "embed-ui")]
         {
             web_ui_status = "Embedded".to_string();
-            router.merge(embedded_ui::router())
+            router.merge(embedded_ui::router().layer(security_headers))
         }
         #[cfg(not(feature = "embed-ui"))]
         {
@@ -761,33 +1007,51 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Export the trusted-proxy settings for the API layer's client-IP resolution.
+fn configure_trusted_proxies(network: &config::NetworkConfig) {
+    let proxies: Vec<String> = network
+        .trusted_proxies
+        .iter()
+        .filter(|entry| {
+            let valid = entry.parse::<paracord_util::net::IpCidr>().is_ok();
+            if !valid {
+                tracing::warn!("Ignoring invalid network.trusted_proxies entry: {}", entry);
+            }
+            valid
+        })
+        .cloned()
+        .collect();
+    if network.trust_proxy && proxies.is_empty() {
+        tracing::warn!(
+            "network.trust_proxy is enabled but network.trusted_proxies is empty; forwarded headers will be ignored"
+        );
+    }
+    std::env::set_var(
+        "PARACORD_TRUST_PROXY",
+        if network.trust_proxy { "true" } else { "false" },
+    );
+    std::env::set_var("PARACORD_TRUSTED_PROXY_IPS", proxies.join(","));
+}
+
+/// Export the security-header overrides for the API layer's header middleware.
+fn configure_security_headers(server: &config::ServerConfig) {
+    let overrides = [
+        ("PARACORD_UI_CSP", &server.ui_content_security_policy),
+        ("PARACORD_REFERRER_POLICY", &server.referrer_policy),
+        ("PARACORD_FRAME_OPTIONS", &server.frame_options),
+    ];
+    for (name, value) in overrides {
+        if let Some(value) = value {
+            std::env::set_var(name, value);
+        }
+    }
+}
+
 /// Ensure all data directories exist before the server starts.
 fn ensure_data_dirs(config: &config::Config) {
-    // Storage directories
-    for dir in [
-        &config.storage.path,
-        &config.media.storage_path,
-        &config.tls.acme.webroot_path,
-        &config.backup.backup_dir,
-    ] {
-        if let Err(e) = std::fs::create_dir_all(dir) {
-            tracing::warn!("Could not create directory '{}': {}", dir, e);
-        }
-    }
-
-    // Database parent directory
-    if matches!(config.database.engine, config::DatabaseEngine::Sqlite) {
-        if let Some(db_path) = config
-            .database
-            .url
-            .strip_prefix("sqlite://")
-            .and_then(|s| s.split('?').next())
-        {
-            if let Some(parent) = std::path::Path::new(db_path).parent() {
-                if !parent.as_os_str().is_empty() {
-                    let _ = std::fs::create_dir_all(parent);
-                }
-            }
+    for dir in config.data_directories() {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("Could not create directory '{}': {}", dir.display(), e);
         }
     }
 }
@@ -881,6 +1145,8 @@ fn harden_secret_file_permissions(path: &Path) {
 
 async fn load_runtime_settings(db: &paracord_db::DbPool) -> paracord_core::RuntimeSettings {
     let mut settings = paracord_core::RuntimeSettings::default();
+    let mut blocked_name_patterns = String::new();
+    let mut blocked_names_fold = settings.name_filter.fold();
 
     if let Ok(all) = paracord_db::server_settings::get_all_settings(db).await {
         for (key, value) in all {
@@ -893,15 +1159,35 @@ async fn load_runtime_settings(db: &paracord_db::DbPool) -> paracord_core::Runti
                         settings.max_guilds_per_user = v;
                     }
                 }
+                "guild_creation_restricted" => settings.guild_creation_restricted = value == "true",
                 "max_members_per_guild" => {
                     if let Ok(v) = value.parse() {
                         settings.max_members_per_guild = v;
                     }
                 }
+                "max_webhooks_per_channel" => {
+                    if let Ok(v) = value.parse() {
+                        settings.max_webhooks_per_channel = v;
+                    }
+                }
+                "max_webhooks_per_guild" => {
+                    if let Ok(v) = value.parse() {
+                        settings.max_webhooks_per_guild = v;
+                    }
+                }
+                "require_email_verification" => {
+                    settings.require_email_verification = value == "true"
+                }
+                "blocked_name_patterns" => blocked_name_patterns = value,
+                "blocked_names_fold" => blocked_names_fold = value == "true",
                 _ => {}
             }
         }
     }
+    match paracord_core::name_filter::NameFilter::new(&blocked_name_patterns, blocked_names_fold) {
+        Ok(filter) => settings.name_filter = filter,
+        Err(e) => tracing::warn!("ignoring invalid blocked_name_patterns: {}", e),
+    }
 
     settings
 }
@@ -1013,6 +1299,48 @@ fn redirect_to_https(req: axum::extract::Request, tls_port: u16) -> axum::respon
     axum::response::Redirect::permanent(&location).into_response()
 }
 
+/// True when `origin` is an `http://` URL whose host is not loopback, i.e.
+/// traffic to it would cross the network unencrypted.
+fn is_plaintext_public_origin(origin: &str) -> bool {
+    let Some(rest) = origin.trim().strip_prefix("http://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = if let Some(bracketed) = authority.strip_prefix('[') {
+        bracketed.split(']').next().unwrap_or(bracketed)
+    } else {
+        authority.split(':').next().unwrap_or(authority)
+    };
+    let host = host.to_ascii_lowercase();
+    if host.is_empty() || host == "localhost" || host.ends_with(".localhost") {
+        return false;
+    }
+    !host
+        .parse::<std::net::IpAddr>()
+        .map(|ip| ip.is_loopback())
+        .unwrap_or(false)
+}
+
+/// Startup warnings for a public URL or CORS/WS origins served over
+/// plaintext HTTP.
+fn plaintext_exposure_warnings(public_url: Option<&str>, origins: &[String]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(url) = public_url.filter(|url| is_plaintext_public_origin(url)) {
+        warnings.push(format!(
+            "server.public_url '{url}' is plaintext HTTP on a non-loopback host; logins and messages will cross the network unencrypted. Enable [tls] (or tls.acme) and use an https:// public_url"
+        ));
+    }
+    for origin in origins
+        .iter()
+        .filter(|origin| is_plaintext_public_origin(origin))
+    {
+        warnings.push(format!(
+            "Allowed CORS/WebSocket origin '{origin}' is plaintext HTTP on a non-loopback host; serve it over HTTPS"
+        ));
+    }
+    warnings
+}
+
 fn normalize_https_host(host: &str, tls_port: u16) -> String {
     let trimmed = host.trim();
     if trimmed.is_empty() {
@@ -1104,24 +1432,31 @@ fn build_at_rest_profile(config: &config::Config) -> Result<AtRestRuntimeProfile
         None
     };
     let file_cryptor = if config.at_rest.encrypt_files {
-        Some(paracord_util::at_rest::FileCryptor::from_master_key(
-            &master_key,
-            config.at_rest.allow_plaintext_file_reads,
-        ))
+        Some(
+            paracord_util::at_rest::FileCryptor::from_master_key(
+                &master_key,
+                config.at_rest.allow_plaintext_file_reads,
+            )
+            .with_chunked_writes(config.at_rest.chunked_files),
+        )
     } else {
         None
     };
 
     tracing::info!(
-        "At-rest encryption enabled (sqlite={}, files={}, allow_plaintext_file_reads={})",
+        "At-rest encryption enabled (sqlite={}, files={}, allow_plaintext_file_reads={}, chunked_files={})",
         encrypt_sqlite,
         config.at_rest.encrypt_files,
-        config.at_rest.allow_plaintext_file_reads
+        config.at_rest.allow_plaintext_file_reads,
+        config.at_rest.chunked_files
     );
 
     Ok(AtRestRuntimeProfile {
         sqlite_key_hex,
         file_cryptor,
+        secret_cryptor: Some(paracord_util::at_rest::SecretCryptor::from_master_key(
+            &master_key,
+        )),
     })
 }
 
@@ -1171,6 +1506,211 @@ async fn cleanup_pending_attachments_once(
     Ok(())
 }
 
+fn spawn_usage_prune(
+    usage: Arc<paracord_core::usage::UserUsageTracker>,
+    shutdown: Arc<tokio::sync::Notify>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    let pruned = usage.prune_idle();
+                    if pruned > 0 {
+                        tracing::debug!(pruned, "pruned idle usage counters");
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Drop one-time account tokens (email verification, password reset) once
+/// they expire.
+fn spawn_expired_token_cleanup(db: paracord_db::DbPool, shutdown: Arc<tokio::sync::Notify>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(300));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    let now = chrono::Utc::now();
+                    match paracord_db::users::purge_expired_email_verifications(&db, now).await {
+                        Ok(0) => {}
+                        Ok(purged) => tracing::debug!(purged, "purged expired email verification tokens"),
+                        Err(e) => tracing::warn!("email verification token purge failed: {}", e),
+                    }
+                    match paracord_db::users::purge_expired_password_resets(&db, now).await {
+                        Ok(0) => {}
+                        Ok(purged) => tracing::debug!(purged, "purged expired password reset tokens"),
+                        Err(e) => tracing::warn!("password reset token purge failed: {}", e),
+                    }
+                    match paracord_db::push::purge_orphaned_subscriptions(&db, now).await {
+                        Ok(0) => {}
+                        Ok(purged) => tracing::debug!(purged, "purged push subscriptions of ended sessions"),
+                        Err(e) => tracing::warn!("push subscription purge failed: {}", e),
+                    }
+                    match paracord_db::federation::purge_expired_read_tokens(&db, now.timestamp_millis()).await {
+                        Ok(0) => {}
+                        Ok(purged) => tracing::debug!(purged, "purged expired federation read tokens"),
+                        Err(e) => tracing::warn!("federation read token purge failed: {}", e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn spawn_orphaned_attachment_gc(
+    state: paracord_core::AppState,
+    interval_seconds: u64,
+    shutdown: Arc<tokio::sync::Notify>,
+) {
+    if interval_seconds == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_seconds));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    let collected =
+                        paracord_api::routes::files::collect_orphaned_attachments(&state, 256);
+                    match collected.await {
+                        Ok(0) => {}
+                        Ok(removed) => tracing::info!(removed, "removed orphaned attachments"),
+                        Err(e) => tracing::warn!("orphaned attachment cleanup failed: {}", e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Discard resumable uploads that sat idle past their expiry, along with
+/// their temp files, even when nobody starts a new upload.
+fn spawn_expired_upload_cleanup(
+    state: paracord_core::AppState,
+    shutdown: Arc<tokio::sync::Notify>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(300));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    match paracord_api::routes::uploads::collect_expired_uploads(&state).await {
+                        Ok(0) => {}
+                        Ok(removed) => tracing::debug!(removed, "removed expired uploads"),
+                        Err(e) => tracing::warn!("expired upload cleanup failed: {}", e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn spawn_thread_auto_archive(state: paracord_core::AppState, shutdown: Arc<tokio::sync::Notify>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    match paracord_api::routes::channels::auto_archive_threads(&state).await {
+                        Ok(0) => {}
+                        Ok(archived) => tracing::debug!(archived, "auto-archived idle threads"),
+                        Err(e) => tracing::warn!("thread auto-archive failed: {}", e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn spawn_temp_ban_expiry(state: paracord_core::AppState, shutdown: Arc<tokio::sync::Notify>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    match paracord_api::routes::bans::expire_temp_bans(&state).await {
+                        Ok(0) => {}
+                        Ok(lifted) => tracing::info!(lifted, "lifted expired temporary bans"),
+                        Err(e) => tracing::warn!("temporary ban expiry failed: {}", e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Periodically re-check LiveKit so voice endpoints fail fast with a clear
+/// 503 (and clients grey out voice) if the media server dies mid-run.
+fn spawn_livekit_health_probe(state: paracord_core::AppState, shutdown: Arc<tokio::sync::Notify>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        // The startup check already ran; skip the immediate first tick.
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    // Read per tick so credentials rotated by an admin are used.
+                    let livekit = state.voice.livekit();
+                    let result = match tokio::time::timeout(
+                        std::time::Duration::from_secs(10),
+                        livekit.check_health(),
+                    )
+                    .await
+                    {
+                        Ok(result) => result,
+                        Err(_) => Err(anyhow::anyhow!("health check timed out")),
+                    };
+                    let available = result.is_ok();
+                    if !state.set_livekit_available(available) {
+                        continue;
+                    }
+                    match result {
+                        Ok(()) => tracing::info!("LiveKit is reachable again; voice re-enabled"),
+                        Err(e) => tracing::warn!("LiveKit became unreachable; voice disabled: {}", e),
+                    }
+                    state.event_bus.dispatch(
+                        paracord_models::gateway::EVENT_VOICE_AVAILABILITY_UPDATE,
+                        serde_json::json!({ "livekit_available": available }),
+                        None,
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Outbound queue backoff from `[federation]`, with each limit kept at least
+/// one unit so a zeroed setting cannot stall or spin the queue.
+fn federation_retry_policy(
+    federation: &config::FederationConfig,
+) -> paracord_federation::OutboundRetryPolicy {
+    let base_delay_ms = federation.queue_retry_base_seconds.max(1) as i64 * 1000;
+    paracord_federation::OutboundRetryPolicy {
+        base_delay_ms,
+        max_delay_ms: (federation.queue_retry_max_seconds as i64 * 1000).max(base_delay_ms),
+        max_attempts: federation.queue_max_attempts.max(1) as i64,
+        max_age_ms: federation.queue_max_age_hours.max(1) as i64 * 3_600_000,
+        dead_letter_retention_ms: federation.dead_letter_retention_hours.max(1) as i64 * 3_600_000,
+    }
+}
+
 fn spawn_federation_delivery_worker(
     state: paracord_core::AppState,
     shutdown: Arc<tokio::sync::Notify>,
@@ -1201,6 +1741,49 @@ fn spawn_federation_delivery_worker(
     });
 }
 
+fn spawn_integration_webhook_worker(
+    state: paracord_core::AppState,
+    shutdown: Arc<tokio::sync::Notify>,
+) {
+    let mut events = state.event_bus.subscribe_system();
+    let queue_state = state.clone();
+    let queue_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = queue_shutdown.notified() => break,
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        paracord_api::routes::integrations::enqueue_integration_event(
+                            &queue_state,
+                            &event,
+                        )
+                        .await;
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("integrations: event listener lagged by {} events", skipped);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                _ = interval.tick() => {
+                    paracord_api::routes::integrations::process_integration_deliveries_once(&state, 64)
+                        .await;
+                }
+            }
+        }
+    });
+}
+
 fn spawn_retention_jobs(
     db: paracord_db::DbPool,
     backend: Arc<paracord_media::Storage>,
@@ -1268,7 +1851,13 @@ async fn run_retention_once(
     }
 
     if let Some(cutoff) = retention_cutoff(now, retention.security_event_days) {
-        let deleted = purge_security_events_older_than(db, cutoff, batch_size).await?;
+        let deleted = purge_security_events_older_than(
+            db,
+            cutoff,
+            batch_size,
+            retention.security_event_archive_path.as_deref(),
+        )
+        .await?;
         if deleted > 0 {
             tracing::info!("Retention removed {} security event(s)", deleted);
         }
@@ -1504,12 +2093,29 @@ async fn purge_security_events_older_than(
     db: &paracord_db::DbPool,
     older_than: chrono::DateTime<chrono::Utc>,
     batch_size: i64,
+    archive_path: Option<&str>,
 ) -> Result<u64> {
     let mut total_deleted = 0_u64;
     loop {
-        let deleted =
-            paracord_db::security_events::purge_entries_older_than(db, older_than, batch_size)
+        let deleted = match archive_path {
+            Some(path) => {
+                let events = paracord_db::security_events::list_events_older_than(
+                    db, older_than, batch_size,
+                )
                 .await?;
+                if events.is_empty() {
+                    break;
+                }
+                // Only delete once the batch is durably archived.
+                append_security_event_archive(path, &events).await?;
+                let ids: Vec<i64> = events.iter().map(|e| e.id).collect();
+                paracord_db::security_events::delete_events(db, &ids).await?
+            }
+            None => {
+                paracord_db::security_events::purge_entries_older_than(db, older_than, batch_size)
+                    .await?
+            }
+        };
         total_deleted = total_deleted.saturating_add(deleted);
         if deleted < batch_size as u64 {
             break;
@@ -1518,6 +2124,45 @@ async fn purge_security_events_older_than(
     Ok(total_deleted)
 }
 
+async fn append_security_event_archive(
+    path: &str,
+    events: &[paracord_db::security_events::SecurityEventRow],
+) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut lines = String::new();
+    for event in events {
+        let line = serde_json::json!({
+            "id": event.id.to_string(),
+            "actor_user_id": event.actor_user_id.map(|id| id.to_string()),
+            "action": event.action,
+            "target_user_id": event.target_user_id.map(|id| id.to_string()),
+            "session_id": event.session_id,
+            "device_id": event.device_id,
+            "user_agent": event.user_agent,
+            "ip_address": event.ip_address,
+            "details": event.details,
+            "created_at": event.created_at.to_rfc3339(),
+        });
+        lines.push_str(&line.to_string());
+        lines.push('\n');
+    }
+
+    if let Some(parent) = Path::new(path).parent() {
+        if !parent.as_os_str().is_empty() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(lines.as_bytes()).await?;
+    file.sync_data().await?;
+    Ok(())
+}
+
 fn attachment_storage_key(attachment: &paracord_db::attachments::AttachmentRow) -> String {
     let ext = std::path::Path::new(&attachment.filename)
         .extension()
@@ -1977,7 +2622,9 @@ async fn handle_webtransport_connection(
 #[cfg(test)]
 mod tests {
     use super::{
-        ensure_federation_signing_key_file, livekit_credentials_look_insecure, normalize_https_host,
+        ensure_federation_signing_key_file, is_plaintext_public_origin,
+        livekit_credentials_look_insecure, normalize_https_host, plaintext_exposure_warnings,
+        purge_security_events_older_than,
     };
 
     #[test]
@@ -1989,6 +2636,35 @@ mod tests {
         assert_eq!(normalize_https_host("[::1]:8080", 8443), "[::1]:8443");
     }
 
+    #[test]
+    fn flags_only_non_loopback_http_origins() {
+        assert!(is_plaintext_public_origin("http://chat.example.com"));
+        assert!(is_plaintext_public_origin("http://203.0.113.7:8080/app"));
+        assert!(!is_plaintext_public_origin("https://chat.example.com"));
+        assert!(!is_plaintext_public_origin("http://localhost:5173"));
+        assert!(!is_plaintext_public_origin("http://127.0.0.1:8080"));
+        assert!(!is_plaintext_public_origin("http://[::1]:8080"));
+        assert!(!is_plaintext_public_origin("http://tauri.localhost"));
+    }
+
+    #[test]
+    fn warns_for_plaintext_public_url_and_origins() {
+        let warnings = plaintext_exposure_warnings(Some("http://chat.example.com"), &[]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("server.public_url"));
+
+        assert!(plaintext_exposure_warnings(Some("https://chat.example.com"), &[]).is_empty());
+        assert!(plaintext_exposure_warnings(None, &[]).is_empty());
+
+        let origins = vec![
+            "https://app.example.com".to_string(),
+            "http://app.example.com".to_string(),
+        ];
+        let warnings = plaintext_exposure_warnings(Some("https://chat.example.com"), &origins);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'http://app.example.com'"));
+    }
+
     #[test]
     fn detects_insecure_livekit_credentials() {
         assert!(livekit_credentials_look_insecure("devkey", "devsecret"));
@@ -2022,4 +2698,73 @@ mod tests {
             .to_string()
             .contains("invalid federation signing key at"));
     }
+
+    #[tokio::test]
+    async fn archives_security_events_before_pruning() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let db_url = format!(
+            "sqlite://{}?mode=rwc",
+            temp_dir
+                .path()
+                .join("retention.db")
+                .to_string_lossy()
+                .replace('\\', "/")
+        );
+        let db = paracord_db::create_pool(&db_url, 1).await.expect("pool");
+        paracord_db::run_migrations(&db).await.expect("migrations");
+
+        let now = chrono::Utc::now();
+        for (id, age_days) in [(1_i64, 60_i64), (2, 45), (3, 1)] {
+            paracord_db::security_events::create_event(
+                &db,
+                id,
+                None,
+                "auth.login",
+                None,
+                None,
+                None,
+                None,
+                Some("127.0.0.1"),
+                None,
+            )
+            .await
+            .expect("create event");
+            let created_at = (now - chrono::Duration::days(age_days))
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string();
+            sqlx::query("UPDATE security_events SET created_at = $1 WHERE id = $2")
+                .bind(created_at)
+                .bind(id)
+                .execute(&db)
+                .await
+                .expect("backdate event");
+        }
+
+        let archive_path = temp_dir.path().join("archive").join("security.jsonl");
+        // A batch size of one exercises the multi-batch loop.
+        let deleted = purge_security_events_older_than(
+            &db,
+            now - chrono::Duration::days(30),
+            1,
+            Some(archive_path.to_str().expect("utf8 path")),
+        )
+        .await
+        .expect("purge");
+        assert_eq!(deleted, 2);
+
+        let remaining = paracord_db::security_events::list_events(&db, None, None, 10)
+            .await
+            .expect("list events");
+        assert_eq!(remaining.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3]);
+
+        let archived = std::fs::read_to_string(&archive_path).expect("archive file");
+        let ids: Vec<String> = archived
+            .lines()
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line).expect("json line");
+                value["id"].as_str().expect("id").to_string()
+            })
+            .collect();
+        assert_eq!(ids, vec!["1", "2"]);
+    }
 }
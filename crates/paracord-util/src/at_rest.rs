@@ -12,7 +12,19 @@ use thiserror::Error;
 
 const FILE_MAGIC_V1: &[u8; 8] = b"PRCENC01";
 const FILE_MAGIC_V2: &[u8; 8] = b"PRCENC02";
+const FILE_MAGIC_V3: &[u8; 8] = b"PRCENC03";
 const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+/// Chunked (v3) payloads start with a random salt from which a per-file key is
+/// derived, so each chunk's nonce only has to be unique within the file: a
+/// big-endian chunk counter followed by a final-chunk flag.
+const CHUNK_KEY_SALT_LEN: usize = 32;
+/// Plaintext bytes per v3 chunk. Only the final chunk may be shorter.
+pub const FILE_CHUNK_LEN: usize = 64 * 1024;
+const SEALED_CHUNK_LEN: usize = FILE_CHUNK_LEN + TAG_LEN;
+const V3_HEADER_LEN: usize = FILE_MAGIC_V3.len() + CHUNK_KEY_SALT_LEN;
+/// Prefix of a sealed database secret: `enc:v1:` + base64(nonce || ciphertext).
+const SECRET_PREFIX: &str = "enc:v1:";
 
 #[derive(Debug, Error)]
 pub enum AtRestKeyError {
@@ -40,10 +52,23 @@ pub enum FileCryptoError {
     PlaintextReadDisabled,
 }
 
+#[derive(Debug, Error)]
+pub enum SecretCryptoError {
+    #[error("secret encryption key is invalid")]
+    InvalidKey,
+    #[error("secret encryption failed")]
+    EncryptFailed,
+    #[error("sealed secret format is invalid")]
+    InvalidPayload,
+    #[error("sealed secret decryption failed")]
+    DecryptFailed,
+}
+
 #[derive(Clone)]
 pub struct FileCryptor {
     key: [u8; 32],
     allow_plaintext_reads: bool,
+    chunked_writes: bool,
 }
 
 impl std::fmt::Debug for FileCryptor {
@@ -51,6 +76,7 @@ impl std::fmt::Debug for FileCryptor {
         f.debug_struct("FileCryptor")
             .field("key", &"<redacted>")
             .field("allow_plaintext_reads", &self.allow_plaintext_reads)
+            .field("chunked_writes", &self.chunked_writes)
             .finish()
     }
 }
@@ -60,22 +86,45 @@ impl FileCryptor {
         Self {
             key: derive_subkey(master_key, b"files"),
             allow_plaintext_reads,
+            chunked_writes: false,
         }
     }
 
+    /// Write new payloads in the chunked v3 format instead of v2. Every
+    /// release can read v3 from here on, but older ones cannot, so this stays
+    /// off until a rollback is no longer on the table.
+    pub fn with_chunked_writes(mut self, enabled: bool) -> Self {
+        self.chunked_writes = enabled;
+        self
+    }
+
     pub fn allow_plaintext_reads(&self) -> bool {
         self.allow_plaintext_reads
     }
 
+    pub fn chunked_writes(&self) -> bool {
+        self.chunked_writes
+    }
+
     pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, FileCryptoError> {
         self.encrypt_with_aad(plaintext, b"")
     }
 
+    /// Encrypt into the chunked v3 format when [`Self::chunked_writes`] is
+    /// on, so the payload can later be decrypted with a [`ChunkedDecryptor`]
+    /// without holding it all in memory, and into v2 otherwise.
     pub fn encrypt_with_aad(
         &self,
         plaintext: &[u8],
         aad: &[u8],
     ) -> Result<Vec<u8>, FileCryptoError> {
+        if self.chunked_writes {
+            let mut encryptor = self.chunked_encryptor(aad)?;
+            let mut out = encryptor.update(plaintext)?;
+            out.extend(encryptor.finish()?);
+            return Ok(out);
+        }
+
         let cipher =
             Aes256Gcm::new_from_slice(&self.key).map_err(|_| FileCryptoError::InvalidKey)?;
         let mut nonce_bytes = [0_u8; NONCE_LEN];
@@ -110,6 +159,12 @@ impl FileCryptor {
             }
             return Err(FileCryptoError::PlaintextReadDisabled);
         }
+        if Self::payload_is_chunked(stored) {
+            let mut decryptor = self.chunked_decryptor(aad)?;
+            let mut out = decryptor.update(stored)?;
+            out.extend(decryptor.finish()?);
+            return Ok(out);
+        }
         if stored.len() <= FILE_MAGIC_V2.len() + NONCE_LEN {
             return Err(FileCryptoError::InvalidPayload);
         }
@@ -139,10 +194,280 @@ impl FileCryptor {
     }
 
     pub fn payload_is_encrypted(payload: &[u8]) -> bool {
-        payload.starts_with(FILE_MAGIC_V1) || payload.starts_with(FILE_MAGIC_V2)
+        payload.starts_with(FILE_MAGIC_V1)
+            || payload.starts_with(FILE_MAGIC_V2)
+            || Self::payload_is_chunked(payload)
+    }
+
+    /// Whether `payload` (or its first bytes) is in the chunked v3 format and
+    /// can therefore be decrypted incrementally.
+    pub fn payload_is_chunked(payload: &[u8]) -> bool {
+        payload.starts_with(FILE_MAGIC_V3)
+    }
+
+    /// Start incremental encryption into the v3 format, bound to `aad`.
+    /// Callers that write new files should check [`Self::chunked_writes`]
+    /// first.
+    pub fn chunked_encryptor(&self, aad: &[u8]) -> Result<ChunkedEncryptor, FileCryptoError> {
+        let mut salt = [0_u8; CHUNK_KEY_SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Ok(ChunkedEncryptor {
+            cipher: self.chunk_cipher(&salt)?,
+            aad: aad.to_vec(),
+            salt,
+            header_written: false,
+            buffer: Vec::new(),
+            next_index: 0,
+        })
+    }
+
+    /// Start incremental decryption of a v3 payload bound to `aad`.
+    pub fn chunked_decryptor(&self, aad: &[u8]) -> Result<ChunkedDecryptor, FileCryptoError> {
+        Ok(ChunkedDecryptor {
+            key: self.key,
+            cipher: None,
+            aad: aad.to_vec(),
+            buffer: Vec::new(),
+            next_index: 0,
+        })
+    }
+
+    fn chunk_cipher(&self, salt: &[u8; CHUNK_KEY_SALT_LEN]) -> Result<Aes256Gcm, FileCryptoError> {
+        chunk_cipher(&self.key, salt)
+    }
+}
+
+/// Seals small secrets stored in database columns (such as TOTP seeds) with a
+/// subkey of the at-rest master key.
+#[derive(Clone)]
+pub struct SecretCryptor {
+    key: [u8; 32],
+}
+
+impl std::fmt::Debug for SecretCryptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretCryptor")
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl SecretCryptor {
+    pub fn from_master_key(master_key: &[u8; 32]) -> Self {
+        Self {
+            key: derive_subkey(master_key, b"secrets"),
+        }
+    }
+
+    /// Encrypt `plaintext` bound to `aad` (e.g. the owning row's id).
+    pub fn seal(&self, plaintext: &str, aad: &[u8]) -> Result<String, SecretCryptoError> {
+        let cipher =
+            Aes256Gcm::new_from_slice(&self.key).map_err(|_| SecretCryptoError::InvalidKey)?;
+        let mut nonce = [0_u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let sealed = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad,
+                },
+            )
+            .map_err(|_| SecretCryptoError::EncryptFailed)?;
+        let mut raw = Vec::with_capacity(NONCE_LEN + sealed.len());
+        raw.extend_from_slice(&nonce);
+        raw.extend_from_slice(&sealed);
+        Ok(format!(
+            "{SECRET_PREFIX}{}",
+            BASE64_STANDARD_NO_PAD.encode(raw)
+        ))
+    }
+
+    pub fn open(&self, stored: &str, aad: &[u8]) -> Result<String, SecretCryptoError> {
+        let encoded = stored
+            .strip_prefix(SECRET_PREFIX)
+            .ok_or(SecretCryptoError::InvalidPayload)?;
+        let raw = BASE64_STANDARD_NO_PAD
+            .decode(encoded)
+            .map_err(|_| SecretCryptoError::InvalidPayload)?;
+        if raw.len() <= NONCE_LEN {
+            return Err(SecretCryptoError::InvalidPayload);
+        }
+        let cipher =
+            Aes256Gcm::new_from_slice(&self.key).map_err(|_| SecretCryptoError::InvalidKey)?;
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&raw[..NONCE_LEN]),
+                Payload {
+                    msg: &raw[NONCE_LEN..],
+                    aad,
+                },
+            )
+            .map_err(|_| SecretCryptoError::DecryptFailed)?;
+        String::from_utf8(plaintext).map_err(|_| SecretCryptoError::InvalidPayload)
+    }
+
+    /// Whether `stored` was written by [`SecretCryptor::seal`] rather than
+    /// kept in plaintext.
+    pub fn is_sealed(stored: &str) -> bool {
+        stored.starts_with(SECRET_PREFIX)
+    }
+}
+
+/// Produces a v3 payload from plaintext fed in pieces. Like the decryptor it
+/// holds back a full chunk until more input shows it is not the last one.
+pub struct ChunkedEncryptor {
+    cipher: Aes256Gcm,
+    aad: Vec<u8>,
+    salt: [u8; CHUNK_KEY_SALT_LEN],
+    header_written: bool,
+    buffer: Vec<u8>,
+    next_index: u32,
+}
+
+impl ChunkedEncryptor {
+    /// Feed the next plaintext bytes, returning whatever stored bytes they
+    /// complete.
+    pub fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, FileCryptoError> {
+        self.buffer.extend_from_slice(input);
+        let mut out = self.take_header();
+        let mut consumed = 0;
+        while self.buffer.len() - consumed > FILE_CHUNK_LEN {
+            let chunk = &self.buffer[consumed..consumed + FILE_CHUNK_LEN];
+            out.extend(self.seal_chunk(chunk, false)?);
+            consumed += FILE_CHUNK_LEN;
+            self.next_index = self
+                .next_index
+                .checked_add(1)
+                .ok_or(FileCryptoError::EncryptFailed)?;
+        }
+        self.buffer.drain(..consumed);
+        Ok(out)
+    }
+
+    /// Seal the held-back final chunk, which may be empty.
+    pub fn finish(mut self) -> Result<Vec<u8>, FileCryptoError> {
+        let mut out = self.take_header();
+        out.extend(self.seal_chunk(&self.buffer, true)?);
+        Ok(out)
+    }
+
+    fn take_header(&mut self) -> Vec<u8> {
+        if self.header_written {
+            return Vec::new();
+        }
+        self.header_written = true;
+        let mut header = Vec::with_capacity(V3_HEADER_LEN);
+        header.extend_from_slice(FILE_MAGIC_V3);
+        header.extend_from_slice(&self.salt);
+        header
+    }
+
+    fn seal_chunk(&self, chunk: &[u8], last: bool) -> Result<Vec<u8>, FileCryptoError> {
+        let nonce = chunk_nonce(self.next_index, last);
+        self.cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: chunk,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| FileCryptoError::EncryptFailed)
+    }
+}
+
+/// Decrypts a v3 payload as it arrives, one [`FILE_CHUNK_LEN`] chunk at a
+/// time. A full chunk is held back until more input shows it is not the last
+/// one, so truncating the payload at a chunk boundary is detected in
+/// [`ChunkedDecryptor::finish`].
+pub struct ChunkedDecryptor {
+    key: [u8; 32],
+    /// Keyed from the header's salt once it has arrived.
+    cipher: Option<Aes256Gcm>,
+    aad: Vec<u8>,
+    buffer: Vec<u8>,
+    next_index: u32,
+}
+
+impl ChunkedDecryptor {
+    /// Feed the next stored bytes, returning whatever plaintext they complete.
+    pub fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, FileCryptoError> {
+        self.buffer.extend_from_slice(input);
+        if self.cipher.is_none() {
+            if self.buffer.len() < V3_HEADER_LEN {
+                return Ok(Vec::new());
+            }
+            if !self.buffer.starts_with(FILE_MAGIC_V3) {
+                return Err(FileCryptoError::InvalidPayload);
+            }
+            let mut salt = [0_u8; CHUNK_KEY_SALT_LEN];
+            salt.copy_from_slice(&self.buffer[FILE_MAGIC_V3.len()..V3_HEADER_LEN]);
+            self.cipher = Some(chunk_cipher(&self.key, &salt)?);
+            self.buffer.drain(..V3_HEADER_LEN);
+        }
+
+        let mut out = Vec::new();
+        let mut consumed = 0;
+        while self.buffer.len() - consumed > SEALED_CHUNK_LEN {
+            let sealed = &self.buffer[consumed..consumed + SEALED_CHUNK_LEN];
+            out.extend(self.open_chunk(sealed, false)?);
+            consumed += SEALED_CHUNK_LEN;
+            self.next_index = self
+                .next_index
+                .checked_add(1)
+                .ok_or(FileCryptoError::InvalidPayload)?;
+        }
+        self.buffer.drain(..consumed);
+        Ok(out)
+    }
+
+    /// Decrypt the held-back final chunk. Fails if the payload was cut short.
+    pub fn finish(self) -> Result<Vec<u8>, FileCryptoError> {
+        if self.cipher.is_none() || self.buffer.len() < TAG_LEN {
+            return Err(FileCryptoError::InvalidPayload);
+        }
+        self.open_chunk(&self.buffer, true)
+    }
+
+    fn open_chunk(&self, sealed: &[u8], last: bool) -> Result<Vec<u8>, FileCryptoError> {
+        let cipher = self
+            .cipher
+            .as_ref()
+            .ok_or(FileCryptoError::InvalidPayload)?;
+        let nonce = chunk_nonce(self.next_index, last);
+        cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: sealed,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| FileCryptoError::DecryptFailed)
     }
 }
 
+/// The AES-GCM key for one v3 payload, derived from the files subkey and the
+/// payload's salt.
+fn chunk_cipher(
+    files_key: &[u8; 32],
+    salt: &[u8; CHUNK_KEY_SALT_LEN],
+) -> Result<Aes256Gcm, FileCryptoError> {
+    let mut key = [0_u8; 32];
+    Hkdf::<Sha256>::new(Some(salt), files_key)
+        .expand(b"paracord-file-chunks-v3", &mut key)
+        .map_err(|_| FileCryptoError::InvalidKey)?;
+    Aes256Gcm::new_from_slice(&key).map_err(|_| FileCryptoError::InvalidKey)
+}
+
+fn chunk_nonce(index: u32, last: bool) -> [u8; NONCE_LEN] {
+    let mut nonce = [0_u8; NONCE_LEN];
+    nonce[NONCE_LEN - 5..NONCE_LEN - 1].copy_from_slice(&index.to_be_bytes());
+    nonce[NONCE_LEN - 1] = u8::from(last);
+    nonce
+}
+
 pub fn parse_master_key(raw: &str) -> Result<[u8; 32], AtRestKeyError> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
@@ -238,7 +563,10 @@ fn encode_hex(bytes: &[u8]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{derive_sqlite_key_hex, parse_master_key, FileCryptoError, FileCryptor};
+    use super::{
+        derive_sqlite_key_hex, parse_master_key, FileCryptoError, FileCryptor, SecretCryptoError,
+        SecretCryptor, FILE_CHUNK_LEN, FILE_MAGIC_V2, V3_HEADER_LEN,
+    };
 
     #[test]
     fn parses_hex_master_key() {
@@ -286,6 +614,136 @@ mod tests {
         assert!(matches!(err, FileCryptoError::DecryptFailed));
     }
 
+    #[test]
+    fn sealed_secrets_roundtrip_and_bind_to_aad() {
+        let master =
+            parse_master_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").expect("master");
+        let cryptor = SecretCryptor::from_master_key(&master);
+        let sealed = cryptor.seal("JBSWY3DPEHPK3PXP", b"totp:1").expect("seal");
+        assert!(SecretCryptor::is_sealed(&sealed));
+        assert!(!sealed.contains("JBSWY3DPEHPK3PXP"));
+
+        assert_eq!(
+            cryptor.open(&sealed, b"totp:1").expect("open"),
+            "JBSWY3DPEHPK3PXP"
+        );
+        let err = cryptor
+            .open(&sealed, b"totp:2")
+            .expect_err("aad mismatch must fail");
+        assert!(matches!(err, SecretCryptoError::DecryptFailed));
+        assert!(!SecretCryptor::is_sealed("JBSWY3DPEHPK3PXP"));
+    }
+
+    #[test]
+    fn chunked_payload_decrypts_incrementally() {
+        let master =
+            parse_master_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").expect("master");
+        let cryptor = FileCryptor::from_master_key(&master, false).with_chunked_writes(true);
+        let plaintext: Vec<u8> = (0..FILE_CHUNK_LEN * 2 + 123)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let encrypted = cryptor
+            .encrypt_with_aad(&plaintext, b"attachment:1")
+            .expect("encrypt");
+        assert!(FileCryptor::payload_is_chunked(&encrypted));
+
+        let mut decryptor = cryptor
+            .chunked_decryptor(b"attachment:1")
+            .expect("decryptor");
+        let mut decrypted = Vec::new();
+        for piece in encrypted.chunks(10_000) {
+            decrypted.extend(decryptor.update(piece).expect("update"));
+        }
+        decrypted.extend(decryptor.finish().expect("finish"));
+        assert_eq!(decrypted, plaintext);
+        assert_eq!(
+            cryptor
+                .decrypt_with_aad(&encrypted, b"attachment:1")
+                .expect("decrypt"),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn chunked_encryption_fed_in_pieces_matches_the_one_shot_format() {
+        let master =
+            parse_master_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").expect("master");
+        let cryptor = FileCryptor::from_master_key(&master, false).with_chunked_writes(true);
+        for len in [0, FILE_CHUNK_LEN, FILE_CHUNK_LEN * 2 + 5] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let mut encryptor = cryptor
+                .chunked_encryptor(b"attachment:2")
+                .expect("encryptor");
+            let mut encrypted = Vec::new();
+            for piece in plaintext.chunks(7_000) {
+                encrypted.extend(encryptor.update(piece).expect("update"));
+            }
+            encrypted.extend(encryptor.finish().expect("finish"));
+            assert_eq!(
+                encrypted.len(),
+                cryptor
+                    .encrypt_with_aad(&plaintext, b"attachment:2")
+                    .expect("encrypt")
+                    .len()
+            );
+            assert_eq!(
+                cryptor
+                    .decrypt_with_aad(&encrypted, b"attachment:2")
+                    .expect("decrypt"),
+                plaintext
+            );
+        }
+    }
+
+    #[test]
+    fn chunked_writes_are_opt_in_and_readable_either_way() {
+        let master =
+            parse_master_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").expect("master");
+        let compat = FileCryptor::from_master_key(&master, false);
+        let chunked = compat.clone().with_chunked_writes(true);
+        let plaintext = b"hello attachment";
+
+        let v2 = compat
+            .encrypt_with_aad(plaintext, b"attachment:3")
+            .expect("encrypt");
+        assert!(v2.starts_with(FILE_MAGIC_V2));
+        let v3 = chunked
+            .encrypt_with_aad(plaintext, b"attachment:3")
+            .expect("encrypt");
+        assert!(FileCryptor::payload_is_chunked(&v3));
+        // Each payload is sealed under its own derived key.
+        let again = chunked
+            .encrypt_with_aad(plaintext, b"attachment:3")
+            .expect("encrypt");
+        assert_ne!(v3[V3_HEADER_LEN..], again[V3_HEADER_LEN..]);
+
+        for payload in [&v2, &v3] {
+            assert_eq!(
+                compat
+                    .decrypt_with_aad(payload, b"attachment:3")
+                    .expect("decrypt"),
+                plaintext
+            );
+        }
+    }
+
+    #[test]
+    fn chunked_payload_truncated_at_a_chunk_boundary_fails() {
+        let master =
+            parse_master_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").expect("master");
+        let cryptor = FileCryptor::from_master_key(&master, false).with_chunked_writes(true);
+        let plaintext = vec![7_u8; FILE_CHUNK_LEN * 2];
+        let encrypted = cryptor.encrypt_with_aad(&plaintext, b"").expect("encrypt");
+        let truncated = &encrypted[..encrypted.len() - (FILE_CHUNK_LEN + 16)];
+
+        let mut decryptor = cryptor.chunked_decryptor(b"").expect("decryptor");
+        decryptor.update(truncated).expect("update");
+        assert!(matches!(
+            decryptor.finish(),
+            Err(FileCryptoError::DecryptFailed)
+        ));
+    }
+
     #[test]
     fn plaintext_read_fallback_when_allowed() {
         let master =
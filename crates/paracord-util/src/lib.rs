@@ -1,4 +1,5 @@
 pub mod at_rest;
+pub mod net;
 pub mod pagination;
 pub mod snowflake;
 pub mod validation;
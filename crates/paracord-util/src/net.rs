@@ -0,0 +1,184 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// An IPv4 or IPv6 network in CIDR notation (e.g. `192.30.252.0/22`).
+///
+/// A bare address parses as a single-host network (`/32` or `/128`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CidrParseError {
+    #[error("invalid IP address")]
+    InvalidAddress,
+    #[error("invalid prefix length")]
+    InvalidPrefix,
+}
+
+impl IpCidr {
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Whether `ip` falls inside this network. IPv4-mapped IPv6 addresses
+    /// (`::ffff:a.b.c.d`) match the equivalent IPv4 network.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6
+                .to_ipv4_mapped()
+                .map(IpAddr::V4)
+                .unwrap_or(IpAddr::V6(*v6)),
+            IpAddr::V4(_) => *ip,
+        };
+        match (self.network, ip) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = prefix_mask_u32(self.prefix_len);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = prefix_mask_u128(self.prefix_len);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = CidrParseError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        let (addr_raw, prefix_raw) = match raw.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (raw, None),
+        };
+        let network: IpAddr = addr_raw
+            .parse()
+            .map_err(|_| CidrParseError::InvalidAddress)?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_raw {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|len| *len <= max_prefix)
+                .ok_or(CidrParseError::InvalidPrefix)?,
+            None => max_prefix,
+        };
+        Ok(Self {
+            network,
+            prefix_len,
+        })
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+/// Whether `ip` falls inside any of `networks`.
+pub fn ip_in_any(networks: &[IpCidr], ip: &IpAddr) -> bool {
+    networks.iter().any(|network| network.contains(ip))
+}
+
+fn prefix_mask_u32(prefix_len: u8) -> u32 {
+    if prefix_len == 0 {
+        0
+    } else {
+        u32::MAX << (32 - u32::from(prefix_len))
+    }
+}
+
+fn prefix_mask_u128(prefix_len: u8) -> u128 {
+    if prefix_len == 0 {
+        0
+    } else {
+        u128::MAX << (128 - u32::from(prefix_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(raw: &str) -> IpAddr {
+        raw.parse().unwrap()
+    }
+
+    #[test]
+    fn ipv4_cidr_matches_addresses_in_range() {
+        let cidr: IpCidr = "192.30.252.0/22".parse().unwrap();
+        assert!(cidr.contains(&ip("192.30.252.1")));
+        assert!(cidr.contains(&ip("192.30.255.254")));
+        assert!(!cidr.contains(&ip("192.30.251.255")));
+        assert!(!cidr.contains(&ip("192.31.0.1")));
+    }
+
+    #[test]
+    fn ipv6_cidr_matches_addresses_in_range() {
+        let cidr: IpCidr = "2a0a:a440::/29".parse().unwrap();
+        assert!(cidr.contains(&ip("2a0a:a440::1")));
+        assert!(cidr.contains(&ip("2a0a:a447:ffff::1")));
+        assert!(!cidr.contains(&ip("2a0a:a448::1")));
+        assert!(!cidr.contains(&ip("192.30.252.1")));
+    }
+
+    #[test]
+    fn ipv4_mapped_ipv6_matches_ipv4_network() {
+        let cidr: IpCidr = "140.82.112.0/20".parse().unwrap();
+        assert!(cidr.contains(&ip("::ffff:140.82.112.10")));
+    }
+
+    #[test]
+    fn bare_address_is_single_host() {
+        let cidr: IpCidr = "10.0.0.5".parse().unwrap();
+        assert_eq!(cidr.prefix_len(), 32);
+        assert!(cidr.contains(&ip("10.0.0.5")));
+        assert!(!cidr.contains(&ip("10.0.0.6")));
+    }
+
+    #[test]
+    fn zero_prefix_matches_everything_in_family() {
+        let cidr: IpCidr = "0.0.0.0/0".parse().unwrap();
+        assert!(cidr.contains(&ip("8.8.8.8")));
+        assert!(!cidr.contains(&ip("::1")));
+    }
+
+    #[test]
+    fn rejects_malformed_cidrs() {
+        assert_eq!(
+            "10.0.0.0/33".parse::<IpCidr>(),
+            Err(CidrParseError::InvalidPrefix)
+        );
+        assert_eq!(
+            "::/129".parse::<IpCidr>(),
+            Err(CidrParseError::InvalidPrefix)
+        );
+        assert_eq!(
+            "10.0.0/8".parse::<IpCidr>(),
+            Err(CidrParseError::InvalidAddress)
+        );
+        assert_eq!(
+            "example.com/24".parse::<IpCidr>(),
+            Err(CidrParseError::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn ip_in_any_checks_all_networks() {
+        let networks: Vec<IpCidr> = ["10.0.0.0/8", "192.168.1.0/24"]
+            .iter()
+            .map(|raw| raw.parse().unwrap())
+            .collect();
+        assert!(ip_in_any(&networks, &ip("192.168.1.77")));
+        assert!(!ip_in_any(&networks, &ip("172.16.0.1")));
+        assert!(!ip_in_any(&[], &ip("10.0.0.1")));
+    }
+}
@@ -4,25 +4,28 @@ use std::io::Write;
 
 /// Application-level zlib-stream compression context (per connection).
 ///
-/// When the client connects with `?compress=zlib-stream`, all server→client
-/// frames are deflate-compressed and sent as binary WebSocket frames with a
-/// Z_SYNC_FLUSH suffix (`0x00 0x00 0xFF 0xFF`) for Discord gateway
-/// compatibility.
+/// When the client connects with `?compress=zlib-stream`, server→client
+/// frames larger than `threshold` bytes are deflate-compressed and sent as
+/// binary WebSocket frames with a Z_SYNC_FLUSH suffix (`0x00 0x00 0xFF 0xFF`)
+/// for Discord gateway compatibility. Smaller frames stay text frames, so
+/// clients tell the two apart by frame type.
 pub struct WsCompressor {
     enabled: bool,
+    threshold: usize,
 }
 
 impl WsCompressor {
-    pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+    pub fn new(enabled: bool, threshold: usize) -> Self {
+        Self { enabled, threshold }
     }
 
     /// Compress a JSON payload for sending to the client.
     ///
-    /// Returns `None` when compression is disabled (caller should send as text).
-    /// Returns `Some(compressed_bytes)` when compression is enabled.
+    /// Returns `None` when compression is disabled or the payload is at most
+    /// `threshold` bytes (caller should send as text).
+    /// Returns `Some(compressed_bytes)` otherwise.
     pub fn compress(&self, json: &str) -> Option<Result<Vec<u8>, std::io::Error>> {
-        if !self.enabled {
+        if !self.enabled || json.len() <= self.threshold {
             return None;
         }
 
@@ -47,13 +50,13 @@ mod tests {
 
     #[test]
     fn disabled_compressor_returns_none() {
-        let c = WsCompressor::new(false);
+        let c = WsCompressor::new(false, 0);
         assert!(c.compress(r#"{"op":0}"#).is_none());
     }
 
     #[test]
     fn enabled_compressor_produces_valid_deflate() {
-        let c = WsCompressor::new(true);
+        let c = WsCompressor::new(true, 0);
         let input = r#"{"op":0,"t":"MESSAGE_CREATE","s":1,"d":{"content":"hello world"}}"#;
         let compressed = c.compress(input).unwrap().unwrap();
 
@@ -68,9 +71,20 @@ mod tests {
         assert_eq!(decompressed, input);
     }
 
+    #[test]
+    fn payloads_up_to_threshold_are_not_compressed() {
+        let small = r#"{"op":11}"#;
+        let large = format!(r#"{{"op":0,"d":{{"content":"{}"}}}}"#, "a".repeat(300));
+        let c = WsCompressor::new(true, 256);
+        assert!(c.compress(small).is_none());
+        assert!(c.compress(&"x".repeat(256)).is_none());
+        let compressed = c.compress(&large).unwrap().unwrap();
+        assert!(compressed.ends_with(&[0x00, 0x00, 0xFF, 0xFF]));
+    }
+
     #[test]
     fn compression_reduces_size() {
-        let c = WsCompressor::new(true);
+        let c = WsCompressor::new(true, 0);
         let input = r#"{"op":0,"t":"READY","s":1,"d":{"user":{"id":"123","username":"test"},"guilds":[{"id":"1","name":"Test Guild","channels":[]},{"id":"2","name":"Another Guild","channels":[]}],"session_id":"abc"}}"#;
         let compressed = c.compress(input).unwrap().unwrap();
         assert!(
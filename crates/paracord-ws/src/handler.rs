@@ -37,6 +37,7 @@ struct CachedSession {
     guild_ids: Vec<i64>,
     guild_owner_ids: HashMap<i64, i64>,
     sequence: u64,
+    suppress_own_message_echo: bool,
     updated_at: i64,
 }
 
@@ -607,7 +608,7 @@ async fn can_receive_channel_event(
 }
 
 pub async fn handle_connection(socket: WebSocket, state: AppState, compress: bool) {
-    let compressor = WsCompressor::new(compress);
+    let compressor = WsCompressor::new(compress, state.config.gateway_compression_threshold_bytes);
     let mut connection_guard = ConnectionGuard::new();
     if !try_acquire_global_connection_slot() {
         let (mut sender, _) = socket.split();
@@ -811,25 +812,24 @@ pub async fn handle_connection(socket: WebSocket, state: AppState, compress: boo
         let online_snapshot = state.online_users.read().await.clone();
         let presence_snapshot = state.user_presences.read().await.clone();
 
-        // Fetch guild data for READY with bounded concurrency.
+        // Guilds, channels, roles and membership come from a fixed number of
+        // batched queries; voice and presence state is then filled in per
+        // guild with bounded concurrency.
+        let ready_guilds = paracord_core::guild::build_ready_payload(&state.db, session.user_id)
+            .await
+            .unwrap_or_default();
         let sem = Arc::new(Semaphore::new(10));
-        let guild_futures: Vec<_> = session
-            .guild_ids
-            .iter()
-            .map(|&gid| {
+        let guild_futures: Vec<_> = ready_guilds
+            .into_iter()
+            .filter(|ready| session.guild_ids.contains(&ready.guild.id))
+            .map(|ready| {
                 let state = state.clone();
                 let sem = sem.clone();
                 let online_snapshot = online_snapshot.clone();
                 let presence_snapshot = presence_snapshot.clone();
                 async move {
                     let _permit = sem.acquire_owned().await.ok()?;
-                    let guild = paracord_db::guilds::get_guild(&state.db, gid)
-                        .await
-                        .ok()
-                        .flatten();
-                    let Some(g) = guild else {
-                        return None;
-                    };
+                    let gid = ready.guild.id;
 
                     // Two independent queries in parallel
                     let (voice_states, member_ids) = tokio::join!(
@@ -856,12 +856,21 @@ pub async fn handle_connection(socket: WebSocket, state: AppState, compress: boo
                                 "suppress": vs.suppress,
                                 "mute": false,
                                 "deaf": false,
+                                "priority_speaker": vs.priority_speaker,
                                 "username": &vs.username,
                                 "avatar_hash": &vs.avatar_hash,
                             })
                         })
                         .collect();
 
+                    let voice_recordings: Vec<Value> = state
+                        .voice
+                        .get_guild_recordings(gid)
+                        .await
+                        .iter()
+                        .map(|r| r.to_json())
+                        .collect();
+
                     // Build presences from member IDs (lightweight query)
                     let presences_json: Vec<Value> = member_ids
                         .iter()
@@ -878,17 +887,11 @@ pub async fn handle_connection(socket: WebSocket, state: AppState, compress: boo
                         })
                         .collect();
 
-                    Some(json!({
-                        "id": g.id.to_string(),
-                        "name": g.name,
-                        "owner_id": g.owner_id.to_string(),
-                        "icon_hash": g.icon_hash,
-                        "member_count": member_ids.len(),
-                        "channels": [],
-                        "voice_states": voice_states_json,
-                        "presences": presences_json,
-                        "lazy": true,
-                    }))
+                    let mut guild_json = ready.to_json();
+                    guild_json["voice_states"] = json!(voice_states_json);
+                    guild_json["voice_recordings"] = json!(voice_recordings);
+                    guild_json["presences"] = json!(presences_json);
+                    Some(guild_json)
                 }
             })
             .collect();
@@ -1067,6 +1070,16 @@ pub async fn handle_connection(socket: WebSocket, state: AppState, compress: boo
     };
 
     if should_mark_offline {
+        // Typing indicators have no grace period: peers should stop seeing
+        // them as soon as the last connection goes away.
+        paracord_core::typing::clear_user_typing(
+            &state.db,
+            &state.event_bus,
+            paracord_core::typing::typing_tracker(),
+            session_user_id,
+        )
+        .await;
+
         // Defer the offline transition through PresenceManager to avoid race
         // conditions where a reconnecting client briefly appears offline.
         let state_clone = state.clone();
@@ -1108,6 +1121,14 @@ pub async fn handle_connection(socket: WebSocket, state: AppState, compress: boo
     }
 }
 
+/// IDENTIFY option: `suppress_own_message_echo: true` drops `MESSAGE_CREATE`
+/// echoes of nonce-tagged sends that named this session's id.
+fn requests_echo_suppression(d: &Value) -> bool {
+    d.get("suppress_own_message_echo")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
 async fn wait_for_identify_or_resume(
     receiver: &mut (impl StreamExt<Item = Result<Message, axum::Error>> + Unpin),
     state: &AppState,
@@ -1131,18 +1152,27 @@ async fn wait_for_identify_or_resume(
                             (Some(session_id), Some(jti)) => (session_id, jti),
                             _ => return None,
                         };
+                        let now = chrono::Utc::now();
                         let active = paracord_db::sessions::is_access_token_active(
                             &state.db,
                             claims.sub,
                             session_id,
                             jti,
-                            chrono::Utc::now(),
+                            now,
+                            state.config.session_idle_cutoff(now),
                         )
                         .await
                         .ok()?;
                         if !active {
                             return None;
                         }
+                        let _ = paracord_db::sessions::touch_session(
+                            &state.db,
+                            session_id,
+                            now,
+                            chrono::Duration::minutes(1),
+                        )
+                        .await;
                         let op = payload.get("op").and_then(|v| v.as_u64())?;
                         if op == OP_IDENTIFY as u64 {
                             let guilds =
@@ -1152,11 +1182,9 @@ async fn wait_for_identify_or_resume(
                             let guild_ids = guilds.iter().map(|g| g.id).collect();
                             let guild_owner_ids =
                                 guilds.iter().map(|g| (g.id, g.owner_id)).collect();
-                            return Some((
-                                Session::new(claims.sub, guild_ids, guild_owner_ids),
-                                false,
-                                0,
-                            ));
+                            let mut session = Session::new(claims.sub, guild_ids, guild_owner_ids);
+                            session.suppress_own_message_echo = requests_echo_suppression(d);
+                            return Some((session, false, 0));
                         }
                         if op == OP_RESUME as u64 {
                             let requested_session_id =
@@ -1190,6 +1218,8 @@ async fn wait_for_identify_or_resume(
                                         );
                                         resumed.session_id = requested_session_id;
                                         resumed.sequence = cached.sequence.max(requested_seq);
+                                        resumed.suppress_own_message_echo =
+                                            cached.suppress_own_message_echo;
                                         return Some((resumed, true, requested_seq));
                                     } else {
                                         let oldest_buffered = event_buffers()
@@ -1214,11 +1244,9 @@ async fn wait_for_identify_or_resume(
                             let guild_ids = guilds.iter().map(|g| g.id).collect();
                             let guild_owner_ids =
                                 guilds.iter().map(|g| (g.id, g.owner_id)).collect();
-                            return Some((
-                                Session::new(claims.sub, guild_ids, guild_owner_ids),
-                                false,
-                                0,
-                            ));
+                            let mut session = Session::new(claims.sub, guild_ids, guild_owner_ids);
+                            session.suppress_own_message_echo = requests_echo_suppression(d);
+                            return Some((session, false, 0));
                         }
                     }
                 }
@@ -1340,6 +1368,11 @@ async fn run_session(
                         if !session.should_receive_event(event.guild_id, event.target_user_ids.as_deref()) {
                             continue;
                         }
+                        if session.suppress_own_message_echo
+                            && event.is_own_message_echo(&session.session_id)
+                        {
+                            continue;
+                        }
 
                         if let Some(guild_id) = event.guild_id {
                             if !can_receive_guild_event(&state, &mut session, guild_id).await {
@@ -1455,6 +1488,18 @@ async fn run_session(
                             break ("websocket send error".to_string(), false);
                         }
                         observability::ws_event_dispatched(&event.event_type);
+                        if event.event_type == EVENT_SESSIONS_REVOKED {
+                            let _ = send_ws_close_logged(
+                                &mut sender,
+                                4004,
+                                "Session revoked",
+                                Some(session.user_id),
+                                Some(session.session_id.as_str()),
+                                "revoked_close",
+                            )
+                            .await;
+                            break ("sessions revoked".to_string(), false);
+                        }
                     }
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
                         tracing::warn!(
@@ -1513,6 +1558,7 @@ async fn run_session(
                 guild_ids: session.guild_ids.clone(),
                 guild_owner_ids: session.guild_owner_ids.clone(),
                 sequence: session.sequence,
+                suppress_own_message_echo: session.suppress_own_message_echo,
                 updated_at: chrono::Utc::now().timestamp(),
             },
         )
@@ -1643,6 +1689,12 @@ async fn handle_client_message(
                         return;
                     }
 
+                    paracord_core::typing::typing_tracker().start(
+                        session.user_id,
+                        cid,
+                        guild_id,
+                        std::time::Instant::now(),
+                    );
                     let typing_payload = json!({
                         "channel_id": channel_id_str,
                         "user_id": session.user_id.to_string(),
@@ -1650,9 +1702,12 @@ async fn handle_client_message(
                     });
 
                     if guild_id.is_none() {
-                        let recipient_ids = paracord_db::dms::get_dm_recipient_ids(&state.db, cid)
-                            .await
-                            .unwrap_or_default();
+                        let recipient_ids = paracord_core::channel::dm_event_recipients(
+                            &state.db,
+                            cid,
+                            session.user_id,
+                        )
+                        .await;
                         state.event_bus.dispatch_to_users(
                             EVENT_TYPING_START,
                             typing_payload,
@@ -1788,6 +1843,7 @@ async fn handle_client_message(
                             Some(guild_id),
                             channel_id,
                             &session.session_id,
+                            perms.contains(Permissions::PRIORITY_SPEAKER),
                         )
                         .await;
                         state
@@ -1818,6 +1874,7 @@ async fn handle_client_message(
                                 "suppress": false,
                                 "mute": false,
                                 "deaf": false,
+                                "priority_speaker": perms.contains(Permissions::PRIORITY_SPEAKER),
                                 "username": vs_user.as_ref().map(|u| u.username.as_str()),
                                 "avatar_hash": vs_user.as_ref().and_then(|u| u.avatar_hash.as_deref()),
                             }),
@@ -1944,3 +2001,42 @@ async fn handle_client_message(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn sent_frame(compressor: &WsCompressor, payload: String) -> Message {
+        let mut sink: Vec<Message> = Vec::new();
+        send_ws_text_logged(
+            &mut sink,
+            payload,
+            compressor,
+            None,
+            None,
+            "dispatch",
+            Some(0),
+            None,
+            None,
+        )
+        .await
+        .expect("send");
+        sink.pop().expect("one frame")
+    }
+
+    #[tokio::test]
+    async fn small_frames_skip_compression_on_zlib_stream_connections() {
+        let compressor = WsCompressor::new(true, 64);
+        let small = r#"{"op":11,"d":null}"#.to_string();
+        assert!(matches!(
+            sent_frame(&compressor, small.clone()).await,
+            Message::Text(text) if text.as_str() == small
+        ));
+
+        let large = json!({ "op": 0, "d": { "content": "a".repeat(200) } }).to_string();
+        assert!(matches!(
+            sent_frame(&compressor, large).await,
+            Message::Binary(_)
+        ));
+    }
+}
@@ -6,6 +6,9 @@ pub struct Session {
     pub guild_owner_ids: HashMap<i64, i64>,
     pub session_id: String,
     pub sequence: u64,
+    /// Set from IDENTIFY; skips `MESSAGE_CREATE` echoes of nonce-tagged sends
+    /// made with this session's id.
+    pub suppress_own_message_echo: bool,
 }
 
 impl Session {
@@ -16,6 +19,7 @@ impl Session {
             guild_owner_ids,
             session_id: uuid::Uuid::new_v4().to_string(),
             sequence: 0,
+            suppress_own_message_echo: false,
         }
     }
 